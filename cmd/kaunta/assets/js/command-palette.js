@@ -0,0 +1,304 @@
+/**
+ * Kaunta Command Palette
+ * ⌘K / Ctrl+K opens a palette for jumping between websites, reports, date
+ * ranges and settings. Outside text fields, T / W / M switch the period to
+ * today, 7 days and 30 days.
+ */
+
+(function () {
+  const DATE_RANGES = [
+    { range: "1", label: "Today", key: "t" },
+    { range: "7", label: "Last 7 days", key: "w" },
+    { range: "30", label: "Last 30 days", key: "m" },
+  ];
+
+  const PAGES = [
+    { label: "Dashboard", href: "/dashboard" },
+    { label: "Overview of all websites", href: "/dashboard/overview" },
+    { label: "Compare websites", href: "/dashboard/compare" },
+    { label: "Visitor map", href: "/dashboard/map" },
+    { label: "Live map", href: "/dashboard/live" },
+    { label: "Campaigns", href: "/dashboard/campaigns" },
+    { label: "Events", href: "/dashboard/events" },
+    { label: "Goals", href: "/dashboard/goals" },
+    { label: "Custom dashboards", href: "/dashboard/custom" },
+    { label: "Alerts", href: "/dashboard/alerts" },
+  ];
+
+  // Home dashboard report tabs, matching data-report-tab in home.html
+  const REPORTS = [
+    { tab: "pages", label: "Pages" },
+    { tab: "referrers", label: "Referrers" },
+    { tab: "browsers", label: "Browsers" },
+    { tab: "browser-versions", label: "Browser versions" },
+    { tab: "os", label: "Operating systems" },
+    { tab: "devices", label: "Device types" },
+    { tab: "screens", label: "Screen sizes" },
+    { tab: "countries", label: "Countries" },
+    { tab: "regions", label: "Regions" },
+    { tab: "cities", label: "Cities" },
+    { tab: "entry-pages", label: "Entry pages" },
+    { tab: "exit-pages", label: "Exit pages" },
+    { tab: "not-found", label: "404s" },
+    { tab: "outbound", label: "Outbound links" },
+  ];
+
+  const SETTINGS = [
+    { label: "Manage websites", href: "/dashboard/websites" },
+    { label: "Security (two-factor)", href: "/dashboard/security" },
+    { label: "Theme: Default", theme: "default" },
+    { label: "Theme: Retro '90s", theme: "retro" },
+  ];
+
+  let overlay = null;
+  let input = null;
+  let list = null;
+  let websites = null;
+  let filtered = [];
+  let activeIndex = 0;
+
+  function isTyping(target) {
+    if (!target) return false;
+    const tag = target.tagName;
+    return tag === "INPUT" || tag === "TEXTAREA" || tag === "SELECT" || target.isContentEditable;
+  }
+
+  function setDateRange(range) {
+    const button = document.querySelector(`[data-date-range="${range}"]`);
+    if (button) {
+      button.click();
+    } else {
+      // Pages without date controls pick the range up on the next visit
+      localStorage.setItem("kaunta_dateRange", range);
+    }
+  }
+
+  function selectWebsite(id) {
+    const select = document.querySelector("#website-selector-container select");
+    if (select && Array.from(select.options).some((option) => option.value === id)) {
+      select.value = id;
+      select.dispatchEvent(new Event("change", { bubbles: true }));
+      return;
+    }
+    localStorage.setItem("kaunta_website", id);
+    window.location.href = "/dashboard";
+  }
+
+  function openReport(tab) {
+    const button = document.querySelector(`[data-report-tab="${tab}"]`);
+    if (button) {
+      button.click();
+      button.scrollIntoView({ block: "nearest", behavior: "smooth" });
+      return;
+    }
+    window.location.href = "/dashboard?tab=" + encodeURIComponent(tab);
+  }
+
+  function setTheme(theme) {
+    const select = document.querySelector('select[aria-label="Dashboard theme"]');
+    if (select) {
+      select.value = theme;
+      select.dispatchEvent(new Event("change", { bubbles: true }));
+    }
+  }
+
+  function buildCommands() {
+    const commands = [];
+    (websites || []).forEach((site) => {
+      commands.push({
+        group: "Websites",
+        label: site.label || (site.name ? `${site.name} (${site.domain})` : site.domain),
+        run: () => selectWebsite(site.id),
+      });
+    });
+    REPORTS.forEach((report) => {
+      commands.push({ group: "Reports", label: report.label, run: () => openReport(report.tab) });
+    });
+    DATE_RANGES.forEach((item) => {
+      commands.push({
+        group: "Date range",
+        label: item.label,
+        hint: item.key.toUpperCase(),
+        run: () => setDateRange(item.range),
+      });
+    });
+    PAGES.forEach((page) => {
+      commands.push({ group: "Go to", label: page.label, run: () => (window.location.href = page.href) });
+    });
+    SETTINGS.forEach((item) => {
+      commands.push({
+        group: "Settings",
+        label: item.label,
+        run: () => (item.href ? (window.location.href = item.href) : setTheme(item.theme)),
+      });
+    });
+    return commands;
+  }
+
+  async function loadWebsites() {
+    if (websites) return;
+
+    // Prefer the page's own selector, which only lists the user's websites
+    const select = document.querySelector("#website-selector-container select");
+    if (select && select.options.length > 0) {
+      websites = Array.from(select.options).map((option) => ({
+        id: option.value,
+        label: option.textContent,
+      }));
+      return;
+    }
+
+    try {
+      const response = await fetch("/api/websites/list", { credentials: "same-origin" });
+      websites = response.ok ? await response.json() : [];
+    } catch (error) {
+      console.error("Command palette: failed to load websites", error);
+      websites = [];
+    }
+    if (overlay && !overlay.hidden) renderList();
+  }
+
+  function renderList() {
+    const query = input.value.trim().toLowerCase();
+    filtered = buildCommands().filter(
+      (command) =>
+        !query || `${command.group} ${command.label}`.toLowerCase().includes(query),
+    );
+    activeIndex = Math.min(activeIndex, Math.max(filtered.length - 1, 0));
+
+    list.replaceChildren();
+    if (filtered.length === 0) {
+      const empty = document.createElement("li");
+      empty.className = "command-palette-empty";
+      empty.textContent = "No matching commands";
+      list.appendChild(empty);
+      return;
+    }
+
+    let lastGroup = "";
+    filtered.forEach((command, index) => {
+      if (command.group !== lastGroup) {
+        lastGroup = command.group;
+        const heading = document.createElement("li");
+        heading.className = "command-palette-group";
+        heading.textContent = command.group;
+        list.appendChild(heading);
+      }
+
+      const item = document.createElement("li");
+      item.className = "command-palette-item";
+      item.setAttribute("role", "option");
+      item.setAttribute("aria-selected", index === activeIndex ? "true" : "false");
+      item.textContent = command.label;
+      if (command.hint) {
+        const kbd = document.createElement("kbd");
+        kbd.textContent = command.hint;
+        item.appendChild(kbd);
+      }
+      item.addEventListener("mousemove", () => {
+        if (activeIndex !== index) {
+          activeIndex = index;
+          highlight();
+        }
+      });
+      item.addEventListener("click", () => runCommand(index));
+      list.appendChild(item);
+    });
+  }
+
+  function highlight() {
+    list.querySelectorAll(".command-palette-item").forEach((item, index) => {
+      const active = index === activeIndex;
+      item.setAttribute("aria-selected", active ? "true" : "false");
+      if (active) item.scrollIntoView({ block: "nearest" });
+    });
+  }
+
+  function runCommand(index) {
+    const command = filtered[index];
+    if (!command) return;
+    closePalette();
+    command.run();
+  }
+
+  function createPalette() {
+    overlay = document.createElement("div");
+    overlay.className = "modal-overlay command-palette-overlay";
+    overlay.hidden = true;
+    overlay.innerHTML = `
+      <div class="modal glass command-palette" role="dialog" aria-modal="true" aria-label="Command palette">
+        <input type="text" class="input" placeholder="Jump to a website, report, date range or setting..." aria-label="Search commands" />
+        <ul class="command-palette-list" role="listbox"></ul>
+        <div class="command-palette-footer">
+          <span><kbd>↑</kbd><kbd>↓</kbd> navigate</span>
+          <span><kbd>Enter</kbd> select</span>
+          <span><kbd>Esc</kbd> close</span>
+          <span><kbd>T</kbd><kbd>W</kbd><kbd>M</kbd> period</span>
+        </div>
+      </div>
+    `;
+    document.body.appendChild(overlay);
+
+    input = overlay.querySelector("input");
+    list = overlay.querySelector(".command-palette-list");
+
+    input.addEventListener("input", () => {
+      activeIndex = 0;
+      renderList();
+    });
+    input.addEventListener("keydown", (event) => {
+      if (event.key === "ArrowDown") {
+        event.preventDefault();
+        activeIndex = (activeIndex + 1) % Math.max(filtered.length, 1);
+        highlight();
+      } else if (event.key === "ArrowUp") {
+        event.preventDefault();
+        activeIndex = (activeIndex - 1 + filtered.length) % Math.max(filtered.length, 1);
+        highlight();
+      } else if (event.key === "Enter") {
+        event.preventDefault();
+        runCommand(activeIndex);
+      }
+    });
+    overlay.addEventListener("click", (event) => {
+      if (event.target === overlay) closePalette();
+    });
+  }
+
+  function openPalette() {
+    if (!overlay) createPalette();
+    overlay.hidden = false;
+    input.value = "";
+    activeIndex = 0;
+    renderList();
+    input.focus();
+    loadWebsites();
+  }
+
+  function closePalette() {
+    if (overlay) overlay.hidden = true;
+  }
+
+  document.addEventListener("keydown", (event) => {
+    const paletteOpen = overlay && !overlay.hidden;
+
+    if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === "k") {
+      event.preventDefault();
+      paletteOpen ? closePalette() : openPalette();
+      return;
+    }
+    if (paletteOpen) {
+      if (event.key === "Escape") closePalette();
+      return;
+    }
+    if (event.metaKey || event.ctrlKey || event.altKey || isTyping(event.target)) {
+      return;
+    }
+
+    const shortcut = DATE_RANGES.find((item) => item.key === event.key.toLowerCase());
+    if (shortcut) {
+      event.preventDefault();
+      setDateRange(shortcut.range);
+    }
+  });
+})();
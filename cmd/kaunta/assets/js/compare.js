@@ -0,0 +1,93 @@
+/**
+ * Kaunta Website Comparison - Datastar Edition
+ * Overlays the pageview series of the selected websites in one Chart.js chart.
+ */
+
+// Keep in sync with .compare-swatch-N in compare.html
+const COMPARE_COLORS = ["#3b82f6", "#f97316", "#22c55e", "#a855f7", "#ef4444"];
+
+let compareChartInstance = null;
+
+function compareLabelFormatter(granularity) {
+  return granularity === "day"
+    ? (ts) => new Date(ts).toLocaleDateString()
+    : (ts) => new Date(ts).toLocaleString();
+}
+
+window.destroyCompareChart = function () {
+  if (compareChartInstance) {
+    compareChartInstance.destroy();
+    compareChartInstance = null;
+  }
+};
+
+// Render (or refresh) the overlay - called whenever $compareChart changes
+window.renderCompareChart = function (chart) {
+  const ctx = document.getElementById("compareChart");
+  if (!ctx || typeof Chart === "undefined") {
+    return;
+  }
+
+  const series = chart && Array.isArray(chart.series) ? chart.series : [];
+  if (series.length === 0) {
+    window.destroyCompareChart();
+    return;
+  }
+
+  const labels = (chart.labels || []).map(compareLabelFormatter(chart.granularity));
+  const datasets = series.map((s, i) => ({
+    label: s.label,
+    data: s.values || [],
+    borderColor: COMPARE_COLORS[i % COMPARE_COLORS.length],
+    backgroundColor: "transparent",
+    tension: 0.4,
+    borderWidth: 2,
+    pointRadius: 2,
+    pointHoverRadius: 5,
+  }));
+
+  if (compareChartInstance) {
+    compareChartInstance.data.labels = labels;
+    compareChartInstance.data.datasets = datasets;
+    compareChartInstance.update("none");
+    return;
+  }
+
+  compareChartInstance = new Chart(ctx, {
+    type: "line",
+    data: { labels, datasets },
+    options: {
+      responsive: true,
+      maintainAspectRatio: false,
+      plugins: {
+        legend: { display: true, position: "bottom" },
+        tooltip: {
+          mode: "index",
+          intersect: false,
+          backgroundColor: "rgba(0, 0, 0, 0.8)",
+          padding: 12,
+        },
+      },
+      scales: {
+        y: {
+          beginAtZero: true,
+          ticks: { precision: 0, color: "#6b7280" },
+          grid: { color: "#e5e7eb" },
+        },
+        x: {
+          ticks: { color: "#6b7280", maxRotation: 0 },
+          grid: { display: false },
+        },
+      },
+      interaction: {
+        mode: "nearest",
+        axis: "x",
+        intersect: false,
+      },
+    },
+  });
+};
+
+window.addEventListener("beforeunload", function () {
+  window.destroyCompareChart();
+});
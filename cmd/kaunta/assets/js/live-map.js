@@ -0,0 +1,135 @@
+/**
+ * Kaunta Live Map - Datastar Edition
+ * Plots current visitors at their city centroid, or their country centroid
+ * when the city is unknown. Points arrive over SSE every few seconds.
+ */
+
+let liveMapInstance = null;
+let liveMarkerLayer = null;
+let countryCentroids = null;
+
+// Center of a country's largest polygon, so overseas territories don't drag
+// the marker into the ocean
+function largestPolygonCenter(feature) {
+  const polygons =
+    feature.geometry.type === "MultiPolygon"
+      ? feature.geometry.coordinates
+      : [feature.geometry.coordinates];
+
+  let best = null;
+  let bestArea = -1;
+  polygons.forEach((polygon) => {
+    const bounds = L.latLngBounds(polygon[0].map(([lng, lat]) => [lat, lng]));
+    const area =
+      (bounds.getNorth() - bounds.getSouth()) * (bounds.getEast() - bounds.getWest());
+    if (area > bestArea) {
+      bestArea = area;
+      best = bounds.getCenter();
+    }
+  });
+  return best ? [best.lat, best.lng] : null;
+}
+
+async function loadCountryCentroids() {
+  if (countryCentroids) {
+    return countryCentroids;
+  }
+
+  const response = await fetch("/assets/data/countries-110m.json");
+  if (!response.ok) {
+    throw new Error(`Failed to load TopoJSON: ${response.statusText}`);
+  }
+  const world = await response.json();
+
+  countryCentroids = new Map();
+  topojson.feature(world, world.objects.countries).features.forEach((feature) => {
+    if (!feature.geometry) return;
+    const center = largestPolygonCenter(feature);
+    if (!center) return;
+    // TopoJSON ids are zero-padded ISO numeric codes; the server sends them unpadded
+    countryCentroids.set(String(parseInt(feature.id, 10)), center);
+  });
+  return countryCentroids;
+}
+
+function liveMarkerLabel(point) {
+  const place = point.city ? `${point.city}, ${point.country_name}` : point.country_name;
+  const label = document.createElement("span");
+  label.textContent = `${place}: ${point.visitors} ${point.visitors === 1 ? "visitor" : "visitors"}`;
+  return label;
+}
+
+window.cleanupLiveMap = function () {
+  if (liveMapInstance) {
+    liveMapInstance.remove();
+    liveMapInstance = null;
+    liveMarkerLayer = null;
+  }
+};
+
+// Render (or refresh) the live markers - called whenever $liveMapPoints changes
+window.renderLiveMap = async function (points) {
+  const container = document.getElementById("live-map");
+  if (!container || typeof L === "undefined" || typeof topojson === "undefined") {
+    return;
+  }
+
+  try {
+    if (!liveMapInstance) {
+      liveMapInstance = L.map("live-map", {
+        zoomControl: true,
+        attributionControl: true,
+        worldCopyJump: true,
+      }).setView([20, 0], 2);
+
+      L.tileLayer("https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png", {
+        attribution:
+          '&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a> contributors',
+        maxZoom: 10,
+        minZoom: 1,
+      }).addTo(liveMapInstance);
+
+      liveMarkerLayer = L.layerGroup().addTo(liveMapInstance);
+    }
+
+    // The container may have been hidden or resized (full screen) since the last render
+    liveMapInstance.invalidateSize();
+
+    const centroids = await loadCountryCentroids();
+    const accent =
+      getComputedStyle(document.documentElement).getPropertyValue("--accent-color").trim() ||
+      "#3b82f6";
+    const list = Array.isArray(points) ? points : [];
+    const maxVisitors = Math.max(1, ...list.map((p) => p.visitors || 0));
+
+    liveMarkerLayer.clearLayers();
+    list.forEach((point) => {
+      const latlng =
+        point.lat != null && point.lon != null ? [point.lat, point.lon] : centroids.get(point.code);
+      if (!latlng) return;
+
+      L.circleMarker(latlng, {
+        radius: 6 + 14 * Math.sqrt((point.visitors || 0) / maxVisitors),
+        color: accent,
+        weight: 2,
+        fillColor: accent,
+        fillOpacity: 0.45,
+        className: "live-map-marker",
+      })
+        .bindTooltip(liveMarkerLabel(point), { direction: "top" })
+        .addTo(liveMarkerLayer);
+    });
+  } catch (error) {
+    console.error("Error rendering live map:", error);
+  }
+};
+
+document.addEventListener("fullscreenchange", function () {
+  if (liveMapInstance) {
+    liveMapInstance.invalidateSize();
+  }
+});
+
+window.addEventListener("beforeunload", function () {
+  window.cleanupLiveMap();
+});
@@ -126,19 +126,16 @@ window.initChoroplethMap = async function(mapData) {
     if (mapData && mapData.data && Array.isArray(mapData.data)) {
       maxValue = Math.max(...mapData.data.map((d) => d.visitors || 0));
       mapData.data.forEach((d) => {
-        dataMap.set(d.country_name, {
+        const entry = {
           visitors: d.visitors || 0,
           percentage: d.percentage || 0,
           name: d.country_name,
           code: d.code,
-        });
+          country: d.country,
+        };
+        dataMap.set(d.country_name, entry);
         if (d.code) {
-          dataMap.set(d.code, {
-            visitors: d.visitors || 0,
-            percentage: d.percentage || 0,
-            name: d.country_name,
-            code: d.code,
-          });
+          dataMap.set(d.code, entry);
         }
       });
     }
@@ -206,10 +203,12 @@ window.initChoroplethMap = async function(mapData) {
 
       if (countryData) {
         layer.on("click", () => {
-          // Apply country filter via Datastar
-          const filterUrl = `/api/dashboard/map-filter-ds?country=${encodeURIComponent(countryData.name)}`;
-          // Trigger SSE request to apply filter
-          window.dispatchEvent(new CustomEvent('datastar-get', { detail: { url: filterUrl } }));
+          // Hand the ISO code to the page, which opens its drill-down panel
+          window.dispatchEvent(
+            new CustomEvent("kaunta-map-country", {
+              detail: { country: countryData.country, name: countryData.name },
+            })
+          );
         });
         layer.on("mouseover", function () {
           container.style.cursor = "pointer";
@@ -280,19 +279,16 @@ window.updateChoroplethMap = function(mapData) {
     if (mapData && mapData.data && Array.isArray(mapData.data)) {
       maxValue = Math.max(...mapData.data.map((d) => d.visitors || 0));
       mapData.data.forEach((d) => {
-        dataMap.set(d.country_name, {
+        const entry = {
           visitors: d.visitors || 0,
           percentage: d.percentage || 0,
           name: d.country_name,
           code: d.code,
-        });
+          country: d.country,
+        };
+        dataMap.set(d.country_name, entry);
         if (d.code) {
-          dataMap.set(d.code, {
-            visitors: d.visitors || 0,
-            percentage: d.percentage || 0,
-            name: d.country_name,
-            code: d.code,
-          });
+          dataMap.set(d.code, entry);
         }
       });
     }
@@ -24,6 +24,13 @@ export default [
     },
   },
   {
-    ignores: ["node_modules/", "cmd/kaunta/assets/vendor/"],
+    // The tracker is built from ES modules
+    files: ["tracker/**/*.js"],
+    languageOptions: {
+      sourceType: "module",
+    },
+  },
+  {
+    ignores: ["node_modules/", "cmd/kaunta/assets/vendor/", "tracker/dist/"],
   },
 ];
@@ -0,0 +1,97 @@
+import { test, expect, type Page } from '@playwright/test';
+import { fileURLToPath } from 'node:url';
+
+const SCRIPT = fileURLToPath(new URL('../../cmd/kaunta/assets/js/command-palette.js', import.meta.url));
+
+/**
+ * Load the palette on a page with the dashboard's period buttons, which
+ * record the range they are clicked for in window.__ranges
+ */
+async function loadPalette(page: Page) {
+  await page.setContent(`
+    <!DOCTYPE html>
+    <html>
+    <body>
+      <button data-date-range="1">Today</button>
+      <button data-date-range="7">7 days</button>
+      <button data-date-range="30">30 days</button>
+      <input id="search" type="text" />
+      <textarea id="notes"></textarea>
+      <div id="editor" contenteditable="true"></div>
+      <script>
+        window.__ranges = [];
+        document.querySelectorAll('[data-date-range]').forEach((button) => {
+          button.addEventListener('click', () => window.__ranges.push(button.dataset.dateRange));
+        });
+      </script>
+    </body>
+    </html>
+  `);
+  await page.addScriptTag({ path: SCRIPT });
+}
+
+async function ranges(page: Page): Promise<string[]> {
+  return page.evaluate(() => (window as any).__ranges);
+}
+
+/**
+ * Test that T, W and M pick today, 7 days and 30 days
+ */
+test('period shortcuts switch the date range', async ({ page }) => {
+  await loadPalette(page);
+
+  await page.keyboard.press('t');
+  await page.keyboard.press('w');
+  await page.keyboard.press('m');
+  await page.keyboard.press('Shift+W');
+  // Other keys, and shortcuts with a modifier, are left alone
+  await page.keyboard.press('x');
+  await page.keyboard.press('Alt+m');
+
+  expect(await ranges(page)).toEqual(['1', '7', '30', '7']);
+});
+
+/**
+ * Test that the shortcuts are plain letters while a text field has focus
+ */
+test('period shortcuts are ignored while typing', async ({ page }) => {
+  await loadPalette(page);
+
+  await page.locator('#search').pressSequentially('twm');
+  await page.locator('#notes').pressSequentially('twm');
+  await page.locator('#editor').pressSequentially('twm');
+
+  expect(await ranges(page)).toEqual([]);
+  await expect(page.locator('#search')).toHaveValue('twm');
+  await expect(page.locator('#notes')).toHaveValue('twm');
+  await expect(page.locator('#editor')).toHaveText('twm');
+});
+
+/**
+ * Test that Ctrl+K opens the palette, which filters its commands and runs
+ * the selected one, and that letters typed into it aren't shortcuts
+ */
+test('command palette runs the selected command', async ({ page }) => {
+  await loadPalette(page);
+
+  await page.keyboard.press('Control+k');
+  const palette = page.locator('.command-palette-overlay');
+  await expect(palette).toBeVisible();
+  await expect(page.getByLabel('Search commands')).toBeFocused();
+
+  await page.keyboard.type('last 30');
+  await expect(page.locator('.command-palette-item')).toHaveCount(1);
+  await expect(page.locator('.command-palette-item')).toContainText('Last 30 days');
+  expect(await ranges(page)).toEqual([]);
+
+  await page.keyboard.press('Enter');
+  await expect(palette).toBeHidden();
+  expect(await ranges(page)).toEqual(['30']);
+
+  // Escape closes it without running anything
+  await page.keyboard.press('Control+k');
+  await expect(palette).toBeVisible();
+  await page.keyboard.press('Escape');
+  await expect(palette).toBeHidden();
+  expect(await ranges(page)).toEqual(['30']);
+});
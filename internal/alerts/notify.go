@@ -0,0 +1,145 @@
+package alerts
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"fmt"
+	"net"
+	"net/http"
+	"net/smtp"
+	"strings"
+	"time"
+
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/safehttp"
+)
+
+// Alert is a fired rule together with the numbers that tripped it
+type Alert struct {
+	Rule     models.AlertRule
+	Domain   string
+	Reason   string
+	Current  int64
+	Baseline float64
+	FiredAt  time.Time
+}
+
+// webhookPayload is the JSON body POSTed to webhook targets
+type webhookPayload struct {
+	RuleID        string  `json:"rule_id"`
+	Rule          string  `json:"rule"`
+	Kind          string  `json:"kind"`
+	WebsiteID     string  `json:"website_id"`
+	Website       string  `json:"website"`
+	Reason        string  `json:"reason"`
+	Visitors      int64   `json:"visitors"`
+	Baseline      float64 `json:"baseline"`
+	WindowMinutes int     `json:"window_minutes"`
+	FiredAt       string  `json:"fired_at"`
+}
+
+// Notifier delivers alerts over webhooks and email
+type Notifier struct {
+	client   *http.Client
+	smtp     config.SMTPConfig
+	sendMail func(addr string, a smtp.Auth, from string, to []string, msg []byte) error
+}
+
+// NewNotifier creates a notifier; email delivery needs smtp.Host to be set
+func NewNotifier(smtpConfig config.SMTPConfig) *Notifier {
+	return &Notifier{
+		client:   safehttp.NewClient(10 * time.Second),
+		smtp:     smtpConfig,
+		sendMail: smtp.SendMail,
+	}
+}
+
+// Send delivers an alert to the rule's channel
+func (n *Notifier) Send(ctx context.Context, alert Alert) error {
+	switch alert.Rule.Channel {
+	case models.AlertChannelWebhook:
+		return n.sendWebhook(ctx, alert)
+	case models.AlertChannelEmail:
+		return n.sendEmail(alert)
+	default:
+		return fmt.Errorf("unknown alert channel: %s", alert.Rule.Channel)
+	}
+}
+
+// newWebhookPayload describes a fired alert for webhook consumers
+func newWebhookPayload(alert Alert) webhookPayload {
+	return webhookPayload{
+		RuleID:        alert.Rule.ID.String(),
+		Rule:          alert.Rule.Name,
+		Kind:          alert.Rule.Kind,
+		WebsiteID:     alert.Rule.WebsiteID.String(),
+		Website:       alert.Domain,
+		Reason:        alert.Reason,
+		Visitors:      alert.Current,
+		Baseline:      alert.Baseline,
+		WindowMinutes: alert.Rule.WindowMinutes,
+		FiredAt:       alert.FiredAt.UTC().Format(time.RFC3339),
+	}
+}
+
+func (n *Notifier) sendWebhook(ctx context.Context, alert Alert) error {
+	body, err := json.Marshal(newWebhookPayload(alert))
+	if err != nil {
+		return err
+	}
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, alert.Rule.Target, bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("User-Agent", "Kaunta-Alerts")
+
+	resp, err := n.client.Do(req)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return fmt.Errorf("webhook returned status %d", resp.StatusCode)
+	}
+	return nil
+}
+
+func (n *Notifier) sendEmail(alert Alert) error {
+	if n.smtp.Host == "" {
+		return fmt.Errorf("email alerts require smtp.host to be configured")
+	}
+	from := n.smtp.From
+	if from == "" {
+		from = "kaunta@" + n.smtp.Host
+	}
+
+	var auth smtp.Auth
+	if n.smtp.Username != "" {
+		auth = smtp.PlainAuth("", n.smtp.Username, n.smtp.Password, n.smtp.Host)
+	}
+
+	addr := net.JoinHostPort(n.smtp.Host, n.smtp.Port)
+	return n.sendMail(addr, auth, from, []string{alert.Rule.Target}, buildEmail(from, alert))
+}
+
+// buildEmail renders a plain-text alert message with its headers
+func buildEmail(from string, alert Alert) []byte {
+	subject := fmt.Sprintf("[Kaunta] %s: %s", alert.Domain, alert.Rule.Name)
+
+	var b strings.Builder
+	fmt.Fprintf(&b, "From: %s\r\n", from)
+	fmt.Fprintf(&b, "To: %s\r\n", alert.Rule.Target)
+	fmt.Fprintf(&b, "Subject: %s\r\n", strings.NewReplacer("\r", "", "\n", "").Replace(subject))
+	fmt.Fprintf(&b, "Date: %s\r\n", alert.FiredAt.Format(time.RFC1123Z))
+	b.WriteString("MIME-Version: 1.0\r\n")
+	b.WriteString("Content-Type: text/plain; charset=utf-8\r\n\r\n")
+	fmt.Fprintf(&b, "Alert %q (%s) fired for %s.\r\n\r\n", alert.Rule.Name, alert.Rule.Kind, alert.Domain)
+	fmt.Fprintf(&b, "%s\r\n\r\n", alert.Reason)
+	fmt.Fprintf(&b, "Fired at %s\r\n", alert.FiredAt.UTC().Format(time.RFC3339))
+	return []byte(b.String())
+}
@@ -0,0 +1,106 @@
+package alerts
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"net/smtp"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/safehttp"
+)
+
+func testAlert(channel, target string) Alert {
+	return Alert{
+		Rule: models.AlertRule{
+			ID:            uuid.New(),
+			WebsiteID:     uuid.New(),
+			Name:          "Front page spike",
+			Kind:          models.AlertSpike,
+			WindowMinutes: 60,
+			Channel:       channel,
+			Target:        target,
+		},
+		Domain:   "example.com",
+		Reason:   "400 visitors in the last 60 minutes",
+		Current:  400,
+		Baseline: 100,
+		FiredAt:  time.Date(2025, 1, 2, 3, 4, 5, 0, time.UTC),
+	}
+}
+
+func TestSendWebhook(t *testing.T) {
+	var got webhookPayload
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		assert.Equal(t, "application/json", r.Header.Get("Content-Type"))
+		require.NoError(t, json.NewDecoder(r.Body).Decode(&got))
+		w.WriteHeader(http.StatusNoContent)
+	}))
+	defer server.Close()
+
+	alert := testAlert(models.AlertChannelWebhook, server.URL)
+	n := NewNotifier(config.SMTPConfig{})
+	n.client = server.Client() // the test server listens on loopback
+	require.NoError(t, n.Send(context.Background(), alert))
+
+	assert.Equal(t, alert.Rule.ID.String(), got.RuleID)
+	assert.Equal(t, "spike", got.Kind)
+	assert.Equal(t, "example.com", got.Website)
+	assert.Equal(t, int64(400), got.Visitors)
+	assert.Equal(t, "2025-01-02T03:04:05Z", got.FiredAt)
+}
+
+func TestSendWebhookRejectsErrorStatus(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusBadGateway)
+	}))
+	defer server.Close()
+
+	n := NewNotifier(config.SMTPConfig{})
+	n.client = server.Client()
+	err := n.Send(context.Background(), testAlert(models.AlertChannelWebhook, server.URL))
+	assert.ErrorContains(t, err, "502")
+}
+
+func TestSendWebhookRefusesInternalAddresses(t *testing.T) {
+	called := false
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		called = true
+	}))
+	defer server.Close()
+
+	err := NewNotifier(config.SMTPConfig{}).Send(context.Background(), testAlert(models.AlertChannelWebhook, server.URL))
+	assert.ErrorIs(t, err, safehttp.ErrBlockedAddress)
+	assert.False(t, called)
+}
+
+func TestSendEmail(t *testing.T) {
+	n := NewNotifier(config.SMTPConfig{Host: "mail.example.com", Port: "587", From: "alerts@example.com"})
+	var gotAddr string
+	var gotTo []string
+	var gotMsg []byte
+	n.sendMail = func(addr string, a smtp.Auth, from string, to []string, msg []byte) error {
+		gotAddr, gotTo, gotMsg = addr, to, msg
+		assert.Nil(t, a)
+		return nil
+	}
+
+	require.NoError(t, n.Send(context.Background(), testAlert(models.AlertChannelEmail, "owner@example.com")))
+	assert.Equal(t, "mail.example.com:587", gotAddr)
+	assert.Equal(t, []string{"owner@example.com"}, gotTo)
+	assert.Contains(t, string(gotMsg), "Subject: [Kaunta] example.com: Front page spike\r\n")
+	assert.Contains(t, string(gotMsg), "400 visitors in the last 60 minutes")
+}
+
+func TestSendEmailRequiresSMTPHost(t *testing.T) {
+	err := NewNotifier(config.SMTPConfig{}).Send(context.Background(), testAlert(models.AlertChannelEmail, "owner@example.com"))
+	assert.ErrorContains(t, err, "smtp.host")
+}
@@ -0,0 +1,152 @@
+package alerts
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"time"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var (
+	nowFunc            = time.Now
+	evaluationInterval = time.Minute
+	// baselineMinutes is how far back the trailing average looks
+	baselineMinutes = 24 * 60
+	// minDropBaseline keeps drop rules quiet on sites too small to measure
+	minDropBaseline = 5.0
+)
+
+// Observation compares a rule's current window with its trailing baseline
+type Observation struct {
+	Domain   string
+	Current  int64   // distinct visitors in the current window
+	Baseline float64 // average distinct visitors per window over the trailing 24h
+}
+
+// Check reports whether a rule trips for an observation, and why
+func Check(rule models.AlertRule, obs Observation) (bool, string) {
+	switch rule.Kind {
+	case models.AlertSpike:
+		if float64(obs.Current) > rule.Threshold*max(obs.Baseline, 1) {
+			return true, fmt.Sprintf("%d visitors in the last %d minutes, %.1fx the trailing average of %.1f",
+				obs.Current, rule.WindowMinutes, float64(obs.Current)/max(obs.Baseline, 1), obs.Baseline)
+		}
+	case models.AlertDrop:
+		if obs.Baseline >= minDropBaseline && float64(obs.Current) < obs.Baseline/rule.Threshold {
+			return true, fmt.Sprintf("%d visitors in the last %d minutes, below 1/%.1f of the trailing average of %.1f",
+				obs.Current, rule.WindowMinutes, rule.Threshold, obs.Baseline)
+		}
+	case models.AlertSilence:
+		// Only sites that normally receive traffic can go silent
+		if obs.Current == 0 && obs.Baseline > 0 {
+			return true, fmt.Sprintf("no events received in the last %d minutes", rule.WindowMinutes)
+		}
+	}
+	return false, ""
+}
+
+// cooledDown reports whether a rule may fire again; a rule fires at most
+// once per window
+func cooledDown(rule models.AlertRule, now time.Time) bool {
+	if rule.LastFiredAt == nil {
+		return true
+	}
+	return now.Sub(*rule.LastFiredAt) >= time.Duration(rule.WindowMinutes)*time.Minute
+}
+
+// Scheduler evaluates every enabled alert rule on a fixed interval
+type Scheduler struct {
+	db       *sql.DB
+	notifier *Notifier
+}
+
+// NewScheduler creates a new alert scheduler
+func NewScheduler(db *sql.DB, smtp config.SMTPConfig) *Scheduler {
+	return &Scheduler{
+		db:       db,
+		notifier: NewNotifier(smtp),
+	}
+}
+
+// Job evaluates the rules every minute. One instance evaluates at a time, so
+// each alert is sent once.
+func (s *Scheduler) Job() database.Job {
+	return database.Job{
+		Name:        "alerts",
+		Description: "Evaluate traffic alert rules and notify the ones that trip",
+		Every:       evaluationInterval,
+		Run:         s.evaluateAll,
+	}
+}
+
+// evaluateAll checks every enabled rule and delivers the ones that trip
+func (s *Scheduler) evaluateAll(ctx context.Context) error {
+	rules, err := models.ListEnabledAlertRules(ctx, s.db)
+	if err != nil {
+		return fmt.Errorf("failed to load alert rules: %w", err)
+	}
+
+	now := nowFunc()
+	for _, rule := range rules {
+		if !cooledDown(rule, now) {
+			continue
+		}
+
+		obs, err := observe(ctx, s.db, rule)
+		if err != nil {
+			logging.L().Warn("failed to evaluate alert rule", slog.String("rule_id", rule.ID.String()), slog.Any("error", err))
+			continue
+		}
+
+		fire, reason := Check(rule, obs)
+		if !fire {
+			continue
+		}
+
+		alert := Alert{Rule: rule, Domain: obs.Domain, Reason: reason, Current: obs.Current, Baseline: obs.Baseline, FiredAt: now}
+		if err := s.notifier.Send(ctx, alert); err != nil {
+			logging.L().Warn("failed to deliver alert", slog.String("rule_id", rule.ID.String()), slog.String("channel", rule.Channel), slog.Any("error", err))
+			continue
+		}
+		if err := models.MarkAlertRuleFired(ctx, s.db, rule.ID, now); err != nil {
+			logging.L().Warn("failed to record alert", slog.String("rule_id", rule.ID.String()), slog.Any("error", err))
+		}
+		if _, err := models.EnqueueWebhookEvent(ctx, s.db, rule.WebsiteID, models.WebhookAlertFired, newWebhookPayload(alert)); err != nil {
+			logging.L().Warn("failed to queue alert webhooks", slog.String("rule_id", rule.ID.String()), slog.Any("error", err))
+		}
+		logging.L().Info("alert fired", slog.String("rule_id", rule.ID.String()), slog.String("kind", rule.Kind), slog.String("reason", reason))
+	}
+	return nil
+}
+
+// observe counts distinct visitors per window over the current window and the
+// trailing baseline, in a single pass over the website's recent events
+func observe(ctx context.Context, db *sql.DB, rule models.AlertRule) (Observation, error) {
+	windows := max(baselineMinutes/rule.WindowMinutes, 1)
+
+	var obs Observation
+	err := db.QueryRowContext(ctx, `
+		WITH buckets AS (
+			SELECT
+				FLOOR(EXTRACT(EPOCH FROM (NOW() - created_at)) / ($2 * 60))::INT AS bucket,
+				COUNT(DISTINCT session_id) AS visitors
+			FROM website_event
+			WHERE website_id = $1
+			  AND created_at >= NOW() - make_interval(mins => $2 * ($3 + 1))
+			GROUP BY 1
+		)
+		SELECT
+			(SELECT domain FROM website WHERE website_id = $1),
+			COALESCE(SUM(visitors) FILTER (WHERE bucket <= 0), 0),
+			COALESCE(SUM(visitors) FILTER (WHERE bucket > 0), 0)::FLOAT8 / $3
+		FROM buckets
+	`, rule.WebsiteID, rule.WindowMinutes, windows).Scan(&obs.Domain, &obs.Current, &obs.Baseline)
+	return obs, err
+}
@@ -0,0 +1,77 @@
+package alerts
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestCheck(t *testing.T) {
+	spike := models.AlertRule{Kind: models.AlertSpike, Threshold: 3, WindowMinutes: 60}
+	drop := models.AlertRule{Kind: models.AlertDrop, Threshold: 2, WindowMinutes: 60}
+	silence := models.AlertRule{Kind: models.AlertSilence, WindowMinutes: 30}
+
+	tests := []struct {
+		name string
+		rule models.AlertRule
+		obs  Observation
+		fire bool
+	}{
+		{"spike over threshold", spike, Observation{Current: 400, Baseline: 100}, true},
+		{"spike at threshold", spike, Observation{Current: 300, Baseline: 100}, false},
+		{"spike on quiet site uses floor of one", spike, Observation{Current: 4, Baseline: 0}, true},
+		{"drop below threshold", drop, Observation{Current: 40, Baseline: 100}, true},
+		{"drop within threshold", drop, Observation{Current: 60, Baseline: 100}, false},
+		{"drop ignored on tiny site", drop, Observation{Current: 0, Baseline: 2}, false},
+		{"silence", silence, Observation{Current: 0, Baseline: 12}, true},
+		{"silence with traffic", silence, Observation{Current: 1, Baseline: 12}, false},
+		{"silence on never-tracked site", silence, Observation{Current: 0, Baseline: 0}, false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			fire, reason := Check(tt.rule, tt.obs)
+			assert.Equal(t, tt.fire, fire)
+			if tt.fire {
+				assert.NotEmpty(t, reason)
+			}
+		})
+	}
+}
+
+func TestCooledDown(t *testing.T) {
+	now := time.Now()
+	rule := models.AlertRule{WindowMinutes: 30}
+	assert.True(t, cooledDown(rule, now))
+
+	recent := now.Add(-10 * time.Minute)
+	rule.LastFiredAt = &recent
+	assert.False(t, cooledDown(rule, now))
+
+	old := now.Add(-31 * time.Minute)
+	rule.LastFiredAt = &old
+	assert.True(t, cooledDown(rule, now))
+}
+
+func TestObserve(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	rule := models.AlertRule{WebsiteID: uuid.New(), WindowMinutes: 60}
+	mock.ExpectQuery("FROM website_event").
+		WithArgs(rule.WebsiteID, 60, 24).
+		WillReturnRows(sqlmock.NewRows([]string{"domain", "current", "baseline"}).AddRow("example.com", 42, 10.5))
+
+	obs, err := observe(context.Background(), db, rule)
+	require.NoError(t, err)
+	assert.Equal(t, Observation{Domain: "example.com", Current: 42, Baseline: 10.5}, obs)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,511 @@
+// Package archive moves closed months of raw events to S3-compatible
+// storage as Parquet files, so long-term storage doesn't have to live in
+// PostgreSQL. Each month becomes one object holding its events together
+// with their sessions' attributes. Archived months can be read back for
+// exports and restored into the database.
+//
+// Websites with their own event retention are left out: their events are
+// meant to expire, not to be kept elsewhere.
+package archive
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"io"
+	"os"
+	"strings"
+	"time"
+
+	"log/slog"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/parquet"
+)
+
+// MonthLayout is the format of archived months
+const MonthLayout = "2006-01"
+
+var (
+	nowFunc        = time.Now
+	archiveEvery   = 24 * time.Hour
+	restoreBatch   = 5000
+	errNotArchived = errors.New("month is not archived")
+)
+
+// Columns is the schema of the archive files. The session_ columns repeat
+// the attributes of each event's session.
+var Columns = []parquet.Column{
+	{Name: "event_id", Type: parquet.String},
+	{Name: "website_id", Type: parquet.String},
+	{Name: "session_id", Type: parquet.String},
+	{Name: "visit_id", Type: parquet.String},
+	{Name: "created_at", Type: parquet.Timestamp},
+	{Name: "page_title", Type: parquet.String},
+	{Name: "hostname", Type: parquet.String},
+	{Name: "url_path", Type: parquet.String},
+	{Name: "url_query", Type: parquet.String},
+	{Name: "referrer_path", Type: parquet.String},
+	{Name: "referrer_query", Type: parquet.String},
+	{Name: "referrer_domain", Type: parquet.String},
+	{Name: "event_name", Type: parquet.String},
+	{Name: "tag", Type: parquet.String},
+	{Name: "event_type", Type: parquet.Int32},
+	{Name: "scroll_depth", Type: parquet.Int32},
+	{Name: "engagement_time", Type: parquet.Int32},
+	{Name: "props", Type: parquet.JSON},
+	{Name: "utm_source", Type: parquet.String},
+	{Name: "utm_medium", Type: parquet.String},
+	{Name: "utm_campaign", Type: parquet.String},
+	{Name: "utm_term", Type: parquet.String},
+	{Name: "utm_content", Type: parquet.String},
+	{Name: "goal_id", Type: parquet.String},
+	{Name: "session_hostname", Type: parquet.String},
+	{Name: "session_browser", Type: parquet.String},
+	{Name: "session_os", Type: parquet.String},
+	{Name: "session_device", Type: parquet.String},
+	{Name: "session_screen", Type: parquet.String},
+	{Name: "session_language", Type: parquet.String},
+	{Name: "session_country", Type: parquet.String},
+	{Name: "session_region", Type: parquet.String},
+	{Name: "session_city", Type: parquet.String},
+	{Name: "session_distinct_id", Type: parquet.String},
+	{Name: "session_created_at", Type: parquet.Timestamp},
+}
+
+// eventColumns is how many of Columns belong to website_event
+const eventColumns = 24
+
+// exportQuery selects a month's events in the order of Columns
+const exportQuery = `
+	SELECT e.event_id, e.website_id, e.session_id, e.visit_id, e.created_at,
+	       e.page_title, e.hostname, e.url_path, e.url_query,
+	       e.referrer_path, e.referrer_query, e.referrer_domain,
+	       e.event_name, e.tag, e.event_type, e.scroll_depth, e.engagement_time, e.props,
+	       e.utm_source, e.utm_medium, e.utm_campaign, e.utm_term, e.utm_content, e.goal_id,
+	       s.hostname, s.browser, s.os, s.device, s.screen, s.language,
+	       s.country, s.region, s.city, s.distinct_id, s.created_at
+	FROM website_event e
+	JOIN website w ON w.website_id = e.website_id
+	LEFT JOIN session s ON s.session_id = e.session_id
+	WHERE e.created_at >= $1 AND e.created_at < $2
+	  AND w.event_retention_days IS NULL
+`
+
+// Store keeps archive files. *s3.Client is the production implementation.
+type Store interface {
+	Put(ctx context.Context, key string, body io.Reader, size int64) error
+	Get(ctx context.Context, key string) (io.ReadCloser, error)
+}
+
+// Archive is an archived month
+type Archive struct {
+	Month      time.Time `json:"month"`
+	Key        string    `json:"key"`
+	Rows       int64     `json:"rows"`
+	Bytes      int64     `json:"bytes"`
+	ArchivedAt time.Time `json:"archived_at"`
+}
+
+// Archiver exports months of events from db to store
+type Archiver struct {
+	db     *sql.DB
+	store  Store
+	prefix string
+}
+
+// New creates an archiver writing objects under prefix
+func New(db *sql.DB, store Store, prefix string) *Archiver {
+	if prefix != "" && !strings.HasSuffix(prefix, "/") {
+		prefix += "/"
+	}
+	return &Archiver{db: db, store: store, prefix: prefix}
+}
+
+// ParseMonth parses a YYYY-MM month
+func ParseMonth(s string) (time.Time, error) {
+	month, err := time.Parse(MonthLayout, s)
+	if err != nil {
+		return time.Time{}, fmt.Errorf("invalid month %q (use YYYY-MM)", s)
+	}
+	return month, nil
+}
+
+func (a *Archiver) key(month time.Time) string {
+	return a.prefix + "events/" + month.Format(MonthLayout) + ".parquet"
+}
+
+// List returns the archived months, oldest first
+func (a *Archiver) List(ctx context.Context) ([]Archive, error) {
+	rows, err := a.db.QueryContext(ctx, `
+		SELECT month, object_key, row_count, byte_size, archived_at
+		FROM event_archive
+		ORDER BY month
+	`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var archives []Archive
+	for rows.Next() {
+		var arc Archive
+		if err := rows.Scan(&arc.Month, &arc.Key, &arc.Rows, &arc.Bytes, &arc.ArchivedAt); err != nil {
+			return nil, err
+		}
+		archives = append(archives, arc)
+	}
+	return archives, rows.Err()
+}
+
+// Pending returns the closed months whose events haven't been archived
+func (a *Archiver) Pending(ctx context.Context) ([]time.Time, error) {
+	rows, err := a.db.QueryContext(ctx, `SELECT month FROM archive_pending_months()`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var months []time.Time
+	for rows.Next() {
+		var month time.Time
+		if err := rows.Scan(&month); err != nil {
+			return nil, err
+		}
+		months = append(months, month)
+	}
+	return months, rows.Err()
+}
+
+// Run archives every pending month. It stops at the first failure, so
+// months are archived in order.
+func (a *Archiver) Run(ctx context.Context) ([]Archive, error) {
+	months, err := a.Pending(ctx)
+	if err != nil {
+		return nil, fmt.Errorf("failed to list pending months: %w", err)
+	}
+	var archived []Archive
+	for _, month := range months {
+		arc, err := a.ArchiveMonth(ctx, month)
+		if err != nil {
+			return archived, fmt.Errorf("failed to archive %s: %w", month.Format(MonthLayout), err)
+		}
+		archived = append(archived, arc)
+	}
+	return archived, nil
+}
+
+// ArchiveMonth uploads the events of a closed month and records the
+// archive. A month is only archived once: by the time it could be archived
+// again some of its partitions may be gone.
+func (a *Archiver) ArchiveMonth(ctx context.Context, month time.Time) (Archive, error) {
+	month = time.Date(month.Year(), month.Month(), 1, 0, 0, 0, 0, time.UTC)
+	end := month.AddDate(0, 1, 0)
+	if end.After(nowFunc().UTC()) {
+		return Archive{}, fmt.Errorf("%s has not ended yet", month.Format(MonthLayout))
+	}
+	var archived bool
+	if err := a.db.QueryRowContext(ctx, `SELECT EXISTS (SELECT 1 FROM event_archive WHERE month = $1)`, month).Scan(&archived); err != nil {
+		return Archive{}, err
+	}
+	if archived {
+		return Archive{}, fmt.Errorf("%s is already archived", month.Format(MonthLayout))
+	}
+
+	file, err := os.CreateTemp("", "kaunta-archive-*.parquet")
+	if err != nil {
+		return Archive{}, err
+	}
+	defer func() {
+		_ = file.Close()
+		_ = os.Remove(file.Name())
+	}()
+
+	count, err := a.writeMonth(ctx, file, month, end)
+	if err != nil {
+		return Archive{}, err
+	}
+	size, err := file.Seek(0, io.SeekEnd)
+	if err != nil {
+		return Archive{}, err
+	}
+	if _, err := file.Seek(0, io.SeekStart); err != nil {
+		return Archive{}, err
+	}
+
+	arc := Archive{Month: month, Key: a.key(month), Rows: count, Bytes: size}
+	if err := a.store.Put(ctx, arc.Key, file, size); err != nil {
+		return Archive{}, fmt.Errorf("failed to upload %s: %w", arc.Key, err)
+	}
+
+	err = a.db.QueryRowContext(ctx, `
+		INSERT INTO event_archive (month, object_key, row_count, byte_size)
+		VALUES ($1, $2, $3, $4)
+		RETURNING archived_at
+	`, month, arc.Key, arc.Rows, arc.Bytes).Scan(&arc.ArchivedAt)
+	if err != nil {
+		return Archive{}, fmt.Errorf("failed to record archive: %w", err)
+	}
+
+	logging.L().Info("archived events", slog.String("month", month.Format(MonthLayout)),
+		slog.String("key", arc.Key), slog.Int64("rows", arc.Rows), slog.Int64("bytes", arc.Bytes))
+	return arc, nil
+}
+
+// writeMonth writes the events in [from, until) to w as Parquet
+func (a *Archiver) writeMonth(ctx context.Context, w io.Writer, from, until time.Time) (int64, error) {
+	rows, err := a.db.QueryContext(ctx, exportQuery, from, until)
+	if err != nil {
+		return 0, fmt.Errorf("failed to read events: %w", err)
+	}
+	defer func() { _ = rows.Close() }()
+
+	pw, err := parquet.NewWriter(w, Columns)
+	if err != nil {
+		return 0, err
+	}
+	for rows.Next() {
+		row, err := scanRow(rows)
+		if err != nil {
+			return 0, err
+		}
+		if err := pw.Write(row); err != nil {
+			return 0, err
+		}
+	}
+	if err := rows.Err(); err != nil {
+		return 0, err
+	}
+	if err := pw.Close(); err != nil {
+		return 0, err
+	}
+	return pw.Rows(), nil
+}
+
+// scanRow reads a row of exportQuery into the Go types of Columns
+func scanRow(rows *sql.Rows) ([]any, error) {
+	strs := make([]sql.NullString, len(Columns))
+	ints := make([]sql.NullInt32, len(Columns))
+	times := make([]sql.NullTime, len(Columns))
+	dest := make([]any, len(Columns))
+	for i, c := range Columns {
+		switch c.Type {
+		case parquet.Int32:
+			dest[i] = &ints[i]
+		case parquet.Timestamp:
+			dest[i] = &times[i]
+		default:
+			dest[i] = &strs[i]
+		}
+	}
+	if err := rows.Scan(dest...); err != nil {
+		return nil, err
+	}
+
+	row := make([]any, len(Columns))
+	for i, c := range Columns {
+		switch {
+		case c.Type == parquet.Int32 && ints[i].Valid:
+			row[i] = ints[i].Int32
+		case c.Type == parquet.Timestamp && times[i].Valid:
+			row[i] = times[i].Time.UTC()
+		case (c.Type == parquet.String || c.Type == parquet.JSON) && strs[i].Valid:
+			row[i] = strs[i].String
+		}
+	}
+	return row, nil
+}
+
+// Open downloads the archive of month and returns a reader for it. Closing
+// the returned file removes the download.
+func (a *Archiver) Open(ctx context.Context, month time.Time) (*parquet.Reader, io.Closer, error) {
+	var key string
+	err := a.db.QueryRowContext(ctx, `SELECT object_key FROM event_archive WHERE month = $1`, month).Scan(&key)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, nil, fmt.Errorf("%s: %w", month.Format(MonthLayout), errNotArchived)
+	}
+	if err != nil {
+		return nil, nil, err
+	}
+
+	body, err := a.store.Get(ctx, key)
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to download %s: %w", key, err)
+	}
+	defer func() { _ = body.Close() }()
+
+	file, err := os.CreateTemp("", "kaunta-archive-*.parquet")
+	if err != nil {
+		return nil, nil, err
+	}
+	download := removeOnClose{file}
+	size, err := io.Copy(file, body)
+	if err != nil {
+		_ = download.Close()
+		return nil, nil, fmt.Errorf("failed to download %s: %w", key, err)
+	}
+	reader, err := parquet.NewReader(file, size)
+	if err != nil {
+		_ = download.Close()
+		return nil, nil, fmt.Errorf("failed to read %s: %w", key, err)
+	}
+	return reader, download, nil
+}
+
+type removeOnClose struct {
+	*os.File
+}
+
+func (f removeOnClose) Close() error {
+	err := f.File.Close()
+	_ = os.Remove(f.Name())
+	return err
+}
+
+// Each calls fn for every archived event of month, optionally only those of
+// one website. Rows are in the order of Columns and reused between calls.
+func (a *Archiver) Each(ctx context.Context, month time.Time, websiteID *uuid.UUID, fn func(row []any) error) error {
+	reader, download, err := a.Open(ctx, month)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = download.Close() }()
+
+	return reader.Each(func(row []any) error {
+		if err := ctx.Err(); err != nil {
+			return err
+		}
+		if websiteID != nil && row[1] != websiteID.String() {
+			return nil
+		}
+		return fn(row)
+	})
+}
+
+// Restore inserts the archived events of month back into website_event,
+// recreating their partitions and any session that was deleted since. Events
+// that are still present are skipped, as are websites that no longer exist.
+func (a *Archiver) Restore(ctx context.Context, month time.Time, websiteID *uuid.UUID) (int64, error) {
+	end := month.AddDate(0, 1, 0)
+	if _, err := a.db.ExecContext(ctx, `SELECT ensure_partitions_between('website_event', $1, $2)`, month, end); err != nil {
+		return 0, fmt.Errorf("failed to create partitions: %w", err)
+	}
+
+	websites := make(map[string]bool)
+	rows, err := a.db.QueryContext(ctx, `SELECT website_id FROM website`)
+	if err != nil {
+		return 0, err
+	}
+	for rows.Next() {
+		var id string
+		if err := rows.Scan(&id); err != nil {
+			_ = rows.Close()
+			return 0, err
+		}
+		websites[id] = true
+	}
+	_ = rows.Close()
+
+	var restored int64
+	batch := make([][]any, 0, restoreBatch)
+	flush := func() error {
+		n, err := a.insertBatch(ctx, batch)
+		restored += n
+		batch = batch[:0]
+		return err
+	}
+	err = a.Each(ctx, month, websiteID, func(row []any) error {
+		if id, _ := row[1].(string); !websites[id] {
+			return nil
+		}
+		batch = append(batch, append([]any(nil), row...))
+		if len(batch) < restoreBatch {
+			return nil
+		}
+		return flush()
+	})
+	if err == nil && len(batch) > 0 {
+		err = flush()
+	}
+	return restored, err
+}
+
+// insertBatch writes one transaction of restored sessions and events
+func (a *Archiver) insertBatch(ctx context.Context, batch [][]any) (int64, error) {
+	tx, err := a.db.BeginTx(ctx, nil)
+	if err != nil {
+		return 0, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	sessions, err := tx.PrepareContext(ctx, `
+		INSERT INTO session (session_id, website_id, hostname, browser, os, device, screen, language,
+		                     country, region, city, distinct_id, created_at)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, COALESCE($13::TIMESTAMPTZ, $14::TIMESTAMPTZ))
+		ON CONFLICT (session_id) DO NOTHING
+	`)
+	if err != nil {
+		return 0, err
+	}
+	events, err := tx.PrepareContext(ctx, `
+		INSERT INTO website_event (event_id, website_id, session_id, visit_id, created_at,
+		                           page_title, hostname, url_path, url_query,
+		                           referrer_path, referrer_query, referrer_domain,
+		                           event_name, tag, event_type, scroll_depth, engagement_time, props,
+		                           utm_source, utm_medium, utm_campaign, utm_term, utm_content, goal_id)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+		        $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+		ON CONFLICT DO NOTHING
+	`)
+	if err != nil {
+		return 0, err
+	}
+
+	var inserted int64
+	for _, row := range batch {
+		session := append([]any{row[2], row[1]}, row[eventColumns:]...)
+		session = append(session, row[4])
+		if _, err := sessions.ExecContext(ctx, session...); err != nil {
+			return 0, fmt.Errorf("failed to restore session %v: %w", row[2], err)
+		}
+		result, err := events.ExecContext(ctx, row[:eventColumns]...)
+		if err != nil {
+			return 0, fmt.Errorf("failed to restore event %v: %w", row[0], err)
+		}
+		n, _ := result.RowsAffected()
+		inserted += n
+	}
+	if err := tx.Commit(); err != nil {
+		return 0, err
+	}
+	return inserted, nil
+}
+
+// Scheduler archives closed months once a day
+type Scheduler struct {
+	archiver *Archiver
+}
+
+// NewScheduler creates a scheduler for archiver
+func NewScheduler(archiver *Archiver) *Scheduler {
+	return &Scheduler{archiver: archiver}
+}
+
+// Job archives pending months daily
+func (s *Scheduler) Job() database.Job {
+	return database.Job{
+		Name:        "archive",
+		Description: "Export closed months of events to object storage",
+		Every:       archiveEvery,
+		Run: func(ctx context.Context) error {
+			if _, err := s.archiver.Run(ctx); err != nil {
+				return fmt.Errorf("event archiving failed: %w", err)
+			}
+			return nil
+		},
+	}
+}
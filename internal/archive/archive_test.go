@@ -0,0 +1,145 @@
+package archive
+
+import (
+	"bytes"
+	"context"
+	"database/sql/driver"
+	"io"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+// memStore keeps objects in memory
+type memStore map[string][]byte
+
+func (m memStore) Put(_ context.Context, key string, body io.Reader, size int64) error {
+	data, err := io.ReadAll(body)
+	if err != nil {
+		return err
+	}
+	if int64(len(data)) != size {
+		return io.ErrShortWrite
+	}
+	m[key] = data
+	return nil
+}
+
+func (m memStore) Get(_ context.Context, key string) (io.ReadCloser, error) {
+	return io.NopCloser(bytes.NewReader(m[key])), nil
+}
+
+func withNow(t *testing.T, now time.Time) {
+	t.Helper()
+	nowFunc = func() time.Time { return now }
+	t.Cleanup(func() { nowFunc = time.Now })
+}
+
+func eventRow(websiteID uuid.UUID, at time.Time, path string) []driver.Value {
+	row := make([]driver.Value, len(Columns))
+	row[0] = uuid.NewString()
+	row[1] = websiteID.String()
+	row[2] = uuid.NewString()
+	row[3] = uuid.NewString()
+	row[4] = at
+	row[7] = path
+	row[14] = int64(1)
+	row[17] = `{"plan":"pro"}`
+	row[30] = "DE"
+	return row
+}
+
+func columnNames() []string {
+	names := make([]string, len(Columns))
+	for i, c := range Columns {
+		names[i] = c.Name
+	}
+	return names
+}
+
+func TestArchiveMonthUploadsParquetAndReadsItBack(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	withNow(t, time.Date(2025, time.February, 3, 0, 0, 0, 0, time.UTC))
+
+	store := memStore{}
+	archiver := New(db, store, "kaunta")
+	month := time.Date(2025, time.January, 1, 0, 0, 0, 0, time.UTC)
+	site, other := uuid.New(), uuid.New()
+	at := time.Date(2025, time.January, 31, 23, 0, 0, 0, time.UTC)
+
+	mock.ExpectQuery("SELECT EXISTS").WithArgs(month).
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(false))
+	mock.ExpectQuery("SELECT e.event_id").WithArgs(month, month.AddDate(0, 1, 0)).
+		WillReturnRows(sqlmock.NewRows(columnNames()).
+			AddRow(eventRow(site, at, "/pricing")...).
+			AddRow(eventRow(other, at, "/")...))
+	mock.ExpectQuery("INSERT INTO event_archive").
+		WithArgs(month, "kaunta/events/2025-01.parquet", int64(2), sqlmock.AnyArg()).
+		WillReturnRows(sqlmock.NewRows([]string{"archived_at"}).AddRow(time.Now()))
+
+	arc, err := archiver.ArchiveMonth(context.Background(), month)
+	require.NoError(t, err)
+	assert.Equal(t, int64(2), arc.Rows)
+	require.Contains(t, store, "kaunta/events/2025-01.parquet")
+	assert.Equal(t, int64(len(store["kaunta/events/2025-01.parquet"])), arc.Bytes)
+
+	mock.ExpectQuery("SELECT object_key FROM event_archive").WithArgs(month).
+		WillReturnRows(sqlmock.NewRows([]string{"object_key"}).AddRow(arc.Key))
+
+	var paths []any
+	err = archiver.Each(context.Background(), month, &site, func(row []any) error {
+		paths = append(paths, row[7])
+		assert.Equal(t, at, row[4])
+		assert.Equal(t, int32(1), row[14])
+		assert.Equal(t, "DE", row[30])
+		assert.Nil(t, row[5])
+		return nil
+	})
+	require.NoError(t, err)
+	assert.Equal(t, []any{"/pricing"}, paths, "only the requested website")
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestArchiveMonthRefusesOpenAndArchivedMonths(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	withNow(t, time.Date(2025, time.February, 3, 0, 0, 0, 0, time.UTC))
+	archiver := New(db, memStore{}, "")
+
+	_, err = archiver.ArchiveMonth(context.Background(), time.Date(2025, time.February, 1, 0, 0, 0, 0, time.UTC))
+	assert.ErrorContains(t, err, "2025-02 has not ended yet")
+
+	mock.ExpectQuery("SELECT EXISTS").WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(true))
+	_, err = archiver.ArchiveMonth(context.Background(), time.Date(2025, time.January, 1, 0, 0, 0, 0, time.UTC))
+	assert.ErrorContains(t, err, "2025-01 is already archived")
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEachRejectsMonthsNotArchived(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	mock.ExpectQuery("SELECT object_key FROM event_archive").
+		WillReturnRows(sqlmock.NewRows([]string{"object_key"}))
+
+	err = New(db, memStore{}, "").Each(context.Background(), time.Date(2024, time.May, 1, 0, 0, 0, 0, time.UTC), nil,
+		func([]any) error { return nil })
+	assert.ErrorIs(t, err, errNotArchived)
+}
+
+func TestParseMonth(t *testing.T) {
+	month, err := ParseMonth("2025-01")
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2025, time.January, 1, 0, 0, 0, 0, time.UTC), month)
+
+	_, err = ParseMonth("2025-01-15")
+	assert.ErrorContains(t, err, "use YYYY-MM")
+}
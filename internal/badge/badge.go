@@ -0,0 +1,173 @@
+// Package badge renders hit-counter badges as standalone SVG: a flat two-part
+// label/value badge, or a retro counter with one lit cell per digit.
+package badge
+
+import (
+	"fmt"
+	"html"
+	"regexp"
+	"strconv"
+	"strings"
+)
+
+const (
+	// StyleFlat is a label/value badge in the style of README shields
+	StyleFlat = "flat"
+	// StyleRetro draws each digit in its own dark cell, like an old hit counter
+	StyleRetro = "retro"
+
+	// DefaultColor is the value background of flat badges
+	DefaultColor = "4c1"
+	// MaxLabelLength caps the label so a badge can't be stretched arbitrarily
+	MaxLabelLength = 40
+	// retroDigits is the minimum number of digit cells in a retro badge
+	retroDigits = 6
+)
+
+// colorPattern accepts 3 or 6 digit hex colors without the leading #
+var colorPattern = regexp.MustCompile(`^(?:[0-9a-fA-F]{3}|[0-9a-fA-F]{6})$`)
+
+// Options describe one badge
+type Options struct {
+	Label string
+	Value int64
+	Style string // flat (default) or retro
+	Color string // Hex value background for flat badges, without #
+}
+
+// Validate checks the style, color and label length
+func (o Options) Validate() error {
+	switch o.Style {
+	case "", StyleFlat, StyleRetro:
+	default:
+		return fmt.Errorf("unknown style %q (valid: %s, %s)", o.Style, StyleFlat, StyleRetro)
+	}
+	if o.Color != "" && !colorPattern.MatchString(o.Color) {
+		return fmt.Errorf("color must be a 3 or 6 digit hex value")
+	}
+	if len([]rune(o.Label)) > MaxLabelLength {
+		return fmt.Errorf("label must be at most %d characters", MaxLabelLength)
+	}
+	return nil
+}
+
+// Render draws the badge; options are assumed valid
+func Render(o Options) []byte {
+	if o.Style == StyleRetro {
+		return renderRetro(o.Label, o.Value)
+	}
+	color := o.Color
+	if color == "" {
+		color = DefaultColor
+	}
+	return renderFlat(o.Label, Compact(o.Value), color)
+}
+
+// RenderMessage draws a gray flat badge carrying a message instead of a
+// count, so broken embeds still show what went wrong
+func RenderMessage(label, message string) []byte {
+	return renderFlat(label, message, "9f9f9f")
+}
+
+// Compact formats n for a flat badge: 999, 1.2k, 45k, 3.4M
+func Compact(n int64) string {
+	switch {
+	case n < 1000:
+		return strconv.FormatInt(n, 10)
+	case n < 1_000_000:
+		return compactUnit(n, 1000, "k")
+	case n < 1_000_000_000:
+		return compactUnit(n, 1_000_000, "M")
+	default:
+		return compactUnit(n, 1_000_000_000, "B")
+	}
+}
+
+func compactUnit(n, unit int64, suffix string) string {
+	if n < 10*unit {
+		// One decimal, truncated so 1999 reads 1.9k rather than 2.0k
+		tenths := n * 10 / unit
+		if tenths%10 == 0 {
+			return strconv.FormatInt(tenths/10, 10) + suffix
+		}
+		return fmt.Sprintf("%d.%d%s", tenths/10, tenths%10, suffix)
+	}
+	return strconv.FormatInt(n/unit, 10) + suffix
+}
+
+// textWidth approximates the rendered width of s in 11px Verdana
+func textWidth(s string) int {
+	width := 0.0
+	for _, r := range s {
+		switch {
+		case strings.ContainsRune("ijlI.,:;!|' ", r):
+			width += 3.5
+		case strings.ContainsRune("mwMW", r):
+			width += 10
+		case r >= 'A' && r <= 'Z':
+			width += 7.5
+		default:
+			width += 6.5
+		}
+	}
+	return int(width + 0.5)
+}
+
+func renderFlat(label, value, color string) []byte {
+	labelWidth := 0
+	if label != "" {
+		labelWidth = textWidth(label) + 10
+	}
+	valueWidth := textWidth(value) + 10
+	total := labelWidth + valueWidth
+	label, value = html.EscapeString(label), html.EscapeString(value)
+
+	var b strings.Builder
+	fmt.Fprintf(&b, `<svg xmlns="http://www.w3.org/2000/svg" width="%d" height="20" role="img" aria-label="%s: %s">`, total, label, value)
+	fmt.Fprintf(&b, `<title>%s: %s</title>`, label, value)
+	b.WriteString(`<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>`)
+	fmt.Fprintf(&b, `<clipPath id="r"><rect width="%d" height="20" rx="3" fill="#fff"/></clipPath>`, total)
+	b.WriteString(`<g clip-path="url(#r)">`)
+	fmt.Fprintf(&b, `<rect width="%d" height="20" fill="#555"/>`, labelWidth)
+	fmt.Fprintf(&b, `<rect x="%d" width="%d" height="20" fill="#%s"/>`, labelWidth, valueWidth, color)
+	fmt.Fprintf(&b, `<rect width="%d" height="20" fill="url(#s)"/>`, total)
+	b.WriteString(`</g>`)
+	b.WriteString(`<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">`)
+	if label != "" {
+		fmt.Fprintf(&b, `<text x="%d" y="15" fill="#010101" fill-opacity=".3">%s</text><text x="%d" y="14">%s</text>`,
+			labelWidth/2, label, labelWidth/2, label)
+	}
+	fmt.Fprintf(&b, `<text x="%d" y="15" fill="#010101" fill-opacity=".3">%s</text><text x="%d" y="14">%s</text>`,
+		labelWidth+valueWidth/2, value, labelWidth+valueWidth/2, value)
+	b.WriteString(`</g></svg>`)
+	return []byte(b.String())
+}
+
+func renderRetro(label string, value int64) []byte {
+	const cellWidth, cellGap, height = 14, 2, 22
+
+	digits := fmt.Sprintf("%0*d", retroDigits, max(value, 0))
+	labelWidth := 0
+	if label != "" {
+		labelWidth = textWidth(label) + 12
+	}
+	total := labelWidth + cellGap + len(digits)*(cellWidth+cellGap)
+	label = html.EscapeString(label)
+
+	var b strings.Builder
+	fmt.Fprintf(&b, `<svg xmlns="http://www.w3.org/2000/svg" width="%d" height="%d" role="img" aria-label="%s: %s">`, total, height, label, digits)
+	fmt.Fprintf(&b, `<title>%s: %s</title>`, label, digits)
+	fmt.Fprintf(&b, `<rect width="%d" height="%d" rx="2" fill="#222"/>`, total, height)
+	if label != "" {
+		fmt.Fprintf(&b, `<text x="%d" y="15" fill="#ccc" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">%s</text>`,
+			labelWidth/2, label)
+	}
+	b.WriteString(`<g font-family="'Courier New',Courier,monospace" font-size="16" font-weight="bold" text-anchor="middle" fill="#39ff14">`)
+	for i, digit := range digits {
+		x := labelWidth + cellGap + i*(cellWidth+cellGap)
+		fmt.Fprintf(&b, `<rect x="%d" y="2" width="%d" height="%d" fill="#000"/><text x="%d" y="17">%c</text>`,
+			x, cellWidth, height-4, x+cellWidth/2, digit)
+	}
+	b.WriteString(`</g></svg>`)
+	return []byte(b.String())
+}
@@ -0,0 +1,47 @@
+package badge
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestCompact(t *testing.T) {
+	for n, want := range map[int64]string{
+		0:             "0",
+		999:           "999",
+		1000:          "1k",
+		1999:          "1.9k",
+		45_678:        "45k",
+		3_400_000:     "3.4M",
+		2_000_000_000: "2B",
+	} {
+		assert.Equal(t, want, Compact(n), n)
+	}
+}
+
+func TestValidate(t *testing.T) {
+	assert.NoError(t, Options{Style: StyleRetro, Color: "ff8800"}.Validate())
+	assert.Error(t, Options{Style: "neon"}.Validate())
+	assert.Error(t, Options{Color: "red;stroke:url(x)"}.Validate())
+	assert.Error(t, Options{Label: strings.Repeat("x", MaxLabelLength+1)}.Validate())
+}
+
+func TestRenderFlat(t *testing.T) {
+	svg := string(Render(Options{Label: `<views>`, Value: 1234, Color: "007ec6"}))
+	assert.True(t, strings.HasPrefix(svg, `<svg xmlns="http://www.w3.org/2000/svg"`))
+	assert.Contains(t, svg, "&lt;views&gt;")
+	assert.NotContains(t, svg, "<views>")
+	assert.Contains(t, svg, ">1.2k</text>")
+	assert.Contains(t, svg, `fill="#007ec6"`)
+}
+
+func TestRenderRetro(t *testing.T) {
+	svg := string(Render(Options{Label: "hits", Value: 42, Style: StyleRetro}))
+	assert.Contains(t, svg, `aria-label="hits: 000042"`)
+	assert.Equal(t, 6, strings.Count(svg, `fill="#000"`))
+
+	svg = string(Render(Options{Value: 12_345_678, Style: StyleRetro}))
+	assert.Equal(t, 8, strings.Count(svg, `fill="#000"`))
+}
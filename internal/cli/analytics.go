@@ -15,6 +15,7 @@ import (
 
 	"github.com/google/uuid"
 	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/warehouse"
 	"github.com/spf13/cobra"
 )
 
@@ -61,13 +62,30 @@ type LiveStatsData struct {
 
 // Stats command structure
 var statsCmd = &cobra.Command{
-	Use:   "stats",
+	Use:   "stats [<website-domain>] [--period 7d]",
 	Short: "View analytics statistics",
 	Long: `View analytics statistics and reports.
 
-Stats commands allow you to view analytics data and generate reports from the command line.`,
-	Run: func(cmd *cobra.Command, args []string) {
-		fmt.Println(cmd.Help())
+Stats commands allow you to view analytics data and generate reports from the command line.
+
+Given just a website, prints a quick summary: visitors, pageviews, bounce
+rate and visit duration, a sparkline of pageviews and the top pages and
+referrers.
+
+Options:
+  --period      24h, 7d, 4w, ... up to 365 days (default 7d)
+  --top N       Number of pages and referrers to show (1-100, default 5)
+  --format      Output format: text, json (default text)
+
+Examples:
+  kaunta stats mysite.com
+  kaunta stats mysite.com --period 30d`,
+	Args: cobra.MaximumNArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		if len(args) == 0 {
+			return cmd.Help()
+		}
+		return runStatsSummary(args[0], statsPeriod, statsFormat, statsTop)
 	},
 }
 
@@ -77,6 +95,7 @@ var (
 	getTopPagesFn          = GetTopPages
 	getBreakdownStatsFn    = GetBreakdownStats
 	getLiveStatsFn         = GetLiveStats
+	getDailyRollupFn       = warehouse.Daily
 	tickerFactory          = func(d time.Duration) (<-chan time.Time, func()) {
 		ticker := time.NewTicker(d)
 		return ticker.C, ticker.Stop
@@ -204,6 +223,34 @@ Press Ctrl+C to stop.`,
 	},
 }
 
+// Daily command flags
+var (
+	dailyDate   string
+	dailyFormat string
+)
+
+var statsDailyCmd = &cobra.Command{
+	Use:   "daily <website-domain> [--date <YYYY-MM-DD>] [--format json|csv]",
+	Short: "Export a finished day's aggregates",
+	Long: `Export the per page, referrer, country and event totals of one UTC day.
+
+Only days that have ended are available, and a day always exports the same
+rows, so a nightly job can load yesterday into a data warehouse and safely
+re-run. CSV rows carry the date and website ID for loading into one table.
+
+Options:
+  --date        UTC day as YYYY-MM-DD (default yesterday)
+  --format      Output format: json, csv (default csv)
+
+Examples:
+  kaunta stats daily mysite.com > mysite-$(date -u -d yesterday +%F).csv
+  kaunta stats daily mysite.com --date 2026-05-01 --format json`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runStatsDaily(args[0], dailyDate, dailyFormat)
+	},
+}
+
 // Command implementations
 
 func runStatsOverview(domain string, days int, format string) error {
@@ -351,6 +398,50 @@ func runStatsBreakdown(domain string, dimension string, days int, top int, forma
 	}
 }
 
+func runStatsDaily(domain, date, format string) error {
+	if format != "json" && format != "csv" {
+		return fmt.Errorf("invalid format: %s (use json or csv)", format)
+	}
+
+	day, err := warehouse.ParseDate(date, time.Now())
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 5*time.Minute)
+	defer cancel()
+
+	websiteID, err := getWebsiteIDByDomainFn(ctx, domain)
+	if err != nil {
+		return err
+	}
+	parsedID, err := uuid.Parse(websiteID)
+	if err != nil {
+		return fmt.Errorf("invalid website ID: %w", err)
+	}
+
+	rollup, err := getDailyRollupFn(ctx, database.DB, parsedID, day)
+	if err != nil {
+		return err
+	}
+
+	if format == "json" {
+		data, err := json.MarshalIndent(rollup, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	}
+	return warehouse.WriteCSV(os.Stdout, rollup)
+}
+
 func runStatsLive(domain string, interval int, format string) error {
 	if interval < 2 || interval > 60 {
 		interval = 5
@@ -1297,6 +1388,12 @@ func init() {
 	statsCmd.AddCommand(statsPagesCmd)
 	statsCmd.AddCommand(statsBreakdownCmd)
 	statsCmd.AddCommand(statsLiveCmd)
+	statsCmd.AddCommand(statsDailyCmd)
+
+	// Summary flags
+	statsCmd.Flags().StringVarP(&statsPeriod, "period", "p", "7d", "Time period, e.g. 24h, 7d, 4w")
+	statsCmd.Flags().IntVarP(&statsTop, "top", "t", 5, "Number of pages and referrers to show (1-100)")
+	statsCmd.Flags().StringVarP(&statsFormat, "format", "f", "text", "Output format (text, json)")
 
 	// Overview command flags
 	statsOverviewCmd.Flags().IntVarP(&overviewDays, "days", "d", 7, "Time period in days (1-365)")
@@ -1315,6 +1412,10 @@ func init() {
 	statsBreakdownCmd.Flags().IntVarP(&breakdownTop, "top", "t", 10, "Number of items to show (1-100)")
 	statsBreakdownCmd.Flags().StringVarP(&breakdownFormat, "format", "f", "table", "Output format (json, table, csv)")
 
+	// Daily command flags
+	statsDailyCmd.Flags().StringVar(&dailyDate, "date", "", "UTC day as YYYY-MM-DD (default yesterday)")
+	statsDailyCmd.Flags().StringVarP(&dailyFormat, "format", "f", "csv", "Output format (json, csv)")
+
 	// Live command flags
 	statsLiveCmd.Flags().IntVarP(&liveInterval, "interval", "i", 5, "Update interval in seconds (2-60)")
 	statsLiveCmd.Flags().StringVarP(&liveFormat, "format", "f", "text", "Output format (json, text)")
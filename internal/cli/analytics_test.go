@@ -7,8 +7,11 @@ import (
 	"testing"
 	"time"
 
+	"github.com/google/uuid"
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/warehouse"
 )
 
 func TestRunStatsOverviewTable(t *testing.T) {
@@ -205,6 +208,39 @@ func stubWebsiteIDLookup(t *testing.T, fn func(ctx context.Context, domain strin
 	})
 }
 
+func TestRunStatsDailyCSV(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+
+	websiteID := uuid.MustParse("11111111-2222-3333-4444-555555555555")
+	stubWebsiteIDLookup(t, func(ctx context.Context, domain string) (string, error) {
+		return websiteID.String(), nil
+	})
+
+	original := getDailyRollupFn
+	getDailyRollupFn = func(ctx context.Context, db warehouse.Querier, id uuid.UUID, day time.Time) (*warehouse.Rollup, error) {
+		assert.Equal(t, websiteID, id)
+		assert.Equal(t, time.Date(2026, 5, 1, 0, 0, 0, 0, time.UTC), day)
+		return &warehouse.Rollup{WebsiteID: id, Date: "2026-05-01", Rows: []warehouse.Row{
+			{Dimension: "page", Value: "/", Pageviews: 10, Visitors: 4},
+		}}, nil
+	}
+	t.Cleanup(func() { getDailyRollupFn = original })
+
+	output, err := captureOutput(t, func() error {
+		return runStatsDaily("example.com", "2026-05-01", "csv")
+	})
+	require.NoError(t, err)
+	assert.Equal(t, "date,website_id,dimension,value,pageviews,events,visitors\n"+
+		"2026-05-01,11111111-2222-3333-4444-555555555555,page,/,10,0,4\n", output)
+}
+
+func TestRunStatsDailyRejectsToday(t *testing.T) {
+	err := runStatsDaily("example.com", time.Now().UTC().Format(warehouse.DateLayout), "csv")
+	require.Error(t, err)
+	assert.Contains(t, err.Error(), "not finalized yet")
+}
+
 func stubOverviewFetcher(t *testing.T, fn func(context.Context, *sql.DB, string, int) (*OverviewStats, error)) {
 	t.Helper()
 	original := getOverviewStats
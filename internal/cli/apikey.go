@@ -2,13 +2,16 @@ package cli
 
 import (
 	"context"
+	"encoding/json"
 	"fmt"
 	"os"
+	"slices"
 	"strings"
 	"text/tabwriter"
 	"time"
 
 	"github.com/google/uuid"
+	"github.com/lib/pq"
 	"github.com/spf13/cobra"
 
 	"github.com/seuros/kaunta/internal/database"
@@ -25,7 +28,7 @@ programmatically via POST /api/ingest.`,
 }
 
 var apikeyCreateCmd = &cobra.Command{
-	Use:   "create <website-domain>",
+	Use:   "create [<website-domain>] [--website <domain|id>] [--scope ingest,stats] [--expires 1h] [--format text|json]",
 	Short: "Create a new API key for a website",
 	Long: `Create a new API key for server-side event ingestion or stats access.
 
@@ -35,44 +38,101 @@ Available scopes:
   ingest  - Allows pushing analytics events via POST /api/ingest (default)
   stats   - Allows reading stats via GET /api/v1/stats/:website_id
 
+The website is given as argument or with --website, by domain or ID. With
+--format json the key and its details are printed as one JSON object, for
+scripts such as a CI job minting a short-lived key:
+
+  KEY=$(kaunta apikey create --website example.com --scope stats --expires 1h --format json | jq -r .api_key)
+
 Examples:
   kaunta apikey create example.com
   kaunta apikey create example.com --name "Rails Backend"
   kaunta apikey create example.com --scope stats --name "Stats Reader"
+  kaunta apikey create example.com --scope stats --rate-limit 60
+  kaunta apikey create example.com --scope stats --expires 90d
   kaunta apikey create example.com --scope ingest,stats --name "Full Access"`,
-	Args: cobra.ExactArgs(1),
+	Args: cobra.MaximumNArgs(1),
 	RunE: func(cmd *cobra.Command, args []string) error {
-		return runAPIKeyCreate(args[0])
+		website, err := apikeyWebsite(args, apikeyWebsiteFlag)
+		if err != nil {
+			return err
+		}
+		return runAPIKeyCreate(website, apikeyFormat)
 	},
 }
 
 var apikeyListCmd = &cobra.Command{
-	Use:   "list <website-domain>",
-	Short: "List API keys for a website",
-	Long: `List all API keys for a website, including revoked keys.
+	Use:   "list [<website-domain>] [--website <domain|id>] [--scope stats] [--format table|json]",
+	Short: "List API keys",
+	Long: `List the API keys of a website, or of every website without one,
+including revoked and expired keys. --scope only lists keys having all the
+given scopes.
 
 Examples:
+  kaunta apikey list
   kaunta apikey list example.com
-  kaunta apikey list example.com --format json`,
-	Args: cobra.ExactArgs(1),
+  kaunta apikey list --website example.com --scope stats --format json`,
+	Args: cobra.MaximumNArgs(1),
 	RunE: func(cmd *cobra.Command, args []string) error {
-		return runAPIKeyList(args[0])
+		website, err := apikeyWebsite(args, apikeyWebsiteFlag)
+		if err != nil {
+			return err
+		}
+		return runAPIKeyList(website, apikeyScopes, apikeyListFormat)
 	},
 }
 
 var apikeyRevokeCmd = &cobra.Command{
-	Use:   "revoke <key-id-or-prefix>",
+	Use:   "revoke <key-id-or-prefix> [--website <domain|id>] [--format text|json]",
 	Short: "Revoke an API key",
 	Long: `Revoke an API key by its ID or prefix.
 
 Revoked keys immediately stop working. This action cannot be undone.
+With --website, a key of another website is refused.
 
 Examples:
   kaunta apikey revoke kaunta_live_abc
-  kaunta apikey revoke 550e8400-e29b-41d4-a716-446655440000`,
+  kaunta apikey revoke 550e8400-e29b-41d4-a716-446655440000
+  kaunta apikey revoke "$KEY_ID" --website example.com --format json`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runAPIKeyRevoke(args[0], apikeyWebsiteFlag, apikeyFormat)
+	},
+}
+
+var apikeyRotateCmd = &cobra.Command{
+	Use:   "rotate <key-id-or-prefix>",
+	Short: "Replace an API key with a new secret",
+	Long: `Create a new API key with the same website, name, scopes, rate limit and
+lifetime, and retire the old one.
+
+The old key keeps working for the --grace period so deployments can switch
+over; with --grace 0 it stops immediately. The new key is displayed ONCE.
+
+Examples:
+  kaunta apikey rotate kaunta_live_abc
+  kaunta apikey rotate kaunta_live_abc --grace 0`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runAPIKeyRotate(args[0])
+	},
+}
+
+var apikeyUpdateCmd = &cobra.Command{
+	Use:   "update <key-id-or-prefix>",
+	Short: "Rename an API key or change its expiry",
+	Long: `Change an API key's name or expiry.
+
+--expires takes a lifetime from now (90d, 12h), an end date (2026-12-31) or
+"never".
+
+Examples:
+  kaunta apikey update kaunta_live_abc --name "Deploy bot"
+  kaunta apikey update kaunta_live_abc --expires 30d
+  kaunta apikey update kaunta_live_abc --expires never`,
 	Args: cobra.ExactArgs(1),
 	RunE: func(cmd *cobra.Command, args []string) error {
-		return runAPIKeyRevoke(args[0])
+		return runAPIKeyUpdate(cmd, args[0])
 	},
 }
 
@@ -94,10 +154,98 @@ Examples:
 var (
 	apikeyName       string
 	apikeyScopes     string
+	apikeyRateLimit  int
+	apikeyExpires    string
+	apikeyGrace      time.Duration
 	apikeyListFormat string
+	apikeyFormat     string
+
+	apikeyWebsiteFlag string
 )
 
-func runAPIKeyCreate(websiteDomain string) error {
+// apikeyWebsite returns the website given as argument or with --website
+func apikeyWebsite(args []string, flag string) (string, error) {
+	if len(args) == 0 {
+		return flag, nil
+	}
+	if flag != "" && !strings.EqualFold(args[0], flag) {
+		return "", fmt.Errorf("website given twice: %s and --website %s", args[0], flag)
+	}
+	return args[0], nil
+}
+
+// lookupAPIKeyWebsite finds a website by domain or ID
+func lookupAPIKeyWebsite(ctx context.Context, website string) (*WebsiteDetail, uuid.UUID, error) {
+	var byID *string
+	if _, err := uuid.Parse(website); err == nil {
+		byID = &website
+	}
+	detail, err := GetWebsiteByDomain(ctx, website, byID)
+	if err != nil {
+		return nil, uuid.Nil, fmt.Errorf("website not found: %w", err)
+	}
+	websiteID, err := uuid.Parse(detail.WebsiteID)
+	if err != nil {
+		return nil, uuid.Nil, fmt.Errorf("invalid website ID: %w", err)
+	}
+	return detail, websiteID, nil
+}
+
+// parseAPIKeyScopes splits a comma-separated --scope
+func parseAPIKeyScopes(value string) []string {
+	var scopes []string
+	for s := range strings.SplitSeq(value, ",") {
+		if s = strings.TrimSpace(s); s != "" {
+			scopes = append(scopes, s)
+		}
+	}
+	return scopes
+}
+
+// apiKeyStatus is active, revoked or expired
+func apiKeyStatus(key *models.APIKey, now time.Time) string {
+	switch {
+	case key.RevokedAt != nil:
+		return "revoked"
+	case key.ExpiresAt != nil && key.ExpiresAt.Before(now):
+		return "expired"
+	}
+	return "active"
+}
+
+// apiKeyJSON is an API key as printed by --format json
+type apiKeyJSON struct {
+	Key     string `json:"api_key,omitempty"` // Only on creation
+	Website string `json:"website,omitempty"`
+	Status  string `json:"status"`
+	*models.APIKey
+}
+
+func printJSON(v any) error {
+	data, err := json.MarshalIndent(v, "", "  ")
+	if err != nil {
+		return fmt.Errorf("failed to marshal JSON: %w", err)
+	}
+	fmt.Println(string(data))
+	return nil
+}
+
+func runAPIKeyCreate(website, format string) error {
+	if website == "" {
+		return fmt.Errorf("a website is required (argument or --website)")
+	}
+	if format != "text" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use text or json)", format)
+	}
+	scopes := parseAPIKeyScopes(apikeyScopes)
+	if err := models.ValidateAPIKeyScopes(scopes); err != nil {
+		return err
+	}
+	expiresAt, err := models.ParseAPIKeyExpiry(apikeyExpires, time.Now())
+	if err != nil {
+		return err
+	}
+
 	cleanup, err := ensureDatabase()
 	if err != nil {
 		return err
@@ -107,15 +255,9 @@ func runAPIKeyCreate(websiteDomain string) error {
 	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
 	defer cancel()
 
-	// Find website by domain
-	website, err := GetWebsiteByDomain(ctx, websiteDomain, nil)
-	if err != nil {
-		return fmt.Errorf("website not found: %w", err)
-	}
-
-	websiteID, err := uuid.Parse(website.WebsiteID)
+	detail, websiteID, err := lookupAPIKeyWebsite(ctx, website)
 	if err != nil {
-		return fmt.Errorf("invalid website ID: %w", err)
+		return err
 	}
 
 	// Create API key
@@ -124,14 +266,6 @@ func runAPIKeyCreate(websiteDomain string) error {
 		namePtr = &apikeyName
 	}
 
-	// Parse scopes
-	var scopes []string
-	if apikeyScopes != "" {
-		for s := range strings.SplitSeq(apikeyScopes, ",") {
-			scopes = append(scopes, strings.TrimSpace(s))
-		}
-	}
-
 	var result *models.APIKeyCreateResult
 	if len(scopes) > 0 {
 		result, err = models.GenerateAPIKeyWithScopes(websiteID, nil, namePtr, scopes)
@@ -141,6 +275,27 @@ func runAPIKeyCreate(websiteDomain string) error {
 	if err != nil {
 		return fmt.Errorf("failed to create API key: %w", err)
 	}
+	if apikeyRateLimit > 0 {
+		if err := models.SetAPIKeyRateLimit(result.APIKey.KeyID, apikeyRateLimit); err != nil {
+			return fmt.Errorf("failed to set rate limit: %w", err)
+		}
+		result.APIKey.RateLimitPerMinute = apikeyRateLimit
+	}
+	if expiresAt != nil {
+		if err := models.SetAPIKeyExpiry(result.APIKey.KeyID, expiresAt); err != nil {
+			return fmt.Errorf("failed to set expiry: %w", err)
+		}
+		result.APIKey.ExpiresAt = expiresAt
+	}
+
+	if format == "json" {
+		return printJSON(apiKeyJSON{
+			Key:     result.FullKey,
+			Website: detail.Domain,
+			Status:  "active",
+			APIKey:  result.APIKey,
+		})
+	}
 
 	fmt.Println()
 	fmt.Println("API Key created successfully!")
@@ -153,13 +308,16 @@ func runAPIKeyCreate(websiteDomain string) error {
 	fmt.Println()
 	fmt.Println("------------------------------------------------------------")
 	fmt.Printf("Key ID:     %s\n", result.APIKey.KeyID)
-	fmt.Printf("Website:    %s (%s)\n", website.Domain, website.WebsiteID)
+	fmt.Printf("Website:    %s (%s)\n", detail.Domain, detail.WebsiteID)
 	if result.APIKey.Name != nil {
 		fmt.Printf("Name:       %s\n", *result.APIKey.Name)
 	}
 	fmt.Printf("Scopes:     %s\n", strings.Join(result.APIKey.Scopes, ", "))
 	fmt.Printf("Rate Limit: %d req/min\n", result.APIKey.RateLimitPerMinute)
 	fmt.Printf("Created:    %s\n", result.APIKey.CreatedAt.Format(time.RFC3339))
+	if result.APIKey.ExpiresAt != nil {
+		fmt.Printf("Expires:    %s\n", result.APIKey.ExpiresAt.Format(time.RFC3339))
+	}
 	fmt.Println()
 	fmt.Println("Usage example:")
 	fmt.Println()
@@ -172,7 +330,15 @@ func runAPIKeyCreate(websiteDomain string) error {
 	return nil
 }
 
-func runAPIKeyList(websiteDomain string) error {
+func runAPIKeyList(website, scope, format string) error {
+	if format != "table" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use table or json)", format)
+	}
+	scopes := parseAPIKeyScopes(scope)
+	if err := models.ValidateAPIKeyScopes(scopes); err != nil {
+		return err
+	}
+
 	cleanup, err := ensureDatabase()
 	if err != nil {
 		return err
@@ -182,59 +348,95 @@ func runAPIKeyList(websiteDomain string) error {
 	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
 	defer cancel()
 
-	// Find website by domain
-	website, err := GetWebsiteByDomain(ctx, websiteDomain, nil)
-	if err != nil {
-		return fmt.Errorf("website not found: %w", err)
+	// Without a website, keys of every website are listed with its domain
+	domains := map[string]string{}
+	var keys []*models.APIKey
+	if website != "" {
+		detail, websiteID, err := lookupAPIKeyWebsite(ctx, website)
+		if err != nil {
+			return err
+		}
+		domains[detail.WebsiteID] = detail.Domain
+		keys, err = models.ListAPIKeys(websiteID)
+		if err != nil {
+			return fmt.Errorf("failed to list API keys: %w", err)
+		}
+	} else {
+		sites, err := ListWebsites(ctx)
+		if err != nil {
+			return err
+		}
+		for _, site := range sites {
+			domains[site.WebsiteID] = site.Domain
+		}
+		keys, err = models.ListAllAPIKeys()
+		if err != nil {
+			return fmt.Errorf("failed to list API keys: %w", err)
+		}
 	}
 
-	websiteID, err := uuid.Parse(website.WebsiteID)
-	if err != nil {
-		return fmt.Errorf("invalid website ID: %w", err)
+	now := time.Now()
+	listed := []apiKeyJSON{}
+	for _, key := range keys {
+		if !slices.ContainsFunc(scopes, func(s string) bool { return !key.HasScope(s) }) {
+			listed = append(listed, apiKeyJSON{
+				Website: domains[key.WebsiteID.String()],
+				Status:  apiKeyStatus(key, now),
+				APIKey:  key,
+			})
+		}
 	}
 
-	// List API keys
-	keys, err := models.ListAPIKeys(websiteID)
-	if err != nil {
-		return fmt.Errorf("failed to list API keys: %w", err)
+	if format == "json" {
+		return printJSON(listed)
 	}
 
-	if len(keys) == 0 {
-		fmt.Printf("No API keys found for website '%s'\n", websiteDomain)
+	if len(listed) == 0 {
+		if website == "" {
+			fmt.Println("No API keys found")
+			return nil
+		}
+		fmt.Printf("No API keys found for website '%s'\n", website)
 		fmt.Println()
-		fmt.Println("Create one with: kaunta apikey create", websiteDomain)
+		fmt.Println("Create one with: kaunta apikey create", website)
 		return nil
 	}
 
-	fmt.Printf("\nAPI Keys for %s (%d total)\n\n", websiteDomain, len(keys))
+	if website != "" {
+		fmt.Printf("\nAPI Keys for %s (%d total)\n\n", website, len(listed))
+	} else {
+		fmt.Printf("\nAPI Keys (%d total)\n\n", len(listed))
+	}
 
 	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
-	_, _ = fmt.Fprintln(w, "PREFIX\tNAME\tSTATUS\tLAST USED\tCREATED")
-	_, _ = fmt.Fprintln(w, "------\t----\t------\t---------\t-------")
+	_, _ = fmt.Fprintln(w, "PREFIX\tWEBSITE\tNAME\tSCOPES\tSTATUS\tLAST USED\tEXPIRES\tCREATED")
+	_, _ = fmt.Fprintln(w, "------\t-------\t----\t------\t------\t---------\t-------\t-------")
 
-	for _, key := range keys {
+	for _, item := range listed {
+		key := item.APIKey
 		name := "-"
 		if key.Name != nil {
 			name = *key.Name
 		}
 
-		status := "active"
-		if key.RevokedAt != nil {
-			status = "revoked"
-		} else if key.ExpiresAt != nil && key.ExpiresAt.Before(time.Now()) {
-			status = "expired"
-		}
-
 		lastUsed := "never"
 		if key.LastUsedAt != nil {
 			lastUsed = key.LastUsedAt.Format("2006-01-02 15:04")
 		}
 
-		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\t%s\t%s\n",
+		expires := "never"
+		if key.ExpiresAt != nil {
+			expires = key.ExpiresAt.Format("2006-01-02 15:04")
+		}
+
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\t%s\t%s\t%s\t%s\t%s\n",
 			key.KeyPrefix,
+			item.Website,
 			name,
-			status,
+			strings.Join(key.Scopes, ","),
+			item.Status,
 			lastUsed,
+			expires,
 			key.CreatedAt.Format("2006-01-02 15:04"),
 		)
 	}
@@ -245,48 +447,159 @@ func runAPIKeyList(websiteDomain string) error {
 	return nil
 }
 
-func runAPIKeyRevoke(keyIDOrPrefix string) error {
+func runAPIKeyRevoke(keyIDOrPrefix, website, format string) error {
+	if format != "text" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use text or json)", format)
+	}
+
 	cleanup, err := ensureDatabase()
 	if err != nil {
 		return err
 	}
 	defer cleanup()
 
-	// Try parsing as UUID first
-	if keyID, err := uuid.Parse(keyIDOrPrefix); err == nil {
-		if err := models.RevokeAPIKey(keyID); err != nil {
-			return fmt.Errorf("failed to revoke API key: %w", err)
+	key, err := findAPIKey(keyIDOrPrefix)
+	if err != nil {
+		return fmt.Errorf("API key not found: %w", err)
+	}
+
+	// With --website, a key of another website is refused, so a pipeline
+	// can't revoke more than it minted
+	domain := ""
+	if website != "" {
+		ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+		defer cancel()
+		detail, websiteID, err := lookupAPIKeyWebsite(ctx, website)
+		if err != nil {
+			return err
 		}
-		fmt.Printf("API key %s revoked successfully\n", keyID)
-		return nil
+		if key.WebsiteID != websiteID {
+			return fmt.Errorf("API key %s does not belong to %s", key.KeyPrefix, detail.Domain)
+		}
+		domain = detail.Domain
 	}
 
-	// Try as prefix
-	if err := models.RevokeAPIKeyByPrefix(keyIDOrPrefix); err != nil {
-		return fmt.Errorf("failed to revoke API key with prefix '%s': %w", keyIDOrPrefix, err)
+	if key.RevokedAt != nil {
+		return fmt.Errorf("API key %s is already revoked", key.KeyPrefix)
+	}
+	if err := models.RevokeAPIKey(key.KeyID); err != nil {
+		return fmt.Errorf("failed to revoke API key: %w", err)
 	}
 
-	fmt.Printf("API key with prefix '%s' revoked successfully\n", keyIDOrPrefix)
+	if format == "json" {
+		now := time.Now().UTC()
+		key.RevokedAt = &now
+		return printJSON(apiKeyJSON{Website: domain, Status: "revoked", APIKey: key})
+	}
+	fmt.Printf("API key %s revoked successfully\n", key.KeyPrefix)
 	return nil
 }
 
-func runAPIKeyShow(keyIDOrPrefix string) error {
+func runAPIKeyRotate(keyIDOrPrefix string) error {
+	if apikeyGrace < 0 {
+		return fmt.Errorf("grace must not be negative")
+	}
+
 	cleanup, err := ensureDatabase()
 	if err != nil {
 		return err
 	}
 	defer cleanup()
 
-	var key *models.APIKey
+	key, err := findAPIKey(keyIDOrPrefix)
+	if err != nil {
+		return fmt.Errorf("API key not found: %w", err)
+	}
+
+	result, err := models.RotateAPIKey(key.KeyID, apikeyGrace)
+	if err != nil {
+		return fmt.Errorf("failed to rotate API key: %w", err)
+	}
 
-	// Try parsing as UUID first
-	if keyID, parseErr := uuid.Parse(keyIDOrPrefix); parseErr == nil {
-		key, err = models.GetAPIKeyByID(keyID)
+	fmt.Println()
+	fmt.Println("API Key rotated successfully!")
+	fmt.Println()
+	fmt.Println("============================================================")
+	fmt.Println("IMPORTANT: Save this key now. It will NOT be shown again.")
+	fmt.Println("============================================================")
+	fmt.Println()
+	fmt.Printf("API Key: %s\n", result.FullKey)
+	fmt.Println()
+	fmt.Printf("New Key ID: %s\n", result.APIKey.KeyID)
+	if result.APIKey.ExpiresAt != nil {
+		fmt.Printf("Expires:    %s\n", result.APIKey.ExpiresAt.Format(time.RFC3339))
+	}
+	if apikeyGrace > 0 {
+		fmt.Printf("Old key %s keeps working until %s\n", key.KeyPrefix, time.Now().Add(apikeyGrace).Format(time.RFC3339))
 	} else {
-		// Try as prefix - need to query by prefix
-		key, err = getAPIKeyByPrefix(keyIDOrPrefix)
+		fmt.Printf("Old key %s revoked\n", key.KeyPrefix)
 	}
+	fmt.Println()
 
+	return nil
+}
+
+func runAPIKeyUpdate(cmd *cobra.Command, keyIDOrPrefix string) error {
+	nameChanged := cmd.Flags().Changed("name")
+	expiresChanged := cmd.Flags().Changed("expires")
+	if !nameChanged && !expiresChanged {
+		return fmt.Errorf("nothing to update (use --name or --expires)")
+	}
+
+	var expiresAt *time.Time
+	if expiresChanged {
+		var err error
+		if expiresAt, err = models.ParseAPIKeyExpiry(apikeyExpires, time.Now()); err != nil {
+			return err
+		}
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	key, err := findAPIKey(keyIDOrPrefix)
+	if err != nil {
+		return fmt.Errorf("API key not found: %w", err)
+	}
+
+	if nameChanged {
+		var name *string
+		if apikeyName != "" {
+			name = &apikeyName
+		}
+		if err := models.SetAPIKeyName(key.KeyID, name); err != nil {
+			return fmt.Errorf("failed to rename API key: %w", err)
+		}
+	}
+	if expiresChanged {
+		if err := models.SetAPIKeyExpiry(key.KeyID, expiresAt); err != nil {
+			return fmt.Errorf("failed to set expiry: %w", err)
+		}
+	}
+
+	fmt.Printf("API key %s updated successfully\n", key.KeyPrefix)
+	return nil
+}
+
+// findAPIKey looks a key up by ID or display prefix
+func findAPIKey(keyIDOrPrefix string) (*models.APIKey, error) {
+	if keyID, err := uuid.Parse(keyIDOrPrefix); err == nil {
+		return models.GetAPIKeyByID(keyID)
+	}
+	return getAPIKeyByPrefix(keyIDOrPrefix)
+}
+
+func runAPIKeyShow(keyIDOrPrefix string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	key, err := findAPIKey(keyIDOrPrefix)
 	if err != nil {
 		return fmt.Errorf("API key not found: %w", err)
 	}
@@ -363,7 +676,7 @@ func getAPIKeyByPrefix(prefix string) (*models.APIKey, error) {
 		&createdByNull,
 		&key.KeyPrefix,
 		&nameNull,
-		&scopes,
+		pq.Array(&scopes),
 		&key.RateLimitPerMinute,
 		&key.CreatedAt,
 		&lastUsedAt,
@@ -391,14 +704,33 @@ func init() {
 	// Create command flags
 	apikeyCreateCmd.Flags().StringVarP(&apikeyName, "name", "n", "", "Friendly name for the API key (e.g., 'Rails Backend')")
 	apikeyCreateCmd.Flags().StringVarP(&apikeyScopes, "scope", "s", "", "Comma-separated scopes (ingest, stats)")
+	apikeyCreateCmd.Flags().IntVar(&apikeyRateLimit, "rate-limit", 0, "Stats API requests per minute for this key (default 1000)")
+	apikeyCreateCmd.Flags().StringVar(&apikeyExpires, "expires", "", "Expiry: lifetime (90d, 12h), date (2026-12-31) or never (default never)")
+	apikeyCreateCmd.Flags().StringVarP(&apikeyWebsiteFlag, "website", "w", "", "Domain or ID of the website")
+	apikeyCreateCmd.Flags().StringVarP(&apikeyFormat, "format", "f", "text", "Output format (text, json)")
+
+	// Rotate command flags
+	apikeyRotateCmd.Flags().DurationVar(&apikeyGrace, "grace", 24*time.Hour, "How long the old key keeps working (0 revokes it now)")
+
+	// Update command flags
+	apikeyUpdateCmd.Flags().StringVarP(&apikeyName, "name", "n", "", "New name (empty clears it)")
+	apikeyUpdateCmd.Flags().StringVar(&apikeyExpires, "expires", "", "Expiry: lifetime (90d, 12h), date (2026-12-31) or never")
 
 	// List command flags
 	apikeyListCmd.Flags().StringVarP(&apikeyListFormat, "format", "f", "table", "Output format (table, json)")
+	apikeyListCmd.Flags().StringVarP(&apikeyWebsiteFlag, "website", "w", "", "Domain or ID of the website (default: every website)")
+	apikeyListCmd.Flags().StringVarP(&apikeyScopes, "scope", "s", "", "Only keys with these comma-separated scopes")
+
+	// Revoke command flags
+	apikeyRevokeCmd.Flags().StringVarP(&apikeyWebsiteFlag, "website", "w", "", "Refuse keys of other websites")
+	apikeyRevokeCmd.Flags().StringVarP(&apikeyFormat, "format", "f", "text", "Output format (text, json)")
 
 	// Add subcommands
 	apikeyCmd.AddCommand(apikeyCreateCmd)
 	apikeyCmd.AddCommand(apikeyListCmd)
 	apikeyCmd.AddCommand(apikeyRevokeCmd)
+	apikeyCmd.AddCommand(apikeyRotateCmd)
+	apikeyCmd.AddCommand(apikeyUpdateCmd)
 	apikeyCmd.AddCommand(apikeyShowCmd)
 
 	// Register with root command
@@ -0,0 +1,126 @@
+package cli
+
+import (
+	"context"
+	"encoding/json"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+var apiKeyColumns = []string{"key_id", "website_id", "created_by", "key_prefix", "name", "scopes",
+	"rate_limit_per_minute", "created_at", "last_used_at", "revoked_at", "expires_at"}
+
+func stubAPIKeyDB(t *testing.T) sqlmock.Sqlmock {
+	t.Helper()
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	originalDB := database.DB
+	database.DB = db
+	t.Cleanup(func() {
+		database.DB = originalDB
+		_ = db.Close()
+	})
+	return mock
+}
+
+func stubAPIKeyWebsite(t *testing.T, websiteID uuid.UUID, domain string) {
+	t.Helper()
+	original := GetWebsiteByDomain
+	GetWebsiteByDomain = func(ctx context.Context, _ string, _ *string) (*WebsiteDetail, error) {
+		return &WebsiteDetail{WebsiteID: websiteID.String(), Domain: domain}, nil
+	}
+	t.Cleanup(func() { GetWebsiteByDomain = original })
+}
+
+func TestAPIKeyWebsite(t *testing.T) {
+	website, err := apikeyWebsite([]string{"example.com"}, "")
+	require.NoError(t, err)
+	assert.Equal(t, "example.com", website)
+
+	website, err = apikeyWebsite(nil, "example.com")
+	require.NoError(t, err)
+	assert.Equal(t, "example.com", website)
+
+	_, err = apikeyWebsite([]string{"example.com"}, "other.com")
+	assert.Error(t, err)
+
+	assert.Equal(t, []string{"ingest", "stats"}, parseAPIKeyScopes(" ingest, stats,"))
+}
+
+func TestRunAPIKeyListFiltersByScope(t *testing.T) {
+	mock := stubAPIKeyDB(t)
+	websiteID := uuid.New()
+	stubAPIKeyWebsite(t, websiteID, "example.com")
+
+	created := time.Now().Add(-time.Hour)
+	mock.ExpectQuery("FROM api_keys").
+		WithArgs(websiteID).
+		WillReturnRows(sqlmock.NewRows(apiKeyColumns).
+			AddRow(uuid.NewString(), websiteID.String(), nil, "kaunta_live_aaaa", "CI", "{stats}", 60, created, nil, nil, created).
+			AddRow(uuid.NewString(), websiteID.String(), nil, "kaunta_live_bbbb", nil, "{ingest}", 1000, created, nil, nil, nil))
+
+	output, err := captureOutput(t, func() error {
+		return runAPIKeyList("example.com", "stats", "json")
+	})
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+
+	var keys []map[string]any
+	require.NoError(t, json.Unmarshal([]byte(output), &keys))
+	require.Len(t, keys, 1)
+	assert.Equal(t, "kaunta_live_aaaa", keys[0]["key_prefix"])
+	assert.Equal(t, "example.com", keys[0]["website"])
+	assert.Equal(t, "expired", keys[0]["status"])
+	assert.NotContains(t, keys[0], "api_key")
+}
+
+func TestRunAPIKeyListRejectsUnknownScope(t *testing.T) {
+	err := runAPIKeyList("", "admin", "table")
+	assert.ErrorContains(t, err, "invalid scope")
+}
+
+func TestRunAPIKeyRevokeRefusesOtherWebsite(t *testing.T) {
+	mock := stubAPIKeyDB(t)
+	stubAPIKeyWebsite(t, uuid.New(), "example.com")
+
+	mock.ExpectQuery("FROM api_keys").
+		WithArgs("kaunta_live_aaaa").
+		WillReturnRows(sqlmock.NewRows(apiKeyColumns).
+			AddRow(uuid.NewString(), uuid.NewString(), nil, "kaunta_live_aaaa", nil, "{stats}", 60, time.Now(), nil, nil, nil))
+
+	err := runAPIKeyRevoke("kaunta_live_aaaa", "example.com", "json")
+	assert.ErrorContains(t, err, "does not belong to example.com")
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRunAPIKeyRevokeJSON(t *testing.T) {
+	mock := stubAPIKeyDB(t)
+	keyID, websiteID := uuid.New(), uuid.New()
+
+	mock.ExpectQuery("FROM api_keys").
+		WithArgs("kaunta_live_aaaa").
+		WillReturnRows(sqlmock.NewRows(apiKeyColumns).
+			AddRow(keyID.String(), websiteID.String(), nil, "kaunta_live_aaaa", nil, "{stats}", 60, time.Now(), nil, nil, nil))
+	mock.ExpectExec("UPDATE api_keys SET revoked_at").
+		WithArgs(keyID).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	output, err := captureOutput(t, func() error {
+		return runAPIKeyRevoke("kaunta_live_aaaa", "", "json")
+	})
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+
+	var key map[string]any
+	require.NoError(t, json.Unmarshal([]byte(output), &key))
+	assert.Equal(t, keyID.String(), key["key_id"])
+	assert.Equal(t, "revoked", key["status"])
+	assert.NotEmpty(t, key["revoked_at"])
+}
@@ -0,0 +1,313 @@
+package cli
+
+import (
+	"context"
+	"encoding/csv"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"text/tabwriter"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/archive"
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/s3"
+)
+
+var archiveCmd = &cobra.Command{
+	Use:   "archive",
+	Short: "Archive raw events to S3-compatible storage",
+	Long: `Export closed months of raw events as Parquet files to S3-compatible
+storage (AWS S3, MinIO, R2, ...) and read them back.
+
+The server archives every closed month once a day when [archive] is
+configured, and only drops expired event partitions once their month is
+archived. These commands run the same archiving by hand and read archives
+back.`,
+}
+
+var archiveRunCmd = &cobra.Command{
+	Use:   "run",
+	Short: "Archive every closed month that isn't archived yet",
+	Args:  cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runArchive()
+	},
+}
+
+var archiveListCmd = &cobra.Command{
+	Use:   "list [--format table|json]",
+	Short: "List archived months",
+	Args:  cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runArchiveList(archiveFormat)
+	},
+}
+
+var archiveExportCmd = &cobra.Command{
+	Use:   "export <YYYY-MM> [--website <domain>]",
+	Short: "Write an archived month's events as CSV to stdout",
+	Long: `Download an archived month and write its events as CSV to stdout, with
+the columns of the Parquet file. The archive files themselves can also be
+read directly with DuckDB, pandas, Spark and other Parquet tools.
+
+Examples:
+  kaunta archive export 2025-01 > january.csv
+  kaunta archive export 2025-01 --website example.com`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runArchiveExport(args[0], archiveWebsite)
+	},
+}
+
+var archiveRestoreCmd = &cobra.Command{
+	Use:   "restore <YYYY-MM> [--website <domain>]",
+	Short: "Load an archived month back into the database",
+	Long: `Insert an archived month's events back into the database, recreating
+their partitions and any session deleted since. Events still in the
+database are left alone, so restoring twice is harmless.
+
+Restored days older than retention_days are dropped again by the next
+partition maintenance; raise retention_days first to keep them.
+
+Examples:
+  kaunta archive restore 2025-01
+  kaunta archive restore 2025-01 --website example.com`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runArchiveRestore(args[0], archiveWebsite)
+	},
+}
+
+// Command flags
+var (
+	archiveFormat  string
+	archiveWebsite string
+)
+
+// newArchiver returns the configured archiver, or nil when archiving is off
+func newArchiver(cfg *config.Config) (*archive.Archiver, error) {
+	if cfg == nil || !cfg.Archive.Enabled() {
+		return nil, nil
+	}
+	store, err := s3.New(s3.Config{
+		Endpoint:  cfg.Archive.Endpoint,
+		Region:    cfg.Archive.Region,
+		Bucket:    cfg.Archive.Bucket,
+		AccessKey: cfg.Archive.AccessKey,
+		SecretKey: cfg.Archive.SecretKey,
+	})
+	if err != nil {
+		return nil, err
+	}
+	return archive.New(database.DB, store, cfg.Archive.Prefix), nil
+}
+
+// openArchiver connects to the database and builds the archiver for a
+// command, failing when archiving isn't configured
+func openArchiver() (*archive.Archiver, func(), error) {
+	cfg, err := config.Load()
+	if err != nil {
+		return nil, nil, err
+	}
+	if !cfg.Archive.Enabled() {
+		return nil, nil, errors.New("archiving is not configured (set archive.bucket or ARCHIVE_S3_BUCKET)")
+	}
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return nil, nil, err
+	}
+	archiver, err := newArchiver(cfg)
+	if err != nil {
+		cleanup()
+		return nil, nil, err
+	}
+	return archiver, cleanup, nil
+}
+
+// archiveWebsiteID resolves --website to a website ID
+func archiveWebsiteID(ctx context.Context, domain string) (*uuid.UUID, error) {
+	if domain == "" {
+		return nil, nil
+	}
+	website, err := fetchWebsiteByDomain(ctx, domain, nil)
+	if err != nil {
+		return nil, err
+	}
+	id, err := uuid.Parse(website.WebsiteID)
+	if err != nil {
+		return nil, err
+	}
+	return &id, nil
+}
+
+// formatBytes renders a size with a binary unit, e.g. 1.5 MiB
+func formatBytes(n int64) string {
+	const unit = 1024
+	if n < unit {
+		return fmt.Sprintf("%d B", n)
+	}
+	div, exp := int64(unit), 0
+	for m := n / unit; m >= unit; m /= unit {
+		div *= unit
+		exp++
+	}
+	return fmt.Sprintf("%.1f %ciB", float64(n)/float64(div), "KMGTPE"[exp])
+}
+
+func runArchive() error {
+	archiver, cleanup, err := openArchiver()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	// Exporting a busy month can take a while
+	ctx, cancel := context.WithTimeout(context.Background(), 2*time.Hour)
+	defer cancel()
+
+	archived, err := archiver.Run(ctx)
+	for _, arc := range archived {
+		fmt.Printf("Archived %s: %d events, %s\n", arc.Month.Format(archive.MonthLayout), arc.Rows, formatBytes(arc.Bytes))
+	}
+	if err != nil {
+		return err
+	}
+	if len(archived) == 0 {
+		fmt.Println("Every closed month is already archived")
+	}
+	return nil
+}
+
+func runArchiveList(format string) error {
+	if format != "table" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use table or json)", format)
+	}
+
+	archiver, cleanup, err := openArchiver()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	archives, err := archiver.List(ctx)
+	if err != nil {
+		return fmt.Errorf("failed to list archives: %w", err)
+	}
+
+	if format == "json" {
+		data, err := json.MarshalIndent(archives, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	}
+
+	if len(archives) == 0 {
+		fmt.Println("No month has been archived")
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	defer func() { _ = w.Flush() }()
+
+	_, _ = fmt.Fprintln(w, "MONTH\tEVENTS\tSIZE\tKEY\tARCHIVED")
+	for _, arc := range archives {
+		_, _ = fmt.Fprintf(w, "%s\t%d\t%s\t%s\t%s\n", arc.Month.Format(archive.MonthLayout), arc.Rows,
+			formatBytes(arc.Bytes), arc.Key, arc.ArchivedAt.Format("2006-01-02 15:04"))
+	}
+	return nil
+}
+
+func runArchiveExport(monthArg, domain string) error {
+	month, err := archive.ParseMonth(monthArg)
+	if err != nil {
+		return err
+	}
+	archiver, cleanup, err := openArchiver()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 2*time.Hour)
+	defer cancel()
+
+	websiteID, err := archiveWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+
+	header := make([]string, len(archive.Columns))
+	for i, c := range archive.Columns {
+		header[i] = c.Name
+	}
+	cw := csv.NewWriter(os.Stdout)
+	if err := cw.Write(header); err != nil {
+		return err
+	}
+	record := make([]string, len(header))
+	err = archiver.Each(ctx, month, websiteID, func(row []any) error {
+		for i, v := range row {
+			switch v := v.(type) {
+			case nil:
+				record[i] = ""
+			case time.Time:
+				record[i] = v.Format(time.RFC3339Nano)
+			default:
+				record[i] = fmt.Sprint(v)
+			}
+		}
+		return cw.Write(record)
+	})
+	cw.Flush()
+	if err != nil {
+		return err
+	}
+	return cw.Error()
+}
+
+func runArchiveRestore(monthArg, domain string) error {
+	month, err := archive.ParseMonth(monthArg)
+	if err != nil {
+		return err
+	}
+	archiver, cleanup, err := openArchiver()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 2*time.Hour)
+	defer cancel()
+
+	websiteID, err := archiveWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+
+	restored, err := archiver.Restore(ctx, month, websiteID)
+	fmt.Printf("Restored %d events from %s\n", restored, month.Format(archive.MonthLayout))
+	return err
+}
+
+func init() {
+	RootCmd.AddCommand(archiveCmd)
+	archiveCmd.AddCommand(archiveRunCmd)
+	archiveCmd.AddCommand(archiveListCmd)
+	archiveCmd.AddCommand(archiveExportCmd)
+	archiveCmd.AddCommand(archiveRestoreCmd)
+
+	archiveListCmd.Flags().StringVarP(&archiveFormat, "format", "f", "table", "Output format (table, json)")
+	archiveExportCmd.Flags().StringVar(&archiveWebsite, "website", "", "Only events of this website (domain)")
+	archiveRestoreCmd.Flags().StringVar(&archiveWebsite, "website", "", "Only events of this website (domain)")
+}
@@ -0,0 +1,31 @@
+package cli
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/config"
+)
+
+func TestFormatBytes(t *testing.T) {
+	assert.Equal(t, "512 B", formatBytes(512))
+	assert.Equal(t, "1.5 KiB", formatBytes(1536))
+	assert.Equal(t, "2.0 GiB", formatBytes(2<<30))
+}
+
+func TestNewArchiver(t *testing.T) {
+	archiver, err := newArchiver(&config.Config{})
+	require.NoError(t, err)
+	assert.Nil(t, archiver, "off without a bucket")
+
+	_, err = newArchiver(&config.Config{Archive: config.ArchiveConfig{Bucket: "events", Endpoint: "minio:9000"}})
+	assert.ErrorContains(t, err, "invalid endpoint")
+
+	archiver, err = newArchiver(&config.Config{Archive: config.ArchiveConfig{
+		Endpoint: "http://minio:9000", Bucket: "events", AccessKey: "kaunta", SecretKey: "secret",
+	}})
+	require.NoError(t, err)
+	assert.NotNil(t, archiver)
+}
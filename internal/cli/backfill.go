@@ -0,0 +1,135 @@
+package cli
+
+import (
+	"errors"
+	"fmt"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/importer"
+)
+
+var backfillRollupsCmd = &cobra.Command{
+	Use:   "backfill-rollups [--website <domain|id>] [--from <YYYY-MM-DD>] [--to <YYYY-MM-DD>]",
+	Short: "Recount rollups and visitor sketches from raw events",
+	Long: `Recount the hourly and daily rollups and the visitor sketches of past days
+from their raw events. The server only rolls up days as they complete, so
+run this after importing history into days it has already rolled up, or
+after an upgrade that changes how rollups are counted; until then long
+dashboard ranges miss or miscount those days.
+
+Days are recounted a chunk at a time, each in its own short transaction,
+and the server's rollup refresh runs in between, so the command is safe to
+run against a live database; --pause slows it down further. Interrupting it
+loses nothing: run it again with a later --from to continue.
+
+--from defaults to the first raw event and --to to today. Days whose raw
+events were dropped by retention keep their rollups, and hourly rollups are
+only recounted for the last week, the time they are kept.
+
+Examples:
+  kaunta backfill-rollups --website example.com
+  kaunta backfill-rollups --from 2025-01-01 --to 2025-03-31 --chunk-days 7 --pause 1s`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runBackfillRollups(backfillWebsite, backfillFrom, backfillTo, backfillChunkDays, backfillPause)
+	},
+}
+
+// Command flags
+var (
+	backfillWebsite   string
+	backfillFrom      string
+	backfillTo        string
+	backfillChunkDays int
+	backfillPause     time.Duration
+)
+
+var backfillRollupsFunc = database.BackfillRollups
+
+func runBackfillRollups(website, fromArg, toArg string, chunkDays int, pause time.Duration) error {
+	if chunkDays < 1 || chunkDays > 366 {
+		return errors.New("--chunk-days must be between 1 and 366")
+	}
+	if pause < 0 {
+		return errors.New("--pause must not be negative")
+	}
+	from, until, err := parseExportRange(fromArg, toArg, time.Now().UTC())
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, stop := importContext()
+	defer stop()
+
+	label := "all websites"
+	var websiteID *uuid.UUID
+	if website != "" {
+		var byID *string
+		if _, err := uuid.Parse(website); err == nil {
+			byID = &website
+		}
+		detail, err := fetchWebsiteByDomain(ctx, website, byID)
+		if err != nil {
+			return err
+		}
+		id, err := uuid.Parse(detail.WebsiteID)
+		if err != nil {
+			return err
+		}
+		label, websiteID = detail.Domain, &id
+	}
+
+	progress := printImportProgress("chunks")
+	start := time.Now()
+	result, err := backfillRollupsFunc(ctx, database.DB, database.BackfillOptions{
+		From:      from,
+		Until:     until,
+		WebsiteID: websiteID,
+		ChunkDays: chunkDays,
+		Pause:     pause,
+		Progress: func(p database.BackfillProgress) {
+			// p.Through is the end of the chunk, the day after the last one recounted
+			through := p.Through.Add(-time.Nanosecond)
+			progress(importer.Progress{Website: label, Total: int64(p.Total), Done: int64(p.Done), Through: through})
+		},
+	})
+	if err != nil {
+		if ctx.Err() != nil {
+			if result.Chunks > 0 {
+				return fmt.Errorf("backfill interrupted; continue with --from %s",
+					result.From.AddDate(0, 0, result.Chunks*chunkDays).Format("2006-01-02"))
+			}
+			return errors.New("backfill interrupted")
+		}
+		return err
+	}
+
+	if result.Chunks == 0 {
+		fmt.Printf("Nothing to recount for %s: no raw events in that range have been rolled up yet\n", label)
+		return nil
+	}
+	fmt.Printf("✓ Recounted %s from %s to %s: %d rollup rows in %s\n", label,
+		result.From.Format("2006-01-02"), result.Until.Format("2006-01-02 15:04"), result.Rows,
+		time.Since(start).Round(time.Second))
+	return nil
+}
+
+func init() {
+	RootCmd.AddCommand(backfillRollupsCmd)
+
+	backfillRollupsCmd.Flags().StringVar(&backfillWebsite, "website", "", "Domain or ID of the website to recount (default all)")
+	backfillRollupsCmd.Flags().StringVar(&backfillFrom, "from", "", "First day to recount (YYYY-MM-DD, UTC)")
+	backfillRollupsCmd.Flags().StringVar(&backfillTo, "to", "", "Last day to recount (YYYY-MM-DD, UTC)")
+	backfillRollupsCmd.Flags().IntVar(&backfillChunkDays, "chunk-days", 1, "Days recounted per transaction")
+	backfillRollupsCmd.Flags().DurationVar(&backfillPause, "pause", 0, "Time to wait between chunks")
+}
@@ -0,0 +1,95 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"os"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func stubBackfill(t *testing.T, fn func(opts database.BackfillOptions) (database.BackfillResult, error)) {
+	t.Helper()
+	original := backfillRollupsFunc
+	t.Cleanup(func() { backfillRollupsFunc = original })
+	backfillRollupsFunc = func(ctx context.Context, db *sql.DB, opts database.BackfillOptions) (database.BackfillResult, error) {
+		return fn(opts)
+	}
+}
+
+func TestRunBackfillRollups(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+
+	websiteID := uuid.New()
+	originalFetch := fetchWebsiteByDomain
+	t.Cleanup(func() { fetchWebsiteByDomain = originalFetch })
+	fetchWebsiteByDomain = func(ctx context.Context, domain string, id *string) (*WebsiteDetail, error) {
+		return &WebsiteDetail{WebsiteID: websiteID.String(), Domain: "example.com"}, nil
+	}
+
+	from := time.Date(2025, 1, 1, 0, 0, 0, 0, time.UTC)
+	stubBackfill(t, func(opts database.BackfillOptions) (database.BackfillResult, error) {
+		assert.Equal(t, from, opts.From)
+		assert.Equal(t, from.AddDate(0, 0, 31), opts.Until)
+		require.NotNil(t, opts.WebsiteID)
+		assert.Equal(t, websiteID, *opts.WebsiteID)
+		assert.Equal(t, 7, opts.ChunkDays)
+		return database.BackfillResult{From: from, Until: opts.Until, Chunks: 5, Rows: 1234}, nil
+	})
+
+	output, err := captureOutput(t, func() error {
+		return runBackfillRollups("example.com", "2025-01-01", "2025-01-31", 7, 0)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "✓ Recounted example.com from 2025-01-01 to 2025-02-01 00:00: 1234 rollup rows")
+}
+
+func TestRunBackfillRollupsNothingToDo(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	stubBackfill(t, func(opts database.BackfillOptions) (database.BackfillResult, error) {
+		assert.Nil(t, opts.WebsiteID)
+		return database.BackfillResult{}, nil
+	})
+
+	output, err := captureOutput(t, func() error {
+		return runBackfillRollups("", "", "", 1, 0)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "Nothing to recount for all websites")
+}
+
+func TestRunBackfillRollupsReportsWhereToContinue(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+
+	ctx, cancel := context.WithCancel(context.Background())
+	cancel()
+	original := signalContextFactory
+	t.Cleanup(func() { signalContextFactory = original })
+	signalContextFactory = func(context.Context, ...os.Signal) (context.Context, context.CancelFunc) {
+		return ctx, func() {}
+	}
+
+	from := time.Date(2025, 1, 1, 0, 0, 0, 0, time.UTC)
+	stubBackfill(t, func(opts database.BackfillOptions) (database.BackfillResult, error) {
+		return database.BackfillResult{From: from, Chunks: 3}, errors.New("canceled")
+	})
+
+	err := runBackfillRollups("", "", "", 2, 0)
+	assert.ErrorContains(t, err, "continue with --from 2025-01-07")
+}
+
+func TestRunBackfillRollupsValidatesFlags(t *testing.T) {
+	assert.ErrorContains(t, runBackfillRollups("", "", "", 0, 0), "--chunk-days")
+	assert.ErrorContains(t, runBackfillRollups("", "", "", 1, -time.Second), "--pause")
+	assert.ErrorContains(t, runBackfillRollups("", "2025-02-01", "2025-01-01", 1, 0), "--from")
+}
@@ -0,0 +1,163 @@
+package cli
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"strings"
+
+	"github.com/blang/semver"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/selfupdate"
+)
+
+var checkUpdateCmd = &cobra.Command{
+	Use:   "check-update",
+	Short: "Check for a newer Kaunta release",
+	Long: `Compare this binary's version with the latest release on GitHub and list
+the database migrations the new version will run when the server starts.
+Migrations only go forward: back up the database before upgrading when any
+are pending, since the old binary can't use a migrated database.
+
+The database is read from the usual configuration; without one, the
+migration check is skipped.
+
+Examples:
+  kaunta check-update
+  kaunta check-update --format json`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runCheckUpdate(checkUpdateFormat)
+	},
+}
+
+var checkUpdateFormat string
+
+// updateStatus is what check-update reports
+type updateStatus struct {
+	Current           string                 `json:"current"`
+	Latest            string                 `json:"latest"`
+	UpdateAvailable   bool                   `json:"update_available"`
+	ReleaseURL        string                 `json:"release_url,omitempty"`
+	DatabaseVersion   *uint                  `json:"database_version,omitempty"`
+	DatabaseDirty     bool                   `json:"database_dirty,omitempty"`
+	DatabaseError     string                 `json:"database_error,omitempty"`
+	PendingMigrations []selfupdate.Migration `json:"pending_migrations"`
+
+	release *selfupdate.Release
+}
+
+var (
+	detectLatestFunc = func() (*selfupdate.Release, error) {
+		return selfupdate.NewClient("seuros", "kaunta").DetectLatest()
+	}
+	releaseMigrationsFunc = func(tag string) ([]selfupdate.Migration, error) {
+		return selfupdate.NewClient("seuros", "kaunta").Migrations(tag)
+	}
+	migrationVersionFunc = database.GetMigrationVersion
+)
+
+// checkForUpdate compares the running version with the latest release and,
+// when a database is configured, lists the release's migrations it hasn't run
+func checkForUpdate() (*updateStatus, error) {
+	versionStr := strings.TrimSpace(strings.TrimPrefix(Version, "v"))
+	if versionStr == "" {
+		return nil, errors.New("update checks are only available for release builds")
+	}
+	current, err := semver.Parse(versionStr)
+	if err != nil {
+		return nil, fmt.Errorf("invalid current version %q: %w", Version, err)
+	}
+
+	latest, err := detectLatestFunc()
+	if err != nil {
+		return nil, fmt.Errorf("failed to check for updates: %w", err)
+	}
+	status := &updateStatus{
+		Current:           current.String(),
+		Latest:            latest.Version.String(),
+		UpdateAvailable:   latest.Version.GT(current),
+		ReleaseURL:        latest.HTMLURL,
+		PendingMigrations: []selfupdate.Migration{},
+		release:           latest,
+	}
+
+	databaseURL := os.Getenv("DATABASE_URL")
+	if databaseURL == "" {
+		return status, nil
+	}
+	version, dirty, err := migrationVersionFunc(databaseURL)
+	if err != nil {
+		status.DatabaseError = err.Error()
+		return status, nil
+	}
+	status.DatabaseVersion, status.DatabaseDirty = &version, dirty
+
+	if !status.UpdateAvailable {
+		return status, nil
+	}
+	migrations, err := releaseMigrationsFunc(latest.TagName)
+	if err != nil {
+		return nil, err
+	}
+	for _, m := range migrations {
+		if m.Version > version {
+			status.PendingMigrations = append(status.PendingMigrations, m)
+		}
+	}
+	return status, nil
+}
+
+func runCheckUpdate(format string) error {
+	if format != "text" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use text or json)", format)
+	}
+	status, err := checkForUpdate()
+	if err != nil {
+		return err
+	}
+	if format == "json" {
+		return printJSON(status)
+	}
+
+	fmt.Printf("Current version: v%s\n", status.Current)
+	fmt.Printf("Latest version:  v%s\n", status.Latest)
+	if !status.UpdateAvailable {
+		fmt.Println("Kaunta is already up to date")
+		return nil
+	}
+	if status.ReleaseURL != "" {
+		fmt.Printf("Release notes:   %s\n", status.ReleaseURL)
+	}
+	printPendingMigrations(status)
+	fmt.Printf("Upgrade with: %s\n", upgradeHint)
+	return nil
+}
+
+// printPendingMigrations describes what upgrading does to the database
+func printPendingMigrations(status *updateStatus) {
+	switch {
+	case status.DatabaseError != "":
+		fmt.Printf("! Database: pending migrations not checked: %s\n", status.DatabaseError)
+	case status.DatabaseVersion == nil:
+		fmt.Println("Database: not configured, pending migrations not checked")
+	case status.DatabaseDirty:
+		fmt.Printf("! Database migration %d is dirty; fix it before upgrading\n", *status.DatabaseVersion)
+	case len(status.PendingMigrations) == 0:
+		fmt.Println("Database: no pending migrations")
+	default:
+		fmt.Printf("Database: %d migrations will run when v%s starts (back up first):\n",
+			len(status.PendingMigrations), status.Latest)
+		for _, m := range status.PendingMigrations {
+			fmt.Printf("  %06d %s\n", m.Version, m.Name)
+		}
+	}
+}
+
+func init() {
+	RootCmd.AddCommand(checkUpdateCmd)
+
+	checkUpdateCmd.Flags().StringVarP(&checkUpdateFormat, "format", "f", "text", "Output format (text, json)")
+}
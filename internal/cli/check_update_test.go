@@ -0,0 +1,83 @@
+package cli
+
+import (
+	"encoding/json"
+	"errors"
+	"testing"
+
+	"github.com/blang/semver"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/selfupdate"
+)
+
+func stubUpdateCheck(t *testing.T, current, latest string, dbVersion uint, dbErr error) {
+	t.Helper()
+	originalVersion := Version
+	originalDetect, originalMigrations, originalDB := detectLatestFunc, releaseMigrationsFunc, migrationVersionFunc
+	t.Cleanup(func() {
+		Version = originalVersion
+		detectLatestFunc, releaseMigrationsFunc, migrationVersionFunc = originalDetect, originalMigrations, originalDB
+	})
+
+	Version = current
+	t.Setenv("DATABASE_URL", "postgres://localhost/kaunta")
+	detectLatestFunc = func() (*selfupdate.Release, error) {
+		return &selfupdate.Release{TagName: "v" + latest, Version: semver.MustParse(latest),
+			HTMLURL: "https://github.com/seuros/kaunta/releases/tag/v" + latest}, nil
+	}
+	releaseMigrationsFunc = func(tag string) ([]selfupdate.Migration, error) {
+		assert.Equal(t, "v"+latest, tag)
+		return []selfupdate.Migration{{Version: 56, Name: "import_progress"}, {Version: 57, Name: "rebuild_rollups"},
+			{Version: 58, Name: "visit_goals"}}, nil
+	}
+	migrationVersionFunc = func(string) (uint, bool, error) {
+		return dbVersion, false, dbErr
+	}
+}
+
+func TestCheckUpdateListsPendingMigrations(t *testing.T) {
+	stubUpdateCheck(t, "1.1.0", "1.2.0", 56, nil)
+
+	output, err := captureOutput(t, func() error {
+		return runCheckUpdate("text")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "Latest version:  v1.2.0")
+	assert.Contains(t, output, "2 migrations will run when v1.2.0 starts")
+	assert.Contains(t, output, "000057 rebuild_rollups")
+	assert.Contains(t, output, "000058 visit_goals")
+	assert.NotContains(t, output, "import_progress")
+}
+
+func TestCheckUpdateUpToDateJSON(t *testing.T) {
+	stubUpdateCheck(t, "v1.2.0", "1.2.0", 58, nil)
+
+	output, err := captureOutput(t, func() error {
+		return runCheckUpdate("json")
+	})
+	require.NoError(t, err)
+	var status map[string]any
+	require.NoError(t, json.Unmarshal([]byte(output), &status))
+	assert.Equal(t, false, status["update_available"])
+	assert.Equal(t, float64(58), status["database_version"])
+	assert.Empty(t, status["pending_migrations"])
+}
+
+func TestCheckUpdateWithUnreachableDatabase(t *testing.T) {
+	stubUpdateCheck(t, "1.1.0", "1.2.0", 0, errors.New("connection refused"))
+
+	output, err := captureOutput(t, func() error {
+		return runCheckUpdate("text")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "pending migrations not checked: connection refused")
+}
+
+func TestCheckUpdateNeedsReleaseBuild(t *testing.T) {
+	stubUpdateCheck(t, "", "1.2.0", 0, nil)
+
+	_, err := checkForUpdate()
+	assert.ErrorContains(t, err, "only available for release builds")
+}
@@ -0,0 +1,60 @@
+package cli
+
+import (
+	"fmt"
+
+	"github.com/spf13/cobra"
+)
+
+var completionsCmd = &cobra.Command{
+	Use:     "completions <bash|zsh|fish>",
+	Aliases: []string{"completion"},
+	Short:   "Generate shell completions",
+	Long: `Print a completion script for bash, zsh or fish. It completes commands,
+flags and the values of --format and --output.
+
+Bash (needs the bash-completion package):
+  kaunta completions bash > /etc/bash_completion.d/kaunta
+
+Zsh (any directory in $fpath, then start a new shell):
+  kaunta completions zsh > "${fpath[1]}/_kaunta"
+
+Fish:
+  kaunta completions fish > ~/.config/fish/completions/kaunta.fish`,
+	ValidArgs: []string{"bash", "zsh", "fish"},
+	Args:      cobra.MatchAll(cobra.ExactArgs(1), cobra.OnlyValidArgs),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runCompletions(cmd, args[0])
+	},
+}
+
+func runCompletions(cmd *cobra.Command, shell string) error {
+	out := cmd.OutOrStdout()
+	switch shell {
+	case "bash":
+		return RootCmd.GenBashCompletionV2(out, true)
+	case "zsh":
+		return RootCmd.GenZshCompletion(out)
+	case "fish":
+		return RootCmd.GenFishCompletion(out, true)
+	}
+	return fmt.Errorf("unsupported shell: %s (use bash, zsh or fish)", shell)
+}
+
+// registerFormatCompletions completes every --format flag with the values
+// listed in its usage. It runs from Execute, once all commands are added.
+func registerFormatCompletions(cmd *cobra.Command) {
+	if f := cmd.LocalNonPersistentFlags().Lookup("format"); f != nil {
+		if choices := formatChoices(f); len(choices) > 0 {
+			_ = cmd.RegisterFlagCompletionFunc("format", cobra.FixedCompletions(choices, cobra.ShellCompDirectiveNoFileComp))
+		}
+	}
+	for _, sub := range cmd.Commands() {
+		registerFormatCompletions(sub)
+	}
+}
+
+func init() {
+	RootCmd.AddCommand(completionsCmd)
+	RootCmd.CompletionOptions.DisableDefaultCmd = true
+}
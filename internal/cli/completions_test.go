@@ -0,0 +1,32 @@
+package cli
+
+import (
+	"bytes"
+	"testing"
+
+	"github.com/spf13/cobra"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestRunCompletions(t *testing.T) {
+	for shell, marker := range map[string]string{
+		"bash": "__start_kaunta",
+		"zsh":  "#compdef kaunta",
+		"fish": "complete -c kaunta",
+	} {
+		t.Run(shell, func(t *testing.T) {
+			var out bytes.Buffer
+			cmd := &cobra.Command{}
+			cmd.SetOut(&out)
+
+			require.NoError(t, runCompletions(cmd, shell))
+			assert.Contains(t, out.String(), marker)
+		})
+	}
+}
+
+func TestCompletionsRejectsUnknownShell(t *testing.T) {
+	assert.Error(t, completionsCmd.Args(completionsCmd, []string{"powershell"}))
+	assert.Error(t, runCompletions(&cobra.Command{}, "powershell"))
+}
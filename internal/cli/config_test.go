@@ -0,0 +1,57 @@
+package cli
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestRunConfigInitWritesTemplate(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "kaunta.toml")
+
+	_, err := captureOutput(t, func() error {
+		return runConfigInit(path, "postgresql://localhost/kaunta", false)
+	})
+	require.NoError(t, err)
+
+	info, err := os.Stat(path)
+	require.NoError(t, err)
+	assert.Equal(t, os.FileMode(0o600), info.Mode().Perm())
+	content, err := os.ReadFile(path)
+	require.NoError(t, err)
+	assert.Contains(t, string(content), `database_url = "postgresql://localhost/kaunta"`)
+
+	_, err = captureOutput(t, func() error {
+		return runConfigInit(path, "", false)
+	})
+	require.Error(t, err)
+	assert.Contains(t, err.Error(), "--force")
+
+	_, err = captureOutput(t, func() error {
+		return runConfigInit(path, "", true)
+	})
+	require.NoError(t, err)
+	content, err = os.ReadFile(path)
+	require.NoError(t, err)
+	assert.Contains(t, string(content), `# database_url = ""`)
+}
+
+func TestRunConfigCheckFailsOnErrors(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "kaunta.toml")
+	require.NoError(t, os.WriteFile(path, []byte("[smtp]\nhots = \"mail.example.com\"\n"), 0o600))
+
+	output, err := captureOutput(t, func() error {
+		return runConfigCheck(path, "text")
+	})
+	require.Error(t, err)
+	assert.Contains(t, output, "✗ smtp.hots: unknown setting; did you mean smtp.host?")
+
+	output, err = captureOutput(t, func() error {
+		return runConfigCheck(path, "json")
+	})
+	require.Error(t, err)
+	assert.Contains(t, output, `"setting": "smtp.hots"`)
+}
@@ -25,6 +25,7 @@ var (
 	RemoveAllowedDomain   = websites.RemoveAllowedDomain
 	GetAllowedDomains     = websites.GetAllowedDomains
 	SetPublicStatsEnabled = websites.SetPublicStatsEnabled
+	SetWebsiteRetention   = websites.SetRetention
 	validateDomain        = websites.ValidateDomain
 )
 
@@ -7,6 +7,7 @@ import (
 	"fmt"
 	"os"
 	"path/filepath"
+	"slices"
 	"strconv"
 	"strings"
 	"time"
@@ -32,6 +33,13 @@ Checks performed:
   - PostgreSQL functions exist
   - PostgreSQL triggers exist
   - Materialized views exist
+  - public_url, secure_cookies, cookie settings and trusted origins agree
+  - Reverse proxy sign-in headers are only trusted from the proxy
+  - SMTP server reachable
+  - Tracker script served at public_url
+
+Warnings (!) point at optional features left off; only failures (✗) make
+the command exit non-zero.
 
 Example:
   kaunta doctor
@@ -45,6 +53,9 @@ type CheckResult struct {
 	Error      string `json:"error,omitempty"`
 	Suggestion string `json:"suggestion,omitempty"`
 	Details    string `json:"details,omitempty"`
+	// Warning marks a passing check with a finding worth reading, such as
+	// an optional feature that isn't configured
+	Warning bool `json:"warning,omitempty"`
 }
 
 var requiredFunctions = []string{
@@ -55,6 +66,9 @@ var requiredFunctions = []string{
 	"cleanup_expired_sessions",
 
 	// Partition management
+	"ensure_daily_partitions",
+	"ensure_partitions_between",
+	"archive_pending_months",
 	"cleanup_old_partitions",
 	"cleanup_old_bot_logs",
 	"get_partition_stats",
@@ -71,12 +85,29 @@ var requiredFunctions = []string{
 	"get_timeseries",
 	"get_breakdown",
 	"validate_origin",
+
+	// Rollups
+	"refresh_event_rollups",
+	"get_rollup_totals",
+	"refresh_visitor_sketches",
+	"get_unique_visitors",
+	"compact_events",
+
+	// TimescaleDB
+	"events_are_hypertable",
+	"drop_expired_event_chunks",
+	"convert_events_to_hypertable",
+	// Tenant isolation
+	"tenant_websites",
+	"enable_tenant_isolation",
 }
 
-var requiredTriggers = []struct {
+type requiredTrigger struct {
 	name  string
 	table string
-}{
+}
+
+var requiredTriggers = []requiredTrigger{
 	{"trg_website_event_realtime_stats", "website_event"},
 	{"trigger_update_trusted_origin_timestamp", "trusted_origin"},
 }
@@ -189,6 +220,14 @@ func checkMigrations(cfg *config.Config) CheckResult {
 	}
 
 	expectedVersion := database.LatestMigrationVersion
+	if version > expectedVersion {
+		return CheckResult{
+			Name:       "Database Migrations",
+			Pass:       false,
+			Error:      fmt.Sprintf("Migration version %d is newer than this binary (%d)", version, expectedVersion),
+			Suggestion: "A newer Kaunta migrated this database; upgrade this binary with: " + upgradeHint,
+		}
+	}
 	if version != expectedVersion {
 		return CheckResult{
 			Name:       "Database Migrations",
@@ -254,15 +293,23 @@ func checkPostgreSQLFunctions(db *sql.DB) CheckResult {
 	}
 }
 
-func checkPostgreSQLTriggers(db *sql.DB) CheckResult {
+func checkPostgreSQLTriggers(db *sql.DB, hypertable bool) CheckResult {
+	triggers := requiredTriggers
+	if hypertable {
+		// Converting events to a hypertable drops the realtime stats trigger
+		triggers = slices.DeleteFunc(slices.Clone(triggers), func(t requiredTrigger) bool {
+			return t.table == "website_event"
+		})
+	}
+
 	query := `
 		SELECT tgname, tgrelid::regclass::text
 		FROM pg_trigger
 		WHERE tgname = ANY($1)
 	`
 
-	triggerNames := make([]string, len(requiredTriggers))
-	for i, t := range requiredTriggers {
+	triggerNames := make([]string, len(triggers))
+	for i, t := range triggers {
 		triggerNames[i] = t.name
 	}
 
@@ -280,7 +327,7 @@ func checkPostgreSQLTriggers(db *sql.DB) CheckResult {
 	}
 
 	var missing []string
-	for _, trigger := range requiredTriggers {
+	for _, trigger := range triggers {
 		if _, found := foundTriggers[trigger.name]; !found {
 			missing = append(missing, trigger.name)
 		}
@@ -298,16 +345,25 @@ func checkPostgreSQLTriggers(db *sql.DB) CheckResult {
 	return CheckResult{
 		Name:    "PostgreSQL Triggers",
 		Pass:    true,
-		Details: fmt.Sprintf("%d/%d triggers found", len(requiredTriggers), len(requiredTriggers)),
+		Details: fmt.Sprintf("%d/%d triggers found", len(triggers), len(triggers)),
 	}
 }
 
-func checkMaterializedViews(db *sql.DB) CheckResult {
+func checkMaterializedViews(db *sql.DB, hypertable bool) CheckResult {
 	query := `
 		SELECT matviewname
 		FROM pg_matviews
 		WHERE schemaname = 'public' AND matviewname = ANY($1)
 	`
+	if hypertable {
+		// The hourly and daily stats are continuous aggregates
+		query += `
+		UNION ALL
+		SELECT view_name
+		FROM timescaledb_information.continuous_aggregates
+		WHERE view_schema = 'public' AND view_name = ANY($1)
+	`
+	}
 
 	rows, err := db.Query(query, pq.Array(requiredMatViews))
 	if err != nil {
@@ -361,6 +417,7 @@ func runDoctor(cmd *cobra.Command, args []string) error {
 	results = append(results, checkGeoIPDatabase(cfg))
 
 	// Connect to database for remaining checks
+	var trustedOrigins []string
 	db, err := sql.Open("postgres", cfg.DatabaseURL)
 	if err != nil {
 		results = append(results, CheckResult{
@@ -376,10 +433,21 @@ func runDoctor(cmd *cobra.Command, args []string) error {
 		results = append(results, checkPostgreSQLVersion(db))
 		results = append(results, checkMigrations(cfg))
 		results = append(results, checkPostgreSQLFunctions(db))
-		results = append(results, checkPostgreSQLTriggers(db))
-		results = append(results, checkMaterializedViews(db))
+		hypertable, _ := database.EventsAreHypertable(context.Background(), db)
+		results = append(results, checkPostgreSQLTriggers(db, hypertable))
+		results = append(results, checkMaterializedViews(db, hypertable))
+		trustedOrigins = loadTrustedOrigins(db, cfg)
+	}
+	if trustedOrigins == nil {
+		trustedOrigins = cfg.TrustedOrigins
 	}
 
+	// Configuration and reachability
+	results = append(results, checkCookiePolicy(cfg, trustedOrigins))
+	results = append(results, checkReverseProxy(cfg))
+	results = append(results, checkSMTP(cfg))
+	results = append(results, checkTrackerScript(cfg))
+
 	// Output results
 	if jsonOutput {
 		outputDoctorJSON(results)
@@ -410,6 +478,8 @@ func outputDoctorHuman(results []CheckResult) {
 		icon := "✓"
 		if !r.Pass {
 			icon = "✗"
+		} else if r.Warning {
+			icon = "!"
 		}
 
 		fmt.Printf("%s %s", icon, r.Name)
@@ -418,7 +488,7 @@ func outputDoctorHuman(results []CheckResult) {
 		}
 		fmt.Println()
 
-		if !r.Pass {
+		if !r.Pass || r.Warning {
 			if r.Error != "" {
 				fmt.Printf("  Error: %s\n", r.Error)
 			}
@@ -0,0 +1,272 @@
+package cli
+
+import (
+	"context"
+	"crypto/tls"
+	"database/sql"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"net/smtp"
+	"strings"
+	"time"
+
+	"github.com/seuros/kaunta/internal/config"
+)
+
+// doctorTimeout bounds each network check of the doctor
+const doctorTimeout = 10 * time.Second
+
+// doctorHTTPClient fetches the tracker script. Redirects aren't followed:
+// browsers drop the script when a proxy redirects it to another address.
+var doctorHTTPClient = &http.Client{
+	Timeout: doctorTimeout,
+	CheckRedirect: func(req *http.Request, via []*http.Request) error {
+		return http.ErrUseLastResponse
+	},
+}
+
+// loadTrustedOrigins returns the trusted origins the server would use:
+// those in the database, or the configured ones when it can't be read
+func loadTrustedOrigins(db *sql.DB, cfg *config.Config) []string {
+	rows, err := db.Query("SELECT unnest(get_trusted_origins())")
+	if err != nil {
+		return cfg.TrustedOrigins
+	}
+	defer func() { _ = rows.Close() }()
+
+	var origins []string
+	for rows.Next() {
+		var domain string
+		if err := rows.Scan(&domain); err != nil {
+			return cfg.TrustedOrigins
+		}
+		origins = append(origins, domain)
+	}
+	return origins
+}
+
+// checkCookiePolicy looks for the settings behind most "login doesn't
+// stick" and "Forbidden" reports: public_url, secure_cookies, the cookie
+// attributes and the trusted origins disagreeing with each other
+func checkCookiePolicy(cfg *config.Config, trustedOrigins []string) CheckResult {
+	const name = "Public URL & Cookies"
+
+	problems := cfg.PolicyWarnings(trustedOrigins)
+	if len(cfg.CORSOrigins) > 0 {
+		sameSite := strings.ToLower(cfg.Cookies.SameSite)
+		if sameSite == "" && !cfg.SecureCookies {
+			sameSite = "lax"
+		}
+		if sameSite == "lax" || sameSite == "strict" {
+			problems = append(problems, fmt.Sprintf("cors_origins is set but cookies are SameSite=%s; browsers won't send the session cookie with requests from %s", sameSite, strings.Join(cfg.CORSOrigins, ", ")))
+		}
+	}
+	if len(problems) > 0 {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      strings.Join(problems, "; "),
+			Suggestion: "Align public_url, secure_cookies, cookies.* and trusted_origins (kaunta domain add <host>)",
+		}
+	}
+
+	if cfg.PublicURL == "" {
+		return CheckResult{
+			Name:       name,
+			Pass:       true,
+			Warning:    true,
+			Error:      "public_url is not set",
+			Suggestion: "Set public_url (or PUBLIC_URL) to the address users open, so emailed links work and cookies can be checked against it",
+		}
+	}
+	return CheckResult{Name: name, Pass: true, Details: cfg.PublicURL}
+}
+
+// checkReverseProxy checks the proxy header sign-in settings. A header
+// that anyone may send, or one that is silently ignored, is a common
+// misconfiguration.
+func checkReverseProxy(cfg *config.Config) CheckResult {
+	const name = "Reverse Proxy Headers"
+	p := cfg.ProxyAuth
+
+	if p.Header == "" {
+		if len(p.TrustedProxies) > 0 {
+			return CheckResult{
+				Name:       name,
+				Pass:       true,
+				Warning:    true,
+				Error:      "proxy_auth.trusted_proxies is set without proxy_auth.header",
+				Suggestion: "Set proxy_auth.header (e.g. Remote-User) to sign users in from the proxy, or remove trusted_proxies",
+			}
+		}
+		return CheckResult{Name: name, Pass: true, Details: "proxy sign-in off"}
+	}
+	if len(p.TrustedProxies) == 0 {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      fmt.Sprintf("proxy_auth.header %s is set but proxy_auth.trusted_proxies is empty, so proxy sign-in is off", p.Header),
+			Suggestion: "Set proxy_auth.trusted_proxies to the IP or network of the reverse proxy",
+		}
+	}
+	networks, err := p.Networks()
+	if err != nil {
+		return CheckResult{Name: name, Pass: false, Error: err.Error(), Suggestion: "List IP addresses or CIDRs, e.g. 172.18.0.0/16"}
+	}
+	for _, network := range networks {
+		if network.Bits() == 0 {
+			return CheckResult{
+				Name:       name,
+				Pass:       false,
+				Error:      fmt.Sprintf("proxy_auth.trusted_proxies contains %s, so any client can sign in as anyone by sending %s", network, p.Header),
+				Suggestion: "Trust only the reverse proxy's address and make it strip the header from client requests",
+			}
+		}
+	}
+	return CheckResult{Name: name, Pass: true, Details: fmt.Sprintf("%s from %s", p.Header, strings.Join(p.TrustedProxies, ", "))}
+}
+
+// checkSMTP connects to the SMTP server and checks that it greets like one
+// and offers STARTTLS before credentials are sent over the connection
+func checkSMTP(cfg *config.Config) CheckResult {
+	const name = "SMTP Server"
+	s := cfg.SMTP
+
+	if s.Host == "" {
+		return CheckResult{
+			Name:       name,
+			Pass:       true,
+			Warning:    true,
+			Error:      "smtp.host is not set",
+			Suggestion: "Invitations, password resets and email alerts can't be sent; set smtp.host (or SMTP_HOST) to enable them",
+		}
+	}
+	addr := net.JoinHostPort(s.Host, s.Port)
+	if s.Port == "465" {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      "port 465 expects TLS from the first byte, which Kaunta doesn't speak",
+			Suggestion: "Use the submission port 587 (STARTTLS) of the mail server",
+		}
+	}
+
+	conn, err := net.DialTimeout("tcp", addr, doctorTimeout)
+	if err != nil {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      err.Error(),
+			Suggestion: fmt.Sprintf("Check smtp.host and smtp.port, and that outgoing connections to %s are allowed", addr),
+		}
+	}
+	_ = conn.SetDeadline(time.Now().Add(doctorTimeout))
+	client, err := smtp.NewClient(conn, s.Host)
+	if err != nil {
+		_ = conn.Close()
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      fmt.Sprintf("%s did not answer as an SMTP server: %v", addr, err),
+			Suggestion: "Check smtp.port; the submission port is usually 587",
+		}
+	}
+	defer func() { _ = client.Close() }()
+
+	if err := client.Hello("localhost"); err != nil {
+		return CheckResult{Name: name, Pass: false, Error: err.Error()}
+	}
+	tlsOffered, _ := client.Extension("STARTTLS")
+	if tlsOffered {
+		if err := client.StartTLS(&tls.Config{ServerName: s.Host}); err != nil {
+			return CheckResult{
+				Name:       name,
+				Pass:       false,
+				Error:      fmt.Sprintf("STARTTLS failed: %v", err),
+				Suggestion: "The server's certificate must be valid for smtp.host",
+			}
+		}
+	} else if s.Username != "" && !isLoopbackHost(s.Host) {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      fmt.Sprintf("%s doesn't offer STARTTLS, and credentials are only sent over TLS", addr),
+			Suggestion: "Use the mail server's submission port (587), or a relay on localhost",
+		}
+	}
+	_ = client.Quit()
+
+	details := addr
+	if tlsOffered {
+		details += ", STARTTLS"
+	}
+	return CheckResult{Name: name, Pass: true, Details: details}
+}
+
+// checkTrackerScript fetches the tracker script the way a visitor's
+// browser would, through public_url when set. A reverse proxy that routes
+// only the dashboard, or answers with a login page, shows up here.
+func checkTrackerScript(cfg *config.Config) CheckResult {
+	const name = "Tracker Script"
+
+	base := strings.TrimSuffix(cfg.PublicURL, "/")
+	if base == "" {
+		base = "http://localhost:" + cfg.Port
+	}
+	url := base + "/k.js"
+
+	ctx, cancel := context.WithTimeout(context.Background(), doctorTimeout)
+	defer cancel()
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, url, nil)
+	if err != nil {
+		return CheckResult{Name: name, Pass: false, Error: err.Error(), Suggestion: "Check public_url"}
+	}
+	resp, err := doctorHTTPClient.Do(req)
+	if err != nil {
+		if cfg.PublicURL == "" {
+			return CheckResult{
+				Name:       name,
+				Pass:       true,
+				Warning:    true,
+				Error:      fmt.Sprintf("could not fetch %s: %v", url, err),
+				Suggestion: "Start the server, or set public_url to check the address visitors use",
+			}
+		}
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      fmt.Sprintf("could not fetch %s: %v", url, err),
+			Suggestion: "Make sure the server is running and the reverse proxy forwards public_url to it",
+		}
+	}
+	defer func() { _ = resp.Body.Close() }()
+	_, _ = io.Copy(io.Discard, io.LimitReader(resp.Body, 1<<20))
+
+	if resp.StatusCode != http.StatusOK {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      fmt.Sprintf("%s answered %s", url, resp.Status),
+			Suggestion: "The reverse proxy must pass /k.js (and /api/send) to Kaunta without authentication",
+		}
+	}
+	if contentType := resp.Header.Get("Content-Type"); !strings.Contains(contentType, "javascript") {
+		return CheckResult{
+			Name:       name,
+			Pass:       false,
+			Error:      fmt.Sprintf("%s is served as %q, not JavaScript", url, contentType),
+			Suggestion: "Something in front of Kaunta answers instead of it; check the reverse proxy routes",
+		}
+	}
+	return CheckResult{Name: name, Pass: true, Details: url}
+}
+
+func isLoopbackHost(host string) bool {
+	if host == "localhost" {
+		return true
+	}
+	ip := net.ParseIP(host)
+	return ip != nil && ip.IsLoopback()
+}
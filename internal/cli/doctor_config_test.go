@@ -0,0 +1,133 @@
+package cli
+
+import (
+	"bufio"
+	"net"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/config"
+)
+
+func TestCheckCookiePolicy(t *testing.T) {
+	cfg := &config.Config{PublicURL: "https://stats.example.com", SecureCookies: true}
+	result := checkCookiePolicy(cfg, []string{"stats.example.com"})
+	assert.True(t, result.Pass)
+	assert.False(t, result.Warning)
+
+	result = checkCookiePolicy(cfg, []string{"localhost"})
+	assert.False(t, result.Pass)
+	assert.Contains(t, result.Error, "not in trusted_origins")
+
+	cfg.CORSOrigins = []string{"https://app.example.org"}
+	cfg.Cookies.SameSite = "strict"
+	result = checkCookiePolicy(cfg, []string{"stats.example.com"})
+	assert.False(t, result.Pass)
+	assert.Contains(t, result.Error, "SameSite=strict")
+
+	result = checkCookiePolicy(&config.Config{SecureCookies: true}, nil)
+	assert.True(t, result.Pass)
+	assert.True(t, result.Warning)
+}
+
+func TestCheckReverseProxy(t *testing.T) {
+	check := func(header string, proxies ...string) CheckResult {
+		return checkReverseProxy(&config.Config{ProxyAuth: config.ProxyAuthConfig{Header: header, TrustedProxies: proxies}})
+	}
+
+	assert.True(t, check("").Pass)
+	assert.True(t, check("", "10.0.0.1").Warning)
+	assert.True(t, check("Remote-User", "10.0.0.1", "172.18.0.0/16").Pass)
+
+	result := check("Remote-User")
+	assert.False(t, result.Pass)
+	assert.Contains(t, result.Error, "trusted_proxies is empty")
+
+	result = check("Remote-User", "0.0.0.0/0")
+	assert.False(t, result.Pass)
+	assert.Contains(t, result.Error, "any client can sign in")
+
+	assert.False(t, check("Remote-User", "proxy.local").Pass)
+}
+
+// fakeSMTP answers one connection like an SMTP server without STARTTLS
+func fakeSMTP(t *testing.T) (host, port string) {
+	t.Helper()
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = ln.Close() })
+
+	go func() {
+		conn, err := ln.Accept()
+		if err != nil {
+			return
+		}
+		defer func() { _ = conn.Close() }()
+		r := bufio.NewReader(conn)
+		_, _ = conn.Write([]byte("220 mail.test ESMTP\r\n"))
+		for {
+			line, err := r.ReadString('\n')
+			if err != nil {
+				return
+			}
+			switch strings.ToUpper(strings.Fields(line)[0]) {
+			case "EHLO":
+				_, _ = conn.Write([]byte("250-mail.test\r\n250 8BITMIME\r\n"))
+			case "QUIT":
+				_, _ = conn.Write([]byte("221 bye\r\n"))
+				return
+			default:
+				_, _ = conn.Write([]byte("502 unsupported\r\n"))
+			}
+		}
+	}()
+	host, port, err = net.SplitHostPort(ln.Addr().String())
+	require.NoError(t, err)
+	return host, port
+}
+
+func TestCheckSMTP(t *testing.T) {
+	result := checkSMTP(&config.Config{})
+	assert.True(t, result.Pass)
+	assert.True(t, result.Warning)
+
+	result = checkSMTP(&config.Config{SMTP: config.SMTPConfig{Host: "smtp.example.com", Port: "465"}})
+	assert.False(t, result.Pass)
+	assert.Contains(t, result.Error, "port 465")
+
+	host, port := fakeSMTP(t)
+	result = checkSMTP(&config.Config{SMTP: config.SMTPConfig{Host: host, Port: port}})
+	assert.True(t, result.Pass, result.Error)
+	assert.Equal(t, net.JoinHostPort(host, port), result.Details)
+}
+
+func TestCheckTrackerScript(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.URL.Path != "/k.js" {
+			http.NotFound(w, r)
+			return
+		}
+		w.Header().Set("Content-Type", "application/javascript; charset=utf-8")
+		_, _ = w.Write([]byte("(function(){})()"))
+	}))
+	defer server.Close()
+
+	result := checkTrackerScript(&config.Config{PublicURL: server.URL + "/"})
+	assert.True(t, result.Pass, result.Error)
+	assert.Equal(t, server.URL+"/k.js", result.Details)
+
+	login := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "text/html")
+		_, _ = w.Write([]byte("<form>sign in</form>"))
+	}))
+	defer login.Close()
+
+	result = checkTrackerScript(&config.Config{PublicURL: login.URL})
+	assert.False(t, result.Pass)
+	assert.Contains(t, result.Error, "not JavaScript")
+}
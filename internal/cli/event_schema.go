@@ -0,0 +1,171 @@
+package cli
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"maps"
+	"os"
+	"slices"
+	"strings"
+	"text/tabwriter"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/eventschema"
+)
+
+var websiteEventSchemaCmd = &cobra.Command{
+	Use:   "event-schema <domain> [--set <file>] [--clear] [--format table|json|ts]",
+	Short: "Show or set the custom events a website sends",
+	Long: `Show or set a website's event schema: the custom events it sends and the
+type of each property (string, number or boolean, with a trailing ? when
+optional). Properties the schema doesn't list are always allowed.
+
+Events that don't match are still recorded, and counted in the dashboard's
+Data quality tab. Use --format ts to generate TypeScript declarations that
+type track() from @kaunta/tracker.
+
+A schema file looks like:
+  {"signup": {"plan": "string", "seats": "number", "trial": "boolean?"}}
+
+Examples:
+  kaunta website event-schema example.com
+  kaunta website event-schema example.com --set schema.json
+  cat schema.json | kaunta website event-schema example.com --set -
+  kaunta website event-schema example.com --format ts > kaunta-events.d.ts
+  kaunta website event-schema example.com --clear`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runWebsiteEventSchema(args[0], eventSchemaSet, eventSchemaClear, eventSchemaFormat)
+	},
+}
+
+// Command flags
+var (
+	eventSchemaSet    string
+	eventSchemaClear  bool
+	eventSchemaFormat string
+)
+
+var (
+	loadEventSchemaFunc = eventschema.Load
+	saveEventSchemaFunc = eventschema.Save
+)
+
+func runWebsiteEventSchema(domain, setPath string, clearSchema bool, format string) error {
+	if format != "table" && format != "json" && format != "ts" {
+		return fmt.Errorf("invalid format: %s (use table, json or ts)", format)
+	}
+	if setPath != "" && clearSchema {
+		return fmt.Errorf("use either --set or --clear")
+	}
+
+	var schema eventschema.Schema
+	if setPath != "" {
+		data, err := readEventSchemaFile(setPath)
+		if err != nil {
+			return err
+		}
+		if schema, err = eventschema.Parse(data); err != nil {
+			return err
+		}
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	website, err := fetchWebsiteByDomain(ctx, domain, nil)
+	if err != nil {
+		return err
+	}
+	websiteID, err := uuid.Parse(website.WebsiteID)
+	if err != nil {
+		return fmt.Errorf("invalid website ID: %w", err)
+	}
+
+	switch {
+	case setPath != "" || clearSchema:
+		if err := saveEventSchemaFunc(ctx, database.DB, websiteID, schema); err != nil {
+			return fmt.Errorf("failed to save event schema: %w", err)
+		}
+		// Status goes to stderr so the output can still be redirected to a file
+		if clearSchema {
+			fmt.Fprintln(os.Stderr, "Event schema cleared.")
+		} else {
+			fmt.Fprintf(os.Stderr, "Event schema saved (%d events). Running servers pick it up within 5 minutes.\n", len(schema))
+		}
+	default:
+		if schema, err = loadEventSchemaFunc(ctx, database.DB, websiteID); err != nil {
+			return fmt.Errorf("failed to load event schema: %w", err)
+		}
+	}
+
+	return outputEventSchema(website.Domain, schema, format)
+}
+
+func readEventSchemaFile(path string) ([]byte, error) {
+	if path == "-" {
+		return io.ReadAll(os.Stdin)
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("failed to read schema: %w", err)
+	}
+	return data, nil
+}
+
+func outputEventSchema(domain string, schema eventschema.Schema, format string) error {
+	switch format {
+	case "json":
+		data, err := json.MarshalIndent(schema, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	case "ts":
+		fmt.Print(schema.TypeScript())
+		return nil
+	}
+
+	if len(schema) == 0 {
+		fmt.Printf("%s has no event schema; every custom event is accepted as is\n", domain)
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	defer func() { _ = w.Flush() }()
+
+	_, _ = fmt.Fprintln(w, "EVENT\tPROPERTIES")
+	for _, name := range slices.Sorted(maps.Keys(schema)) {
+		props := schema[name]
+		parts := make([]string, 0, len(props))
+		for _, prop := range slices.Sorted(maps.Keys(props)) {
+			parts = append(parts, prop+": "+props[prop])
+		}
+		if len(parts) == 0 {
+			parts = append(parts, "-")
+		}
+		_, _ = fmt.Fprintf(w, "%s\t%s\n", name, strings.Join(parts, ", "))
+	}
+	return nil
+}
+
+func init() {
+	websiteCmd.AddCommand(websiteEventSchemaCmd)
+
+	websiteEventSchemaCmd.Flags().StringVar(&eventSchemaSet, "set", "", "Replace the schema with a JSON file (- for stdin)")
+	websiteEventSchemaCmd.Flags().BoolVar(&eventSchemaClear, "clear", false, "Remove the schema")
+	websiteEventSchemaCmd.Flags().StringVarP(&eventSchemaFormat, "format", "f", "table", "Output format (table, json, ts)")
+}
@@ -0,0 +1,80 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/eventschema"
+)
+
+func stubEventSchemaWebsite(t *testing.T) uuid.UUID {
+	t.Helper()
+	stubDB(t)
+	stubConnectClose(t)
+
+	websiteID := uuid.New()
+	originalFetch, originalLoad, originalSave := fetchWebsiteByDomain, loadEventSchemaFunc, saveEventSchemaFunc
+	t.Cleanup(func() {
+		fetchWebsiteByDomain, loadEventSchemaFunc, saveEventSchemaFunc = originalFetch, originalLoad, originalSave
+	})
+	fetchWebsiteByDomain = func(ctx context.Context, domain string, byID *string) (*WebsiteDetail, error) {
+		return &WebsiteDetail{WebsiteID: websiteID.String(), Domain: domain}, nil
+	}
+	return websiteID
+}
+
+func TestRunWebsiteEventSchemaShow(t *testing.T) {
+	websiteID := stubEventSchemaWebsite(t)
+	loadEventSchemaFunc = func(ctx context.Context, db *sql.DB, id uuid.UUID) (eventschema.Schema, error) {
+		assert.Equal(t, websiteID, id)
+		return eventschema.Schema{"signup": {"plan": "string", "trial": "boolean?"}, "logout": {}}, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runWebsiteEventSchema("example.com", "", false, "table")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "EVENT")
+	assert.Regexp(t, `signup\s+plan: string, trial: boolean\?`, output)
+	assert.Regexp(t, `logout\s+-`, output)
+
+	output, err = captureOutput(t, func() error {
+		return runWebsiteEventSchema("example.com", "", false, "ts")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, `"trial"?: boolean;`)
+
+	assert.Error(t, runWebsiteEventSchema("example.com", "", false, "xml"))
+}
+
+func TestRunWebsiteEventSchemaSet(t *testing.T) {
+	websiteID := stubEventSchemaWebsite(t)
+	var saved eventschema.Schema
+	saveEventSchemaFunc = func(ctx context.Context, db *sql.DB, id uuid.UUID, s eventschema.Schema) error {
+		assert.Equal(t, websiteID, id)
+		saved = s
+		return nil
+	}
+
+	path := filepath.Join(t.TempDir(), "schema.json")
+	require.NoError(t, os.WriteFile(path, []byte(`{"signup": {"seats": "number"}}`), 0o600))
+
+	_, err := captureOutput(t, func() error {
+		return runWebsiteEventSchema("example.com", path, false, "json")
+	})
+	require.NoError(t, err)
+	assert.Equal(t, eventschema.Schema{"signup": {"seats": "number"}}, saved)
+
+	// Invalid schemas are rejected before touching the database
+	require.NoError(t, os.WriteFile(path, []byte(`{"signup": {"seats": "int"}}`), 0o600))
+	assert.ErrorContains(t, runWebsiteEventSchema("example.com", path, false, "table"), `unknown type "int"`)
+
+	assert.Error(t, runWebsiteEventSchema("example.com", path, true, "table"))
+}
@@ -0,0 +1,158 @@
+package cli
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"os"
+	"syscall"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+	"golang.org/x/term"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/export"
+)
+
+var exportCmd = &cobra.Command{
+	Use:   "export --website <domain|id> [--from <date>] [--to <date>] [--format ndjson|csv|parquet]",
+	Short: "Export a website's data to files",
+	Long: `Write everything Kaunta stores about a website to a directory, the
+counterpart to 'kaunta import':
+
+  events.<format>         raw events with the attributes of their session
+  rollups.<format>        daily counts per page, referrer, country, browser, ...
+  daily_summary.<format>  pageviews, visits and bounces of compacted days
+  manifest.json           the website's settings, the range and the files
+
+The events file has the columns of the event archives. Days whose raw
+events were dropped by retention or compaction are still in the rollups;
+months moved to S3 are read with 'kaunta archive export'.
+
+--from and --to are UTC dates and both included; without them everything
+is exported.
+
+Examples:
+  kaunta export --website example.com
+  kaunta export --website example.com --from 2025-01-01 --to 2025-03-31 --format parquet --output q1`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runExport(exportWebsite, exportFrom, exportTo, exportFormat, exportOutput)
+	},
+}
+
+// Command flags
+var (
+	exportWebsite string
+	exportFrom    string
+	exportTo      string
+	exportFormat  string
+	exportOutput  string
+)
+
+// parseExportRange turns the --from and --to dates into [from, until)
+func parseExportRange(fromArg, toArg string, now time.Time) (time.Time, time.Time, error) {
+	from := time.Unix(0, 0).UTC()
+	until := time.Date(now.Year(), now.Month(), now.Day(), 0, 0, 0, 0, time.UTC).AddDate(0, 0, 1)
+	if fromArg != "" {
+		day, err := time.Parse("2006-01-02", fromArg)
+		if err != nil {
+			return time.Time{}, time.Time{}, fmt.Errorf("invalid --from %q (use YYYY-MM-DD)", fromArg)
+		}
+		from = day
+	}
+	if toArg != "" {
+		day, err := time.Parse("2006-01-02", toArg)
+		if err != nil {
+			return time.Time{}, time.Time{}, fmt.Errorf("invalid --to %q (use YYYY-MM-DD)", toArg)
+		}
+		until = day.AddDate(0, 0, 1)
+	}
+	if !from.Before(until) {
+		return time.Time{}, time.Time{}, errors.New("--from must not be after --to")
+	}
+	return from, until, nil
+}
+
+func runExport(website, fromArg, toArg, formatArg, output string) error {
+	if website == "" {
+		return errors.New("--website is required")
+	}
+	format, err := export.ParseFormat(formatArg)
+	if err != nil {
+		return err
+	}
+	from, until, err := parseExportRange(fromArg, toArg, time.Now().UTC())
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, stop := signalContextFactory(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+
+	var byID *string
+	if _, err := uuid.Parse(website); err == nil {
+		byID = &website
+	}
+	detail, err := fetchWebsiteByDomain(ctx, website, byID)
+	if err != nil {
+		return err
+	}
+	websiteID, err := uuid.Parse(detail.WebsiteID)
+	if err != nil {
+		return err
+	}
+	if output == "" {
+		output = "kaunta-export-" + detail.Domain
+	}
+
+	interactive := term.IsTerminal(int(os.Stderr.Fd()))
+	manifest, err := export.Export(ctx, database.DB, export.Options{
+		WebsiteID: websiteID,
+		Website:   detail,
+		From:      from,
+		Until:     until,
+		Format:    format,
+		Dir:       output,
+		Progress: func(file string, rows int64, done bool) {
+			switch {
+			case interactive && done:
+				fmt.Fprintf(os.Stderr, "\r\033[K")
+			case interactive:
+				fmt.Fprintf(os.Stderr, "\r\033[K%s: %d rows", file, rows)
+			case !done:
+				fmt.Fprintf(os.Stderr, "%s: %d rows\n", file, rows)
+			}
+		},
+	})
+	if err != nil {
+		if ctx.Err() != nil {
+			return errors.New("export interrupted")
+		}
+		return err
+	}
+
+	for _, file := range manifest.Files {
+		fmt.Printf("✓ %s: %d rows, %s\n", file.Name, file.Rows, formatBytes(file.Bytes))
+	}
+	fmt.Printf("Exported %s to %s/\n", detail.Domain, output)
+	return nil
+}
+
+func init() {
+	RootCmd.AddCommand(exportCmd)
+
+	exportCmd.Flags().StringVar(&exportWebsite, "website", "", "Domain or ID of the website to export")
+	exportCmd.Flags().StringVar(&exportFrom, "from", "", "First day to export (YYYY-MM-DD, UTC)")
+	exportCmd.Flags().StringVar(&exportTo, "to", "", "Last day to export (YYYY-MM-DD, UTC)")
+	exportCmd.Flags().StringVarP(&exportFormat, "format", "f", "ndjson", "Output format (ndjson, csv, parquet)")
+	exportCmd.Flags().StringVarP(&exportOutput, "output", "o", "", "Directory to write to (default kaunta-export-<domain>)")
+}
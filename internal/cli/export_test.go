@@ -0,0 +1,28 @@
+package cli
+
+import (
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseExportRange(t *testing.T) {
+	now := time.Date(2025, 6, 15, 18, 30, 0, 0, time.UTC)
+
+	from, until, err := parseExportRange("", "", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Unix(0, 0).UTC(), from)
+	assert.Equal(t, time.Date(2025, 6, 16, 0, 0, 0, 0, time.UTC), until)
+
+	from, until, err = parseExportRange("2025-01-01", "2025-03-31", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2025, 1, 1, 0, 0, 0, 0, time.UTC), from)
+	assert.Equal(t, time.Date(2025, 4, 1, 0, 0, 0, 0, time.UTC), until)
+
+	_, _, err = parseExportRange("2025-02-01", "2025-01-31", now)
+	assert.ErrorContains(t, err, "--from must not be after --to")
+	_, _, err = parseExportRange("01/02/2025", "", now)
+	assert.ErrorContains(t, err, "invalid --from")
+}
@@ -0,0 +1,213 @@
+package cli
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/gdpr"
+)
+
+var gdprCmd = &cobra.Command{
+	Use:   "gdpr",
+	Short: "Handle data subject requests",
+	Long:  `Erase a visitor's data on request and check erasure receipts.`,
+}
+
+var gdprDeleteCmd = &cobra.Command{
+	Use:   "delete (--visitor <id> | --identified-id <id>) [--website <domain|id>] [--dry-run]",
+	Short: "Erase everything stored about a visitor",
+	Long: `Delete a visitor's sessions, events, goal completions and engaged time,
+and recount the rollups and visitor sketches of the days they were seen on
+without them. Select the visitor by ID (--visitor, the session_id in exports
+and the API; a visitor gets a new one every month, so pass each) or by the
+ID your site sent with their hits (--identified-id, the id field of
+/api/send, stored as distinct_id), on every website or only --website.
+
+The erasure is recorded in the audit log and summarized in a receipt that
+names the visitor only by a SHA-256 hash. With erasure_signing_key set the
+receipt is signed (HMAC-SHA256), and kaunta gdpr verify checks it later;
+--receipt saves it as JSON for your compliance records.
+
+Rollups of days whose raw events retention has already dropped only hold
+counts and stay. Parquet files in the event archive aren't changed: the
+receipt lists the archived months that may still hold the visitor's
+events, to be handled in the bucket.
+
+With --dry-run nothing is deleted; the command reports what would be.
+
+Examples:
+  kaunta gdpr delete --identified-id user-4821 --dry-run
+  kaunta gdpr delete --visitor 3f1c0b9e-5d4a-5b8e-9c1f-2a7d6e4b8c90 --website example.com --receipt erasure.json`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runGDPRDelete(gdprVisitors, gdprIdentifiedID, gdprWebsite, gdprDryRun, gdprFormat, gdprReceipt)
+	},
+}
+
+var gdprVerifyCmd = &cobra.Command{
+	Use:   "verify <receipt.json>",
+	Short: "Check the signature of an erasure receipt",
+	Long: `Check that an erasure receipt saved with kaunta gdpr delete --receipt was
+signed with this server's erasure_signing_key and hasn't been changed since.`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runGDPRVerify(args[0])
+	},
+}
+
+// Command flags
+var (
+	gdprVisitors     []string
+	gdprIdentifiedID string
+	gdprWebsite      string
+	gdprDryRun       bool
+	gdprFormat       string
+	gdprReceipt      string
+)
+
+var (
+	eraseFunc = gdpr.Erase
+
+	// erasureKey returns the key signing receipts, empty when none is set
+	erasureKey = func() string {
+		cfg, err := config.Load()
+		if err != nil {
+			return ""
+		}
+		return cfg.ErasureKey
+	}
+)
+
+func runGDPRDelete(visitors []string, identifiedID, website string, dryRun bool, format, receiptPath string) error {
+	if format != "text" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use text or json)", format)
+	}
+	subject := gdpr.Subject{IdentifiedID: strings.TrimSpace(identifiedID)}
+	for _, v := range visitors {
+		id, err := uuid.Parse(strings.TrimSpace(v))
+		if err != nil {
+			return fmt.Errorf("invalid --visitor %q: visitor IDs are UUIDs", v)
+		}
+		subject.Visitors = append(subject.Visitors, id)
+	}
+	if len(subject.Visitors) == 0 && subject.IdentifiedID == "" {
+		return errors.New("--visitor or --identified-id is required")
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Minute)
+	defer cancel()
+
+	if website != "" {
+		var byID *string
+		if _, err := uuid.Parse(website); err == nil {
+			byID = &website
+		}
+		detail, err := fetchWebsiteByDomain(ctx, website, byID)
+		if err != nil {
+			return err
+		}
+		id, err := uuid.Parse(detail.WebsiteID)
+		if err != nil {
+			return err
+		}
+		subject.WebsiteID = &id
+	}
+
+	receipt, err := eraseFunc(ctx, database.DB, subject, dryRun, "cli")
+	if err != nil {
+		return fmt.Errorf("erasure failed, nothing was deleted: %w", err)
+	}
+	key := erasureKey()
+	if key != "" {
+		receipt.Sign(key)
+	}
+
+	if receiptPath != "" {
+		data, err := json.MarshalIndent(receipt, "", "  ")
+		if err != nil {
+			return err
+		}
+		if err := os.WriteFile(receiptPath, append(data, '\n'), 0o600); err != nil {
+			return fmt.Errorf("erased, but failed to save the receipt: %w", err)
+		}
+	}
+	if format == "json" {
+		return printJSON(receipt)
+	}
+
+	if dryRun {
+		fmt.Println("Would erase:")
+	} else {
+		fmt.Printf("✓ Erased (receipt %s, %s):\n", receipt.ID, receipt.ErasedAt.Format(time.RFC3339))
+	}
+	fmt.Printf("  Sessions:          %d\n", receipt.Sessions)
+	fmt.Printf("  Events:            %d\n", receipt.Events)
+	fmt.Printf("  Goal completions:  %d\n", receipt.Goals)
+	fmt.Printf("  Engaged time:      %d visits\n", receipt.Engagement)
+	fmt.Printf("  Days recounted:    %d (%d rollup rows)\n", receipt.Days, receipt.Rollups)
+	fmt.Printf("  Subject SHA-256:   %s\n", receipt.Subject)
+	if receipt.Signature != "" {
+		fmt.Printf("  Signature:         %s\n", receipt.Signature)
+	}
+	if len(receipt.ArchivedMonths) > 0 {
+		fmt.Printf("! The event archive of %s may still hold this visitor's events; those files aren't changed\n",
+			strings.Join(receipt.ArchivedMonths, ", "))
+	}
+	if !dryRun && key == "" {
+		fmt.Println("! The receipt is unsigned; set erasure_signing_key to sign receipts")
+	}
+	return nil
+}
+
+func runGDPRVerify(path string) error {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return err
+	}
+	var receipt gdpr.Receipt
+	if err := json.Unmarshal(data, &receipt); err != nil {
+		return fmt.Errorf("not an erasure receipt: %w", err)
+	}
+	if receipt.Signature == "" {
+		return errors.New("the receipt is unsigned")
+	}
+	key := erasureKey()
+	if key == "" {
+		return errors.New("erasure_signing_key is not set")
+	}
+	if !receipt.Verify(key) {
+		return errors.New("invalid signature: the receipt was changed or signed with another key")
+	}
+	fmt.Printf("✓ Receipt %s is valid: %d sessions and %d events erased on %s\n",
+		receipt.ID, receipt.Sessions, receipt.Events, receipt.ErasedAt.Format(time.RFC3339))
+	return nil
+}
+
+func init() {
+	RootCmd.AddCommand(gdprCmd)
+	gdprCmd.AddCommand(gdprDeleteCmd)
+	gdprCmd.AddCommand(gdprVerifyCmd)
+
+	gdprDeleteCmd.Flags().StringArrayVar(&gdprVisitors, "visitor", nil, "Visitor (session) ID to erase; repeat for several")
+	gdprDeleteCmd.Flags().StringVar(&gdprIdentifiedID, "identified-id", "", "ID your site sent with the visitor's hits (distinct_id)")
+	gdprDeleteCmd.Flags().StringVar(&gdprWebsite, "website", "", "Only erase on this website (domain or ID)")
+	gdprDeleteCmd.Flags().BoolVar(&gdprDryRun, "dry-run", false, "Report what would be erased without erasing")
+	gdprDeleteCmd.Flags().StringVarP(&gdprFormat, "format", "f", "text", "Output format (text, json)")
+	gdprDeleteCmd.Flags().StringVar(&gdprReceipt, "receipt", "", "Also save the receipt as JSON to this file")
+}
@@ -0,0 +1,92 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/gdpr"
+)
+
+func stubErasure(t *testing.T, key string, receipt gdpr.Receipt) {
+	t.Helper()
+	originalErase, originalKey := eraseFunc, erasureKey
+	t.Cleanup(func() { eraseFunc, erasureKey = originalErase, originalKey })
+	erasureKey = func() string { return key }
+	eraseFunc = func(ctx context.Context, db *sql.DB, subject gdpr.Subject, dryRun bool, actor string) (gdpr.Receipt, error) {
+		assert.Equal(t, "user-1", subject.IdentifiedID)
+		assert.Equal(t, "cli", actor)
+		receipt.Subject = subject.Hash()
+		receipt.DryRun = dryRun
+		return receipt, nil
+	}
+}
+
+func TestRunGDPRDeleteSavesSignedReceipt(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	stubErasure(t, "secret", gdpr.Receipt{ID: uuid.New(), ErasedAt: time.Now().UTC(), Actor: "cli", Sessions: 2, Events: 40})
+
+	path := filepath.Join(t.TempDir(), "receipt.json")
+	output, err := captureOutput(t, func() error {
+		return runGDPRDelete(nil, "user-1", "", false, "text", path)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "✓ Erased")
+	assert.Contains(t, output, "Events:            40")
+	assert.Contains(t, output, "Signature:         sha256=")
+	assert.NotContains(t, output, "user-1")
+
+	data, err := os.ReadFile(path)
+	require.NoError(t, err)
+	var receipt gdpr.Receipt
+	require.NoError(t, json.Unmarshal(data, &receipt))
+	assert.True(t, receipt.Verify("secret"))
+
+	output, err = captureOutput(t, func() error { return runGDPRVerify(path) })
+	require.NoError(t, err)
+	assert.Contains(t, output, "is valid: 2 sessions and 40 events erased")
+}
+
+func TestRunGDPRDeleteWarnsWithoutKey(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	stubErasure(t, "", gdpr.Receipt{ID: uuid.New(), ArchivedMonths: []string{"2026-01"}})
+
+	output, err := captureOutput(t, func() error {
+		return runGDPRDelete(nil, "user-1", "", false, "text", "")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "The event archive of 2026-01 may still hold")
+	assert.Contains(t, output, "receipt is unsigned")
+}
+
+func TestRunGDPRVerifyRejectsEditedReceipt(t *testing.T) {
+	original := erasureKey
+	t.Cleanup(func() { erasureKey = original })
+	erasureKey = func() string { return "secret" }
+
+	receipt := gdpr.Receipt{ID: uuid.New(), Events: 40}
+	receipt.Sign("secret")
+	receipt.Events = 4
+	data, err := json.Marshal(receipt)
+	require.NoError(t, err)
+	path := filepath.Join(t.TempDir(), "receipt.json")
+	require.NoError(t, os.WriteFile(path, data, 0o600))
+
+	assert.ErrorContains(t, runGDPRVerify(path), "invalid signature")
+}
+
+func TestRunGDPRDeleteValidatesFlags(t *testing.T) {
+	assert.ErrorContains(t, runGDPRDelete(nil, "", "", false, "text", ""), "--visitor or --identified-id")
+	assert.ErrorContains(t, runGDPRDelete([]string{"abc"}, "", "", false, "text", ""), "invalid --visitor")
+	assert.ErrorContains(t, runGDPRDelete(nil, "user-1", "", false, "yaml", ""), "invalid format")
+}
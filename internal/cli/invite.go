@@ -0,0 +1,106 @@
+package cli
+
+import (
+	"context"
+	"fmt"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/handlers"
+	"github.com/seuros/kaunta/internal/mailer"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var inviteCmd = &cobra.Command{
+	Use:   "invite <email> (--website <domain> | --team <name>) [--role viewer]",
+	Short: "Invite someone to a website or team",
+	Long: `Create a one-time invitation link granting a role on a website or a team.
+The link is emailed when SMTP is configured and always printed, so it can be
+shared by hand. Links point at public_url (PUBLIC_URL) and expire after 7 days.
+
+Examples:
+  kaunta invite bob@example.com --website example.com --role editor
+  kaunta invite carol@example.com --team Marketing`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runInvite(args[0], inviteWebsite, inviteTeam, inviteRole)
+	},
+}
+
+// Command flags
+var (
+	inviteWebsite string
+	inviteTeam    string
+	inviteRole    string
+)
+
+func runInvite(email, website, team, roleName string) error {
+	if (website == "") == (team == "") {
+		return fmt.Errorf("give exactly one of --website or --team")
+	}
+	role, err := models.ParseWebsiteRole(roleName)
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	var websiteID, teamID *uuid.UUID
+	if website != "" {
+		id, err := publicTokenWebsiteID(ctx, website)
+		if err != nil {
+			return err
+		}
+		websiteID = &id
+	} else {
+		id, err := lookupTeamID(ctx, team)
+		if err != nil {
+			return err
+		}
+		teamID = &id
+	}
+
+	inv, token, err := models.CreateInvitation(ctx, database.DB, email, websiteID, teamID, role, nil)
+	if err != nil {
+		return fmt.Errorf("failed to create invitation: %w", err)
+	}
+
+	cfg, _ := config.Load()
+	if cfg == nil {
+		cfg = &config.Config{}
+	}
+	link := cfg.PublicURL + "/invite/" + token
+	if cfg.PublicURL == "" {
+		fmt.Println("⚠ public_url is not configured; prefix the link below with your Kaunta URL")
+	}
+
+	fmt.Printf("✓ Invited %s to %s '%s' as %s\n", inv.Email, inv.Kind(), inv.Target, inv.Role)
+	if m := mailer.New(cfg.SMTP); m.Enabled() && cfg.PublicURL != "" {
+		if err := handlers.SendInvitation(m, inv, link); err != nil {
+			fmt.Printf("⚠ Failed to send the email: %v\n", err)
+		} else {
+			fmt.Printf("✓ Emailed the invitation to %s\n", inv.Email)
+		}
+	}
+	fmt.Printf("  Link (expires %s): %s\n", inv.ExpiresAt.Format("2006-01-02 15:04"), link)
+	return nil
+}
+
+func init() {
+	RootCmd.AddCommand(inviteCmd)
+
+	inviteCmd.Flags().StringVar(&inviteWebsite, "website", "", "Domain of the website to invite to")
+	inviteCmd.Flags().StringVar(&inviteTeam, "team", "", "Name of the team to invite to")
+	inviteCmd.Flags().StringVarP(&inviteRole, "role", "r", "viewer", "Role to grant (owner, editor, viewer)")
+}
@@ -0,0 +1,224 @@
+package cli
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"strings"
+	"text/tabwriter"
+	"time"
+
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/alerts"
+	"github.com/seuros/kaunta/internal/archive"
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/retention"
+	"github.com/seuros/kaunta/internal/webhooks"
+)
+
+var jobsCmd = &cobra.Command{
+	Use:   "jobs",
+	Short: "Inspect and run background jobs",
+	Long: `The server runs its background work as jobs on fixed intervals: partition
+maintenance, event archiving, retention, rollups, materialized views,
+traffic alerts and webhook maintenance (weekly summaries). Each job runs on
+one server at a time, and its last run and next due run are kept in the
+database, so a restarted server picks up the schedule where it left off.`,
+}
+
+var jobsListCmd = &cobra.Command{
+	Use:   "list [--format table|json]",
+	Short: "List jobs with their last and next run",
+	Args:  cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runJobsList(jobsFormat)
+	},
+}
+
+var jobsRunCmd = &cobra.Command{
+	Use:   "run <job>",
+	Short: "Run a job now",
+	Long: `Run a job now in this process instead of waiting for its next run, for
+instance retention after changing a policy or rollups after a long outage.
+It doesn't run while a server is running the same job, and doesn't move the
+job's next scheduled run.
+
+Examples:
+  kaunta jobs run retention
+  kaunta jobs run rollups`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runJobsRun(args[0])
+	},
+}
+
+var jobsFormat string
+
+var (
+	// configuredJobs returns the jobs the server runs with the current config
+	configuredJobs = func() ([]database.Job, error) {
+		cfg, err := config.Load()
+		if err != nil {
+			return nil, err
+		}
+		archiver, err := newArchiver(cfg)
+		if err != nil {
+			return nil, fmt.Errorf("failed to set up event archiving: %w", err)
+		}
+		return serverJobs(cfg, archiver), nil
+	}
+	listJobsFunc = database.ListJobs
+	runJobFunc   = database.RunJob
+)
+
+// serverJobs returns the background jobs of a server running with cfg.
+// The archive job only exists when archiving is configured.
+func serverJobs(cfg *config.Config, archiver *archive.Archiver) []database.Job {
+	retentionDays := 0
+	purgeDryRun := false
+	var smtpConfig config.SMTPConfig
+	if cfg != nil {
+		retentionDays = cfg.RetentionDays
+		purgeDryRun = cfg.PurgeDryRun
+		smtpConfig = cfg.SMTP
+	}
+
+	jobs := []database.Job{
+		database.NewPartitionScheduler(retentionDays, archiver != nil).Job(),
+		database.NewRollupScheduler().Job(),
+	}
+	jobs = append(jobs, database.NewMaterializedViewScheduler().Jobs()...)
+	jobs = append(jobs, retention.NewScheduler(database.DB, purgeDryRun).Job())
+	if archiver != nil {
+		jobs = append(jobs, archive.NewScheduler(archiver).Job())
+	}
+	return append(jobs,
+		alerts.NewScheduler(database.DB, smtpConfig).Job(),
+		webhooks.NewDispatcher(database.DB).MaintenanceJob(),
+	)
+}
+
+func runJobsList(format string) error {
+	if format != "table" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use table or json)", format)
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	jobs, err := configuredJobs()
+	if err != nil {
+		return err
+	}
+	ctx, stop := importContext()
+	defer stop()
+	statuses, err := listJobsFunc(ctx, database.DB, jobs)
+	if err != nil {
+		return fmt.Errorf("failed to list jobs: %w", err)
+	}
+
+	if format == "json" {
+		return printJSON(statuses)
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	defer func() { _ = w.Flush() }()
+
+	_, _ = fmt.Fprintln(w, "JOB\tEVERY\tLAST RUN\tTOOK\tSTATUS\tNEXT RUN")
+	for _, s := range statuses {
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\t%s\t%s\t%s\n", s.Name, formatEvery(s.Every),
+			formatJobTime(s.LastStartedAt), jobDuration(s), jobStatus(s), formatJobTime(s.NextRunAt))
+	}
+	return nil
+}
+
+func runJobsRun(name string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	jobs, err := configuredJobs()
+	if err != nil {
+		return err
+	}
+	job, ok := database.FindJob(jobs, name)
+	if !ok {
+		names := make([]string, 0, len(jobs))
+		for _, j := range jobs {
+			names = append(names, j.Name)
+		}
+		return fmt.Errorf("unknown job %q (jobs: %s)", name, strings.Join(names, ", "))
+	}
+
+	ctx, stop := importContext()
+	defer stop()
+
+	fmt.Printf("Running %s: %s\n", job.Name, job.Description)
+	start := time.Now()
+	ran, err := runJobFunc(ctx, database.DB, job, database.JobTriggerManual)
+	if !ran {
+		return errors.New(job.Name + " didn't run: a server is running it right now, or its lock couldn't be taken")
+	}
+	if err != nil {
+		return fmt.Errorf("%s failed: %w", job.Name, err)
+	}
+	fmt.Printf("✓ %s finished in %s\n", job.Name, time.Since(start).Round(time.Millisecond))
+	return nil
+}
+
+// formatEvery renders an interval as 1m, 5m, 1h or 24h
+func formatEvery(d time.Duration) string {
+	s := d.String()
+	if strings.HasSuffix(s, "m0s") {
+		s = s[:len(s)-2]
+	}
+	if strings.HasSuffix(s, "h0m") {
+		s = s[:len(s)-2]
+	}
+	return s
+}
+
+func formatJobTime(t *time.Time) string {
+	if t == nil {
+		return "-"
+	}
+	return t.Local().Format("2006-01-02 15:04")
+}
+
+func jobDuration(s database.JobStatus) string {
+	if s.LastStartedAt == nil || s.LastFinishedAt == nil {
+		return "-"
+	}
+	return s.LastFinishedAt.Sub(*s.LastStartedAt).Round(time.Millisecond).String()
+}
+
+// jobStatus summarizes the last run: never run, ok or the error
+func jobStatus(s database.JobStatus) string {
+	switch {
+	case s.LastStartedAt == nil:
+		return "never run"
+	case s.LastError != "":
+		msg := s.LastError
+		if len(msg) > 60 {
+			msg = msg[:57] + "..."
+		}
+		return "failed: " + msg
+	default:
+		return "ok"
+	}
+}
+
+func init() {
+	RootCmd.AddCommand(jobsCmd)
+	jobsCmd.AddCommand(jobsListCmd)
+	jobsCmd.AddCommand(jobsRunCmd)
+
+	jobsListCmd.Flags().StringVarP(&jobsFormat, "format", "f", "table", "Output format (table, json)")
+}
@@ -0,0 +1,106 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func stubJobs(t *testing.T, jobs []database.Job) {
+	t.Helper()
+	stubDB(t)
+	stubConnectClose(t)
+
+	originalJobs, originalList, originalRun := configuredJobs, listJobsFunc, runJobFunc
+	t.Cleanup(func() { configuredJobs, listJobsFunc, runJobFunc = originalJobs, originalList, originalRun })
+	configuredJobs = func() ([]database.Job, error) { return jobs, nil }
+}
+
+func TestRunJobsListTable(t *testing.T) {
+	stubJobs(t, nil)
+	started := time.Date(2026, 3, 4, 3, 0, 0, 0, time.Local)
+	finished := started.Add(1500 * time.Millisecond)
+	next := started.Add(24 * time.Hour)
+	listJobsFunc = func(ctx context.Context, db *sql.DB, jobs []database.Job) ([]database.JobStatus, error) {
+		return []database.JobStatus{
+			{Name: "rollups", Every: 5 * time.Minute},
+			{Name: "retention", Every: 24 * time.Hour, LastStartedAt: &started, LastFinishedAt: &finished,
+				LastError: "disk full", NextRunAt: &next, Runs: 3, Failures: 1},
+		}, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runJobsList("table")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "JOB")
+	assert.Regexp(t, `rollups\s+5m\s+-\s+-\s+never run\s+-`, output)
+	assert.Regexp(t, `retention\s+24h\s+2026-03-04 03:00\s+1.5s\s+failed: disk full\s+2026-03-05 03:00`, output)
+}
+
+func TestRunJobsListJSON(t *testing.T) {
+	stubJobs(t, nil)
+	listJobsFunc = func(ctx context.Context, db *sql.DB, jobs []database.Job) ([]database.JobStatus, error) {
+		return []database.JobStatus{{Name: "alerts", Description: "Evaluate alerts", Every: time.Minute}}, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runJobsList("json")
+	})
+	require.NoError(t, err)
+	var statuses []map[string]any
+	require.NoError(t, json.Unmarshal([]byte(output), &statuses))
+	require.Len(t, statuses, 1)
+	assert.Equal(t, "alerts", statuses[0]["name"])
+	assert.Equal(t, float64(60), statuses[0]["every_seconds"])
+}
+
+func TestRunJobsRun(t *testing.T) {
+	stubJobs(t, []database.Job{{Name: "retention", Description: "Apply policies", Every: 24 * time.Hour}})
+	var trigger string
+	runJobFunc = func(ctx context.Context, db *sql.DB, job database.Job, tr string) (bool, error) {
+		trigger = tr
+		return true, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runJobsRun("retention")
+	})
+	require.NoError(t, err)
+	assert.Equal(t, database.JobTriggerManual, trigger)
+	assert.Contains(t, output, "✓ retention finished")
+}
+
+func TestRunJobsRunErrors(t *testing.T) {
+	stubJobs(t, []database.Job{{Name: "retention"}, {Name: "rollups"}})
+
+	err := runJobsRun("salts")
+	assert.EqualError(t, err, `unknown job "salts" (jobs: retention, rollups)`)
+
+	runJobFunc = func(ctx context.Context, db *sql.DB, job database.Job, tr string) (bool, error) {
+		return false, nil
+	}
+	_, err = captureOutput(t, func() error { return runJobsRun("rollups") })
+	assert.ErrorContains(t, err, "rollups didn't run")
+
+	runJobFunc = func(ctx context.Context, db *sql.DB, job database.Job, tr string) (bool, error) {
+		return true, errors.New("disk full")
+	}
+	_, err = captureOutput(t, func() error { return runJobsRun("retention") })
+	assert.EqualError(t, err, "retention failed: disk full")
+}
+
+func TestFormatEvery(t *testing.T) {
+	assert.Equal(t, "1m", formatEvery(time.Minute))
+	assert.Equal(t, "24h", formatEvery(24*time.Hour))
+	assert.Equal(t, "30s", formatEvery(30*time.Second))
+	assert.Equal(t, "1h30m", formatEvery(90*time.Minute))
+}
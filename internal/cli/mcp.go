@@ -0,0 +1,75 @@
+package cli
+
+import (
+	"context"
+	"fmt"
+	"os"
+	"syscall"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/mcp"
+)
+
+var mcpWebsite string
+
+var mcpCmd = &cobra.Command{
+	Use:   "mcp [--website <domain>]",
+	Short: "Serve stats to LLM agents over the Model Context Protocol",
+	Long: `Run an MCP (Model Context Protocol) server on stdin/stdout so agents and
+editors can query this instance's analytics.
+
+Tools:
+  list_websites  - Tracked websites
+  get_stats      - Pageviews, visitors, visits and engagement vs. the previous period
+  top_pages      - Most viewed pages
+  breakdown      - Visitors by referrer, country, device, utm_source, ...
+
+The server reads the database directly with the configured DATABASE_URL, so
+it can see every website unless --website limits it to one. To use it
+remotely instead, point the client at https://your-kaunta-host/api/mcp with
+an API key that has the stats scope.
+
+Example client configuration:
+  {"mcpServers": {"kaunta": {"command": "kaunta", "args": ["mcp"]}}}`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runMCP(mcpWebsite)
+	},
+}
+
+func runMCP(websiteDomain string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	var website *uuid.UUID
+	if websiteDomain != "" {
+		ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+		site, err := GetWebsiteByDomain(ctx, websiteDomain, nil)
+		cancel()
+		if err != nil {
+			return fmt.Errorf("website not found: %w", err)
+		}
+		websiteID, err := uuid.Parse(site.WebsiteID)
+		if err != nil {
+			return fmt.Errorf("invalid website ID: %w", err)
+		}
+		website = &websiteID
+	}
+
+	ctx, stop := signalContextFactory(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+
+	return mcp.NewStatsServer(database.DB, website, Version).ServeStdio(ctx, os.Stdin, os.Stdout)
+}
+
+func init() {
+	RootCmd.AddCommand(mcpCmd)
+	mcpCmd.Flags().StringVarP(&mcpWebsite, "website", "w", "", "Only expose this website")
+}
@@ -0,0 +1,58 @@
+package cli
+
+import (
+	"fmt"
+	"slices"
+	"strings"
+
+	"github.com/spf13/cobra"
+	"github.com/spf13/pflag"
+)
+
+// outputMode is the global --output flag
+var outputMode string
+
+// applyOutputMode turns --output json into the command's own JSON switch:
+// its --format flag when that offers json, or its --json flag. An explicit
+// --format wins. Commands without JSON output refuse --output json rather
+// than print text a script would misread.
+func applyOutputMode(cmd *cobra.Command) error {
+	switch outputMode {
+	case "", "text":
+		return nil
+	case "json":
+	default:
+		return fmt.Errorf("invalid --output: %s (use text or json)", outputMode)
+	}
+
+	if f := cmd.Flags().Lookup("format"); f != nil && slices.Contains(formatChoices(f), "json") {
+		if f.Changed {
+			return nil
+		}
+		return f.Value.Set("json")
+	}
+	if f := cmd.Flags().Lookup("json"); f != nil && f.Value.Type() == "bool" {
+		return f.Value.Set("true")
+	}
+	return fmt.Errorf("%s has no JSON output", cmd.CommandPath())
+}
+
+// formatChoices reads the values of a --format flag from its usage, which
+// ends in "(table, json)" by convention
+func formatChoices(f *pflag.Flag) []string {
+	open, end := strings.LastIndex(f.Usage, "("), strings.LastIndex(f.Usage, ")")
+	if open < 0 || end < open {
+		return nil
+	}
+	var choices []string
+	for choice := range strings.SplitSeq(f.Usage[open+1:end], ",") {
+		choices = append(choices, strings.TrimSpace(choice))
+	}
+	return choices
+}
+
+func init() {
+	RootCmd.PersistentFlags().StringVar(&outputMode, "output", "text",
+		"Output mode (text, json); json switches the command to its JSON output")
+	_ = RootCmd.RegisterFlagCompletionFunc("output", cobra.FixedCompletions([]string{"text", "json"}, cobra.ShellCompDirectiveNoFileComp))
+}
@@ -0,0 +1,76 @@
+package cli
+
+import (
+	"testing"
+
+	"github.com/spf13/cobra"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func withOutputMode(t *testing.T, mode string) {
+	t.Helper()
+	original := outputMode
+	outputMode = mode
+	t.Cleanup(func() { outputMode = original })
+}
+
+func TestApplyOutputModeSetsFormat(t *testing.T) {
+	withOutputMode(t, "json")
+	var format string
+	cmd := &cobra.Command{Use: "list"}
+	cmd.Flags().StringVarP(&format, "format", "f", "table", "Output format (table, json)")
+
+	require.NoError(t, applyOutputMode(cmd))
+	assert.Equal(t, "json", format)
+}
+
+func TestApplyOutputModeKeepsExplicitFormat(t *testing.T) {
+	withOutputMode(t, "json")
+	var format string
+	cmd := &cobra.Command{Use: "pages"}
+	cmd.Flags().StringVarP(&format, "format", "f", "table", "Output format (json, table, csv)")
+	require.NoError(t, cmd.Flags().Set("format", "csv"))
+
+	require.NoError(t, applyOutputMode(cmd))
+	assert.Equal(t, "csv", format)
+}
+
+func TestApplyOutputModeSetsJSONFlag(t *testing.T) {
+	withOutputMode(t, "json")
+	var asJSON bool
+	cmd := &cobra.Command{Use: "tail"}
+	cmd.Flags().BoolVar(&asJSON, "json", false, "Print each hit as JSON")
+
+	require.NoError(t, applyOutputMode(cmd))
+	assert.True(t, asJSON)
+}
+
+func TestApplyOutputModeRejectsCommandsWithoutJSON(t *testing.T) {
+	withOutputMode(t, "json")
+	var format string
+	cmd := &cobra.Command{Use: "export"}
+	cmd.Flags().StringVarP(&format, "format", "f", "ndjson", "Output format (ndjson, csv, parquet)")
+
+	err := applyOutputMode(cmd)
+	require.Error(t, err)
+	assert.Contains(t, err.Error(), "export has no JSON output")
+	assert.Equal(t, "ndjson", format)
+}
+
+func TestApplyOutputModeText(t *testing.T) {
+	withOutputMode(t, "text")
+	assert.NoError(t, applyOutputMode(&cobra.Command{Use: "serve"}))
+
+	withOutputMode(t, "yaml")
+	assert.Error(t, applyOutputMode(&cobra.Command{Use: "serve"}))
+}
+
+func TestFormatChoices(t *testing.T) {
+	cmd := &cobra.Command{Use: "list"}
+	cmd.Flags().String("format", "table", "Output format (table, json, csv)")
+	cmd.Flags().String("since", "", "Start date")
+
+	assert.Equal(t, []string{"table", "json", "csv"}, formatChoices(cmd.Flags().Lookup("format")))
+	assert.Nil(t, formatChoices(cmd.Flags().Lookup("since")))
+}
@@ -0,0 +1,162 @@
+package cli
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/retention"
+)
+
+var pruneCmd = &cobra.Command{
+	Use:   "prune --website <domain|id> --before <YYYY-MM-DD> [--rollups] [--dry-run]",
+	Short: "Delete a website's events before a date",
+	Long: `Delete a website's raw events before a date, once, the way a retention
+policy would: the events are compacted into daily summaries first, so the
+dashboard's totals keep covering those days, and the sessions only they
+reference go too. With --rollups the rollups, visitor sketches and daily
+summaries before the date are deleted as well, and those days disappear
+from the dashboard entirely. The prune is recorded in the audit log.
+
+Events are deleted for good; save a copy first with kaunta export if you
+may need them. Events the rollups haven't reached yet are kept until a
+later run. To keep a website pruned, set a retention policy instead
+(kaunta website retention).
+
+With --dry-run nothing is deleted; the command reports what would be.
+
+Examples:
+  kaunta prune --website example.com --before 2024-01-01 --dry-run
+  kaunta prune --website example.com --before 2024-01-01 --rollups`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runPrune(pruneWebsite, pruneBefore, pruneRollups, pruneDryRun, pruneFormat)
+	},
+}
+
+// Command flags
+var (
+	pruneWebsite string
+	pruneBefore  string
+	pruneRollups bool
+	pruneDryRun  bool
+	pruneFormat  string
+)
+
+var pruneFunc = retention.Prune
+
+// parsePruneBefore turns --before into the start of that day, which may not
+// be in the future
+func parsePruneBefore(arg string, now time.Time) (time.Time, error) {
+	if arg == "" {
+		return time.Time{}, errors.New("--before is required")
+	}
+	before, err := time.Parse("2006-01-02", arg)
+	if err != nil {
+		return time.Time{}, fmt.Errorf("invalid --before %q (use YYYY-MM-DD)", arg)
+	}
+	if before.After(now) {
+		return time.Time{}, errors.New("--before must not be in the future")
+	}
+	return before, nil
+}
+
+func runPrune(website, beforeArg string, rollups, dryRun bool, format string) error {
+	if website == "" {
+		return errors.New("--website is required")
+	}
+	if format != "text" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use text or json)", format)
+	}
+	before, err := parsePruneBefore(beforeArg, time.Now().UTC())
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	// Deleting months of events can take a while
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Minute)
+	defer cancel()
+
+	var byID *string
+	if _, err := uuid.Parse(website); err == nil {
+		byID = &website
+	}
+	detail, err := fetchWebsiteByDomain(ctx, website, byID)
+	if err != nil {
+		return err
+	}
+	websiteID, err := uuid.Parse(detail.WebsiteID)
+	if err != nil {
+		return err
+	}
+
+	result, err := pruneFunc(ctx, database.DB, websiteID, detail.Domain, before, rollups, dryRun, "cli")
+	if err != nil {
+		return fmt.Errorf("failed to prune %s: %w", detail.Domain, err)
+	}
+	if format == "json" {
+		return printJSON(result)
+	}
+
+	summary := fmt.Sprintf("%s before %s: %d events, %d sessions, %d rollup rows",
+		detail.Domain, before.Format("2006-01-02"), result.Events, result.Sessions, result.Rollups)
+	if dryRun {
+		fmt.Println("Would prune " + summary)
+		if !result.Empty() {
+			fmt.Printf("Run without --dry-run to delete them; kaunta export --website %s --to %s saves a copy first\n",
+				detail.Domain, before.AddDate(0, 0, -1).Format("2006-01-02"))
+		}
+		return nil
+	}
+
+	fmt.Println("✓ Pruned " + summary)
+	if result.Deferred {
+		fmt.Println("Some events were kept because the rollups haven't reached their days yet; run the same command again after the next rollup refresh")
+	}
+	if tables := vacuumTables(result); len(tables) > 0 {
+		fmt.Println()
+		fmt.Println("Deleted rows leave dead space behind until PostgreSQL vacuums the tables. Autovacuum gets")
+		fmt.Println("there eventually; to make the space reusable and refresh the planner statistics now, run:")
+		fmt.Printf("  psql \"$DATABASE_URL\" -c 'VACUUM (ANALYZE) %s'\n", strings.Join(tables, ", "))
+		fmt.Println("New events reuse the space, but the files only shrink with VACUUM FULL or pg_repack;")
+		fmt.Println("VACUUM FULL locks the table, so stop the server first.")
+	}
+	return nil
+}
+
+// vacuumTables lists the tables a prune deleted rows from
+func vacuumTables(result retention.Result) []string {
+	var tables []string
+	if result.Events > 0 {
+		tables = append(tables, "website_event")
+	}
+	if result.Sessions > 0 {
+		tables = append(tables, "session")
+	}
+	if result.Rollups > 0 {
+		tables = append(tables, "event_rollup", "visitor_sketch", "daily_summary")
+	}
+	return tables
+}
+
+func init() {
+	RootCmd.AddCommand(pruneCmd)
+
+	pruneCmd.Flags().StringVar(&pruneWebsite, "website", "", "Domain or ID of the website to prune")
+	pruneCmd.Flags().StringVar(&pruneBefore, "before", "", "Delete data before this day (YYYY-MM-DD, UTC)")
+	pruneCmd.Flags().BoolVar(&pruneRollups, "rollups", false, "Also delete rollups, visitor sketches and daily summaries")
+	pruneCmd.Flags().BoolVar(&pruneDryRun, "dry-run", false, "Report what would be deleted without deleting")
+	pruneCmd.Flags().StringVarP(&pruneFormat, "format", "f", "text", "Output format (text, json)")
+}
@@ -0,0 +1,117 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/retention"
+)
+
+func stubPrune(t *testing.T, result retention.Result) {
+	t.Helper()
+	originalFetch, originalPrune := fetchWebsiteByDomain, pruneFunc
+	t.Cleanup(func() { fetchWebsiteByDomain, pruneFunc = originalFetch, originalPrune })
+	fetchWebsiteByDomain = func(ctx context.Context, domain string, websiteID *string) (*WebsiteDetail, error) {
+		return &WebsiteDetail{WebsiteID: result.WebsiteID.String(), Domain: "example.com"}, nil
+	}
+	pruneFunc = func(ctx context.Context, db *sql.DB, websiteID uuid.UUID, domain string, before time.Time, rollups, dryRun bool, actor string) (retention.Result, error) {
+		assert.Equal(t, result.WebsiteID, websiteID)
+		assert.Equal(t, "example.com", domain)
+		assert.Equal(t, time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC), before)
+		assert.Equal(t, result.RollupCutoff != nil, rollups)
+		assert.Equal(t, result.DryRun, dryRun)
+		assert.Equal(t, "cli", actor)
+		return result, nil
+	}
+}
+
+func TestParsePruneBefore(t *testing.T) {
+	now := time.Date(2026, 6, 1, 12, 0, 0, 0, time.UTC)
+
+	before, err := parsePruneBefore("2026-06-01", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2026, 6, 1, 0, 0, 0, 0, time.UTC), before)
+
+	_, err = parsePruneBefore("2026-06-02", now)
+	assert.ErrorContains(t, err, "future")
+	_, err = parsePruneBefore("June 1st", now)
+	assert.ErrorContains(t, err, "YYYY-MM-DD")
+	_, err = parsePruneBefore("", now)
+	assert.ErrorContains(t, err, "required")
+}
+
+func TestRunPruneDryRun(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	before := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	stubPrune(t, retention.Result{
+		Policy:      retention.Policy{WebsiteID: uuid.New(), Domain: "example.com"},
+		EventCutoff: &before,
+		Events:      1200,
+		Sessions:    300,
+		DryRun:      true,
+	})
+
+	output, err := captureOutput(t, func() error {
+		return runPrune("example.com", "2024-01-01", false, true, "text")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "Would prune example.com before 2024-01-01: 1200 events, 300 sessions, 0 rollup rows")
+	assert.Contains(t, output, "kaunta export --website example.com --to 2023-12-31")
+	assert.NotContains(t, output, "VACUUM")
+}
+
+func TestRunPruneSuggestsVacuum(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	before := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	stubPrune(t, retention.Result{
+		Policy:       retention.Policy{WebsiteID: uuid.New(), Domain: "example.com"},
+		EventCutoff:  &before,
+		RollupCutoff: &before,
+		Events:       1200,
+		Rollups:      40,
+		Deferred:     true,
+	})
+
+	output, err := captureOutput(t, func() error {
+		return runPrune("example.com", "2024-01-01", true, false, "text")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "✓ Pruned example.com before 2024-01-01: 1200 events, 0 sessions, 40 rollup rows")
+	assert.Contains(t, output, "rollups haven't reached their days yet")
+	assert.Contains(t, output, "VACUUM (ANALYZE) website_event, event_rollup, visitor_sketch, daily_summary'")
+}
+
+func TestRunPruneJSON(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	before := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	stubPrune(t, retention.Result{
+		Policy:      retention.Policy{WebsiteID: uuid.New(), Domain: "example.com"},
+		EventCutoff: &before,
+		Events:      5,
+	})
+
+	output, err := captureOutput(t, func() error {
+		return runPrune("example.com", "2024-01-01", false, false, "json")
+	})
+	require.NoError(t, err)
+
+	var result map[string]any
+	require.NoError(t, json.Unmarshal([]byte(output), &result))
+	assert.Equal(t, float64(5), result["events"])
+	assert.Equal(t, "2024-01-01T00:00:00Z", result["event_cutoff"])
+}
+
+func TestRunPruneValidatesFlags(t *testing.T) {
+	assert.ErrorContains(t, runPrune("", "2024-01-01", false, false, "text"), "--website")
+	assert.ErrorContains(t, runPrune("example.com", "2024-01-01", false, false, "yaml"), "invalid format")
+}
@@ -0,0 +1,212 @@
+package cli
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"os"
+	"strings"
+	"text/tabwriter"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var publicTokenCmd = &cobra.Command{
+	Use:   "public-token",
+	Short: "Manage public read tokens for counters",
+	Long: `Manage public read tokens for a website.
+
+A public token exposes only the metrics it was created with at
+GET /api/public/counter/:token, without authentication and with CORS open to
+any origin, so pages can show live counters without access to the stats API.
+
+Available metrics:
+  online          - Visitors in the last 5 minutes
+  pageviews       - All-time pageviews
+  visitors        - All-time unique visitors
+  pageviews_today - Pageviews since midnight (server time)
+  visitors_today  - Unique visitors since midnight (server time)`,
+}
+
+var publicTokenCreateCmd = &cobra.Command{
+	Use:   "create <domain> --metrics <metrics-csv> [--name <name>]",
+	Short: "Create a public token",
+	Long: `Create a public token exposing the given metrics of a website.
+
+Examples:
+  kaunta website public-token create example.com --metrics pageviews
+  kaunta website public-token create example.com --metrics online,visitors_today --name "Footer counter"`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runPublicTokenCreate(args[0], publicTokenMetrics, publicTokenName)
+	},
+}
+
+var publicTokenListCmd = &cobra.Command{
+	Use:   "list <domain> [--format table|json]",
+	Short: "List a website's public tokens",
+	Args:  cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runPublicTokenList(args[0], publicTokenFormat)
+	},
+}
+
+var publicTokenRevokeCmd = &cobra.Command{
+	Use:   "revoke <domain> <token>",
+	Short: "Revoke a public token",
+	Long: `Revoke a public token. Counters using it return 404 immediately.
+
+Example:
+  kaunta website public-token revoke example.com kaunta_pub_3f9a...`,
+	Args: cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runPublicTokenRevoke(args[0], args[1])
+	},
+}
+
+// Command flags
+var (
+	publicTokenMetrics string
+	publicTokenName    string
+	publicTokenFormat  string
+)
+
+// publicTokenWebsiteID resolves a domain to its website ID
+func publicTokenWebsiteID(ctx context.Context, domain string) (uuid.UUID, error) {
+	website, err := GetWebsiteByDomain(ctx, domain, nil)
+	if err != nil {
+		return uuid.UUID{}, fmt.Errorf("website not found: %w", err)
+	}
+	websiteID, err := uuid.Parse(website.WebsiteID)
+	if err != nil {
+		return uuid.UUID{}, fmt.Errorf("invalid website ID: %w", err)
+	}
+	return websiteID, nil
+}
+
+func runPublicTokenCreate(domain, metricsCSV, name string) error {
+	var metrics []string
+	for m := range strings.SplitSeq(metricsCSV, ",") {
+		if m = strings.TrimSpace(m); m != "" {
+			metrics = append(metrics, m)
+		}
+	}
+	if _, err := models.ValidatePublicTokenMetrics(metrics); err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+
+	var namePtr *string
+	if name != "" {
+		namePtr = &name
+	}
+	token, err := models.CreatePublicToken(ctx, database.DB, websiteID, nil, namePtr, metrics)
+	if err != nil {
+		return fmt.Errorf("failed to create public token: %w", err)
+	}
+
+	fmt.Printf("Public token created for '%s'\n\n", domain)
+	fmt.Printf("Token:   %s\n", token.Token)
+	fmt.Printf("Metrics: %s\n", strings.Join(token.Metrics, ", "))
+	fmt.Println()
+	fmt.Println("Fetch the counter from any page with:")
+	fmt.Println()
+	fmt.Printf("  fetch('https://your-kaunta-host/api/public/counter/%s').then(r => r.json())\n", token.Token)
+	fmt.Println()
+	return nil
+}
+
+func runPublicTokenList(domain, format string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	tokens, err := models.ListPublicTokens(ctx, database.DB, websiteID)
+	if err != nil {
+		return fmt.Errorf("failed to list public tokens: %w", err)
+	}
+
+	if format == "json" {
+		encoder := json.NewEncoder(os.Stdout)
+		encoder.SetIndent("", "  ")
+		return encoder.Encode(tokens)
+	}
+
+	if len(tokens) == 0 {
+		fmt.Printf("No public tokens found for website '%s'\n", domain)
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	_, _ = fmt.Fprintln(w, "TOKEN\tNAME\tMETRICS\tCREATED")
+	for _, token := range tokens {
+		name := "-"
+		if token.Name != nil {
+			name = *token.Name
+		}
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\t%s\n",
+			token.Token, name, strings.Join(token.Metrics, ","), token.CreatedAt.Format("2006-01-02 15:04"))
+	}
+	return w.Flush()
+}
+
+func runPublicTokenRevoke(domain, token string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	if err := models.DeletePublicToken(ctx, database.DB, websiteID, token); err != nil {
+		return fmt.Errorf("failed to revoke public token: %w", err)
+	}
+
+	fmt.Printf("Public token revoked for '%s'\n", domain)
+	return nil
+}
+
+func init() {
+	websiteCmd.AddCommand(publicTokenCmd)
+	publicTokenCmd.AddCommand(publicTokenCreateCmd)
+	publicTokenCmd.AddCommand(publicTokenListCmd)
+	publicTokenCmd.AddCommand(publicTokenRevokeCmd)
+
+	publicTokenCreateCmd.Flags().StringVarP(&publicTokenMetrics, "metrics", "m", "", "Comma-separated metrics to expose (required)")
+	publicTokenCreateCmd.Flags().StringVarP(&publicTokenName, "name", "n", "", "Label for the token")
+	_ = publicTokenCreateCmd.MarkFlagRequired("metrics")
+	publicTokenListCmd.Flags().StringVarP(&publicTokenFormat, "format", "f", "table", "Output format (table, json)")
+}
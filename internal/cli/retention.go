@@ -0,0 +1,279 @@
+package cli
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"os"
+	"sort"
+	"strings"
+	"text/tabwriter"
+	"time"
+
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/retention"
+)
+
+var websiteRetentionCmd = &cobra.Command{
+	Use:   "retention <domain> [--events <days>] [--rollups <days>]",
+	Short: "Show or set how long a website's data is kept",
+	Long: `Show or set a website's retention policy.
+
+--events limits how many days of raw events (and the sessions only they
+reference) are kept. It can only shorten the server-wide retention_days.
+--rollups limits how many days of hourly and daily rollups are kept; by
+default they are kept forever so long-range reports keep working.
+
+Pass 0 to go back to the default. Expired data is purged once a day by the
+server; preview a purge with 'kaunta website purge --dry-run'.
+
+Examples:
+  kaunta website retention example.com
+  kaunta website retention example.com --events 180
+  kaunta website retention example.com --events 0 --rollups 730`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		var events, rollups *int
+		if cmd.Flags().Changed("events") {
+			events = &retentionEvents
+		}
+		if cmd.Flags().Changed("rollups") {
+			rollups = &retentionRollups
+		}
+		return runWebsiteRetention(args[0], events, rollups)
+	},
+}
+
+var websitePurgeCmd = &cobra.Command{
+	Use:   "purge [--dry-run] [--format table|json]",
+	Short: "Apply website retention policies now",
+	Long: `Delete the data every website's retention policy has expired, the same
+way the server does daily. Each purge is recorded in the audit log.
+
+With --dry-run nothing is deleted; the command reports what would be.
+
+Examples:
+  kaunta website purge --dry-run
+  kaunta website purge`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runWebsitePurge(purgeDryRun, purgeFormat)
+	},
+}
+
+var auditLogCmd = &cobra.Command{
+	Use:   "audit-log [--limit <n>] [--format table|json]",
+	Short: "Show recent destructive maintenance such as retention purges",
+	Args:  cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runAuditLog(auditLogLimit, auditLogFormat)
+	},
+}
+
+// Command flags
+var (
+	retentionEvents  int
+	retentionRollups int
+	purgeDryRun      bool
+	purgeFormat      string
+	auditLogLimit    int
+	auditLogFormat   string
+)
+
+var (
+	setWebsiteRetentionFunc = SetWebsiteRetention
+	runRetentionFunc        = retention.Run
+	listAuditLogFunc        = models.ListAuditLog
+)
+
+func runWebsiteRetention(domain string, events, rollups *int) error {
+	for _, days := range []*int{events, rollups} {
+		if days != nil && *days < 0 {
+			return fmt.Errorf("retention must be a number of days, or 0 for the default")
+		}
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	website, err := fetchWebsiteByDomain(ctx, domain, nil)
+	if err != nil {
+		return err
+	}
+
+	if events != nil || rollups != nil {
+		eventDays, rollupDays := website.EventRetentionDays, website.RollupRetentionDays
+		if events != nil {
+			eventDays = positiveDays(*events)
+		}
+		if rollups != nil {
+			rollupDays = positiveDays(*rollups)
+		}
+		if website, err = setWebsiteRetentionFunc(ctx, domain, eventDays, rollupDays); err != nil {
+			return err
+		}
+		fmt.Println("Retention updated.")
+	}
+
+	fmt.Printf("Website:  %s\n", website.Domain)
+	fmt.Printf("Events:   %s\n", describeRetention(website.EventRetentionDays, "server default"))
+	fmt.Printf("Rollups:  %s\n", describeRetention(website.RollupRetentionDays, "forever"))
+	return nil
+}
+
+// positiveDays maps 0 (back to the default) to nil
+func positiveDays(days int) *int {
+	if days == 0 {
+		return nil
+	}
+	return &days
+}
+
+func describeRetention(days *int, fallback string) string {
+	if days == nil {
+		return fallback
+	}
+	return fmt.Sprintf("%d days", *days)
+}
+
+func runWebsitePurge(dryRun bool, format string) error {
+	if format != "table" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use table or json)", format)
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	// Deleting months of events can take a while
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Minute)
+	defer cancel()
+
+	results, err := runRetentionFunc(ctx, database.DB, dryRun, "cli")
+	if outErr := outputPurgeResults(results, format); outErr != nil {
+		return outErr
+	}
+	return err
+}
+
+func outputPurgeResults(results []retention.Result, format string) error {
+	if format == "json" {
+		data, err := json.MarshalIndent(results, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	}
+
+	if len(results) == 0 {
+		fmt.Println("No website has a retention policy")
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	defer func() { _ = w.Flush() }()
+
+	verb := "DELETED"
+	if results[0].DryRun {
+		verb = "WOULD DELETE"
+	}
+	_, _ = fmt.Fprintf(w, "WEBSITE\tEVENTS BEFORE\tROLLUPS BEFORE\t%s\n", verb)
+	for _, r := range results {
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\t%d events, %d sessions, %d rollup rows\n",
+			r.Domain, formatCutoff(r.EventCutoff), formatCutoff(r.RollupCutoff), r.Events, r.Sessions, r.Rollups)
+	}
+	return nil
+}
+
+func formatCutoff(cutoff *time.Time) string {
+	if cutoff == nil {
+		return "-"
+	}
+	return cutoff.Format("2006-01-02 15:04")
+}
+
+func runAuditLog(limit int, format string) error {
+	if format != "table" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use table or json)", format)
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	entries, err := listAuditLogFunc(ctx, database.DB, limit)
+	if err != nil {
+		return fmt.Errorf("failed to read audit log: %w", err)
+	}
+
+	if format == "json" {
+		data, err := json.MarshalIndent(entries, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	}
+
+	if len(entries) == 0 {
+		fmt.Println("The audit log is empty")
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	defer func() { _ = w.Flush() }()
+
+	_, _ = fmt.Fprintln(w, "TIME\tACTION\tACTOR\tDETAILS")
+	for _, e := range entries {
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\t%s\n",
+			e.CreatedAt.Format("2006-01-02 15:04:05"), e.Action, e.Actor, formatAuditDetails(e.Details))
+	}
+	return nil
+}
+
+// formatAuditDetails renders details as sorted key=value pairs
+func formatAuditDetails(details map[string]any) string {
+	keys := make([]string, 0, len(details))
+	for k, v := range details {
+		if v != nil {
+			keys = append(keys, k)
+		}
+	}
+	sort.Strings(keys)
+
+	parts := make([]string, len(keys))
+	for i, k := range keys {
+		parts[i] = fmt.Sprintf("%s=%v", k, details[k])
+	}
+	return strings.Join(parts, " ")
+}
+
+func init() {
+	websiteCmd.AddCommand(websiteRetentionCmd)
+	websiteCmd.AddCommand(websitePurgeCmd)
+	RootCmd.AddCommand(auditLogCmd)
+
+	websiteRetentionCmd.Flags().IntVar(&retentionEvents, "events", 0, "Days of raw events to keep (0 for the server default)")
+	websiteRetentionCmd.Flags().IntVar(&retentionRollups, "rollups", 0, "Days of rollups to keep (0 keeps them forever)")
+	websitePurgeCmd.Flags().BoolVar(&purgeDryRun, "dry-run", false, "Report what would be deleted without deleting")
+	websitePurgeCmd.Flags().StringVarP(&purgeFormat, "format", "f", "table", "Output format (table, json)")
+	auditLogCmd.Flags().IntVarP(&auditLogLimit, "limit", "n", 50, "Number of entries to show")
+	auditLogCmd.Flags().StringVarP(&auditLogFormat, "format", "f", "table", "Output format (table, json)")
+}
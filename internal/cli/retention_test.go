@@ -0,0 +1,77 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/retention"
+)
+
+func TestRunWebsiteRetention(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+
+	current := 365
+	originalFetch, originalSet := fetchWebsiteByDomain, setWebsiteRetentionFunc
+	t.Cleanup(func() { fetchWebsiteByDomain, setWebsiteRetentionFunc = originalFetch, originalSet })
+	fetchWebsiteByDomain = func(ctx context.Context, domain string, websiteID *string) (*WebsiteDetail, error) {
+		return &WebsiteDetail{Domain: domain, RollupRetentionDays: &current}, nil
+	}
+	setWebsiteRetentionFunc = func(ctx context.Context, domain string, eventDays, rollupDays *int) (*WebsiteDetail, error) {
+		require.NotNil(t, eventDays)
+		assert.Equal(t, 180, *eventDays)
+		assert.Nil(t, rollupDays) // 0 clears it
+		return &WebsiteDetail{Domain: domain, EventRetentionDays: eventDays}, nil
+	}
+
+	events, rollups := 180, 0
+	output, err := captureOutput(t, func() error {
+		return runWebsiteRetention("example.com", &events, &rollups)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "Events:   180 days")
+	assert.Contains(t, output, "Rollups:  forever")
+
+	negative := -1
+	err = runWebsiteRetention("example.com", &negative, nil)
+	assert.Error(t, err)
+}
+
+func TestRunWebsitePurgeDryRun(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+
+	original := runRetentionFunc
+	t.Cleanup(func() { runRetentionFunc = original })
+	cutoff := time.Date(2026, 1, 1, 0, 0, 0, 0, time.UTC)
+	runRetentionFunc = func(ctx context.Context, db *sql.DB, dryRun bool, actor string) ([]retention.Result, error) {
+		assert.True(t, dryRun)
+		assert.Equal(t, "cli", actor)
+		return []retention.Result{{
+			Policy:      retention.Policy{Domain: "example.com", EventDays: 180},
+			EventCutoff: &cutoff,
+			Events:      1200,
+			Sessions:    300,
+			DryRun:      true,
+		}}, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runWebsitePurge(true, "table")
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "WOULD DELETE")
+	assert.Contains(t, output, "1200 events, 300 sessions, 0 rollup rows")
+
+	assert.Error(t, runWebsitePurge(true, "xml"))
+}
+
+func TestFormatAuditDetails(t *testing.T) {
+	details := map[string]any{"events": 12, "domain": "example.com", "rollup_cutoff": nil}
+	assert.Equal(t, "domain=example.com events=12", formatAuditDetails(details))
+}
@@ -18,6 +18,7 @@ import (
 	"path"
 	"strconv"
 	"strings"
+	"syscall"
 	"time"
 
 	"github.com/go-chi/chi/v5"
@@ -32,8 +33,12 @@ import (
 	"github.com/seuros/kaunta/internal/geoip"
 	"github.com/seuros/kaunta/internal/handlers"
 	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/mailer"
 	appmiddleware "github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
 	"github.com/seuros/kaunta/internal/realtime"
+	"github.com/seuros/kaunta/internal/redis"
+	"github.com/seuros/kaunta/internal/webhooks"
 )
 
 var Version string
@@ -52,6 +57,9 @@ It provides real-time analytics and a clean dashboard interface.`,
 	Version: Version,
 	// Load config from file/env/flags (runs before all commands)
 	PersistentPreRunE: func(cmd *cobra.Command, args []string) error {
+		if err := applyOutputMode(cmd); err != nil {
+			return err
+		}
 		cfg, err := config.LoadWithOverrides(databaseURL, port, dataDir)
 		if err != nil {
 			logging.L().Warn("failed to load config overrides", slog.Any("error", err))
@@ -69,6 +77,16 @@ It provides real-time analytics and a clean dashboard interface.`,
 			_ = os.Setenv("DATA_DIR", cfg.DataDir)
 		}
 		_ = os.Setenv("SECURE_COOKIES", strconv.FormatBool(cfg.SecureCookies))
+		database.SetPoolSettings(database.PoolSettings{
+			Size:             cfg.DatabasePool.Size,
+			AcquireTimeout:   cfg.DatabasePool.AcquireTimeout,
+			StatementTimeout: cfg.DatabasePool.StatementTimeout,
+			IdleLifetime:     cfg.DatabasePool.IdleLifetime,
+		})
+		database.SetStatsQuerySettings(database.StatsQuerySettings{
+			Timeout:       cfg.StatsQueries.Timeout,
+			SlowThreshold: cfg.StatsQueries.SlowThreshold,
+		})
 		return nil
 	},
 	// Default to serve command if no subcommand provided
@@ -114,6 +132,7 @@ func Execute(
 
 	// Hide self-upgrade flags in dev builds (after Version is set)
 	hideSelfUpgradeFlagsIfDevBuild()
+	registerFormatCompletions(RootCmd)
 
 	return RootCmd.Execute()
 }
@@ -251,20 +270,107 @@ func serveAnalytics(
 	ctx, cancel := context.WithCancel(context.Background())
 	defer cancel()
 
+	// Sync trusted origins from config to database
+	cfg, err := config.Load()
+	if err != nil {
+		logging.L().Warn("failed to load config for trusted origins", slog.Any("error", err))
+	} else if len(cfg.TrustedOrigins) > 0 {
+		syncTrustedOrigins(cfg.TrustedOrigins)
+	}
+
 	realtimeHub := realtime.NewHub()
+	handlers.SetRealtimeHub(realtimeHub)
 	logging.L().Info("starting realtime websocket listener")
-	if err := realtime.StartListener(ctx, databaseURL, realtimeHub); err != nil {
+	if redisClient := connectRedis(cfg); redisClient != nil {
+		defer func() { _ = redisClient.Close() }()
+		realtime.UseRedis(redisClient)
+		realtime.StartRedisListener(ctx, redisClient, realtimeHub)
+		logging.L().Info("realtime websocket listener started on redis")
+	} else if err := realtime.StartListener(ctx, databaseURL, realtimeHub); err != nil {
 		logging.L().Error("failed to start realtime listener", slog.Any("error", err))
 	} else {
 		logging.L().Info("realtime websocket listener started successfully")
 	}
+	go handlers.SyncStatsCache(ctx, realtimeHub)
+	if cfg != nil {
+		appmiddleware.SetTwoFactorPolicy(cfg.Require2FA)
+		handlers.SetCookiePolicy(cfg.Cookies.SameSite, cfg.Cookies.Domain)
+	}
 
-	// Sync trusted origins from config to database
-	cfg, err := config.Load()
+	// Send stats and report queries to the read replica while it is healthy
+	if cfg != nil && cfg.Replica.URL != "" {
+		if err := database.ConnectReplica(cfg.Replica.URL); err != nil {
+			logging.L().Warn("read replica unavailable, stats queries use the primary", slog.Any("error", err))
+		} else {
+			defer func() {
+				if err := database.CloseReplica(); err != nil {
+					logging.L().Warn("error closing read replica", slog.Any("error", err))
+				}
+			}()
+			replicaMonitor := database.NewReplicaMonitor(cfg.Replica.MaxLag)
+			replicaMonitor.Start()
+			defer replicaMonitor.Stop()
+		}
+	}
+
+	// Confine stats queries to the caller's websites with row-level security.
+	// Serving without it when it was asked for would defeat the point.
+	if cfg != nil && cfg.StatsQueries.TenantIsolation {
+		protected, err := database.EnableTenantIsolation(ctx, database.DB)
+		if err != nil {
+			logging.Fatal("failed to enable tenant isolation", slog.Any("error", err))
+		}
+		logging.L().Info("tenant isolation enabled", slog.Int("tables_protected", protected))
+	}
+
+	// Send account and alert emails
+	var smtpConfig config.SMTPConfig
+	var publicURL string
+	if cfg != nil {
+		smtpConfig = cfg.SMTP
+		publicURL = cfg.PublicURL
+	}
+	handlers.SetMailer(mailer.New(smtpConfig), publicURL)
+
+	// Deliver queued webhook payloads in the background
+	webhookDispatcher := webhooks.NewDispatcher(database.DB)
+	webhookDispatcher.Start()
+	defer webhookDispatcher.Stop()
+
+	// Point out TimescaleDB when the server has it and events don't use it yet
+	if status, err := database.GetTimescaleStatus(ctx, database.DB); err == nil && status.Available && !status.Hypertable {
+		logging.L().Info("TimescaleDB is available; run 'kaunta timescale enable' to store events in a compressed hypertable")
+	}
+
+	// Export closed months to object storage; expired partitions then wait
+	// for their month to be archived
+	archiver, err := newArchiver(cfg)
 	if err != nil {
-		logging.L().Warn("failed to load config for trusted origins", slog.Any("error", err))
-	} else if len(cfg.TrustedOrigins) > 0 {
-		syncTrustedOrigins(cfg.TrustedOrigins)
+		logging.L().Warn("event archiving disabled", slog.Any("error", err))
+	}
+
+	// Run partition maintenance, retention, rollups, alerts and the other
+	// background jobs; kaunta jobs list shows when they last ran
+	jobScheduler := database.NewJobScheduler(database.DB, serverJobs(cfg, archiver))
+	jobScheduler.Start()
+	defer jobScheduler.Stop()
+
+	// Write accepted hits in batches instead of one INSERT per request. The
+	// buffer is flushed on shutdown, after the server stops accepting hits.
+	if cfg != nil && cfg.Ingest.FlushInterval > 0 {
+		eventBuffer := handlers.NewEventBuffer(database.DB, handlers.EventBufferSettings{
+			FlushInterval: cfg.Ingest.FlushInterval,
+			BatchSize:     cfg.Ingest.BatchSize,
+			MaxPending:    cfg.Ingest.MaxPending,
+		})
+		eventBuffer.Start()
+		handlers.SetEventBuffer(eventBuffer)
+		defer eventBuffer.Stop()
+	}
+	// Refuse hits beyond this many at once so a slow database can't pile up
+	// requests waiting for connections
+	if cfg != nil {
+		handlers.SetIngestConcurrency(cfg.Ingest.MaxInFlight)
 	}
 
 	// Ensure self website exists for dogfooding (creates if missing for existing installations)
@@ -298,9 +404,9 @@ func serveAnalytics(
 		JSON:            os.Getenv("KAUNTA_LOG_FORMAT") == "json" || os.Getenv("KAUNTA_LOG_FORMAT") == "structured",
 		LogLevel:        httplog.LevelByName(os.Getenv("KAUNTA_LOG_LEVEL")),
 		Concise:         true,
-		QuietDownRoutes: []string{"/up", "/health"},
+		QuietDownRoutes: []string{"/up", "/health", "/metrics"},
 	})
-	r.Use(httplog.RequestLogger(requestLogger, []string{"/up", "/health"}))
+	r.Use(httplog.RequestLogger(requestLogger, []string{"/up", "/health", "/metrics"}))
 
 	r.Use(addVersionHeader())
 
@@ -324,14 +430,50 @@ func serveAnalytics(
 
 	realtimeHub.SetAllowedOrigins(trustedOriginURLs)
 
+	// Cross-origin API access; those origins may also post with a CSRF token
+	var corsOrigins []string
+	if cfg != nil {
+		corsOrigins = cfg.CORSOrigins
+		for _, warning := range cfg.PolicyWarnings(trustedOrigins) {
+			logging.L().Warn("cookie and origin settings are inconsistent", slog.String("problem", warning))
+		}
+	}
+	trustedOriginURLs = append(trustedOriginURLs, corsOrigins...)
+	r.Use(appmiddleware.CORS(corsOrigins))
+
 	// Determine if we should use secure cookies (HTTPS required)
 	secureEnabled := secureCookiesEnabled(cfg)
 
+	csrfMode := config.CSRFModeCookie
+	if cfg != nil && cfg.CSRFMode == config.CSRFModeHeader {
+		csrfMode = config.CSRFModeHeader
+	}
 	r.Use(csrfMiddleware(csrfOptions{
 		Secure:        secureEnabled,
 		TrustedOrigin: trustedOriginURLs,
+		Mode:          csrfMode,
 	}))
 
+	// Sign-in by an authenticating reverse proxy (Authelia, authentik, ...)
+	if cfg != nil && cfg.ProxyAuth.Enabled() {
+		networks, err := cfg.ProxyAuth.Networks()
+		if err != nil {
+			logging.Fatal("invalid proxy auth settings", slog.Any("error", err))
+		}
+		r.Use(appmiddleware.ProxyAuth(appmiddleware.ProxyAuthSettings{
+			Header:       cfg.ProxyAuth.Header,
+			EmailHeader:  cfg.ProxyAuth.EmailHeader,
+			NameHeader:   cfg.ProxyAuth.NameHeader,
+			GroupsHeader: cfg.ProxyAuth.GroupsHeader,
+			Trusted:      networks,
+		}, handlers.ProxySignIn(cfg.ProxyAuth.AutoCreate)))
+		logging.L().Info("proxy header sign-in enabled",
+			slog.String("header", cfg.ProxyAuth.Header),
+			slog.Any("trusted_proxies", cfg.ProxyAuth.TrustedProxies))
+	} else if cfg != nil && cfg.ProxyAuth.Header != "" {
+		logging.L().Warn("proxy_auth.header is set but proxy_auth.trusted_proxies is empty; proxy header sign-in is off")
+	}
+
 	// Static assets - serve embedded JS/CSS files
 	r.Get("/ws/realtime", realtimeHub.Handler())
 	r.Get("/assets/vendor/{filename:.+}", vendorAssetHandler(vendorJS, vendorCSS))
@@ -348,12 +490,33 @@ func serveAnalytics(
 	r.Get("/health", handleHealth)
 	r.Get("/up", upHandler)
 	r.Get("/api/version", handleVersion)
+	if cfg != nil && cfg.MetricsEnabled {
+		r.Get("/metrics", handlers.HandleMetrics)
+	}
 
-	// Tracker script
-	trackerHandler := handleTrackerScript(trackerScript)
+	// OpenAPI document and interactive explorer (public; the explorer calls
+	// endpoints with the visitor's own session or API key)
+	r.Get("/api/openapi.json", handlers.HandleOpenAPI(Version))
+	r.Get("/api/docs", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/api_docs", "views/layouts/base", map[string]any{
+			"Title":   "API - Kaunta",
+			"Version": Version,
+		}); err != nil {
+			http.Error(w, "Failed to render API docs", http.StatusInternalServerError)
+		}
+	})
+
+	// Tracker script: /k.js always serves the running release's script, and
+	// /k.v<version>.js the same bytes under a URL that never changes, so
+	// sites can pin it with a Subresource Integrity hash
+	trackerHandler := handleTrackerScript(trackerScript, trackerCacheControl)
 	r.Get("/k.js", trackerHandler.ServeHTTP)
 	r.Get("/kaunta.js", trackerHandler.ServeHTTP)
 	r.Get("/script.js", trackerHandler.ServeHTTP)
+	if Version != "" {
+		r.Get(handlers.VersionedTrackerPath(Version), handleTrackerScript(trackerScript, versionedTrackerCacheControl).ServeHTTP)
+	}
+	r.Get("/api/tracker", handlers.HandleTrackerInfo(trackerScript, Version))
 
 	// Static assets (favicon, etc.) from embedded FS
 	assetsSubFS, err := fs.Sub(assetsFS.(embed.FS), "assets")
@@ -378,7 +541,7 @@ func serveAnalytics(
 
 	// Tracking API (Umami-compatible)
 	r.Options("/api/send", optionsOK)
-	r.Post("/api/send", handlers.HandleTracking)
+	r.With(handlers.ShedIngestOverload).Post("/api/send", handlers.HandleTracking)
 
 	// Pixel tracking (for email, RSS, no-JS environments)
 	r.Get("/p/{id}.gif", handlers.HandlePixelTracking)
@@ -387,11 +550,11 @@ func serveAnalytics(
 	// Uses API key authentication instead of session-based auth
 	r.Options("/api/ingest", optionsOK)
 	r.Options("/api/ingest/batch", optionsOK)
-	r.With(appmiddleware.APIKeyAuth).Post("/api/ingest", handlers.HandleIngest)
-	r.With(appmiddleware.APIKeyAuth).Post("/api/ingest/batch", handlers.HandleIngestBatch)
+	r.With(handlers.ShedIngestOverload, appmiddleware.APIKeyAuth).Post("/api/ingest", handlers.HandleIngest)
+	r.With(handlers.ShedIngestOverload, appmiddleware.APIKeyAuth).Post("/api/ingest/batch", handlers.HandleIngestBatch)
 
 	// Stats API (Plausible-inspired) - protected
-	r.With(appmiddleware.Auth).Get("/api/stats/realtime/{website_id}", handlers.HandleCurrentVisitors)
+	r.With(appmiddleware.Auth, appmiddleware.RequireWebsiteRole(models.RoleViewer)).Get("/api/stats/realtime/{website_id}", handlers.HandleCurrentVisitors)
 
 	// Auth API endpoints (public)
 	// Rate limiter for login endpoint (5 requests per minute per direct peer IP)
@@ -409,6 +572,8 @@ func serveAnalytics(
 	)
 	r.With(loginLimiter.Handler).Post("/api/auth/login", handlers.HandleLogin)
 	r.With(loginLimiter.Handler).Get("/api/auth/login", handlers.HandleLoginSSE)
+	r.With(loginLimiter.Handler).Post("/api/auth/login/2fa", handlers.HandleLoginTwoFactor)
+	r.With(loginLimiter.Handler).Get("/api/auth/login/2fa", handlers.HandleLoginTwoFactorSSE)
 
 	// Login page (public)
 	r.Get("/login", func(w http.ResponseWriter, r *http.Request) {
@@ -419,34 +584,225 @@ func serveAnalytics(
 		}
 	})
 
+	// Invitation page (public; offers signup, or accepting when signed in)
+	r.With(appmiddleware.OptionalAuth).Get("/invite/{token}", func(w http.ResponseWriter, r *http.Request) {
+		token := chi.URLParam(r, "token")
+		username := ""
+		if user := appmiddleware.GetUser(r); user != nil {
+			username = user.Username
+		}
+		if err := render(w, "views/invite", "views/layouts/base", map[string]any{
+			"Title":      "Invitation - Kaunta",
+			"Token":      token,
+			"Invitation": handlers.LookupInvitation(r, token),
+			"Username":   username,
+		}); err != nil {
+			http.Error(w, "Failed to render invitation view", http.StatusInternalServerError)
+		}
+	})
+	r.With(loginLimiter.Handler).Post("/api/invitations/{token}/signup", handlers.HandleInvitationSignup)
+
+	// Exclude-my-visits helper (public; it only sets a cookie for this browser)
+	r.Get("/exclude", func(w http.ResponseWriter, r *http.Request) {
+		site := r.URL.Query().Get("site")
+		if err := render(w, "views/exclude", "views/layouts/base", map[string]any{
+			"Title":      "Exclude My Visits - Kaunta",
+			"Excluded":   handlers.IsExcluded(r),
+			"Site":       site,
+			"ExcludeURL": handlers.ExcludeSiteURL(site, true),
+			"IncludeURL": handlers.ExcludeSiteURL(site, false),
+		}); err != nil {
+			http.Error(w, "Failed to render exclude view", http.StatusInternalServerError)
+		}
+	})
+	r.Post("/exclude", handlers.HandleExclude)
+
+	// Password reset pages (public)
+	r.Get("/forgot-password", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/forgot_password", "views/layouts/base", map[string]any{
+			"Title": "Forgot Password - Kaunta",
+		}); err != nil {
+			http.Error(w, "Failed to render forgot password view", http.StatusInternalServerError)
+		}
+	})
+	r.Get("/reset-password/{token}", func(w http.ResponseWriter, r *http.Request) {
+		token := chi.URLParam(r, "token")
+		if err := render(w, "views/reset_password", "views/layouts/base", map[string]any{
+			"Title":    "Reset Password - Kaunta",
+			"Token":    token,
+			"Username": handlers.LookupPasswordReset(r, token),
+		}); err != nil {
+			http.Error(w, "Failed to render reset password view", http.StatusInternalServerError)
+		}
+	})
+	r.With(loginLimiter.Handler).Post("/api/auth/forgot-password", handlers.HandleForgotPassword)
+	r.With(loginLimiter.Handler).Post("/api/auth/reset-password/{token}", handlers.HandleResetPassword)
+
+	// Email change confirmation link (public; the token identifies the user)
+	r.Get("/confirm-email/{token}", handlers.HandleConfirmEmail)
+
 	// Dashboard UI (protected)
 	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard", func(w http.ResponseWriter, r *http.Request) {
 		if err := render(w, "views/dashboard/home", "views/layouts/dashboard", map[string]any{
 			"Title":         "Dashboard",
 			"Version":       Version,
 			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
 		}); err != nil {
 			http.Error(w, "Failed to render dashboard", http.StatusInternalServerError)
 		}
 	})
 
+	// All-websites overview (protected, landing page after login)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/overview", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/overview", "views/layouts/dashboard", map[string]any{
+			"Title":         "Overview",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render overview", http.StatusInternalServerError)
+		}
+	})
+
+	// Website comparison UI (protected)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/compare", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/compare", "views/layouts/dashboard", map[string]any{
+			"Title":         "Compare Websites",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render comparison", http.StatusInternalServerError)
+		}
+	})
+
 	// Map UI (protected)
 	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/map", func(w http.ResponseWriter, r *http.Request) {
 		if err := render(w, "views/dashboard/map", "views/layouts/dashboard", map[string]any{
 			"Title":         "Map",
 			"Version":       Version,
 			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
 		}); err != nil {
 			http.Error(w, "Failed to render map view", http.StatusInternalServerError)
 		}
 	})
 
+	// Custom dashboards UI (protected)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/custom", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/custom", "views/layouts/dashboard", map[string]any{
+			"Title":         "Custom Dashboards",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render custom dashboards view", http.StatusInternalServerError)
+		}
+	})
+
+	// Traffic alerts UI (protected)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/alerts", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/alerts", "views/layouts/dashboard", map[string]any{
+			"Title":         "Alerts",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render alerts view", http.StatusInternalServerError)
+		}
+	})
+
+	// Outgoing webhooks UI (protected)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/webhooks", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/webhooks", "views/layouts/dashboard", map[string]any{
+			"Title":         "Webhooks",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render webhooks view", http.StatusInternalServerError)
+		}
+	})
+
+	// API keys UI (protected)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/api-keys", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/api_keys", "views/layouts/dashboard", map[string]any{
+			"Title":         "API Keys",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render API keys view", http.StatusInternalServerError)
+		}
+	})
+
+	// Account security UI (protected; also where users who must enroll in
+	// 2FA are sent)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/security", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/security", "views/layouts/dashboard", map[string]any{
+			"Title":         "Security",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+			"EmailStatus":   r.URL.Query().Get("email"),
+		}); err != nil {
+			http.Error(w, "Failed to render security view", http.StatusInternalServerError)
+		}
+	})
+
+	// Live visitor map UI (protected)
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/live", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/live", "views/layouts/dashboard", map[string]any{
+			"Title":         "Live Map",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render live map view", http.StatusInternalServerError)
+		}
+	})
+
+	// Shared custom dashboard (public, only renders dashboards with is_public set)
+	r.Get("/share/dashboards/{id}", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/shared_dashboard", "views/layouts/base", map[string]any{
+			"Title":       "Shared Dashboard",
+			"Version":     Version,
+			"DashboardID": chi.URLParam(r, "id"),
+		}); err != nil {
+			http.Error(w, "Failed to render shared dashboard", http.StatusInternalServerError)
+		}
+	})
+
+	// TV / kiosk view (public, the secret token in the URL is the credential)
+	r.Get("/kiosk/{token}", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/kiosk", "views/layouts/base", map[string]any{
+			"Title":   "Kaunta Kiosk",
+			"Version": Version,
+			"Token":   chi.URLParam(r, "token"),
+		}); err != nil {
+			http.Error(w, "Failed to render kiosk view", http.StatusInternalServerError)
+		}
+	})
+
 	// Campaigns UI (protected)
 	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/campaigns", func(w http.ResponseWriter, r *http.Request) {
 		if err := render(w, "views/dashboard/campaigns", "views/layouts/dashboard", map[string]any{
 			"Title":         "Campaigns",
 			"Version":       Version,
 			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
 		}); err != nil {
 			http.Error(w, "Failed to render campaigns view", http.StatusInternalServerError)
 		}
@@ -454,44 +810,169 @@ func serveAnalytics(
 
 	// Protected API endpoints
 	authProtected := r.With(appmiddleware.Auth)
+
+	// Stats and admin API rate limits, kept apart from the collect endpoints so
+	// one runaway integration can't starve the dashboard. API keys carry their
+	// own per-key and per-website limits; signed-in users share api_rate_limit.
+	apiLimiter := appmiddleware.NewRateLimiter(time.Minute)
+	adminAPIRateLimit := 300
+	if cfg != nil {
+		adminAPIRateLimit = cfg.APIRateLimit
+	}
+	adminAPI := authProtected.With(apiLimiter.UserRateLimit(adminAPIRateLimit))
+	statsAPI := r.With(appmiddleware.APIKeyAuthAny, apiLimiter.APIKeyRateLimit)
+
+	// Per-website roles: requests naming a website need at least this role
+	// on it. Routes that address a stored record (goal, API key, dashboard)
+	// check the record's website in the handler instead.
+	viewerAPI := authProtected.With(appmiddleware.RequireWebsiteRole(models.RoleViewer))
+	editorAPI := authProtected.With(appmiddleware.RequireWebsiteRole(models.RoleEditor))
+	ownerAPI := authProtected.With(appmiddleware.RequireWebsiteRole(models.RoleOwner))
+	adminViewerAPI := adminAPI.With(appmiddleware.RequireWebsiteRole(models.RoleViewer))
+	adminEditorAPI := adminAPI.With(appmiddleware.RequireWebsiteRole(models.RoleEditor))
+	adminOwnerAPI := adminAPI.With(appmiddleware.RequireWebsiteRole(models.RoleOwner))
 	authProtected.Post("/api/auth/logout", handlers.HandleLogoutSSE)
 	authProtected.Get("/api/auth/me", handlers.HandleMe)
+	authProtected.Post("/api/auth/theme", handlers.HandleSetTheme)
+	authProtected.Get("/api/auth/2fa", handlers.HandleTwoFactorInit)
+	authProtected.Post("/api/auth/2fa/setup", handlers.HandleTwoFactorSetup)
+	authProtected.Post("/api/auth/2fa/enable", handlers.HandleTwoFactorEnable)
+	authProtected.Post("/api/auth/2fa/disable", handlers.HandleTwoFactorDisable)
+	authProtected.Post("/api/auth/2fa/recovery-codes", handlers.HandleTwoFactorRecoveryCodes)
+	authProtected.Get("/api/auth/sessions", handlers.HandleSessionsInit)
+	authProtected.Delete("/api/auth/sessions/{session_id}", handlers.HandleSessionRevoke)
+	authProtected.Post("/api/auth/sessions/revoke-others", handlers.HandleSessionsRevokeOthers)
+	authProtected.Get("/api/auth/tokens", handlers.HandlePersonalTokensInit)
+	authProtected.Post("/api/auth/tokens", handlers.HandlePersonalTokenCreate)
+	authProtected.Delete("/api/auth/tokens/{token_id}", handlers.HandlePersonalTokenRevoke)
+	authProtected.Get("/api/auth/account", handlers.HandleAccountInit)
+	authProtected.With(loginLimiter.Handler).Post("/api/auth/account/password", handlers.HandleAccountPassword)
+	authProtected.With(loginLimiter.Handler).Post("/api/auth/account/email", handlers.HandleAccountEmail)
+	authProtected.With(loginLimiter.Handler).Post("/api/auth/account/delete", handlers.HandleAccountDelete)
 
 	// Dashboard API endpoints (protected, SSE-based)
-	authProtected.Get("/api/websites", handlers.HandleWebsites)
+	adminAPI.Get("/api/websites", handlers.HandleWebsites)
+	authProtected.Get("/api/dashboard/overview", handlers.HandleOverview)
+	authProtected.Get("/api/dashboard/compare-init", handlers.HandleCompareInit)
+	authProtected.Get("/api/dashboard/compare", handlers.HandleCompare)
 	authProtected.Get("/api/dashboard/init", handlers.HandleDashboardInit)
-	authProtected.Get("/api/dashboard/stats", handlers.HandleDashboardStats)
-	authProtected.Get("/api/dashboard/timeseries", handlers.HandleTimeSeries)
-	authProtected.Get("/api/dashboard/chart", handlers.HandleTimeSeries)
-	authProtected.Get("/api/dashboard/breakdown", handlers.HandleBreakdown)
-	authProtected.Get("/api/dashboard/map", handlers.HandleMapData)
-	authProtected.Get("/api/dashboard/realtime", handlers.HandleRealtimeVisitors)
+	viewerAPI.Get("/api/dashboard/stats", handlers.HandleDashboardStats)
+	viewerAPI.Get("/api/dashboard/timeseries", handlers.HandleTimeSeries)
+	viewerAPI.Get("/api/dashboard/chart", handlers.HandleTimeSeries)
+	viewerAPI.Get("/api/dashboard/breakdown", handlers.HandleBreakdown)
+	viewerAPI.Get("/api/dashboard/breakdown/trends", handlers.HandleBreakdownTrends)
+	viewerAPI.Get("/api/dashboard/not-found", handlers.HandleNotFound)
+	viewerAPI.Get("/api/dashboard/outbound", handlers.HandleOutbound)
+	viewerAPI.Get("/api/dashboard/data-quality", handlers.HandleDataQuality)
+	authProtected.Get("/api/dashboard/custom-init", handlers.HandleCustomDashboardsInit)
+	viewerAPI.Post("/api/dashboard/custom", handlers.HandleCustomDashboardCreate)
+	authProtected.Get("/api/dashboard/custom/{id}", handlers.HandleCustomDashboardShow)
+	authProtected.Delete("/api/dashboard/custom/{id}", handlers.HandleCustomDashboardDelete)
+	authProtected.Post("/api/dashboard/custom/{id}/share", handlers.HandleCustomDashboardShare)
+	authProtected.Post("/api/dashboard/custom/{id}/widgets", handlers.HandleCustomWidgetCreate)
+	authProtected.Delete("/api/dashboard/custom/{id}/widgets/{widget_id}", handlers.HandleCustomWidgetDelete)
+	authProtected.Get("/api/dashboard/alerts-init", handlers.HandleAlertsInit)
+	viewerAPI.Post("/api/dashboard/alerts", handlers.HandleAlertCreate)
+	authProtected.Post("/api/dashboard/alerts/{id}/enabled", handlers.HandleAlertToggle)
+	authProtected.Delete("/api/dashboard/alerts/{id}", handlers.HandleAlertDelete)
+	authProtected.Get("/api/dashboard/webhooks-init", handlers.HandleWebhooksInit)
+	editorAPI.Post("/api/dashboard/webhooks", handlers.HandleWebhookCreate)
+	authProtected.Post("/api/dashboard/webhooks/{id}/enabled", handlers.HandleWebhookToggle)
+	authProtected.Post("/api/dashboard/webhooks/{id}/test", handlers.HandleWebhookTest)
+	authProtected.Delete("/api/dashboard/webhooks/{id}", handlers.HandleWebhookDelete)
+	authProtected.Get("/api/dashboard/api-keys-init", handlers.HandleAPIKeysInit)
+	ownerAPI.Post("/api/dashboard/api-keys", handlers.HandleAPIKeyCreate)
+	authProtected.Post("/api/dashboard/api-keys/{id}/rotate", handlers.HandleAPIKeyRotate)
+	authProtected.Delete("/api/dashboard/api-keys/{id}", handlers.HandleAPIKeyRevoke)
+	ownerAPI.Post("/api/dashboard/kiosk", handlers.HandleKioskLink)
+	ownerAPI.Delete("/api/dashboard/kiosk", handlers.HandleKioskLinkDelete)
+	ownerAPI.Post("/api/dashboard/kiosk/settings", handlers.HandleKioskLinkSettings)
+	viewerAPI.Get("/api/dashboard/map", handlers.HandleMapData)
+	viewerAPI.Get("/api/dashboard/map/country", handlers.HandleMapCountry)
+	viewerAPI.Get("/api/dashboard/realtime", handlers.HandleRealtimeVisitors)
+	viewerAPI.Get("/api/dashboard/realtime/stream", handlers.HandleRealtimeStream)
 	authProtected.Get("/api/dashboard/campaigns-init", handlers.HandleCampaignsInit)
-	authProtected.Get("/api/dashboard/campaigns", handlers.HandleCampaigns)
+	viewerAPI.Get("/api/dashboard/campaigns", handlers.HandleCampaigns)
 	authProtected.Get("/api/dashboard/websites-init", handlers.HandleWebsitesInit)
 	authProtected.Post("/api/dashboard/websites-create", handlers.HandleWebsitesCreate)
 	authProtected.Get("/api/dashboard/map-init", handlers.HandleMapInit)
-	authProtected.Get("/api/dashboard/goals", handlers.HandleGoals)
-	authProtected.Post("/api/dashboard/goals", handlers.HandleGoalsCreate)
+	authProtected.Get("/api/dashboard/live-map-init", handlers.HandleLiveMapInit)
+	viewerAPI.Get("/api/dashboard/live-map", handlers.HandleLiveMap)
+	viewerAPI.Get("/api/dashboard/goals", handlers.HandleGoals)
+	editorAPI.Post("/api/dashboard/goals", handlers.HandleGoalsCreate)
 	authProtected.Put("/api/dashboard/goals/{id}", handlers.HandleGoalsUpdate)
 	authProtected.Delete("/api/dashboard/goals/{id}", handlers.HandleGoalsDelete)
 	authProtected.Get("/api/dashboard/goals/{id}/analytics", handlers.HandleGoalsAnalytics)
 	authProtected.Get("/api/dashboard/goals/{id}/breakdown/{type}", handlers.HandleGoalsBreakdown)
+	authProtected.Get("/api/dashboard/events-init", handlers.HandleEventsInit)
+	viewerAPI.Get("/api/dashboard/events", handlers.HandleEvents)
+	viewerAPI.Get("/api/dashboard/events/properties", handlers.HandleEventProperties)
+	viewerAPI.Get("/api/dashboard/export.csv", handlers.HandleExportCSV)
+	viewerAPI.Get("/api/dashboard/report.pdf", handlers.HandleReportPDF)
 
 	// Website Management API (protected)
-	authProtected.Get("/api/websites/list", handlers.HandleWebsiteList)
-	authProtected.Get("/api/websites/{website_id}", handlers.HandleWebsiteShow)
-	authProtected.Post("/api/websites", handlers.HandleWebsiteCreate)
-	authProtected.Put("/api/websites/{website_id}", handlers.HandleWebsiteUpdate)
-	authProtected.Post("/api/websites/{website_id}/domains", handlers.HandleAddDomain)
-	authProtected.Delete("/api/websites/{website_id}/domains", handlers.HandleRemoveDomain)
-	authProtected.Patch("/api/websites/{website_id}/public-stats", handlers.HandleSetPublicStats)
+	adminAPI.Get("/api/websites/list", handlers.HandleWebsiteList)
+	adminViewerAPI.Get("/api/websites/{website_id}", handlers.HandleWebsiteShow)
+	adminAPI.Post("/api/websites", handlers.HandleWebsiteCreate)
+	adminEditorAPI.Put("/api/websites/{website_id}", handlers.HandleWebsiteUpdate)
+	adminEditorAPI.Post("/api/websites/{website_id}/domains", handlers.HandleAddDomain)
+	adminEditorAPI.Delete("/api/websites/{website_id}/domains", handlers.HandleRemoveDomain)
+	adminOwnerAPI.Patch("/api/websites/{website_id}/public-stats", handlers.HandleSetPublicStats)
+	adminOwnerAPI.Get("/api/websites/{website_id}/public-tokens", handlers.HandlePublicTokenList)
+	adminOwnerAPI.Post("/api/websites/{website_id}/public-tokens", handlers.HandlePublicTokenCreate)
+	adminOwnerAPI.Delete("/api/websites/{website_id}/public-tokens/{token}", handlers.HandlePublicTokenDelete)
+	adminOwnerAPI.Get("/api/websites/{website_id}/members", handlers.HandleWebsiteMembers)
+	adminOwnerAPI.Put("/api/websites/{website_id}/members", handlers.HandleWebsiteMemberSet)
+	adminOwnerAPI.Delete("/api/websites/{website_id}/members/{user_id}", handlers.HandleWebsiteMemberDelete)
+	adminOwnerAPI.Put("/api/websites/{website_id}/team", handlers.HandleWebsiteTeam)
+	adminOwnerAPI.Post("/api/websites/{website_id}/invitations", handlers.HandleWebsiteInvite)
+
+	// Teams API (protected; team roles are checked in the handlers)
+	adminAPI.Get("/api/teams", handlers.HandleTeams)
+	adminAPI.Post("/api/teams", handlers.HandleTeamCreate)
+	authProtected.Post("/api/teams/current", handlers.HandleSetCurrentTeam)
+	adminAPI.Delete("/api/teams/{team_id}", handlers.HandleTeamDelete)
+	adminAPI.Get("/api/teams/{team_id}/members", handlers.HandleTeamMembers)
+	adminAPI.Put("/api/teams/{team_id}/members", handlers.HandleTeamMemberSet)
+	adminAPI.Delete("/api/teams/{team_id}/members/{user_id}", handlers.HandleTeamMemberDelete)
+	adminAPI.Post("/api/teams/{team_id}/invitations", handlers.HandleTeamInvite)
+	authProtected.Post("/api/invitations/{token}/accept", handlers.HandleInvitationAccept)
 
 	// Public Stats API (no auth, opt-in per website)
 	r.Get("/api/public/stats/{website_id}", handlers.HandlePublicStats)
+	r.Get("/api/public/dashboards/{id}", handlers.HandlePublicCustomDashboard)
+	r.Get("/api/public/kiosk/{token}", handlers.HandlePublicKiosk)
+	r.With(loginLimiter.Handler).Post("/api/public/kiosk/{token}/unlock", handlers.HandleKioskUnlock)
+	r.Get("/api/public/counter/{token}", handlers.HandlePublicCounter)
+	r.Options("/api/public/counter/{token}", handlers.HandlePublicCounter)
+
+	// Hit-counter badges for READMEs and static sites (public stats or public token)
+	r.Get("/badge/{id}.svg", handlers.HandleBadge)
 
 	// API Key Stats API (requires API key with stats scope)
-	r.With(appmiddleware.APIKeyAuthAny).Get("/api/v1/stats/{website_id}", handlers.HandleAPIStats)
+	statsAPI.Get("/api/v1/stats/{website_id}", handlers.HandleAPIStats)
+	statsAPI.Get("/api/v1/stats/{website_id}/summary", handlers.HandleAPISummary)
+	statsAPI.Get("/api/v1/stats/{website_id}/pages", handlers.HandleAPIPages)
+	statsAPI.Get("/api/v1/stats/{website_id}/timeseries", handlers.HandleAPITimeseries)
+	statsAPI.Get("/api/v1/stats/{website_id}/breakdown/{dimension}", handlers.HandleAPIBreakdown)
+	statsAPI.Get("/api/v1/stats/{website_id}/realtime", handlers.HandleAPIRealtimeStream)
+	statsAPI.Get("/api/v1/stats/{website_id}/daily", handlers.HandleAPIDailyRollup)
+	statsAPI.Post("/api/v1/query", handlers.HandleAPIQuery)
+	if cfg != nil && cfg.GraphQLEnabled {
+		statsAPI.Get("/api/v1/graphql", handlers.HandleGraphQL)
+		statsAPI.Post("/api/v1/graphql", handlers.HandleGraphQL)
+	}
+
+	// MCP tools for LLM agents, limited to the API key's website
+	statsAPI.Post("/api/mcp", handlers.HandleMCP(Version))
+
+	// Grafana JSON datasource (URL: https://<host>/api/grafana)
+	statsAPI.Get("/api/grafana", handlers.HandleGrafanaHealth)
+	statsAPI.Get("/api/grafana/", handlers.HandleGrafanaHealth)
+	statsAPI.Post("/api/grafana/metrics", handlers.HandleGrafanaMetrics)
+	statsAPI.Post("/api/grafana/query", handlers.HandleGrafanaQuery)
+	statsAPI.Post("/api/grafana/tag-keys", handlers.HandleGrafanaTagKeys)
+	statsAPI.Post("/api/grafana/tag-values", handlers.HandleGrafanaTagValues)
 
 	// Website Management Dashboard page (protected)
 	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/websites", func(w http.ResponseWriter, r *http.Request) {
@@ -499,6 +980,8 @@ func serveAnalytics(
 			"Title":         "Websites",
 			"Version":       Version,
 			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
 		}); err != nil {
 			http.Error(w, "Failed to render websites view", http.StatusInternalServerError)
 		}
@@ -509,19 +992,56 @@ func serveAnalytics(
 			"Title":         "Goals",
 			"Version":       Version,
 			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
 		}); err != nil {
 			http.Error(w, "Failed to render goals view", http.StatusInternalServerError)
 		}
 	})
 
+	r.With(appmiddleware.AuthWithRedirect).Get("/dashboard/events", func(w http.ResponseWriter, r *http.Request) {
+		if err := render(w, "views/dashboard/events", "views/layouts/dashboard", map[string]any{
+			"Title":         "Events",
+			"Version":       Version,
+			"SelfWebsiteID": config.SelfWebsiteID,
+			"Theme":         handlers.ResolveTheme(r),
+			"Teams":         handlers.ResolveTeams(r),
+		}); err != nil {
+			http.Error(w, "Failed to render events view", http.StatusInternalServerError)
+		}
+	})
+
 	port := getEnv("PORT", "3000")
 	server := &http.Server{
 		Addr:    ":" + port,
 		Handler: r,
 	}
 	logging.L().Info("starting kaunta server", slog.String("port", port))
-	if err := server.ListenAndServe(); err != nil && err != http.ErrServerClosed {
-		logging.Fatal("http server exited", slog.Any("error", err))
+
+	signalCtx, stopSignals := signalContextFactory(ctx, os.Interrupt, syscall.SIGTERM)
+	defer stopSignals()
+
+	serverErr := make(chan error, 1)
+	go func() {
+		serverErr <- server.ListenAndServe()
+	}()
+
+	select {
+	case err := <-serverErr:
+		if err != nil && err != http.ErrServerClosed {
+			logging.Fatal("http server exited", slog.Any("error", err))
+		}
+		return nil
+	case <-signalCtx.Done():
+	}
+
+	// Stop taking requests; the deferred Stop calls then flush buffered
+	// events and close the database
+	logging.L().Info("shutting down kaunta server")
+	shutdownCtx, cancelShutdown := context.WithTimeout(context.Background(), 10*time.Second)
+	defer cancelShutdown()
+	if err := server.Shutdown(shutdownCtx); err != nil {
+		logging.L().Warn("error shutting down server", slog.Any("error", err))
 	}
 	return nil
 }
@@ -548,7 +1068,14 @@ func handleVersion(w http.ResponseWriter, r *http.Request) {
 	})
 }
 
-func handleTrackerScript(trackerScript []byte) http.Handler {
+// Cache lifetimes of the tracker script: an hour at the rolling URL, so
+// upgrades reach visitors quickly, and a year at the versioned one
+const (
+	trackerCacheControl          = "public, max-age=3600, immutable"
+	versionedTrackerCacheControl = "public, max-age=31536000, immutable"
+)
+
+func handleTrackerScript(trackerScript []byte, cacheControl string) http.Handler {
 	hash := sha256.Sum256(trackerScript)
 	etag := `"` + hex.EncodeToString(hash[:8]) + `"`
 
@@ -560,7 +1087,7 @@ func handleTrackerScript(trackerScript []byte) http.Handler {
 		w.Header().Set("Access-Control-Allow-Origin", "*")
 		w.Header().Set("Access-Control-Allow-Methods", "GET, OPTIONS")
 		w.Header().Set("Timing-Allow-Origin", "*")
-		w.Header().Set("Cache-Control", "public, max-age=3600, immutable")
+		w.Header().Set("Cache-Control", cacheControl)
 		w.Header().Set("ETag", etag)
 
 		if match := r.Header.Get("If-None-Match"); match == etag {
@@ -592,6 +1119,20 @@ func secureCookiesEnabled(cfg *config.Config) bool {
 	return env == "true"
 }
 
+// connectRedis opens the configured Redis, or returns nil when none is set or
+// it is unreachable so realtime state stays in PostgreSQL
+func connectRedis(cfg *config.Config) *redis.Client {
+	if cfg == nil || cfg.RedisURL == "" {
+		return nil
+	}
+	client, err := redis.Open(cfg.RedisURL, 10)
+	if err != nil {
+		logging.L().Warn("redis unavailable, realtime state stays in PostgreSQL", slog.Any("error", err))
+		return nil
+	}
+	return client
+}
+
 func upHandler(w http.ResponseWriter, r *http.Request) {
 	if err := pingDatabase(); err != nil {
 		http.Error(w, "database unavailable", http.StatusServiceUnavailable)
@@ -632,6 +1173,7 @@ func addVersionHeader() func(http.Handler) http.Handler {
 type csrfOptions struct {
 	Secure        bool
 	TrustedOrigin []string
+	Mode          string // config.CSRFModeCookie or config.CSRFModeHeader
 }
 
 func csrfMiddleware(opts csrfOptions) func(http.Handler) http.Handler {
@@ -642,6 +1184,12 @@ func csrfMiddleware(opts csrfOptions) func(http.Handler) http.Handler {
 
 	return func(next http.Handler) http.Handler {
 		return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+			// Before the token, so tracking responses never set a cookie
+			if isTrackingTransport(r) {
+				next.ServeHTTP(w, r)
+				return
+			}
+
 			token, err := ensureCSRFToken(w, r, opts.Secure)
 			if err != nil {
 				logging.L().Error("failed to ensure CSRF token", slog.Any("error", err))
@@ -649,6 +1197,12 @@ func csrfMiddleware(opts csrfOptions) func(http.Handler) http.Handler {
 				return
 			}
 
+			headerMode := opts.Mode == config.CSRFModeHeader
+			if headerMode {
+				// For clients on other origins, which can't read the cookie
+				w.Header().Set("X-CSRF-Token", token)
+			}
+
 			if shouldSkipCSRF(r) {
 				next.ServeHTTP(w, r)
 				return
@@ -668,7 +1222,14 @@ func csrfMiddleware(opts csrfOptions) func(http.Handler) http.Handler {
 				}
 			}
 
+			// A custom header can only come from this origin or, after a
+			// preflight, from a CORS origin; the Origin check above covers
+			// the rest
 			headerToken := r.Header.Get("X-CSRF-Token")
+			if headerMode && headerToken == "" && r.Header.Get("X-Requested-With") != "" {
+				next.ServeHTTP(w, r)
+				return
+			}
 			if headerToken == "" || headerToken != token {
 				http.Error(w, "invalid CSRF token", http.StatusForbidden)
 				return
@@ -690,15 +1251,7 @@ func ensureCSRFToken(w http.ResponseWriter, r *http.Request, secure bool) (strin
 	}
 	token := base64.RawStdEncoding.EncodeToString(tokenBytes)
 
-	http.SetCookie(w, &http.Cookie{
-		Name:     "kaunta_csrf",
-		Value:    token,
-		Path:     "/",
-		Secure:   secure,
-		HttpOnly: false,
-		SameSite: http.SameSiteLaxMode,
-		Expires:  time.Now().Add(7 * 24 * time.Hour),
-	})
+	http.SetCookie(w, handlers.CSRFCookie(token, time.Now().Add(7*24*time.Hour), secure))
 	return token, nil
 }
 
@@ -711,14 +1264,38 @@ func isSafeMethod(method string) bool {
 	}
 }
 
-func shouldSkipCSRF(r *http.Request) bool {
+// isTrackingTransport reports whether the request is part of tracking a
+// website: the tracker script, its hits and the pixel. Browsers make them from
+// other sites, where cookies are blocked or rejected, so they neither need
+// nor get one.
+func isTrackingTransport(r *http.Request) bool {
 	path := r.URL.Path
-	if path == "/api/send" {
+	switch path {
+	case "/api/send", "/api/tracker", "/k.js", "/kaunta.js", "/script.js":
 		return true
 	}
+	if strings.HasPrefix(path, "/k.v") && strings.HasSuffix(path, ".js") {
+		return true
+	}
+	return strings.HasPrefix(path, "/p/") && strings.HasSuffix(path, ".gif")
+}
+
+func shouldSkipCSRF(r *http.Request) bool {
+	path := r.URL.Path
 	if strings.HasPrefix(path, "/api/ingest") {
 		return true
 	}
+	// Authenticated by API key header, never by cookie
+	if path == "/api/v1/graphql" || path == "/api/v1/query" || path == "/api/mcp" || strings.HasPrefix(path, "/api/grafana/") {
+		return true
+	}
+	// Personal access tokens come in a header a browser never adds on its
+	// own; with a session cookie the request is still checked
+	if strings.HasPrefix(r.Header.Get("Authorization"), "Bearer "+models.PersonalTokenPrefix) {
+		if _, err := r.Cookie("kaunta_session"); err != nil {
+			return true
+		}
+	}
 	if isSafeMethod(r.Method) && (strings.HasSuffix(path, ".js") || strings.HasSuffix(path, ".css")) {
 		return true
 	}
@@ -1110,7 +1687,7 @@ func loginPageHTML() string {
           const data = await response.json();
 
           if (response.ok && data.success) {
-            window.location.href = '/dashboard';
+            window.location.href = '/dashboard/overview';
           } else {
             errorDiv.textContent = data.error || 'Login failed';
             errorDiv.classList.add('show');
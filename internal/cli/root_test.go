@@ -13,6 +13,7 @@ import (
 	"github.com/DATA-DOG/go-sqlmock"
 	"github.com/seuros/kaunta/internal/config"
 	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/handlers"
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
 )
@@ -82,7 +83,7 @@ func TestHandleTrackerScriptSetsCachingAndSecurityHeaders(t *testing.T) {
 	script := []byte("console.log('hello');")
 	req := httptest.NewRequest(http.MethodGet, "/k.js", nil)
 	resp := httptest.NewRecorder()
-	handleTrackerScript(script).ServeHTTP(resp, req)
+	handleTrackerScript(script, trackerCacheControl).ServeHTTP(resp, req)
 
 	body, err := io.ReadAll(resp.Body)
 	require.NoError(t, err)
@@ -190,6 +191,98 @@ func TestOptionsOKSetsDefaultHeadersWithoutOrigin(t *testing.T) {
 	assert.Contains(t, resp.Header().Get("Access-Control-Allow-Headers"), "X-API-Key")
 }
 
+func TestCSRFMiddlewareModes(t *testing.T) {
+	ok := http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) { w.WriteHeader(http.StatusOK) })
+	post := func(handler http.Handler, headers map[string]string) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodPost, "/api/websites", nil)
+		req.AddCookie(&http.Cookie{Name: "kaunta_csrf", Value: "token"})
+		for name, value := range headers {
+			req.Header.Set(name, value)
+		}
+		resp := httptest.NewRecorder()
+		handler.ServeHTTP(resp, req)
+		return resp
+	}
+	trusted := []string{"https://app.example.com"}
+
+	cookieMode := csrfMiddleware(csrfOptions{TrustedOrigin: trusted, Mode: config.CSRFModeCookie})(ok)
+	assert.Equal(t, http.StatusOK, post(cookieMode, map[string]string{"X-CSRF-Token": "token"}).Code)
+	assert.Equal(t, http.StatusForbidden, post(cookieMode, map[string]string{"X-Requested-With": "fetch"}).Code)
+	assert.Empty(t, post(cookieMode, nil).Header().Get("X-CSRF-Token"))
+
+	headerMode := csrfMiddleware(csrfOptions{TrustedOrigin: trusted, Mode: config.CSRFModeHeader})(ok)
+	resp := post(headerMode, map[string]string{"X-Requested-With": "fetch"})
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Equal(t, "token", resp.Header().Get("X-CSRF-Token"))
+	assert.Equal(t, http.StatusOK, post(headerMode, map[string]string{"X-CSRF-Token": "token"}).Code)
+	assert.Equal(t, http.StatusForbidden, post(headerMode, nil).Code)
+	// A wrong token isn't rescued by the custom header
+	assert.Equal(t, http.StatusForbidden, post(headerMode, map[string]string{"X-CSRF-Token": "wrong", "X-Requested-With": "fetch"}).Code)
+	// Nor is an untrusted origin
+	assert.Equal(t, http.StatusForbidden, post(headerMode, map[string]string{"Origin": "https://evil.example.com", "X-Requested-With": "fetch"}).Code)
+	assert.Equal(t, http.StatusOK, post(headerMode, map[string]string{"Origin": "https://app.example.com", "X-Requested-With": "fetch"}).Code)
+}
+
+// Other sites can't toggle a visitor's exclusion: setting it takes a POST
+// with the CSRF token
+func TestExcludeNeedsCSRFToken(t *testing.T) {
+	handler := csrfMiddleware(csrfOptions{TrustedOrigin: []string{"https://kaunta.example.com"}})(http.HandlerFunc(handlers.HandleExclude))
+	post := func(token string) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodPost, "/exclude?set=1", nil)
+		req.AddCookie(&http.Cookie{Name: "kaunta_csrf", Value: "token"})
+		if token != "" {
+			req.Header.Set("X-CSRF-Token", token)
+		}
+		resp := httptest.NewRecorder()
+		handler.ServeHTTP(resp, req)
+		return resp
+	}
+
+	resp := post("")
+	assert.Equal(t, http.StatusForbidden, resp.Code)
+	assert.NotContains(t, resp.Header().Get("Set-Cookie"), "kaunta_exclude=")
+
+	resp = post("token")
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Contains(t, resp.Header().Get("Set-Cookie"), "kaunta_exclude=1")
+}
+
+// The tracking transport must work from any site without cookies: none is
+// required, and none is set, so browsers have nothing to block or reject
+func TestTrackingTransportIsCookieless(t *testing.T) {
+	ok := http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) { w.WriteHeader(http.StatusOK) })
+	handler := csrfMiddleware(csrfOptions{TrustedOrigin: []string{"https://kaunta.example.com"}, Secure: true})(ok)
+
+	requests := []struct {
+		method string
+		path   string
+	}{
+		{http.MethodGet, "/k.js"},
+		{http.MethodGet, "/k.v1.4.0.js"},
+		{http.MethodGet, "/script.js"},
+		{http.MethodGet, "/api/tracker"},
+		{http.MethodOptions, "/api/send"},
+		{http.MethodPost, "/api/send"},
+		{http.MethodGet, "/p/550e8400-e29b-41d4-a716-446655440000.gif"},
+	}
+	for _, tt := range requests {
+		t.Run(tt.method+" "+tt.path, func(t *testing.T) {
+			req := httptest.NewRequest(tt.method, tt.path, nil)
+			req.Header.Set("Origin", "https://blog.example.org")
+			resp := httptest.NewRecorder()
+			handler.ServeHTTP(resp, req)
+
+			assert.Equal(t, http.StatusOK, resp.Code)
+			assert.Empty(t, resp.Header().Values("Set-Cookie"))
+		})
+	}
+
+	// Everything else still gets its CSRF cookie
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, httptest.NewRequest(http.MethodGet, "/login", nil))
+	assert.Contains(t, resp.Header().Get("Set-Cookie"), "kaunta_csrf=")
+}
+
 func TestRateLimitKeyFromRemoteAddr(t *testing.T) {
 	req := httptest.NewRequest(http.MethodGet, "/x", nil)
 	req.RemoteAddr = "203.0.113.10:4242"
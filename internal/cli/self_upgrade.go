@@ -10,15 +10,36 @@ import (
 	"runtime"
 	"strings"
 
-	"github.com/blang/semver"
 	"github.com/seuros/kaunta/internal/selfupdate"
 	"github.com/spf13/cobra"
 )
 
+// upgradeHint tells how to install a newer release
+const upgradeHint = "kaunta self-update"
+
+var selfUpdateCmd = &cobra.Command{
+	Use:   "self-update",
+	Short: "Upgrade this binary to the latest release",
+	Long: `Download the latest release for this platform and replace the running
+binary with it. The download is checked against the release's checksums,
+whose Ed25519 signature must match the key built into this binary, so
+nothing is replaced unless the release was signed by the Kaunta release
+pipeline.
+
+Before upgrading, the database migrations the new version will run on its
+first start are listed (see kaunta check-update); back up the database when
+there are any. Docker builds don't have this command: pull the new image.`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runSelfUpgrade(false, selfUpdateYes)
+	},
+}
+
 var (
 	selfUpgradeRequested bool
 	selfUpgradeCheckOnly bool
 	selfUpgradeAutoYes   bool
+	selfUpdateYes        bool
 )
 
 func setupSelfUpgrade() {
@@ -52,6 +73,7 @@ func hideSelfUpgradeFlagsIfDevBuild() {
 		_ = RootCmd.PersistentFlags().MarkHidden("self-upgrade")
 		_ = RootCmd.PersistentFlags().MarkHidden("self-upgrade-check")
 		_ = RootCmd.PersistentFlags().MarkHidden("self-upgrade-yes")
+		selfUpdateCmd.Hidden = true
 	}
 }
 
@@ -70,38 +92,25 @@ func handleSelfUpgradeFlags() error {
 }
 
 func runSelfUpgrade(checkOnly, autoYes bool) error {
-	versionStr := strings.TrimSpace(strings.TrimPrefix(Version, "v"))
-	if versionStr == "" {
-		return errors.New("self-upgrade is only available for release builds")
-	}
-
-	current, err := semver.Parse(versionStr)
-	if err != nil {
-		return fmt.Errorf("invalid current version %q: %w", Version, err)
-	}
-
-	fmt.Printf("Checking current version... v%s\n", current)
-
-	fmt.Print("Checking latest released version... ")
-	client := selfupdate.NewClient("seuros", "kaunta")
-	latest, err := client.DetectLatest()
+	status, err := checkForUpdate()
 	if err != nil {
-		fmt.Println()
-		return fmt.Errorf("failed to check for updates: %w", err)
+		return err
 	}
 
-	latestVer := latest.Version
-	fmt.Printf("v%s\n", latestVer)
-
-	if !latestVer.GT(current) {
+	fmt.Printf("Current version: v%s\n", status.Current)
+	fmt.Printf("Latest version:  v%s\n", status.Latest)
+	if !status.UpdateAvailable {
 		fmt.Println("Kaunta is already up to date")
 		return nil
 	}
-
-	fmt.Printf("New release found! v%s --> v%s\n", current, latestVer)
+	fmt.Printf("New release found! v%s --> v%s\n", status.Current, status.Latest)
+	printPendingMigrations(status)
 	if checkOnly {
 		return nil
 	}
+	if status.DatabaseDirty {
+		return errors.New("the database has a dirty migration; fix it before upgrading")
+	}
 
 	exe, err := os.Executable()
 	if err != nil {
@@ -113,7 +122,7 @@ func runSelfUpgrade(checkOnly, autoYes bool) error {
 	fmt.Printf("  * Current exe: %q\n", exe)
 	fmt.Printf("  * Target OS/Arch: %s/%s\n", runtime.GOOS, runtime.GOARCH)
 	// Find the appropriate asset for this platform
-	asset, err := latest.FindAsset()
+	asset, err := status.release.FindAsset()
 	if err != nil {
 		return fmt.Errorf("failed to find download: %w", err)
 	}
@@ -124,6 +133,9 @@ func runSelfUpgrade(checkOnly, autoYes bool) error {
 
 	if !autoYes {
 		fmt.Println("The new release will download and replace the current binary.")
+		if len(status.PendingMigrations) > 0 {
+			fmt.Println("Its migrations run when the server restarts; back up the database first.")
+		}
 		fmt.Print("Do you want to continue? [Y/n] ")
 
 		reader := bufio.NewReader(os.Stdin)
@@ -139,11 +151,17 @@ func runSelfUpgrade(checkOnly, autoYes bool) error {
 		}
 	}
 
-	fmt.Println("Downloading release...")
-	if err := selfupdate.UpdateTo(asset.BrowserDownloadURL, exe); err != nil {
+	fmt.Println("Downloading and verifying release...")
+	if err := selfupdate.Update(status.release, asset, exe); err != nil {
 		return fmt.Errorf("self-upgrade failed: %w", err)
 	}
 
-	fmt.Printf("Updated Kaunta to v%s\n", latestVer)
+	fmt.Printf("Updated Kaunta to v%s; restart the server to run it\n", status.Latest)
 	return nil
 }
+
+func init() {
+	RootCmd.AddCommand(selfUpdateCmd)
+
+	selfUpdateCmd.Flags().BoolVarP(&selfUpdateYes, "yes", "y", false, "Skip the confirmation prompt")
+}
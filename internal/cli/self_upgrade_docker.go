@@ -2,6 +2,9 @@
 
 package cli
 
+// upgradeHint tells how to install a newer release
+const upgradeHint = "docker pull ghcr.io/seuros/kaunta:latest"
+
 func setupSelfUpgrade() {}
 
 func hideSelfUpgradeFlagsIfDevBuild() {}
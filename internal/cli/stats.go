@@ -1,4 +1,288 @@
 package cli
 
-// This file now uses the comprehensive analytics implementation in analytics.go
-// The statsCmd and subcommands are defined there
+// The statsCmd subcommands are defined in analytics.go. Running
+// `kaunta stats <website>` without one prints the summary below.
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"fmt"
+	"os"
+	"strconv"
+	"strings"
+	"text/tabwriter"
+	"time"
+	"unicode/utf8"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+// Summary command flags
+var (
+	statsPeriod string
+	statsFormat string
+	statsTop    int
+)
+
+// SummaryStats is the terminal summary of a website
+type SummaryStats struct {
+	Days             int            `json:"days"`
+	Online           int64          `json:"online"`
+	Visitors         int64          `json:"visitors"`
+	Pageviews        int64          `json:"pageviews"`
+	BounceRate       float64        `json:"bounce_rate"`
+	AvgVisitDuration float64        `json:"avg_visit_duration_seconds"`
+	Granularity      string         `json:"granularity"`
+	Series           []SummaryPoint `json:"series"`
+	TopPages         []SummaryItem  `json:"top_pages"`
+	TopReferrers     []SummaryItem  `json:"top_referrers"`
+}
+
+// SummaryPoint is the pageviews of one hour or day
+type SummaryPoint struct {
+	Time  time.Time `json:"time"`
+	Views int64     `json:"views"`
+}
+
+// SummaryItem is a row of a top list
+type SummaryItem struct {
+	Name  string `json:"name"`
+	Views int64  `json:"views"`
+}
+
+var getSummaryStatsFn = GetSummaryStats
+
+// sparkBars are the levels of a sparkline, lowest first
+var sparkBars = []rune("▁▂▃▄▅▆▇█")
+
+// parsePeriod turns a --period such as 24h, 7d, 4w or 30 into days
+func parsePeriod(period string) (int, error) {
+	value := strings.ToLower(strings.TrimSpace(period))
+	unit := 1
+	switch {
+	case strings.HasSuffix(value, "h"):
+		value, unit = strings.TrimSuffix(value, "h"), 0
+	case strings.HasSuffix(value, "w"):
+		value, unit = strings.TrimSuffix(value, "w"), 7
+	case strings.HasSuffix(value, "d"):
+		value = strings.TrimSuffix(value, "d")
+	}
+	n, err := strconv.Atoi(value)
+	if err != nil {
+		return 0, fmt.Errorf("invalid period %q (use e.g. 24h, 7d, 4w)", period)
+	}
+	days := n * unit
+	if unit == 0 {
+		// Stats cover whole days, so hours must add up to them
+		if n%24 != 0 {
+			return 0, fmt.Errorf("invalid period %q (hours must be a multiple of 24)", period)
+		}
+		days = n / 24
+	}
+	if days < 1 || days > 365 {
+		return 0, fmt.Errorf("period must be between 1 and 365 days")
+	}
+	return days, nil
+}
+
+// sparkline draws values as a row of bars scaled to the largest
+func sparkline(values []int64) string {
+	var peak int64
+	for _, v := range values {
+		peak = max(peak, v)
+	}
+	var b strings.Builder
+	for _, v := range values {
+		level := 0
+		if peak > 0 {
+			level = int(v * int64(len(sparkBars)-1) / peak)
+		}
+		if v > 0 && level == 0 && len(values) > 1 {
+			level = 1 // Keep any traffic visible next to a large peak
+		}
+		b.WriteRune(sparkBars[level])
+	}
+	return b.String()
+}
+
+// formatCount renders n with thousands separators
+func formatCount(n int64) string {
+	if n < 0 {
+		return "-" + formatCount(-n)
+	}
+	s := strconv.FormatInt(n, 10)
+	for i := len(s) - 3; i > 0; i -= 3 {
+		s = s[:i] + "," + s[i:]
+	}
+	return s
+}
+
+// truncate shortens s to width runes, marking the cut with an ellipsis
+func truncate(s string, width int) string {
+	if utf8.RuneCountInString(s) <= width {
+		return s
+	}
+	return string([]rune(s)[:width-1]) + "…"
+}
+
+func runStatsSummary(domain, period, format string, top int) error {
+	days, err := parsePeriod(period)
+	if err != nil {
+		return err
+	}
+	if top < 1 || top > 100 {
+		return fmt.Errorf("top must be between 1 and 100")
+	}
+	if format != "text" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use text or json)", format)
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := getWebsiteIDByDomainFn(ctx, domain)
+	if err != nil {
+		return err
+	}
+	stats, err := getSummaryStatsFn(ctx, database.DB, websiteID, days, top)
+	if err != nil {
+		return err
+	}
+
+	if format == "json" {
+		data, err := json.MarshalIndent(stats, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	}
+	return outputSummaryText(stats, domain)
+}
+
+// GetSummaryStats gathers the summary through the same functions as the
+// dashboard, so both show the same numbers
+func GetSummaryStats(ctx context.Context, db *sql.DB, websiteID string, days, top int) (*SummaryStats, error) {
+	stats := &SummaryStats{Days: days, Granularity: "day"}
+	if days <= 2 {
+		stats.Granularity = "hour"
+	}
+
+	err := db.QueryRowContext(ctx, `SELECT * FROM get_dashboard_stats($1, $2)`, websiteID, days).
+		Scan(&stats.Online, &stats.Pageviews, &stats.Visitors, &stats.BounceRate, &stats.AvgVisitDuration)
+	if err != nil {
+		return nil, fmt.Errorf("failed to query stats: %w", err)
+	}
+
+	rows, err := db.QueryContext(ctx,
+		`SELECT * FROM get_timeseries($1, $2, NULL, NULL, NULL, NULL, $3)`, websiteID, days, stats.Granularity)
+	if err != nil {
+		return nil, fmt.Errorf("failed to query timeseries: %w", err)
+	}
+	for rows.Next() {
+		var point SummaryPoint
+		if err := rows.Scan(&point.Time, &point.Views); err != nil {
+			_ = rows.Close()
+			return nil, err
+		}
+		stats.Series = append(stats.Series, point)
+	}
+	_ = rows.Close()
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+
+	stats.TopPages, err = querySummaryItems(ctx, db,
+		`SELECT path, views FROM get_top_pages($1, $2, $3)`, websiteID, days, top)
+	if err != nil {
+		return nil, fmt.Errorf("failed to query top pages: %w", err)
+	}
+	stats.TopReferrers, err = querySummaryItems(ctx, db,
+		`SELECT name, count FROM get_breakdown($1, 'referrer', $2, $3)`, websiteID, days, top)
+	if err != nil {
+		return nil, fmt.Errorf("failed to query top referrers: %w", err)
+	}
+	return stats, nil
+}
+
+func querySummaryItems(ctx context.Context, db *sql.DB, query string, args ...any) ([]SummaryItem, error) {
+	rows, err := db.QueryContext(ctx, query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := []SummaryItem{}
+	for rows.Next() {
+		var item SummaryItem
+		var name sql.NullString
+		if err := rows.Scan(&name, &item.Views); err != nil {
+			return nil, err
+		}
+		item.Name = name.String
+		if item.Name == "" {
+			item.Name = "(none)"
+		}
+		items = append(items, item)
+	}
+	return items, rows.Err()
+}
+
+func outputSummaryText(stats *SummaryStats, domain string) error {
+	period := fmt.Sprintf("last %d days", stats.Days)
+	if stats.Days == 1 {
+		period = "last 24 hours"
+	}
+	fmt.Printf("\n%s, %s\n\n", domain, period)
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	_, _ = fmt.Fprintf(w, "  Visitors\t%s\tPageviews\t%s\n", formatCount(stats.Visitors), formatCount(stats.Pageviews))
+	_, _ = fmt.Fprintf(w, "  Bounce rate\t%.1f%%\tAvg visit\t%s\n", stats.BounceRate,
+		(time.Duration(stats.AvgVisitDuration) * time.Second).String())
+	_, _ = fmt.Fprintf(w, "  Online now\t%d\t\t\n", stats.Online)
+	_ = w.Flush()
+
+	if len(stats.Series) > 0 {
+		values := make([]int64, len(stats.Series))
+		peak := stats.Series[0]
+		for i, point := range stats.Series {
+			values[i] = point.Views
+			if point.Views > peak.Views {
+				peak = point
+			}
+		}
+		layout := "Jan 2"
+		if stats.Granularity == "hour" {
+			layout = "Jan 2 15:00"
+		}
+		fmt.Printf("\n  Pageviews per %s  %s  (peak %s, %s)\n", stats.Granularity, sparkline(values),
+			formatCount(peak.Views), peak.Time.Local().Format(layout))
+	}
+
+	printSummaryList("Top pages", stats.TopPages)
+	printSummaryList("Top referrers", stats.TopReferrers)
+	fmt.Println()
+	return nil
+}
+
+func printSummaryList(title string, items []SummaryItem) {
+	fmt.Printf("\n%s\n", title)
+	if len(items) == 0 {
+		fmt.Println("  No data")
+		return
+	}
+	width := 0
+	for _, item := range items {
+		width = max(width, len(formatCount(item.Views)))
+	}
+	for _, item := range items {
+		fmt.Printf("  %*s  %s\n", width, formatCount(item.Views), truncate(item.Name, 60))
+	}
+}
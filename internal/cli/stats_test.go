@@ -0,0 +1,101 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParsePeriod(t *testing.T) {
+	for period, days := range map[string]int{"7d": 7, "24h": 1, "48H": 2, "4w": 28, "30": 30, "365d": 365} {
+		got, err := parsePeriod(period)
+		require.NoError(t, err, period)
+		assert.Equal(t, days, got, period)
+	}
+	for _, period := range []string{"", "0d", "12h", "53w", "week"} {
+		_, err := parsePeriod(period)
+		assert.Error(t, err, period)
+	}
+}
+
+func TestSparkline(t *testing.T) {
+	assert.Equal(t, "▁▄█", sparkline([]int64{0, 50, 100}))
+	assert.Equal(t, "▂█", sparkline([]int64{1, 1000}))
+	assert.Equal(t, "▁▁", sparkline([]int64{0, 0}))
+	assert.Equal(t, "", sparkline(nil))
+}
+
+func TestFormatCount(t *testing.T) {
+	assert.Equal(t, "0", formatCount(0))
+	assert.Equal(t, "999", formatCount(999))
+	assert.Equal(t, "1,234,567", formatCount(1234567))
+	assert.Equal(t, "-1,000", formatCount(-1000))
+}
+
+func TestGetSummaryStats(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	day := time.Date(2025, 3, 1, 0, 0, 0, 0, time.UTC)
+	mock.ExpectQuery("get_dashboard_stats").WithArgs("site-123", 7).
+		WillReturnRows(sqlmock.NewRows([]string{"online", "pageviews", "visitors", "bounce_rate", "avg_visit_duration"}).
+			AddRow(2, 300, 120, 41.5, 75.0))
+	mock.ExpectQuery("get_timeseries").WithArgs("site-123", 7, "day").
+		WillReturnRows(sqlmock.NewRows([]string{"hour", "views"}).AddRow(day, 100).AddRow(day.AddDate(0, 0, 1), 200))
+	mock.ExpectQuery("get_top_pages").WithArgs("site-123", 7, 5).
+		WillReturnRows(sqlmock.NewRows([]string{"path", "views"}).AddRow("/", 180))
+	mock.ExpectQuery("get_breakdown").WithArgs("site-123", 7, 5).
+		WillReturnRows(sqlmock.NewRows([]string{"name", "count"}).AddRow(nil, 90).AddRow("news.ycombinator.com", 40))
+
+	stats, err := GetSummaryStats(context.Background(), db, "site-123", 7, 5)
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+	assert.Equal(t, int64(120), stats.Visitors)
+	assert.Len(t, stats.Series, 2)
+	assert.Equal(t, []SummaryItem{{"/", 180}}, stats.TopPages)
+	assert.Equal(t, []SummaryItem{{"(none)", 90}, {"news.ycombinator.com", 40}}, stats.TopReferrers)
+}
+
+func TestRunStatsSummary(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+	stubWebsiteIDLookup(t, func(ctx context.Context, domain string) (string, error) {
+		return "site-123", nil
+	})
+	original := getSummaryStatsFn
+	t.Cleanup(func() { getSummaryStatsFn = original })
+	getSummaryStatsFn = func(ctx context.Context, db *sql.DB, websiteID string, days, top int) (*SummaryStats, error) {
+		assert.Equal(t, 30, days)
+		assert.Equal(t, 5, top)
+		return &SummaryStats{
+			Days: days, Visitors: 1234, Pageviews: 5678, BounceRate: 41.5, AvgVisitDuration: 83,
+			Granularity:  "day",
+			Series:       []SummaryPoint{{time.Date(2025, 3, 1, 12, 0, 0, 0, time.UTC), 10}, {time.Date(2025, 3, 2, 12, 0, 0, 0, time.UTC), 80}},
+			TopPages:     []SummaryItem{{"/", 4000}, {"/pricing", 12}},
+			TopReferrers: []SummaryItem{},
+		}, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runStatsSummary("example.com", "30d", "text", 5)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, "example.com, last 30 days")
+	assert.Contains(t, output, "1,234")
+	assert.Contains(t, output, "41.5%")
+	assert.Contains(t, output, "1m23s")
+	assert.Contains(t, output, "▁█  (peak 80, Mar 2)")
+	assert.Contains(t, output, "  4,000  /\n     12  /pricing\n")
+	assert.Contains(t, output, "Top referrers\n  No data")
+
+	_, err = captureOutput(t, func() error {
+		return runStatsSummary("example.com", "fortnight", "text", 5)
+	})
+	assert.ErrorContains(t, err, "invalid period")
+}
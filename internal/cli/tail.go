@@ -0,0 +1,168 @@
+package cli
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"net/url"
+	"os"
+	"strings"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/config"
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+var tailCmd = &cobra.Command{
+	Use:   "tail [--website <domain|id>] [--json]",
+	Short: "Stream accepted hits as they arrive",
+	Long: `Print every hit the server accepts as it arrives: the time, website, page,
+country, referrer and custom event name. Use it to check a new tracker
+snippet without opening the dashboard. Bots, spam referrers and hits from
+origins that aren't allowed are rejected before this point, so a hit that
+never shows up was refused; kaunta test tracking <domain> tells why.
+
+Hits come from the same feed as the dashboard's realtime view (PostgreSQL
+LISTEN/NOTIFY, or Redis when redis_url is set), so tail has to reach the
+same database or Redis as the server. With --json each hit is printed as a
+JSON object on its own line. Stop with Ctrl-C.
+
+Examples:
+  kaunta tail --website example.com
+  kaunta tail --json | jq -r .path`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTail(tailWebsite, tailJSON)
+	},
+}
+
+// Command flags
+var (
+	tailWebsite string
+	tailJSON    bool
+)
+
+// tailSubscribe streams the raw realtime payloads published by the servers
+// until ctx is done
+var tailSubscribe = func(ctx context.Context) (<-chan []byte, error) {
+	hub := realtime.NewHub()
+	cfg, err := config.Load()
+	if err != nil {
+		return nil, err
+	}
+	if client := connectRedis(cfg); client != nil {
+		context.AfterFunc(ctx, func() { _ = client.Close() })
+		realtime.StartRedisListener(ctx, client, hub)
+	} else if err := realtime.StartListener(ctx, os.Getenv("DATABASE_URL"), hub); err != nil {
+		return nil, fmt.Errorf("failed to listen for hits: %w", err)
+	}
+	hits, cancel := hub.Subscribe()
+	context.AfterFunc(ctx, cancel)
+	return hits, nil
+}
+
+func runTail(website string, asJSON bool) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, stop := importContext()
+	defer stop()
+
+	// Domains to label hits with; a website added while tailing shows its ID
+	domains := make(map[string]string)
+	target := ""
+	if website != "" {
+		var byID *string
+		if _, err := uuid.Parse(website); err == nil {
+			byID = &website
+		}
+		detail, err := fetchWebsiteByDomain(ctx, website, byID)
+		if err != nil {
+			return err
+		}
+		target = detail.WebsiteID
+		domains[detail.WebsiteID] = detail.Domain
+	} else {
+		sites, err := ListWebsites(ctx)
+		if err != nil {
+			return fmt.Errorf("failed to list websites: %w", err)
+		}
+		for _, site := range sites {
+			domains[site.WebsiteID] = site.Domain
+		}
+	}
+
+	hits, err := tailSubscribe(ctx)
+	if err != nil {
+		return err
+	}
+	if target != "" {
+		fmt.Fprintf(os.Stderr, "Waiting for hits on %s (Ctrl-C to stop)\n", domains[target])
+	} else {
+		fmt.Fprintln(os.Stderr, "Waiting for hits on all websites (Ctrl-C to stop)")
+	}
+
+	for {
+		select {
+		case <-ctx.Done():
+			return nil
+		case msg, ok := <-hits:
+			if !ok {
+				return nil
+			}
+			var hit realtime.EventPayload
+			if err := json.Unmarshal(msg, &hit); err != nil || hit.WebsiteID == "" {
+				continue
+			}
+			if target != "" && hit.WebsiteID != target {
+				continue
+			}
+			if asJSON {
+				fmt.Println(string(msg))
+				continue
+			}
+			domain, ok := domains[hit.WebsiteID]
+			if !ok {
+				domain = hit.WebsiteID
+			}
+			fmt.Println(formatTailHit(hit, domain))
+		}
+	}
+}
+
+// formatTailHit renders a hit as one line: time, website, page, country,
+// referrer host and event name
+func formatTailHit(hit realtime.EventPayload, domain string) string {
+	page, host := hit.Path, ""
+	if u, err := url.Parse(hit.Path); err == nil && u.Host != "" {
+		page, host = u.Path, u.Hostname()
+		if page == "" {
+			page = "/"
+		}
+	}
+	country := hit.Country
+	if country == "" {
+		country = "--"
+	}
+
+	line := fmt.Sprintf("%s  %-24s %-3s %s", hit.CreatedAt.Local().Format("15:04:05"), domain, country, page)
+	if ref, err := url.Parse(hit.Referrer); err == nil && ref.Hostname() != "" && ref.Hostname() != host {
+		line += "  ← " + strings.TrimPrefix(ref.Hostname(), "www.")
+	}
+	if hit.Name != "" {
+		line += "  [" + hit.Name + "]"
+	}
+	return line
+}
+
+func init() {
+	RootCmd.AddCommand(tailCmd)
+
+	tailCmd.Flags().StringVar(&tailWebsite, "website", "", "Domain or ID of the website to follow (default all)")
+	tailCmd.Flags().BoolVar(&tailJSON, "json", false, "Print each hit as a JSON object per line")
+}
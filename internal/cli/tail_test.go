@@ -0,0 +1,57 @@
+package cli
+
+import (
+	"context"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+func TestFormatTailHit(t *testing.T) {
+	at := time.Date(2026, 3, 4, 12, 30, 5, 0, time.Local)
+	hit := realtime.EventPayload{
+		Path:      "https://example.com/pricing?plan=pro",
+		Referrer:  "https://www.google.com/search?q=kaunta",
+		Country:   "DE",
+		CreatedAt: at,
+	}
+	assert.Equal(t, "12:30:05  example.com              DE  /pricing  ← google.com", formatTailHit(hit, "example.com"))
+
+	// Internal navigation has no referrer worth showing
+	hit.Referrer = "https://example.com/"
+	hit.Name = "signup"
+	hit.Country = ""
+	assert.Equal(t, "12:30:05  example.com              --  /pricing  [signup]", formatTailHit(hit, "example.com"))
+}
+
+func TestRunTailFiltersWebsite(t *testing.T) {
+	stubDB(t)
+	stubConnectClose(t)
+
+	originalFetch, originalSubscribe := fetchWebsiteByDomain, tailSubscribe
+	t.Cleanup(func() { fetchWebsiteByDomain, tailSubscribe = originalFetch, originalSubscribe })
+	fetchWebsiteByDomain = func(ctx context.Context, domain string, id *string) (*WebsiteDetail, error) {
+		return &WebsiteDetail{WebsiteID: "site-1", Domain: "example.com"}, nil
+	}
+	tailSubscribe = func(ctx context.Context) (<-chan []byte, error) {
+		hits := make(chan []byte, 3)
+		hits <- []byte(`{"type":"event","website_id":"site-2","path":"https://other.com/"}`)
+		hits <- []byte(`not json`)
+		hits <- []byte(`{"type":"event","website_id":"site-1","path":"https://example.com/docs","name":"download"}`)
+		close(hits)
+		return hits, nil
+	}
+
+	output, err := captureOutput(t, func() error {
+		return runTail("example.com", true)
+	})
+	require.NoError(t, err)
+	lines := strings.Split(strings.TrimSpace(output), "\n")
+	require.Len(t, lines, 1)
+	assert.Contains(t, lines[0], `"name":"download"`)
+}
@@ -0,0 +1,506 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"text/tabwriter"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var teamCmd = &cobra.Command{
+	Use:   "team",
+	Short: "Manage teams",
+	Long: `Manage teams. Websites in a team are shared with its members, who get
+their team role (owner, editor or viewer) on every one of them. Team owners
+manage the team's members.`,
+}
+
+var teamCreateCmd = &cobra.Command{
+	Use:   "create <name> --owner <username>",
+	Short: "Create a team",
+	Long: `Create a team with its first owner.
+
+Example:
+  kaunta team create Marketing --owner alice`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamCreate(args[0], teamOwner)
+	},
+}
+
+var teamListCmd = &cobra.Command{
+	Use:   "list <username> [--format table|json]",
+	Short: "List a user's teams",
+	Args:  cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamList(args[0], teamFormat)
+	},
+}
+
+var teamDeleteCmd = &cobra.Command{
+	Use:   "delete <name>",
+	Short: "Delete a team",
+	Long:  `Delete a team. Its websites stay with their owners and members.`,
+	Args:  cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamDelete(args[0])
+	},
+}
+
+var teamMemberCmd = &cobra.Command{
+	Use:   "member",
+	Short: "Manage who is in a team",
+}
+
+var teamMemberAddCmd = &cobra.Command{
+	Use:   "add <team> <username> --role <owner|editor|viewer>",
+	Short: "Add a user to a team or change their role",
+	Args:  cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamMemberAdd(args[0], args[1], teamMemberRole)
+	},
+}
+
+var teamMemberListCmd = &cobra.Command{
+	Use:   "list <team> [--format table|json]",
+	Short: "List a team's members",
+	Args:  cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamMemberList(args[0], teamFormat)
+	},
+}
+
+var teamMemberRemoveCmd = &cobra.Command{
+	Use:   "remove <team> <username>",
+	Short: "Remove a user from a team",
+	Args:  cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamMemberRemove(args[0], args[1])
+	},
+}
+
+var teamGroupCmd = &cobra.Command{
+	Use:   "group",
+	Short: "Map directory groups to teams",
+	Long: `Map groups passed on by the sign-in proxy (proxy_auth.groups_header) to
+teams. On every sign-in, users join each team one of their groups maps to,
+with the highest mapped role, and leave teams none of their groups map to
+any more. Memberships added with 'kaunta team member add' are left alone.`,
+}
+
+var teamGroupAddCmd = &cobra.Command{
+	Use:   "add <team> <group> --role <owner|editor|viewer>",
+	Short: "Give members of a group a role in a team",
+	Long: `Give members of a group a role in a team, or change the role of an
+existing mapping. Users pick it up on their next sign-in.
+
+Example:
+  kaunta team group add Marketing marketing-staff --role editor`,
+	Args: cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamGroupAdd(args[0], args[1], teamMemberRole)
+	},
+}
+
+var teamGroupListCmd = &cobra.Command{
+	Use:   "list [--format table|json]",
+	Short: "List group mappings",
+	Args:  cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamGroupList(teamFormat)
+	},
+}
+
+var teamGroupRemoveCmd = &cobra.Command{
+	Use:   "remove <team> <group>",
+	Short: "Remove a group mapping",
+	Long: `Remove a group mapping. Users it added leave the team on their next
+sign-in, unless another of their groups maps to it.`,
+	Args: cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTeamGroupRemove(args[0], args[1])
+	},
+}
+
+var websiteSetTeamCmd = &cobra.Command{
+	Use:   "set-team <domain> [team]",
+	Short: "Move a website into a team",
+	Long: `Move a website into a team, or out of its team when no team is given.
+
+Examples:
+  kaunta website set-team example.com Marketing
+  kaunta website set-team example.com`,
+	Args: cobra.RangeArgs(1, 2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		team := ""
+		if len(args) == 2 {
+			team = args[1]
+		}
+		return runWebsiteSetTeam(args[0], team)
+	},
+}
+
+// Command flags
+var (
+	teamOwner      string
+	teamMemberRole string
+	teamFormat     string
+)
+
+// lookupTeamID resolves a team name to its ID
+func lookupTeamID(ctx context.Context, name string) (uuid.UUID, error) {
+	teamID, err := models.FindTeamByName(ctx, database.DB, name)
+	if errors.Is(err, models.ErrTeamNotFound) {
+		return uuid.UUID{}, fmt.Errorf("team '%s' not found", name)
+	}
+	if err != nil {
+		return uuid.UUID{}, fmt.Errorf("failed to check team: %w", err)
+	}
+	return teamID, nil
+}
+
+func runTeamCreate(name, owner string) error {
+	if owner == "" {
+		return fmt.Errorf("--owner is required")
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	ownerID, err := lookupUserID(ctx, owner)
+	if err != nil {
+		return err
+	}
+	team, err := models.CreateTeam(ctx, database.DB, name, ownerID)
+	if errors.Is(err, models.ErrTeamExists) {
+		return fmt.Errorf("team '%s' already exists", name)
+	}
+	if err != nil {
+		return fmt.Errorf("failed to create team: %w", err)
+	}
+
+	fmt.Printf("✓ Team '%s' created (ID: %s), owned by '%s'\n", team.Name, team.TeamID, owner)
+	return nil
+}
+
+func runTeamList(username, format string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	userID, err := lookupUserID(ctx, username)
+	if err != nil {
+		return err
+	}
+	teams, err := models.ListUserTeams(ctx, database.DB, userID)
+	if err != nil {
+		return fmt.Errorf("failed to list teams: %w", err)
+	}
+
+	if format == "json" {
+		encoder := json.NewEncoder(os.Stdout)
+		encoder.SetIndent("", "  ")
+		return encoder.Encode(teams)
+	}
+
+	if len(teams) == 0 {
+		fmt.Printf("'%s' is not in any team\n", username)
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	_, _ = fmt.Fprintln(w, "NAME\tROLE\tWEBSITES\tCURRENT")
+	for _, t := range teams {
+		current := ""
+		if t.Current {
+			current = "✓"
+		}
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%d\t%s\n", t.Name, t.Role, t.Websites, current)
+	}
+	return w.Flush()
+}
+
+func runTeamDelete(name string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	teamID, err := lookupTeamID(ctx, name)
+	if err != nil {
+		return err
+	}
+	if err := models.DeleteTeam(ctx, database.DB, teamID); err != nil {
+		return fmt.Errorf("failed to delete team: %w", err)
+	}
+
+	fmt.Printf("✓ Team '%s' deleted\n", name)
+	return nil
+}
+
+func runTeamMemberAdd(team, username, roleName string) error {
+	role, err := models.ParseWebsiteRole(roleName)
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	teamID, err := lookupTeamID(ctx, team)
+	if err != nil {
+		return err
+	}
+	_, err = models.SetTeamMember(ctx, database.DB, teamID, username, role)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		return fmt.Errorf("user '%s' not found", username)
+	case errors.Is(err, models.ErrLastTeamOwner):
+		return fmt.Errorf("'%s' is the only owner of '%s'; add another owner first", username, team)
+	case err != nil:
+		return fmt.Errorf("failed to add member: %w", err)
+	}
+
+	fmt.Printf("✓ '%s' is now %s in team '%s'\n", username, role, team)
+	return nil
+}
+
+func runTeamMemberList(team, format string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	teamID, err := lookupTeamID(ctx, team)
+	if err != nil {
+		return err
+	}
+	members, err := models.ListTeamMembers(ctx, database.DB, teamID)
+	if err != nil {
+		return fmt.Errorf("failed to list members: %w", err)
+	}
+
+	if format == "json" {
+		encoder := json.NewEncoder(os.Stdout)
+		encoder.SetIndent("", "  ")
+		return encoder.Encode(members)
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	_, _ = fmt.Fprintln(w, "USERNAME\tROLE\tADDED")
+	for _, m := range members {
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\n", m.Username, m.Role, m.CreatedAt.Format("2006-01-02 15:04"))
+	}
+	return w.Flush()
+}
+
+func runTeamMemberRemove(team, username string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	teamID, err := lookupTeamID(ctx, team)
+	if err != nil {
+		return err
+	}
+	userID, err := lookupUserID(ctx, username)
+	if err != nil {
+		return err
+	}
+	err = models.RemoveTeamMember(ctx, database.DB, teamID, userID)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		return fmt.Errorf("'%s' is not in team '%s'", username, team)
+	case errors.Is(err, models.ErrLastTeamOwner):
+		return fmt.Errorf("'%s' is the only owner of '%s'; add another owner first", username, team)
+	case err != nil:
+		return fmt.Errorf("failed to remove member: %w", err)
+	}
+
+	fmt.Printf("✓ '%s' removed from team '%s'\n", username, team)
+	return nil
+}
+
+func runTeamGroupAdd(team, group, roleName string) error {
+	role, err := models.ParseWebsiteRole(roleName)
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	teamID, err := lookupTeamID(ctx, team)
+	if err != nil {
+		return err
+	}
+	if err := models.SetGroupMapping(ctx, database.DB, group, teamID, role); err != nil {
+		return fmt.Errorf("failed to map group: %w", err)
+	}
+
+	fmt.Printf("✓ Members of group '%s' are now %s in team '%s' from their next sign-in\n", group, role, team)
+	return nil
+}
+
+func runTeamGroupList(format string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	mappings, err := models.ListGroupMappings(ctx, database.DB)
+	if err != nil {
+		return fmt.Errorf("failed to list group mappings: %w", err)
+	}
+
+	if format == "json" {
+		encoder := json.NewEncoder(os.Stdout)
+		encoder.SetIndent("", "  ")
+		return encoder.Encode(mappings)
+	}
+
+	if len(mappings) == 0 {
+		fmt.Println("No group mappings")
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	_, _ = fmt.Fprintln(w, "GROUP\tTEAM\tROLE")
+	for _, m := range mappings {
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\n", m.Group, m.TeamName, m.Role)
+	}
+	return w.Flush()
+}
+
+func runTeamGroupRemove(team, group string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	teamID, err := lookupTeamID(ctx, team)
+	if err != nil {
+		return err
+	}
+	err = models.RemoveGroupMapping(ctx, database.DB, group, teamID)
+	if errors.Is(err, sql.ErrNoRows) {
+		return fmt.Errorf("group '%s' is not mapped to team '%s'", group, team)
+	}
+	if err != nil {
+		return fmt.Errorf("failed to remove group mapping: %w", err)
+	}
+
+	fmt.Printf("✓ Group '%s' no longer maps to team '%s'\n", group, team)
+	return nil
+}
+
+func runWebsiteSetTeam(domain, team string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	var teamID *uuid.UUID
+	if team != "" {
+		id, err := lookupTeamID(ctx, team)
+		if err != nil {
+			return err
+		}
+		teamID = &id
+	}
+	if err := models.SetWebsiteTeam(ctx, database.DB, websiteID, teamID); err != nil {
+		return fmt.Errorf("failed to move website: %w", err)
+	}
+
+	if team == "" {
+		fmt.Printf("✓ '%s' is no longer in a team\n", domain)
+	} else {
+		fmt.Printf("✓ '%s' moved to team '%s'\n", domain, team)
+	}
+	return nil
+}
+
+func init() {
+	RootCmd.AddCommand(teamCmd)
+	teamCmd.AddCommand(teamCreateCmd)
+	teamCmd.AddCommand(teamListCmd)
+	teamCmd.AddCommand(teamDeleteCmd)
+	teamCmd.AddCommand(teamMemberCmd)
+	teamMemberCmd.AddCommand(teamMemberAddCmd)
+	teamMemberCmd.AddCommand(teamMemberListCmd)
+	teamMemberCmd.AddCommand(teamMemberRemoveCmd)
+	teamCmd.AddCommand(teamGroupCmd)
+	teamGroupCmd.AddCommand(teamGroupAddCmd)
+	teamGroupCmd.AddCommand(teamGroupListCmd)
+	teamGroupCmd.AddCommand(teamGroupRemoveCmd)
+	websiteCmd.AddCommand(websiteSetTeamCmd)
+
+	teamCreateCmd.Flags().StringVar(&teamOwner, "owner", "", "Username of the team's first owner")
+	teamMemberAddCmd.Flags().StringVarP(&teamMemberRole, "role", "r", "viewer", "Role in the team (owner, editor, viewer)")
+	teamListCmd.Flags().StringVarP(&teamFormat, "format", "f", "table", "Output format (table, json)")
+	teamMemberListCmd.Flags().StringVarP(&teamFormat, "format", "f", "table", "Output format (table, json)")
+	teamGroupAddCmd.Flags().StringVarP(&teamMemberRole, "role", "r", "viewer", "Role in the team (owner, editor, viewer)")
+	teamGroupListCmd.Flags().StringVarP(&teamFormat, "format", "f", "table", "Output format (table, json)")
+}
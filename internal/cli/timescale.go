@@ -0,0 +1,162 @@
+package cli
+
+import (
+	"bufio"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"strings"
+	"time"
+
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+var timescaleCmd = &cobra.Command{
+	Use:   "timescale",
+	Short: "Store events in a TimescaleDB hypertable",
+	Long: `Use TimescaleDB, when the PostgreSQL server has it, to store events in a
+compressed hypertable instead of native daily partitions.`,
+}
+
+var timescaleStatusCmd = &cobra.Command{
+	Use:   "status [--format table|json]",
+	Short: "Show whether TimescaleDB is available and how events are stored",
+	Args:  cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTimescaleStatus(timescaleFormat)
+	},
+}
+
+var timescaleEnableCmd = &cobra.Command{
+	Use:   "enable [--compress-after 168h]",
+	Short: "Convert events to a compressed TimescaleDB hypertable",
+	Long: `Install the timescaledb extension and move website_event into a
+hypertable with daily chunks. Chunks older than --compress-after are
+compressed, and the hourly and daily stats behind the charts become
+continuous aggregates refreshed by TimescaleDB.
+
+The extension must be in shared_preload_libraries, and installing it usually
+needs a superuser. The events are copied in one transaction that locks them
+until it commits, so stop the server first. There is no command to convert
+back.
+
+Examples:
+  kaunta timescale enable
+  kaunta timescale enable --compress-after 72h --force`,
+	Args: cobra.NoArgs,
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runTimescaleEnable(timescaleCompressAfter, timescaleForce)
+	},
+}
+
+// Command flags
+var (
+	timescaleFormat        string
+	timescaleCompressAfter time.Duration
+	timescaleForce         bool
+)
+
+func runTimescaleStatus(format string) error {
+	if format != "table" && format != "json" {
+		return fmt.Errorf("invalid format: %s (use table or json)", format)
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	status, err := database.GetTimescaleStatus(ctx, database.DB)
+	if err != nil {
+		return fmt.Errorf("failed to check TimescaleDB: %w", err)
+	}
+
+	if format == "json" {
+		data, err := json.MarshalIndent(status, "", "  ")
+		if err != nil {
+			return fmt.Errorf("failed to marshal JSON: %w", err)
+		}
+		fmt.Println(string(data))
+		return nil
+	}
+
+	switch {
+	case !status.Available:
+		fmt.Println("TimescaleDB: not available on this server")
+		fmt.Println("Events:      native daily partitions")
+	case !status.Hypertable:
+		version := status.Version
+		if version == "" {
+			version = "not installed"
+		}
+		fmt.Printf("TimescaleDB: available (%s)\n", version)
+		fmt.Println("Events:      native daily partitions; run 'kaunta timescale enable' to convert")
+	default:
+		fmt.Printf("TimescaleDB: %s\n", status.Version)
+		fmt.Printf("Events:      hypertable, %d of %d chunks compressed, %s\n",
+			status.CompressedChunks, status.Chunks, status.Size)
+	}
+	return nil
+}
+
+func runTimescaleEnable(compressAfter time.Duration, force bool) error {
+	if compressAfter < time.Hour {
+		return errors.New("--compress-after must be at least 1h")
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	hypertable, err := database.EventsAreHypertable(context.Background(), database.DB)
+	if err != nil {
+		return fmt.Errorf("failed to check event storage: %w", err)
+	}
+	if hypertable {
+		fmt.Println("Events are already stored in a hypertable")
+		return nil
+	}
+
+	if !force {
+		fmt.Print("Copy all events into a TimescaleDB hypertable? The server should be stopped. (yes/no): ")
+		response, _ := bufio.NewReader(os.Stdin).ReadString('\n')
+		response = strings.ToLower(strings.TrimSpace(response))
+		if response != "yes" && response != "y" {
+			fmt.Println("Conversion cancelled")
+			return nil
+		}
+	}
+
+	// Copying and indexing every event takes a while on large installs
+	ctx, cancel := context.WithTimeout(context.Background(), 12*time.Hour)
+	defer cancel()
+
+	start := time.Now()
+	copied, err := database.ConvertEventsToHypertable(ctx, database.DB, compressAfter)
+	if err != nil {
+		return err
+	}
+	fmt.Printf("Moved %d events into a hypertable in %s; chunks older than %s will be compressed\n",
+		copied, time.Since(start).Round(time.Second), compressAfter)
+	return nil
+}
+
+func init() {
+	RootCmd.AddCommand(timescaleCmd)
+	timescaleCmd.AddCommand(timescaleStatusCmd)
+	timescaleCmd.AddCommand(timescaleEnableCmd)
+
+	timescaleStatusCmd.Flags().StringVarP(&timescaleFormat, "format", "f", "table", "Output format (table, json)")
+	timescaleEnableCmd.Flags().DurationVar(&timescaleCompressAfter, "compress-after", 7*24*time.Hour, "Compress chunks older than this")
+	timescaleEnableCmd.Flags().BoolVarP(&timescaleForce, "force", "f", false, "Skip confirmation prompt")
+}
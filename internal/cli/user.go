@@ -2,9 +2,13 @@ package cli
 
 import (
 	"bufio"
+	"context"
 	"crypto/rand"
+	"database/sql"
 	"encoding/base64"
+	"errors"
 	"fmt"
+	"io"
 	"os"
 	"strings"
 	"syscall"
@@ -15,14 +19,17 @@ import (
 
 	"log/slog"
 
+	"github.com/seuros/kaunta/internal/config"
 	"github.com/seuros/kaunta/internal/database"
 	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/models"
 )
 
 var userCmd = &cobra.Command{
 	Use:   "user",
 	Short: "Manage users",
-	Long:  `Manage Kaunta users via CLI. Create, list, and delete users.`,
+	Long: `Manage Kaunta users via CLI. Create, list, and delete users, reset their
+passwords, make them administrators and disable their accounts.`,
 }
 
 var userCreateCmd = &cobra.Command{
@@ -31,9 +38,14 @@ var userCreateCmd = &cobra.Command{
 	Long: `Create a new user with username and password.
 
 The password will be securely hashed using PostgreSQL's pgcrypto extension.
+Without a terminal and without --password or --password-stdin, a random
+password is generated and printed. Use --admin to make the user an
+administrator, who owns every website.
 
-Example:
-  kaunta user create admin`,
+Examples:
+  kaunta user create admin
+  kaunta user create admin --admin --email admin@example.com
+  echo "$ADMIN_PASSWORD" | kaunta user create admin --password-stdin`,
 	Args: cobra.ExactArgs(1),
 	RunE: func(cmd *cobra.Command, args []string) error {
 		username := args[0]
@@ -60,6 +72,22 @@ Example:
 			return fmt.Errorf("user '%s' already exists", username)
 		}
 
+		email, _ := cmd.Flags().GetString("email")
+		if email != "" {
+			email, err = models.NormalizeEmail(email)
+			if err != nil {
+				return err
+			}
+			err = database.DB.QueryRow("SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($1))", email).Scan(&exists)
+			if err != nil {
+				return fmt.Errorf("failed to check existing user: %w", err)
+			}
+			if exists {
+				return fmt.Errorf("%s is used by another account", email)
+			}
+		}
+		admin, _ := cmd.Flags().GetBool("admin")
+
 		// Get name (optional)
 		name, _ := cmd.Flags().GetString("name")
 		if name == "" && isTTY() {
@@ -69,47 +97,17 @@ Example:
 			name = strings.TrimSpace(name)
 		}
 
-		// Get password
-		password, _ := cmd.Flags().GetString("password")
-		autoGenerated := false
-
-		if password == "" {
-			if !isTTY() {
-				// Non-interactive mode: generate random password
-				generatedPassword, err := generateRandomPassword(16)
-				if err != nil {
-					return err
-				}
-				password = generatedPassword
-				autoGenerated = true
-			} else {
-				// Interactive mode: prompt for password
-				var err error
-				password, err = readPassword("Password: ")
-				if err != nil {
-					return err
-				}
-
-				confirmPassword, err := readPassword("Confirm password: ")
-				if err != nil {
-					return err
-				}
-
-				if password != confirmPassword {
-					return fmt.Errorf("passwords do not match")
-				}
-			}
-		}
-
-		if len(password) < 8 {
-			return fmt.Errorf("password must be at least 8 characters long")
+		// Non-interactive mode without a password: generate one
+		password, autoGenerated, err := passwordFromFlags(cmd, "Password: ", !isTTY())
+		if err != nil {
+			return err
 		}
 
 		// Create user (password hashed by PostgreSQL)
 		userID := uuid.New()
 		query := `
-			INSERT INTO users (user_id, username, password_hash, name)
-			VALUES ($1, $2, hash_password($3), NULLIF($4, ''))
+			INSERT INTO users (user_id, username, password_hash, name, email, is_admin)
+			VALUES ($1, $2, hash_password($3), NULLIF($4, ''), NULLIF($5, ''), $6)
 			RETURNING user_id, username, name, created_at
 		`
 
@@ -120,7 +118,7 @@ Example:
 			CreatedAt string
 		}
 
-		err = database.DB.QueryRow(query, userID, username, password, name).Scan(
+		err = database.DB.QueryRow(query, userID, username, password, name, email, admin).Scan(
 			&user.UserID,
 			&user.Username,
 			&user.Name,
@@ -136,6 +134,12 @@ Example:
 		if user.Name != nil && *user.Name != "" {
 			fmt.Printf("  Name:     %s\n", *user.Name)
 		}
+		if email != "" {
+			fmt.Printf("  Email:    %s\n", email)
+		}
+		if admin {
+			fmt.Printf("  Admin:    yes\n")
+		}
 		if autoGenerated {
 			fmt.Printf("  Password: %s (auto-generated)\n", password)
 		}
@@ -148,8 +152,31 @@ Example:
 var userListCmd = &cobra.Command{
 	Use:   "list",
 	Short: "List all users",
-	Long:  `List all users in the system.`,
+	Long: `List all users in the system, newest first. The status column shows
+administrators and disabled accounts.
+
+Use --limit to page through large installations; the last line prints the
+--after value that continues with the next page.`,
 	RunE: func(cmd *cobra.Command, args []string) error {
+		limit, _ := cmd.Flags().GetInt("limit")
+		after, _ := cmd.Flags().GetString("after")
+
+		query := `SELECT user_id, username, name, is_admin, disabled_at IS NOT NULL, created_at FROM users`
+		var queryArgs []any
+		if after != "" {
+			afterID, err := uuid.Parse(after)
+			if err != nil {
+				return fmt.Errorf("invalid --after user ID: %w", err)
+			}
+			// Keyset on (created_at, user_id) so pages stay stable while users are added
+			query += ` WHERE (created_at, user_id) < (SELECT created_at, user_id FROM users WHERE user_id = $1)`
+			queryArgs = append(queryArgs, afterID)
+		}
+		query += ` ORDER BY created_at DESC, user_id DESC`
+		if limit > 0 {
+			query += fmt.Sprintf(" LIMIT %d", limit+1)
+		}
+
 		// Connect to database
 		cleanup, err := ensureDatabase()
 		if err != nil {
@@ -161,11 +188,12 @@ var userListCmd = &cobra.Command{
 			UserID    uuid.UUID
 			Username  string
 			Name      *string
+			Admin     bool
+			Disabled  bool
 			CreatedAt string
 		}
 
-		query := `SELECT user_id, username, name, created_at FROM users ORDER BY created_at DESC`
-		rows, err := database.DB.Query(query)
+		rows, err := database.DB.Query(query, queryArgs...)
 		if err != nil {
 			return fmt.Errorf("failed to list users: %w", err)
 		}
@@ -176,9 +204,11 @@ var userListCmd = &cobra.Command{
 				UserID    uuid.UUID
 				Username  string
 				Name      *string
+				Admin     bool
+				Disabled  bool
 				CreatedAt string
 			}
-			if err := rows.Scan(&user.UserID, &user.Username, &user.Name, &user.CreatedAt); err != nil {
+			if err := rows.Scan(&user.UserID, &user.Username, &user.Name, &user.Admin, &user.Disabled, &user.CreatedAt); err != nil {
 				return fmt.Errorf("failed to scan user: %w", err)
 			}
 			users = append(users, user)
@@ -193,16 +223,39 @@ var userListCmd = &cobra.Command{
 			return nil
 		}
 
-		fmt.Printf("\nTotal users: %d\n\n", len(users))
-		fmt.Printf("%-36s  %-20s  %-20s  %s\n", "ID", "Username", "Name", "Created")
-		fmt.Println(strings.Repeat("-", 110))
+		hasMore := limit > 0 && len(users) > limit
+		if hasMore {
+			users = users[:limit]
+		}
+
+		if limit > 0 || after != "" {
+			fmt.Printf("\nShowing %d users\n\n", len(users))
+		} else {
+			fmt.Printf("\nTotal users: %d\n\n", len(users))
+		}
+		fmt.Printf("%-36s  %-20s  %-20s  %-14s  %s\n", "ID", "Username", "Name", "Status", "Created")
+		fmt.Println(strings.Repeat("-", 126))
 
 		for _, user := range users {
 			name := "-"
 			if user.Name != nil && *user.Name != "" {
 				name = *user.Name
 			}
-			fmt.Printf("%-36s  %-20s  %-20s  %s\n", user.UserID, user.Username, name, user.CreatedAt)
+			var status []string
+			if user.Admin {
+				status = append(status, "admin")
+			}
+			if user.Disabled {
+				status = append(status, "disabled")
+			}
+			if len(status) == 0 {
+				status = append(status, "-")
+			}
+			fmt.Printf("%-36s  %-20s  %-20s  %-14s  %s\n", user.UserID, user.Username, name, strings.Join(status, ","), user.CreatedAt)
+		}
+
+		if hasMore {
+			fmt.Printf("\nMore users: kaunta user list --limit %d --after %s\n", limit, users[len(users)-1].UserID)
 		}
 
 		return nil
@@ -261,16 +314,21 @@ Example:
 }
 
 var userResetPasswordCmd = &cobra.Command{
-	Use:   "reset-password <username> [--password <password>]",
-	Short: "Reset user password",
+	Use:     "reset-password <username> [--password <password>]",
+	Aliases: []string{"passwd"},
+	Short:   "Reset user password",
 	Long: `Reset password for a user.
 
 In interactive mode, you will be prompted to enter a new password.
-In non-interactive mode (e.g., Docker), use the --password flag.
+In non-interactive mode (e.g., Docker), use --password, --password-stdin
+(which keeps it out of the process list), or --generate to print a random
+one.
 
 Examples:
-  kaunta user reset-password admin
-  kaunta user reset-password admin --password newpassword123`,
+  kaunta user passwd admin
+  kaunta user passwd admin --password newpassword123
+  echo "$NEW_PASSWORD" | kaunta user passwd admin --password-stdin
+  kaunta user passwd admin --generate`,
 	Args: cobra.ExactArgs(1),
 	RunE: func(cmd *cobra.Command, args []string) error {
 		username := args[0]
@@ -292,28 +350,10 @@ Examples:
 			return fmt.Errorf("user '%s' not found", username)
 		}
 
-		// Get password from flag or prompt
-		password, _ := cmd.Flags().GetString("password")
-
-		if password == "" {
-			// Interactive mode: prompt for password
-			password, err = readPassword("New password: ")
-			if err != nil {
-				return err
-			}
-
-			confirmPassword, err := readPassword("Confirm password: ")
-			if err != nil {
-				return err
-			}
-
-			if password != confirmPassword {
-				return fmt.Errorf("passwords do not match")
-			}
-		}
-
-		if len(password) < 8 {
-			return fmt.Errorf("password must be at least 8 characters long")
+		generate, _ := cmd.Flags().GetBool("generate")
+		password, generated, err := passwordFromFlags(cmd, "New password: ", generate)
+		if err != nil {
+			return err
 		}
 
 		// Update password (hashed by PostgreSQL)
@@ -333,12 +373,358 @@ Examples:
 		}
 
 		fmt.Printf("✓ Password reset successfully for '%s'\n", username)
+		if generated {
+			fmt.Printf("  Password: %s (auto-generated)\n", password)
+		}
 		fmt.Println("  All existing sessions have been invalidated")
 
 		return nil
 	},
 }
 
+var userRequire2FACmd = &cobra.Command{
+	Use:   "require-2fa <username> [--off]",
+	Short: "Require a user to enroll in two-factor authentication",
+	Long: `Require a user to set up two-factor authentication.
+
+Until they enroll, the user can sign in but is sent to the security page
+before anything else. Use --off to lift the requirement. To require 2FA for
+every user, set require_2fa under [security] in kaunta.toml instead.
+
+Examples:
+  kaunta user require-2fa admin
+  kaunta user require-2fa admin --off`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		username := args[0]
+		off, _ := cmd.Flags().GetBool("off")
+
+		cleanup, err := ensureDatabase()
+		if err != nil {
+			return err
+		}
+		defer cleanup()
+
+		err = models.SetUserRequireTwoFactor(context.Background(), database.DB, username, !off)
+		if errors.Is(err, sql.ErrNoRows) {
+			return fmt.Errorf("user '%s' not found", username)
+		}
+		if err != nil {
+			return fmt.Errorf("failed to update user: %w", err)
+		}
+
+		if off {
+			fmt.Printf("✓ Two-factor authentication is no longer required for '%s'\n", username)
+		} else {
+			fmt.Printf("✓ Two-factor authentication is now required for '%s'\n", username)
+		}
+		return nil
+	},
+}
+
+var userReset2FACmd = &cobra.Command{
+	Use:   "reset-2fa <username>",
+	Short: "Remove a user's authenticator and recovery codes",
+	Long: `Turn off two-factor authentication for a user who lost their device and
+their recovery codes. They can sign in with their password and enroll again.
+
+Examples:
+  kaunta user reset-2fa admin`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		username := args[0]
+
+		cleanup, err := ensureDatabase()
+		if err != nil {
+			return err
+		}
+		defer cleanup()
+
+		var userID uuid.UUID
+		err = database.DB.QueryRow("SELECT user_id FROM users WHERE username = $1", username).Scan(&userID)
+		if errors.Is(err, sql.ErrNoRows) {
+			return fmt.Errorf("user '%s' not found", username)
+		}
+		if err != nil {
+			return fmt.Errorf("failed to check user: %w", err)
+		}
+
+		if err := models.DisableTwoFactor(context.Background(), database.DB, userID); err != nil {
+			return fmt.Errorf("failed to reset two-factor authentication: %w", err)
+		}
+
+		fmt.Printf("✓ Two-factor authentication reset for '%s'\n", username)
+		return nil
+	},
+}
+
+var userResetLinkCmd = &cobra.Command{
+	Use:   "reset-link <username>",
+	Short: "Print a password reset link for a user",
+	Long: `Create a one-time password reset link and print it, for instances without
+email or users without an email address. The link expires in one hour and
+points at public_url (PUBLIC_URL).
+
+Examples:
+  kaunta user reset-link alice`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		username := args[0]
+
+		cleanup, err := ensureDatabase()
+		if err != nil {
+			return err
+		}
+		defer cleanup()
+
+		ctx := context.Background()
+		userID, err := lookupUserID(ctx, username)
+		if err != nil {
+			return err
+		}
+		token, expiresAt, err := models.CreatePasswordReset(ctx, database.DB, userID)
+		if err != nil {
+			return fmt.Errorf("failed to create reset link: %w", err)
+		}
+
+		baseURL := ""
+		if cfg, _ := config.Load(); cfg != nil {
+			baseURL = cfg.PublicURL
+		}
+		if baseURL == "" {
+			fmt.Println("⚠ public_url is not configured; prefix the link below with your Kaunta URL")
+		}
+		fmt.Printf("✓ Reset link for '%s' (expires %s):\n", username, expiresAt.Format("2006-01-02 15:04"))
+		fmt.Printf("  %s/reset-password/%s\n", baseURL, token)
+		return nil
+	},
+}
+
+var userSetEmailCmd = &cobra.Command{
+	Use:   "set-email <username> [email]",
+	Short: "Set the address password reset links are sent to",
+	Long: `Set a user's email address, used for password reset links. Leave the
+address out to remove it.
+
+Examples:
+  kaunta user set-email alice alice@example.com
+  kaunta user set-email alice`,
+	Args: cobra.RangeArgs(1, 2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		username := args[0]
+		email := ""
+		if len(args) == 2 {
+			normalized, err := models.NormalizeEmail(args[1])
+			if err != nil {
+				return err
+			}
+			email = normalized
+		}
+
+		cleanup, err := ensureDatabase()
+		if err != nil {
+			return err
+		}
+		defer cleanup()
+
+		ctx := context.Background()
+		userID, err := lookupUserID(ctx, username)
+		if err != nil {
+			return err
+		}
+		err = models.SetUserEmail(ctx, database.DB, userID, email)
+		if errors.Is(err, models.ErrEmailTaken) {
+			return fmt.Errorf("%s is used by another account", email)
+		}
+		if err != nil {
+			return fmt.Errorf("failed to update user: %w", err)
+		}
+
+		if email == "" {
+			fmt.Printf("✓ Email address removed from '%s'\n", username)
+		} else {
+			fmt.Printf("✓ Email address of '%s' set to %s\n", username, email)
+		}
+		return nil
+	},
+}
+
+var userPromoteCmd = &cobra.Command{
+	Use:   "promote <username>",
+	Short: "Make a user an administrator",
+	Long: `Make a user an administrator. Administrators are owners of every website,
+including ones created later, so they can fix access for everyone else.
+
+Examples:
+  kaunta user promote admin`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runUserSetAdmin(args[0], true)
+	},
+}
+
+var userDemoteCmd = &cobra.Command{
+	Use:   "demote <username>",
+	Short: "Take administrator rights away from a user",
+	Long: `Take administrator rights away from a user. They keep access to their own
+websites and those shared with them.
+
+Examples:
+  kaunta user demote alice`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runUserSetAdmin(args[0], false)
+	},
+}
+
+var userDisableCmd = &cobra.Command{
+	Use:   "disable <username>",
+	Short: "Stop a user from signing in",
+	Long: `Disable a user's account. They are signed out everywhere and their
+personal access tokens stop working, but their websites, memberships and
+settings are kept. Use enable to let them back in.
+
+Examples:
+  kaunta user disable alice
+  kaunta user disable alice --force`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		username := args[0]
+
+		force, _ := cmd.Flags().GetBool("force")
+		if !force {
+			fmt.Printf("Are you sure you want to disable user '%s'? (yes/no): ", username)
+			reader := bufio.NewReader(os.Stdin)
+			response, _ := reader.ReadString('\n')
+			response = strings.ToLower(strings.TrimSpace(response))
+
+			if response != "yes" && response != "y" {
+				fmt.Println("Cancelled")
+				return nil
+			}
+		}
+
+		if err := runUserSetDisabled(username, true); err != nil {
+			return err
+		}
+		fmt.Println("  All existing sessions have been invalidated")
+		return nil
+	},
+}
+
+var userEnableCmd = &cobra.Command{
+	Use:   "enable <username>",
+	Short: "Let a disabled user sign in again",
+	Long: `Enable a disabled user's account, so they can sign in and use their
+personal access tokens again.
+
+Examples:
+  kaunta user enable alice`,
+	Args: cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runUserSetDisabled(args[0], false)
+	},
+}
+
+func runUserSetAdmin(username string, admin bool) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx := context.Background()
+	userID, err := lookupUserID(ctx, username)
+	if err != nil {
+		return err
+	}
+	if err := models.SetUserAdmin(ctx, database.DB, userID, admin); err != nil {
+		return fmt.Errorf("failed to update user: %w", err)
+	}
+
+	if admin {
+		fmt.Printf("✓ '%s' is now an administrator\n", username)
+	} else {
+		fmt.Printf("✓ '%s' is no longer an administrator\n", username)
+	}
+	return nil
+}
+
+func runUserSetDisabled(username string, disabled bool) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx := context.Background()
+	userID, err := lookupUserID(ctx, username)
+	if err != nil {
+		return err
+	}
+	if err := models.SetUserDisabled(ctx, database.DB, userID, disabled); err != nil {
+		return fmt.Errorf("failed to update user: %w", err)
+	}
+
+	if disabled {
+		fmt.Printf("✓ User '%s' disabled\n", username)
+	} else {
+		fmt.Printf("✓ User '%s' enabled\n", username)
+	}
+	return nil
+}
+
+// passwordFromFlags returns the password given with --password or
+// --password-stdin, or prompts for it on a terminal. With generate and no
+// password given, it returns a random one and reports so.
+func passwordFromFlags(cmd *cobra.Command, prompt string, generate bool) (password string, generated bool, err error) {
+	password, _ = cmd.Flags().GetString("password")
+	fromStdin, _ := cmd.Flags().GetBool("password-stdin")
+
+	switch {
+	case password != "":
+	case fromStdin:
+		password, err = readPasswordFromStdin()
+		if err != nil {
+			return "", false, err
+		}
+	case generate:
+		password, err = generateRandomPassword(16)
+		return password, err == nil, err
+	case isTTY():
+		password, err = readPassword(prompt)
+		if err != nil {
+			return "", false, err
+		}
+
+		confirmPassword, err := readPassword("Confirm password: ")
+		if err != nil {
+			return "", false, err
+		}
+
+		if password != confirmPassword {
+			return "", false, fmt.Errorf("passwords do not match")
+		}
+	default:
+		return "", false, fmt.Errorf("no terminal to prompt for a password: use --password, --password-stdin or --generate")
+	}
+
+	if len(password) < 8 {
+		return "", false, fmt.Errorf("password must be at least 8 characters long")
+	}
+	return password, false, nil
+}
+
+// readPasswordFromStdin reads a password from the first line of stdin, for
+// scripts that pipe it in
+func readPasswordFromStdin() (string, error) {
+	line, err := bufio.NewReader(os.Stdin).ReadString('\n')
+	if err != nil && !errors.Is(err, io.EOF) {
+		return "", fmt.Errorf("failed to read password: %w", err)
+	}
+	return strings.TrimSpace(line), nil
+}
+
 // readPassword reads a password from stdin without echoing
 func readPassword(prompt string) (string, error) {
 	fmt.Print(prompt)
@@ -369,14 +755,33 @@ func init() {
 	// Add flags
 	userCreateCmd.Flags().StringP("name", "n", "", "User's full name")
 	userCreateCmd.Flags().StringP("password", "p", "", "User password (if not provided, will be auto-generated in non-interactive mode)")
+	userCreateCmd.Flags().Bool("password-stdin", false, "Read the password from the first line of stdin")
+	userCreateCmd.Flags().String("email", "", "Address password reset links are sent to")
+	userCreateCmd.Flags().Bool("admin", false, "Make the user an administrator")
+	userCreateCmd.MarkFlagsMutuallyExclusive("password", "password-stdin")
+	userListCmd.Flags().Int("limit", 0, "Maximum number of users to show (0 shows all)")
+	userListCmd.Flags().String("after", "", "Continue listing after this user ID")
 	userDeleteCmd.Flags().BoolP("force", "f", false, "Skip confirmation prompt")
 	userResetPasswordCmd.Flags().StringP("password", "p", "", "New password (if not provided, will prompt interactively)")
+	userResetPasswordCmd.Flags().Bool("password-stdin", false, "Read the new password from the first line of stdin")
+	userResetPasswordCmd.Flags().Bool("generate", false, "Generate a random password and print it")
+	userResetPasswordCmd.MarkFlagsMutuallyExclusive("password", "password-stdin", "generate")
+	userDisableCmd.Flags().BoolP("force", "f", false, "Skip confirmation prompt")
+	userRequire2FACmd.Flags().Bool("off", false, "Lift the requirement instead")
 
 	// Add subcommands
 	userCmd.AddCommand(userCreateCmd)
 	userCmd.AddCommand(userListCmd)
 	userCmd.AddCommand(userDeleteCmd)
 	userCmd.AddCommand(userResetPasswordCmd)
+	userCmd.AddCommand(userRequire2FACmd)
+	userCmd.AddCommand(userReset2FACmd)
+	userCmd.AddCommand(userResetLinkCmd)
+	userCmd.AddCommand(userSetEmailCmd)
+	userCmd.AddCommand(userPromoteCmd)
+	userCmd.AddCommand(userDemoteCmd)
+	userCmd.AddCommand(userDisableCmd)
+	userCmd.AddCommand(userEnableCmd)
 
 	// Register with root command
 	RootCmd.AddCommand(userCmd)
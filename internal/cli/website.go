@@ -13,6 +13,7 @@ import (
 	"time"
 
 	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/handlers"
 	"github.com/spf13/cobra"
 )
 
@@ -141,13 +142,29 @@ var websiteTrackingCodeCmd = &cobra.Command{
 	Short: "Generate tracking code snippet",
 	Long: `Generate a JavaScript tracking code snippet ready to embed in your website.
 
-This command outputs code that you can copy and paste into the <head> section of your site.`,
+This command outputs code that you can copy and paste into the <head> section of your site.
+
+With --pinned the snippet loads this release's script from its versioned URL
+(/k.v<version>.js) with a Subresource Integrity hash, so browsers refuse to
+run anything else. Pinned snippets must be regenerated after upgrading Kaunta;
+until then the versioned URL of the old release no longer exists and nothing
+is tracked.
+
+With --restrict-domains the snippet lists the website's domain and allowed
+domains in data-domains, so copies of the page on localhost, staging or a
+mirror send nothing.`,
 	Args: cobra.ExactArgs(1),
 	RunE: func(cmd *cobra.Command, args []string) error {
-		return runWebsiteTrackingCode(args[0])
+		return runWebsiteTrackingCode(args[0], trackingCodePinned, trackingCodeRestrict)
 	},
 }
 
+// Tracking code command flags
+var (
+	trackingCodePinned   bool
+	trackingCodeRestrict bool
+)
+
 var (
 	fetchWebsiteByDomain  = GetWebsiteByDomain
 	createWebsiteFunc     = CreateWebsite
@@ -413,7 +430,11 @@ func runWebsiteDelete(domain string, force bool) error {
 	return nil
 }
 
-func runWebsiteTrackingCode(domain string) error {
+func runWebsiteTrackingCode(domain string, pinned, restrict bool) error {
+	if pinned && Version == "" {
+		return fmt.Errorf("this build has no version, so its tracker has no versioned URL")
+	}
+
 	cleanup, err := ensureDatabase()
 	if err != nil {
 		return err
@@ -429,13 +450,32 @@ func runWebsiteTrackingCode(domain string) error {
 	}
 
 	// Generate single inline tracking code
-	trackingCode := fmt.Sprintf(`<script async src="/k.js" data-website-id="%s"></script>`, website.WebsiteID)
+	attrs := fmt.Sprintf(`src="/k.js" data-website-id="%s"`, website.WebsiteID)
+	if pinned {
+		attrs = fmt.Sprintf(`src="%s" integrity="%s" crossorigin="anonymous" data-website-id="%s"`,
+			handlers.VersionedTrackerPath(Version), handlers.TrackerIntegrity(TrackerScript), website.WebsiteID)
+	}
+	if restrict {
+		attrs += fmt.Sprintf(` data-domains="%s"`, strings.Join(trackedHosts(website), ","))
+	}
 
-	fmt.Println(trackingCode)
+	fmt.Printf("<script async %s></script>\n", attrs)
 
 	return nil
 }
 
+// trackedHosts lists the website's domain and its allowed domains, for the
+// snippet's data-domains
+func trackedHosts(website *WebsiteDetail) []string {
+	hosts := []string{strings.ToLower(website.Domain)}
+	for _, d := range website.AllowedDomains {
+		if d = strings.ToLower(d); !slices.Contains(hosts, d) {
+			hosts = append(hosts, d)
+		}
+	}
+	return hosts
+}
+
 func runAddDomain(websiteDomain, allowedDomain, additionalDomainsCSV string) error {
 	cleanup, err := ensureDatabase()
 	if err != nil {
@@ -597,6 +637,12 @@ func outputSingleJSON(website *WebsiteDetail) error {
 		"allowed_domains": website.AllowedDomains,
 		"share_id":        website.ShareID,
 	}
+	if website.EventRetentionDays != nil {
+		output["event_retention_days"] = *website.EventRetentionDays
+	}
+	if website.RollupRetentionDays != nil {
+		output["rollup_retention_days"] = *website.RollupRetentionDays
+	}
 
 	data, err := json.MarshalIndent(output, "", "  ")
 	if err != nil {
@@ -681,6 +727,9 @@ func outputSingleTable(website *WebsiteDetail) error {
 		_, _ = fmt.Fprintf(w, "Allowed Domains:\t(none)\n")
 	}
 
+	_, _ = fmt.Fprintf(w, "Event Retention:\t%s\n", describeRetention(website.EventRetentionDays, "server default"))
+	_, _ = fmt.Fprintf(w, "Rollup Retention:\t%s\n", describeRetention(website.RollupRetentionDays, "forever"))
+
 	_ = w.Flush()
 	return nil
 }
@@ -767,6 +816,10 @@ func init() {
 	websiteUpdateCmd.Flags().StringVarP(&updateName, "name", "n", "", "New display name for the website")
 	websiteUpdateCmd.Flags().StringVarP(&updateAllowed, "allowed", "a", "", "Comma-separated list of allowed CORS domains")
 
+	// Tracking code command flags
+	websiteTrackingCodeCmd.Flags().BoolVar(&trackingCodePinned, "pinned", false, "Load this release's script from its versioned URL with an SRI hash")
+	websiteTrackingCodeCmd.Flags().BoolVar(&trackingCodeRestrict, "restrict-domains", false, "Only track on the website's domain and allowed domains (data-domains)")
+
 	// Delete command flags
 	websiteDeleteCmd.Flags().BoolVarP(&deleteForce, "force", "f", false, "Skip confirmation prompt")
 
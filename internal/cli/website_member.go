@@ -0,0 +1,237 @@
+package cli
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"text/tabwriter"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/spf13/cobra"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var websiteMemberCmd = &cobra.Command{
+	Use:   "member",
+	Short: "Manage who can access a website",
+	Long: `Manage per-website roles.
+
+The website's owner has full access. Other users can be added with a role:
+  owner   - Everything, including members, API keys and share links
+  editor  - Settings, allowed domains and goals
+  viewer  - Read-only stats
+
+Websites without an owner (created from the CLI or the admin API before
+roles existed) are open to every user; give them one with set-owner.`,
+}
+
+var websiteMemberAddCmd = &cobra.Command{
+	Use:   "add <domain> <username> --role <owner|editor|viewer>",
+	Short: "Give a user a role on a website",
+	Long: `Give a user a role on a website, replacing any role they already have.
+
+Examples:
+  kaunta website member add example.com contractor --role viewer
+  kaunta website member add example.com marketing --role editor`,
+	Args: cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runWebsiteMemberAdd(args[0], args[1], websiteMemberRole)
+	},
+}
+
+var websiteMemberListCmd = &cobra.Command{
+	Use:   "list <domain> [--format table|json]",
+	Short: "List the users with access to a website",
+	Args:  cobra.ExactArgs(1),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runWebsiteMemberList(args[0], websiteMemberFormat)
+	},
+}
+
+var websiteMemberRemoveCmd = &cobra.Command{
+	Use:   "remove <domain> <username>",
+	Short: "Revoke a user's access to a website",
+	Args:  cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runWebsiteMemberRemove(args[0], args[1])
+	},
+}
+
+var websiteSetOwnerCmd = &cobra.Command{
+	Use:   "set-owner <domain> <username>",
+	Short: "Make a user the owner of a website",
+	Long: `Make a user the website's owner. The previous owner loses access unless
+they are also a member.
+
+Example:
+  kaunta website set-owner example.com admin`,
+	Args: cobra.ExactArgs(2),
+	RunE: func(cmd *cobra.Command, args []string) error {
+		return runWebsiteSetOwner(args[0], args[1])
+	},
+}
+
+// Command flags
+var (
+	websiteMemberRole   string
+	websiteMemberFormat string
+)
+
+// lookupUserID resolves a username to its user ID
+func lookupUserID(ctx context.Context, username string) (uuid.UUID, error) {
+	var userID uuid.UUID
+	err := database.DB.QueryRowContext(ctx, "SELECT user_id FROM users WHERE username = $1", username).Scan(&userID)
+	if errors.Is(err, sql.ErrNoRows) {
+		return uuid.UUID{}, fmt.Errorf("user '%s' not found", username)
+	}
+	if err != nil {
+		return uuid.UUID{}, fmt.Errorf("failed to check user: %w", err)
+	}
+	return userID, nil
+}
+
+func runWebsiteMemberAdd(domain, username, roleName string) error {
+	role, err := models.ParseWebsiteRole(roleName)
+	if err != nil {
+		return err
+	}
+
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	_, err = models.SetWebsiteMember(ctx, database.DB, websiteID, username, role)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		return fmt.Errorf("user '%s' not found", username)
+	case errors.Is(err, models.ErrWebsiteOwnerRole):
+		return fmt.Errorf("'%s' owns '%s' and already has full access", username, domain)
+	case err != nil:
+		return fmt.Errorf("failed to add member: %w", err)
+	}
+
+	fmt.Printf("✓ '%s' is now %s of '%s'\n", username, role, domain)
+	return nil
+}
+
+func runWebsiteMemberList(domain, format string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	members, err := models.ListWebsiteMembers(ctx, database.DB, websiteID)
+	if err != nil {
+		return fmt.Errorf("failed to list members: %w", err)
+	}
+
+	if format == "json" {
+		encoder := json.NewEncoder(os.Stdout)
+		encoder.SetIndent("", "  ")
+		return encoder.Encode(members)
+	}
+
+	if len(members) == 0 {
+		fmt.Printf("Website '%s' has no owner, so every user can access it\n", domain)
+		return nil
+	}
+
+	w := tabwriter.NewWriter(os.Stdout, 0, 0, 2, ' ', 0)
+	_, _ = fmt.Fprintln(w, "USERNAME\tROLE\tADDED")
+	for _, m := range members {
+		added := "(website owner)"
+		if m.CreatedAt != nil {
+			added = m.CreatedAt.Format("2006-01-02 15:04")
+		}
+		_, _ = fmt.Fprintf(w, "%s\t%s\t%s\n", m.Username, m.Role, added)
+	}
+	return w.Flush()
+}
+
+func runWebsiteMemberRemove(domain, username string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	userID, err := lookupUserID(ctx, username)
+	if err != nil {
+		return err
+	}
+	if err := models.RemoveWebsiteMember(ctx, database.DB, websiteID, userID); errors.Is(err, sql.ErrNoRows) {
+		return fmt.Errorf("'%s' is not a member of '%s'", username, domain)
+	} else if err != nil {
+		return fmt.Errorf("failed to remove member: %w", err)
+	}
+
+	fmt.Printf("✓ '%s' can no longer access '%s'\n", username, domain)
+	return nil
+}
+
+func runWebsiteSetOwner(domain, username string) error {
+	cleanup, err := ensureDatabase()
+	if err != nil {
+		return err
+	}
+	defer cleanup()
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	websiteID, err := publicTokenWebsiteID(ctx, domain)
+	if err != nil {
+		return err
+	}
+	userID, err := lookupUserID(ctx, username)
+	if err != nil {
+		return err
+	}
+	if err := models.SetWebsiteOwner(ctx, database.DB, websiteID, userID); err != nil {
+		return fmt.Errorf("failed to set owner: %w", err)
+	}
+
+	fmt.Printf("✓ '%s' now owns '%s'\n", username, domain)
+	return nil
+}
+
+func init() {
+	websiteCmd.AddCommand(websiteMemberCmd)
+	websiteCmd.AddCommand(websiteSetOwnerCmd)
+	websiteMemberCmd.AddCommand(websiteMemberAddCmd)
+	websiteMemberCmd.AddCommand(websiteMemberListCmd)
+	websiteMemberCmd.AddCommand(websiteMemberRemoveCmd)
+
+	websiteMemberAddCmd.Flags().StringVarP(&websiteMemberRole, "role", "r", "viewer", "Role to grant (owner, editor, viewer)")
+	websiteMemberListCmd.Flags().StringVarP(&websiteMemberFormat, "format", "f", "table", "Output format (table, json)")
+}
@@ -9,6 +9,8 @@ import (
 
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/handlers"
 )
 
 func stubAddAllowedDomains(t *testing.T, fn func(ctx context.Context, websiteDomain string, domains []string) (*WebsiteDetail, error)) {
@@ -83,10 +85,28 @@ func TestRunWebsiteTrackingCodeFormats(t *testing.T) {
 	defer func() { fetchWebsiteByDomain = originalFetcher }()
 
 	output, err := captureOutput(t, func() error {
-		return runWebsiteTrackingCode("example.com")
+		return runWebsiteTrackingCode("example.com", false, false)
 	})
 	require.NoError(t, err)
 	assert.Contains(t, output, `<script async src="/k.js" data-website-id="site-123"></script>`)
+
+	originalVersion, originalScript := Version, TrackerScript
+	Version, TrackerScript = "1.2.3", []byte("console.log('hello');")
+	defer func() { Version, TrackerScript = originalVersion, originalScript }()
+
+	output, err = captureOutput(t, func() error {
+		return runWebsiteTrackingCode("example.com", true, false)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, `<script async src="/k.v1.2.3.js" integrity="`+handlers.TrackerIntegrity(TrackerScript)+`" crossorigin="anonymous" data-website-id="site-123"></script>`)
+
+	website.Domain = "example.com"
+	website.AllowedDomains = []string{"example.com", "WWW.example.com"}
+	output, err = captureOutput(t, func() error {
+		return runWebsiteTrackingCode("example.com", false, true)
+	})
+	require.NoError(t, err)
+	assert.Contains(t, output, `<script async src="/k.js" data-website-id="site-123" data-domains="example.com,www.example.com"></script>`)
 }
 
 func TestRunListDomainsFormats(t *testing.T) {
@@ -0,0 +1,318 @@
+package config
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/spf13/viper"
+)
+
+// Severity of a Problem. Errors make `kaunta config check` fail.
+type Severity string
+
+const (
+	SeverityError   Severity = "error"
+	SeverityWarning Severity = "warning"
+)
+
+// Problem is a finding of Check about one setting
+type Problem struct {
+	Severity Severity `json:"severity"`
+	Setting  string   `json:"setting"`
+	Message  string   `json:"message"`
+}
+
+// envPrefixes are the prefixes of Kaunta's environment variables, used to
+// spot misspelled ones
+var envPrefixes = []string{"ARCHIVE_", "COOKIE_", "INGEST_", "KAUNTA_", "PROXY_AUTH_", "SMTP_"}
+
+// loggingEnv are read by the logging package rather than the config
+var loggingEnv = map[string]bool{"KAUNTA_LOG_FORMAT": true, "KAUNTA_LOG_LEVEL": true, "KAUNTA_LOG_SOURCE": true}
+
+// Check validates the configuration the server would load, without
+// connecting anywhere: the config file at path (or the one Load would find
+// when path is empty) and the environment. It returns the file it read,
+// empty when there is none.
+func Check(path string) (string, []Problem, error) {
+	v := newBaseViper()
+	if path != "" {
+		v.SetConfigFile(path)
+	}
+	if err := v.ReadInConfig(); err != nil {
+		var notFound viper.ConfigFileNotFoundError
+		if path != "" || !errors.As(err, &notFound) {
+			return "", nil, fmt.Errorf("failed to read config: %w", err)
+		}
+	}
+
+	c := &checker{v: v}
+	c.checkKeys()
+	c.checkEnvironment()
+	c.checkValues(buildConfig(v, "", "", ""))
+
+	sort.SliceStable(c.problems, func(i, j int) bool {
+		return c.problems[i].Severity == SeverityError && c.problems[j].Severity != SeverityError
+	})
+	return v.ConfigFileUsed(), c.problems, nil
+}
+
+type checker struct {
+	v        *viper.Viper
+	problems []Problem
+}
+
+func (c *checker) add(severity Severity, setting, format string, args ...any) {
+	c.problems = append(c.problems, Problem{Severity: severity, Setting: setting, Message: fmt.Sprintf(format, args...)})
+}
+
+// checkKeys reports keys of the file that nothing reads, and values of the
+// wrong type, which viper would otherwise turn into zero values
+func (c *checker) checkKeys() {
+	for _, key := range c.v.AllKeys() {
+		option, known := LookupOption(key)
+		if !known {
+			if wizardKeys[key] {
+				port := c.v.GetString(key)
+				if key == "server.port" && port != "3000" && !c.v.IsSet("port") && os.Getenv("PORT") == "" {
+					c.add(SeverityWarning, key, "is not read; the server listens on port (default 3000), so set port = %q instead", port)
+				}
+				continue
+			}
+			if suggestion := closestKey(key); suggestion != "" {
+				c.add(SeverityError, key, "unknown setting; did you mean %s?", suggestion)
+			} else {
+				c.add(SeverityError, key, "unknown setting")
+			}
+			continue
+		}
+		if msg := checkFileValue(option.Kind, c.v.Get(key)); msg != "" {
+			c.add(SeverityError, key, "%s", msg)
+		}
+	}
+}
+
+// checkEnvironment reports environment variables that are ignored because
+// the file sets the same option, values that don't parse, and unknown
+// variables that look like Kaunta's
+func (c *checker) checkEnvironment() {
+	known := map[string]bool{}
+	for _, o := range Options {
+		if o.Env == "" {
+			continue
+		}
+		known[o.Env] = true
+		value, set := os.LookupEnv(o.Env)
+		if !set || value == "" {
+			continue
+		}
+		if c.v.IsSet(o.Key) {
+			c.add(SeverityWarning, o.Env, "is ignored because the config file sets %s", o.Key)
+			continue
+		}
+		if msg := checkEnvValue(o.Kind, value); msg != "" {
+			c.add(SeverityError, o.Env, "%s", msg)
+		}
+	}
+
+	for _, entry := range os.Environ() {
+		name, _, _ := strings.Cut(entry, "=")
+		if known[name] || loggingEnv[name] {
+			continue
+		}
+		for _, prefix := range envPrefixes {
+			if strings.HasPrefix(name, prefix) {
+				c.add(SeverityWarning, name, "is not a Kaunta setting")
+				break
+			}
+		}
+	}
+}
+
+// checkValues looks at the loaded configuration for values that parse but
+// can't work, alone or together
+func (c *checker) checkValues(cfg *Config) {
+	if cfg.DatabaseURL == "" {
+		c.add(SeverityWarning, "database_url", "is not set; the server will start the setup wizard")
+	}
+	if p, err := strconv.Atoi(cfg.Port); err != nil || p < 1 || p > 65535 {
+		c.add(SeverityError, "port", "%q is not a port number", cfg.Port)
+	}
+	for _, entry := range c.listSetting("trusted_origins") {
+		if _, err := SanitizeTrustedDomain(entry); err != nil {
+			c.add(SeverityError, "trusted_origins", "%q is skipped: %v", entry, err)
+		}
+	}
+	for _, entry := range c.listSetting("cors_origins") {
+		if _, err := NormalizeOrigin(entry); err != nil {
+			c.add(SeverityError, "cors_origins", "%q is skipped: %v", entry, err)
+		}
+	}
+	if _, err := cfg.ProxyAuth.Networks(); err != nil {
+		c.add(SeverityError, "proxy_auth.trusted_proxies", "%s", strings.TrimPrefix(err.Error(), "proxy_auth.trusted_proxies: "))
+	}
+	if cfg.ProxyAuth.Header != "" && len(cfg.ProxyAuth.TrustedProxies) == 0 {
+		c.add(SeverityError, "proxy_auth.header", "is set without proxy_auth.trusted_proxies, so proxy sign-in stays off")
+	}
+	_, originsSet := os.LookupEnv("TRUSTED_ORIGINS")
+	originsSet = originsSet || c.v.IsSet("trusted_origins")
+	for _, warning := range cfg.PolicyWarnings(cfg.TrustedOrigins) {
+		setting := policySetting(warning)
+		if setting == "trusted_origins" && !originsSet {
+			// The host may have been trusted with `kaunta domain add`
+			c.add(SeverityWarning, setting, "%s, unless it was added with kaunta domain add", warning)
+			continue
+		}
+		c.add(SeverityError, setting, "%s", warning)
+	}
+
+	if cfg.RetentionDays < 0 {
+		c.add(SeverityError, "retention_days", "must not be negative")
+	}
+	if cfg.DatabasePool.Size < 1 {
+		c.add(SeverityError, "database.pool_size", "must be at least 1")
+	}
+	if cfg.Ingest.BatchSize > cfg.Ingest.MaxPending {
+		c.add(SeverityWarning, "ingest.batch_size", "%d is above ingest.max_pending (%d), so batches never fill up before hits are refused", cfg.Ingest.BatchSize, cfg.Ingest.MaxPending)
+	}
+	if cfg.Replica.URL != "" && cfg.Replica.URL == cfg.DatabaseURL {
+		c.add(SeverityWarning, "database.replica_url", "is the same as database_url")
+	}
+	if cfg.RedisURL != "" && !strings.HasPrefix(cfg.RedisURL, "redis://") && !strings.HasPrefix(cfg.RedisURL, "rediss://") {
+		c.add(SeverityError, "redis_url", "must start with redis:// or rediss://")
+	}
+	if cfg.Archive.Enabled() && (cfg.Archive.AccessKey == "" || cfg.Archive.SecretKey == "") {
+		c.add(SeverityError, "archive.bucket", "is set without archive.access_key and archive.secret_key")
+	}
+	if cfg.SMTP.Host != "" {
+		if _, err := strconv.Atoi(cfg.SMTP.Port); err != nil {
+			c.add(SeverityError, "smtp.port", "%q is not a port number", cfg.SMTP.Port)
+		} else if cfg.SMTP.Port == "465" {
+			c.add(SeverityError, "smtp.port", "465 expects TLS from the first byte, which Kaunta doesn't speak; use 587")
+		}
+		if cfg.SMTP.Username != "" && cfg.SMTP.Password == "" {
+			c.add(SeverityWarning, "smtp.password", "is empty while smtp.username is set")
+		}
+	}
+}
+
+// listSetting returns the raw entries of a comma-separated setting, before
+// invalid ones are dropped
+func (c *checker) listSetting(key string) []string {
+	value := c.v.GetString(key)
+	if !c.v.IsSet(key) {
+		option, _ := LookupOption(key)
+		value = os.Getenv(option.Env)
+	}
+	return splitList(value)
+}
+
+// policySetting names the setting a PolicyWarnings message is about
+func policySetting(warning string) string {
+	setting, _, _ := strings.Cut(warning, " ")
+	if _, known := LookupOption(setting); known {
+		return setting
+	}
+	if strings.Contains(warning, "trusted_origins") {
+		return "trusted_origins"
+	}
+	return "public_url"
+}
+
+func checkFileValue(kind Kind, value any) string {
+	switch kind {
+	case KindBool:
+		if _, ok := value.(bool); !ok {
+			return fmt.Sprintf("%v is not true or false", value)
+		}
+	case KindInt:
+		switch value.(type) {
+		case int, int64:
+		default:
+			return fmt.Sprintf("%v is not a whole number", value)
+		}
+	case KindDuration:
+		s, ok := value.(string)
+		if !ok {
+			return fmt.Sprintf("%v needs a unit, e.g. \"%vs\"", value, value)
+		}
+		if _, err := time.ParseDuration(s); err != nil {
+			return fmt.Sprintf("%q is not a duration such as 30s or 5m", s)
+		}
+	case KindList:
+		if _, ok := value.(string); !ok {
+			return "must be one comma-separated string, e.g. \"a.example.com,b.example.com\""
+		}
+	case KindString:
+		switch value.(type) {
+		case string, int, int64:
+		default:
+			return fmt.Sprintf("%v is not a string", value)
+		}
+	}
+	return ""
+}
+
+func checkEnvValue(kind Kind, value string) string {
+	switch kind {
+	case KindBool:
+		if value != "true" && value != "false" {
+			return fmt.Sprintf("%q is read as false; use true or false", value)
+		}
+	case KindInt:
+		if _, err := strconv.Atoi(value); err != nil {
+			return fmt.Sprintf("%q is not a whole number and is ignored", value)
+		}
+	case KindDuration:
+		if _, err := time.ParseDuration(value); err != nil {
+			return fmt.Sprintf("%q is not a duration such as 30s or 5m and is ignored", value)
+		}
+	}
+	return ""
+}
+
+// closestKey suggests a known key for a misspelled one
+func closestKey(key string) string {
+	best, bestDistance := "", 4
+	for _, o := range Options {
+		if d := editDistance(key, o.Key); d < bestDistance {
+			best, bestDistance = o.Key, d
+		}
+	}
+	if best != "" {
+		return best
+	}
+	// A known setting in the wrong table, e.g. smtp_host or server.data_dir
+	_, name, nested := strings.Cut(key, ".")
+	for _, o := range Options {
+		if o.Key == strings.Replace(key, "_", ".", 1) || (nested && o.Key == name) {
+			return o.Key
+		}
+	}
+	return ""
+}
+
+// editDistance is the Levenshtein distance between a and b
+func editDistance(a, b string) int {
+	prev := make([]int, len(b)+1)
+	for j := range prev {
+		prev[j] = j
+	}
+	for i := 1; i <= len(a); i++ {
+		cur := make([]int, len(b)+1)
+		cur[0] = i
+		for j := 1; j <= len(b); j++ {
+			cost := 1
+			if a[i-1] == b[j-1] {
+				cost = 0
+			}
+			cur[j] = min(prev[j]+1, cur[j-1]+1, prev[j-1]+cost)
+		}
+		prev = cur
+	}
+	return prev[len(b)]
+}
@@ -3,7 +3,9 @@ package config
 import (
 	"os"
 	"path/filepath"
+	"strconv"
 	"strings"
+	"time"
 
 	"github.com/spf13/viper"
 )
@@ -19,7 +21,119 @@ type Config struct {
 	DataDir        string
 	SecureCookies  bool
 	TrustedOrigins []string
-	InstallLock    bool // Whether installation is locked (setup completed)
+	InstallLock    bool     // Whether installation is locked (setup completed)
+	GraphQLEnabled bool     // Whether the /api/v1/graphql stats endpoint is served
+	MetricsEnabled bool     // Whether Prometheus metrics are served at /metrics
+	APIRateLimit   int      // Admin API requests per minute per signed-in user (0 disables)
+	RetentionDays  int      // Days of raw events kept before their partitions are dropped (0 keeps everything)
+	PurgeDryRun    bool     // Per-website retention purges only log what they would delete
+	Require2FA     bool     // Every user must enroll in two-factor authentication
+	PublicURL      string   // Base URL for links in emails, e.g. https://stats.example.com
+	CORSOrigins    []string // Origins (scheme://host[:port]) allowed to call the API from a browser
+	CSRFMode       string   // CSRFModeCookie or CSRFModeHeader
+	RedisURL       string   // redis:// URL for realtime state shared between instances
+	ErasureKey     string   // Secret signing erasure receipts; unsigned while empty
+	Cookies        CookieConfig
+	DatabasePool   DatabasePoolConfig
+	Replica        ReplicaConfig
+	StatsQueries   StatsQueryConfig
+	Ingest         IngestConfig
+	Archive        ArchiveConfig
+	ProxyAuth      ProxyAuthConfig
+	SMTP           SMTPConfig
+}
+
+// DatabasePoolConfig tunes the database connection pool
+type DatabasePoolConfig struct {
+	Size             int           // Maximum open connections
+	AcquireTimeout   time.Duration // How long opening a connection may take
+	StatementTimeout time.Duration // Cancel statements running longer than this (0 disables)
+	IdleLifetime     time.Duration // Close connections idle this long
+}
+
+// ReplicaConfig routes stats and report queries to a read-only replica.
+// Writes always go to the primary. It is off while URL is empty.
+type ReplicaConfig struct {
+	URL    string        // DSN of the replica, same formats as database_url
+	MaxLag time.Duration // Use the primary while the replica is further behind (0 ignores lag)
+}
+
+// StatsQueryConfig bounds the queries behind dashboards and the stats API
+type StatsQueryConfig struct {
+	Timeout         time.Duration // Cancel a stats query running longer than this (0 disables)
+	SlowThreshold   time.Duration // Log stats queries slower than this with their parameters (0 disables)
+	TenantIsolation bool          // Run stats queries under row-level security limited to the caller's websites
+}
+
+// IngestConfig controls how accepted hits are written. They wait in memory
+// for up to FlushInterval and are inserted in batches; a crash loses at most
+// MaxPending of them. A zero FlushInterval writes each hit as it arrives.
+type IngestConfig struct {
+	FlushInterval time.Duration // Write buffered hits this often
+	BatchSize     int           // Write early once this many hits are waiting
+	MaxPending    int           // Answer 503 beyond this many unwritten hits
+	MaxInFlight   int           // Answer 503 beyond this many hits being processed at once (0 disables)
+}
+
+// ArchiveConfig exports closed months of raw events as Parquet files to
+// S3-compatible storage. It is off while Bucket is empty.
+type ArchiveConfig struct {
+	Endpoint  string // e.g. https://s3.eu-central-1.amazonaws.com or http://minio:9000
+	Region    string // Signing region; us-east-1 when empty
+	Bucket    string // Bucket receiving the archives
+	AccessKey string // Access key ID
+	SecretKey string // Secret access key
+	Prefix    string // Key prefix inside the bucket, e.g. kaunta/
+}
+
+// Enabled reports whether event archiving is configured
+func (a ArchiveConfig) Enabled() bool {
+	return a.Bucket != ""
+}
+
+// ProxyAuthConfig signs users in from the headers of an authenticating
+// reverse proxy such as Authelia, authentik or oauth2-proxy. It is off
+// while Header or TrustedProxies is empty.
+type ProxyAuthConfig struct {
+	Header         string   // Header holding the username, e.g. Remote-User
+	EmailHeader    string   // Optional header holding the email, e.g. Remote-Email
+	NameHeader     string   // Optional header holding the display name, e.g. Remote-Name
+	GroupsHeader   string   // Optional header listing groups, e.g. Remote-Groups; enables group mappings
+	TrustedProxies []string // IPs or CIDRs of the proxy; headers from anyone else are ignored
+	AutoCreate     bool     // Create accounts for unknown usernames on first visit
+}
+
+// Enabled reports whether proxy header authentication is configured
+func (p ProxyAuthConfig) Enabled() bool {
+	return p.Header != "" && len(p.TrustedProxies) > 0
+}
+
+// CSRF modes. In cookie mode an unsafe request must echo the kaunta_csrf
+// cookie in X-CSRF-Token. Header mode also accepts a request carrying
+// X-Requested-With, which browsers only let other origins send after a CORS
+// preflight, and returns the token in an X-CSRF-Token response header for
+// clients that can't read the cookie.
+const (
+	CSRFModeCookie = "cookie"
+	CSRFModeHeader = "header"
+)
+
+// CookieConfig overrides the attributes of the session and CSRF cookies.
+// Secure comes from SecureCookies.
+type CookieConfig struct {
+	SameSite string // lax, strict or none; empty picks none with Secure, lax without
+	Domain   string // Share cookies with subdomains, e.g. example.com; empty means host-only
+}
+
+// SMTPConfig holds the mail server used to deliver email alerts,
+// invitations and password resets.
+// Email delivery is disabled while Host is empty.
+type SMTPConfig struct {
+	Host     string
+	Port     string
+	Username string
+	Password string
+	From     string
 }
 
 // Load loads configuration from multiple sources with priority:
@@ -66,6 +180,13 @@ func buildConfig(v *viper.Viper, overrideDatabaseURL, overridePort, overrideData
 		SecureCookies:  true, // Default to secure (safe for production/HTTPS proxies)
 		TrustedOrigins: []string{"localhost"},
 		InstallLock:    false,
+		APIRateLimit:   300,
+		CSRFMode:       CSRFModeCookie,
+		DatabasePool:   DatabasePoolConfig{Size: 25, AcquireTimeout: 10 * time.Second, IdleLifetime: 5 * time.Minute},
+		Replica:        ReplicaConfig{MaxLag: 30 * time.Second},
+		StatsQueries:   StatsQueryConfig{Timeout: 30 * time.Second, SlowThreshold: 2 * time.Second},
+		Ingest:         IngestConfig{FlushInterval: time.Second, BatchSize: 500, MaxPending: 10000, MaxInFlight: 500},
+		SMTP:           SMTPConfig{Port: "587"},
 	}
 
 	// Apply config file values
@@ -87,6 +208,113 @@ func buildConfig(v *viper.Viper, overrideDatabaseURL, overridePort, overrideData
 	if v.IsSet("security.install_lock") {
 		cfg.InstallLock = v.GetBool("security.install_lock")
 	}
+	if v.IsSet("graphql_enabled") {
+		cfg.GraphQLEnabled = v.GetBool("graphql_enabled")
+	}
+	if v.IsSet("metrics_enabled") {
+		cfg.MetricsEnabled = v.GetBool("metrics_enabled")
+	}
+	if v.IsSet("api_rate_limit") {
+		cfg.APIRateLimit = v.GetInt("api_rate_limit")
+	}
+	if v.IsSet("retention_days") {
+		cfg.RetentionDays = v.GetInt("retention_days")
+	}
+	if v.IsSet("retention_dry_run") {
+		cfg.PurgeDryRun = v.GetBool("retention_dry_run")
+	}
+	if v.IsSet("security.require_2fa") {
+		cfg.Require2FA = v.GetBool("security.require_2fa")
+	}
+	if v.IsSet("database.tenant_isolation") {
+		cfg.StatsQueries.TenantIsolation = v.GetBool("database.tenant_isolation")
+	}
+	if v.IsSet("public_url") {
+		cfg.PublicURL = v.GetString("public_url")
+	}
+	if v.IsSet("cors_origins") {
+		cfg.CORSOrigins = parseCORSOrigins(v.GetString("cors_origins"))
+	} else if env := os.Getenv("CORS_ORIGINS"); env != "" {
+		cfg.CORSOrigins = parseCORSOrigins(env)
+	}
+	if v.IsSet("proxy_auth.trusted_proxies") {
+		cfg.ProxyAuth.TrustedProxies = splitList(v.GetString("proxy_auth.trusted_proxies"))
+	} else if env := os.Getenv("PROXY_AUTH_TRUSTED_PROXIES"); env != "" {
+		cfg.ProxyAuth.TrustedProxies = splitList(env)
+	}
+	if v.IsSet("proxy_auth.auto_create") {
+		cfg.ProxyAuth.AutoCreate = v.GetBool("proxy_auth.auto_create")
+	} else {
+		cfg.ProxyAuth.AutoCreate = os.Getenv("PROXY_AUTH_AUTO_CREATE") == "true"
+	}
+	stringFields := []struct {
+		key, env string
+		dst      *string
+	}{
+		{"smtp.host", "SMTP_HOST", &cfg.SMTP.Host},
+		{"smtp.port", "SMTP_PORT", &cfg.SMTP.Port},
+		{"smtp.username", "SMTP_USERNAME", &cfg.SMTP.Username},
+		{"smtp.password", "SMTP_PASSWORD", &cfg.SMTP.Password},
+		{"smtp.from", "SMTP_FROM", &cfg.SMTP.From},
+		{"cookies.same_site", "COOKIE_SAME_SITE", &cfg.Cookies.SameSite},
+		{"cookies.domain", "COOKIE_DOMAIN", &cfg.Cookies.Domain},
+		{"csrf_mode", "CSRF_MODE", &cfg.CSRFMode},
+		{"redis_url", "REDIS_URL", &cfg.RedisURL},
+		{"erasure_signing_key", "ERASURE_SIGNING_KEY", &cfg.ErasureKey},
+		{"database.replica_url", "DATABASE_REPLICA_URL", &cfg.Replica.URL},
+		{"archive.endpoint", "ARCHIVE_S3_ENDPOINT", &cfg.Archive.Endpoint},
+		{"archive.region", "ARCHIVE_S3_REGION", &cfg.Archive.Region},
+		{"archive.bucket", "ARCHIVE_S3_BUCKET", &cfg.Archive.Bucket},
+		{"archive.access_key", "ARCHIVE_S3_ACCESS_KEY", &cfg.Archive.AccessKey},
+		{"archive.secret_key", "ARCHIVE_S3_SECRET_KEY", &cfg.Archive.SecretKey},
+		{"archive.prefix", "ARCHIVE_PREFIX", &cfg.Archive.Prefix},
+		{"proxy_auth.header", "PROXY_AUTH_HEADER", &cfg.ProxyAuth.Header},
+		{"proxy_auth.email_header", "PROXY_AUTH_EMAIL_HEADER", &cfg.ProxyAuth.EmailHeader},
+		{"proxy_auth.name_header", "PROXY_AUTH_NAME_HEADER", &cfg.ProxyAuth.NameHeader},
+		{"proxy_auth.groups_header", "PROXY_AUTH_GROUPS_HEADER", &cfg.ProxyAuth.GroupsHeader},
+	}
+	for _, f := range stringFields {
+		if v.IsSet(f.key) {
+			*f.dst = v.GetString(f.key)
+		} else if env := os.Getenv(f.env); env != "" {
+			*f.dst = env
+		}
+	}
+	durationFields := []struct {
+		key, env string
+		dst      *time.Duration
+	}{
+		{"database.acquire_timeout", "DB_ACQUIRE_TIMEOUT", &cfg.DatabasePool.AcquireTimeout},
+		{"database.statement_timeout", "DB_STATEMENT_TIMEOUT", &cfg.DatabasePool.StatementTimeout},
+		{"database.idle_lifetime", "DB_IDLE_LIFETIME", &cfg.DatabasePool.IdleLifetime},
+		{"database.replica_max_lag", "DB_REPLICA_MAX_LAG", &cfg.Replica.MaxLag},
+		{"database.stats_query_timeout", "DB_STATS_QUERY_TIMEOUT", &cfg.StatsQueries.Timeout},
+		{"database.slow_query_threshold", "DB_SLOW_QUERY_THRESHOLD", &cfg.StatsQueries.SlowThreshold},
+		{"ingest.flush_interval", "INGEST_FLUSH_INTERVAL", &cfg.Ingest.FlushInterval},
+	}
+	for _, f := range durationFields {
+		if v.IsSet(f.key) {
+			*f.dst = v.GetDuration(f.key)
+		} else if d, err := time.ParseDuration(os.Getenv(f.env)); err == nil {
+			*f.dst = d
+		}
+	}
+	intFields := []struct {
+		key, env string
+		dst      *int
+	}{
+		{"database.pool_size", "DB_POOL_SIZE", &cfg.DatabasePool.Size},
+		{"ingest.batch_size", "INGEST_BATCH_SIZE", &cfg.Ingest.BatchSize},
+		{"ingest.max_pending", "INGEST_MAX_PENDING", &cfg.Ingest.MaxPending},
+		{"ingest.max_in_flight", "INGEST_MAX_IN_FLIGHT", &cfg.Ingest.MaxInFlight},
+	}
+	for _, f := range intFields {
+		if v.IsSet(f.key) {
+			*f.dst = v.GetInt(f.key)
+		} else if n, err := strconv.Atoi(os.Getenv(f.env)); err == nil {
+			*f.dst = n
+		}
+	}
 
 	// Environment fallback (only if not configured)
 	if cfg.DatabaseURL == "" {
@@ -113,6 +341,38 @@ func buildConfig(v *viper.Viper, overrideDatabaseURL, overridePort, overrideData
 		}
 		// Otherwise keep default (true)
 	}
+	if !v.IsSet("graphql_enabled") {
+		cfg.GraphQLEnabled = os.Getenv("GRAPHQL_ENABLED") == "true"
+	}
+	if !v.IsSet("metrics_enabled") {
+		cfg.MetricsEnabled = os.Getenv("METRICS_ENABLED") == "true"
+	}
+	if !v.IsSet("api_rate_limit") {
+		if limit, err := strconv.Atoi(os.Getenv("API_RATE_LIMIT")); err == nil {
+			cfg.APIRateLimit = limit
+		}
+	}
+	if !v.IsSet("retention_days") {
+		if days, err := strconv.Atoi(os.Getenv("RETENTION_DAYS")); err == nil {
+			cfg.RetentionDays = days
+		}
+	}
+	if !v.IsSet("retention_dry_run") {
+		cfg.PurgeDryRun = os.Getenv("RETENTION_DRY_RUN") == "true"
+	}
+	if !v.IsSet("security.require_2fa") {
+		cfg.Require2FA = os.Getenv("REQUIRE_2FA") == "true"
+	}
+	if !v.IsSet("database.tenant_isolation") {
+		cfg.StatsQueries.TenantIsolation = os.Getenv("DB_TENANT_ISOLATION") == "true"
+	}
+	if !v.IsSet("public_url") {
+		cfg.PublicURL = os.Getenv("PUBLIC_URL")
+	}
+	cfg.PublicURL = strings.TrimRight(cfg.PublicURL, "/")
+	cfg.Cookies.SameSite = strings.ToLower(strings.TrimSpace(cfg.Cookies.SameSite))
+	cfg.CSRFMode = strings.ToLower(strings.TrimSpace(cfg.CSRFMode))
+	cfg.Cookies.Domain = strings.TrimPrefix(strings.ToLower(strings.TrimSpace(cfg.Cookies.Domain)), ".")
 
 	// Apply overrides (flags) last
 	if overrideDatabaseURL != "" {
@@ -128,6 +388,17 @@ func buildConfig(v *viper.Viper, overrideDatabaseURL, overridePort, overrideData
 	return cfg
 }
 
+// splitList splits a comma-separated setting, dropping blank entries
+func splitList(value string) []string {
+	items := []string{}
+	for _, part := range strings.Split(value, ",") {
+		if part = strings.TrimSpace(part); part != "" {
+			items = append(items, part)
+		}
+	}
+	return items
+}
+
 // parseTrustedOrigins parses a comma-separated string into a slice of trimmed, lowercased origins
 func parseTrustedOrigins(originsStr string) []string {
 	if originsStr == "" {
@@ -4,6 +4,7 @@ import (
 	"os"
 	"path/filepath"
 	"testing"
+	"time"
 
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
@@ -127,6 +128,283 @@ data_dir = "./config-data"
 	assert.Equal(t, []string{"example.com", "foo.test"}, cfg.TrustedOrigins)
 }
 
+func TestLoadSMTPSettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	writeTestConfig(t, home, `
+[smtp]
+host = "mail.example.com"
+from = "alerts@example.com"
+`)
+
+	unsetEnv(t, "SMTP_PORT")
+	t.Setenv("SMTP_HOST", "ignored.example.com")
+	t.Setenv("SMTP_USERNAME", "mailer")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+
+	assert.Equal(t, "mail.example.com", cfg.SMTP.Host) // config file wins over env
+	assert.Equal(t, "587", cfg.SMTP.Port)
+	assert.Equal(t, "mailer", cfg.SMTP.Username)
+	assert.Equal(t, "alerts@example.com", cfg.SMTP.From)
+}
+
+func TestLoadGraphQLEnabled(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "GRAPHQL_ENABLED")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.GraphQLEnabled) // opt-in
+
+	t.Setenv("GRAPHQL_ENABLED", "true")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.True(t, cfg.GraphQLEnabled)
+
+	writeTestConfig(t, home, `graphql_enabled = false`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.GraphQLEnabled) // config file wins over env
+}
+
+func TestLoadAPIRateLimit(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "API_RATE_LIMIT")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, 300, cfg.APIRateLimit)
+
+	t.Setenv("API_RATE_LIMIT", "0")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 0, cfg.APIRateLimit) // disabled
+
+	writeTestConfig(t, home, `api_rate_limit = 120`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 120, cfg.APIRateLimit) // config file wins over env
+}
+
+func TestLoadRetentionDays(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "RETENTION_DAYS")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, 0, cfg.RetentionDays) // keep everything
+
+	t.Setenv("RETENTION_DAYS", "400")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 400, cfg.RetentionDays)
+
+	writeTestConfig(t, home, `retention_days = 90`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 90, cfg.RetentionDays)
+}
+
+func TestLoadRetentionDryRun(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "RETENTION_DRY_RUN")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.PurgeDryRun)
+
+	t.Setenv("RETENTION_DRY_RUN", "true")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.True(t, cfg.PurgeDryRun)
+
+	writeTestConfig(t, home, `retention_dry_run = false`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.PurgeDryRun) // config file wins over env
+}
+
+func TestLoadDatabasePoolAndMetrics(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	for _, key := range []string{"DB_POOL_SIZE", "DB_ACQUIRE_TIMEOUT", "DB_STATEMENT_TIMEOUT", "DB_IDLE_LIFETIME", "METRICS_ENABLED"} {
+		unsetEnv(t, key)
+	}
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, DatabasePoolConfig{Size: 25, AcquireTimeout: 10 * time.Second, IdleLifetime: 5 * time.Minute}, cfg.DatabasePool)
+	assert.False(t, cfg.MetricsEnabled)
+
+	t.Setenv("DB_POOL_SIZE", "50")
+	t.Setenv("DB_STATEMENT_TIMEOUT", "30s")
+	t.Setenv("METRICS_ENABLED", "true")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 50, cfg.DatabasePool.Size)
+	assert.Equal(t, 30*time.Second, cfg.DatabasePool.StatementTimeout)
+	assert.True(t, cfg.MetricsEnabled)
+
+	writeTestConfig(t, home, "[database]\npool_size = 10\nidle_lifetime = \"1m\"\n")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 10, cfg.DatabasePool.Size) // config file wins over env
+	assert.Equal(t, time.Minute, cfg.DatabasePool.IdleLifetime)
+}
+
+func TestLoadReplicaSettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "DATABASE_REPLICA_URL")
+	unsetEnv(t, "DB_REPLICA_MAX_LAG")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, ReplicaConfig{MaxLag: 30 * time.Second}, cfg.Replica)
+
+	t.Setenv("DATABASE_REPLICA_URL", "postgres://replica/kaunta")
+	t.Setenv("DB_REPLICA_MAX_LAG", "2m")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, ReplicaConfig{URL: "postgres://replica/kaunta", MaxLag: 2 * time.Minute}, cfg.Replica)
+}
+
+func TestLoadStatsQuerySettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "DB_STATS_QUERY_TIMEOUT")
+	unsetEnv(t, "DB_SLOW_QUERY_THRESHOLD")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, StatsQueryConfig{Timeout: 30 * time.Second, SlowThreshold: 2 * time.Second}, cfg.StatsQueries)
+
+	t.Setenv("DB_SLOW_QUERY_THRESHOLD", "500ms")
+	writeTestConfig(t, home, "[database]\nstats_query_timeout = \"10s\"\n")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, StatsQueryConfig{Timeout: 10 * time.Second, SlowThreshold: 500 * time.Millisecond}, cfg.StatsQueries)
+}
+
+func TestLoadArchiveSettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	for _, env := range []string{"ARCHIVE_S3_ENDPOINT", "ARCHIVE_S3_REGION", "ARCHIVE_S3_BUCKET",
+		"ARCHIVE_S3_ACCESS_KEY", "ARCHIVE_S3_SECRET_KEY", "ARCHIVE_PREFIX"} {
+		unsetEnv(t, env)
+	}
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.Archive.Enabled())
+
+	t.Setenv("ARCHIVE_S3_SECRET_KEY", "secret")
+	writeTestConfig(t, home, `
+[archive]
+endpoint = "http://minio:9000"
+bucket = "analytics"
+access_key = "kaunta"
+prefix = "events/"
+`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.True(t, cfg.Archive.Enabled())
+	assert.Equal(t, ArchiveConfig{
+		Endpoint:  "http://minio:9000",
+		Bucket:    "analytics",
+		AccessKey: "kaunta",
+		SecretKey: "secret",
+		Prefix:    "events/",
+	}, cfg.Archive)
+}
+
+func TestLoadIngestSettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "INGEST_FLUSH_INTERVAL")
+	unsetEnv(t, "INGEST_BATCH_SIZE")
+	unsetEnv(t, "INGEST_MAX_PENDING")
+	unsetEnv(t, "INGEST_MAX_IN_FLIGHT")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, IngestConfig{FlushInterval: time.Second, BatchSize: 500, MaxPending: 10000, MaxInFlight: 500}, cfg.Ingest)
+
+	t.Setenv("INGEST_FLUSH_INTERVAL", "250ms")
+	t.Setenv("INGEST_MAX_PENDING", "2000")
+	t.Setenv("INGEST_MAX_IN_FLIGHT", "0")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, 250*time.Millisecond, cfg.Ingest.FlushInterval)
+	assert.Equal(t, 2000, cfg.Ingest.MaxPending)
+	assert.Zero(t, cfg.Ingest.MaxInFlight)
+
+	writeTestConfig(t, home, "[ingest]\nflush_interval = \"0s\"\nbatch_size = 100\n")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Zero(t, cfg.Ingest.FlushInterval) // config file wins over env
+	assert.Equal(t, 100, cfg.Ingest.BatchSize)
+	assert.Equal(t, 2000, cfg.Ingest.MaxPending)
+}
+
+func TestLoadRequire2FA(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "REQUIRE_2FA")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.Require2FA)
+
+	t.Setenv("REQUIRE_2FA", "true")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.True(t, cfg.Require2FA)
+
+	writeTestConfig(t, home, "[security]\nrequire_2fa = false")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.Require2FA) // config file wins over env
+}
+
+func TestLoadTenantIsolation(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	unsetEnv(t, "DB_TENANT_ISOLATION")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.StatsQueries.TenantIsolation)
+
+	t.Setenv("DB_TENANT_ISOLATION", "true")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.True(t, cfg.StatsQueries.TenantIsolation)
+
+	writeTestConfig(t, home, "[database]\ntenant_isolation = false")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.StatsQueries.TenantIsolation) // config file wins over env
+}
+
 func TestSanitizeTrustedDomain(t *testing.T) {
 	tests := []struct {
 		input       string
@@ -155,3 +433,110 @@ func TestSanitizeTrustedDomain(t *testing.T) {
 		assert.Equal(t, tt.expected, got)
 	}
 }
+
+func TestLoadPublicURL(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	t.Setenv("PUBLIC_URL", "https://env.example.com/")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, "https://env.example.com", cfg.PublicURL)
+
+	writeTestConfig(t, home, `public_url = "https://stats.example.com"`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, "https://stats.example.com", cfg.PublicURL) // config file wins over env
+}
+
+func TestLoadCookieAndCORSSettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+	t.Setenv("COOKIE_SAME_SITE", "Strict")
+	t.Setenv("CORS_ORIGINS", "https://App.example.com/, not-an-origin, http://localhost:5173")
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.Equal(t, "strict", cfg.Cookies.SameSite)
+	assert.Equal(t, []string{"https://app.example.com", "http://localhost:5173"}, cfg.CORSOrigins)
+
+	writeTestConfig(t, home, `cors_origins = "https://dash.example.com"
+
+[cookies]
+same_site = "none"
+domain = ".example.com"
+`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, "none", cfg.Cookies.SameSite)
+	assert.Equal(t, "example.com", cfg.Cookies.Domain)
+	assert.Equal(t, []string{"https://dash.example.com"}, cfg.CORSOrigins)
+}
+
+func TestPolicyWarnings(t *testing.T) {
+	cfg := &Config{SecureCookies: true, PublicURL: "https://stats.example.com"}
+	assert.Empty(t, cfg.PolicyWarnings([]string{"stats.example.com"}))
+
+	// Forms from an untrusted host are rejected
+	assert.Len(t, cfg.PolicyWarnings([]string{"localhost"}), 1)
+
+	// Secure cookies don't survive plain HTTP, except on localhost
+	cfg = &Config{SecureCookies: true, PublicURL: "http://stats.example.com"}
+	assert.Len(t, cfg.PolicyWarnings([]string{"stats.example.com"}), 1)
+	cfg = &Config{SecureCookies: true, PublicURL: "http://localhost:3000"}
+	assert.Empty(t, cfg.PolicyWarnings([]string{"localhost"}))
+
+	cfg = &Config{Cookies: CookieConfig{SameSite: "none"}}
+	assert.Len(t, cfg.PolicyWarnings(nil), 1)
+
+	cfg = &Config{SecureCookies: true, PublicURL: "https://stats.example.com", Cookies: CookieConfig{Domain: "other.com"}}
+	assert.Len(t, cfg.PolicyWarnings([]string{"stats.example.com"}), 1)
+	cfg.Cookies.Domain = "example.com"
+	assert.Empty(t, cfg.PolicyWarnings([]string{"stats.example.com"}))
+
+	cfg = &Config{CSRFMode: CSRFModeHeader}
+	assert.Empty(t, cfg.PolicyWarnings(nil))
+	cfg.CSRFMode = "token"
+	assert.Len(t, cfg.PolicyWarnings(nil), 1)
+}
+
+func TestLoadProxyAuthSettings(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("XDG_CONFIG_HOME", filepath.Join(home, ".config"))
+
+	cfg, err := Load()
+	require.NoError(t, err)
+	assert.False(t, cfg.ProxyAuth.Enabled())
+
+	t.Setenv("PROXY_AUTH_HEADER", "Remote-User")
+	t.Setenv("PROXY_AUTH_TRUSTED_PROXIES", "10.0.0.0/8, 192.168.1.5")
+	t.Setenv("PROXY_AUTH_AUTO_CREATE", "true")
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.True(t, cfg.ProxyAuth.Enabled())
+	assert.True(t, cfg.ProxyAuth.AutoCreate)
+	assert.Equal(t, []string{"10.0.0.0/8", "192.168.1.5"}, cfg.ProxyAuth.TrustedProxies)
+
+	networks, err := cfg.ProxyAuth.Networks()
+	require.NoError(t, err)
+	require.Len(t, networks, 2)
+	assert.Equal(t, "192.168.1.5/32", networks[1].String())
+
+	writeTestConfig(t, home, `[proxy_auth]
+header = "X-Forwarded-User"
+email_header = "X-Forwarded-Email"
+trusted_proxies = "::1"
+auto_create = false`)
+	cfg, err = Load()
+	require.NoError(t, err)
+	assert.Equal(t, "X-Forwarded-User", cfg.ProxyAuth.Header)
+	assert.Equal(t, "X-Forwarded-Email", cfg.ProxyAuth.EmailHeader)
+	assert.Equal(t, []string{"::1"}, cfg.ProxyAuth.TrustedProxies)
+	assert.False(t, cfg.ProxyAuth.AutoCreate)
+
+	_, err = ProxyAuthConfig{TrustedProxies: []string{"proxy.local"}}.Networks()
+	assert.Error(t, err)
+}
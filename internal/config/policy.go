@@ -0,0 +1,106 @@
+package config
+
+import (
+	"fmt"
+	"net"
+	"net/url"
+	"strings"
+)
+
+// parseCORSOrigins parses a comma-separated list of origins, dropping
+// entries that aren't a scheme and host
+func parseCORSOrigins(originsStr string) []string {
+	origins := []string{}
+	for _, part := range strings.Split(originsStr, ",") {
+		if origin, err := NormalizeOrigin(part); err == nil {
+			origins = append(origins, origin)
+		}
+	}
+	return origins
+}
+
+// NormalizeOrigin validates a browser origin such as https://app.example.com
+// and returns it lowercased, without a trailing slash
+func NormalizeOrigin(raw string) (string, error) {
+	cleaned := strings.ToLower(strings.TrimSuffix(strings.TrimSpace(raw), "/"))
+	u, err := url.Parse(cleaned)
+	if err != nil || (u.Scheme != "http" && u.Scheme != "https") || u.Host == "" ||
+		u.Path != "" || u.RawQuery != "" || u.Fragment != "" || u.User != nil {
+		return "", fmt.Errorf("invalid origin %q: expected scheme://host[:port]", raw)
+	}
+	if strings.Contains(u.Host, "*") {
+		return "", fmt.Errorf("wildcards are not allowed in origins")
+	}
+	return u.Scheme + "://" + u.Host, nil
+}
+
+// PolicyWarnings checks the cookie and public URL settings against each
+// other and against the trusted origins in effect, and describes
+// combinations browsers will reject. A mismatch here typically shows up as
+// a login that succeeds but doesn't stick, or as "Forbidden" on every form.
+func (c *Config) PolicyWarnings(trustedOrigins []string) []string {
+	var warnings []string
+
+	switch c.Cookies.SameSite {
+	case "", "lax", "strict":
+	case "none":
+		if !c.SecureCookies {
+			warnings = append(warnings, "cookies.same_site = none requires secure_cookies = true; browsers reject SameSite=None cookies without Secure")
+		}
+	default:
+		warnings = append(warnings, fmt.Sprintf("cookies.same_site %q is not lax, strict or none; using the default", c.Cookies.SameSite))
+	}
+
+	switch c.CSRFMode {
+	case "", CSRFModeCookie, CSRFModeHeader:
+	default:
+		warnings = append(warnings, fmt.Sprintf("csrf_mode %q is not cookie or header; using cookie", c.CSRFMode))
+	}
+
+	if c.PublicURL == "" {
+		return warnings
+	}
+	u, err := url.Parse(c.PublicURL)
+	if err != nil || u.Host == "" {
+		return append(warnings, fmt.Sprintf("public_url %q is not an absolute URL", c.PublicURL))
+	}
+	host := u.Hostname()
+
+	if u.Scheme == "http" && c.SecureCookies && !isLocalHost(host) {
+		warnings = append(warnings, fmt.Sprintf("public_url %s is plain HTTP but secure_cookies = true; browsers drop Secure cookies over HTTP, so logins won't stick", c.PublicURL))
+	}
+	if u.Scheme == "https" && !c.SecureCookies {
+		warnings = append(warnings, fmt.Sprintf("public_url %s is HTTPS but secure_cookies = false; session cookies would also be sent over plain HTTP", c.PublicURL))
+	}
+	if d := c.Cookies.Domain; d != "" && host != d && !strings.HasSuffix(host, "."+d) {
+		warnings = append(warnings, fmt.Sprintf("cookies.domain %s does not cover the public_url host %s; browsers will reject the cookies", d, host))
+	}
+	if !hostTrusted(trustedOrigins, u.Host) {
+		warnings = append(warnings, fmt.Sprintf("the public_url host %s is not in trusted_origins; form posts from it will be rejected with Forbidden", u.Host))
+	}
+	return warnings
+}
+
+// hostTrusted reports whether host (with optional port) is one of the
+// trusted origin domains
+func hostTrusted(trustedOrigins []string, host string) bool {
+	host = strings.ToLower(host)
+	hostname := host
+	if h, _, err := net.SplitHostPort(host); err == nil {
+		hostname = h
+	}
+	for _, origin := range trustedOrigins {
+		if origin == host || origin == hostname {
+			return true
+		}
+	}
+	return false
+}
+
+func isLocalHost(host string) bool {
+	if host == "localhost" {
+		return true
+	}
+	ip := net.ParseIP(host)
+	return ip != nil && ip.IsLoopback()
+}
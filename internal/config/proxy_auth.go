@@ -0,0 +1,25 @@
+package config
+
+import (
+	"fmt"
+	"net/netip"
+)
+
+// Networks parses TrustedProxies. Plain IPs become single-address
+// prefixes.
+func (p ProxyAuthConfig) Networks() ([]netip.Prefix, error) {
+	networks := make([]netip.Prefix, 0, len(p.TrustedProxies))
+	for _, entry := range p.TrustedProxies {
+		if prefix, err := netip.ParsePrefix(entry); err == nil {
+			networks = append(networks, prefix.Masked())
+			continue
+		}
+		addr, err := netip.ParseAddr(entry)
+		if err != nil {
+			return nil, fmt.Errorf("proxy_auth.trusted_proxies: %q is not an IP address or CIDR", entry)
+		}
+		addr = addr.Unmap()
+		networks = append(networks, netip.PrefixFrom(addr, addr.BitLen()))
+	}
+	return networks, nil
+}
@@ -0,0 +1,124 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"math"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// backfillLockRetry is how long a backfill chunk waits for a rollup refresh
+// running elsewhere before trying again
+var backfillLockRetry = 5 * time.Second
+
+// BackfillOptions selects the days BackfillRollups recounts
+type BackfillOptions struct {
+	From      time.Time  // First day; earlier days are moved up to the first raw event
+	Until     time.Time  // Day after the last; later days are cut at the last rollup refresh
+	WebsiteID *uuid.UUID // Only this website; nil for all of them
+	ChunkDays int        // Days recounted per statement, 1 when 0
+	Pause     time.Duration
+	Progress  func(BackfillProgress)
+}
+
+// BackfillProgress is reported after every chunk
+type BackfillProgress struct {
+	Done, Total int       // Chunks
+	Through     time.Time // End of the last chunk recounted
+	Rows        int64     // Rollup rows written so far
+}
+
+// BackfillResult is what BackfillRollups recounted. From and Until are zero
+// when there was nothing to recount.
+type BackfillResult struct {
+	From, Until time.Time
+	Chunks      int
+	Rows        int64
+}
+
+// BackfillRollups recounts the rollups and visitor sketches of days that are
+// already rolled up from their raw events with rebuild_rollups(), after an
+// import into the past or a change to how rollups are counted. Each chunk
+// commits on its own and takes the rollup job lock, so the server's rollup
+// refresh runs in between and no chunk locks the tables for long. Days with
+// no raw events left keep their rollups.
+func BackfillRollups(ctx context.Context, db *sql.DB, opts BackfillOptions) (BackfillResult, error) {
+	var result BackfillResult
+	chunk := max(opts.ChunkDays, 1)
+
+	var firstEvent, rolledUntil sql.NullTime
+	if err := db.QueryRowContext(ctx, `
+		SELECT
+			(SELECT MIN(created_at) FROM website_event WHERE $1::uuid IS NULL OR website_id = $1),
+			(SELECT MAX(rolled_until) FROM rollup_state)
+	`, opts.WebsiteID).Scan(&firstEvent, &rolledUntil); err != nil {
+		return result, err
+	}
+	if !firstEvent.Valid || !rolledUntil.Valid {
+		return result, nil
+	}
+
+	from := opts.From.UTC().Truncate(24 * time.Hour)
+	if first := firstEvent.Time.UTC().Truncate(24 * time.Hour); first.After(from) {
+		from = first
+	}
+	until := opts.Until
+	if until.IsZero() || until.After(rolledUntil.Time) {
+		until = rolledUntil.Time
+	}
+	if !from.Before(until) {
+		return result, nil
+	}
+	result.From, result.Until = from, until
+
+	days := int(math.Ceil(until.Sub(from).Hours() / 24))
+	total := (days + chunk - 1) / chunk
+	for start := from; start.Before(until); start = start.AddDate(0, 0, chunk) {
+		end := start.AddDate(0, 0, chunk)
+		if end.After(until) {
+			end = until
+		}
+
+		rows, err := backfillChunk(ctx, db, start, end, opts.WebsiteID)
+		if err != nil {
+			return result, fmt.Errorf("failed to recount %s: %w", start.Format("2006-01-02"), err)
+		}
+		result.Chunks++
+		result.Rows += rows
+		if opts.Progress != nil {
+			opts.Progress(BackfillProgress{Done: result.Chunks, Total: total, Through: end, Rows: result.Rows})
+		}
+
+		if opts.Pause > 0 && end.Before(until) {
+			select {
+			case <-ctx.Done():
+				return result, ctx.Err()
+			case <-time.After(opts.Pause):
+			}
+		}
+	}
+	return result, nil
+}
+
+// backfillChunk recounts [from, until) while holding the rollup job lock,
+// waiting for it while a refresh runs
+func backfillChunk(ctx context.Context, db *sql.DB, from, until time.Time, websiteID *uuid.UUID) (int64, error) {
+	for {
+		var rows int64
+		var err error
+		ran := RunExclusive(ctx, db, "rollups", func() {
+			err = db.QueryRowContext(ctx, `SELECT rebuild_rollups($1, $2, $3)`, from, until, websiteID).Scan(&rows)
+		})
+		if ran {
+			return rows, err
+		}
+		select {
+		case <-ctx.Done():
+			return 0, ctx.Err()
+		case <-time.After(backfillLockRetry):
+		}
+	}
+}
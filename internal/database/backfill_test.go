@@ -0,0 +1,89 @@
+package database
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func expectBackfillChunk(mock sqlmock.Sqlmock, from, until time.Time, websiteID *uuid.UUID, rows int) {
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").WithArgs(jobLockNamespace, "rollups").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(true))
+	mock.ExpectQuery(`SELECT rebuild_rollups\(\$1, \$2, \$3\)`).WithArgs(from, until, websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"rebuild_rollups"}).AddRow(rows))
+	mock.ExpectExec("SELECT pg_advisory_unlock").WithArgs(jobLockNamespace, "rollups").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+}
+
+func TestBackfillRollupsRecountsInChunks(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	websiteID := uuid.New()
+	day := func(d int) time.Time { return time.Date(2025, 3, d, 0, 0, 0, 0, time.UTC) }
+	rolledUntil := day(6).Add(5 * time.Hour)
+
+	// The range starts at the first raw event and stops at the last refresh
+	mock.ExpectQuery("SELECT MIN\\(created_at\\)").WithArgs(&websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"min", "max"}).AddRow(day(2).Add(13*time.Hour), rolledUntil))
+	expectBackfillChunk(mock, day(2), day(4), &websiteID, 100)
+	expectBackfillChunk(mock, day(4), day(6), &websiteID, 80)
+	expectBackfillChunk(mock, day(6), rolledUntil, &websiteID, 5)
+
+	var reported []BackfillProgress
+	result, err := BackfillRollups(context.Background(), DB, BackfillOptions{
+		From:      day(1),
+		Until:     day(10),
+		WebsiteID: &websiteID,
+		ChunkDays: 2,
+		Progress:  func(p BackfillProgress) { reported = append(reported, p) },
+	})
+	require.NoError(t, err)
+	assert.Equal(t, day(2), result.From)
+	assert.Equal(t, rolledUntil, result.Until)
+	assert.Equal(t, 3, result.Chunks)
+	assert.Equal(t, int64(185), result.Rows)
+	require.Len(t, reported, 3)
+	assert.Equal(t, BackfillProgress{Done: 3, Total: 3, Through: rolledUntil, Rows: 185}, reported[2])
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestBackfillRollupsWaitsForRefresh(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	original := backfillLockRetry
+	backfillLockRetry = time.Millisecond
+	t.Cleanup(func() { backfillLockRetry = original })
+
+	day := time.Date(2025, 3, 2, 0, 0, 0, 0, time.UTC)
+	mock.ExpectQuery("SELECT MIN\\(created_at\\)").
+		WillReturnRows(sqlmock.NewRows([]string{"min", "max"}).AddRow(day, day.AddDate(0, 0, 1)))
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(false))
+	expectBackfillChunk(mock, day, day.AddDate(0, 0, 1), nil, 7)
+
+	result, err := BackfillRollups(context.Background(), DB, BackfillOptions{From: day})
+	require.NoError(t, err)
+	assert.Equal(t, int64(7), result.Rows)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestBackfillRollupsWithoutRollups(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT MIN\\(created_at\\)").
+		WillReturnRows(sqlmock.NewRows([]string{"min", "max"}).AddRow(time.Now(), nil))
+
+	result, err := BackfillRollups(context.Background(), DB, BackfillOptions{})
+	require.NoError(t, err)
+	assert.Zero(t, result.Chunks)
+	assert.True(t, result.From.IsZero())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
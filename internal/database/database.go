@@ -2,9 +2,14 @@ package database
 
 import (
 	"database/sql"
+	"errors"
 	"fmt"
+	"net/url"
 	"os"
 	"runtime"
+	"strconv"
+	"strings"
+	"time"
 
 	_ "github.com/lib/pq"
 
@@ -15,6 +20,89 @@ import (
 
 var DB *sql.DB
 
+// PoolSettings tunes the connection pool. Zero values keep the driver
+// defaults.
+type PoolSettings struct {
+	Size             int           // Maximum open connections
+	AcquireTimeout   time.Duration // How long opening a connection may take
+	StatementTimeout time.Duration // Server-side limit for each statement
+	IdleLifetime     time.Duration // Close connections idle this long
+}
+
+var poolSettings PoolSettings
+
+// SetPoolSettings configures the pools opened by later connects
+func SetPoolSettings(settings PoolSettings) {
+	poolSettings = settings
+}
+
+// ErrUnsupportedDatabase is returned for a DATABASE_URL that doesn't point
+// at PostgreSQL
+var ErrUnsupportedDatabase = errors.New("unsupported database")
+
+// CheckURL rejects database URLs for engines Kaunta can't run on. The schema
+// relies on PostgreSQL itself (partitioned tables, materialized views and
+// PL/pgSQL functions and triggers), so other engines would only fail later
+// with a confusing driver error. Both URLs and key=value DSNs are accepted.
+func CheckURL(databaseURL string) error {
+	if isSQLite(databaseURL) {
+		return fmt.Errorf("%w: SQLite is not supported; Kaunta needs PostgreSQL 18 or newer", ErrUnsupportedDatabase)
+	}
+	if isMySQL(databaseURL) {
+		return fmt.Errorf("%w: MySQL and MariaDB are not supported; Kaunta needs PostgreSQL 18 or newer", ErrUnsupportedDatabase)
+	}
+	scheme, _, hasScheme := strings.Cut(databaseURL, "://")
+	scheme = strings.ToLower(scheme)
+	if hasScheme && scheme != "postgres" && scheme != "postgresql" {
+		return fmt.Errorf("%w %q: Kaunta needs PostgreSQL 18 or newer (postgres://...)", ErrUnsupportedDatabase, scheme)
+	}
+	return nil
+}
+
+// isSQLite recognizes sqlite:, sqlite3: and file: URLs and bare paths to
+// database files. A key=value DSN is PostgreSQL even when its dbname ends
+// in .db.
+func isSQLite(databaseURL string) bool {
+	lower := strings.ToLower(databaseURL)
+	for _, prefix := range []string{"sqlite:", "sqlite3:", "file:"} {
+		if strings.HasPrefix(lower, prefix) {
+			return true
+		}
+	}
+	if isKeyValueDSN(lower) {
+		return false
+	}
+	path, _, _ := strings.Cut(lower, "?")
+	for _, ext := range []string{".db", ".sqlite", ".sqlite3"} {
+		if strings.HasSuffix(path, ext) && !strings.Contains(path, "://") {
+			return true
+		}
+	}
+	return false
+}
+
+// isKeyValueDSN reports whether databaseURL is a lib/pq connection string
+// such as "host=localhost dbname=kaunta.db", whose first field is a
+// keyword=value pair
+func isKeyValueDSN(databaseURL string) bool {
+	fields := strings.Fields(databaseURL)
+	if len(fields) == 0 || strings.Contains(databaseURL, "://") {
+		return false
+	}
+	key, _, ok := strings.Cut(fields[0], "=")
+	return ok && key != "" && !strings.ContainsAny(key, "/\\.:?@")
+}
+
+// isMySQL recognizes mysql:// and mariadb:// URLs and go-sql-driver DSNs
+// such as user:pass@tcp(localhost:3306)/kaunta
+func isMySQL(databaseURL string) bool {
+	lower := strings.ToLower(databaseURL)
+	return strings.HasPrefix(lower, "mysql://") ||
+		strings.HasPrefix(lower, "mariadb://") ||
+		strings.Contains(lower, "@tcp(") ||
+		strings.Contains(lower, "@unix(")
+}
+
 // Connect connects to database using DATABASE_URL environment variable
 func Connect() error {
 	databaseURL := os.Getenv("DATABASE_URL")
@@ -29,12 +117,22 @@ func ConnectWithURL(databaseURL string) error {
 	if databaseURL == "" {
 		return fmt.Errorf("database URL cannot be empty")
 	}
+	if err := CheckURL(databaseURL); err != nil {
+		return err
+	}
 
 	var err error
-	DB, err = sql.Open("postgres", databaseURL)
+	DB, err = sql.Open("postgres", withConnParams(databaseURL, poolSettings))
 	if err != nil {
 		return fmt.Errorf("failed to open database: %w", err)
 	}
+	if poolSettings.Size > 0 {
+		DB.SetMaxOpenConns(poolSettings.Size)
+		DB.SetMaxIdleConns(poolSettings.Size)
+	}
+	if poolSettings.IdleLifetime > 0 {
+		DB.SetConnMaxIdleTime(poolSettings.IdleLifetime)
+	}
 
 	// Test connection
 	if err = DB.Ping(); err != nil {
@@ -50,6 +148,45 @@ func ConnectWithURL(databaseURL string) error {
 	return nil
 }
 
+// withConnParams adds the timeouts in settings to a URL or key=value DSN,
+// leaving parameters the DSN already sets alone
+func withConnParams(databaseURL string, settings PoolSettings) string {
+	params := [][2]string{}
+	if settings.AcquireTimeout > 0 {
+		// connect_timeout is in whole seconds; lib/pq treats 0 as no limit
+		seconds := max(int(settings.AcquireTimeout.Round(time.Second)/time.Second), 1)
+		params = append(params, [2]string{"connect_timeout", strconv.Itoa(seconds)})
+	}
+	if settings.StatementTimeout > 0 {
+		params = append(params, [2]string{"statement_timeout", strconv.FormatInt(settings.StatementTimeout.Milliseconds(), 10)})
+	}
+	if len(params) == 0 {
+		return databaseURL
+	}
+
+	if strings.Contains(databaseURL, "://") {
+		u, err := url.Parse(databaseURL)
+		if err != nil {
+			return databaseURL
+		}
+		q := u.Query()
+		for _, p := range params {
+			if !q.Has(p[0]) {
+				q.Set(p[0], p[1])
+			}
+		}
+		u.RawQuery = q.Encode()
+		return u.String()
+	}
+
+	for _, p := range params {
+		if !strings.Contains(databaseURL, p[0]+"=") {
+			databaseURL += " " + p[0] + "=" + p[1]
+		}
+	}
+	return databaseURL
+}
+
 // configureAsyncIO enables PostgreSQL 18 async I/O on supported platforms.
 // On Linux with io_uring support, this can provide up to 3x faster sequential scans.
 // Falls back gracefully on macOS/BSD/Windows.
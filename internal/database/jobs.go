@@ -0,0 +1,212 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"time"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// What started a job run
+const (
+	JobTriggerSchedule = "schedule"
+	JobTriggerManual   = "manual"
+)
+
+// maxJobErrorLength caps the error kept in job_run
+const maxJobErrorLength = 1000
+
+// Job is a background task the server runs every interval, on one instance
+// at a time
+type Job struct {
+	Name        string
+	Description string
+	Every       time.Duration
+	Lock        string // Advisory lock shared with other runs of the job; Name when empty
+	Run         func(ctx context.Context) error
+}
+
+func (j Job) lock() string {
+	if j.Lock != "" {
+		return j.Lock
+	}
+	return j.Name
+}
+
+// JobStatus is a job with its last and next run, as recorded in job_run.
+// The times are nil for a job that hasn't run yet.
+type JobStatus struct {
+	Name           string        `json:"name"`
+	Description    string        `json:"description"`
+	Every          time.Duration `json:"-"`
+	LastStartedAt  *time.Time    `json:"last_started_at,omitempty"`
+	LastFinishedAt *time.Time    `json:"last_finished_at,omitempty"`
+	LastError      string        `json:"last_error,omitempty"`
+	LastTrigger    string        `json:"last_trigger,omitempty"`
+	NextRunAt      *time.Time    `json:"next_run_at,omitempty"`
+	Runs           int64         `json:"runs"`
+	Failures       int64         `json:"failures"`
+}
+
+// MarshalJSON reports the interval in seconds
+func (s JobStatus) MarshalJSON() ([]byte, error) {
+	type status JobStatus
+	return json.Marshal(struct {
+		status
+		EverySeconds float64 `json:"every_seconds"`
+	}{status(s), s.Every.Seconds()})
+}
+
+// JobScheduler runs jobs on their intervals. A job is first run when the
+// next run recorded by the previous server is due, then every interval.
+type JobScheduler struct {
+	db       *sql.DB
+	jobs     []Job
+	stopChan chan struct{}
+}
+
+// NewJobScheduler creates a scheduler for jobs
+func NewJobScheduler(db *sql.DB, jobs []Job) *JobScheduler {
+	return &JobScheduler{db: db, jobs: jobs, stopChan: make(chan struct{})}
+}
+
+// Start schedules every job
+func (s *JobScheduler) Start() {
+	logging.L().Info("starting job scheduler", slog.Int("jobs", len(s.jobs)))
+	for _, job := range s.jobs {
+		go s.loop(job)
+	}
+}
+
+// Stop gracefully stops the scheduler
+func (s *JobScheduler) Stop() {
+	close(s.stopChan)
+}
+
+func (s *JobScheduler) loop(job Job) {
+	timer := time.NewTimer(s.firstDelay(job))
+	defer timer.Stop()
+
+	for {
+		select {
+		case <-timer.C:
+			_, _ = RunJob(context.Background(), s.db, job, JobTriggerSchedule)
+			timer.Reset(job.Every)
+		case <-s.stopChan:
+			return
+		}
+	}
+}
+
+// firstDelay is how long until the job's recorded next run, at most one
+// interval; jobs without one run right away
+func (s *JobScheduler) firstDelay(job Job) time.Duration {
+	var next sql.NullTime
+	err := s.db.QueryRow(`SELECT next_run_at FROM job_run WHERE name = $1`, job.Name).Scan(&next)
+	if err != nil {
+		if !errors.Is(err, sql.ErrNoRows) {
+			logging.L().Warn("failed to read job schedule", slog.String("job", job.Name), slog.Any("error", err))
+		}
+		return 0
+	}
+	if !next.Valid {
+		return 0
+	}
+	return min(max(next.Time.Sub(nowFunc()), 0), job.Every)
+}
+
+// RunJob runs job now unless another instance is running it, records the
+// run and reports whether it ran. Scheduled runs also record when the job
+// is due next.
+func RunJob(ctx context.Context, db *sql.DB, job Job, trigger string) (bool, error) {
+	var runErr error
+	started := nowFunc()
+	ran := RunExclusive(ctx, db, job.lock(), func() { runErr = job.Run(ctx) })
+	if !ran {
+		return false, nil
+	}
+	finished := nowFunc()
+
+	var lastError sql.NullString
+	if runErr != nil {
+		logging.L().Warn("job failed", slog.String("job", job.Name), slog.Any("error", runErr))
+		lastError = sql.NullString{String: runErr.Error(), Valid: true}
+		if len(lastError.String) > maxJobErrorLength {
+			lastError.String = lastError.String[:maxJobErrorLength]
+		}
+	}
+	var next sql.NullTime
+	if trigger == JobTriggerSchedule {
+		next = sql.NullTime{Time: finished.Add(job.Every), Valid: true}
+	}
+
+	if _, err := db.ExecContext(context.Background(), `
+		INSERT INTO job_run (name, last_started_at, last_finished_at, last_error, last_trigger, next_run_at, runs, failures)
+		VALUES ($1, $2, $3, $4, $5, $6, 1, CASE WHEN $4::text IS NULL THEN 0 ELSE 1 END)
+		ON CONFLICT (name) DO UPDATE SET
+			last_started_at = EXCLUDED.last_started_at,
+			last_finished_at = EXCLUDED.last_finished_at,
+			last_error = EXCLUDED.last_error,
+			last_trigger = EXCLUDED.last_trigger,
+			next_run_at = COALESCE(EXCLUDED.next_run_at, job_run.next_run_at),
+			runs = job_run.runs + 1,
+			failures = job_run.failures + EXCLUDED.failures
+	`, job.Name, started, finished, lastError, trigger, next); err != nil {
+		logging.L().Warn("failed to record job run", slog.String("job", job.Name), slog.Any("error", err))
+	}
+	return true, runErr
+}
+
+// ListJobs returns the status of jobs, in their order
+func ListJobs(ctx context.Context, db *sql.DB, jobs []Job) ([]JobStatus, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT name, last_started_at, last_finished_at, last_error, last_trigger, next_run_at, runs, failures
+		FROM job_run
+	`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	recorded := make(map[string]JobStatus)
+	for rows.Next() {
+		var s JobStatus
+		var started, finished time.Time
+		var lastError sql.NullString
+		var next sql.NullTime
+		if err := rows.Scan(&s.Name, &started, &finished, &lastError, &s.LastTrigger, &next, &s.Runs, &s.Failures); err != nil {
+			return nil, err
+		}
+		s.LastStartedAt, s.LastFinishedAt, s.LastError = &started, &finished, lastError.String
+		if next.Valid {
+			s.NextRunAt = &next.Time
+		}
+		recorded[s.Name] = s
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+
+	statuses := make([]JobStatus, 0, len(jobs))
+	for _, job := range jobs {
+		s := recorded[job.Name]
+		s.Name, s.Description, s.Every = job.Name, job.Description, job.Every
+		statuses = append(statuses, s)
+	}
+	return statuses, nil
+}
+
+// FindJob returns the job called name
+func FindJob(jobs []Job, name string) (Job, bool) {
+	for _, job := range jobs {
+		if job.Name == name {
+			return job, true
+		}
+	}
+	return Job{}, false
+}
@@ -0,0 +1,136 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func stubJobClock(t *testing.T, now time.Time) {
+	t.Helper()
+	original := nowFunc
+	nowFunc = func() time.Time { return now }
+	t.Cleanup(func() { nowFunc = original })
+}
+
+func TestRunJobRecordsScheduledRun(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	now := time.Date(2026, 3, 4, 12, 0, 0, 0, time.UTC)
+	stubJobClock(t, now)
+
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").WithArgs(jobLockNamespace, "view:daily_website_stats").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(true))
+	mock.ExpectExec("SELECT pg_advisory_unlock").WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO job_run").
+		WithArgs("daily-stats", now, now, sql.NullString{}, JobTriggerSchedule, sql.NullTime{Time: now.Add(time.Hour), Valid: true}).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	calls := 0
+	job := Job{Name: "daily-stats", Every: time.Hour, Lock: "view:daily_website_stats",
+		Run: func(context.Context) error { calls++; return nil }}
+	ran, err := RunJob(context.Background(), DB, job, JobTriggerSchedule)
+	require.NoError(t, err)
+	assert.True(t, ran)
+	assert.Equal(t, 1, calls)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRunJobRecordsManualFailure(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	now := time.Date(2026, 3, 4, 12, 0, 0, 0, time.UTC)
+	stubJobClock(t, now)
+
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").WithArgs(jobLockNamespace, "retention").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(true))
+	mock.ExpectExec("SELECT pg_advisory_unlock").WillReturnResult(sqlmock.NewResult(0, 1))
+	// Manual runs keep the scheduled next run
+	mock.ExpectExec("INSERT INTO job_run").
+		WithArgs("retention", now, now, sql.NullString{String: "disk full", Valid: true}, JobTriggerManual, sql.NullTime{}).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	job := Job{Name: "retention", Every: 24 * time.Hour, Run: func(context.Context) error { return errors.New("disk full") }}
+	ran, err := RunJob(context.Background(), DB, job, JobTriggerManual)
+	assert.True(t, ran)
+	assert.EqualError(t, err, "disk full")
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRunJobSkipsWhileRunningElsewhere(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(false))
+
+	job := Job{Name: "rollups", Every: 5 * time.Minute, Run: func(context.Context) error {
+		t.Fatal("job ran without the lock")
+		return nil
+	}}
+	ran, err := RunJob(context.Background(), DB, job, JobTriggerManual)
+	require.NoError(t, err)
+	assert.False(t, ran)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestListJobs(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	started := time.Date(2026, 3, 4, 3, 0, 0, 0, time.UTC)
+
+	mock.ExpectQuery("SELECT name, last_started_at, last_finished_at, last_error, last_trigger, next_run_at, runs, failures").
+		WillReturnRows(sqlmock.NewRows([]string{"name", "last_started_at", "last_finished_at", "last_error", "last_trigger", "next_run_at", "runs", "failures"}).
+			AddRow("retention", started, started.Add(time.Minute), "disk full", JobTriggerSchedule, started.Add(24*time.Hour), 12, 1).
+			AddRow("retired-job", started, started, nil, JobTriggerSchedule, nil, 3, 0))
+
+	jobs := []Job{
+		{Name: "rollups", Description: "Roll up", Every: 5 * time.Minute},
+		{Name: "retention", Description: "Purge", Every: 24 * time.Hour},
+	}
+	statuses, err := ListJobs(context.Background(), DB, jobs)
+	require.NoError(t, err)
+	require.Len(t, statuses, 2)
+
+	assert.Equal(t, "rollups", statuses[0].Name)
+	assert.Nil(t, statuses[0].LastStartedAt)
+	assert.Zero(t, statuses[0].Runs)
+
+	assert.Equal(t, "Purge", statuses[1].Description)
+	assert.Equal(t, "disk full", statuses[1].LastError)
+	assert.Equal(t, int64(12), statuses[1].Runs)
+	require.NotNil(t, statuses[1].NextRunAt)
+	assert.Equal(t, started.Add(24*time.Hour), *statuses[1].NextRunAt)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestJobSchedulerFirstDelay(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	now := time.Date(2026, 3, 4, 12, 0, 0, 0, time.UTC)
+	stubJobClock(t, now)
+
+	// Due in 2 hours, never later than an interval
+	mock.ExpectQuery("SELECT next_run_at FROM job_run").WithArgs("partitions").
+		WillReturnRows(sqlmock.NewRows([]string{"next_run_at"}).AddRow(now.Add(2 * time.Hour)))
+	mock.ExpectQuery("SELECT next_run_at FROM job_run").WithArgs("alerts").
+		WillReturnRows(sqlmock.NewRows([]string{"next_run_at"}).AddRow(now.Add(time.Hour)))
+	// Overdue or never run
+	mock.ExpectQuery("SELECT next_run_at FROM job_run").WithArgs("retention").
+		WillReturnRows(sqlmock.NewRows([]string{"next_run_at"}).AddRow(now.Add(-time.Hour)))
+	mock.ExpectQuery("SELECT next_run_at FROM job_run").WithArgs("rollups").
+		WillReturnError(sql.ErrNoRows)
+
+	s := NewJobScheduler(DB, nil)
+	assert.Equal(t, 2*time.Hour, s.firstDelay(Job{Name: "partitions", Every: 24 * time.Hour}))
+	assert.Equal(t, time.Minute, s.firstDelay(Job{Name: "alerts", Every: time.Minute}))
+	assert.Zero(t, s.firstDelay(Job{Name: "retention", Every: 24 * time.Hour}))
+	assert.Zero(t, s.firstDelay(Job{Name: "rollups", Every: 5 * time.Minute}))
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,49 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"database/sql/driver"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// jobLockNamespace is the first key of Kaunta's advisory locks, keeping them
+// apart from other applications sharing the database
+const jobLockNamespace = 0x4b41554e // "KAUN"
+
+// RunExclusive runs fn unless another Kaunta instance is already running the
+// job called name, and reports whether it ran. The instance running fn holds
+// a session advisory lock on a dedicated connection, which PostgreSQL also
+// releases if the instance dies mid-job. When the lock can't be checked the
+// job is skipped until its next run.
+func RunExclusive(ctx context.Context, db *sql.DB, name string, fn func()) bool {
+	conn, err := db.Conn(ctx)
+	if err != nil {
+		logging.L().Warn("failed to reserve connection for job lock", slog.String("job", name), slog.Any("error", err))
+		return false
+	}
+	defer func() { _ = conn.Close() }()
+
+	var locked bool
+	if err := conn.QueryRowContext(ctx, `SELECT pg_try_advisory_lock($1, hashtext($2))`, jobLockNamespace, name).Scan(&locked); err != nil {
+		logging.L().Warn("failed to take job lock", slog.String("job", name), slog.Any("error", err))
+		return false
+	}
+	if !locked {
+		logging.L().Debug("job running on another instance", slog.String("job", name))
+		return false
+	}
+
+	defer func() {
+		if _, err := conn.ExecContext(context.Background(), `SELECT pg_advisory_unlock($1, hashtext($2))`, jobLockNamespace, name); err != nil {
+			// Don't return a connection still holding the lock to the pool
+			_ = conn.Raw(func(any) error { return driver.ErrBadConn })
+			logging.L().Warn("failed to release job lock", slog.String("job", name), slog.Any("error", err))
+		}
+	}()
+	fn()
+	return true
+}
@@ -0,0 +1,49 @@
+package database
+
+import (
+	"context"
+	"errors"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+)
+
+func TestRunExclusiveRunsWhileHoldingLock(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").WithArgs(jobLockNamespace, "rollups").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(true))
+	mock.ExpectExec("SELECT pg_advisory_unlock").WithArgs(jobLockNamespace, "rollups").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	calls := 0
+	ran := RunExclusive(context.Background(), DB, "rollups", func() { calls++ })
+	assert.True(t, ran)
+	assert.Equal(t, 1, calls)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRunExclusiveSkipsWhenLockedElsewhere(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").
+		WillReturnRows(sqlmock.NewRows([]string{"locked"}).AddRow(false))
+
+	ran := RunExclusive(context.Background(), DB, "rollups", func() { t.Fatal("job ran without the lock") })
+	assert.False(t, ran)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRunExclusiveSkipsWhenLockFails(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT pg_try_advisory_lock").WillReturnError(errors.New("connection reset"))
+
+	ran := RunExclusive(context.Background(), DB, "rollups", func() { t.Fatal("job ran without the lock") })
+	assert.False(t, ran)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -15,6 +15,10 @@ var migrationFS embed.FS
 
 // RunMigrations runs all pending database migrations
 func RunMigrations(databaseURL string) error {
+	if err := CheckURL(databaseURL); err != nil {
+		return err
+	}
+
 	// Create iofs source from embedded files
 	sourceDriver, err := iofs.New(migrationFS, "migrations")
 	if err != nil {
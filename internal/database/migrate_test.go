@@ -0,0 +1,30 @@
+package database
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+// Alert rules, webhooks and custom dashboards re-check access with
+// user_websites_all_teams: it must follow team membership but not the
+// dashboard's team switcher
+func TestUserWebsitesAllTeamsIgnoresTeamSwitcher(t *testing.T) {
+	migration, err := migrationFS.ReadFile("migrations/000061_user_websites_all_teams.up.sql")
+	require.NoError(t, err)
+
+	allTeams, scoped, ok := strings.Cut(string(migration), "CREATE OR REPLACE FUNCTION user_websites(")
+	require.True(t, ok)
+	_, allTeams, ok = strings.Cut(allTeams, "CREATE OR REPLACE FUNCTION user_websites_all_teams(")
+	require.True(t, ok)
+
+	assert.Contains(t, allTeams, "JOIN team_member t ON t.team_id = w.team_id")
+	assert.Contains(t, allTeams, "FROM website_member m")
+	assert.Contains(t, allTeams, "w.deleted_at IS NULL")
+	assert.NotContains(t, allTeams, "current_team_id")
+
+	assert.Contains(t, scoped, "FROM user_websites_all_teams(p_user_id) a")
+	assert.Contains(t, scoped, "u.current_team_id")
+}
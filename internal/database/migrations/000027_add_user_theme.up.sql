@@ -0,0 +1,6 @@
+-- Migration 000027: Per-user dashboard theme
+-- Stores the selected dashboard skin ('default', 'retro'). Share links can
+-- still override it per request with ?theme=.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS theme VARCHAR(32) NOT NULL DEFAULT 'default';
+
+COMMENT ON COLUMN users.theme IS 'Dashboard theme selected by the user (CSS token set)';
@@ -0,0 +1,87 @@
+-- Migration 000028: Page URL search for get_top_pages()
+-- Adds an optional ILIKE pattern so the Pages report and stats API can narrow
+-- thousands of URLs down to a section. Callers translate user input
+-- (substring or glob such as /blog/*) into the LIKE pattern.
+
+DROP FUNCTION IF EXISTS get_top_pages(UUID, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR);
+
+CREATE OR REPLACE FUNCTION get_top_pages(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 1,
+    p_limit INTEGER DEFAULT 10,
+    p_offset INTEGER DEFAULT 0,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_sort_by VARCHAR DEFAULT 'views',
+    p_sort_order VARCHAR DEFAULT 'desc',
+    p_path_pattern VARCHAR DEFAULT NULL
+)
+RETURNS TABLE (
+    path VARCHAR,
+    views BIGINT,
+    unique_visitors BIGINT,
+    avg_engagement_time NUMERIC,
+    total_count BIGINT
+) AS $$
+BEGIN
+    RETURN QUERY
+    WITH filtered_events AS (
+        SELECT e.url_path, e.session_id, e.engagement_time
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= CURRENT_DATE - (p_days || ' days')::INTERVAL
+          AND e.event_type = 1
+          AND e.url_path IS NOT NULL
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_path_pattern IS NULL OR e.url_path ILIKE p_path_pattern)
+    ),
+    page_stats AS (
+        SELECT
+            fe.url_path,
+            COUNT(*)::BIGINT as view_count,
+            COUNT(DISTINCT fe.session_id)::BIGINT as unique_visitor_count,
+            ROUND(AVG(COALESCE(fe.engagement_time, 0)), 0) as avg_time
+        FROM filtered_events fe
+        GROUP BY fe.url_path
+    ),
+    total_count_cte AS (
+        SELECT COUNT(*)::BIGINT as total FROM page_stats
+    )
+    SELECT
+        ps.url_path::VARCHAR,
+        ps.view_count,
+        ps.unique_visitor_count,
+        ps.avg_time,
+        tc.total as total_count
+    FROM page_stats ps
+    CROSS JOIN total_count_cte tc
+    ORDER BY
+        CASE WHEN p_sort_order = 'desc' THEN
+            CASE p_sort_by
+                WHEN 'views' THEN ps.view_count
+                WHEN 'unique_visitors' THEN ps.unique_visitor_count
+                WHEN 'avg_engagement_time' THEN ps.avg_time::BIGINT
+                ELSE ps.view_count
+            END
+        END DESC NULLS LAST,
+        CASE WHEN p_sort_order = 'asc' THEN
+            CASE p_sort_by
+                WHEN 'views' THEN ps.view_count
+                WHEN 'unique_visitors' THEN ps.unique_visitor_count
+                WHEN 'avg_engagement_time' THEN ps.avg_time::BIGINT
+                ELSE ps.view_count
+            END
+        END ASC NULLS LAST,
+        CASE WHEN p_sort_by = 'path' AND p_sort_order = 'desc' THEN ps.url_path END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'path' AND p_sort_order = 'asc' THEN ps.url_path END ASC NULLS LAST
+    LIMIT p_limit
+    OFFSET p_offset;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_top_pages(UUID, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR)
+IS 'Top pages with pagination, sorting and optional ILIKE path pattern (p_path_pattern)';
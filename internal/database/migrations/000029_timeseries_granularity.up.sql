@@ -0,0 +1,137 @@
+-- Migration 000029: Minute, hour and day granularity for get_timeseries()
+-- Daily buckets hide launch-day dynamics, so the chart can now ask for:
+--   minute - the realtime window (last 60 minutes, p_days is ignored)
+--   hour   - intended for 24h/7d ranges
+--   day    - longer ranges
+-- Empty buckets are returned as zero so charts keep a continuous time axis.
+-- Unfiltered hour/day queries read settled buckets from the rollups and only
+-- scan raw events for the buckets the scheduler has not refreshed yet.
+
+-- ============================================================================
+-- 1. Extend the hourly rollup from 48 hours to 7 days
+-- ============================================================================
+
+DROP MATERIALIZED VIEW IF EXISTS hourly_website_stats;
+
+CREATE MATERIALIZED VIEW hourly_website_stats AS
+WITH base_events AS (
+    SELECT website_id, DATE_TRUNC('hour', created_at) as hour, created_at, session_id, visit_id, event_type, engagement_time, url_path
+    FROM website_event WHERE created_at >= NOW() - INTERVAL '7 days' AND event_type = 1
+),
+page_counts AS (
+    SELECT website_id, hour, url_path, COUNT(*) as page_count
+    FROM base_events WHERE url_path IS NOT NULL
+    GROUP BY website_id, hour, url_path
+),
+ranked_pages AS (
+    SELECT website_id, hour, url_path, page_count,
+           ROW_NUMBER() OVER (PARTITION BY website_id, hour ORDER BY page_count DESC) as page_rank
+    FROM page_counts
+),
+aggregated_metrics AS (
+    SELECT website_id, hour, COUNT(*) as pageviews, COUNT(DISTINCT session_id) as sessions,
+           COUNT(DISTINCT visit_id) as visits, COUNT(*) FILTER (WHERE event_type = 2) as custom_events,
+           AVG(engagement_time) FILTER (WHERE engagement_time IS NOT NULL) as avg_engagement_time
+    FROM base_events GROUP BY website_id, hour
+)
+SELECT am.website_id, am.hour, am.pageviews, am.sessions, am.visits, am.custom_events, am.avg_engagement_time,
+       (SELECT jsonb_object_agg(url_path, page_count) FROM ranked_pages rp WHERE rp.website_id = am.website_id AND rp.hour = am.hour AND rp.page_rank <= 5) as top_pages
+FROM aggregated_metrics am;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_hourly_stats_pk ON hourly_website_stats (website_id, hour);
+
+COMMENT ON MATERIALIZED VIEW hourly_website_stats IS 'Refresh every 5 minutes; covers the last 7 days for hour-level charts';
+
+-- ============================================================================
+-- 2. get_timeseries() with a granularity argument
+-- ============================================================================
+
+DROP FUNCTION IF EXISTS get_timeseries(UUID, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR);
+
+CREATE OR REPLACE FUNCTION get_timeseries(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 7,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL,
+    p_granularity VARCHAR DEFAULT 'hour'
+)
+RETURNS TABLE (
+    hour TIMESTAMPTZ,
+    views BIGINT
+) AS $$
+DECLARE
+    v_unit TEXT;
+    v_step INTERVAL;
+    v_start TIMESTAMPTZ;
+    v_settled TIMESTAMPTZ;
+    v_unfiltered BOOLEAN;
+BEGIN
+    v_unit := CASE LOWER(COALESCE(p_granularity, 'hour'))
+        WHEN 'minute' THEN 'minute'
+        WHEN 'day' THEN 'day'
+        ELSE 'hour'
+    END;
+    v_step := ('1 ' || v_unit)::INTERVAL;
+
+    IF v_unit = 'minute' THEN
+        v_start := DATE_TRUNC('minute', NOW() - INTERVAL '59 minutes');
+    ELSE
+        v_start := DATE_TRUNC(v_unit, NOW() - (p_days || ' days')::INTERVAL);
+    END IF;
+
+    v_unfiltered := p_country IS NULL AND p_browser IS NULL AND p_device IS NULL AND p_page_path IS NULL;
+
+    -- Buckets before v_settled come from the rollup; later ones from raw events.
+    -- The margin covers the rollup refresh interval.
+    v_settled := CASE
+        WHEN v_unfiltered AND v_unit = 'hour' THEN DATE_TRUNC('hour', NOW() - INTERVAL '10 minutes')
+        WHEN v_unfiltered AND v_unit = 'day' THEN DATE_TRUNC('day', NOW() - INTERVAL '2 hours')
+        ELSE v_start
+    END;
+
+    RETURN QUERY
+    WITH buckets AS (
+        SELECT b AS bucket
+        FROM generate_series(v_start, DATE_TRUNC(v_unit, NOW()), v_step) AS b
+    ),
+    rolled AS (
+        SELECT h.hour AS bucket, h.pageviews AS views
+        FROM hourly_website_stats h
+        WHERE v_unit = 'hour'
+          AND h.website_id = p_website_id
+          AND h.hour >= v_start
+          AND h.hour < v_settled
+        UNION ALL
+        SELECT d.date::TIMESTAMPTZ AS bucket, d.pageviews AS views
+        FROM daily_website_stats d
+        WHERE v_unit = 'day'
+          AND d.website_id = p_website_id
+          AND d.date::TIMESTAMPTZ >= v_start
+          AND d.date::TIMESTAMPTZ < v_settled
+    ),
+    live AS (
+        SELECT DATE_TRUNC(v_unit, e.created_at) AS bucket, COUNT(*)::BIGINT AS views
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= GREATEST(v_start, v_settled)
+          AND e.event_type = 1
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path)
+        GROUP BY 1
+    )
+    SELECT
+        b.bucket::TIMESTAMPTZ AS hour,
+        COALESCE(r.views, l.views, 0)::BIGINT AS views
+    FROM buckets b
+    LEFT JOIN rolled r ON r.bucket = b.bucket
+    LEFT JOIN live l ON l.bucket = b.bucket
+    ORDER BY b.bucket ASC;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_timeseries IS 'Pageview time series in minute, hour or day buckets with empty buckets filled as zero';
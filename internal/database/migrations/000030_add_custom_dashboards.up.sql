@@ -0,0 +1,33 @@
+-- Migration 000030: Custom dashboards
+-- Users compose their own dashboards from widgets. Each widget carries a
+-- saved query (period, metric or dimension, goal and segment filters) in
+-- JSONB so new widget options do not need schema changes.
+-- Public dashboards follow the public stats opt-in model: readable without
+-- login at /share/dashboards/{dashboard_id} once is_public is set.
+
+CREATE TABLE custom_dashboard (
+    dashboard_id UUID PRIMARY KEY DEFAULT uuidv7(),
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    is_public BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_custom_dashboard_user ON custom_dashboard(user_id);
+
+CREATE TABLE custom_dashboard_widget (
+    widget_id UUID PRIMARY KEY DEFAULT uuidv7(),
+    dashboard_id UUID NOT NULL REFERENCES custom_dashboard(dashboard_id) ON DELETE CASCADE,
+    position INT NOT NULL DEFAULT 0,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('metric', 'timeseries', 'breakdown', 'goal')),
+    title VARCHAR(100) NOT NULL DEFAULT '',
+    query JSONB NOT NULL DEFAULT '{}'::JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_custom_dashboard_widget_dashboard ON custom_dashboard_widget(dashboard_id, position);
+
+COMMENT ON TABLE custom_dashboard IS 'User-composed dashboards; is_public exposes them read-only via share link';
+COMMENT ON COLUMN custom_dashboard_widget.query IS 'Saved query: days, metric, dimension, goal_id and country/browser/device/page segment filters';
@@ -0,0 +1,28 @@
+-- Migration 000031: Traffic alert rules
+-- Per-website rules evaluated by the alert scheduler every minute:
+--   spike   - visitors in the window exceed threshold x the trailing average
+--   drop    - visitors in the window fall below the trailing average / threshold
+--   silence - no events at all in the window (e.g. a broken tracking snippet)
+-- Alerts are delivered to a webhook URL or an email address. last_fired_at
+-- is the cooldown marker so a rule fires at most once per window.
+
+CREATE TABLE alert_rule (
+    rule_id UUID PRIMARY KEY DEFAULT uuidv7(),
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('spike', 'drop', 'silence')),
+    threshold NUMERIC(6, 2) NOT NULL DEFAULT 3 CHECK (threshold >= 1),
+    window_minutes INT NOT NULL DEFAULT 60 CHECK (window_minutes BETWEEN 5 AND 1440),
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('webhook', 'email')),
+    target VARCHAR(500) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_fired_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_alert_rule_user ON alert_rule(user_id);
+CREATE INDEX idx_alert_rule_enabled ON alert_rule(website_id) WHERE enabled;
+
+COMMENT ON TABLE alert_rule IS 'Traffic alert rules (spike, drop, silence) delivered via webhook or email';
+COMMENT ON COLUMN alert_rule.threshold IS 'Multiplier against the trailing 24h per-window average; unused for silence rules';
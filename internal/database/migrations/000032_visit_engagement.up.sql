@@ -0,0 +1,109 @@
+-- Migration 000032: Visit engagement and engagement-aware headline metrics
+-- The tracker now reports engaged (visible and focused) time as "engagement"
+-- pings when the page is hidden or the SPA navigates away. Pings are summed
+-- per visit in visit_engagement, so single-page visits get a real duration
+-- instead of a zero-second pageview gap.
+--
+-- get_dashboard_stats() gains avg_visit_duration (seconds) and computes the
+-- bounce rate per visit: a bounce is a visit with one pageview and less than
+-- 10 seconds of engaged time. Visits without engagement data (older tracker,
+-- pixel) fall back to the gap between their first and last event.
+-- It also honours p_days (1 = today) instead of always reporting today.
+
+CREATE TABLE visit_engagement (
+    visit_id UUID PRIMARY KEY,
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    session_id UUID NOT NULL,
+    engaged_ms BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_visit_engagement_website ON visit_engagement(website_id, updated_at);
+
+COMMENT ON TABLE visit_engagement IS 'Engaged time per visit, summed from tracker engagement pings';
+
+DROP FUNCTION IF EXISTS get_dashboard_stats(UUID, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR);
+
+CREATE FUNCTION get_dashboard_stats(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 1,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL
+)
+RETURNS TABLE (
+    current_visitors BIGINT,
+    today_pageviews BIGINT,
+    today_visitors BIGINT,
+    bounce_rate NUMERIC(5,2),
+    avg_visit_duration NUMERIC(10,1)
+) AS $$
+DECLARE
+    v_start TIMESTAMPTZ := CURRENT_DATE - (GREATEST(p_days, 1) - 1);
+    v_current_visitors BIGINT;
+    v_pageviews BIGINT;
+    v_visitors BIGINT;
+    v_visits BIGINT;
+    v_bounces BIGINT;
+    v_avg_duration NUMERIC;
+BEGIN
+    -- 1. Current visitors (sessions in last 5 minutes)
+    SELECT COUNT(DISTINCT e.session_id) INTO v_current_visitors
+    FROM website_event e
+    JOIN session s ON e.session_id = s.session_id
+    WHERE e.website_id = p_website_id
+      AND e.created_at >= NOW() - INTERVAL '5 minutes'
+      AND e.event_type = 1
+      AND (p_country IS NULL OR s.country = p_country)
+      AND (p_browser IS NULL OR s.browser = p_browser)
+      AND (p_device IS NULL OR s.device = p_device)
+      AND (p_page_path IS NULL OR e.url_path = p_page_path);
+
+    -- 2. Pageviews and unique visitors for the period
+    SELECT COUNT(*), COUNT(DISTINCT e.session_id) INTO v_pageviews, v_visitors
+    FROM website_event e
+    JOIN session s ON e.session_id = s.session_id
+    WHERE e.website_id = p_website_id
+      AND e.created_at >= v_start
+      AND e.event_type = 1
+      AND (p_country IS NULL OR s.country = p_country)
+      AND (p_browser IS NULL OR s.browser = p_browser)
+      AND (p_device IS NULL OR s.device = p_device)
+      AND (p_page_path IS NULL OR e.url_path = p_page_path);
+
+    -- 3. Per-visit bounce and duration, preferring engaged time over event gaps
+    WITH visits AS (
+        SELECT
+            e.visit_id,
+            COUNT(*) FILTER (WHERE e.event_type = 1) AS pageviews,
+            EXTRACT(EPOCH FROM MAX(e.created_at) - MIN(e.created_at)) AS gap_seconds
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path)
+        GROUP BY e.visit_id
+        HAVING COUNT(*) FILTER (WHERE e.event_type = 1) > 0
+    )
+    SELECT
+        COUNT(*),
+        COUNT(*) FILTER (WHERE v.pageviews = 1 AND COALESCE(ve.engaged_ms, 0) < 10000),
+        AVG(COALESCE(ve.engaged_ms / 1000.0, v.gap_seconds))
+    INTO v_visits, v_bounces, v_avg_duration
+    FROM visits v
+    LEFT JOIN visit_engagement ve ON ve.visit_id = v.visit_id;
+
+    RETURN QUERY SELECT
+        v_current_visitors,
+        v_pageviews,
+        v_visitors,
+        CASE WHEN v_visits > 0 THEN ROUND(v_bounces::NUMERIC / v_visits::NUMERIC * 100, 2) ELSE 0 END::NUMERIC(5,2),
+        ROUND(COALESCE(v_avg_duration, 0), 1)::NUMERIC(10,1);
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_dashboard_stats IS 'Headline metrics: current visitors, pageviews, visitors, engagement-aware bounce rate and average visit duration (seconds)';
@@ -0,0 +1,95 @@
+-- Migration 000033: Device breakdowns
+-- Adds the dimensions behind the dashboard's Devices section:
+--   browser_version - browser name + major version (e.g. "Chrome 126")
+--   screen          - screen class bucketed from the session's screen width
+-- The existing get_breakdown() is kept as get_base_breakdown() and the new
+-- get_breakdown() delegates every other dimension to it unchanged.
+
+-- ============================================================================
+-- 1. Browser version on sessions
+-- ============================================================================
+
+ALTER TABLE session ADD COLUMN IF NOT EXISTS browser_version VARCHAR(20);
+
+-- ============================================================================
+-- 2. Screen classes
+-- ============================================================================
+
+-- Buckets a "WIDTHxHEIGHT" screen into Mobile / Tablet / Laptop / Desktop
+CREATE OR REPLACE FUNCTION screen_class(p_screen VARCHAR)
+RETURNS VARCHAR AS $$
+    SELECT (CASE
+        WHEN p_screen IS NULL OR split_part(p_screen, 'x', 1) !~ '^[0-9]{1,5}$' THEN 'Unknown'
+        WHEN split_part(p_screen, 'x', 1)::INTEGER < 576 THEN 'Mobile'
+        WHEN split_part(p_screen, 'x', 1)::INTEGER < 992 THEN 'Tablet'
+        WHEN split_part(p_screen, 'x', 1)::INTEGER < 1440 THEN 'Laptop'
+        ELSE 'Desktop'
+    END)::VARCHAR
+$$ LANGUAGE SQL IMMUTABLE;
+
+-- ============================================================================
+-- 3. get_breakdown() with browser_version and screen dimensions
+-- ============================================================================
+
+ALTER FUNCTION get_breakdown(UUID, VARCHAR, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR)
+    RENAME TO get_base_breakdown;
+
+CREATE OR REPLACE FUNCTION get_breakdown(
+    p_website_id UUID,
+    p_dimension VARCHAR,
+    p_days INTEGER DEFAULT 1,
+    p_limit INTEGER DEFAULT 10,
+    p_offset INTEGER DEFAULT 0,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL,
+    p_sort_by VARCHAR DEFAULT 'count',
+    p_sort_order VARCHAR DEFAULT 'desc'
+)
+RETURNS TABLE (name VARCHAR, count BIGINT, total_count BIGINT) AS $$
+BEGIN
+    IF p_dimension NOT IN ('browser_version', 'screen') THEN
+        RETURN QUERY
+        SELECT * FROM get_base_breakdown(
+            p_website_id, p_dimension, p_days, p_limit, p_offset,
+            p_country, p_browser, p_device, p_page_path, p_sort_by, p_sort_order
+        );
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    WITH breakdown_data AS (
+        SELECT
+            (CASE p_dimension
+                WHEN 'browser_version' THEN
+                    COALESCE(s.browser, 'Unknown') || COALESCE(' ' || NULLIF(split_part(s.browser_version, '.', 1), ''), '')
+                ELSE screen_class(s.screen)
+            END)::VARCHAR as dim_name,
+            COUNT(*)::BIGINT as dim_count
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= CURRENT_DATE - (p_days || ' days')::INTERVAL
+          AND e.event_type = 1
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path)
+        GROUP BY 1
+    ),
+    total_count_cte AS (
+        SELECT COUNT(*)::BIGINT as total FROM breakdown_data
+    )
+    SELECT bd.dim_name, bd.dim_count, tc.total
+    FROM breakdown_data bd
+    CROSS JOIN total_count_cte tc
+    ORDER BY
+        CASE WHEN p_sort_by = 'count' AND p_sort_order = 'desc' THEN bd.dim_count END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'count' AND p_sort_order = 'asc' THEN bd.dim_count END ASC NULLS LAST,
+        CASE WHEN p_sort_by = 'name' AND p_sort_order = 'desc' THEN bd.dim_name END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'name' AND p_sort_order = 'asc' THEN bd.dim_name END ASC NULLS LAST
+    LIMIT p_limit
+    OFFSET p_offset;
+END;
+$$ LANGUAGE plpgsql STABLE;
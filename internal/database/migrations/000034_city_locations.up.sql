@@ -0,0 +1,13 @@
+-- Migration 000034: City centroids for the live visitor map
+-- One row per (country, city) with the coordinates GeoLite2 publishes for the
+-- city, rounded to one decimal. Coordinates are keyed by city, never by
+-- visitor, so the live map can only plot visitors at their city's centroid.
+
+CREATE TABLE IF NOT EXISTS city_location (
+    country CHAR(2) NOT NULL,
+    city VARCHAR(50) NOT NULL,
+    latitude NUMERIC(4,1) NOT NULL CHECK (latitude BETWEEN -90 AND 90),
+    longitude NUMERIC(4,1) NOT NULL CHECK (longitude BETWEEN -180 AND 180),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (country, city)
+);
@@ -0,0 +1,13 @@
+-- Migration 000035: Kiosk links
+-- A kiosk link is a secret share token that opens a read-only, auto-refreshing
+-- TV view of one website without a login. Each website has at most one link;
+-- generating a new one replaces (and so revokes) the old token.
+
+CREATE TABLE IF NOT EXISTS kiosk_link (
+    token VARCHAR(64) PRIMARY KEY,
+    website_id UUID NOT NULL UNIQUE REFERENCES website(website_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE kiosk_link IS 'Secret tokens for the unauthenticated TV / kiosk view of a website';
@@ -0,0 +1,45 @@
+-- Migration 000036: Outgoing webhooks
+-- A webhook subscribes a URL to events of one website:
+--   goal.completed - a session completed a conversion goal
+--   alert.fired    - a traffic alert rule tripped
+--   summary.weekly - last week's summary is ready (Mondays, UTC)
+-- Triggers write rows to webhook_delivery (an outbox); the webhook dispatcher
+-- POSTs them signed with the webhook's secret and retries failures with
+-- backoff. Deliveries double as the log shown in the dashboard.
+
+CREATE TABLE webhook (
+    webhook_id UUID PRIMARY KEY DEFAULT uuidv7(),
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    url VARCHAR(500) NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    events TEXT[] NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_summary_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_user ON webhook(user_id);
+CREATE INDEX idx_webhook_website ON webhook(website_id) WHERE enabled;
+
+CREATE TABLE webhook_delivery (
+    delivery_id UUID PRIMARY KEY DEFAULT uuidv7(),
+    webhook_id UUID NOT NULL REFERENCES webhook(webhook_id) ON DELETE CASCADE,
+    event VARCHAR(40) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    response_status INT,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webhook_delivery_due ON webhook_delivery(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX idx_webhook_delivery_webhook ON webhook_delivery(webhook_id, created_at DESC);
+
+COMMENT ON TABLE webhook IS 'Per-website webhook subscriptions (goal.completed, alert.fired, summary.weekly)';
+COMMENT ON TABLE webhook_delivery IS 'Outbox and delivery log of webhook payloads; pending rows are retried with backoff';
+COMMENT ON COLUMN webhook.secret IS 'HMAC-SHA256 key for the X-Kaunta-Signature header';
+COMMENT ON COLUMN webhook.last_summary_at IS 'When the last summary.weekly payload was queued';
@@ -0,0 +1,20 @@
+-- Migration 000037: Public read tokens
+-- A public token exposes a chosen subset of a website's headline numbers
+-- (e.g. total pageviews and current visitors) at /api/public/counter/{token}
+-- with open CORS, for counters embedded in public pages. Unlike public stats,
+-- a website can have several tokens, each with its own metrics, and deleting
+-- a token revokes it.
+
+CREATE TABLE public_token (
+    token VARCHAR(64) PRIMARY KEY,
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    name VARCHAR(100),
+    metrics TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_public_token_website ON public_token(website_id);
+
+COMMENT ON TABLE public_token IS 'Tokens for the unauthenticated, CORS-enabled counter endpoint; each exposes only its metrics';
+COMMENT ON COLUMN public_token.metrics IS 'Any of: online, pageviews, visitors, pageviews_today, visitors_today';
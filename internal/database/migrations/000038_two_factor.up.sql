@@ -0,0 +1,36 @@
+-- Migration 000038: Two-factor authentication
+-- Users can enroll a TOTP authenticator app. totp_secret is written when
+-- setup starts and only takes effect once totp_enabled_at is set by a
+-- confirmed code. totp_last_step records the last accepted time step so a
+-- code can't be replayed. require_2fa forces enrollment for one user; the
+-- security.require_2fa setting forces it for everyone.
+-- Recovery codes are single-use and stored as SHA-256 hashes.
+-- A login challenge is issued after a correct password when the user has
+-- 2FA enabled, and is exchanged for a session together with a valid code.
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS totp_secret VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS totp_enabled_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS totp_last_step BIGINT,
+    ADD COLUMN IF NOT EXISTS require_2fa BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE user_recovery_code (
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, code_hash)
+);
+
+CREATE TABLE login_challenge (
+    challenge_hash VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON COLUMN users.totp_enabled_at IS 'When TOTP enrollment was confirmed; NULL while 2FA is off';
+COMMENT ON COLUMN users.require_2fa IS 'User must enroll in 2FA before using the dashboard';
+COMMENT ON TABLE user_recovery_code IS 'Single-use 2FA recovery codes (SHA-256 hashes)';
+COMMENT ON TABLE login_challenge IS 'Pending logins waiting for a second factor';
@@ -0,0 +1,33 @@
+-- Migration 000039: Per-website roles
+-- A website's user_id is its owner. Other users get access through
+-- website_member with one of three roles:
+--   owner  - everything, including members, API keys and share links
+--   editor - settings, domains and goals, but not members or sharing
+--   viewer - read-only stats
+-- Websites without an owner (created from the CLI or the admin API) stay
+-- open to every user, as they were before roles existed.
+
+CREATE TABLE website_member (
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (website_id, user_id)
+);
+
+CREATE INDEX idx_website_member_user ON website_member(user_id);
+
+-- Websites a user can access, with their role on each
+CREATE OR REPLACE FUNCTION user_websites(p_user_id UUID)
+RETURNS TABLE (website_id UUID, role VARCHAR) AS $$
+    SELECT w.website_id,
+           CASE WHEN w.user_id = p_user_id OR w.user_id IS NULL THEN 'owner' ELSE m.role END::VARCHAR
+    FROM website w
+    LEFT JOIN website_member m ON m.website_id = w.website_id AND m.user_id = p_user_id
+    WHERE w.deleted_at IS NULL
+      AND (w.user_id = p_user_id OR w.user_id IS NULL OR m.user_id IS NOT NULL)
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON TABLE website_member IS 'Users other than the owner with access to a website';
+COMMENT ON COLUMN website_member.role IS 'owner, editor or viewer';
+COMMENT ON FUNCTION user_websites(UUID) IS 'Websites a user can access and their role on each';
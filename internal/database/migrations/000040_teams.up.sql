@@ -0,0 +1,65 @@
+-- Migration 000040: Teams
+-- A website can belong to a team. Team members get their team role
+-- (owner, editor or viewer) on every website of the team, on top of any
+-- role they hold on the website itself; the highest role wins.
+-- users.current_team_id is the team picked in the dashboard switcher.
+-- While set, user_websites() only returns that team's websites, which
+-- scopes every list and access check to it. NULL means all teams.
+
+CREATE TABLE team (
+    team_id UUID PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE team_member (
+    team_id UUID NOT NULL REFERENCES team(team_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE INDEX idx_team_member_user ON team_member(user_id);
+
+ALTER TABLE website ADD COLUMN team_id UUID REFERENCES team(team_id) ON DELETE SET NULL;
+CREATE INDEX idx_website_team ON website(team_id) WHERE team_id IS NOT NULL;
+
+ALTER TABLE users ADD COLUMN current_team_id UUID REFERENCES team(team_id) ON DELETE SET NULL;
+
+CREATE OR REPLACE FUNCTION website_role_rank(p_role VARCHAR)
+RETURNS INT AS $$
+    SELECT CASE p_role WHEN 'owner' THEN 3 WHEN 'editor' THEN 2 WHEN 'viewer' THEN 1 ELSE 0 END
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Websites a user can access, with their role on each. Ownerless websites
+-- outside any team stay open to everyone, as before.
+CREATE OR REPLACE FUNCTION user_websites(p_user_id UUID)
+RETURNS TABLE (website_id UUID, role VARCHAR) AS $$
+    SELECT w.website_id,
+           (ARRAY['viewer', 'editor', 'owner'])[MAX(a.rank)]::VARCHAR
+    FROM (
+        SELECT w.website_id, 3 AS rank
+        FROM website w
+        WHERE w.user_id = p_user_id OR (w.user_id IS NULL AND w.team_id IS NULL)
+        UNION ALL
+        SELECT m.website_id, website_role_rank(m.role)
+        FROM website_member m
+        WHERE m.user_id = p_user_id
+        UNION ALL
+        SELECT w.website_id, website_role_rank(t.role)
+        FROM website w
+        JOIN team_member t ON t.team_id = w.team_id
+        WHERE t.user_id = p_user_id
+    ) a
+    JOIN website w ON w.website_id = a.website_id
+    LEFT JOIN users u ON u.user_id = p_user_id
+    WHERE w.deleted_at IS NULL
+      AND (u.current_team_id IS NULL OR w.team_id = u.current_team_id)
+    GROUP BY w.website_id
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON TABLE team IS 'Groups of users sharing a set of websites';
+COMMENT ON TABLE team_member IS 'Users in a team and their role on its websites';
+COMMENT ON COLUMN website.team_id IS 'Team whose members can access the website';
+COMMENT ON COLUMN users.current_team_id IS 'Team selected in the dashboard; NULL shows all teams';
@@ -0,0 +1,29 @@
+-- Migration 000041: Email invitations
+-- An invitation grants a role on one website or one team to whoever opens
+-- its link, either by creating an account or with an existing one. Only a
+-- hash of the token is stored; the link works once and expires.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email VARCHAR(255);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email ON users(LOWER(email)) WHERE email IS NOT NULL;
+
+CREATE TABLE invitation (
+    invitation_id UUID PRIMARY KEY,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    email VARCHAR(255) NOT NULL,
+    website_id UUID REFERENCES website(website_id) ON DELETE CASCADE,
+    team_id UUID REFERENCES team(team_id) ON DELETE CASCADE,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    invited_by UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    accepted_by UUID REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((website_id IS NULL) <> (team_id IS NULL))
+);
+
+CREATE INDEX idx_invitation_website ON invitation(website_id) WHERE website_id IS NOT NULL;
+CREATE INDEX idx_invitation_team ON invitation(team_id) WHERE team_id IS NOT NULL;
+
+COMMENT ON COLUMN users.email IS 'Optional contact address, set when accepting an invitation';
+COMMENT ON TABLE invitation IS 'One-time links granting a role on a website or team';
+COMMENT ON COLUMN invitation.token_hash IS 'SHA-256 of the token in the invitation link';
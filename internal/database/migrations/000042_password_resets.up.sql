@@ -0,0 +1,16 @@
+-- Migration 000042: Password resets
+-- A reset link lets a user choose a new password without signing in. Only
+-- a hash of the token is stored; a link works once, expires after an hour,
+-- and every open link for the user stops working once one is used.
+
+CREATE TABLE password_reset (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_password_reset_user ON password_reset(user_id);
+
+COMMENT ON TABLE password_reset IS 'One-time password reset links';
+COMMENT ON COLUMN password_reset.token_hash IS 'SHA-256 of the token in the reset link';
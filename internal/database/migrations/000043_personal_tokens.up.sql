@@ -0,0 +1,22 @@
+-- Migration 000043: Personal access tokens
+-- A personal token calls the management API as the user who created it,
+-- with their roles, instead of a dashboard session. Read tokens are limited
+-- to GET requests. Only a hash of the token is stored.
+
+CREATE TABLE personal_token (
+    token_id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    token_prefix VARCHAR(20) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    scope VARCHAR(10) NOT NULL CHECK (scope IN ('read', 'write')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_personal_token_user ON personal_token(user_id);
+
+COMMENT ON TABLE personal_token IS 'User-scoped tokens for the management API';
+COMMENT ON COLUMN personal_token.token_prefix IS 'Start of the token, shown to tell tokens apart';
@@ -0,0 +1,11 @@
+-- Migration 000044: Password-protected and expiring kiosk links
+-- A kiosk link can require a password before showing any numbers, and can
+-- stop working at a set time, so a link sent to a client ends with the
+-- engagement. Both are optional and survive rotating the token.
+
+ALTER TABLE kiosk_link
+    ADD COLUMN password_hash TEXT,
+    ADD COLUMN expires_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN kiosk_link.password_hash IS 'hash_password() of the viewer password; NULL for an open link';
+COMMENT ON COLUMN kiosk_link.expires_at IS 'When the link stops working; NULL never expires';
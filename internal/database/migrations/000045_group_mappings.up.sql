@@ -0,0 +1,19 @@
+-- Migration 000045: Directory group mappings
+-- Maps a group name passed on by the sign-in proxy (e.g. Remote-Groups) to
+-- a team and role. Each sign-in gives the user the highest mapped role in
+-- every team one of their groups maps to, and takes them out of teams no
+-- group maps to any more. Only memberships added this way (synced) are
+-- changed; memberships set by hand are left alone.
+
+CREATE TABLE group_mapping (
+    group_name VARCHAR(255) NOT NULL,
+    team_id UUID NOT NULL REFERENCES team(team_id) ON DELETE CASCADE,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_name, team_id)
+);
+
+ALTER TABLE team_member ADD COLUMN synced BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON TABLE group_mapping IS 'Directory groups whose members join a team with a role on sign-in';
+COMMENT ON COLUMN team_member.synced IS 'Set when the membership comes from a group mapping; group sync only changes these';
@@ -0,0 +1,16 @@
+-- Migration 000046: Email address changes
+-- A signed-in user changing their address gets a confirmation link at the
+-- new one; the address only changes once the link is opened, so nobody can
+-- point password resets at an inbox they don't control. Only a hash of the
+-- token is stored, and a newer request replaces any pending one.
+
+CREATE TABLE email_change (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL UNIQUE REFERENCES users(user_id) ON DELETE CASCADE,
+    new_email VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE email_change IS 'Pending email address changes awaiting confirmation';
+COMMENT ON COLUMN email_change.token_hash IS 'SHA-256 of the token in the confirmation link';
@@ -0,0 +1,33 @@
+-- Migration 000047: Partition maintenance
+-- website_event, bot_detection_log and event_idempotency are partitioned by
+-- day, but only the partitions created at install time existed, so inserts
+-- started failing a month later. ensure_daily_partitions creates whichever
+-- partitions are missing in a window around today; the server's partition
+-- scheduler calls it daily and drops partitions past their retention.
+
+CREATE OR REPLACE FUNCTION ensure_daily_partitions(parent TEXT, days_back INTEGER, days_ahead INTEGER)
+RETURNS INTEGER AS $$
+DECLARE
+    day DATE;
+    part_name TEXT;
+    created INTEGER := 0;
+BEGIN
+    FOR i IN -days_back..days_ahead LOOP
+        day := CURRENT_DATE + i;
+        part_name := parent || '_' || TO_CHAR(day, 'YYYY_MM_DD');
+        IF to_regclass(format('public.%I', part_name)) IS NULL THEN
+            EXECUTE format('CREATE TABLE %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+                part_name, parent, day, day + 1);
+            created := created + 1;
+        END IF;
+    END LOOP;
+    RETURN created;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION ensure_daily_partitions IS 'Creates the missing daily partitions of parent from days_back days ago to days_ahead days ahead. Called by the partition scheduler.';
+
+-- Catch up installs that already ran past their initial partitions
+SELECT ensure_daily_partitions('website_event', 7, 30);
+SELECT ensure_daily_partitions('bot_detection_log', 1, 7);
+SELECT ensure_daily_partitions('event_idempotency', 1, 7);
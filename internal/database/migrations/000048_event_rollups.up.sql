@@ -0,0 +1,366 @@
+-- Migration 000048: Continuous aggregation rollups
+-- Long dashboard ranges re-count every raw event on each request. event_rollup
+-- keeps hourly and daily counts per page, referrer, country, campaign and
+-- custom event, filled in incrementally by refresh_event_rollups() (run every
+-- few minutes by the server). get_rollup_totals() stitches daily rollups,
+-- hourly rollups and the raw events newer than the last refresh together, so
+-- results stay current.
+--
+-- get_breakdown() and get_top_pages() read rollups for unfiltered ranges of 7
+-- days or more and raw events otherwise. Hit counts and engagement time are
+-- exact; unique visitors are summed per day, so a visitor returning on several
+-- days of the range is counted once per day.
+
+-- ============================================================================
+-- 1. Rollup tables
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS event_rollup (
+    website_id UUID NOT NULL,
+    granularity VARCHAR(4) NOT NULL CHECK (granularity IN ('hour', 'day')),
+    bucket TIMESTAMPTZ NOT NULL,
+    dimension VARCHAR(16) NOT NULL,
+    value TEXT NOT NULL,
+    hits BIGINT NOT NULL,
+    visitors BIGINT NOT NULL,
+    engagement_time BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (website_id, dimension, granularity, bucket, value)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_rollup_bucket ON event_rollup (granularity, bucket);
+
+COMMENT ON TABLE event_rollup IS 'Hourly and daily event counts per dimension value, maintained by refresh_event_rollups()';
+COMMENT ON COLUMN event_rollup.dimension IS 'page, referrer, country, utm_campaign or event';
+COMMENT ON COLUMN event_rollup.hits IS 'Pageviews, or occurrences for the event dimension';
+COMMENT ON COLUMN event_rollup.visitors IS 'Distinct sessions within the bucket';
+
+-- How far each granularity has been rolled up (exclusive)
+CREATE TABLE IF NOT EXISTS rollup_state (
+    granularity VARCHAR(4) PRIMARY KEY,
+    rolled_until TIMESTAMPTZ NOT NULL
+);
+
+-- ============================================================================
+-- 2. Aggregation shared by the refresh and the raw tail of queries
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION aggregate_event_dimensions(
+    p_from TIMESTAMPTZ,
+    p_until TIMESTAMPTZ,
+    p_website_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    website_id UUID,
+    dimension VARCHAR,
+    value TEXT,
+    hits BIGINT,
+    visitors BIGINT,
+    engagement_time BIGINT
+) AS $$
+    WITH events AS (
+        SELECT e.website_id, e.session_id, e.event_type, e.event_name, e.url_path,
+               e.referrer_domain, e.referrer_path, e.utm_campaign, e.engagement_time, s.country
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.created_at >= p_from
+          AND e.created_at < p_until
+          AND e.event_type IN (1, 2)
+          AND (p_website_id IS NULL OR e.website_id = p_website_id)
+    ),
+    dims AS (
+        SELECT ev.website_id, ev.session_id, COALESCE(ev.engagement_time, 0) AS engagement_time, d.dimension, d.value
+        FROM events ev
+        CROSS JOIN LATERAL (VALUES
+            ('page', COALESCE(ev.url_path, 'Unknown')),
+            ('referrer', CASE
+                WHEN ev.referrer_domain IS NOT NULL THEN ev.referrer_domain || COALESCE(ev.referrer_path, '')
+                ELSE 'Direct / None'
+            END),
+            ('country', COALESCE(ev.country, 'Unknown')),
+            ('utm_campaign', COALESCE(ev.utm_campaign, 'Direct / None'))
+        ) AS d(dimension, value)
+        WHERE ev.event_type = 1
+        UNION ALL
+        SELECT ev.website_id, ev.session_id, 0, 'event', ev.event_name
+        FROM events ev
+        WHERE ev.event_type = 2 AND ev.event_name IS NOT NULL
+    )
+    SELECT
+        dims.website_id,
+        dims.dimension::VARCHAR,
+        dims.value::TEXT,
+        COUNT(*)::BIGINT,
+        COUNT(DISTINCT dims.session_id)::BIGINT,
+        SUM(dims.engagement_time)::BIGINT
+    FROM dims
+    GROUP BY dims.website_id, dims.dimension, dims.value;
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON FUNCTION aggregate_event_dimensions IS 'Counts raw events per rollup dimension value in [p_from, p_until)';
+
+-- ============================================================================
+-- 3. Incremental refresh
+-- ============================================================================
+
+-- Rolls up completed buckets since the last run and returns the rows written.
+-- Buckets are only rolled once they are a few minutes old so in-flight
+-- inserts land first. A first run backfills history p_max_days at a time;
+-- hourly rollups only cover the last 7 days since daily ones serve anything
+-- older.
+CREATE OR REPLACE FUNCTION refresh_event_rollups(p_max_days INTEGER DEFAULT 31)
+RETURNS INTEGER AS $$
+DECLARE
+    v_unit TEXT;
+    v_from TIMESTAMPTZ;
+    v_until TIMESTAMPTZ;
+    v_rows INTEGER := 0;
+    v_count INTEGER;
+BEGIN
+    FOREACH v_unit IN ARRAY ARRAY['hour', 'day'] LOOP
+        v_until := CASE v_unit
+            WHEN 'hour' THEN DATE_TRUNC('hour', NOW() - INTERVAL '5 minutes')
+            ELSE DATE_TRUNC('day', NOW() - INTERVAL '1 hour')
+        END;
+
+        SELECT rolled_until INTO v_from FROM rollup_state WHERE granularity = v_unit;
+        IF v_from IS NULL THEN
+            SELECT DATE_TRUNC(v_unit, MIN(created_at)) INTO v_from FROM website_event;
+            IF v_unit = 'hour' THEN
+                v_from := GREATEST(v_from, DATE_TRUNC('hour', NOW() - INTERVAL '7 days'));
+            END IF;
+            v_from := COALESCE(v_from, v_until);
+        END IF;
+
+        v_until := LEAST(v_until, v_from + (p_max_days || ' days')::INTERVAL);
+        CONTINUE WHEN v_from >= v_until;
+
+        DELETE FROM event_rollup
+        WHERE granularity = v_unit AND bucket >= v_from AND bucket < v_until;
+
+        INSERT INTO event_rollup (website_id, granularity, bucket, dimension, value, hits, visitors, engagement_time)
+        SELECT a.website_id, v_unit, b.bucket, a.dimension, a.value, a.hits, a.visitors, a.engagement_time
+        FROM generate_series(v_from, v_until - ('1 ' || v_unit)::INTERVAL, ('1 ' || v_unit)::INTERVAL) AS b(bucket)
+        CROSS JOIN LATERAL aggregate_event_dimensions(b.bucket, b.bucket + ('1 ' || v_unit)::INTERVAL) a;
+        GET DIAGNOSTICS v_count = ROW_COUNT;
+        v_rows := v_rows + v_count;
+
+        INSERT INTO rollup_state (granularity, rolled_until) VALUES (v_unit, v_until)
+        ON CONFLICT (granularity) DO UPDATE SET rolled_until = EXCLUDED.rolled_until;
+    END LOOP;
+
+    DELETE FROM event_rollup WHERE granularity = 'hour' AND bucket < NOW() - INTERVAL '8 days';
+
+    RETURN v_rows;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION refresh_event_rollups IS 'Rolls completed hours and days into event_rollup; returns rows written';
+
+-- ============================================================================
+-- 4. Reading rollups
+-- ============================================================================
+
+-- Totals per value of one dimension since p_since: a partial first day and
+-- everything after the last refresh come from raw events, full days from
+-- daily rollups and the hours in between from hourly rollups. Until the
+-- rollups have caught up the whole range is read from raw events.
+CREATE OR REPLACE FUNCTION get_rollup_totals(
+    p_website_id UUID,
+    p_dimension VARCHAR,
+    p_since TIMESTAMPTZ
+)
+RETURNS TABLE (
+    value TEXT,
+    hits BIGINT,
+    visitors BIGINT,
+    engagement_time BIGINT
+) AS $$
+DECLARE
+    v_day_start TIMESTAMPTZ;
+    v_day_until TIMESTAMPTZ;
+    v_hour_until TIMESTAMPTZ;
+BEGIN
+    SELECT rolled_until INTO v_day_until FROM rollup_state WHERE granularity = 'day';
+    SELECT rolled_until INTO v_hour_until FROM rollup_state WHERE granularity = 'hour';
+
+    v_day_start := DATE_TRUNC('day', p_since);
+    IF v_day_start < p_since THEN
+        v_day_start := v_day_start + INTERVAL '1 day';
+    END IF;
+
+    IF v_day_until IS NULL OR v_hour_until IS NULL
+       OR v_day_until < NOW() - INTERVAL '2 days' OR v_hour_until < v_day_until THEN
+        v_day_start := p_since;
+        v_day_until := p_since;
+        v_hour_until := p_since;
+    END IF;
+    v_day_until := GREATEST(v_day_until, v_day_start);
+    v_hour_until := GREATEST(v_hour_until, v_day_until);
+
+    RETURN QUERY
+    WITH parts AS (
+        SELECT a.value, a.hits, a.visitors, a.engagement_time
+        FROM aggregate_event_dimensions(p_since, v_day_start, p_website_id) a
+        WHERE a.dimension = p_dimension AND v_day_start > p_since
+        UNION ALL
+        SELECT r.value, r.hits, r.visitors, r.engagement_time
+        FROM event_rollup r
+        WHERE r.website_id = p_website_id
+          AND r.dimension = p_dimension
+          AND r.granularity = 'day'
+          AND r.bucket >= v_day_start
+          AND r.bucket < v_day_until
+        UNION ALL
+        SELECT r.value, r.hits, r.visitors, r.engagement_time
+        FROM event_rollup r
+        WHERE r.website_id = p_website_id
+          AND r.dimension = p_dimension
+          AND r.granularity = 'hour'
+          AND r.bucket >= v_day_until
+          AND r.bucket < v_hour_until
+        UNION ALL
+        SELECT a.value, a.hits, a.visitors, a.engagement_time
+        FROM aggregate_event_dimensions(v_hour_until, 'infinity', p_website_id) a
+        WHERE a.dimension = p_dimension
+    )
+    SELECT parts.value, SUM(parts.hits)::BIGINT, SUM(parts.visitors)::BIGINT, SUM(parts.engagement_time)::BIGINT
+    FROM parts
+    GROUP BY parts.value;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_rollup_totals IS 'Per-value totals of a rollup dimension since p_since, from rollups plus raw events after the last refresh';
+
+-- ============================================================================
+-- 5. get_breakdown() and get_top_pages() read rollups for long ranges
+-- ============================================================================
+
+ALTER FUNCTION get_breakdown(UUID, VARCHAR, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR)
+    RENAME TO get_raw_breakdown;
+
+CREATE OR REPLACE FUNCTION get_breakdown(
+    p_website_id UUID,
+    p_dimension VARCHAR,
+    p_days INTEGER DEFAULT 1,
+    p_limit INTEGER DEFAULT 10,
+    p_offset INTEGER DEFAULT 0,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL,
+    p_sort_by VARCHAR DEFAULT 'count',
+    p_sort_order VARCHAR DEFAULT 'desc'
+)
+RETURNS TABLE (name VARCHAR, count BIGINT, total_count BIGINT) AS $$
+BEGIN
+    IF p_days < 7 OR p_dimension NOT IN ('page', 'referrer', 'country', 'utm_campaign')
+       OR p_country IS NOT NULL OR p_browser IS NOT NULL OR p_device IS NOT NULL OR p_page_path IS NOT NULL THEN
+        RETURN QUERY
+        SELECT * FROM get_raw_breakdown(
+            p_website_id, p_dimension, p_days, p_limit, p_offset,
+            p_country, p_browser, p_device, p_page_path, p_sort_by, p_sort_order
+        );
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    WITH breakdown_data AS (
+        SELECT t.value::VARCHAR as dim_name, t.hits as dim_count
+        FROM get_rollup_totals(p_website_id, p_dimension, CURRENT_DATE - (p_days || ' days')::INTERVAL) t
+    ),
+    total_count_cte AS (
+        SELECT COUNT(*)::BIGINT as total FROM breakdown_data
+    )
+    SELECT bd.dim_name, bd.dim_count, tc.total
+    FROM breakdown_data bd
+    CROSS JOIN total_count_cte tc
+    ORDER BY
+        CASE WHEN p_sort_by = 'count' AND p_sort_order = 'desc' THEN bd.dim_count END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'count' AND p_sort_order = 'asc' THEN bd.dim_count END ASC NULLS LAST,
+        CASE WHEN p_sort_by = 'name' AND p_sort_order = 'desc' THEN bd.dim_name END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'name' AND p_sort_order = 'asc' THEN bd.dim_name END ASC NULLS LAST
+    LIMIT p_limit
+    OFFSET p_offset;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+ALTER FUNCTION get_top_pages(UUID, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR)
+    RENAME TO get_raw_top_pages;
+
+CREATE OR REPLACE FUNCTION get_top_pages(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 1,
+    p_limit INTEGER DEFAULT 10,
+    p_offset INTEGER DEFAULT 0,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_sort_by VARCHAR DEFAULT 'views',
+    p_sort_order VARCHAR DEFAULT 'desc',
+    p_path_pattern VARCHAR DEFAULT NULL
+)
+RETURNS TABLE (
+    path VARCHAR,
+    views BIGINT,
+    unique_visitors BIGINT,
+    avg_engagement_time NUMERIC,
+    total_count BIGINT
+) AS $$
+BEGIN
+    IF p_days < 7 OR p_country IS NOT NULL OR p_browser IS NOT NULL OR p_device IS NOT NULL THEN
+        RETURN QUERY
+        SELECT * FROM get_raw_top_pages(
+            p_website_id, p_days, p_limit, p_offset,
+            p_country, p_browser, p_device, p_sort_by, p_sort_order, p_path_pattern
+        );
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    WITH page_stats AS (
+        SELECT
+            t.value as url_path,
+            t.hits as view_count,
+            t.visitors as unique_visitor_count,
+            ROUND(t.engagement_time::NUMERIC / NULLIF(t.hits, 0), 0) as avg_time
+        FROM get_rollup_totals(p_website_id, 'page', CURRENT_DATE - (p_days || ' days')::INTERVAL) t
+        WHERE t.value <> 'Unknown'
+          AND (p_path_pattern IS NULL OR t.value ILIKE p_path_pattern)
+    ),
+    total_count_cte AS (
+        SELECT COUNT(*)::BIGINT as total FROM page_stats
+    )
+    SELECT
+        ps.url_path::VARCHAR,
+        ps.view_count,
+        ps.unique_visitor_count,
+        ps.avg_time,
+        tc.total as total_count
+    FROM page_stats ps
+    CROSS JOIN total_count_cte tc
+    ORDER BY
+        CASE WHEN p_sort_order = 'desc' THEN
+            CASE p_sort_by
+                WHEN 'views' THEN ps.view_count
+                WHEN 'unique_visitors' THEN ps.unique_visitor_count
+                WHEN 'avg_engagement_time' THEN ps.avg_time::BIGINT
+                ELSE ps.view_count
+            END
+        END DESC NULLS LAST,
+        CASE WHEN p_sort_order = 'asc' THEN
+            CASE p_sort_by
+                WHEN 'views' THEN ps.view_count
+                WHEN 'unique_visitors' THEN ps.unique_visitor_count
+                WHEN 'avg_engagement_time' THEN ps.avg_time::BIGINT
+                ELSE ps.view_count
+            END
+        END ASC NULLS LAST,
+        CASE WHEN p_sort_by = 'path' AND p_sort_order = 'desc' THEN ps.url_path END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'path' AND p_sort_order = 'asc' THEN ps.url_path END ASC NULLS LAST
+    LIMIT p_limit
+    OFFSET p_offset;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_top_pages(UUID, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR, VARCHAR)
+IS 'Top pages with pagination, sorting and optional ILIKE path pattern; ranges of 7+ days without filters read event_rollup';
@@ -0,0 +1,28 @@
+-- Migration 000049: Per-website retention and the audit log
+-- Websites can keep raw events (and the sessions only they reference) for
+-- fewer days than the server-wide retention, and drop rollups after a while
+-- instead of keeping them forever. NULL leaves the server default in place.
+-- Every purge is recorded in audit_log.
+
+ALTER TABLE website ADD COLUMN IF NOT EXISTS event_retention_days INTEGER
+    CHECK (event_retention_days > 0);
+ALTER TABLE website ADD COLUMN IF NOT EXISTS rollup_retention_days INTEGER
+    CHECK (rollup_retention_days > 0);
+
+COMMENT ON COLUMN website.event_retention_days IS 'Days of raw events kept for this website; NULL follows retention_days';
+COMMENT ON COLUMN website.rollup_retention_days IS 'Days of event_rollup rows kept for this website; NULL keeps them forever';
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    audit_id BIGSERIAL PRIMARY KEY,
+    action VARCHAR(64) NOT NULL,
+    -- Entries outlive the website; details keep its domain
+    website_id UUID REFERENCES website(website_id) ON DELETE SET NULL,
+    actor VARCHAR(255) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_website ON audit_log (website_id, created_at DESC);
+
+COMMENT ON TABLE audit_log IS 'Record of destructive maintenance such as retention purges';
@@ -0,0 +1,383 @@
+-- Migration 000050: HyperLogLog sketches for unique visitors
+-- Unique visitors over a long range need COUNT(DISTINCT session_id) over
+-- every raw event in it, and event_rollup can only add up daily counts,
+-- counting a visitor once per day. visitor_sketch keeps a HyperLogLog sketch
+-- of the sessions per day, for the whole website and per rollup dimension
+-- value, filled in by refresh_visitor_sketches() alongside the rollups.
+-- Merging the sketches of a range takes the highest rank per register, so a
+-- range of any length costs at most 1024 rows per value and day, and the
+-- estimate stays within a few percent of the exact count.
+--
+-- A sketch is stored sparsely: one row per register that has been set. With
+-- 1024 registers the typical error is about 3%.
+
+-- ============================================================================
+-- 1. Sketch storage
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS visitor_sketch (
+    website_id UUID NOT NULL,
+    bucket TIMESTAMPTZ NOT NULL,
+    dimension VARCHAR(16) NOT NULL,
+    value TEXT NOT NULL,
+    register SMALLINT NOT NULL,
+    rank SMALLINT NOT NULL,
+    PRIMARY KEY (website_id, dimension, bucket, value, register)
+);
+
+CREATE INDEX IF NOT EXISTS idx_visitor_sketch_bucket ON visitor_sketch (bucket);
+
+COMMENT ON TABLE visitor_sketch IS 'Daily HyperLogLog sketches of sessions per dimension value, maintained by refresh_visitor_sketches()';
+COMMENT ON COLUMN visitor_sketch.dimension IS 'site (value is empty), page, referrer, country, utm_campaign or event';
+COMMENT ON COLUMN visitor_sketch.rank IS 'Highest rank seen in this register; unset registers have no row';
+
+-- ============================================================================
+-- 2. HyperLogLog helpers
+-- ============================================================================
+
+-- Register (top 10 bits) and rank (position of the first set bit in the
+-- remaining 54) of a session's 64-bit hash
+CREATE OR REPLACE FUNCTION hll_hash(p_session_id UUID, OUT register SMALLINT, OUT rank SMALLINT) AS $$
+    SELECT ((h >> 54) & 1023)::SMALLINT,
+           COALESCE(NULLIF(POSITION('1' IN h::BIT(54)::TEXT), 0), 55)::SMALLINT
+    FROM (SELECT ('x' || SUBSTR(MD5(p_session_id::TEXT), 1, 16))::BIT(64)::BIGINT AS h) hashed;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Cardinality estimate from the number of set registers and the sum of
+-- 2^-rank over them, with linear counting for small cardinalities
+CREATE OR REPLACE FUNCTION hll_estimate(p_registers BIGINT, p_inverse_sum DOUBLE PRECISION)
+RETURNS BIGINT AS $$
+    SELECT CASE
+        WHEN p_registers = 0 THEN 0
+        WHEN raw.estimate <= 2.5 * 1024 AND p_registers < 1024 THEN ROUND(1024 * LN(1024.0 / (1024 - p_registers)))
+        ELSE ROUND(raw.estimate)
+    END::BIGINT
+    FROM (
+        SELECT (0.7213 / (1 + 1.079 / 1024)) * 1024 * 1024 / (p_inverse_sum + (1024 - p_registers)) AS estimate
+    ) raw;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Sessions behind each rollup dimension value in [p_from, p_until), plus the
+-- 'site' dimension for every pageview
+CREATE OR REPLACE FUNCTION event_dimension_sessions(
+    p_from TIMESTAMPTZ,
+    p_until TIMESTAMPTZ,
+    p_website_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    website_id UUID,
+    created_at TIMESTAMPTZ,
+    session_id UUID,
+    dimension VARCHAR,
+    value TEXT
+) AS $$
+    WITH events AS (
+        SELECT e.website_id, e.created_at, e.session_id, e.event_type, e.event_name, e.url_path,
+               e.referrer_domain, e.referrer_path, e.utm_campaign, s.country
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.created_at >= p_from
+          AND e.created_at < p_until
+          AND e.event_type IN (1, 2)
+          AND (p_website_id IS NULL OR e.website_id = p_website_id)
+    )
+    SELECT ev.website_id, ev.created_at, ev.session_id, d.dimension::VARCHAR, d.value::TEXT
+    FROM events ev
+    CROSS JOIN LATERAL (VALUES
+        ('site', ''),
+        ('page', COALESCE(ev.url_path, 'Unknown')),
+        ('referrer', CASE
+            WHEN ev.referrer_domain IS NOT NULL THEN ev.referrer_domain || COALESCE(ev.referrer_path, '')
+            ELSE 'Direct / None'
+        END),
+        ('country', COALESCE(ev.country, 'Unknown')),
+        ('utm_campaign', COALESCE(ev.utm_campaign, 'Direct / None'))
+    ) AS d(dimension, value)
+    WHERE ev.event_type = 1
+    UNION ALL
+    SELECT ev.website_id, ev.created_at, ev.session_id, 'event', ev.event_name
+    FROM events ev
+    WHERE ev.event_type = 2 AND ev.event_name IS NOT NULL;
+$$ LANGUAGE sql STABLE;
+
+-- ============================================================================
+-- 3. Incremental refresh
+-- ============================================================================
+
+-- Sketches completed days since the last run and returns the rows written.
+-- Progress is kept in rollup_state under 'hll'. A first run backfills
+-- history p_max_days at a time.
+CREATE OR REPLACE FUNCTION refresh_visitor_sketches(p_max_days INTEGER DEFAULT 31)
+RETURNS INTEGER AS $$
+DECLARE
+    v_from TIMESTAMPTZ;
+    v_until TIMESTAMPTZ := DATE_TRUNC('day', NOW() - INTERVAL '1 hour');
+    v_rows INTEGER;
+BEGIN
+    SELECT rolled_until INTO v_from FROM rollup_state WHERE granularity = 'hll';
+    IF v_from IS NULL THEN
+        SELECT DATE_TRUNC('day', MIN(created_at)) INTO v_from FROM website_event;
+        v_from := COALESCE(v_from, v_until);
+    END IF;
+
+    v_until := LEAST(v_until, v_from + (p_max_days || ' days')::INTERVAL);
+    IF v_from >= v_until THEN
+        RETURN 0;
+    END IF;
+
+    DELETE FROM visitor_sketch WHERE bucket >= v_from AND bucket < v_until;
+
+    INSERT INTO visitor_sketch (website_id, bucket, dimension, value, register, rank)
+    SELECT d.website_id, DATE_TRUNC('day', d.created_at), d.dimension, d.value, h.register, MAX(h.rank)
+    FROM event_dimension_sessions(v_from, v_until) d
+    CROSS JOIN LATERAL hll_hash(d.session_id) h
+    GROUP BY 1, 2, 3, 4, 5;
+    GET DIAGNOSTICS v_rows = ROW_COUNT;
+
+    INSERT INTO rollup_state (granularity, rolled_until) VALUES ('hll', v_until)
+    ON CONFLICT (granularity) DO UPDATE SET rolled_until = EXCLUDED.rolled_until;
+
+    RETURN v_rows;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION refresh_visitor_sketches IS 'Sketches the sessions of completed days into visitor_sketch; returns rows written';
+
+-- ============================================================================
+-- 4. Reading sketches
+-- ============================================================================
+
+-- Estimated unique visitors per value of one dimension since p_since: full
+-- days merge their sketches, the partial first day and everything after the
+-- last refresh are hashed from raw events. Until the sketches have caught up
+-- the whole range is hashed from raw events.
+CREATE OR REPLACE FUNCTION get_unique_visitors(
+    p_website_id UUID,
+    p_dimension VARCHAR,
+    p_since TIMESTAMPTZ
+)
+RETURNS TABLE (value TEXT, visitors BIGINT) AS $$
+DECLARE
+    v_day_start TIMESTAMPTZ;
+    v_day_until TIMESTAMPTZ;
+BEGIN
+    SELECT rolled_until INTO v_day_until FROM rollup_state WHERE granularity = 'hll';
+
+    v_day_start := DATE_TRUNC('day', p_since);
+    IF v_day_start < p_since THEN
+        v_day_start := v_day_start + INTERVAL '1 day';
+    END IF;
+
+    IF v_day_until IS NULL OR v_day_until < NOW() - INTERVAL '2 days' THEN
+        v_day_start := p_since;
+        v_day_until := p_since;
+    END IF;
+    v_day_until := GREATEST(v_day_until, v_day_start);
+
+    RETURN QUERY
+    WITH registers AS (
+        SELECT h.value, h.register, h.rank
+        FROM (
+            SELECT d.value, (hll_hash(d.session_id)).*
+            FROM event_dimension_sessions(p_since, v_day_start, p_website_id) d
+            WHERE d.dimension = p_dimension AND v_day_start > p_since
+            UNION ALL
+            SELECT d.value, (hll_hash(d.session_id)).*
+            FROM event_dimension_sessions(v_day_until, 'infinity', p_website_id) d
+            WHERE d.dimension = p_dimension
+        ) h
+        UNION ALL
+        SELECT vs.value, vs.register, vs.rank
+        FROM visitor_sketch vs
+        WHERE vs.website_id = p_website_id
+          AND vs.dimension = p_dimension
+          AND vs.bucket >= v_day_start
+          AND vs.bucket < v_day_until
+    ),
+    merged AS (
+        SELECT registers.value, registers.register, MAX(registers.rank) AS rank
+        FROM registers
+        GROUP BY registers.value, registers.register
+    )
+    SELECT merged.value, hll_estimate(COUNT(*), SUM(POWER(2, -merged.rank::DOUBLE PRECISION)))
+    FROM merged
+    GROUP BY merged.value;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_unique_visitors IS 'HyperLogLog estimate of unique visitors per dimension value since p_since';
+
+-- ============================================================================
+-- 5. Long ranges estimate unique visitors from sketches
+-- ============================================================================
+
+-- Same as 000048, with visitors per value from get_unique_visitors() instead
+-- of daily counts added up
+CREATE OR REPLACE FUNCTION get_rollup_totals(
+    p_website_id UUID,
+    p_dimension VARCHAR,
+    p_since TIMESTAMPTZ
+)
+RETURNS TABLE (
+    value TEXT,
+    hits BIGINT,
+    visitors BIGINT,
+    engagement_time BIGINT
+) AS $$
+DECLARE
+    v_day_start TIMESTAMPTZ;
+    v_day_until TIMESTAMPTZ;
+    v_hour_until TIMESTAMPTZ;
+BEGIN
+    SELECT rolled_until INTO v_day_until FROM rollup_state WHERE granularity = 'day';
+    SELECT rolled_until INTO v_hour_until FROM rollup_state WHERE granularity = 'hour';
+
+    v_day_start := DATE_TRUNC('day', p_since);
+    IF v_day_start < p_since THEN
+        v_day_start := v_day_start + INTERVAL '1 day';
+    END IF;
+
+    IF v_day_until IS NULL OR v_hour_until IS NULL
+       OR v_day_until < NOW() - INTERVAL '2 days' OR v_hour_until < v_day_until THEN
+        v_day_start := p_since;
+        v_day_until := p_since;
+        v_hour_until := p_since;
+    END IF;
+    v_day_until := GREATEST(v_day_until, v_day_start);
+    v_hour_until := GREATEST(v_hour_until, v_day_until);
+
+    RETURN QUERY
+    WITH parts AS (
+        SELECT a.value, a.hits, a.engagement_time
+        FROM aggregate_event_dimensions(p_since, v_day_start, p_website_id) a
+        WHERE a.dimension = p_dimension AND v_day_start > p_since
+        UNION ALL
+        SELECT r.value, r.hits, r.engagement_time
+        FROM event_rollup r
+        WHERE r.website_id = p_website_id
+          AND r.dimension = p_dimension
+          AND r.granularity = 'day'
+          AND r.bucket >= v_day_start
+          AND r.bucket < v_day_until
+        UNION ALL
+        SELECT r.value, r.hits, r.engagement_time
+        FROM event_rollup r
+        WHERE r.website_id = p_website_id
+          AND r.dimension = p_dimension
+          AND r.granularity = 'hour'
+          AND r.bucket >= v_day_until
+          AND r.bucket < v_hour_until
+        UNION ALL
+        SELECT a.value, a.hits, a.engagement_time
+        FROM aggregate_event_dimensions(v_hour_until, 'infinity', p_website_id) a
+        WHERE a.dimension = p_dimension
+    ),
+    totals AS (
+        SELECT parts.value, SUM(parts.hits)::BIGINT AS hits, SUM(parts.engagement_time)::BIGINT AS engagement_time
+        FROM parts
+        GROUP BY parts.value
+    )
+    SELECT totals.value, totals.hits, COALESCE(u.visitors, 0), totals.engagement_time
+    FROM totals
+    LEFT JOIN get_unique_visitors(p_website_id, p_dimension, p_since) u ON u.value = totals.value;
+END;
+$$ LANGUAGE plpgsql STABLE;
+COMMENT ON FUNCTION get_rollup_totals IS 'Per-value totals of a rollup dimension since p_since, from rollups plus raw events after the last refresh, with sketched unique visitors';
+
+-- Same as 000032, except that unfiltered ranges of 7 days or more estimate
+-- unique visitors from sketches
+CREATE OR REPLACE FUNCTION get_dashboard_stats(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 1,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL
+)
+RETURNS TABLE (
+    current_visitors BIGINT,
+    today_pageviews BIGINT,
+    today_visitors BIGINT,
+    bounce_rate NUMERIC(5,2),
+    avg_visit_duration NUMERIC(10,1)
+) AS $$
+DECLARE
+    v_start TIMESTAMPTZ := CURRENT_DATE - (GREATEST(p_days, 1) - 1);
+    v_sketched BOOLEAN := p_days >= 7 AND p_country IS NULL AND p_browser IS NULL
+                          AND p_device IS NULL AND p_page_path IS NULL;
+    v_current_visitors BIGINT;
+    v_pageviews BIGINT;
+    v_visitors BIGINT;
+    v_visits BIGINT;
+    v_bounces BIGINT;
+    v_avg_duration NUMERIC;
+BEGIN
+    -- 1. Current visitors (sessions in last 5 minutes)
+    SELECT COUNT(DISTINCT e.session_id) INTO v_current_visitors
+    FROM website_event e
+    JOIN session s ON e.session_id = s.session_id
+    WHERE e.website_id = p_website_id
+      AND e.created_at >= NOW() - INTERVAL '5 minutes'
+      AND e.event_type = 1
+      AND (p_country IS NULL OR s.country = p_country)
+      AND (p_browser IS NULL OR s.browser = p_browser)
+      AND (p_device IS NULL OR s.device = p_device)
+      AND (p_page_path IS NULL OR e.url_path = p_page_path);
+
+    -- 2. Pageviews and unique visitors for the period
+    IF v_sketched THEN
+        SELECT COUNT(*) INTO v_pageviews
+        FROM website_event e
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND e.event_type = 1;
+
+        SELECT COALESCE(SUM(u.visitors), 0) INTO v_visitors
+        FROM get_unique_visitors(p_website_id, 'site', v_start) u;
+    ELSE
+        SELECT COUNT(*), COUNT(DISTINCT e.session_id) INTO v_pageviews, v_visitors
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND e.event_type = 1
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path);
+    END IF;
+
+    -- 3. Per-visit bounce and duration, preferring engaged time over event gaps
+    WITH visits AS (
+        SELECT
+            e.visit_id,
+            COUNT(*) FILTER (WHERE e.event_type = 1) AS pageviews,
+            EXTRACT(EPOCH FROM MAX(e.created_at) - MIN(e.created_at)) AS gap_seconds
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path)
+        GROUP BY e.visit_id
+        HAVING COUNT(*) FILTER (WHERE e.event_type = 1) > 0
+    )
+    SELECT
+        COUNT(*),
+        COUNT(*) FILTER (WHERE v.pageviews = 1 AND COALESCE(ve.engaged_ms, 0) < 10000),
+        AVG(COALESCE(ve.engaged_ms / 1000.0, v.gap_seconds))
+    INTO v_visits, v_bounces, v_avg_duration
+    FROM visits v
+    LEFT JOIN visit_engagement ve ON ve.visit_id = v.visit_id;
+
+    RETURN QUERY SELECT
+        v_current_visitors,
+        v_pageviews,
+        v_visitors,
+        CASE WHEN v_visits > 0 THEN ROUND(v_bounces::NUMERIC / v_visits::NUMERIC * 100, 2) ELSE 0 END::NUMERIC(5,2),
+        ROUND(COALESCE(v_avg_duration, 0), 1)::NUMERIC(10,1);
+END;
+$$ LANGUAGE plpgsql STABLE;
+COMMENT ON FUNCTION get_dashboard_stats IS 'Headline metrics: current visitors, pageviews, visitors (sketched for unfiltered ranges of 7+ days), engagement-aware bounce rate and average visit duration (seconds)';
@@ -0,0 +1,486 @@
+-- Migration 000051: Compact expired raw events into daily summaries
+-- Dropping raw events at the retention horizon used to take every report
+-- that reads them along. Before the server drops a day of raw events,
+-- compact_events() makes sure the day is covered by event_rollup and
+-- visitor_sketch and writes its headline numbers (pageviews, visits, bounces,
+-- visit duration) to daily_summary. get_dashboard_stats(), get_timeseries()
+-- and the rollup-backed breakdowns then answer for compacted days from those
+-- summaries, so unfiltered reports keep working forever at a fraction of the
+-- storage. Filtered reports need raw events and only cover the days kept.
+--
+-- Browsers, operating systems and devices join the rollup dimensions so
+-- their breakdowns survive compaction too. Rollups and sketches are rebuilt
+-- from the raw events still stored to add them.
+
+-- ============================================================================
+-- 1. Browser, OS and device rollups
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION aggregate_event_dimensions(
+    p_from TIMESTAMPTZ,
+    p_until TIMESTAMPTZ,
+    p_website_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    website_id UUID,
+    dimension VARCHAR,
+    value TEXT,
+    hits BIGINT,
+    visitors BIGINT,
+    engagement_time BIGINT
+) AS $$
+    WITH events AS (
+        SELECT e.website_id, e.session_id, e.event_type, e.event_name, e.url_path,
+               e.referrer_domain, e.referrer_path, e.utm_campaign, e.engagement_time,
+               s.country, s.browser, s.os, s.device
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.created_at >= p_from
+          AND e.created_at < p_until
+          AND e.event_type IN (1, 2)
+          AND (p_website_id IS NULL OR e.website_id = p_website_id)
+    ),
+    dims AS (
+        SELECT ev.website_id, ev.session_id, COALESCE(ev.engagement_time, 0) AS engagement_time, d.dimension, d.value
+        FROM events ev
+        CROSS JOIN LATERAL (VALUES
+            ('page', COALESCE(ev.url_path, 'Unknown')),
+            ('referrer', CASE
+                WHEN ev.referrer_domain IS NOT NULL THEN ev.referrer_domain || COALESCE(ev.referrer_path, '')
+                ELSE 'Direct / None'
+            END),
+            ('country', COALESCE(ev.country, 'Unknown')),
+            ('utm_campaign', COALESCE(ev.utm_campaign, 'Direct / None')),
+            ('browser', COALESCE(ev.browser, 'Unknown')),
+            ('os', COALESCE(ev.os, 'Unknown')),
+            ('device', COALESCE(ev.device, 'Unknown'))
+        ) AS d(dimension, value)
+        WHERE ev.event_type = 1
+        UNION ALL
+        SELECT ev.website_id, ev.session_id, 0, 'event', ev.event_name
+        FROM events ev
+        WHERE ev.event_type = 2 AND ev.event_name IS NOT NULL
+    )
+    SELECT
+        dims.website_id,
+        dims.dimension::VARCHAR,
+        dims.value::TEXT,
+        COUNT(*)::BIGINT,
+        COUNT(DISTINCT dims.session_id)::BIGINT,
+        SUM(dims.engagement_time)::BIGINT
+    FROM dims
+    GROUP BY dims.website_id, dims.dimension, dims.value;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION event_dimension_sessions(
+    p_from TIMESTAMPTZ,
+    p_until TIMESTAMPTZ,
+    p_website_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    website_id UUID,
+    created_at TIMESTAMPTZ,
+    session_id UUID,
+    dimension VARCHAR,
+    value TEXT
+) AS $$
+    WITH events AS (
+        SELECT e.website_id, e.created_at, e.session_id, e.event_type, e.event_name, e.url_path,
+               e.referrer_domain, e.referrer_path, e.utm_campaign,
+               s.country, s.browser, s.os, s.device
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.created_at >= p_from
+          AND e.created_at < p_until
+          AND e.event_type IN (1, 2)
+          AND (p_website_id IS NULL OR e.website_id = p_website_id)
+    )
+    SELECT ev.website_id, ev.created_at, ev.session_id, d.dimension::VARCHAR, d.value::TEXT
+    FROM events ev
+    CROSS JOIN LATERAL (VALUES
+        ('site', ''),
+        ('page', COALESCE(ev.url_path, 'Unknown')),
+        ('referrer', CASE
+            WHEN ev.referrer_domain IS NOT NULL THEN ev.referrer_domain || COALESCE(ev.referrer_path, '')
+            ELSE 'Direct / None'
+        END),
+        ('country', COALESCE(ev.country, 'Unknown')),
+        ('utm_campaign', COALESCE(ev.utm_campaign, 'Direct / None')),
+        ('browser', COALESCE(ev.browser, 'Unknown')),
+        ('os', COALESCE(ev.os, 'Unknown')),
+        ('device', COALESCE(ev.device, 'Unknown'))
+    ) AS d(dimension, value)
+    WHERE ev.event_type = 1
+    UNION ALL
+    SELECT ev.website_id, ev.created_at, ev.session_id, 'event', ev.event_name
+    FROM events ev
+    WHERE ev.event_type = 2 AND ev.event_name IS NOT NULL;
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON COLUMN event_rollup.dimension IS 'page, referrer, country, utm_campaign, browser, os, device or event';
+COMMENT ON COLUMN visitor_sketch.dimension IS 'site (value is empty), page, referrer, country, utm_campaign, browser, os, device or event';
+
+-- Roll up and sketch the stored raw events again, a month per refresh.
+-- Rollups of days whose raw events are gone are left as they are.
+DELETE FROM rollup_state WHERE granularity IN ('hour', 'day', 'hll');
+
+-- Same as 000048, with browser, OS and device breakdowns from rollups
+CREATE OR REPLACE FUNCTION get_breakdown(
+    p_website_id UUID,
+    p_dimension VARCHAR,
+    p_days INTEGER DEFAULT 1,
+    p_limit INTEGER DEFAULT 10,
+    p_offset INTEGER DEFAULT 0,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL,
+    p_sort_by VARCHAR DEFAULT 'count',
+    p_sort_order VARCHAR DEFAULT 'desc'
+)
+RETURNS TABLE (name VARCHAR, count BIGINT, total_count BIGINT) AS $$
+BEGIN
+    IF p_days < 7 OR p_dimension NOT IN ('page', 'referrer', 'country', 'utm_campaign', 'browser', 'os', 'device')
+       OR p_country IS NOT NULL OR p_browser IS NOT NULL OR p_device IS NOT NULL OR p_page_path IS NOT NULL THEN
+        RETURN QUERY
+        SELECT * FROM get_raw_breakdown(
+            p_website_id, p_dimension, p_days, p_limit, p_offset,
+            p_country, p_browser, p_device, p_page_path, p_sort_by, p_sort_order
+        );
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    WITH breakdown_data AS (
+        SELECT t.value::VARCHAR as dim_name, t.hits as dim_count
+        FROM get_rollup_totals(p_website_id, p_dimension, CURRENT_DATE - (p_days || ' days')::INTERVAL) t
+    ),
+    total_count_cte AS (
+        SELECT COUNT(*)::BIGINT as total FROM breakdown_data
+    )
+    SELECT bd.dim_name, bd.dim_count, tc.total
+    FROM breakdown_data bd
+    CROSS JOIN total_count_cte tc
+    ORDER BY
+        CASE WHEN p_sort_by = 'count' AND p_sort_order = 'desc' THEN bd.dim_count END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'count' AND p_sort_order = 'asc' THEN bd.dim_count END ASC NULLS LAST,
+        CASE WHEN p_sort_by = 'name' AND p_sort_order = 'desc' THEN bd.dim_name END DESC NULLS LAST,
+        CASE WHEN p_sort_by = 'name' AND p_sort_order = 'asc' THEN bd.dim_name END ASC NULLS LAST
+    LIMIT p_limit
+    OFFSET p_offset;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- ============================================================================
+-- 2. Daily summaries
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS daily_summary (
+    website_id UUID NOT NULL,
+    day TIMESTAMPTZ NOT NULL,
+    pageviews BIGINT NOT NULL,
+    visits BIGINT NOT NULL,
+    bounces BIGINT NOT NULL,
+    visit_seconds NUMERIC NOT NULL,
+    PRIMARY KEY (website_id, day)
+);
+
+COMMENT ON TABLE daily_summary IS 'Headline numbers of days whose raw events were compacted by compact_events()';
+COMMENT ON COLUMN daily_summary.visits IS 'Visits with a pageview, by the day they started';
+COMMENT ON COLUMN daily_summary.visit_seconds IS 'Total visit duration, engaged time preferred over event gaps';
+
+-- Summarizes the days of raw events before p_before (optionally of one
+-- website) and returns the time before which raw events may be deleted: the
+-- start of p_before's day, or less while rollups and sketches are behind.
+-- NULL means nothing can be deleted yet. Running it again over the same days
+-- rewrites their summaries.
+CREATE OR REPLACE FUNCTION compact_events(p_before TIMESTAMPTZ, p_website_id UUID DEFAULT NULL)
+RETURNS TIMESTAMPTZ AS $$
+DECLARE
+    v_until TIMESTAMPTZ := DATE_TRUNC('day', p_before);
+    v_from TIMESTAMPTZ;
+    v_rolled TIMESTAMPTZ;
+BEGIN
+    FOR v_rolled IN
+        SELECT s.rolled_until FROM (VALUES ('day'), ('hll')) AS g(granularity)
+        LEFT JOIN rollup_state s ON s.granularity = g.granularity
+    LOOP
+        IF v_rolled IS NULL THEN
+            RETURN NULL;
+        END IF;
+        v_until := LEAST(v_until, v_rolled);
+    END LOOP;
+
+    SELECT DATE_TRUNC('day', MIN(e.created_at)) INTO v_from
+    FROM website_event e
+    WHERE e.created_at < v_until
+      AND (p_website_id IS NULL OR e.website_id = p_website_id);
+    IF v_from IS NULL THEN
+        RETURN v_until;
+    END IF;
+
+    DELETE FROM daily_summary ds
+    WHERE ds.day >= v_from AND ds.day < v_until
+      AND (p_website_id IS NULL OR ds.website_id = p_website_id);
+
+    WITH visits AS (
+        SELECT
+            e.website_id,
+            e.visit_id,
+            DATE_TRUNC('day', MIN(e.created_at)) AS day,
+            COUNT(*) FILTER (WHERE e.event_type = 1) AS pageviews,
+            EXTRACT(EPOCH FROM MAX(e.created_at) - MIN(e.created_at)) AS gap_seconds
+        FROM website_event e
+        WHERE e.created_at >= v_from
+          AND e.created_at < v_until
+          AND (p_website_id IS NULL OR e.website_id = p_website_id)
+        GROUP BY e.website_id, e.visit_id
+        HAVING COUNT(*) FILTER (WHERE e.event_type = 1) > 0
+    )
+    INSERT INTO daily_summary (website_id, day, pageviews, visits, bounces, visit_seconds)
+    SELECT
+        v.website_id,
+        v.day,
+        SUM(v.pageviews),
+        COUNT(*),
+        COUNT(*) FILTER (WHERE v.pageviews = 1 AND COALESCE(ve.engaged_ms, 0) < 10000),
+        COALESCE(SUM(COALESCE(ve.engaged_ms / 1000.0, v.gap_seconds)), 0)
+    FROM visits v
+    LEFT JOIN visit_engagement ve ON ve.visit_id = v.visit_id
+    GROUP BY v.website_id, v.day;
+
+    RETURN v_until;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION compact_events IS 'Summarizes raw events before p_before into daily_summary; returns the time before which they may be deleted';
+
+-- ============================================================================
+-- 3. Reports read summaries for compacted days
+-- ============================================================================
+
+-- Same as 000050, with compacted days added to unfiltered ranges, whose
+-- visitors are then always estimated from sketches
+CREATE OR REPLACE FUNCTION get_dashboard_stats(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 1,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL
+)
+RETURNS TABLE (
+    current_visitors BIGINT,
+    today_pageviews BIGINT,
+    today_visitors BIGINT,
+    bounce_rate NUMERIC(5,2),
+    avg_visit_duration NUMERIC(10,1)
+) AS $$
+DECLARE
+    v_start TIMESTAMPTZ := CURRENT_DATE - (GREATEST(p_days, 1) - 1);
+    v_unfiltered BOOLEAN := p_country IS NULL AND p_browser IS NULL AND p_device IS NULL AND p_page_path IS NULL;
+    v_sketched BOOLEAN := p_days >= 7 AND v_unfiltered;
+    v_current_visitors BIGINT;
+    v_pageviews BIGINT;
+    v_visitors BIGINT;
+    v_visits BIGINT;
+    v_bounces BIGINT;
+    v_avg_duration NUMERIC;
+    v_raw_start TIMESTAMPTZ;
+    v_summary RECORD;
+BEGIN
+    IF v_unfiltered THEN
+        SELECT MIN(e.created_at) INTO v_raw_start FROM website_event e WHERE e.website_id = p_website_id;
+        -- Compacted days only have sketches of their visitors
+        v_sketched := v_sketched OR v_start < DATE_TRUNC('day', v_raw_start);
+    END IF;
+
+    -- 1. Current visitors (sessions in last 5 minutes)
+    SELECT COUNT(DISTINCT e.session_id) INTO v_current_visitors
+    FROM website_event e
+    JOIN session s ON e.session_id = s.session_id
+    WHERE e.website_id = p_website_id
+      AND e.created_at >= NOW() - INTERVAL '5 minutes'
+      AND e.event_type = 1
+      AND (p_country IS NULL OR s.country = p_country)
+      AND (p_browser IS NULL OR s.browser = p_browser)
+      AND (p_device IS NULL OR s.device = p_device)
+      AND (p_page_path IS NULL OR e.url_path = p_page_path);
+
+    -- 2. Pageviews and unique visitors for the period
+    IF v_sketched THEN
+        SELECT COUNT(*) INTO v_pageviews
+        FROM website_event e
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND e.event_type = 1;
+
+        SELECT COALESCE(SUM(u.visitors), 0) INTO v_visitors
+        FROM get_unique_visitors(p_website_id, 'site', v_start) u;
+    ELSE
+        SELECT COUNT(*), COUNT(DISTINCT e.session_id) INTO v_pageviews, v_visitors
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND e.event_type = 1
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path);
+    END IF;
+
+    -- 3. Per-visit bounce and duration, preferring engaged time over event gaps
+    WITH visits AS (
+        SELECT
+            e.visit_id,
+            COUNT(*) FILTER (WHERE e.event_type = 1) AS pageviews,
+            EXTRACT(EPOCH FROM MAX(e.created_at) - MIN(e.created_at)) AS gap_seconds
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= v_start
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path)
+        GROUP BY e.visit_id
+        HAVING COUNT(*) FILTER (WHERE e.event_type = 1) > 0
+    )
+    SELECT
+        COUNT(*),
+        COUNT(*) FILTER (WHERE v.pageviews = 1 AND COALESCE(ve.engaged_ms, 0) < 10000),
+        AVG(COALESCE(ve.engaged_ms / 1000.0, v.gap_seconds))
+    INTO v_visits, v_bounces, v_avg_duration
+    FROM visits v
+    LEFT JOIN visit_engagement ve ON ve.visit_id = v.visit_id;
+
+    -- 4. Compacted days before the first stored raw event
+    IF v_unfiltered THEN
+        SELECT
+            COALESCE(SUM(ds.pageviews), 0) AS pageviews,
+            COALESCE(SUM(ds.visits), 0) AS visits,
+            COALESCE(SUM(ds.bounces), 0) AS bounces,
+            COALESCE(SUM(ds.visit_seconds), 0) AS visit_seconds
+        INTO v_summary
+        FROM daily_summary ds
+        WHERE ds.website_id = p_website_id
+          AND ds.day >= v_start
+          AND (v_raw_start IS NULL OR ds.day < DATE_TRUNC('day', v_raw_start));
+
+        IF v_summary.visits > 0 THEN
+            v_pageviews := v_pageviews + v_summary.pageviews;
+            v_avg_duration := (COALESCE(v_avg_duration, 0) * v_visits + v_summary.visit_seconds) / (v_visits + v_summary.visits);
+            v_visits := v_visits + v_summary.visits;
+            v_bounces := v_bounces + v_summary.bounces;
+        END IF;
+    END IF;
+
+    RETURN QUERY SELECT
+        v_current_visitors,
+        v_pageviews,
+        v_visitors,
+        CASE WHEN v_visits > 0 THEN ROUND(v_bounces::NUMERIC / v_visits::NUMERIC * 100, 2) ELSE 0 END::NUMERIC(5,2),
+        ROUND(COALESCE(v_avg_duration, 0), 1)::NUMERIC(10,1);
+END;
+$$ LANGUAGE plpgsql STABLE;
+COMMENT ON FUNCTION get_dashboard_stats IS 'Headline metrics: current visitors, pageviews, visitors (sketched for unfiltered ranges of 7+ days), engagement-aware bounce rate and average visit duration (seconds), including compacted days';
+
+-- Same as 000029, with daily buckets of compacted days from daily_summary
+CREATE OR REPLACE FUNCTION get_timeseries(
+    p_website_id UUID,
+    p_days INTEGER DEFAULT 7,
+    p_country VARCHAR DEFAULT NULL,
+    p_browser VARCHAR DEFAULT NULL,
+    p_device VARCHAR DEFAULT NULL,
+    p_page_path VARCHAR DEFAULT NULL,
+    p_granularity VARCHAR DEFAULT 'hour'
+)
+RETURNS TABLE (
+    hour TIMESTAMPTZ,
+    views BIGINT
+) AS $$
+DECLARE
+    v_unit TEXT;
+    v_step INTERVAL;
+    v_start TIMESTAMPTZ;
+    v_settled TIMESTAMPTZ;
+    v_unfiltered BOOLEAN;
+BEGIN
+    v_unit := CASE LOWER(COALESCE(p_granularity, 'hour'))
+        WHEN 'minute' THEN 'minute'
+        WHEN 'day' THEN 'day'
+        ELSE 'hour'
+    END;
+    v_step := ('1 ' || v_unit)::INTERVAL;
+
+    IF v_unit = 'minute' THEN
+        v_start := DATE_TRUNC('minute', NOW() - INTERVAL '59 minutes');
+    ELSE
+        v_start := DATE_TRUNC(v_unit, NOW() - (p_days || ' days')::INTERVAL);
+    END IF;
+
+    v_unfiltered := p_country IS NULL AND p_browser IS NULL AND p_device IS NULL AND p_page_path IS NULL;
+
+    -- Buckets before v_settled come from the rollup; later ones from raw events.
+    -- The margin covers the rollup refresh interval.
+    v_settled := CASE
+        WHEN v_unfiltered AND v_unit = 'hour' THEN DATE_TRUNC('hour', NOW() - INTERVAL '10 minutes')
+        WHEN v_unfiltered AND v_unit = 'day' THEN DATE_TRUNC('day', NOW() - INTERVAL '2 hours')
+        ELSE v_start
+    END;
+
+    RETURN QUERY
+    WITH buckets AS (
+        SELECT b AS bucket
+        FROM generate_series(v_start, DATE_TRUNC(v_unit, NOW()), v_step) AS b
+    ),
+    rolled AS (
+        SELECT h.hour AS bucket, h.pageviews AS views
+        FROM hourly_website_stats h
+        WHERE v_unit = 'hour'
+          AND h.website_id = p_website_id
+          AND h.hour >= v_start
+          AND h.hour < v_settled
+        UNION ALL
+        SELECT d.date::TIMESTAMPTZ AS bucket, d.pageviews AS views
+        FROM daily_website_stats d
+        WHERE v_unit = 'day'
+          AND d.website_id = p_website_id
+          AND d.date::TIMESTAMPTZ >= v_start
+          AND d.date::TIMESTAMPTZ < v_settled
+    ),
+    compacted AS (
+        SELECT ds.day AS bucket, ds.pageviews AS views
+        FROM daily_summary ds
+        WHERE v_unit = 'day'
+          AND v_unfiltered
+          AND ds.website_id = p_website_id
+          AND ds.day >= v_start
+          AND ds.day < v_settled
+    ),
+    live AS (
+        SELECT DATE_TRUNC(v_unit, e.created_at) AS bucket, COUNT(*)::BIGINT AS views
+        FROM website_event e
+        JOIN session s ON e.session_id = s.session_id
+        WHERE e.website_id = p_website_id
+          AND e.created_at >= GREATEST(v_start, v_settled)
+          AND e.event_type = 1
+          AND (p_country IS NULL OR s.country = p_country)
+          AND (p_browser IS NULL OR s.browser = p_browser)
+          AND (p_device IS NULL OR s.device = p_device)
+          AND (p_page_path IS NULL OR e.url_path = p_page_path)
+        GROUP BY 1
+    )
+    SELECT
+        b.bucket::TIMESTAMPTZ AS hour,
+        COALESCE(r.views, c.views, l.views, 0)::BIGINT AS views
+    FROM buckets b
+    LEFT JOIN rolled r ON r.bucket = b.bucket
+    LEFT JOIN compacted c ON c.bucket = b.bucket
+    LEFT JOIN live l ON l.bucket = b.bucket
+    ORDER BY b.bucket ASC;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION get_timeseries IS 'Pageview time series in minute, hour or day buckets with empty buckets filled as zero';
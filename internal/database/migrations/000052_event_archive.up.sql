@@ -0,0 +1,58 @@
+-- Migration 000052: Event archive
+-- Closed months of raw events can be exported as Parquet files to
+-- S3-compatible storage. event_archive records which months are stored
+-- where; when archiving is on, the partition scheduler only drops the
+-- partitions of archived months.
+
+CREATE TABLE IF NOT EXISTS event_archive (
+    month DATE PRIMARY KEY CHECK (month = DATE_TRUNC('month', month)),
+    object_key TEXT NOT NULL,
+    row_count BIGINT NOT NULL,
+    byte_size BIGINT NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE event_archive IS 'Months of raw events exported to object storage as Parquet';
+
+-- Months before the current one that still have event partitions and
+-- haven't been archived
+CREATE OR REPLACE FUNCTION archive_pending_months()
+RETURNS TABLE(month DATE) AS $$
+    SELECT DISTINCT DATE_TRUNC('month', TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD'))::DATE AS month
+    FROM pg_inherits i
+    JOIN pg_class c ON c.oid = i.inhrelid
+    WHERE i.inhparent = 'website_event'::regclass
+      AND c.relname ~ '^website_event_\d{4}_\d{2}_\d{2}$'
+      AND TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD') < DATE_TRUNC('month', CURRENT_DATE)
+      AND NOT EXISTS (
+          SELECT 1 FROM event_archive a
+          WHERE a.month = DATE_TRUNC('month', TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD'))::DATE
+      )
+    ORDER BY 1;
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON FUNCTION archive_pending_months IS 'Closed months with event partitions that are not in event_archive';
+
+-- Restoring an archived month needs its partitions back, wherever they fall
+-- relative to today
+CREATE OR REPLACE FUNCTION ensure_partitions_between(parent TEXT, p_from DATE, p_until DATE)
+RETURNS INTEGER AS $$
+DECLARE
+    day DATE := p_from;
+    part_name TEXT;
+    created INTEGER := 0;
+BEGIN
+    WHILE day < p_until LOOP
+        part_name := parent || '_' || TO_CHAR(day, 'YYYY_MM_DD');
+        IF to_regclass(format('public.%I', part_name)) IS NULL THEN
+            EXECUTE format('CREATE TABLE %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+                part_name, parent, day, day + 1);
+            created := created + 1;
+        END IF;
+        day := day + 1;
+    END LOOP;
+    RETURN created;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION ensure_partitions_between IS 'Creates the missing daily partitions of parent for the days in [p_from, p_until)';
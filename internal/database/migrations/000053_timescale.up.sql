@@ -0,0 +1,304 @@
+-- Migration 000053: TimescaleDB hypertables
+-- website_event is natively partitioned by day. On servers with the
+-- TimescaleDB extension, `kaunta timescale enable` calls
+-- convert_events_to_hypertable() to move the events into a hypertable with
+-- daily chunks instead: older chunks are compressed, and the hourly and daily
+-- stats behind the charts become continuous aggregates, refreshed
+-- incrementally by TimescaleDB instead of recomputed by the server.
+--
+-- Installs without TimescaleDB are unaffected: the partition and archive
+-- functions only take their hypertable paths once website_event is one.
+
+-- ============================================================================
+-- 1. Detection
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION events_are_hypertable()
+RETURNS BOOLEAN AS $$
+DECLARE
+    found BOOLEAN;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        RETURN FALSE;
+    END IF;
+    -- The catalog views only exist with the extension installed
+    EXECUTE $q$
+        SELECT EXISTS (
+            SELECT 1 FROM timescaledb_information.hypertables
+            WHERE hypertable_schema = 'public' AND hypertable_name = 'website_event'
+        )
+    $q$ INTO found;
+    RETURN found;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+COMMENT ON FUNCTION events_are_hypertable IS 'True once website_event has been converted to a TimescaleDB hypertable';
+
+-- ============================================================================
+-- 2. Partition maintenance skips hypertables
+-- ============================================================================
+
+-- Same as 000047; hypertables create their chunks on insert
+CREATE OR REPLACE FUNCTION ensure_daily_partitions(parent TEXT, days_back INTEGER, days_ahead INTEGER)
+RETURNS INTEGER AS $$
+DECLARE
+    day DATE;
+    part_name TEXT;
+    created INTEGER := 0;
+BEGIN
+    IF (SELECT relkind FROM pg_class WHERE oid = parent::regclass) <> 'p' THEN
+        RETURN 0;
+    END IF;
+    FOR i IN -days_back..days_ahead LOOP
+        day := CURRENT_DATE + i;
+        part_name := parent || '_' || TO_CHAR(day, 'YYYY_MM_DD');
+        IF to_regclass(format('public.%I', part_name)) IS NULL THEN
+            EXECUTE format('CREATE TABLE %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+                part_name, parent, day, day + 1);
+            created := created + 1;
+        END IF;
+    END LOOP;
+    RETURN created;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Same as 000052, likewise
+CREATE OR REPLACE FUNCTION ensure_partitions_between(parent TEXT, p_from DATE, p_until DATE)
+RETURNS INTEGER AS $$
+DECLARE
+    day DATE := p_from;
+    part_name TEXT;
+    created INTEGER := 0;
+BEGIN
+    IF (SELECT relkind FROM pg_class WHERE oid = parent::regclass) <> 'p' THEN
+        RETURN 0;
+    END IF;
+    WHILE day < p_until LOOP
+        part_name := parent || '_' || TO_CHAR(day, 'YYYY_MM_DD');
+        IF to_regclass(format('public.%I', part_name)) IS NULL THEN
+            EXECUTE format('CREATE TABLE %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+                part_name, parent, day, day + 1);
+            created := created + 1;
+        END IF;
+        day := day + 1;
+    END LOOP;
+    RETURN created;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Same as 000052, except that hypertable chunks aren't named by day, so
+-- every closed month from the oldest event on is pending until archived
+CREATE OR REPLACE FUNCTION archive_pending_months()
+RETURNS TABLE(month DATE) AS $$
+BEGIN
+    IF events_are_hypertable() THEN
+        RETURN QUERY
+        SELECT m::DATE
+        FROM generate_series(
+            DATE_TRUNC('month', (SELECT MIN(e.created_at) FROM website_event e)),
+            DATE_TRUNC('month', CURRENT_DATE) - INTERVAL '1 month',
+            INTERVAL '1 month'
+        ) AS m
+        WHERE NOT EXISTS (SELECT 1 FROM event_archive a WHERE a.month = m::DATE)
+        ORDER BY 1;
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    SELECT DISTINCT DATE_TRUNC('month', TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD'))::DATE
+    FROM pg_inherits i
+    JOIN pg_class c ON c.oid = i.inhrelid
+    WHERE i.inhparent = 'website_event'::regclass
+      AND c.relname ~ '^website_event_\d{4}_\d{2}_\d{2}$'
+      AND TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD') < DATE_TRUNC('month', CURRENT_DATE)
+      AND NOT EXISTS (
+          SELECT 1 FROM event_archive a
+          WHERE a.month = DATE_TRUNC('month', TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD'))::DATE
+      )
+    ORDER BY 1;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- Drops the event chunks that end before p_before. With p_archived, chunks
+-- of months not yet in event_archive are kept. Returns the chunks dropped.
+CREATE OR REPLACE FUNCTION drop_expired_event_chunks(p_before TIMESTAMPTZ, p_archived BOOLEAN)
+RETURNS INTEGER AS $$
+DECLARE
+    v_before TIMESTAMPTZ := p_before;
+    v_unarchived DATE;
+    dropped INTEGER;
+BEGIN
+    IF p_archived THEN
+        SELECT COALESCE(MIN(p.month), DATE_TRUNC('month', CURRENT_DATE)::DATE) INTO v_unarchived
+        FROM archive_pending_months() p;
+        v_before := LEAST(v_before, v_unarchived::TIMESTAMPTZ);
+    END IF;
+
+    SELECT COUNT(*) INTO dropped FROM drop_chunks('website_event', older_than => v_before);
+    RETURN dropped;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION drop_expired_event_chunks IS 'Drops website_event hypertable chunks before p_before, keeping unarchived months when p_archived';
+
+-- ============================================================================
+-- 3. Conversion
+-- ============================================================================
+
+-- Moves website_event into a hypertable with daily chunks, compressed once
+-- older than p_compress_after, and returns the events copied. Runs in one
+-- transaction and locks the events for its duration, so the server should
+-- be stopped. Views reading website_event are recreated; the hourly and daily
+-- stats become continuous aggregates. The realtime_stats_cache trigger is
+-- dropped: hypertables don't support transition tables and nothing reads
+-- that cache.
+CREATE OR REPLACE FUNCTION convert_events_to_hypertable(p_compress_after INTERVAL DEFAULT INTERVAL '7 days')
+RETURNS BIGINT AS $$
+DECLARE
+    v_views TEXT[];
+    v_kinds TEXT[];
+    v_definitions TEXT[];
+    v_comments TEXT[];
+    v_view_indexes TEXT[];
+    v_indexes TEXT[];
+    v_constraints TEXT[];
+    v_columns TEXT;
+    v_statement TEXT;
+    v_rows BIGINT;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        RAISE EXCEPTION 'the timescaledb extension is not installed';
+    END IF;
+    IF events_are_hypertable() THEN
+        RETURN 0;
+    END IF;
+
+    -- Everything that goes with the table and isn't copied by LIKE
+    SELECT array_agg(v.relname::TEXT ORDER BY v.relname),
+           array_agg(v.relkind::TEXT ORDER BY v.relname),
+           array_agg(pg_get_viewdef(v.oid) ORDER BY v.relname),
+           array_agg(obj_description(v.oid, 'pg_class') ORDER BY v.relname)
+    INTO v_views, v_kinds, v_definitions, v_comments
+    FROM (
+        SELECT DISTINCT c.oid, c.relname, c.relkind
+        FROM pg_depend d
+        JOIN pg_rewrite r ON r.oid = d.objid
+        JOIN pg_class c ON c.oid = r.ev_class
+        WHERE d.classid = 'pg_rewrite'::regclass
+          AND d.refobjid = 'website_event'::regclass
+          AND c.oid <> 'website_event'::regclass
+    ) v;
+
+    SELECT array_agg(pi.indexdef)
+    INTO v_view_indexes
+    FROM pg_indexes pi
+    WHERE pi.schemaname = 'public'
+      AND pi.tablename = ANY(v_views)
+      AND pi.tablename NOT IN ('hourly_website_stats', 'daily_website_stats');
+
+    SELECT array_agg(REPLACE(pg_get_indexdef(i.indexrelid), ' ON ONLY ', ' ON '))
+    INTO v_indexes
+    FROM pg_index i
+    WHERE i.indrelid = 'website_event'::regclass
+      AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = i.indexrelid);
+
+    SELECT array_agg(format('ALTER TABLE website_event ADD CONSTRAINT %I %s', c.conname, pg_get_constraintdef(c.oid)))
+    INTO v_constraints
+    FROM pg_constraint c
+    WHERE c.conrelid = 'website_event'::regclass
+      AND c.contype IN ('p', 'f');
+
+    SELECT string_agg(quote_ident(a.attname), ', ' ORDER BY a.attnum)
+    INTO v_columns
+    FROM pg_attribute a
+    WHERE a.attrelid = 'website_event'::regclass
+      AND a.attnum > 0
+      AND NOT a.attisdropped
+      AND a.attgenerated = '';
+
+    FOR i IN 1..COALESCE(array_length(v_views, 1), 0) LOOP
+        EXECUTE format('DROP %s %I',
+            CASE v_kinds[i] WHEN 'm' THEN 'MATERIALIZED VIEW' ELSE 'VIEW' END, v_views[i]);
+    END LOOP;
+    DROP TRIGGER IF EXISTS trg_website_event_realtime_stats ON website_event;
+
+    -- Copy the events into the hypertable
+    ALTER TABLE website_event RENAME TO website_event_partitioned;
+    CREATE TABLE website_event (
+        LIKE website_event_partitioned
+        INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING GENERATED INCLUDING COMMENTS INCLUDING STORAGE
+    );
+    PERFORM create_hypertable('website_event', 'created_at',
+        chunk_time_interval => INTERVAL '1 day', create_default_indexes => FALSE);
+
+    EXECUTE format('INSERT INTO website_event (%s) SELECT %s FROM website_event_partitioned', v_columns, v_columns);
+    GET DIAGNOSTICS v_rows = ROW_COUNT;
+    DROP TABLE website_event_partitioned;
+
+    -- Keys and indexes are built once the events are in
+    FOREACH v_statement IN ARRAY COALESCE(v_constraints, '{}') LOOP
+        EXECUTE v_statement;
+    END LOOP;
+    FOREACH v_statement IN ARRAY COALESCE(v_indexes, '{}') LOOP
+        EXECUTE v_statement;
+    END LOOP;
+
+    ALTER TABLE website_event SET (
+        timescaledb.compress,
+        timescaledb.compress_segmentby = 'website_id',
+        timescaledb.compress_orderby = 'created_at DESC'
+    );
+    PERFORM add_compression_policy('website_event', compress_after => p_compress_after);
+
+    -- Continuous aggregates with the columns get_timeseries() reads. Buckets
+    -- after the last refresh are aggregated from raw events at query time.
+    CREATE MATERIALIZED VIEW hourly_website_stats
+    WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+    SELECT website_id,
+           time_bucket(INTERVAL '1 hour', created_at) AS hour,
+           COUNT(*) AS pageviews,
+           AVG(engagement_time) AS avg_engagement_time
+    FROM website_event
+    WHERE event_type = 1
+    GROUP BY website_id, time_bucket(INTERVAL '1 hour', created_at)
+    WITH NO DATA;
+
+    CREATE MATERIALIZED VIEW daily_website_stats
+    WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+    SELECT website_id,
+           time_bucket(INTERVAL '1 day', created_at) AS date,
+           COUNT(*) AS pageviews,
+           AVG(engagement_time) AS avg_engagement_time
+    FROM website_event
+    WHERE event_type = 1
+    GROUP BY website_id, time_bucket(INTERVAL '1 day', created_at)
+    WITH NO DATA;
+
+    PERFORM add_continuous_aggregate_policy('hourly_website_stats',
+        start_offset => INTERVAL '3 days', end_offset => INTERVAL '1 hour', schedule_interval => INTERVAL '5 minutes');
+    PERFORM add_continuous_aggregate_policy('daily_website_stats',
+        start_offset => INTERVAL '3 days', end_offset => INTERVAL '1 hour', schedule_interval => INTERVAL '1 hour');
+
+    COMMENT ON VIEW hourly_website_stats IS 'Continuous aggregate of pageviews per website and hour, refreshed by TimescaleDB';
+    COMMENT ON VIEW daily_website_stats IS 'Continuous aggregate of pageviews per website and day, refreshed by TimescaleDB';
+
+    -- The other views, as they were
+    FOR i IN 1..COALESCE(array_length(v_views, 1), 0) LOOP
+        CONTINUE WHEN v_views[i] IN ('hourly_website_stats', 'daily_website_stats');
+        EXECUTE format('CREATE %s %I AS %s',
+            CASE v_kinds[i] WHEN 'm' THEN 'MATERIALIZED VIEW' ELSE 'VIEW' END, v_views[i], v_definitions[i]);
+        IF v_comments[i] IS NOT NULL THEN
+            EXECUTE format('COMMENT ON %s %I IS %L',
+                CASE v_kinds[i] WHEN 'm' THEN 'MATERIALIZED VIEW' ELSE 'VIEW' END, v_views[i], v_comments[i]);
+        END IF;
+    END LOOP;
+    FOREACH v_statement IN ARRAY COALESCE(v_view_indexes, '{}') LOOP
+        EXECUTE v_statement;
+    END LOOP;
+
+    COMMENT ON TABLE website_event IS 'TimescaleDB hypertable with daily chunks, compressed after they age';
+    RETURN v_rows;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION convert_events_to_hypertable IS 'Moves website_event into a compressed TimescaleDB hypertable and turns the hourly and daily stats into continuous aggregates';
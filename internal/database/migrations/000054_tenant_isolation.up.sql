@@ -0,0 +1,85 @@
+-- Migration 000054: Row-level tenant isolation for stats queries
+-- Shared instances can run every dashboard and stats API query as the
+-- kaunta_tenant role inside a transaction that names the websites the
+-- caller may read. Row-level security policies on every table with a
+-- website_id column then hide other tenants' rows, so a query that forgets
+-- or mangles its website filter returns nothing instead of someone else's
+-- data. Nothing changes until tenant isolation is switched on: the server
+-- then calls enable_tenant_isolation() at startup.
+
+-- Websites the current transaction may read; empty when unset, so a query
+-- running as kaunta_tenant without a scope sees no rows
+CREATE OR REPLACE FUNCTION tenant_websites()
+RETURNS UUID[] AS $$
+    SELECT COALESCE(NULLIF(current_setting('kaunta.tenant_websites', true), ''), '{}')::UUID[]
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON FUNCTION tenant_websites() IS 'Websites visible to kaunta_tenant in this transaction, from the kaunta.tenant_websites setting';
+
+-- Creates the kaunta_tenant role, lets the current user switch to it and
+-- protects every table with a website_id column. Safe to run on every
+-- startup: it only touches tables that aren't protected yet, which also
+-- covers tables added by later migrations. Returns the tables protected.
+CREATE OR REPLACE FUNCTION enable_tenant_isolation()
+RETURNS INTEGER AS $$
+DECLARE
+    v_table TEXT;
+    v_protected INTEGER := 0;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'kaunta_tenant') THEN
+        CREATE ROLE kaunta_tenant NOLOGIN;
+    END IF;
+    IF NOT pg_has_role(current_user, 'kaunta_tenant', 'MEMBER') THEN
+        EXECUTE format('GRANT kaunta_tenant TO %I', current_user);
+    END IF;
+
+    GRANT USAGE ON SCHEMA public TO kaunta_tenant;
+
+    -- Partitions are left out so they can only be read through their
+    -- parent, where the policies apply
+    FOR v_table IN
+        SELECT c.relname
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'public'
+          AND c.relkind IN ('r', 'p', 'v', 'm')
+          AND NOT c.relispartition
+          AND NOT has_table_privilege('kaunta_tenant', c.oid, 'SELECT')
+    LOOP
+        EXECUTE format('GRANT SELECT ON %I TO kaunta_tenant', v_table);
+    END LOOP;
+
+    FOR v_table IN
+        SELECT c.relname
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_attribute a ON a.attrelid = c.oid
+        WHERE n.nspname = 'public'
+          AND c.relkind IN ('r', 'p')
+          AND NOT c.relispartition
+          AND a.attname = 'website_id'
+          AND NOT a.attisdropped
+          AND NOT EXISTS (
+              SELECT 1 FROM pg_policy p
+              WHERE p.polrelid = c.oid AND p.polname = 'tenant_isolation'
+          )
+    LOOP
+        EXECUTE format(
+            'CREATE POLICY tenant_isolation ON %I FOR SELECT TO kaunta_tenant USING (website_id = ANY (tenant_websites()))',
+            v_table
+        );
+        -- The owner bypasses row-level security; this keeps every other role
+        -- (a replica user, say) reading and writing as before
+        EXECUTE format(
+            'CREATE POLICY tenant_unrestricted ON %I USING (current_user <> ''kaunta_tenant'') WITH CHECK (current_user <> ''kaunta_tenant'')',
+            v_table
+        );
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', v_table);
+        v_protected := v_protected + 1;
+    END LOOP;
+
+    RETURN v_protected;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION enable_tenant_isolation() IS 'Sets up the kaunta_tenant role and row-level security on tables with a website_id column; returns the tables newly protected';
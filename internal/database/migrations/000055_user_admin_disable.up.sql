@@ -0,0 +1,43 @@
+-- Migration 000055: Administrators and disabled accounts
+-- Administrators hold the owner role on every website, so an instance
+-- always has someone who can fix access. Disabled accounts keep their data
+-- and settings but can't sign in, and their sessions and personal access
+-- tokens stop working.
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN users.is_admin IS 'Administrators are owners of every website';
+COMMENT ON COLUMN users.disabled_at IS 'When the account was disabled; NULL while it can sign in';
+
+-- Websites a user can access, with their role on each. Ownerless websites
+-- outside any team stay open to everyone; administrators own everything.
+CREATE OR REPLACE FUNCTION user_websites(p_user_id UUID)
+RETURNS TABLE (website_id UUID, role VARCHAR) AS $$
+    SELECT w.website_id,
+           (ARRAY['viewer', 'editor', 'owner'])[MAX(a.rank)]::VARCHAR
+    FROM (
+        SELECT w.website_id, 3 AS rank
+        FROM website w
+        WHERE w.user_id = p_user_id OR (w.user_id IS NULL AND w.team_id IS NULL)
+        UNION ALL
+        SELECT w.website_id, 3
+        FROM website w
+        JOIN users admin ON admin.user_id = p_user_id AND admin.is_admin
+        UNION ALL
+        SELECT m.website_id, website_role_rank(m.role)
+        FROM website_member m
+        WHERE m.user_id = p_user_id
+        UNION ALL
+        SELECT w.website_id, website_role_rank(t.role)
+        FROM website w
+        JOIN team_member t ON t.team_id = w.team_id
+        WHERE t.user_id = p_user_id
+    ) a
+    JOIN website w ON w.website_id = a.website_id
+    LEFT JOIN users u ON u.user_id = p_user_id
+    WHERE w.deleted_at IS NULL
+      AND (u.current_team_id IS NULL OR w.team_id = u.current_team_id)
+    GROUP BY w.website_id
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,23 @@
+-- Migration 000056: Import progress
+-- `kaunta import` copies history from Umami and Plausible. Each imported
+-- site records how far it got, so an interrupted import continues where it
+-- stopped and running it again later only picks up what is new.
+
+CREATE TABLE IF NOT EXISTS import_progress (
+    source VARCHAR(20) NOT NULL,
+    source_id TEXT NOT NULL,
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    cursor_at TIMESTAMPTZ,
+    cursor_id TEXT,
+    imported BIGINT NOT NULL DEFAULT 0,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    PRIMARY KEY (source, source_id)
+);
+
+COMMENT ON TABLE import_progress IS 'How far each site imported from another analytics tool has been copied';
+COMMENT ON COLUMN import_progress.source_id IS 'The site in the source: Umami website ID or Plausible site domain';
+COMMENT ON COLUMN import_progress.cursor_at IS 'Time of the last event (Umami) or day (Plausible) imported';
+COMMENT ON COLUMN import_progress.cursor_id IS 'ID of the last event imported at cursor_at (Umami)';
+COMMENT ON COLUMN import_progress.imported IS 'Events written so far';
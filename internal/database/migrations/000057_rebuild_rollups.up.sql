@@ -0,0 +1,76 @@
+-- Migration 000057: Rebuilding rollups of past days
+-- refresh_event_rollups() and refresh_visitor_sketches() only move forward
+-- from rollup_state.rolled_until, so events written into days that are
+-- already rolled up (imported history, demo data) never reach event_rollup
+-- or visitor_sketch, and long dashboard ranges miss them. rebuild_rollups()
+-- recounts a range of those days from the raw events.
+--
+-- Days before the first raw event of a website are left alone: their raw
+-- events were dropped by retention or compaction and their rollups are all
+-- that is left. Days not rolled up yet are left to the regular refresh.
+
+CREATE OR REPLACE FUNCTION rebuild_rollups(
+    p_from TIMESTAMPTZ,
+    p_until TIMESTAMPTZ,
+    p_website_id UUID DEFAULT NULL
+)
+RETURNS INTEGER AS $$
+DECLARE
+    v_unit TEXT;
+    v_from TIMESTAMPTZ;
+    v_until TIMESTAMPTZ;
+    v_rows INTEGER := 0;
+    v_count INTEGER;
+BEGIN
+    FOREACH v_unit IN ARRAY ARRAY['hour', 'day', 'hll'] LOOP
+        SELECT rolled_until INTO v_until FROM rollup_state WHERE granularity = v_unit;
+        CONTINUE WHEN v_until IS NULL;
+
+        v_from := DATE_TRUNC(CASE v_unit WHEN 'hour' THEN 'hour' ELSE 'day' END, p_from);
+        IF v_unit = 'hour' THEN
+            -- Hourly rollups are only kept for the last week
+            v_from := GREATEST(v_from, DATE_TRUNC('hour', NOW() - INTERVAL '7 days'));
+        END IF;
+        v_until := LEAST(v_until, p_until);
+        CONTINUE WHEN v_from >= v_until;
+
+        IF v_unit = 'hll' THEN
+            DELETE FROM visitor_sketch vs
+            WHERE vs.bucket >= v_from AND vs.bucket < v_until
+              AND (p_website_id IS NULL OR vs.website_id = p_website_id)
+              AND vs.bucket >= (
+                  SELECT DATE_TRUNC('day', MIN(e.created_at)) FROM website_event e WHERE e.website_id = vs.website_id
+              );
+
+            INSERT INTO visitor_sketch (website_id, bucket, dimension, value, register, rank)
+            SELECT d.website_id, DATE_TRUNC('day', d.created_at), d.dimension, d.value, h.register, MAX(h.rank)
+            FROM event_dimension_sessions(v_from, v_until, p_website_id) d
+            CROSS JOIN LATERAL hll_hash(d.session_id) h
+            GROUP BY 1, 2, 3, 4, 5
+            ON CONFLICT (website_id, dimension, bucket, value, register) DO UPDATE SET rank = EXCLUDED.rank;
+        ELSE
+            DELETE FROM event_rollup r
+            WHERE r.granularity = v_unit AND r.bucket >= v_from AND r.bucket < v_until
+              AND (p_website_id IS NULL OR r.website_id = p_website_id)
+              AND r.bucket >= (
+                  SELECT DATE_TRUNC(v_unit, MIN(e.created_at)) FROM website_event e WHERE e.website_id = r.website_id
+              );
+
+            INSERT INTO event_rollup (website_id, granularity, bucket, dimension, value, hits, visitors, engagement_time)
+            SELECT a.website_id, v_unit, b.bucket, a.dimension, a.value, a.hits, a.visitors, a.engagement_time
+            FROM generate_series(v_from, v_until - ('1 ' || v_unit)::INTERVAL, ('1 ' || v_unit)::INTERVAL) AS b(bucket)
+            CROSS JOIN LATERAL aggregate_event_dimensions(b.bucket, b.bucket + ('1 ' || v_unit)::INTERVAL, p_website_id) a
+            ON CONFLICT (website_id, dimension, granularity, bucket, value) DO UPDATE SET
+                hits = EXCLUDED.hits,
+                visitors = EXCLUDED.visitors,
+                engagement_time = EXCLUDED.engagement_time;
+        END IF;
+        GET DIAGNOSTICS v_count = ROW_COUNT;
+        v_rows := v_rows + v_count;
+    END LOOP;
+
+    RETURN v_rows;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION rebuild_rollups IS 'Recounts event_rollup and visitor_sketch for already rolled-up days in [p_from, p_until) from raw events; returns rows written';
@@ -0,0 +1,21 @@
+-- Migration 000058: Background job runs
+-- The server runs its background jobs (partitions, rollups, retention,
+-- alerts, ...) through one scheduler. Each job's last run and next due run
+-- are kept here, so `kaunta jobs list` can show them and a restarted server
+-- doesn't rerun a daily job it ran an hour ago.
+
+CREATE TABLE IF NOT EXISTS job_run (
+    name TEXT PRIMARY KEY,
+    last_started_at TIMESTAMPTZ NOT NULL,
+    last_finished_at TIMESTAMPTZ NOT NULL,
+    last_error TEXT,
+    last_trigger VARCHAR(20) NOT NULL,
+    next_run_at TIMESTAMPTZ,
+    runs BIGINT NOT NULL DEFAULT 0,
+    failures BIGINT NOT NULL DEFAULT 0
+);
+
+COMMENT ON TABLE job_run IS 'Last and next run of each background job';
+COMMENT ON COLUMN job_run.last_error IS 'Error of the last run, NULL when it succeeded';
+COMMENT ON COLUMN job_run.last_trigger IS 'What started the last run: schedule or manual (kaunta jobs run)';
+COMMENT ON COLUMN job_run.next_run_at IS 'When the scheduler runs the job next; manual runs leave it alone';
@@ -0,0 +1,8 @@
+-- Migration 000059: Consent on sessions
+-- Sites that load the tracker with data-consent="manual" only send hits
+-- after the visitor opts in, and mark them as consented. The session keeps
+-- when that first happened, as a record of the opt-in.
+
+ALTER TABLE session ADD COLUMN IF NOT EXISTS consented_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN session.consented_at IS 'When the visitor opted in through the tracker consent gate; NULL for sites without it';
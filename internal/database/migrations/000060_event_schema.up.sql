@@ -0,0 +1,35 @@
+-- Migration 000060: Event schemas and data quality
+-- A website can declare the custom events it sends and the type of their
+-- properties. The collector still stores every event, but counts the ones
+-- that don't match (unknown names, missing properties, wrong types) per day
+-- for the dashboard's Data quality tab.
+
+CREATE TABLE IF NOT EXISTS event_schema (
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    event_name VARCHAR(50) NOT NULL,
+    properties JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (website_id, event_name)
+);
+
+COMMENT ON TABLE event_schema IS 'Custom events a website declares, checked by the collector';
+COMMENT ON COLUMN event_schema.properties IS 'Property name to type: string, number or boolean, with a trailing ? when optional';
+
+CREATE TABLE IF NOT EXISTS event_quality_issue (
+    website_id UUID NOT NULL REFERENCES website(website_id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    event_name VARCHAR(50) NOT NULL,
+    issue VARCHAR(20) NOT NULL,
+    property VARCHAR(100) NOT NULL DEFAULT '',
+    expected VARCHAR(20) NOT NULL DEFAULT '',
+    received VARCHAR(20) NOT NULL DEFAULT '',
+    hits BIGINT NOT NULL DEFAULT 0,
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sample JSONB,
+    PRIMARY KEY (website_id, day, event_name, issue, property)
+);
+
+COMMENT ON TABLE event_quality_issue IS 'Custom events that did not match the website event schema, counted per day';
+COMMENT ON COLUMN event_quality_issue.issue IS 'unknown_event, missing_property or wrong_type';
+COMMENT ON COLUMN event_quality_issue.received IS 'Type of the value received, for wrong_type';
+COMMENT ON COLUMN event_quality_issue.sample IS 'Properties of the latest mismatched event';
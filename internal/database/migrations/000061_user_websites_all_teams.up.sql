@@ -0,0 +1,47 @@
+-- Migration 000061: Access across every team
+-- Alert rules, webhooks and custom dashboards re-check that their owner can
+-- still access their website each time they run. That check must follow
+-- team membership, so leaving a team or taking a website out of one stops
+-- them, but not the dashboard's team switcher: picking another team in the
+-- header must not pause a user's alerts. user_websites_all_teams() is
+-- user_websites() without the switcher; user_websites() now builds on it.
+
+CREATE OR REPLACE FUNCTION user_websites_all_teams(p_user_id UUID)
+RETURNS TABLE (website_id UUID, role VARCHAR) AS $$
+    SELECT w.website_id,
+           (ARRAY['viewer', 'editor', 'owner'])[MAX(a.rank)]::VARCHAR
+    FROM (
+        SELECT w.website_id, 3 AS rank
+        FROM website w
+        WHERE w.user_id = p_user_id OR (w.user_id IS NULL AND w.team_id IS NULL)
+        UNION ALL
+        SELECT w.website_id, 3
+        FROM website w
+        JOIN users admin ON admin.user_id = p_user_id AND admin.is_admin
+        UNION ALL
+        SELECT m.website_id, website_role_rank(m.role)
+        FROM website_member m
+        WHERE m.user_id = p_user_id
+        UNION ALL
+        SELECT w.website_id, website_role_rank(t.role)
+        FROM website w
+        JOIN team_member t ON t.team_id = w.team_id
+        WHERE t.user_id = p_user_id
+    ) a
+    JOIN website w ON w.website_id = a.website_id
+    WHERE w.deleted_at IS NULL
+    GROUP BY w.website_id
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON FUNCTION user_websites_all_teams(UUID) IS 'Websites a user can access and their role on each, whichever team the dashboard is switched to';
+
+-- Websites a user can access, with their role on each, limited to the team
+-- picked in the dashboard switcher
+CREATE OR REPLACE FUNCTION user_websites(p_user_id UUID)
+RETURNS TABLE (website_id UUID, role VARCHAR) AS $$
+    SELECT a.website_id, a.role
+    FROM user_websites_all_teams(p_user_id) a
+    JOIN website w ON w.website_id = a.website_id
+    LEFT JOIN users u ON u.user_id = p_user_id
+    WHERE u.current_team_id IS NULL OR w.team_id = u.current_team_id
+$$ LANGUAGE sql STABLE;
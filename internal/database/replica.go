@@ -0,0 +1,147 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"sync/atomic"
+	"time"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// ReadDB is an optional read-only replica for stats and report queries
+var ReadDB *sql.DB
+
+// replicaHealthy is cleared while the replica is unreachable or lagging
+var replicaHealthy atomic.Bool
+
+// ConnectReplica opens the read replica. Reads go to it once it answers;
+// until then, and whenever the ReplicaMonitor finds it unhealthy, they go to
+// the primary.
+func ConnectReplica(replicaURL string) error {
+	if err := CheckURL(replicaURL); err != nil {
+		return err
+	}
+
+	db, err := sql.Open("postgres", withConnParams(replicaURL, poolSettings))
+	if err != nil {
+		return fmt.Errorf("failed to open replica: %w", err)
+	}
+	if poolSettings.Size > 0 {
+		db.SetMaxOpenConns(poolSettings.Size)
+		db.SetMaxIdleConns(poolSettings.Size)
+	}
+	if poolSettings.IdleLifetime > 0 {
+		db.SetConnMaxIdleTime(poolSettings.IdleLifetime)
+	}
+
+	ReadDB = db
+	replicaHealthy.Store(db.Ping() == nil)
+	logging.L().Info("read replica configured", slog.Bool("healthy", replicaHealthy.Load()))
+	return nil
+}
+
+// CloseReplica closes the read replica, if any
+func CloseReplica() error {
+	if ReadDB == nil {
+		return nil
+	}
+	err := ReadDB.Close()
+	ReadDB = nil
+	replicaHealthy.Store(false)
+	return err
+}
+
+// Reader returns the database for stats and report queries: the replica
+// while it is healthy, otherwise the primary
+func Reader() *sql.DB {
+	if ReadDB != nil && replicaHealthy.Load() {
+		return ReadDB
+	}
+	return DB
+}
+
+// ReplicaMonitor checks the read replica and routes reads back to the
+// primary while it is down or too far behind
+type ReplicaMonitor struct {
+	interval time.Duration
+	maxLag   time.Duration
+	stopChan chan struct{}
+}
+
+// NewReplicaMonitor creates a monitor checking every 10 seconds. A replica
+// more than maxLag behind is skipped (0 ignores lag).
+func NewReplicaMonitor(maxLag time.Duration) *ReplicaMonitor {
+	return &ReplicaMonitor{
+		interval: 10 * time.Second,
+		maxLag:   maxLag,
+		stopChan: make(chan struct{}),
+	}
+}
+
+// Start checks the replica on every interval
+func (rm *ReplicaMonitor) Start() {
+	logging.L().Info("starting replica monitor", slog.Duration("max_lag", rm.maxLag))
+	go rm.run()
+}
+
+// Stop gracefully stops the monitor
+func (rm *ReplicaMonitor) Stop() {
+	close(rm.stopChan)
+}
+
+func (rm *ReplicaMonitor) run() {
+	ticker := time.NewTicker(rm.interval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ticker.C:
+			rm.check()
+		case <-rm.stopChan:
+			return
+		}
+	}
+}
+
+func (rm *ReplicaMonitor) check() {
+	if ReadDB == nil {
+		return
+	}
+
+	err := rm.probe()
+	healthy := err == nil
+	if replicaHealthy.Swap(healthy) == healthy {
+		return
+	}
+	if healthy {
+		logging.L().Info("read replica healthy again, routing stats queries to it")
+	} else {
+		logging.L().Warn("read replica unhealthy, routing stats queries to the primary", slog.Any("error", err))
+	}
+}
+
+// probe reports why the replica shouldn't serve reads, or nil
+func (rm *ReplicaMonitor) probe() error {
+	ctx, cancel := context.WithTimeout(context.Background(), 5*time.Second)
+	defer cancel()
+
+	// Lag is zero while the replica has replayed everything it received, so
+	// an idle primary doesn't make it look stale
+	var lag sql.NullFloat64
+	if err := ReadDB.QueryRowContext(ctx, `
+		SELECT CASE
+			WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0
+			ELSE EXTRACT(EPOCH FROM NOW() - pg_last_xact_replay_timestamp())
+		END
+	`).Scan(&lag); err != nil {
+		return err
+	}
+	if rm.maxLag > 0 && lag.Valid && time.Duration(lag.Float64*float64(time.Second)) > rm.maxLag {
+		return fmt.Errorf("replica is %.0fs behind", lag.Float64)
+	}
+	return nil
+}
@@ -0,0 +1,62 @@
+package database
+
+import (
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func withMockReplica(t *testing.T) sqlmock.Sqlmock {
+	t.Helper()
+	replica, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	original := ReadDB
+	ReadDB = replica
+	replicaHealthy.Store(true)
+	t.Cleanup(func() {
+		ReadDB = original
+		replicaHealthy.Store(false)
+		_ = replica.Close()
+	})
+	return mock
+}
+
+func TestReaderFallsBackToPrimary(t *testing.T) {
+	_, cleanup := withMockDB(t)
+	defer cleanup()
+
+	assert.Same(t, DB, Reader(), "no replica configured")
+
+	withMockReplica(t)
+	assert.Same(t, ReadDB, Reader())
+
+	replicaHealthy.Store(false)
+	assert.Same(t, DB, Reader())
+}
+
+func TestReplicaMonitorCheck(t *testing.T) {
+	_, cleanup := withMockDB(t)
+	defer cleanup()
+	mock := withMockReplica(t)
+	monitor := NewReplicaMonitor(30 * time.Second)
+
+	mock.ExpectQuery("pg_last_xact_replay_timestamp").
+		WillReturnRows(sqlmock.NewRows([]string{"lag"}).AddRow(120.0))
+	monitor.check()
+	assert.Same(t, DB, Reader(), "lagging replica is skipped")
+
+	mock.ExpectQuery("pg_last_xact_replay_timestamp").
+		WillReturnRows(sqlmock.NewRows([]string{"lag"}).AddRow(2.5))
+	monitor.check()
+	assert.Same(t, ReadDB, Reader())
+
+	mock.ExpectQuery("pg_last_xact_replay_timestamp").WillReturnError(errors.New("connection refused"))
+	monitor.check()
+	assert.Same(t, DB, Reader(), "unreachable replica is skipped")
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -1,210 +1,288 @@
 package database
 
 import (
+	"context"
+	"database/sql"
+	"errors"
 	"fmt"
 	"time"
 
 	"log/slog"
 
+	"github.com/lib/pq"
+
 	"github.com/seuros/kaunta/internal/logging"
 )
 
 var (
-	nowFunc             = time.Now
-	partitionDaysAhead  = 30
-	retentionPeriodDays = 90
+	nowFunc            = time.Now
+	partitionDaysAhead = 30
+	partitionDaysBack  = 7
 )
 
-// PartitionScheduler manages automatic partition creation and cleanup
+// partitionedTable is a table partitioned by day
+type partitionedTable struct {
+	name      string
+	keepDays  int  // Partitions older than this are dropped; 0 keeps them all
+	compact   bool // Summarize rows with compact_events() before dropping them
+	archived  bool // Keep partitions until their month is in event_archive
+	timescale bool // May have been converted to a TimescaleDB hypertable
+}
+
+// PartitionScheduler keeps daily partitions created ahead of today and
+// drops the ones past their retention
 type PartitionScheduler struct {
-	databaseURL string
-	stopChan    chan struct{}
+	tables []partitionedTable
 }
 
-// NewPartitionScheduler creates a new partition scheduler
-func NewPartitionScheduler(databaseURL string) *PartitionScheduler {
+// NewPartitionScheduler creates a partition scheduler. Events are kept for
+// eventRetentionDays, or forever when it is 0. With archiveEvents, expired
+// events are also kept until their month has been archived.
+func NewPartitionScheduler(eventRetentionDays int, archiveEvents bool) *PartitionScheduler {
 	return &PartitionScheduler{
-		databaseURL: databaseURL,
-		stopChan:    make(chan struct{}),
+		tables: []partitionedTable{
+			{name: "website_event", keepDays: eventRetentionDays, compact: true, archived: archiveEvents, timescale: true},
+			{name: "bot_detection_log", keepDays: 30},
+			{name: "event_idempotency", keepDays: 7},
+		},
 	}
 }
 
-// Start begins the partition management tasks
-func (ps *PartitionScheduler) Start() {
-	logging.L().Info("starting partition scheduler")
-
-	// Create future partitions daily at 2 AM
-	go ps.schedulePartitionCreation()
-
-	// Clean up old partitions weekly
-	go ps.schedulePartitionCleanup()
-}
-
-// Stop gracefully stops the scheduler
-func (ps *PartitionScheduler) Stop() {
-	close(ps.stopChan)
+// Job maintains the partitions daily
+func (ps *PartitionScheduler) Job() Job {
+	return Job{
+		Name:        "partitions",
+		Description: "Create upcoming daily partitions and drop expired ones",
+		Every:       24 * time.Hour,
+		Run:         func(context.Context) error { return ps.maintain() },
+	}
 }
 
-// schedulePartitionCreation creates partitions 30 days in advance
-func (ps *PartitionScheduler) schedulePartitionCreation() {
-	ticker := time.NewTicker(24 * time.Hour)
-	defer ticker.Stop()
-
-	// Run immediately on start
-	ps.createFuturePartitions()
-
-	for {
-		select {
-		case <-ticker.C:
-			ps.createFuturePartitions()
-		case <-ps.stopChan:
-			return
+func (ps *PartitionScheduler) maintain() error {
+	var errs []error
+	for _, table := range ps.tables {
+		errs = append(errs, ps.createPartitions(table))
+		if table.keepDays > 0 {
+			errs = append(errs, ps.dropExpiredPartitions(table))
 		}
 	}
+	return errors.Join(errs...)
 }
 
-// createFuturePartitions creates partitions for the next 30 days
-func (ps *PartitionScheduler) createFuturePartitions() {
-	logging.L().Info("creating future partitions")
-
-	for i := 1; i <= partitionDaysAhead; i++ {
-		date := nowFunc().AddDate(0, 0, i)
-		partitionName := fmt.Sprintf("website_event_%s", date.Format("2006_01_02"))
-		startDate := date.Format("2006-01-02")
-		endDate := date.AddDate(0, 0, 1).Format("2006-01-02")
-
-		query := fmt.Sprintf(`
-			CREATE TABLE IF NOT EXISTS %s
-			PARTITION OF website_event
-			FOR VALUES FROM ('%s') TO ('%s')
-		`, partitionName, startDate, endDate)
-
-		_, err := DB.Exec(query)
-		if err != nil {
-			logging.L().Warn("failed to create partition", slog.String("partition", partitionName), slog.Any("error", err))
-			continue
-		}
-
-		logging.L().Info("created partition", slog.String("partition", partitionName))
+// createPartitions fills in any missing partition from a week back (after
+// downtime) to partitionDaysAhead ahead
+func (ps *PartitionScheduler) createPartitions(table partitionedTable) error {
+	var created int
+	err := DB.QueryRow(`SELECT ensure_daily_partitions($1, $2, $3)`,
+		table.name, partitionDaysBack, partitionDaysAhead).Scan(&created)
+	if err != nil {
+		logging.L().Warn("failed to create partitions", slog.String("table", table.name), slog.Any("error", err))
+		return fmt.Errorf("failed to create %s partitions: %w", table.name, err)
+	}
+	if created > 0 {
+		logging.L().Info("created partitions", slog.String("table", table.name), slog.Int("count", created))
 	}
+	return nil
 }
 
-// schedulePartitionCleanup removes partitions older than 90 days
-func (ps *PartitionScheduler) schedulePartitionCleanup() {
-	ticker := time.NewTicker(7 * 24 * time.Hour) // Weekly
-	defer ticker.Stop()
-
-	for {
-		select {
-		case <-ticker.C:
-			ps.cleanupOldPartitions()
-		case <-ps.stopChan:
-			return
+// dropExpiredPartitions detaches and drops the partitions of days before the
+// retention cutoff. Detaching concurrently doesn't block inserts into the
+// parent table.
+func (ps *PartitionScheduler) dropExpiredPartitions(table partitionedTable) error {
+	cutoff := nowFunc().UTC().AddDate(0, 0, -table.keepDays)
+	if table.compact {
+		compacted, ok, err := compactBefore(cutoff)
+		if !ok {
+			return err
+		}
+		cutoff = compacted
+	}
+	if table.timescale {
+		hypertable, err := EventsAreHypertable(context.Background(), DB)
+		if err != nil {
+			logging.L().Warn("failed to check event storage", slog.Any("error", err))
+			return fmt.Errorf("failed to check event storage: %w", err)
+		}
+		if hypertable {
+			return dropExpiredChunks(table, cutoff)
 		}
 	}
-}
-
-// cleanupOldPartitions drops partitions older than retention period
-func (ps *PartitionScheduler) cleanupOldPartitions() {
-	cutoffDate := nowFunc().AddDate(0, 0, -retentionPeriodDays)
-
-	logging.L().Info("cleaning up old partitions", slog.String("cutoff", cutoffDate.Format("2006-01-02")))
 
-	// Find old partitions
 	rows, err := DB.Query(`
-		SELECT tablename
-		FROM pg_tables
-		WHERE schemaname = 'public'
-		  AND tablename LIKE 'website_event_%'
-		  AND tablename < $1
-		ORDER BY tablename
-	`, fmt.Sprintf("website_event_%s", cutoffDate.Format("2006_01_02")))
-
+		SELECT c.relname
+		FROM pg_inherits i
+		JOIN pg_class c ON c.oid = i.inhrelid
+		WHERE i.inhparent = $1::regclass
+		  AND c.relname ~ $2
+		  AND c.relname < $3
+		  AND (NOT $4 OR EXISTS (
+		      SELECT 1 FROM event_archive a
+		      WHERE a.month = DATE_TRUNC('month', TO_DATE(RIGHT(c.relname, 10), 'YYYY_MM_DD'))::DATE
+		  ))
+		ORDER BY c.relname
+	`, table.name, "^"+table.name+`_\d{4}_\d{2}_\d{2}$`, fmt.Sprintf("%s_%s", table.name, cutoff.Format("2006_01_02")), table.archived)
 	if err != nil {
-		logging.L().Warn("failed to query old partitions", slog.Any("error", err))
-		return
+		logging.L().Warn("failed to query expired partitions", slog.String("table", table.name), slog.Any("error", err))
+		return fmt.Errorf("failed to query expired %s partitions: %w", table.name, err)
 	}
-	defer func() {
-		if err := rows.Close(); err != nil {
-			logging.L().Warn("failed to close partition rows", slog.Any("error", err))
-		}
-	}()
-
-	droppedCount := 0
+	var expired []string
 	for rows.Next() {
-		var tableName string
-		if err := rows.Scan(&tableName); err != nil {
+		var name string
+		if err := rows.Scan(&name); err == nil {
+			expired = append(expired, name)
+		}
+	}
+	_ = rows.Close()
+
+	dropped := 0
+	var errs []error
+	for _, name := range expired {
+		// CONCURRENTLY can't run in a transaction, so each statement commits
+		// on its own
+		if _, err := DB.Exec(fmt.Sprintf("ALTER TABLE %s DETACH PARTITION %s CONCURRENTLY",
+			pq.QuoteIdentifier(table.name), pq.QuoteIdentifier(name))); err != nil {
+			logging.L().Warn("failed to detach partition", slog.String("partition", name), slog.Any("error", err))
+			errs = append(errs, fmt.Errorf("failed to detach %s: %w", name, err))
 			continue
 		}
-
-		// Drop old partition
-		query := fmt.Sprintf("DROP TABLE IF EXISTS %s", tableName)
-		_, err := DB.Exec(query)
-		if err != nil {
-			logging.L().Warn("failed to drop partition", slog.String("partition", tableName), slog.Any("error", err))
+		if _, err := DB.Exec(fmt.Sprintf("DROP TABLE IF EXISTS %s", pq.QuoteIdentifier(name))); err != nil {
+			logging.L().Warn("failed to drop partition", slog.String("partition", name), slog.Any("error", err))
+			errs = append(errs, fmt.Errorf("failed to drop %s: %w", name, err))
 			continue
 		}
-
-		logging.L().Info("dropped old partition", slog.String("partition", tableName))
-		droppedCount++
+		dropped++
 	}
-
-	if droppedCount > 0 {
-		logging.L().Info("partition cleanup complete", slog.Int("dropped_count", droppedCount))
+	if dropped > 0 {
+		logging.L().Info("dropped expired partitions", slog.String("table", table.name), slog.Int("count", dropped),
+			slog.String("cutoff", cutoff.Format("2006-01-02")))
 	}
+	return errors.Join(errs...)
 }
 
-// MaterializedViewScheduler manages concurrent refreshes
-type MaterializedViewScheduler struct {
-	stopChan chan struct{}
+// dropExpiredChunks drops the hypertable chunks of days before the cutoff
+func dropExpiredChunks(table partitionedTable, cutoff time.Time) error {
+	var dropped int
+	if err := DB.QueryRow(`SELECT drop_expired_event_chunks($1, $2)`, cutoff, table.archived).Scan(&dropped); err != nil {
+		logging.L().Warn("failed to drop expired chunks", slog.String("table", table.name), slog.Any("error", err))
+		return fmt.Errorf("failed to drop expired %s chunks: %w", table.name, err)
+	}
+	if dropped > 0 {
+		logging.L().Info("dropped expired chunks", slog.String("table", table.name), slog.Int("count", dropped),
+			slog.String("cutoff", cutoff.Format("2006-01-02")))
+	}
+	return nil
 }
 
-// NewMaterializedViewScheduler creates a new refresh scheduler
-func NewMaterializedViewScheduler() *MaterializedViewScheduler {
-	return &MaterializedViewScheduler{
-		stopChan: make(chan struct{}),
+// compactBefore summarizes raw events before cutoff and returns the UTC day
+// before which their partitions may be dropped. Days not yet covered by
+// rollups and sketches are kept for a later run.
+func compactBefore(cutoff time.Time) (time.Time, bool, error) {
+	var compacted sql.NullTime
+	if err := DB.QueryRow(`SELECT compact_events($1)`, cutoff).Scan(&compacted); err != nil {
+		logging.L().Warn("failed to compact expired events", slog.Any("error", err))
+		return time.Time{}, false, fmt.Errorf("failed to compact expired events: %w", err)
+	}
+	if !compacted.Valid {
+		logging.L().Info("expired events are kept until rollups catch up")
+		return time.Time{}, false, nil
 	}
+	if compacted.Time.Before(cutoff) {
+		return compacted.Time.UTC().Truncate(24 * time.Hour), true, nil
+	}
+	return cutoff, true, nil
 }
 
-// Start begins the refresh tasks
-func (mvs *MaterializedViewScheduler) Start() {
-	logging.L().Info("starting materialized view refresh scheduler")
+// RollupScheduler keeps event_rollup current by rolling up completed hours
+// and days
+type RollupScheduler struct {
+	interval time.Duration
+}
 
-	// Real-time stats: every minute
-	go mvs.scheduleRefresh("realtime_website_stats", 1*time.Minute)
+// NewRollupScheduler creates a rollup scheduler that refreshes every 5
+// minutes
+func NewRollupScheduler() *RollupScheduler {
+	return &RollupScheduler{interval: 5 * time.Minute}
+}
 
-	// Hourly stats: every 5 minutes
-	go mvs.scheduleRefresh("hourly_website_stats", 5*time.Minute)
+// Job refreshes the rollups on every interval. Backfills take the same lock.
+func (rs *RollupScheduler) Job() Job {
+	return Job{
+		Name:        "rollups",
+		Description: "Roll completed hours and days into rollups and visitor sketches",
+		Every:       rs.interval,
+		Run:         func(context.Context) error { return rs.refresh() },
+	}
+}
 
-	// Daily stats: every hour
-	go mvs.scheduleRefresh("daily_website_stats", 1*time.Hour)
+// refresh rolls up and sketches whatever completed since the last run. A
+// fresh install backfills history a month per run until it catches up.
+func (rs *RollupScheduler) refresh() error {
+	jobs := []struct{ name, query string }{
+		{"event rollups", `SELECT refresh_event_rollups()`},
+		{"visitor sketches", `SELECT refresh_visitor_sketches()`},
+	}
+	var errs []error
+	for _, job := range jobs {
+		start := time.Now()
+
+		var rowsWritten int
+		if err := DB.QueryRow(job.query).Scan(&rowsWritten); err != nil {
+			logging.L().Warn("failed to refresh "+job.name, slog.Any("error", err))
+			errs = append(errs, fmt.Errorf("failed to refresh %s: %w", job.name, err))
+			continue
+		}
+		if rowsWritten > 0 {
+			logging.L().Info("refreshed "+job.name, slog.Int("rows", rowsWritten),
+				slog.Duration("duration", time.Since(start)))
+		}
+	}
+	return errors.Join(errs...)
 }
 
-// Stop gracefully stops the scheduler
-func (mvs *MaterializedViewScheduler) Stop() {
-	close(mvs.stopChan)
+// MaterializedViewScheduler manages concurrent refreshes
+type MaterializedViewScheduler struct{}
+
+// NewMaterializedViewScheduler creates a new refresh scheduler
+func NewMaterializedViewScheduler() *MaterializedViewScheduler {
+	return &MaterializedViewScheduler{}
 }
 
-// scheduleRefresh refreshes a materialized view at the specified interval
-func (mvs *MaterializedViewScheduler) scheduleRefresh(viewName string, interval time.Duration) {
-	ticker := time.NewTicker(interval)
-	defer ticker.Stop()
+// Jobs returns a refresh job per materialized view
+func (mvs *MaterializedViewScheduler) Jobs() []Job {
+	// Real-time stats: every minute
+	jobs := []Job{mvs.refreshJob("realtime-stats", "realtime_website_stats", 1*time.Minute)}
+
+	// With events in a hypertable, the hourly and daily stats are continuous
+	// aggregates refreshed by TimescaleDB
+	hypertable, err := EventsAreHypertable(context.Background(), DB)
+	if err != nil {
+		logging.L().Warn("failed to check event storage", slog.Any("error", err))
+	}
+	if hypertable {
+		return jobs
+	}
 
-	// Initial refresh on startup
-	mvs.refreshView(viewName)
+	return append(jobs,
+		// Hourly stats: every 5 minutes
+		mvs.refreshJob("hourly-stats", "hourly_website_stats", 5*time.Minute),
+		// Daily stats: every hour
+		mvs.refreshJob("daily-stats", "daily_website_stats", 1*time.Hour),
+	)
+}
 
-	for {
-		select {
-		case <-ticker.C:
-			mvs.refreshView(viewName)
-		case <-mvs.stopChan:
-			return
-		}
+// refreshJob refreshes a materialized view at the specified interval
+func (mvs *MaterializedViewScheduler) refreshJob(name, viewName string, interval time.Duration) Job {
+	return Job{
+		Name:        name,
+		Description: "Refresh the " + viewName + " materialized view",
+		Every:       interval,
+		Lock:        "view:" + viewName,
+		Run:         func(context.Context) error { return mvs.refreshView(viewName) },
 	}
 }
 
 // refreshView performs a concurrent refresh of the materialized view
-func (mvs *MaterializedViewScheduler) refreshView(viewName string) {
+func (mvs *MaterializedViewScheduler) refreshView(viewName string) error {
 	start := time.Now()
 
 	query := fmt.Sprintf("REFRESH MATERIALIZED VIEW CONCURRENTLY %s", viewName)
@@ -214,10 +292,11 @@ func (mvs *MaterializedViewScheduler) refreshView(viewName string) {
 
 	if err != nil {
 		logging.L().Warn("failed to refresh materialized view", slog.String("view", viewName), slog.Any("error", err))
-		return
+		return fmt.Errorf("failed to refresh %s: %w", viewName, err)
 	}
 
 	logging.L().Info("refreshed materialized view", slog.String("view", viewName), slog.Duration("duration", duration))
+	return nil
 }
 
 // GetMaterializedViewStats returns refresh statistics
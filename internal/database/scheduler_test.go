@@ -72,7 +72,7 @@ func TestMaterializedViewSchedulerRefreshView(t *testing.T) {
 		WillReturnResult(sqlmock.NewResult(0, 0))
 
 	mvs := &MaterializedViewScheduler{}
-	mvs.refreshView("test_view")
+	assert.NoError(t, mvs.refreshView("test_view"))
 
 	require.NoError(t, mock.ExpectationsWereMet())
 }
@@ -85,74 +85,169 @@ func TestMaterializedViewSchedulerRefreshViewError(t *testing.T) {
 		WillReturnError(assert.AnError)
 
 	mvs := &MaterializedViewScheduler{}
-	mvs.refreshView("bad_view")
+	assert.ErrorContains(t, mvs.refreshView("bad_view"), "bad_view")
 
 	require.NoError(t, mock.ExpectationsWereMet())
 }
 
 func TestNewPartitionSchedulerInitializesFields(t *testing.T) {
-	ps := NewPartitionScheduler("postgres://example")
-	require.Equal(t, "postgres://example", ps.databaseURL)
-	require.NotNil(t, ps.stopChan)
+	ps := NewPartitionScheduler(180, false)
+	require.Len(t, ps.tables, 3)
+	assert.Equal(t, partitionedTable{name: "website_event", keepDays: 180, compact: true, timescale: true}, ps.tables[0])
+
+	job := ps.Job()
+	assert.Equal(t, "partitions", job.Name)
+	assert.Equal(t, 24*time.Hour, job.Every)
 }
 
-func TestNewMaterializedViewSchedulerInitializesStopChan(t *testing.T) {
-	mvs := NewMaterializedViewScheduler()
-	require.NotNil(t, mvs.stopChan)
+func TestMaterializedViewSchedulerJobs(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT events_are_hypertable").
+		WillReturnRows(sqlmock.NewRows([]string{"hypertable"}).AddRow(false))
+
+	jobs := NewMaterializedViewScheduler().Jobs()
+	require.Len(t, jobs, 3)
+	assert.Equal(t, "realtime-stats", jobs[0].Name)
+	assert.Equal(t, "view:realtime_website_stats", jobs[0].lock())
+	assert.Equal(t, time.Hour, jobs[2].Every)
+
+	require.NoError(t, mock.ExpectationsWereMet())
 }
 
-func TestPartitionSchedulerCreatesFuturePartitions(t *testing.T) {
+func TestPartitionSchedulerCreatesPartitions(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT ensure_daily_partitions").
+		WithArgs("website_event", partitionDaysBack, partitionDaysAhead).
+		WillReturnRows(sqlmock.NewRows([]string{"created"}).AddRow(3))
+
+	ps := &PartitionScheduler{}
+	ps.createPartitions(partitionedTable{name: "website_event"})
+
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPartitionSchedulerDropsExpiredPartitions(t *testing.T) {
 	mock, cleanup := withMockDB(t)
 	defer cleanup()
 
-	partitionDaysAhead = 2
 	nowFunc = func() time.Time {
-		return time.Date(2025, time.January, 1, 0, 0, 0, 0, time.UTC)
+		return time.Date(2025, time.March, 1, 0, 0, 0, 0, time.UTC)
 	}
-	t.Cleanup(func() {
-		partitionDaysAhead = 30
-		nowFunc = time.Now
-	})
-
-	mock.ExpectExec("CREATE TABLE IF NOT EXISTS website_event_2025_01_02").
+	t.Cleanup(func() { nowFunc = time.Now })
+
+	mock.ExpectQuery("SELECT c.relname").
+		WithArgs("website_event", `^website_event_\d{4}_\d{2}_\d{2}$`, "website_event_2025_01_30", false).
+		WillReturnRows(sqlmock.NewRows([]string{"relname"}).
+			AddRow("website_event_2025_01_01").
+			AddRow("website_event_2025_01_02"))
+	mock.ExpectExec(`ALTER TABLE "website_event" DETACH PARTITION "website_event_2025_01_01" CONCURRENTLY`).
 		WillReturnResult(sqlmock.NewResult(0, 0))
-	mock.ExpectExec("CREATE TABLE IF NOT EXISTS website_event_2025_01_03").
+	mock.ExpectExec(`DROP TABLE IF EXISTS "website_event_2025_01_01"`).
 		WillReturnResult(sqlmock.NewResult(0, 0))
+	// A failed detach keeps the partition
+	mock.ExpectExec(`ALTER TABLE "website_event" DETACH PARTITION "website_event_2025_01_02" CONCURRENTLY`).
+		WillReturnError(assert.AnError)
+
+	ps := &PartitionScheduler{}
+	ps.dropExpiredPartitions(partitionedTable{name: "website_event", keepDays: 30})
+
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPartitionSchedulerCompactsBeforeDropping(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	nowFunc = func() time.Time {
+		return time.Date(2025, time.March, 1, 12, 0, 0, 0, time.UTC)
+	}
+	t.Cleanup(func() { nowFunc = time.Now })
+
+	// Sketches only reach Jan 20, so later days keep their raw events
+	mock.ExpectQuery("SELECT compact_events").
+		WithArgs(time.Date(2025, time.January, 30, 12, 0, 0, 0, time.UTC)).
+		WillReturnRows(sqlmock.NewRows([]string{"compacted"}).AddRow(time.Date(2025, time.January, 20, 0, 0, 0, 0, time.UTC)))
+	mock.ExpectQuery("SELECT c.relname").
+		WithArgs("website_event", `^website_event_\d{4}_\d{2}_\d{2}$`, "website_event_2025_01_20", false).
+		WillReturnRows(sqlmock.NewRows([]string{"relname"}))
 
 	ps := &PartitionScheduler{}
-	ps.createFuturePartitions()
+	ps.dropExpiredPartitions(partitionedTable{name: "website_event", keepDays: 30, compact: true})
 
 	require.NoError(t, mock.ExpectationsWereMet())
 }
 
-func TestPartitionSchedulerCleanupOldPartitions(t *testing.T) {
+func TestPartitionSchedulerDropsExpiredChunksOfHypertables(t *testing.T) {
 	mock, cleanup := withMockDB(t)
 	defer cleanup()
 
-	retentionPeriodDays = 30
 	nowFunc = func() time.Time {
 		return time.Date(2025, time.March, 1, 0, 0, 0, 0, time.UTC)
 	}
-	t.Cleanup(func() {
-		retentionPeriodDays = 90
-		nowFunc = time.Now
-	})
+	t.Cleanup(func() { nowFunc = time.Now })
 
-	rows := sqlmock.NewRows([]string{"tablename"}).
-		AddRow("website_event_2025_01_01").
-		AddRow("website_event_2025_01_02")
+	mock.ExpectQuery("SELECT events_are_hypertable").
+		WillReturnRows(sqlmock.NewRows([]string{"hypertable"}).AddRow(true))
+	mock.ExpectQuery("SELECT drop_expired_event_chunks").
+		WithArgs(time.Date(2025, time.January, 30, 0, 0, 0, 0, time.UTC), true).
+		WillReturnRows(sqlmock.NewRows([]string{"dropped"}).AddRow(2))
 
-	mock.ExpectQuery("SELECT\\s+tablename").
-		WithArgs("website_event_2025_01_30").
-		WillReturnRows(rows)
+	ps := &PartitionScheduler{}
+	ps.dropExpiredPartitions(partitionedTable{name: "website_event", keepDays: 30, archived: true, timescale: true})
 
-	mock.ExpectExec("DROP TABLE IF EXISTS website_event_2025_01_01").
-		WillReturnResult(sqlmock.NewResult(0, 0))
-	mock.ExpectExec("DROP TABLE IF EXISTS website_event_2025_01_02").
-		WillReturnResult(sqlmock.NewResult(0, 0))
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPartitionSchedulerKeepsEventsUntilRollupsCatchUp(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT compact_events").
+		WillReturnRows(sqlmock.NewRows([]string{"compacted"}).AddRow(nil))
 
 	ps := &PartitionScheduler{}
-	ps.cleanupOldPartitions()
+	ps.dropExpiredPartitions(partitionedTable{name: "website_event", keepDays: 30, compact: true})
+
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPartitionSchedulerKeepsEventsWithoutRetention(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	// Only partition creation runs for a table kept forever
+	mock.ExpectQuery("SELECT ensure_daily_partitions").
+		WithArgs("website_event", partitionDaysBack, partitionDaysAhead).
+		WillReturnRows(sqlmock.NewRows([]string{"created"}).AddRow(0))
+
+	ps := &PartitionScheduler{tables: []partitionedTable{{name: "website_event"}}}
+	assert.NoError(t, ps.maintain())
+
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRollupSchedulerRefresh(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT refresh_event_rollups\\(\\)").
+		WillReturnRows(sqlmock.NewRows([]string{"rows"}).AddRow(42))
+	mock.ExpectQuery("SELECT refresh_visitor_sketches\\(\\)").
+		WillReturnRows(sqlmock.NewRows([]string{"rows"}).AddRow(7))
+	// A failed rollup doesn't hold back the sketches
+	mock.ExpectQuery("SELECT refresh_event_rollups\\(\\)").
+		WillReturnError(assert.AnError)
+	mock.ExpectQuery("SELECT refresh_visitor_sketches\\(\\)").
+		WillReturnRows(sqlmock.NewRows([]string{"rows"}).AddRow(0))
+
+	rs := NewRollupScheduler()
+	assert.Equal(t, 5*time.Minute, rs.interval)
+	assert.NoError(t, rs.refresh())
+	assert.ErrorContains(t, rs.refresh(), "event rollups")
 
 	require.NoError(t, mock.ExpectationsWereMet())
 }
@@ -0,0 +1,195 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"database/sql/driver"
+	"errors"
+	"fmt"
+	"strings"
+	"time"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// StatsQuerySettings bounds the queries behind dashboards and the stats API.
+// Zero values disable the limit.
+type StatsQuerySettings struct {
+	Timeout       time.Duration // Cancel a stats query running longer than this
+	SlowThreshold time.Duration // Log stats queries running longer than this
+}
+
+var statsQuerySettings StatsQuerySettings
+
+// statsLogger is swapped in tests
+var statsLogger = logging.L
+
+// SetStatsQuerySettings configures the limits applied by QueryStats and
+// QueryRowStats
+func SetStatsQuerySettings(settings StatsQuerySettings) {
+	statsQuerySettings = settings
+}
+
+// StatsContext derives the context a stats query runs under. Use it for
+// stats work that doesn't go through QueryStats, such as reports.
+func StatsContext(ctx context.Context) (context.Context, context.CancelFunc) {
+	if statsQuerySettings.Timeout <= 0 {
+		return context.WithCancel(ctx)
+	}
+	return context.WithTimeout(ctx, statsQuerySettings.Timeout)
+}
+
+// StatsRow is a *sql.Row whose timeout ends once it is scanned
+type StatsRow struct {
+	row    *sql.Row
+	err    error // Set when the query couldn't start
+	cancel context.CancelFunc
+	done   func(error)
+}
+
+// Scan copies the columns into dest like sql.Row.Scan
+func (r *StatsRow) Scan(dest ...any) error {
+	defer r.cancel()
+	err := r.err
+	if err == nil {
+		err = r.row.Scan(dest...)
+	}
+	r.done(err)
+	return err
+}
+
+// StatsRows is a *sql.Rows whose timeout ends once it is closed
+type StatsRows struct {
+	*sql.Rows
+	cancel context.CancelFunc
+	done   func(error)
+}
+
+// Close closes the rows and releases the query's timeout
+func (r *StatsRows) Close() error {
+	defer r.cancel()
+	err := r.Rows.Close()
+	if err == nil {
+		err = r.Rows.Err()
+	}
+	r.done(err)
+	return err
+}
+
+// QueryStats runs a stats query on the reader under the stats timeout. The
+// timeout covers reading the rows, so callers must close them. In tenant
+// isolation mode the query only sees the websites in ctx.
+func QueryStats(ctx context.Context, query string, args ...any) (*StatsRows, error) {
+	ctx, cancel := StatsContext(ctx)
+	done := trackStatsQuery(ctx, query, args)
+	db, release, err := StatsQuerier(ctx)
+	if err != nil {
+		cancel()
+		done(err)
+		return nil, err
+	}
+	rows, err := db.QueryContext(ctx, query, args...)
+	if err != nil {
+		release()
+		cancel()
+		done(err)
+		return nil, err
+	}
+	return &StatsRows{Rows: rows, cancel: releaseThen(release, cancel), done: done}, nil
+}
+
+// QueryRowStats runs a single-row stats query on the reader under the stats
+// timeout, which ends when the row is scanned. In tenant isolation mode the
+// query only sees the websites in ctx.
+func QueryRowStats(ctx context.Context, query string, args ...any) *StatsRow {
+	ctx, cancel := StatsContext(ctx)
+	done := trackStatsQuery(ctx, query, args)
+	db, release, err := StatsQuerier(ctx)
+	if err != nil {
+		return &StatsRow{err: err, cancel: cancel, done: done}
+	}
+	return &StatsRow{row: db.QueryRowContext(ctx, query, args...), cancel: releaseThen(release, cancel), done: done}
+}
+
+// TrackStats wraps the querier from StatsQuerier so the queries other
+// packages run on it, such as reports, are logged like QueryStats when slow
+// or timed out. Pair it with StatsContext for the timeout. The duration
+// covers running the query, not reading its rows.
+func TrackStats(db Querier) Querier {
+	return trackedQuerier{db}
+}
+
+type trackedQuerier struct {
+	db Querier
+}
+
+func (q trackedQuerier) QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error) {
+	done := trackStatsQuery(ctx, query, args)
+	rows, err := q.db.QueryContext(ctx, query, args...)
+	done(err)
+	return rows, err
+}
+
+func (q trackedQuerier) QueryRowContext(ctx context.Context, query string, args ...any) *sql.Row {
+	done := trackStatsQuery(ctx, query, args)
+	row := q.db.QueryRowContext(ctx, query, args...)
+	done(row.Err())
+	return row
+}
+
+// releaseThen releases a stats querier before cancelling its context, so a
+// tenant transaction is rolled back rather than torn down by the cancel
+func releaseThen(release func(), cancel context.CancelFunc) context.CancelFunc {
+	return func() {
+		release()
+		cancel()
+	}
+}
+
+// trackStatsQuery returns a callback that logs the query with its parameters
+// (website, range, filters) when it timed out or ran past the threshold
+func trackStatsQuery(ctx context.Context, query string, args []any) func(error) {
+	start := time.Now()
+	return func(err error) {
+		elapsed := time.Since(start)
+		timedOut := errors.Is(ctx.Err(), context.DeadlineExceeded)
+		threshold := statsQuerySettings.SlowThreshold
+		if !timedOut && (threshold <= 0 || elapsed < threshold) {
+			return
+		}
+
+		msg := "slow stats query"
+		if timedOut {
+			msg = "stats query timed out"
+		}
+		statsLogger().Warn(msg,
+			slog.Duration("duration", elapsed),
+			slog.String("query", compactQuery(query)),
+			slog.Any("args", formatArgs(args)),
+			slog.Any("error", err),
+		)
+	}
+}
+
+// compactQuery folds a query onto one line for the log
+func compactQuery(query string) string {
+	return strings.Join(strings.Fields(query), " ")
+}
+
+func formatArgs(args []any) []string {
+	out := make([]string, len(args))
+	for i, arg := range args {
+		if v, ok := arg.(driver.Valuer); ok {
+			if value, err := v.Value(); err == nil {
+				arg = value
+			}
+		}
+		if b, ok := arg.([]byte); ok {
+			arg = string(b)
+		}
+		out[i] = fmt.Sprint(arg)
+	}
+	return out
+}
@@ -0,0 +1,124 @@
+package database
+
+import (
+	"bytes"
+	"context"
+	"testing"
+	"time"
+
+	"log/slog"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func withStatsSettings(t *testing.T, settings StatsQuerySettings) *bytes.Buffer {
+	t.Helper()
+	var buf bytes.Buffer
+	originalSettings, originalLogger := statsQuerySettings, statsLogger
+	statsQuerySettings = settings
+	statsLogger = func() *slog.Logger { return slog.New(slog.NewTextHandler(&buf, nil)) }
+	t.Cleanup(func() {
+		statsQuerySettings, statsLogger = originalSettings, originalLogger
+	})
+	return &buf
+}
+
+func TestQueryStatsLogsSlowQueriesWithParameters(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	logs := withStatsSettings(t, StatsQuerySettings{SlowThreshold: 10 * time.Millisecond})
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("SELECT \\* FROM get_breakdown").
+		WillDelayFor(20 * time.Millisecond).
+		WillReturnRows(sqlmock.NewRows([]string{"name", "count"}).AddRow("/", 3))
+
+	rows, err := QueryStats(context.Background(), `
+		SELECT * FROM get_breakdown($1, $2, $3)
+	`, websiteID, 30, pq.Array([]string{"US"}))
+	require.NoError(t, err)
+	for rows.Next() {
+	}
+	require.NoError(t, rows.Close())
+
+	out := logs.String()
+	assert.Contains(t, out, "slow stats query")
+	assert.Contains(t, out, `query="SELECT * FROM get_breakdown($1, $2, $3)"`)
+	assert.Contains(t, out, websiteID.String())
+	assert.Contains(t, out, `{\"US\"}`, "array filters are logged by value")
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestTrackStatsLogsSlowQueries(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	logs := withStatsSettings(t, StatsQuerySettings{SlowThreshold: 10 * time.Millisecond})
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("SELECT \\* FROM get_timeseries").
+		WillDelayFor(20 * time.Millisecond).
+		WillReturnRows(sqlmock.NewRows([]string{"bucket", "views"}))
+	mock.ExpectQuery("SELECT name FROM website").
+		WillReturnRows(sqlmock.NewRows([]string{"name"}).AddRow("demo"))
+
+	db := TrackStats(DB)
+	rows, err := db.QueryContext(context.Background(), `SELECT * FROM get_timeseries($1)`, websiteID)
+	require.NoError(t, err)
+	require.NoError(t, rows.Close())
+	var name string
+	require.NoError(t, db.QueryRowContext(context.Background(), `SELECT name FROM website`).Scan(&name))
+
+	out := logs.String()
+	assert.Contains(t, out, "slow stats query")
+	assert.Contains(t, out, websiteID.String())
+	assert.NotContains(t, out, "SELECT name FROM website")
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestQueryRowStatsSkipsFastQueries(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	logs := withStatsSettings(t, StatsQuerySettings{SlowThreshold: time.Second})
+
+	mock.ExpectQuery("SELECT \\* FROM get_dashboard_stats").
+		WillReturnRows(sqlmock.NewRows([]string{"pageviews"}).AddRow(12))
+
+	var pageviews int
+	require.NoError(t, QueryRowStats(context.Background(), `SELECT * FROM get_dashboard_stats($1)`, uuid.New()).Scan(&pageviews))
+	assert.Equal(t, 12, pageviews)
+	assert.Empty(t, logs.String())
+}
+
+func TestQueryRowStatsCancelsAfterTimeout(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	logs := withStatsSettings(t, StatsQuerySettings{Timeout: 10 * time.Millisecond})
+
+	mock.ExpectQuery("SELECT \\* FROM get_dashboard_stats").
+		WillDelayFor(time.Second).
+		WillReturnRows(sqlmock.NewRows([]string{"pageviews"}).AddRow(12))
+
+	var pageviews int
+	err := QueryRowStats(context.Background(), `SELECT * FROM get_dashboard_stats($1)`, uuid.New()).Scan(&pageviews)
+	assert.Error(t, err)
+	assert.Contains(t, logs.String(), "stats query timed out")
+}
+
+func TestStatsContext(t *testing.T) {
+	withStatsSettings(t, StatsQuerySettings{})
+	ctx, cancel := StatsContext(context.Background())
+	_, hasDeadline := ctx.Deadline()
+	cancel()
+	assert.False(t, hasDeadline, "no timeout configured")
+
+	withStatsSettings(t, StatsQuerySettings{Timeout: time.Minute})
+	ctx, cancel = StatsContext(context.Background())
+	defer cancel()
+	deadline, hasDeadline := ctx.Deadline()
+	assert.True(t, hasDeadline)
+	assert.WithinDuration(t, time.Now().Add(time.Minute), deadline, time.Second)
+}
@@ -0,0 +1,122 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"sync"
+	"sync/atomic"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+)
+
+// ErrNoTenantScope is returned for a stats query in tenant isolation mode
+// when nothing said which websites it may read
+var ErrNoTenantScope = errors.New("stats query has no tenant scope")
+
+// tenantIsolation is set once EnableTenantIsolation succeeds
+var tenantIsolation atomic.Bool
+
+// Querier runs queries on a *sql.DB or inside a *sql.Tx
+type Querier interface {
+	QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error)
+	QueryRowContext(ctx context.Context, query string, args ...any) *sql.Row
+}
+
+type tenantContextKey struct{}
+
+// tenantScope holds the websites a request's stats queries may read, either
+// fixed or looked up on the first stats query
+type tenantScope struct {
+	once       sync.Once
+	resolve    func(context.Context) ([]uuid.UUID, error)
+	websiteIDs []uuid.UUID
+	err        error
+}
+
+func (s *tenantScope) websites(ctx context.Context) ([]uuid.UUID, error) {
+	if s.resolve != nil {
+		s.once.Do(func() { s.websiteIDs, s.err = s.resolve(ctx) })
+	}
+	return s.websiteIDs, s.err
+}
+
+// EnableTenantIsolation protects every table with a website_id column with
+// row-level security and from then on runs stats queries as the
+// kaunta_tenant role, confined to the websites in their context. It returns
+// the tables newly protected. Creating the role needs CREATEROLE.
+func EnableTenantIsolation(ctx context.Context, db *sql.DB) (int, error) {
+	var protected int
+	if err := db.QueryRowContext(ctx, `SELECT enable_tenant_isolation()`).Scan(&protected); err != nil {
+		return 0, err
+	}
+	SetTenantIsolation(true)
+	return protected, nil
+}
+
+// SetTenantIsolation switches tenant isolation on or off without preparing
+// the database; EnableTenantIsolation does both
+func SetTenantIsolation(enabled bool) {
+	tenantIsolation.Store(enabled)
+}
+
+// TenantIsolationEnabled reports whether stats queries are confined to the
+// websites in their context
+func TenantIsolationEnabled() bool {
+	return tenantIsolation.Load()
+}
+
+// WithTenant confines the stats queries run under ctx to websiteIDs
+func WithTenant(ctx context.Context, websiteIDs ...uuid.UUID) context.Context {
+	return context.WithValue(ctx, tenantContextKey{}, &tenantScope{websiteIDs: websiteIDs})
+}
+
+// WithTenantResolver confines the stats queries run under ctx to the
+// websites resolve returns. It is called once, on the first stats query.
+func WithTenantResolver(ctx context.Context, resolve func(context.Context) ([]uuid.UUID, error)) context.Context {
+	return context.WithValue(ctx, tenantContextKey{}, &tenantScope{resolve: resolve})
+}
+
+// TenantWebsites returns the websites the stats queries run under ctx may
+// read, or ErrNoTenantScope when ctx doesn't say
+func TenantWebsites(ctx context.Context) ([]uuid.UUID, error) {
+	scope, ok := ctx.Value(tenantContextKey{}).(*tenantScope)
+	if !ok {
+		return nil, ErrNoTenantScope
+	}
+	return scope.websites(ctx)
+}
+
+// StatsQuerier returns where stats work under ctx runs and a func releasing
+// it. That is the reader, or in tenant isolation mode a read-only
+// transaction on it running as kaunta_tenant and limited to the websites in
+// ctx. Use it for stats work that doesn't go through QueryStats.
+func StatsQuerier(ctx context.Context) (Querier, func(), error) {
+	db := Reader()
+	if !tenantIsolation.Load() {
+		return db, func() {}, nil
+	}
+
+	websiteIDs, err := TenantWebsites(ctx)
+	if err != nil {
+		return nil, nil, err
+	}
+	ids := make([]string, len(websiteIDs))
+	for i, id := range websiteIDs {
+		ids[i] = id.String()
+	}
+
+	tx, err := db.BeginTx(ctx, &sql.TxOptions{ReadOnly: true})
+	if err != nil {
+		return nil, nil, err
+	}
+	if _, err := tx.ExecContext(ctx,
+		`SELECT set_config('role', 'kaunta_tenant', true), set_config('kaunta.tenant_websites', $1, true)`,
+		pq.Array(ids),
+	); err != nil {
+		_ = tx.Rollback()
+		return nil, nil, err
+	}
+	return tx, func() { _ = tx.Rollback() }, nil
+}
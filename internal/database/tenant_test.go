@@ -0,0 +1,127 @@
+package database
+
+import (
+	"context"
+	"fmt"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func withTenantIsolation(t *testing.T) {
+	t.Helper()
+	SetTenantIsolation(true)
+	t.Cleanup(func() { SetTenantIsolation(false) })
+}
+
+func TestEnableTenantIsolation(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	t.Cleanup(func() { SetTenantIsolation(false) })
+
+	mock.ExpectQuery("SELECT enable_tenant_isolation").
+		WillReturnRows(sqlmock.NewRows([]string{"protected"}).AddRow(21))
+
+	assert.False(t, TenantIsolationEnabled())
+	protected, err := EnableTenantIsolation(context.Background(), DB)
+	require.NoError(t, err)
+	assert.Equal(t, 21, protected)
+	assert.True(t, TenantIsolationEnabled())
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEnableTenantIsolationLeavesItOffOnError(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT enable_tenant_isolation").
+		WillReturnError(fmt.Errorf("permission denied to create role"))
+
+	_, err := EnableTenantIsolation(context.Background(), DB)
+	assert.Error(t, err)
+	assert.False(t, TenantIsolationEnabled())
+}
+
+func TestQueryStatsWithoutTenantIsolationUsesReader(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT \\* FROM get_breakdown").
+		WillReturnRows(sqlmock.NewRows([]string{"name"}).AddRow("/"))
+
+	rows, err := QueryStats(context.Background(), `SELECT * FROM get_breakdown($1)`, uuid.New())
+	require.NoError(t, err)
+	require.NoError(t, rows.Close())
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestQueryStatsRunsAsTenant(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	withTenantIsolation(t)
+
+	first, second := uuid.New(), uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectExec("SELECT set_config\\('role', 'kaunta_tenant', true\\), set_config\\('kaunta.tenant_websites', \\$1, true\\)").
+		WithArgs(fmt.Sprintf(`{"%s","%s"}`, first, second)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectQuery("SELECT \\* FROM get_breakdown").
+		WithArgs(first).
+		WillReturnRows(sqlmock.NewRows([]string{"name"}).AddRow("/"))
+	mock.ExpectRollback()
+
+	ctx := WithTenant(context.Background(), first, second)
+	rows, err := QueryStats(ctx, `SELECT * FROM get_breakdown($1)`, first)
+	require.NoError(t, err)
+	for rows.Next() {
+	}
+	require.NoError(t, rows.Close())
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestQueryRowStatsResolvesTenantOnce(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	withTenantIsolation(t)
+
+	websiteID := uuid.New()
+	resolved := 0
+	ctx := WithTenantResolver(context.Background(), func(context.Context) ([]uuid.UUID, error) {
+		resolved++
+		return []uuid.UUID{websiteID}, nil
+	})
+
+	for range 2 {
+		mock.ExpectBegin()
+		mock.ExpectExec("SELECT set_config").
+			WithArgs(fmt.Sprintf(`{"%s"}`, websiteID)).
+			WillReturnResult(sqlmock.NewResult(0, 1))
+		mock.ExpectQuery("SELECT \\* FROM get_dashboard_stats").
+			WillReturnRows(sqlmock.NewRows([]string{"pageviews"}).AddRow(12))
+		mock.ExpectRollback()
+
+		var pageviews int
+		require.NoError(t, QueryRowStats(ctx, `SELECT * FROM get_dashboard_stats($1)`, websiteID).Scan(&pageviews))
+		assert.Equal(t, 12, pageviews)
+	}
+	assert.Equal(t, 1, resolved)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestStatsQueriesWithoutTenantScopeFail(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+	withTenantIsolation(t)
+
+	_, err := QueryStats(context.Background(), `SELECT * FROM get_breakdown($1)`, uuid.New())
+	assert.ErrorIs(t, err, ErrNoTenantScope)
+
+	var pageviews int
+	err = QueryRowStats(context.Background(), `SELECT * FROM get_dashboard_stats($1)`, uuid.New()).Scan(&pageviews)
+	assert.ErrorIs(t, err, ErrNoTenantScope)
+
+	require.NoError(t, mock.ExpectationsWereMet(), "nothing reaches the database")
+}
@@ -0,0 +1,100 @@
+package database
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"time"
+)
+
+// ErrTimescaleUnavailable is returned when converting events on a server
+// without the TimescaleDB extension
+var ErrTimescaleUnavailable = errors.New("the timescaledb extension is not available on this server")
+
+// TimescaleStatus describes the TimescaleDB extension and how events are
+// stored
+type TimescaleStatus struct {
+	Available        bool   `json:"available"`                   // The server can install the extension
+	Version          string `json:"version,omitempty"`           // Installed version, empty when not installed
+	Hypertable       bool   `json:"hypertable"`                  // website_event is a hypertable
+	Chunks           int64  `json:"chunks,omitempty"`            // Chunks of website_event
+	CompressedChunks int64  `json:"compressed_chunks,omitempty"` // Chunks already compressed
+	Size             string `json:"size,omitempty"`              // Size of website_event including indexes
+}
+
+// EventsAreHypertable reports whether website_event has been converted to a
+// TimescaleDB hypertable
+func EventsAreHypertable(ctx context.Context, db *sql.DB) (bool, error) {
+	var hypertable bool
+	err := db.QueryRowContext(ctx, `SELECT events_are_hypertable()`).Scan(&hypertable)
+	return hypertable, err
+}
+
+// GetTimescaleStatus reports whether TimescaleDB is available and, once
+// events are a hypertable, how much of them is compressed
+func GetTimescaleStatus(ctx context.Context, db *sql.DB) (*TimescaleStatus, error) {
+	status := &TimescaleStatus{}
+	var version sql.NullString
+	err := db.QueryRowContext(ctx, `
+		SELECT installed_version FROM pg_available_extensions WHERE name = 'timescaledb'
+	`).Scan(&version)
+	if errors.Is(err, sql.ErrNoRows) {
+		return status, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	status.Available = true
+	status.Version = version.String
+
+	if status.Hypertable, err = EventsAreHypertable(ctx, db); err != nil || !status.Hypertable {
+		return status, err
+	}
+	err = db.QueryRowContext(ctx, `
+		SELECT COUNT(*), COUNT(*) FILTER (WHERE is_compressed),
+		       pg_size_pretty(hypertable_size('website_event'))
+		FROM timescaledb_information.chunks
+		WHERE hypertable_schema = 'public' AND hypertable_name = 'website_event'
+	`).Scan(&status.Chunks, &status.CompressedChunks, &status.Size)
+	if err != nil {
+		return nil, err
+	}
+	return status, nil
+}
+
+// ConvertEventsToHypertable installs TimescaleDB and moves website_event into
+// a hypertable whose chunks are compressed once older than compressAfter,
+// then fills the continuous aggregates that replace the hourly and daily
+// stats views. It returns the events copied, 0 when already converted.
+func ConvertEventsToHypertable(ctx context.Context, db *sql.DB, compressAfter time.Duration) (int64, error) {
+	var available bool
+	if err := db.QueryRowContext(ctx, `
+		SELECT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'timescaledb')
+	`).Scan(&available); err != nil {
+		return 0, err
+	}
+	if !available {
+		return 0, ErrTimescaleUnavailable
+	}
+
+	if _, err := db.ExecContext(ctx, `CREATE EXTENSION IF NOT EXISTS timescaledb`); err != nil {
+		return 0, fmt.Errorf("failed to install timescaledb: %w", err)
+	}
+
+	var copied int64
+	if err := db.QueryRowContext(ctx, `SELECT convert_events_to_hypertable(make_interval(secs => $1))`,
+		compressAfter.Seconds()).Scan(&copied); err != nil {
+		return 0, fmt.Errorf("failed to convert events: %w", err)
+	}
+
+	// Refreshing a continuous aggregate can't run inside the conversion's
+	// transaction; without it charts would aggregate raw events until the
+	// refresh policies catch up
+	for _, view := range []string{"hourly_website_stats", "daily_website_stats"} {
+		if _, err := db.ExecContext(ctx, fmt.Sprintf(`CALL refresh_continuous_aggregate('%s', NULL, NULL)`, view)); err != nil {
+			return copied, fmt.Errorf("failed to refresh %s: %w", view, err)
+		}
+	}
+	return copied, nil
+}
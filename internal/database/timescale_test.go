@@ -0,0 +1,79 @@
+package database
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestGetTimescaleStatus(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	// Not available on this server
+	mock.ExpectQuery("SELECT installed_version FROM pg_available_extensions").
+		WillReturnRows(sqlmock.NewRows([]string{"installed_version"}))
+	status, err := GetTimescaleStatus(context.Background(), DB)
+	require.NoError(t, err)
+	assert.Equal(t, &TimescaleStatus{}, status)
+
+	// Installed, events not converted
+	mock.ExpectQuery("SELECT installed_version FROM pg_available_extensions").
+		WillReturnRows(sqlmock.NewRows([]string{"installed_version"}).AddRow("2.22.0"))
+	mock.ExpectQuery("SELECT events_are_hypertable").
+		WillReturnRows(sqlmock.NewRows([]string{"hypertable"}).AddRow(false))
+	status, err = GetTimescaleStatus(context.Background(), DB)
+	require.NoError(t, err)
+	assert.Equal(t, &TimescaleStatus{Available: true, Version: "2.22.0"}, status)
+
+	// Converted
+	mock.ExpectQuery("SELECT installed_version FROM pg_available_extensions").
+		WillReturnRows(sqlmock.NewRows([]string{"installed_version"}).AddRow("2.22.0"))
+	mock.ExpectQuery("SELECT events_are_hypertable").
+		WillReturnRows(sqlmock.NewRows([]string{"hypertable"}).AddRow(true))
+	mock.ExpectQuery("FROM timescaledb_information.chunks").
+		WillReturnRows(sqlmock.NewRows([]string{"chunks", "compressed", "size"}).AddRow(40, 33, "1200 MB"))
+	status, err = GetTimescaleStatus(context.Background(), DB)
+	require.NoError(t, err)
+	assert.Equal(t, &TimescaleStatus{Available: true, Version: "2.22.0", Hypertable: true,
+		Chunks: 40, CompressedChunks: 33, Size: "1200 MB"}, status)
+
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestConvertEventsToHypertable(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT EXISTS").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(true))
+	mock.ExpectExec("CREATE EXTENSION IF NOT EXISTS timescaledb").
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectQuery("SELECT convert_events_to_hypertable").WithArgs(float64(7 * 24 * 3600)).
+		WillReturnRows(sqlmock.NewRows([]string{"copied"}).AddRow(1500))
+	mock.ExpectExec(`CALL refresh_continuous_aggregate\('hourly_website_stats', NULL, NULL\)`).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectExec(`CALL refresh_continuous_aggregate\('daily_website_stats', NULL, NULL\)`).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+
+	copied, err := ConvertEventsToHypertable(context.Background(), DB, 7*24*time.Hour)
+	require.NoError(t, err)
+	assert.Equal(t, int64(1500), copied)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestConvertEventsToHypertableWithoutExtension(t *testing.T) {
+	mock, cleanup := withMockDB(t)
+	defer cleanup()
+
+	mock.ExpectQuery("SELECT EXISTS").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(false))
+
+	_, err := ConvertEventsToHypertable(context.Background(), DB, time.Hour)
+	assert.ErrorIs(t, err, ErrTimescaleUnavailable)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
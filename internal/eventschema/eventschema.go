@@ -0,0 +1,258 @@
+// Package eventschema checks custom events against the events a website
+// declares: their names and the types of their properties. The collector
+// stores mismatched events anyway and counts them per day, so the dashboard's
+// Data quality tab shows tracking bugs without losing data.
+package eventschema
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"fmt"
+	"maps"
+	"slices"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// Property types
+const (
+	TypeString  = "string"
+	TypeNumber  = "number"
+	TypeBoolean = "boolean"
+)
+
+// Ways an event can fail to match its schema
+const (
+	IssueUnknownEvent    = "unknown_event"
+	IssueMissingProperty = "missing_property"
+	IssueWrongType       = "wrong_type"
+)
+
+const (
+	maxEventName    = 50
+	maxPropertyName = 100
+)
+
+// Schema maps event names to their properties, and each property to its
+// type: "string", "number" or "boolean", with a trailing "?" when optional.
+//
+//	{"signup": {"plan": "string", "trial": "boolean?"}}
+type Schema map[string]map[string]string
+
+// Issue is one way an event doesn't match its schema
+type Issue struct {
+	Kind     string `json:"issue"`
+	Property string `json:"property,omitempty"`
+	Expected string `json:"expected,omitempty"`
+	Received string `json:"received,omitempty"`
+}
+
+// Parse reads a schema from JSON and checks its names and types
+func Parse(data []byte) (Schema, error) {
+	var s Schema
+	if err := json.Unmarshal(data, &s); err != nil {
+		return nil, fmt.Errorf("invalid schema JSON: %w", err)
+	}
+	if err := s.Check(); err != nil {
+		return nil, err
+	}
+	return s, nil
+}
+
+// Check reports the first invalid event name, property name or type
+func (s Schema) Check() error {
+	for _, name := range slices.Sorted(maps.Keys(s)) {
+		if strings.TrimSpace(name) == "" || len(name) > maxEventName {
+			return fmt.Errorf("event name %q must be 1 to %d characters", name, maxEventName)
+		}
+		props := s[name]
+		for _, prop := range slices.Sorted(maps.Keys(props)) {
+			if prop == "" || len(prop) > maxPropertyName {
+				return fmt.Errorf("%s: property name %q must be 1 to %d characters", name, prop, maxPropertyName)
+			}
+			if _, _, ok := parseType(props[prop]); !ok {
+				return fmt.Errorf("%s.%s: unknown type %q (use string, number or boolean, with ? when optional)", name, prop, props[prop])
+			}
+		}
+	}
+	return nil
+}
+
+func parseType(t string) (typ string, optional, ok bool) {
+	typ, optional = strings.CutSuffix(t, "?")
+	switch typ {
+	case TypeString, TypeNumber, TypeBoolean:
+		return typ, optional, true
+	}
+	return typ, optional, false
+}
+
+// Validate checks an event against the schema. An empty schema accepts
+// everything. Properties the schema doesn't list are allowed, since
+// site-wide properties are sent with every event.
+func (s Schema) Validate(name string, props map[string]any) []Issue {
+	if len(s) == 0 {
+		return nil
+	}
+	declared, ok := s[name]
+	if !ok {
+		return []Issue{{Kind: IssueUnknownEvent}}
+	}
+
+	var issues []Issue
+	for _, prop := range slices.Sorted(maps.Keys(declared)) {
+		typ, optional, _ := parseType(declared[prop])
+		value, present := props[prop]
+		if !present || value == nil {
+			if !optional {
+				issues = append(issues, Issue{Kind: IssueMissingProperty, Property: prop, Expected: typ})
+			}
+			continue
+		}
+		if received := TypeOf(value); received != typ {
+			issues = append(issues, Issue{Kind: IssueWrongType, Property: prop, Expected: typ, Received: received})
+		}
+	}
+	return issues
+}
+
+// TypeOf names the JSON type of a decoded value
+func TypeOf(v any) string {
+	switch v.(type) {
+	case string:
+		return TypeString
+	case float64, float32, int, int64, json.Number:
+		return TypeNumber
+	case bool:
+		return TypeBoolean
+	case []any:
+		return "array"
+	case map[string]any:
+		return "object"
+	case nil:
+		return "null"
+	}
+	return "unknown"
+}
+
+// TypeScript renders the schema as a declaration typing the events of the
+// @kaunta/tracker package
+func (s Schema) TypeScript() string {
+	var b strings.Builder
+	b.WriteString("// Generated by kaunta website event-schema --format ts\nexport {};\n\n")
+	b.WriteString("declare module '@kaunta/tracker' {\n  interface Events {\n")
+	for _, name := range slices.Sorted(maps.Keys(s)) {
+		props := s[name]
+		if len(props) == 0 {
+			fmt.Fprintf(&b, "    %s: {};\n", strconv.Quote(name))
+			continue
+		}
+		fmt.Fprintf(&b, "    %s: {\n", strconv.Quote(name))
+		for _, prop := range slices.Sorted(maps.Keys(props)) {
+			typ, optional, _ := parseType(props[prop])
+			mark := ""
+			if optional {
+				mark = "?"
+			}
+			fmt.Fprintf(&b, "      %s%s: %s;\n", strconv.Quote(prop), mark, typ)
+		}
+		b.WriteString("    };\n")
+	}
+	b.WriteString("  }\n}\n")
+	return b.String()
+}
+
+// Load returns a website's schema, empty when it declares no events
+func Load(ctx context.Context, db *sql.DB, websiteID uuid.UUID) (Schema, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT event_name, properties
+		FROM event_schema
+		WHERE website_id = $1
+	`, websiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	s := Schema{}
+	for rows.Next() {
+		var name string
+		var raw []byte
+		if err := rows.Scan(&name, &raw); err != nil {
+			return nil, err
+		}
+		props := map[string]string{}
+		if err := json.Unmarshal(raw, &props); err != nil {
+			return nil, fmt.Errorf("invalid properties for event %q: %w", name, err)
+		}
+		s[name] = props
+	}
+	return s, rows.Err()
+}
+
+// Save replaces a website's schema; an empty schema removes it
+func Save(ctx context.Context, db *sql.DB, websiteID uuid.UUID, s Schema) error {
+	if err := s.Check(); err != nil {
+		return err
+	}
+
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if _, err := tx.ExecContext(ctx, `DELETE FROM event_schema WHERE website_id = $1`, websiteID); err != nil {
+		return err
+	}
+	for _, name := range slices.Sorted(maps.Keys(s)) {
+		props := s[name]
+		if props == nil {
+			props = map[string]string{}
+		}
+		raw, err := json.Marshal(props)
+		if err != nil {
+			return err
+		}
+		if _, err := tx.ExecContext(ctx, `
+			INSERT INTO event_schema (website_id, event_name, properties)
+			VALUES ($1, $2, $3)
+		`, websiteID, name, string(raw)); err != nil {
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+// Record counts the issues found in one event on the day of at, keeping the
+// event's properties as the latest sample
+func Record(ctx context.Context, db *sql.DB, websiteID uuid.UUID, name string, issues []Issue, props map[string]any, at time.Time) error {
+	var sample sql.NullString
+	if len(props) > 0 {
+		if raw, err := json.Marshal(props); err == nil {
+			sample = sql.NullString{String: string(raw), Valid: true}
+		}
+	}
+	day := at.UTC().Format(time.DateOnly)
+
+	for _, issue := range issues {
+		if _, err := db.ExecContext(ctx, `
+			INSERT INTO event_quality_issue
+				(website_id, day, event_name, issue, property, expected, received, hits, last_seen_at, sample)
+			VALUES ($1, $2, $3, $4, $5, $6, $7, 1, $8, $9)
+			ON CONFLICT (website_id, day, event_name, issue, property) DO UPDATE SET
+				expected = EXCLUDED.expected,
+				received = EXCLUDED.received,
+				hits = event_quality_issue.hits + 1,
+				last_seen_at = EXCLUDED.last_seen_at,
+				sample = EXCLUDED.sample
+		`, websiteID, day, name, issue.Kind, issue.Property, issue.Expected, issue.Received, at, sample); err != nil {
+			return err
+		}
+	}
+	return nil
+}
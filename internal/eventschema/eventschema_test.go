@@ -0,0 +1,129 @@
+package eventschema
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParse(t *testing.T) {
+	s, err := Parse([]byte(`{"signup": {"plan": "string", "trial": "boolean?"}, "logout": {}}`))
+	require.NoError(t, err)
+	assert.Equal(t, Schema{
+		"signup": {"plan": "string", "trial": "boolean?"},
+		"logout": {},
+	}, s)
+
+	_, err = Parse([]byte(`{"signup": {"plan": "text"}}`))
+	assert.ErrorContains(t, err, `signup.plan: unknown type "text"`)
+
+	_, err = Parse([]byte(`{"": {}}`))
+	assert.ErrorContains(t, err, "must be 1 to 50 characters")
+
+	_, err = Parse([]byte(`["signup"]`))
+	assert.ErrorContains(t, err, "invalid schema JSON")
+}
+
+func TestValidate(t *testing.T) {
+	s := Schema{
+		"signup":   {"plan": "string", "seats": "number", "trial": "boolean?"},
+		"download": {},
+	}
+
+	tests := []struct {
+		name   string
+		event  string
+		props  map[string]any
+		issues []Issue
+	}{
+		{"matches", "signup", map[string]any{"plan": "pro", "seats": float64(3)}, nil},
+		{"optional and extra properties", "signup", map[string]any{"plan": "pro", "seats": float64(3), "trial": true, "env": "prod"}, nil},
+		{"unknown event", "sign-up", nil, []Issue{{Kind: IssueUnknownEvent}}},
+		{"missing property", "signup", map[string]any{"seats": float64(1)}, []Issue{
+			{Kind: IssueMissingProperty, Property: "plan", Expected: "string"},
+		}},
+		{"wrong types", "signup", map[string]any{"plan": "pro", "seats": "3", "trial": "yes"}, []Issue{
+			{Kind: IssueWrongType, Property: "seats", Expected: "number", Received: "string"},
+			{Kind: IssueWrongType, Property: "trial", Expected: "boolean", Received: "string"},
+		}},
+		{"null counts as missing", "signup", map[string]any{"plan": nil, "seats": float64(1)}, []Issue{
+			{Kind: IssueMissingProperty, Property: "plan", Expected: "string"},
+		}},
+		{"event without properties", "download", map[string]any{"file": "a.pdf"}, nil},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			assert.Equal(t, tt.issues, s.Validate(tt.event, tt.props))
+		})
+	}
+
+	// Websites without a schema accept everything
+	assert.Nil(t, Schema{}.Validate("anything", nil))
+}
+
+func TestTypeScript(t *testing.T) {
+	s := Schema{
+		"signup": {"plan": "string", "trial": "boolean?"},
+		"logout": {},
+	}
+	assert.Equal(t, `// Generated by kaunta website event-schema --format ts
+export {};
+
+declare module '@kaunta/tracker' {
+  interface Events {
+    "logout": {};
+    "signup": {
+      "plan": string;
+      "trial"?: boolean;
+    };
+  }
+}
+`, s.TypeScript())
+}
+
+func TestSave(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectExec("DELETE FROM event_schema").WithArgs(websiteID).WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectExec("INSERT INTO event_schema").WithArgs(websiteID, "logout", `{}`).WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO event_schema").WithArgs(websiteID, "signup", `{"plan":"string"}`).WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+
+	require.NoError(t, Save(context.Background(), db, websiteID, Schema{"signup": {"plan": "string"}, "logout": nil}))
+	assert.NoError(t, mock.ExpectationsWereMet())
+
+	// Invalid schemas never reach the database
+	assert.Error(t, Save(context.Background(), db, websiteID, Schema{"signup": {"plan": "text"}}))
+}
+
+func TestRecord(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	at := time.Date(2026, 3, 1, 23, 30, 0, 0, time.UTC)
+	props := map[string]any{"seats": "3"}
+
+	mock.ExpectExec("INSERT INTO event_quality_issue").
+		WithArgs(websiteID, "2026-03-01", "signup", IssueMissingProperty, "plan", "string", "", at, `{"seats":"3"}`).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO event_quality_issue").
+		WithArgs(websiteID, "2026-03-01", "signup", IssueWrongType, "seats", "number", "string", at, `{"seats":"3"}`).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	require.NoError(t, Record(context.Background(), db, websiteID, "signup", []Issue{
+		{Kind: IssueMissingProperty, Property: "plan", Expected: "string"},
+		{Kind: IssueWrongType, Property: "seats", Expected: "number", Received: "string"},
+	}, props, at))
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
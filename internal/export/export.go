@@ -0,0 +1,249 @@
+// Package export writes everything Kaunta stores about a website to files
+// that other tools can read: its raw events joined with their sessions,
+// the daily rollups behind the dashboard and the headline numbers of
+// compacted days. It is the counterpart to the importer. Rows are streamed
+// from the database, so exports of any size run in constant memory (except
+// Parquet, which holds one row group at a time).
+package export
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"time"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/archive"
+	"github.com/seuros/kaunta/internal/parquet"
+)
+
+// progressEvery is how many rows are written between progress reports
+const progressEvery = 10000
+
+// Table is one file of an export
+type Table struct {
+	Name    string
+	Columns []parquet.Column
+	// Query selects the rows of a website ($1) in [$2, $3) in the order of
+	// Columns
+	Query string
+}
+
+// Tables are the files of an export. The events file has the columns of
+// the event archives, so both are read the same way.
+var Tables = []Table{
+	{
+		Name:    "events",
+		Columns: archive.Columns,
+		Query: `
+			SELECT e.event_id, e.website_id, e.session_id, e.visit_id, e.created_at,
+			       e.page_title, e.hostname, e.url_path, e.url_query,
+			       e.referrer_path, e.referrer_query, e.referrer_domain,
+			       e.event_name, e.tag, e.event_type, e.scroll_depth, e.engagement_time, e.props,
+			       e.utm_source, e.utm_medium, e.utm_campaign, e.utm_term, e.utm_content, e.goal_id,
+			       s.hostname, s.browser, s.os, s.device, s.screen, s.language,
+			       s.country, s.region, s.city, s.distinct_id, s.created_at
+			FROM website_event e
+			LEFT JOIN session s ON s.session_id = e.session_id
+			WHERE e.website_id = $1 AND e.created_at >= $2 AND e.created_at < $3
+			ORDER BY e.created_at, e.event_id
+		`,
+	},
+	{
+		Name: "rollups",
+		Columns: []parquet.Column{
+			{Name: "day", Type: parquet.Timestamp},
+			{Name: "dimension", Type: parquet.String},
+			{Name: "value", Type: parquet.String},
+			{Name: "hits", Type: parquet.Int64},
+			{Name: "visitors", Type: parquet.Int64},
+			{Name: "engagement_time", Type: parquet.Int64},
+		},
+		Query: `
+			SELECT bucket, dimension, value, hits, visitors, engagement_time
+			FROM event_rollup
+			WHERE website_id = $1 AND granularity = 'day' AND bucket >= $2 AND bucket < $3
+			ORDER BY bucket, dimension, value
+		`,
+	},
+	{
+		Name: "daily_summary",
+		Columns: []parquet.Column{
+			{Name: "day", Type: parquet.Timestamp},
+			{Name: "pageviews", Type: parquet.Int64},
+			{Name: "visits", Type: parquet.Int64},
+			{Name: "bounces", Type: parquet.Int64},
+			{Name: "visit_seconds", Type: parquet.Int64},
+		},
+		Query: `
+			SELECT day, pageviews, visits, bounces, ROUND(visit_seconds)::BIGINT
+			FROM daily_summary
+			WHERE website_id = $1 AND day >= $2 AND day < $3
+			ORDER BY day
+		`,
+	},
+}
+
+// Options selects what to export and where
+type Options struct {
+	WebsiteID uuid.UUID
+	// Website is stored in the manifest as is
+	Website any
+	From    time.Time
+	Until   time.Time
+	Format  Format
+	Dir     string
+	// Progress, when set, is called while a file is written and once it's
+	// complete
+	Progress func(file string, rows int64, done bool)
+}
+
+// File is a written file of an export
+type File struct {
+	Name  string `json:"name"`
+	Table string `json:"table"`
+	Rows  int64  `json:"rows"`
+	Bytes int64  `json:"bytes"`
+}
+
+// Manifest describes an export. It is written as manifest.json next to the
+// files.
+type Manifest struct {
+	Website    any       `json:"website"`
+	From       time.Time `json:"from"`
+	Until      time.Time `json:"until"`
+	Format     Format    `json:"format"`
+	ExportedAt time.Time `json:"exported_at"`
+	Files      []File    `json:"files"`
+}
+
+// Export writes every table of a website to opts.Dir, then the manifest.
+// Files are written under a temporary name and renamed when complete, so
+// an interrupted export never leaves a truncated file behind.
+func Export(ctx context.Context, db *sql.DB, opts Options) (*Manifest, error) {
+	if err := os.MkdirAll(opts.Dir, 0o755); err != nil {
+		return nil, err
+	}
+	manifest := &Manifest{
+		Website:    opts.Website,
+		From:       opts.From,
+		Until:      opts.Until,
+		Format:     opts.Format,
+		ExportedAt: time.Now().UTC(),
+	}
+	for _, table := range Tables {
+		file, err := exportTable(ctx, db, table, opts)
+		if err != nil {
+			return nil, fmt.Errorf("failed to export %s: %w", table.Name, err)
+		}
+		manifest.Files = append(manifest.Files, file)
+	}
+
+	data, err := json.MarshalIndent(manifest, "", "  ")
+	if err != nil {
+		return nil, err
+	}
+	if err := os.WriteFile(filepath.Join(opts.Dir, "manifest.json"), append(data, '\n'), 0o644); err != nil {
+		return nil, err
+	}
+	return manifest, nil
+}
+
+func exportTable(ctx context.Context, db *sql.DB, table Table, opts Options) (File, error) {
+	file := File{Name: table.Name + opts.Format.Extension(), Table: table.Name}
+	path := filepath.Join(opts.Dir, file.Name)
+	out, err := os.Create(path + ".partial")
+	if err != nil {
+		return File{}, err
+	}
+	defer func() {
+		_ = out.Close()
+		_ = os.Remove(path + ".partial")
+	}()
+
+	w, err := opts.Format.NewWriter(out, table.Columns)
+	if err != nil {
+		return File{}, err
+	}
+	rows, err := db.QueryContext(ctx, table.Query, opts.WebsiteID, opts.From, opts.Until)
+	if err != nil {
+		return File{}, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	for rows.Next() {
+		row, err := scanRow(rows, table.Columns)
+		if err != nil {
+			return File{}, err
+		}
+		if err := w.Write(row); err != nil {
+			return File{}, err
+		}
+		file.Rows++
+		if opts.Progress != nil && file.Rows%progressEvery == 0 {
+			opts.Progress(file.Name, file.Rows, false)
+		}
+	}
+	if err := rows.Err(); err != nil {
+		return File{}, err
+	}
+	if err := w.Close(); err != nil {
+		return File{}, err
+	}
+	if err := out.Close(); err != nil {
+		return File{}, err
+	}
+	info, err := os.Stat(path + ".partial")
+	if err != nil {
+		return File{}, err
+	}
+	file.Bytes = info.Size()
+	if err := os.Rename(path+".partial", path); err != nil {
+		return File{}, err
+	}
+	if opts.Progress != nil {
+		opts.Progress(file.Name, file.Rows, true)
+	}
+	return file, nil
+}
+
+// scanRow reads a row into the Go types of columns. Every call returns a
+// new slice, as the Parquet writer keeps the rows of a row group.
+func scanRow(rows *sql.Rows, columns []parquet.Column) ([]any, error) {
+	strs := make([]sql.NullString, len(columns))
+	ints := make([]sql.NullInt64, len(columns))
+	times := make([]sql.NullTime, len(columns))
+	dest := make([]any, len(columns))
+	for i, c := range columns {
+		switch c.Type {
+		case parquet.Int32, parquet.Int64:
+			dest[i] = &ints[i]
+		case parquet.Timestamp:
+			dest[i] = &times[i]
+		default:
+			dest[i] = &strs[i]
+		}
+	}
+	if err := rows.Scan(dest...); err != nil {
+		return nil, err
+	}
+
+	row := make([]any, len(columns))
+	for i, c := range columns {
+		switch {
+		case c.Type == parquet.Int32 && ints[i].Valid:
+			row[i] = int32(ints[i].Int64)
+		case c.Type == parquet.Int64 && ints[i].Valid:
+			row[i] = ints[i].Int64
+		case c.Type == parquet.Timestamp && times[i].Valid:
+			row[i] = times[i].Time.UTC()
+		case (c.Type == parquet.String || c.Type == parquet.JSON) && strs[i].Valid:
+			row[i] = strs[i].String
+		}
+	}
+	return row, nil
+}
@@ -0,0 +1,140 @@
+package export
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/parquet"
+)
+
+var testColumns = []parquet.Column{
+	{Name: "url_path", Type: parquet.String},
+	{Name: "props", Type: parquet.JSON},
+	{Name: "scroll_depth", Type: parquet.Int32},
+	{Name: "created_at", Type: parquet.Timestamp},
+}
+
+func TestParseFormat(t *testing.T) {
+	f, err := ParseFormat("parquet")
+	require.NoError(t, err)
+	assert.Equal(t, ".parquet", f.Extension())
+
+	_, err = ParseFormat("xlsx")
+	assert.ErrorContains(t, err, "invalid format")
+}
+
+func TestTextFormats(t *testing.T) {
+	at := time.Date(2025, 3, 1, 12, 0, 0, 0, time.UTC)
+	rows := [][]any{
+		{"/", `{"plan":"pro"}`, int32(80), at},
+		{"/a,b", nil, nil, nil},
+	}
+
+	var out bytes.Buffer
+	w, err := NDJSON.NewWriter(&out, testColumns)
+	require.NoError(t, err)
+	for _, row := range rows {
+		require.NoError(t, w.Write(row))
+	}
+	require.NoError(t, w.Close())
+	assert.Equal(t,
+		`{"url_path":"/","props":{"plan":"pro"},"scroll_depth":80,"created_at":"2025-03-01T12:00:00Z"}`+"\n"+
+			`{"url_path":"/a,b","props":null,"scroll_depth":null,"created_at":null}`+"\n",
+		out.String())
+
+	out.Reset()
+	w, err = CSV.NewWriter(&out, testColumns)
+	require.NoError(t, err)
+	for _, row := range rows {
+		require.NoError(t, w.Write(row))
+	}
+	require.NoError(t, w.Close())
+	assert.Equal(t,
+		"url_path,props,scroll_depth,created_at\n"+
+			`/,"{""plan"":""pro""}",80,2025-03-01T12:00:00Z`+"\n"+
+			"\"/a,b\",,,\n",
+		out.String())
+
+	assert.ErrorContains(t, w.Write([]any{"/"}), "1 values for 4 columns")
+}
+
+func TestExport(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	from := time.Date(2025, 1, 1, 0, 0, 0, 0, time.UTC)
+	until := time.Date(2025, 2, 1, 0, 0, 0, 0, time.UTC)
+	day := time.Date(2025, 1, 5, 0, 0, 0, 0, time.UTC)
+
+	events := make([]string, len(Tables[0].Columns))
+	for i, c := range Tables[0].Columns {
+		events[i] = c.Name
+	}
+	event := make([]any, len(events))
+	event[0], event[1], event[4], event[7], event[14] = uuid.NewString(), websiteID.String(), day, "/", int64(1)
+	mock.ExpectQuery("FROM website_event e").WithArgs(websiteID, from, until).
+		WillReturnRows(sqlmock.NewRows(events).AddRow(event...))
+	mock.ExpectQuery("FROM event_rollup").WithArgs(websiteID, from, until).
+		WillReturnRows(sqlmock.NewRows([]string{"bucket", "dimension", "value", "hits", "visitors", "engagement_time"}).
+			AddRow(day, "page", "/", int64(12), int64(7), int64(300)).
+			AddRow(day, "country", "DE", int64(12), int64(7), int64(0)))
+	mock.ExpectQuery("FROM daily_summary").WithArgs(websiteID, from, until).
+		WillReturnRows(sqlmock.NewRows([]string{"day", "pageviews", "visits", "bounces", "visit_seconds"}))
+
+	dir := filepath.Join(t.TempDir(), "out")
+	var done []string
+	manifest, err := Export(context.Background(), db, Options{
+		WebsiteID: websiteID,
+		Website:   map[string]string{"domain": "example.com"},
+		From:      from,
+		Until:     until,
+		Format:    Parquet,
+		Dir:       dir,
+		Progress: func(file string, rows int64, complete bool) {
+			if complete {
+				done = append(done, file)
+			}
+		},
+	})
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+	assert.Equal(t, []string{"events.parquet", "rollups.parquet", "daily_summary.parquet"}, done)
+	require.Len(t, manifest.Files, 3)
+	assert.Equal(t, int64(1), manifest.Files[0].Rows)
+	assert.Equal(t, int64(2), manifest.Files[1].Rows)
+	assert.Equal(t, int64(0), manifest.Files[2].Rows)
+
+	data, err := os.ReadFile(filepath.Join(dir, "rollups.parquet"))
+	require.NoError(t, err)
+	reader, err := parquet.NewReader(bytes.NewReader(data), int64(len(data)))
+	require.NoError(t, err)
+	var hits []any
+	require.NoError(t, reader.Each(func(row []any) error {
+		hits = append(hits, row[3])
+		return nil
+	}))
+	assert.Equal(t, []any{int64(12), int64(12)}, hits)
+
+	data, err = os.ReadFile(filepath.Join(dir, "manifest.json"))
+	require.NoError(t, err)
+	var written Manifest
+	require.NoError(t, json.Unmarshal(data, &written))
+	assert.Equal(t, Parquet, written.Format)
+	assert.Equal(t, map[string]any{"domain": "example.com"}, written.Website)
+
+	leftovers, err := filepath.Glob(filepath.Join(dir, "*.partial"))
+	require.NoError(t, err)
+	assert.Empty(t, leftovers)
+}
@@ -0,0 +1,153 @@
+package export
+
+import (
+	"bufio"
+	"encoding/csv"
+	"encoding/json"
+	"fmt"
+	"io"
+	"strconv"
+	"time"
+
+	"github.com/seuros/kaunta/internal/parquet"
+)
+
+// Format is the file format of an export
+type Format string
+
+// Export formats
+const (
+	NDJSON  Format = "ndjson"
+	CSV     Format = "csv"
+	Parquet Format = "parquet"
+)
+
+// ParseFormat parses a --format value
+func ParseFormat(s string) (Format, error) {
+	switch f := Format(s); f {
+	case NDJSON, CSV, Parquet:
+		return f, nil
+	}
+	return "", fmt.Errorf("invalid format: %s (use ndjson, csv or parquet)", s)
+}
+
+// Extension is the file name extension of the format
+func (f Format) Extension() string {
+	return "." + string(f)
+}
+
+// RowWriter writes the rows of one file. Rows are in the order of the
+// columns the writer was created with, holding the Go types of their
+// parquet.Type or nil.
+type RowWriter interface {
+	Write(row []any) error
+	Close() error
+}
+
+// NewWriter returns a RowWriter for columns writing to w in the format
+func (f Format) NewWriter(w io.Writer, columns []parquet.Column) (RowWriter, error) {
+	switch f {
+	case NDJSON:
+		return &ndjsonWriter{w: bufio.NewWriter(w), columns: columns}, nil
+	case CSV:
+		return newCSVWriter(w, columns)
+	case Parquet:
+		return parquet.NewWriter(w, columns)
+	}
+	return nil, fmt.Errorf("unknown format %q", f)
+}
+
+// ndjsonWriter writes one JSON object per line, keyed by column name. JSON
+// columns are embedded as JSON rather than as strings.
+type ndjsonWriter struct {
+	w       *bufio.Writer
+	columns []parquet.Column
+}
+
+func (n *ndjsonWriter) Write(row []any) error {
+	if len(row) != len(n.columns) {
+		return fmt.Errorf("%d values for %d columns", len(row), len(n.columns))
+	}
+	_ = n.w.WriteByte('{')
+	for i, c := range n.columns {
+		if i > 0 {
+			_ = n.w.WriteByte(',')
+		}
+		key, _ := json.Marshal(c.Name)
+		_, _ = n.w.Write(key)
+		_ = n.w.WriteByte(':')
+
+		var value []byte
+		var err error
+		switch v := row[i].(type) {
+		case nil:
+			value = []byte("null")
+		case string:
+			if c.Type == parquet.JSON && json.Valid([]byte(v)) {
+				value = []byte(v)
+			} else {
+				value, err = json.Marshal(v)
+			}
+		case time.Time:
+			value, err = json.Marshal(v.Format(time.RFC3339Nano))
+		default:
+			value, err = json.Marshal(v)
+		}
+		if err != nil {
+			return err
+		}
+		_, _ = n.w.Write(value)
+	}
+	_, err := n.w.WriteString("}\n")
+	return err
+}
+
+func (n *ndjsonWriter) Close() error {
+	return n.w.Flush()
+}
+
+// csvWriter writes a header with the column names, then one record per row.
+// Timestamps are RFC 3339 and NULL is an empty field.
+type csvWriter struct {
+	w      *csv.Writer
+	record []string
+}
+
+func newCSVWriter(w io.Writer, columns []parquet.Column) (*csvWriter, error) {
+	cw := &csvWriter{w: csv.NewWriter(w), record: make([]string, len(columns))}
+	for i, c := range columns {
+		cw.record[i] = c.Name
+	}
+	if err := cw.w.Write(cw.record); err != nil {
+		return nil, err
+	}
+	return cw, nil
+}
+
+func (c *csvWriter) Write(row []any) error {
+	if len(row) != len(c.record) {
+		return fmt.Errorf("%d values for %d columns", len(row), len(c.record))
+	}
+	for i, v := range row {
+		switch v := v.(type) {
+		case nil:
+			c.record[i] = ""
+		case string:
+			c.record[i] = v
+		case int32:
+			c.record[i] = strconv.FormatInt(int64(v), 10)
+		case int64:
+			c.record[i] = strconv.FormatInt(v, 10)
+		case time.Time:
+			c.record[i] = v.Format(time.RFC3339Nano)
+		default:
+			c.record[i] = fmt.Sprint(v)
+		}
+	}
+	return c.w.Write(c.record)
+}
+
+func (c *csvWriter) Close() error {
+	c.w.Flush()
+	return c.w.Error()
+}
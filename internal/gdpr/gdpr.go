@@ -0,0 +1,296 @@
+// Package gdpr erases a visitor's data on request. Their sessions, events,
+// goal completions and engaged time are deleted, and the rollups and visitor
+// sketches of the days they were seen on are recounted without them. A
+// receipt names the visitor only by a hash and can be signed, so it can be
+// kept for compliance records after the data is gone.
+package gdpr
+
+import (
+	"context"
+	"crypto/hmac"
+	"crypto/sha256"
+	"database/sql"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"sort"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var nowFunc = time.Now
+
+// Subject is whose data is erased: visitors by ID (the session IDs in
+// exports and the API) and the sessions whose distinct_id is IdentifiedID
+type Subject struct {
+	Visitors     []uuid.UUID
+	IdentifiedID string
+	WebsiteID    *uuid.UUID // Only this website; nil for all of them
+}
+
+// Hash identifies the subject in receipts and the audit log without naming it
+func (s Subject) Hash() string {
+	ids := make([]string, 0, len(s.Visitors)+1)
+	for _, v := range s.Visitors {
+		ids = append(ids, "visitor:"+v.String())
+	}
+	if s.IdentifiedID != "" {
+		ids = append(ids, "identified:"+s.IdentifiedID)
+	}
+	sort.Strings(ids)
+
+	h := sha256.New()
+	for _, id := range ids {
+		_, _ = h.Write([]byte(id + "\n"))
+	}
+	return hex.EncodeToString(h.Sum(nil))
+}
+
+// Receipt is what an erasure deleted, or would delete in a dry run
+type Receipt struct {
+	ID             uuid.UUID  `json:"id"`
+	ErasedAt       time.Time  `json:"erased_at"`
+	Actor          string     `json:"actor"`
+	Subject        string     `json:"subject_sha256"`
+	WebsiteID      *uuid.UUID `json:"website_id,omitempty"`
+	Sessions       int64      `json:"sessions"`
+	Events         int64      `json:"events"`
+	Goals          int64      `json:"goal_completions"`
+	Engagement     int64      `json:"visit_engagement"`
+	Days           int        `json:"days_recounted"`
+	Rollups        int64      `json:"rollup_rows_recounted"`
+	ArchivedMonths []string   `json:"archived_months,omitempty"` // Archive files that may still hold the events
+	DryRun         bool       `json:"dry_run,omitempty"`
+	Signature      string     `json:"signature,omitempty"`
+}
+
+// Sign sets the receipt's signature: the hex HMAC-SHA256 of its JSON without
+// a signature, keyed with key
+func (r *Receipt) Sign(key string) {
+	r.Signature = r.signature(key)
+}
+
+// Verify reports whether the receipt was signed with key and not changed since
+func (r Receipt) Verify(key string) bool {
+	return r.Signature != "" && hmac.Equal([]byte(r.Signature), []byte(r.signature(key)))
+}
+
+func (r Receipt) signature(key string) string {
+	r.Signature = ""
+	body, _ := json.Marshal(r)
+	mac := hmac.New(sha256.New, []byte(key))
+	_, _ = mac.Write(body)
+	return "sha256=" + hex.EncodeToString(mac.Sum(nil))
+}
+
+// day is a day the subject was seen on a website
+type day struct {
+	websiteID uuid.UUID
+	start     time.Time
+}
+
+// Erase deletes the subject's data in a single transaction, or counts it with
+// dryRun. Erasures are recorded in the audit log under actor, even when
+// nothing was found. Rollups of days whose raw events were already dropped
+// by retention only hold counts and are left alone.
+func Erase(ctx context.Context, db *sql.DB, subject Subject, dryRun bool, actor string) (Receipt, error) {
+	receipt := Receipt{
+		ID:        uuid.New(),
+		ErasedAt:  nowFunc().UTC(),
+		Actor:     actor,
+		Subject:   subject.Hash(),
+		WebsiteID: subject.WebsiteID,
+		DryRun:    dryRun,
+	}
+	if len(subject.Visitors) == 0 && subject.IdentifiedID == "" {
+		return receipt, errors.New("no visitor or identified ID to erase")
+	}
+
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return receipt, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	sessions, err := sessionIDs(ctx, tx, subject)
+	if err != nil {
+		return receipt, err
+	}
+	days, err := seenOn(ctx, tx, sessions, subject.WebsiteID)
+	if err != nil {
+		return receipt, err
+	}
+	receipt.Days = len(days)
+	if receipt.ArchivedMonths, err = archivedMonths(ctx, tx, sessions, subject.WebsiteID); err != nil {
+		return receipt, err
+	}
+
+	// Deleting a session would cascade to its events and goal completions;
+	// they go first so the receipt counts them
+	counts := []struct {
+		dst  *int64
+		from string
+	}{
+		{&receipt.Goals, `FROM goal_completions WHERE session_id = ANY($1) AND ($2::uuid IS NULL OR website_id = $2)`},
+		{&receipt.Engagement, `FROM visit_engagement WHERE session_id = ANY($1) AND ($2::uuid IS NULL OR website_id = $2)`},
+		{&receipt.Events, `FROM website_event WHERE session_id = ANY($1) AND ($2::uuid IS NULL OR website_id = $2)`},
+		{&receipt.Sessions, `FROM session WHERE session_id = ANY($1) AND ($2::uuid IS NULL OR website_id = $2)`},
+	}
+	for _, c := range counts {
+		if *c.dst, err = erase(ctx, tx, dryRun, c.from, pq.Array(sessions), subject.WebsiteID); err != nil {
+			return receipt, err
+		}
+	}
+	if dryRun {
+		return receipt, nil
+	}
+
+	for _, d := range days {
+		rows, err := recount(ctx, tx, d)
+		if err != nil {
+			return receipt, err
+		}
+		receipt.Rollups += rows
+	}
+
+	if err := models.RecordAudit(ctx, tx, models.AuditErasure, subject.WebsiteID, actor, map[string]any{
+		"receipt":          receipt.ID,
+		"subject_sha256":   receipt.Subject,
+		"sessions":         receipt.Sessions,
+		"events":           receipt.Events,
+		"goal_completions": receipt.Goals,
+		"days_recounted":   receipt.Days,
+	}); err != nil {
+		return receipt, err
+	}
+	return receipt, tx.Commit()
+}
+
+// sessionIDs returns the subject's sessions: the visitors asked for and the
+// sessions with the identified ID
+func sessionIDs(ctx context.Context, tx *sql.Tx, subject Subject) ([]uuid.UUID, error) {
+	seen := make(map[uuid.UUID]bool)
+	var ids []uuid.UUID
+	for _, id := range subject.Visitors {
+		if !seen[id] {
+			seen[id] = true
+			ids = append(ids, id)
+		}
+	}
+	if subject.IdentifiedID == "" {
+		return ids, nil
+	}
+
+	rows, err := tx.QueryContext(ctx, `
+		SELECT session_id FROM session
+		WHERE distinct_id = $1 AND ($2::uuid IS NULL OR website_id = $2)
+	`, subject.IdentifiedID, subject.WebsiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+	for rows.Next() {
+		var id uuid.UUID
+		if err := rows.Scan(&id); err != nil {
+			return nil, err
+		}
+		if !seen[id] {
+			seen[id] = true
+			ids = append(ids, id)
+		}
+	}
+	return ids, rows.Err()
+}
+
+// seenOn returns the days with raw events of the sessions
+func seenOn(ctx context.Context, tx *sql.Tx, sessions []uuid.UUID, websiteID *uuid.UUID) ([]day, error) {
+	rows, err := tx.QueryContext(ctx, `
+		SELECT DISTINCT website_id, DATE_TRUNC('day', created_at)
+		FROM website_event
+		WHERE session_id = ANY($1) AND ($2::uuid IS NULL OR website_id = $2)
+		ORDER BY 1, 2
+	`, pq.Array(sessions), websiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var days []day
+	for rows.Next() {
+		var d day
+		if err := rows.Scan(&d.websiteID, &d.start); err != nil {
+			return nil, err
+		}
+		days = append(days, d)
+	}
+	return days, rows.Err()
+}
+
+// archivedMonths lists the archived months from the first session on, whose
+// Parquet files may hold the sessions' events
+func archivedMonths(ctx context.Context, tx *sql.Tx, sessions []uuid.UUID, websiteID *uuid.UUID) ([]string, error) {
+	rows, err := tx.QueryContext(ctx, `
+		SELECT TO_CHAR(month, 'YYYY-MM') FROM event_archive
+		WHERE month >= (
+			SELECT DATE_TRUNC('month', MIN(created_at)) FROM session
+			WHERE session_id = ANY($1) AND ($2::uuid IS NULL OR website_id = $2)
+		)
+		ORDER BY month
+	`, pq.Array(sessions), websiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var months []string
+	for rows.Next() {
+		var month string
+		if err := rows.Scan(&month); err != nil {
+			return nil, err
+		}
+		months = append(months, month)
+	}
+	return months, rows.Err()
+}
+
+// recount rebuilds a day's rollups and sketches from the raw events left.
+// They are cleared first: rebuild_rollups() leaves days before a website's
+// first raw event alone, and the erased events may have been the first.
+// Hourly rollups older than a week aren't rebuilt and expire with the rest.
+func recount(ctx context.Context, tx *sql.Tx, d day) (int64, error) {
+	until := d.start.AddDate(0, 0, 1)
+	if _, err := tx.ExecContext(ctx, `
+		DELETE FROM event_rollup
+		WHERE website_id = $1 AND bucket >= $2 AND bucket < $3
+		  AND (granularity = 'day' OR bucket >= NOW() - INTERVAL '7 days')
+	`, d.websiteID, d.start, until); err != nil {
+		return 0, err
+	}
+	if _, err := tx.ExecContext(ctx, `
+		DELETE FROM visitor_sketch WHERE website_id = $1 AND bucket >= $2 AND bucket < $3
+	`, d.websiteID, d.start, until); err != nil {
+		return 0, err
+	}
+	var rows int64
+	err := tx.QueryRowContext(ctx, `SELECT rebuild_rollups($1, $2, $3)`, d.start, until, d.websiteID).Scan(&rows)
+	return rows, err
+}
+
+// erase deletes the rows matched by from (a FROM ... WHERE clause), or
+// counts them with dryRun
+func erase(ctx context.Context, tx *sql.Tx, dryRun bool, from string, args ...any) (int64, error) {
+	if dryRun {
+		var count int64
+		err := tx.QueryRowContext(ctx, `SELECT COUNT(*) `+from, args...).Scan(&count)
+		return count, err
+	}
+	res, err := tx.ExecContext(ctx, `DELETE `+from, args...)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
@@ -0,0 +1,114 @@
+package gdpr
+
+import (
+	"context"
+	"encoding/json"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestSubjectHashIgnoresOrder(t *testing.T) {
+	a, b := uuid.New(), uuid.New()
+	assert.Equal(t,
+		Subject{Visitors: []uuid.UUID{a, b}, IdentifiedID: "user-1"}.Hash(),
+		Subject{Visitors: []uuid.UUID{b, a}, IdentifiedID: "user-1"}.Hash())
+	assert.NotEqual(t, Subject{IdentifiedID: "user-1"}.Hash(), Subject{IdentifiedID: "user-2"}.Hash())
+	assert.NotContains(t, Subject{IdentifiedID: "user-1"}.Hash(), "user-1")
+}
+
+func TestReceiptSignature(t *testing.T) {
+	receipt := Receipt{ID: uuid.New(), ErasedAt: time.Now().UTC(), Actor: "cli", Subject: "abc", Events: 12}
+	receipt.Sign("secret")
+	require.NotEmpty(t, receipt.Signature)
+	assert.True(t, receipt.Verify("secret"))
+	assert.False(t, receipt.Verify("other"))
+
+	// A receipt read back from its JSON still verifies, and edits break it
+	data, err := json.Marshal(receipt)
+	require.NoError(t, err)
+	var saved Receipt
+	require.NoError(t, json.Unmarshal(data, &saved))
+	assert.True(t, saved.Verify("secret"))
+	saved.Events = 0
+	assert.False(t, saved.Verify("secret"))
+
+	assert.False(t, Receipt{}.Verify("secret"))
+}
+
+func TestEraseDeletesAndRecounts(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	visitor, identified, websiteID := uuid.New(), uuid.New(), uuid.New()
+	day := time.Date(2026, 5, 3, 0, 0, 0, 0, time.UTC)
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT session_id FROM session").WithArgs("user-1", nil).
+		WillReturnRows(sqlmock.NewRows([]string{"session_id"}).AddRow(identified.String()).AddRow(visitor.String()))
+	mock.ExpectQuery("SELECT DISTINCT website_id").
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "day"}).AddRow(websiteID.String(), day))
+	mock.ExpectQuery("FROM event_archive").
+		WillReturnRows(sqlmock.NewRows([]string{"month"}).AddRow("2026-04"))
+	mock.ExpectExec("DELETE FROM goal_completions").WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("DELETE FROM visit_engagement").WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectExec("DELETE FROM website_event").WillReturnResult(sqlmock.NewResult(0, 40))
+	mock.ExpectExec("DELETE FROM session").WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectExec("DELETE FROM event_rollup").WithArgs(websiteID, day, day.AddDate(0, 0, 1)).
+		WillReturnResult(sqlmock.NewResult(0, 30))
+	mock.ExpectExec("DELETE FROM visitor_sketch").WithArgs(websiteID, day, day.AddDate(0, 0, 1)).
+		WillReturnResult(sqlmock.NewResult(0, 90))
+	mock.ExpectQuery(`SELECT rebuild_rollups\(\$1, \$2, \$3\)`).WithArgs(day, day.AddDate(0, 0, 1), websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"rebuild_rollups"}).AddRow(110))
+	mock.ExpectExec("INSERT INTO audit_log").WithArgs("gdpr.erase", nil, "cli", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(1, 1))
+	mock.ExpectCommit()
+
+	receipt, err := Erase(context.Background(), db, Subject{Visitors: []uuid.UUID{visitor}, IdentifiedID: "user-1"}, false, "cli")
+	require.NoError(t, err)
+	assert.Equal(t, int64(2), receipt.Sessions)
+	assert.Equal(t, int64(40), receipt.Events)
+	assert.Equal(t, int64(1), receipt.Goals)
+	assert.Equal(t, int64(2), receipt.Engagement)
+	assert.Equal(t, 1, receipt.Days)
+	assert.Equal(t, int64(110), receipt.Rollups)
+	assert.Equal(t, []string{"2026-04"}, receipt.ArchivedMonths)
+	assert.False(t, receipt.DryRun)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEraseDryRunCounts(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	visitor, websiteID := uuid.New(), uuid.New()
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT DISTINCT website_id").WithArgs(sqlmock.AnyArg(), &websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "day"}))
+	mock.ExpectQuery("FROM event_archive").
+		WillReturnRows(sqlmock.NewRows([]string{"month"}))
+	for _, table := range []string{"goal_completions", "visit_engagement", "website_event", "session"} {
+		mock.ExpectQuery("SELECT COUNT\\(\\*\\) FROM " + table).
+			WillReturnRows(sqlmock.NewRows([]string{"count"}).AddRow(3))
+	}
+	mock.ExpectRollback()
+
+	receipt, err := Erase(context.Background(), db, Subject{Visitors: []uuid.UUID{visitor}, WebsiteID: &websiteID}, true, "cli")
+	require.NoError(t, err)
+	assert.True(t, receipt.DryRun)
+	assert.Equal(t, int64(3), receipt.Events)
+	assert.Zero(t, receipt.Rollups)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEraseNeedsASubject(t *testing.T) {
+	_, err := Erase(context.Background(), nil, Subject{}, true, "cli")
+	assert.Error(t, err)
+}
@@ -4,6 +4,7 @@ import (
 	"compress/gzip"
 	"fmt"
 	"io"
+	"math"
 	"net"
 	"net/http"
 	"os"
@@ -83,6 +84,29 @@ func LookupIP(ipStr string) (country, city, region string) {
 	return country, city, region
 }
 
+// LookupCityCentroid returns the coordinates GeoLite2 publishes for the IP's
+// city, rounded to one decimal (~10 km); ok is false when no city is known
+func LookupCityCentroid(ipStr string) (lat, lon float64, ok bool) {
+	if reader == nil {
+		return 0, 0, false
+	}
+
+	ip := net.ParseIP(ipStr)
+	if ip == nil {
+		return 0, 0, false
+	}
+
+	record, err := reader.City(ip)
+	if err != nil || record.City.Names["en"] == "" {
+		return 0, 0, false
+	}
+	if record.Location.Latitude == 0 && record.Location.Longitude == 0 {
+		return 0, 0, false
+	}
+
+	return math.Round(record.Location.Latitude*10) / 10, math.Round(record.Location.Longitude*10) / 10, true
+}
+
 // Close closes the GeoIP database
 func Close() error {
 	if reader != nil {
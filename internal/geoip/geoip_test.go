@@ -52,6 +52,14 @@ func TestLookupIP(t *testing.T) {
 	}
 }
 
+func TestLookupCityCentroidWithoutDatabase(t *testing.T) {
+	if reader != nil {
+		t.Skip("GeoIP database loaded")
+	}
+	_, _, ok := LookupCityCentroid("8.8.8.8")
+	assert.False(t, ok)
+}
+
 func TestCloseWithoutInit(t *testing.T) {
 	// Should not panic if Close called without Init
 	err := Close()
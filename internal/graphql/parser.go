@@ -0,0 +1,454 @@
+// Package graphql parses the subset of GraphQL used by the stats query API:
+// query operations with variables, aliases, arguments and nested selections.
+// Fragments, directives, mutations and subscriptions are not supported.
+package graphql
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+)
+
+// Field is one selected field, with its arguments resolved against the
+// request variables
+type Field struct {
+	Alias      string
+	Name       string
+	Arguments  map[string]any
+	Selections []*Field
+}
+
+// Key is the name the field's value is returned under
+func (f *Field) Key() string {
+	if f.Alias != "" {
+		return f.Alias
+	}
+	return f.Name
+}
+
+// Operation is a parsed query operation
+type Operation struct {
+	Name       string
+	Selections []*Field
+}
+
+// Parse parses a query document and returns the operation named
+// operationName (or the only operation when it is empty), substituting
+// variables into field arguments.
+func Parse(source, operationName string, variables map[string]any) (*Operation, error) {
+	tokens, err := tokenize(source)
+	if err != nil {
+		return nil, err
+	}
+	p := &parser{tokens: tokens}
+
+	var ops []*Operation
+	for !p.done() {
+		op, err := p.parseOperation(variables)
+		if err != nil {
+			return nil, err
+		}
+		ops = append(ops, op)
+	}
+
+	switch {
+	case len(ops) == 0:
+		return nil, fmt.Errorf("document has no operations")
+	case operationName != "":
+		for _, op := range ops {
+			if op.Name == operationName {
+				return op, nil
+			}
+		}
+		return nil, fmt.Errorf("unknown operation %q", operationName)
+	case len(ops) > 1:
+		return nil, fmt.Errorf("operationName is required when the document has several operations")
+	}
+	return ops[0], nil
+}
+
+type tokenKind int
+
+const (
+	tokenPunct tokenKind = iota
+	tokenName
+	tokenInt
+	tokenFloat
+	tokenString
+)
+
+type token struct {
+	kind  tokenKind
+	value string
+	pos   int
+}
+
+func tokenize(src string) ([]token, error) {
+	var tokens []token
+	for i := 0; i < len(src); {
+		c := src[i]
+		switch {
+		case c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == ',':
+			i++
+		case c == '#':
+			for i < len(src) && src[i] != '\n' {
+				i++
+			}
+		case strings.IndexByte("!$():=@[]{}|", c) >= 0:
+			tokens = append(tokens, token{tokenPunct, string(c), i})
+			i++
+		case c == '.':
+			if !strings.HasPrefix(src[i:], "...") {
+				return nil, fmt.Errorf("unexpected character %q at %d", c, i)
+			}
+			tokens = append(tokens, token{tokenPunct, "...", i})
+			i += 3
+		case c == '_' || isLetter(c):
+			start := i
+			for i < len(src) && (src[i] == '_' || isLetter(src[i]) || isDigit(src[i])) {
+				i++
+			}
+			tokens = append(tokens, token{tokenName, src[start:i], start})
+		case c == '-' || isDigit(c):
+			start := i
+			kind := tokenInt
+			i++
+			for i < len(src) && (isDigit(src[i]) || strings.IndexByte(".eE+-", src[i]) >= 0) {
+				if !isDigit(src[i]) {
+					kind = tokenFloat
+				}
+				i++
+			}
+			tokens = append(tokens, token{kind, src[start:i], start})
+		case c == '"':
+			value, end, err := readString(src, i)
+			if err != nil {
+				return nil, err
+			}
+			tokens = append(tokens, token{tokenString, value, i})
+			i = end
+		default:
+			return nil, fmt.Errorf("unexpected character %q at %d", c, i)
+		}
+	}
+	return tokens, nil
+}
+
+// readString reads the quoted string starting at src[start], returning its
+// unescaped value and the index after the closing quote
+func readString(src string, start int) (string, int, error) {
+	if strings.HasPrefix(src[start:], `"""`) {
+		return "", 0, fmt.Errorf("block strings are not supported (at %d)", start)
+	}
+	for i := start + 1; i < len(src); i++ {
+		switch src[i] {
+		case '\\':
+			i++
+		case '\n':
+			return "", 0, fmt.Errorf("unterminated string at %d", start)
+		case '"':
+			value, err := strconv.Unquote(src[start : i+1])
+			if err != nil {
+				return "", 0, fmt.Errorf("invalid string at %d", start)
+			}
+			return value, i + 1, nil
+		}
+	}
+	return "", 0, fmt.Errorf("unterminated string at %d", start)
+}
+
+func isLetter(c byte) bool { return (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') }
+func isDigit(c byte) bool { return c >= '0' && c <= '9' }
+
+type parser struct {
+	tokens []token
+	pos    int
+	vars   map[string]any
+}
+
+func (p *parser) done() bool { return p.pos >= len(p.tokens) }
+
+func (p *parser) peek() token {
+	if p.done() {
+		return token{kind: tokenPunct, value: "<EOF>", pos: -1}
+	}
+	return p.tokens[p.pos]
+}
+
+func (p *parser) peekPunct(value string) bool {
+	t := p.peek()
+	return t.kind == tokenPunct && t.value == value
+}
+
+func (p *parser) expectPunct(value string) error {
+	t := p.peek()
+	if t.kind != tokenPunct || t.value != value {
+		return p.unexpected(t, value)
+	}
+	p.pos++
+	return nil
+}
+
+func (p *parser) expectName() (string, error) {
+	t := p.peek()
+	if t.kind != tokenName {
+		return "", p.unexpected(t, "a name")
+	}
+	p.pos++
+	return t.value, nil
+}
+
+func (p *parser) unexpected(t token, want string) error {
+	if t.pos < 0 {
+		return fmt.Errorf("unexpected end of query, expected %s", want)
+	}
+	return fmt.Errorf("unexpected %q at %d, expected %s", t.value, t.pos, want)
+}
+
+func (p *parser) parseOperation(variables map[string]any) (*Operation, error) {
+	op := &Operation{}
+	p.vars = map[string]any{}
+
+	// The shorthand form is a bare selection set
+	if !p.peekPunct("{") {
+		keyword, err := p.expectName()
+		if err != nil {
+			return nil, err
+		}
+		switch keyword {
+		case "query":
+		case "mutation", "subscription":
+			return nil, fmt.Errorf("%s operations are not supported", keyword)
+		case "fragment":
+			return nil, fmt.Errorf("fragments are not supported")
+		default:
+			return nil, fmt.Errorf("unknown operation type %q", keyword)
+		}
+		if p.peek().kind == tokenName {
+			op.Name, _ = p.expectName()
+		}
+		if p.peekPunct("(") {
+			if err := p.parseVariableDefinitions(variables); err != nil {
+				return nil, err
+			}
+		}
+	}
+
+	selections, err := p.parseSelectionSet()
+	if err != nil {
+		return nil, err
+	}
+	op.Selections = selections
+	return op, nil
+}
+
+// parseVariableDefinitions reads ($name: Type = default, ...) and binds each
+// variable to its supplied value or default
+func (p *parser) parseVariableDefinitions(variables map[string]any) error {
+	if err := p.expectPunct("("); err != nil {
+		return err
+	}
+	for !p.peekPunct(")") {
+		if err := p.expectPunct("$"); err != nil {
+			return err
+		}
+		name, err := p.expectName()
+		if err != nil {
+			return err
+		}
+		if err := p.expectPunct(":"); err != nil {
+			return err
+		}
+		required, err := p.parseType()
+		if err != nil {
+			return err
+		}
+
+		value, supplied := variables[name]
+		if p.peekPunct("=") {
+			p.pos++
+			def, err := p.parseValue(true)
+			if err != nil {
+				return err
+			}
+			if !supplied {
+				value, supplied = def, true
+			}
+		}
+		if required && (!supplied || value == nil) {
+			return fmt.Errorf("variable $%s is required", name)
+		}
+		p.vars[name] = value
+	}
+	p.pos++
+	return nil
+}
+
+// parseType skips a type reference and reports whether it is non-null
+func (p *parser) parseType() (bool, error) {
+	if p.peekPunct("[") {
+		p.pos++
+		if _, err := p.parseType(); err != nil {
+			return false, err
+		}
+		if err := p.expectPunct("]"); err != nil {
+			return false, err
+		}
+	} else if _, err := p.expectName(); err != nil {
+		return false, err
+	}
+	if p.peekPunct("!") {
+		p.pos++
+		return true, nil
+	}
+	return false, nil
+}
+
+func (p *parser) parseSelectionSet() ([]*Field, error) {
+	if err := p.expectPunct("{"); err != nil {
+		return nil, err
+	}
+	var fields []*Field
+	for !p.peekPunct("}") {
+		if p.peekPunct("...") {
+			return nil, fmt.Errorf("fragments are not supported")
+		}
+		field, err := p.parseField()
+		if err != nil {
+			return nil, err
+		}
+		fields = append(fields, field)
+	}
+	p.pos++
+	if len(fields) == 0 {
+		return nil, fmt.Errorf("empty selection set")
+	}
+	return fields, nil
+}
+
+func (p *parser) parseField() (*Field, error) {
+	name, err := p.expectName()
+	if err != nil {
+		return nil, err
+	}
+	field := &Field{Name: name, Arguments: map[string]any{}}
+	if p.peekPunct(":") {
+		p.pos++
+		field.Alias = name
+		if field.Name, err = p.expectName(); err != nil {
+			return nil, err
+		}
+	}
+
+	if p.peekPunct("(") {
+		p.pos++
+		for !p.peekPunct(")") {
+			arg, err := p.expectName()
+			if err != nil {
+				return nil, err
+			}
+			if err := p.expectPunct(":"); err != nil {
+				return nil, err
+			}
+			value, err := p.parseValue(false)
+			if err != nil {
+				return nil, err
+			}
+			field.Arguments[arg] = value
+		}
+		p.pos++
+	}
+	if p.peekPunct("@") {
+		return nil, fmt.Errorf("directives are not supported")
+	}
+
+	if p.peekPunct("{") {
+		if field.Selections, err = p.parseSelectionSet(); err != nil {
+			return nil, err
+		}
+	}
+	return field, nil
+}
+
+// parseValue reads a literal or variable; constant values (variable
+// defaults) may not reference variables
+func (p *parser) parseValue(constant bool) (any, error) {
+	t := p.peek()
+	switch t.kind {
+	case tokenInt:
+		p.pos++
+		n, err := strconv.Atoi(t.value)
+		if err != nil {
+			return nil, fmt.Errorf("invalid integer %q", t.value)
+		}
+		return n, nil
+	case tokenFloat:
+		p.pos++
+		f, err := strconv.ParseFloat(t.value, 64)
+		if err != nil {
+			return nil, fmt.Errorf("invalid number %q", t.value)
+		}
+		return f, nil
+	case tokenString:
+		p.pos++
+		return t.value, nil
+	case tokenName:
+		p.pos++
+		switch t.value {
+		case "true":
+			return true, nil
+		case "false":
+			return false, nil
+		case "null":
+			return nil, nil
+		}
+		return t.value, nil // enum values are passed as strings
+	}
+
+	switch t.value {
+	case "$":
+		if constant {
+			return nil, p.unexpected(t, "a constant value")
+		}
+		p.pos++
+		name, err := p.expectName()
+		if err != nil {
+			return nil, err
+		}
+		value, ok := p.vars[name]
+		if !ok {
+			return nil, fmt.Errorf("variable $%s is not defined", name)
+		}
+		return value, nil
+	case "[":
+		p.pos++
+		list := []any{}
+		for !p.peekPunct("]") {
+			item, err := p.parseValue(constant)
+			if err != nil {
+				return nil, err
+			}
+			list = append(list, item)
+		}
+		p.pos++
+		return list, nil
+	case "{":
+		p.pos++
+		object := map[string]any{}
+		for !p.peekPunct("}") {
+			key, err := p.expectName()
+			if err != nil {
+				return nil, err
+			}
+			if err := p.expectPunct(":"); err != nil {
+				return nil, err
+			}
+			if object[key], err = p.parseValue(constant); err != nil {
+				return nil, err
+			}
+		}
+		p.pos++
+		return object, nil
+	}
+	return nil, p.unexpected(t, "a value")
+}
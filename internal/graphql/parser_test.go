@@ -0,0 +1,83 @@
+package graphql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseShorthandQuery(t *testing.T) {
+	op, err := Parse(`{ stats { pageviews visitors } }`, "", nil)
+	require.NoError(t, err)
+	require.Len(t, op.Selections, 1)
+
+	stats := op.Selections[0]
+	assert.Equal(t, "stats", stats.Key())
+	require.Len(t, stats.Selections, 2)
+	assert.Equal(t, "pageviews", stats.Selections[0].Name)
+	assert.Equal(t, "visitors", stats.Selections[1].Name)
+}
+
+func TestParseArgumentsAliasesAndVariables(t *testing.T) {
+	query := `
+		# Two slices of the same breakdown in one request
+		query Countries($days: Int = 7, $device: String!) {
+			mobile: breakdown(dimension: countries, days: $days, filter: { device: $device }, limit: 5) {
+				name
+				count
+			}
+			top: breakdown(dimension: "referrers", sortOrder: asc, flags: [1, 2.5, true, null]) { name }
+		}
+	`
+	op, err := Parse(query, "", map[string]any{"device": "mobile"})
+	require.NoError(t, err)
+	assert.Equal(t, "Countries", op.Name)
+	require.Len(t, op.Selections, 2)
+
+	mobile := op.Selections[0]
+	assert.Equal(t, "mobile", mobile.Key())
+	assert.Equal(t, "breakdown", mobile.Name)
+	assert.Equal(t, "countries", mobile.Arguments["dimension"])
+	assert.Equal(t, 7, mobile.Arguments["days"])
+	assert.Equal(t, map[string]any{"device": "mobile"}, mobile.Arguments["filter"])
+	assert.Equal(t, 5, mobile.Arguments["limit"])
+
+	top := op.Selections[1]
+	assert.Equal(t, "referrers", top.Arguments["dimension"])
+	assert.Equal(t, "asc", top.Arguments["sortOrder"])
+	assert.Equal(t, []any{1, 2.5, true, nil}, top.Arguments["flags"])
+}
+
+func TestParseSelectsOperationByName(t *testing.T) {
+	query := `query A { stats { visitors } } query B { website { domain } }`
+
+	op, err := Parse(query, "B", nil)
+	require.NoError(t, err)
+	assert.Equal(t, "website", op.Selections[0].Name)
+
+	_, err = Parse(query, "", nil)
+	assert.Error(t, err)
+
+	_, err = Parse(query, "C", nil)
+	assert.Error(t, err)
+}
+
+func TestParseErrors(t *testing.T) {
+	tests := map[string]string{
+		"unsupported mutation": `mutation { reset }`,
+		"fragment spread":      `{ stats { ...Fields } }`,
+		"directive":            `{ stats @skip(if: true) { visitors } }`,
+		"unterminated":         `{ stats { visitors }`,
+		"missing variable":     `query ($days: Int!) { stats(days: $days) { visitors } }`,
+		"undefined variable":   `{ stats(days: $days) { visitors } }`,
+		"bad string":           `{ stats(page: "/oops) { visitors } }`,
+		"empty selection":      `{ }`,
+	}
+	for name, query := range tests {
+		t.Run(name, func(t *testing.T) {
+			_, err := Parse(query, "", nil)
+			assert.Error(t, err)
+		})
+	}
+}
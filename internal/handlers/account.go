@@ -0,0 +1,249 @@
+package handlers
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"net/mail"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/mailer"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// accountSettings is what the account card shows
+type accountSettings struct {
+	Email        string
+	PendingEmail string
+	Owned        *models.AccountOwnershipError
+}
+
+// loadAccountSettingsFunc loads the account card (can be mocked in tests)
+var loadAccountSettingsFunc = func(ctx context.Context, userID uuid.UUID) (*accountSettings, error) {
+	var settings accountSettings
+	var email *string
+	if err := database.DB.QueryRowContext(ctx, `SELECT email FROM users WHERE user_id = $1`, userID).Scan(&email); err != nil {
+		return nil, err
+	}
+	if email != nil {
+		settings.Email = *email
+	}
+	var err error
+	if settings.PendingEmail, err = models.GetPendingEmailChange(ctx, database.DB, userID); err != nil {
+		return nil, err
+	}
+	if settings.Owned, err = models.AccountOwnership(ctx, database.DB, userID); err != nil {
+		return nil, err
+	}
+	return &settings, nil
+}
+
+// HandleAccountInit renders the signed-in user's account settings via
+// Datastar SSE
+// GET /api/auth/account
+func HandleAccountInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	streamAccountSettings(w, r, user, nil)
+}
+
+// HandleAccountPassword changes the password after checking the current
+// one. Other sessions are signed out.
+// POST /api/auth/account/password (form: current, password, confirm)
+func HandleAccountPassword(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	password := r.FormValue("password")
+	passwordErr := ""
+	switch {
+	case len(password) < 8:
+		passwordErr = "Password must be at least 8 characters"
+	case password != r.FormValue("confirm"):
+		passwordErr = "Passwords do not match"
+	default:
+		err := models.ChangePassword(r.Context(), database.DB, user.UserID, user.SessionID, r.FormValue("current"), password)
+		passwordErr = accountFormError("HandleAccountPassword", err)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if passwordErr != "" {
+			_ = sse.PatchSignals(map[string]any{"accountError": passwordErr, "accountMessage": ""})
+			return
+		}
+		_ = sse.PatchSignals(map[string]any{
+			"accountError":    "",
+			"accountMessage":  "Password changed. Other devices were signed out.",
+			"currentPassword": "",
+			"newPassword":     "",
+			"confirmPassword": "",
+		})
+	})
+}
+
+// HandleAccountEmail sends a confirmation link to a new email address. The
+// address only changes once the link is opened.
+// POST /api/auth/account/email (form: email, password)
+func HandleAccountEmail(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	email := strings.TrimSpace(r.FormValue("email"))
+	emailErr := ""
+	if _, err := mail.ParseAddress(email); err != nil || strings.ContainsAny(email, "<> ") {
+		emailErr = "Enter a valid email address"
+	} else if !accountMailer.Enabled() {
+		emailErr = "Email is not set up on this server. Ask an administrator to change your address."
+	} else {
+		token, expiresAt, err := models.RequestEmailChange(r.Context(), database.DB, user.UserID, r.FormValue("password"), email)
+		if err == nil {
+			err = SendEmailChange(accountMailer, email, user.Username, publicLink(r, "/confirm-email/"+token), expiresAt)
+		}
+		emailErr = accountFormError("HandleAccountEmail", err)
+	}
+
+	if emailErr != "" {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"accountError": emailErr, "accountMessage": ""})
+		})
+		return
+	}
+	streamAccountSettings(w, r, user, map[string]any{
+		"accountError":   "",
+		"accountMessage": "Check " + email + " for a confirmation link.",
+		"emailPassword":  "",
+	})
+}
+
+// SendEmailChange emails a link confirming a new address
+func SendEmailChange(m *mailer.Mailer, email, username, link string, expiresAt time.Time) error {
+	body := fmt.Sprintf(`Someone asked to use this address for the Kaunta account %q.
+
+Open this link to confirm it:
+
+%s
+
+The link works once and expires on %s. If you didn't ask for this, ignore
+this email; the account keeps its current address.
+`, username, link, expiresAt.UTC().Format("2 Jan 2006 15:04 MST"))
+	return m.Send(email, "Confirm your new Kaunta email address", body)
+}
+
+// HandleConfirmEmail applies an email change from its confirmation link and
+// sends the user to the security page
+// GET /confirm-email/:token
+func HandleConfirmEmail(w http.ResponseWriter, r *http.Request) {
+	status := "confirmed"
+	_, err := models.ConfirmEmailChange(r.Context(), database.DB, chi.URLParam(r, "token"))
+	switch {
+	case errors.Is(err, models.ErrEmailChangeNotFound):
+		status = "expired"
+	case errors.Is(err, models.ErrEmailTaken):
+		status = "taken"
+	case err != nil:
+		log.Printf("HandleConfirmEmail: %v", err)
+		status = "failed"
+	}
+	http.Redirect(w, r, "/dashboard/security?email="+status, http.StatusSeeOther)
+}
+
+// HandleAccountDelete deletes the signed-in user's account after checking
+// their password. It is refused while they own teams or websites alone.
+// POST /api/auth/account/delete (form: password)
+func HandleAccountDelete(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	err := models.DeleteAccount(r.Context(), database.DB, user.UserID, r.FormValue("password"))
+	if err != nil {
+		deleteErr := accountFormError("HandleAccountDelete", err)
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"accountError": deleteErr, "accountMessage": ""})
+		})
+		return
+	}
+
+	http.SetCookie(w, sessionCookie("", time.Now().Add(-1*time.Hour)))
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.ExecuteScript("localStorage.removeItem('kaunta_website')")
+		_ = sse.ExecuteScript("localStorage.removeItem('kaunta_dateRange')")
+		_ = sse.ExecuteScript("window.location.href = '/login'")
+	})
+}
+
+// streamAccountSettings re-renders the account card after a change
+func streamAccountSettings(w http.ResponseWriter, r *http.Request, user *middleware.UserContext, signals map[string]any) {
+	settings, err := loadAccountSettingsFunc(r.Context(), user.UserID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("streamAccountSettings: %v", err)
+			_ = sse.PatchSignals(map[string]any{"accountError": "Failed to load account settings"})
+			return
+		}
+		_ = sse.PatchSignals(map[string]any{
+			"accountEmail":        settings.Email,
+			"accountPendingEmail": settings.PendingEmail,
+			"accountDeletable":    settings.Owned == nil,
+		})
+		_ = sse.PatchElementsWithMode("#account-ownership", buildAccountOwnershipHTML(settings.Owned), "inner")
+		if signals != nil {
+			_ = sse.PatchSignals(signals)
+		}
+	})
+}
+
+func buildAccountOwnershipHTML(owned *models.AccountOwnershipError) string {
+	if owned == nil {
+		return ""
+	}
+
+	var b strings.Builder
+	b.WriteString(`<p class="stat-label">Before you can delete your account, make someone else an owner of these, or delete them:</p><ul>`)
+	for _, name := range owned.Teams {
+		fmt.Fprintf(&b, `<li>Team <strong>%s</strong></li>`, escapeHTML(name))
+	}
+	for _, domain := range owned.Websites {
+		fmt.Fprintf(&b, `<li>Website <strong>%s</strong></li>`, escapeHTML(domain))
+	}
+	b.WriteString(`</ul>`)
+	return b.String()
+}
+
+// accountFormError turns a model error into a message for the account card.
+// Unexpected errors are logged.
+func accountFormError(handler string, err error) string {
+	var owned *models.AccountOwnershipError
+	switch {
+	case err == nil:
+		return ""
+	case errors.Is(err, models.ErrPasswordIncorrect):
+		return "Current password is incorrect"
+	case errors.Is(err, models.ErrEmailTaken):
+		return "That email address is used by another account"
+	case errors.As(err, &owned):
+		return "You still own teams or websites; see the list below"
+	}
+	log.Printf("%s: %v", handler, err)
+	return "Something went wrong, please try again"
+}
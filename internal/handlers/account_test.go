@@ -0,0 +1,85 @@
+package handlers
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func accountRequest(target string, form url.Values, user *middleware.UserContext) *http.Request {
+	req := formRequest(target, form)
+	return req.WithContext(middleware.ContextWithUser(req.Context(), user))
+}
+
+func TestBuildAccountOwnershipHTML(t *testing.T) {
+	assert.Empty(t, buildAccountOwnershipHTML(nil))
+
+	html := buildAccountOwnershipHTML(&models.AccountOwnershipError{
+		Teams:    []string{"<Marketing>"},
+		Websites: []string{"example.com"},
+	})
+	assert.Contains(t, html, "Team <strong>&lt;Marketing&gt;</strong>")
+	assert.Contains(t, html, "Website <strong>example.com</strong>")
+}
+
+func TestHandleAccountInit(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New(), SessionID: uuid.New()}
+	stubVar(t, &loadAccountSettingsFunc, func(ctx context.Context, userID uuid.UUID) (*accountSettings, error) {
+		assert.Equal(t, user.UserID, userID)
+		return &accountSettings{
+			Email: "alice@example.com",
+			Owned: &models.AccountOwnershipError{Websites: []string{"example.com"}},
+		}, nil
+	})
+
+	req := httptest.NewRequest(http.MethodGet, "/api/auth/account", nil)
+	resp := httptest.NewRecorder()
+	HandleAccountInit(resp, req.WithContext(middleware.ContextWithUser(req.Context(), user)))
+	body := resp.Body.String()
+	assert.Contains(t, body, `"accountEmail":"alice@example.com"`)
+	assert.Contains(t, body, `"accountDeletable":false`)
+	assert.Contains(t, body, "#account-ownership")
+
+	resp = httptest.NewRecorder()
+	HandleAccountInit(resp, req)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+}
+
+func TestHandleAccountPasswordValidation(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New(), SessionID: uuid.New()}
+
+	resp := httptest.NewRecorder()
+	HandleAccountPassword(resp, accountRequest("/api/auth/account/password", url.Values{"password": {"short"}, "confirm": {"short"}}, user))
+	assert.Contains(t, resp.Body.String(), "Password must be at least 8 characters")
+
+	resp = httptest.NewRecorder()
+	HandleAccountPassword(resp, accountRequest("/api/auth/account/password", url.Values{"password": {"longenough1"}, "confirm": {"longenough2"}}, user))
+	assert.Contains(t, resp.Body.String(), "Passwords do not match")
+}
+
+func TestHandleAccountEmailValidation(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New(), SessionID: uuid.New()}
+	stubVar(t, &accountMailer, nil)
+
+	resp := httptest.NewRecorder()
+	HandleAccountEmail(resp, accountRequest("/api/auth/account/email", url.Values{"email": {"not an address"}}, user))
+	assert.Contains(t, resp.Body.String(), "Enter a valid email address")
+
+	resp = httptest.NewRecorder()
+	HandleAccountEmail(resp, accountRequest("/api/auth/account/email", url.Values{"email": {"alice@example.com"}}, user))
+	assert.Contains(t, resp.Body.String(), "Email is not set up on this server")
+}
+
+func TestAccountFormError(t *testing.T) {
+	assert.Empty(t, accountFormError("test", nil))
+	assert.Equal(t, "Current password is incorrect", accountFormError("test", models.ErrPasswordIncorrect))
+	assert.Contains(t, accountFormError("test", &models.AccountOwnershipError{Teams: []string{"Marketing"}}), "You still own")
+}
@@ -0,0 +1,251 @@
+package handlers
+
+import (
+	"fmt"
+	"log"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// alertKindLabels are the display names for alert rule kinds
+var alertKindLabels = map[string]string{
+	models.AlertSpike:   "Spike",
+	models.AlertDrop:    "Drop",
+	models.AlertSilence: "Silence",
+}
+
+// HandleAlertsInit loads websites and the user's alert rules
+// GET /api/dashboard/alerts-init
+func HandleAlertsInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	rows, queryErr := database.DB.Query(`
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
+		ORDER BY domain
+	`, user.UserID)
+	if queryErr == nil {
+		websites = scanWebsiteInfos(rows)
+	}
+
+	var rules []models.AlertRule
+	if queryErr == nil {
+		rules, queryErr = models.ListAlertRules(r.Context(), database.DB, user.UserID)
+	}
+
+	selectedWebsite := selectedWebsiteFromRequest(r)
+	if selectedWebsite == "" && len(websites) > 0 {
+		selectedWebsite = websites[0].ID
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleAlertsInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Failed to load alerts",
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#alert-rule-list", buildAlertRuleListHTML(rules, websites), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"selectedWebsite": selectedWebsite,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+		if html := buildWebsiteSelectorHTML(websites, selectedWebsite, "alerts"); html != "" {
+			_ = sse.PatchElements("#website-selector-container", html)
+		}
+	})
+}
+
+// HandleAlertCreate stores a new alert rule for the selected website
+// POST /api/dashboard/alerts (form: website_id, name, kind, threshold, window_minutes, channel, target)
+func HandleAlertCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	websiteID, err := uuid.Parse(r.FormValue("website_id"))
+	threshold, _ := strconv.ParseFloat(r.FormValue("threshold"), 64)
+	window, _ := strconv.Atoi(r.FormValue("window_minutes"))
+	rule := models.AlertRule{
+		UserID:        user.UserID,
+		WebsiteID:     websiteID,
+		Name:          strings.TrimSpace(r.FormValue("name")),
+		Kind:          r.FormValue("kind"),
+		Threshold:     threshold,
+		WindowMinutes: window,
+		Channel:       r.FormValue("channel"),
+		Target:        strings.TrimSpace(r.FormValue("target")),
+	}
+	validationErr := rule.Validate()
+	if err != nil {
+		validationErr = fmt.Errorf("select a website first")
+	}
+	if validationErr != nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"alertError": validationErr.Error(), "submitting": false})
+		})
+		return
+	}
+
+	if err := models.CreateAlertRule(r.Context(), database.DB, &rule); err != nil {
+		log.Printf("HandleAlertCreate: %v", err)
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"alertError": "Failed to create alert", "submitting": false})
+		})
+		return
+	}
+
+	streamAlertRuleList(w, r, user.UserID, map[string]any{
+		"showAlertModal": false,
+		"alertError":     false,
+		"submitting":     false,
+	})
+}
+
+// HandleAlertToggle pauses or resumes an alert rule
+// POST /api/dashboard/alerts/{id}/enabled?enabled=true
+func HandleAlertToggle(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	ruleID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid alert ID")
+		return
+	}
+	enabled, _ := strconv.ParseBool(r.URL.Query().Get("enabled"))
+
+	if err := models.SetAlertRuleEnabled(r.Context(), database.DB, ruleID, user.UserID, enabled); err != nil {
+		respondError(w, r, http.StatusNotFound, "Alert not found")
+		return
+	}
+	streamAlertRuleList(w, r, user.UserID, nil)
+}
+
+// HandleAlertDelete removes an alert rule
+// DELETE /api/dashboard/alerts/{id}
+func HandleAlertDelete(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	ruleID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid alert ID")
+		return
+	}
+
+	if err := models.DeleteAlertRule(r.Context(), database.DB, ruleID, user.UserID); err != nil {
+		respondError(w, r, http.StatusNotFound, "Alert not found")
+		return
+	}
+	streamAlertRuleList(w, r, user.UserID, nil)
+}
+
+// streamAlertRuleList re-renders the rule list after a change
+func streamAlertRuleList(w http.ResponseWriter, r *http.Request, userID uuid.UUID, signals map[string]any) {
+	rules, err := models.ListAlertRules(r.Context(), database.DB, userID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("streamAlertRuleList: %v", err)
+		} else {
+			_ = sse.PatchElementsWithMode("#alert-rule-list", buildAlertRuleListHTML(rules, nil), "inner")
+		}
+		if signals != nil {
+			_ = sse.PatchSignals(signals)
+		}
+	})
+}
+
+func buildAlertRuleListHTML(rules []models.AlertRule, websites []WebsiteInfo) string {
+	if len(rules) == 0 {
+		return `<div class="empty-state-mini"><div>[!]</div><div>No alerts yet</div></div>`
+	}
+
+	domains := make(map[string]string, len(websites))
+	for _, w := range websites {
+		domains[w.ID] = websiteLabel(w)
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="alerts-table"><thead><tr><th>Alert</th><th>Condition</th><th>Delivery</th><th>Last fired</th><th style="text-align:right">Actions</th></tr></thead><tbody>`)
+	for _, rule := range rules {
+		id := rule.ID.String()
+		website := ""
+		if label := domains[rule.WebsiteID.String()]; label != "" {
+			website = `<div class="stat-label">` + escapeHTML(label) + `</div>`
+		}
+		lastFired := "Never"
+		if rule.LastFiredAt != nil {
+			lastFired = rule.LastFiredAt.Format(time.DateTime)
+		}
+		toggleLabel, next := "Pause", "false"
+		if !rule.Enabled {
+			toggleLabel, next = "Resume", "true"
+		}
+		fmt.Fprintf(&b, `<tr id="alert-%s"%s><td>%s%s</td><td>%s</td><td>%s</td><td>%s</td><td class="alert-actions">`,
+			id, alertRowClass(rule), escapeHTML(rule.Name), website,
+			escapeHTML(describeAlertRule(rule)),
+			escapeHTML(rule.Channel+": "+rule.Target),
+			escapeHTML(lastFired))
+		fmt.Fprintf(&b, `<button class="btn btn-xs btn-ghost" data-on:click="@post('/api/dashboard/alerts/%s/enabled?enabled=%s', { headers: { 'X-CSRF-Token': getAlertsCsrfToken() } })">%s</button>`,
+			id, next, toggleLabel)
+		fmt.Fprintf(&b, `<button class="btn btn-xs btn-danger" data-on:click="confirm('Delete this alert?') && @delete('/api/dashboard/alerts/%s', { headers: { 'X-CSRF-Token': getAlertsCsrfToken() } })">Delete</button></td></tr>`,
+			id)
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
+
+func alertRowClass(rule models.AlertRule) string {
+	if rule.Enabled {
+		return ""
+	}
+	return ` class="alert-paused"`
+}
+
+// describeAlertRule renders a rule's condition, e.g. "Spike: visitors > 3x trailing average per 60 min"
+func describeAlertRule(rule models.AlertRule) string {
+	label := alertKindLabels[rule.Kind]
+	switch rule.Kind {
+	case models.AlertSpike:
+		return fmt.Sprintf("%s: visitors > %gx trailing average per %d min", label, rule.Threshold, rule.WindowMinutes)
+	case models.AlertDrop:
+		return fmt.Sprintf("%s: visitors < 1/%gx trailing average per %d min", label, rule.Threshold, rule.WindowMinutes)
+	case models.AlertSilence:
+		return fmt.Sprintf("%s: no events for %d min", label, rule.WindowMinutes)
+	}
+	return label
+}
@@ -0,0 +1,39 @@
+package handlers
+
+import (
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestDescribeAlertRule(t *testing.T) {
+	assert.Equal(t, "Spike: visitors > 3x trailing average per 60 min",
+		describeAlertRule(models.AlertRule{Kind: models.AlertSpike, Threshold: 3, WindowMinutes: 60}))
+	assert.Equal(t, "Drop: visitors < 1/2.5x trailing average per 30 min",
+		describeAlertRule(models.AlertRule{Kind: models.AlertDrop, Threshold: 2.5, WindowMinutes: 30}))
+	assert.Equal(t, "Silence: no events for 30 min",
+		describeAlertRule(models.AlertRule{Kind: models.AlertSilence, WindowMinutes: 30}))
+}
+
+func TestBuildAlertRuleListHTML(t *testing.T) {
+	assert.Contains(t, buildAlertRuleListHTML(nil, nil), "No alerts yet")
+
+	websiteID := uuid.New()
+	rules := []models.AlertRule{
+		{ID: uuid.New(), WebsiteID: websiteID, Name: "<HN> spike", Kind: models.AlertSpike, Threshold: 3, WindowMinutes: 60,
+			Channel: models.AlertChannelWebhook, Target: "https://hooks.example.com", Enabled: true},
+		{ID: uuid.New(), WebsiteID: websiteID, Name: "Snippet broken", Kind: models.AlertSilence, WindowMinutes: 30,
+			Channel: models.AlertChannelEmail, Target: "owner@example.com"},
+	}
+	html := buildAlertRuleListHTML(rules, []WebsiteInfo{{ID: websiteID.String(), Domain: "example.com"}})
+
+	assert.Contains(t, html, "&lt;HN&gt; spike")
+	assert.Contains(t, html, "example.com")
+	assert.Contains(t, html, "Never")
+	assert.Contains(t, html, "/api/dashboard/alerts/"+rules[0].ID.String()+"/enabled?enabled=false")
+	assert.Contains(t, html, "/api/dashboard/alerts/"+rules[1].ID.String()+"/enabled?enabled=true")
+	assert.Contains(t, html, `class="alert-paused"`)
+}
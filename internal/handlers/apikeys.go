@@ -0,0 +1,258 @@
+package handlers
+
+import (
+	"fmt"
+	"log"
+	"net/http"
+	"slices"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// apiKeyRotationGrace is how long a key rotated from the dashboard keeps working
+const apiKeyRotationGrace = 24 * time.Hour
+
+// HandleAPIKeysInit loads the user's websites and the selected website's keys
+// GET /api/dashboard/api-keys-init
+func HandleAPIKeysInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	rows, queryErr := database.DB.Query(`
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1) WHERE role = 'owner')
+		ORDER BY domain
+	`, user.UserID)
+	if queryErr == nil {
+		websites = scanWebsiteInfos(rows)
+	}
+
+	selectedWebsite := selectedWebsiteFromRequest(r)
+	if !slices.ContainsFunc(websites, func(site WebsiteInfo) bool { return site.ID == selectedWebsite }) {
+		selectedWebsite = ""
+		if len(websites) > 0 {
+			selectedWebsite = websites[0].ID
+		}
+	}
+
+	var keys []*models.APIKey
+	if queryErr == nil && selectedWebsite != "" {
+		keys, queryErr = models.ListAPIKeys(uuid.MustParse(selectedWebsite))
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleAPIKeysInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Failed to load API keys",
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#api-key-list", buildAPIKeyListHTML(keys, time.Now()), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"selectedWebsite": selectedWebsite,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+		if html := buildWebsiteSelectorHTML(websites, selectedWebsite, "api-keys"); html != "" {
+			_ = sse.PatchElements("#website-selector-container", html)
+		}
+	})
+}
+
+// HandleAPIKeyCreate issues a key for one of the user's websites. The secret
+// is shown once in the response.
+// POST /api/dashboard/api-keys (form: website_id, name, scopes..., expires)
+func HandleAPIKeyCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	fail := func(message string) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"apiKeyError": message, "submitting": false})
+		})
+	}
+
+	websiteID, err := uuid.Parse(r.FormValue("website_id"))
+	if err != nil || !userHasWebsiteRole(r.Context(), user.UserID, websiteID, models.RoleOwner) {
+		fail("Select a website first")
+		return
+	}
+	scopes := r.Form["scopes"]
+	if len(scopes) == 0 {
+		fail("Select at least one scope")
+		return
+	}
+	expiresAt, err := models.ParseAPIKeyExpiry(r.FormValue("expires"), time.Now())
+	if err != nil {
+		fail(err.Error())
+		return
+	}
+	var name *string
+	if value := strings.TrimSpace(r.FormValue("name")); value != "" {
+		name = &value
+	}
+
+	result, err := models.GenerateAPIKeyWithScopes(websiteID, &user.UserID, name, scopes)
+	if err == nil && expiresAt != nil {
+		err = models.SetAPIKeyExpiry(result.APIKey.KeyID, expiresAt)
+	}
+	if err != nil {
+		log.Printf("HandleAPIKeyCreate: %v", err)
+		fail("Failed to create API key")
+		return
+	}
+
+	streamAPIKeyList(w, websiteID, map[string]any{
+		"showAPIKeyModal": false,
+		"apiKeySecret":    result.FullKey,
+		"apiKeyError":     false,
+		"submitting":      false,
+	})
+}
+
+// HandleAPIKeyRotate replaces a key with a new secret; the old one keeps
+// working for apiKeyRotationGrace
+// POST /api/dashboard/api-keys/{id}/rotate
+func HandleAPIKeyRotate(w http.ResponseWriter, r *http.Request) {
+	key, ok := ownedAPIKeyFromRequest(w, r)
+	if !ok {
+		return
+	}
+
+	result, err := models.RotateAPIKey(key.KeyID, apiKeyRotationGrace)
+	if err != nil {
+		log.Printf("HandleAPIKeyRotate: %v", err)
+		respondError(w, r, http.StatusConflict, "API key cannot be rotated")
+		return
+	}
+	streamAPIKeyList(w, key.WebsiteID, map[string]any{"apiKeySecret": result.FullKey})
+}
+
+// HandleAPIKeyRevoke stops a key from working immediately
+// DELETE /api/dashboard/api-keys/{id}
+func HandleAPIKeyRevoke(w http.ResponseWriter, r *http.Request) {
+	key, ok := ownedAPIKeyFromRequest(w, r)
+	if !ok {
+		return
+	}
+
+	if err := models.RevokeAPIKey(key.KeyID); err != nil {
+		respondError(w, r, http.StatusNotFound, "API key not found")
+		return
+	}
+	streamAPIKeyList(w, key.WebsiteID, nil)
+}
+
+// ownedAPIKeyFromRequest loads the {id} key if it belongs to one of the
+// user's websites, writing the error response otherwise
+func ownedAPIKeyFromRequest(w http.ResponseWriter, r *http.Request) (*models.APIKey, bool) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return nil, false
+	}
+	keyID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid API key ID")
+		return nil, false
+	}
+
+	key, err := models.GetAPIKeyByID(keyID)
+	if err != nil || !userHasWebsiteRole(r.Context(), user.UserID, key.WebsiteID, models.RoleOwner) {
+		respondError(w, r, http.StatusNotFound, "API key not found")
+		return nil, false
+	}
+	return key, true
+}
+
+// streamAPIKeyList re-renders the website's key list after a change
+func streamAPIKeyList(w http.ResponseWriter, websiteID uuid.UUID, signals map[string]any) {
+	keys, err := models.ListAPIKeys(websiteID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("streamAPIKeyList: %v", err)
+		} else {
+			_ = sse.PatchElementsWithMode("#api-key-list", buildAPIKeyListHTML(keys, time.Now()), "inner")
+		}
+		if signals != nil {
+			_ = sse.PatchSignals(signals)
+		}
+	})
+}
+
+func buildAPIKeyListHTML(keys []*models.APIKey, now time.Time) string {
+	if len(keys) == 0 {
+		return `<div class="empty-state-mini"><div>[~]</div><div>No API keys for this website yet</div></div>`
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="api-keys-table"><thead><tr><th>Key</th><th>Scopes</th><th>Last used</th><th>Expires</th><th>Status</th><th style="text-align:right">Actions</th></tr></thead><tbody>`)
+	for _, key := range keys {
+		id := key.KeyID.String()
+		label := `<code>` + escapeHTML(key.KeyPrefix) + `…</code>`
+		if key.Name != nil {
+			label = escapeHTML(*key.Name) + `<div class="stat-label">` + label + `</div>`
+		}
+		lastUsed := "never"
+		if key.LastUsedAt != nil {
+			lastUsed = key.LastUsedAt.Format(time.DateTime)
+		}
+		expires := "never"
+		if key.ExpiresAt != nil {
+			expires = key.ExpiresAt.Format(time.DateTime)
+		}
+
+		status, active := "active", true
+		switch {
+		case key.RevokedAt != nil:
+			status, active = "revoked", false
+		case key.ExpiresAt != nil && !key.ExpiresAt.After(now):
+			status, active = "expired", false
+		}
+		rowClass := ""
+		if !active {
+			rowClass = ` class="api-key-inactive"`
+		}
+
+		fmt.Fprintf(&b, `<tr id="api-key-%s"%s><td>%s</td><td>%s</td><td>%s</td><td>%s</td><td><span class="api-key-status api-key-status-%s">%s</span></td><td class="api-key-actions">`,
+			id, rowClass, label,
+			escapeHTML(strings.Join(key.Scopes, ", ")),
+			escapeHTML(lastUsed), escapeHTML(expires),
+			status, status)
+		if active {
+			fmt.Fprintf(&b, `<button class="btn btn-xs btn-ghost" data-on:click="confirm('Issue a new secret? The current one keeps working for 24 hours.') && @post('/api/dashboard/api-keys/%s/rotate', { headers: { 'X-CSRF-Token': getAPIKeysCsrfToken() } })">Rotate</button>`,
+				id)
+			fmt.Fprintf(&b, `<button class="btn btn-xs btn-danger" data-on:click="confirm('Revoke this key? Requests using it will fail immediately.') && @delete('/api/dashboard/api-keys/%s', { headers: { 'X-CSRF-Token': getAPIKeysCsrfToken() } })">Revoke</button>`,
+				id)
+		}
+		b.WriteString(`</td></tr>`)
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
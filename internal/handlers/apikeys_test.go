@@ -0,0 +1,32 @@
+package handlers
+
+import (
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestBuildAPIKeyListHTML(t *testing.T) {
+	now := time.Date(2026, 5, 1, 12, 0, 0, 0, time.UTC)
+	assert.Contains(t, buildAPIKeyListHTML(nil, now), "No API keys")
+
+	name := "<CI>"
+	lastUsed := now.Add(-time.Hour)
+	expired := now.Add(-time.Minute)
+	keys := []*models.APIKey{
+		{KeyID: uuid.New(), KeyPrefix: "kaunta_live_aaaa", Name: &name, Scopes: []string{"ingest", "stats"}, LastUsedAt: &lastUsed},
+		{KeyID: uuid.New(), KeyPrefix: "kaunta_live_bbbb", Scopes: []string{"stats"}, ExpiresAt: &expired},
+	}
+	html := buildAPIKeyListHTML(keys, now)
+
+	assert.Contains(t, html, "&lt;CI&gt;")
+	assert.Contains(t, html, "ingest, stats")
+	assert.Contains(t, html, "2026-05-01 11:00:00")
+	assert.Contains(t, html, "/api/dashboard/api-keys/"+keys[0].KeyID.String()+"/rotate")
+	assert.NotContains(t, html, "/api/dashboard/api-keys/"+keys[1].KeyID.String()+"/rotate")
+	assert.Contains(t, html, "api-key-status-expired")
+}
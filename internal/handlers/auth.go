@@ -32,17 +32,23 @@ type LoginResponse struct {
 		Username string    `json:"username"`
 		Name     *string   `json:"name,omitempty"`
 	} `json:"user,omitempty"`
+	// Set instead of User when the account has 2FA: send the challenge
+	// with a code to /api/auth/login/2fa to finish signing in
+	TwoFactorRequired bool   `json:"two_factor_required,omitempty"`
+	Challenge         string `json:"challenge,omitempty"`
 }
 
 type userRecord struct {
-	UserID       uuid.UUID
-	Username     string
-	Name         sql.NullString
-	PasswordHash string
+	UserID           uuid.UUID
+	Username         string
+	Name             sql.NullString
+	PasswordHash     string
+	TwoFactorEnabled bool
 }
 
 var (
 	fetchUserByUsername    = fetchUserFromDB
+	fetchUserByIDFunc      = fetchUserByIDFromDB
 	verifyPasswordHashFunc = verifyPasswordInDB
 	insertSessionFunc      = insertSessionInDB
 	sessionTokenGenerator  = generateSessionToken
@@ -91,11 +97,42 @@ func HandleLogin(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	// The password alone isn't enough for accounts with 2FA
+	if user.TwoFactorEnabled {
+		challenge, err := createLoginChallengeFunc(r.Context(), user.UserID)
+		if err != nil {
+			respondError(w, r, http.StatusInternalServerError, "Failed to create session")
+			return
+		}
+		render.JSON(w, r, LoginResponse{
+			Message:           "Two-factor code required",
+			TwoFactorRequired: true,
+			Challenge:         challenge,
+		})
+		return
+	}
+
+	if err := startSession(w, r, user.UserID); err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to create session")
+		return
+	}
+
+	render.JSON(w, r, loginResponse(user))
+}
+
+// startSession creates a 7 day session for the user and sets the session
+// cookie. Call it before anything is written to w.
+func startSession(w http.ResponseWriter, r *http.Request, userID uuid.UUID) error {
+	_, err := openSession(w, r, userID)
+	return err
+}
+
+// openSession is startSession that also returns the new session token
+func openSession(w http.ResponseWriter, r *http.Request, userID uuid.UUID) (string, error) {
 	// Generate session token
 	token, tokenHash, err := sessionTokenGenerator()
 	if err != nil {
-		respondError(w, r, http.StatusInternalServerError, "Failed to create session")
-		return
+		return "", err
 	}
 
 	// Create session in database
@@ -109,29 +146,16 @@ func HandleLogin(w http.ResponseWriter, r *http.Request) {
 	}
 	ipAddress := clientIP(r)
 
-	if err := insertSessionFunc(sessionID, user.UserID, tokenHash, expiresAt, userAgent, ipAddress); err != nil {
-		respondError(w, r, http.StatusInternalServerError, "Failed to create session")
-		return
+	if err := insertSessionFunc(sessionID, userID, tokenHash, expiresAt, userAgent, ipAddress); err != nil {
+		return "", err
 	}
 
-	// Set session cookie
-	secure := secureCookiesEnabled()
-	sameSite := "Lax"
-	if secure {
-		sameSite = "None" // Required for cross-domain CNAME setups
-	}
+	http.SetCookie(w, sessionCookie(token, expiresAt))
+	return token, nil
+}
 
-	http.SetCookie(w, &http.Cookie{
-		Name:     "kaunta_session",
-		Value:    token,
-		Expires:  expiresAt,
-		HttpOnly: true,
-		Secure:   secure,
-		SameSite: parseSameSite(sameSite),
-		Path:     "/",
-	})
-
-	// Return success response
+// loginResponse is the success response for a signed-in user
+func loginResponse(user *userRecord) LoginResponse {
 	response := LoginResponse{
 		Success: true,
 		Message: "Login successful",
@@ -149,8 +173,7 @@ func HandleLogin(w http.ResponseWriter, r *http.Request) {
 		nameStr := user.Name.String
 		response.User.Name = &nameStr
 	}
-
-	render.JSON(w, r, response)
+	return response
 }
 
 // HandleMe returns current user info
@@ -194,17 +217,30 @@ func parseSameSite(mode string) http.SameSite {
 
 func fetchUserFromDB(username string) (*userRecord, error) {
 	query := `
-		SELECT user_id, username, name, password_hash
+		SELECT user_id, username, name, password_hash, totp_enabled_at IS NOT NULL
 		FROM users
-		WHERE username = $1
+		WHERE username = $1 AND disabled_at IS NULL
 	`
+	return scanUserRecord(database.DB.QueryRow(query, username))
+}
+
+func fetchUserByIDFromDB(userID uuid.UUID) (*userRecord, error) {
+	query := `
+		SELECT user_id, username, name, password_hash, totp_enabled_at IS NOT NULL
+		FROM users
+		WHERE user_id = $1 AND disabled_at IS NULL
+	`
+	return scanUserRecord(database.DB.QueryRow(query, userID))
+}
 
+func scanUserRecord(row *sql.Row) (*userRecord, error) {
 	var record userRecord
-	err := database.DB.QueryRow(query, username).Scan(
+	err := row.Scan(
 		&record.UserID,
 		&record.Username,
 		&record.Name,
 		&record.PasswordHash,
+		&record.TwoFactorEnabled,
 	)
 	if err != nil {
 		return nil, err
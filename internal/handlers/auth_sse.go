@@ -8,9 +8,8 @@ import (
 	"net/http"
 	"time"
 
-	"github.com/google/uuid"
-
 	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
 )
 
 // DatastarLoginRequest represents the login signals from Datastar
@@ -19,15 +18,11 @@ type DatastarLoginRequest struct {
 	Password string `json:"password"`
 }
 
-// HandleLoginSSE handles login via Datastar SSE
+// HandleLoginSSE handles login via Datastar SSE. Accounts with 2FA get a
+// challenge signal instead of a session and finish at HandleLoginTwoFactorSSE.
 // GET /api/auth/login-ds?datastar={signals}
 func HandleLoginSSE(w http.ResponseWriter, r *http.Request) {
 	signalsJSON := r.URL.Query().Get("datastar")
-	userAgent := r.Header.Get("User-Agent")
-	if len(userAgent) > 500 {
-		userAgent = userAgent[:500]
-	}
-	ipAddress := clientIP(r)
 
 	w.Header().Set("Content-Type", "text/event-stream")
 	w.Header().Set("Cache-Control", "no-cache")
@@ -51,10 +46,10 @@ func HandleLoginSSE(w http.ResponseWriter, r *http.Request) {
 		parseErr = "Username and password are required"
 	}
 
-	// Authenticate user BEFORE streaming
+	// Authenticate user BEFORE streaming; on success the session cookie is
+	// set here, before the headers are written
 	var authErr string
-	var token string
-	var expiresAt time.Time
+	var challenge string
 
 	if parseErr == "" {
 		// Fetch user from database
@@ -63,48 +58,17 @@ func HandleLoginSSE(w http.ResponseWriter, r *http.Request) {
 			authErr = "Invalid username or password"
 		} else if err != nil {
 			authErr = "Authentication error"
-		} else {
-			// Verify password
-			passwordValid, verifyErr := verifyPasswordHashFunc(req.Password, user.PasswordHash)
-			if verifyErr != nil || !passwordValid {
-				authErr = "Invalid username or password"
-			} else {
-				// Generate session token
-				var tokenHash string
-				token, tokenHash, err = sessionTokenGenerator()
-				if err != nil {
-					authErr = "Failed to create session"
-				} else {
-					// Create session in database
-					sessionID := uuid.New()
-					expiresAt = time.Now().Add(7 * 24 * time.Hour) // 7 days
-
-					if err := insertSessionFunc(sessionID, user.UserID, tokenHash, expiresAt, userAgent, ipAddress); err != nil {
-						authErr = "Failed to create session"
-					}
-				}
+		} else if passwordValid, verifyErr := verifyPasswordHashFunc(req.Password, user.PasswordHash); verifyErr != nil || !passwordValid {
+			authErr = "Invalid username or password"
+		} else if user.TwoFactorEnabled {
+			if challenge, err = createLoginChallengeFunc(r.Context(), user.UserID); err != nil {
+				authErr = "Failed to create session"
 			}
+		} else if err := startSession(w, r, user.UserID); err != nil {
+			authErr = "Failed to create session"
 		}
 	}
 
-	// Set cookie on success BEFORE starting stream (headers must be set first)
-	if parseErr == "" && authErr == "" {
-		secure := secureCookiesEnabled()
-		sameSite := http.SameSiteLaxMode
-		if secure {
-			sameSite = http.SameSiteNoneMode
-		}
-		http.SetCookie(w, &http.Cookie{
-			Name:     "kaunta_session",
-			Value:    token,
-			Expires:  expiresAt,
-			HttpOnly: true,
-			Secure:   secure,
-			SameSite: sameSite,
-			Path:     "/",
-		})
-	}
-
 	writer := bufio.NewWriter(w)
 	sse := NewDatastarSSE(writer)
 
@@ -128,15 +92,61 @@ func HandleLoginSSE(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	if challenge != "" {
+		_ = sse.PatchSignals(map[string]any{
+			"challenge": challenge,
+			"code":      "",
+			"error":     "",
+			"loading":   false,
+		})
+		_ = writer.Flush()
+		flusher.Flush()
+		return
+	}
+
 	_ = sse.PatchSignals(map[string]any{
 		"error":   "",
 		"loading": false,
 	})
-	_ = sse.ExecuteScript("window.location.href = '/dashboard'")
+	_ = sse.ExecuteScript("window.location.href = '/dashboard/overview'")
 	_ = writer.Flush()
 	flusher.Flush()
 }
 
+// HandleLoginTwoFactorSSE finishes a login that HandleLoginSSE answered with
+// a challenge, using an authenticator or recovery code
+// GET /api/auth/login/2fa?datastar={signals}
+func HandleLoginTwoFactorSSE(w http.ResponseWriter, r *http.Request) {
+	var req TwoFactorLoginRequest
+	var loginErr string
+	expired := false
+
+	signalsJSON := r.URL.Query().Get("datastar")
+	if signalsJSON == "" {
+		loginErr = "Invalid request"
+	} else if err := json.Unmarshal([]byte(signalsJSON), &req); err != nil {
+		loginErr = "Invalid request format"
+	} else if req.Challenge == "" || req.Code == "" {
+		loginErr = "Enter the code from your authenticator app"
+	} else if _, err := completeTwoFactorLogin(w, r, req.Challenge, req.Code); err != nil {
+		_, loginErr = twoFactorLoginError(err)
+		expired = errors.Is(err, models.ErrLoginChallengeNotFound)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if loginErr != "" {
+			signals := map[string]any{"error": loginErr, "loading": false, "code": ""}
+			if expired {
+				signals["challenge"] = ""
+			}
+			_ = sse.PatchSignals(signals)
+			return
+		}
+		_ = sse.PatchSignals(map[string]any{"error": "", "loading": false})
+		_ = sse.ExecuteScript("window.location.href = '/dashboard/overview'")
+	})
+}
+
 // HandleLogoutSSE handles logout via Datastar SSE
 // POST /api/auth/logout-ds
 func HandleLogoutSSE(w http.ResponseWriter, r *http.Request) {
@@ -152,21 +162,7 @@ func HandleLogoutSSE(w http.ResponseWriter, r *http.Request) {
 		logoutErr = "Failed to logout"
 	}
 
-	secure := secureCookiesEnabled()
-	sameSite := http.SameSiteLaxMode
-	if secure {
-		sameSite = http.SameSiteNoneMode
-	}
-
-	http.SetCookie(w, &http.Cookie{
-		Name:     "kaunta_session",
-		Value:    "",
-		Expires:  time.Now().Add(-1 * time.Hour),
-		HttpOnly: true,
-		Secure:   secure,
-		SameSite: sameSite,
-		Path:     "/",
-	})
+	http.SetCookie(w, sessionCookie("", time.Now().Add(-1*time.Hour)))
 
 	w.Header().Set("Content-Type", "text/event-stream")
 	w.Header().Set("Cache-Control", "no-cache")
@@ -0,0 +1,118 @@
+package handlers
+
+import (
+	"database/sql"
+	"errors"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/badge"
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// badgeMaxAge is how long READMEs, image proxies and browsers may cache a badge
+const badgeMaxAge = "public, max-age=300"
+
+// badgeMetrics maps badge metric names to the public counter metrics
+var badgeMetrics = map[string]string{
+	"views":          "pageviews",
+	"pageviews":      "pageviews",
+	"visitors":       "visitors",
+	"online":         "online",
+	"views_today":    "pageviews_today",
+	"visitors_today": "visitors_today",
+}
+
+// publicStatsMetrics are the metrics of a website with public stats enabled
+var publicStatsMetrics = []string{"online", "pageviews", "visitors"}
+
+// HandleBadge renders a metric as an embeddable SVG badge. The ID is either a
+// website ID with public stats enabled or a public token exposing the metric.
+// GET /badge/:id.svg?metric=views&label=views&style=flat|retro&color=4c1
+func HandleBadge(w http.ResponseWriter, r *http.Request) {
+	metric := queryString(r, "metric", "views")
+	opts := badge.Options{
+		Label: metric,
+		Style: r.URL.Query().Get("style"),
+		Color: strings.TrimPrefix(r.URL.Query().Get("color"), "#"),
+	}
+	if labels, ok := r.URL.Query()["label"]; ok {
+		// An empty label hides the label part
+		opts.Label = labels[0]
+	}
+
+	counterMetric, ok := badgeMetrics[metric]
+	if !ok {
+		writeBadgeError(w, http.StatusBadRequest, "unknown metric")
+		return
+	}
+	if err := opts.Validate(); err != nil {
+		writeBadgeError(w, http.StatusBadRequest, "invalid options")
+		return
+	}
+
+	token, err := badgeSource(r, chi.URLParam(r, "id"))
+	if errors.Is(err, models.ErrPublicTokenNotFound) || (err == nil && !token.Exposes(counterMetric)) {
+		writeBadgeError(w, http.StatusNotFound, "not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleBadge: %v", err)
+		writeBadgeError(w, http.StatusInternalServerError, "error")
+		return
+	}
+
+	// Only compute the requested metric
+	token.Metrics = []string{counterMetric}
+	counter, err := publicCounterData(r.Context(), token)
+	if err != nil {
+		log.Printf("HandleBadge: %v", err)
+		writeBadgeError(w, http.StatusInternalServerError, "error")
+		return
+	}
+	opts.Value = counter[counterMetric]
+
+	w.Header().Set("Content-Type", "image/svg+xml")
+	w.Header().Set("Cache-Control", badgeMaxAge)
+	w.Header().Set("Content-Security-Policy", "default-src 'none'; style-src 'unsafe-inline'")
+	_, _ = w.Write(badge.Render(opts))
+}
+
+// badgeSource resolves a badge ID to the metrics it may show: a public
+// token's, or the public stats of a website that has them enabled.
+// Returns ErrPublicTokenNotFound when neither applies.
+func badgeSource(r *http.Request, id string) (*models.PublicToken, error) {
+	if strings.HasPrefix(id, "kaunta_pub_") {
+		return models.GetPublicToken(r.Context(), database.DB, id)
+	}
+
+	websiteID, err := uuid.Parse(id)
+	if err != nil {
+		return nil, models.ErrPublicTokenNotFound
+	}
+	var enabled bool
+	err = database.QueryRowStats(database.WithTenant(r.Context(), websiteID),
+		`SELECT public_stats_enabled FROM website WHERE website_id = $1 AND deleted_at IS NULL`,
+		websiteID,
+	).Scan(&enabled)
+	if err == sql.ErrNoRows || (err == nil && !enabled) {
+		return nil, models.ErrPublicTokenNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &models.PublicToken{WebsiteID: websiteID, Metrics: publicStatsMetrics}, nil
+}
+
+// writeBadgeError answers with a gray badge so a broken embed still renders
+func writeBadgeError(w http.ResponseWriter, status int, message string) {
+	w.Header().Set("Content-Type", "image/svg+xml")
+	w.Header().Set("Cache-Control", "no-cache")
+	w.WriteHeader(status)
+	_, _ = w.Write(badge.RenderMessage("kaunta", message))
+}
@@ -0,0 +1,57 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestHandleBadge(t *testing.T) {
+	websiteID := uuid.New()
+	router, queue, cleanup := setupHTTPTest(t, "/badge/{id}.svg", HandleBadge, []mockResponse{
+		{match: "SELECT public_stats_enabled", columns: []string{"public_stats_enabled"}, rows: [][]any{{true}}},
+		{match: "INTERVAL '5 minutes'", columns: []string{"count"}, rows: [][]any{{int64(2)}}},
+		{match: "COUNT(*) as pageviews", columns: []string{"pageviews", "visitors"}, rows: [][]any{{int64(1234), int64(456)}}},
+	})
+	defer cleanup()
+
+	rec := httptest.NewRecorder()
+	router.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, "/badge/"+websiteID.String()+".svg?metric=views&label=hits", nil))
+
+	require.Equal(t, http.StatusOK, rec.Code)
+	assert.Equal(t, "image/svg+xml", rec.Header().Get("Content-Type"))
+	assert.Equal(t, badgeMaxAge, rec.Header().Get("Cache-Control"))
+	assert.Contains(t, rec.Body.String(), `aria-label="hits: 1.2k"`)
+	require.NoError(t, queue.expectationsMet())
+}
+
+func TestHandleBadgeErrors(t *testing.T) {
+	websiteID := uuid.New()
+	router, queue, cleanup := setupHTTPTest(t, "/badge/{id}.svg", HandleBadge, []mockResponse{
+		{match: "SELECT public_stats_enabled", columns: []string{"public_stats_enabled"}, rows: [][]any{{false}}},
+		{match: "SELECT public_stats_enabled", columns: []string{"public_stats_enabled"}, rows: [][]any{{true}}},
+	})
+	defer cleanup()
+
+	tests := []struct {
+		path   string
+		status int
+	}{
+		{"/badge/" + websiteID.String() + ".svg", http.StatusNotFound},
+		{"/badge/" + websiteID.String() + ".svg?metric=visitors_today", http.StatusNotFound},
+		{"/badge/" + websiteID.String() + ".svg?metric=revenue", http.StatusBadRequest},
+		{"/badge/" + websiteID.String() + ".svg?style=neon", http.StatusBadRequest},
+		{"/badge/not-a-website.svg", http.StatusNotFound},
+	}
+	for _, tt := range tests {
+		rec := httptest.NewRecorder()
+		router.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, tt.path, nil))
+		assert.Equal(t, tt.status, rec.Code, tt.path)
+		assert.Equal(t, "image/svg+xml", rec.Header().Get("Content-Type"), tt.path)
+	}
+	require.NoError(t, queue.expectationsMet())
+}
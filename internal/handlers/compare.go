@@ -0,0 +1,247 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+// compareMaxWebsites caps how many websites one comparison overlays
+const compareMaxWebsites = 5
+
+// CompareWebsite is one website's headline metrics and pageview series in a comparison
+type CompareWebsite struct {
+	WebsiteInfo
+	Visitors         int64    `json:"visitors"`
+	Pageviews        int64    `json:"pageviews"`
+	BounceRate       float64  `json:"bounce_rate"`
+	AvgVisitDuration float64  `json:"avg_visit_duration"`
+	Timestamps       []string `json:"timestamps"`
+	Values           []int64  `json:"values"`
+}
+
+// HandleCompareInit loads the user's websites for the comparison picker
+// GET /api/dashboard/compare-init
+func HandleCompareInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	rows, queryErr := database.QueryStats(r.Context(), `
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
+		ORDER BY domain
+	`, user.UserID)
+	if queryErr == nil {
+		websites = scanWebsiteInfos(rows.Rows)
+		_ = rows.Close()
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleCompareInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Failed to load websites",
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#compare-picker", buildComparePickerHTML(websites), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+	})
+}
+
+// HandleCompare returns headline metrics and overlaid pageview series for
+// up to compareMaxWebsites websites the user can view
+// GET /api/dashboard/compare?websites=id1,id2&days=7
+func HandleCompare(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	ids, parseErr := parseCompareWebsiteIDs(r.URL.Query().Get("websites"))
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+
+	var sites []CompareWebsite
+	var queryErr error
+	if user == nil {
+		parseErr = "Not authenticated"
+	} else if parseErr == "" {
+		sites, queryErr = loadCompareWebsites(r.Context(), user.UserID, ids, days)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			_ = sse.PatchSignals(map[string]any{"compareError": parseErr, "compareLoading": false})
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleCompare: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{"compareError": "Failed to load comparison", "compareLoading": false})
+			return
+		}
+
+		labels := []string{}
+		series := make([]map[string]any, 0, len(sites))
+		for _, site := range sites {
+			if len(site.Timestamps) > len(labels) {
+				labels = site.Timestamps
+			}
+			series = append(series, map[string]any{"label": websiteLabel(site.WebsiteInfo), "values": site.Values})
+		}
+
+		_ = sse.PatchElementsWithMode("#compare-metrics", buildCompareTableHTML(sites), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"compareChart": map[string]any{
+				"labels":      labels,
+				"series":      series,
+				"granularity": timeseriesGranularity("", days),
+			},
+			"compareError":   false,
+			"compareLoading": false,
+		})
+	})
+}
+
+// parseCompareWebsiteIDs parses a comma-separated list of website IDs,
+// dropping duplicates
+func parseCompareWebsiteIDs(raw string) ([]uuid.UUID, string) {
+	var ids []uuid.UUID
+	seen := make(map[uuid.UUID]bool)
+	for part := range strings.SplitSeq(raw, ",") {
+		part = strings.TrimSpace(part)
+		if part == "" {
+			continue
+		}
+		id, err := uuid.Parse(part)
+		if err != nil {
+			return nil, "Invalid website ID"
+		}
+		if !seen[id] {
+			seen[id] = true
+			ids = append(ids, id)
+		}
+	}
+	if len(ids) == 0 {
+		return nil, "Select at least one website"
+	}
+	if len(ids) > compareMaxWebsites {
+		return nil, fmt.Sprintf("Compare at most %d websites", compareMaxWebsites)
+	}
+	return ids, ""
+}
+
+// loadCompareWebsites loads the metrics and series of each requested website
+// the user can view, in the requested order; any others are skipped
+func loadCompareWebsites(ctx context.Context, userID uuid.UUID, ids []uuid.UUID, days int) ([]CompareWebsite, error) {
+	rows, err := database.QueryStats(ctx, `
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1)) AND website_id = ANY($2)
+	`, userID, pq.Array(ids))
+	if err != nil {
+		return nil, err
+	}
+	owned := make(map[string]WebsiteInfo)
+	for _, site := range scanWebsiteInfos(rows.Rows) {
+		owned[site.ID] = site
+	}
+	_ = rows.Close()
+
+	granularity := timeseriesGranularity("", days)
+	sites := make([]CompareWebsite, 0, len(ids))
+	for _, id := range ids {
+		info, ok := owned[id.String()]
+		if !ok {
+			continue
+		}
+		site := CompareWebsite{WebsiteInfo: info}
+
+		var current int64
+		if err := database.QueryRowStats(ctx,
+			`SELECT * FROM get_dashboard_stats($1, $2, NULL, NULL, NULL, NULL)`,
+			id, days,
+		).Scan(&current, &site.Pageviews, &site.Visitors, &site.BounceRate, &site.AvgVisitDuration); err != nil {
+			return nil, fmt.Errorf("stats for %s: %w", info.Domain, err)
+		}
+
+		series, err := database.QueryStats(ctx,
+			`SELECT * FROM get_timeseries($1, $2, NULL, NULL, NULL, NULL, $3)`,
+			id, days, granularity,
+		)
+		if err != nil {
+			return nil, fmt.Errorf("timeseries for %s: %w", info.Domain, err)
+		}
+		for series.Next() {
+			var timestamp string
+			var value int64
+			if err := series.Scan(&timestamp, &value); err != nil {
+				_ = series.Close()
+				return nil, err
+			}
+			site.Timestamps = append(site.Timestamps, timestamp)
+			site.Values = append(site.Values, value)
+		}
+		_ = series.Close()
+
+		sites = append(sites, site)
+	}
+	return sites, nil
+}
+
+func buildComparePickerHTML(websites []WebsiteInfo) string {
+	if len(websites) == 0 {
+		return ""
+	}
+
+	var b strings.Builder
+	for _, site := range websites {
+		id := jsStringEscape(site.ID)
+		toggle := fmt.Sprintf(`$compareSelected = $compareSelected.includes('%[1]s') ? $compareSelected.filter(id => id !== '%[1]s') : [...$compareSelected, '%[1]s'].slice(-%[2]d); localStorage.setItem('kaunta_compare', JSON.stringify($compareSelected))`,
+			id, compareMaxWebsites)
+		fmt.Fprintf(&b, `<button class="btn btn-sm compare-site" data-class:active="$compareSelected.includes('%s')" data-on:click="%s">%s</button>`,
+			id, escapeHTML(toggle), escapeHTML(websiteLabel(site)))
+	}
+	return b.String()
+}
+
+func buildCompareTableHTML(sites []CompareWebsite) string {
+	if len(sites) == 0 {
+		return `<div class="empty-state-mini">Select websites to compare</div>`
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="breakdown-table"><thead><tr><th>Website</th><th style="text-align:right">Visitors</th><th style="text-align:right">Pageviews</th><th style="text-align:right">Bounce rate</th><th style="text-align:right">Avg. visit</th></tr></thead><tbody>`)
+	for i, site := range sites {
+		fmt.Fprintf(&b, `<tr><td><span class="compare-swatch compare-swatch-%d"></span>%s</td><td style="text-align:right">%s</td><td style="text-align:right">%s</td><td style="text-align:right">%.1f%%</td><td style="text-align:right">%s</td></tr>`,
+			i,
+			escapeHTML(websiteLabel(site.WebsiteInfo)),
+			formatNumber(int(site.Visitors)),
+			formatNumber(int(site.Pageviews)),
+			site.BounceRate,
+			formatDuration(site.AvgVisitDuration),
+		)
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
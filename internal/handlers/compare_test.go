@@ -0,0 +1,83 @@
+package handlers
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestParseCompareWebsiteIDs(t *testing.T) {
+	a, b := uuid.New(), uuid.New()
+
+	ids, errMsg := parseCompareWebsiteIDs(a.String() + ", " + b.String() + "," + a.String())
+	assert.Empty(t, errMsg)
+	assert.Equal(t, []uuid.UUID{a, b}, ids)
+
+	_, errMsg = parseCompareWebsiteIDs("")
+	assert.Equal(t, "Select at least one website", errMsg)
+
+	_, errMsg = parseCompareWebsiteIDs(a.String() + ",nope")
+	assert.Equal(t, "Invalid website ID", errMsg)
+
+	many := ""
+	for range compareMaxWebsites + 1 {
+		many += uuid.NewString() + ","
+	}
+	_, errMsg = parseCompareWebsiteIDs(many)
+	assert.Contains(t, errMsg, "at most")
+}
+
+func TestLoadCompareWebsitesSkipsWebsitesNotOwned(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	userID, owned, foreign := uuid.New(), uuid.New(), uuid.New()
+	mock.ExpectQuery("FROM website").
+		WithArgs(userID, sqlmock.AnyArg()).
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "name", "domain"}).
+			AddRow(owned.String(), "EU", "eu.example.com"))
+	mock.ExpectQuery("get_dashboard_stats").
+		WithArgs(owned, 7).
+		WillReturnRows(sqlmock.NewRows([]string{"current", "pageviews", "visitors", "bounce", "duration"}).
+			AddRow(1, 120, 40, 35.5, 62.0))
+	mock.ExpectQuery("get_timeseries").
+		WithArgs(owned, 7, granularityHour).
+		WillReturnRows(sqlmock.NewRows([]string{"bucket", "value"}).
+			AddRow("2026-01-01T00:00:00Z", 5).
+			AddRow("2026-01-01T01:00:00Z", 8))
+
+	sites, err := loadCompareWebsites(context.Background(), userID, []uuid.UUID{foreign, owned}, 7)
+	require.NoError(t, err)
+	require.Len(t, sites, 1)
+	assert.Equal(t, "eu.example.com", sites[0].Domain)
+	assert.Equal(t, int64(120), sites[0].Pageviews)
+	assert.Equal(t, int64(40), sites[0].Visitors)
+	assert.Equal(t, []int64{5, 8}, sites[0].Values)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestBuildCompareTableHTML(t *testing.T) {
+	html := buildCompareTableHTML([]CompareWebsite{{
+		WebsiteInfo: WebsiteInfo{ID: uuid.NewString(), Name: "<Brand>", Domain: "brand.example"},
+		Visitors:    1200,
+		BounceRate:  41.26,
+	}})
+	assert.Contains(t, html, "&lt;Brand&gt;")
+	assert.Contains(t, html, "1,200")
+	assert.Contains(t, html, "41.3%")
+	assert.Contains(t, html, "compare-swatch-0")
+
+	assert.Contains(t, buildCompareTableHTML(nil), "Select websites to compare")
+}
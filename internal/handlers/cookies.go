@@ -0,0 +1,91 @@
+package handlers
+
+import (
+	"net/http"
+	"strings"
+	"time"
+)
+
+var (
+	// cookieSameSite overrides the SameSite attribute of the session and
+	// CSRF cookies; zero keeps the defaults
+	cookieSameSite http.SameSite
+	// cookieDomain is the Domain attribute of the session and CSRF cookies;
+	// empty makes them host-only
+	cookieDomain string
+)
+
+// SetCookiePolicy applies the cookies.same_site and cookies.domain settings.
+// An empty or unknown sameSite keeps the defaults.
+func SetCookiePolicy(sameSite, domain string) {
+	cookieSameSite = 0
+	switch strings.ToLower(sameSite) {
+	case "lax", "strict", "none":
+		cookieSameSite = parseSameSite(sameSite)
+	}
+	cookieDomain = domain
+}
+
+// sessionCookie builds the kaunta_session cookie. Without a configured
+// SameSite it is None when Secure (for cross-domain CNAME setups) and Lax
+// otherwise.
+func sessionCookie(value string, expires time.Time) *http.Cookie {
+	secure := secureCookiesEnabled()
+	sameSite := cookieSameSite
+	if sameSite == 0 {
+		sameSite = http.SameSiteLaxMode
+		if secure {
+			sameSite = http.SameSiteNoneMode
+		}
+	}
+	return &http.Cookie{
+		Name:     "kaunta_session",
+		Value:    value,
+		Expires:  expires,
+		HttpOnly: true,
+		Secure:   secure,
+		SameSite: sameSite,
+		Domain:   cookieDomain,
+		Path:     "/",
+	}
+}
+
+// CSRFCookie builds the kaunta_csrf cookie. Scripts read it to send the
+// token back in the X-CSRF-Token header, so it isn't HttpOnly.
+func CSRFCookie(value string, expires time.Time, secure bool) *http.Cookie {
+	sameSite := cookieSameSite
+	if sameSite == 0 {
+		sameSite = http.SameSiteLaxMode
+	}
+	return &http.Cookie{
+		Name:     "kaunta_csrf",
+		Value:    value,
+		Path:     "/",
+		Domain:   cookieDomain,
+		Secure:   secure,
+		HttpOnly: false,
+		SameSite: sameSite,
+		Expires:  expires,
+	}
+}
+
+// kioskCookieName holds the access key of a password-protected kiosk link
+const kioskCookieName = "kaunta_kiosk"
+
+// kioskCookie builds the cookie that keeps a protected kiosk link unlocked.
+// It is scoped to the link's API path, so each link has its own.
+func kioskCookie(token, accessKey string) *http.Cookie {
+	sameSite := cookieSameSite
+	if sameSite == 0 {
+		sameSite = http.SameSiteLaxMode
+	}
+	return &http.Cookie{
+		Name:     kioskCookieName,
+		Value:    accessKey,
+		Path:     "/api/public/kiosk/" + token,
+		Expires:  time.Now().Add(30 * 24 * time.Hour),
+		HttpOnly: true,
+		Secure:   secureCookiesEnabled(),
+		SameSite: sameSite,
+	}
+}
@@ -0,0 +1,34 @@
+package handlers
+
+import (
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestCookiePolicy(t *testing.T) {
+	t.Cleanup(func() { SetCookiePolicy("", "") })
+	t.Setenv("SECURE_COOKIES", "true")
+
+	// Defaults: None with Secure for the session, Lax for CSRF, host-only
+	session := sessionCookie("token", time.Now())
+	assert.Equal(t, http.SameSiteNoneMode, session.SameSite)
+	assert.True(t, session.Secure)
+	assert.Empty(t, session.Domain)
+	assert.Equal(t, http.SameSiteLaxMode, CSRFCookie("csrf", time.Now(), true).SameSite)
+
+	SetCookiePolicy("strict", "example.com")
+	session = sessionCookie("token", time.Now())
+	assert.Equal(t, http.SameSiteStrictMode, session.SameSite)
+	assert.Equal(t, "example.com", session.Domain)
+	csrf := CSRFCookie("csrf", time.Now(), true)
+	assert.Equal(t, http.SameSiteStrictMode, csrf.SameSite)
+	assert.Equal(t, "example.com", csrf.Domain)
+
+	// Unknown values keep the defaults
+	t.Setenv("SECURE_COOKIES", "false")
+	SetCookiePolicy("sideways", "")
+	assert.Equal(t, http.SameSiteLaxMode, sessionCookie("token", time.Now()).SameSite)
+}
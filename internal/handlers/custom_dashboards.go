@@ -0,0 +1,679 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"slices"
+	"strconv"
+	"strings"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// customWidgetTopLimit is the number of rows shown by breakdown widgets
+const customWidgetTopLimit = 10
+
+// customWidgetMetricLabels are the display names for metric widgets
+var customWidgetMetricLabels = map[string]string{
+	"visitors":           "Visitors",
+	"pageviews":          "Pageviews",
+	"bounce_rate":        "Bounce rate",
+	"avg_visit_duration": "Avg. visit duration",
+	"current_visitors":   "Current visitors",
+}
+
+// HandleCustomDashboardsInit loads websites and the user's custom dashboards
+// GET /api/dashboard/custom-init
+func HandleCustomDashboardsInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	rows, queryErr := database.DB.Query(`
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
+		ORDER BY domain
+	`, user.UserID)
+	if queryErr == nil {
+		websites = scanWebsiteInfos(rows)
+	}
+
+	var dashboards []models.CustomDashboard
+	if queryErr == nil {
+		dashboards, queryErr = models.ListDashboards(r.Context(), database.DB, user.UserID)
+	}
+
+	selectedWebsite := selectedWebsiteFromRequest(r)
+	if selectedWebsite == "" && len(websites) > 0 {
+		selectedWebsite = websites[0].ID
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleCustomDashboardsInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Failed to load dashboards",
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#custom-dashboard-list", buildCustomDashboardListHTML(dashboards, websites), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"selectedWebsite": selectedWebsite,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+		if html := buildWebsiteSelectorHTML(websites, selectedWebsite, "custom"); html != "" {
+			_ = sse.PatchElements("#website-selector-container", html)
+		}
+	})
+}
+
+// HandleCustomDashboardCreate creates an empty dashboard for the selected website
+// POST /api/dashboard/custom (form: website_id, name)
+func HandleCustomDashboardCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	name := strings.TrimSpace(r.FormValue("name"))
+	websiteID, err := uuid.Parse(r.FormValue("website_id"))
+	if name == "" || len(name) > 100 || err != nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"customError": "A name (up to 100 characters) and a website are required",
+				"submitting":  false,
+			})
+		})
+		return
+	}
+
+	dashboard, err := models.CreateDashboard(r.Context(), database.DB, user.UserID, websiteID, name)
+	if err != nil {
+		log.Printf("HandleCustomDashboardCreate: %v", err)
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"customError": "Failed to create dashboard",
+				"submitting":  false,
+			})
+		})
+		return
+	}
+
+	dashboards, listErr := models.ListDashboards(r.Context(), database.DB, user.UserID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if listErr == nil {
+			_ = sse.PatchElementsWithMode("#custom-dashboard-list", buildCustomDashboardListHTML(dashboards, nil), "inner")
+		}
+		_ = sse.PatchSignals(map[string]any{
+			"activeDashboard": dashboardSignal(dashboard),
+			"dashboardForm":   map[string]string{"name": ""},
+			"customError":     false,
+			"submitting":      false,
+		})
+		_ = sse.PatchElementsWithMode("#custom-widget-grid", buildCustomWidgetGridHTML(dashboard, nil, nil, true), "inner")
+	})
+}
+
+// HandleCustomDashboardShow renders every widget of a dashboard the user owns
+// GET /api/dashboard/custom/{id}
+func HandleCustomDashboardShow(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	streamCustomDashboard(w, r, &user.UserID)
+}
+
+// HandlePublicCustomDashboard renders a dashboard shared via its public link
+// GET /api/public/dashboards/{id}
+func HandlePublicCustomDashboard(w http.ResponseWriter, r *http.Request) {
+	streamCustomDashboard(w, r, nil)
+}
+
+func streamCustomDashboard(w http.ResponseWriter, r *http.Request, ownerID *uuid.UUID) {
+	dashboardID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid dashboard ID")
+		return
+	}
+
+	dashboard, err := models.GetDashboard(r.Context(), database.DB, dashboardID, ownerID)
+	if errors.Is(err, models.ErrDashboardNotFound) {
+		respondError(w, r, http.StatusNotFound, "Dashboard not found")
+		return
+	}
+	if err != nil {
+		log.Printf("streamCustomDashboard: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to load dashboard")
+		return
+	}
+
+	editable := ownerID != nil
+	grid, err := renderCustomDashboardGrid(r.Context(), dashboard, editable)
+	if err != nil {
+		log.Printf("streamCustomDashboard: list widgets: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to load dashboard")
+		return
+	}
+
+	var goals []goalOption
+	if editable {
+		goals = loadGoalOptions(dashboard.WebsiteID)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchElementsWithMode("#custom-widget-grid", grid, "inner")
+		signals := map[string]any{
+			"activeDashboard":  dashboardSignal(dashboard),
+			"dashboardLoading": false,
+		}
+		if editable {
+			signals["dashboardGoalCount"] = len(goals)
+			_ = sse.PatchElementsWithMode("#widget-goal", buildGoalOptionsHTML(goals), "inner")
+		}
+		_ = sse.PatchSignals(signals)
+	})
+}
+
+// HandleCustomDashboardDelete removes a dashboard
+// DELETE /api/dashboard/custom/{id}
+func HandleCustomDashboardDelete(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	dashboardID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid dashboard ID")
+		return
+	}
+
+	if err := models.DeleteDashboard(r.Context(), database.DB, dashboardID, user.UserID); err != nil {
+		respondError(w, r, http.StatusNotFound, "Dashboard not found")
+		return
+	}
+
+	dashboards, listErr := models.ListDashboards(r.Context(), database.DB, user.UserID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if listErr == nil {
+			_ = sse.PatchElementsWithMode("#custom-dashboard-list", buildCustomDashboardListHTML(dashboards, nil), "inner")
+		}
+		_ = sse.PatchElementsWithMode("#custom-widget-grid", "", "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"activeDashboard": map[string]any{"id": "", "name": "", "website_id": "", "is_public": false},
+		})
+	})
+}
+
+// HandleCustomDashboardShare turns the public share link on or off
+// POST /api/dashboard/custom/{id}/share?public=true
+func HandleCustomDashboardShare(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	dashboardID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid dashboard ID")
+		return
+	}
+	public, _ := strconv.ParseBool(r.URL.Query().Get("public"))
+
+	// Publishing exposes the website's stats, which only its owners may do
+	if public {
+		dashboard, err := models.GetDashboard(r.Context(), database.DB, dashboardID, &user.UserID)
+		if err != nil {
+			respondError(w, r, http.StatusNotFound, "Dashboard not found")
+			return
+		}
+		if !userHasWebsiteRole(r.Context(), user.UserID, dashboard.WebsiteID, models.RoleOwner) {
+			respondError(w, r, http.StatusForbidden, "Only the website's owners can share its stats publicly")
+			return
+		}
+	}
+
+	if err := models.SetDashboardPublic(r.Context(), database.DB, dashboardID, user.UserID, public); err != nil {
+		respondError(w, r, http.StatusNotFound, "Dashboard not found")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{
+			"activeDashboard": map[string]any{"is_public": public},
+		})
+	})
+}
+
+// HandleCustomWidgetCreate appends a widget bound to a saved query
+// POST /api/dashboard/custom/{id}/widgets (form: kind, title, days, metric, dimension, goal_id, country, browser, device, page)
+func HandleCustomWidgetCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	dashboardID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid dashboard ID")
+		return
+	}
+
+	dashboard, err := models.GetDashboard(r.Context(), database.DB, dashboardID, &user.UserID)
+	if err != nil {
+		respondError(w, r, http.StatusNotFound, "Dashboard not found")
+		return
+	}
+
+	kind := r.FormValue("kind")
+	title := strings.TrimSpace(r.FormValue("title"))
+	query := widgetQueryFromForm(r)
+	if err := query.Validate(kind, customWidgetDimensions()); err != nil || len(title) > 100 {
+		msg := "Title must be at most 100 characters"
+		if err != nil {
+			msg = err.Error()
+		}
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"customError": msg, "submitting": false})
+		})
+		return
+	}
+
+	if _, err := models.AddWidget(r.Context(), database.DB, dashboard.ID, kind, title, query); err != nil {
+		log.Printf("HandleCustomWidgetCreate: %v", err)
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"customError": "Failed to add widget", "submitting": false})
+		})
+		return
+	}
+
+	grid, gridErr := renderCustomDashboardGrid(r.Context(), dashboard, true)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if gridErr == nil {
+			_ = sse.PatchElementsWithMode("#custom-widget-grid", grid, "inner")
+		}
+		_ = sse.PatchSignals(map[string]any{
+			"showWidgetModal": false,
+			"customError":     false,
+			"submitting":      false,
+		})
+	})
+}
+
+// HandleCustomWidgetDelete removes a widget from a dashboard
+// DELETE /api/dashboard/custom/{id}/widgets/{widget_id}
+func HandleCustomWidgetDelete(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	dashboardID, err1 := uuid.Parse(chi.URLParam(r, "id"))
+	widgetID, err2 := uuid.Parse(chi.URLParam(r, "widget_id"))
+	if err1 != nil || err2 != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid ID")
+		return
+	}
+
+	if err := models.DeleteWidget(r.Context(), database.DB, widgetID, dashboardID, user.UserID); err != nil {
+		respondError(w, r, http.StatusNotFound, "Widget not found")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchElements(fmt.Sprintf("#widget-%s", widgetID), fmt.Sprintf(`<div id="widget-%s" hidden></div>`, widgetID))
+	})
+}
+
+// renderCustomDashboardGrid runs every widget's saved query and renders the grid
+func renderCustomDashboardGrid(ctx context.Context, dashboard *models.CustomDashboard, editable bool) (string, error) {
+	widgets, err := models.ListWidgets(ctx, database.DB, dashboard.ID)
+	if err != nil {
+		return "", err
+	}
+
+	// A public link has no signed-in user to scope its stats queries by, and
+	// a dashboard only ever shows its own website
+	ctx = database.WithTenant(ctx, dashboard.WebsiteID)
+
+	bodies := make([]string, len(widgets))
+	for i, widget := range widgets {
+		bodies[i] = renderCustomWidgetBody(ctx, dashboard.WebsiteID, widget)
+	}
+	return buildCustomWidgetGridHTML(dashboard, widgets, bodies, editable), nil
+}
+
+func widgetQueryFromForm(r *http.Request) models.WidgetQuery {
+	days, err := strconv.Atoi(r.FormValue("days"))
+	if err != nil {
+		days = 7
+	}
+	return models.WidgetQuery{
+		Days:      days,
+		Metric:    r.FormValue("metric"),
+		Dimension: r.FormValue("dimension"),
+		GoalID:    r.FormValue("goal_id"),
+		Country:   strings.TrimSpace(r.FormValue("country")),
+		Browser:   strings.TrimSpace(r.FormValue("browser")),
+		Device:    strings.TrimSpace(r.FormValue("device")),
+		Page:      strings.TrimSpace(r.FormValue("page")),
+	}
+}
+
+// customWidgetDimensions lists the breakdown tabs a widget can be bound to
+func customWidgetDimensions() []string {
+	dims := make([]string, 0, len(breakdownDimensions))
+	for tab := range breakdownDimensions {
+		dims = append(dims, tab)
+	}
+	slices.Sort(dims)
+	return dims
+}
+
+func dashboardSignal(d *models.CustomDashboard) map[string]any {
+	return map[string]any{
+		"id":         d.ID.String(),
+		"name":       d.Name,
+		"website_id": d.WebsiteID.String(),
+		"is_public":  d.IsPublic,
+	}
+}
+
+type goalOption struct {
+	ID   string
+	Name string
+}
+
+func loadGoalOptions(websiteID uuid.UUID) []goalOption {
+	rows, err := database.DB.Query(`SELECT id, name FROM goals WHERE website_id = $1 ORDER BY name`, websiteID)
+	if err != nil {
+		return []goalOption{}
+	}
+	defer func() { _ = rows.Close() }()
+
+	goals := make([]goalOption, 0)
+	for rows.Next() {
+		var g goalOption
+		if err := rows.Scan(&g.ID, &g.Name); err == nil {
+			goals = append(goals, g)
+		}
+	}
+	return goals
+}
+
+func buildGoalOptionsHTML(goals []goalOption) string {
+	var b strings.Builder
+	b.WriteString(`<option value="">Select goal...</option>`)
+	for _, g := range goals {
+		fmt.Fprintf(&b, `<option value="%s">%s</option>`, escapeHTML(g.ID), escapeHTML(g.Name))
+	}
+	return b.String()
+}
+
+// widgetFilterArgs converts a widget's segment filters to SQL arguments (NULL when unset)
+func widgetFilterArgs(q models.WidgetQuery) (country, browser, device, page any) {
+	if q.Country != "" {
+		country = q.Country
+	}
+	if q.Browser != "" {
+		browser = q.Browser
+	}
+	if q.Device != "" {
+		device = q.Device
+	}
+	if q.Page != "" {
+		page = q.Page
+	}
+	return
+}
+
+// renderCustomWidgetBody runs a widget's saved query and renders its content
+func renderCustomWidgetBody(ctx context.Context, websiteID uuid.UUID, widget models.DashboardWidget) string {
+	q := widget.Query
+	country, browser, device, page := widgetFilterArgs(q)
+
+	switch widget.Kind {
+	case models.WidgetMetric:
+		var current, pageviews, visitors int64
+		var bounce, duration float64
+		err := database.QueryRowStats(ctx,
+			`SELECT * FROM get_dashboard_stats($1, $2, $3, $4, $5, $6)`,
+			websiteID, q.Days, country, browser, device, page,
+		).Scan(&current, &pageviews, &visitors, &bounce, &duration)
+		if err != nil {
+			return customWidgetError(err)
+		}
+		values := map[string]string{
+			"visitors":           formatNumber(int(visitors)),
+			"pageviews":          formatNumber(int(pageviews)),
+			"bounce_rate":        fmt.Sprintf("%.1f%%", bounce),
+			"avg_visit_duration": formatDuration(duration),
+			"current_visitors":   formatNumber(int(current)),
+		}
+		return buildCustomMetricHTML(customWidgetMetricLabels[q.Metric], values[q.Metric])
+
+	case models.WidgetTimeseries:
+		rows, err := database.QueryStats(ctx,
+			`SELECT * FROM get_timeseries($1, $2, $3, $4, $5, $6, $7)`,
+			websiteID, q.Days, country, browser, device, page, timeseriesGranularity("", q.Days),
+		)
+		if err != nil {
+			return customWidgetError(err)
+		}
+		defer func() { _ = rows.Close() }()
+		var values []int64
+		var total int64
+		for rows.Next() {
+			var bucket sql.NullTime
+			var views int64
+			if err := rows.Scan(&bucket, &views); err != nil {
+				return customWidgetError(err)
+			}
+			values = append(values, views)
+			total += views
+		}
+		return fmt.Sprintf(`<div class="stat-value">%s</div><div class="stat-label">Pageviews</div><div class="custom-widget-chart">%s</div>`,
+			formatNumber(int(total)), buildSparklineSVG(values, 480, 96))
+
+	case models.WidgetBreakdown:
+		items, err := loadCustomBreakdown(ctx, websiteID, q)
+		if err != nil {
+			return customWidgetError(err)
+		}
+		return buildBreakdownTableHTML(q.Dimension, items)
+
+	case models.WidgetGoal:
+		var name string
+		var completions, uniqueSessions, totalSessions int
+		err := database.QueryRowStats(ctx, `
+			SELECT
+				g.name,
+				(SELECT COUNT(*) FROM goal_completions gc
+				  WHERE gc.goal_id = g.id AND gc.completed_at >= NOW() - ($3 || ' days')::INTERVAL),
+				(SELECT COUNT(DISTINCT gc.session_id) FROM goal_completions gc
+				  WHERE gc.goal_id = g.id AND gc.completed_at >= NOW() - ($3 || ' days')::INTERVAL),
+				(SELECT COUNT(DISTINCT we.session_id) FROM website_event we
+				  WHERE we.website_id = g.website_id AND we.created_at >= NOW() - ($3 || ' days')::INTERVAL)
+			FROM goals g
+			WHERE g.id = $1 AND g.website_id = $2
+		`, q.GoalID, websiteID, q.Days).Scan(&name, &completions, &uniqueSessions, &totalSessions)
+		if err == sql.ErrNoRows {
+			return `<div class="empty-state-mini">Goal no longer exists</div>`
+		}
+		if err != nil {
+			return customWidgetError(err)
+		}
+		rate := 0.0
+		if totalSessions > 0 {
+			rate = float64(uniqueSessions) / float64(totalSessions) * 100
+		}
+		return buildCustomMetricHTML(name+" completions", formatNumber(completions)) +
+			fmt.Sprintf(`<div class="stat-label">%.1f%% conversion rate</div>`, rate)
+	}
+
+	return `<div class="empty-state-mini">Unsupported widget</div>`
+}
+
+func loadCustomBreakdown(ctx context.Context, websiteID uuid.UUID, q models.WidgetQuery) ([]BreakdownItem, error) {
+	country, browser, device, page := widgetFilterArgs(q)
+
+	var rows *database.StatsRows
+	var err error
+	switch q.Dimension {
+	case "pages":
+		rows, err = database.QueryStats(ctx,
+			`SELECT path, views, total_count FROM get_top_pages($1, $2, $3, 0, $4, $5, $6, 'views', 'desc', NULL)`,
+			websiteID, q.Days, customWidgetTopLimit, country, browser, device,
+		)
+	default:
+		if q.Dimension == "countries" {
+			country = nil
+		}
+		rows, err = database.QueryStats(ctx,
+			`SELECT * FROM get_breakdown($1, $2, $3, $4, 0, $5, $6, $7, $8, 'count', 'desc')`,
+			websiteID, breakdownDimensions[q.Dimension], q.Days, customWidgetTopLimit, country, browser, device, page,
+		)
+	}
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]BreakdownItem, 0)
+	for rows.Next() {
+		var item BreakdownItem
+		var total int64
+		if err := rows.Scan(&item.Name, &item.Count, &total); err != nil {
+			return nil, err
+		}
+		if q.Dimension == "countries" {
+			item.Code = item.Name
+			item.Name = getCountryName(item.Code)
+		}
+		items = append(items, item)
+	}
+	return items, rows.Err()
+}
+
+func customWidgetError(err error) string {
+	log.Printf("custom dashboard widget: %v", err)
+	return `<div class="empty-state-mini">Unable to load this widget</div>`
+}
+
+func buildCustomMetricHTML(label, value string) string {
+	return fmt.Sprintf(`<div class="stat-value">%s</div><div class="stat-label">%s</div>`, escapeHTML(value), escapeHTML(label))
+}
+
+func buildCustomDashboardListHTML(dashboards []models.CustomDashboard, websites []WebsiteInfo) string {
+	if len(dashboards) == 0 {
+		return `<div class="empty-state-mini"><div>[+]</div><div>No custom dashboards yet</div></div>`
+	}
+
+	domains := make(map[string]string, len(websites))
+	for _, w := range websites {
+		domains[w.ID] = websiteLabel(w)
+	}
+
+	var b strings.Builder
+	for _, d := range dashboards {
+		badge := ""
+		if d.IsPublic {
+			badge = ` <span class="badge">public</span>`
+		}
+		subtitle := ""
+		if label := domains[d.WebsiteID.String()]; label != "" {
+			subtitle = `<div class="stat-label">` + escapeHTML(label) + `</div>`
+		}
+		id := d.ID.String()
+		fmt.Fprintf(&b, `<button class="custom-dashboard-item btn btn-ghost" data-class:active="$activeDashboard && $activeDashboard.id === '%s'" data-on:click="$dashboardLoading = true; @get('/api/dashboard/custom/%s')"><span>%s%s</span>%s</button>`,
+			id, id, escapeHTML(d.Name), badge, subtitle)
+	}
+	return b.String()
+}
+
+func buildCustomWidgetGridHTML(d *models.CustomDashboard, widgets []models.DashboardWidget, bodies []string, editable bool) string {
+	if len(widgets) == 0 {
+		if editable {
+			return `<div class="empty-state"><div class="empty-state-icon">[+]</div><div class="empty-state-title">This dashboard is empty</div><div class="empty-state-text">Add a metric, chart, breakdown or goal widget to get started.</div></div>`
+		}
+		return `<div class="empty-state"><div class="empty-state-title">Nothing to show yet</div></div>`
+	}
+
+	var b strings.Builder
+	for i, widget := range widgets {
+		title := widget.Title
+		if title == "" {
+			title = defaultWidgetTitle(widget)
+		}
+		remove := ""
+		if editable {
+			remove = fmt.Sprintf(`<button class="btn btn-xs btn-ghost" title="Remove widget" data-on:click="@delete('/api/dashboard/custom/%s/widgets/%s', { headers: { 'X-CSRF-Token': getCustomDashboardCsrfToken() } })">&times;</button>`,
+				d.ID, widget.ID)
+		}
+		fmt.Fprintf(&b, `<div id="widget-%s" class="section glass card custom-widget custom-widget-%s"><div class="section-header"><h3>%s</h3><span class="stat-label">%s</span>%s</div>%s</div>`,
+			widget.ID,
+			escapeHTML(widget.Kind),
+			escapeHTML(title),
+			escapeHTML(describeWidgetQuery(widget.Query)),
+			remove,
+			bodies[i],
+		)
+	}
+	return b.String()
+}
+
+func defaultWidgetTitle(widget models.DashboardWidget) string {
+	switch widget.Kind {
+	case models.WidgetMetric:
+		return customWidgetMetricLabels[widget.Query.Metric]
+	case models.WidgetTimeseries:
+		return "Pageviews over time"
+	case models.WidgetBreakdown:
+		return breakdownHeaderLabel(widget.Query.Dimension)
+	case models.WidgetGoal:
+		return "Goal"
+	}
+	return "Widget"
+}
+
+// describeWidgetQuery summarises the period and segment of a saved query, e.g. "7 days · country DE"
+func describeWidgetQuery(q models.WidgetQuery) string {
+	parts := []string{fmt.Sprintf("%d days", q.Days)}
+	for _, f := range []struct{ name, value string }{
+		{"country", q.Country}, {"browser", q.Browser}, {"device", q.Device}, {"page", q.Page},
+	} {
+		if f.value != "" {
+			parts = append(parts, f.name+" "+f.value)
+		}
+	}
+	return strings.Join(parts, " · ")
+}
@@ -0,0 +1,74 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestDescribeWidgetQuery(t *testing.T) {
+	assert.Equal(t, "7 days", describeWidgetQuery(models.WidgetQuery{Days: 7}))
+	assert.Equal(t, "30 days · country DE · device mobile",
+		describeWidgetQuery(models.WidgetQuery{Days: 30, Country: "DE", Device: "mobile"}))
+}
+
+func TestBuildCustomWidgetGridHTML(t *testing.T) {
+	dashboard := &models.CustomDashboard{ID: uuid.New(), Name: "Launch"}
+	widgets := []models.DashboardWidget{
+		{ID: uuid.New(), Kind: models.WidgetMetric, Query: models.WidgetQuery{Days: 1, Metric: "visitors"}},
+		{ID: uuid.New(), Kind: models.WidgetBreakdown, Title: "<Top> pages", Query: models.WidgetQuery{Days: 7, Dimension: "pages"}},
+	}
+	bodies := []string{buildCustomMetricHTML("Visitors", "1,200"), "<table></table>"}
+
+	editable := buildCustomWidgetGridHTML(dashboard, widgets, bodies, true)
+	assert.Contains(t, editable, "<h3>Visitors</h3>")
+	assert.Contains(t, editable, "&lt;Top&gt; pages")
+	assert.Contains(t, editable, "1,200")
+	assert.Contains(t, editable, "@delete('/api/dashboard/custom/"+dashboard.ID.String()+"/widgets/"+widgets[0].ID.String())
+
+	shared := buildCustomWidgetGridHTML(dashboard, widgets, bodies, false)
+	assert.NotContains(t, shared, "@delete")
+
+	assert.Contains(t, buildCustomWidgetGridHTML(dashboard, nil, nil, true), "This dashboard is empty")
+}
+
+func TestRenderCustomDashboardGridRunsAsTenant(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	originalDB := database.DB
+	database.DB = db
+	database.SetTenantIsolation(true)
+	t.Cleanup(func() {
+		database.DB = originalDB
+		database.SetTenantIsolation(false)
+	})
+
+	// A public link has no user, so the dashboard's website is the only scope
+	dashboard := &models.CustomDashboard{ID: uuid.New(), WebsiteID: uuid.New(), Name: "Launch"}
+	mock.ExpectQuery("FROM custom_dashboard_widget").
+		WithArgs(dashboard.ID).
+		WillReturnRows(sqlmock.NewRows([]string{"widget_id", "dashboard_id", "position", "kind", "title", "query"}).
+			AddRow(uuid.New(), dashboard.ID, 0, models.WidgetMetric, "", []byte(`{"days":7,"metric":"visitors"}`)))
+	mock.ExpectBegin()
+	mock.ExpectExec("SELECT set_config\\('role', 'kaunta_tenant', true\\)").
+		WithArgs(fmt.Sprintf(`{"%s"}`, dashboard.WebsiteID)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectQuery("get_dashboard_stats").
+		WillReturnRows(sqlmock.NewRows([]string{"current_visitors", "pageviews", "visitors", "bounce_rate", "avg_visit_duration"}).
+			AddRow(int64(2), int64(300), int64(1200), 40.0, 61.5))
+	mock.ExpectRollback()
+
+	grid, err := renderCustomDashboardGrid(context.Background(), dashboard, false)
+	require.NoError(t, err)
+	assert.Contains(t, grid, "1,200")
+	require.NoError(t, mock.ExpectationsWereMet())
+}
@@ -2,11 +2,14 @@ package handlers
 
 import (
 	"bufio"
+	"context"
 	"database/sql"
 	"encoding/json"
 	"fmt"
 	"log"
+	"math"
 	"net/http"
+	"slices"
 	"strings"
 	"time"
 
@@ -15,6 +18,7 @@ import (
 
 	"github.com/seuros/kaunta/internal/database"
 	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
 )
 
 // Website represents a website for the dashboard selector
@@ -123,9 +127,11 @@ func buildWebsiteSelectorHTML(websites []WebsiteInfo, selectedWebsite, context s
 	case "dashboard":
 		selectClass = "input focus-ring"
 		readonlyClass = "input"
-		changeHandler = `const value = (event.target && event.target.value) ? event.target.value : ''; if (value) { localStorage.setItem('kaunta_website', value); } else { localStorage.removeItem('kaunta_website'); } $selectedWebsite = value; $lastBreakdownKey = ''; $lastChartWebsite = ''; @get('/api/dashboard/stats?website=' + encodeURIComponent(value));`
+		changeHandler = `const value = (event.target && event.target.value) ? event.target.value : ''; if (value) { localStorage.setItem('kaunta_website', value); } else { localStorage.removeItem('kaunta_website'); } $selectedWebsite = value; $lastBreakdownKey = ''; $lastChartKey = ''; @get('/api/dashboard/stats?website=' + encodeURIComponent(value));`
 	case "campaigns":
 		changeHandler = `const value = (event.target && event.target.value) ? event.target.value : ''; if (value) { localStorage.setItem('kaunta_website', value); } else { localStorage.removeItem('kaunta_website'); } $selectedWebsite = value; @get('/api/dashboard/campaigns?website_id=' + encodeURIComponent(value));`
+	case "api-keys":
+		changeHandler = `const value = (event.target && event.target.value) ? event.target.value : ''; if (value) { localStorage.setItem('kaunta_website', value); } else { localStorage.removeItem('kaunta_website'); } $selectedWebsite = value; @get('/api/dashboard/api-keys-init?website=' + encodeURIComponent(value));`
 	case "map":
 		// default change handler already updates localStorage and signal; map effect handles fetching
 	default:
@@ -233,7 +239,7 @@ func HandleDashboardInit(w http.ResponseWriter, r *http.Request) {
 	query := `
 		SELECT website_id, COALESCE(name, ''), domain
 		FROM website
-		WHERE user_id = $1 -- Direct user_id column
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
 		ORDER BY domain
 	`
 	rows, err := database.DB.Query(query, user.UserID)
@@ -243,28 +249,22 @@ func HandleDashboardInit(w http.ResponseWriter, r *http.Request) {
 		websites = scanWebsiteInfos(rows)
 	}
 
-	// Determine selected website
+	// Determine selected website; only one from the user's list is loaded
 	selectedWebsite := selectedWebsiteFromRequest(r)
-	if selectedWebsite == "" && len(websites) > 0 {
-		selectedWebsite = websites[0].ID
+	if !slices.ContainsFunc(websites, func(site WebsiteInfo) bool { return site.ID == selectedWebsite }) {
+		selectedWebsite = ""
+		if len(websites) > 0 {
+			selectedWebsite = websites[0].ID
+		}
 	}
 
 	// Query stats if we have a selected website
-	var currentVisitors, todayPageviews, todayVisitors int64
-	var bounceRateNumeric float64
+	var stats dashboardStats
 	var statsErr error
 	if selectedWebsite != "" {
 		websiteID, parseErr := uuid.Parse(selectedWebsite)
 		if parseErr == nil {
-			statsQuery := `SELECT * FROM get_dashboard_stats($1, 1, $2, $3, $4, $5)`
-			statsErr = database.DB.QueryRow(
-				statsQuery,
-				websiteID,
-				nil, // country
-				nil, // browser
-				nil, // device
-				nil, // page
-			).Scan(&currentVisitors, &todayPageviews, &todayVisitors, &bounceRateNumeric)
+			stats, statsErr = loadDashboardStats(r.Context(), websiteID, nil, nil, nil, nil)
 		}
 	}
 
@@ -284,7 +284,7 @@ func HandleDashboardInit(w http.ResponseWriter, r *http.Request) {
 
 	bounceRate := "0%"
 	if statsErr == nil {
-		bounceRate = fmt.Sprintf("%.1f%%", bounceRateNumeric)
+		bounceRate = fmt.Sprintf("%.1f%%", stats.BounceRate)
 	}
 
 	_ = sse.PatchSignals(map[string]any{
@@ -292,15 +292,38 @@ func HandleDashboardInit(w http.ResponseWriter, r *http.Request) {
 		"websitesLoading": false,
 		"websitesError":   false,
 		"stats": map[string]any{
-			"current_visitors":  currentVisitors,
-			"today_pageviews":   todayPageviews,
-			"today_visitors":    todayVisitors,
-			"today_bounce_rate": bounceRate,
+			"current_visitors":   stats.CurrentVisitors,
+			"today_pageviews":    stats.Pageviews,
+			"today_visitors":     stats.Visitors,
+			"today_bounce_rate":  bounceRate,
+			"avg_visit_duration": formatDuration(stats.AvgVisitDuration),
 		},
 	})
 	flush()
 }
 
+// dashboardStats is a row of get_dashboard_stats() for the last day
+type dashboardStats struct {
+	CurrentVisitors  int64
+	Pageviews        int64
+	Visitors         int64
+	BounceRate       float64
+	AvgVisitDuration float64
+}
+
+// loadDashboardStats returns today's headline numbers through the stats cache
+func loadDashboardStats(ctx context.Context, websiteID uuid.UUID, country, browser, device, page any) (dashboardStats, error) {
+	key := statsCacheKey("dashboard_stats", country, browser, device, page)
+	return cachedStats(websiteID, key, lastDays(1), func() (dashboardStats, error) {
+		var stats dashboardStats
+		err := database.QueryRowStats(ctx,
+			`SELECT * FROM get_dashboard_stats($1, 1, $2, $3, $4, $5)`,
+			websiteID, country, browser, device, page,
+		).Scan(&stats.CurrentVisitors, &stats.Pageviews, &stats.Visitors, &stats.BounceRate, &stats.AvgVisitDuration)
+		return stats, err
+	})
+}
+
 // HandleDashboardStats returns dashboard stats via Datastar SSE
 // GET /api/dashboard/stats
 func HandleDashboardStats(w http.ResponseWriter, r *http.Request) {
@@ -333,20 +356,11 @@ func HandleDashboardStats(w http.ResponseWriter, r *http.Request) {
 	}
 
 	// Query database BEFORE streaming
-	var currentVisitors, todayPageviews, todayVisitors int64
-	var bounceRateNumeric float64
+	var stats dashboardStats
 	var queryErr error
 
 	if parseErr == "" {
-		query := `SELECT * FROM get_dashboard_stats($1, 1, $2, $3, $4, $5)`
-		queryErr = database.DB.QueryRow(
-			query,
-			websiteID,
-			countryParam,
-			browserParam,
-			deviceParam,
-			pageParam,
-		).Scan(&currentVisitors, &todayPageviews, &todayVisitors, &bounceRateNumeric)
+		stats, queryErr = loadDashboardStats(r.Context(), websiteID, countryParam, browserParam, deviceParam, pageParam)
 	}
 
 	streamDatastar(w, func(sse *DatastarSSE) {
@@ -361,24 +375,26 @@ func HandleDashboardStats(w http.ResponseWriter, r *http.Request) {
 		if queryErr != nil {
 			_ = sse.PatchSignals(map[string]any{
 				"stats": map[string]any{
-					"current_visitors":  0,
-					"today_pageviews":   0,
-					"today_visitors":    0,
-					"today_bounce_rate": "0%",
+					"current_visitors":   0,
+					"today_pageviews":    0,
+					"today_visitors":     0,
+					"today_bounce_rate":  "0%",
+					"avg_visit_duration": formatDuration(0),
 				},
 				"statsLoading": false,
 			})
 			return
 		}
 
-		bounceRate := fmt.Sprintf("%.1f%%", bounceRateNumeric)
+		bounceRate := fmt.Sprintf("%.1f%%", stats.BounceRate)
 
 		_ = sse.PatchSignals(map[string]any{
 			"stats": map[string]any{
-				"current_visitors":  currentVisitors,
-				"today_pageviews":   todayPageviews,
-				"today_visitors":    todayVisitors,
-				"today_bounce_rate": bounceRate,
+				"current_visitors":   stats.CurrentVisitors,
+				"today_pageviews":    stats.Pageviews,
+				"today_visitors":     stats.Visitors,
+				"today_bounce_rate":  bounceRate,
+				"avg_visit_duration": formatDuration(stats.AvgVisitDuration),
 			},
 			"statsLoading": false,
 		})
@@ -386,7 +402,8 @@ func HandleDashboardStats(w http.ResponseWriter, r *http.Request) {
 }
 
 // HandleTimeSeries returns time series data via Datastar SSE
-// GET /api/dashboard/timeseries-ds?website_id=...&days=7&country=...&browser=...&device=...&page=...
+// GET /api/dashboard/timeseries-ds?website_id=...&days=7&granularity=hour&country=...&browser=...&device=...&page=...
+// granularity is minute (last hour), hour or day; defaults by range
 // Also supports: website (alias for website_id)
 func HandleTimeSeries(w http.ResponseWriter, r *http.Request) {
 	query := r.URL.Query()
@@ -394,7 +411,8 @@ func HandleTimeSeries(w http.ResponseWriter, r *http.Request) {
 	if websiteIDStr == "" {
 		websiteIDStr = query.Get("website")
 	}
-	days := min(queryInt(r, "days", 7), 90)
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+	granularity := timeseriesGranularity(query.Get("granularity"), days)
 	country := query.Get("country")
 	browser := query.Get("browser")
 	device := query.Get("device")
@@ -423,21 +441,24 @@ func HandleTimeSeries(w http.ResponseWriter, r *http.Request) {
 	var queryErr error
 
 	if parseErr == "" {
-		query := `SELECT * FROM get_timeseries($1, $2, $3, $4, $5, $6)`
-		rows, err := database.DB.Query(
-			query,
-			websiteID,
-			days,
-			countryParam,
-			browserParam,
-			deviceParam,
-			pageParam,
-		)
-		if err != nil {
-			queryErr = err
-		} else {
+		key := statsCacheKey("timeseries", days, granularity, countryParam, browserParam, deviceParam, pageParam)
+		points, queryErr = cachedStats(websiteID, key, lastDays(days), func() ([]TimeSeriesPoint, error) {
+			query := `SELECT * FROM get_timeseries($1, $2, $3, $4, $5, $6, $7)`
+			rows, err := database.QueryStats(r.Context(),
+				query,
+				websiteID,
+				days,
+				countryParam,
+				browserParam,
+				deviceParam,
+				pageParam,
+				granularity,
+			)
+			if err != nil {
+				return nil, err
+			}
 			defer func() { _ = rows.Close() }()
-			points = make([]TimeSeriesPoint, 0)
+			points := make([]TimeSeriesPoint, 0)
 			for rows.Next() {
 				var timestamp string
 				var value int64
@@ -449,7 +470,8 @@ func HandleTimeSeries(w http.ResponseWriter, r *http.Request) {
 					Value:     int(value),
 				})
 			}
-		}
+			return points, nil
+		})
 	}
 
 	streamDatastar(w, func(sse *DatastarSSE) {
@@ -483,8 +505,9 @@ func HandleTimeSeries(w http.ResponseWriter, r *http.Request) {
 		if len(values) == 0 {
 			script = "window.destroyChart && window.destroyChart();"
 		} else {
-			script = fmt.Sprintf(`(function(){const _kauntaLabels=%s.map(ts=>new Date(ts).toLocaleString());const _kauntaValues=%s;window.initChart&&window.initChart(_kauntaLabels,_kauntaValues);})();`,
+			script = fmt.Sprintf(`(function(){const _kauntaLabels=%s.map(ts=>new Date(ts).%s());const _kauntaValues=%s;window.initChart&&window.initChart(_kauntaLabels,_kauntaValues);})();`,
 				string(labelsJSON),
+				chartLabelFormatter(granularity),
 				string(valuesJSON),
 			)
 		}
@@ -496,13 +519,25 @@ func HandleTimeSeries(w http.ResponseWriter, r *http.Request) {
 	})
 }
 
+// chartLabelFormatter picks the Date method used to label chart buckets
+func chartLabelFormatter(granularity string) string {
+	switch granularity {
+	case granularityMinute:
+		return "toLocaleTimeString"
+	case granularityDay:
+		return "toLocaleDateString"
+	default:
+		return "toLocaleString"
+	}
+}
+
 // HandleBreakdown returns breakdown data via Datastar SSE
 // GET /api/dashboard/breakdown
 func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 	query := r.URL.Query()
 	datastarParam := query.Get("datastar")
 
-	var websiteIDStr, breakdownType string
+	var websiteIDStr, breakdownType, searchParam string
 
 	if datastarParam != "" {
 		var signals map[string]any
@@ -515,9 +550,17 @@ func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 			if tab, ok := signals["activeTab"].(string); ok && tab != "" {
 				breakdownType = tab
 			}
+			// Get page search (pages tab only)
+			if search, ok := signals["pageSearch"].(string); ok {
+				searchParam = search
+			}
 		}
 	}
 
+	if searchParam == "" {
+		searchParam = query.Get("search")
+	}
+
 	if websiteIDStr == "" {
 		websiteIDStr = query.Get("website_id")
 		if websiteIDStr == "" {
@@ -552,27 +595,7 @@ func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	dimensionMap := map[string]string{
-		"pages":        "pages",
-		"referrers":    "referrer",
-		"browsers":     "browser",
-		"devices":      "device",
-		"countries":    "country",
-		"cities":       "city",
-		"regions":      "region",
-		"os":           "os",
-		"utm_source":   "utm_source",
-		"utm_medium":   "utm_medium",
-		"utm_campaign": "utm_campaign",
-		"utm_term":     "utm_term",
-		"utm_content":  "utm_content",
-		"entry_page":   "entry_page",
-		"exit_page":    "exit_page",
-		"entry-pages":  "entry_page",
-		"exit-pages":   "exit_page",
-	}
-
-	dimension, ok := dimensionMap[breakdownType]
+	dimension, ok := breakdownDimensions[breakdownType]
 	if !ok {
 		streamDatastar(w, func(sse *DatastarSSE) {
 			patchBreakdownErrorState(sse, "Invalid breakdown type: "+breakdownType)
@@ -602,15 +625,54 @@ func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 		pageParam = page
 	}
 
+	type breakdownPage struct {
+		items []BreakdownItem
+		total int64
+	}
+	key := statsCacheKey("breakdown", breakdownType, pagination.Per, pagination.Offset, pagination.SortBy, pagination.SortOrder,
+		searchParam, countryParam, browserParam, deviceParam, pageParam)
+	result, queryErr := cachedStats(websiteID, key, lastDays(1), func() (breakdownPage, error) {
+		items, total, err := loadBreakdown(r.Context(), websiteID, breakdownType, dimension, pagination, searchParam,
+			countryParam, browserParam, deviceParam, pageParam)
+		return breakdownPage{items, total}, err
+	})
+	items, totalCount := result.items, result.total
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			fmt.Printf("DEBUG: Database query error: %v\n", queryErr)
+			patchBreakdownErrorState(sse, "Database error: "+queryErr.Error())
+			return
+		}
+
+		meta := BuildPaginationMeta(pagination, totalCount)
+
+		_ = sse.PatchElementsWithMode("#breakdown-content-body", buildBreakdownTrendTableHTML(websiteID, breakdownType, items), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"breakdownLoading": false,
+			"breakdownError":   false,
+			"pagination": map[string]any{
+				"page":        meta.Page,
+				"per":         meta.Per,
+				"total":       meta.Total,
+				"total_pages": meta.TotalPages,
+				"has_more":    meta.HasMore,
+			},
+		})
+	})
+}
+
+// loadBreakdown runs the query behind a dashboard breakdown tab
+func loadBreakdown(ctx context.Context, websiteID uuid.UUID, breakdownType, dimension string, pagination PaginationParams, searchParam string,
+	countryParam, browserParam, deviceParam, pageParam any) ([]BreakdownItem, int64, error) {
 	var items []BreakdownItem
 	var totalCount int64
-	var queryErr error
 
 	if breakdownType == "pages" {
 		// Use get_top_pages() for pages breakdown
-		query := `SELECT * FROM get_top_pages($1, 1, $2, $3, $4, $5, $6, $7, $8)`
+		query := `SELECT * FROM get_top_pages($1, 1, $2, $3, $4, $5, $6, $7, $8, $9)`
 
-		rows, err := database.DB.Query(
+		rows, err := database.QueryStats(ctx,
 			query,
 			websiteID,
 			pagination.Per,
@@ -620,33 +682,33 @@ func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 			deviceParam,
 			pagination.SortBy,
 			string(pagination.SortOrder),
+			pathSearchPattern(searchParam),
 		)
 		if err != nil {
-			queryErr = err
-		} else {
-			defer func() { _ = rows.Close() }()
-			items = make([]BreakdownItem, 0)
-			for rows.Next() {
-				var path string
-				var views int64
-				var uniqueVisitors int64
-				var avgEngagement *float64
-				var rowTotal int64
-				if err := rows.Scan(&path, &views, &uniqueVisitors, &avgEngagement, &rowTotal); err != nil {
-					continue
-				}
-				totalCount = rowTotal
-				items = append(items, BreakdownItem{
-					Name:  path,
-					Count: int(views),
-				})
+			return nil, 0, err
+		}
+		defer func() { _ = rows.Close() }()
+		items = make([]BreakdownItem, 0)
+		for rows.Next() {
+			var path string
+			var views int64
+			var uniqueVisitors int64
+			var avgEngagement *float64
+			var rowTotal int64
+			if err := rows.Scan(&path, &views, &uniqueVisitors, &avgEngagement, &rowTotal); err != nil {
+				continue
 			}
+			totalCount = rowTotal
+			items = append(items, BreakdownItem{
+				Name:  path,
+				Count: int(views),
+			})
 		}
 	} else if breakdownType == "countries" {
 		// Special handling for countries to include ISO code and name conversion
 		query := `SELECT * FROM get_breakdown($1, $2, 1, $3, $4, $5, $6, $7, $8, $9, $10)`
 
-		rows, err := database.DB.Query(
+		rows, err := database.QueryStats(ctx,
 			query,
 			websiteID,
 			dimension,
@@ -660,30 +722,29 @@ func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 			string(pagination.SortOrder),
 		)
 		if err != nil {
-			queryErr = err
-		} else {
-			defer func() { _ = rows.Close() }()
-			items = make([]BreakdownItem, 0)
-			for rows.Next() {
-				var isoCode string
-				var count int64
-				var rowTotal int64
-				if err := rows.Scan(&isoCode, &count, &rowTotal); err != nil {
-					continue
-				}
-				totalCount = rowTotal
-				items = append(items, BreakdownItem{
-					Name:  getCountryName(isoCode),
-					Code:  isoCode,
-					Count: int(count),
-				})
+			return nil, 0, err
+		}
+		defer func() { _ = rows.Close() }()
+		items = make([]BreakdownItem, 0)
+		for rows.Next() {
+			var isoCode string
+			var count int64
+			var rowTotal int64
+			if err := rows.Scan(&isoCode, &count, &rowTotal); err != nil {
+				continue
 			}
+			totalCount = rowTotal
+			items = append(items, BreakdownItem{
+				Name:  getCountryName(isoCode),
+				Code:  isoCode,
+				Count: int(count),
+			})
 		}
 	} else {
 		// Generic breakdown handler
 		query := `SELECT * FROM get_breakdown($1, $2, 1, $3, $4, $5, $6, $7, $8, $9, $10)`
 
-		rows, err := database.DB.Query(
+		rows, err := database.QueryStats(ctx,
 			query,
 			websiteID,
 			dimension,
@@ -697,44 +758,21 @@ func HandleBreakdown(w http.ResponseWriter, r *http.Request) {
 			string(pagination.SortOrder),
 		)
 		if err != nil {
-			queryErr = err
-		} else {
-			defer func() { _ = rows.Close() }()
-			items = make([]BreakdownItem, 0)
-			for rows.Next() {
-				var item BreakdownItem
-				var rowTotal int64
-				if err := rows.Scan(&item.Name, &item.Count, &rowTotal); err != nil {
-					continue
-				}
-				totalCount = rowTotal
-				items = append(items, item)
+			return nil, 0, err
+		}
+		defer func() { _ = rows.Close() }()
+		items = make([]BreakdownItem, 0)
+		for rows.Next() {
+			var item BreakdownItem
+			var rowTotal int64
+			if err := rows.Scan(&item.Name, &item.Count, &rowTotal); err != nil {
+				continue
 			}
+			totalCount = rowTotal
+			items = append(items, item)
 		}
 	}
-
-	streamDatastar(w, func(sse *DatastarSSE) {
-		if queryErr != nil {
-			fmt.Printf("DEBUG: Database query error: %v\n", queryErr)
-			patchBreakdownErrorState(sse, "Database error: "+queryErr.Error())
-			return
-		}
-
-		meta := BuildPaginationMeta(pagination, totalCount)
-
-		_ = sse.PatchElementsWithMode("#breakdown-content-body", buildBreakdownTableHTML(breakdownType, items), "inner")
-		_ = sse.PatchSignals(map[string]any{
-			"breakdownLoading": false,
-			"breakdownError":   false,
-			"pagination": map[string]any{
-				"page":        meta.Page,
-				"per":         meta.Per,
-				"total":       meta.Total,
-				"total_pages": meta.TotalPages,
-				"has_more":    meta.HasMore,
-			},
-		})
-	})
+	return items, totalCount, nil
 }
 
 // HandleMapData returns map data via Datastar SSE
@@ -773,7 +811,7 @@ func HandleMapData(w http.ResponseWriter, r *http.Request) {
 
 	if parseErr == "" {
 		query := `SELECT * FROM get_map_data($1, $2, $3, $4, $5, $6)`
-		rows, err := database.DB.Query(
+		rows, err := database.QueryStats(r.Context(),
 			query,
 			websiteID,
 			days,
@@ -834,6 +872,82 @@ func HandleMapData(w http.ResponseWriter, r *http.Request) {
 	})
 }
 
+// mapCountryTopLimit is the number of regions and cities listed for a clicked country
+const mapCountryTopLimit = 10
+
+// HandleMapCountry drills into one country picked on the map, listing its
+// top regions and cities for the selected period
+// GET /api/dashboard/map/country?website_id=...&country=US&days=7
+func HandleMapCountry(w http.ResponseWriter, r *http.Request) {
+	query := r.URL.Query()
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+	country := strings.ToUpper(strings.TrimSpace(query.Get("country")))
+
+	websiteID, parseErr := parseRequiredWebsiteID(query.Get("website_id"))
+	if parseErr == "" && len(country) != 2 {
+		parseErr = "Invalid country code"
+	}
+
+	var regions, cities []BreakdownItem
+	var queryErr error
+	if parseErr == "" {
+		regions, queryErr = loadCountryBreakdown(r.Context(), websiteID, "region", country, days)
+		if queryErr == nil {
+			cities, queryErr = loadCountryBreakdown(r.Context(), websiteID, "city", country, days)
+		}
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			_ = sse.PatchSignals(map[string]any{
+				"mapCountryError":   parseErr,
+				"mapCountryLoading": false,
+			})
+			return
+		}
+
+		if queryErr != nil {
+			log.Printf("HandleMapCountry: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"mapCountryError":   "Failed to load country breakdown",
+				"mapCountryLoading": false,
+			})
+			return
+		}
+
+		html := `<div class="map-country-grid"><div>` + buildBreakdownTableHTML("regions", regions) +
+			`</div><div>` + buildBreakdownTableHTML("cities", cities) + `</div></div>`
+		_ = sse.PatchElementsWithMode("#map-country-panel", html, "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"mapCountryName":    getCountryName(country),
+			"mapCountryError":   false,
+			"mapCountryLoading": false,
+		})
+	})
+}
+
+func loadCountryBreakdown(ctx context.Context, websiteID uuid.UUID, dimension, country string, days int) ([]BreakdownItem, error) {
+	rows, err := database.QueryStats(ctx,
+		`SELECT * FROM get_breakdown($1, $2, $3, $4, 0, $5, NULL, NULL, NULL, 'count', 'desc')`,
+		websiteID, dimension, days, mapCountryTopLimit, country,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]BreakdownItem, 0)
+	for rows.Next() {
+		var item BreakdownItem
+		var rowTotal int64
+		if err := rows.Scan(&item.Name, &item.Count, &rowTotal); err != nil {
+			continue
+		}
+		items = append(items, item)
+	}
+	return items, rows.Err()
+}
+
 // HandleRealtimeVisitors returns current visitors count via Datastar SSE
 // GET /api/dashboard/realtime-ds?website_id=...
 func HandleRealtimeVisitors(w http.ResponseWriter, r *http.Request) {
@@ -847,14 +961,7 @@ func HandleRealtimeVisitors(w http.ResponseWriter, r *http.Request) {
 	var queryErr error
 
 	if parseErr == "" {
-		query := `
-			SELECT COUNT(DISTINCT session_id)
-			FROM website_event
-			WHERE website_id = $1
-			  AND created_at >= NOW() - INTERVAL '5 minutes'
-			  AND event_type = 1
-		`
-		queryErr = database.DB.QueryRow(query, websiteID).Scan(&count)
+		count, queryErr = countCurrentVisitors(r.Context(), websiteID)
 	}
 
 	streamDatastar(w, func(sse *DatastarSSE) {
@@ -902,7 +1009,7 @@ func HandleCampaignsInit(w http.ResponseWriter, r *http.Request) {
 	query := `
 		SELECT website_id, COALESCE(name, ''), domain
 		FROM website
-		WHERE user_id = $1 AND deleted_at IS NULL
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
 		ORDER BY domain
 	`
 	rows, err := database.DB.Query(query, user.UserID)
@@ -971,21 +1078,21 @@ func HandleCampaigns(w http.ResponseWriter, r *http.Request) {
 
 		// If dimension is specified, load only that dimension
 		if dimension != "" {
-			loadCampaignUTMData(sse, websiteID, dimension, sortBy, sortOrder)
+			loadCampaignUTMData(r.Context(), sse, websiteID, dimension, sortBy, sortOrder)
 		} else {
 
 			// Load all UTM dimensions
-			loadCampaignUTMData(sse, websiteID, "source", sortBy, sortOrder)
-			loadCampaignUTMData(sse, websiteID, "medium", sortBy, sortOrder)
-			loadCampaignUTMData(sse, websiteID, "campaign", sortBy, sortOrder)
-			loadCampaignUTMData(sse, websiteID, "term", sortBy, sortOrder)
-			loadCampaignUTMData(sse, websiteID, "content", sortBy, sortOrder)
+			loadCampaignUTMData(r.Context(), sse, websiteID, "source", sortBy, sortOrder)
+			loadCampaignUTMData(r.Context(), sse, websiteID, "medium", sortBy, sortOrder)
+			loadCampaignUTMData(r.Context(), sse, websiteID, "campaign", sortBy, sortOrder)
+			loadCampaignUTMData(r.Context(), sse, websiteID, "term", sortBy, sortOrder)
+			loadCampaignUTMData(r.Context(), sse, websiteID, "content", sortBy, sortOrder)
 		}
 	})
 }
 
 // loadCampaignUTMData loads UTM data for a specific dimension and sends it via SSE
-func loadCampaignUTMData(sse *DatastarSSE, websiteID, dimension, sortBy, sortOrder string) {
+func loadCampaignUTMData(ctx context.Context, sse *DatastarSSE, websiteID, dimension, sortBy, sortOrder string) {
 	websiteUUID, err := uuid.Parse(websiteID)
 	if err != nil {
 		return
@@ -1000,7 +1107,7 @@ func loadCampaignUTMData(sse *DatastarSSE, websiteID, dimension, sortBy, sortOrd
 
 	query := `SELECT * FROM get_breakdown($1, $2, 1, 50, 0, NULL, NULL, NULL, NULL, $3, $4)`
 	utmDimension := "utm_" + dimension
-	rows, err := database.DB.Query(query, websiteUUID, utmDimension, sortBy, sortOrder)
+	rows, err := database.QueryStats(ctx, query, websiteUUID, utmDimension, sortBy, sortOrder)
 
 	var items []BreakdownItem
 	if err == nil {
@@ -1067,51 +1174,111 @@ func buildUTMTableHTML(dimension string, items []BreakdownItem, sortBy, sortOrde
 	)
 }
 
+// breakdownDimensions maps dashboard tab names to get_breakdown() dimensions
+var breakdownDimensions = map[string]string{
+	"pages":            "pages",
+	"referrers":        "referrer",
+	"browsers":         "browser",
+	"browser-versions": "browser_version",
+	"devices":          "device",
+	"screens":          "screen",
+	"countries":        "country",
+	"cities":           "city",
+	"regions":          "region",
+	"os":               "os",
+	"utm_source":       "utm_source",
+	"utm_medium":       "utm_medium",
+	"utm_campaign":     "utm_campaign",
+	"utm_term":         "utm_term",
+	"utm_content":      "utm_content",
+	"entry_page":       "entry_page",
+	"exit_page":        "exit_page",
+	"entry-pages":      "entry_page",
+	"exit-pages":       "exit_page",
+}
+
 var breakdownLabels = map[string]string{
-	"pages":        "Pages",
-	"referrers":    "Referrers",
-	"referrer":     "Referrers",
-	"browsers":     "Browsers",
-	"browser":      "Browsers",
-	"devices":      "Devices",
-	"device":       "Devices",
-	"countries":    "Countries",
-	"country":      "Countries",
-	"cities":       "Cities",
-	"regions":      "Regions",
-	"os":           "Operating Systems",
-	"entry-pages":  "Entry Pages",
-	"exit-pages":   "Exit Pages",
-	"entry_page":   "Entry Pages",
-	"exit_page":    "Exit Pages",
-	"utm_source":   "UTM Source",
-	"utm_medium":   "UTM Medium",
-	"utm_campaign": "UTM Campaign",
-	"utm_term":     "UTM Term",
-	"utm_content":  "UTM Content",
+	"pages":            "Pages",
+	"referrers":        "Referrers",
+	"referrer":         "Referrers",
+	"browsers":         "Browsers",
+	"browser":          "Browsers",
+	"browser-versions": "Browser Versions",
+	"browser_version":  "Browser Versions",
+	"devices":          "Devices",
+	"device":           "Devices",
+	"countries":        "Countries",
+	"country":          "Countries",
+	"cities":           "Cities",
+	"regions":          "Regions",
+	"os":               "Operating Systems",
+	"screens":          "Screen Sizes",
+	"screen":           "Screen Sizes",
+	"entry-pages":      "Entry Pages",
+	"exit-pages":       "Exit Pages",
+	"entry_page":       "Entry Pages",
+	"exit_page":        "Exit Pages",
+	"utm_source":       "UTM Source",
+	"utm_medium":       "UTM Medium",
+	"utm_campaign":     "UTM Campaign",
+	"utm_term":         "UTM Term",
+	"utm_content":      "UTM Content",
+}
+
+// breakdownFilterKeys maps breakdown tabs whose rows can be clicked to the
+// $filters key they set
+var breakdownFilterKeys = map[string]string{
+	"browsers": "browser",
+	"devices":  "device",
+}
+
+// screenClassDevices picks the device icon shown next to each screen class
+var screenClassDevices = map[string]string{
+	"Mobile":  "mobile",
+	"Tablet":  "tablet",
+	"Laptop":  "desktop",
+	"Desktop": "desktop",
 }
 
 func buildBreakdownTableHTML(breakdownType string, items []BreakdownItem) string {
+	return renderBreakdownTable(breakdownType, items, false)
+}
+
+// renderBreakdownTable builds the breakdown table; withTrends adds an empty
+// per-row trend cell (#breakdown-trend-N) for HandleBreakdownTrends to fill
+func renderBreakdownTable(breakdownType string, items []BreakdownItem, withTrends bool) string {
 	if len(items) == 0 {
 		return `<div class="empty-state"><div class="empty-state-icon">[=]</div><div class="empty-state-title">No data yet</div><div class="empty-state-text">Start tracking to see breakdown data</div></div>`
 	}
 
 	header := breakdownHeaderLabel(breakdownType)
+	trendHeader := ""
+	if withTrends {
+		trendHeader = `<th class="breakdown-trend">Trend</th>`
+	}
+
 	var rows strings.Builder
-	for _, item := range items {
+	for i, item := range items {
 		label := strings.TrimSpace(item.Name)
 		if label == "" {
 			label = "Unknown"
 		}
-		fmt.Fprintf(&rows, `<tr><td style="display:flex;align-items:center;gap:8px">%s<span>%s</span></td><td style="text-align:right;font-weight:500;color:var(--accent-color)">%s</td></tr>`,
+		trendCell := ""
+		if withTrends {
+			trendCell = fmt.Sprintf(`<td class="breakdown-trend" id="breakdown-trend-%d"></td>`, i)
+		}
+		fmt.Fprintf(&rows, `<tr%s><td style="display:flex;align-items:center;gap:8px">%s<span>%s</span></td>%s<td style="text-align:right;font-weight:500;color:var(--accent-color)">%s</td></tr>`,
+			breakdownRowFilterAttrs(breakdownType, item),
 			breakdownRowPrefix(breakdownType, item),
 			escapeHTML(label),
+			trendCell,
 			formatNumber(item.Count),
 		)
 	}
 
-	return fmt.Sprintf(`<table class="breakdown-table"><thead><tr><th>%s</th><th style="text-align:right">Count</th></tr></thead><tbody>%s</tbody></table>`,
+	return fmt.Sprintf(`<table class="breakdown-table"><thead><tr><th>%s</th>%s<th style="text-align:right">Count</th></tr></thead><tbody>%s</tbody></table>`,
 		escapeHTML(header),
+		trendHeader,
 		rows.String(),
 	)
 }
@@ -1152,6 +1319,16 @@ func breakdownRowPrefix(breakdownType string, item BreakdownItem) string {
 		if icon := browserIconHTML(item.Name); icon != "" {
 			return `<span class="breakdown-icon">` + icon + `</span>`
 		}
+	case "browser-versions", "browser_version":
+		if fields := strings.Fields(item.Name); len(fields) > 0 {
+			if icon := browserIconHTML(fields[0]); icon != "" {
+				return `<span class="breakdown-icon">` + icon + `</span>`
+			}
+		}
+	case "screens", "screen":
+		if icon := deviceIconHTML(screenClassDevices[item.Name]); icon != "" {
+			return `<span class="breakdown-icon">` + icon + `</span>`
+		}
 	case "os":
 		if icon := osIconHTML(item.Name); icon != "" {
 			return `<span class="breakdown-icon">` + icon + `</span>`
@@ -1164,6 +1341,16 @@ func breakdownRowPrefix(breakdownType string, item BreakdownItem) string {
 	return ""
 }
 
+// breakdownRowFilterAttrs makes a row clickable to filter the dashboard by its value
+func breakdownRowFilterAttrs(breakdownType string, item BreakdownItem) string {
+	key, ok := breakdownFilterKeys[breakdownType]
+	if !ok || strings.TrimSpace(item.Name) == "" {
+		return ""
+	}
+	return fmt.Sprintf(` class="breakdown-row-filter" title="Filter by %s" data-on:click="$filters.%s = '%s'"`,
+		escapeHTML(item.Name), key, escapeHTML(jsStringEscape(item.Name)))
+}
+
 func buildBreakdownErrorHTML(message string) string {
 	if strings.TrimSpace(message) == "" {
 		message = "Unable to load breakdown data."
@@ -1337,6 +1524,19 @@ func formatNumber(n int) string {
 	return sign + b.String()
 }
 
+// formatDuration formats a duration in seconds as "45s", "3m 12s" or "1h 05m"
+func formatDuration(seconds float64) string {
+	total := int(math.Round(max(seconds, 0)))
+	switch {
+	case total < 60:
+		return fmt.Sprintf("%ds", total)
+	case total < 3600:
+		return fmt.Sprintf("%dm %02ds", total/60, total%60)
+	default:
+		return fmt.Sprintf("%dh %02dm", total/3600, total%3600/60)
+	}
+}
+
 // HandleWebsitesInit initializes the websites management page
 // GET /api/dashboard/websites-init-ds
 func HandleWebsitesInit(w http.ResponseWriter, r *http.Request) {
@@ -1365,7 +1565,7 @@ func HandleWebsitesInit(w http.ResponseWriter, r *http.Request) {
 	query := `
 		SELECT website_id, domain, COALESCE(name, ''), allowed_domains, public_stats_enabled
 		FROM website
-		WHERE user_id = $1 AND deleted_at IS NULL
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
 		ORDER BY domain
 	`
 	rows, err := database.DB.Query(query, user.UserID)
@@ -1487,8 +1687,8 @@ func HandleWebsitesCreate(w http.ResponseWriter, r *http.Request) {
 	allowedDomainsJSON, _ := json.Marshal(allowedDomains)
 
 	_, err := database.DB.Exec(`
-		INSERT INTO website (website_id, user_id, domain, name, allowed_domains, created_at, updated_at)
-		VALUES ($1, $2, $3, $4, $5::jsonb, NOW(), NOW())
+		INSERT INTO website (website_id, user_id, team_id, domain, name, allowed_domains, created_at, updated_at)
+		VALUES ($1, $2, (SELECT current_team_id FROM users WHERE user_id = $2), $3, $4, $5::jsonb, NOW(), NOW())
 	`, websiteID, user.UserID, domain, name, string(allowedDomainsJSON))
 
 	if err != nil {
@@ -1541,7 +1741,7 @@ func HandleMapInit(w http.ResponseWriter, r *http.Request) {
 	query := `
 		SELECT website_id, COALESCE(name, ''), domain
 		FROM website
-		WHERE user_id = $1 AND deleted_at IS NULL
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
 		ORDER BY domain
 	`
 	rows, err := database.DB.Query(query, user.UserID)
@@ -1551,14 +1751,17 @@ func HandleMapInit(w http.ResponseWriter, r *http.Request) {
 		websites = scanWebsiteInfos(rows)
 	}
 
-	// Determine selected website
+	// Determine selected website; only one from the user's list is loaded
 	selectedWebsite := r.URL.Query().Get("website")
-	if selectedWebsite == "" && len(websites) > 0 {
-		selectedWebsite = websites[0].ID
+	if !slices.ContainsFunc(websites, func(site WebsiteInfo) bool { return site.ID == selectedWebsite }) {
+		selectedWebsite = ""
+		if len(websites) > 0 {
+			selectedWebsite = websites[0].ID
+		}
 	}
 
 	// Query map data if we have a selected website
-	days := 7
+	days := min(max(queryInt(r, "days", 7), 1), 90)
 	mapData := make([]MapDataPoint, 0)
 	var totalVisitors int64
 
@@ -1566,7 +1769,7 @@ func HandleMapInit(w http.ResponseWriter, r *http.Request) {
 		websiteID, parseErr := uuid.Parse(selectedWebsite)
 		if parseErr == nil {
 			mapQuery := `SELECT * FROM get_map_data($1, $2, NULL, NULL, NULL, NULL)`
-			mapRows, mapErr := database.DB.Query(mapQuery, websiteID, days)
+			mapRows, mapErr := database.QueryStats(r.Context(), mapQuery, websiteID, days)
 			if mapErr == nil {
 				defer func() { _ = mapRows.Close() }()
 				for mapRows.Next() {
@@ -1771,6 +1974,16 @@ func HandleGoalsUpdate(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	if !userHasGoalRole(r, goalID, models.RoleEditor) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"goalError":   "Goal not found",
+				"goalLoading": false,
+			})
+		})
+		return
+	}
+
 	var targetURL, targetEvent *string
 	switch goalType {
 	case "page_view":
@@ -1850,6 +2063,16 @@ func HandleGoalsDelete(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	if !userHasGoalRole(r, goalID, models.RoleEditor) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"goalError":   "Goal not found",
+				"goalLoading": false,
+			})
+		})
+		return
+	}
+
 	// Get website ID for cache invalidation
 	var websiteID uuid.UUID
 	_ = database.DB.QueryRow("SELECT website_id FROM goals WHERE id = $1", goalID).Scan(&websiteID)
@@ -1887,6 +2110,20 @@ func HandleGoalsDelete(w http.ResponseWriter, r *http.Request) {
 	})
 }
 
+// userHasGoalRole reports whether the signed-in user holds at least role on
+// the goal's website
+func userHasGoalRole(r *http.Request, goalID string, role models.WebsiteRole) bool {
+	user := middleware.GetUser(r)
+	if user == nil {
+		return false
+	}
+	var websiteID uuid.UUID
+	if err := database.DB.QueryRowContext(r.Context(), "SELECT website_id FROM goals WHERE id = $1", goalID).Scan(&websiteID); err != nil {
+		return false
+	}
+	return userHasWebsiteRole(r.Context(), user.UserID, websiteID, role)
+}
+
 func loadGoalsForWebsite(websiteID string) ([]GoalInfo, error) {
 	rows, err := database.DB.Query(`
 		SELECT id, website_id, name, target_url, target_event
@@ -1948,11 +2185,21 @@ func HandleGoalsAnalytics(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	if !userHasGoalRole(r, goalID, models.RoleViewer) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"analyticsError":   "Goal not found",
+				"analyticsLoading": false,
+			})
+		})
+		return
+	}
+
 	// Query goal analytics
 	var completions, uniqueSessions, totalSessions int
 	var conversionRate float64
 
-	err := database.DB.QueryRow(`
+	err := database.QueryRowStats(r.Context(), `
 		WITH goal_completions AS (
 			SELECT COUNT(*) as completions, COUNT(DISTINCT session_id) as unique_sessions
 			FROM goal_completions gc
@@ -1991,7 +2238,7 @@ func HandleGoalsAnalytics(w http.ResponseWriter, r *http.Request) {
 
 	chartLabels := make([]string, 0, 32)
 	chartValues := make([]int, 0, 32)
-	timeRows, timeErr := database.DB.Query(`
+	timeRows, timeErr := database.QueryStats(r.Context(), `
 		SELECT date_trunc('hour', gc.completed_at) AS bucket, COUNT(*) as count
 		FROM goal_completions gc
 		WHERE gc.goal_id = $1
@@ -2054,6 +2301,16 @@ func HandleGoalsBreakdown(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	if !userHasGoalRole(r, goalID, models.RoleViewer) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"breakdownError":   "Goal not found",
+				"breakdownLoading": false,
+			})
+		})
+		return
+	}
+
 	// Map breakdown type to column
 	columnMap := map[string]string{
 		"pages":    "url_path",
@@ -2086,7 +2343,7 @@ func HandleGoalsBreakdown(w http.ResponseWriter, r *http.Request) {
 		LIMIT 10
 	`, column, column)
 
-	rows, err := database.DB.Query(query, goalID, days)
+	rows, err := database.QueryStats(r.Context(), query, goalID, days)
 	if err != nil {
 		streamDatastar(w, func(sse *DatastarSSE) {
 			html := buildBreakdownErrorHTML("Failed to load breakdown data")
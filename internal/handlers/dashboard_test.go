@@ -0,0 +1,108 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestHandleMapCountry_Success(t *testing.T) {
+	websiteID := uuid.New()
+	responses := []mockResponse{
+		{
+			match:   "SELECT * FROM get_breakdown(",
+			args:    []any{websiteID, "region", 30, mapCountryTopLimit, "DE"},
+			columns: []string{"name", "count", "total"},
+			rows:    [][]any{{"Bavaria", 12, 20}, {"Berlin", 8, 20}},
+		},
+		{
+			match:   "SELECT * FROM get_breakdown(",
+			args:    []any{websiteID, "city", 30, mapCountryTopLimit, "DE"},
+			columns: []string{"name", "count", "total"},
+			rows:    [][]any{{"Munich", 11, 20}},
+		},
+	}
+
+	handler, queue, cleanup := setupHTTPTest(t, "/api/dashboard/map/country", HandleMapCountry, responses)
+	defer cleanup()
+
+	req := httptest.NewRequest(http.MethodGet, "/api/dashboard/map/country?website_id="+websiteID.String()+"&country=de&days=30", nil)
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+
+	assert.Equal(t, http.StatusOK, resp.Code)
+	body := resp.Body.String()
+	assert.Contains(t, body, "#map-country-panel")
+	assert.Contains(t, body, "Bavaria")
+	assert.Contains(t, body, "Berlin")
+	assert.Contains(t, body, "Munich")
+	assert.Contains(t, body, `"mapCountryName":"Germany"`)
+	require.NoError(t, queue.expectationsMet())
+}
+
+func TestHandleMapCountry_ClampsDays(t *testing.T) {
+	websiteID := uuid.New()
+	responses := []mockResponse{
+		{match: "get_breakdown(", args: []any{websiteID, "region", 90, mapCountryTopLimit, "FR"}, columns: []string{"name", "count", "total"}},
+		{match: "get_breakdown(", args: []any{websiteID, "city", 90, mapCountryTopLimit, "FR"}, columns: []string{"name", "count", "total"}},
+	}
+
+	handler, queue, cleanup := setupHTTPTest(t, "/api/dashboard/map/country", HandleMapCountry, responses)
+	defer cleanup()
+
+	req := httptest.NewRequest(http.MethodGet, "/api/dashboard/map/country?website_id="+websiteID.String()+"&country=FR&days=365", nil)
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+
+	assert.Equal(t, http.StatusOK, resp.Code)
+	require.NoError(t, queue.expectationsMet())
+}
+
+func TestHandleMapCountry_InvalidInput(t *testing.T) {
+	tests := map[string]struct {
+		query string
+		err   string
+	}{
+		"missing website": {query: "country=DE", err: "Website ID is required"},
+		"invalid website": {query: "website_id=nope&country=DE", err: "Invalid website ID"},
+		"invalid country": {query: "website_id=" + uuid.New().String() + "&country=DEU", err: "Invalid country code"},
+		"missing country": {query: "website_id=" + uuid.New().String(), err: "Invalid country code"},
+	}
+
+	for name, tc := range tests {
+		t.Run(name, func(t *testing.T) {
+			handler, queue, cleanup := setupHTTPTest(t, "/api/dashboard/map/country", HandleMapCountry, nil)
+			defer cleanup()
+
+			req := httptest.NewRequest(http.MethodGet, "/api/dashboard/map/country?"+tc.query, nil)
+			resp := httptest.NewRecorder()
+			handler.ServeHTTP(resp, req)
+
+			assert.Contains(t, resp.Body.String(), `"mapCountryError":"`+tc.err+`"`)
+			require.NoError(t, queue.expectationsMet())
+		})
+	}
+}
+
+func TestHandleMapCountry_QueryError(t *testing.T) {
+	websiteID := uuid.New()
+	responses := []mockResponse{
+		{match: "get_breakdown(", err: assert.AnError},
+	}
+
+	handler, queue, cleanup := setupHTTPTest(t, "/api/dashboard/map/country", HandleMapCountry, responses)
+	defer cleanup()
+
+	req := httptest.NewRequest(http.MethodGet, "/api/dashboard/map/country?website_id="+websiteID.String()+"&country=DE", nil)
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+
+	body := resp.Body.String()
+	assert.Contains(t, body, `"mapCountryError":"Failed to load country breakdown"`)
+	assert.NotContains(t, body, "#map-country-panel")
+	require.NoError(t, queue.expectationsMet())
+}
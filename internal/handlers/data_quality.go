@@ -0,0 +1,231 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"maps"
+	"net/http"
+	"strings"
+	"sync"
+	"time"
+
+	"log/slog"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/eventschema"
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// dataQualityLimit is the number of issues shown in the report
+const dataQualityLimit = 100
+
+// maxSampleLength caps the sample properties shown per issue
+const maxSampleLength = 120
+
+// eventSchemaCache keeps each website's event schema for a few minutes, so
+// checking an event doesn't cost a query. Schemas are set from the CLI, in
+// another process, so entries expire instead of being invalidated.
+type eventSchemaCache struct {
+	mu      sync.RWMutex
+	entries map[uuid.UUID]eventSchemaEntry
+	ttl     time.Duration
+}
+
+type eventSchemaEntry struct {
+	schema    eventschema.Schema
+	lastFetch time.Time
+}
+
+var (
+	schemaCache = &eventSchemaCache{
+		entries: make(map[uuid.UUID]eventSchemaEntry),
+		ttl:     5 * time.Minute, // Same as the goal cache
+	}
+	loadEventSchemaFunc   = eventschema.Load
+	recordEventIssuesFunc = eventschema.Record
+)
+
+func (c *eventSchemaCache) get(ctx context.Context, websiteID uuid.UUID) (eventschema.Schema, error) {
+	c.mu.RLock()
+	entry, ok := c.entries[websiteID]
+	c.mu.RUnlock()
+	if ok && time.Since(entry.lastFetch) < c.ttl {
+		return entry.schema, nil
+	}
+
+	schema, err := loadEventSchemaFunc(ctx, database.DB, websiteID)
+	if err != nil {
+		return nil, err
+	}
+	c.mu.Lock()
+	c.entries[websiteID] = eventSchemaEntry{schema: schema, lastFetch: time.Now()}
+	c.mu.Unlock()
+	return schema, nil
+}
+
+// checkEventSchema counts the ways a custom event doesn't match its website's
+// event schema. The event is stored either way; events the tracker sends on
+// its own (link clicks, 404s) are never checked.
+func checkEventSchema(ctx context.Context, websiteID uuid.UUID, name string, props, data map[string]any, at time.Time) {
+	name = strings.TrimSpace(name)
+	switch name {
+	case "", OutboundEventName, DownloadEventName, MailtoEventName, NotFoundEventName:
+		return
+	}
+
+	schema, err := schemaCache.get(ctx, websiteID)
+	if err != nil {
+		logging.L().Warn("failed to load event schema", slog.String("website_id", websiteID.String()), slog.Any("error", err))
+		return
+	}
+
+	combined := make(map[string]any, len(props)+len(data))
+	maps.Copy(combined, props)
+	maps.Copy(combined, data)
+
+	issues := schema.Validate(name, combined)
+	if len(issues) == 0 {
+		return
+	}
+	if err := recordEventIssuesFunc(ctx, database.DB, websiteID, name, issues, combined, at); err != nil {
+		logging.L().Warn("failed to record event schema issues",
+			slog.String("website_id", websiteID.String()),
+			slog.String("event", name),
+			slog.Any("error", err))
+	}
+}
+
+// DataQualityIssue is one kind of mismatch between an event and the schema,
+// summed over the report's days
+type DataQualityIssue struct {
+	Event    string    `json:"event"`
+	Issue    string    `json:"issue"`
+	Property string    `json:"property,omitempty"`
+	Expected string    `json:"expected,omitempty"`
+	Received string    `json:"received,omitempty"`
+	Hits     int64     `json:"hits"`
+	LastSeen time.Time `json:"last_seen"`
+	Sample   string    `json:"sample,omitempty"`
+}
+
+// HandleDataQuality renders the data quality report: custom events that
+// don't match the website's event schema
+// GET /api/dashboard/data-quality?website=...&days=7
+func HandleDataQuality(w http.ResponseWriter, r *http.Request) {
+	query := r.URL.Query()
+	websiteIDStr := query.Get("website_id")
+	if websiteIDStr == "" {
+		websiteIDStr = query.Get("website")
+	}
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+
+	websiteID, parseErr := parseRequiredWebsiteID(websiteIDStr)
+
+	var issues []DataQualityIssue
+	var schema eventschema.Schema
+	var queryErr error
+	if parseErr == "" {
+		schema, queryErr = schemaCache.get(r.Context(), websiteID)
+		if queryErr == nil {
+			issues, queryErr = loadDataQualityIssues(r.Context(), websiteID, days, dataQualityLimit)
+		}
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			patchBreakdownErrorState(sse, parseErr)
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleDataQuality: %v", queryErr)
+			patchBreakdownErrorState(sse, "Failed to load data quality")
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#breakdown-content-body", buildDataQualityTableHTML(issues, len(schema)), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"breakdownLoading": false,
+			"breakdownError":   false,
+		})
+	})
+}
+
+// loadDataQualityIssues returns the most frequent schema mismatches of the
+// last days, with the latest sample of each
+func loadDataQualityIssues(ctx context.Context, websiteID uuid.UUID, days, limit int) ([]DataQualityIssue, error) {
+	rows, err := database.QueryStats(ctx, `
+		SELECT
+			event_name, issue, property, expected, received, hits, last_seen_at, COALESCE(sample::text, '')
+		FROM (
+			SELECT
+				event_name, issue, property, expected, received, sample, last_seen_at,
+				SUM(hits) OVER w AS hits,
+				ROW_NUMBER() OVER (w ORDER BY last_seen_at DESC) AS latest
+			FROM event_quality_issue
+			WHERE website_id = $1
+			  AND day >= CURRENT_DATE - $2::int
+			WINDOW w AS (PARTITION BY event_name, issue, property)
+		) i
+		WHERE latest = 1
+		ORDER BY hits DESC, event_name, issue, property
+		LIMIT $3
+	`, websiteID, days-1, limit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	issues := make([]DataQualityIssue, 0)
+	for rows.Next() {
+		var i DataQualityIssue
+		if err := rows.Scan(&i.Event, &i.Issue, &i.Property, &i.Expected, &i.Received, &i.Hits, &i.LastSeen, &i.Sample); err != nil {
+			return nil, err
+		}
+		issues = append(issues, i)
+	}
+	return issues, rows.Err()
+}
+
+// describeIssue explains an issue in the report's words
+func describeIssue(i DataQualityIssue) string {
+	switch i.Issue {
+	case eventschema.IssueUnknownEvent:
+		return "Not in the schema"
+	case eventschema.IssueMissingProperty:
+		return fmt.Sprintf("Missing %s (%s)", i.Property, i.Expected)
+	case eventschema.IssueWrongType:
+		return fmt.Sprintf("%s: expected %s, got %s", i.Property, i.Expected, i.Received)
+	}
+	return i.Issue
+}
+
+func buildDataQualityTableHTML(issues []DataQualityIssue, schemaEvents int) string {
+	if len(issues) == 0 {
+		if schemaEvents == 0 {
+			return `<div class="empty-state"><div class="empty-state-icon">{ }</div><div class="empty-state-title">No event schema</div><div class="empty-state-text">Declare the custom events this website sends and their property types with <code>kaunta website event-schema &lt;domain&gt; --set schema.json</code>; events that don't match are listed here.</div></div>`
+		}
+		return fmt.Sprintf(`<div class="empty-state"><div class="empty-state-icon">{ }</div><div class="empty-state-title">All custom events match the schema</div><div class="empty-state-text">The schema declares %s events.</div></div>`,
+			formatNumber(schemaEvents))
+	}
+
+	var rows strings.Builder
+	for _, i := range issues {
+		sample := i.Sample
+		if len(sample) > maxSampleLength {
+			sample = sample[:maxSampleLength-3] + "..."
+		}
+		fmt.Fprintf(&rows, `<tr><td>%s</td><td>%s</td><td><code>%s</code></td><td>%s</td><td style="text-align:right">%s</td></tr>`,
+			escapeHTML(i.Event),
+			escapeHTML(describeIssue(i)),
+			escapeHTML(sample),
+			i.LastSeen.UTC().Format("2006-01-02 15:04"),
+			formatNumber(int(i.Hits)),
+		)
+	}
+
+	return fmt.Sprintf(`<table class="breakdown-table data-quality-table"><thead><tr><th>Event</th><th>Issue</th><th>Latest properties</th><th>Last seen (UTC)</th><th style="text-align:right">Events</th></tr></thead><tbody>%s</tbody></table>`,
+		rows.String())
+}
@@ -0,0 +1,99 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/eventschema"
+)
+
+func TestCheckEventSchemaRecordsIssues(t *testing.T) {
+	originalLoad, originalRecord := loadEventSchemaFunc, recordEventIssuesFunc
+	defer func() { loadEventSchemaFunc, recordEventIssuesFunc = originalLoad, originalRecord }()
+
+	websiteID := uuid.New()
+	delete(schemaCache.entries, websiteID)
+	defer delete(schemaCache.entries, websiteID)
+
+	loads := 0
+	loadEventSchemaFunc = func(context.Context, *sql.DB, uuid.UUID) (eventschema.Schema, error) {
+		loads++
+		return eventschema.Schema{"signup": {"plan": "string"}}, nil
+	}
+	var recorded []eventschema.Issue
+	var sample map[string]any
+	recordEventIssuesFunc = func(_ context.Context, _ *sql.DB, _ uuid.UUID, name string, issues []eventschema.Issue, props map[string]any, _ time.Time) error {
+		assert.Equal(t, "signup", name)
+		recorded, sample = issues, props
+		return nil
+	}
+
+	checkEventSchema(context.Background(), websiteID, "signup", map[string]any{"env": "prod"}, map[string]any{"plan": float64(1)}, time.Now())
+	assert.Equal(t, []eventschema.Issue{{Kind: eventschema.IssueWrongType, Property: "plan", Expected: "string", Received: "number"}}, recorded)
+	assert.Equal(t, map[string]any{"env": "prod", "plan": float64(1)}, sample)
+
+	// Matching events, and the tracker's own events, record nothing
+	recorded = nil
+	checkEventSchema(context.Background(), websiteID, "signup", nil, map[string]any{"plan": "pro"}, time.Now())
+	checkEventSchema(context.Background(), websiteID, OutboundEventName, nil, nil, time.Now())
+	checkEventSchema(context.Background(), websiteID, DownloadEventName, nil, nil, time.Now())
+	checkEventSchema(context.Background(), websiteID, MailtoEventName, nil, nil, time.Now())
+	checkEventSchema(context.Background(), websiteID, NotFoundEventName, nil, nil, time.Now())
+	assert.Nil(t, recorded)
+
+	assert.Equal(t, 1, loads, "schema should be cached")
+}
+
+func TestLoadDataQualityIssues(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	websiteID := uuid.New()
+	seen := time.Date(2026, 3, 1, 12, 0, 0, 0, time.UTC)
+	mock.ExpectQuery("FROM event_quality_issue").
+		WithArgs(websiteID, 6, dataQualityLimit).
+		WillReturnRows(sqlmock.NewRows([]string{"event_name", "issue", "property", "expected", "received", "hits", "last_seen_at", "sample"}).
+			AddRow("signup", eventschema.IssueWrongType, "seats", "number", "string", 12, seen, `{"seats": "3"}`).
+			AddRow("sign-up", eventschema.IssueUnknownEvent, "", "", "", 2, seen, ""))
+
+	issues, err := loadDataQualityIssues(context.Background(), websiteID, 7, dataQualityLimit)
+	require.NoError(t, err)
+	require.Len(t, issues, 2)
+	assert.Equal(t, DataQualityIssue{
+		Event: "signup", Issue: eventschema.IssueWrongType, Property: "seats", Expected: "number", Received: "string",
+		Hits: 12, LastSeen: seen, Sample: `{"seats": "3"}`,
+	}, issues[0])
+	assert.Equal(t, "sign-up", issues[1].Event)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestBuildDataQualityTableHTML(t *testing.T) {
+	html := buildDataQualityTableHTML([]DataQualityIssue{
+		{Event: "<signup>", Issue: eventschema.IssueWrongType, Property: "seats", Expected: "number", Received: "string", Hits: 1200},
+		{Event: "signup", Issue: eventschema.IssueMissingProperty, Property: "plan", Expected: "string", Hits: 3},
+		{Event: "sign-up", Issue: eventschema.IssueUnknownEvent, Hits: 1},
+	}, 2)
+
+	assert.Contains(t, html, "&lt;signup&gt;")
+	assert.Contains(t, html, "seats: expected number, got string")
+	assert.Contains(t, html, "Missing plan (string)")
+	assert.Contains(t, html, "Not in the schema")
+	assert.Contains(t, html, formatNumber(1200))
+
+	assert.Contains(t, buildDataQualityTableHTML(nil, 0), "No event schema")
+	assert.Contains(t, buildDataQualityTableHTML(nil, 2), "All custom events match the schema")
+}
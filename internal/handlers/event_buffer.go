@@ -0,0 +1,340 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"log/slog"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+// ErrEventBufferFull is returned when too many accepted hits are waiting to
+// be written, usually because the database is down or slow
+var ErrEventBufferFull = errors.New("event buffer full")
+
+// eventRow is one website_event row
+type eventRow struct {
+	EventID        uuid.UUID
+	WebsiteID      uuid.UUID
+	SessionID      uuid.UUID
+	VisitID        uuid.UUID
+	CreatedAt      time.Time
+	PageTitle      *string
+	Hostname       *string
+	URLPath        *string
+	URLQuery       *string
+	ReferrerPath   *string
+	ReferrerQuery  *string
+	ReferrerDomain *string
+	EventName      *string
+	Tag            *string
+	EventType      int
+	ScrollDepth    *int
+	EngagementTime *int
+	Props          any
+	UTMSource      *string
+	UTMMedium      *string
+	UTMCampaign    *string
+	UTMTerm        *string
+	UTMContent     *string
+	GoalID         *uuid.UUID
+}
+
+var eventColumns = []string{
+	"event_id", "website_id", "session_id", "visit_id", "created_at",
+	"page_title", "hostname", "url_path", "url_query",
+	"referrer_path", "referrer_query", "referrer_domain",
+	"event_name", "tag", "event_type",
+	"scroll_depth", "engagement_time", "props",
+	"utm_source", "utm_medium", "utm_campaign", "utm_term", "utm_content",
+	"goal_id",
+}
+
+func (e *eventRow) values() []any {
+	return []any{
+		e.EventID, e.WebsiteID, e.SessionID, e.VisitID, e.CreatedAt,
+		e.PageTitle, e.Hostname, e.URLPath, e.URLQuery,
+		e.ReferrerPath, e.ReferrerQuery, e.ReferrerDomain,
+		e.EventName, e.Tag, e.EventType,
+		e.ScrollDepth, e.EngagementTime, e.Props,
+		e.UTMSource, e.UTMMedium, e.UTMCampaign, e.UTMTerm, e.UTMContent,
+		e.GoalID,
+	}
+}
+
+// maxRowsPerInsert keeps a statement under PostgreSQL's 65535 parameters
+const maxRowsPerInsert = 1000
+
+// insertEventRows writes rows with multi-row INSERTs and marks the cached
+// stats they change stale
+func insertEventRows(ctx context.Context, db *sql.DB, rows []eventRow) error {
+	for start := 0; start < len(rows); start += maxRowsPerInsert {
+		chunk := rows[start:min(start+maxRowsPerInsert, len(rows))]
+
+		var b strings.Builder
+		b.WriteString("INSERT INTO website_event (" + strings.Join(eventColumns, ", ") + ") VALUES ")
+		args := make([]any, 0, len(chunk)*len(eventColumns))
+		for i := range chunk {
+			if i > 0 {
+				b.WriteString(", ")
+			}
+			b.WriteByte('(')
+			for c := range eventColumns {
+				if c > 0 {
+					b.WriteString(", ")
+				}
+				fmt.Fprintf(&b, "$%d", len(args)+c+1)
+			}
+			b.WriteByte(')')
+			args = append(args, chunk[i].values()...)
+		}
+		if _, err := db.ExecContext(ctx, b.String(), args...); err != nil {
+			return err
+		}
+		for i := range chunk {
+			statsCache.Touch(chunk[i].WebsiteID, chunk[i].CreatedAt)
+		}
+	}
+	return nil
+}
+
+// EventBufferSettings limits how many hits wait in memory, and so how many
+// a crash can lose
+type EventBufferSettings struct {
+	FlushInterval time.Duration // Write whatever is waiting this often
+	BatchSize     int           // Write early once this many hits are waiting
+	MaxPending    int           // Refuse hits beyond this many unwritten ones
+}
+
+// EventBuffer collects accepted hits and writes them in batches instead of
+// one INSERT per request
+type EventBuffer struct {
+	db       *sql.DB
+	settings EventBufferSettings
+
+	mu      sync.Mutex
+	pending []eventRow
+	oldest  time.Time // When the longest-waiting hit was accepted
+
+	written  atomic.Int64 // Hits written
+	failures atomic.Int64 // Flushes that left hits waiting
+	failing  atomic.Int64 // Such flushes in a row, reset by a successful one
+
+	flushNow chan struct{}
+	stopChan chan struct{}
+	done     chan struct{}
+}
+
+// NewEventBuffer creates a buffer writing to db
+func NewEventBuffer(db *sql.DB, settings EventBufferSettings) *EventBuffer {
+	return &EventBuffer{
+		db:       db,
+		settings: settings,
+		flushNow: make(chan struct{}, 1),
+		stopChan: make(chan struct{}),
+		done:     make(chan struct{}),
+	}
+}
+
+// Start writes buffered hits in the background
+func (b *EventBuffer) Start() {
+	logging.L().Info("starting event buffer",
+		slog.Duration("flush_interval", b.settings.FlushInterval),
+		slog.Int("batch_size", b.settings.BatchSize),
+		slog.Int("max_pending", b.settings.MaxPending))
+	go b.run()
+}
+
+// Stop writes whatever is still buffered and stops the background writer.
+// Call it after the HTTP server has stopped accepting hits.
+func (b *EventBuffer) Stop() {
+	close(b.stopChan)
+	<-b.done
+}
+
+// Add queues a row. It returns ErrEventBufferFull when MaxPending hits are
+// already waiting.
+func (b *EventBuffer) Add(row eventRow) error {
+	b.mu.Lock()
+	if b.settings.MaxPending > 0 && len(b.pending) >= b.settings.MaxPending {
+		b.mu.Unlock()
+		return ErrEventBufferFull
+	}
+	if len(b.pending) == 0 {
+		b.oldest = time.Now()
+	}
+	b.pending = append(b.pending, row)
+	full := b.settings.BatchSize > 0 && len(b.pending) >= b.settings.BatchSize
+	b.mu.Unlock()
+
+	if full {
+		select {
+		case b.flushNow <- struct{}{}:
+		default:
+		}
+	}
+	return nil
+}
+
+// Pending returns how many hits are waiting to be written
+func (b *EventBuffer) Pending() int {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	return len(b.pending)
+}
+
+// Full reports whether Add would refuse a hit
+func (b *EventBuffer) Full() bool {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	return b.settings.MaxPending > 0 && len(b.pending) >= b.settings.MaxPending
+}
+
+// OldestPending returns how long the longest-waiting hit has waited, 0 when
+// none are
+func (b *EventBuffer) OldestPending() time.Duration {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	if len(b.pending) == 0 {
+		return 0
+	}
+	return time.Since(b.oldest)
+}
+
+// RetryAfter suggests when a refused client should try again: the flush
+// interval (at least a second), doubled for every flush in a row that
+// couldn't write, up to a minute
+func (b *EventBuffer) RetryAfter() time.Duration {
+	retry := max(b.settings.FlushInterval, time.Second)
+	for range min(b.failing.Load(), 6) {
+		retry *= 2
+	}
+	return min(retry, time.Minute)
+}
+
+func (b *EventBuffer) run() {
+	defer close(b.done)
+	ticker := time.NewTicker(b.settings.FlushInterval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ticker.C:
+			b.flush()
+		case <-b.flushNow:
+			b.flush()
+		case <-b.stopChan:
+			b.flush()
+			return
+		}
+	}
+}
+
+// flush writes everything waiting. When a batch is refused because of its
+// data, rows are retried one by one so a single bad row can't hold back the
+// rest; bad rows are dropped. Rows that fail for any other reason, such as a
+// lost connection, stay buffered for the next flush.
+func (b *EventBuffer) flush() {
+	b.mu.Lock()
+	rows, oldest := b.pending, b.oldest
+	b.pending = nil
+	b.mu.Unlock()
+	if len(rows) == 0 {
+		return
+	}
+
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	start := time.Now()
+	err := insertEventRows(ctx, b.db, rows)
+	if err == nil {
+		b.written.Add(int64(len(rows)))
+		b.failing.Store(0)
+		logging.L().Debug("flushed events", slog.Int("count", len(rows)), slog.Duration("duration", time.Since(start)))
+		return
+	}
+
+	retry := rows
+	if isRowError(err) {
+		logging.L().Warn("batch event insert refused, retrying rows individually",
+			slog.Int("count", len(rows)), slog.Any("error", err))
+		retry = nil
+		for _, row := range rows {
+			err := insertEventRows(ctx, b.db, []eventRow{row})
+			switch {
+			case err == nil:
+				b.written.Add(1)
+			case isRowError(err):
+				logging.L().Error("dropping event that can't be inserted",
+					slog.String("event_id", row.EventID.String()),
+					slog.String("website_id", row.WebsiteID.String()),
+					slog.Any("error", err))
+			default:
+				retry = append(retry, row)
+			}
+		}
+		if len(retry) == 0 {
+			b.failing.Store(0)
+			return
+		}
+	}
+
+	// Older rows go first; new hits are still refused beyond MaxPending
+	b.mu.Lock()
+	b.pending = append(retry, b.pending...)
+	b.oldest = oldest
+	b.mu.Unlock()
+	b.failures.Add(1)
+	b.failing.Add(1)
+	logging.L().Warn("event insert failed, keeping events for the next flush",
+		slog.Int("count", len(retry)), slog.Any("error", err))
+}
+
+// isRowError reports whether err is caused by the row's data (a constraint,
+// a bad value, or no partition for its date) rather than the connection
+func isRowError(err error) bool {
+	var pqErr *pq.Error
+	if !errors.As(err, &pqErr) {
+		return false
+	}
+	class := pqErr.Code.Class()
+	return class == "22" || class == "23"
+}
+
+// eventBuffer is set while the server buffers hits; nil writes each hit
+// as it arrives
+var eventBuffer *EventBuffer
+
+// SetEventBuffer routes accepted hits through buffer (nil writes them
+// directly)
+func SetEventBuffer(buffer *EventBuffer) {
+	eventBuffer = buffer
+}
+
+// writeEvent stores an accepted hit, through the buffer when one is set, and
+// counts pageviews towards the shared online visitors
+func writeEvent(ctx context.Context, row eventRow) error {
+	var err error
+	if eventBuffer != nil {
+		err = eventBuffer.Add(row)
+	} else {
+		err = insertEventRows(ctx, database.DB, []eventRow{row})
+	}
+	if err == nil && row.EventType == 1 {
+		realtime.MarkPresent(ctx, row.WebsiteID, row.SessionID, row.CreatedAt)
+	}
+	return err
+}
@@ -0,0 +1,125 @@
+package handlers
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func testEventRow() eventRow {
+	path := "/pricing"
+	return eventRow{
+		EventID:   uuid.New(),
+		WebsiteID: uuid.New(),
+		SessionID: uuid.New(),
+		VisitID:   uuid.New(),
+		CreatedAt: time.Now(),
+		URLPath:   &path,
+		EventType: 1,
+	}
+}
+
+func TestInsertEventRowsUsesOneStatement(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	mock.ExpectExec(`INSERT INTO website_event \(event_id, .*, goal_id\) VALUES \(\$1, .*, \$24\), \(\$25, .*, \$48\)$`).
+		WillReturnResult(sqlmock.NewResult(0, 2))
+
+	require.NoError(t, insertEventRows(context.Background(), db, []eventRow{testEventRow(), testEventRow()}))
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEventBufferRefusesBeyondMaxPending(t *testing.T) {
+	buffer := NewEventBuffer(nil, EventBufferSettings{FlushInterval: time.Hour, MaxPending: 2})
+
+	require.NoError(t, buffer.Add(testEventRow()))
+	require.NoError(t, buffer.Add(testEventRow()))
+	assert.ErrorIs(t, buffer.Add(testEventRow()), ErrEventBufferFull)
+	assert.Equal(t, 2, buffer.Pending())
+}
+
+func TestEventBufferStopFlushes(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	buffer := NewEventBuffer(db, EventBufferSettings{FlushInterval: time.Hour, BatchSize: 100, MaxPending: 100})
+	buffer.Start()
+	require.NoError(t, buffer.Add(testEventRow()))
+	require.NoError(t, buffer.Add(testEventRow()))
+
+	mock.ExpectExec("INSERT INTO website_event").WillReturnResult(sqlmock.NewResult(0, 2))
+	buffer.Stop()
+
+	assert.Zero(t, buffer.Pending())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEventBufferKeepsRowsWhenDatabaseIsDown(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	buffer := NewEventBuffer(db, EventBufferSettings{FlushInterval: time.Hour})
+	require.NoError(t, buffer.Add(testEventRow()))
+	require.NoError(t, buffer.Add(testEventRow()))
+
+	mock.ExpectExec("INSERT INTO website_event").WillReturnError(errors.New("connection refused"))
+	buffer.flush()
+
+	assert.Equal(t, 2, buffer.Pending())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEventBufferDropsRowsTheDatabaseRefuses(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	buffer := NewEventBuffer(db, EventBufferSettings{FlushInterval: time.Hour})
+	require.NoError(t, buffer.Add(testEventRow()))
+	require.NoError(t, buffer.Add(testEventRow()))
+
+	noPartition := &pq.Error{Code: "23514", Message: "no partition of relation \"website_event\" found for row"}
+	mock.ExpectExec("INSERT INTO website_event").WillReturnError(noPartition)
+	mock.ExpectExec("INSERT INTO website_event").WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO website_event").WillReturnError(noPartition)
+	buffer.flush()
+
+	assert.Zero(t, buffer.Pending())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEventBufferRetryAfterBacksOffWhileFlushesFail(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	buffer := NewEventBuffer(db, EventBufferSettings{FlushInterval: 500 * time.Millisecond})
+	assert.Equal(t, time.Second, buffer.RetryAfter())
+	require.NoError(t, buffer.Add(testEventRow()))
+
+	mock.ExpectExec("INSERT INTO website_event").WillReturnError(errors.New("connection refused"))
+	mock.ExpectExec("INSERT INTO website_event").WillReturnError(errors.New("connection refused"))
+	buffer.flush()
+	buffer.flush()
+	assert.Equal(t, 4*time.Second, buffer.RetryAfter())
+	assert.Positive(t, buffer.OldestPending())
+
+	mock.ExpectExec("INSERT INTO website_event").WillReturnResult(sqlmock.NewResult(0, 1))
+	buffer.flush()
+	assert.Equal(t, time.Second, buffer.RetryAfter())
+	assert.Zero(t, buffer.OldestPending())
+	assert.EqualValues(t, 1, buffer.written.Load())
+	assert.EqualValues(t, 2, buffer.failures.Load())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,346 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"net/url"
+	"strings"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+// EventSummary is a custom event name with its volume over the selected period
+type EventSummary struct {
+	Name           string `json:"name"`
+	Count          int    `json:"count"`
+	UniqueVisitors int    `json:"unique_visitors"`
+}
+
+// EventPropertyValue is one value of a custom event property and how often it was seen
+type EventPropertyValue struct {
+	Value string `json:"value"`
+	Count int    `json:"count"`
+}
+
+// EventProperty groups the top values recorded for a single property key
+type EventProperty struct {
+	Key    string               `json:"key"`
+	Values []EventPropertyValue `json:"values"`
+}
+
+const (
+	eventsListLimit       = 100
+	eventPropertyTopLimit = 10
+	// Ranges at least this long are counted from event_rollup
+	eventsRollupMinDays   = 7
+)
+
+// HandleEventsInit initializes the events page with websites list
+// GET /api/dashboard/events-init
+func HandleEventsInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	var queryErr error
+
+	query := `
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
+		ORDER BY domain
+	`
+	rows, err := database.DB.Query(query, user.UserID)
+	if err != nil {
+		queryErr = err
+	} else {
+		websites = scanWebsiteInfos(rows)
+	}
+
+	selectedWebsite := selectedWebsiteFromRequest(r)
+	if selectedWebsite == "" && len(websites) > 0 {
+		selectedWebsite = websites[0].ID
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleEventsInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   fmt.Sprintf("Failed to load websites: %v", queryErr),
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"selectedWebsite": selectedWebsite,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+
+		if html := buildWebsiteSelectorHTML(websites, selectedWebsite, "events"); html != "" {
+			_ = sse.PatchElements("#website-selector-container", html)
+		}
+	})
+}
+
+// HandleEvents lists custom event names with counts and unique visitors.
+// A cursor from the "Load more" button appends the next page to the table.
+// GET /api/dashboard/events?website=...&days=7&cursor=...
+func HandleEvents(w http.ResponseWriter, r *http.Request) {
+	websiteID, parseErr := parseRequiredWebsiteID(selectedWebsiteFromRequest(r))
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+	cursor, _ := DecodeCursor(r.URL.Query().Get("cursor"))
+
+	var events []EventSummary
+	var next string
+	var queryErr error
+	if parseErr == "" {
+		events, next, queryErr = loadEventSummaries(r.Context(), websiteID, days, cursor)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			patchEventsErrorState(sse, "#events-list-body", parseErr)
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleEvents: query error: %v", queryErr)
+			patchEventsErrorState(sse, "#events-list-body", "Failed to load events")
+			return
+		}
+
+		if cursor != nil {
+			_ = sse.PatchElementsWithMode("#events-list-rows", buildEventRowsHTML(events), "append")
+			_ = sse.PatchElementsWithMode("#events-load-more", buildEventsLoadMoreHTML(next), "outer")
+		} else {
+			_ = sse.PatchElementsWithMode("#events-list-body", buildEventsTableHTML(events, next), "inner")
+		}
+		_ = sse.PatchSignals(map[string]any{
+			"eventsLoading": false,
+			"eventsError":   false,
+		})
+	})
+}
+
+// HandleEventProperties breaks a single custom event down by its JSON properties,
+// returning the most frequent values for every property key
+// GET /api/dashboard/events/properties?website=...&event=...&days=7
+func HandleEventProperties(w http.ResponseWriter, r *http.Request) {
+	websiteID, parseErr := parseRequiredWebsiteID(selectedWebsiteFromRequest(r))
+	eventName := strings.TrimSpace(queryString(r, "event", ""))
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+
+	if parseErr == "" && eventName == "" {
+		parseErr = "Event name is required"
+	}
+
+	var properties []EventProperty
+	var queryErr error
+	if parseErr == "" {
+		properties, queryErr = loadEventProperties(r.Context(), websiteID, eventName, days)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			patchEventsErrorState(sse, "#event-properties-body", parseErr)
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleEventProperties: query error: %v", queryErr)
+			patchEventsErrorState(sse, "#event-properties-body", "Failed to load event properties")
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#event-properties-body", buildEventPropertiesHTML(eventName, properties), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"selectedEvent":     eventName,
+			"propertiesLoading": false,
+			"eventsError":       false,
+		})
+	})
+}
+
+// loadEventSummaries reads one page of events, busiest first, starting after
+// cursor. The returned token fetches the following page; it is empty on the last.
+func loadEventSummaries(ctx context.Context, websiteID uuid.UUID, days int, cursor *Cursor) ([]EventSummary, string, error) {
+	params := PaginationParams{Per: eventsListLimit, SortBy: "count", SortOrder: SortDesc, Cursor: cursor}
+	inner := `
+		SELECT event_name, COUNT(*) AS count, COUNT(DISTINCT session_id) AS unique_visitors
+		FROM website_event
+		WHERE website_id = $1
+		  AND event_type = 2
+		  AND event_name IS NOT NULL
+		  AND created_at >= NOW() - ($2 || ' days')::INTERVAL
+		GROUP BY event_name
+	`
+	if days >= eventsRollupMinDays {
+		inner = `
+		SELECT value AS event_name, hits AS count, visitors AS unique_visitors
+		FROM get_rollup_totals($1, 'event', NOW() - ($2 || ' days')::INTERVAL)
+	`
+	}
+	query, args := keysetQuery(inner, []any{websiteID, days}, params, keysetColumn{"t.count", "bigint"}, "t.event_name")
+	rows, err := database.QueryStats(ctx, query, args...)
+	if err != nil {
+		return nil, "", err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var events []EventSummary
+	var cursors [][2]string
+	for rows.Next() {
+		var e EventSummary
+		var c [2]string
+		if err := rows.Scan(&e.Name, &e.Count, &e.UniqueVisitors, &c[0], &c[1]); err != nil {
+			continue
+		}
+		events = append(events, e)
+		cursors = append(cursors, c)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, "", err
+	}
+	events, next := keysetPage(events, cursors, params)
+	return events, next, nil
+}
+
+func loadEventProperties(ctx context.Context, websiteID uuid.UUID, eventName string, days int) ([]EventProperty, error) {
+	query := `
+		WITH counted AS (
+			SELECT kv.key, kv.value, COUNT(*) AS count,
+			       ROW_NUMBER() OVER (PARTITION BY kv.key ORDER BY COUNT(*) DESC, kv.value) AS rank
+			FROM website_event e
+			CROSS JOIN LATERAL jsonb_each_text(
+				CASE WHEN jsonb_typeof(e.props) = 'object' THEN e.props ELSE '{}'::jsonb END
+			) AS kv
+			WHERE e.website_id = $1
+			  AND e.event_type = 2
+			  AND e.event_name = $2
+			  AND e.props IS NOT NULL
+			  AND e.created_at >= NOW() - ($3 || ' days')::INTERVAL
+			GROUP BY kv.key, kv.value
+		)
+		SELECT key, COALESCE(value, ''), count
+		FROM counted
+		WHERE rank <= $4
+		ORDER BY key, count DESC, value
+	`
+	rows, err := database.QueryStats(ctx, query, websiteID, eventName, days, eventPropertyTopLimit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var properties []EventProperty
+	for rows.Next() {
+		var key string
+		var value EventPropertyValue
+		if err := rows.Scan(&key, &value.Value, &value.Count); err != nil {
+			continue
+		}
+		if n := len(properties); n == 0 || properties[n-1].Key != key {
+			properties = append(properties, EventProperty{Key: key})
+		}
+		last := &properties[len(properties)-1]
+		last.Values = append(last.Values, value)
+	}
+	return properties, rows.Err()
+}
+
+// buildEventsTableHTML renders the custom events list; each row loads its property breakdown
+func buildEventsTableHTML(events []EventSummary, next string) string {
+	if len(events) == 0 {
+		return `<div class="empty-state"><div class="empty-state-icon">[=]</div><div class="empty-state-title">No custom events yet</div><div class="empty-state-text">Send events with kaunta.track('name', { key: 'value' }) to see them here</div></div>`
+	}
+
+	return fmt.Sprintf(`<table class="breakdown-table"><thead><tr><th>Event</th><th style="text-align:right">Count</th><th style="text-align:right">Unique Visitors</th></tr></thead><tbody id="events-list-rows">%s</tbody></table>%s`,
+		buildEventRowsHTML(events), buildEventsLoadMoreHTML(next))
+}
+
+func buildEventRowsHTML(events []EventSummary) string {
+	var rows strings.Builder
+	for _, e := range events {
+		fmt.Fprintf(&rows, `<tr class="event-row" style="cursor:pointer" data-class:active="$selectedEvent === '%s'" data-on:click="$selectedEvent = '%s'; $propertiesLoading = true; @get('/api/dashboard/events/properties?website=' + encodeURIComponent($selectedWebsite) + '&event=%s&days=' + $dateRange)"><td>%s</td><td style="text-align:right;font-weight:500;color:var(--accent-color)">%s</td><td style="text-align:right">%s</td></tr>`,
+			escapeHTML(jsStringEscape(e.Name)),
+			escapeHTML(jsStringEscape(e.Name)),
+			escapeHTML(url.QueryEscape(e.Name)),
+			escapeHTML(e.Name),
+			formatNumber(e.Count),
+			formatNumber(e.UniqueVisitors),
+		)
+	}
+	return rows.String()
+}
+
+// buildEventsLoadMoreHTML renders the "Load more" button, or an empty
+// placeholder once the last page is shown
+func buildEventsLoadMoreHTML(next string) string {
+	if next == "" {
+		return `<div id="events-load-more"></div>`
+	}
+	return fmt.Sprintf(`<div id="events-load-more" class="load-more"><button class="btn btn-sm btn-ghost" data-on:click="@get('/api/dashboard/events?website=' + encodeURIComponent($selectedWebsite) + '&days=' + $dateRange + '&cursor=%s')">Load more events</button></div>`,
+		url.QueryEscape(next))
+}
+
+// buildEventPropertiesHTML renders one compact table per property key with its top values
+func buildEventPropertiesHTML(eventName string, properties []EventProperty) string {
+	if len(properties) == 0 {
+		return fmt.Sprintf(`<div class="empty-state"><div class="empty-state-icon">[=]</div><div class="empty-state-title">No properties</div><div class="empty-state-text">%s was recorded without properties in this period</div></div>`, escapeHTML(eventName))
+	}
+
+	var b strings.Builder
+	b.WriteString(`<div class="event-properties-grid">`)
+	for _, prop := range properties {
+		var rows strings.Builder
+		for _, v := range prop.Values {
+			label := v.Value
+			if strings.TrimSpace(label) == "" {
+				label = "(empty)"
+			}
+			fmt.Fprintf(&rows, `<tr><td>%s</td><td style="text-align:right;font-weight:500;color:var(--accent-color)">%s</td></tr>`,
+				escapeHTML(label),
+				formatNumber(v.Count),
+			)
+		}
+		fmt.Fprintf(&b, `<table class="breakdown-table glass card"><thead><tr><th>%s</th><th style="text-align:right">Count</th></tr></thead><tbody>%s</tbody></table>`,
+			escapeHTML(prop.Key),
+			rows.String(),
+		)
+	}
+	b.WriteString(`</div>`)
+	return b.String()
+}
+
+func patchEventsErrorState(sse *DatastarSSE, selector, message string) {
+	_ = sse.PatchElementsWithMode(selector, fmt.Sprintf(`<div class="empty-state"><div class="empty-state-icon">[!]</div><div class="empty-state-title">Unable to load events</div><div class="empty-state-text">%s</div></div>`, escapeHTML(message)), "inner")
+	_ = sse.PatchSignals(map[string]any{
+		"eventsError":       message,
+		"eventsLoading":     false,
+		"propertiesLoading": false,
+	})
+}
+
+// jsStringEscape makes a value safe to embed inside a single-quoted JS string literal
+func jsStringEscape(s string) string {
+	s = strings.ReplaceAll(s, `\`, `\\`)
+	s = strings.ReplaceAll(s, `'`, `\'`)
+	s = strings.ReplaceAll(s, "\n", `\n`)
+	s = strings.ReplaceAll(s, "\r", `\r`)
+	return s
+}
@@ -0,0 +1,53 @@
+package handlers
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestBuildEventsTableHTML(t *testing.T) {
+	t.Run("empty list renders empty state", func(t *testing.T) {
+		html := buildEventsTableHTML(nil, "")
+		assert.Contains(t, html, "No custom events yet")
+	})
+
+	t.Run("rows are escaped and link to property breakdown", func(t *testing.T) {
+		html := buildEventsTableHTML([]EventSummary{
+			{Name: "Signup <b>", Count: 1200, UniqueVisitors: 800},
+			{Name: "it's", Count: 3, UniqueVisitors: 2},
+		}, "")
+		assert.Contains(t, html, "Signup &lt;b&gt;")
+		assert.NotContains(t, html, "Signup <b>")
+		assert.Contains(t, html, "1,200")
+		assert.Contains(t, html, "event=Signup+%3Cb%3E")
+		assert.Contains(t, html, `$selectedEvent = 'it\&#39;s'`)
+		assert.NotContains(t, html, "Load more events")
+	})
+
+	t.Run("next page adds a load more button", func(t *testing.T) {
+		next := EncodeCursor(Cursor{SortBy: "count", SortOrder: SortDesc, Value: "3", Key: "signup"})
+		html := buildEventsTableHTML([]EventSummary{{Name: "signup", Count: 3, UniqueVisitors: 2}}, next)
+		assert.Contains(t, html, `<tbody id="events-list-rows">`)
+		assert.Contains(t, html, "Load more events")
+		assert.Contains(t, html, "&cursor="+next)
+	})
+}
+
+func TestBuildEventPropertiesHTML(t *testing.T) {
+	t.Run("no properties", func(t *testing.T) {
+		html := buildEventPropertiesHTML("Download", nil)
+		assert.Contains(t, html, "Download was recorded without properties")
+	})
+
+	t.Run("one table per key", func(t *testing.T) {
+		html := buildEventPropertiesHTML("Download", []EventProperty{
+			{Key: "format", Values: []EventPropertyValue{{Value: "pdf", Count: 10}, {Value: "", Count: 1}}},
+			{Key: "plan", Values: []EventPropertyValue{{Value: "pro", Count: 4}}},
+		})
+		assert.Equal(t, 2, strings.Count(html, "<table"))
+		assert.Contains(t, html, "<th>format</th>")
+		assert.Contains(t, html, "(empty)")
+	})
+}
@@ -0,0 +1,88 @@
+package handlers
+
+import (
+	"net/http"
+	"net/url"
+	"strings"
+	"time"
+
+	"github.com/seuros/kaunta/internal/websites"
+)
+
+// excludeCookieName marks a browser whose visits aren't recorded. It is set
+// on the Kaunta server's domain and the tracker sends hits to other origins
+// without cookies, so it only covers sites served from that same origin; the
+// tracker's own flag (ExcludeParam) covers the rest.
+const excludeCookieName = "kaunta_exclude"
+
+// ExcludeParam is the query parameter that sets (1) or clears (0) the
+// tracker's exclusion flag in a website's localStorage
+const ExcludeParam = "kaunta_exclude"
+
+// IsExcluded reports whether the request comes from a browser excluded at /exclude
+func IsExcluded(r *http.Request) bool {
+	cookie, err := r.Cookie(excludeCookieName)
+	return err == nil && cookie.Value == "1"
+}
+
+// HandleExclude sets (set=1) or clears (set=0) the exclusion cookie for this
+// browser, then reloads the page. Only a POST with a CSRF token can change
+// it, so other sites can't toggle it with a link or an image.
+// POST /exclude?set=1|0
+func HandleExclude(w http.ResponseWriter, r *http.Request) {
+	var excluded bool
+	switch r.URL.Query().Get("set") {
+	case "1":
+		excluded = true
+	case "0":
+	default:
+		respondError(w, r, http.StatusBadRequest, "set must be 1 or 0")
+		return
+	}
+
+	SetExcluded(w, excluded)
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.ExecuteScript("window.location.reload()")
+	})
+}
+
+// SetExcluded sets or clears the exclusion cookie. Tracking requests from
+// other sites never carry it, so it needs no SameSite=None.
+func SetExcluded(w http.ResponseWriter, excluded bool) {
+	cookie := &http.Cookie{
+		Name:     excludeCookieName,
+		Value:    "1",
+		Path:     "/",
+		Domain:   cookieDomain,
+		Expires:  time.Now().Add(400 * 24 * time.Hour), // The longest browsers keep
+		HttpOnly: true,
+		Secure:   secureCookiesEnabled(),
+		SameSite: http.SameSiteLaxMode,
+	}
+	if !excluded {
+		cookie.Value = ""
+		cookie.Expires = time.Unix(0, 0)
+		cookie.MaxAge = -1
+	}
+	http.SetCookie(w, cookie)
+}
+
+// ExcludeSiteURL links to a website's home page with the parameter that makes
+// the tracker set (or clear) its exclusion flag there. site must be a bare
+// domain; anything else returns "".
+func ExcludeSiteURL(site string, excluded bool) string {
+	site = strings.ToLower(strings.TrimSpace(site))
+	if site == "" || websites.ValidateDomain(site) != nil {
+		return ""
+	}
+	value := "0"
+	if excluded {
+		value = "1"
+	}
+	scheme := "https"
+	if site == "localhost" || strings.HasPrefix(site, "localhost:") {
+		scheme = "http"
+	}
+	u := url.URL{Scheme: scheme, Host: site, Path: "/", RawQuery: ExcludeParam + "=" + value}
+	return u.String()
+}
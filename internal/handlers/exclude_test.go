@@ -0,0 +1,65 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestSetExcluded(t *testing.T) {
+	t.Setenv("SECURE_COOKIES", "true")
+
+	resp := httptest.NewRecorder()
+	SetExcluded(resp, true)
+	cookies := resp.Result().Cookies()
+	require.Len(t, cookies, 1)
+	assert.Equal(t, "1", cookies[0].Value)
+	assert.True(t, cookies[0].HttpOnly)
+	assert.True(t, cookies[0].Secure)
+	assert.Equal(t, http.SameSiteLaxMode, cookies[0].SameSite)
+
+	req := httptest.NewRequest(http.MethodPost, "/api/send", nil)
+	assert.False(t, IsExcluded(req))
+	req.AddCookie(cookies[0])
+	assert.True(t, IsExcluded(req))
+
+	resp = httptest.NewRecorder()
+	SetExcluded(resp, false)
+	cookies = resp.Result().Cookies()
+	require.Len(t, cookies, 1)
+	assert.Equal(t, -1, cookies[0].MaxAge)
+}
+
+func TestHandleExclude(t *testing.T) {
+	rec := httptest.NewRecorder()
+	HandleExclude(rec, httptest.NewRequest(http.MethodPost, "/exclude?set=1", nil))
+	assert.Equal(t, http.StatusOK, rec.Code)
+	cookies := rec.Result().Cookies()
+	require.Len(t, cookies, 1)
+	assert.Equal(t, "1", cookies[0].Value)
+	assert.Contains(t, rec.Body.String(), "window.location.reload()")
+
+	rec = httptest.NewRecorder()
+	HandleExclude(rec, httptest.NewRequest(http.MethodPost, "/exclude?set=0", nil))
+	cookies = rec.Result().Cookies()
+	require.Len(t, cookies, 1)
+	assert.Equal(t, -1, cookies[0].MaxAge)
+
+	rec = httptest.NewRecorder()
+	HandleExclude(rec, httptest.NewRequest(http.MethodPost, "/exclude", nil))
+	assert.Equal(t, http.StatusBadRequest, rec.Code)
+	assert.Empty(t, rec.Result().Cookies())
+}
+
+func TestExcludeSiteURL(t *testing.T) {
+	assert.Equal(t, "https://example.com/?kaunta_exclude=1", ExcludeSiteURL("Example.com", true))
+	assert.Equal(t, "https://example.com/?kaunta_exclude=0", ExcludeSiteURL("example.com", false))
+	assert.Equal(t, "http://localhost:3000/?kaunta_exclude=1", ExcludeSiteURL("localhost:3000", true))
+
+	for _, site := range []string{"", "https://example.com", "example.com/path", "evil.com?x=1", "javascript:alert(1)"} {
+		assert.Empty(t, ExcludeSiteURL(site, true), site)
+	}
+}
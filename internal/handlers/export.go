@@ -0,0 +1,215 @@
+package handlers
+
+import (
+	"database/sql"
+	"encoding/csv"
+	"fmt"
+	"log"
+	"net/http"
+	"time"
+
+	"github.com/go-chi/render"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/warehouse"
+)
+
+// csvFlushEvery controls how many rows are buffered before flushing to the client
+const csvFlushEvery = 500
+
+// exportFilters holds the optional dashboard filters applied to an export
+type exportFilters struct {
+	Country any
+	Browser any
+	Device  any
+	Page    any
+}
+
+func parseExportFilters(r *http.Request) exportFilters {
+	var f exportFilters
+	query := r.URL.Query()
+	if v := query.Get("country"); v != "" {
+		f.Country = v
+	}
+	if v := query.Get("browser"); v != "" {
+		f.Browser = v
+	}
+	if v := query.Get("device"); v != "" {
+		f.Device = v
+	}
+	if v := query.Get("page"); v != "" {
+		f.Page = v
+	}
+	return f
+}
+
+// HandleExportCSV streams a breakdown report as CSV, honouring the active date range and filters.
+// Rows are written as they are read from the database so large exports never sit in memory.
+// GET /api/dashboard/export.csv?website=...&report=pages&days=7&country=...&browser=...&device=...&page=...&search=...
+func HandleExportCSV(w http.ResponseWriter, r *http.Request) {
+	websiteID, errMsg := parseRequiredWebsiteID(selectedWebsiteFromRequest(r))
+	if errMsg != "" {
+		respondError(w, r, http.StatusBadRequest, errMsg)
+		return
+	}
+
+	report := queryString(r, "report", "pages")
+	days := min(max(queryInt(r, "days", 1), 1), 365)
+	filters := parseExportFilters(r)
+
+	var rows *database.StatsRows
+	var header []string
+	var err error
+
+	switch report {
+	case "pages":
+		header = []string{"path", "views", "unique_visitors", "avg_engagement_seconds"}
+		rows, err = database.QueryStats(r.Context(),
+			`SELECT path, views, unique_visitors, COALESCE(avg_engagement_time, 0) FROM get_top_pages($1, $2, NULL::INTEGER, 0, $3, $4, $5, 'views', 'desc', $6)`,
+			websiteID, days, filters.Country, filters.Browser, filters.Device, pathSearchPattern(r.URL.Query().Get("search")))
+	case "events":
+		header = []string{"event", "count", "unique_visitors"}
+		rows, err = database.QueryStats(r.Context(), `
+			SELECT e.event_name, COUNT(*), COUNT(DISTINCT e.session_id)
+			FROM website_event e
+			JOIN session s ON e.session_id = s.session_id
+			WHERE e.website_id = $1
+			  AND e.event_type = 2
+			  AND e.event_name IS NOT NULL
+			  AND e.created_at >= NOW() - ($2 || ' days')::INTERVAL
+			  AND ($3::VARCHAR IS NULL OR s.country = $3)
+			  AND ($4::VARCHAR IS NULL OR s.browser = $4)
+			  AND ($5::VARCHAR IS NULL OR s.device = $5)
+			  AND ($6::VARCHAR IS NULL OR e.url_path = $6)
+			GROUP BY e.event_name
+			ORDER BY COUNT(*) DESC, e.event_name
+		`, websiteID, days, filters.Country, filters.Browser, filters.Device, filters.Page)
+	default:
+		dimension, ok := breakdownDimensions[report]
+		if !ok || dimension == "pages" {
+			respondError(w, r, http.StatusBadRequest, "Invalid report: "+report)
+			return
+		}
+		header = []string{dimension, "count"}
+		country := filters.Country
+		if dimension == "country" {
+			country = nil
+		}
+		rows, err = database.QueryStats(r.Context(),
+			`SELECT name, count FROM get_breakdown($1, $2, $3, NULL::INTEGER, 0, $4, $5, $6, $7, 'count', 'desc')`,
+			websiteID, dimension, days, country, filters.Browser, filters.Device, filters.Page)
+	}
+
+	if err != nil {
+		log.Printf("HandleExportCSV: query error for %s: %v", report, err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to export report")
+		return
+	}
+	defer func() { _ = rows.Close() }()
+
+	filename := fmt.Sprintf("kaunta-%s-%s-%dd-%s.csv", report, websiteID.String()[:8], days, time.Now().UTC().Format("2006-01-02"))
+	w.Header().Set("Content-Type", "text/csv; charset=utf-8")
+	w.Header().Set("Content-Disposition", fmt.Sprintf(`attachment; filename="%s"`, filename))
+	w.Header().Set("Cache-Control", "no-store")
+	w.WriteHeader(http.StatusOK)
+
+	if err := writeCSVRows(w, header, rows.Rows); err != nil {
+		log.Printf("HandleExportCSV: stream error for %s (%s): %v", report, websiteID, err)
+	}
+}
+
+// HandleAPIDailyRollup returns the finalized per-page, referrer, country and
+// event aggregates of one UTC day, for idempotent nightly warehouse syncs
+// Requires API key with 'stats' scope
+// GET /api/v1/stats/{website_id}/daily?date=2026-05-01&format=json|csv
+func HandleAPIDailyRollup(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
+		return
+	}
+
+	format := queryString(r, "format", "json")
+	if format != "json" && format != "csv" {
+		respondError(w, r, http.StatusBadRequest, "Invalid format: "+format)
+		return
+	}
+	day, err := warehouse.ParseDate(r.URL.Query().Get("date"), time.Now())
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	ctx, cancel := database.StatsContext(r.Context())
+	defer cancel()
+	db, release, err := database.StatsQuerier(ctx)
+	if err != nil {
+		log.Printf("HandleAPIDailyRollup: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch daily rollup")
+		return
+	}
+	defer release()
+	rollup, err := warehouse.Daily(ctx, database.TrackStats(db), websiteID, day)
+	if err != nil {
+		log.Printf("HandleAPIDailyRollup: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch daily rollup")
+		return
+	}
+
+	if format == "json" {
+		render.JSON(w, r, rollup)
+		return
+	}
+	filename := fmt.Sprintf("kaunta-daily-%s-%s.csv", websiteID.String()[:8], rollup.Date)
+	w.Header().Set("Content-Type", "text/csv; charset=utf-8")
+	w.Header().Set("Content-Disposition", fmt.Sprintf(`attachment; filename="%s"`, filename))
+	if err := warehouse.WriteCSV(w, rollup); err != nil {
+		log.Printf("HandleAPIDailyRollup: write error for %s: %v", websiteID, err)
+	}
+}
+
+// writeCSVRows copies every row to w as CSV, flushing periodically so the
+// client receives data while the query is still being read.
+func writeCSVRows(w http.ResponseWriter, header []string, rows *sql.Rows) error {
+	flusher, _ := w.(http.Flusher)
+	cw := csv.NewWriter(w)
+
+	if err := cw.Write(header); err != nil {
+		return err
+	}
+
+	values := make([]sql.NullString, len(header))
+	dest := make([]any, len(header))
+	for i := range values {
+		dest[i] = &values[i]
+	}
+	record := make([]string, len(header))
+
+	written := 0
+	for rows.Next() {
+		if err := rows.Scan(dest...); err != nil {
+			return err
+		}
+		for i, v := range values {
+			record[i] = v.String
+		}
+		if err := cw.Write(record); err != nil {
+			return err
+		}
+		written++
+		if written%csvFlushEvery == 0 {
+			cw.Flush()
+			if flusher != nil {
+				flusher.Flush()
+			}
+		}
+	}
+
+	cw.Flush()
+	if flusher != nil {
+		flusher.Flush()
+	}
+	if err := cw.Error(); err != nil {
+		return err
+	}
+	return rows.Err()
+}
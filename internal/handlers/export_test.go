@@ -0,0 +1,81 @@
+package handlers
+
+import (
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestHandleExportCSV(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	websiteID := "11111111-2222-3333-4444-555555555555"
+
+	t.Run("streams breakdown rows with filters", func(t *testing.T) {
+		mock.ExpectQuery("get_breakdown").
+			WithArgs(sqlmock.AnyArg(), "referrer", 7, nil, "Chrome", nil, nil).
+			WillReturnRows(sqlmock.NewRows([]string{"name", "count"}).
+				AddRow("google.com", 12).
+				AddRow(`news, "daily"`, 3))
+
+		req := httptest.NewRequest(http.MethodGet, "/api/dashboard/export.csv?website="+websiteID+"&report=referrers&days=7&browser=Chrome", nil)
+		rec := httptest.NewRecorder()
+		HandleExportCSV(rec, req)
+
+		assert.Equal(t, http.StatusOK, rec.Code)
+		assert.Equal(t, "text/csv; charset=utf-8", rec.Header().Get("Content-Type"))
+		assert.Contains(t, rec.Header().Get("Content-Disposition"), "kaunta-referrers-11111111-7d-")
+		assert.Equal(t, "referrer,count\ngoogle.com,12\n\"news, \"\"daily\"\"\",3\n", rec.Body.String())
+		require.NoError(t, mock.ExpectationsWereMet())
+	})
+
+	t.Run("runs as the tenant in tenant isolation mode", func(t *testing.T) {
+		database.SetTenantIsolation(true)
+		defer database.SetTenantIsolation(false)
+
+		mock.ExpectBegin()
+		mock.ExpectExec("SELECT set_config\\('role', 'kaunta_tenant', true\\)").
+			WithArgs(fmt.Sprintf(`{"%s"}`, websiteID)).
+			WillReturnResult(sqlmock.NewResult(0, 1))
+		mock.ExpectQuery("get_breakdown").
+			WillReturnRows(sqlmock.NewRows([]string{"name", "count"}).AddRow("DE", 4))
+		mock.ExpectRollback()
+
+		req := httptest.NewRequest(http.MethodGet, "/api/dashboard/export.csv?website="+websiteID+"&report=countries", nil)
+		req = req.WithContext(database.WithTenant(req.Context(), uuid.MustParse(websiteID)))
+		rec := httptest.NewRecorder()
+		HandleExportCSV(rec, req)
+
+		assert.Equal(t, http.StatusOK, rec.Code)
+		assert.Equal(t, "country,count\nDE,4\n", rec.Body.String())
+		require.NoError(t, mock.ExpectationsWereMet())
+	})
+
+	t.Run("rejects unknown report", func(t *testing.T) {
+		req := httptest.NewRequest(http.MethodGet, "/api/dashboard/export.csv?website="+websiteID+"&report=nope", nil)
+		rec := httptest.NewRecorder()
+		HandleExportCSV(rec, req)
+		assert.Equal(t, http.StatusBadRequest, rec.Code)
+	})
+
+	t.Run("requires website", func(t *testing.T) {
+		req := httptest.NewRequest(http.MethodGet, "/api/dashboard/export.csv?report=pages", nil)
+		rec := httptest.NewRecorder()
+		HandleExportCSV(rec, req)
+		assert.Equal(t, http.StatusBadRequest, rec.Code)
+	})
+}
@@ -0,0 +1,421 @@
+package handlers
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"slices"
+	"time"
+
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/query"
+)
+
+const (
+	// grafanaDefaultTop is how many dimension values a grouped target charts,
+	// or how many rows a table target returns, unless its payload sets limit
+	grafanaDefaultTop = 10
+	// grafanaTagValueLimit caps the values offered for an ad hoc filter key
+	grafanaTagValueLimit = 100
+)
+
+// errGrafanaQueryFailed marks database failures, as opposed to invalid targets
+var errGrafanaQueryFailed = errors.New("query failed")
+
+// grafanaQueryRequest is the body the Grafana JSON datasource posts to /query
+type grafanaQueryRequest struct {
+	Range struct {
+		From time.Time `json:"from"`
+		To   time.Time `json:"to"`
+	} `json:"range"`
+	IntervalMs   int64                `json:"intervalMs"`
+	Targets      []grafanaTarget      `json:"targets"`
+	AdhocFilters []grafanaAdhocFilter `json:"adhocFilters,omitempty"`
+}
+
+// grafanaTarget is one query of a panel; Target is a metric name
+type grafanaTarget struct {
+	Target  string         `json:"target"`
+	RefID   string         `json:"refId"`
+	Type    string         `json:"type,omitempty"` // Older plugin versions: timeserie or table
+	Hide    bool           `json:"hide,omitempty"`
+	Payload grafanaPayload `json:"payload"`
+}
+
+// grafanaPayload holds the per-target options offered by /metrics
+type grafanaPayload struct {
+	Format    string      `json:"format,omitempty"`    // time_series (default) or table
+	Dimension string      `json:"dimension,omitempty"` // Group by this dimension
+	Limit     json.Number `json:"limit,omitempty"`     // Top dimension values (series) or rows (table)
+}
+
+type grafanaAdhocFilter struct {
+	Key      string `json:"key"`
+	Operator string `json:"operator"`
+	Value    string `json:"value"`
+}
+
+type grafanaMetric struct {
+	Label    string                 `json:"label"`
+	Value    string                 `json:"value"`
+	Payloads []grafanaPayloadOption `json:"payloads"`
+}
+
+type grafanaPayloadOption struct {
+	Label       string          `json:"label"`
+	Name        string          `json:"name"`
+	Type        string          `json:"type"`
+	Placeholder string          `json:"placeholder,omitempty"`
+	Options     []grafanaOption `json:"options,omitempty"`
+}
+
+type grafanaOption struct {
+	Label string `json:"label"`
+	Value string `json:"value"`
+}
+
+// grafanaSeries is a time series; each datapoint is [value, unix milliseconds]
+type grafanaSeries struct {
+	Target     string  `json:"target"`
+	Datapoints [][]any `json:"datapoints"`
+}
+
+type grafanaTable struct {
+	Type    string          `json:"type"`
+	Columns []grafanaColumn `json:"columns"`
+	Rows    [][]any         `json:"rows"`
+}
+
+type grafanaColumn struct {
+	Text string `json:"text"`
+	Type string `json:"type"`
+}
+
+type grafanaTag struct {
+	Type string `json:"type,omitempty"`
+	Text string `json:"text"`
+}
+
+// HandleGrafanaHealth answers the datasource's connection test
+// Requires API key with 'stats' scope
+// GET /api/grafana
+func HandleGrafanaHealth(w http.ResponseWriter, r *http.Request) {
+	if _, ok := authorizeGrafanaRequest(w, r); !ok {
+		return
+	}
+	render.JSON(w, r, map[string]string{"status": "ok"})
+}
+
+// HandleGrafanaMetrics lists the metrics a target can chart, with the format,
+// group-by and limit options of each
+// POST /api/grafana/metrics
+func HandleGrafanaMetrics(w http.ResponseWriter, r *http.Request) {
+	if _, ok := authorizeGrafanaRequest(w, r); !ok {
+		return
+	}
+
+	dimensions := []grafanaOption{{Label: "(none)", Value: ""}}
+	for _, dim := range query.Dimensions() {
+		dimensions = append(dimensions, grafanaOption{Label: dim, Value: dim})
+	}
+	payloads := []grafanaPayloadOption{
+		{Label: "Format", Name: "format", Type: "select", Options: []grafanaOption{
+			{Label: "Time series", Value: "time_series"},
+			{Label: "Table", Value: "table"},
+		}},
+		{Label: "Group by", Name: "dimension", Type: "select", Options: dimensions},
+		{Label: "Top", Name: "limit", Type: "input", Placeholder: fmt.Sprint(grafanaDefaultTop)},
+	}
+
+	metrics := make([]grafanaMetric, 0, len(query.Metrics()))
+	for _, name := range query.Metrics() {
+		metrics = append(metrics, grafanaMetric{Label: name, Value: name, Payloads: payloads})
+	}
+	render.JSON(w, r, metrics)
+}
+
+// HandleGrafanaQuery runs a panel's targets over the dashboard time range
+// Requires API key with 'stats' scope
+// POST /api/grafana/query
+func HandleGrafanaQuery(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeGrafanaRequest(w, r)
+	if !ok {
+		return
+	}
+
+	var req grafanaQueryRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	filters, err := grafanaFilters(req.AdhocFilters)
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	ctx, cancel := context.WithTimeout(r.Context(), apiQueryTimeout)
+	defer cancel()
+
+	results := make([]any, 0, len(req.Targets))
+	for _, target := range req.Targets {
+		if target.Hide || target.Target == "" {
+			continue
+		}
+		result, err := runGrafanaTarget(ctx, websiteID, req, target, filters)
+		if errors.Is(err, errGrafanaQueryFailed) {
+			log.Printf("HandleGrafanaQuery: %v", err)
+			respondError(w, r, http.StatusInternalServerError, "Failed to run query")
+			return
+		}
+		if err != nil {
+			respondError(w, r, http.StatusBadRequest, fmt.Sprintf("%s: %v", target.RefID, err))
+			return
+		}
+		results = append(results, result...)
+	}
+
+	render.JSON(w, r, results)
+}
+
+// HandleGrafanaTagKeys lists the dimensions usable as ad hoc filters
+// POST /api/grafana/tag-keys
+func HandleGrafanaTagKeys(w http.ResponseWriter, r *http.Request) {
+	if _, ok := authorizeGrafanaRequest(w, r); !ok {
+		return
+	}
+
+	keys := make([]grafanaTag, 0, len(query.Dimensions()))
+	for _, dim := range query.Dimensions() {
+		keys = append(keys, grafanaTag{Type: "string", Text: dim})
+	}
+	render.JSON(w, r, keys)
+}
+
+// HandleGrafanaTagValues lists a dimension's most visited values over the
+// last 30 days
+// POST /api/grafana/tag-values {"key": "country"}
+func HandleGrafanaTagValues(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeGrafanaRequest(w, r)
+	if !ok {
+		return
+	}
+
+	var req struct {
+		Key string `json:"key"`
+	}
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+
+	ctx, cancel := context.WithTimeout(r.Context(), apiQueryTimeout)
+	defer cancel()
+
+	_, rows, err := runGrafanaQuery(ctx, websiteID, query.Request{
+		Metrics:    []string{"visitors"},
+		Dimensions: []string{req.Key},
+		Days:       30,
+		Limit:      grafanaTagValueLimit,
+	})
+	if errors.Is(err, errGrafanaQueryFailed) {
+		log.Printf("HandleGrafanaTagValues: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to run query")
+		return
+	}
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	values := make([]grafanaTag, 0, len(rows))
+	for _, row := range rows {
+		if value, ok := row[0].(string); ok {
+			values = append(values, grafanaTag{Text: value})
+		}
+	}
+	render.JSON(w, r, values)
+}
+
+// authorizeGrafanaRequest resolves the API key's website, writing the error
+// response and returning false when the key may not read its stats
+func authorizeGrafanaRequest(w http.ResponseWriter, r *http.Request) (uuid.UUID, bool) {
+	apiKey := middleware.GetAPIKey(r)
+	if apiKey == nil {
+		respondError(w, r, http.StatusUnauthorized, "Unauthorized")
+		return uuid.UUID{}, false
+	}
+	return apiKey.WebsiteID, authorizeAPIStatsWebsite(w, r, apiKey.WebsiteID)
+}
+
+// runGrafanaTarget charts one metric, as a single series, one series per top
+// dimension value, or a table
+func runGrafanaTarget(ctx context.Context, websiteID uuid.UUID, req grafanaQueryRequest, target grafanaTarget, filters []query.Filter) ([]any, error) {
+	top := grafanaDefaultTop
+	if target.Payload.Limit != "" {
+		n, err := target.Payload.Limit.Int64()
+		if err != nil || n < 1 || n > query.MaxLimit {
+			return nil, fmt.Errorf("limit must be between 1 and %d", query.MaxLimit)
+		}
+		top = int(n)
+	}
+
+	base := query.Request{
+		Metrics: []string{target.Target},
+		Filters: filters,
+		From:    &req.Range.From,
+		To:      &req.Range.To,
+	}
+	dimension := target.Payload.Dimension
+	if dimension != "" {
+		base.Dimensions = []string{dimension}
+	}
+
+	if target.Payload.Format == "table" || target.Type == "table" {
+		base.Limit = top
+		q, rows, err := runGrafanaQuery(ctx, websiteID, base)
+		if err != nil {
+			return nil, err
+		}
+		return []any{grafanaTableFromRows(q.Columns, rows)}, nil
+	}
+
+	base.Granularity = grafanaGranularity(req.IntervalMs)
+	base.Limit = query.MaxLimit
+	if dimension != "" {
+		// Chart only the top values over the whole range, so a long tail of
+		// rare values can't crowd out the series that matter
+		totals := base
+		totals.Granularity, totals.Limit = "", top
+		_, rows, err := runGrafanaQuery(ctx, websiteID, totals)
+		if err != nil {
+			return nil, err
+		}
+		var values []string
+		for _, row := range rows {
+			if value, ok := row[0].(string); ok {
+				values = append(values, value)
+			}
+		}
+		if len(values) == 0 {
+			return nil, nil
+		}
+		base.Filters = append(slices.Clone(filters), query.Filter{Dimension: dimension, Values: values})
+	}
+
+	_, rows, err := runGrafanaQuery(ctx, websiteID, base)
+	if err != nil {
+		return nil, err
+	}
+	var results []any
+	for _, series := range grafanaSeriesFromRows(target.Target, rows, dimension != "") {
+		results = append(results, series)
+	}
+	return results, nil
+}
+
+// runGrafanaQuery compiles and runs req; database failures wrap
+// errGrafanaQueryFailed
+func runGrafanaQuery(ctx context.Context, websiteID uuid.UUID, req query.Request) (*query.Query, [][]any, error) {
+	q, err := query.Compile(websiteID, req)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	rows, err := database.QueryStats(ctx, q.SQL, q.Args...)
+	if err != nil {
+		return nil, nil, fmt.Errorf("%w: %v", errGrafanaQueryFailed, err)
+	}
+	defer func() { _ = rows.Close() }()
+
+	data, err := q.Scan(rows.Rows)
+	if err != nil {
+		return nil, nil, fmt.Errorf("%w: %v", errGrafanaQueryFailed, err)
+	}
+	return q, data, nil
+}
+
+// grafanaFilters converts ad hoc filters; only = and != map onto query filters
+func grafanaFilters(adhoc []grafanaAdhocFilter) ([]query.Filter, error) {
+	filters := make([]query.Filter, 0, len(adhoc))
+	for _, f := range adhoc {
+		var operator string
+		switch f.Operator {
+		case "=":
+			operator = "is"
+		case "!=":
+			operator = "is_not"
+		default:
+			return nil, fmt.Errorf("unsupported ad hoc filter operator %q on %s", f.Operator, f.Key)
+		}
+		filters = append(filters, query.Filter{Dimension: f.Key, Operator: operator, Values: []string{f.Value}})
+	}
+	return filters, nil
+}
+
+// grafanaGranularity picks the finest bucket at least as wide as the panel's
+// interval, so a series never has more points than Grafana asked for
+func grafanaGranularity(intervalMs int64) string {
+	interval := time.Duration(intervalMs) * time.Millisecond
+	switch {
+	case interval <= time.Minute:
+		return "minute"
+	case interval <= time.Hour:
+		return "hour"
+	case interval <= 24*time.Hour:
+		return "day"
+	case interval <= 7*24*time.Hour:
+		return "week"
+	default:
+		return "month"
+	}
+}
+
+// grafanaSeriesFromRows turns [time, metric] rows into one series, or
+// [time, dimension, metric] rows into one series per dimension value
+func grafanaSeriesFromRows(metric string, rows [][]any, grouped bool) []grafanaSeries {
+	var series []grafanaSeries
+	index := make(map[string]int)
+	for _, row := range rows {
+		name := metric
+		if grouped {
+			name = "(none)"
+			if value, ok := row[1].(string); ok {
+				name = value
+			}
+		}
+		i, ok := index[name]
+		if !ok {
+			i = len(series)
+			index[name] = i
+			series = append(series, grafanaSeries{Target: name, Datapoints: [][]any{}})
+		}
+		at := row[0].(time.Time)
+		series[i].Datapoints = append(series[i].Datapoints, []any{row[len(row)-1], at.UnixMilli()})
+	}
+	return series
+}
+
+// grafanaTableFromRows builds a table response; times become unix milliseconds
+func grafanaTableFromRows(columns []string, rows [][]any) grafanaTable {
+	table := grafanaTable{Type: "table", Rows: rows}
+	for i, name := range columns {
+		column := grafanaColumn{Text: name, Type: "string"}
+		switch {
+		case name == "time":
+			column.Type = "time"
+			for _, row := range rows {
+				row[i] = row[i].(time.Time).UnixMilli()
+			}
+		case slices.Contains(query.Metrics(), name):
+			column.Type = "number"
+		}
+		table.Columns = append(table.Columns, column)
+	}
+	return table
+}
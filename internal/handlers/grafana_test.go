@@ -0,0 +1,72 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestHandleGrafanaQueryRequiresAPIKey(t *testing.T) {
+	rec := httptest.NewRecorder()
+	HandleGrafanaHealth(rec, httptest.NewRequest(http.MethodGet, "/api/grafana", nil))
+	assert.Equal(t, http.StatusUnauthorized, rec.Code)
+
+	rec = httptest.NewRecorder()
+	HandleGrafanaQuery(rec, httptest.NewRequest(http.MethodPost, "/api/grafana/query", strings.NewReader(`{"targets": []}`)))
+	assert.Equal(t, http.StatusUnauthorized, rec.Code)
+}
+
+func TestGrafanaGranularity(t *testing.T) {
+	assert.Equal(t, "minute", grafanaGranularity(20_000))
+	assert.Equal(t, "hour", grafanaGranularity(120_000))
+	assert.Equal(t, "day", grafanaGranularity(int64(24*time.Hour/time.Millisecond)))
+	assert.Equal(t, "week", grafanaGranularity(int64(48*time.Hour/time.Millisecond)))
+	assert.Equal(t, "month", grafanaGranularity(int64(30*24*time.Hour/time.Millisecond)))
+}
+
+func TestGrafanaFilters(t *testing.T) {
+	filters, err := grafanaFilters([]grafanaAdhocFilter{
+		{Key: "country", Operator: "=", Value: "DE"},
+		{Key: "device", Operator: "!=", Value: "mobile"},
+	})
+	require.NoError(t, err)
+	require.Len(t, filters, 2)
+	assert.Equal(t, "is", filters[0].Operator)
+	assert.Equal(t, []string{"mobile"}, filters[1].Values)
+	assert.Equal(t, "is_not", filters[1].Operator)
+
+	_, err = grafanaFilters([]grafanaAdhocFilter{{Key: "country", Operator: "=~", Value: "D.*"}})
+	assert.ErrorContains(t, err, "unsupported ad hoc filter operator")
+}
+
+func TestGrafanaSeriesFromRows(t *testing.T) {
+	t0 := time.Date(2026, 5, 1, 10, 0, 0, 0, time.UTC)
+	t1 := t0.Add(time.Hour)
+
+	series := grafanaSeriesFromRows("pageviews", [][]any{{t0, int64(4)}, {t1, int64(7)}}, false)
+	require.Len(t, series, 1)
+	assert.Equal(t, "pageviews", series[0].Target)
+	assert.Equal(t, [][]any{{int64(4), t0.UnixMilli()}, {int64(7), t1.UnixMilli()}}, series[0].Datapoints)
+
+	series = grafanaSeriesFromRows("visitors", [][]any{
+		{t0, "DE", int64(3)},
+		{t0, nil, int64(1)},
+		{t1, "DE", int64(5)},
+	}, true)
+	require.Len(t, series, 2)
+	assert.Equal(t, "DE", series[0].Target)
+	assert.Len(t, series[0].Datapoints, 2)
+	assert.Equal(t, "(none)", series[1].Target)
+}
+
+func TestGrafanaTableFromRows(t *testing.T) {
+	table := grafanaTableFromRows([]string{"country", "visitors"}, [][]any{{"DE", int64(3)}})
+	assert.Equal(t, "table", table.Type)
+	assert.Equal(t, []grafanaColumn{{Text: "country", Type: "string"}, {Text: "visitors", Type: "number"}}, table.Columns)
+	assert.Equal(t, [][]any{{"DE", int64(3)}}, table.Rows)
+}
@@ -0,0 +1,435 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"fmt"
+	"math"
+	"net/http"
+	"slices"
+	"strings"
+
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/graphql"
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+const (
+	// graphQLMaxBodyBytes caps the size of a GraphQL request body
+	graphQLMaxBodyBytes = 64 << 10
+	// graphQLMaxRootFields caps how many reports one request may ask for
+	graphQLMaxRootFields = 20
+)
+
+// graphQLRequest is a standard GraphQL-over-HTTP request
+type graphQLRequest struct {
+	Query         string         `json:"query"`
+	OperationName string         `json:"operationName,omitempty"`
+	Variables     map[string]any `json:"variables,omitempty"`
+}
+
+type graphQLError struct {
+	Message string   `json:"message"`
+	Path    []string `json:"path,omitempty"`
+}
+
+type graphQLResponse struct {
+	Data   map[string]any `json:"data"`
+	Errors []graphQLError `json:"errors,omitempty"`
+}
+
+// graphQLResolver resolves a root field for a website, returning an object
+// (map[string]any) or a list of objects and the GraphQL type name
+type graphQLResolver func(ctx context.Context, websiteID uuid.UUID, args map[string]any) (any, string, error)
+
+// graphQLRootFields is the Query type of the stats schema
+var graphQLRootFields = map[string]graphQLResolver{
+	"website":    resolveGraphQLWebsite,
+	"stats":      resolveGraphQLStats,
+	"timeseries": resolveGraphQLTimeseries,
+	"breakdown":  resolveGraphQLBreakdown,
+	"pages":      resolveGraphQLPages,
+}
+
+// HandleGraphQL answers GraphQL queries over the stats of the API key's website
+// Requires API key with 'stats' scope; only enabled when graphql_enabled is set
+// POST /api/v1/graphql {"query": "...", "variables": {...}}
+// GET  /api/v1/graphql?query=...&variables=...
+func HandleGraphQL(w http.ResponseWriter, r *http.Request) {
+	apiKey := middleware.GetAPIKey(r)
+	if apiKey == nil {
+		respondError(w, r, http.StatusUnauthorized, "Unauthorized")
+		return
+	}
+	if !authorizeAPIStatsWebsite(w, r, apiKey.WebsiteID) {
+		return
+	}
+
+	var req graphQLRequest
+	if r.Method == http.MethodGet {
+		req.Query = r.URL.Query().Get("query")
+		req.OperationName = r.URL.Query().Get("operationName")
+		if raw := r.URL.Query().Get("variables"); raw != "" {
+			if err := json.Unmarshal([]byte(raw), &req.Variables); err != nil {
+				respondError(w, r, http.StatusBadRequest, "Invalid variables")
+				return
+			}
+		}
+	} else if err := json.NewDecoder(http.MaxBytesReader(w, r.Body, graphQLMaxBodyBytes)).Decode(&req); err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid request body")
+		return
+	}
+	if strings.TrimSpace(req.Query) == "" {
+		respondError(w, r, http.StatusBadRequest, "Missing query")
+		return
+	}
+
+	render.JSON(w, r, executeGraphQL(r.Context(), apiKey.WebsiteID, req))
+}
+
+// executeGraphQL parses the request and resolves each root field; a failing
+// field is returned as null with an error so the others still come back
+func executeGraphQL(ctx context.Context, websiteID uuid.UUID, req graphQLRequest) graphQLResponse {
+	op, err := graphql.Parse(req.Query, req.OperationName, req.Variables)
+	if err != nil {
+		return graphQLResponse{Errors: []graphQLError{{Message: err.Error()}}}
+	}
+	if len(op.Selections) > graphQLMaxRootFields {
+		return graphQLResponse{Errors: []graphQLError{{Message: fmt.Sprintf("at most %d root fields per query", graphQLMaxRootFields)}}}
+	}
+
+	resp := graphQLResponse{Data: map[string]any{}}
+	for _, field := range op.Selections {
+		key := field.Key()
+		if field.Name == "__typename" {
+			resp.Data[key] = "Query"
+			continue
+		}
+
+		resolve, ok := graphQLRootFields[field.Name]
+		if !ok {
+			resp.Errors = append(resp.Errors, graphQLError{Message: fmt.Sprintf("cannot query field %q on type \"Query\"", field.Name), Path: []string{key}})
+			resp.Data[key] = nil
+			continue
+		}
+
+		value, typeName, err := resolve(ctx, websiteID, field.Arguments)
+		if err == nil {
+			value, err = selectGraphQLFields(value, typeName, field)
+		}
+		if err != nil {
+			resp.Errors = append(resp.Errors, graphQLError{Message: err.Error(), Path: []string{key}})
+			resp.Data[key] = nil
+			continue
+		}
+		resp.Data[key] = value
+	}
+	return resp
+}
+
+// selectGraphQLFields projects a resolved object or list onto the field's
+// selection set
+func selectGraphQLFields(value any, typeName string, field *graphql.Field) (any, error) {
+	if len(field.Selections) == 0 {
+		return nil, fmt.Errorf("field %q of type %q must have a selection of subfields", field.Name, typeName)
+	}
+
+	project := func(object map[string]any) (map[string]any, error) {
+		out := make(map[string]any, len(field.Selections))
+		for _, sel := range field.Selections {
+			if sel.Name == "__typename" {
+				out[sel.Key()] = typeName
+				continue
+			}
+			v, ok := object[sel.Name]
+			if !ok {
+				return nil, fmt.Errorf("cannot query field %q on type %q", sel.Name, typeName)
+			}
+			if len(sel.Selections) > 0 {
+				return nil, fmt.Errorf("field %q of type %q has no subfields", sel.Name, typeName)
+			}
+			out[sel.Key()] = v
+		}
+		return out, nil
+	}
+
+	switch v := value.(type) {
+	case map[string]any:
+		return project(v)
+	case []map[string]any:
+		list := make([]map[string]any, 0, len(v))
+		for _, object := range v {
+			projected, err := project(object)
+			if err != nil {
+				return nil, err
+			}
+			list = append(list, projected)
+		}
+		return list, nil
+	}
+	return nil, fmt.Errorf("unexpected value for %q", field.Name)
+}
+
+func resolveGraphQLWebsite(ctx context.Context, websiteID uuid.UUID, _ map[string]any) (any, string, error) {
+	var name, domain string
+	err := database.QueryRowStats(ctx,
+		`SELECT COALESCE(name, ''), domain FROM website WHERE website_id = $1 AND deleted_at IS NULL`,
+		websiteID,
+	).Scan(&name, &domain)
+	if err != nil {
+		return nil, "", fmt.Errorf("failed to load website")
+	}
+	return map[string]any{"id": websiteID.String(), "name": name, "domain": domain}, "Website", nil
+}
+
+func resolveGraphQLStats(ctx context.Context, websiteID uuid.UUID, args map[string]any) (any, string, error) {
+	days, err := graphQLIntArg(args, "days", 30, 1, 365)
+	if err != nil {
+		return nil, "", err
+	}
+	country, browser, device, page, err := graphQLFilterArg(args)
+	if err != nil {
+		return nil, "", err
+	}
+
+	var online int
+	var pageviews, visitors int64
+	var bounceRate, avgVisitDuration float64
+	err = database.QueryRowStats(ctx,
+		`SELECT * FROM get_dashboard_stats($1, $2, $3, $4, $5, $6)`,
+		websiteID, days, country, browser, device, page,
+	).Scan(&online, &pageviews, &visitors, &bounceRate, &avgVisitDuration)
+	if err != nil {
+		return nil, "", fmt.Errorf("failed to fetch stats")
+	}
+	return map[string]any{
+		"days":             days,
+		"online":           online,
+		"pageviews":        pageviews,
+		"visitors":         visitors,
+		"bounceRate":       bounceRate,
+		"avgVisitDuration": avgVisitDuration,
+	}, "Stats", nil
+}
+
+func resolveGraphQLTimeseries(ctx context.Context, websiteID uuid.UUID, args map[string]any) (any, string, error) {
+	days, err := graphQLIntArg(args, "days", 7, 1, 365)
+	if err != nil {
+		return nil, "", err
+	}
+	granularity, err := graphQLStringArg(args, "granularity")
+	if err != nil {
+		return nil, "", err
+	}
+	country, browser, device, page, err := graphQLFilterArg(args)
+	if err != nil {
+		return nil, "", err
+	}
+
+	rows, err := database.QueryStats(ctx,
+		`SELECT * FROM get_timeseries($1, $2, $3, $4, $5, $6, $7)`,
+		websiteID, days, country, browser, device, page, timeseriesGranularity(granularity, days),
+	)
+	if err != nil {
+		return nil, "", fmt.Errorf("failed to fetch timeseries")
+	}
+	defer func() { _ = rows.Close() }()
+
+	points := make([]map[string]any, 0)
+	for rows.Next() {
+		var point TimeSeriesPoint
+		if err := rows.Scan(&point.Timestamp, &point.Value); err != nil {
+			return nil, "", fmt.Errorf("failed to fetch timeseries")
+		}
+		points = append(points, map[string]any{"timestamp": point.Timestamp, "value": point.Value})
+	}
+	return points, "TimeseriesPoint", nil
+}
+
+func resolveGraphQLBreakdown(ctx context.Context, websiteID uuid.UUID, args map[string]any) (any, string, error) {
+	dimension, err := graphQLStringArg(args, "dimension")
+	if err != nil {
+		return nil, "", err
+	}
+	dbDimension, known := breakdownDimensions[dimension]
+	if !known || dbDimension == "pages" {
+		return nil, "", fmt.Errorf("unknown dimension %q", dimension)
+	}
+	days, err := graphQLIntArg(args, "days", 7, 1, 365)
+	if err != nil {
+		return nil, "", err
+	}
+	limit, offset, sortBy, sortOrder, err := graphQLListArgs(args, "breakdown")
+	if err != nil {
+		return nil, "", err
+	}
+	country, browser, device, page, err := graphQLFilterArg(args)
+	if err != nil {
+		return nil, "", err
+	}
+
+	rows, err := database.QueryStats(ctx,
+		`SELECT * FROM get_breakdown($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)`,
+		websiteID, dbDimension, days, limit, offset,
+		country, browser, device, page, sortBy, sortOrder,
+	)
+	if err != nil {
+		return nil, "", fmt.Errorf("failed to fetch breakdown")
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]map[string]any, 0)
+	for rows.Next() {
+		var item BreakdownItem
+		var total int64
+		if err := rows.Scan(&item.Name, &item.Count, &total); err != nil {
+			return nil, "", fmt.Errorf("failed to fetch breakdown")
+		}
+		if dbDimension == "country" {
+			item.Code = item.Name
+			item.Name = getCountryName(item.Code)
+		}
+		items = append(items, map[string]any{"name": item.Name, "code": item.Code, "count": item.Count})
+	}
+	return items, "BreakdownItem", nil
+}
+
+func resolveGraphQLPages(ctx context.Context, websiteID uuid.UUID, args map[string]any) (any, string, error) {
+	days, err := graphQLIntArg(args, "days", 7, 1, 365)
+	if err != nil {
+		return nil, "", err
+	}
+	limit, offset, sortBy, sortOrder, err := graphQLListArgs(args, "pages")
+	if err != nil {
+		return nil, "", err
+	}
+	search, err := graphQLStringArg(args, "search")
+	if err != nil {
+		return nil, "", err
+	}
+
+	rows, err := database.QueryStats(ctx,
+		`SELECT * FROM get_top_pages($1, $2, $3, $4, NULL, NULL, NULL, $5, $6, $7)`,
+		websiteID, days, limit, offset, sortBy, sortOrder, pathSearchPattern(search),
+	)
+	if err != nil {
+		return nil, "", fmt.Errorf("failed to fetch pages")
+	}
+	defer func() { _ = rows.Close() }()
+
+	pages := make([]map[string]any, 0)
+	for rows.Next() {
+		var page TopPage
+		var avgEngagement sql.NullFloat64
+		var total int64
+		if err := rows.Scan(&page.Path, &page.Views, &page.UniqueVisitors, &avgEngagement, &total); err != nil {
+			return nil, "", fmt.Errorf("failed to fetch pages")
+		}
+		item := map[string]any{
+			"path":              page.Path,
+			"views":             page.Views,
+			"uniqueVisitors":    page.UniqueVisitors,
+			"avgEngagementTime": nil,
+		}
+		if avgEngagement.Valid {
+			item["avgEngagementTime"] = avgEngagement.Float64
+		}
+		pages = append(pages, item)
+	}
+	return pages, "Page", nil
+}
+
+// graphQLIntArg reads an optional Int argument clamped to [lo, hi]; JSON
+// variables arrive as float64
+func graphQLIntArg(args map[string]any, name string, def, lo, hi int) (int, error) {
+	var n int
+	switch v := args[name].(type) {
+	case nil:
+		n = def
+	case int:
+		n = v
+	case float64:
+		if v != math.Trunc(v) {
+			return 0, fmt.Errorf("argument %q must be an Int", name)
+		}
+		n = int(v)
+	default:
+		return 0, fmt.Errorf("argument %q must be an Int", name)
+	}
+	return min(max(n, lo), hi), nil
+}
+
+// graphQLStringArg reads an optional String or enum argument
+func graphQLStringArg(args map[string]any, name string) (string, error) {
+	switch v := args[name].(type) {
+	case nil:
+		return "", nil
+	case string:
+		return v, nil
+	}
+	return "", fmt.Errorf("argument %q must be a String", name)
+}
+
+// graphQLListArgs reads limit/offset/sortBy/sortOrder, validating the sort
+// column like ParsePaginationParamsWithValidation
+func graphQLListArgs(args map[string]any, endpointType string) (limit, offset int, sortBy, sortOrder string, err error) {
+	if limit, err = graphQLIntArg(args, "limit", 10, 1, 100); err != nil {
+		return
+	}
+	if offset, err = graphQLIntArg(args, "offset", 0, 0, math.MaxInt32); err != nil {
+		return
+	}
+	if sortBy, err = graphQLStringArg(args, "sortBy"); err != nil {
+		return
+	}
+	if sortOrder, err = graphQLStringArg(args, "sortOrder"); err != nil {
+		return
+	}
+
+	valid := ValidSortColumns[endpointType]
+	sortBy = strings.ToLower(sortBy)
+	if !slices.Contains(valid, sortBy) {
+		sortBy = valid[0]
+	}
+	if sortOrder = strings.ToLower(sortOrder); sortOrder != string(SortAsc) {
+		sortOrder = string(SortDesc)
+	}
+	return
+}
+
+// graphQLFilterArg reads the optional filter: {country, browser, device, page}
+// input object (NULL when unset), like apiSegmentFilters
+func graphQLFilterArg(args map[string]any) (country, browser, device, page any, err error) {
+	raw, ok := args["filter"]
+	if !ok || raw == nil {
+		return
+	}
+	filter, ok := raw.(map[string]any)
+	if !ok {
+		err = fmt.Errorf("argument \"filter\" must be an object")
+		return
+	}
+
+	values := make(map[string]any, 4)
+	for key, v := range filter {
+		switch key {
+		case "country", "browser", "device", "page":
+		default:
+			err = fmt.Errorf("unknown filter %q", key)
+			return
+		}
+		s, isString := v.(string)
+		if v != nil && !isString {
+			err = fmt.Errorf("filter %q must be a String", key)
+			return
+		}
+		if s != "" {
+			values[key] = s
+		}
+	}
+	return values["country"], values["browser"], values["device"], values["page"], nil
+}
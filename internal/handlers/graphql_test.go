@@ -0,0 +1,95 @@
+package handlers
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestExecuteGraphQL(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("get_dashboard_stats").
+		WithArgs(websiteID, 7, nil, nil, "mobile", nil).
+		WillReturnRows(sqlmock.NewRows([]string{"current", "pageviews", "visitors", "bounce", "duration"}).
+			AddRow(2, 120, 40, 35.5, 61.0))
+	mock.ExpectQuery("get_breakdown").
+		WithArgs(websiteID, "country", 7, 3, 0, nil, nil, nil, nil, "count", "desc").
+		WillReturnRows(sqlmock.NewRows([]string{"name", "count", "total"}).
+			AddRow("DE", 25, 2))
+
+	resp := executeGraphQL(context.Background(), websiteID, graphQLRequest{
+		Query: `
+			query ($device: String) {
+				mobile: stats(days: 7, filter: { device: $device }) { visitors bounceRate __typename }
+				countries: breakdown(dimension: countries, limit: 3, sortBy: "bogus") { name code count }
+				unknown { value }
+			}
+		`,
+		Variables: map[string]any{"device": "mobile"},
+	})
+
+	assert.Equal(t, map[string]any{
+		"visitors":   int64(40),
+		"bounceRate": 35.5,
+		"__typename": "Stats",
+	}, resp.Data["mobile"])
+	assert.Equal(t, []map[string]any{
+		{"name": "Germany", "code": "DE", "count": 25},
+	}, resp.Data["countries"])
+
+	// Unknown fields are reported without failing the rest of the query
+	require.Len(t, resp.Errors, 1)
+	assert.Equal(t, []string{"unknown"}, resp.Errors[0].Path)
+	assert.Nil(t, resp.Data["unknown"])
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestExecuteGraphQLRejectsInvalidQueries(t *testing.T) {
+	resp := executeGraphQL(context.Background(), uuid.New(), graphQLRequest{Query: `{ stats { visitors }`})
+	assert.Nil(t, resp.Data)
+	require.Len(t, resp.Errors, 1)
+
+	resp = executeGraphQL(context.Background(), uuid.New(), graphQLRequest{Query: `{ stats(days: "week") { visitors } breakdown(dimension: pages) { name } }`})
+	require.Len(t, resp.Errors, 2)
+	assert.Nil(t, resp.Data["stats"])
+	assert.Nil(t, resp.Data["breakdown"])
+}
+
+func TestGraphQLFilterArg(t *testing.T) {
+	country, browser, device, page, err := graphQLFilterArg(map[string]any{
+		"filter": map[string]any{"country": "DE", "browser": "", "page": nil},
+	})
+	require.NoError(t, err)
+	assert.Equal(t, "DE", country)
+	assert.Nil(t, browser)
+	assert.Nil(t, device)
+	assert.Nil(t, page)
+
+	_, _, _, _, err = graphQLFilterArg(map[string]any{"filter": map[string]any{"os": "Linux"}})
+	assert.Error(t, err)
+}
+
+func TestHandleGraphQLRequiresAPIKey(t *testing.T) {
+	req := httptest.NewRequest(http.MethodPost, "/api/v1/graphql", strings.NewReader(`{"query":"{ stats { visitors } }"}`))
+	resp := httptest.NewRecorder()
+	HandleGraphQL(resp, req)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+}
@@ -133,6 +133,10 @@ func HandleIngest(w http.ResponseWriter, r *http.Request) {
 		logging.L().Error("failed to process ingest event",
 			slog.String("website_id", apiKey.WebsiteID.String()),
 			slog.Any("error", err))
+		if errors.Is(err, ErrEventBufferFull) {
+			respondIngestOverloaded(w, r)
+			return
+		}
 		respondError(w, r, http.StatusInternalServerError, "Failed to process event")
 		return
 	}
@@ -204,10 +208,15 @@ func HandleIngestBatch(w http.ResponseWriter, r *http.Request) {
 		}
 
 		if _, err := processIngestEvent(ctx, r, apiKey, &payload); err != nil {
+			message := "processing failed"
+			if errors.Is(err, ErrEventBufferFull) {
+				ingestShed.Add(1)
+				message = "too many events waiting to be saved, retry later"
+			}
 			response.Failed++
 			response.Errors = append(response.Errors, BatchError{
 				Index: i,
-				Error: "processing failed",
+				Error: message,
 			})
 			continue
 		}
@@ -255,9 +264,11 @@ func processIngestEvent(ctx context.Context, r *http.Request, apiKey *models.API
 
 	// Parse client info from User-Agent
 	browser, os, device := parseUserAgent(userAgent)
+	browserVersion := parseBrowserVersion(userAgent, browser)
 
 	// GeoIP lookup
 	countryStr, cityStr, regionStr := geoip.LookupIP(ip)
+	rememberCityCentroid(ip, countryStr, cityStr)
 	country := &countryStr
 	region := &regionStr
 	city := &cityStr
@@ -310,7 +321,7 @@ func processIngestEvent(ctx context.Context, r *http.Request, apiKey *models.API
 	}
 
 	// Upsert session
-	err = upsertSessionForIngest(ctx, sessionID, websiteID, browser, os, device,
+	err = upsertSessionForIngest(ctx, sessionID, websiteID, browser, browserVersion, os, device,
 		screen, language, country, region, city, payload.UserID, urlPath)
 	if err != nil {
 		return nil, fmt.Errorf("failed to create session: %w", err)
@@ -320,22 +331,7 @@ func processIngestEvent(ctx context.Context, r *http.Request, apiKey *models.API
 	visitSalt := hashDate(createdAt, "hour")
 	visitID := generateUUID(sessionID.String(), visitSalt)
 
-	// Save event
-	eventID, err := saveIngestEvent(ctx, websiteID, sessionID, visitID, createdAt, payload,
-		browser, os, device, country, region, city, hostname, urlPath, urlQuery)
-	if err != nil {
-		return nil, fmt.Errorf("failed to save event: %w", err)
-	}
-
-	// Record idempotency key if provided
-	if payload.EventID != nil {
-		eventUUID, err := uuid.Parse(*payload.EventID)
-		if err == nil {
-			_ = models.InsertEventID(eventUUID, websiteID)
-		}
-	}
-
-	// Check goals
+	// Check goals before saving so the event is tagged in the same insert
 	eventType := 1 // pageview
 	if payload.Event != "page_view" {
 		eventType = 2 // custom event
@@ -346,12 +342,24 @@ func processIngestEvent(ctx context.Context, r *http.Request, apiKey *models.API
 		eventName = nil
 	}
 
+	eventID := uuid.New()
 	goalID := checkAndRecordGoalCompletion(ctx, websiteID, sessionID, eventID, eventType, urlPath, eventName)
-	if goalID != nil {
-		_, _ = database.DB.ExecContext(ctx,
-			`UPDATE website_event SET goal_id = $1 WHERE event_id = $2 AND created_at = $3`,
-			goalID, eventID, createdAt,
-		)
+
+	// Save event
+	if err := saveIngestEvent(ctx, eventID, websiteID, sessionID, visitID, createdAt, payload,
+		hostname, urlPath, urlQuery, goalID); err != nil {
+		return nil, fmt.Errorf("failed to save event: %w", err)
+	}
+	if eventType == 2 {
+		checkEventSchema(ctx, websiteID, payload.Event, payload.Properties, nil, createdAt)
+	}
+
+	// Record idempotency key if provided
+	if payload.EventID != nil {
+		eventUUID, err := uuid.Parse(*payload.EventID)
+		if err == nil {
+			_ = models.InsertEventID(eventUUID, websiteID)
+		}
 	}
 
 	return map[string]any{
@@ -488,27 +496,26 @@ func generateDeterministicUUID(parts ...string) uuid.UUID {
 
 // upsertSessionForIngest creates or updates a session for ingested events
 func upsertSessionForIngest(ctx context.Context, sessionID, websiteID uuid.UUID,
-	browser, os, device, screen, language, country, region, city *string,
+	browser, browserVersion, os, device, screen, language, country, region, city *string,
 	distinctID *string, urlPath *string) error {
 
 	query := `
 		INSERT INTO session (
-			session_id, website_id, browser, os, device, screen, language,
+			session_id, website_id, browser, browser_version, os, device, screen, language,
 			country, region, city, created_at, distinct_id, entry_page, exit_page
-		) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), $11, $12, $12)
+		) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), $12, $13, $13)
 		ON CONFLICT (session_id) DO UPDATE SET exit_page = EXCLUDED.entry_page
 	`
-	_, err := database.DB.ExecContext(ctx, query, sessionID, websiteID, browser, os, device,
+	_, err := database.DB.ExecContext(ctx, query, sessionID, websiteID, browser, browserVersion, os, device,
 		screen, language, country, region, city, distinctID, urlPath)
 	return err
 }
 
-// saveIngestEvent saves an event from the ingest API
-func saveIngestEvent(ctx context.Context, websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
-	payload *IngestPayload, browser, os, device, country, region, city *string,
-	hostname, urlPath, urlQuery *string) (uuid.UUID, error) {
+// saveIngestEvent saves an event from the ingest API, through the event
+// buffer when one is set
+func saveIngestEvent(ctx context.Context, eventID, websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
+	payload *IngestPayload, hostname, urlPath, urlQuery *string, goalID *uuid.UUID) error {
 
-	eventID := uuid.New()
 	eventType := 1 // pageview
 	var eventName *string
 
@@ -547,36 +554,36 @@ func saveIngestEvent(ctx context.Context, websiteID, sessionID, visitID uuid.UUI
 		title = &payload.Title
 	}
 
-	query := `
-		INSERT INTO website_event (
-			event_id, website_id, session_id, visit_id, created_at,
-			page_title, hostname, url_path, url_query,
-			referrer_path, referrer_query, referrer_domain,
-			event_name, event_type, props,
-			utm_source, utm_medium, utm_campaign, utm_term, utm_content
-		) VALUES (
-			$1, $2, $3, $4, $5,
-			$6, $7, $8, $9,
-			$10, $11, $12,
-			$13, $14, $15,
-			$16, $17, $18, $19, $20
-		)
-	`
-
-	_, err := database.DB.ExecContext(ctx, query,
-		eventID, websiteID, sessionID, visitID, createdAt,
-		title, hostname, urlPath, urlQuery,
-		referrerPath, referrerQuery, referrerDomain,
-		eventName, eventType, propsJSON,
-		payload.UTMSource, payload.UTMMedium, payload.UTMCampaign, payload.UTMTerm, payload.UTMContent,
-	)
+	err := writeEvent(ctx, eventRow{
+		EventID:        eventID,
+		WebsiteID:      websiteID,
+		SessionID:      sessionID,
+		VisitID:        visitID,
+		CreatedAt:      createdAt,
+		PageTitle:      title,
+		Hostname:       hostname,
+		URLPath:        urlPath,
+		URLQuery:       urlQuery,
+		ReferrerPath:   referrerPath,
+		ReferrerQuery:  referrerQuery,
+		ReferrerDomain: referrerDomain,
+		EventName:      eventName,
+		EventType:      eventType,
+		Props:          propsJSON,
+		UTMSource:      payload.UTMSource,
+		UTMMedium:      payload.UTMMedium,
+		UTMCampaign:    payload.UTMCampaign,
+		UTMTerm:        payload.UTMTerm,
+		UTMContent:     payload.UTMContent,
+		GoalID:         goalID,
+	})
 
 	if err != nil {
 		logging.L().Error("failed to insert ingest event",
 			slog.String("event_id", eventID.String()),
 			slog.Any("error", err))
-		return uuid.Nil, err
+		return err
 	}
 
-	return eventID, nil
+	return nil
 }
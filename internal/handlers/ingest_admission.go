@@ -0,0 +1,62 @@
+package handlers
+
+import (
+	"math"
+	"net/http"
+	"strconv"
+	"sync/atomic"
+	"time"
+)
+
+var (
+	ingestLimit    atomic.Int64 // Hits processed at once before new ones are refused (0 is unlimited)
+	ingestInFlight atomic.Int64 // Hits being processed
+	ingestShed     atomic.Int64 // Hits refused with a 503
+)
+
+// SetIngestConcurrency limits how many tracking and ingest requests are
+// processed at once; beyond it they are refused (0 removes the limit)
+func SetIngestConcurrency(limit int) {
+	ingestLimit.Store(int64(limit))
+}
+
+// admitIngest reserves a slot for a hit unless the event buffer is full or
+// the concurrency limit is reached. Call release once the hit is handled.
+func admitIngest() (release func(), ok bool) {
+	if eventBuffer != nil && eventBuffer.Full() {
+		return nil, false
+	}
+	inFlight := ingestInFlight.Add(1)
+	if limit := ingestLimit.Load(); limit > 0 && inFlight > limit {
+		ingestInFlight.Add(-1)
+		return nil, false
+	}
+	return func() { ingestInFlight.Add(-1) }, true
+}
+
+// ShedIngestOverload answers tracking and ingest requests with a 503 and
+// Retry-After while the server can't take more hits, before they reach the
+// database. While the database is slow, requests then fail fast instead of
+// piling up waiting for connections.
+func ShedIngestOverload(next http.Handler) http.Handler {
+	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		release, ok := admitIngest()
+		if !ok {
+			respondIngestOverloaded(w, r)
+			return
+		}
+		defer release()
+		next.ServeHTTP(w, r)
+	})
+}
+
+// respondIngestOverloaded refuses a hit the server can't write soon
+func respondIngestOverloaded(w http.ResponseWriter, r *http.Request) {
+	ingestShed.Add(1)
+	retry := time.Second
+	if eventBuffer != nil {
+		retry = eventBuffer.RetryAfter()
+	}
+	w.Header().Set("Retry-After", strconv.Itoa(int(math.Ceil(retry.Seconds()))))
+	respondError(w, r, http.StatusServiceUnavailable, "Too many events waiting to be saved, retry later")
+}
@@ -0,0 +1,50 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestShedIngestOverloadWhenBufferIsFull(t *testing.T) {
+	buffer := NewEventBuffer(nil, EventBufferSettings{FlushInterval: 2 * time.Second, MaxPending: 1})
+	require.NoError(t, buffer.Add(testEventRow()))
+	SetEventBuffer(buffer)
+	defer SetEventBuffer(nil)
+
+	handler := ShedIngestOverload(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		t.Fatal("handler must not run")
+	}))
+	shed := ingestShed.Load()
+	rec := httptest.NewRecorder()
+	handler.ServeHTTP(rec, httptest.NewRequest(http.MethodPost, "/api/send", nil))
+
+	assert.Equal(t, http.StatusServiceUnavailable, rec.Code)
+	assert.Equal(t, "2", rec.Header().Get("Retry-After"))
+	assert.Equal(t, shed+1, ingestShed.Load())
+}
+
+func TestShedIngestOverloadBeyondConcurrencyLimit(t *testing.T) {
+	SetIngestConcurrency(1)
+	defer SetIngestConcurrency(0)
+
+	inner := httptest.NewRecorder()
+	handler := ShedIngestOverload(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		// A second hit arriving while this one is processed is refused
+		ShedIngestOverload(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+			t.Fatal("nested handler must not run")
+		})).ServeHTTP(inner, r)
+		w.WriteHeader(http.StatusAccepted)
+	}))
+
+	rec := httptest.NewRecorder()
+	handler.ServeHTTP(rec, httptest.NewRequest(http.MethodPost, "/api/send", nil))
+	assert.Equal(t, http.StatusAccepted, rec.Code)
+	assert.Equal(t, http.StatusServiceUnavailable, inner.Code)
+	assert.Equal(t, "1", inner.Header().Get("Retry-After"))
+	assert.Zero(t, ingestInFlight.Load(), "slots are released")
+}
@@ -0,0 +1,227 @@
+package handlers
+
+import (
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/mailer"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var (
+	// accountMailer delivers invitations and other account emails; nil or
+	// unconfigured means links are only returned to the caller
+	accountMailer *mailer.Mailer
+	// publicURL is the configured base URL for links in emails
+	publicURL string
+)
+
+// SetMailer wires the mailer used for account emails and the base URL their
+// links point to. An empty publicURL falls back to the request's host.
+func SetMailer(m *mailer.Mailer, baseURL string) {
+	accountMailer = m
+	publicURL = strings.TrimRight(baseURL, "/")
+}
+
+// publicLink builds an absolute URL for path, from the configured public URL
+// or else the scheme and host the request came in on
+func publicLink(r *http.Request, path string) string {
+	if publicURL != "" {
+		return publicURL + path
+	}
+	scheme := "http"
+	if r.TLS != nil || r.Header.Get("X-Forwarded-Proto") == "https" {
+		scheme = "https"
+	}
+	return scheme + "://" + r.Host + path
+}
+
+// InvitationRequest invites an email address with a role
+type InvitationRequest struct {
+	Email string `json:"email"`
+	Role  string `json:"role"` // owner, editor or viewer; default viewer
+}
+
+// InvitationResponse is a created invitation with its one-time link. The
+// link is only ever returned here, so it can be shared by hand when email
+// delivery is off.
+type InvitationResponse struct {
+	*models.Invitation
+	Link    string `json:"link"`
+	Emailed bool   `json:"emailed"`
+}
+
+// HandleWebsiteInvite invites someone to a website
+// POST /api/websites/:website_id/invitations
+func HandleWebsiteInvite(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+	createInvitation(w, r, &websiteID, nil)
+}
+
+// HandleTeamInvite invites someone to a team
+// POST /api/teams/:team_id/invitations
+func HandleTeamInvite(w http.ResponseWriter, r *http.Request) {
+	teamID, ok := teamFromParam(w, r, models.RoleOwner)
+	if !ok {
+		return
+	}
+	createInvitation(w, r, nil, &teamID)
+}
+
+func createInvitation(w http.ResponseWriter, r *http.Request, websiteID, teamID *uuid.UUID) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	defer func() { _ = r.Body.Close() }()
+	var req InvitationRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if req.Role == "" {
+		req.Role = string(models.RoleViewer)
+	}
+	role, err := models.ParseWebsiteRole(req.Role)
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+	if _, err := models.NormalizeEmail(req.Email); err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	inv, token, err := models.CreateInvitation(r.Context(), database.DB, req.Email, websiteID, teamID, role, &user.UserID)
+	if err != nil {
+		log.Printf("createInvitation: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to create invitation")
+		return
+	}
+
+	resp := InvitationResponse{Invitation: inv, Link: publicLink(r, "/invite/"+token)}
+	if accountMailer.Enabled() {
+		if err := SendInvitation(accountMailer, inv, resp.Link); err != nil {
+			log.Printf("createInvitation: sending email: %v", err)
+		} else {
+			resp.Emailed = true
+		}
+	}
+	render.Status(r, http.StatusCreated)
+	render.JSON(w, r, resp)
+}
+
+// SendInvitation emails an invitation link
+func SendInvitation(m *mailer.Mailer, inv *models.Invitation, link string) error {
+	inviter := "Someone"
+	if inv.InvitedBy != nil {
+		inviter = *inv.InvitedBy
+	}
+	subject := fmt.Sprintf("You're invited to %s on Kaunta", inv.Target)
+	body := fmt.Sprintf(`%s invited you to the %s %q on Kaunta as %s.
+
+Open this link to accept, with your existing account or a new one:
+
+%s
+
+The link works once and expires on %s.
+`, inviter, inv.Kind(), inv.Target, inv.Role, link, inv.ExpiresAt.UTC().Format("2 Jan 2006 15:04 MST"))
+	return m.Send(inv.Email, subject, body)
+}
+
+// LookupInvitation returns the pending invitation for the invite page, or
+// nil when the link is expired, used or unknown
+func LookupInvitation(r *http.Request, token string) *models.Invitation {
+	inv, err := models.GetInvitation(r.Context(), database.DB, token)
+	if err != nil {
+		if !errors.Is(err, models.ErrInvitationNotFound) {
+			log.Printf("LookupInvitation: %v", err)
+		}
+		return nil
+	}
+	return inv
+}
+
+// HandleInvitationAccept accepts an invitation with the signed-in account
+// via Datastar SSE
+// POST /api/invitations/:token/accept
+func HandleInvitationAccept(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	acceptErr := ""
+	err := models.AcceptInvitation(r.Context(), database.DB, chi.URLParam(r, "token"), user.UserID)
+	if errors.Is(err, models.ErrInvitationNotFound) {
+		acceptErr = "This invitation has expired or was already used"
+	} else if err != nil {
+		log.Printf("HandleInvitationAccept: %v", err)
+		acceptErr = "Failed to accept invitation"
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if acceptErr != "" {
+			_ = sse.PatchSignals(map[string]any{"inviteError": acceptErr, "submitting": false})
+			return
+		}
+		_ = sse.ExecuteScript("localStorage.removeItem('kaunta_website'); window.location.href = '/dashboard/overview'")
+	})
+}
+
+// HandleInvitationSignup creates an account from an invitation, signs it in
+// and accepts the invitation, via Datastar SSE
+// POST /api/invitations/:token/signup (form: username, name, password)
+func HandleInvitationSignup(w http.ResponseWriter, r *http.Request) {
+	username := strings.TrimSpace(r.FormValue("username"))
+	name := strings.TrimSpace(r.FormValue("name"))
+	password := r.FormValue("password")
+
+	signupErr := ""
+	switch {
+	case len(username) < 3:
+		signupErr = "Username must be at least 3 characters"
+	case len(password) < 8:
+		signupErr = "Password must be at least 8 characters"
+	}
+
+	if signupErr == "" {
+		userID, err := models.SignUpWithInvitation(r.Context(), database.DB, chi.URLParam(r, "token"), username, password, name)
+		switch {
+		case errors.Is(err, models.ErrInvitationNotFound):
+			signupErr = "This invitation has expired or was already used"
+		case errors.Is(err, models.ErrUsernameTaken):
+			signupErr = "That username is taken"
+		case err != nil:
+			log.Printf("HandleInvitationSignup: %v", err)
+			signupErr = "Failed to create account"
+		default:
+			if err := startSession(w, r, userID); err != nil {
+				log.Printf("HandleInvitationSignup: %v", err)
+				signupErr = "Account created, but signing in failed. Log in to continue."
+			}
+		}
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if signupErr != "" {
+			_ = sse.PatchSignals(map[string]any{"inviteError": signupErr, "submitting": false})
+			return
+		}
+		_ = sse.ExecuteScript("window.location.href = '/dashboard/overview'")
+	})
+}
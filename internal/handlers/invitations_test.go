@@ -0,0 +1,62 @@
+package handlers
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestPublicLink(t *testing.T) {
+	stubVar(t, &publicURL, "")
+	stubVar(t, &accountMailer, accountMailer)
+
+	req := httptest.NewRequest(http.MethodPost, "/api/teams", nil)
+	req.Host = "stats.example.com"
+	assert.Equal(t, "http://stats.example.com/invite/abc", publicLink(req, "/invite/abc"))
+
+	req.Header.Set("X-Forwarded-Proto", "https")
+	assert.Equal(t, "https://stats.example.com/invite/abc", publicLink(req, "/invite/abc"))
+
+	// The configured public URL wins over the request's host
+	SetMailer(nil, "https://kaunta.example.org/")
+	assert.Equal(t, "https://kaunta.example.org/invite/abc", publicLink(req, "/invite/abc"))
+}
+
+func TestTeamInviteValidation(t *testing.T) {
+	ownerTeam := uuid.New()
+	stubVar(t, &teamRoleFunc, func(ctx context.Context, teamID, userID uuid.UUID) (models.WebsiteRole, error) {
+		return models.RoleOwner, nil
+	})
+
+	router := chi.NewRouter()
+	router.Post("/api/teams/{team_id}/invitations", HandleTeamInvite)
+	target := "/api/teams/" + ownerTeam.String() + "/invitations"
+
+	resp := httptest.NewRecorder()
+	router.ServeHTTP(resp, teamsRequest(http.MethodPost, target, `{"email":"not an address"}`))
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+
+	resp = httptest.NewRecorder()
+	router.ServeHTTP(resp, teamsRequest(http.MethodPost, target, `{"email":"bob@example.com","role":"admin"}`))
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
+
+func TestHandleInvitationSignupValidation(t *testing.T) {
+	form := url.Values{"username": {"bob"}, "password": {"short"}}
+	req := httptest.NewRequest(http.MethodPost, "/api/invitations/abc/signup", strings.NewReader(form.Encode()))
+	req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+
+	resp := httptest.NewRecorder()
+	HandleInvitationSignup(resp, req)
+	assert.Contains(t, resp.Body.String(), "Password must be at least 8 characters")
+	assert.Empty(t, resp.Header().Values("Set-Cookie"))
+}
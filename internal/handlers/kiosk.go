@@ -0,0 +1,332 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// kioskTopPagesLimit is how many pages the TV view lists
+const kioskTopPagesLimit = 8
+
+// kioskPasswordMinLength is the shortest password a kiosk link accepts
+const kioskPasswordMinLength = 6
+
+// KioskSnapshot is what the TV view shows: the realtime number and today's totals
+type KioskSnapshot struct {
+	Name      string `json:"name"`
+	Online    int64  `json:"online"`
+	Pageviews int64  `json:"pageviews"`
+	Visitors  int64  `json:"visitors"`
+	Updated   string `json:"updated"`
+}
+
+// HandleKioskLink returns the kiosk link of a website, creating it on first
+// use; rotate=true replaces the token so the old URL stops working
+// POST /api/dashboard/kiosk?website_id=...&rotate=true
+func HandleKioskLink(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	websiteID, parseErr := parseRequiredWebsiteID(r.URL.Query().Get("website_id"))
+	if parseErr != "" {
+		respondError(w, r, http.StatusBadRequest, parseErr)
+		return
+	}
+	rotate, _ := strconv.ParseBool(r.URL.Query().Get("rotate"))
+
+	link, err := models.GetKioskLink(r.Context(), database.DB, websiteID, user.UserID)
+	if rotate || errors.Is(err, models.ErrKioskLinkNotFound) {
+		link, err = models.RotateKioskLink(r.Context(), database.DB, websiteID, user.UserID)
+	}
+	if errors.Is(err, models.ErrKioskLinkNotFound) {
+		respondError(w, r, http.StatusNotFound, "Website not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleKioskLink: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to create kiosk link")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{"kiosk": kioskLinkSignal(link, time.Now())})
+	})
+}
+
+// HandleKioskLinkSettings sets a kiosk link's password and expiry. A blank
+// password keeps the current one; a blank expires makes the link permanent.
+// POST /api/dashboard/kiosk/settings?website_id=... (form: password, remove_password, expires)
+func HandleKioskLinkSettings(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	websiteID, parseErr := parseRequiredWebsiteID(r.URL.Query().Get("website_id"))
+	if parseErr != "" {
+		respondError(w, r, http.StatusBadRequest, parseErr)
+		return
+	}
+
+	fail := func(message string) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"kioskSettingsError": message})
+		})
+	}
+
+	var password *string
+	if remove, _ := strconv.ParseBool(r.FormValue("remove_password")); remove {
+		password = new(string)
+	} else if value := r.FormValue("password"); value != "" {
+		if len(value) < kioskPasswordMinLength {
+			fail(fmt.Sprintf("Password must be at least %d characters", kioskPasswordMinLength))
+			return
+		}
+		password = &value
+	}
+	expiresAt, err := models.ParseAPIKeyExpiry(r.FormValue("expires"), time.Now())
+	if err != nil {
+		fail(err.Error())
+		return
+	}
+
+	link, err := models.SetKioskLinkProtection(r.Context(), database.DB, websiteID, user.UserID, password, expiresAt)
+	if errors.Is(err, models.ErrKioskLinkNotFound) {
+		respondError(w, r, http.StatusNotFound, "Kiosk link not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleKioskLinkSettings: %v", err)
+		fail("Failed to save the link settings")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{
+			"kiosk":              kioskLinkSignal(link, time.Now()),
+			"kioskSettingsError": "",
+		})
+	})
+}
+
+// kioskLinkSignal is the dashboard's view of a kiosk link. expires_on is
+// the exclusive end date the settings form edits.
+func kioskLinkSignal(link *models.KioskLink, now time.Time) map[string]any {
+	expiresOn, expired := "", false
+	if link.ExpiresAt != nil {
+		expiresOn = link.ExpiresAt.UTC().Format(time.DateOnly)
+		expired = !link.ExpiresAt.After(now)
+	}
+	return map[string]any{
+		"website":    link.WebsiteID.String(),
+		"path":       "/kiosk/" + link.Token,
+		"protected":  link.HasPassword,
+		"expires_on": expiresOn,
+		"expired":    expired,
+	}
+}
+
+// HandleKioskLinkDelete revokes a website's kiosk link
+// DELETE /api/dashboard/kiosk?website_id=...
+func HandleKioskLinkDelete(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	websiteID, parseErr := parseRequiredWebsiteID(r.URL.Query().Get("website_id"))
+	if parseErr != "" {
+		respondError(w, r, http.StatusBadRequest, parseErr)
+		return
+	}
+
+	if err := models.DeleteKioskLink(r.Context(), database.DB, websiteID, user.UserID); err != nil {
+		respondError(w, r, http.StatusNotFound, "Kiosk link not found")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{
+			"kiosk": map[string]any{"website": "", "path": "", "protected": false, "expires_on": "", "expired": false},
+		})
+	})
+}
+
+// HandlePublicKiosk renders the TV view for a kiosk token (no auth
+// required). Password-protected links first need the cookie set by
+// HandleKioskUnlock.
+// GET /api/public/kiosk/{token}
+func HandlePublicKiosk(w http.ResponseWriter, r *http.Request) {
+	token := chi.URLParam(r, "token")
+	site, err := models.GetKioskWebsite(r.Context(), database.DB, token)
+	if err != nil {
+		if !errors.Is(err, models.ErrKioskLinkNotFound) {
+			log.Printf("HandlePublicKiosk: %v", err)
+		}
+		streamKioskInvalid(w)
+		return
+	}
+
+	accessKey := ""
+	if cookie, err := r.Cookie(kioskCookieName); err == nil {
+		accessKey = cookie.Value
+	}
+	if !site.Unlocks(accessKey) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"kioskLocked": true, "kioskLoading": false})
+		})
+		return
+	}
+	streamKiosk(w, r, site)
+}
+
+// HandleKioskUnlock checks the password of a protected kiosk link and
+// remembers it in a cookie scoped to the link
+// POST /api/public/kiosk/{token}/unlock (form: password)
+func HandleKioskUnlock(w http.ResponseWriter, r *http.Request) {
+	token := chi.URLParam(r, "token")
+	accessKey, err := models.UnlockKiosk(r.Context(), database.DB, token, r.FormValue("password"))
+	if errors.Is(err, models.ErrKioskPasswordIncorrect) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"kioskUnlockError": "Incorrect password"})
+		})
+		return
+	}
+	var site *models.KioskWebsite
+	if err == nil {
+		site, err = models.GetKioskWebsite(r.Context(), database.DB, token)
+	}
+	if err != nil {
+		if !errors.Is(err, models.ErrKioskLinkNotFound) {
+			log.Printf("HandleKioskUnlock: %v", err)
+		}
+		streamKioskInvalid(w)
+		return
+	}
+
+	http.SetCookie(w, kioskCookie(token, accessKey))
+	streamKiosk(w, r, site)
+}
+
+// streamKioskInvalid tells the TV view its link is gone
+func streamKioskInvalid(w http.ResponseWriter) {
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{
+			"kioskError":   "This kiosk link has expired or is no longer valid",
+			"kioskLoading": false,
+		})
+	})
+}
+
+// streamKiosk sends the TV view's numbers, chart and top pages
+func streamKiosk(w http.ResponseWriter, r *http.Request, site *models.KioskWebsite) {
+	snapshot, chart, pages, err := loadKioskSnapshot(r.Context(), site)
+	if err != nil {
+		log.Printf("streamKiosk: %v", err)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			// Keep showing the last snapshot; the next refresh will try again
+			_ = sse.PatchSignals(map[string]any{"kioskLoading": false, "kioskLocked": false})
+			return
+		}
+		_ = sse.PatchElementsWithMode("#kiosk-chart", buildSparklineSVG(chart, 960, 200), "inner")
+		_ = sse.PatchElementsWithMode("#kiosk-pages", buildKioskPagesHTML(pages), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"kiosk":            snapshot,
+			"kioskError":       false,
+			"kioskLoading":     false,
+			"kioskLocked":      false,
+			"kioskUnlockError": "",
+		})
+	})
+}
+
+// loadKioskSnapshot gathers today's numbers, hourly pageviews and top pages
+func loadKioskSnapshot(ctx context.Context, site *models.KioskWebsite) (*KioskSnapshot, []int64, []BreakdownItem, error) {
+	snapshot := &KioskSnapshot{Name: site.Name}
+	if snapshot.Name == "" {
+		snapshot.Name = site.Domain
+	}
+	ctx = database.WithTenant(ctx, site.WebsiteID)
+
+	var bounce, duration float64
+	err := database.QueryRowStats(ctx,
+		`SELECT * FROM get_dashboard_stats($1, 1, NULL, NULL, NULL, NULL)`,
+		site.WebsiteID,
+	).Scan(&snapshot.Online, &snapshot.Pageviews, &snapshot.Visitors, &bounce, &duration)
+	if err != nil {
+		return nil, nil, nil, fmt.Errorf("stats: %w", err)
+	}
+
+	chart, err := loadKioskChart(ctx, site.WebsiteID)
+	if err != nil {
+		return nil, nil, nil, fmt.Errorf("timeseries: %w", err)
+	}
+
+	pages, err := loadCustomBreakdown(ctx, site.WebsiteID, models.WidgetQuery{Days: 1, Dimension: "pages"})
+	if err != nil {
+		return nil, nil, nil, fmt.Errorf("top pages: %w", err)
+	}
+	if len(pages) > kioskTopPagesLimit {
+		pages = pages[:kioskTopPagesLimit]
+	}
+
+	snapshot.Updated = time.Now().UTC().Format(time.RFC3339)
+	return snapshot, chart, pages, nil
+}
+
+// loadKioskChart returns today's pageviews per hour
+func loadKioskChart(ctx context.Context, websiteID uuid.UUID) ([]int64, error) {
+	rows, err := database.QueryStats(ctx,
+		`SELECT * FROM get_timeseries($1, 1, NULL, NULL, NULL, NULL, $2)`,
+		websiteID, granularityHour,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	values := make([]int64, 0, 24)
+	for rows.Next() {
+		var bucket sql.NullTime
+		var views int64
+		if err := rows.Scan(&bucket, &views); err != nil {
+			return nil, err
+		}
+		values = append(values, views)
+	}
+	return values, rows.Err()
+}
+
+func buildKioskPagesHTML(pages []BreakdownItem) string {
+	if len(pages) == 0 {
+		return `<div class="empty-state-mini">No pageviews yet today</div>`
+	}
+
+	var b strings.Builder
+	b.WriteString(`<ol class="kiosk-pages-list">`)
+	for _, p := range pages {
+		fmt.Fprintf(&b, `<li><span class="kiosk-page-path">%s</span><span class="kiosk-page-views">%s</span></li>`,
+			escapeHTML(p.Name), formatNumber(p.Count))
+	}
+	b.WriteString(`</ol>`)
+	return b.String()
+}
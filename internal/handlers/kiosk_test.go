@@ -0,0 +1,55 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestKioskLinkSignal(t *testing.T) {
+	now := time.Date(2026, 5, 1, 12, 0, 0, 0, time.UTC)
+	ends := time.Date(2026, 6, 1, 0, 0, 0, 0, time.UTC)
+	link := &models.KioskLink{Token: "abc", WebsiteID: uuid.New(), HasPassword: true, ExpiresAt: &ends}
+
+	signal := kioskLinkSignal(link, now)
+	assert.Equal(t, "/kiosk/abc", signal["path"])
+	assert.Equal(t, true, signal["protected"])
+	assert.Equal(t, "2026-06-01", signal["expires_on"])
+	assert.Equal(t, false, signal["expired"])
+
+	assert.Equal(t, true, kioskLinkSignal(link, ends)["expired"])
+}
+
+func TestHandleKioskLinkSettingsValidation(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New()}
+	target := "/api/dashboard/kiosk/settings?website_id=" + uuid.New().String()
+	cases := map[string]url.Values{
+		"at least 6 characters": {"password": {"abc"}},
+		"invalid expiry":        {"expires": {"someday"}},
+		"not in the future":     {"expires": {"2001-01-01"}},
+	}
+	for message, form := range cases {
+		req := httptest.NewRequest(http.MethodPost, target, strings.NewReader(form.Encode()))
+		req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+		resp := httptest.NewRecorder()
+		HandleKioskLinkSettings(resp, req.WithContext(middleware.ContextWithUser(req.Context(), user)))
+		assert.Contains(t, resp.Body.String(), "kioskSettingsError", message)
+		assert.Contains(t, resp.Body.String(), message)
+	}
+}
+
+func TestKioskCookieScopedToLink(t *testing.T) {
+	cookie := kioskCookie("abc", "key")
+	assert.Equal(t, kioskCookieName, cookie.Name)
+	assert.Equal(t, "/api/public/kiosk/abc", cookie.Path)
+	assert.True(t, cookie.HttpOnly)
+}
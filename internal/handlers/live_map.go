@@ -0,0 +1,201 @@
+package handlers
+
+import (
+	"context"
+	"log"
+	"net/http"
+	"sync"
+	"time"
+
+	"log/slog"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/geoip"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+var (
+	// liveMapInterval is how often the live map stream pushes fresh points
+	liveMapInterval = 10 * time.Second
+	// knownCityCentroids remembers cities already stored in city_location
+	knownCityCentroids sync.Map
+)
+
+// LiveMapPoint is a group of current visitors plotted at their city's
+// centroid, or at their country's centroid when the city is unknown
+type LiveMapPoint struct {
+	Country     string   `json:"country"`
+	CountryName string   `json:"country_name"`
+	Code        string   `json:"code"` // TopoJSON country code
+	City        string   `json:"city,omitempty"`
+	Lat         *float64 `json:"lat,omitempty"`
+	Lon         *float64 `json:"lon,omitempty"`
+	Visitors    int64    `json:"visitors"`
+}
+
+// rememberCityCentroid stores the centroid of a visitor's city the first
+// time it is seen; the visitor's own coordinates are never kept
+func rememberCityCentroid(ip, country, city string) {
+	if country == "" || city == "" || len(city) > 50 {
+		return
+	}
+	key := country + "|" + city
+	if _, seen := knownCityCentroids.Load(key); seen {
+		return
+	}
+
+	lat, lon, ok := geoip.LookupCityCentroid(ip)
+	if !ok {
+		return
+	}
+	if _, err := database.DB.Exec(`
+		INSERT INTO city_location (country, city, latitude, longitude)
+		VALUES ($1, $2, $3, $4)
+		ON CONFLICT (country, city) DO NOTHING
+	`, country, city, lat, lon); err != nil {
+		logging.L().Debug("failed to store city centroid", slog.String("city", city), slog.Any("error", err))
+		return
+	}
+	knownCityCentroids.Store(key, struct{}{})
+}
+
+// HandleLiveMapInit loads websites for the live map page
+// GET /api/dashboard/live-map-init
+func HandleLiveMapInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	rows, queryErr := database.DB.Query(`
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1))
+		ORDER BY domain
+	`, user.UserID)
+	if queryErr == nil {
+		websites = scanWebsiteInfos(rows)
+	}
+
+	selectedWebsite := selectedWebsiteFromRequest(r)
+	if selectedWebsite == "" && len(websites) > 0 {
+		selectedWebsite = websites[0].ID
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleLiveMapInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Failed to load websites",
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"selectedWebsite": selectedWebsite,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+		if html := buildWebsiteSelectorHTML(websites, selectedWebsite, "map"); html != "" {
+			_ = sse.PatchElements("#website-selector-container", html)
+		}
+	})
+}
+
+// HandleLiveMap streams the locations of current visitors (last 5 minutes),
+// pushing a fresh snapshot every liveMapInterval until the client disconnects
+// GET /api/dashboard/live-map?website_id=...
+func HandleLiveMap(w http.ResponseWriter, r *http.Request) {
+	websiteID, parseErr := parseRequiredWebsiteID(r.URL.Query().Get("website_id"))
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			_ = sse.PatchSignals(map[string]any{"liveMapError": parseErr})
+			return
+		}
+
+		ctx := r.Context()
+		ticker := time.NewTicker(liveMapInterval)
+		defer ticker.Stop()
+
+		for {
+			points, err := loadLiveMapPoints(ctx, websiteID)
+			if err != nil {
+				if ctx.Err() != nil {
+					return
+				}
+				log.Printf("HandleLiveMap: %v", err)
+				_ = sse.PatchSignals(map[string]any{"liveMapError": "Failed to load current visitors"})
+			} else {
+				var total int64
+				for _, p := range points {
+					total += p.Visitors
+				}
+				if err := sse.PatchSignals(map[string]any{
+					"liveMapPoints":   points,
+					"liveMapVisitors": total,
+					"liveMapUpdated":  time.Now().UTC().Format(time.RFC3339),
+					"liveMapError":    false,
+				}); err != nil {
+					return // client went away
+				}
+			}
+
+			select {
+			case <-ctx.Done():
+				return
+			case <-ticker.C:
+			}
+		}
+	})
+}
+
+// loadLiveMapPoints groups current visitors by country and city, joining the
+// stored city centroids
+func loadLiveMapPoints(ctx context.Context, websiteID uuid.UUID) ([]LiveMapPoint, error) {
+	rows, err := database.QueryStats(ctx, `
+		SELECT
+			s.country,
+			COALESCE(s.city, ''),
+			c.latitude::FLOAT8,
+			c.longitude::FLOAT8,
+			COUNT(DISTINCT e.session_id)
+		FROM website_event e
+		JOIN session s ON e.session_id = s.session_id
+		LEFT JOIN city_location c ON c.country = s.country AND c.city = s.city
+		WHERE e.website_id = $1
+		  AND e.created_at >= NOW() - INTERVAL '5 minutes'
+		  AND s.country IS NOT NULL AND TRIM(s.country) <> ''
+		GROUP BY s.country, s.city, c.latitude, c.longitude
+		ORDER BY 5 DESC
+	`, websiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	points := make([]LiveMapPoint, 0)
+	for rows.Next() {
+		var p LiveMapPoint
+		if err := rows.Scan(&p.Country, &p.City, &p.Lat, &p.Lon, &p.Visitors); err != nil {
+			return nil, err
+		}
+		p.CountryName = getCountryName(p.Country)
+		p.Code = getTopoJSONCode(p.Country)
+		points = append(points, p)
+	}
+	return points, rows.Err()
+}
@@ -0,0 +1,64 @@
+package handlers
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestLoadLiveMapPoints(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("LEFT JOIN city_location").
+		WithArgs(websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"country", "city", "latitude", "longitude", "visitors"}).
+			AddRow("DE", "Berlin", 52.5, 13.4, 3).
+			AddRow("FR", "", nil, nil, 1))
+
+	points, err := loadLiveMapPoints(context.Background(), websiteID)
+	require.NoError(t, err)
+	require.Len(t, points, 2)
+
+	assert.Equal(t, "Berlin", points[0].City)
+	assert.Equal(t, "Germany", points[0].CountryName)
+	assert.Equal(t, "276", points[0].Code)
+	require.NotNil(t, points[0].Lat)
+	assert.InDelta(t, 52.5, *points[0].Lat, 0.001)
+	assert.Equal(t, int64(3), points[0].Visitors)
+
+	// Unknown city falls back to the country centroid on the client
+	assert.Nil(t, points[1].Lat)
+	assert.Nil(t, points[1].Lon)
+	assert.Equal(t, "250", points[1].Code)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRememberCityCentroidSkipsWithoutGeoIP(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	// No GeoIP database is loaded in tests, so nothing is written
+	rememberCityCentroid("8.8.8.8", "US", "Mountain View")
+	rememberCityCentroid("8.8.8.8", "", "")
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
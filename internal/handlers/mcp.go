@@ -0,0 +1,38 @@
+package handlers
+
+import (
+	"log"
+	"net/http"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/mcp"
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+// HandleMCP serves the MCP stats tools over streamable HTTP, limited to the
+// API key's website
+// Requires API key with 'stats' scope
+// POST /api/mcp
+func HandleMCP(version string) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		apiKey := middleware.GetAPIKey(r)
+		if apiKey == nil {
+			respondError(w, r, http.StatusUnauthorized, "Unauthorized")
+			return
+		}
+		if !authorizeAPIStatsWebsite(w, r, apiKey.WebsiteID) {
+			return
+		}
+
+		ctx, cancel := database.StatsContext(r.Context())
+		defer cancel()
+		db, release, err := database.StatsQuerier(ctx)
+		if err != nil {
+			log.Printf("HandleMCP: %v", err)
+			respondError(w, r, http.StatusInternalServerError, "Failed to query stats")
+			return
+		}
+		defer release()
+		mcp.NewStatsServer(database.TrackStats(db), &apiKey.WebsiteID, version).ServeHTTP(w, r.WithContext(ctx))
+	}
+}
@@ -0,0 +1,17 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestHandleMCPRequiresAPIKey(t *testing.T) {
+	rec := httptest.NewRecorder()
+	body := strings.NewReader(`{"jsonrpc":"2.0","id":1,"method":"tools/list"}`)
+	HandleMCP("test")(rec, httptest.NewRequest(http.MethodPost, "/api/mcp", body))
+	assert.Equal(t, http.StatusUnauthorized, rec.Code)
+}
@@ -0,0 +1,46 @@
+package handlers
+
+import (
+	"fmt"
+	"io"
+	"net/http"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+// HandleMetrics serves database pool, stats cache and ingest metrics in the
+// Prometheus text format
+// GET /metrics
+func HandleMetrics(w http.ResponseWriter, r *http.Request) {
+	w.Header().Set("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
+
+	if database.DB != nil {
+		stats := database.DB.Stats()
+		writeMetric(w, "kaunta_db_pool_max_open", "gauge", "Maximum open connections (0 is unlimited)", float64(stats.MaxOpenConnections))
+		writeMetric(w, "kaunta_db_pool_open", "gauge", "Open connections, in use or idle", float64(stats.OpenConnections))
+		writeMetric(w, "kaunta_db_pool_in_use", "gauge", "Connections in use", float64(stats.InUse))
+		writeMetric(w, "kaunta_db_pool_idle", "gauge", "Idle connections", float64(stats.Idle))
+		// database/sql only counts finished waits; rate() of the seconds is
+		// the average number of queries waiting for a connection
+		writeMetric(w, "kaunta_db_pool_wait_count_total", "counter", "Queries that waited for a free connection", float64(stats.WaitCount))
+		writeMetric(w, "kaunta_db_pool_wait_seconds_total", "counter", "Time spent waiting for a free connection", stats.WaitDuration.Seconds())
+		writeMetric(w, "kaunta_db_pool_idle_closed_total", "counter", "Connections closed for exceeding the idle lifetime", float64(stats.MaxIdleTimeClosed))
+	}
+
+	writeMetric(w, "kaunta_stats_cache_hits_total", "counter", "Dashboard queries answered from the stats cache", float64(statsCache.hits.Load()))
+	writeMetric(w, "kaunta_stats_cache_misses_total", "counter", "Dashboard queries that ran against the database", float64(statsCache.misses.Load()))
+
+	writeMetric(w, "kaunta_ingest_in_flight", "gauge", "Tracking and ingest requests being processed", float64(ingestInFlight.Load()))
+	writeMetric(w, "kaunta_ingest_shed_total", "counter", "Hits refused because the server couldn't take more", float64(ingestShed.Load()))
+	if eventBuffer != nil {
+		writeMetric(w, "kaunta_ingest_pending", "gauge", "Accepted hits waiting to be written", float64(eventBuffer.Pending()))
+		writeMetric(w, "kaunta_ingest_capacity", "gauge", "Hits that may wait before new ones are refused (0 is unlimited)", float64(eventBuffer.settings.MaxPending))
+		writeMetric(w, "kaunta_ingest_oldest_pending_seconds", "gauge", "How long the longest-waiting hit has waited", eventBuffer.OldestPending().Seconds())
+		writeMetric(w, "kaunta_ingest_written_total", "counter", "Buffered hits written", float64(eventBuffer.written.Load()))
+		writeMetric(w, "kaunta_ingest_flush_failures_total", "counter", "Flushes that left hits waiting, usually because the database was unavailable", float64(eventBuffer.failures.Load()))
+	}
+}
+
+func writeMetric(w io.Writer, name, kind, help string, value float64) {
+	_, _ = fmt.Fprintf(w, "# HELP %s %s\n# TYPE %s %s\n%s %g\n", name, help, name, kind, name, value)
+}
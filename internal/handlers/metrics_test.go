@@ -0,0 +1,44 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestHandleMetrics(t *testing.T) {
+	db, _, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	db.SetMaxOpenConns(25)
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	buffer := NewEventBuffer(db, EventBufferSettings{FlushInterval: time.Hour})
+	require.NoError(t, buffer.Add(testEventRow()))
+	SetEventBuffer(buffer)
+	defer SetEventBuffer(nil)
+
+	rec := httptest.NewRecorder()
+	HandleMetrics(rec, httptest.NewRequest(http.MethodGet, "/metrics", nil))
+
+	assert.Equal(t, http.StatusOK, rec.Code)
+	assert.Contains(t, rec.Header().Get("Content-Type"), "text/plain")
+	body := rec.Body.String()
+	assert.Contains(t, body, "# TYPE kaunta_db_pool_in_use gauge\nkaunta_db_pool_in_use 0\n")
+	assert.Contains(t, body, "kaunta_db_pool_max_open 25\n")
+	assert.Contains(t, body, "# TYPE kaunta_db_pool_wait_seconds_total counter\n")
+	assert.Contains(t, body, "kaunta_ingest_pending 1\n")
+	assert.Contains(t, body, "kaunta_ingest_capacity 0\n")
+	assert.Contains(t, body, "# TYPE kaunta_ingest_shed_total counter\n")
+	assert.Contains(t, body, "# TYPE kaunta_ingest_oldest_pending_seconds gauge\n")
+}
@@ -0,0 +1,156 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+// NotFoundEventName is the custom event the tracker sends from 404 pages
+const NotFoundEventName = "404"
+
+const (
+	// notFoundTopLimit is the number of missing URLs shown in the report
+	notFoundTopLimit = 25
+	// notFoundReferrerLimit is the number of referrers listed per missing URL
+	notFoundReferrerLimit = 5
+)
+
+// NotFoundReferrer is a page that linked to a missing URL
+type NotFoundReferrer struct {
+	Referrer string `json:"referrer"` // domain + path, empty for direct visits
+	Hits     int64  `json:"hits"`
+}
+
+// NotFoundPage is a missing URL with the referrers sending visitors to it
+type NotFoundPage struct {
+	Path      string             `json:"path"`
+	Hits      int64              `json:"hits"`
+	Visitors  int64              `json:"visitors"`
+	Referrers []NotFoundReferrer `json:"referrers"`
+}
+
+// HandleNotFound renders the 404 report: top missing URLs and their referrers
+// GET /api/dashboard/not-found?website=...&days=7
+func HandleNotFound(w http.ResponseWriter, r *http.Request) {
+	query := r.URL.Query()
+	websiteIDStr := query.Get("website_id")
+	if websiteIDStr == "" {
+		websiteIDStr = query.Get("website")
+	}
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+
+	websiteID, parseErr := parseRequiredWebsiteID(websiteIDStr)
+
+	var pages []NotFoundPage
+	var queryErr error
+	if parseErr == "" {
+		pages, queryErr = loadNotFoundPages(r.Context(), websiteID, days, notFoundTopLimit)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			patchBreakdownErrorState(sse, parseErr)
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleNotFound: %v", queryErr)
+			patchBreakdownErrorState(sse, "Failed to load 404 report")
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#breakdown-content-body", buildNotFoundTableHTML(pages), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"breakdownLoading": false,
+			"breakdownError":   false,
+		})
+	})
+}
+
+// loadNotFoundPages returns the most-hit missing URLs, each with its top referrers
+func loadNotFoundPages(ctx context.Context, websiteID uuid.UUID, days, limit int) ([]NotFoundPage, error) {
+	rows, err := database.QueryStats(ctx, `
+		WITH hits AS (
+			SELECT
+				url_path AS path,
+				COALESCE(referrer_domain, '') || COALESCE(referrer_path, '') AS referrer,
+				session_id
+			FROM website_event
+			WHERE website_id = $1
+			  AND event_type = 2
+			  AND event_name = $2
+			  AND url_path IS NOT NULL
+			  AND created_at >= NOW() - make_interval(days => $3)
+		),
+		top_paths AS (
+			SELECT path, COUNT(*) AS hits, COUNT(DISTINCT session_id) AS visitors
+			FROM hits
+			GROUP BY path
+			ORDER BY hits DESC, path
+			LIMIT $4
+		)
+		SELECT p.path, p.hits, p.visitors, r.referrer, r.hits
+		FROM top_paths p
+		CROSS JOIN LATERAL (
+			SELECT referrer, COUNT(*) AS hits
+			FROM hits h
+			WHERE h.path = p.path
+			GROUP BY referrer
+			ORDER BY hits DESC, referrer
+			LIMIT $5
+		) r
+		ORDER BY p.hits DESC, p.path, r.hits DESC, r.referrer
+	`, websiteID, NotFoundEventName, days, limit, notFoundReferrerLimit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	pages := make([]NotFoundPage, 0)
+	for rows.Next() {
+		var path, referrer string
+		var hits, visitors, referrerHits int64
+		if err := rows.Scan(&path, &hits, &visitors, &referrer, &referrerHits); err != nil {
+			return nil, err
+		}
+		if len(pages) == 0 || pages[len(pages)-1].Path != path {
+			pages = append(pages, NotFoundPage{Path: path, Hits: hits, Visitors: visitors})
+		}
+		last := &pages[len(pages)-1]
+		last.Referrers = append(last.Referrers, NotFoundReferrer{Referrer: referrer, Hits: referrerHits})
+	}
+	return pages, rows.Err()
+}
+
+func buildNotFoundTableHTML(pages []NotFoundPage) string {
+	if len(pages) == 0 {
+		return `<div class="empty-state"><div class="empty-state-icon">404</div><div class="empty-state-title">No broken links recorded</div><div class="empty-state-text">Add <code>data-track-404="true"</code> to the tracker script on your 404 page, or call <code>kaunta.track404()</code> from it.</div></div>`
+	}
+
+	var rows strings.Builder
+	for _, page := range pages {
+		referrers := make([]string, 0, len(page.Referrers))
+		for _, ref := range page.Referrers {
+			name := `<span class="breakdown-sublist-meta">(direct)</span>`
+			if ref.Referrer != "" {
+				name = escapeHTML(ref.Referrer)
+			}
+			referrers = append(referrers, fmt.Sprintf(`<li>%s <span class="breakdown-sublist-meta">%s</span></li>`, name, formatNumber(int(ref.Hits))))
+		}
+		fmt.Fprintf(&rows, `<tr><td><code>%s</code></td><td><ul class="breakdown-sublist">%s</ul></td><td style="text-align:right">%s</td><td style="text-align:right">%s</td></tr>`,
+			escapeHTML(page.Path),
+			strings.Join(referrers, ""),
+			formatNumber(int(page.Visitors)),
+			formatNumber(int(page.Hits)),
+		)
+	}
+
+	return fmt.Sprintf(`<table class="breakdown-table not-found-table"><thead><tr><th>Missing URL</th><th>Referrers</th><th style="text-align:right">Visitors</th><th style="text-align:right">Hits</th></tr></thead><tbody>%s</tbody></table>`,
+		rows.String())
+}
@@ -0,0 +1,72 @@
+package handlers
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestLoadNotFoundPagesGroupsReferrersByPath(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("FROM website_event").
+		WithArgs(websiteID, NotFoundEventName, 7, notFoundTopLimit, notFoundReferrerLimit).
+		WillReturnRows(sqlmock.NewRows([]string{"path", "hits", "visitors", "referrer", "referrer_hits"}).
+			AddRow("/old-pricing", 12, 9, "example.com/blog/launch", 8).
+			AddRow("/old-pricing", 12, 9, "", 4).
+			AddRow("/docs/v1", 3, 3, "/docs", 3))
+
+	pages, err := loadNotFoundPages(context.Background(), websiteID, 7, notFoundTopLimit)
+	require.NoError(t, err)
+	require.Len(t, pages, 2)
+
+	assert.Equal(t, "/old-pricing", pages[0].Path)
+	assert.Equal(t, int64(12), pages[0].Hits)
+	assert.Equal(t, int64(9), pages[0].Visitors)
+	assert.Equal(t, []NotFoundReferrer{
+		{Referrer: "example.com/blog/launch", Hits: 8},
+		{Referrer: "", Hits: 4},
+	}, pages[0].Referrers)
+
+	assert.Equal(t, "/docs/v1", pages[1].Path)
+	assert.Equal(t, []NotFoundReferrer{{Referrer: "/docs", Hits: 3}}, pages[1].Referrers)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestBuildNotFoundTableHTML(t *testing.T) {
+	html := buildNotFoundTableHTML([]NotFoundPage{{
+		Path:     "/<script>",
+		Hits:     1200,
+		Visitors: 900,
+		Referrers: []NotFoundReferrer{
+			{Referrer: "example.com/a&b", Hits: 5},
+			{Referrer: "", Hits: 2},
+		},
+	}})
+
+	assert.Contains(t, html, "<code>/&lt;script&gt;</code>")
+	assert.Contains(t, html, "example.com/a&amp;b")
+	assert.Contains(t, html, "(direct)")
+	assert.Contains(t, html, "1,200")
+	assert.NotContains(t, html, "<script>")
+}
+
+func TestBuildNotFoundTableHTMLEmpty(t *testing.T) {
+	html := buildNotFoundTableHTML(nil)
+	assert.Contains(t, html, "No broken links recorded")
+	assert.Contains(t, html, "data-track-404")
+}
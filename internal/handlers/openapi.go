@@ -0,0 +1,713 @@
+package handlers
+
+import (
+	"encoding/json"
+	"net/http"
+	"slices"
+	"strconv"
+	"strings"
+
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/openapi"
+	"github.com/seuros/kaunta/internal/query"
+	"github.com/seuros/kaunta/internal/warehouse"
+)
+
+// apiError is the body of every JSON error response (see respondError)
+type apiError struct {
+	Error string `json:"error"`
+}
+
+// apiKeySecurity accepts an API key as a bearer token or X-API-Key header
+var apiKeySecurity = []map[string][]string{{"apiKey": {}}, {"apiKeyHeader": {}}}
+
+// sessionSecurity is the dashboard session set by /api/auth/login
+var sessionSecurity = []map[string][]string{{"session": {}}}
+
+// managementSecurity is a session or a personal access token
+var managementSecurity = []map[string][]string{{"session": {}}, {"personalToken": {}}}
+
+// APIDocument describes Kaunta's JSON API as an OpenAPI 3 document. Bodies
+// are derived from the handlers' request and response types; Datastar
+// endpoints under /api/dashboard serve the UI and are not part of it.
+func APIDocument(version string) *openapi.Document {
+	doc := openapi.New("Kaunta API", version,
+		"Collect events, query stats and manage websites. Stats endpoints take an API key with the stats scope; "+
+			"management endpoints take the session cookie set by /api/auth/login or a personal access token.")
+	doc.Tags = []openapi.Tag{
+		{Name: "collect", Description: "Send pageviews and events"},
+		{Name: "auth", Description: "Dashboard sessions"},
+		{Name: "stats", Description: "Read analytics"},
+		{Name: "admin", Description: "Manage websites"},
+		{Name: "teams", Description: "Share websites with groups of users"},
+	}
+	doc.Components.SecuritySchemes["apiKey"] = openapi.SecurityScheme{
+		Type:         "http",
+		Scheme:       "bearer",
+		BearerFormat: "kaunta_live_...",
+		Description:  "API key created with `kaunta apikey create`",
+	}
+	doc.Components.SecuritySchemes["apiKeyHeader"] = openapi.SecurityScheme{
+		Type: "apiKey",
+		In:   "header",
+		Name: "X-API-Key",
+	}
+	doc.Components.SecuritySchemes["session"] = openapi.SecurityScheme{
+		Type:        "apiKey",
+		In:          "cookie",
+		Name:        "kaunta_session",
+		Description: "Session cookie; unsafe methods also need the kaunta_csrf cookie echoed in X-CSRF-Token",
+	}
+	doc.Components.SecuritySchemes["personalToken"] = openapi.SecurityScheme{
+		Type:         "http",
+		Scheme:       "bearer",
+		BearerFormat: "kaunta_pat_...",
+		Description:  "Personal access token from Dashboard → API Keys; acts as its user, and read tokens only allow GET",
+	}
+
+	errorSchema := doc.SchemaOf(apiError{})
+	withErrors := func(responses map[string]openapi.Response, statuses ...int) map[string]openapi.Response {
+		for _, status := range statuses {
+			responses[strconv.Itoa(status)] = jsonResponse(http.StatusText(status), errorSchema)
+		}
+		return responses
+	}
+	object := &openapi.Schema{Type: "object", AdditionalProperties: &openapi.Schema{}}
+	paginated := func(item any) *openapi.Schema {
+		return &openapi.Schema{
+			Type: "object",
+			Properties: map[string]*openapi.Schema{
+				"data":       {Type: "array", Items: doc.SchemaOf(item)},
+				"pagination": doc.SchemaOf(PaginationMeta{}),
+			},
+			Required: []string{"data", "pagination"},
+		}
+	}
+
+	// collect
+	doc.Add(http.MethodPost, "/api/send", openapi.Operation{
+		Tags:        []string{"collect"},
+		Summary:     "Record a pageview or event",
+		Description: "Umami-compatible endpoint used by the tracker script. The Origin must be one of the website's allowed domains. A text/plain body is read as JSON, for hits sent as the page unloads without a CORS preflight.",
+		OperationID: "send",
+		RequestBody: &openapi.RequestBody{
+			Required: true,
+			Content: map[string]openapi.MediaType{
+				"application/json": {Schema: doc.SchemaOf(TrackingPayload{})},
+				"text/plain":       {Schema: doc.SchemaOf(TrackingPayload{})},
+			},
+		},
+		Responses: withErrors(map[string]openapi.Response{
+			"202": jsonResponse("Accepted", object),
+		}, http.StatusBadRequest, http.StatusForbidden, http.StatusNotFound),
+	})
+	doc.Add(http.MethodGet, "/p/{id}.gif", openapi.Operation{
+		Tags:        []string{"collect"},
+		Summary:     "Record a pageview with a tracking pixel",
+		OperationID: "pixel",
+		Parameters: []openapi.Parameter{
+			pathParam("id", "Website ID"),
+			queryParam("url", "string", "Page URL (defaults to the Referer)"),
+			queryParam("title", "string", "Page title"),
+			queryParam("referrer", "string", "Referrer URL"),
+			queryParam("hostname", "string", "Hostname"),
+		},
+		Responses: map[string]openapi.Response{
+			"200": {Description: "1x1 transparent GIF, returned even when the hit is rejected",
+				Content: map[string]openapi.MediaType{"image/gif": {Schema: &openapi.Schema{Type: "string", Format: "binary"}}}},
+		},
+	})
+	doc.Add(http.MethodPost, "/api/ingest", openapi.Operation{
+		Tags:        []string{"collect"},
+		Summary:     "Ingest a server-side event",
+		Description: "Events are recorded for the API key's website. Sending the same event_id twice is a no-op.",
+		OperationID: "ingest",
+		RequestBody: jsonBody(doc.SchemaOf(IngestPayload{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"202": jsonResponse("Accepted", object),
+		}, http.StatusBadRequest, http.StatusUnauthorized),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodPost, "/api/ingest/batch", openapi.Operation{
+		Tags:        []string{"collect"},
+		Summary:     "Ingest up to 100 server-side events",
+		OperationID: "ingestBatch",
+		RequestBody: jsonBody(doc.SchemaOf(BatchIngestRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"202": jsonResponse("Accepted; per-event failures are listed in errors", doc.SchemaOf(BatchIngestResponse{})),
+		}, http.StatusBadRequest, http.StatusUnauthorized),
+		Security: apiKeySecurity,
+	})
+
+	doc.Add(http.MethodGet, "/api/tracker", openapi.Operation{
+		Tags:        []string{"collect"},
+		Summary:     "Tracker script URLs and SRI hash",
+		Description: "The url serves this release's tracker script and never changes, so it can be pinned with the integrity hash. latest_url follows upgrades.",
+		OperationID: "trackerInfo",
+		Responses: map[string]openapi.Response{
+			"200": jsonResponse("Tracker script", doc.SchemaOf(TrackerInfo{})),
+		},
+	})
+
+	// auth
+	doc.Add(http.MethodPost, "/api/auth/login", openapi.Operation{
+		Tags:        []string{"auth"},
+		Summary:     "Log in",
+		Description: "Sets the kaunta_session cookie. For accounts with two-factor authentication, returns two_factor_required and a challenge for /api/auth/login/2fa instead. Limited to 5 attempts per minute.",
+		OperationID: "login",
+		RequestBody: jsonBody(doc.SchemaOf(LoginRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Logged in", doc.SchemaOf(LoginResponse{})),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusTooManyRequests),
+	})
+	doc.Add(http.MethodPost, "/api/auth/login/2fa", openapi.Operation{
+		Tags:        []string{"auth"},
+		Summary:     "Complete a two-factor login",
+		Description: "Exchanges the login challenge and an authenticator or recovery code for the kaunta_session cookie. A challenge expires after 5 minutes or 5 codes.",
+		OperationID: "loginTwoFactor",
+		RequestBody: jsonBody(doc.SchemaOf(TwoFactorLoginRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Logged in", doc.SchemaOf(LoginResponse{})),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusTooManyRequests),
+	})
+	doc.Add(http.MethodGet, "/api/auth/me", openapi.Operation{
+		Tags:        []string{"auth"},
+		Summary:     "Current user",
+		OperationID: "me",
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("The logged-in user", object),
+		}, http.StatusUnauthorized),
+		Security: sessionSecurity,
+	})
+
+	// stats
+	websiteParam := pathParam("website_id", "Website ID")
+	daysParam := func(def int) openapi.Parameter {
+		p := queryParam("days", "integer", "Period in days (1-365)")
+		p.Schema.Default = def
+		return p
+	}
+	segmentParams := []openapi.Parameter{
+		queryParam("country", "string", "Only visits from this ISO country code"),
+		queryParam("browser", "string", "Only visits with this browser"),
+		queryParam("device", "string", "Only visits from this device type"),
+		queryParam("page", "string", "Only visits to this path"),
+	}
+	pageParams := func() []openapi.Parameter {
+		page := queryParam("page", "integer", "1-indexed page")
+		page.Schema.Default = 1
+		per := queryParam("per", "integer", "Items per page (max 100)")
+		per.Schema.Default = 10
+		cursor := queryParam("cursor", "string", "pagination.next_cursor of the previous page; takes precedence over page")
+		return []openapi.Parameter{page, per, cursor}
+	}
+	paginationParams := func(sortColumns []string, defaultSort, defaultOrder string) []openapi.Parameter {
+		sortBy := queryParam("sort_by", "string", "Sort column")
+		sortBy.Schema.Enum, sortBy.Schema.Default = sortColumns, defaultSort
+		sortOrder := queryParam("sort_order", "string", "Sort direction")
+		sortOrder.Schema.Enum, sortOrder.Schema.Default = []string{"asc", "desc"}, defaultOrder
+		return append(pageParams(), sortBy, sortOrder)
+	}
+	statsErrors := []int{http.StatusBadRequest, http.StatusUnauthorized, http.StatusForbidden, http.StatusNotFound, http.StatusTooManyRequests}
+
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Summary stats",
+		Description: "Online visitors and all-time totals, plus bounce rate and average visit duration for the period.",
+		OperationID: "stats",
+		Parameters:  append([]openapi.Parameter{websiteParam, daysParam(30)}, segmentParams...),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Stats", doc.SchemaOf(APIStats{})),
+		}, statsErrors...),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}/summary", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "SSR summary",
+		Description: "Online visitors and the today, 7 day and 30 day totals in one response, for server-rendered layouts. Send the ETag back in If-None-Match to get 304 Not Modified while the numbers are unchanged.",
+		OperationID: "summary",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Summary", doc.SchemaOf(APISummary{})),
+			"304": {Description: "Not modified since If-None-Match"},
+		}, statsErrors...),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}/pages", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Top pages",
+		OperationID: "pages",
+		Parameters: append([]openapi.Parameter{websiteParam, daysParam(7),
+			queryParam("search", "string", `Substring ("pricing") or glob ("/blog/*") to match paths`)},
+			paginationParams(ValidSortColumns["pages"], "views", "desc")...),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("A page of results", paginated(TopPage{})),
+		}, statsErrors...),
+		Security: apiKeySecurity,
+	})
+	granularity := queryParam("granularity", "string", "Bucket size (default: hour up to 7 days, day beyond)")
+	granularity.Schema.Enum = []string{"minute", "hour", "day"}
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}/timeseries", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Pageviews over time",
+		OperationID: "timeseries",
+		Parameters:  append([]openapi.Parameter{websiteParam, daysParam(7), granularity}, segmentParams...),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Pageviews per bucket", doc.SchemaOf(APITimeseries{})),
+		}, statsErrors...),
+		Security: apiKeySecurity,
+	})
+	dimension := pathParam("dimension", "Breakdown dimension")
+	dimension.Schema = &openapi.Schema{Type: "string", Enum: apiBreakdownDimensions()}
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}/breakdown/{dimension}", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Breakdown by dimension",
+		OperationID: "breakdown",
+		// page is the pagination parameter here, so the page segment filter is left out
+		Parameters: append(append([]openapi.Parameter{websiteParam, dimension, daysParam(7)}, segmentParams[:3]...),
+			paginationParams(ValidSortColumns["breakdown"], "count", "desc")...),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("A page of results", paginated(BreakdownItem{})),
+		}, statsErrors...),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}/realtime", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Stream realtime visitors and hits",
+		Description: "Server-sent events: `visitors` carries the online count, `hit` each new pageview or event.",
+		OperationID: "realtime",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": {Description: "Event stream",
+				Content: map[string]openapi.MediaType{"text/event-stream": {Schema: &openapi.Schema{Type: "string"}}}},
+		}, statsErrors...),
+		Security: apiKeySecurity,
+	})
+	daily := jsonResponse("The day's aggregates, ordered by dimension and value", doc.SchemaOf(warehouse.Rollup{}))
+	daily.Content["text/csv"] = openapi.MediaType{Schema: &openapi.Schema{Type: "string"}}
+	dailyFormat := queryParam("format", "string", "Response format")
+	dailyFormat.Schema.Enum, dailyFormat.Schema.Default = []string{"json", "csv"}, "json"
+	doc.Add(http.MethodGet, "/api/v1/stats/{website_id}/daily", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Daily rollup for warehouse syncs",
+		Description: "Per page, referrer, country and event totals for one finished UTC day. Reloading a date returns the same rows, so a sync can replace the day instead of appending.",
+		OperationID: "dailyRollup",
+		Parameters: []openapi.Parameter{websiteParam,
+			queryParam("date", "string", "UTC day as YYYY-MM-DD, before today (default yesterday)"), dailyFormat},
+		Responses: withErrors(map[string]openapi.Response{"200": daily}, statsErrors...),
+		Security:  apiKeySecurity,
+	})
+	doc.Add(http.MethodPost, "/api/v1/query", openapi.Operation{
+		Tags:    []string{"stats"},
+		Summary: "Ad-hoc aggregation",
+		Description: "Metrics grouped by dimensions and an optional time bucket over the API key's website. " +
+			"Metrics: " + strings.Join(query.Metrics(), ", ") + ". Dimensions: " + strings.Join(query.Dimensions(), ", ") +
+			", and " + query.PropertyPrefix + "<key> for a custom event property. " +
+			"Granularities: " + strings.Join(query.Granularities(), ", ") + ".",
+		OperationID: "query",
+		RequestBody: jsonBody(doc.SchemaOf(query.Request{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("One row per group, values in column order", doc.SchemaOf(query.Result{})),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusForbidden, http.StatusTooManyRequests),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodPost, "/api/grafana/metrics", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Grafana datasource metrics",
+		Description: "Part of the Grafana JSON datasource API rooted at /api/grafana. Lists the metrics a panel target can chart and the format, group-by and limit options of each.",
+		OperationID: "grafanaMetrics",
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Metrics", &openapi.Schema{Type: "array", Items: doc.SchemaOf(grafanaMetric{})}),
+		}, http.StatusUnauthorized, http.StatusForbidden, http.StatusTooManyRequests),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodPost, "/api/grafana/query", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Grafana datasource query",
+		Description: "Runs each target (a metric name) over the panel's time range as a time series, one series per top dimension value, or a table. Ad hoc filters support = and !=.",
+		OperationID: "grafanaQuery",
+		RequestBody: jsonBody(doc.SchemaOf(grafanaQueryRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Time series ({target, datapoints: [[value, unix ms]]}) and tables ({type: table, columns, rows})", &openapi.Schema{Type: "array", Items: &openapi.Schema{}}),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusForbidden, http.StatusTooManyRequests),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodPost, "/api/v1/graphql", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "GraphQL query",
+		Description: "Only mounted when graphql_enabled is set. Root fields: website, stats, timeseries, breakdown, pages.",
+		OperationID: "graphql",
+		RequestBody: jsonBody(doc.SchemaOf(graphQLRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Query result; field errors are reported in errors", doc.SchemaOf(graphQLResponse{})),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusForbidden, http.StatusTooManyRequests),
+		Security: apiKeySecurity,
+	})
+	doc.Add(http.MethodGet, "/api/public/stats/{website_id}", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Public stats",
+		Description: "No authentication; only for websites with public stats enabled.",
+		OperationID: "publicStats",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Stats", doc.SchemaOf(PublicStats{})),
+		}, http.StatusBadRequest, http.StatusNotFound),
+	})
+	publicToken := pathParam("token", "Public token")
+	publicToken.Schema = &openapi.Schema{Type: "string"}
+	counterProperties := make(map[string]*openapi.Schema, len(models.PublicTokenMetrics))
+	for _, metric := range models.PublicTokenMetrics {
+		counterProperties[metric] = &openapi.Schema{Type: "integer"}
+	}
+	doc.Add(http.MethodGet, "/api/public/counter/{token}", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Public counter",
+		Description: "No authentication; returns only the metrics the public token exposes. CORS-enabled and cacheable for 30 seconds.",
+		OperationID: "publicCounter",
+		Parameters:  []openapi.Parameter{publicToken},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Exposed metrics", &openapi.Schema{Type: "object", Properties: counterProperties}),
+		}, http.StatusNotFound),
+	})
+	doc.Add(http.MethodGet, "/api/stats/realtime/{website_id}", openapi.Operation{
+		Tags:        []string{"stats"},
+		Summary:     "Current visitors",
+		Description: "Visitors seen in the last 5 minutes.",
+		OperationID: "currentVisitors",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Visitor count", &openapi.Schema{
+				Type:       "object",
+				Properties: map[string]*openapi.Schema{"value": {Type: "integer"}},
+			}),
+		}, http.StatusBadRequest, http.StatusUnauthorized),
+		Security: managementSecurity,
+	})
+
+	// admin
+	websiteSchema := doc.SchemaOf(WebsiteDetailResponse{})
+	adminErrors := []int{http.StatusBadRequest, http.StatusUnauthorized, http.StatusForbidden, http.StatusNotFound, http.StatusTooManyRequests}
+	doc.Add(http.MethodGet, "/api/websites", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "List websites",
+		OperationID: "listWebsites",
+		Parameters:  paginationParams(ValidSortColumns["websites"], "name", "asc"),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("A page of websites", paginated(Website{})),
+		}, http.StatusUnauthorized, http.StatusTooManyRequests),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPost, "/api/websites", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Create a website",
+		Description: "The domain and its www/http(s) variants become the allowed domains.",
+		OperationID: "createWebsite",
+		RequestBody: jsonBody(doc.SchemaOf(CreateWebsiteRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"201": jsonResponse("Created", websiteSchema),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusTooManyRequests),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodGet, "/api/websites/list", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "List websites with allowed domains",
+		OperationID: "listWebsiteDetails",
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Websites", &openapi.Schema{Type: "array", Items: websiteSchema}),
+		}, http.StatusUnauthorized, http.StatusTooManyRequests),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodGet, "/api/websites/{website_id}", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Show a website",
+		OperationID: "showWebsite",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Website", websiteSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPut, "/api/websites/{website_id}", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Rename a website",
+		OperationID: "updateWebsite",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(doc.SchemaOf(UpdateWebsiteRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Updated", websiteSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPost, "/api/websites/{website_id}/domains", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Allow a domain",
+		OperationID: "addDomain",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(doc.SchemaOf(DomainRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Updated", websiteSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodDelete, "/api/websites/{website_id}/domains", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Remove an allowed domain",
+		OperationID: "removeDomain",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(doc.SchemaOf(DomainRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Updated", websiteSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPatch, "/api/websites/{website_id}/public-stats", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Enable or disable public stats",
+		OperationID: "setPublicStats",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(&openapi.Schema{
+			Type:       "object",
+			Properties: map[string]*openapi.Schema{"enabled": {Type: "boolean"}},
+			Required:   []string{"enabled"},
+		}),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Updated", websiteSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodGet, "/api/websites/{website_id}/public-tokens", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "List public tokens",
+		OperationID: "listPublicTokens",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Public tokens", &openapi.Schema{Type: "array", Items: doc.SchemaOf(models.PublicToken{})}),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPost, "/api/websites/{website_id}/public-tokens", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Create a public token",
+		Description: "The token exposes only the listed metrics at /api/public/counter/{token}.",
+		OperationID: "createPublicToken",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(&openapi.Schema{
+			Type: "object",
+			Properties: map[string]*openapi.Schema{
+				"name":    {Type: "string"},
+				"metrics": {Type: "array", Items: &openapi.Schema{Type: "string", Enum: models.PublicTokenMetrics}},
+			},
+			Required: []string{"metrics"},
+		}),
+		Responses: withErrors(map[string]openapi.Response{
+			"201": jsonResponse("Created", doc.SchemaOf(models.PublicToken{})),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodDelete, "/api/websites/{website_id}/public-tokens/{token}", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Revoke a public token",
+		OperationID: "deletePublicToken",
+		Parameters:  []openapi.Parameter{websiteParam, publicToken},
+		Responses: withErrors(map[string]openapi.Response{
+			"204": {Description: "Revoked"},
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	memberUser := pathParam("user_id", "Member user ID")
+	doc.Add(http.MethodGet, "/api/websites/{website_id}/members", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "List website members",
+		Description: "The website's owner comes first with primary set.",
+		OperationID: "listWebsiteMembers",
+		Parameters:  []openapi.Parameter{websiteParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Members", &openapi.Schema{Type: "array", Items: doc.SchemaOf(models.WebsiteMember{})}),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPut, "/api/websites/{website_id}/members", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Add a member or change their role",
+		OperationID: "setWebsiteMember",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(doc.SchemaOf(WebsiteMemberRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Member", doc.SchemaOf(models.WebsiteMember{})),
+		}, append(adminErrors, http.StatusConflict)...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodDelete, "/api/websites/{website_id}/members/{user_id}", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Remove a website member",
+		OperationID: "removeWebsiteMember",
+		Parameters:  []openapi.Parameter{websiteParam, memberUser},
+		Responses: withErrors(map[string]openapi.Response{
+			"204": {Description: "Removed"},
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPut, "/api/websites/{website_id}/team", openapi.Operation{
+		Tags:        []string{"admin"},
+		Summary:     "Move a website into a team",
+		Description: "Needs the owner role on the website and at least editor in the team. A null team_id takes the website out of its team.",
+		OperationID: "setWebsiteTeam",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(doc.SchemaOf(WebsiteTeamRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Moved", object),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+
+	teamParam := pathParam("team_id", "Team ID")
+	teamSchema := doc.SchemaOf(models.Team{})
+	teamMemberSchema := doc.SchemaOf(models.TeamMember{})
+	doc.Add(http.MethodGet, "/api/teams", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "List your teams",
+		OperationID: "listTeams",
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Teams", &openapi.Schema{Type: "array", Items: teamSchema}),
+		}, http.StatusUnauthorized, http.StatusTooManyRequests),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPost, "/api/teams", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "Create a team",
+		Description: "The creator becomes the team's first owner.",
+		OperationID: "createTeam",
+		RequestBody: jsonBody(doc.SchemaOf(TeamRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"201": jsonResponse("Created", teamSchema),
+		}, http.StatusBadRequest, http.StatusUnauthorized, http.StatusConflict, http.StatusTooManyRequests),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodDelete, "/api/teams/{team_id}", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "Delete a team",
+		Description: "Needs the owner role in the team. Its websites stay with their owners.",
+		OperationID: "deleteTeam",
+		Parameters:  []openapi.Parameter{teamParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"204": {Description: "Deleted"},
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodGet, "/api/teams/{team_id}/members", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "List team members",
+		OperationID: "listTeamMembers",
+		Parameters:  []openapi.Parameter{teamParam},
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Members", &openapi.Schema{Type: "array", Items: teamMemberSchema}),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPut, "/api/teams/{team_id}/members", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "Add a team member or change their role",
+		Description: "Needs the owner role in the team.",
+		OperationID: "setTeamMember",
+		Parameters:  []openapi.Parameter{teamParam},
+		RequestBody: jsonBody(doc.SchemaOf(WebsiteMemberRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"200": jsonResponse("Member", teamMemberSchema),
+		}, append(adminErrors, http.StatusConflict)...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodDelete, "/api/teams/{team_id}/members/{user_id}", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "Remove a team member",
+		Description: "Needs the owner role in the team. The last owner can't be removed.",
+		OperationID: "removeTeamMember",
+		Parameters:  []openapi.Parameter{teamParam, memberUser},
+		Responses: withErrors(map[string]openapi.Response{
+			"204": {Description: "Removed"},
+		}, append(adminErrors, http.StatusConflict)...),
+		Security: managementSecurity,
+	})
+
+	invitationSchema := doc.SchemaOf(InvitationResponse{})
+	doc.Add(http.MethodPost, "/api/websites/{website_id}/invitations", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "Invite someone to a website",
+		Description: "Needs the owner role on the website. The one-time link is emailed when SMTP is configured and returned either way; it expires after 7 days.",
+		OperationID: "createWebsiteInvitation",
+		Parameters:  []openapi.Parameter{websiteParam},
+		RequestBody: jsonBody(doc.SchemaOf(InvitationRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"201": jsonResponse("Invitation", invitationSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+	doc.Add(http.MethodPost, "/api/teams/{team_id}/invitations", openapi.Operation{
+		Tags:        []string{"teams"},
+		Summary:     "Invite someone to a team",
+		Description: "Needs the owner role in the team. The one-time link is emailed when SMTP is configured and returned either way; it expires after 7 days.",
+		OperationID: "createTeamInvitation",
+		Parameters:  []openapi.Parameter{teamParam},
+		RequestBody: jsonBody(doc.SchemaOf(InvitationRequest{})),
+		Responses: withErrors(map[string]openapi.Response{
+			"201": jsonResponse("Invitation", invitationSchema),
+		}, adminErrors...),
+		Security: managementSecurity,
+	})
+
+	return doc
+}
+
+// HandleOpenAPI serves the API document as JSON
+// GET /api/openapi.json
+func HandleOpenAPI(version string) http.HandlerFunc {
+	spec, err := json.Marshal(APIDocument(version))
+	return func(w http.ResponseWriter, r *http.Request) {
+		if err != nil {
+			respondError(w, r, http.StatusInternalServerError, "Failed to build API document")
+			return
+		}
+		w.Header().Set("Content-Type", "application/json")
+		w.Header().Set("Access-Control-Allow-Origin", "*")
+		_, _ = w.Write(spec)
+	}
+}
+
+// apiBreakdownDimensions lists the dimensions HandleAPIBreakdown accepts
+func apiBreakdownDimensions() []string {
+	dims := make([]string, 0, len(breakdownDimensions))
+	for tab, column := range breakdownDimensions {
+		if column != "pages" {
+			dims = append(dims, tab)
+		}
+	}
+	slices.Sort(dims)
+	return dims
+}
+
+func jsonBody(schema *openapi.Schema) *openapi.RequestBody {
+	return &openapi.RequestBody{
+		Required: true,
+		Content:  map[string]openapi.MediaType{"application/json": {Schema: schema}},
+	}
+}
+
+func jsonResponse(description string, schema *openapi.Schema) openapi.Response {
+	return openapi.Response{
+		Description: description,
+		Content:     map[string]openapi.MediaType{"application/json": {Schema: schema}},
+	}
+}
+
+func pathParam(name, description string) openapi.Parameter {
+	return openapi.Parameter{
+		Name:        name,
+		In:          "path",
+		Description: description,
+		Required:    true,
+		Schema:      &openapi.Schema{Type: "string", Format: "uuid"},
+	}
+}
+
+func queryParam(name, typ, description string) openapi.Parameter {
+	return openapi.Parameter{Name: name, In: "query", Description: description, Schema: &openapi.Schema{Type: typ}}
+}
@@ -0,0 +1,81 @@
+package handlers
+
+import (
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/openapi"
+)
+
+func TestAPIDocument(t *testing.T) {
+	doc := APIDocument("1.2.3")
+	assert.Equal(t, "1.2.3", doc.Info.Version)
+
+	for path, method := range map[string]string{
+		"/api/send":                      "post",
+		"/api/ingest/batch":              "post",
+		"/api/tracker":                   "get",
+		"/api/auth/login":                "post",
+		"/api/v1/stats/{website_id}":     "get",
+		"/api/websites/{website_id}":     "put",
+		"/api/public/stats/{website_id}": "get",
+	} {
+		require.Contains(t, doc.Paths, path)
+		assert.Contains(t, doc.Paths[path], method, path)
+	}
+
+	tags := make(map[string]bool)
+	for _, tag := range doc.Tags {
+		tags[tag.Name] = true
+	}
+	operationIDs := make(map[string]bool)
+	for path, item := range doc.Paths {
+		for method, op := range item {
+			assert.False(t, operationIDs[op.OperationID], "duplicate operationId %s", op.OperationID)
+			operationIDs[op.OperationID] = true
+			for _, tag := range op.Tags {
+				assert.True(t, tags[tag], "%s %s has unknown tag %s", method, path, tag)
+			}
+			for _, p := range op.Parameters {
+				if p.In == "path" {
+					assert.Contains(t, path, "{"+p.Name+"}")
+				}
+			}
+		}
+	}
+
+	// Every $ref points at a registered schema
+	spec, err := json.Marshal(doc)
+	require.NoError(t, err)
+	for _, part := range strings.Split(string(spec), `"$ref":"#/components/schemas/`)[1:] {
+		name := part[:strings.Index(part, `"`)]
+		assert.Contains(t, doc.Components.Schemas, name)
+	}
+}
+
+func TestAPIDocumentBreakdownDimensions(t *testing.T) {
+	dims := apiBreakdownDimensions()
+	assert.Contains(t, dims, "countries")
+	assert.Contains(t, dims, "utm_source")
+	assert.NotContains(t, dims, "pages")
+}
+
+func TestHandleOpenAPI(t *testing.T) {
+	req := httptest.NewRequest(http.MethodGet, "/api/openapi.json", nil)
+	resp := httptest.NewRecorder()
+	HandleOpenAPI("dev")(resp, req)
+
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Equal(t, "application/json", resp.Header().Get("Content-Type"))
+
+	var doc openapi.Document
+	require.NoError(t, json.Unmarshal(resp.Body.Bytes(), &doc))
+	assert.Equal(t, openapi.Version, doc.OpenAPI)
+	assert.NotEmpty(t, doc.Paths)
+}
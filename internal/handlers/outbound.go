@@ -0,0 +1,162 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+// Custom events the tracker sends for link clicks
+const (
+	OutboundEventName = "Outbound Link: Click" // links to other sites
+	DownloadEventName = "File Download"        // with data-track-downloads
+	MailtoEventName   = "Mailto Link: Click"   // with data-track-mailto
+)
+
+const (
+	// outboundTopLimit is the number of destinations shown in the report
+	outboundTopLimit = 25
+	// outboundSourceLimit is the number of source pages listed per destination
+	outboundSourceLimit = 5
+)
+
+// OutboundSource is a page on the site where an outbound link was clicked
+type OutboundSource struct {
+	Path string `json:"path"`
+	Hits int64  `json:"hits"`
+}
+
+// OutboundLink is an external destination with the pages its clicks came from
+type OutboundLink struct {
+	URL      string           `json:"url"`
+	Hits     int64            `json:"hits"`
+	Visitors int64            `json:"visitors"`
+	Sources  []OutboundSource `json:"sources"`
+}
+
+// HandleOutbound renders the outbound links report: top external destinations
+// and the pages they were clicked from
+// GET /api/dashboard/outbound?website=...&days=7
+func HandleOutbound(w http.ResponseWriter, r *http.Request) {
+	query := r.URL.Query()
+	websiteIDStr := query.Get("website_id")
+	if websiteIDStr == "" {
+		websiteIDStr = query.Get("website")
+	}
+	days := min(max(queryInt(r, "days", 7), 1), 90)
+
+	websiteID, parseErr := parseRequiredWebsiteID(websiteIDStr)
+
+	var links []OutboundLink
+	var queryErr error
+	if parseErr == "" {
+		links, queryErr = loadOutboundLinks(r.Context(), websiteID, days, outboundTopLimit)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			patchBreakdownErrorState(sse, parseErr)
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleOutbound: %v", queryErr)
+			patchBreakdownErrorState(sse, "Failed to load outbound links")
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#breakdown-content-body", buildOutboundTableHTML(links), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"breakdownLoading": false,
+			"breakdownError":   false,
+		})
+	})
+}
+
+// loadOutboundLinks returns the most-clicked external destinations, each with
+// its top source pages
+func loadOutboundLinks(ctx context.Context, websiteID uuid.UUID, days, limit int) ([]OutboundLink, error) {
+	rows, err := database.QueryStats(ctx, `
+		WITH clicks AS (
+			SELECT
+				props->>'url' AS url,
+				COALESCE(url_path, '') AS source,
+				session_id
+			FROM website_event
+			WHERE website_id = $1
+			  AND event_type = 2
+			  AND event_name = $2
+			  AND props->>'url' IS NOT NULL
+			  AND created_at >= NOW() - make_interval(days => $3)
+		),
+		top_links AS (
+			SELECT url, COUNT(*) AS hits, COUNT(DISTINCT session_id) AS visitors
+			FROM clicks
+			GROUP BY url
+			ORDER BY hits DESC, url
+			LIMIT $4
+		)
+		SELECT l.url, l.hits, l.visitors, s.source, s.hits
+		FROM top_links l
+		CROSS JOIN LATERAL (
+			SELECT source, COUNT(*) AS hits
+			FROM clicks c
+			WHERE c.url = l.url
+			GROUP BY source
+			ORDER BY hits DESC, source
+			LIMIT $5
+		) s
+		ORDER BY l.hits DESC, l.url, s.hits DESC, s.source
+	`, websiteID, OutboundEventName, days, limit, outboundSourceLimit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	links := make([]OutboundLink, 0)
+	for rows.Next() {
+		var url, source string
+		var hits, visitors, sourceHits int64
+		if err := rows.Scan(&url, &hits, &visitors, &source, &sourceHits); err != nil {
+			return nil, err
+		}
+		if len(links) == 0 || links[len(links)-1].URL != url {
+			links = append(links, OutboundLink{URL: url, Hits: hits, Visitors: visitors})
+		}
+		last := &links[len(links)-1]
+		last.Sources = append(last.Sources, OutboundSource{Path: source, Hits: sourceHits})
+	}
+	return links, rows.Err()
+}
+
+func buildOutboundTableHTML(links []OutboundLink) string {
+	if len(links) == 0 {
+		return `<div class="empty-state"><div class="empty-state-icon">[&gt;]</div><div class="empty-state-title">No outbound clicks yet</div><div class="empty-state-text">Clicks on links to other sites are tracked automatically unless <code>data-track-outbound="false"</code> is set.</div></div>`
+	}
+
+	var rows strings.Builder
+	for _, link := range links {
+		sources := make([]string, 0, len(link.Sources))
+		for _, src := range link.Sources {
+			path := src.Path
+			if path == "" {
+				path = "/"
+			}
+			sources = append(sources, fmt.Sprintf(`<li>%s <span class="breakdown-sublist-meta">%s</span></li>`, escapeHTML(path), formatNumber(int(src.Hits))))
+		}
+		fmt.Fprintf(&rows, `<tr><td style="word-break:break-all">%s</td><td><ul class="breakdown-sublist">%s</ul></td><td style="text-align:right">%s</td><td style="text-align:right">%s</td></tr>`,
+			escapeHTML(link.URL),
+			strings.Join(sources, ""),
+			formatNumber(int(link.Visitors)),
+			formatNumber(int(link.Hits)),
+		)
+	}
+
+	return fmt.Sprintf(`<table class="breakdown-table outbound-table"><thead><tr><th>Destination</th><th>Source pages</th><th style="text-align:right">Visitors</th><th style="text-align:right">Clicks</th></tr></thead><tbody>%s</tbody></table>`,
+		rows.String())
+}
@@ -0,0 +1,58 @@
+package handlers
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestLoadOutboundLinksGroupsSourcesByDestination(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("props->>'url'").
+		WithArgs(websiteID, OutboundEventName, 30, outboundTopLimit, outboundSourceLimit).
+		WillReturnRows(sqlmock.NewRows([]string{"url", "hits", "visitors", "source", "source_hits"}).
+			AddRow("https://github.com/seuros/kaunta", 40, 31, "/", 25).
+			AddRow("https://github.com/seuros/kaunta", 40, 31, "/docs", 15).
+			AddRow("https://example.org/", 2, 2, "/blog", 2))
+
+	links, err := loadOutboundLinks(context.Background(), websiteID, 30, outboundTopLimit)
+	require.NoError(t, err)
+	require.Len(t, links, 2)
+
+	assert.Equal(t, "https://github.com/seuros/kaunta", links[0].URL)
+	assert.Equal(t, int64(40), links[0].Hits)
+	assert.Equal(t, int64(31), links[0].Visitors)
+	assert.Equal(t, []OutboundSource{{Path: "/", Hits: 25}, {Path: "/docs", Hits: 15}}, links[0].Sources)
+	assert.Equal(t, []OutboundSource{{Path: "/blog", Hits: 2}}, links[1].Sources)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestBuildOutboundTableHTML(t *testing.T) {
+	html := buildOutboundTableHTML([]OutboundLink{{
+		URL:      "https://example.org/?a=1&b=<2>",
+		Hits:     3,
+		Visitors: 2,
+		Sources:  []OutboundSource{{Path: "", Hits: 3}},
+	}})
+
+	assert.Contains(t, html, "https://example.org/?a=1&amp;b=&lt;2&gt;")
+	assert.Contains(t, html, "<li>/ ")
+	assert.Contains(t, html, "Source pages")
+
+	assert.Contains(t, buildOutboundTableHTML(nil), "No outbound clicks yet")
+}
@@ -0,0 +1,175 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+// overviewSparklineDays is the number of daily buckets shown per website card
+const overviewSparklineDays = 7
+
+// WebsiteOverview is the at-a-glance activity for one website on the overview page
+type WebsiteOverview struct {
+	WebsiteInfo
+	TodayVisitors    int     `json:"today_visitors"`
+	RealtimeVisitors int     `json:"realtime_visitors"`
+	Sparkline        []int64 `json:"sparkline"`
+}
+
+// HandleOverview renders a card for every website the user can view with a
+// 7-day pageview sparkline, today's visitors and the realtime count
+// GET /api/dashboard/overview
+func HandleOverview(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"overviewError":   "Not authenticated",
+				"overviewLoading": false,
+			})
+		})
+		return
+	}
+
+	sites, queryErr := loadWebsiteOverviews(r.Context(), user.UserID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleOverview: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"overviewError":   "Failed to load websites",
+				"overviewLoading": false,
+			})
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#overview-grid", buildOverviewGridHTML(sites), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"overviewCount":   len(sites),
+			"overviewLoading": false,
+			"overviewError":   false,
+		})
+	})
+}
+
+func loadWebsiteOverviews(ctx context.Context, userID uuid.UUID) ([]WebsiteOverview, error) {
+	query := `
+		SELECT
+			w.website_id,
+			COALESCE(w.name, ''),
+			w.domain,
+			(
+				SELECT COUNT(DISTINCT e.session_id)
+				FROM website_event e
+				WHERE e.website_id = w.website_id
+				  AND e.event_type = 1
+				  AND e.created_at >= CURRENT_DATE
+			) AS today_visitors,
+			(
+				SELECT COUNT(DISTINCT e.session_id)
+				FROM website_event e
+				WHERE e.website_id = w.website_id
+				  AND e.event_type = 1
+				  AND e.created_at >= NOW() - INTERVAL '5 minutes'
+			) AS realtime_visitors,
+			ARRAY(
+				SELECT COUNT(e.event_id)
+				FROM generate_series(CURRENT_DATE - ($2::INT - 1), CURRENT_DATE, INTERVAL '1 day') AS d(day)
+				LEFT JOIN website_event e
+				  ON e.website_id = w.website_id
+				 AND e.event_type = 1
+				 AND e.created_at >= d.day
+				 AND e.created_at < d.day + INTERVAL '1 day'
+				GROUP BY d.day
+				ORDER BY d.day
+			) AS sparkline
+		FROM website w
+		WHERE w.website_id IN (SELECT website_id FROM user_websites($1))
+		ORDER BY w.domain
+	`
+	rows, err := database.QueryStats(ctx, query, userID, overviewSparklineDays)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var sites []WebsiteOverview
+	for rows.Next() {
+		var site WebsiteOverview
+		if err := rows.Scan(
+			&site.ID, &site.Name, &site.Domain,
+			&site.TodayVisitors, &site.RealtimeVisitors,
+			pq.Array(&site.Sparkline),
+		); err != nil {
+			continue
+		}
+		sites = append(sites, site)
+	}
+	return sites, rows.Err()
+}
+
+func buildOverviewGridHTML(sites []WebsiteOverview) string {
+	if len(sites) == 0 {
+		return `<div class="empty-state"><div class="empty-state-icon">🌐</div><div class="empty-state-title">No websites found</div><div class="empty-state-text"><a href="/dashboard/websites">Add a website</a> to get started.</div></div>`
+	}
+
+	var b strings.Builder
+	for _, site := range sites {
+		liveClass, pulse := "", ""
+		if site.RealtimeVisitors > 0 {
+			liveClass, pulse = " live", `<span class="pulse"></span>`
+		}
+		openHandler := fmt.Sprintf(`localStorage.setItem('kaunta_website', '%s'); window.location.href = '/dashboard'`, jsStringEscape(site.ID))
+		fmt.Fprintf(&b, `<div class="overview-card stat-card glass card" role="link" tabindex="0" style="cursor:pointer" data-on:click="%s"><div class="stat-header"><div><div class="stat-label">%s</div><div class="overview-domain">%s</div></div></div><div class="overview-metrics"><div><div class="stat-value">%s</div><div class="stat-label">Visitors today</div></div><div><div class="stat-value%s">%s%s</div><div class="stat-label">Right now</div></div></div><div class="overview-sparkline">%s</div></div>`,
+			escapeHTML(openHandler),
+			escapeHTML(websiteLabel(site.WebsiteInfo)),
+			escapeHTML(site.Domain),
+			formatNumber(site.TodayVisitors),
+			liveClass,
+			pulse,
+			formatNumber(site.RealtimeVisitors),
+			buildSparklineSVG(site.Sparkline, 240, 40),
+		)
+	}
+	return b.String()
+}
+
+// buildSparklineSVG renders values as an inline SVG polyline scaled to width x height
+func buildSparklineSVG(values []int64, width, height int) string {
+	if len(values) == 0 {
+		return ""
+	}
+
+	var peak int64
+	for _, v := range values {
+		peak = max(peak, v)
+	}
+
+	const pad = 2.0
+	w, h := float64(width), float64(height)
+	step := 0.0
+	if len(values) > 1 {
+		step = (w - 2*pad) / float64(len(values)-1)
+	}
+
+	points := make([]string, len(values))
+	for i, v := range values {
+		y := h - pad
+		if peak > 0 {
+			y = h - pad - float64(v)/float64(peak)*(h-2*pad)
+		}
+		points[i] = fmt.Sprintf("%.1f,%.1f", pad+float64(i)*step, y)
+	}
+
+	return fmt.Sprintf(`<svg class="sparkline" width="%d" height="%d" viewBox="0 0 %d %d" preserveAspectRatio="none" aria-hidden="true"><polyline fill="none" stroke="var(--accent-color)" stroke-width="1.5" stroke-linejoin="round" stroke-linecap="round" points="%s"/></svg>`,
+		width, height, width, height, strings.Join(points, " "))
+}
@@ -0,0 +1,31 @@
+package handlers
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestBuildSparklineSVG(t *testing.T) {
+	assert.Empty(t, buildSparklineSVG(nil, 100, 20))
+
+	svg := buildSparklineSVG([]int64{0, 5, 10}, 104, 24)
+	assert.Contains(t, svg, `points="2.0,22.0 52.0,12.0 102.0,2.0"`)
+
+	flat := buildSparklineSVG([]int64{0, 0}, 104, 24)
+	assert.Contains(t, flat, `points="2.0,22.0 102.0,22.0"`)
+}
+
+func TestBuildOverviewGridHTML(t *testing.T) {
+	assert.Contains(t, buildOverviewGridHTML(nil), "No websites found")
+
+	html := buildOverviewGridHTML([]WebsiteOverview{
+		{WebsiteInfo: WebsiteInfo{ID: "a", Name: "Blog", Domain: "blog.example"}, TodayVisitors: 1500, RealtimeVisitors: 3, Sparkline: []int64{1, 2}},
+		{WebsiteInfo: WebsiteInfo{ID: "b", Domain: "quiet.example"}},
+	})
+	assert.Equal(t, 2, strings.Count(html, `class="overview-card`))
+	assert.Contains(t, html, "1,500")
+	assert.Equal(t, 1, strings.Count(html, `class="pulse"`))
+	assert.Contains(t, html, "localStorage.setItem(&#39;kaunta_website&#39;, &#39;a&#39;)")
+}
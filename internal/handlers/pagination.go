@@ -1,6 +1,10 @@
 package handlers
 
 import (
+	"encoding/base64"
+	"encoding/json"
+	"errors"
+	"fmt"
 	"net/http"
 	"slices"
 	"strconv"
@@ -22,15 +26,27 @@ type PaginationParams struct {
 	Offset    int           `json:"-"`          // Calculated offset for SQL (not exposed in JSON)
 	SortBy    string        `json:"sort_by"`    // Column to sort by (default: "count")
 	SortOrder SortDirection `json:"sort_order"` // Sort direction: "asc" or "desc" (default: "desc")
+	Cursor    *Cursor       `json:"-"`          // Position after which the page starts (takes precedence over page)
+}
+
+// Cursor marks the last row of a page for keyset pagination. Clients receive
+// it as an opaque next_cursor token; it only applies to the sort it was
+// issued for.
+type Cursor struct {
+	SortBy    string        `json:"s"`
+	SortOrder SortDirection `json:"o"`
+	Value     string        `json:"v"` // Sort column value of the last row
+	Key       string        `json:"k"` // Unique tie-breaker of the last row
 }
 
 // PaginationMeta contains pagination metadata
 type PaginationMeta struct {
-	Page       int   `json:"page"`
-	Per        int   `json:"per"`
-	Total      int64 `json:"total"`       // Total items across all pages
-	TotalPages int   `json:"total_pages"` // Calculated total pages
-	HasMore    bool  `json:"has_more"`    // Whether more pages exist
+	Page       int    `json:"page,omitempty"` // Omitted when paging by cursor
+	Per        int    `json:"per"`
+	Total      int64  `json:"total"`                 // Total items across all pages
+	TotalPages int    `json:"total_pages"`           // Calculated total pages
+	HasMore    bool   `json:"has_more"`              // Whether more pages exist
+	NextCursor string `json:"next_cursor,omitempty"` // Pass as ?cursor= to fetch the next page
 }
 
 // PaginatedResponse wraps any list response with pagination metadata
@@ -44,6 +60,28 @@ var ValidSortColumns = map[string][]string{
 	"breakdown": {"count", "name"},
 	"pages":     {"views", "path", "unique_visitors", "avg_engagement_time"},
 	"map":       {"visitors", "country", "percentage"},
+	"websites":  {"name", "domain", "created_at"},
+}
+
+var errInvalidCursor = errors.New("invalid cursor")
+
+// EncodeCursor serializes a cursor into a URL-safe token
+func EncodeCursor(c Cursor) string {
+	data, _ := json.Marshal(c)
+	return base64.RawURLEncoding.EncodeToString(data)
+}
+
+// DecodeCursor parses a token produced by EncodeCursor
+func DecodeCursor(token string) (*Cursor, error) {
+	data, err := base64.RawURLEncoding.DecodeString(token)
+	if err != nil {
+		return nil, errInvalidCursor
+	}
+	var c Cursor
+	if err := json.Unmarshal(data, &c); err != nil || c.SortBy == "" {
+		return nil, errInvalidCursor
+	}
+	return &c, nil
 }
 
 // queryInt fetches an integer query parameter with a default value.
@@ -83,13 +121,27 @@ func ParsePaginationParams(r *http.Request) PaginationParams {
 		sortOrder = SortDesc
 	}
 
-	return PaginationParams{
+	params := PaginationParams{
 		Page:      page,
 		Per:       per,
 		Offset:    offset,
 		SortBy:    sortBy,
 		SortOrder: sortOrder,
 	}
+	if token := r.URL.Query().Get("cursor"); token != "" {
+		// An unreadable cursor restarts from the first page
+		params.Cursor, _ = DecodeCursor(token)
+	}
+	return params
+}
+
+// activeCursor returns the cursor if it was issued for the current sort.
+// Changing sort_by or sort_order invalidates it and paging restarts.
+func (p PaginationParams) activeCursor() *Cursor {
+	if p.Cursor == nil || p.Cursor.SortBy != p.SortBy || p.Cursor.SortOrder != p.SortOrder {
+		return nil
+	}
+	return p.Cursor
 }
 
 // ParsePaginationParamsWithValidation extracts pagination with column validation
@@ -130,3 +182,65 @@ func NewPaginatedResponse(data any, params PaginationParams, total int64) Pagina
 		Pagination: BuildPaginationMeta(params, total),
 	}
 }
+
+// NewKeysetPaginatedResponse wraps a page read through keysetQuery. HasMore
+// follows the extra row fetched by the query rather than the total.
+func NewKeysetPaginatedResponse(data any, params PaginationParams, total int64, nextCursor string) PaginatedResponse {
+	meta := BuildPaginationMeta(params, total)
+	meta.HasMore = nextCursor != ""
+	meta.NextCursor = nextCursor
+	if params.activeCursor() != nil {
+		meta.Page = 0
+	}
+	return PaginatedResponse{Data: data, Pagination: meta}
+}
+
+// keysetColumn is a sortable column of a keyset-paginated query: an
+// expression over the wrapped row t and the type cursor values cast back to
+type keysetColumn struct {
+	expr string
+	cast string
+}
+
+// keysetQuery wraps a list query so it can be paged by cursor as well as by
+// offset. Rows are ordered by sort with key as tie-breaker, and each row gets
+// two extra trailing columns, cursor_value and cursor_key. One row more than
+// a page is fetched so keysetPage can tell whether another page follows.
+func keysetQuery(inner string, args []any, params PaginationParams, sort keysetColumn, key string) (string, []any) {
+	dir, cmp := "DESC", "<"
+	if params.SortOrder == SortAsc {
+		dir, cmp = "ASC", ">"
+	}
+
+	where := ""
+	offset := params.Offset
+	if c := params.activeCursor(); c != nil {
+		args = append(args, c.Value, c.Key)
+		where = fmt.Sprintf("WHERE (%s, %s) %s ($%d::%s, $%d)", sort.expr, key, cmp, len(args)-1, sort.cast, len(args))
+		offset = 0
+	}
+	args = append(args, params.Per+1, offset)
+
+	query := fmt.Sprintf(`SELECT t.*, (%s)::text AS cursor_value, (%s)::text AS cursor_key
+		FROM (%s) t
+		%s
+		ORDER BY %s %s, %s %s
+		LIMIT $%d OFFSET $%d`,
+		sort.expr, key, inner, where, sort.expr, dir, key, dir, len(args)-1, len(args))
+	return query, args
+}
+
+// keysetPage trims the extra row fetched by keysetQuery. cursors holds each
+// row's cursor_value and cursor_key; the returned token is empty on the last page.
+func keysetPage[T any](items []T, cursors [][2]string, params PaginationParams) ([]T, string) {
+	if len(items) <= params.Per {
+		return items, ""
+	}
+	last := cursors[params.Per-1]
+	return items[:params.Per], EncodeCursor(Cursor{
+		SortBy:    params.SortBy,
+		SortOrder: params.SortOrder,
+		Value:     last[0],
+		Key:       last[1],
+	})
+}
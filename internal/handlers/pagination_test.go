@@ -3,6 +3,7 @@ package handlers
 import (
 	"net/http/httptest"
 	"net/url"
+	"strings"
 	"testing"
 )
 
@@ -193,3 +194,138 @@ func TestNewPaginatedResponse(t *testing.T) {
 		t.Error("Pagination.HasMore should be true")
 	}
 }
+
+func TestCursorRoundTrip(t *testing.T) {
+	cursor := Cursor{SortBy: "views", SortOrder: SortDesc, Value: "42", Key: "/blog/a?b=c"}
+
+	decoded, err := DecodeCursor(EncodeCursor(cursor))
+	if err != nil {
+		t.Fatalf("DecodeCursor() error = %v", err)
+	}
+	if *decoded != cursor {
+		t.Errorf("DecodeCursor() = %+v, want %+v", *decoded, cursor)
+	}
+
+	for _, token := range []string{"not base64!", "bm90IGpzb24", "e30"} {
+		if _, err := DecodeCursor(token); err == nil {
+			t.Errorf("DecodeCursor(%q) should fail", token)
+		}
+	}
+}
+
+func TestParsePaginationParamsCursor(t *testing.T) {
+	token := EncodeCursor(Cursor{SortBy: "views", SortOrder: SortDesc, Value: "42", Key: "/pricing"})
+
+	req := httptest.NewRequest("GET", "/test?sort_by=views&cursor="+token, nil)
+	if params := ParsePaginationParamsWithValidation(req, "pages"); params.activeCursor() == nil {
+		t.Error("cursor issued for the same sort should be active")
+	}
+
+	req = httptest.NewRequest("GET", "/test?sort_by=path&cursor="+token, nil)
+	if params := ParsePaginationParamsWithValidation(req, "pages"); params.activeCursor() != nil {
+		t.Error("cursor issued for another sort should be ignored")
+	}
+
+	req = httptest.NewRequest("GET", "/test?cursor=garbage", nil)
+	if params := ParsePaginationParams(req); params.Cursor != nil {
+		t.Error("invalid cursor should be ignored")
+	}
+}
+
+func TestKeysetQuery(t *testing.T) {
+	sort := keysetColumn{"t.views", "bigint"}
+
+	query, args := keysetQuery("SELECT * FROM items WHERE site = $1", []any{"site"},
+		PaginationParams{Page: 3, Per: 10, Offset: 20, SortBy: "views", SortOrder: SortDesc}, sort, "t.path")
+	for _, want := range []string{"ORDER BY t.views DESC, t.path DESC", "LIMIT $2 OFFSET $3"} {
+		if !strings.Contains(query, want) {
+			t.Errorf("offset query = %q, want it to contain %q", query, want)
+		}
+	}
+	if strings.Contains(query, "WHERE (t.views") {
+		t.Error("offset query should not filter by cursor")
+	}
+	if len(args) != 3 || args[1] != 11 || args[2] != 20 {
+		t.Errorf("offset args = %v, want [site 11 20]", args)
+	}
+
+	params := PaginationParams{Page: 3, Per: 10, Offset: 20, SortBy: "views", SortOrder: SortAsc,
+		Cursor: &Cursor{SortBy: "views", SortOrder: SortAsc, Value: "42", Key: "/pricing"}}
+	query, args = keysetQuery("SELECT * FROM items WHERE site = $1", []any{"site"}, params, sort, "t.path")
+	if want := "WHERE (t.views, t.path) > ($2::bigint, $3)"; !strings.Contains(query, want) {
+		t.Errorf("cursor query = %q, want it to contain %q", query, want)
+	}
+	if len(args) != 5 || args[1] != "42" || args[2] != "/pricing" || args[4] != 0 {
+		t.Errorf("cursor args = %v, want [site 42 /pricing 11 0]", args)
+	}
+}
+
+func TestKeysetPage(t *testing.T) {
+	params := PaginationParams{Per: 2, SortBy: "views", SortOrder: SortDesc}
+	cursors := [][2]string{{"9", "/a"}, {"5", "/b"}, {"1", "/c"}}
+
+	items, next := keysetPage([]string{"/a", "/b", "/c"}, cursors, params)
+	if len(items) != 2 {
+		t.Errorf("len(items) = %d, want 2", len(items))
+	}
+	if next != EncodeCursor(Cursor{SortBy: "views", SortOrder: SortDesc, Value: "5", Key: "/b"}) {
+		t.Errorf("next cursor = %q, want one pointing at /b", next)
+	}
+
+	items, next = keysetPage([]string{"/a", "/b"}, cursors[:2], params)
+	if len(items) != 2 || next != "" {
+		t.Errorf("last page = %v, %q; want 2 items and no cursor", items, next)
+	}
+
+	params.Page, params.Cursor = 1, &Cursor{SortBy: "views", SortOrder: SortDesc, Value: "9", Key: "/a"}
+	response := NewKeysetPaginatedResponse(items, params, 2, "")
+	if response.Pagination.HasMore || response.Pagination.Page != 0 {
+		t.Errorf("Pagination = %+v, want no page number and HasMore false", response.Pagination)
+	}
+}
+
+func TestPathSearchPattern(t *testing.T) {
+	tests := []struct {
+		search   string
+		expected any
+	}{
+		{"", nil},
+		{"   ", nil},
+		{"pricing", "%pricing%"},
+		{"/blog/*", "/blog/%"},
+		{"/docs/v?/intro", "/docs/v_/intro"},
+		{"*/amp", "%/amp"},
+		{"100%_off", `%100\%\_off%`},
+		{`/a\b*`, `/a\\b%`},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.search, func(t *testing.T) {
+			if got := pathSearchPattern(tt.search); got != tt.expected {
+				t.Errorf("pathSearchPattern(%q) = %v, want %v", tt.search, got, tt.expected)
+			}
+		})
+	}
+}
+
+func TestTimeseriesGranularity(t *testing.T) {
+	tests := []struct {
+		requested string
+		days      int
+		expected  string
+	}{
+		{"minute", 30, "minute"},
+		{"hour", 30, "hour"},
+		{"day", 1, "day"},
+		{"", 1, "hour"},
+		{"", 7, "hour"},
+		{"", 30, "day"},
+		{"week", 7, "hour"},
+	}
+
+	for _, tt := range tests {
+		if got := timeseriesGranularity(tt.requested, tt.days); got != tt.expected {
+			t.Errorf("timeseriesGranularity(%q, %d) = %q, want %q", tt.requested, tt.days, got, tt.expected)
+		}
+	}
+}
@@ -0,0 +1,119 @@
+package handlers
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/mailer"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// forgotPasswordSent is shown whether or not an account matched, so the
+// form can't be used to find out which usernames and addresses exist
+const forgotPasswordSent = "If an account with an email address matches, a reset link is on its way. It expires in one hour."
+
+// HandleForgotPassword emails a password reset link via Datastar SSE
+// POST /api/auth/forgot-password (form: login, a username or email address)
+func HandleForgotPassword(w http.ResponseWriter, r *http.Request) {
+	login := strings.TrimSpace(r.FormValue("login"))
+
+	message, forgotErr := forgotPasswordSent, ""
+	switch {
+	case login == "":
+		message, forgotErr = "", "Enter your username or email address"
+	case !accountMailer.Enabled():
+		message, forgotErr = "", "Email is not set up on this server. Ask an administrator for a reset link."
+	default:
+		if err := sendPasswordReset(r, login); err != nil {
+			log.Printf("HandleForgotPassword: %v", err)
+		}
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{"message": message, "error": forgotErr, "loading": false})
+	})
+}
+
+// sendPasswordReset emails a reset link when login names an account with
+// an email address, and does nothing otherwise
+func sendPasswordReset(r *http.Request, login string) error {
+	user, err := models.FindPasswordResetUser(r.Context(), database.DB, login)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil
+	}
+	if err != nil {
+		return err
+	}
+	if user.Email == nil {
+		return nil
+	}
+
+	token, expiresAt, err := models.CreatePasswordReset(r.Context(), database.DB, user.UserID)
+	if err != nil {
+		return err
+	}
+	return SendPasswordReset(accountMailer, *user.Email, user.Username, publicLink(r, "/reset-password/"+token), expiresAt)
+}
+
+// SendPasswordReset emails a password reset link
+func SendPasswordReset(m *mailer.Mailer, email, username, link string, expiresAt time.Time) error {
+	body := fmt.Sprintf(`Someone asked to reset the password of the Kaunta account %q.
+
+Open this link to choose a new password:
+
+%s
+
+The link works once and expires on %s. If you didn't ask for this, ignore
+this email; your password stays the same.
+`, username, link, expiresAt.UTC().Format("2 Jan 2006 15:04 MST"))
+	return m.Send(email, "Reset your Kaunta password", body)
+}
+
+// LookupPasswordReset returns the username a pending reset link is for, or
+// "" when the link is expired, used or unknown
+func LookupPasswordReset(r *http.Request, token string) string {
+	username, err := models.GetPasswordResetUsername(r.Context(), database.DB, token)
+	if err != nil && !errors.Is(err, models.ErrPasswordResetNotFound) {
+		log.Printf("LookupPasswordReset: %v", err)
+	}
+	return username
+}
+
+// HandleResetPassword sets a new password from a reset link via Datastar
+// SSE. All of the user's sessions are signed out.
+// POST /api/auth/reset-password/:token (form: password, confirm)
+func HandleResetPassword(w http.ResponseWriter, r *http.Request) {
+	password := r.FormValue("password")
+
+	resetErr := ""
+	switch {
+	case len(password) < 8:
+		resetErr = "Password must be at least 8 characters"
+	case password != r.FormValue("confirm"):
+		resetErr = "Passwords do not match"
+	default:
+		_, err := models.ResetPassword(r.Context(), database.DB, chi.URLParam(r, "token"), password)
+		if errors.Is(err, models.ErrPasswordResetNotFound) {
+			resetErr = "This reset link has expired or was already used"
+		} else if err != nil {
+			log.Printf("HandleResetPassword: %v", err)
+			resetErr = "Failed to reset password"
+		}
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if resetErr != "" {
+			_ = sse.PatchSignals(map[string]any{"error": resetErr, "loading": false})
+			return
+		}
+		_ = sse.PatchSignals(map[string]any{"error": "", "loading": false, "done": true, "password": "", "confirm": ""})
+	})
+}
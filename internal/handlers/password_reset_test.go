@@ -0,0 +1,39 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func formRequest(target string, form url.Values) *http.Request {
+	req := httptest.NewRequest(http.MethodPost, target, strings.NewReader(form.Encode()))
+	req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+	return req
+}
+
+func TestHandleForgotPasswordWithoutEmail(t *testing.T) {
+	stubVar(t, &accountMailer, nil)
+
+	resp := httptest.NewRecorder()
+	HandleForgotPassword(resp, formRequest("/api/auth/forgot-password", url.Values{"login": {"alice"}}))
+	assert.Contains(t, resp.Body.String(), "Email is not set up on this server")
+
+	resp = httptest.NewRecorder()
+	HandleForgotPassword(resp, formRequest("/api/auth/forgot-password", url.Values{}))
+	assert.Contains(t, resp.Body.String(), "Enter your username or email address")
+}
+
+func TestHandleResetPasswordValidation(t *testing.T) {
+	resp := httptest.NewRecorder()
+	HandleResetPassword(resp, formRequest("/api/auth/reset-password/abc", url.Values{"password": {"short"}, "confirm": {"short"}}))
+	assert.Contains(t, resp.Body.String(), "Password must be at least 8 characters")
+
+	resp = httptest.NewRecorder()
+	HandleResetPassword(resp, formRequest("/api/auth/reset-password/abc", url.Values{"password": {"longenough1"}, "confirm": {"longenough2"}}))
+	assert.Contains(t, resp.Body.String(), "Passwords do not match")
+}
@@ -0,0 +1,170 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// listPersonalTokensFunc loads a user's tokens (can be mocked in tests)
+var listPersonalTokensFunc = func(ctx context.Context, userID uuid.UUID) ([]models.PersonalToken, error) {
+	return models.ListPersonalTokens(ctx, database.DB, userID)
+}
+
+// HandlePersonalTokensInit renders the user's personal access tokens via
+// Datastar SSE
+// GET /api/auth/tokens
+func HandlePersonalTokensInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	streamPersonalTokenList(w, r, user, nil)
+}
+
+// HandlePersonalTokenCreate issues a personal access token. The secret is
+// shown once in the response.
+// POST /api/auth/tokens (form: name, scope, expires)
+func HandlePersonalTokenCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	fail := func(message string) {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"tokenError": message, "tokenSubmitting": false})
+		})
+	}
+
+	name := strings.TrimSpace(r.FormValue("name"))
+	if name == "" {
+		fail("Give the token a name")
+		return
+	}
+	scope, err := models.ParsePersonalTokenScope(r.FormValue("scope"))
+	if err != nil {
+		fail("Choose read or write access")
+		return
+	}
+	expiresAt, err := models.ParseAPIKeyExpiry(r.FormValue("expires"), time.Now())
+	if err != nil {
+		fail(err.Error())
+		return
+	}
+
+	_, secret, err := models.CreatePersonalToken(r.Context(), database.DB, user.UserID, name, scope, expiresAt)
+	if err != nil {
+		log.Printf("HandlePersonalTokenCreate: %v", err)
+		fail("Failed to create token")
+		return
+	}
+	streamPersonalTokenList(w, r, user, map[string]any{
+		"showTokenModal":  false,
+		"tokenSecret":     secret,
+		"tokenError":      false,
+		"tokenSubmitting": false,
+	})
+}
+
+// HandlePersonalTokenRevoke stops one of the user's tokens from working
+// DELETE /api/auth/tokens/:token_id
+func HandlePersonalTokenRevoke(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	tokenID, err := uuid.Parse(chi.URLParam(r, "token_id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid token ID")
+		return
+	}
+
+	err = models.RevokePersonalToken(r.Context(), database.DB, user.UserID, tokenID)
+	if errors.Is(err, sql.ErrNoRows) {
+		respondError(w, r, http.StatusNotFound, "Token not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandlePersonalTokenRevoke: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to revoke token")
+		return
+	}
+	streamPersonalTokenList(w, r, user, nil)
+}
+
+// streamPersonalTokenList re-renders the token list after a change
+func streamPersonalTokenList(w http.ResponseWriter, r *http.Request, user *middleware.UserContext, signals map[string]any) {
+	tokens, err := listPersonalTokensFunc(r.Context(), user.UserID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("streamPersonalTokenList: %v", err)
+		} else {
+			_ = sse.PatchElementsWithMode("#personal-token-list", buildPersonalTokenListHTML(tokens, time.Now()), "inner")
+		}
+		if signals != nil {
+			_ = sse.PatchSignals(signals)
+		}
+	})
+}
+
+func buildPersonalTokenListHTML(tokens []models.PersonalToken, now time.Time) string {
+	if len(tokens) == 0 {
+		return `<div class="empty-state-mini"><div>[~]</div><div>No personal access tokens yet</div></div>`
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="api-keys-table"><thead><tr><th>Token</th><th>Access</th><th>Last used</th><th>Expires</th><th>Status</th><th style="text-align:right">Actions</th></tr></thead><tbody>`)
+	for _, t := range tokens {
+		id := t.TokenID.String()
+		label := escapeHTML(t.Name) + `<div class="stat-label"><code>` + escapeHTML(t.TokenPrefix) + `…</code></div>`
+		lastUsed := "never"
+		if t.LastUsedAt != nil {
+			lastUsed = t.LastUsedAt.Format(time.DateTime)
+		}
+		expires := "never"
+		if t.ExpiresAt != nil {
+			expires = t.ExpiresAt.Format(time.DateTime)
+		}
+
+		status, active := "active", true
+		switch {
+		case t.RevokedAt != nil:
+			status, active = "revoked", false
+		case t.ExpiresAt != nil && !t.ExpiresAt.After(now):
+			status, active = "expired", false
+		}
+		rowClass := ""
+		if !active {
+			rowClass = ` class="api-key-inactive"`
+		}
+
+		fmt.Fprintf(&b, `<tr id="personal-token-%s"%s><td>%s</td><td>%s</td><td>%s</td><td>%s</td><td><span class="api-key-status api-key-status-%s">%s</span></td><td class="api-key-actions">`,
+			id, rowClass, label, escapeHTML(t.Scope),
+			escapeHTML(lastUsed), escapeHTML(expires),
+			status, status)
+		if active {
+			fmt.Fprintf(&b, `<button class="btn btn-xs btn-danger" data-on:click="confirm('Revoke this token? Requests using it will fail immediately.') && @delete('/api/auth/tokens/%s', { headers: { 'X-CSRF-Token': getAPIKeysCsrfToken() } })">Revoke</button>`,
+				id)
+		}
+		b.WriteString(`</td></tr>`)
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
@@ -0,0 +1,83 @@
+package handlers
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestBuildPersonalTokenListHTML(t *testing.T) {
+	now := time.Now()
+	active, revoked := uuid.New(), uuid.New()
+	past := now.Add(-time.Hour)
+
+	html := buildPersonalTokenListHTML([]models.PersonalToken{
+		{TokenID: active, Name: "Deploy <script>", TokenPrefix: "kaunta_pat_12345678", Scope: "write", CreatedAt: now, LastUsedAt: &past},
+		{TokenID: revoked, Name: "Old", TokenPrefix: "kaunta_pat_abcdefgh", Scope: "read", CreatedAt: now, RevokedAt: &past},
+	}, now)
+
+	assert.Contains(t, html, "Deploy &lt;script&gt;")
+	assert.Contains(t, html, "kaunta_pat_12345678…")
+	assert.Contains(t, html, past.Format(time.DateTime))
+	assert.Contains(t, html, "/api/auth/tokens/"+active.String())
+	assert.NotContains(t, html, "/api/auth/tokens/"+revoked.String())
+	assert.Contains(t, html, "api-key-status-revoked")
+
+	assert.Contains(t, buildPersonalTokenListHTML(nil, now), "No personal access tokens")
+}
+
+func TestHandlePersonalTokensInit(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New()}
+	stubVar(t, &listPersonalTokensFunc, func(ctx context.Context, userID uuid.UUID) ([]models.PersonalToken, error) {
+		assert.Equal(t, user.UserID, userID)
+		return []models.PersonalToken{{TokenID: uuid.New(), Name: "CI", TokenPrefix: "kaunta_pat_ci", Scope: "read"}}, nil
+	})
+
+	req := httptest.NewRequest(http.MethodGet, "/api/auth/tokens", nil)
+	resp := httptest.NewRecorder()
+	HandlePersonalTokensInit(resp, req.WithContext(middleware.ContextWithUser(req.Context(), user)))
+	assert.Contains(t, resp.Body.String(), "#personal-token-list")
+	assert.Contains(t, resp.Body.String(), "kaunta_pat_ci")
+
+	resp = httptest.NewRecorder()
+	HandlePersonalTokensInit(resp, req)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+}
+
+func TestHandlePersonalTokenCreateValidation(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New()}
+	cases := map[string]url.Values{
+		"Give the token a name": {"scope": {"read"}},
+		"Choose read or write":  {"name": {"CI"}, "scope": {"admin"}},
+		"invalid expiry":        {"name": {"CI"}, "scope": {"read"}, "expires": {"later"}},
+	}
+	for message, form := range cases {
+		req := httptest.NewRequest(http.MethodPost, "/api/auth/tokens", strings.NewReader(form.Encode()))
+		req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+		resp := httptest.NewRecorder()
+		HandlePersonalTokenCreate(resp, req.WithContext(middleware.ContextWithUser(req.Context(), user)))
+		assert.Contains(t, resp.Body.String(), "tokenError", message)
+		assert.Contains(t, resp.Body.String(), message)
+	}
+}
+
+func TestHandlePersonalTokenRevokeInvalidID(t *testing.T) {
+	router := chi.NewRouter()
+	router.Delete("/api/auth/tokens/{token_id}", HandlePersonalTokenRevoke)
+
+	req := teamsRequest(http.MethodDelete, "/api/auth/tokens/not-a-uuid", "")
+	resp := httptest.NewRecorder()
+	router.ServeHTTP(resp, req)
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
@@ -37,6 +37,15 @@ func HandlePixelTracking(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	// An overloaded server skips the hit; a broken image would show in the email
+	release, ok := admitIngest()
+	if !ok {
+		ingestShed.Add(1)
+		servePixel(w)
+		return
+	}
+	defer release()
+
 	payload := buildPixelPayload(r, websiteID)
 	req := withPixelPayload(r, payload)
 
@@ -0,0 +1,49 @@
+package handlers
+
+import (
+	"context"
+	"log"
+	"net/http"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// findOrCreateProxyUserFunc resolves a proxy identity to an account (can be
+// mocked in tests)
+var findOrCreateProxyUserFunc = func(ctx context.Context, identity middleware.ProxyIdentity, create bool) (uuid.UUID, bool, error) {
+	return models.FindOrCreateProxyUser(ctx, database.DB, identity.Username, identity.Email, identity.Name, create)
+}
+
+// syncGroupTeamsFunc applies group mappings to a user (can be mocked in tests)
+var syncGroupTeamsFunc = func(ctx context.Context, userID uuid.UUID, groups []string) error {
+	return models.SyncGroupTeams(ctx, database.DB, userID, groups)
+}
+
+// ProxySignIn signs in users vouched for by a trusted reverse proxy. With
+// autoCreate (proxy_auth.auto_create), unknown usernames get an account;
+// they start without access to any website until they are given a role or
+// a group mapping puts them in a team. When the proxy passes groups, team
+// memberships are synced from the group mappings on every sign-in.
+func ProxySignIn(autoCreate bool) middleware.ProxySignIn {
+	return func(w http.ResponseWriter, r *http.Request, identity middleware.ProxyIdentity) (string, error) {
+		userID, created, err := findOrCreateProxyUserFunc(r.Context(), identity, autoCreate)
+		if err != nil {
+			return "", err
+		}
+		if created {
+			log.Printf("ProxySignIn: created user %q", identity.Username)
+		}
+		if identity.Groups != nil {
+			// A failed sync keeps the previous memberships; signing in
+			// still works
+			if err := syncGroupTeamsFunc(r.Context(), userID, identity.Groups); err != nil {
+				log.Printf("ProxySignIn: sync groups of %q: %v", identity.Username, err)
+			}
+		}
+		return openSession(w, r, userID)
+	}
+}
@@ -0,0 +1,56 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/middleware"
+)
+
+func TestProxySignIn(t *testing.T) {
+	userID := uuid.New()
+	stubVar(t, &findOrCreateProxyUserFunc, func(ctx context.Context, identity middleware.ProxyIdentity, create bool) (uuid.UUID, bool, error) {
+		if identity.Username == "alice" {
+			return userID, false, nil
+		}
+		if !create {
+			return uuid.Nil, false, sql.ErrNoRows
+		}
+		return uuid.New(), true, nil
+	})
+	var synced [][]string
+	stubVar(t, &syncGroupTeamsFunc, func(ctx context.Context, id uuid.UUID, groups []string) error {
+		synced = append(synced, groups)
+		return nil
+	})
+	stubVar(t, &sessionTokenGenerator, func() (string, string, error) { return "token", "hash", nil })
+	stubVar(t, &insertSessionFunc, func(sessionID, id uuid.UUID, tokenHash string, expiresAt time.Time, userAgent, ipAddress string) error {
+		return nil
+	})
+
+	req := httptest.NewRequest(http.MethodGet, "/dashboard", nil)
+	resp := httptest.NewRecorder()
+	token, err := ProxySignIn(false)(resp, req, middleware.ProxyIdentity{Username: "alice", Groups: []string{"staff"}})
+	require.NoError(t, err)
+	assert.Equal(t, "token", token)
+	assert.Contains(t, resp.Header().Get("Set-Cookie"), "kaunta_session=token")
+	assert.Equal(t, [][]string{{"staff"}}, synced)
+
+	// Without a groups header, memberships are left alone
+	_, err = ProxySignIn(false)(httptest.NewRecorder(), req, middleware.ProxyIdentity{Username: "alice"})
+	require.NoError(t, err)
+	assert.Len(t, synced, 1)
+
+	_, err = ProxySignIn(false)(httptest.NewRecorder(), req, middleware.ProxyIdentity{Username: "bob"})
+	assert.ErrorIs(t, err, sql.ErrNoRows)
+	_, err = ProxySignIn(true)(httptest.NewRecorder(), req, middleware.ProxyIdentity{Username: "bob"})
+	assert.NoError(t, err)
+}
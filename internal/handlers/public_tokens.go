@@ -0,0 +1,194 @@
+package handlers
+
+import (
+	"context"
+	"errors"
+	"log"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// publicCounterMaxAge is how long browsers and CDNs may cache a counter
+const publicCounterMaxAge = "public, max-age=30"
+
+// HandlePublicCounter returns only the metrics a public token exposes (no
+// auth required, the token is the credential)
+// GET /api/public/counter/:token
+func HandlePublicCounter(w http.ResponseWriter, r *http.Request) {
+	w.Header().Set("Access-Control-Allow-Origin", "*")
+	w.Header().Set("Access-Control-Allow-Methods", "GET, OPTIONS")
+	w.Header().Set("Access-Control-Allow-Headers", "Content-Type")
+
+	if r.Method == http.MethodOptions {
+		w.WriteHeader(http.StatusNoContent)
+		return
+	}
+
+	token, err := models.GetPublicToken(r.Context(), database.DB, chi.URLParam(r, "token"))
+	if errors.Is(err, models.ErrPublicTokenNotFound) {
+		respondError(w, r, http.StatusNotFound, "Counter not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandlePublicCounter: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Database error")
+		return
+	}
+
+	counter, err := publicCounterData(r.Context(), token)
+	if err != nil {
+		log.Printf("HandlePublicCounter: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch stats")
+		return
+	}
+
+	w.Header().Set("Cache-Control", publicCounterMaxAge)
+	render.JSON(w, r, counter)
+}
+
+// publicCounterData fetches the token's metrics, querying only what it exposes
+func publicCounterData(ctx context.Context, token *models.PublicToken) (map[string]int64, error) {
+	counter := make(map[string]int64, len(token.Metrics))
+	ctx = database.WithTenant(ctx, token.WebsiteID)
+
+	if token.Exposes("online") || token.Exposes("pageviews") || token.Exposes("visitors") {
+		stats, err := getPublicStatsData(ctx, token.WebsiteID)
+		if err != nil {
+			return nil, err
+		}
+		all := map[string]int64{
+			"online":    int64(stats.Online),
+			"pageviews": stats.Pageviews,
+			"visitors":  stats.Visitors,
+		}
+		for name, value := range all {
+			if token.Exposes(name) {
+				counter[name] = value
+			}
+		}
+	}
+
+	if token.Exposes("pageviews_today") || token.Exposes("visitors_today") {
+		var current, pageviews, visitors int64
+		var bounceRate, avgVisitDuration float64
+		err := database.QueryRowStats(ctx,
+			`SELECT * FROM get_dashboard_stats($1, 1, NULL, NULL, NULL, NULL)`,
+			token.WebsiteID,
+		).Scan(&current, &pageviews, &visitors, &bounceRate, &avgVisitDuration)
+		if err != nil {
+			return nil, err
+		}
+		if token.Exposes("pageviews_today") {
+			counter["pageviews_today"] = pageviews
+		}
+		if token.Exposes("visitors_today") {
+			counter["visitors_today"] = visitors
+		}
+	}
+
+	return counter, nil
+}
+
+// HandlePublicTokenList lists a website's public tokens
+// GET /api/websites/:website_id/public-tokens
+func HandlePublicTokenList(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+
+	tokens, err := models.ListPublicTokens(r.Context(), database.DB, websiteID)
+	if err != nil {
+		log.Printf("HandlePublicTokenList: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to list public tokens")
+		return
+	}
+	render.JSON(w, r, tokens)
+}
+
+// HandlePublicTokenCreate mints a public token for a website
+// POST /api/websites/:website_id/public-tokens {"name": "Footer counter", "metrics": ["pageviews", "online"]}
+func HandlePublicTokenCreate(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+
+	var req struct {
+		Name    string   `json:"name"`
+		Metrics []string `json:"metrics" validate:"required"`
+	}
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if _, err := models.ValidatePublicTokenMetrics(req.Metrics); err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	var name *string
+	if value := strings.TrimSpace(req.Name); value != "" {
+		name = &value
+	}
+	var createdBy *uuid.UUID
+	if user := middleware.GetUser(r); user != nil {
+		createdBy = &user.UserID
+	}
+
+	token, err := models.CreatePublicToken(r.Context(), database.DB, websiteID, createdBy, name, req.Metrics)
+	if err != nil {
+		log.Printf("HandlePublicTokenCreate: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to create public token")
+		return
+	}
+
+	render.Status(r, http.StatusCreated)
+	render.JSON(w, r, token)
+}
+
+// HandlePublicTokenDelete revokes a public token
+// DELETE /api/websites/:website_id/public-tokens/:token
+func HandlePublicTokenDelete(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+
+	err := models.DeletePublicToken(r.Context(), database.DB, websiteID, chi.URLParam(r, "token"))
+	if errors.Is(err, models.ErrPublicTokenNotFound) {
+		respondError(w, r, http.StatusNotFound, "Public token not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandlePublicTokenDelete: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to delete public token")
+		return
+	}
+	w.WriteHeader(http.StatusNoContent)
+}
+
+// websiteFromParam resolves the website_id URL param to an existing website
+func websiteFromParam(w http.ResponseWriter, r *http.Request) (uuid.UUID, bool) {
+	websiteIDStr, ok := parseWebsiteID(w, r, chi.URLParam(r, "website_id"))
+	if !ok {
+		return uuid.UUID{}, false
+	}
+
+	ctx, cancel := context.WithTimeout(r.Context(), 30*time.Second)
+	defer cancel()
+
+	if _, err := getWebsiteByID(ctx, websiteIDStr); err != nil {
+		respondError(w, r, http.StatusNotFound, err.Error())
+		return uuid.UUID{}, false
+	}
+	return uuid.MustParse(websiteIDStr), true
+}
@@ -0,0 +1,98 @@
+package handlers
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestHandlePublicCounterExposesOnlyTokenMetrics(t *testing.T) {
+	websiteID := uuid.New()
+	router, queue, cleanup := setupHTTPTest(t, "/api/public/counter/{token}", HandlePublicCounter, []mockResponse{
+		{
+			match:   "FROM public_token t",
+			args:    []any{"kaunta_pub_abc"},
+			columns: []string{"token", "website_id", "created_by", "name", "metrics", "created_at"},
+			rows:    [][]any{{"kaunta_pub_abc", websiteID.String(), nil, nil, "{online,pageviews_today}", time.Now()}},
+		},
+		{match: "INTERVAL '5 minutes'", columns: []string{"count"}, rows: [][]any{{int64(3)}}},
+		{match: "COUNT(*) as pageviews", columns: []string{"pageviews", "visitors"}, rows: [][]any{{int64(900), int64(400)}}},
+		{
+			match:   "get_dashboard_stats($1, 1, NULL, NULL, NULL, NULL)",
+			columns: []string{"current_visitors", "pageviews", "visitors", "bounce_rate", "avg_visit_duration"},
+			rows:    [][]any{{int64(3), int64(120), int64(45), 40.0, 61.5}},
+		},
+	})
+	defer cleanup()
+
+	rec := httptest.NewRecorder()
+	router.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, "/api/public/counter/kaunta_pub_abc", nil))
+
+	require.Equal(t, http.StatusOK, rec.Code)
+	assert.Equal(t, "*", rec.Header().Get("Access-Control-Allow-Origin"))
+	assert.Equal(t, publicCounterMaxAge, rec.Header().Get("Cache-Control"))
+
+	var body map[string]int64
+	require.NoError(t, json.Unmarshal(rec.Body.Bytes(), &body))
+	assert.Equal(t, map[string]int64{"online": 3, "pageviews_today": 120}, body)
+	require.NoError(t, queue.expectationsMet())
+}
+
+func TestHandlePublicCounterUnknownToken(t *testing.T) {
+	router, queue, cleanup := setupHTTPTest(t, "/api/public/counter/{token}", HandlePublicCounter, []mockResponse{
+		{match: "FROM public_token t", columns: []string{"token", "website_id", "created_by", "name", "metrics", "created_at"}},
+	})
+	defer cleanup()
+
+	rec := httptest.NewRecorder()
+	router.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, "/api/public/counter/kaunta_pub_revoked", nil))
+	assert.Equal(t, http.StatusNotFound, rec.Code)
+	assert.Equal(t, "*", rec.Header().Get("Access-Control-Allow-Origin"))
+
+	// Malformed tokens are rejected without a query
+	rec = httptest.NewRecorder()
+	router.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, "/api/public/counter/"+uuid.NewString(), nil))
+	assert.Equal(t, http.StatusNotFound, rec.Code)
+	require.NoError(t, queue.expectationsMet())
+}
+
+func TestPublicCounterDataRunsAsTenant(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	originalDB := database.DB
+	database.DB = db
+	database.SetTenantIsolation(true)
+	t.Cleanup(func() {
+		database.DB = originalDB
+		database.SetTenantIsolation(false)
+	})
+
+	token := &models.PublicToken{WebsiteID: uuid.New(), Metrics: []string{"visitors_today"}}
+	mock.ExpectBegin()
+	mock.ExpectExec("SELECT set_config\\('role', 'kaunta_tenant', true\\)").
+		WithArgs(fmt.Sprintf(`{"%s"}`, token.WebsiteID)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectQuery("get_dashboard_stats").
+		WithArgs(token.WebsiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"current_visitors", "pageviews", "visitors", "bounce_rate", "avg_visit_duration"}).
+			AddRow(int64(3), int64(120), int64(45), 40.0, 61.5))
+	mock.ExpectRollback()
+
+	counter, err := publicCounterData(context.Background(), token)
+	require.NoError(t, err)
+	assert.Equal(t, map[string]int64{"visitors_today": 45}, counter)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
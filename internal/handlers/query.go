@@ -0,0 +1,62 @@
+package handlers
+
+import (
+	"context"
+	"log"
+	"net/http"
+	"time"
+
+	"github.com/go-chi/render"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/query"
+)
+
+// apiQueryTimeout bounds how long an ad-hoc query may run
+const apiQueryTimeout = 30 * time.Second
+
+// HandleAPIQuery runs an ad-hoc aggregation over the API key's website and
+// returns it as a table
+// Requires API key with 'stats' scope
+// POST /api/v1/query {"metrics": ["visitors"], "dimensions": ["country"], "filters": [...], "granularity": "day", "days": 30, "limit": 100}
+func HandleAPIQuery(w http.ResponseWriter, r *http.Request) {
+	apiKey := middleware.GetAPIKey(r)
+	if apiKey == nil {
+		respondError(w, r, http.StatusUnauthorized, "Unauthorized")
+		return
+	}
+	if !authorizeAPIStatsWebsite(w, r, apiKey.WebsiteID) {
+		return
+	}
+
+	var req query.Request
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	q, err := query.Compile(apiKey.WebsiteID, req)
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	ctx, cancel := context.WithTimeout(r.Context(), apiQueryTimeout)
+	defer cancel()
+
+	rows, err := database.QueryStats(ctx, q.SQL, q.Args...)
+	if err != nil {
+		log.Printf("HandleAPIQuery: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to run query")
+		return
+	}
+	defer func() { _ = rows.Close() }()
+
+	data, err := q.Scan(rows.Rows)
+	if err != nil {
+		log.Printf("HandleAPIQuery: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to run query")
+		return
+	}
+
+	render.JSON(w, r, query.Result{Columns: q.Columns, Rows: data})
+}
@@ -0,0 +1,17 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestHandleAPIQueryRequiresAPIKey(t *testing.T) {
+	req := httptest.NewRequest(http.MethodPost, "/api/v1/query", strings.NewReader(`{"metrics":["visitors"]}`))
+	resp := httptest.NewRecorder()
+	HandleAPIQuery(resp, req)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+}
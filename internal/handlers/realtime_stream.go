@@ -0,0 +1,202 @@
+package handlers
+
+import (
+	"bufio"
+	"context"
+	"encoding/json"
+	"fmt"
+	"log"
+	"net/http"
+	"time"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+var (
+	// realtimeHub delivers tracked events to realtime streams; when nil the
+	// streams fall back to polling the visitor count
+	realtimeHub *realtime.Hub
+	// realtimeRecountInterval is the shortest gap between two visitor counts
+	realtimeRecountInterval = 5 * time.Second
+	// realtimeHeartbeatInterval forces a visitor count (and keeps proxies from
+	// closing the stream) when no hits arrive
+	realtimeHeartbeatInterval = 30 * time.Second
+)
+
+// SetRealtimeHub wires the hub fed by the Postgres realtime listener
+func SetRealtimeHub(hub *realtime.Hub) {
+	realtimeHub = hub
+}
+
+// RealtimeVisitors is the payload of a "visitors" stream event
+type RealtimeVisitors struct {
+	Online int `json:"online"`
+}
+
+// RealtimeHit is the payload of a "hit" stream event; session and visit IDs
+// are deliberately left out
+type RealtimeHit struct {
+	Type      string    `json:"type"`
+	Path      string    `json:"path,omitempty"`
+	Title     string    `json:"title,omitempty"`
+	CreatedAt time.Time `json:"created_at"`
+}
+
+// HandleAPIRealtimeStream streams current visitors and recent hits for a
+// website as plain server-sent events (requires an API key with stats scope)
+// GET /api/v1/stats/{website_id}/realtime
+func HandleAPIRealtimeStream(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
+		return
+	}
+
+	flusher, ok := w.(http.Flusher)
+	if !ok {
+		respondError(w, r, http.StatusInternalServerError, "Streaming not supported")
+		return
+	}
+	setSSEHeaders(w)
+	writer := bufio.NewWriter(&sseResponseWriter{ResponseWriter: w, flusher: flusher})
+
+	err := watchRealtime(r.Context(), websiteID,
+		func(online int) error {
+			return writeSSEEvent(writer, "visitors", RealtimeVisitors{Online: online})
+		},
+		func(hit RealtimeHit) error {
+			return writeSSEEvent(writer, "hit", hit)
+		},
+	)
+	if err != nil && r.Context().Err() == nil {
+		log.Printf("HandleAPIRealtimeStream: %v", err)
+	}
+}
+
+// HandleRealtimeStream keeps the dashboard's current visitors card up to date
+// until the client disconnects
+// GET /api/dashboard/realtime/stream?website_id=...
+func HandleRealtimeStream(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	websiteID, parseErr := parseRequiredWebsiteID(r.URL.Query().Get("website_id"))
+	if user == nil {
+		parseErr = "Not authenticated"
+	} else if parseErr == "" && !userHasWebsiteRole(r.Context(), user.UserID, websiteID, models.RoleViewer) {
+		parseErr = "Website not found"
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			_ = sse.PatchSignals(map[string]any{"realtimeError": parseErr})
+			return
+		}
+
+		err := watchRealtime(r.Context(), websiteID,
+			func(online int) error {
+				return sse.PatchSignals(map[string]any{
+					"stats":         map[string]any{"current_visitors": online},
+					"realtimeError": false,
+				})
+			},
+			nil,
+		)
+		if err != nil && r.Context().Err() == nil {
+			log.Printf("HandleRealtimeStream: %v", err)
+		}
+	})
+}
+
+// watchRealtime reports the website's current visitors, then every hit the
+// hub publishes for it, recounting visitors after new hits (at most once per
+// realtimeRecountInterval) and on every heartbeat. It returns when ctx is
+// done, a count fails or a callback returns an error.
+func watchRealtime(ctx context.Context, websiteID uuid.UUID, onVisitors func(int) error, onHit func(RealtimeHit) error) error {
+	var events <-chan []byte
+	if realtimeHub != nil {
+		var cancel func()
+		events, cancel = realtimeHub.Subscribe()
+		defer cancel()
+	}
+
+	report := func() error {
+		online, err := countCurrentVisitors(ctx, websiteID)
+		if err != nil {
+			return fmt.Errorf("count visitors: %w", err)
+		}
+		return onVisitors(online)
+	}
+	if err := report(); err != nil {
+		return err
+	}
+
+	ticker := time.NewTicker(realtimeRecountInterval)
+	defer ticker.Stop()
+	lastCount := time.Now()
+	dirty := false
+	target := websiteID.String()
+
+	for {
+		select {
+		case <-ctx.Done():
+			return nil
+		case msg, ok := <-events:
+			if !ok {
+				events = nil
+				continue
+			}
+			var payload realtime.EventPayload
+			if err := json.Unmarshal(msg, &payload); err != nil || payload.WebsiteID != target {
+				continue
+			}
+			dirty = true
+			if onHit != nil {
+				hit := RealtimeHit{Type: payload.Type, Path: payload.Path, Title: payload.Title, CreatedAt: payload.CreatedAt}
+				if err := onHit(hit); err != nil {
+					return err
+				}
+			}
+		case <-ticker.C:
+			// Without a hub there is nothing to mark the count stale, so poll
+			if dirty || events == nil || time.Since(lastCount) >= realtimeHeartbeatInterval {
+				if err := report(); err != nil {
+					return err
+				}
+				lastCount = time.Now()
+				dirty = false
+			}
+		}
+	}
+}
+
+// countCurrentVisitors counts distinct sessions with a pageview in the last
+// 5 minutes, from the shared Redis counters when configured
+func countCurrentVisitors(ctx context.Context, websiteID uuid.UUID) (int, error) {
+	if count, ok := realtime.OnlineVisitors(ctx, websiteID); ok {
+		return count, nil
+	}
+	var count int
+	err := database.QueryRowStats(ctx, `
+		SELECT COUNT(DISTINCT session_id)
+		FROM website_event
+		WHERE website_id = $1
+		  AND created_at >= NOW() - INTERVAL '5 minutes'
+		  AND event_type = 1
+	`, websiteID).Scan(&count)
+	return count, err
+}
+
+// writeSSEEvent writes one named server-sent event with a JSON payload
+func writeSSEEvent(w *bufio.Writer, event string, data any) error {
+	payload, err := json.Marshal(data)
+	if err != nil {
+		return err
+	}
+	if _, err := fmt.Fprintf(w, "event: %s\ndata: %s\n\n", event, payload); err != nil {
+		return err
+	}
+	return w.Flush()
+}
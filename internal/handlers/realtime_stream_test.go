@@ -0,0 +1,90 @@
+package handlers
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+func TestWatchRealtimeFiltersHitsByWebsite(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	originalHub := realtimeHub
+	hub := realtime.NewHub()
+	SetRealtimeHub(hub)
+	defer SetRealtimeHub(originalHub)
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("SELECT COUNT\\(DISTINCT session_id\\)").
+		WithArgs(websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"count"}).AddRow(4))
+
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+
+	online := make(chan int, 1)
+	hits := make(chan RealtimeHit, 1)
+	done := make(chan error, 1)
+	go func() {
+		done <- watchRealtime(ctx, websiteID,
+			func(n int) error { online <- n; return nil },
+			func(hit RealtimeHit) error { hits <- hit; return nil },
+		)
+	}()
+
+	select {
+	case n := <-online:
+		assert.Equal(t, 4, n)
+	case <-time.After(time.Second):
+		t.Fatal("no visitor count reported")
+	}
+
+	other, _ := json.Marshal(realtime.EventPayload{Type: "event", WebsiteID: uuid.NewString(), Path: "/other"})
+	own, _ := json.Marshal(realtime.EventPayload{Type: "event", WebsiteID: websiteID.String(), SessionID: uuid.NewString(), Path: "/pricing"})
+	hub.Broadcast(other)
+	hub.Broadcast(own)
+
+	select {
+	case hit := <-hits:
+		assert.Equal(t, "/pricing", hit.Path)
+		assert.Equal(t, "event", hit.Type)
+	case <-time.After(time.Second):
+		t.Fatal("hit for the website was not delivered")
+	}
+
+	cancel()
+	select {
+	case err := <-done:
+		assert.NoError(t, err)
+	case <-time.After(time.Second):
+		t.Fatal("watchRealtime did not stop after cancel")
+	}
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestHandleAPIRealtimeStreamRequiresAPIKey(t *testing.T) {
+	router := chi.NewRouter()
+	router.Get("/api/v1/stats/{website_id}/realtime", HandleAPIRealtimeStream)
+
+	resp := httptest.NewRecorder()
+	router.ServeHTTP(resp, httptest.NewRequest(http.MethodGet, "/api/v1/stats/"+uuid.NewString()+"/realtime", nil))
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+}
@@ -0,0 +1,51 @@
+package handlers
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/report"
+)
+
+// HandleReportPDF renders the dashboard summary for a period as a downloadable PDF
+// GET /api/dashboard/report.pdf?website=...&days=30
+func HandleReportPDF(w http.ResponseWriter, r *http.Request) {
+	websiteID, errMsg := parseRequiredWebsiteID(selectedWebsiteFromRequest(r))
+	if errMsg != "" {
+		respondError(w, r, http.StatusBadRequest, errMsg)
+		return
+	}
+	days := min(max(queryInt(r, "days", 30), 1), 365)
+
+	ctx, cancel := database.StatsContext(r.Context())
+	defer cancel()
+	db, release, err := database.StatsQuerier(ctx)
+	if err != nil {
+		log.Printf("HandleReportPDF: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to build report")
+		return
+	}
+	defer release()
+	rep, err := report.Build(ctx, database.TrackStats(db), websiteID, days)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			respondError(w, r, http.StatusNotFound, "Website not found")
+			return
+		}
+		log.Printf("HandleReportPDF: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to build report")
+		return
+	}
+
+	filename := fmt.Sprintf("kaunta-report-%s-%dd-%s.pdf", rep.Domain, days, rep.GeneratedAt.Format("2006-01-02"))
+	w.Header().Set("Content-Type", "application/pdf")
+	w.Header().Set("Content-Disposition", fmt.Sprintf(`attachment; filename="%s"`, filename))
+	w.Header().Set("Cache-Control", "no-store")
+	if err := report.RenderPDF(w, rep); err != nil {
+		log.Printf("HandleReportPDF: render error for %s: %v", websiteID, err)
+	}
+}
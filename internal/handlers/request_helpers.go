@@ -67,3 +67,57 @@ func clientIPFromRequest(r *http.Request, proxyMode string) string {
 	}
 	return clientIP(r)
 }
+
+// pathSearchPattern turns a page search box value into an ILIKE pattern for
+// get_top_pages(). Input containing * or ? is treated as a glob anchored to
+// the whole path (/blog/* matches everything under /blog/); anything else is
+// a substring match. Returns nil (SQL NULL) for an empty search.
+func pathSearchPattern(search string) any {
+	search = strings.TrimSpace(search)
+	if search == "" {
+		return nil
+	}
+
+	glob := strings.ContainsAny(search, "*?")
+	var b strings.Builder
+	if !glob {
+		b.WriteByte('%')
+	}
+	for _, r := range search {
+		switch r {
+		case '%', '_', '\\':
+			b.WriteByte('\\')
+			b.WriteRune(r)
+		case '*':
+			b.WriteByte('%')
+		case '?':
+			b.WriteByte('_')
+		default:
+			b.WriteRune(r)
+		}
+	}
+	if !glob {
+		b.WriteByte('%')
+	}
+	return b.String()
+}
+
+// Chart bucket sizes accepted by get_timeseries()
+const (
+	granularityMinute = "minute"
+	granularityHour   = "hour"
+	granularityDay    = "day"
+)
+
+// timeseriesGranularity validates a requested chart granularity, falling back
+// to hourly buckets for ranges up to a week and daily buckets beyond that.
+func timeseriesGranularity(requested string, days int) string {
+	switch requested {
+	case granularityMinute, granularityHour, granularityDay:
+		return requested
+	}
+	if days <= 7 {
+		return granularityHour
+	}
+	return granularityDay
+}
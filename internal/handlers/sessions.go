@@ -0,0 +1,149 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// listLoginSessionsFunc loads a user's sessions (can be mocked in tests)
+var listLoginSessionsFunc = func(ctx context.Context, userID uuid.UUID) ([]models.LoginSession, error) {
+	return models.ListLoginSessions(ctx, database.DB, userID)
+}
+
+// HandleSessionsInit renders the signed-in user's sessions via Datastar SSE
+// GET /api/auth/sessions
+func HandleSessionsInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	streamSessionList(w, r, user, nil)
+}
+
+// HandleSessionRevoke signs one session out. Revoking the current session
+// logs the user out here too.
+// DELETE /api/auth/sessions/:session_id
+func HandleSessionRevoke(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	sessionID, err := uuid.Parse(chi.URLParam(r, "session_id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid session ID")
+		return
+	}
+
+	err = models.RevokeLoginSession(r.Context(), database.DB, user.UserID, sessionID)
+	if errors.Is(err, sql.ErrNoRows) {
+		respondError(w, r, http.StatusNotFound, "Session not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleSessionRevoke: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to revoke session")
+		return
+	}
+
+	if sessionID == user.SessionID {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.ExecuteScript("window.location.href = '/login'")
+		})
+		return
+	}
+	streamSessionList(w, r, user, nil)
+}
+
+// HandleSessionsRevokeOthers signs the user out on every other device
+// POST /api/auth/sessions/revoke-others
+func HandleSessionsRevokeOthers(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	revoked, err := models.RevokeOtherLoginSessions(r.Context(), database.DB, user.UserID, user.SessionID)
+	if err != nil {
+		log.Printf("HandleSessionsRevokeOthers: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to revoke sessions")
+		return
+	}
+	streamSessionList(w, r, user, map[string]any{
+		"sessionsMessage": fmt.Sprintf("Signed out %d other session(s)", revoked),
+	})
+}
+
+// streamSessionList re-renders the session list after a change
+func streamSessionList(w http.ResponseWriter, r *http.Request, user *middleware.UserContext, signals map[string]any) {
+	sessions, err := listLoginSessionsFunc(r.Context(), user.UserID)
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("streamSessionList: %v", err)
+			_ = sse.PatchSignals(map[string]any{"sessionsError": "Failed to load sessions"})
+			return
+		}
+		_ = sse.PatchElementsWithMode("#session-list", buildSessionListHTML(sessions, user.SessionID), "inner")
+		if signals != nil {
+			_ = sse.PatchSignals(signals)
+		}
+	})
+}
+
+// sessionDevice describes a session's user agent as "Browser on OS"
+func sessionDevice(userAgent *string) string {
+	if userAgent == nil || *userAgent == "" {
+		return "Unknown device"
+	}
+	browser, os, _ := parseUserAgent(*userAgent)
+	return *browser + " on " + *os
+}
+
+func buildSessionListHTML(sessions []models.LoginSession, current uuid.UUID) string {
+	if len(sessions) == 0 {
+		return `<div class="empty-state-mini"><div>[~]</div><div>No active sessions</div></div>`
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="sessions-table"><thead><tr><th>Device</th><th>IP address</th><th>Last seen</th><th>Signed in</th><th style="text-align:right">Actions</th></tr></thead><tbody>`)
+	for _, s := range sessions {
+		id := s.SessionID.String()
+		device := escapeHTML(sessionDevice(s.UserAgent))
+		if s.SessionID == current {
+			device += ` <span class="session-current">This device</span>`
+		}
+		ip := "unknown"
+		if s.IPAddress != nil {
+			ip = *s.IPAddress
+		}
+
+		fmt.Fprintf(&b, `<tr id="session-%s"><td>%s</td><td>%s</td><td>%s</td><td>%s</td><td class="session-actions">`,
+			id, device, escapeHTML(ip),
+			s.LastUsedAt.Format(time.DateTime), s.CreatedAt.Format(time.DateTime))
+		confirmText := "Sign this session out?"
+		if s.SessionID == current {
+			confirmText = "Sign out of this device?"
+		}
+		fmt.Fprintf(&b, `<button class="btn btn-xs btn-danger" data-on:click="confirm('%s') && @delete('/api/auth/sessions/%s', { headers: { 'X-CSRF-Token': getSecurityCsrfToken() } })">Sign out</button>`,
+			confirmText, id)
+		b.WriteString(`</td></tr>`)
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
@@ -0,0 +1,64 @@
+package handlers
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestBuildSessionListHTML(t *testing.T) {
+	current, other := uuid.New(), uuid.New()
+	ua := "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/537.36 Chrome/120.0 Safari/537.36"
+	ip := "203.0.113.7"
+	now := time.Now()
+
+	html := buildSessionListHTML([]models.LoginSession{
+		{SessionID: current, UserAgent: &ua, IPAddress: &ip, CreatedAt: now, LastUsedAt: now},
+		{SessionID: other, CreatedAt: now, LastUsedAt: now},
+	}, current)
+
+	assert.Contains(t, html, "Chrome on macOS")
+	assert.Contains(t, html, "This device")
+	assert.Contains(t, html, "203.0.113.7")
+	assert.Contains(t, html, "Unknown device")
+	assert.Contains(t, html, "/api/auth/sessions/"+other.String())
+
+	assert.Contains(t, buildSessionListHTML(nil, current), "No active sessions")
+}
+
+func TestHandleSessionsInit(t *testing.T) {
+	user := &middleware.UserContext{UserID: uuid.New(), SessionID: uuid.New()}
+	stubVar(t, &listLoginSessionsFunc, func(ctx context.Context, userID uuid.UUID) ([]models.LoginSession, error) {
+		assert.Equal(t, user.UserID, userID)
+		return []models.LoginSession{{SessionID: user.SessionID}}, nil
+	})
+
+	req := httptest.NewRequest(http.MethodGet, "/api/auth/sessions", nil)
+	resp := httptest.NewRecorder()
+	HandleSessionsInit(resp, req.WithContext(middleware.ContextWithUser(req.Context(), user)))
+	assert.Contains(t, resp.Body.String(), "#session-list")
+	assert.Contains(t, resp.Body.String(), "This device")
+
+	resp = httptest.NewRecorder()
+	HandleSessionsInit(resp, req)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+}
+
+func TestHandleSessionRevokeInvalidID(t *testing.T) {
+	router := chi.NewRouter()
+	router.Delete("/api/auth/sessions/{session_id}", HandleSessionRevoke)
+
+	req := teamsRequest(http.MethodDelete, "/api/auth/sessions/not-a-uuid", "")
+	resp := httptest.NewRecorder()
+	router.ServeHTTP(resp, req)
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
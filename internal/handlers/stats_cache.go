@@ -0,0 +1,223 @@
+package handlers
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+// statsPeriod is the time range an aggregation covers
+type statsPeriod struct {
+	From time.Time
+	To   time.Time
+}
+
+// lastDays is the period of a "last N days" report, which includes now
+func lastDays(days int) statsPeriod {
+	now := time.Now()
+	return statsPeriod{From: now.Add(-time.Duration(days) * 24 * time.Hour), To: now}
+}
+
+// statsCacheEntry is one cached aggregation result
+type statsCacheEntry struct {
+	value    any
+	period   statsPeriod
+	open     bool // Period reaches today, so new hits still land in it
+	loadedAt time.Time
+	expires  time.Time
+	stale    bool // A hit inside period was written after loadedAt
+}
+
+// StatsCache keeps expensive aggregation results per website. Results for a
+// period that includes today expire after openTTL; closed periods only
+// change through late hits, so they are kept for closedTTL. Written hits
+// mark the results covering them stale, and stale results are reloaded once
+// they are older than staleGrace, so a busy website costs at most one query
+// per result every staleGrace.
+type StatsCache struct {
+	mu         sync.Mutex
+	entries    map[uuid.UUID]map[string]*statsCacheEntry
+	size       int
+	openTTL    time.Duration
+	closedTTL  time.Duration
+	staleGrace time.Duration
+	maxEntries int
+
+	hits   atomic.Int64
+	misses atomic.Int64
+}
+
+var statsCache = NewStatsCache(30*time.Second, time.Hour, 5*time.Second, 10000)
+
+// NewStatsCache creates an empty cache
+func NewStatsCache(openTTL, closedTTL, staleGrace time.Duration, maxEntries int) *StatsCache {
+	return &StatsCache{
+		entries:    make(map[uuid.UUID]map[string]*statsCacheEntry),
+		openTTL:    openTTL,
+		closedTTL:  closedTTL,
+		staleGrace: staleGrace,
+		maxEntries: maxEntries,
+	}
+}
+
+func (sc *StatsCache) get(websiteID uuid.UUID, key string) (any, bool) {
+	sc.mu.Lock()
+	defer sc.mu.Unlock()
+
+	entry, ok := sc.entries[websiteID][key]
+	if !ok {
+		return nil, false
+	}
+	now := time.Now()
+	if now.After(entry.expires) || (entry.stale && now.Sub(entry.loadedAt) >= sc.staleGrace) {
+		sc.remove(websiteID, key)
+		return nil, false
+	}
+	return entry.value, true
+}
+
+func (sc *StatsCache) put(websiteID uuid.UUID, key string, period statsPeriod, value any) {
+	sc.mu.Lock()
+	defer sc.mu.Unlock()
+
+	now := time.Now()
+	open := !period.To.Before(startOfDay(now))
+	ttl := sc.closedTTL
+	if open {
+		ttl = sc.openTTL
+	}
+
+	if sc.size >= sc.maxEntries {
+		sc.evictExpired(now)
+	}
+	if sc.size >= sc.maxEntries {
+		// Too many live results; start over rather than track usage
+		sc.entries = make(map[uuid.UUID]map[string]*statsCacheEntry)
+		sc.size = 0
+	}
+
+	website := sc.entries[websiteID]
+	if website == nil {
+		website = make(map[string]*statsCacheEntry)
+		sc.entries[websiteID] = website
+	}
+	if _, exists := website[key]; !exists {
+		sc.size++
+	}
+	website[key] = &statsCacheEntry{value: value, period: period, open: open, loadedAt: now, expires: now.Add(ttl)}
+}
+
+func (sc *StatsCache) remove(websiteID uuid.UUID, key string) {
+	website := sc.entries[websiteID]
+	if _, ok := website[key]; !ok {
+		return
+	}
+	delete(website, key)
+	sc.size--
+	if len(website) == 0 {
+		delete(sc.entries, websiteID)
+	}
+}
+
+func (sc *StatsCache) evictExpired(now time.Time) {
+	for websiteID, website := range sc.entries {
+		for key, entry := range website {
+			if now.After(entry.expires) {
+				sc.remove(websiteID, key)
+			}
+		}
+	}
+}
+
+// Touch marks a website's results covering at stale. The ingest path calls
+// it for every hit it writes.
+func (sc *StatsCache) Touch(websiteID uuid.UUID, at time.Time) {
+	sc.mu.Lock()
+	defer sc.mu.Unlock()
+
+	for _, entry := range sc.entries[websiteID] {
+		if !at.Before(entry.period.From) && (entry.open || !at.After(entry.period.To)) {
+			entry.stale = true
+		}
+	}
+}
+
+// Invalidate drops every cached result for a website
+func (sc *StatsCache) Invalidate(websiteID uuid.UUID) {
+	sc.mu.Lock()
+	defer sc.mu.Unlock()
+
+	sc.size -= len(sc.entries[websiteID])
+	delete(sc.entries, websiteID)
+}
+
+// SyncStatsCache marks cached results stale for hits tracked by any instance,
+// as the realtime hub relays them, so dashboards served by one instance
+// follow hits collected by the others. It returns when ctx is done.
+func SyncStatsCache(ctx context.Context, hub *realtime.Hub) {
+	events, cancel := hub.Subscribe()
+	defer cancel()
+
+	for {
+		select {
+		case <-ctx.Done():
+			return
+		case msg, ok := <-events:
+			if !ok {
+				return
+			}
+			var payload realtime.EventPayload
+			if err := json.Unmarshal(msg, &payload); err != nil {
+				continue
+			}
+			if websiteID, err := uuid.Parse(payload.WebsiteID); err == nil {
+				statsCache.Touch(websiteID, payload.CreatedAt)
+			}
+		}
+	}
+}
+
+// cachedStats returns the cached result of load for key, loading and caching
+// it on a miss. Errors are not cached.
+func cachedStats[T any](websiteID uuid.UUID, key string, period statsPeriod, load func() (T, error)) (T, error) {
+	if value, ok := statsCache.get(websiteID, key); ok {
+		if typed, ok := value.(T); ok {
+			statsCache.hits.Add(1)
+			return typed, nil
+		}
+	}
+	statsCache.misses.Add(1)
+
+	value, err := load()
+	if err != nil {
+		return value, err
+	}
+	statsCache.put(websiteID, key, period, value)
+	return value, nil
+}
+
+// statsCacheKey joins the query name and its arguments into a cache key
+func statsCacheKey(parts ...any) string {
+	keys := make([]string, len(parts))
+	for i, part := range parts {
+		if part == nil {
+			keys[i] = "\x00"
+			continue
+		}
+		keys[i] = fmt.Sprint(part)
+	}
+	return strings.Join(keys, "|")
+}
+
+func startOfDay(t time.Time) time.Time {
+	year, month, day := t.UTC().Date()
+	return time.Date(year, month, day, 0, 0, 0, 0, time.UTC)
+}
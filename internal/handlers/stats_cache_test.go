@@ -0,0 +1,146 @@
+package handlers
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/realtime"
+)
+
+func useStatsCache(t *testing.T, cache *StatsCache) {
+	t.Helper()
+	original := statsCache
+	statsCache = cache
+	t.Cleanup(func() { statsCache = original })
+}
+
+// countingLoad returns a loader that reports how often it ran
+func countingLoad(loads *int) func() (int, error) {
+	return func() (int, error) {
+		*loads++
+		return *loads, nil
+	}
+}
+
+func TestCachedStatsReusesResults(t *testing.T) {
+	useStatsCache(t, NewStatsCache(time.Minute, time.Hour, 0, 100))
+	websiteID := uuid.New()
+	loads := 0
+
+	for range 3 {
+		value, err := cachedStats(websiteID, statsCacheKey("stats", 7, nil), lastDays(7), countingLoad(&loads))
+		require.NoError(t, err)
+		assert.Equal(t, 1, value)
+	}
+	assert.Equal(t, 1, loads)
+
+	// Other filters are cached separately
+	_, err := cachedStats(websiteID, statsCacheKey("stats", 7, "US"), lastDays(7), countingLoad(&loads))
+	require.NoError(t, err)
+	assert.Equal(t, 2, loads)
+}
+
+func TestCachedStatsSkipsErrors(t *testing.T) {
+	useStatsCache(t, NewStatsCache(time.Minute, time.Hour, 0, 100))
+	websiteID := uuid.New()
+
+	_, err := cachedStats(websiteID, "stats", lastDays(1), func() (int, error) { return 0, errors.New("timeout") })
+	require.Error(t, err)
+
+	value, err := cachedStats(websiteID, "stats", lastDays(1), func() (int, error) { return 42, nil })
+	require.NoError(t, err)
+	assert.Equal(t, 42, value)
+}
+
+func TestStatsCacheTouchReloadsCoveringResults(t *testing.T) {
+	useStatsCache(t, NewStatsCache(time.Minute, time.Hour, 0, 100))
+	websiteID := uuid.New()
+	lastWeek := startOfDay(time.Now()).AddDate(0, 0, -7)
+	closed := statsPeriod{From: lastWeek, To: lastWeek.Add(24*time.Hour - time.Nanosecond)}
+	openLoads, closedLoads := 0, 0
+
+	load := func() {
+		_, _ = cachedStats(websiteID, "open", lastDays(1), countingLoad(&openLoads))
+		_, _ = cachedStats(websiteID, "closed", closed, countingLoad(&closedLoads))
+	}
+	load()
+
+	// A hit now only changes the open period
+	statsCache.Touch(websiteID, time.Now())
+	load()
+	assert.Equal(t, 2, openLoads)
+	assert.Equal(t, 1, closedLoads)
+
+	// A late hit for last week changes the closed one
+	statsCache.Touch(websiteID, lastWeek.Add(time.Hour))
+	load()
+	assert.Equal(t, 2, openLoads)
+	assert.Equal(t, 2, closedLoads)
+
+	// Other websites are unaffected
+	statsCache.Touch(uuid.New(), time.Now())
+	load()
+	assert.Equal(t, 2, openLoads)
+}
+
+func TestStatsCacheStaleGrace(t *testing.T) {
+	useStatsCache(t, NewStatsCache(time.Minute, time.Hour, time.Hour, 100))
+	websiteID := uuid.New()
+	loads := 0
+
+	_, _ = cachedStats(websiteID, "stats", lastDays(1), countingLoad(&loads))
+	statsCache.Touch(websiteID, time.Now())
+	_, _ = cachedStats(websiteID, "stats", lastDays(1), countingLoad(&loads))
+	assert.Equal(t, 1, loads, "stale results are served until they are staleGrace old")
+}
+
+func TestStatsCacheBoundsEntries(t *testing.T) {
+	cache := NewStatsCache(time.Minute, time.Hour, 0, 2)
+	useStatsCache(t, cache)
+	websiteID := uuid.New()
+	loads := 0
+
+	for _, key := range []string{"a", "b", "c"} {
+		_, _ = cachedStats(websiteID, key, lastDays(1), countingLoad(&loads))
+	}
+	assert.LessOrEqual(t, cache.size, 2)
+
+	cache.Invalidate(websiteID)
+	assert.Zero(t, cache.size)
+}
+
+func TestSyncStatsCacheFollowsRelayedHits(t *testing.T) {
+	useStatsCache(t, NewStatsCache(time.Minute, time.Hour, 0, 100))
+	hub := realtime.NewHub()
+	ctx, cancel := context.WithCancel(context.Background())
+	done := make(chan struct{})
+	go func() {
+		SyncStatsCache(ctx, hub)
+		close(done)
+	}()
+	defer func() {
+		cancel()
+		<-done
+	}()
+
+	websiteID := uuid.New()
+	loads := 0
+	_, _ = cachedStats(websiteID, "stats", lastDays(1), countingLoad(&loads))
+
+	// A hit relayed from another instance; broadcast until the subscription is up
+	payload, err := json.Marshal(realtime.NewEventPayload("event", websiteID, uuid.New(), uuid.New(), "/", "", time.Now()))
+	require.NoError(t, err)
+
+	require.Eventually(t, func() bool {
+		hub.Broadcast(payload)
+		_, _ = cachedStats(websiteID, "stats", lastDays(1), countingLoad(&loads))
+		return loads > 1
+	}, time.Second, 10*time.Millisecond)
+}
@@ -1,6 +1,7 @@
 package handlers
 
 import (
+	"context"
 	"database/sql"
 	"net/http"
 
@@ -19,20 +20,23 @@ type PublicStats struct {
 	Visitors  int64 `json:"visitors"`
 }
 
+// APIStats is the API key stats response: the public stats plus
+// engagement-aware bounce rate and average visit duration for a period
+type APIStats struct {
+	PublicStats
+	Days             int     `json:"days"`
+	BounceRate       float64 `json:"bounce_rate"`
+	AvgVisitDuration float64 `json:"avg_visit_duration"` // seconds
+}
+
 // getPublicStatsData fetches online users, total pageviews, and visitors for a website
-func getPublicStatsData(websiteID uuid.UUID) (*PublicStats, error) {
+func getPublicStatsData(ctx context.Context, websiteID uuid.UUID) (*PublicStats, error) {
 	stats := &PublicStats{}
+	ctx = database.WithTenant(ctx, websiteID)
 
 	// Get online users (distinct sessions in last 5 minutes)
-	onlineQuery := `
-		SELECT COUNT(DISTINCT session_id)
-		FROM website_event
-		WHERE website_id = $1
-		  AND created_at >= NOW() - INTERVAL '5 minutes'
-		  AND event_type = 1
-	`
-	if err := database.DB.QueryRow(onlineQuery, websiteID).Scan(&stats.Online); err != nil {
-		stats.Online = 0
+	if online, err := countCurrentVisitors(ctx, websiteID); err == nil {
+		stats.Online = online
 	}
 
 	// Get total pageviews and unique visitors (all time)
@@ -44,7 +48,7 @@ func getPublicStatsData(websiteID uuid.UUID) (*PublicStats, error) {
 		WHERE website_id = $1
 		  AND event_type = 1
 	`
-	if err := database.DB.QueryRow(totalsQuery, websiteID).Scan(&stats.Pageviews, &stats.Visitors); err != nil {
+	if err := database.QueryRowStats(ctx, totalsQuery, websiteID).Scan(&stats.Pageviews, &stats.Visitors); err != nil {
 		stats.Pageviews = 0
 		stats.Visitors = 0
 	}
@@ -95,7 +99,7 @@ func HandlePublicStats(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	stats, err := getPublicStatsData(websiteID)
+	stats, err := getPublicStatsData(r.Context(), websiteID)
 	if err != nil {
 		respondError(w, r, http.StatusInternalServerError, "Failed to fetch stats")
 		return
@@ -104,33 +108,40 @@ func HandlePublicStats(w http.ResponseWriter, r *http.Request) {
 	render.JSON(w, r, stats)
 }
 
-// HandleAPIStats returns stats for a website via API key (always available)
-// Requires API key with 'stats' scope
-// GET /api/v1/stats/:website_id
-func HandleAPIStats(w http.ResponseWriter, r *http.Request) {
+// authorizeAPIStatsRequest validates the website_id URL param against the
+// request's API key (stats scope, same website) and that the website exists.
+// On failure it writes the error response and returns ok=false.
+func authorizeAPIStatsRequest(w http.ResponseWriter, r *http.Request) (uuid.UUID, bool) {
 	websiteIDStr := chi.URLParam(r, "website_id")
 	websiteID, err := uuid.Parse(websiteIDStr)
 	if err != nil {
 		respondError(w, r, http.StatusBadRequest, "Invalid website ID")
-		return
+		return uuid.UUID{}, false
 	}
 
+	return websiteID, authorizeAPIStatsWebsite(w, r, websiteID)
+}
+
+// authorizeAPIStatsWebsite checks the request's API key has the stats scope
+// for websiteID and that the website exists, writing the error response
+// and returning false otherwise
+func authorizeAPIStatsWebsite(w http.ResponseWriter, r *http.Request, websiteID uuid.UUID) bool {
 	apiKey := middleware.GetAPIKey(r)
 	if apiKey == nil {
 		respondError(w, r, http.StatusUnauthorized, "Unauthorized")
-		return
+		return false
 	}
 
 	// Check if API key has stats scope
 	if !apiKey.HasScope("stats") {
 		respondError(w, r, http.StatusForbidden, "API key does not have stats permission")
-		return
+		return false
 	}
 
 	// Verify website matches API key's website
 	if apiKey.WebsiteID != websiteID {
 		respondError(w, r, http.StatusForbidden, "API key not authorized for this website")
-		return
+		return false
 	}
 
 	// Check if website exists
@@ -138,10 +149,54 @@ func HandleAPIStats(w http.ResponseWriter, r *http.Request) {
 	query := `SELECT EXISTS(SELECT 1 FROM website WHERE website_id = $1 AND deleted_at IS NULL)`
 	if err := database.DB.QueryRow(query, websiteID).Scan(&exists); err != nil || !exists {
 		respondError(w, r, http.StatusNotFound, "Website not found")
+		return false
+	}
+
+	return true
+}
+
+// apiSegmentFilters reads the optional country/browser/device/page segment
+// filters shared by the stats API endpoints (NULL when unset)
+func apiSegmentFilters(r *http.Request) (country, browser, device, page any) {
+	query := r.URL.Query()
+	if v := query.Get("country"); v != "" {
+		country = v
+	}
+	if v := query.Get("browser"); v != "" {
+		browser = v
+	}
+	if v := query.Get("device"); v != "" {
+		device = v
+	}
+	if v := query.Get("page"); v != "" {
+		page = v
+	}
+	return
+}
+
+// HandleAPIStats returns stats for a website via API key (always available)
+// Requires API key with 'stats' scope; days scopes the bounce rate and visit
+// duration, which honour the country/browser/device/page segment filters
+// GET /api/v1/stats/:website_id?days=30&country=DE
+func HandleAPIStats(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
 		return
 	}
 
-	stats, err := getPublicStatsData(websiteID)
+	public, err := getPublicStatsData(r.Context(), websiteID)
+	if err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch stats")
+		return
+	}
+
+	stats := APIStats{PublicStats: *public, Days: min(max(queryInt(r, "days", 30), 1), 365)}
+	country, browser, device, page := apiSegmentFilters(r)
+	var current, pageviews, visitors int64
+	err = database.QueryRowStats(r.Context(),
+		`SELECT * FROM get_dashboard_stats($1, $2, $3, $4, $5, $6)`,
+		websiteID, stats.Days, country, browser, device, page,
+	).Scan(&current, &pageviews, &visitors, &stats.BounceRate, &stats.AvgVisitDuration)
 	if err != nil {
 		respondError(w, r, http.StatusInternalServerError, "Failed to fetch stats")
 		return
@@ -149,3 +204,164 @@ func HandleAPIStats(w http.ResponseWriter, r *http.Request) {
 
 	render.JSON(w, r, stats)
 }
+
+// apiPagesSortColumns maps the Pages report sort_by values to keyset columns
+var apiPagesSortColumns = map[string]keysetColumn{
+	"views":               {"t.views", "bigint"},
+	"path":                {"t.path", "text"},
+	"unique_visitors":     {"t.unique_visitors", "bigint"},
+	"avg_engagement_time": {"t.avg_engagement_time", "numeric"},
+}
+
+// HandleAPIPages returns the paginated Pages report via API key
+// search accepts a substring ("pricing") or a glob ("/blog/*")
+// Pass pagination.next_cursor back as cursor to page through large sites.
+// GET /api/v1/stats/:website_id/pages?days=7&search=/blog/*&per=25&sort_by=views&sort_order=desc&cursor=...
+func HandleAPIPages(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
+		return
+	}
+
+	days := min(max(queryInt(r, "days", 7), 1), 365)
+	pagination := ParsePaginationParamsWithValidation(r, "pages")
+
+	query, args := keysetQuery(
+		`SELECT * FROM get_top_pages($1, $2, NULL, 0, NULL, NULL, NULL, $3, $4, $5)`,
+		[]any{websiteID, days, pagination.SortBy, string(pagination.SortOrder), pathSearchPattern(r.URL.Query().Get("search"))},
+		pagination, apiPagesSortColumns[pagination.SortBy], "t.path",
+	)
+	rows, err := database.QueryStats(r.Context(), query, args...)
+	if err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch pages")
+		return
+	}
+	defer func() { _ = rows.Close() }()
+
+	pages := make([]TopPage, 0)
+	var cursors [][2]string
+	var total int64
+	for rows.Next() {
+		var page TopPage
+		var avgEngagement sql.NullFloat64
+		var cursor [2]string
+		if err := rows.Scan(&page.Path, &page.Views, &page.UniqueVisitors, &avgEngagement, &total, &cursor[0], &cursor[1]); err != nil {
+			respondError(w, r, http.StatusInternalServerError, "Failed to fetch pages")
+			return
+		}
+		if avgEngagement.Valid {
+			page.AvgEngagementTime = &avgEngagement.Float64
+		}
+		pages = append(pages, page)
+		cursors = append(cursors, cursor)
+	}
+
+	pages, next := keysetPage(pages, cursors, pagination)
+	render.JSON(w, r, NewKeysetPaginatedResponse(pages, pagination, total, next))
+}
+
+// APITimeseries is the API key pageviews-over-time response
+type APITimeseries struct {
+	Days        int               `json:"days"`
+	Granularity string            `json:"granularity"`
+	Data        []TimeSeriesPoint `json:"data"`
+}
+
+// HandleAPITimeseries returns pageviews per bucket via API key
+// granularity is minute, hour or day (default: hour up to 7 days, day beyond)
+// GET /api/v1/stats/:website_id/timeseries?days=7&granularity=day&country=DE
+func HandleAPITimeseries(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
+		return
+	}
+
+	days := min(max(queryInt(r, "days", 7), 1), 365)
+	result := APITimeseries{
+		Days:        days,
+		Granularity: timeseriesGranularity(r.URL.Query().Get("granularity"), days),
+		Data:        make([]TimeSeriesPoint, 0),
+	}
+	country, browser, device, page := apiSegmentFilters(r)
+
+	rows, err := database.QueryStats(r.Context(),
+		`SELECT * FROM get_timeseries($1, $2, $3, $4, $5, $6, $7)`,
+		websiteID, days, country, browser, device, page, result.Granularity,
+	)
+	if err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch timeseries")
+		return
+	}
+	defer func() { _ = rows.Close() }()
+
+	for rows.Next() {
+		var point TimeSeriesPoint
+		if err := rows.Scan(&point.Timestamp, &point.Value); err != nil {
+			respondError(w, r, http.StatusInternalServerError, "Failed to fetch timeseries")
+			return
+		}
+		result.Data = append(result.Data, point)
+	}
+
+	render.JSON(w, r, result)
+}
+
+// HandleAPIBreakdown returns a paginated breakdown report via API key.
+// dimension is any dashboard breakdown except pages (see HandleAPIPages):
+// referrers, browsers, browser-versions, os, devices, screens, countries,
+// regions, cities, entry-pages, exit-pages or a utm_* parameter.
+// GET /api/v1/stats/:website_id/breakdown/:dimension?days=7&per=25&sort_by=count&sort_order=desc&cursor=...
+func HandleAPIBreakdown(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
+		return
+	}
+
+	dimension := chi.URLParam(r, "dimension")
+	dbDimension, known := breakdownDimensions[dimension]
+	if !known || dbDimension == "pages" {
+		respondError(w, r, http.StatusBadRequest, "Unknown dimension: "+dimension)
+		return
+	}
+
+	days := min(max(queryInt(r, "days", 7), 1), 365)
+	pagination := ParsePaginationParamsWithValidation(r, "breakdown")
+	country, browser, device, page := apiSegmentFilters(r)
+
+	sort := keysetColumn{`t."count"`, "bigint"}
+	if pagination.SortBy == "name" {
+		sort = keysetColumn{"COALESCE(t.name, '')", "text"}
+	}
+	query, args := keysetQuery(
+		`SELECT * FROM get_breakdown($1, $2, $3, NULL, 0, $4, $5, $6, $7, $8, $9)`,
+		[]any{websiteID, dbDimension, days, country, browser, device, page, pagination.SortBy, string(pagination.SortOrder)},
+		pagination, sort, "COALESCE(t.name, '')",
+	)
+	rows, err := database.QueryStats(r.Context(), query, args...)
+	if err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch breakdown")
+		return
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]BreakdownItem, 0)
+	var cursors [][2]string
+	var total int64
+	for rows.Next() {
+		var item BreakdownItem
+		var cursor [2]string
+		if err := rows.Scan(&item.Name, &item.Count, &total, &cursor[0], &cursor[1]); err != nil {
+			respondError(w, r, http.StatusInternalServerError, "Failed to fetch breakdown")
+			return
+		}
+		if dbDimension == "country" {
+			item.Code = item.Name
+			item.Name = getCountryName(item.Code)
+		}
+		items = append(items, item)
+		cursors = append(cursors, cursor)
+	}
+
+	items, next := keysetPage(items, cursors, pagination)
+	render.JSON(w, r, NewKeysetPaginatedResponse(items, pagination, total, next))
+}
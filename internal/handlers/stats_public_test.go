@@ -0,0 +1,45 @@
+package handlers
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+)
+
+func TestAPISegmentFilters(t *testing.T) {
+	req := httptest.NewRequest(http.MethodGet, "/?country=DE&device=mobile&browser=", nil)
+	country, browser, device, page := apiSegmentFilters(req)
+
+	assert.Equal(t, "DE", country)
+	assert.Nil(t, browser)
+	assert.Equal(t, "mobile", device)
+	assert.Nil(t, page)
+}
+
+func TestStatsAPIEndpointsRequireAPIKey(t *testing.T) {
+	router := chi.NewRouter()
+	router.Get("/api/v1/stats/{website_id}/timeseries", HandleAPITimeseries)
+	router.Get("/api/v1/stats/{website_id}/breakdown/{dimension}", HandleAPIBreakdown)
+	router.Get("/api/v1/stats/{website_id}/daily", HandleAPIDailyRollup)
+	router.Get("/api/v1/stats/{website_id}/summary", HandleAPISummary)
+
+	websiteID := uuid.NewString()
+	for _, path := range []string{
+		"/api/v1/stats/" + websiteID + "/timeseries",
+		"/api/v1/stats/" + websiteID + "/breakdown/referrers",
+		"/api/v1/stats/" + websiteID + "/daily?date=2026-05-01",
+		"/api/v1/stats/" + websiteID + "/summary",
+	} {
+		resp := httptest.NewRecorder()
+		router.ServeHTTP(resp, httptest.NewRequest(http.MethodGet, path, nil))
+		assert.Equal(t, http.StatusUnauthorized, resp.Code, path)
+	}
+
+	resp := httptest.NewRecorder()
+	router.ServeHTTP(resp, httptest.NewRequest(http.MethodGet, "/api/v1/stats/not-a-uuid/timeseries", nil))
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
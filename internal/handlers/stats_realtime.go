@@ -6,8 +6,6 @@ import (
 	"github.com/go-chi/chi/v5"
 	"github.com/go-chi/render"
 	"github.com/google/uuid"
-
-	"github.com/seuros/kaunta/internal/database"
 )
 
 // HandleCurrentVisitors returns count of visitors in last 5 minutes
@@ -20,18 +18,8 @@ func HandleCurrentVisitors(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	// Count distinct sessions from last 5 minutes
-	// (Plausible uses last 5 minutes as default)
-	query := `
-		SELECT COUNT(DISTINCT session_id)
-		FROM website_event
-		WHERE website_id = $1
-		  AND created_at >= NOW() - INTERVAL '5 minutes'
-		  AND event_type = 1
-	`
-
-	var count int
-	if err := database.DB.QueryRow(query, websiteID).Scan(&count); err != nil {
+	count, err := countCurrentVisitors(r.Context(), websiteID)
+	if err != nil {
 		respondError(w, r, http.StatusInternalServerError, "Failed to query current visitors")
 		return
 	}
@@ -0,0 +1,94 @@
+package handlers
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"log"
+	"net/http"
+	"strings"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+// summaryMaxAge lets SSR frameworks and shared caches reuse a summary briefly
+// before revalidating it with If-None-Match
+const summaryMaxAge = "private, max-age=60"
+
+// APISummaryPeriod is one period of the stats summary
+type APISummaryPeriod struct {
+	Pageviews        int64   `json:"pageviews"`
+	Visitors         int64   `json:"visitors"`
+	BounceRate       float64 `json:"bounce_rate"`
+	AvgVisitDuration float64 `json:"avg_visit_duration"` // seconds
+}
+
+// APISummary is everything a site layout usually shows, in one response:
+// current visitors and the today, 7 day and 30 day totals
+type APISummary struct {
+	WebsiteID uuid.UUID        `json:"website_id"`
+	Online    int64            `json:"online"`
+	Today     APISummaryPeriod `json:"today"`
+	Last7     APISummaryPeriod `json:"7d"`
+	Last30    APISummaryPeriod `json:"30d"`
+}
+
+// HandleAPISummary returns the stats summary with an ETag, answering
+// 304 Not Modified when the numbers have not changed since If-None-Match
+// Requires API key with 'stats' scope
+// GET /api/v1/stats/:website_id/summary
+func HandleAPISummary(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := authorizeAPIStatsRequest(w, r)
+	if !ok {
+		return
+	}
+
+	summary := APISummary{WebsiteID: websiteID}
+	var online7, online30 int64
+	err := database.QueryRowStats(r.Context(), `
+		SELECT d1.*, d7.*, d30.*
+		FROM get_dashboard_stats($1, 1, NULL, NULL, NULL, NULL) d1,
+		     get_dashboard_stats($1, 7, NULL, NULL, NULL, NULL) d7,
+		     get_dashboard_stats($1, 30, NULL, NULL, NULL, NULL) d30
+	`, websiteID).Scan(
+		&summary.Online, &summary.Today.Pageviews, &summary.Today.Visitors, &summary.Today.BounceRate, &summary.Today.AvgVisitDuration,
+		&online7, &summary.Last7.Pageviews, &summary.Last7.Visitors, &summary.Last7.BounceRate, &summary.Last7.AvgVisitDuration,
+		&online30, &summary.Last30.Pageviews, &summary.Last30.Visitors, &summary.Last30.BounceRate, &summary.Last30.AvgVisitDuration,
+	)
+	if err != nil {
+		log.Printf("HandleAPISummary: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch stats")
+		return
+	}
+
+	body, err := json.Marshal(summary)
+	if err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to fetch stats")
+		return
+	}
+	hash := sha256.Sum256(body)
+	etag := `"` + hex.EncodeToString(hash[:8]) + `"`
+
+	w.Header().Set("ETag", etag)
+	w.Header().Set("Cache-Control", summaryMaxAge)
+	if etagMatches(r.Header.Get("If-None-Match"), etag) {
+		w.WriteHeader(http.StatusNotModified)
+		return
+	}
+	w.Header().Set("Content-Type", "application/json")
+	_, _ = w.Write(append(body, '\n'))
+}
+
+// etagMatches reports whether an If-None-Match header lists etag, comparing
+// weakly as RFC 9110 requires for If-None-Match
+func etagMatches(header, etag string) bool {
+	for candidate := range strings.SplitSeq(header, ",") {
+		candidate = strings.TrimPrefix(strings.TrimSpace(candidate), "W/")
+		if candidate == etag || candidate == "*" {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,17 @@
+package handlers
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestETagMatches(t *testing.T) {
+	etag := `"0123456789abcdef"`
+	assert.True(t, etagMatches(etag, etag))
+	assert.True(t, etagMatches(`W/"0123456789abcdef"`, etag))
+	assert.True(t, etagMatches(`"other", "0123456789abcdef"`, etag))
+	assert.True(t, etagMatches("*", etag))
+	assert.False(t, etagMatches("", etag))
+	assert.False(t, etagMatches(`"other"`, etag))
+}
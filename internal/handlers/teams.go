@@ -0,0 +1,301 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"log"
+	"net/http"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// teamRoleFunc resolves a user's role in a team (can be mocked in tests)
+var teamRoleFunc = func(ctx context.Context, teamID, userID uuid.UUID) (models.WebsiteRole, error) {
+	return models.GetTeamRole(ctx, database.DB, teamID, userID)
+}
+
+// TeamRequest creates a team
+type TeamRequest struct {
+	Name string `json:"name"`
+}
+
+// WebsiteTeamRequest moves a website into a team; a null team_id takes it
+// out of its team
+type WebsiteTeamRequest struct {
+	TeamID *uuid.UUID `json:"team_id"`
+}
+
+// ResolveTeams lists the signed-in user's teams for the dashboard team
+// switcher
+func ResolveTeams(r *http.Request) []models.Team {
+	user := middleware.GetUser(r)
+	if user == nil || database.DB == nil {
+		return nil
+	}
+	teams, err := models.ListUserTeams(r.Context(), database.DB, user.UserID)
+	if err != nil {
+		log.Printf("ResolveTeams: %v", err)
+	}
+	return teams
+}
+
+// teamFromParam parses the {team_id} route parameter and checks the user
+// holds at least role in the team, writing the error response otherwise
+func teamFromParam(w http.ResponseWriter, r *http.Request, role models.WebsiteRole) (uuid.UUID, bool) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return uuid.UUID{}, false
+	}
+	teamID, err := uuid.Parse(chi.URLParam(r, "team_id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid team ID")
+		return uuid.UUID{}, false
+	}
+
+	have, err := teamRoleFunc(r.Context(), teamID, user.UserID)
+	switch {
+	case errors.Is(err, models.ErrTeamNotFound):
+		respondError(w, r, http.StatusNotFound, "Team not found")
+		return uuid.UUID{}, false
+	case err != nil:
+		log.Printf("teamFromParam: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to check team access")
+		return uuid.UUID{}, false
+	case !have.Allows(role):
+		respondError(w, r, http.StatusForbidden, "This requires the "+string(role)+" role in the team")
+		return uuid.UUID{}, false
+	}
+	return teamID, true
+}
+
+// HandleTeams lists the current user's teams
+// GET /api/teams
+func HandleTeams(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	teams, err := models.ListUserTeams(r.Context(), database.DB, user.UserID)
+	if err != nil {
+		log.Printf("HandleTeams: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to list teams")
+		return
+	}
+	render.JSON(w, r, teams)
+}
+
+// HandleTeamCreate creates a team owned by the current user
+// POST /api/teams
+func HandleTeamCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	defer func() { _ = r.Body.Close() }()
+	var req TeamRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if req.Name == "" {
+		respondError(w, r, http.StatusBadRequest, "Name is required")
+		return
+	}
+
+	team, err := models.CreateTeam(r.Context(), database.DB, req.Name, user.UserID)
+	if errors.Is(err, models.ErrTeamExists) {
+		respondError(w, r, http.StatusConflict, "A team with this name already exists")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleTeamCreate: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to create team")
+		return
+	}
+	render.Status(r, http.StatusCreated)
+	render.JSON(w, r, team)
+}
+
+// HandleTeamDelete deletes a team; its websites stay with their owners
+// DELETE /api/teams/:team_id
+func HandleTeamDelete(w http.ResponseWriter, r *http.Request) {
+	teamID, ok := teamFromParam(w, r, models.RoleOwner)
+	if !ok {
+		return
+	}
+
+	if err := models.DeleteTeam(r.Context(), database.DB, teamID); err != nil {
+		log.Printf("HandleTeamDelete: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to delete team")
+		return
+	}
+	w.WriteHeader(http.StatusNoContent)
+}
+
+// HandleSetCurrentTeam switches the team the dashboard is scoped to via
+// Datastar SSE, then reloads the page. An empty team_id shows all teams.
+// POST /api/teams/current?team_id=uuid
+func HandleSetCurrentTeam(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	var teamID *uuid.UUID
+	if raw := r.URL.Query().Get("team_id"); raw != "" {
+		parsed, err := uuid.Parse(raw)
+		if err != nil {
+			respondError(w, r, http.StatusBadRequest, "Invalid team ID")
+			return
+		}
+		teamID = &parsed
+	}
+
+	err := models.SetCurrentTeam(r.Context(), database.DB, user.UserID, teamID)
+	if errors.Is(err, models.ErrTeamNotFound) {
+		respondError(w, r, http.StatusNotFound, "Team not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleSetCurrentTeam: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to switch team")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		// The selected website may belong to another team
+		_ = sse.ExecuteScript("localStorage.removeItem('kaunta_website'); window.location.reload()")
+	})
+}
+
+// HandleTeamMembers lists a team's members
+// GET /api/teams/:team_id/members
+func HandleTeamMembers(w http.ResponseWriter, r *http.Request) {
+	teamID, ok := teamFromParam(w, r, models.RoleViewer)
+	if !ok {
+		return
+	}
+
+	members, err := models.ListTeamMembers(r.Context(), database.DB, teamID)
+	if err != nil {
+		log.Printf("HandleTeamMembers: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to list members")
+		return
+	}
+	render.JSON(w, r, members)
+}
+
+// HandleTeamMemberSet adds a user to a team or changes their role
+// PUT /api/teams/:team_id/members
+func HandleTeamMemberSet(w http.ResponseWriter, r *http.Request) {
+	teamID, ok := teamFromParam(w, r, models.RoleOwner)
+	if !ok {
+		return
+	}
+
+	defer func() { _ = r.Body.Close() }()
+	var req WebsiteMemberRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if req.Username == "" {
+		respondError(w, r, http.StatusBadRequest, "Username is required")
+		return
+	}
+	role, err := models.ParseWebsiteRole(req.Role)
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	member, err := models.SetTeamMember(r.Context(), database.DB, teamID, req.Username, role)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		respondError(w, r, http.StatusNotFound, "User not found")
+		return
+	case errors.Is(err, models.ErrLastTeamOwner):
+		respondError(w, r, http.StatusConflict, "A team needs at least one owner")
+		return
+	case err != nil:
+		log.Printf("HandleTeamMemberSet: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to save member")
+		return
+	}
+	render.JSON(w, r, member)
+}
+
+// HandleTeamMemberDelete removes a user from a team
+// DELETE /api/teams/:team_id/members/:user_id
+func HandleTeamMemberDelete(w http.ResponseWriter, r *http.Request) {
+	teamID, ok := teamFromParam(w, r, models.RoleOwner)
+	if !ok {
+		return
+	}
+	userID, err := uuid.Parse(chi.URLParam(r, "user_id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid user ID")
+		return
+	}
+
+	err = models.RemoveTeamMember(r.Context(), database.DB, teamID, userID)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		respondError(w, r, http.StatusNotFound, "Member not found")
+		return
+	case errors.Is(err, models.ErrLastTeamOwner):
+		respondError(w, r, http.StatusConflict, "A team needs at least one owner")
+		return
+	case err != nil:
+		log.Printf("HandleTeamMemberDelete: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to remove member")
+		return
+	}
+	w.WriteHeader(http.StatusNoContent)
+}
+
+// HandleWebsiteTeam moves a website into one of the user's teams, where
+// they need at least the editor role, or out of its team
+// PUT /api/websites/:website_id/team
+func HandleWebsiteTeam(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+
+	defer func() { _ = r.Body.Close() }()
+	var req WebsiteTeamRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if req.TeamID != nil {
+		role, err := teamRoleFunc(r.Context(), *req.TeamID, user.UserID)
+		if err != nil || !role.Allows(models.RoleEditor) {
+			respondError(w, r, http.StatusNotFound, "Team not found")
+			return
+		}
+	}
+
+	if err := models.SetWebsiteTeam(r.Context(), database.DB, websiteID, req.TeamID); err != nil {
+		log.Printf("HandleWebsiteTeam: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to move website")
+		return
+	}
+	render.JSON(w, r, map[string]any{"website_id": websiteID, "team_id": req.TeamID})
+}
@@ -0,0 +1,72 @@
+package handlers
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func teamsRequest(method, target, body string) *http.Request {
+	req := httptest.NewRequest(method, target, strings.NewReader(body))
+	req.Header.Set("Content-Type", "application/json")
+	user := &middleware.UserContext{UserID: uuid.New(), Username: "demo"}
+	return req.WithContext(middleware.ContextWithUser(req.Context(), user))
+}
+
+func TestTeamEndpointsCheckTeamRole(t *testing.T) {
+	viewerTeam := uuid.New()
+	stubVar(t, &teamRoleFunc, func(ctx context.Context, teamID, userID uuid.UUID) (models.WebsiteRole, error) {
+		if teamID == viewerTeam {
+			return models.RoleViewer, nil
+		}
+		return "", models.ErrTeamNotFound
+	})
+
+	router := chi.NewRouter()
+	router.Put("/api/teams/{team_id}/members", HandleTeamMemberSet)
+	router.Delete("/api/teams/{team_id}", HandleTeamDelete)
+	router.Get("/api/teams/{team_id}/members", HandleTeamMembers)
+
+	// Viewers can't manage members or delete the team
+	resp := httptest.NewRecorder()
+	router.ServeHTTP(resp, teamsRequest(http.MethodPut, "/api/teams/"+viewerTeam.String()+"/members", `{"username":"bob","role":"editor"}`))
+	assert.Equal(t, http.StatusForbidden, resp.Code)
+
+	resp = httptest.NewRecorder()
+	router.ServeHTTP(resp, teamsRequest(http.MethodDelete, "/api/teams/"+viewerTeam.String(), ""))
+	assert.Equal(t, http.StatusForbidden, resp.Code)
+
+	// Teams the user isn't in don't exist for them
+	resp = httptest.NewRecorder()
+	router.ServeHTTP(resp, teamsRequest(http.MethodGet, "/api/teams/"+uuid.NewString()+"/members", ""))
+	assert.Equal(t, http.StatusNotFound, resp.Code)
+
+	resp = httptest.NewRecorder()
+	router.ServeHTTP(resp, teamsRequest(http.MethodGet, "/api/teams/not-a-uuid/members", ""))
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
+
+func TestHandleTeamCreateValidation(t *testing.T) {
+	resp := httptest.NewRecorder()
+	HandleTeamCreate(resp, httptest.NewRequest(http.MethodPost, "/api/teams", strings.NewReader(`{"name":"Marketing"}`)))
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+
+	resp = httptest.NewRecorder()
+	HandleTeamCreate(resp, teamsRequest(http.MethodPost, "/api/teams", `{"name":""}`))
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
+
+func TestHandleSetCurrentTeamInvalidID(t *testing.T) {
+	resp := httptest.NewRecorder()
+	HandleSetCurrentTeam(resp, teamsRequest(http.MethodPost, "/api/teams/current?team_id=nope", ""))
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+}
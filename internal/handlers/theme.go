@@ -0,0 +1,55 @@
+package handlers
+
+import (
+	"log"
+	"net/http"
+	"strconv"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// ResolveTheme picks the dashboard theme for a page render. An explicit
+// ?theme= (used by share links) wins over the signed-in user's preference.
+func ResolveTheme(r *http.Request) string {
+	if theme := r.URL.Query().Get("theme"); models.IsValidTheme(theme) {
+		return theme
+	}
+	user := middleware.GetUser(r)
+	if user == nil || database.DB == nil {
+		return models.DefaultTheme
+	}
+	theme, err := models.GetUserTheme(r.Context(), database.DB, user.UserID)
+	if err != nil {
+		log.Printf("ResolveTheme: %v", err)
+	}
+	return theme
+}
+
+// HandleSetTheme stores the current user's dashboard theme via Datastar SSE
+// POST /api/auth/theme?theme=retro
+func HandleSetTheme(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	theme := r.URL.Query().Get("theme")
+	if !models.IsValidTheme(theme) {
+		respondError(w, r, http.StatusBadRequest, "Unknown theme")
+		return
+	}
+
+	if err := models.SetUserTheme(r.Context(), database.DB, user.UserID, theme); err != nil {
+		log.Printf("HandleSetTheme: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to save theme")
+		return
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		_ = sse.PatchSignals(map[string]any{"theme": theme})
+		_ = sse.ExecuteScript("document.documentElement.dataset.theme = " + strconv.Quote(theme))
+	})
+}
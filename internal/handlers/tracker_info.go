@@ -0,0 +1,48 @@
+package handlers
+
+import (
+	"crypto/sha512"
+	"encoding/base64"
+	"net/http"
+
+	"github.com/go-chi/render"
+)
+
+// TrackerInfo describes the tracker script served by this release
+type TrackerInfo struct {
+	Version   string `json:"version"`
+	URL       string `json:"url"`        // versioned URL, never serves other bytes
+	LatestURL string `json:"latest_url"` // rolling URL, follows upgrades
+	Integrity string `json:"integrity"`  // Subresource Integrity hash of the script
+}
+
+// VersionedTrackerPath is the URL of a release's tracker script
+func VersionedTrackerPath(version string) string {
+	return "/k.v" + version + ".js"
+}
+
+// TrackerIntegrity is the Subresource Integrity hash of the tracker script
+func TrackerIntegrity(trackerScript []byte) string {
+	hash := sha512.Sum384(trackerScript)
+	return "sha384-" + base64.StdEncoding.EncodeToString(hash[:])
+}
+
+// HandleTrackerInfo returns the versioned tracker URL and its SRI hash, for
+// sites that pin the script
+// GET /api/tracker
+func HandleTrackerInfo(trackerScript []byte, version string) http.HandlerFunc {
+	info := TrackerInfo{
+		Version:   version,
+		LatestURL: "/k.js",
+		Integrity: TrackerIntegrity(trackerScript),
+	}
+	if version != "" {
+		info.URL = VersionedTrackerPath(version)
+	}
+
+	return func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Access-Control-Allow-Origin", "*")
+		w.Header().Set("Cache-Control", "public, max-age=300")
+		render.JSON(w, r, info)
+	}
+}
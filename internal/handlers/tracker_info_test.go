@@ -0,0 +1,32 @@
+package handlers
+
+import (
+	"crypto/sha512"
+	"encoding/base64"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestHandleTrackerInfoPublishesVersionedURLAndIntegrity(t *testing.T) {
+	script := []byte("console.log('hello');")
+	req := httptest.NewRequest(http.MethodGet, "/api/tracker", nil)
+	resp := httptest.NewRecorder()
+	HandleTrackerInfo(script, "1.2.3").ServeHTTP(resp, req)
+
+	var info TrackerInfo
+	require.NoError(t, json.NewDecoder(resp.Body).Decode(&info))
+
+	hash := sha512.Sum384(script)
+	assert.Equal(t, TrackerInfo{
+		Version:   "1.2.3",
+		URL:       "/k.v1.2.3.js",
+		LatestURL: "/k.js",
+		Integrity: "sha384-" + base64.StdEncoding.EncodeToString(hash[:]),
+	}, info)
+	assert.Equal(t, "*", resp.Header().Get("Access-Control-Allow-Origin"))
+}
@@ -5,9 +5,11 @@ import (
 	"crypto/md5"
 	"encoding/hex"
 	"encoding/json"
+	"errors"
 	"maps"
 	"net/http"
 	"net/url"
+	"regexp"
 	"strings"
 	"time"
 
@@ -21,6 +23,7 @@ import (
 	"github.com/seuros/kaunta/internal/geoip"
 	"github.com/seuros/kaunta/internal/logging"
 	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
 	"github.com/seuros/kaunta/internal/realtime"
 )
 
@@ -44,7 +47,7 @@ var spamReferrers = []string{
 
 // TrackingPayload matches Umami's /api/send payload
 type TrackingPayload struct {
-	Type    string      `json:"type"` // "event" or "identify"
+	Type    string      `json:"type"` // "event", "identify" or "engagement"
 	Payload PayloadData `json:"payload"`
 }
 
@@ -68,7 +71,11 @@ type PayloadData struct {
 	IP        *string        `json:"ip,omitempty"`
 	UserAgent *string        `json:"userAgent,omitempty"`
 	Timestamp *int64         `json:"timestamp,omitempty"`
-	ID        *string        `json:"id,omitempty"` // distinct_id
+	ID        *string        `json:"id,omitempty"`      // distinct_id
+	Consent   *bool          `json:"consent,omitempty"` // sent after opt-in by trackers with data-consent="manual"
+
+	// Sent by trackers with data-hash-routing: the URL's #/route is part of the page's path
+	HashRouting bool `json:"hash_routing,omitempty"`
 
 	// Enhanced tracking (Phase 2)
 	ScrollDepth    *int           `json:"scroll_depth,omitempty"`    // 0-100 percentage
@@ -83,7 +90,10 @@ type PayloadData struct {
 	UTMContent  *string `json:"utm_content,omitempty"`  // ad variant identifier
 }
 
-// getTrackingPayload extracts TrackingPayload from either JSON POST body or pixel query params
+// getTrackingPayload extracts TrackingPayload from either JSON POST body or pixel query params.
+// The body is read as JSON whatever its Content-Type: the tracker sends hits
+// fired as the page unloads as text/plain, which needs no CORS preflight, and
+// sendBeacon sends strings as text/plain too.
 func getTrackingPayload(r *http.Request) (*TrackingPayload, error) {
 	if payload, ok := r.Context().Value(pixelPayloadContextKey).(TrackingPayload); ok {
 		return &payload, nil
@@ -103,6 +113,14 @@ func withPixelPayload(r *http.Request, payload TrackingPayload) *http.Request {
 
 // HandleTracking is the /api/send endpoint - compatible with Umami
 func HandleTracking(w http.ResponseWriter, r *http.Request) {
+	// Allowed before any error, so the tracker's debug mode can read why a
+	// hit was rejected; responses carry nothing but the outcome
+	if origin := r.Header.Get("Origin"); origin != "" && origin != "null" {
+		w.Header().Set("Access-Control-Allow-Origin", origin)
+	} else {
+		w.Header().Set("Access-Control-Allow-Origin", "*")
+	}
+
 	payload, err := getTrackingPayload(r)
 	if err != nil {
 		respondError(w, r, http.StatusBadRequest, "Invalid JSON payload")
@@ -120,7 +138,11 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 		"SELECT COALESCE(proxy_mode, 'none') FROM website WHERE website_id = $1",
 		websiteID,
 	).Scan(&proxyMode); err != nil {
-		respondError(w, r, http.StatusNotFound, "Website not found")
+		render.Status(r, http.StatusNotFound)
+		render.JSON(w, r, map[string]any{
+			"error": "Website not found",
+			"hint":  "Check data-website-id against: kaunta website show <domain>",
+		})
 		return
 	}
 
@@ -170,10 +192,11 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	if origin != "" && origin != "null" {
-		w.Header().Set("Access-Control-Allow-Origin", origin)
-	} else {
-		w.Header().Set("Access-Control-Allow-Origin", "*")
+	// The site owner's own browser, excluded at /exclude
+	if IsExcluded(r) {
+		render.Status(r, http.StatusAccepted)
+		render.JSON(w, r, map[string]any{"dropped": "excluded"})
+		return
 	}
 
 	ip := clientIPFromRequest(r, proxyMode)
@@ -212,7 +235,9 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 	}
 
 	browser, osName, device := parseUserAgent(userAgent)
+	browserVersion := parseBrowserVersion(userAgent, browser)
 	countryStr, cityStr, regionStr := geoIPLookup(ip)
+	rememberCityCentroid(ip, countryStr, cityStr)
 	country := &countryStr
 	region := &regionStr
 	city := &cityStr
@@ -228,14 +253,18 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 	var entryPath *string
 	if payload.Payload.URL != nil {
 		if u, err := url.Parse(*payload.Payload.URL); err == nil {
-			path := u.Path
+			path := pagePath(u, payload.Payload.HashRouting)
 			entryPath = &path
 		}
 	}
 
 	distinctID := payload.Payload.ID
-	if err := upsertSession(sessionID, websiteID, browser, osName, device,
-		payload.Payload.Screen, payload.Payload.Language, country, region, city, distinctID, entryPath); err != nil {
+	var consentedAt *time.Time
+	if payload.Payload.Consent != nil && *payload.Payload.Consent {
+		consentedAt = &createdAt
+	}
+	if err := upsertSession(sessionID, websiteID, browser, browserVersion, osName, device,
+		payload.Payload.Screen, payload.Payload.Language, country, region, city, distinctID, entryPath, consentedAt); err != nil {
 		logging.L().Error("session creation error",
 			slog.String("website_id", websiteID.String()),
 			slog.String("session_id", sessionID.String()),
@@ -248,13 +277,6 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 		visitSalt := hashDate(createdAt, "hour")
 		visitID := generateUUID(sessionID.String(), visitSalt)
 
-		eventID, err := saveEvent(websiteID, sessionID, visitID, createdAt, payload.Payload,
-			browser, osName, device, country, region, city)
-		if err != nil {
-			respondError(w, r, http.StatusInternalServerError, "Failed to save event: " + err.Error())
-			return
-		}
-
 		ctx, cancel := context.WithTimeout(context.Background(), 2*time.Second)
 		defer cancel()
 
@@ -266,11 +288,14 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 		var urlPath *string
 		if payload.Payload.URL != nil {
 			if u, err := url.Parse(*payload.Payload.URL); err == nil {
-				path := u.Path
+				path := pagePath(u, payload.Payload.HashRouting)
 				urlPath = &path
 			}
 		}
 
+		// Goals are matched before the event is written so it can be tagged in
+		// the same insert; a buffered event can't be updated afterwards
+		eventID := uuid.New()
 		goalID := checkAndRecordGoalCompletion(
 			ctx,
 			websiteID,
@@ -281,11 +306,16 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 			payload.Payload.Name,
 		)
 
-		if goalID != nil {
-			_, _ = database.DB.ExecContext(ctx,
-				`UPDATE website_event SET goal_id = $1 WHERE event_id = $2`,
-				goalID, eventID,
-			)
+		if err := saveEvent(context.Background(), eventID, websiteID, sessionID, visitID, createdAt, payload.Payload, goalID); err != nil {
+			if errors.Is(err, ErrEventBufferFull) {
+				respondIngestOverloaded(w, r)
+				return
+			}
+			respondError(w, r, http.StatusInternalServerError, "Failed to save event: " + err.Error())
+			return
+		}
+		if eventType == 2 {
+			checkEventSchema(ctx, websiteID, *payload.Payload.Name, payload.Payload.Props, payload.Payload.Data, createdAt)
 		}
 
 		eventPath := ""
@@ -297,18 +327,23 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 			eventTitle = *payload.Payload.Title
 		}
 
-		realtime.NotifyEvent(
-			context.Background(),
-			realtime.NewEventPayload(
-				payload.Type,
-				websiteID,
-				sessionID,
-				visitID,
-				eventPath,
-				eventTitle,
-				createdAt,
-			),
+		event := realtime.NewEventPayload(
+			payload.Type,
+			websiteID,
+			sessionID,
+			visitID,
+			eventPath,
+			eventTitle,
+			createdAt,
 		)
+		event.Country = countryStr
+		if payload.Payload.Name != nil {
+			event.Name = strings.TrimSpace(*payload.Payload.Name)
+		}
+		if payload.Payload.Referrer != nil {
+			event.Referrer = *payload.Payload.Referrer
+		}
+		realtime.NotifyEvent(context.Background(), event)
 
 		render.Status(r, http.StatusAccepted)
 		render.JSON(w, r, map[string]any{
@@ -318,10 +353,29 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	if payload.Type == "identify" && payload.Payload.Data != nil {
+	if payload.Type == "identify" && (payload.Payload.ID != nil || payload.Payload.Data != nil) {
+		render.Status(r, http.StatusAccepted)
+		render.JSON(w, r, map[string]any{
+			"sessionId": sessionID.String(),
+		})
+		return
+	}
+
+	// Engagement pings carry engaged time not yet reported for this visit
+	if payload.Type == "engagement" && payload.Payload.EngagementTime != nil {
+		visitSalt := hashDate(createdAt, "hour")
+		visitID := generateUUID(sessionID.String(), visitSalt)
+
+		if err := recordVisitEngagement(visitID, websiteID, sessionID, *payload.Payload.EngagementTime); err != nil {
+			logging.L().Error("failed to record engagement", slog.String("visit_id", visitID.String()), slog.Any("error", err))
+			respondError(w, r, http.StatusInternalServerError, "Failed to record engagement")
+			return
+		}
+
 		render.Status(r, http.StatusAccepted)
 		render.JSON(w, r, map[string]any{
 			"sessionId": sessionID.String(),
+			"visitId":   visitID.String(),
 		})
 		return
 	}
@@ -329,30 +383,55 @@ func HandleTracking(w http.ResponseWriter, r *http.Request) {
 	respondError(w, r, http.StatusBadRequest, "Invalid type")
 }
 
+// maxEngagementPingMs caps a single engagement ping so one stuck tab
+// cannot inflate a visit's duration
+const maxEngagementPingMs = 30 * 60 * 1000
+
+// recordVisitEngagement adds engaged milliseconds to a visit's running total
+func recordVisitEngagement(visitID, websiteID, sessionID uuid.UUID, engagedMs int) error {
+	if engagedMs <= 0 {
+		return nil
+	}
+	_, err := database.DB.Exec(`
+		INSERT INTO visit_engagement (visit_id, website_id, session_id, engaged_ms)
+		VALUES ($1, $2, $3, $4)
+		ON CONFLICT (visit_id) DO UPDATE
+		SET engaged_ms = visit_engagement.engaged_ms + EXCLUDED.engaged_ms,
+		    updated_at = NOW()
+	`, visitID, websiteID, sessionID, min(engagedMs, maxEngagementPingMs))
+	return err
+}
+
 // upsertSession creates or updates a session
 // On INSERT: sets entry_page and exit_page to the first page visited
-// On UPDATE: only updates exit_page (entry_page remains the original landing page)
+// On UPDATE: updates exit_page (entry_page remains the original landing page),
+// and distinct_id when the hit carries one, so identify() applies to a session
+// that started anonymous. consented_at keeps the first opt-in.
 func upsertSession(
 	sessionID, websiteID uuid.UUID,
-	browser, os, device, screen, language, country, region, city, distinctID, urlPath *string,
+	browser, browserVersion, os, device, screen, language, country, region, city, distinctID, urlPath *string,
+	consentedAt *time.Time,
 ) error {
 	query := `
 		INSERT INTO session (
-			session_id, website_id, browser, os, device, screen, language,
-			country, region, city, created_at, distinct_id, entry_page, exit_page
-		) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), $11, $12, $12)
-		ON CONFLICT (session_id) DO UPDATE SET exit_page = EXCLUDED.entry_page
+			session_id, website_id, browser, browser_version, os, device, screen, language,
+			country, region, city, created_at, distinct_id, entry_page, exit_page, consented_at
+		) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), $12, $13, $13, $14)
+		ON CONFLICT (session_id) DO UPDATE SET
+			exit_page = COALESCE(EXCLUDED.entry_page, session.exit_page),
+			distinct_id = COALESCE(EXCLUDED.distinct_id, session.distinct_id),
+			consented_at = COALESCE(session.consented_at, EXCLUDED.consented_at)
 	`
-	_, err := database.DB.Exec(query, sessionID, websiteID, browser, os, device,
-		screen, language, country, region, city, distinctID, urlPath)
+	_, err := database.DB.Exec(query, sessionID, websiteID, browser, browserVersion, os, device,
+		screen, language, country, region, city, distinctID, urlPath, consentedAt)
 	return err
 }
 
-// saveEvent saves a pageview or custom event
-func saveEvent(websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
-	payload PayloadData, browser, os, device, country, region, city *string) (uuid.UUID, error) {
+// saveEvent saves a pageview or custom event, through the event buffer when
+// one is set
+func saveEvent(ctx context.Context, eventID, websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
+	payload PayloadData, goalID *uuid.UUID) error {
 
-	eventID := uuid.New()
 	eventType := 1
 	if payload.Name != nil && strings.TrimSpace(*payload.Name) != "" {
 		eventType = 2
@@ -362,7 +441,7 @@ func saveEvent(websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
 	var urlPath, urlQuery, hostname, referrerPath, referrerQuery, referrerDomain *string
 	if payload.URL != nil {
 		if u, err := url.Parse(*payload.URL); err == nil {
-			path := u.Path
+			path := pagePath(u, payload.HashRouting)
 			urlPath = &path
 			query := u.RawQuery
 			if query != "" {
@@ -380,7 +459,7 @@ func saveEvent(websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
 	// Parse referrer
 	if payload.Referrer != nil {
 		if u, err := url.Parse(*payload.Referrer); err == nil {
-			path := u.Path
+			path := pagePath(u, payload.HashRouting)
 			referrerPath = &path
 			query := u.RawQuery
 			if query != "" {
@@ -427,25 +506,6 @@ func saveEvent(websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
 		}
 	}
 
-	// Enhanced schema: includes Phase 2 fields + UTM tracking
-	query := `
-		INSERT INTO website_event (
-			event_id, website_id, session_id, visit_id, created_at,
-			page_title, hostname, url_path, url_query,
-			referrer_path, referrer_query, referrer_domain,
-			event_name, tag, event_type,
-			scroll_depth, engagement_time, props,
-			utm_source, utm_medium, utm_campaign, utm_term, utm_content
-		) VALUES (
-			$1, $2, $3, $4, $5,
-			$6, $7, $8, $9,
-			$10, $11, $12,
-			$13, $14, $15,
-			$16, $17, $18,
-			$19, $20, $21, $22, $23
-		)
-	`
-
 	logging.L().Debug("inserting event",
 		slog.Int("event_type", eventType),
 		slog.String("event_id", eventID.String()),
@@ -454,21 +514,38 @@ func saveEvent(websiteID, sessionID, visitID uuid.UUID, createdAt time.Time,
 		slog.String("visit_id", visitID.String()),
 	)
 
-	_, err := database.DB.Exec(query,
-		eventID, websiteID, sessionID, visitID, createdAt,
-		payload.Title, hostname, urlPath, urlQuery,
-		referrerPath, referrerQuery, referrerDomain,
-		payload.Name, payload.Tag, eventType,
-		scrollDepth, engagementTime, propsJSON,
-		payload.UTMSource, payload.UTMMedium, payload.UTMCampaign, payload.UTMTerm, payload.UTMContent,
-	)
-
+	err := writeEvent(ctx, eventRow{
+		EventID:        eventID,
+		WebsiteID:      websiteID,
+		SessionID:      sessionID,
+		VisitID:        visitID,
+		CreatedAt:      createdAt,
+		PageTitle:      payload.Title,
+		Hostname:       hostname,
+		URLPath:        urlPath,
+		URLQuery:       urlQuery,
+		ReferrerPath:   referrerPath,
+		ReferrerQuery:  referrerQuery,
+		ReferrerDomain: referrerDomain,
+		EventName:      payload.Name,
+		Tag:            payload.Tag,
+		EventType:      eventType,
+		ScrollDepth:    scrollDepth,
+		EngagementTime: engagementTime,
+		Props:          propsJSON,
+		UTMSource:      payload.UTMSource,
+		UTMMedium:      payload.UTMMedium,
+		UTMCampaign:    payload.UTMCampaign,
+		UTMTerm:        payload.UTMTerm,
+		UTMContent:     payload.UTMContent,
+		GoalID:         goalID,
+	})
 	if err != nil {
 		logging.L().Error("failed to insert event", slog.Any("error", err))
-		return uuid.Nil, err
+		return err
 	}
 
-	return eventID, nil
+	return nil
 }
 
 // checkAndRecordGoalCompletion matches the event against active goals and records completions
@@ -500,6 +577,7 @@ func checkAndRecordGoalCompletion(
 
 	// Match goals based on event type
 	var matchedGoalID *uuid.UUID
+	var matchedGoal cachedGoal
 
 	for _, goal := range goals {
 		matched := false
@@ -520,6 +598,7 @@ func checkAndRecordGoalCompletion(
 
 		if matched {
 			matchedGoalID = &goal.ID
+			matchedGoal = goal
 			break // First match wins (goals should be mutually exclusive)
 		}
 	}
@@ -556,7 +635,7 @@ func checkAndRecordGoalCompletion(
 
 	// Record new goal completion (INSERT into goal_completions)
 	completionID := uuid.New()
-	_, err = database.DB.ExecContext(ctx,
+	result, err := database.DB.ExecContext(ctx,
 		`INSERT INTO goal_completions
             (id, goal_id, session_id, event_id, website_id, completed_at)
          VALUES ($1, $2, $3, $4, $5, NOW())
@@ -574,11 +653,43 @@ func checkAndRecordGoalCompletion(
 			slog.String("goal_id", matchedGoalID.String()),
 			slog.String("session_id", sessionID.String()),
 			slog.String("completion_id", completionID.String()))
+
+		// Only the insert that won the race notifies webhooks
+		if inserted, _ := result.RowsAffected(); inserted > 0 {
+			queueGoalCompletedWebhooks(ctx, websiteID, sessionID, completionID, matchedGoal)
+		}
 	}
 
 	return matchedGoalID
 }
 
+// queueGoalCompletedWebhooks queues a goal.completed delivery for the
+// website's subscribed webhooks
+func queueGoalCompletedWebhooks(ctx context.Context, websiteID, sessionID, completionID uuid.UUID, goal cachedGoal) {
+	payload := map[string]string{
+		"goal_id":       goal.ID.String(),
+		"completion_id": completionID.String(),
+		"session_id":    sessionID.String(),
+		"type":          goal.Type,
+		"target":        goal.TargetValue,
+		"completed_at":  time.Now().UTC().Format(time.RFC3339),
+	}
+	if _, err := models.EnqueueWebhookEvent(ctx, database.DB, websiteID, models.WebhookGoalCompleted, payload); err != nil {
+		logging.L().Warn("failed to queue goal webhooks",
+			slog.String("goal_id", goal.ID.String()),
+			slog.Any("error", err))
+	}
+}
+
+// pagePath is the path a page is reported under. Hash-routed sites
+// (/#/guide) keep the fragment, since it names the page.
+func pagePath(u *url.URL, hashRouting bool) string {
+	if hashRouting && u.Fragment != "" {
+		return u.Path + "#" + u.Fragment
+	}
+	return u.Path
+}
+
 // generateUUID creates a deterministic UUID from components
 func generateUUID(parts ...string) uuid.UUID {
 	combined := strings.Join(parts, "|")
@@ -681,6 +792,38 @@ func parseUserAgent(ua string) (browser, os, device *string) {
 	return
 }
 
+// browserVersionPatterns locate the version token for each browser parseUserAgent detects
+var browserVersionPatterns = map[string]*regexp.Regexp{
+	"Edge":    regexp.MustCompile(`edg(?:e|a|ios)?/([\d.]+)`),
+	"Chrome":  regexp.MustCompile(`(?:chrome|crios)/([\d.]+)`),
+	"Firefox": regexp.MustCompile(`(?:firefox|fxios)/([\d.]+)`),
+	"Safari":  regexp.MustCompile(`version/([\d.]+)`),
+}
+
+// parseBrowserVersion extracts the major.minor version of the detected browser
+func parseBrowserVersion(ua string, browser *string) *string {
+	if browser == nil {
+		return nil
+	}
+	pattern, ok := browserVersionPatterns[*browser]
+	if !ok {
+		return nil
+	}
+	match := pattern.FindStringSubmatch(strings.ToLower(ua))
+	if match == nil {
+		return nil
+	}
+	parts := strings.Split(strings.Trim(match[1], "."), ".")
+	if len(parts) > 2 {
+		parts = parts[:2]
+	}
+	version := strings.Join(parts, ".")
+	if version == "" || len(version) > 20 {
+		return nil
+	}
+	return &version
+}
+
 // geoIPLookup performs country/city/region lookup for an IP address
 func geoIPLookup(ip string) (country, city, region string) {
 	country, city, region = geoip.LookupIP(ip)
@@ -1,8 +1,19 @@
 package handlers
 
 import (
+	"net/http"
+	"net/http/httptest"
+	"net/url"
 	"strings"
 	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
 )
 
 // TestGetClientIPLogic tests the IP extraction logic without Fiber dependency
@@ -128,3 +139,140 @@ func TestProxyModeValues(t *testing.T) {
 		})
 	}
 }
+
+// TestRecordVisitEngagement tests that pings accumulate per visit and are capped
+func TestRecordVisitEngagement(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	visitID, websiteID, sessionID := uuid.New(), uuid.New(), uuid.New()
+
+	mock.ExpectExec("INSERT INTO visit_engagement").
+		WithArgs(visitID, websiteID, sessionID, 12000).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	require.NoError(t, recordVisitEngagement(visitID, websiteID, sessionID, 12000))
+
+	mock.ExpectExec("INSERT INTO visit_engagement").
+		WithArgs(visitID, websiteID, sessionID, maxEngagementPingMs).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	require.NoError(t, recordVisitEngagement(visitID, websiteID, sessionID, 10*maxEngagementPingMs))
+
+	// Empty pings never touch the database
+	require.NoError(t, recordVisitEngagement(visitID, websiteID, sessionID, 0))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+// TestUpsertSessionKeepsDistinctID tests that a later hit can identify a
+// session and that anonymous hits don't clear its ID
+func TestUpsertSessionKeepsDistinctID(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	sessionID, websiteID := uuid.New(), uuid.New()
+	userID := "user-42"
+
+	mock.ExpectExec(`ON CONFLICT \(session_id\) DO UPDATE SET\s+exit_page = COALESCE\(EXCLUDED.entry_page, session.exit_page\),\s+distinct_id = COALESCE\(EXCLUDED.distinct_id, session.distinct_id\)`).
+		WithArgs(sessionID, websiteID, nil, nil, nil, nil, nil, nil, nil, nil, nil, userID, nil, nil).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	require.NoError(t, upsertSession(sessionID, websiteID, nil, nil, nil, nil, nil, nil, nil, nil, nil, &userID, nil, nil))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+// TestUpsertSessionRecordsConsent tests that the first opt-in is kept
+func TestUpsertSessionRecordsConsent(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	sessionID, websiteID := uuid.New(), uuid.New()
+	consentedAt := time.Date(2026, 3, 1, 10, 0, 0, 0, time.UTC)
+
+	mock.ExpectExec(`consented_at = COALESCE\(session.consented_at, EXCLUDED.consented_at\)`).
+		WithArgs(sessionID, websiteID, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, consentedAt).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	require.NoError(t, upsertSession(sessionID, websiteID, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, &consentedAt))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestParseBrowserVersion(t *testing.T) {
+	tests := []struct {
+		name     string
+		ua       string
+		expected string
+	}{
+		{"chrome", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.6478.127 Safari/537.36", "126.0"},
+		{"edge", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36 Edg/126.0.2592.87", "126.0"},
+		{"firefox", "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0", "128.0"},
+		{"safari", "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_5) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Safari/605.1.15", "17.5"},
+		{"unknown browser", "curl/8.6.0", ""},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			browser, _, _ := parseUserAgent(tt.ua)
+			version := parseBrowserVersion(tt.ua, browser)
+			if tt.expected == "" {
+				assert.Nil(t, version)
+				return
+			}
+			require.NotNil(t, version)
+			assert.Equal(t, tt.expected, *version)
+		})
+	}
+}
+
+func TestPagePath(t *testing.T) {
+	tests := []struct {
+		name        string
+		url         string
+		hashRouting bool
+		expected    string
+	}{
+		{"path only", "https://example.com/docs?ref=1#install", false, "/docs"},
+		{"hash route", "https://example.com/#/guide/setup?tab=2", true, "/#/guide/setup?tab=2"},
+		{"hash routing without hash", "https://example.com/about", true, "/about"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			u, err := url.Parse(tt.url)
+			require.NoError(t, err)
+			assert.Equal(t, tt.expected, pagePath(u, tt.hashRouting))
+		})
+	}
+}
+
+func TestGetTrackingPayloadReadsAnyContentType(t *testing.T) {
+	body := `{"type":"engagement","payload":{"website":"550e8400-e29b-41d4-a716-446655440000","engagement_time":4200}}`
+
+	// application/json from fetch, text/plain from exit hits and sendBeacon
+	for _, contentType := range []string{"application/json", "text/plain", "text/plain;charset=UTF-8"} {
+		t.Run(contentType, func(t *testing.T) {
+			req := httptest.NewRequest(http.MethodPost, "/api/send", strings.NewReader(body))
+			req.Header.Set("Content-Type", contentType)
+
+			payload, err := getTrackingPayload(req)
+			require.NoError(t, err)
+			assert.Equal(t, "engagement", payload.Type)
+			require.NotNil(t, payload.Payload.EngagementTime)
+			assert.Equal(t, 4200, *payload.Payload.EngagementTime)
+		})
+	}
+}
@@ -0,0 +1,138 @@
+package handlers
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"net/url"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+// maxTrendKeys caps how many rows a single trends request may ask for
+const maxTrendKeys = 100
+
+// breakdownTrendKeys maps breakdown tabs that show per-row sparklines to the
+// SQL expression producing the same row key as get_breakdown()/get_top_pages()
+var breakdownTrendKeys = map[string]string{
+	"pages":     "e.url_path",
+	"referrers": "COALESCE(e.referrer_domain || COALESCE(e.referrer_path, ''), 'Direct / None')",
+	"countries": "COALESCE(s.country, 'Unknown')",
+}
+
+// HandleBreakdownTrends fills the sparkline cells of the visible breakdown
+// rows with one daily series per key, in a single query
+// GET /api/dashboard/breakdown/trends?website=...&tab=pages&days=7&key=/a&key=/b
+func HandleBreakdownTrends(w http.ResponseWriter, r *http.Request) {
+	query := r.URL.Query()
+	tab := query.Get("tab")
+	keys := query["key"]
+	days := 7
+	if queryInt(r, "days", 7) == 30 {
+		days = 30
+	}
+
+	websiteID, parseErr := parseRequiredWebsiteID(query.Get("website"))
+	keyExpr, ok := breakdownTrendKeys[tab]
+	if parseErr == "" && !ok {
+		parseErr = "Invalid breakdown type: " + tab
+	}
+	if len(keys) > maxTrendKeys {
+		keys = keys[:maxTrendKeys]
+	}
+
+	var series [][]int64
+	var queryErr error
+	if parseErr == "" && len(keys) > 0 {
+		series, queryErr = loadBreakdownTrends(r.Context(), websiteID, keyExpr, keys, days)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if parseErr != "" {
+			log.Printf("HandleBreakdownTrends: %s", parseErr)
+			return
+		}
+		if queryErr != nil {
+			log.Printf("HandleBreakdownTrends: query error: %v", queryErr)
+			return
+		}
+
+		for i, values := range series {
+			_ = sse.PatchElementsWithMode(fmt.Sprintf("#breakdown-trend-%d", i), buildSparklineSVG(values, 80, 20), "inner")
+		}
+	})
+}
+
+// loadBreakdownTrends returns one daily pageview series per key, in key order
+func loadBreakdownTrends(ctx context.Context, websiteID uuid.UUID, keyExpr string, keys []string, days int) ([][]int64, error) {
+	query := fmt.Sprintf(`
+		WITH matched AS (
+			SELECT %[1]s AS key, e.created_at
+			FROM website_event e
+			JOIN session s ON e.session_id = s.session_id
+			WHERE e.website_id = $1
+			  AND e.event_type = 1
+			  AND e.created_at >= CURRENT_DATE - ($2::INT - 1)
+			  AND %[1]s = ANY($3::TEXT[])
+		)
+		SELECT k.idx, ARRAY(
+			SELECT COUNT(m.created_at)
+			FROM generate_series(CURRENT_DATE - ($2::INT - 1), CURRENT_DATE, INTERVAL '1 day') AS d(day)
+			LEFT JOIN matched m
+			  ON m.key = k.key
+			 AND m.created_at >= d.day
+			 AND m.created_at < d.day + INTERVAL '1 day'
+			GROUP BY d.day
+			ORDER BY d.day
+		)
+		FROM unnest($3::TEXT[]) WITH ORDINALITY AS k(key, idx)
+		ORDER BY k.idx
+	`, keyExpr)
+
+	rows, err := database.QueryStats(ctx, query, websiteID, days, pq.Array(keys))
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	series := make([][]int64, len(keys))
+	for rows.Next() {
+		var idx int
+		var values []int64
+		if err := rows.Scan(&idx, pq.Array(&values)); err != nil {
+			return nil, err
+		}
+		if idx >= 1 && idx <= len(series) {
+			series[idx-1] = values
+		}
+	}
+	return series, rows.Err()
+}
+
+// buildBreakdownTrendTableHTML renders the breakdown table and, for tabs with
+// trends, a loader that requests sparklines for exactly the rows shown.
+// The loader re-runs when $trendDays changes.
+func buildBreakdownTrendTableHTML(websiteID uuid.UUID, breakdownType string, items []BreakdownItem) string {
+	if _, ok := breakdownTrendKeys[breakdownType]; !ok || len(items) == 0 {
+		return buildBreakdownTableHTML(breakdownType, items)
+	}
+
+	params := url.Values{}
+	params.Set("website", websiteID.String())
+	params.Set("tab", breakdownType)
+	for _, item := range items {
+		key := item.Name
+		if breakdownType == "countries" {
+			key = item.Code
+		}
+		params.Add("key", key)
+	}
+
+	loader := fmt.Sprintf(`<div hidden aria-hidden="true" data-effect="@get('/api/dashboard/breakdown/trends?%s&days=' + $trendDays)"></div>`,
+		escapeHTML(params.Encode()))
+	return renderBreakdownTable(breakdownType, items, true) + loader
+}
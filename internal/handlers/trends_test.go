@@ -0,0 +1,50 @@
+package handlers
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+)
+
+func TestBuildBreakdownTrendTableHTML(t *testing.T) {
+	websiteID := uuid.MustParse("11111111-1111-1111-1111-111111111111")
+
+	pages := buildBreakdownTrendTableHTML(websiteID, "pages", []BreakdownItem{
+		{Name: "/", Count: 10},
+		{Name: "/blog?a=1", Count: 4},
+	})
+	assert.Contains(t, pages, `id="breakdown-trend-0"`)
+	assert.Contains(t, pages, `id="breakdown-trend-1"`)
+	assert.Contains(t, pages, "key=%2F&amp;key=%2Fblog%3Fa%3D1&amp;tab=pages")
+	assert.Contains(t, pages, "$trendDays")
+
+	countries := buildBreakdownTrendTableHTML(websiteID, "countries", []BreakdownItem{
+		{Name: "Germany", Code: "DE", Count: 3},
+	})
+	assert.Contains(t, countries, "key=DE&amp;")
+
+	browsers := buildBreakdownTrendTableHTML(websiteID, "browsers", []BreakdownItem{{Name: "Firefox", Count: 1}})
+	assert.NotContains(t, browsers, "breakdown-trend")
+	assert.Equal(t, buildBreakdownTableHTML("browsers", []BreakdownItem{{Name: "Firefox", Count: 1}}), browsers)
+
+	empty := buildBreakdownTrendTableHTML(websiteID, "pages", nil)
+	assert.False(t, strings.Contains(empty, "data-effect"))
+}
+
+func TestBreakdownRowFilters(t *testing.T) {
+	browsers := buildBreakdownTableHTML("browsers", []BreakdownItem{{Name: "Chrome", Count: 5}})
+	assert.Contains(t, browsers, `class="breakdown-row-filter"`)
+	assert.Contains(t, browsers, `data-on:click="$filters.browser = &#39;Chrome&#39;"`)
+
+	devices := buildBreakdownTableHTML("devices", []BreakdownItem{{Name: "o'mobile", Count: 1}})
+	assert.Contains(t, devices, `$filters.device = &#39;o\&#39;mobile&#39;`)
+
+	versions := buildBreakdownTableHTML("browser-versions", []BreakdownItem{{Name: "Firefox 128", Count: 2}})
+	assert.NotContains(t, versions, "breakdown-row-filter")
+	assert.Contains(t, versions, "<th>Browser Versions</th>")
+
+	screens := buildBreakdownTableHTML("screens", []BreakdownItem{{Name: "Tablet", Count: 2}})
+	assert.Contains(t, screens, `<span class="breakdown-icon">`+deviceIcons["tablet"])
+}
@@ -0,0 +1,289 @@
+package handlers
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"log"
+	"net/http"
+	"time"
+
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/qrcode"
+	"github.com/seuros/kaunta/internal/totp"
+)
+
+const (
+	// totpIssuer labels the account in authenticator apps
+	totpIssuer = "Kaunta"
+	// twoFactorQRSize is the rendered size of the enrollment QR code, in pixels
+	twoFactorQRSize = 200
+	// loginExpiredMessage is shown when a login challenge has run out
+	loginExpiredMessage = "Login expired, please sign in again"
+)
+
+var (
+	createLoginChallengeFunc = func(ctx context.Context, userID uuid.UUID) (string, error) {
+		return models.CreateLoginChallenge(ctx, database.DB, userID)
+	}
+	claimLoginChallengeFunc = func(ctx context.Context, token string) (uuid.UUID, error) {
+		return models.ClaimLoginChallenge(ctx, database.DB, token)
+	}
+	deleteLoginChallengeFunc = func(ctx context.Context, token string) error {
+		return models.DeleteLoginChallenge(ctx, database.DB, token)
+	}
+	verifyTwoFactorFunc = func(ctx context.Context, userID uuid.UUID, code string, now time.Time) error {
+		return models.VerifyTwoFactor(ctx, database.DB, userID, code, now)
+	}
+)
+
+// TwoFactorLoginRequest is the second step of a login for accounts with 2FA
+type TwoFactorLoginRequest struct {
+	Challenge string `json:"challenge"`
+	Code      string `json:"code"` // Authenticator or recovery code
+}
+
+// HandleLoginTwoFactor exchanges the challenge from HandleLogin and a code
+// for a session
+// POST /api/auth/login/2fa
+func HandleLoginTwoFactor(w http.ResponseWriter, r *http.Request) {
+	defer func() { _ = r.Body.Close() }()
+	var req TwoFactorLoginRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if req.Challenge == "" || req.Code == "" {
+		respondError(w, r, http.StatusBadRequest, "Challenge and code are required")
+		return
+	}
+
+	user, err := completeTwoFactorLogin(w, r, req.Challenge, req.Code)
+	if err != nil {
+		status, message := twoFactorLoginError(err)
+		respondError(w, r, status, message)
+		return
+	}
+	render.JSON(w, r, loginResponse(user))
+}
+
+// completeTwoFactorLogin checks the code against the challenge's user and
+// starts their session. Every call counts as one attempt on the challenge.
+func completeTwoFactorLogin(w http.ResponseWriter, r *http.Request, challenge, code string) (*userRecord, error) {
+	ctx := r.Context()
+	userID, err := claimLoginChallengeFunc(ctx, challenge)
+	if err != nil {
+		return nil, err
+	}
+	if err := verifyTwoFactorFunc(ctx, userID, code, time.Now()); err != nil {
+		return nil, err
+	}
+	user, err := fetchUserByIDFunc(userID)
+	if err != nil {
+		return nil, err
+	}
+
+	if err := deleteLoginChallengeFunc(ctx, challenge); err != nil {
+		log.Printf("completeTwoFactorLogin: delete challenge: %v", err)
+	}
+	if err := startSession(w, r, userID); err != nil {
+		return nil, fmt.Errorf("start session: %w", err)
+	}
+	return user, nil
+}
+
+// twoFactorLoginError maps a completeTwoFactorLogin error to a response
+func twoFactorLoginError(err error) (int, string) {
+	switch {
+	case errors.Is(err, models.ErrLoginChallengeNotFound):
+		return http.StatusUnauthorized, loginExpiredMessage
+	case errors.Is(err, models.ErrInvalidTwoFactorCode):
+		return http.StatusUnauthorized, "Invalid two-factor code"
+	default:
+		log.Printf("two-factor login: %v", err)
+		return http.StatusInternalServerError, "Authentication error"
+	}
+}
+
+// HandleTwoFactorInit loads the signed-in user's 2FA state
+// GET /api/auth/2fa
+func HandleTwoFactorInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	status, err := models.GetTwoFactorStatus(r.Context(), database.DB, user.UserID)
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("HandleTwoFactorInit: %v", err)
+			_ = sse.PatchSignals(map[string]any{
+				"twoFactorLoading": false,
+				"twoFactorError":   "Failed to load two-factor settings",
+			})
+			return
+		}
+		_ = sse.PatchSignals(twoFactorSignals(status))
+	})
+}
+
+// HandleTwoFactorSetup issues a new secret and shows it as a QR code. The
+// secret only protects logins once HandleTwoFactorEnable confirms it.
+// POST /api/auth/2fa/setup
+func HandleTwoFactorSetup(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	secret, err := models.BeginTwoFactorSetup(r.Context(), database.DB, user.UserID)
+	var qr string
+	if err == nil {
+		// The secret can still be typed in if the URI doesn't fit a QR code
+		if code, encodeErr := qrcode.Encode([]byte(totp.URI(totpIssuer, user.Username, secret))); encodeErr == nil {
+			qr = string(code.SVG(twoFactorQRSize))
+		} else {
+			log.Printf("HandleTwoFactorSetup: %v", encodeErr)
+		}
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		switch {
+		case errors.Is(err, models.ErrTwoFactorAlreadyEnabled):
+			_ = sse.PatchSignals(map[string]any{"twoFactorError": "Two-factor authentication is already enabled"})
+		case err != nil:
+			log.Printf("HandleTwoFactorSetup: %v", err)
+			_ = sse.PatchSignals(map[string]any{"twoFactorError": "Failed to start two-factor setup"})
+		default:
+			_ = sse.PatchElementsWithMode("#two-factor-qr", qr, "inner")
+			_ = sse.PatchSignals(map[string]any{"twoFactorSecret": secret, "twoFactorError": false})
+		}
+	})
+}
+
+// HandleTwoFactorEnable confirms setup with a code and returns the recovery
+// codes, which are shown once
+// POST /api/auth/2fa/enable (form: code)
+func HandleTwoFactorEnable(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	codes, err := models.EnableTwoFactor(r.Context(), database.DB, user.UserID, r.FormValue("code"), time.Now())
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			_ = sse.PatchSignals(map[string]any{"twoFactorError": twoFactorFormError("HandleTwoFactorEnable", err)})
+			return
+		}
+		_ = sse.PatchElementsWithMode("#two-factor-qr", "", "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"twoFactorEnabled":  true,
+			"twoFactorSecret":   "",
+			"twoFactorError":    false,
+			"recoveryCodes":     codes,
+			"recoveryCodesLeft": len(codes),
+		})
+	})
+}
+
+// HandleTwoFactorDisable turns 2FA off after checking a current code. It is
+// refused while the user or the instance requires 2FA.
+// POST /api/auth/2fa/disable (form: code)
+func HandleTwoFactorDisable(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	ctx := r.Context()
+	status, err := models.GetTwoFactorStatus(ctx, database.DB, user.UserID)
+	required := err == nil && (status.Required || middleware.TwoFactorPolicyRequired())
+	if err == nil && !required {
+		err = models.VerifyTwoFactor(ctx, database.DB, user.UserID, r.FormValue("code"), time.Now())
+	}
+	if err == nil && !required {
+		err = models.DisableTwoFactor(ctx, database.DB, user.UserID)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		switch {
+		case required:
+			_ = sse.PatchSignals(map[string]any{"twoFactorError": "Two-factor authentication is required for your account"})
+		case err != nil:
+			_ = sse.PatchSignals(map[string]any{"twoFactorError": twoFactorFormError("HandleTwoFactorDisable", err)})
+		default:
+			_ = sse.PatchSignals(map[string]any{
+				"twoFactorEnabled":  false,
+				"twoFactorError":    false,
+				"recoveryCodes":     []string{},
+				"recoveryCodesLeft": 0,
+			})
+		}
+	})
+}
+
+// HandleTwoFactorRecoveryCodes replaces the recovery codes after checking
+// a current code
+// POST /api/auth/2fa/recovery-codes (form: code)
+func HandleTwoFactorRecoveryCodes(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	ctx := r.Context()
+	var codes []string
+	err := models.VerifyTwoFactor(ctx, database.DB, user.UserID, r.FormValue("code"), time.Now())
+	if err == nil {
+		codes, err = models.RegenerateRecoveryCodes(ctx, database.DB, user.UserID)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			_ = sse.PatchSignals(map[string]any{"twoFactorError": twoFactorFormError("HandleTwoFactorRecoveryCodes", err)})
+			return
+		}
+		_ = sse.PatchSignals(map[string]any{
+			"twoFactorError":    false,
+			"recoveryCodes":     codes,
+			"recoveryCodesLeft": len(codes),
+		})
+	})
+}
+
+func twoFactorSignals(status *models.TwoFactorStatus) map[string]any {
+	return map[string]any{
+		"twoFactorLoading":  false,
+		"twoFactorError":    false,
+		"twoFactorEnabled":  status.Enabled(),
+		"twoFactorRequired": status.Required || middleware.TwoFactorPolicyRequired(),
+		"recoveryCodesLeft": status.RecoveryCodesLeft,
+	}
+}
+
+// twoFactorFormError is the message shown on the security page for err
+func twoFactorFormError(handler string, err error) string {
+	switch {
+	case errors.Is(err, models.ErrInvalidTwoFactorCode):
+		return "That code didn't match; check your device's clock and try again"
+	case errors.Is(err, models.ErrTwoFactorSetupNotStarted):
+		return "Start setup first"
+	case errors.Is(err, models.ErrTwoFactorAlreadyEnabled):
+		return "Two-factor authentication is already enabled"
+	case errors.Is(err, models.ErrTwoFactorNotEnabled):
+		return "Two-factor authentication is not enabled"
+	default:
+		log.Printf("%s: %v", handler, err)
+		return "Something went wrong, please try again"
+	}
+}
@@ -0,0 +1,180 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// stubVar replaces a package-level function variable for one test
+func stubVar[T any](t *testing.T, target *T, value T) {
+	t.Helper()
+	original := *target
+	*target = value
+	t.Cleanup(func() { *target = original })
+}
+
+func stubTwoFactorUser(t *testing.T, userID uuid.UUID) {
+	t.Helper()
+	stubFetchUser(t, func(username string) (*userRecord, error) {
+		return &userRecord{UserID: userID, Username: username, PasswordHash: "hashed", TwoFactorEnabled: true}, nil
+	})
+	stubVerifyPassword(t, func(password, passwordHash string) (bool, error) { return true, nil })
+	stubInsertSession(t, func(sessionID, userID uuid.UUID, tokenHash string, expiresAt time.Time, userAgent, ipAddress string) error {
+		t.Fatal("no session may be created before the second factor")
+		return nil
+	})
+	stubVar(t, &createLoginChallengeFunc, func(ctx context.Context, gotUserID uuid.UUID) (string, error) {
+		assert.Equal(t, userID, gotUserID)
+		return "challenge-token", nil
+	})
+}
+
+func findSessionCookie(resp *http.Response) *http.Cookie {
+	for _, c := range resp.Cookies() {
+		if c.Name == "kaunta_session" {
+			return c
+		}
+	}
+	return nil
+}
+
+func TestHandleLoginTwoFactorChallenge(t *testing.T) {
+	stubTwoFactorUser(t, uuid.New())
+
+	req := httptest.NewRequest(http.MethodPost, "/api/auth/login", strings.NewReader(`{"username":"demo","password":"secret"}`))
+	req.Header.Set("Content-Type", "application/json")
+	resp := httptest.NewRecorder()
+	HandleLogin(resp, req)
+
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Nil(t, findSessionCookie(resp.Result()))
+
+	var data LoginResponse
+	require.NoError(t, json.Unmarshal(resp.Body.Bytes(), &data))
+	assert.False(t, data.Success)
+	assert.True(t, data.TwoFactorRequired)
+	assert.Equal(t, "challenge-token", data.Challenge)
+	assert.Nil(t, data.User)
+}
+
+func TestHandleLoginSSETwoFactorChallenge(t *testing.T) {
+	stubTwoFactorUser(t, uuid.New())
+
+	signals := url.QueryEscape(`{"username":"demo","password":"secret"}`)
+	req := httptest.NewRequest(http.MethodGet, "/api/auth/login?datastar="+signals, nil)
+	resp := httptest.NewRecorder()
+	HandleLoginSSE(resp, req)
+
+	assert.Nil(t, findSessionCookie(resp.Result()))
+	assert.Contains(t, resp.Body.String(), `"challenge":"challenge-token"`)
+	assert.NotContains(t, resp.Body.String(), "/dashboard/overview")
+}
+
+func TestHandleLoginTwoFactor(t *testing.T) {
+	userID := uuid.New()
+	stubVar(t, &claimLoginChallengeFunc, func(ctx context.Context, token string) (uuid.UUID, error) {
+		if token != "challenge-token" {
+			return uuid.Nil, models.ErrLoginChallengeNotFound
+		}
+		return userID, nil
+	})
+	stubVar(t, &verifyTwoFactorFunc, func(ctx context.Context, gotUserID uuid.UUID, code string, now time.Time) error {
+		assert.Equal(t, userID, gotUserID)
+		if code != "123456" {
+			return models.ErrInvalidTwoFactorCode
+		}
+		return nil
+	})
+	deleted := false
+	stubVar(t, &deleteLoginChallengeFunc, func(ctx context.Context, token string) error {
+		deleted = true
+		return nil
+	})
+	stubVar(t, &fetchUserByIDFunc, func(id uuid.UUID) (*userRecord, error) {
+		return &userRecord{UserID: id, Username: "demo", Name: sql.NullString{String: "Demo", Valid: true}}, nil
+	})
+	sessions := 0
+	stubInsertSession(t, func(sessionID, gotUserID uuid.UUID, tokenHash string, expiresAt time.Time, userAgent, ipAddress string) error {
+		sessions++
+		assert.Equal(t, userID, gotUserID)
+		return nil
+	})
+	stubSessionTokenGenerator(t, func() (string, string, error) { return "plain-token", "hashed-token", nil })
+
+	post := func(body string) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodPost, "/api/auth/login/2fa", strings.NewReader(body))
+		req.Header.Set("Content-Type", "application/json")
+		resp := httptest.NewRecorder()
+		HandleLoginTwoFactor(resp, req)
+		return resp
+	}
+
+	resp := post(`{"challenge":"challenge-token","code":"000000"}`)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+	assert.Contains(t, resp.Body.String(), "Invalid two-factor code")
+
+	resp = post(`{"challenge":"stale","code":"123456"}`)
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+	assert.Contains(t, resp.Body.String(), loginExpiredMessage)
+
+	resp = post(`{"challenge":"challenge-token"}`)
+	assert.Equal(t, http.StatusBadRequest, resp.Code)
+	assert.Equal(t, 0, sessions)
+
+	resp = post(`{"challenge":"challenge-token","code":"123456"}`)
+	require.Equal(t, http.StatusOK, resp.Code)
+	assert.Equal(t, 1, sessions)
+	assert.True(t, deleted)
+	cookie := findSessionCookie(resp.Result())
+	require.NotNil(t, cookie)
+	assert.Equal(t, "plain-token", cookie.Value)
+
+	var data LoginResponse
+	require.NoError(t, json.Unmarshal(resp.Body.Bytes(), &data))
+	assert.True(t, data.Success)
+	require.NotNil(t, data.User)
+	assert.Equal(t, userID, data.User.UserID)
+}
+
+func TestHandleLoginTwoFactorSSEExpired(t *testing.T) {
+	stubVar(t, &claimLoginChallengeFunc, func(ctx context.Context, token string) (uuid.UUID, error) {
+		return uuid.Nil, models.ErrLoginChallengeNotFound
+	})
+
+	signals := url.QueryEscape(`{"challenge":"stale","code":"123456"}`)
+	req := httptest.NewRequest(http.MethodGet, "/api/auth/login/2fa?datastar="+signals, nil)
+	resp := httptest.NewRecorder()
+	HandleLoginTwoFactorSSE(resp, req)
+
+	body := resp.Body.String()
+	assert.Contains(t, body, loginExpiredMessage)
+	assert.Contains(t, body, `"challenge":""`)
+	assert.Nil(t, findSessionCookie(resp.Result()))
+}
+
+func TestTwoFactorEndpointsRequireSession(t *testing.T) {
+	for _, handler := range []http.HandlerFunc{
+		HandleTwoFactorInit,
+		HandleTwoFactorSetup,
+		HandleTwoFactorEnable,
+		HandleTwoFactorDisable,
+		HandleTwoFactorRecoveryCodes,
+	} {
+		resp := httptest.NewRecorder()
+		handler(resp, httptest.NewRequest(http.MethodPost, "/api/auth/2fa", nil))
+		assert.Equal(t, http.StatusUnauthorized, resp.Code)
+	}
+}
@@ -9,16 +9,19 @@ type Website struct {
 
 // DashboardStats holds basic stats for the dashboard
 type DashboardStats struct {
-	CurrentVisitors int    `json:"current_visitors"`
-	TodayPageviews  int    `json:"today_pageviews"`
-	TodayVisitors   int    `json:"today_visitors"`
-	TodayBounceRate string `json:"today_bounce_rate"`
+	CurrentVisitors  int    `json:"current_visitors"`
+	TodayPageviews   int    `json:"today_pageviews"`
+	TodayVisitors    int    `json:"today_visitors"`
+	TodayBounceRate  string `json:"today_bounce_rate"`
+	AvgVisitDuration string `json:"avg_visit_duration"`
 }
 
 // TopPage represents a page with stats
 type TopPage struct {
-	Path  string `json:"path"`
-	Views int    `json:"views"`
+	Path              string   `json:"path"`
+	Views             int      `json:"views"`
+	UniqueVisitors    int      `json:"unique_visitors,omitempty"`
+	AvgEngagementTime *float64 `json:"avg_engagement_time,omitempty"`
 }
 
 // TimeSeriesPoint represents a data point in time series
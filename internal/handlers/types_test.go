@@ -60,32 +60,35 @@ func TestDashboardStats_JSONMarshaling(t *testing.T) {
 		{
 			name: "Normal stats",
 			stats: DashboardStats{
-				CurrentVisitors: 42,
-				TodayPageviews:  1337,
-				TodayVisitors:   256,
-				TodayBounceRate: "45.2%",
+				CurrentVisitors:  42,
+				TodayPageviews:   1337,
+				TodayVisitors:    256,
+				TodayBounceRate:  "45.2%",
+				AvgVisitDuration: "1m 14s",
 			},
-			expected: `{"current_visitors":42,"today_pageviews":1337,"today_visitors":256,"today_bounce_rate":"45.2%"}`,
+			expected: `{"current_visitors":42,"today_pageviews":1337,"today_visitors":256,"today_bounce_rate":"45.2%","avg_visit_duration":"1m 14s"}`,
 		},
 		{
 			name: "Zero stats",
 			stats: DashboardStats{
-				CurrentVisitors: 0,
-				TodayPageviews:  0,
-				TodayVisitors:   0,
-				TodayBounceRate: "0%",
+				CurrentVisitors:  0,
+				TodayPageviews:   0,
+				TodayVisitors:    0,
+				TodayBounceRate:  "0%",
+				AvgVisitDuration: "0s",
 			},
-			expected: `{"current_visitors":0,"today_pageviews":0,"today_visitors":0,"today_bounce_rate":"0%"}`,
+			expected: `{"current_visitors":0,"today_pageviews":0,"today_visitors":0,"today_bounce_rate":"0%","avg_visit_duration":"0s"}`,
 		},
 		{
 			name: "High traffic stats",
 			stats: DashboardStats{
-				CurrentVisitors: 9999,
-				TodayPageviews:  1000000,
-				TodayVisitors:   500000,
-				TodayBounceRate: "12.3%",
+				CurrentVisitors:  9999,
+				TodayPageviews:   1000000,
+				TodayVisitors:    500000,
+				TodayBounceRate:  "12.3%",
+				AvgVisitDuration: "2m 03s",
 			},
-			expected: `{"current_visitors":9999,"today_pageviews":1000000,"today_visitors":500000,"today_bounce_rate":"12.3%"}`,
+			expected: `{"current_visitors":9999,"today_pageviews":1000000,"today_visitors":500000,"today_bounce_rate":"12.3%","avg_visit_duration":"2m 03s"}`,
 		},
 	}
 
@@ -0,0 +1,300 @@
+package handlers
+
+import (
+	"fmt"
+	"log"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// webhookDeliveryLogLimit caps the deliveries shown in the dashboard log
+const webhookDeliveryLogLimit = 50
+
+// webhookEventLabels are the display names for webhook events
+var webhookEventLabels = map[string]string{
+	models.WebhookGoalCompleted: "Goal completed",
+	models.WebhookAlertFired:    "Alert fired",
+	models.WebhookWeeklySummary: "Weekly summary",
+	models.WebhookPing:          "Test ping",
+}
+
+// HandleWebhooksInit loads websites, the user's webhooks and recent deliveries
+// GET /api/dashboard/webhooks-init
+func HandleWebhooksInit(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Not authenticated",
+				"websitesLoading": false,
+			})
+		})
+		return
+	}
+
+	var websites []WebsiteInfo
+	rows, queryErr := database.DB.Query(`
+		SELECT website_id, COALESCE(name, ''), domain
+		FROM website
+		WHERE website_id IN (SELECT website_id FROM user_websites($1) WHERE role IN ('owner', 'editor'))
+		ORDER BY domain
+	`, user.UserID)
+	if queryErr == nil {
+		websites = scanWebsiteInfos(rows)
+	}
+
+	var hooks []models.Webhook
+	if queryErr == nil {
+		hooks, queryErr = models.ListWebhooks(r.Context(), database.DB, user.UserID)
+	}
+	var deliveries []models.WebhookDelivery
+	if queryErr == nil {
+		deliveries, queryErr = models.ListWebhookDeliveries(r.Context(), database.DB, user.UserID, webhookDeliveryLogLimit)
+	}
+
+	selectedWebsite := selectedWebsiteFromRequest(r)
+	if selectedWebsite == "" && len(websites) > 0 {
+		selectedWebsite = websites[0].ID
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if queryErr != nil {
+			log.Printf("HandleWebhooksInit: query error: %v", queryErr)
+			_ = sse.PatchSignals(map[string]any{
+				"websitesError":   "Failed to load webhooks",
+				"websitesLoading": false,
+				"websites":        []WebsiteInfo{},
+			})
+			return
+		}
+
+		_ = sse.PatchElementsWithMode("#webhook-list", buildWebhookListHTML(hooks, websites), "inner")
+		_ = sse.PatchElementsWithMode("#webhook-deliveries", buildWebhookDeliveriesHTML(deliveries), "inner")
+		_ = sse.PatchSignals(map[string]any{
+			"websites":        websites,
+			"selectedWebsite": selectedWebsite,
+			"websitesLoading": false,
+			"websitesError":   false,
+		})
+		if html := buildWebsiteSelectorHTML(websites, selectedWebsite, "webhooks"); html != "" {
+			_ = sse.PatchElements("#website-selector-container", html)
+		}
+	})
+}
+
+// HandleWebhookCreate subscribes a URL to events of the selected website.
+// The signing secret is shown once in the response.
+// POST /api/dashboard/webhooks (form: website_id, url, events...)
+func HandleWebhookCreate(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	websiteID, err := uuid.Parse(r.FormValue("website_id"))
+	hook := models.Webhook{
+		UserID:    user.UserID,
+		WebsiteID: websiteID,
+		URL:       strings.TrimSpace(r.FormValue("url")),
+		Events:    r.Form["events"],
+	}
+	validationErr := hook.Validate()
+	if err != nil {
+		validationErr = fmt.Errorf("select a website first")
+	}
+	if validationErr != nil {
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"webhookError": validationErr.Error(), "submitting": false})
+		})
+		return
+	}
+
+	if err := models.CreateWebhook(r.Context(), database.DB, &hook); err != nil {
+		log.Printf("HandleWebhookCreate: %v", err)
+		streamDatastar(w, func(sse *DatastarSSE) {
+			_ = sse.PatchSignals(map[string]any{"webhookError": "Failed to create webhook", "submitting": false})
+		})
+		return
+	}
+
+	streamWebhookList(w, r, user.UserID, map[string]any{
+		"showWebhookModal": false,
+		"webhookSecret":    hook.Secret,
+		"webhookError":     false,
+		"submitting":       false,
+	})
+}
+
+// HandleWebhookToggle pauses or resumes a webhook
+// POST /api/dashboard/webhooks/{id}/enabled?enabled=true
+func HandleWebhookToggle(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	webhookID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid webhook ID")
+		return
+	}
+	enabled, _ := strconv.ParseBool(r.URL.Query().Get("enabled"))
+
+	if err := models.SetWebhookEnabled(r.Context(), database.DB, webhookID, user.UserID, enabled); err != nil {
+		respondError(w, r, http.StatusNotFound, "Webhook not found")
+		return
+	}
+	streamWebhookList(w, r, user.UserID, nil)
+}
+
+// HandleWebhookTest queues a ping delivery so the endpoint can be checked
+// POST /api/dashboard/webhooks/{id}/test
+func HandleWebhookTest(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	webhookID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid webhook ID")
+		return
+	}
+
+	payload := map[string]string{"message": "Test delivery from Kaunta"}
+	if err := models.EnqueueWebhookDelivery(r.Context(), database.DB, webhookID, user.UserID, models.WebhookPing, payload); err != nil {
+		respondError(w, r, http.StatusNotFound, "Webhook not found")
+		return
+	}
+	streamWebhookList(w, r, user.UserID, nil)
+}
+
+// HandleWebhookDelete removes a webhook and its delivery log
+// DELETE /api/dashboard/webhooks/{id}
+func HandleWebhookDelete(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+	webhookID, err := uuid.Parse(chi.URLParam(r, "id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid webhook ID")
+		return
+	}
+
+	if err := models.DeleteWebhook(r.Context(), database.DB, webhookID, user.UserID); err != nil {
+		respondError(w, r, http.StatusNotFound, "Webhook not found")
+		return
+	}
+	streamWebhookList(w, r, user.UserID, nil)
+}
+
+// streamWebhookList re-renders the webhook list and delivery log after a change
+func streamWebhookList(w http.ResponseWriter, r *http.Request, userID uuid.UUID, signals map[string]any) {
+	hooks, err := models.ListWebhooks(r.Context(), database.DB, userID)
+	var deliveries []models.WebhookDelivery
+	if err == nil {
+		deliveries, err = models.ListWebhookDeliveries(r.Context(), database.DB, userID, webhookDeliveryLogLimit)
+	}
+
+	streamDatastar(w, func(sse *DatastarSSE) {
+		if err != nil {
+			log.Printf("streamWebhookList: %v", err)
+		} else {
+			_ = sse.PatchElementsWithMode("#webhook-list", buildWebhookListHTML(hooks, nil), "inner")
+			_ = sse.PatchElementsWithMode("#webhook-deliveries", buildWebhookDeliveriesHTML(deliveries), "inner")
+		}
+		if signals != nil {
+			_ = sse.PatchSignals(signals)
+		}
+	})
+}
+
+func buildWebhookListHTML(hooks []models.Webhook, websites []WebsiteInfo) string {
+	if len(hooks) == 0 {
+		return `<div class="empty-state-mini"><div>[~]</div><div>No webhooks yet</div></div>`
+	}
+
+	domains := make(map[string]string, len(websites))
+	for _, w := range websites {
+		domains[w.ID] = websiteLabel(w)
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="webhooks-table"><thead><tr><th>Endpoint</th><th>Events</th><th>Created</th><th style="text-align:right">Actions</th></tr></thead><tbody>`)
+	for _, hook := range hooks {
+		id := hook.ID.String()
+		website := ""
+		if label := domains[hook.WebsiteID.String()]; label != "" {
+			website = `<div class="stat-label">` + escapeHTML(label) + `</div>`
+		}
+		events := make([]string, 0, len(hook.Events))
+		for _, event := range hook.Events {
+			events = append(events, webhookEventLabel(event))
+		}
+		toggleLabel, next, rowClass := "Pause", "false", ""
+		if !hook.Enabled {
+			toggleLabel, next, rowClass = "Resume", "true", ` class="webhook-paused"`
+		}
+		fmt.Fprintf(&b, `<tr id="webhook-%s"%s><td><code>%s</code>%s</td><td>%s</td><td>%s</td><td class="webhook-actions">`,
+			id, rowClass, escapeHTML(hook.URL), website,
+			escapeHTML(strings.Join(events, ", ")),
+			escapeHTML(hook.CreatedAt.Format(time.DateOnly)))
+		fmt.Fprintf(&b, `<button class="btn btn-xs btn-ghost" data-on:click="@post('/api/dashboard/webhooks/%s/test', { headers: { 'X-CSRF-Token': getWebhooksCsrfToken() } })">Send test</button>`,
+			id)
+		fmt.Fprintf(&b, `<button class="btn btn-xs btn-ghost" data-on:click="@post('/api/dashboard/webhooks/%s/enabled?enabled=%s', { headers: { 'X-CSRF-Token': getWebhooksCsrfToken() } })">%s</button>`,
+			id, next, toggleLabel)
+		fmt.Fprintf(&b, `<button class="btn btn-xs btn-danger" data-on:click="confirm('Delete this webhook and its delivery log?') && @delete('/api/dashboard/webhooks/%s', { headers: { 'X-CSRF-Token': getWebhooksCsrfToken() } })">Delete</button></td></tr>`,
+			id)
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
+
+func buildWebhookDeliveriesHTML(deliveries []models.WebhookDelivery) string {
+	if len(deliveries) == 0 {
+		return `<div class="empty-state-mini"><div>[ ]</div><div>No deliveries yet</div></div>`
+	}
+
+	var b strings.Builder
+	b.WriteString(`<table class="webhooks-table"><thead><tr><th>Queued</th><th>Event</th><th>Status</th><th>Attempts</th><th>Response</th></tr></thead><tbody>`)
+	for _, d := range deliveries {
+		response := "-"
+		if d.ResponseStatus != nil {
+			response = "HTTP " + strconv.Itoa(*d.ResponseStatus)
+		}
+		if d.LastError != "" && d.Status != models.WebhookDeliveryDelivered {
+			response = d.LastError
+		}
+		status := d.Status
+		if d.Status == models.WebhookDeliveryPending && d.Attempts > 0 {
+			status = "retrying at " + d.NextAttemptAt.Format(time.TimeOnly)
+		}
+		fmt.Fprintf(&b, `<tr><td>%s</td><td>%s</td><td><span class="webhook-status webhook-status-%s">%s</span></td><td>%d</td><td>%s</td></tr>`,
+			escapeHTML(d.CreatedAt.Format(time.DateTime)),
+			escapeHTML(webhookEventLabel(d.Event)),
+			escapeHTML(d.Status), escapeHTML(status),
+			d.Attempts,
+			escapeHTML(response))
+	}
+	b.WriteString(`</tbody></table>`)
+	return b.String()
+}
+
+func webhookEventLabel(event string) string {
+	if label, ok := webhookEventLabels[event]; ok {
+		return label
+	}
+	return event
+}
@@ -0,0 +1,48 @@
+package handlers
+
+import (
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestBuildWebhookListHTML(t *testing.T) {
+	assert.Contains(t, buildWebhookListHTML(nil, nil), "No webhooks yet")
+
+	websiteID := uuid.New()
+	hooks := []models.Webhook{
+		{ID: uuid.New(), WebsiteID: websiteID, URL: "https://hooks.example.com/<kaunta>",
+			Events: []string{models.WebhookGoalCompleted, models.WebhookWeeklySummary}, Enabled: true},
+		{ID: uuid.New(), WebsiteID: websiteID, URL: "https://hooks.example.com/paused",
+			Events: []string{models.WebhookAlertFired}},
+	}
+	html := buildWebhookListHTML(hooks, []WebsiteInfo{{ID: websiteID.String(), Domain: "example.com"}})
+
+	assert.Contains(t, html, "https://hooks.example.com/&lt;kaunta&gt;")
+	assert.Contains(t, html, "example.com")
+	assert.Contains(t, html, "Goal completed, Weekly summary")
+	assert.Contains(t, html, "/api/dashboard/webhooks/"+hooks[0].ID.String()+"/test")
+	assert.Contains(t, html, "/api/dashboard/webhooks/"+hooks[0].ID.String()+"/enabled?enabled=false")
+	assert.Contains(t, html, "/api/dashboard/webhooks/"+hooks[1].ID.String()+"/enabled?enabled=true")
+	assert.Contains(t, html, `class="webhook-paused"`)
+}
+
+func TestBuildWebhookDeliveriesHTML(t *testing.T) {
+	assert.Contains(t, buildWebhookDeliveriesHTML(nil), "No deliveries yet")
+
+	ok, badGateway := 200, 502
+	html := buildWebhookDeliveriesHTML([]models.WebhookDelivery{
+		{Event: models.WebhookPing, Status: models.WebhookDeliveryDelivered, Attempts: 1, ResponseStatus: &ok, CreatedAt: time.Now()},
+		{Event: models.WebhookAlertFired, Status: models.WebhookDeliveryPending, Attempts: 2, ResponseStatus: &badGateway,
+			LastError: "webhook returned status 502", NextAttemptAt: time.Date(2026, 3, 4, 12, 30, 0, 0, time.UTC), CreatedAt: time.Now()},
+	})
+
+	assert.Contains(t, html, "Test ping")
+	assert.Contains(t, html, "HTTP 200")
+	assert.Contains(t, html, "retrying at 12:30:00")
+	assert.Contains(t, html, "webhook returned status 502")
+}
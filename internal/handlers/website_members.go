@@ -0,0 +1,117 @@
+package handlers
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"log"
+	"net/http"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// websiteRoleFunc resolves a user's role on a website (can be mocked in tests)
+var websiteRoleFunc = func(ctx context.Context, websiteID, userID uuid.UUID) (models.WebsiteRole, error) {
+	return models.GetWebsiteRole(ctx, database.DB, websiteID, userID)
+}
+
+// userHasWebsiteRole reports whether the user holds at least role on the
+// website. Routes get this from middleware.RequireWebsiteRole; handlers
+// that only learn the website from a stored record check it themselves.
+func userHasWebsiteRole(ctx context.Context, userID, websiteID uuid.UUID, role models.WebsiteRole) bool {
+	have, err := websiteRoleFunc(ctx, websiteID, userID)
+	return err == nil && have.Allows(role)
+}
+
+// WebsiteMemberRequest grants a user a role on a website
+type WebsiteMemberRequest struct {
+	Username string `json:"username"`
+	Role     string `json:"role"` // owner, editor or viewer
+}
+
+// HandleWebsiteMembers lists the users with access to a website
+// GET /api/websites/:website_id/members
+func HandleWebsiteMembers(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+
+	members, err := models.ListWebsiteMembers(r.Context(), database.DB, websiteID)
+	if err != nil {
+		log.Printf("HandleWebsiteMembers: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to list members")
+		return
+	}
+	render.JSON(w, r, members)
+}
+
+// HandleWebsiteMemberSet adds a user to a website or changes their role
+// PUT /api/websites/:website_id/members
+func HandleWebsiteMemberSet(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+
+	defer func() { _ = r.Body.Close() }()
+	var req WebsiteMemberRequest
+	if !decodeJSONBody(w, r, &req) {
+		return
+	}
+	if req.Username == "" {
+		respondError(w, r, http.StatusBadRequest, "Username is required")
+		return
+	}
+	role, err := models.ParseWebsiteRole(req.Role)
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, err.Error())
+		return
+	}
+
+	member, err := models.SetWebsiteMember(r.Context(), database.DB, websiteID, req.Username, role)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		respondError(w, r, http.StatusNotFound, "User not found")
+		return
+	case errors.Is(err, models.ErrWebsiteOwnerRole):
+		respondError(w, r, http.StatusConflict, "The website's owner always has full access")
+		return
+	case err != nil:
+		log.Printf("HandleWebsiteMemberSet: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to save member")
+		return
+	}
+	render.JSON(w, r, member)
+}
+
+// HandleWebsiteMemberDelete revokes a user's access to a website
+// DELETE /api/websites/:website_id/members/:user_id
+func HandleWebsiteMemberDelete(w http.ResponseWriter, r *http.Request) {
+	websiteID, ok := websiteFromParam(w, r)
+	if !ok {
+		return
+	}
+	userID, err := uuid.Parse(chi.URLParam(r, "user_id"))
+	if err != nil {
+		respondError(w, r, http.StatusBadRequest, "Invalid user ID")
+		return
+	}
+
+	err = models.RemoveWebsiteMember(r.Context(), database.DB, websiteID, userID)
+	if errors.Is(err, sql.ErrNoRows) {
+		respondError(w, r, http.StatusNotFound, "Member not found")
+		return
+	}
+	if err != nil {
+		log.Printf("HandleWebsiteMemberDelete: %v", err)
+		respondError(w, r, http.StatusInternalServerError, "Failed to remove member")
+		return
+	}
+	w.WriteHeader(http.StatusNoContent)
+}
@@ -2,13 +2,17 @@ package handlers
 
 import (
 	"context"
+	"log"
 	"net/http"
 	"time"
 
 	"github.com/go-chi/chi/v5"
 	"github.com/go-chi/render"
+	"github.com/google/uuid"
 
 	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/middleware"
+	"github.com/seuros/kaunta/internal/models"
 	"github.com/seuros/kaunta/internal/websites"
 )
 
@@ -29,6 +33,10 @@ func createWebsite(ctx context.Context, domain, name string, allowedDomains []st
 	return websites.Create(ctx, domain, name, allowedDomains)
 }
 
+func setWebsiteOwner(ctx context.Context, websiteID, userID uuid.UUID) error {
+	return models.SetWebsiteOwner(ctx, database.DB, websiteID, userID)
+}
+
 func updateWebsite(ctx context.Context, domain string, name *string) (*WebsiteDetail, error) {
 	return websites.Update(ctx, domain, name, nil)
 }
@@ -53,21 +61,38 @@ func newWebsiteDetailResponse(website *WebsiteDetail) WebsiteDetailResponse {
 	}
 }
 
-// HandleWebsites returns list of all websites with pagination
+// websitesSortColumns maps /api/websites sort_by values to keyset columns.
+// name sorts by the displayed name, which falls back to the domain.
+var websitesSortColumns = map[string]keysetColumn{
+	"name":       {"COALESCE(t.name, t.domain)", "text"},
+	"domain":     {"t.domain", "text"},
+	"created_at": {"COALESCE(t.created_at, 'epoch'::timestamptz)", "timestamptz"},
+}
+
+// HandleWebsites returns the websites the user can access, with pagination
+// GET /api/websites?per=25&sort_by=name&sort_order=asc&cursor=...
 func HandleWebsites(w http.ResponseWriter, r *http.Request) {
-	pagination := ParsePaginationParams(r)
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
+	pagination := ParsePaginationParamsWithValidation(r, "websites")
+	if r.URL.Query().Get("sort_order") == "" {
+		pagination.SortOrder = SortAsc
+	}
 
-	// Query with COUNT and pagination
-	rows, err := database.DB.Query(`
+	query, args := keysetQuery(`
 		WITH total AS (
-			SELECT COUNT(*)::BIGINT as count FROM website
+			SELECT COUNT(*)::BIGINT as count FROM user_websites($1)
 		)
-		SELECT w.website_id, w.domain, w.name, t.count as total_count
+		SELECT w.website_id, w.domain, w.name, t.count as total_count, w.created_at
 		FROM website w
 		CROSS JOIN total t
-		ORDER BY w.name, w.domain
-		LIMIT $1 OFFSET $2
-	`, pagination.Per, pagination.Offset)
+		WHERE w.website_id IN (SELECT website_id FROM user_websites($1))
+	`, []any{user.UserID}, pagination, websitesSortColumns[pagination.SortBy], "t.website_id::text")
+	rows, err := database.DB.Query(query, args...)
 
 	if err != nil {
 		respondError(w, r, http.StatusInternalServerError, "Failed to query websites")
@@ -76,12 +101,15 @@ func HandleWebsites(w http.ResponseWriter, r *http.Request) {
 	defer func() { _ = rows.Close() }()
 
 	var websites []Website
+	var cursors [][2]string
 	var totalCount int64
 	for rows.Next() {
 		var website Website
 		var name *string
 		var rowTotal int64
-		if err := rows.Scan(&website.ID, &website.Domain, &name, &rowTotal); err != nil {
+		var createdAt *time.Time // sort column only
+		var cursor [2]string
+		if err := rows.Scan(&website.ID, &website.Domain, &name, &rowTotal, &createdAt, &cursor[0], &cursor[1]); err != nil {
 			continue
 		}
 		totalCount = rowTotal // Capture total count
@@ -91,9 +119,11 @@ func HandleWebsites(w http.ResponseWriter, r *http.Request) {
 			website.Name = website.Domain
 		}
 		websites = append(websites, website)
+		cursors = append(cursors, cursor)
 	}
 
-	render.JSON(w, r, NewPaginatedResponse(websites, pagination, totalCount))
+	websites, next := keysetPage(websites, cursors, pagination)
+	render.JSON(w, r, NewKeysetPaginatedResponse(websites, pagination, totalCount, next))
 }
 
 // HandleWebsiteShow returns a single website with its allowed domains
@@ -115,8 +145,15 @@ func HandleWebsiteShow(w http.ResponseWriter, r *http.Request) {
 	render.JSON(w, r, newWebsiteDetailResponse(website))
 }
 
-// HandleWebsiteList returns all websites with allowed domains
+// HandleWebsiteList returns the websites the user can access with allowed
+// domains
 func HandleWebsiteList(w http.ResponseWriter, r *http.Request) {
+	user := middleware.GetUser(r)
+	if user == nil {
+		respondError(w, r, http.StatusUnauthorized, "Not authenticated")
+		return
+	}
+
 	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
 	defer cancel()
 
@@ -125,10 +162,17 @@ func HandleWebsiteList(w http.ResponseWriter, r *http.Request) {
 		respondError(w, r, http.StatusInternalServerError, err.Error())
 		return
 	}
+	roles, err := models.UserWebsiteRoles(ctx, database.DB, user.UserID)
+	if err != nil {
+		respondError(w, r, http.StatusInternalServerError, "Failed to load website access")
+		return
+	}
 
 	result := make([]WebsiteDetailResponse, 0, len(websites))
 	for _, website := range websites {
-		result = append(result, newWebsiteDetailResponse(website))
+		if id, err := uuid.Parse(website.WebsiteID); err == nil && roles[id] != "" {
+			result = append(result, newWebsiteDetailResponse(website))
+		}
 	}
 
 	render.JSON(w, r, result)
@@ -166,6 +210,18 @@ func HandleWebsiteCreate(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	// The creator owns the website; without an owner it would be open to
+	// every user. It joins the team they're switched to, if any.
+	if user := middleware.GetUser(r); user != nil {
+		websiteID := uuid.MustParse(website.WebsiteID)
+		if err := setWebsiteOwner(ctx, websiteID, user.UserID); err != nil {
+			log.Printf("HandleWebsiteCreate: set owner: %v", err)
+		}
+		if err := models.MoveWebsiteToCurrentTeam(ctx, database.DB, websiteID, user.UserID); err != nil {
+			log.Printf("HandleWebsiteCreate: set team: %v", err)
+		}
+	}
+
 	render.Status(r, http.StatusCreated)
 	render.JSON(w, r, newWebsiteDetailResponse(website))
 }
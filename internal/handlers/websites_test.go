@@ -6,18 +6,28 @@ import (
 	"net/http/httptest"
 	"testing"
 
+	"github.com/google/uuid"
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/middleware"
 )
 
+var websitesTestUser = &middleware.UserContext{UserID: uuid.MustParse("6f1c2f0e-6a57-4c1e-9d0a-2b7b5d8e1c01"), Username: "demo"}
+
+func websitesRequest(target string) *http.Request {
+	req := httptest.NewRequest(http.MethodGet, target, nil)
+	return req.WithContext(middleware.ContextWithUser(req.Context(), websitesTestUser))
+}
+
 func TestHandleWebsites_Success(t *testing.T) {
 	responses := []mockResponse{
 		{
-			match:   "SELECT w.website_id, w.domain, w.name, t.count as total_count",
-			columns: []string{"website_id", "domain", "name", "total_count"},
+			match:   "WHERE w.website_id IN (SELECT website_id FROM user_websites($1))",
+			columns: []string{"website_id", "domain", "name", "total_count", "created_at", "cursor_value", "cursor_key"},
 			rows: [][]any{
-				{"id-1", "example.com", "Example", int64(2)},
-				{"id-2", "demo.com", nil, int64(2)},
+				{"id-1", "example.com", "Example", int64(2), nil, "Example", "id-1"},
+				{"id-2", "demo.com", nil, int64(2), nil, "demo.com", "id-2"},
 			},
 		},
 	}
@@ -25,7 +35,7 @@ func TestHandleWebsites_Success(t *testing.T) {
 	handler, queue, cleanup := setupHTTPTest(t, "/api/websites", HandleWebsites, responses)
 	defer cleanup()
 
-	req := httptest.NewRequest(http.MethodGet, "/api/websites", nil)
+	req := websitesRequest("/api/websites")
 	resp := httptest.NewRecorder()
 	handler.ServeHTTP(resp, req)
 
@@ -49,6 +59,41 @@ func TestHandleWebsites_Success(t *testing.T) {
 	assert.Equal(t, 1, paginatedResp.Pagination.Page)
 	assert.Equal(t, 10, paginatedResp.Pagination.Per)
 	assert.False(t, paginatedResp.Pagination.HasMore)
+	assert.Empty(t, paginatedResp.Pagination.NextCursor)
+
+	require.NoError(t, queue.expectationsMet())
+}
+
+func TestHandleWebsites_NextCursor(t *testing.T) {
+	responses := []mockResponse{
+		{
+			match:   "ORDER BY COALESCE(t.name, t.domain) ASC, t.website_id::text ASC",
+			columns: []string{"website_id", "domain", "name", "total_count", "created_at", "cursor_value", "cursor_key"},
+			rows: [][]any{
+				{"id-1", "example.com", "Example", int64(2), nil, "Example", "id-1"},
+				{"id-2", "demo.com", nil, int64(2), nil, "demo.com", "id-2"},
+			},
+			args: []any{websitesTestUser.UserID, 2, 0},
+		},
+	}
+
+	handler, queue, cleanup := setupHTTPTest(t, "/api/websites", HandleWebsites, responses)
+	defer cleanup()
+
+	req := websitesRequest("/api/websites?per=1")
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+
+	assert.Equal(t, http.StatusOK, resp.Code)
+
+	var paginatedResp PaginatedResponse
+	require.NoError(t, json.NewDecoder(resp.Body).Decode(&paginatedResp))
+	assert.Len(t, paginatedResp.Data, 1)
+	assert.True(t, paginatedResp.Pagination.HasMore)
+
+	cursor, err := DecodeCursor(paginatedResp.Pagination.NextCursor)
+	require.NoError(t, err)
+	assert.Equal(t, Cursor{SortBy: "name", SortOrder: SortAsc, Value: "Example", Key: "id-1"}, *cursor)
 
 	require.NoError(t, queue.expectationsMet())
 }
@@ -64,10 +109,21 @@ func TestHandleWebsites_QueryError(t *testing.T) {
 	handler, queue, cleanup := setupHTTPTest(t, "/api/websites", HandleWebsites, responses)
 	defer cleanup()
 
-	req := httptest.NewRequest(http.MethodGet, "/api/websites", nil)
+	req := websitesRequest("/api/websites")
 	resp := httptest.NewRecorder()
 	handler.ServeHTTP(resp, req)
 
 	assert.Equal(t, http.StatusInternalServerError, resp.Code)
 	require.NoError(t, queue.expectationsMet())
 }
+
+func TestHandleWebsites_RequiresUser(t *testing.T) {
+	handler, queue, cleanup := setupHTTPTest(t, "/api/websites", HandleWebsites, nil)
+	defer cleanup()
+
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, httptest.NewRequest(http.MethodGet, "/api/websites", nil))
+
+	assert.Equal(t, http.StatusUnauthorized, resp.Code)
+	require.NoError(t, queue.expectationsMet())
+}
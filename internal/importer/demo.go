@@ -0,0 +1,356 @@
+package importer
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"fmt"
+	"math"
+	"math/rand/v2"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// demoNamespace seeds the IDs of demo sessions and events, so seeding the
+// same days twice writes them once
+var demoNamespace = uuid.MustParse("0b4c6d3a-8e21-4f5b-b7c9-5a2e91d04f68")
+
+// DemoOptions configures ImportDemo
+type DemoOptions struct {
+	Domain   string    // Website to fill, created when missing
+	Days     int       // Days of traffic, today included
+	Visitors int       // Average visits per weekday
+	Seed     uint64    // The same seed gives the same traffic
+	Now      time.Time // Traffic stops here; time.Now() when zero
+	Progress Reporter
+}
+
+// DemoResult summarizes ImportDemo
+type DemoResult struct {
+	WebsiteID uuid.UUID
+	Created   bool  // The website didn't exist
+	Days      int   // Days written
+	Imported  int64 // Events written; 0 for days seeded before
+	Rollups   int64 // Rollup rows recounted
+}
+
+type demoLocation struct {
+	country, region, city, language string
+	weight                          int
+}
+
+type demoPlatform struct {
+	device, os, browser, screen string
+	weight                      int
+}
+
+type demoSource struct {
+	referrer string    // domain/path, empty for direct
+	utm      [3]string // utm_source, utm_medium, utm_campaign
+	landing  string    // Entry page, empty for one by its weight
+	weight   int
+	spike    bool // Source of the occasional traffic spike
+}
+
+type demoPage struct {
+	path, title   string
+	entry, follow int // Weights as entry page and as next page
+}
+
+var demoLocations = []demoLocation{
+	{"US", "California", "San Francisco", "en-US", 10},
+	{"US", "New York", "New York", "en-US", 9},
+	{"US", "Texas", "Austin", "en-US", 5},
+	{"US", "Washington", "Seattle", "en-US", 4},
+	{"GB", "England", "London", "en-GB", 8},
+	{"DE", "Land Berlin", "Berlin", "de-DE", 6},
+	{"DE", "Bavaria", "Munich", "de-DE", 4},
+	{"FR", "Île-de-France", "Paris", "fr-FR", 6},
+	{"IN", "Karnataka", "Bengaluru", "en-IN", 7},
+	{"CA", "Ontario", "Toronto", "en-CA", 5},
+	{"NL", "North Holland", "Amsterdam", "nl-NL", 4},
+	{"BR", "São Paulo", "São Paulo", "pt-BR", 4},
+	{"JP", "Tokyo", "Tokyo", "ja-JP", 3},
+	{"AU", "New South Wales", "Sydney", "en-AU", 3},
+	{"ES", "Madrid", "Madrid", "es-ES", 3},
+	{"PL", "Mazovia", "Warsaw", "pl-PL", 2},
+	{"SE", "Stockholm", "Stockholm", "sv-SE", 2},
+	{"MA", "Casablanca-Settat", "Casablanca", "fr-MA", 1},
+}
+
+var demoPlatforms = []demoPlatform{
+	{"desktop", "Windows", "Chrome", "1920x1080", 20},
+	{"desktop", "Windows", "Edge", "1920x1080", 8},
+	{"desktop", "Windows", "Firefox", "1536x864", 5},
+	{"desktop", "macOS", "Chrome", "1440x900", 10},
+	{"desktop", "macOS", "Safari", "1512x982", 8},
+	{"desktop", "macOS", "Firefox", "1440x900", 3},
+	{"desktop", "Linux", "Firefox", "1920x1080", 4},
+	{"desktop", "Linux", "Chrome", "2560x1440", 3},
+	{"mobile", "iOS", "Safari", "390x844", 17},
+	{"mobile", "iOS", "Chrome", "393x852", 3},
+	{"mobile", "Android", "Chrome", "412x915", 18},
+	{"mobile", "Android", "Firefox", "412x915", 1},
+}
+
+var demoSources = []demoSource{
+	{weight: 35},
+	{referrer: "google.com", weight: 28},
+	{referrer: "duckduckgo.com", weight: 4},
+	{referrer: "bing.com", weight: 3},
+	{referrer: "github.com", weight: 5, landing: "/docs/getting-started"},
+	{referrer: "reddit.com/r/selfhosted", weight: 4, landing: "/blog/self-hosting-guide"},
+	{referrer: "news.ycombinator.com/item", weight: 2, landing: "/blog/privacy-first-analytics", spike: true},
+	{referrer: "t.co", weight: 2},
+	{referrer: "linkedin.com", weight: 2, landing: "/blog/privacy-first-analytics"},
+	{utm: [3]string{"newsletter", "email", "monthly-update"}, weight: 4, landing: "/blog/release-notes"},
+	{referrer: "producthunt.com", utm: [3]string{"producthunt", "referral", "launch"}, weight: 1, landing: "/pricing"},
+}
+
+var demoPages = []demoPage{
+	{"/", "Home", 40, 10},
+	{"/features", "Features", 6, 10},
+	{"/pricing", "Pricing", 8, 12},
+	{"/docs", "Documentation", 6, 8},
+	{"/docs/getting-started", "Getting started", 6, 9},
+	{"/docs/api", "API reference", 3, 5},
+	{"/blog", "Blog", 4, 5},
+	{"/blog/privacy-first-analytics", "Why privacy-first analytics", 6, 3},
+	{"/blog/self-hosting-guide", "Self-hosting guide", 4, 3},
+	{"/blog/release-notes", "Release notes", 2, 2},
+	{"/changelog", "Changelog", 1, 3},
+	{"/about", "About", 1, 3},
+	{"/contact", "Contact", 1, 2},
+}
+
+// demoHours weighs the hours of the day (UTC): quiet nights, busy
+// afternoons
+var demoHours = []int{2, 1, 1, 1, 1, 2, 3, 5, 7, 8, 9, 9, 8, 9, 10, 10, 9, 8, 7, 7, 6, 5, 4, 3}
+
+// demoWeekdays scales traffic from Sunday to Saturday
+var demoWeekdays = []float64{0.6, 1.05, 1.1, 1.1, 1.05, 0.95, 0.65}
+
+// weightedIndex picks one of n indexes with probability by weight
+func weightedIndex(r *rand.Rand, n int, weight func(int) int) int {
+	total := 0
+	for i := range n {
+		total += weight(i)
+	}
+	roll := r.IntN(total)
+	for i := range n {
+		if roll -= weight(i); roll < 0 {
+			return i
+		}
+	}
+	return n - 1
+}
+
+func pick[T any](r *rand.Rand, items []T, weight func(T) int) T {
+	return items[weightedIndex(r, len(items), func(i int) int { return weight(items[i]) })]
+}
+
+// demoDay generates the traffic of the day starting at start. Each day has
+// its own random source, so a day looks the same whatever range it is
+// seeded in.
+func demoDay(domain string, start time.Time, visitors int, seed uint64) ([]Session, []Event) {
+	dayNumber := start.Unix() / 86400
+	r := rand.New(rand.NewPCG(seed, uint64(dayNumber)))
+	id := func(parts ...any) uuid.UUID {
+		return uuid.NewSHA1(demoNamespace, []byte(fmt.Sprint(domain, "/", start.Format(time.DateOnly), "/", seed, parts)))
+	}
+
+	// Weekly rhythm on a slow seasonal wave, with the odd day on the front
+	// page of Hacker News
+	volume := float64(visitors) * demoWeekdays[start.Weekday()] *
+		(1 + 0.15*math.Sin(2*math.Pi*float64(dayNumber)/120)) * (0.85 + 0.3*r.Float64())
+	spike := r.IntN(30) == 0
+	if spike {
+		volume *= 3
+	}
+	n := int(volume + 0.5)
+
+	sessions := make([]Session, 0, n)
+	var events []Event
+	for i := range n {
+		hour := weightedIndex(r, len(demoHours), func(h int) int { return demoHours[h] })
+		at := start.Add(time.Duration(hour)*time.Hour + time.Duration(r.IntN(3600))*time.Second)
+
+		location := pick(r, demoLocations, func(l demoLocation) int { return l.weight })
+		platform := pick(r, demoPlatforms, func(p demoPlatform) int { return p.weight })
+		source := pick(r, demoSources, func(s demoSource) int {
+			if spike && s.spike {
+				return 150
+			}
+			return s.weight
+		})
+
+		sessionID := id("s", i)
+		sessions = append(sessions, Session{
+			ID: sessionID, Hostname: domain, Browser: platform.browser, OS: platform.os,
+			Device: platform.device, Screen: platform.screen, Language: location.language,
+			Country: location.country, Region: location.region, City: location.city, CreatedAt: at,
+		})
+
+		// About 45% of visits bounce; the others browse a few pages
+		path := source.landing
+		if path == "" {
+			path = pick(r, demoPages, func(p demoPage) int { return p.entry }).path
+		}
+		views := 1
+		if r.Float64() >= 0.45 {
+			views = 2
+			for views < 8 && r.Float64() < 0.45 {
+				views++
+			}
+		}
+
+		for j := range views {
+			page := demoPageByPath(path)
+			e := Event{
+				ID: id("e", i, j), SessionID: sessionID, VisitID: sessionID, CreatedAt: at, Type: 1,
+				URLPath: page.path, PageTitle: page.title, Hostname: domain,
+			}
+			if j == 0 {
+				e.ReferrerDomain, e.ReferrerPath, _ = strings.Cut(source.referrer, "/")
+				if e.ReferrerPath != "" {
+					e.ReferrerPath = "/" + e.ReferrerPath
+				}
+				e.UTMSource, e.UTMMedium, e.UTMCampaign = source.utm[0], source.utm[1], source.utm[2]
+			}
+			events = append(events, e)
+
+			if name, props := demoCustomEvent(r, page.path, platform); name != "" {
+				events = append(events, Event{
+					ID: id("c", i, j), SessionID: sessionID, VisitID: sessionID,
+					CreatedAt: at.Add(time.Duration(5+r.IntN(40)) * time.Second), Type: 2,
+					URLPath: page.path, Hostname: domain, Name: name, Props: props,
+				})
+			}
+
+			at = at.Add(time.Duration(20+r.IntN(160)) * time.Second)
+			path = pick(r, demoPages, func(p demoPage) int {
+				if p.path == page.path {
+					return 0
+				}
+				return p.follow
+			}).path
+		}
+	}
+	return sessions, events
+}
+
+func demoPageByPath(path string) demoPage {
+	for _, p := range demoPages {
+		if p.path == path {
+			return p
+		}
+	}
+	return demoPage{path: path, title: path}
+}
+
+// demoCustomEvent decides whether a pageview leads to a custom event, and
+// returns its name and properties
+func demoCustomEvent(r *rand.Rand, path string, platform demoPlatform) (string, []byte) {
+	var name string
+	props := map[string]string{}
+	switch roll := r.Float64(); {
+	case path == "/pricing" && roll < 0.08:
+		name = "signup"
+		props["plan"] = []string{"starter", "starter", "starter", "pro", "pro", "team"}[r.IntN(6)]
+	case path == "/docs/getting-started" && roll < 0.12:
+		name = "download"
+		props["os"] = platform.os
+	case strings.HasPrefix(path, "/blog/") && roll < 0.05:
+		name = "newsletter-signup"
+	case roll < 0.03:
+		name = "outbound-link"
+		props["url"] = []string{"https://github.com/seuros/kaunta", "https://twitter.com", "https://www.postgresql.org"}[r.IntN(3)]
+	default:
+		return "", nil
+	}
+	if len(props) == 0 {
+		return name, nil
+	}
+	data, _ := json.Marshal(props)
+	return name, data
+}
+
+// ImportDemo fills a website with synthetic traffic over the last
+// opts.Days days: daily and weekly rhythms, a mix of sources, countries
+// and devices, and custom events. Each day is one transaction. The rollups
+// of the days are recounted afterwards, so long dashboard ranges show the
+// traffic right away.
+func ImportDemo(ctx context.Context, db *sql.DB, opts DemoOptions) (DemoResult, error) {
+	var result DemoResult
+	if opts.Days < 1 {
+		return result, fmt.Errorf("days must be at least 1")
+	}
+	if opts.Visitors < 1 {
+		return result, fmt.Errorf("visitors must be at least 1")
+	}
+	now := opts.Now
+	if now.IsZero() {
+		now = time.Now()
+	}
+	now = now.UTC()
+	domain := strings.ToLower(opts.Domain)
+
+	var err error
+	result.WebsiteID, result.Created, err = resolveWebsite(ctx, db, uuid.Nil, domain, "Demo", time.Time{})
+	if err != nil {
+		return result, err
+	}
+
+	today := time.Date(now.Year(), now.Month(), now.Day(), 0, 0, 0, 0, time.UTC)
+	from := today.AddDate(0, 0, 1-opts.Days)
+	progress := Progress{Website: domain, Total: int64(opts.Days)}
+	for start := from; !start.After(today); start = start.AddDate(0, 0, 1) {
+		sessions, events := demoDay(domain, start, opts.Visitors, opts.Seed)
+		if start.Equal(today) {
+			sessions, events = demoBefore(sessions, events, now)
+		}
+
+		err := commitBatch(ctx, db, result.WebsiteID, sessions, events, func(tx *sql.Tx, written int64) error {
+			result.Imported += written
+			return nil
+		})
+		if err != nil {
+			return result, fmt.Errorf("%s: %w", start.Format(time.DateOnly), err)
+		}
+		result.Days++
+
+		progress.Imported = result.Imported
+		progress.Done++
+		progress.Through = start
+		if opts.Progress != nil {
+			opts.Progress(progress)
+		}
+	}
+
+	err = db.QueryRowContext(ctx, `SELECT rebuild_rollups($1, $2, $3)`,
+		from, today.AddDate(0, 0, 1), result.WebsiteID).Scan(&result.Rollups)
+	if err != nil {
+		return result, fmt.Errorf("failed to rebuild rollups: %w", err)
+	}
+	return result, nil
+}
+
+// demoBefore keeps the sessions started before now and their events up to
+// now, so today's traffic doesn't run ahead of the clock
+func demoBefore(sessions []Session, events []Event, now time.Time) ([]Session, []Event) {
+	var keptSessions []Session
+	for _, s := range sessions {
+		if s.CreatedAt.Before(now) {
+			keptSessions = append(keptSessions, s)
+		}
+	}
+	var keptEvents []Event
+	for _, e := range events {
+		if e.CreatedAt.Before(now) {
+			keptEvents = append(keptEvents, e)
+		}
+	}
+	return keptSessions, keptEvents
+}
@@ -0,0 +1,129 @@
+package importer
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestDemoDayIsDeterministic(t *testing.T) {
+	day := time.Date(2025, 3, 4, 0, 0, 0, 0, time.UTC)
+
+	sessions, events := demoDay("demo", day, 100, 1)
+	again, againEvents := demoDay("demo", day, 100, 1)
+	assert.Equal(t, sessions, again)
+	assert.Equal(t, events, againEvents)
+
+	other, _ := demoDay("demo", day, 100, 2)
+	assert.NotEqual(t, sessions[0].ID, other[0].ID)
+}
+
+func TestDemoDayLooksLikeTraffic(t *testing.T) {
+	start := time.Date(2025, 1, 5, 0, 0, 0, 0, time.UTC) // A Sunday
+	var weekdays, weekends, night, afternoon, pageviews, custom int
+	visits := 0
+	for d := range 56 {
+		day := start.AddDate(0, 0, d)
+		sessions, events := demoDay("demo", day, 200, 7)
+
+		if weekday := day.Weekday(); weekday == time.Saturday || weekday == time.Sunday {
+			weekends += len(sessions)
+		} else {
+			weekdays += len(sessions)
+		}
+		visits += len(sessions)
+
+		known := make(map[uuid.UUID]bool, len(sessions))
+		for _, s := range sessions {
+			known[s.ID] = true
+			assert.Equal(t, day, s.CreatedAt.Truncate(24*time.Hour))
+			switch hour := s.CreatedAt.Hour(); {
+			case hour >= 1 && hour < 5:
+				night++
+			case hour >= 13 && hour < 17:
+				afternoon++
+			}
+		}
+		for _, e := range events {
+			require.True(t, known[e.SessionID])
+			assert.Equal(t, "demo", e.Hostname)
+			if e.Type == 1 {
+				pageviews++
+			} else {
+				custom++
+				assert.NotEmpty(t, e.Name)
+			}
+		}
+	}
+
+	assert.Less(t, float64(weekends)/16, float64(weekdays)/40*0.8, "weekends are quieter")
+	assert.Less(t, night*4, afternoon, "nights are quieter")
+	assert.Greater(t, pageviews, visits*3/2)
+	assert.Less(t, pageviews, visits*3)
+	assert.Positive(t, custom)
+}
+
+func TestDemoBeforeDropsTheFuture(t *testing.T) {
+	now := time.Date(2025, 3, 4, 12, 0, 0, 0, time.UTC)
+	sessions, events := demoDay("demo", now.Truncate(24*time.Hour), 100, 1)
+
+	kept, keptEvents := demoBefore(sessions, events, now)
+	assert.NotEmpty(t, kept)
+	assert.Less(t, len(kept), len(sessions))
+	for _, e := range keptEvents {
+		assert.True(t, e.CreatedAt.Before(now))
+	}
+}
+
+func TestImportDemo(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	today := time.Date(2025, 3, 4, 0, 0, 0, 0, time.UTC)
+	mock.ExpectQuery("SELECT website_id FROM website").
+		WillReturnRows(sqlmock.NewRows([]string{"website_id"}).AddRow(websiteID.String()))
+	mock.ExpectBegin()
+	mock.ExpectExec("INSERT INTO session").WillReturnResult(sqlmock.NewResult(0, 100))
+	mock.ExpectExec("SELECT ensure_partitions_between").WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO website_event").WillReturnResult(sqlmock.NewResult(0, 250))
+	mock.ExpectCommit()
+	// Nothing of today has happened yet at midnight
+	mock.ExpectBegin()
+	mock.ExpectCommit()
+	mock.ExpectQuery(`SELECT rebuild_rollups\(\$1, \$2, \$3\)`).
+		WithArgs(today.AddDate(0, 0, -1), today.AddDate(0, 0, 1), websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"rebuild_rollups"}).AddRow(420))
+
+	var reported []Progress
+	result, err := ImportDemo(context.Background(), db, DemoOptions{
+		Domain:   "Demo",
+		Days:     2,
+		Visitors: 100,
+		Seed:     1,
+		Now:      today,
+		Progress: func(p Progress) { reported = append(reported, p) },
+	})
+	require.NoError(t, err)
+	assert.Equal(t, websiteID, result.WebsiteID)
+	assert.False(t, result.Created)
+	assert.Equal(t, 2, result.Days)
+	assert.Equal(t, int64(250), result.Imported)
+	assert.Equal(t, int64(420), result.Rollups)
+	require.Len(t, reported, 2)
+	assert.Equal(t, "demo", reported[1].Website)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestImportDemoValidatesOptions(t *testing.T) {
+	_, err := ImportDemo(context.Background(), nil, DemoOptions{Domain: "demo", Days: 0, Visitors: 10})
+	assert.Error(t, err)
+	_, err = ImportDemo(context.Background(), nil, DemoOptions{Domain: "demo", Days: 1})
+	assert.Error(t, err)
+}
@@ -0,0 +1,314 @@
+// Package importer copies history from other analytics tools into Kaunta,
+// so switching doesn't mean starting from zero. Umami's database maps onto
+// Kaunta's schema almost column for column and is copied event by event.
+// Plausible only exports daily aggregates, from which sessions and events
+// matching those totals are synthesized. Demo traffic, for trying Kaunta
+// out, is synthesized from scratch.
+//
+// Imports write in batches, one transaction each, and record their progress
+// in import_progress, so an interrupted import continues where it stopped.
+// Imported rows have stable IDs and existing rows are left alone, so running
+// an import twice doesn't count anything twice.
+package importer
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/websites"
+)
+
+// writeChunk is how many rows go into one INSERT
+var writeChunk = 5000
+
+// Progress reports how far the import of one site got
+type Progress struct {
+	Website  string    // Domain of the Kaunta website written to
+	Imported int64     // Events written so far, including earlier runs
+	Total    int64     // Events (Umami) or days (Plausible) to go through, 0 when unknown
+	Done     int64     // Events or days gone through in this run
+	Through  time.Time // Time of the last event or day imported
+}
+
+// Reporter is told about progress after every batch
+type Reporter func(Progress)
+
+// Session is an imported session
+type Session struct {
+	ID         uuid.UUID
+	Hostname   string
+	Browser    string
+	OS         string
+	Device     string
+	Screen     string
+	Language   string
+	Country    string
+	Region     string
+	City       string
+	DistinctID string
+	CreatedAt  time.Time
+}
+
+// Event is an imported pageview (type 1) or custom event (type 2)
+type Event struct {
+	ID             uuid.UUID
+	SessionID      uuid.UUID
+	VisitID        uuid.UUID
+	CreatedAt      time.Time
+	Type           int
+	URLPath        string
+	URLQuery       string
+	ReferrerPath   string
+	ReferrerQuery  string
+	ReferrerDomain string
+	PageTitle      string
+	Hostname       string
+	Name           string
+	Tag            string
+	UTMSource      string
+	UTMMedium      string
+	UTMCampaign    string
+	UTMTerm        string
+	UTMContent     string
+	Props          []byte // JSON object, nil for none
+}
+
+// cursor is where an import of one site stands
+type cursor struct {
+	websiteID uuid.UUID
+	at        time.Time
+	id        string
+	imported  int64
+}
+
+// loadCursor returns the recorded progress of a site, or ok false when it
+// hasn't been imported before
+func loadCursor(ctx context.Context, db *sql.DB, source, sourceID string) (c cursor, ok bool, err error) {
+	var at sql.NullTime
+	var id sql.NullString
+	err = db.QueryRowContext(ctx, `
+		SELECT website_id, cursor_at, cursor_id, imported FROM import_progress
+		WHERE source = $1 AND source_id = $2
+	`, source, sourceID).Scan(&c.websiteID, &at, &id, &c.imported)
+	if errors.Is(err, sql.ErrNoRows) {
+		return cursor{}, false, nil
+	}
+	if err != nil {
+		return cursor{}, false, err
+	}
+	c.at, c.id = at.Time, id.String
+	return c, true, nil
+}
+
+// saveCursor records progress within the transaction that wrote the batch
+func saveCursor(ctx context.Context, tx *sql.Tx, source, sourceID string, c cursor) error {
+	_, err := tx.ExecContext(ctx, `
+		INSERT INTO import_progress (source, source_id, website_id, cursor_at, cursor_id, imported)
+		VALUES ($1, $2, $3, $4, NULLIF($5, ''), $6)
+		ON CONFLICT (source, source_id) DO UPDATE SET
+			website_id = EXCLUDED.website_id,
+			cursor_at = EXCLUDED.cursor_at,
+			cursor_id = EXCLUDED.cursor_id,
+			imported = EXCLUDED.imported,
+			updated_at = NOW()
+	`, source, sourceID, c.websiteID, c.at, c.id, c.imported)
+	return err
+}
+
+// markComplete records that a site's import ran to the end
+func markComplete(ctx context.Context, db *sql.DB, source, sourceID string) error {
+	_, err := db.ExecContext(ctx, `
+		UPDATE import_progress SET completed_at = NOW(), updated_at = NOW()
+		WHERE source = $1 AND source_id = $2
+	`, source, sourceID)
+	return err
+}
+
+// resolveWebsite finds the Kaunta website a site is imported into: the one
+// with its ID, else the one with its domain. Without either, it is created
+// with the source's ID, so trackers still sending that ID keep working.
+func resolveWebsite(ctx context.Context, db *sql.DB, id uuid.UUID, domain, name string, createdAt time.Time) (websiteID uuid.UUID, created bool, err error) {
+	err = db.QueryRowContext(ctx, `
+		SELECT website_id FROM website
+		WHERE deleted_at IS NULL AND (website_id = $1 OR LOWER(domain) = LOWER($2))
+		ORDER BY website_id = $1 DESC
+		LIMIT 1
+	`, id, domain).Scan(&websiteID)
+	if !errors.Is(err, sql.ErrNoRows) {
+		return websiteID, false, err
+	}
+
+	if err := websites.ValidateDomain(domain); err != nil {
+		return uuid.Nil, false, fmt.Errorf("cannot create website for %q: %w", domain, err)
+	}
+	if name == "" {
+		name = domain
+	}
+	if createdAt.IsZero() {
+		createdAt = time.Now()
+	}
+	if id == uuid.Nil {
+		id = uuid.New()
+	}
+	_, err = db.ExecContext(ctx, `
+		INSERT INTO website (website_id, domain, name, allowed_domains, created_at, updated_at)
+		VALUES ($1, $2, $3, '[]'::jsonb, $4, NOW())
+	`, id, domain, name, createdAt)
+	if err != nil {
+		return uuid.Nil, false, fmt.Errorf("failed to create website %s: %w", domain, err)
+	}
+	return id, true, nil
+}
+
+// writeBatch inserts sessions and events of one website, creating the event
+// partitions they need. Sessions get the first and last page of their
+// events as entry and exit page. Rows that already exist are skipped; it
+// returns the events written.
+func writeBatch(ctx context.Context, tx *sql.Tx, websiteID uuid.UUID, sessions []Session, events []Event) (int64, error) {
+	if len(events) == 0 {
+		return 0, nil
+	}
+
+	entry := make(map[uuid.UUID]string)
+	exit := make(map[uuid.UUID]string)
+	for _, e := range events {
+		if e.Type != 1 || e.URLPath == "" {
+			continue
+		}
+		if _, ok := entry[e.SessionID]; !ok {
+			entry[e.SessionID] = e.URLPath
+		}
+		exit[e.SessionID] = e.URLPath
+	}
+
+	for start := 0; start < len(sessions); start += writeChunk {
+		chunk := sessions[start:min(start+writeChunk, len(sessions))]
+		if err := insertSessions(ctx, tx, websiteID, chunk, entry, exit); err != nil {
+			return 0, fmt.Errorf("failed to import sessions: %w", err)
+		}
+	}
+
+	var written int64
+	for start := 0; start < len(events); start += writeChunk {
+		chunk := events[start:min(start+writeChunk, len(events))]
+		n, err := insertEvents(ctx, tx, websiteID, chunk)
+		if err != nil {
+			return written, fmt.Errorf("failed to import events: %w", err)
+		}
+		written += n
+	}
+	return written, nil
+}
+
+func insertSessions(ctx context.Context, tx *sql.Tx, websiteID uuid.UUID, sessions []Session, entry, exit map[uuid.UUID]string) error {
+	n := len(sessions)
+	ids := make([]string, n)
+	cols := make([][]string, 12)
+	for i := range cols {
+		cols[i] = make([]string, n)
+	}
+	for i, s := range sessions {
+		ids[i] = s.ID.String()
+		for j, v := range []string{s.Hostname, s.Browser, s.OS, s.Device, s.Screen, s.Language,
+			s.Country, s.Region, s.City, s.DistinctID, entry[s.ID], exit[s.ID]} {
+			cols[j][i] = v
+		}
+	}
+	createdAt := make([]string, n)
+	for i, s := range sessions {
+		createdAt[i] = s.CreatedAt.UTC().Format(time.RFC3339Nano)
+	}
+
+	// The column widths are Kaunta's; longer values are cut rather than
+	// failing the batch
+	_, err := tx.ExecContext(ctx, `
+		INSERT INTO session (session_id, website_id, hostname, browser, os, device, screen, language,
+		                     country, region, city, distinct_id, entry_page, exit_page, created_at)
+		SELECT id, $1, LEFT(NULLIF(hostname, ''), 100), LEFT(NULLIF(browser, ''), 20), LEFT(NULLIF(os, ''), 20),
+		       LEFT(NULLIF(device, ''), 20), LEFT(NULLIF(screen, ''), 11), LEFT(NULLIF(language, ''), 35),
+		       UPPER(LEFT(NULLIF(country, ''), 2)), LEFT(NULLIF(region, ''), 50), LEFT(NULLIF(city, ''), 50),
+		       LEFT(NULLIF(distinct_id, ''), 500), LEFT(NULLIF(entry_page, ''), 500), LEFT(NULLIF(exit_page, ''), 500),
+		       created_at
+		FROM UNNEST($2::uuid[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[],
+		            $9::text[], $10::text[], $11::text[], $12::text[], $13::text[], $14::text[], $15::timestamptz[])
+		     AS s(id, hostname, browser, os, device, screen, language, country, region, city, distinct_id,
+		          entry_page, exit_page, created_at)
+		ON CONFLICT (session_id) DO UPDATE SET
+			entry_page = COALESCE(session.entry_page, EXCLUDED.entry_page),
+			exit_page = COALESCE(EXCLUDED.exit_page, session.exit_page)
+	`, websiteID, pq.Array(ids),
+		pq.Array(cols[0]), pq.Array(cols[1]), pq.Array(cols[2]), pq.Array(cols[3]), pq.Array(cols[4]),
+		pq.Array(cols[5]), pq.Array(cols[6]), pq.Array(cols[7]), pq.Array(cols[8]), pq.Array(cols[9]),
+		pq.Array(cols[10]), pq.Array(cols[11]), pq.Array(createdAt))
+	return err
+}
+
+func insertEvents(ctx context.Context, tx *sql.Tx, websiteID uuid.UUID, events []Event) (int64, error) {
+	n := len(events)
+	ids := make([][]string, 3)
+	for i := range ids {
+		ids[i] = make([]string, n)
+	}
+	cols := make([][]string, 14)
+	for i := range cols {
+		cols[i] = make([]string, n)
+	}
+	createdAt := make([]string, n)
+	types := make([]int64, n)
+	props := make([]sql.NullString, n)
+	for i, e := range events {
+		ids[0][i], ids[1][i], ids[2][i] = e.ID.String(), e.SessionID.String(), e.VisitID.String()
+		createdAt[i] = e.CreatedAt.UTC().Format(time.RFC3339Nano)
+		types[i] = int64(e.Type)
+		for j, v := range []string{e.URLPath, e.URLQuery, e.ReferrerPath, e.ReferrerQuery, e.ReferrerDomain,
+			e.PageTitle, e.Hostname, e.Name, e.Tag, e.UTMSource, e.UTMMedium, e.UTMCampaign, e.UTMTerm, e.UTMContent} {
+			cols[j][i] = v
+		}
+		if e.Props != nil {
+			props[i] = sql.NullString{String: string(e.Props), Valid: true}
+		}
+	}
+
+	if _, err := tx.ExecContext(ctx, `
+		SELECT ensure_partitions_between('website_event', MIN(t)::DATE, MAX(t)::DATE + 1)
+		FROM UNNEST($1::timestamptz[]) AS t
+	`, pq.Array(createdAt)); err != nil {
+		return 0, fmt.Errorf("failed to create partitions: %w", err)
+	}
+
+	result, err := tx.ExecContext(ctx, `
+		INSERT INTO website_event (event_id, website_id, session_id, visit_id, created_at, event_type,
+		                           url_path, url_query, referrer_path, referrer_query, referrer_domain,
+		                           page_title, hostname, event_name, tag,
+		                           utm_source, utm_medium, utm_campaign, utm_term, utm_content, props)
+		SELECT id, $1, session_id, visit_id, created_at, event_type,
+		       LEFT(NULLIF(url_path, ''), 500), LEFT(NULLIF(url_query, ''), 500),
+		       LEFT(NULLIF(referrer_path, ''), 500), LEFT(NULLIF(referrer_query, ''), 500),
+		       LEFT(NULLIF(referrer_domain, ''), 500), LEFT(NULLIF(page_title, ''), 500),
+		       LEFT(NULLIF(hostname, ''), 100), LEFT(NULLIF(event_name, ''), 50), LEFT(NULLIF(tag, ''), 50),
+		       LEFT(NULLIF(utm_source, ''), 255), LEFT(NULLIF(utm_medium, ''), 255),
+		       LEFT(NULLIF(utm_campaign, ''), 255), LEFT(NULLIF(utm_term, ''), 255),
+		       LEFT(NULLIF(utm_content, ''), 255), props::jsonb
+		FROM UNNEST($2::uuid[], $3::uuid[], $4::uuid[], $5::timestamptz[], $6::smallint[],
+		            $7::text[], $8::text[], $9::text[], $10::text[], $11::text[], $12::text[], $13::text[],
+		            $14::text[], $15::text[], $16::text[], $17::text[], $18::text[], $19::text[], $20::text[],
+		            $21::text[])
+		     AS e(id, session_id, visit_id, created_at, event_type, url_path, url_query, referrer_path,
+		          referrer_query, referrer_domain, page_title, hostname, event_name, tag,
+		          utm_source, utm_medium, utm_campaign, utm_term, utm_content, props)
+		ON CONFLICT DO NOTHING
+	`, websiteID, pq.Array(ids[0]), pq.Array(ids[1]), pq.Array(ids[2]), pq.Array(createdAt), pq.Array(types),
+		pq.Array(cols[0]), pq.Array(cols[1]), pq.Array(cols[2]), pq.Array(cols[3]), pq.Array(cols[4]),
+		pq.Array(cols[5]), pq.Array(cols[6]), pq.Array(cols[7]), pq.Array(cols[8]), pq.Array(cols[9]),
+		pq.Array(cols[10]), pq.Array(cols[11]), pq.Array(cols[12]), pq.Array(cols[13]), pq.Array(props))
+	if err != nil {
+		return 0, err
+	}
+	return result.RowsAffected()
+}
@@ -0,0 +1,519 @@
+package importer
+
+import (
+	"archive/zip"
+	"context"
+	"database/sql"
+	"encoding/csv"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"regexp"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+const sourcePlausible = "plausible"
+
+// plausibleNamespace seeds the IDs of synthesized sessions and events, so a
+// second import of the same export produces the same rows
+var plausibleNamespace = uuid.MustParse("6f1c2b9e-3f5a-4d8e-9a47-1c0e8b2d5f73")
+
+// plausibleFile matches the CSV files of a Plausible export, e.g.
+// imported_entry_pages_20230101_20231231.csv
+var plausibleFile = regexp.MustCompile(`^imported_([a-z_]+?)(?:_\d{8}_\d{8})?\.csv$`)
+
+// Plausible names some values differently from Kaunta's tracker
+var plausibleValues = map[string]string{
+	"Mac":       "macOS",
+	"GNU/Linux": "Linux",
+	"Desktop":   "desktop",
+	"Mobile":    "mobile",
+	"Tablet":    "tablet",
+	"Laptop":    "desktop",
+}
+
+// PlausibleOptions configures ImportPlausible
+type PlausibleOptions struct {
+	Domain   string         // Kaunta website to import into, created when missing
+	Location *time.Location // Time zone of the site in Plausible, which its days are in (UTC when nil)
+	Restart  bool           // Import every day again instead of only those after the recorded progress
+	Progress Reporter       // Optional
+}
+
+// PlausibleResult is what ImportPlausible did
+type PlausibleResult struct {
+	WebsiteID uuid.UUID
+	Created   bool  // The Kaunta website was created by the import
+	Days      int   // Days imported in this run
+	Imported  int64 // Events written, including earlier runs
+}
+
+// weighted is a value and how often it occurred on a day
+type weighted struct {
+	value string
+	count int64
+}
+
+type plausibleSource struct {
+	referrer string
+	utm      [5]string // source, medium, campaign, term, content
+	visits   int64
+}
+
+type plausibleEvent struct {
+	name, linkURL, path string
+	count               int64
+}
+
+// plausibleDay holds one day of a Plausible export
+type plausibleDay struct {
+	visits, pageviews, bounces, visitDuration int64
+	hasTotals                                 bool
+	pages                                     []weighted
+	hostnames                                 map[string]string
+	entries                                   []weighted
+	sources                                   []plausibleSource
+	countries, devices, browsers, oses        []weighted
+	events                                    []plausibleEvent
+}
+
+// PlausibleExport is a Plausible CSV export, by day
+type PlausibleExport struct {
+	days map[string]*plausibleDay
+}
+
+// Days returns the days in the export, oldest first
+func (x *PlausibleExport) Days() []string {
+	days := make([]string, 0, len(x.days))
+	for day := range x.days {
+		days = append(days, day)
+	}
+	sort.Strings(days)
+	return days
+}
+
+func (x *PlausibleExport) day(date string) *plausibleDay {
+	d, ok := x.days[date]
+	if !ok {
+		d = &plausibleDay{hostnames: make(map[string]string)}
+		x.days[date] = d
+	}
+	return d
+}
+
+// ReadPlausibleExport reads the CSV files of a Plausible export from a zip
+// file, a directory or a single CSV file. Files other than the imported_*
+// tables are ignored.
+func ReadPlausibleExport(path string) (*PlausibleExport, error) {
+	x := &PlausibleExport{days: make(map[string]*plausibleDay)}
+
+	info, err := os.Stat(path)
+	if err != nil {
+		return nil, err
+	}
+	switch {
+	case info.IsDir():
+		entries, err := os.ReadDir(path)
+		if err != nil {
+			return nil, err
+		}
+		for _, entry := range entries {
+			if err := x.readFile(filepath.Join(path, entry.Name())); err != nil {
+				return nil, err
+			}
+		}
+	case strings.EqualFold(filepath.Ext(path), ".zip"):
+		archive, err := zip.OpenReader(path)
+		if err != nil {
+			return nil, err
+		}
+		defer func() { _ = archive.Close() }()
+		for _, f := range archive.File {
+			table := plausibleTable(f.Name)
+			if table == "" {
+				continue
+			}
+			r, err := f.Open()
+			if err != nil {
+				return nil, err
+			}
+			err = x.readTable(table, r)
+			_ = r.Close()
+			if err != nil {
+				return nil, fmt.Errorf("%s: %w", f.Name, err)
+			}
+		}
+	default:
+		if err := x.readFile(path); err != nil {
+			return nil, err
+		}
+	}
+
+	if len(x.days) == 0 {
+		return nil, errors.New("no Plausible export tables (imported_*.csv) found")
+	}
+	return x, nil
+}
+
+// plausibleTable returns the table a file of the export holds, or "" when
+// it isn't one
+func plausibleTable(name string) string {
+	m := plausibleFile.FindStringSubmatch(filepath.Base(name))
+	if m == nil {
+		return ""
+	}
+	return m[1]
+}
+
+func (x *PlausibleExport) readFile(path string) error {
+	table := plausibleTable(path)
+	if table == "" {
+		return nil
+	}
+	f, err := os.Open(path)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = f.Close() }()
+	if err := x.readTable(table, f); err != nil {
+		return fmt.Errorf("%s: %w", filepath.Base(path), err)
+	}
+	return nil
+}
+
+// readTable adds the rows of one export table to their days
+func (x *PlausibleExport) readTable(table string, r io.Reader) error {
+	cr := csv.NewReader(r)
+	cr.FieldsPerRecord = -1
+	header, err := cr.Read()
+	if errors.Is(err, io.EOF) {
+		return nil
+	}
+	if err != nil {
+		return err
+	}
+	index := make(map[string]int, len(header))
+	for i, name := range header {
+		index[strings.TrimSpace(strings.TrimPrefix(name, "\ufeff"))] = i
+	}
+	if _, ok := index["date"]; !ok {
+		return errors.New("no date column")
+	}
+
+	for {
+		record, err := cr.Read()
+		if errors.Is(err, io.EOF) {
+			return nil
+		}
+		if err != nil {
+			return err
+		}
+		get := func(column string) string {
+			if i, ok := index[column]; ok && i < len(record) {
+				return strings.TrimSpace(record[i])
+			}
+			return ""
+		}
+		num := func(columns ...string) int64 {
+			for _, column := range columns {
+				if n, err := strconv.ParseInt(get(column), 10, 64); err == nil {
+					return n
+				}
+			}
+			return 0
+		}
+
+		date := get("date")
+		if _, err := time.Parse(time.DateOnly, date); err != nil {
+			return fmt.Errorf("invalid date %q", date)
+		}
+		d := x.day(date)
+
+		switch table {
+		case "visitors":
+			d.hasTotals = true
+			d.visits += num("visits")
+			d.pageviews += num("pageviews")
+			d.bounces += num("bounces")
+			d.visitDuration += num("visit_duration")
+		case "pages":
+			page := get("page")
+			d.pages = append(d.pages, weighted{page, num("pageviews", "visits")})
+			if hostname := get("hostname"); hostname != "" {
+				d.hostnames[page] = hostname
+			}
+		case "entry_pages":
+			d.entries = append(d.entries, weighted{get("entry_page"), num("entrances", "visits")})
+		case "sources":
+			referrer := strings.TrimPrefix(strings.TrimPrefix(get("referrer"), "https://"), "http://")
+			if referrer == "" {
+				if source := get("source"); source != "" && source != "Direct / None" {
+					referrer = source
+				}
+			}
+			d.sources = append(d.sources, plausibleSource{
+				referrer: referrer,
+				utm:      [5]string{get("utm_source"), get("utm_medium"), get("utm_campaign"), get("utm_term"), get("utm_content")},
+				visits:   num("visits", "visitors"),
+			})
+		case "locations":
+			d.countries = append(d.countries, weighted{get("country"), num("visits", "visitors")})
+		case "devices":
+			d.devices = append(d.devices, weighted{plausibleValue(get("device")), num("visits", "visitors")})
+		case "browsers":
+			d.browsers = append(d.browsers, weighted{plausibleValue(get("browser")), num("visits", "visitors")})
+		case "operating_systems":
+			d.oses = append(d.oses, weighted{plausibleValue(get("operating_system")), num("visits", "visitors")})
+		case "custom_events":
+			d.events = append(d.events, plausibleEvent{get("name"), get("link_url"), get("path"), num("events", "visitors")})
+		}
+	}
+}
+
+func plausibleValue(v string) string {
+	if mapped, ok := plausibleValues[v]; ok {
+		return mapped
+	}
+	return v
+}
+
+// expand repeats each value count times, cut or padded with "" to n values
+func expand(values []weighted, n int) []string {
+	out := make([]string, 0, n)
+	for _, v := range values {
+		for c := int64(0); c < v.count && len(out) < n; c++ {
+			out = append(out, v.value)
+		}
+	}
+	for len(out) < n {
+		out = append(out, "")
+	}
+	return out
+}
+
+func sum(values []weighted) int64 {
+	var total int64
+	for _, v := range values {
+		total += v.count
+	}
+	return total
+}
+
+// synthesize builds sessions and events matching a day's totals. Every
+// visit becomes a session, spread evenly over the day, starting on its entry
+// page from its source; pageviews beyond the first go to the visits that
+// didn't bounce, spaced to add up to the day's visit duration. Breakdowns
+// are assigned visit by visit, so each one matches the export on its own
+// while combinations of them (e.g. browsers per country) don't carry
+// meaning.
+func (d *plausibleDay) synthesize(seed string, start time.Time) ([]Session, []Event) {
+	visits := d.visits
+	if !d.hasTotals {
+		for _, breakdown := range [][]weighted{d.entries, d.countries, d.devices, d.browsers, d.oses} {
+			visits = max(visits, sum(breakdown))
+		}
+		var sourced int64
+		for _, s := range d.sources {
+			sourced += s.visits
+		}
+		visits = max(visits, sourced)
+	}
+	pageviews := d.pageviews
+	if pageviews == 0 {
+		pageviews = sum(d.pages)
+	}
+	if visits == 0 && (pageviews > 0 || len(d.events) > 0) {
+		visits = 1
+	}
+	if visits == 0 {
+		return nil, nil
+	}
+	pageviews = max(pageviews, visits)
+	n := int(visits)
+
+	// Entry pages, from the entry page table or else the most viewed pages
+	entries := expand(d.entries, n)
+	if len(d.entries) == 0 {
+		entries = expand(d.pages, n)
+	}
+	remaining := make(map[string]int64)
+	for _, p := range d.pages {
+		remaining[p.value] += p.count
+	}
+	for i, page := range entries {
+		if page == "" {
+			entries[i] = "/"
+		}
+		remaining[entries[i]]--
+	}
+	var extra []weighted
+	for _, p := range d.pages {
+		if left := remaining[p.value]; left > 0 {
+			extra = append(extra, weighted{p.value, left})
+			remaining[p.value] = 0
+		}
+	}
+	extraPages := expand(extra, int(pageviews)-n)
+
+	// Visits that didn't bounce carry the pageviews beyond the first
+	engaged := n
+	if d.hasTotals && d.bounces > 0 && d.bounces < visits {
+		engaged = n - int(d.bounces)
+	}
+	views := make([][]string, n)
+	for i := range views {
+		views[i] = []string{entries[i]}
+	}
+	for i, page := range extraPages {
+		if page == "" {
+			page = entries[i%engaged]
+		}
+		views[i%engaged] = append(views[i%engaged], page)
+	}
+	var gap time.Duration
+	if extraViews := len(extraPages); extraViews > 0 && d.visitDuration > 0 {
+		gap = time.Duration(d.visitDuration) * time.Second / time.Duration(extraViews)
+	}
+
+	var sources []plausibleSource
+	for _, s := range d.sources {
+		for c := int64(0); c < s.visits && len(sources) < n; c++ {
+			sources = append(sources, s)
+		}
+	}
+	countries, devices := expand(d.countries, n), expand(d.devices, n)
+	browsers, oses := expand(d.browsers, n), expand(d.oses, n)
+
+	id := func(parts ...any) uuid.UUID {
+		return uuid.NewSHA1(plausibleNamespace, []byte(seed+fmt.Sprint(parts...)))
+	}
+	spacing := 24 * time.Hour / time.Duration(n)
+	sessions := make([]Session, n)
+	var events []Event
+	for i := range n {
+		sessionID := id("s", i)
+		at := start.Add(time.Duration(i) * spacing)
+		sessions[i] = Session{
+			ID: sessionID, Country: countries[i], Device: devices[i],
+			Browser: browsers[i], OS: oses[i], CreatedAt: at,
+		}
+		for j, page := range views[i] {
+			path, query, _ := strings.Cut(page, "?")
+			e := Event{
+				ID: id("e", i, "/", j), SessionID: sessionID, VisitID: sessionID,
+				CreatedAt: at.Add(time.Duration(j) * gap), Type: 1,
+				URLPath: path, URLQuery: query, Hostname: d.hostnames[page],
+			}
+			if j == 0 && i < len(sources) {
+				e.ReferrerDomain, e.ReferrerPath, _ = strings.Cut(sources[i].referrer, "/")
+				if e.ReferrerPath != "" {
+					e.ReferrerPath = "/" + e.ReferrerPath
+				}
+				u := sources[i].utm
+				e.UTMSource, e.UTMMedium, e.UTMCampaign, e.UTMTerm, e.UTMContent = u[0], u[1], u[2], u[3], u[4]
+			}
+			events = append(events, e)
+		}
+	}
+
+	// Custom events go round the visits, after their pageviews
+	occurrence := 0
+	for _, ev := range d.events {
+		props := map[string]string{}
+		if ev.linkURL != "" {
+			props["url"] = ev.linkURL
+		}
+		if ev.path != "" {
+			props["path"] = ev.path
+		}
+		var propsJSON []byte
+		if len(props) > 0 {
+			propsJSON, _ = json.Marshal(props)
+		}
+		for c := int64(0); c < ev.count; c++ {
+			i := occurrence % n
+			last := len(views[i]) - 1
+			path, _, _ := strings.Cut(views[i][last], "?")
+			events = append(events, Event{
+				ID: id("c", occurrence), SessionID: sessions[i].ID, VisitID: sessions[i].ID,
+				CreatedAt: sessions[i].CreatedAt.Add(time.Duration(last)*gap + time.Second), Type: 2,
+				URLPath: path, Name: ev.name, Props: propsJSON,
+			})
+			occurrence++
+		}
+	}
+
+	return sessions, events
+}
+
+// ImportPlausible writes the days of a Plausible export into a Kaunta
+// website as synthesized sessions and events, one transaction per day.
+// Days already imported are skipped unless opts.Restart is set.
+func ImportPlausible(ctx context.Context, db *sql.DB, export *PlausibleExport, opts PlausibleOptions) (PlausibleResult, error) {
+	var result PlausibleResult
+	loc := opts.Location
+	if loc == nil {
+		loc = time.UTC
+	}
+	domain := strings.ToLower(opts.Domain)
+
+	c, resumed, err := loadCursor(ctx, db, sourcePlausible, domain)
+	if err != nil {
+		return result, err
+	}
+	if resumed && opts.Restart {
+		c = cursor{websiteID: c.websiteID}
+	}
+	if !resumed {
+		c.websiteID, result.Created, err = resolveWebsite(ctx, db, uuid.Nil, domain, "", time.Time{})
+		if err != nil {
+			return result, err
+		}
+	}
+	result.WebsiteID = c.websiteID
+
+	days := export.Days()
+	progress := Progress{Website: domain, Imported: c.imported, Total: int64(len(days))}
+	for _, date := range days {
+		start, _ := time.ParseInLocation(time.DateOnly, date, loc)
+		if !c.at.IsZero() && !start.After(c.at) {
+			progress.Done++
+			continue
+		}
+
+		sessions, events := export.days[date].synthesize(domain+"/"+date+"/", start)
+		c.at = start
+		err := commitBatch(ctx, db, c.websiteID, sessions, events, func(tx *sql.Tx, written int64) error {
+			c.imported += written
+			return saveCursor(ctx, tx, sourcePlausible, domain, c)
+		})
+		if err != nil {
+			result.Imported = c.imported
+			return result, fmt.Errorf("%s: %w", date, err)
+		}
+		result.Days++
+
+		progress.Imported = c.imported
+		progress.Done++
+		progress.Through = start
+		if opts.Progress != nil {
+			opts.Progress(progress)
+		}
+	}
+
+	result.Imported = c.imported
+	if !resumed && result.Days == 0 {
+		if err := commitProgress(ctx, db, sourcePlausible, domain, c); err != nil {
+			return result, err
+		}
+	}
+	return result, markComplete(ctx, db, sourcePlausible, domain)
+}
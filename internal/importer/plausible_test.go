@@ -0,0 +1,177 @@
+package importer
+
+import (
+	"context"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func writeExport(t *testing.T, files map[string]string) string {
+	t.Helper()
+	dir := t.TempDir()
+	for name, content := range files {
+		require.NoError(t, os.WriteFile(filepath.Join(dir, name), []byte(content), 0o600))
+	}
+	return dir
+}
+
+func TestReadPlausibleExport(t *testing.T) {
+	dir := writeExport(t, map[string]string{
+		"imported_visitors_20240101_20240102.csv": "date,visitors,pageviews,bounces,visits,visit_duration\n" +
+			"2024-01-01,3,7,2,4,300\n2024-01-02,1,1,1,1,0\n",
+		"imported_entry_pages_20240101_20240102.csv": "date,entry_page,visitors,entrances,visit_duration,bounces,pageviews\n" +
+			"2024-01-01,/,2,3,0,0,0\n",
+		"imported_operating_systems_20240101_20240102.csv": "date,operating_system,operating_system_version,visitors,visits\n" +
+			"2024-01-01,Mac,14,2,2\n",
+		"notes.txt": "not part of the export",
+	})
+
+	export, err := ReadPlausibleExport(dir)
+	require.NoError(t, err)
+	assert.Equal(t, []string{"2024-01-01", "2024-01-02"}, export.Days())
+
+	day := export.days["2024-01-01"]
+	assert.True(t, day.hasTotals)
+	assert.Equal(t, int64(4), day.visits)
+	assert.Equal(t, int64(7), day.pageviews)
+	assert.Equal(t, []weighted{{"/", 3}}, day.entries)
+	assert.Equal(t, []weighted{{"macOS", 2}}, day.oses)
+
+	_, err = ReadPlausibleExport(writeExport(t, map[string]string{"readme.md": "#"}))
+	assert.Error(t, err)
+}
+
+func TestPlausibleTable(t *testing.T) {
+	assert.Equal(t, "entry_pages", plausibleTable("export/imported_entry_pages_20230101_20231231.csv"))
+	assert.Equal(t, "visitors", plausibleTable("imported_visitors.csv"))
+	assert.Equal(t, "", plausibleTable("visitors.csv"))
+}
+
+func TestSynthesizeMatchesDayTotals(t *testing.T) {
+	day := &plausibleDay{
+		hasTotals: true, visits: 4, pageviews: 7, bounces: 2, visitDuration: 300,
+		pages:     []weighted{{"/", 4}, {"/pricing", 2}, {"/docs?page=2", 1}},
+		hostnames: map[string]string{"/pricing": "example.com"},
+		sources: []plausibleSource{
+			{referrer: "news.ycombinator.com/item", visits: 1},
+			{referrer: "Newsletter", utm: [5]string{"newsletter", "email", "launch"}, visits: 1},
+		},
+		countries: []weighted{{"DE", 3}, {"US", 1}},
+		devices:   []weighted{{"mobile", 4}},
+		events:    []plausibleEvent{{name: "Signup", path: "/pricing", count: 2}},
+	}
+	start := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	sessions, events := day.synthesize("example.com/2024-01-01/", start)
+
+	require.Len(t, sessions, 4)
+	pageviews := map[string]int{}
+	perSession := map[uuid.UUID]int{}
+	var custom int
+	for _, e := range events {
+		if e.Type == 2 {
+			custom++
+			assert.Equal(t, "Signup", e.Name)
+			assert.JSONEq(t, `{"path":"/pricing"}`, string(e.Props))
+			continue
+		}
+		pageviews[e.URLPath]++
+		perSession[e.SessionID]++
+		assert.False(t, e.CreatedAt.Before(start))
+	}
+	assert.Equal(t, map[string]int{"/": 4, "/pricing": 2, "/docs": 1}, pageviews)
+	assert.Equal(t, 2, custom)
+
+	bounced := 0
+	for _, n := range perSession {
+		if n == 1 {
+			bounced++
+		}
+	}
+	assert.Equal(t, 2, bounced)
+
+	countries := map[string]int{}
+	for _, s := range sessions {
+		countries[s.Country]++
+		assert.Equal(t, "mobile", s.Device)
+	}
+	assert.Equal(t, map[string]int{"DE": 3, "US": 1}, countries)
+
+	// The first pageview of a visit carries its source
+	assert.Equal(t, "news.ycombinator.com", events[0].ReferrerDomain)
+	assert.Equal(t, "/item", events[0].ReferrerPath)
+	var campaigns int
+	for _, e := range events {
+		if e.UTMCampaign == "launch" {
+			campaigns++
+			assert.Equal(t, "Newsletter", e.ReferrerDomain)
+		}
+	}
+	assert.Equal(t, 1, campaigns)
+
+	// Importing the same day again produces the same rows
+	again, _ := day.synthesize("example.com/2024-01-01/", start)
+	assert.Equal(t, sessions, again)
+}
+
+func TestSynthesizeWithoutTotals(t *testing.T) {
+	day := &plausibleDay{
+		pages:     []weighted{{"/", 5}},
+		countries: []weighted{{"FR", 2}},
+	}
+	sessions, events := day.synthesize("x/", time.Now())
+	assert.Len(t, sessions, 2)
+	assert.Len(t, events, 5)
+
+	sessions, events = (&plausibleDay{}).synthesize("x/", time.Now())
+	assert.Empty(t, sessions)
+	assert.Empty(t, events)
+}
+
+func TestImportPlausibleSkipsImportedDays(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	export, err := ReadPlausibleExport(writeExport(t, map[string]string{
+		"imported_visitors.csv": "date,visitors,pageviews,bounces,visits,visit_duration\n" +
+			"2024-01-01,1,1,1,1,0\n2024-01-02,1,2,0,1,60\n",
+	}))
+	require.NoError(t, err)
+
+	websiteID := uuid.New()
+	done := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	mock.ExpectQuery("SELECT website_id, cursor_at, cursor_id, imported FROM import_progress").
+		WithArgs("plausible", "example.com").
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "cursor_at", "cursor_id", "imported"}).
+			AddRow(websiteID.String(), done, nil, 1))
+	mock.ExpectBegin()
+	mock.ExpectExec("INSERT INTO session").WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("SELECT ensure_partitions_between").WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO website_event").WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectExec("INSERT INTO import_progress").
+		WithArgs("plausible", "example.com", websiteID, done.AddDate(0, 0, 1), "", int64(3)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+	mock.ExpectExec("UPDATE import_progress SET completed_at").WillReturnResult(sqlmock.NewResult(0, 1))
+
+	var reported []Progress
+	result, err := ImportPlausible(context.Background(), db, export, PlausibleOptions{
+		Domain:   "Example.com",
+		Progress: func(p Progress) { reported = append(reported, p) },
+	})
+	require.NoError(t, err)
+	assert.Equal(t, websiteID, result.WebsiteID)
+	assert.Equal(t, 1, result.Days)
+	assert.Equal(t, int64(3), result.Imported)
+	require.Len(t, reported, 1)
+	assert.Equal(t, int64(2), reported[0].Done)
+	assert.Equal(t, int64(2), reported[0].Total)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,364 @@
+package importer
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+)
+
+const sourceUmami = "umami"
+
+// UmamiOptions configures ImportUmami
+type UmamiOptions struct {
+	Website  string   // Only the Umami website with this domain or ID; empty imports all
+	Restart  bool     // Start over instead of continuing from the recorded progress
+	Batch    int      // Events per transaction
+	Progress Reporter // Optional
+}
+
+// UmamiSite is an Umami website and what became of it
+type UmamiSite struct {
+	SourceID  uuid.UUID
+	Domain    string
+	Name      string
+	CreatedAt time.Time
+	WebsiteID uuid.UUID // The Kaunta website written to
+	Created   bool      // The Kaunta website was created by the import
+	Imported  int64     // Events written, including earlier runs
+}
+
+// umamiColumns lists the columns an Umami database has. Umami added and
+// moved columns between releases (visit_id, tag and the UTM columns came
+// later, hostname moved from session to website_event), so the queries only
+// read what is there.
+type umamiColumns map[string]bool
+
+func loadUmamiColumns(ctx context.Context, src *sql.DB) (umamiColumns, error) {
+	rows, err := src.QueryContext(ctx, `
+		SELECT table_name || '.' || column_name FROM information_schema.columns
+		WHERE table_schema = current_schema()
+		  AND table_name IN ('website', 'session', 'website_event', 'event_data')
+	`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	cols := make(umamiColumns)
+	for rows.Next() {
+		var col string
+		if err := rows.Scan(&col); err != nil {
+			return nil, err
+		}
+		cols[col] = true
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	for _, required := range []string{"website.website_id", "session.session_id", "website_event.event_id"} {
+		if !cols[required] {
+			return nil, fmt.Errorf("not an Umami v2 database: %s is missing", required)
+		}
+	}
+	return cols, nil
+}
+
+// expr returns alias.column when the column exists and NULL otherwise
+func (c umamiColumns) expr(table, alias, column string) string {
+	if c[table+"."+column] {
+		return alias + "." + column
+	}
+	return "NULL"
+}
+
+func (c umamiColumns) eventQuery() string {
+	e := func(column string) string { return c.expr("website_event", "e", column) }
+	visitID := "e.session_id"
+	if c["website_event.visit_id"] {
+		visitID = "COALESCE(e.visit_id, e.session_id)"
+	}
+	// Umami keeps custom event properties as one row per key
+	props := "NULL"
+	if c["event_data.website_event_id"] && c["event_data.data_key"] {
+		props = `(SELECT jsonb_object_agg(d.data_key, CASE
+				WHEN d.number_value IS NOT NULL THEN to_jsonb(d.number_value)
+				WHEN d.date_value IS NOT NULL THEN to_jsonb(d.date_value)
+				WHEN d.data_type = 3 THEN to_jsonb(d.string_value = 'true')
+				ELSE to_jsonb(d.string_value)
+			END)::text
+			FROM event_data d WHERE d.website_event_id = e.event_id)`
+	}
+	return `
+		SELECT e.event_id, e.session_id, ` + visitID + `, e.created_at, e.event_type,
+		       COALESCE(e.url_path, ''), COALESCE(e.url_query, ''),
+		       COALESCE(e.referrer_path, ''), COALESCE(e.referrer_query, ''), COALESCE(e.referrer_domain, ''),
+		       COALESCE(e.page_title, ''), COALESCE(` + e("hostname") + `, ''),
+		       COALESCE(e.event_name, ''), COALESCE(` + e("tag") + `, ''),
+		       COALESCE(` + e("utm_source") + `, ''), COALESCE(` + e("utm_medium") + `, ''),
+		       COALESCE(` + e("utm_campaign") + `, ''), COALESCE(` + e("utm_term") + `, ''),
+		       COALESCE(` + e("utm_content") + `, ''), ` + props + `
+		FROM website_event e
+		WHERE e.website_id = $1
+		  AND e.event_type IN (1, 2)
+		  AND (e.created_at, e.event_id) > ($2, $3::uuid)
+		ORDER BY e.created_at, e.event_id
+		LIMIT $4`
+}
+
+func (c umamiColumns) sessionQuery() string {
+	s := func(column string) string { return c.expr("session", "s", column) }
+	return `
+		SELECT s.session_id, COALESCE(` + s("hostname") + `, ''), COALESCE(s.browser, ''), COALESCE(s.os, ''),
+		       COALESCE(s.device, ''), COALESCE(s.screen, ''), COALESCE(s.language, ''), COALESCE(s.country, ''),
+		       COALESCE(` + s("region") + `, ` + s("subdivision1") + `, ''), COALESCE(s.city, ''),
+		       COALESCE(` + s("distinct_id") + `, ''), s.created_at
+		FROM session s
+		WHERE s.session_id = ANY($1::uuid[])`
+}
+
+// UmamiSites lists the websites of an Umami database, optionally only the
+// one with the given domain or ID
+func UmamiSites(ctx context.Context, src *sql.DB, website string) ([]UmamiSite, error) {
+	rows, err := src.QueryContext(ctx, `
+		SELECT website_id, domain, COALESCE(name, ''), created_at FROM website
+		WHERE deleted_at IS NULL AND ($1 = '' OR website_id::text = $1 OR LOWER(domain) = LOWER($1))
+		ORDER BY created_at
+	`, website)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var sites []UmamiSite
+	for rows.Next() {
+		var site UmamiSite
+		var createdAt sql.NullTime
+		if err := rows.Scan(&site.SourceID, &site.Domain, &site.Name, &createdAt); err != nil {
+			return nil, err
+		}
+		site.CreatedAt = createdAt.Time
+		sites = append(sites, site)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	if website != "" && len(sites) == 0 {
+		return nil, fmt.Errorf("website '%s' not found in the Umami database", website)
+	}
+	return sites, nil
+}
+
+// ImportUmami copies the websites, sessions, pageviews and custom events of
+// an Umami (v2, PostgreSQL) database into dst. Each Umami website goes into
+// the Kaunta website with the same ID or domain, created when there is
+// none. It returns the sites gone through, including the one that failed.
+func ImportUmami(ctx context.Context, dst, src *sql.DB, opts UmamiOptions) ([]UmamiSite, error) {
+	if opts.Batch <= 0 {
+		opts.Batch = writeChunk
+	}
+	cols, err := loadUmamiColumns(ctx, src)
+	if err != nil {
+		return nil, err
+	}
+	sites, err := UmamiSites(ctx, src, opts.Website)
+	if err != nil {
+		return nil, err
+	}
+
+	for i := range sites {
+		if err := importUmamiSite(ctx, dst, src, cols, &sites[i], opts); err != nil {
+			return sites[:i+1], fmt.Errorf("%s: %w", sites[i].Domain, err)
+		}
+	}
+	return sites, nil
+}
+
+func importUmamiSite(ctx context.Context, dst, src *sql.DB, cols umamiColumns, site *UmamiSite, opts UmamiOptions) error {
+	sourceID := site.SourceID.String()
+	c, resumed, err := loadCursor(ctx, dst, sourceUmami, sourceID)
+	if err != nil {
+		return err
+	}
+	if resumed && opts.Restart {
+		c = cursor{websiteID: c.websiteID}
+	}
+	if !resumed {
+		c.websiteID, site.Created, err = resolveWebsite(ctx, dst, site.SourceID, site.Domain, site.Name, site.CreatedAt)
+		if err != nil {
+			return err
+		}
+	}
+	site.WebsiteID = c.websiteID
+
+	progress := Progress{Website: site.Domain, Imported: c.imported}
+	if opts.Progress != nil {
+		if err := src.QueryRowContext(ctx, `
+			SELECT COUNT(*) FROM website_event e
+			WHERE e.website_id = $1 AND e.event_type IN (1, 2) AND (e.created_at, e.event_id) > ($2, $3::uuid)
+		`, site.SourceID, c.at, cursorID(c.id)).Scan(&progress.Total); err != nil {
+			return err
+		}
+		opts.Progress(progress)
+	}
+
+	eventQuery, sessionQuery := cols.eventQuery(), cols.sessionQuery()
+	for {
+		events, err := readUmamiEvents(ctx, src, eventQuery, site.SourceID, c, opts.Batch)
+		if err != nil {
+			return fmt.Errorf("failed to read events: %w", err)
+		}
+		if len(events) == 0 {
+			break
+		}
+		sessions, err := readUmamiSessions(ctx, src, sessionQuery, events)
+		if err != nil {
+			return fmt.Errorf("failed to read sessions: %w", err)
+		}
+
+		last := events[len(events)-1]
+		c.at, c.id = last.CreatedAt, last.ID.String()
+		err = commitBatch(ctx, dst, c.websiteID, sessions, events, func(tx *sql.Tx, written int64) error {
+			c.imported += written
+			return saveCursor(ctx, tx, sourceUmami, sourceID, c)
+		})
+		if err != nil {
+			return err
+		}
+
+		progress.Imported = c.imported
+		progress.Done += int64(len(events))
+		progress.Through = c.at
+		if opts.Progress != nil {
+			opts.Progress(progress)
+		}
+		if len(events) < opts.Batch {
+			break
+		}
+	}
+
+	site.Imported = c.imported
+	if !resumed && progress.Done == 0 {
+		// Nothing to import yet; remember the website anyway
+		if err := commitProgress(ctx, dst, sourceUmami, sourceID, c); err != nil {
+			return err
+		}
+	}
+	return markComplete(ctx, dst, sourceUmami, sourceID)
+}
+
+// cursorID is the event ID to continue after, the nil UUID at the start
+func cursorID(id string) string {
+	if id == "" {
+		return uuid.Nil.String()
+	}
+	return id
+}
+
+func readUmamiEvents(ctx context.Context, src *sql.DB, query string, websiteID uuid.UUID, c cursor, limit int) ([]Event, error) {
+	rows, err := src.QueryContext(ctx, query, websiteID, c.at, cursorID(c.id), limit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var events []Event
+	for rows.Next() {
+		var e Event
+		var props sql.NullString
+		if err := rows.Scan(&e.ID, &e.SessionID, &e.VisitID, &e.CreatedAt, &e.Type,
+			&e.URLPath, &e.URLQuery, &e.ReferrerPath, &e.ReferrerQuery, &e.ReferrerDomain,
+			&e.PageTitle, &e.Hostname, &e.Name, &e.Tag,
+			&e.UTMSource, &e.UTMMedium, &e.UTMCampaign, &e.UTMTerm, &e.UTMContent, &props); err != nil {
+			return nil, err
+		}
+		if props.Valid {
+			e.Props = []byte(props.String)
+		}
+		events = append(events, e)
+	}
+	return events, rows.Err()
+}
+
+func readUmamiSessions(ctx context.Context, src *sql.DB, query string, events []Event) ([]Session, error) {
+	seen := make(map[uuid.UUID]bool)
+	var ids []string
+	for _, e := range events {
+		if !seen[e.SessionID] {
+			seen[e.SessionID] = true
+			ids = append(ids, e.SessionID.String())
+		}
+	}
+
+	rows, err := src.QueryContext(ctx, query, pq.Array(ids))
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var sessions []Session
+	for rows.Next() {
+		var s Session
+		var createdAt sql.NullTime
+		if err := rows.Scan(&s.ID, &s.Hostname, &s.Browser, &s.OS, &s.Device, &s.Screen, &s.Language,
+			&s.Country, &s.Region, &s.City, &s.DistinctID, &createdAt); err != nil {
+			return nil, err
+		}
+		s.CreatedAt = createdAt.Time
+		delete(seen, s.ID)
+		sessions = append(sessions, s)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+
+	// Events can outlive their session in Umami; keep them with a bare one
+	for _, e := range events {
+		if seen[e.SessionID] {
+			delete(seen, e.SessionID)
+			sessions = append(sessions, Session{ID: e.SessionID, CreatedAt: e.CreatedAt})
+		}
+	}
+	for i := range sessions {
+		if sessions[i].CreatedAt.IsZero() {
+			sessions[i].CreatedAt = events[0].CreatedAt
+		}
+	}
+	return sessions, nil
+}
+
+// commitBatch writes a batch in one transaction together with the progress
+// recorded by save, which is given the events written
+func commitBatch(ctx context.Context, db *sql.DB, websiteID uuid.UUID, sessions []Session, events []Event, save func(tx *sql.Tx, written int64) error) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	written, err := writeBatch(ctx, tx, websiteID, sessions, events)
+	if err != nil {
+		return err
+	}
+	if err := save(tx, written); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+// commitProgress records progress without writing events
+func commitProgress(ctx context.Context, db *sql.DB, source, sourceID string, c cursor) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if err := saveCursor(ctx, tx, source, sourceID, c); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
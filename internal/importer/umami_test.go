@@ -0,0 +1,46 @@
+package importer
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestUmamiQueriesFollowSchemaVersion(t *testing.T) {
+	// An early v2 schema: hostname on the session, no visits, tags or UTM columns
+	old := umamiColumns{
+		"website_event.event_id": true, "session.session_id": true, "session.hostname": true,
+		"session.subdivision1": true,
+	}
+	assert.Contains(t, old.eventQuery(), "SELECT e.event_id, e.session_id, e.session_id, e.created_at")
+	assert.Contains(t, old.eventQuery(), "COALESCE(NULL, '')")
+	assert.NotContains(t, old.eventQuery(), "event_data")
+	assert.Contains(t, old.sessionQuery(), "COALESCE(s.hostname, '')")
+	assert.Contains(t, old.sessionQuery(), "COALESCE(NULL, s.subdivision1, '')")
+
+	current := umamiColumns{
+		"website_event.event_id": true, "website_event.visit_id": true, "website_event.hostname": true,
+		"website_event.utm_source": true, "session.session_id": true, "session.region": true,
+		"event_data.website_event_id": true, "event_data.data_key": true,
+	}
+	assert.Contains(t, current.eventQuery(), "COALESCE(e.visit_id, e.session_id)")
+	assert.Contains(t, current.eventQuery(), "COALESCE(e.hostname, '')")
+	assert.Contains(t, current.eventQuery(), "COALESCE(e.utm_source, '')")
+	assert.Contains(t, current.eventQuery(), "FROM event_data d WHERE d.website_event_id = e.event_id")
+	assert.Contains(t, current.sessionQuery(), "COALESCE(NULL, '')")
+}
+
+func TestLoadUmamiColumnsRejectsOtherDatabases(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	mock.ExpectQuery("FROM information_schema.columns").
+		WillReturnRows(sqlmock.NewRows([]string{"column"}).AddRow("website.website_id"))
+
+	_, err = loadUmamiColumns(context.Background(), db)
+	assert.ErrorContains(t, err, "not an Umami v2 database")
+}
@@ -0,0 +1,69 @@
+// Package mailer sends plain-text account emails (invitations, password
+// resets) through the configured SMTP server.
+package mailer
+
+import (
+	"errors"
+	"fmt"
+	"net"
+	"net/smtp"
+	"strings"
+	"time"
+
+	"github.com/seuros/kaunta/internal/config"
+)
+
+// ErrNotConfigured is returned by Send while no SMTP host is configured
+var ErrNotConfigured = errors.New("email requires smtp.host to be configured")
+
+// Mailer delivers messages over SMTP
+type Mailer struct {
+	smtp     config.SMTPConfig
+	sendMail func(addr string, a smtp.Auth, from string, to []string, msg []byte) error
+	now      func() time.Time
+}
+
+// New creates a mailer; sending needs smtpConfig.Host to be set
+func New(smtpConfig config.SMTPConfig) *Mailer {
+	return &Mailer{smtp: smtpConfig, sendMail: smtp.SendMail, now: time.Now}
+}
+
+// Enabled reports whether an SMTP server is configured
+func (m *Mailer) Enabled() bool {
+	return m != nil && m.smtp.Host != ""
+}
+
+// Send delivers a plain-text message to one recipient
+func (m *Mailer) Send(to, subject, body string) error {
+	if !m.Enabled() {
+		return ErrNotConfigured
+	}
+	from := m.smtp.From
+	if from == "" {
+		from = "kaunta@" + m.smtp.Host
+	}
+
+	var auth smtp.Auth
+	if m.smtp.Username != "" {
+		auth = smtp.PlainAuth("", m.smtp.Username, m.smtp.Password, m.smtp.Host)
+	}
+
+	addr := net.JoinHostPort(m.smtp.Host, m.smtp.Port)
+	return m.sendMail(addr, auth, from, []string{to}, m.build(from, to, subject, body))
+}
+
+// build renders the message with its headers. Header values are stripped
+// of line breaks so user input can't inject headers.
+func (m *Mailer) build(from, to, subject, body string) []byte {
+	clean := strings.NewReplacer("\r", "", "\n", "")
+
+	var b strings.Builder
+	fmt.Fprintf(&b, "From: %s\r\n", from)
+	fmt.Fprintf(&b, "To: %s\r\n", clean.Replace(to))
+	fmt.Fprintf(&b, "Subject: %s\r\n", clean.Replace(subject))
+	fmt.Fprintf(&b, "Date: %s\r\n", m.now().Format(time.RFC1123Z))
+	b.WriteString("MIME-Version: 1.0\r\n")
+	b.WriteString("Content-Type: text/plain; charset=utf-8\r\n\r\n")
+	b.WriteString(strings.ReplaceAll(strings.ReplaceAll(body, "\r\n", "\n"), "\n", "\r\n"))
+	return []byte(b.String())
+}
@@ -0,0 +1,43 @@
+package mailer
+
+import (
+	"net/smtp"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/config"
+)
+
+func TestSendRequiresHost(t *testing.T) {
+	m := New(config.SMTPConfig{Port: "587"})
+	assert.False(t, m.Enabled())
+	assert.ErrorIs(t, m.Send("bob@example.com", "Hi", "Hello"), ErrNotConfigured)
+
+	var nilMailer *Mailer
+	assert.False(t, nilMailer.Enabled())
+}
+
+func TestSendBuildsMessage(t *testing.T) {
+	m := New(config.SMTPConfig{Host: "mail.example.com", Port: "25"})
+	m.now = func() time.Time { return time.Date(2026, 1, 2, 3, 4, 5, 0, time.UTC) }
+
+	var gotAddr string
+	var gotTo []string
+	var gotMsg string
+	m.sendMail = func(addr string, a smtp.Auth, from string, to []string, msg []byte) error {
+		gotAddr, gotTo, gotMsg = addr, to, string(msg)
+		assert.Nil(t, a)
+		assert.Equal(t, "kaunta@mail.example.com", from)
+		return nil
+	}
+
+	require.NoError(t, m.Send("bob@example.com", "Join\r\nBcc: evil@example.com", "Line one\nLine two"))
+	assert.Equal(t, "mail.example.com:25", gotAddr)
+	assert.Equal(t, []string{"bob@example.com"}, gotTo)
+	assert.Contains(t, gotMsg, "Subject: JoinBcc: evil@example.com\r\n")
+	assert.Contains(t, gotMsg, "Line one\r\nLine two")
+	assert.Contains(t, gotMsg, "Date: Fri, 02 Jan 2026 03:04:05 +0000")
+}
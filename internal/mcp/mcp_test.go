@@ -0,0 +1,117 @@
+package mcp
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/openapi"
+)
+
+func testServer() *Server {
+	s := NewServer("test", "1.0.0", "Test server")
+	s.AddTool(Tool{Name: "echo", Description: "Echo", InputSchema: &openapi.Schema{Type: "object"}},
+		func(ctx context.Context, arguments json.RawMessage) (any, error) {
+			var args map[string]any
+			_ = json.Unmarshal(arguments, &args)
+			if args["fail"] == true {
+				return nil, errors.New("tool failed")
+			}
+			return args, nil
+		})
+	return s
+}
+
+func call(t *testing.T, s *Server, message string) map[string]any {
+	t.Helper()
+	out := s.Handle(context.Background(), []byte(message))
+	require.NotNil(t, out)
+	var resp map[string]any
+	require.NoError(t, json.Unmarshal(out, &resp))
+	return resp
+}
+
+func TestInitialize(t *testing.T) {
+	resp := call(t, testServer(), `{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"c","version":"1"}}}`)
+	result := resp["result"].(map[string]any)
+	assert.Equal(t, "2025-03-26", result["protocolVersion"])
+	assert.Equal(t, "Test server", result["instructions"])
+	assert.Contains(t, result["capabilities"], "tools")
+
+	// Unknown revisions get the newest one
+	resp = call(t, testServer(), `{"jsonrpc":"2.0","id":2,"method":"initialize","params":{"protocolVersion":"1999-01-01"}}`)
+	assert.Equal(t, ProtocolVersion, resp["result"].(map[string]any)["protocolVersion"])
+}
+
+func TestToolsListAndCall(t *testing.T) {
+	s := testServer()
+
+	resp := call(t, s, `{"jsonrpc":"2.0","id":"a","method":"tools/list"}`)
+	assert.Equal(t, "a", resp["id"])
+	tools := resp["result"].(map[string]any)["tools"].([]any)
+	require.Len(t, tools, 1)
+	assert.Equal(t, "echo", tools[0].(map[string]any)["name"])
+
+	resp = call(t, s, `{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"x":1}}}`)
+	result := resp["result"].(map[string]any)
+	assert.Nil(t, result["isError"])
+	assert.Equal(t, map[string]any{"x": 1.0}, result["structuredContent"])
+	assert.JSONEq(t, `{"x": 1}`, result["content"].([]any)[0].(map[string]any)["text"].(string))
+
+	resp = call(t, s, `{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"echo","arguments":{"fail":true}}}`)
+	result = resp["result"].(map[string]any)
+	assert.Equal(t, true, result["isError"])
+	assert.Equal(t, "tool failed", result["content"].([]any)[0].(map[string]any)["text"])
+
+	resp = call(t, s, `{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"nope"}}`)
+	assert.Equal(t, float64(codeInvalidParams), resp["error"].(map[string]any)["code"])
+}
+
+func TestProtocolErrors(t *testing.T) {
+	s := testServer()
+
+	resp := call(t, s, `{not json`)
+	assert.Equal(t, float64(codeParseError), resp["error"].(map[string]any)["code"])
+	assert.Nil(t, resp["id"])
+
+	resp = call(t, s, `{"jsonrpc":"2.0","id":1,"method":"resources/list"}`)
+	assert.Equal(t, float64(codeMethodNotFound), resp["error"].(map[string]any)["code"])
+
+	resp = call(t, s, `{"id":1,"method":"ping"}`)
+	assert.Equal(t, float64(codeInvalidRequest), resp["error"].(map[string]any)["code"])
+
+	assert.Nil(t, s.Handle(context.Background(), []byte(`{"jsonrpc":"2.0","method":"notifications/initialized"}`)))
+}
+
+func TestServeStdio(t *testing.T) {
+	in := strings.NewReader(`{"jsonrpc":"2.0","method":"notifications/initialized"}` + "\n\n" +
+		`{"jsonrpc":"2.0","id":1,"method":"ping"}` + "\n")
+	var out bytes.Buffer
+	require.NoError(t, testServer().ServeStdio(context.Background(), in, &out))
+	assert.Equal(t, `{"jsonrpc":"2.0","id":1,"result":{}}`+"\n", out.String())
+}
+
+func TestServeHTTP(t *testing.T) {
+	s := testServer()
+
+	rec := httptest.NewRecorder()
+	s.ServeHTTP(rec, httptest.NewRequest(http.MethodPost, "/api/mcp", strings.NewReader(`{"jsonrpc":"2.0","id":1,"method":"ping"}`)))
+	assert.Equal(t, http.StatusOK, rec.Code)
+	assert.Equal(t, "application/json", rec.Header().Get("Content-Type"))
+
+	rec = httptest.NewRecorder()
+	s.ServeHTTP(rec, httptest.NewRequest(http.MethodPost, "/api/mcp", strings.NewReader(`{"jsonrpc":"2.0","method":"notifications/initialized"}`)))
+	assert.Equal(t, http.StatusAccepted, rec.Code)
+
+	rec = httptest.NewRecorder()
+	s.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, "/api/mcp", nil))
+	assert.Equal(t, http.StatusMethodNotAllowed, rec.Code)
+}
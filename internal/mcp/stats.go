@@ -0,0 +1,353 @@
+package mcp
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log"
+	"slices"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/openapi"
+	"github.com/seuros/kaunta/internal/query"
+)
+
+const (
+	defaultDays  = 7
+	defaultLimit = 10
+	maxLimit     = 100
+)
+
+// errQueryFailed hides database details from the model; the cause is logged
+var errQueryFailed = errors.New("query failed, see the server log")
+
+// Website is a tracked website as returned by list_websites
+type Website struct {
+	WebsiteID string `json:"website_id"`
+	Domain    string `json:"domain"`
+	Name      string `json:"name,omitempty"`
+}
+
+// Period is a resolved date range; To is exclusive
+type Period struct {
+	From time.Time `json:"from"`
+	To   time.Time `json:"to"`
+}
+
+// periodArgs are the arguments every report tool accepts
+type periodArgs struct {
+	Website string `json:"website"`
+	Days    int    `json:"days"`
+	From    string `json:"from"`
+	To      string `json:"to"`
+}
+
+type stats struct {
+	Pageviews         int64    `json:"pageviews"`
+	Visitors          int64    `json:"visitors"`
+	Visits            int64    `json:"visits"`
+	AvgEngagementTime *float64 `json:"avg_engagement_time_seconds"`
+}
+
+type statsResult struct {
+	Website        Website            `json:"website"`
+	Period         Period             `json:"period"`
+	PreviousPeriod Period             `json:"previous_period"`
+	Current        stats              `json:"current"`
+	Previous       stats              `json:"previous"`
+	Change         map[string]float64 `json:"change_percent"`
+}
+
+type breakdownResult struct {
+	Website   Website          `json:"website"`
+	Period    Period           `json:"period"`
+	Dimension string           `json:"dimension"`
+	Rows      []map[string]any `json:"rows"`
+}
+
+// Querier runs the tools' queries: a *sql.DB, or a *sql.Tx such as the
+// tenant-scoped one from database.StatsQuerier
+type Querier interface {
+	QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error)
+	QueryRowContext(ctx context.Context, query string, args ...any) *sql.Row
+}
+
+// statsTools serves the stats tools from db; a non-nil website limits every
+// tool to that website
+type statsTools struct {
+	db      Querier
+	website *uuid.UUID
+	now     func() time.Time
+}
+
+// NewStatsServer creates a server with the list_websites, get_stats,
+// top_pages and breakdown tools. When website is set (an API key's website)
+// the tools can only read that website.
+func NewStatsServer(db Querier, website *uuid.UUID, version string) *Server {
+	t := &statsTools{db: db, website: website, now: time.Now}
+	s := NewServer("kaunta", version,
+		"Kaunta is a web analytics server. Call list_websites first to find the domain, then get_stats for "+
+			"headline numbers compared with the previous period, top_pages, or breakdown for referrers, countries, "+
+			"devices and other dimensions. Periods are the last `days` (default 7) or from/to dates (YYYY-MM-DD, "+
+			"inclusive, UTC).")
+
+	s.AddTool(Tool{
+		Name:        "list_websites",
+		Title:       "List websites",
+		Description: "List the tracked websites with their IDs and domains.",
+		InputSchema: &openapi.Schema{Type: "object", Properties: map[string]*openapi.Schema{}},
+	}, t.listWebsites)
+	s.AddTool(Tool{
+		Name:        "get_stats",
+		Title:       "Get stats",
+		Description: "Pageviews, unique visitors, visits and average engagement time for a period, with the previous period of the same length and the change in percent.",
+		InputSchema: periodSchema(nil, nil),
+	}, t.getStats)
+	s.AddTool(Tool{
+		Name:        "top_pages",
+		Title:       "Top pages",
+		Description: "The most viewed pages of a period with their pageviews and unique visitors.",
+		InputSchema: periodSchema(map[string]*openapi.Schema{
+			"limit": {Type: "integer", Description: fmt.Sprintf("Number of pages, 1-%d (default %d)", maxLimit, defaultLimit)},
+		}, nil),
+	}, t.topPages)
+	s.AddTool(Tool{
+		Name:        "breakdown",
+		Title:       "Breakdown",
+		Description: "Visitors and pageviews of a period grouped by a dimension such as referrer, country, device, browser or utm_source.",
+		InputSchema: periodSchema(map[string]*openapi.Schema{
+			"dimension": {Type: "string", Enum: query.Dimensions()},
+			"limit":     {Type: "integer", Description: fmt.Sprintf("Number of rows, 1-%d (default %d)", maxLimit, defaultLimit)},
+		}, []string{"dimension"}),
+	}, t.breakdown)
+	return s
+}
+
+// periodSchema is the input schema of a report tool: the website, the period
+// and any extra properties
+func periodSchema(extra map[string]*openapi.Schema, required []string) *openapi.Schema {
+	properties := map[string]*openapi.Schema{
+		"website": {Type: "string", Description: "Domain or website ID"},
+		"days":    {Type: "integer", Description: fmt.Sprintf("Period ending now, 1-365 (default %d); ignored when from and to are set", defaultDays)},
+		"from":    {Type: "string", Format: "date", Description: "First day, YYYY-MM-DD"},
+		"to":      {Type: "string", Format: "date", Description: "Last day (inclusive), YYYY-MM-DD"},
+	}
+	for name, schema := range extra {
+		properties[name] = schema
+	}
+	return &openapi.Schema{Type: "object", Properties: properties, Required: required}
+}
+
+func (t *statsTools) listWebsites(ctx context.Context, _ json.RawMessage) (any, error) {
+	rows, err := t.db.QueryContext(ctx, `
+		SELECT website_id, domain, COALESCE(name, '')
+		FROM website
+		WHERE deleted_at IS NULL AND ($1::UUID IS NULL OR website_id = $1)
+		ORDER BY domain
+	`, t.website)
+	if err != nil {
+		log.Printf("mcp list_websites: %v", err)
+		return nil, errQueryFailed
+	}
+	defer func() { _ = rows.Close() }()
+
+	websites := make([]Website, 0)
+	for rows.Next() {
+		var site Website
+		if err := rows.Scan(&site.WebsiteID, &site.Domain, &site.Name); err != nil {
+			log.Printf("mcp list_websites: %v", err)
+			return nil, errQueryFailed
+		}
+		websites = append(websites, site)
+	}
+	return map[string]any{"websites": websites}, rows.Err()
+}
+
+func (t *statsTools) getStats(ctx context.Context, arguments json.RawMessage) (any, error) {
+	var args periodArgs
+	if err := json.Unmarshal(arguments, &args); err != nil {
+		return nil, fmt.Errorf("invalid arguments: %v", err)
+	}
+	site, period, err := t.resolve(ctx, args)
+	if err != nil {
+		return nil, err
+	}
+
+	length := period.To.Sub(period.From)
+	result := statsResult{
+		Website:        site,
+		Period:         period,
+		PreviousPeriod: Period{From: period.From.Add(-length), To: period.From},
+		Change:         make(map[string]float64),
+	}
+	for _, p := range []struct {
+		period Period
+		into   *stats
+	}{{result.Period, &result.Current}, {result.PreviousPeriod, &result.Previous}} {
+		rows, err := t.run(ctx, site, p.period, query.Request{
+			Metrics: []string{"pageviews", "visitors", "visits", "avg_engagement_time"},
+		})
+		if err != nil {
+			return nil, err
+		}
+		row := rows[0]
+		p.into.Pageviews, p.into.Visitors, p.into.Visits = row[0].(int64), row[1].(int64), row[2].(int64)
+		if avg, ok := row[3].(float64); ok {
+			p.into.AvgEngagementTime = &avg
+		}
+	}
+
+	for name, pair := range map[string][2]int64{
+		"pageviews": {result.Current.Pageviews, result.Previous.Pageviews},
+		"visitors":  {result.Current.Visitors, result.Previous.Visitors},
+		"visits":    {result.Current.Visits, result.Previous.Visits},
+	} {
+		if pair[1] > 0 {
+			result.Change[name] = float64(pair[0]-pair[1]) * 100 / float64(pair[1])
+		}
+	}
+	return result, nil
+}
+
+func (t *statsTools) topPages(ctx context.Context, arguments json.RawMessage) (any, error) {
+	var args struct {
+		periodArgs
+		Limit int `json:"limit"`
+	}
+	if err := json.Unmarshal(arguments, &args); err != nil {
+		return nil, fmt.Errorf("invalid arguments: %v", err)
+	}
+	return t.grouped(ctx, args.periodArgs, "page", []string{"pageviews", "visitors"}, args.Limit)
+}
+
+func (t *statsTools) breakdown(ctx context.Context, arguments json.RawMessage) (any, error) {
+	var args struct {
+		periodArgs
+		Dimension string `json:"dimension"`
+		Limit     int    `json:"limit"`
+	}
+	if err := json.Unmarshal(arguments, &args); err != nil {
+		return nil, fmt.Errorf("invalid arguments: %v", err)
+	}
+	if !slices.Contains(query.Dimensions(), args.Dimension) {
+		return nil, fmt.Errorf("dimension must be one of: %s", strings.Join(query.Dimensions(), ", "))
+	}
+	return t.grouped(ctx, args.periodArgs, args.Dimension, []string{"visitors", "pageviews"}, args.Limit)
+}
+
+// grouped returns the top rows of a period grouped by dimension, ordered by
+// the first metric
+func (t *statsTools) grouped(ctx context.Context, args periodArgs, dimension string, metrics []string, limit int) (any, error) {
+	if limit == 0 {
+		limit = defaultLimit
+	}
+	if limit < 1 || limit > maxLimit {
+		return nil, fmt.Errorf("limit must be between 1 and %d", maxLimit)
+	}
+	site, period, err := t.resolve(ctx, args)
+	if err != nil {
+		return nil, err
+	}
+
+	columns := append([]string{dimension}, metrics...)
+	rows, err := t.run(ctx, site, period, query.Request{Metrics: metrics, Dimensions: []string{dimension}, Limit: limit})
+	if err != nil {
+		return nil, err
+	}
+	result := breakdownResult{Website: site, Period: period, Dimension: dimension, Rows: make([]map[string]any, 0, len(rows))}
+	for _, row := range rows {
+		item := make(map[string]any, len(columns))
+		for i, column := range columns {
+			item[column] = row[i]
+		}
+		result.Rows = append(result.Rows, item)
+	}
+	return result, nil
+}
+
+// run compiles req over the website and period and returns its rows
+func (t *statsTools) run(ctx context.Context, site Website, period Period, req query.Request) ([][]any, error) {
+	req.From, req.To = &period.From, &period.To
+	q, err := query.Compile(uuid.MustParse(site.WebsiteID), req)
+	if err != nil {
+		return nil, err
+	}
+
+	rows, err := t.db.QueryContext(ctx, q.SQL, q.Args...)
+	if err != nil {
+		log.Printf("mcp: %v", err)
+		return nil, errQueryFailed
+	}
+	defer func() { _ = rows.Close() }()
+
+	data, err := q.Scan(rows)
+	if err != nil {
+		log.Printf("mcp: %v", err)
+		return nil, errQueryFailed
+	}
+	return data, nil
+}
+
+// resolve finds the website named by args and the period it asks for
+func (t *statsTools) resolve(ctx context.Context, args periodArgs) (Website, Period, error) {
+	period, err := parsePeriod(args, t.now())
+	if err != nil {
+		return Website{}, Period{}, err
+	}
+
+	name := strings.TrimSpace(args.Website)
+	if name == "" && t.website == nil {
+		return Website{}, Period{}, errors.New("website is required; call list_websites to find it")
+	}
+
+	var site Website
+	err = t.db.QueryRowContext(ctx, `
+		SELECT website_id, domain, COALESCE(name, '')
+		FROM website
+		WHERE deleted_at IS NULL
+		  AND ($1 = '' OR website_id::TEXT = $1 OR domain = $1)
+		  AND ($2::UUID IS NULL OR website_id = $2)
+		LIMIT 1
+	`, name, t.website).Scan(&site.WebsiteID, &site.Domain, &site.Name)
+	if err == sql.ErrNoRows {
+		return Website{}, Period{}, fmt.Errorf("website not found: %s", name)
+	}
+	if err != nil {
+		log.Printf("mcp: %v", err)
+		return Website{}, Period{}, errQueryFailed
+	}
+	return site, period, nil
+}
+
+// parsePeriod reads from/to dates (to inclusive) or the last days ending now
+func parsePeriod(args periodArgs, now time.Time) (Period, error) {
+	if args.From != "" || args.To != "" {
+		from, err := time.Parse(time.DateOnly, args.From)
+		if err != nil {
+			return Period{}, errors.New("from must be a date (YYYY-MM-DD)")
+		}
+		to, err := time.Parse(time.DateOnly, args.To)
+		if err != nil {
+			return Period{}, errors.New("to must be a date (YYYY-MM-DD)")
+		}
+		if to.Before(from) {
+			return Period{}, errors.New("to must not be before from")
+		}
+		return Period{From: from, To: to.AddDate(0, 0, 1)}, nil
+	}
+
+	days := args.Days
+	if days == 0 {
+		days = defaultDays
+	}
+	if days < 1 || days > 365 {
+		return Period{}, errors.New("days must be between 1 and 365")
+	}
+	now = now.UTC()
+	return Period{From: now.AddDate(0, 0, -days), To: now}, nil
+}
@@ -0,0 +1,84 @@
+package mcp
+
+import (
+	"context"
+	"encoding/json"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParsePeriod(t *testing.T) {
+	now := time.Date(2026, 5, 10, 15, 0, 0, 0, time.UTC)
+
+	period, err := parsePeriod(periodArgs{}, now)
+	require.NoError(t, err)
+	assert.Equal(t, Period{From: now.AddDate(0, 0, -7), To: now}, period)
+
+	period, err = parsePeriod(periodArgs{From: "2026-05-04", To: "2026-05-10"}, now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2026, 5, 4, 0, 0, 0, 0, time.UTC), period.From)
+	assert.Equal(t, time.Date(2026, 5, 11, 0, 0, 0, 0, time.UTC), period.To)
+
+	for _, args := range []periodArgs{
+		{Days: 400},
+		{From: "2026-05-04"},
+		{From: "2026-05-10", To: "2026-05-04"},
+		{From: "last week", To: "2026-05-04"},
+	} {
+		_, err := parsePeriod(args, now)
+		assert.Error(t, err, args)
+	}
+}
+
+func TestStatsServerTools(t *testing.T) {
+	s := NewStatsServer(nil, nil, "1.0.0")
+	var names []string
+	for _, tool := range s.Tools() {
+		names = append(names, tool.Name)
+	}
+	assert.Equal(t, []string{"list_websites", "get_stats", "top_pages", "breakdown"}, names)
+}
+
+func TestGetStats(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("FROM website").
+		WithArgs("blog.example.com", nil).
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "domain", "name"}).
+			AddRow(websiteID.String(), "blog.example.com", "Blog"))
+	columns := []string{"pageviews", "visitors", "visits", "avg_engagement_time"}
+	mock.ExpectQuery("FROM website_event").
+		WillReturnRows(sqlmock.NewRows(columns).AddRow(int64(300), int64(120), int64(150), 42.0))
+	mock.ExpectQuery("FROM website_event").
+		WillReturnRows(sqlmock.NewRows(columns).AddRow(int64(200), int64(100), int64(0), nil))
+
+	tools := &statsTools{db: db, now: func() time.Time { return time.Date(2026, 5, 10, 0, 0, 0, 0, time.UTC) }}
+	value, err := tools.getStats(context.Background(), json.RawMessage(`{"website": "blog.example.com", "from": "2026-05-04", "to": "2026-05-10"}`))
+	require.NoError(t, err)
+
+	result := value.(statsResult)
+	assert.Equal(t, "Blog", result.Website.Name)
+	assert.Equal(t, int64(300), result.Current.Pageviews)
+	assert.Equal(t, 42.0, *result.Current.AvgEngagementTime)
+	assert.Nil(t, result.Previous.AvgEngagementTime)
+	assert.Equal(t, time.Date(2026, 4, 27, 0, 0, 0, 0, time.UTC), result.PreviousPeriod.From)
+	assert.Equal(t, map[string]float64{"pageviews": 50, "visitors": 20}, result.Change)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestResolveRequiresWebsite(t *testing.T) {
+	tools := &statsTools{now: time.Now}
+	_, err := tools.topPages(context.Background(), json.RawMessage(`{}`))
+	assert.ErrorContains(t, err, "website is required")
+
+	_, err = tools.breakdown(context.Background(), json.RawMessage(`{"website": "x", "dimension": "password"}`))
+	assert.ErrorContains(t, err, "dimension must be one of")
+}
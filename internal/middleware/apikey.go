@@ -7,7 +7,9 @@ import (
 	"strings"
 
 	"github.com/go-chi/render"
+	"github.com/google/uuid"
 
+	"github.com/seuros/kaunta/internal/database"
 	"github.com/seuros/kaunta/internal/models"
 )
 
@@ -84,6 +86,13 @@ func apiKeyAuthWithScope(next http.Handler, requiredScope string) http.Handler {
 		go models.UpdateAPIKeyLastUsed(apiKey.KeyID)
 
 		ctx := context.WithValue(r.Context(), apiKeyContextKey, apiKey)
+		if database.TenantIsolationEnabled() {
+			// A malformed website leaves the key without a scope, so its
+			// stats queries fail instead of reading every website
+			if websiteID, err := uuid.Parse(apiKey.WebsiteID); err == nil {
+				ctx = database.WithTenant(ctx, websiteID)
+			}
+		}
 		next.ServeHTTP(w, r.WithContext(ctx))
 	})
 }
@@ -7,11 +7,13 @@ import (
 	"encoding/hex"
 	"net/http"
 	"strings"
+	"sync/atomic"
 
 	"github.com/go-chi/render"
 	"github.com/google/uuid"
 
 	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
 )
 
 // UserContext holds the authenticated user information
@@ -19,10 +21,41 @@ type UserContext struct {
 	UserID    uuid.UUID
 	Username  string
 	SessionID uuid.UUID
+	// TwoFactorSetupRequired is set when the user must enroll in 2FA (by the
+	// per-user flag or the global policy) and hasn't yet. Such sessions can
+	// only reach the enrollment page and /api/auth/.
+	TwoFactorSetupRequired bool
+	// TokenScope is set when the request was authenticated by a personal
+	// access token rather than a session: "read" or "write"
+	TokenScope string
 }
 
+// ReadOnly reports whether the user came in with a read-only personal token
+func (u *UserContext) ReadOnly() bool {
+	return u.TokenScope == models.PersonalTokenRead
+}
+
+// TwoFactorSetupPath is the page users who still have to enroll in 2FA are
+// sent to
+const TwoFactorSetupPath = "/dashboard/security"
+
 var sessionValidator = validateSessionFromDB
 
+var personalTokenValidator = validatePersonalTokenFromDB
+
+// requireTwoFactor is the security.require_2fa policy
+var requireTwoFactor atomic.Bool
+
+// SetTwoFactorPolicy makes 2FA enrollment mandatory for every user
+func SetTwoFactorPolicy(required bool) {
+	requireTwoFactor.Store(required)
+}
+
+// TwoFactorPolicyRequired reports whether 2FA is mandatory for every user
+func TwoFactorPolicyRequired() bool {
+	return requireTwoFactor.Load()
+}
+
 type contextKey string
 
 const userContextKey contextKey = "user"
@@ -37,6 +70,11 @@ func Auth(next http.Handler) http.Handler {
 			return
 		}
 
+		if strings.HasPrefix(token, models.PersonalTokenPrefix) {
+			authPersonalToken(w, r, next, token)
+			return
+		}
+
 		userCtx, err := sessionValidator(HashToken(token))
 		if err == sql.ErrNoRows {
 			render.Status(r, http.StatusUnauthorized)
@@ -48,12 +86,57 @@ func Auth(next http.Handler) http.Handler {
 			render.JSON(w, r, map[string]any{"error": "Authentication error"})
 			return
 		}
+		if userCtx.TwoFactorSetupRequired && !allowedDuringTwoFactorSetup(r.URL.Path) {
+			render.Status(r, http.StatusForbidden)
+			render.JSON(w, r, map[string]any{
+				"error":     "Two-factor authentication setup required",
+				"setup_url": TwoFactorSetupPath,
+			})
+			return
+		}
 
-		ctx := context.WithValue(r.Context(), userContextKey, userCtx)
-		next.ServeHTTP(w, r.WithContext(ctx))
+		next.ServeHTTP(w, r.WithContext(ContextWithUser(r.Context(), userCtx)))
 	})
 }
 
+// authPersonalToken finishes Auth for a personal access token. Tokens can't
+// reach the account endpoints under /api/auth/ (so a leaked token can't
+// mint more tokens or touch sessions and 2FA), and read tokens are limited
+// to safe methods.
+func authPersonalToken(w http.ResponseWriter, r *http.Request, next http.Handler, token string) {
+	userCtx, err := personalTokenValidator(token)
+	if err == sql.ErrNoRows {
+		render.Status(r, http.StatusUnauthorized)
+		render.JSON(w, r, map[string]any{"error": "Unauthorized - invalid, revoked or expired token"})
+		return
+	}
+	if err != nil {
+		render.Status(r, http.StatusInternalServerError)
+		render.JSON(w, r, map[string]any{"error": "Authentication error"})
+		return
+	}
+	if userCtx.TwoFactorSetupRequired {
+		render.Status(r, http.StatusForbidden)
+		render.JSON(w, r, map[string]any{
+			"error":     "Two-factor authentication setup required",
+			"setup_url": TwoFactorSetupPath,
+		})
+		return
+	}
+	if strings.HasPrefix(r.URL.Path, "/api/auth/") {
+		render.Status(r, http.StatusForbidden)
+		render.JSON(w, r, map[string]any{"error": "Personal access tokens cannot manage the account"})
+		return
+	}
+	if userCtx.ReadOnly() && !readMethod(r.Method) {
+		render.Status(r, http.StatusForbidden)
+		render.JSON(w, r, map[string]any{"error": "This token is read-only"})
+		return
+	}
+
+	next.ServeHTTP(w, r.WithContext(ContextWithUser(r.Context(), userCtx)))
+}
+
 // AuthWithRedirect middleware validates session tokens and redirects to /login for dashboard routes.
 func AuthWithRedirect(next http.Handler) http.Handler {
 	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
@@ -68,9 +151,26 @@ func AuthWithRedirect(next http.Handler) http.Handler {
 			http.Redirect(w, r, "/login", http.StatusSeeOther)
 			return
 		}
+		if userCtx.TwoFactorSetupRequired && !allowedDuringTwoFactorSetup(r.URL.Path) {
+			http.Redirect(w, r, TwoFactorSetupPath, http.StatusSeeOther)
+			return
+		}
 
-		ctx := context.WithValue(r.Context(), userContextKey, userCtx)
-		next.ServeHTTP(w, r.WithContext(ctx))
+		next.ServeHTTP(w, r.WithContext(ContextWithUser(r.Context(), userCtx)))
+	})
+}
+
+// OptionalAuth loads the user context when the request carries a valid
+// session and otherwise lets it through anonymously, for public pages that
+// adapt to a signed-in visitor.
+func OptionalAuth(next http.Handler) http.Handler {
+	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if token := extractSessionToken(r); token != "" {
+			if userCtx, err := sessionValidator(HashToken(token)); err == nil && !userCtx.TwoFactorSetupRequired {
+				r = r.WithContext(ContextWithUser(r.Context(), userCtx))
+			}
+		}
+		next.ServeHTTP(w, r)
 	})
 }
 
@@ -82,9 +182,30 @@ func GetUser(r *http.Request) *UserContext {
 	return nil
 }
 
-// ContextWithUser attaches a user context to the provided context.
+// ContextWithUser attaches a user context to the provided context. In
+// tenant isolation mode it also confines stats queries to the websites the
+// user can access.
 func ContextWithUser(ctx context.Context, user *UserContext) context.Context {
-	return context.WithValue(ctx, userContextKey, user)
+	ctx = context.WithValue(ctx, userContextKey, user)
+	if database.TenantIsolationEnabled() {
+		userID := user.UserID
+		ctx = database.WithTenantResolver(ctx, func(ctx context.Context) ([]uuid.UUID, error) {
+			return userWebsitesLookup(ctx, userID)
+		})
+	}
+	return ctx
+}
+
+// allowedDuringTwoFactorSetup lists what a session that still has to enroll
+// in 2FA can reach: the enrollment page, and the auth API (which includes
+// the enrollment endpoints and logout)
+func allowedDuringTwoFactorSetup(path string) bool {
+	return path == TwoFactorSetupPath || strings.HasPrefix(path, "/api/auth/")
+}
+
+// readMethod reports whether a method only reads, for read-only tokens
+func readMethod(method string) bool {
+	return method == http.MethodGet || method == http.MethodHead || method == http.MethodOptions
 }
 
 func extractSessionToken(r *http.Request) string {
@@ -106,15 +227,35 @@ func HashToken(token string) string {
 
 func validateSessionFromDB(tokenHash string) (*UserContext, error) {
 	var userCtx UserContext
-	query := `SELECT user_id, username, session_id FROM validate_session($1)`
+	query := `
+		SELECT s.user_id, s.username, s.session_id,
+			u.totp_enabled_at IS NULL AND (u.require_2fa OR $2)
+		FROM validate_session($1) s
+		JOIN users u ON u.user_id = s.user_id
+		WHERE u.disabled_at IS NULL
+	`
 
-	err := database.DB.QueryRow(query, tokenHash).Scan(
+	err := database.DB.QueryRow(query, tokenHash, TwoFactorPolicyRequired()).Scan(
 		&userCtx.UserID,
 		&userCtx.Username,
 		&userCtx.SessionID,
+		&userCtx.TwoFactorSetupRequired,
 	)
 	if err != nil {
 		return nil, err
 	}
 	return &userCtx, nil
 }
+
+func validatePersonalTokenFromDB(token string) (*UserContext, error) {
+	owner, err := models.ValidatePersonalToken(context.Background(), database.DB, token, TwoFactorPolicyRequired())
+	if err != nil {
+		return nil, err
+	}
+	return &UserContext{
+		UserID:                 owner.UserID,
+		Username:               owner.Username,
+		TwoFactorSetupRequired: owner.TwoFactorSetupRequired,
+		TokenScope:             owner.Scope,
+	}, nil
+}
@@ -176,3 +176,109 @@ func TestAuthWithRedirectValidToken(t *testing.T) {
 
 	assert.Equal(t, http.StatusOK, resp.Code)
 }
+
+func TestAuthTwoFactorSetupRequired(t *testing.T) {
+	stubSessionValidator(t, func(tokenHash string) (*UserContext, error) {
+		return &UserContext{UserID: uuid.New(), Username: "test", TwoFactorSetupRequired: true}, nil
+	})
+
+	req := httptest.NewRequest(http.MethodGet, "/api/dashboard/stats", nil)
+	req.AddCookie(&http.Cookie{Name: "kaunta_session", Value: "token"})
+	resp := executeAuth(t, req, nil)
+	assert.Equal(t, http.StatusForbidden, resp.Code)
+	assert.Contains(t, resp.Body.String(), TwoFactorSetupPath)
+
+	// Enrollment and logout stay reachable
+	req = httptest.NewRequest(http.MethodPost, "/api/auth/2fa/setup", nil)
+	req.AddCookie(&http.Cookie{Name: "kaunta_session", Value: "token"})
+	assert.Equal(t, http.StatusOK, executeAuth(t, req, nil).Code)
+
+	req = httptest.NewRequest(http.MethodGet, "/dashboard/overview", nil)
+	req.AddCookie(&http.Cookie{Name: "kaunta_session", Value: "token"})
+	resp = executeAuthWithRedirect(t, req, nil)
+	assert.Equal(t, http.StatusSeeOther, resp.Code)
+	assert.Equal(t, TwoFactorSetupPath, resp.Header().Get("Location"))
+
+	req = httptest.NewRequest(http.MethodGet, TwoFactorSetupPath, nil)
+	req.AddCookie(&http.Cookie{Name: "kaunta_session", Value: "token"})
+	assert.Equal(t, http.StatusOK, executeAuthWithRedirect(t, req, nil).Code)
+}
+
+func TestTwoFactorPolicy(t *testing.T) {
+	t.Cleanup(func() { SetTwoFactorPolicy(false) })
+
+	assert.False(t, TwoFactorPolicyRequired())
+	SetTwoFactorPolicy(true)
+	assert.True(t, TwoFactorPolicyRequired())
+}
+
+func TestOptionalAuth(t *testing.T) {
+	userID := uuid.New()
+	stubSessionValidator(t, func(tokenHash string) (*UserContext, error) {
+		if tokenHash == HashToken("valid") {
+			return &UserContext{UserID: userID, Username: "demo"}, nil
+		}
+		return nil, sql.ErrNoRows
+	})
+
+	var seen *UserContext
+	handler := OptionalAuth(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		seen = GetUser(r)
+		w.WriteHeader(http.StatusOK)
+	}))
+
+	for token, wantUser := range map[string]bool{"": false, "stale": false, "valid": true} {
+		seen = nil
+		req := httptest.NewRequest(http.MethodGet, "/invite/abc", nil)
+		if token != "" {
+			req.AddCookie(&http.Cookie{Name: "kaunta_session", Value: token})
+		}
+		recorder := httptest.NewRecorder()
+		handler.ServeHTTP(recorder, req)
+
+		assert.Equal(t, http.StatusOK, recorder.Code, token)
+		if wantUser {
+			require.NotNil(t, seen)
+			assert.Equal(t, userID, seen.UserID)
+		} else {
+			assert.Nil(t, seen, token)
+		}
+	}
+}
+
+func TestAuthPersonalToken(t *testing.T) {
+	userID := uuid.New()
+	original := personalTokenValidator
+	t.Cleanup(func() { personalTokenValidator = original })
+	personalTokenValidator = func(token string) (*UserContext, error) {
+		switch token {
+		case "kaunta_pat_read":
+			return &UserContext{UserID: userID, Username: "ci", TokenScope: "read"}, nil
+		case "kaunta_pat_write":
+			return &UserContext{UserID: userID, Username: "ci", TokenScope: "write"}, nil
+		}
+		return nil, sql.ErrNoRows
+	}
+	stubSessionValidator(t, func(tokenHash string) (*UserContext, error) {
+		t.Fatal("personal tokens must not be looked up as sessions")
+		return nil, nil
+	})
+
+	request := func(method, path, token string) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(method, path, nil)
+		req.Header.Set("Authorization", "Bearer "+token)
+		return executeAuth(t, req, func(w http.ResponseWriter, r *http.Request) {
+			assert.Equal(t, userID, GetUser(r).UserID)
+			w.WriteHeader(http.StatusOK)
+		})
+	}
+
+	assert.Equal(t, http.StatusOK, request(http.MethodGet, "/api/websites", "kaunta_pat_read").Code)
+	assert.Equal(t, http.StatusForbidden, request(http.MethodPost, "/api/websites", "kaunta_pat_read").Code)
+	assert.Equal(t, http.StatusOK, request(http.MethodPost, "/api/websites", "kaunta_pat_write").Code)
+	assert.Equal(t, http.StatusUnauthorized, request(http.MethodGet, "/api/websites", "kaunta_pat_revoked").Code)
+
+	// No token can manage the account, including other tokens
+	assert.Equal(t, http.StatusForbidden, request(http.MethodPost, "/api/auth/tokens", "kaunta_pat_write").Code)
+	assert.Equal(t, http.StatusForbidden, request(http.MethodGet, "/api/auth/sessions", "kaunta_pat_write").Code)
+}
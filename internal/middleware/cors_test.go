@@ -0,0 +1,42 @@
+package middleware
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestCORS(t *testing.T) {
+	handler := CORS([]string{"https://app.example.com"})(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusOK)
+	}))
+
+	// Allowed origin gets credentialed CORS headers
+	req := httptest.NewRequest(http.MethodGet, "/api/websites/list", nil)
+	req.Header.Set("Origin", "https://app.example.com")
+	resp := httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Equal(t, "https://app.example.com", resp.Header().Get("Access-Control-Allow-Origin"))
+	assert.Equal(t, "true", resp.Header().Get("Access-Control-Allow-Credentials"))
+	assert.Equal(t, "X-CSRF-Token", resp.Header().Get("Access-Control-Expose-Headers"))
+
+	// Preflights are answered without reaching the route
+	req = httptest.NewRequest(http.MethodOptions, "/api/websites", nil)
+	req.Header.Set("Origin", "https://app.example.com")
+	req.Header.Set("Access-Control-Request-Method", "POST")
+	resp = httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+	assert.Equal(t, http.StatusNoContent, resp.Code)
+	assert.Contains(t, resp.Header().Get("Access-Control-Allow-Methods"), "POST")
+	assert.Contains(t, resp.Header().Get("Access-Control-Allow-Headers"), "X-Requested-With")
+
+	// Other origins get nothing
+	req = httptest.NewRequest(http.MethodGet, "/api/websites/list", nil)
+	req.Header.Set("Origin", "https://evil.example.com")
+	resp = httptest.NewRecorder()
+	handler.ServeHTTP(resp, req)
+	assert.Empty(t, resp.Header().Get("Access-Control-Allow-Origin"))
+}
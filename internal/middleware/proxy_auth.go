@@ -0,0 +1,131 @@
+package middleware
+
+import (
+	"log/slog"
+	"net"
+	"net/http"
+	"net/netip"
+	"strings"
+
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// ProxyIdentity is the user an authenticating reverse proxy vouches for
+type ProxyIdentity struct {
+	Username string
+	Email    string
+	Name     string
+	// Groups is nil when no groups header is configured, so group
+	// mappings are left alone, and empty when the user is in no group
+	Groups []string
+}
+
+// ProxySignIn starts a session for a proxy identity and returns its token.
+// It returns sql.ErrNoRows when the user doesn't exist and may not be
+// created.
+type ProxySignIn func(w http.ResponseWriter, r *http.Request, identity ProxyIdentity) (string, error)
+
+// ProxyAuthSettings configures ProxyAuth
+type ProxyAuthSettings struct {
+	Header       string
+	EmailHeader  string
+	NameHeader   string
+	GroupsHeader string
+	Trusted      []netip.Prefix
+}
+
+// ProxyAuth signs in the user named in the proxy's header. The header is
+// only believed when the connection comes straight from a trusted proxy,
+// since anyone else could set it. A matching session is reused; otherwise
+// signIn starts one, and the request continues with the new session cookie
+// so Auth sees it. Logging out of Kaunta only lasts until the next request;
+// sign out at the proxy instead.
+func ProxyAuth(settings ProxyAuthSettings, signIn ProxySignIn) func(http.Handler) http.Handler {
+	return func(next http.Handler) http.Handler {
+		return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+			username := strings.TrimSpace(r.Header.Get(settings.Header))
+			if username == "" || !trustedPeer(r, settings.Trusted) {
+				next.ServeHTTP(w, r)
+				return
+			}
+
+			if cookie, err := r.Cookie("kaunta_session"); err == nil && cookie.Value != "" {
+				if user, err := sessionValidator(HashToken(cookie.Value)); err == nil && user.Username == username {
+					next.ServeHTTP(w, r)
+					return
+				}
+			}
+
+			token, err := signIn(w, r, ProxyIdentity{
+				Username: username,
+				Email:    headerValue(r, settings.EmailHeader),
+				Name:     headerValue(r, settings.NameHeader),
+				Groups:   headerList(r, settings.GroupsHeader),
+			})
+			if err != nil {
+				// Unknown users without auto-provisioning fall through to
+				// the login page
+				logging.L().Warn("proxy header sign-in failed",
+					slog.String("username", username), slog.Any("error", err))
+				next.ServeHTTP(w, r)
+				return
+			}
+			replaceSessionCookie(r, token)
+			next.ServeHTTP(w, r)
+		})
+	}
+}
+
+// trustedPeer reports whether the direct peer (not a forwarded-for
+// address) is one of the trusted proxies
+func trustedPeer(r *http.Request, trusted []netip.Prefix) bool {
+	host, _, err := net.SplitHostPort(r.RemoteAddr)
+	if err != nil {
+		host = r.RemoteAddr
+	}
+	addr, err := netip.ParseAddr(host)
+	if err != nil {
+		return false
+	}
+	addr = addr.Unmap()
+	for _, prefix := range trusted {
+		if prefix.Contains(addr) {
+			return true
+		}
+	}
+	return false
+}
+
+func headerValue(r *http.Request, name string) string {
+	if name == "" {
+		return ""
+	}
+	return strings.TrimSpace(r.Header.Get(name))
+}
+
+// headerList splits a comma-separated header such as Remote-Groups. It
+// returns nil when name is empty.
+func headerList(r *http.Request, name string) []string {
+	if name == "" {
+		return nil
+	}
+	items := []string{}
+	for _, item := range strings.Split(r.Header.Get(name), ",") {
+		if item = strings.TrimSpace(item); item != "" {
+			items = append(items, item)
+		}
+	}
+	return items
+}
+
+// replaceSessionCookie makes the rest of the request see the new session
+func replaceSessionCookie(r *http.Request, token string) {
+	cookies := r.Cookies()
+	r.Header.Del("Cookie")
+	for _, c := range cookies {
+		if c.Name != "kaunta_session" {
+			r.AddCookie(c)
+		}
+	}
+	r.AddCookie(&http.Cookie{Name: "kaunta_session", Value: token})
+}
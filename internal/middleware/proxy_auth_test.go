@@ -0,0 +1,77 @@
+package middleware
+
+import (
+	"database/sql"
+	"net/http"
+	"net/http/httptest"
+	"net/netip"
+	"testing"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+)
+
+func TestProxyAuth(t *testing.T) {
+	stubSessionValidator(t, func(tokenHash string) (*UserContext, error) {
+		if tokenHash == HashToken("alice-session") {
+			return &UserContext{UserID: uuid.New(), Username: "alice"}, nil
+		}
+		return nil, sql.ErrNoRows
+	})
+
+	var signedIn []ProxyIdentity
+	signIn := func(w http.ResponseWriter, r *http.Request, identity ProxyIdentity) (string, error) {
+		if identity.Username == "mallory" {
+			return "", sql.ErrNoRows
+		}
+		signedIn = append(signedIn, identity)
+		return "new-session", nil
+	}
+	settings := ProxyAuthSettings{
+		Header:      "Remote-User",
+		EmailHeader: "Remote-Email",
+		Trusted:     []netip.Prefix{netip.MustParsePrefix("10.0.0.0/8")},
+	}
+
+	var session string
+	handler := ProxyAuth(settings, signIn)(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		session = ""
+		if cookie, err := r.Cookie("kaunta_session"); err == nil {
+			session = cookie.Value
+		}
+	}))
+	serve := func(peer, user, cookie string) {
+		req := httptest.NewRequest(http.MethodGet, "/dashboard", nil)
+		req.RemoteAddr = peer + ":40000"
+		if user != "" {
+			req.Header.Set("Remote-User", user)
+			req.Header.Set("Remote-Email", user+"@example.com")
+		}
+		if cookie != "" {
+			req.AddCookie(&http.Cookie{Name: "kaunta_session", Value: cookie})
+		}
+		handler.ServeHTTP(httptest.NewRecorder(), req)
+	}
+
+	// Headers from anyone but the proxy are ignored
+	serve("203.0.113.9", "alice", "")
+	assert.Empty(t, signedIn)
+	assert.Empty(t, session)
+
+	serve("10.1.2.3", "alice", "")
+	assert.Equal(t, []ProxyIdentity{{Username: "alice", Email: "alice@example.com"}}, signedIn)
+	assert.Equal(t, "new-session", session)
+
+	// A session for the same user is reused; another user's is replaced
+	serve("10.1.2.3", "alice", "alice-session")
+	assert.Len(t, signedIn, 1)
+	assert.Equal(t, "alice-session", session)
+
+	serve("10.1.2.3", "bob", "alice-session")
+	assert.Len(t, signedIn, 2)
+	assert.Equal(t, "new-session", session)
+
+	// Unknown users without auto-provisioning continue unauthenticated
+	serve("10.1.2.3", "mallory", "")
+	assert.Empty(t, session)
+}
@@ -0,0 +1,135 @@
+package middleware
+
+import (
+	"net/http"
+	"strconv"
+	"sync"
+	"time"
+
+	"github.com/go-chi/render"
+)
+
+// RateLimiter counts requests per key in fixed windows. Unlike the httprate
+// limiters on login and setup, the limit is chosen per request, so every API
+// key can carry its own.
+type RateLimiter struct {
+	window time.Duration
+	now    func() time.Time
+
+	mu        sync.Mutex
+	counters  map[string]*rateWindow
+	lastSweep time.Time
+}
+
+type rateWindow struct {
+	start time.Time
+	count int
+}
+
+// rateDecision is the outcome of counting one request against a limit
+type rateDecision struct {
+	allowed   bool
+	limit     int
+	remaining int
+	reset     time.Duration
+}
+
+// NewRateLimiter creates a limiter with the given window length
+func NewRateLimiter(window time.Duration) *RateLimiter {
+	return &RateLimiter{
+		window:   window,
+		now:      time.Now,
+		counters: make(map[string]*rateWindow),
+	}
+}
+
+// allow counts a request against key. A limit of zero or less is unlimited.
+func (l *RateLimiter) allow(key string, limit int) rateDecision {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	now := l.now()
+	if now.Sub(l.lastSweep) >= l.window {
+		// Drop counters of keys that went quiet so the map stays bounded
+		for k, w := range l.counters {
+			if now.Sub(w.start) >= l.window {
+				delete(l.counters, k)
+			}
+		}
+		l.lastSweep = now
+	}
+
+	w, ok := l.counters[key]
+	if !ok || now.Sub(w.start) >= l.window {
+		w = &rateWindow{start: now.Truncate(l.window)}
+		l.counters[key] = w
+	}
+	reset := w.start.Add(l.window).Sub(now)
+
+	if limit <= 0 {
+		return rateDecision{allowed: true, reset: reset}
+	}
+	if w.count >= limit {
+		return rateDecision{allowed: false, limit: limit, reset: reset}
+	}
+	w.count++
+	return rateDecision{allowed: true, limit: limit, remaining: limit - w.count, reset: reset}
+}
+
+// APIKeyRateLimit limits requests per API key and per website across all of
+// its keys, using the limits stored with the key. It must run after
+// APIKeyAuthAny.
+func (l *RateLimiter) APIKeyRateLimit(next http.Handler) http.Handler {
+	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		apiKey := GetAPIKey(r)
+		if apiKey == nil {
+			next.ServeHTTP(w, r)
+			return
+		}
+
+		decision := l.allow("key:"+apiKey.KeyID.String(), apiKey.RateLimitPerMinute)
+		if decision.allowed {
+			website := l.allow("website:"+apiKey.WebsiteID.String(), apiKey.WebsiteRateLimit)
+			if !website.allowed || (website.limit > 0 && (decision.limit == 0 || website.remaining < decision.remaining)) {
+				decision = website
+			}
+		}
+		l.respond(w, r, next, decision)
+	})
+}
+
+// UserRateLimit limits requests per signed-in user to limit per window. It
+// must run after Auth.
+func (l *RateLimiter) UserRateLimit(limit int) func(http.Handler) http.Handler {
+	return func(next http.Handler) http.Handler {
+		return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+			user := GetUser(r)
+			if user == nil || limit <= 0 {
+				next.ServeHTTP(w, r)
+				return
+			}
+			l.respond(w, r, next, l.allow("user:"+user.UserID.String(), limit))
+		})
+	}
+}
+
+// respond sets the RateLimit-* headers and serves the request or a 429
+func (l *RateLimiter) respond(w http.ResponseWriter, r *http.Request, next http.Handler, d rateDecision) {
+	if d.limit > 0 {
+		resetSeconds := strconv.Itoa(int((d.reset + time.Second - 1) / time.Second))
+		w.Header().Set("RateLimit-Limit", strconv.Itoa(d.limit))
+		w.Header().Set("RateLimit-Remaining", strconv.Itoa(d.remaining))
+		w.Header().Set("RateLimit-Reset", resetSeconds)
+		w.Header().Set("RateLimit-Policy", strconv.Itoa(d.limit)+";w="+strconv.Itoa(int(l.window/time.Second)))
+		if !d.allowed {
+			w.Header().Set("Retry-After", resetSeconds)
+		}
+	}
+
+	if !d.allowed {
+		render.Status(r, http.StatusTooManyRequests)
+		render.JSON(w, r, map[string]any{"error": "Rate limit exceeded"})
+		return
+	}
+	next.ServeHTTP(w, r)
+}
@@ -0,0 +1,102 @@
+package middleware
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func newTestRateLimiter(now *time.Time) *RateLimiter {
+	l := NewRateLimiter(time.Minute)
+	l.now = func() time.Time { return *now }
+	return l
+}
+
+func serveWithAPIKey(l *RateLimiter, apiKey *models.APIKey) *httptest.ResponseRecorder {
+	req := httptest.NewRequest(http.MethodGet, "/api/v1/stats/x", nil)
+	req = req.WithContext(context.WithValue(req.Context(), apiKeyContextKey, apiKey))
+	resp := httptest.NewRecorder()
+	l.APIKeyRateLimit(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusOK)
+	})).ServeHTTP(resp, req)
+	return resp
+}
+
+func TestAPIKeyRateLimit(t *testing.T) {
+	now := time.Date(2026, 5, 1, 12, 0, 15, 0, time.UTC)
+	l := newTestRateLimiter(&now)
+	apiKey := &models.APIKey{KeyID: uuid.New(), WebsiteID: uuid.New(), RateLimitPerMinute: 2, WebsiteRateLimit: 100}
+
+	resp := serveWithAPIKey(l, apiKey)
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Equal(t, "2", resp.Header().Get("RateLimit-Limit"))
+	assert.Equal(t, "1", resp.Header().Get("RateLimit-Remaining"))
+	assert.Equal(t, "45", resp.Header().Get("RateLimit-Reset"))
+	assert.Equal(t, "2;w=60", resp.Header().Get("RateLimit-Policy"))
+
+	assert.Equal(t, http.StatusOK, serveWithAPIKey(l, apiKey).Code)
+
+	resp = serveWithAPIKey(l, apiKey)
+	assert.Equal(t, http.StatusTooManyRequests, resp.Code)
+	assert.Equal(t, "0", resp.Header().Get("RateLimit-Remaining"))
+	assert.Equal(t, "45", resp.Header().Get("Retry-After"))
+	assert.Contains(t, resp.Body.String(), "Rate limit exceeded")
+
+	// Another key on the same website has its own budget
+	other := &models.APIKey{KeyID: uuid.New(), WebsiteID: apiKey.WebsiteID, RateLimitPerMinute: 2, WebsiteRateLimit: 100}
+	assert.Equal(t, http.StatusOK, serveWithAPIKey(l, other).Code)
+
+	// The next window starts fresh
+	now = now.Add(time.Minute)
+	assert.Equal(t, http.StatusOK, serveWithAPIKey(l, apiKey).Code)
+}
+
+func TestAPIKeyRateLimitWebsiteAggregate(t *testing.T) {
+	now := time.Date(2026, 5, 1, 12, 0, 0, 0, time.UTC)
+	l := newTestRateLimiter(&now)
+	websiteID := uuid.New()
+
+	first := &models.APIKey{KeyID: uuid.New(), WebsiteID: websiteID, RateLimitPerMinute: 10, WebsiteRateLimit: 2}
+	second := &models.APIKey{KeyID: uuid.New(), WebsiteID: websiteID, RateLimitPerMinute: 10, WebsiteRateLimit: 2}
+
+	resp := serveWithAPIKey(l, first)
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Equal(t, "2", resp.Header().Get("RateLimit-Limit")) // the tighter website limit is reported
+
+	assert.Equal(t, http.StatusOK, serveWithAPIKey(l, second).Code)
+	assert.Equal(t, http.StatusTooManyRequests, serveWithAPIKey(l, first).Code)
+}
+
+func TestUserRateLimit(t *testing.T) {
+	now := time.Date(2026, 5, 1, 12, 0, 0, 0, time.UTC)
+	l := newTestRateLimiter(&now)
+	handler := l.UserRateLimit(1)(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusOK)
+	}))
+
+	serve := func(user *UserContext) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodGet, "/api/websites", nil)
+		if user != nil {
+			req = req.WithContext(ContextWithUser(req.Context(), user))
+		}
+		resp := httptest.NewRecorder()
+		handler.ServeHTTP(resp, req)
+		return resp
+	}
+
+	user := &UserContext{UserID: uuid.New()}
+	assert.Equal(t, http.StatusOK, serve(user).Code)
+	assert.Equal(t, http.StatusTooManyRequests, serve(user).Code)
+	assert.Equal(t, http.StatusOK, serve(&UserContext{UserID: uuid.New()}).Code)
+
+	resp := serve(nil)
+	assert.Equal(t, http.StatusOK, resp.Code)
+	assert.Empty(t, resp.Header().Get("RateLimit-Limit"))
+}
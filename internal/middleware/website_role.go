@@ -0,0 +1,114 @@
+package middleware
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"log/slog"
+	"net/http"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/go-chi/render"
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+// websiteRoleLookup resolves a user's role on a website (can be mocked in tests)
+var websiteRoleLookup = func(ctx context.Context, websiteID, userID uuid.UUID) (models.WebsiteRole, error) {
+	return models.GetWebsiteRole(ctx, database.DB, websiteID, userID)
+}
+
+// userWebsitesLookup lists the websites a user can access (can be mocked in tests)
+var userWebsitesLookup = func(ctx context.Context, userID uuid.UUID) ([]uuid.UUID, error) {
+	roles, err := models.UserWebsiteRoles(ctx, database.DB, userID)
+	if err != nil {
+		return nil, err
+	}
+	websiteIDs := make([]uuid.UUID, 0, len(roles))
+	for websiteID := range roles {
+		websiteIDs = append(websiteIDs, websiteID)
+	}
+	return websiteIDs, nil
+}
+
+// RequireWebsiteRole rejects requests for a website the signed-in user
+// doesn't hold at least role on. It must run after Auth. Every website ID
+// the request carries is checked: the {website_id} route parameter, the
+// website_id, website and selectedWebsite query parameters, the Datastar
+// selectedWebsite signal, and a website_id form field. Requests without
+// one pass through, and malformed IDs are left to the handler to reject.
+// In tenant isolation mode the checked websites become the request's
+// tenant scope.
+func RequireWebsiteRole(role models.WebsiteRole) func(http.Handler) http.Handler {
+	return func(next http.Handler) http.Handler {
+		return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+			user := GetUser(r)
+			if user == nil {
+				render.Status(r, http.StatusUnauthorized)
+				render.JSON(w, r, map[string]any{"error": "Not authenticated"})
+				return
+			}
+
+			websiteIDs := requestWebsiteIDs(r)
+			for _, websiteID := range websiteIDs {
+				have, err := websiteRoleLookup(r.Context(), websiteID, user.UserID)
+				switch {
+				case errors.Is(err, models.ErrNoWebsiteAccess):
+					render.Status(r, http.StatusNotFound)
+					render.JSON(w, r, map[string]any{"error": "Website not found"})
+					return
+				case err != nil:
+					logging.L().Error("website role lookup failed", slog.Any("error", err))
+					render.Status(r, http.StatusInternalServerError)
+					render.JSON(w, r, map[string]any{"error": "Failed to check website access"})
+					return
+				case !have.Allows(role):
+					render.Status(r, http.StatusForbidden)
+					render.JSON(w, r, map[string]any{"error": "This requires the " + string(role) + " role on the website"})
+					return
+				}
+			}
+			if len(websiteIDs) > 0 && database.TenantIsolationEnabled() {
+				r = r.WithContext(database.WithTenant(r.Context(), websiteIDs...))
+			}
+			next.ServeHTTP(w, r)
+		})
+	}
+}
+
+// requestWebsiteIDs collects the distinct, well-formed website IDs in r
+func requestWebsiteIDs(r *http.Request) []uuid.UUID {
+	query := r.URL.Query()
+	candidates := []string{
+		chi.URLParam(r, "website_id"),
+		query.Get("website_id"),
+		query.Get("website"),
+		query.Get("selectedWebsite"),
+	}
+	if ds := query.Get("datastar"); ds != "" {
+		var signals map[string]any
+		if err := json.Unmarshal([]byte(ds), &signals); err == nil {
+			if selected, ok := signals["selectedWebsite"].(string); ok {
+				candidates = append(candidates, selected)
+			}
+		}
+	}
+	if r.Method != http.MethodGet {
+		candidates = append(candidates, r.PostFormValue("website_id"))
+	}
+
+	var ids []uuid.UUID
+	seen := map[uuid.UUID]bool{}
+	for _, candidate := range candidates {
+		id, err := uuid.Parse(candidate)
+		if err != nil || seen[id] {
+			continue
+		}
+		seen[id] = true
+		ids = append(ids, id)
+	}
+	return ids
+}
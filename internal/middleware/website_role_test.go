@@ -0,0 +1,159 @@
+package middleware
+
+import (
+	"context"
+	"errors"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func stubWebsiteRoles(t *testing.T, roles map[uuid.UUID]models.WebsiteRole) {
+	t.Helper()
+	original := websiteRoleLookup
+	websiteRoleLookup = func(ctx context.Context, websiteID, userID uuid.UUID) (models.WebsiteRole, error) {
+		role, ok := roles[websiteID]
+		if !ok {
+			return "", models.ErrNoWebsiteAccess
+		}
+		return role, nil
+	}
+	t.Cleanup(func() {
+		websiteRoleLookup = original
+	})
+}
+
+func executeWebsiteRole(t *testing.T, role models.WebsiteRole, req *http.Request) *httptest.ResponseRecorder {
+	t.Helper()
+	router := chi.NewRouter()
+	router.Use(func(next http.Handler) http.Handler {
+		return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+			next.ServeHTTP(w, r.WithContext(ContextWithUser(r.Context(), &UserContext{UserID: uuid.New(), Username: "demo"})))
+		})
+	})
+	router.With(RequireWebsiteRole(role)).HandleFunc("/*", func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusOK)
+	})
+	router.With(RequireWebsiteRole(role)).HandleFunc("/api/websites/{website_id}", func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusOK)
+	})
+
+	recorder := httptest.NewRecorder()
+	router.ServeHTTP(recorder, req)
+	return recorder
+}
+
+func TestRequireWebsiteRoleWithoutUser(t *testing.T) {
+	recorder := httptest.NewRecorder()
+	handler := RequireWebsiteRole(models.RoleViewer)(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		t.Fatal("handler must not run")
+	}))
+	handler.ServeHTTP(recorder, httptest.NewRequest(http.MethodGet, "/api/dashboard/stats/"+uuid.NewString(), nil))
+	assert.Equal(t, http.StatusUnauthorized, recorder.Code)
+}
+
+func TestRequireWebsiteRoleRouteParam(t *testing.T) {
+	viewed, edited := uuid.New(), uuid.New()
+	stubWebsiteRoles(t, map[uuid.UUID]models.WebsiteRole{viewed: models.RoleViewer, edited: models.RoleEditor})
+
+	resp := executeWebsiteRole(t, models.RoleViewer, httptest.NewRequest(http.MethodGet, "/api/websites/"+viewed.String(), nil))
+	assert.Equal(t, http.StatusOK, resp.Code)
+
+	resp = executeWebsiteRole(t, models.RoleEditor, httptest.NewRequest(http.MethodGet, "/api/websites/"+viewed.String(), nil))
+	assert.Equal(t, http.StatusForbidden, resp.Code)
+	assert.Contains(t, resp.Body.String(), "editor role")
+
+	resp = executeWebsiteRole(t, models.RoleEditor, httptest.NewRequest(http.MethodGet, "/api/websites/"+edited.String(), nil))
+	assert.Equal(t, http.StatusOK, resp.Code)
+
+	resp = executeWebsiteRole(t, models.RoleViewer, httptest.NewRequest(http.MethodGet, "/api/websites/"+uuid.NewString(), nil))
+	assert.Equal(t, http.StatusNotFound, resp.Code)
+}
+
+func TestRequireWebsiteRoleChecksEveryWebsite(t *testing.T) {
+	mine, other := uuid.New(), uuid.New()
+	stubWebsiteRoles(t, map[uuid.UUID]models.WebsiteRole{mine: models.RoleOwner})
+
+	// compare-style requests name several websites; all must be accessible
+	target := "/api/dashboard/stats?website_id=" + mine.String() + "&website=" + other.String()
+	resp := executeWebsiteRole(t, models.RoleViewer, httptest.NewRequest(http.MethodGet, target, nil))
+	assert.Equal(t, http.StatusNotFound, resp.Code)
+
+	signals := url.QueryEscape(`{"selectedWebsite":"` + other.String() + `"}`)
+	resp = executeWebsiteRole(t, models.RoleViewer, httptest.NewRequest(http.MethodGet, "/api/dashboard/map?datastar="+signals, nil))
+	assert.Equal(t, http.StatusNotFound, resp.Code)
+
+	signals = url.QueryEscape(`{"selectedWebsite":"` + mine.String() + `"}`)
+	resp = executeWebsiteRole(t, models.RoleViewer, httptest.NewRequest(http.MethodGet, "/api/dashboard/map?datastar="+signals, nil))
+	assert.Equal(t, http.StatusOK, resp.Code)
+}
+
+func TestRequireWebsiteRoleFormField(t *testing.T) {
+	viewed := uuid.New()
+	stubWebsiteRoles(t, map[uuid.UUID]models.WebsiteRole{viewed: models.RoleViewer})
+
+	req := httptest.NewRequest(http.MethodPost, "/api/dashboard/goals", strings.NewReader("website_id="+viewed.String()+"&name=Signup"))
+	req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+	resp := executeWebsiteRole(t, models.RoleEditor, req)
+	assert.Equal(t, http.StatusForbidden, resp.Code)
+}
+
+func TestRequireWebsiteRolePassesThroughWithoutWebsite(t *testing.T) {
+	stubWebsiteRoles(t, nil)
+
+	resp := executeWebsiteRole(t, models.RoleOwner, httptest.NewRequest(http.MethodGet, "/api/dashboard/goals?website_id=not-a-uuid", nil))
+	assert.Equal(t, http.StatusOK, resp.Code)
+}
+
+func TestRequireWebsiteRoleLookupError(t *testing.T) {
+	original := websiteRoleLookup
+	websiteRoleLookup = func(ctx context.Context, websiteID, userID uuid.UUID) (models.WebsiteRole, error) {
+		return "", errors.New("connection refused")
+	}
+	t.Cleanup(func() { websiteRoleLookup = original })
+
+	resp := executeWebsiteRole(t, models.RoleViewer, httptest.NewRequest(http.MethodGet, "/api/websites/"+uuid.NewString(), nil))
+	assert.Equal(t, http.StatusInternalServerError, resp.Code)
+}
+
+func TestRequireWebsiteRoleScopesTenant(t *testing.T) {
+	viewed, other := uuid.New(), uuid.New()
+	stubWebsiteRoles(t, map[uuid.UUID]models.WebsiteRole{viewed: models.RoleViewer, other: models.RoleViewer})
+	originalWebsites := userWebsitesLookup
+	userWebsitesLookup = func(ctx context.Context, userID uuid.UUID) ([]uuid.UUID, error) {
+		return []uuid.UUID{viewed, other}, nil
+	}
+	database.SetTenantIsolation(true)
+	t.Cleanup(func() {
+		userWebsitesLookup = originalWebsites
+		database.SetTenantIsolation(false)
+	})
+
+	var scope []uuid.UUID
+	handler := RequireWebsiteRole(models.RoleViewer)(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		var err error
+		scope, err = database.TenantWebsites(r.Context())
+		require.NoError(t, err)
+	}))
+	serve := func(target string) {
+		req := httptest.NewRequest(http.MethodGet, target, nil)
+		req = req.WithContext(ContextWithUser(req.Context(), &UserContext{UserID: uuid.New(), Username: "demo"}))
+		handler.ServeHTTP(httptest.NewRecorder(), req)
+	}
+
+	serve("/api/dashboard/stats?website_id=" + viewed.String())
+	assert.Equal(t, []uuid.UUID{viewed}, scope, "a request naming a website only reads that website")
+
+	serve("/api/dashboard/overview")
+	assert.ElementsMatch(t, []uuid.UUID{viewed, other}, scope, "otherwise every website the user can access")
+}
@@ -0,0 +1,263 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// EmailChangeTTL is how long an email confirmation link stays valid
+const EmailChangeTTL = 24 * time.Hour
+
+var (
+	// ErrPasswordIncorrect is returned when the current password doesn't
+	// match
+	ErrPasswordIncorrect = errors.New("current password is incorrect")
+	// ErrEmailChangeNotFound is returned for unknown, expired or used
+	// confirmation links
+	ErrEmailChangeNotFound = errors.New("email confirmation link expired or not found")
+)
+
+// AccountOwnershipError lists what a user still owns alone. Their account
+// can't be deleted until someone else owns these, or they are deleted.
+type AccountOwnershipError struct {
+	Teams    []string
+	Websites []string
+}
+
+func (e *AccountOwnershipError) Error() string {
+	var parts []string
+	if len(e.Teams) > 0 {
+		parts = append(parts, "teams "+strings.Join(e.Teams, ", "))
+	}
+	if len(e.Websites) > 0 {
+		parts = append(parts, "websites "+strings.Join(e.Websites, ", "))
+	}
+	return fmt.Sprintf("transfer or delete what you own first: %s", strings.Join(parts, "; "))
+}
+
+// ChangePassword sets a new password after checking the current one. Every
+// other session of the user is signed out; keep stays signed in.
+func ChangePassword(ctx context.Context, db *sql.DB, userID, keep uuid.UUID, current, password string) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if err := checkPassword(ctx, tx, userID, current); err != nil {
+		return err
+	}
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET password_hash = hash_password($2), updated_at = NOW() WHERE user_id = $1
+	`, userID, password); err != nil {
+		return err
+	}
+	if _, err := tx.ExecContext(ctx, `DELETE FROM password_reset WHERE user_id = $1`, userID); err != nil {
+		return err
+	}
+	if _, err := tx.ExecContext(ctx, `
+		DELETE FROM user_sessions WHERE user_id = $1 AND session_id <> $2
+	`, userID, keep); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+// RequestEmailChange checks the password and issues a confirmation token
+// for a new address, replacing any pending change. It returns the token
+// with its expiry.
+func RequestEmailChange(ctx context.Context, db *sql.DB, userID uuid.UUID, password, email string) (string, time.Time, error) {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return "", time.Time{}, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if err := checkPassword(ctx, tx, userID, password); err != nil {
+		return "", time.Time{}, err
+	}
+	if err := checkEmailFree(ctx, tx, userID, email); err != nil {
+		return "", time.Time{}, err
+	}
+
+	raw := make([]byte, 32)
+	if _, err := rand.Read(raw); err != nil {
+		return "", time.Time{}, err
+	}
+	token := hex.EncodeToString(raw)
+	expiresAt := time.Now().Add(EmailChangeTTL)
+
+	if _, err := tx.ExecContext(ctx, `
+		INSERT INTO email_change (token_hash, user_id, new_email, expires_at) VALUES ($1, $2, $3, $4)
+		ON CONFLICT (user_id) DO UPDATE
+		SET token_hash = EXCLUDED.token_hash, new_email = EXCLUDED.new_email,
+		    expires_at = EXCLUDED.expires_at, created_at = NOW()
+	`, sha256Hex(token), userID, email, expiresAt); err != nil {
+		return "", time.Time{}, err
+	}
+	return token, expiresAt, tx.Commit()
+}
+
+// GetPendingEmailChange returns the address the user is confirming, or ""
+func GetPendingEmailChange(ctx context.Context, db *sql.DB, userID uuid.UUID) (string, error) {
+	var email string
+	err := db.QueryRowContext(ctx, `
+		SELECT new_email FROM email_change WHERE user_id = $1 AND expires_at > NOW()
+	`, userID).Scan(&email)
+	if err == sql.ErrNoRows {
+		return "", nil
+	}
+	return email, err
+}
+
+// ConfirmEmailChange switches the user to the address a confirmation link
+// was sent to and returns it. The link works once.
+func ConfirmEmailChange(ctx context.Context, db *sql.DB, token string) (string, error) {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return "", err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var userID uuid.UUID
+	var email string
+	err = tx.QueryRowContext(ctx, `
+		DELETE FROM email_change WHERE token_hash = $1 AND expires_at > NOW()
+		RETURNING user_id, new_email
+	`, sha256Hex(token)).Scan(&userID, &email)
+	if err == sql.ErrNoRows {
+		return "", ErrEmailChangeNotFound
+	}
+	if err != nil {
+		return "", err
+	}
+
+	// Someone may have taken the address since the link was sent
+	if err := checkEmailFree(ctx, tx, userID, email); err != nil {
+		return "", err
+	}
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET email = $2, updated_at = NOW() WHERE user_id = $1
+	`, userID, email); err != nil {
+		return "", err
+	}
+	return email, tx.Commit()
+}
+
+// AccountOwnership returns what the user owns alone: teams without another
+// owner and websites they are the primary owner of. It returns nil when
+// the account can be deleted.
+func AccountOwnership(ctx context.Context, db *sql.DB, userID uuid.UUID) (*AccountOwnershipError, error) {
+	return accountOwnership(ctx, db, userID)
+}
+
+// DeleteAccount deletes the user after checking their password. It returns
+// an *AccountOwnershipError while they still own teams or websites alone.
+func DeleteAccount(ctx context.Context, db *sql.DB, userID uuid.UUID, password string) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if err := checkPassword(ctx, tx, userID, password); err != nil {
+		return err
+	}
+	owned, err := accountOwnership(ctx, tx, userID)
+	if err != nil {
+		return err
+	}
+	if owned != nil {
+		return owned
+	}
+	// Sessions, tokens, memberships and the rest cascade
+	if _, err := tx.ExecContext(ctx, `DELETE FROM users WHERE user_id = $1`, userID); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+// queryer is what accountOwnership needs from a *sql.DB or *sql.Tx
+type queryer interface {
+	QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error)
+}
+
+func accountOwnership(ctx context.Context, q queryer, userID uuid.UUID) (*AccountOwnershipError, error) {
+	teams, err := queryNames(ctx, q, `
+		SELECT t.name FROM team t
+		JOIN team_member m ON m.team_id = t.team_id AND m.user_id = $1 AND m.role = 'owner'
+		WHERE NOT EXISTS (
+			SELECT 1 FROM team_member o
+			WHERE o.team_id = t.team_id AND o.role = 'owner' AND o.user_id <> $1
+		)
+		ORDER BY t.name
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	websites, err := queryNames(ctx, q, `
+		SELECT domain FROM website
+		WHERE user_id = $1 AND deleted_at IS NULL
+		ORDER BY domain
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	if len(teams) == 0 && len(websites) == 0 {
+		return nil, nil
+	}
+	return &AccountOwnershipError{Teams: teams, Websites: websites}, nil
+}
+
+func queryNames(ctx context.Context, q queryer, query string, args ...any) ([]string, error) {
+	rows, err := q.QueryContext(ctx, query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var names []string
+	for rows.Next() {
+		var name string
+		if err := rows.Scan(&name); err != nil {
+			return nil, err
+		}
+		names = append(names, name)
+	}
+	return names, rows.Err()
+}
+
+func checkPassword(ctx context.Context, tx *sql.Tx, userID uuid.UUID, password string) error {
+	var ok bool
+	err := tx.QueryRowContext(ctx, `
+		SELECT verify_password($2, password_hash) FROM users WHERE user_id = $1
+	`, userID, password).Scan(&ok)
+	if err != nil {
+		return err
+	}
+	if !ok {
+		return ErrPasswordIncorrect
+	}
+	return nil
+}
+
+func checkEmailFree(ctx context.Context, tx *sql.Tx, userID uuid.UUID, email string) error {
+	var taken bool
+	if err := tx.QueryRowContext(ctx, `
+		SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($2) AND user_id <> $1)
+	`, userID, email).Scan(&taken); err != nil {
+		return err
+	}
+	if taken {
+		return ErrEmailTaken
+	}
+	return nil
+}
@@ -0,0 +1,146 @@
+package models
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestChangePassword(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID, sessionID := uuid.New(), uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT verify_password").WithArgs(userID, "oldpassword").
+		WillReturnRows(sqlmock.NewRows([]string{"ok"}).AddRow(true))
+	mock.ExpectExec("UPDATE users SET password_hash").WithArgs(userID, "newpassword").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("DELETE FROM password_reset").WithArgs(userID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectExec("DELETE FROM user_sessions").WithArgs(userID, sessionID).
+		WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectCommit()
+
+	require.NoError(t, ChangePassword(context.Background(), db, userID, sessionID, "oldpassword", "newpassword"))
+
+	// A wrong current password changes nothing
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT verify_password").WithArgs(userID, "guess").
+		WillReturnRows(sqlmock.NewRows([]string{"ok"}).AddRow(false))
+	mock.ExpectRollback()
+
+	err = ChangePassword(context.Background(), db, userID, sessionID, "guess", "newpassword")
+	assert.ErrorIs(t, err, ErrPasswordIncorrect)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRequestEmailChange(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT verify_password").WithArgs(userID, "password").
+		WillReturnRows(sqlmock.NewRows([]string{"ok"}).AddRow(true))
+	mock.ExpectQuery("SELECT EXISTS").WithArgs(userID, "new@example.com").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(false))
+	mock.ExpectExec("INSERT INTO email_change").
+		WithArgs(sqlmock.AnyArg(), userID, "new@example.com", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+
+	token, _, err := RequestEmailChange(context.Background(), db, userID, "password", "new@example.com")
+	require.NoError(t, err)
+	assert.Len(t, token, 64)
+
+	// Addresses of other accounts are refused before a link is sent
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT verify_password").WithArgs(userID, "password").
+		WillReturnRows(sqlmock.NewRows([]string{"ok"}).AddRow(true))
+	mock.ExpectQuery("SELECT EXISTS").WithArgs(userID, "bob@example.com").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(true))
+	mock.ExpectRollback()
+
+	_, _, err = RequestEmailChange(context.Background(), db, userID, "password", "bob@example.com")
+	assert.ErrorIs(t, err, ErrEmailTaken)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestConfirmEmailChange(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("DELETE FROM email_change").WithArgs(sha256Hex("token")).
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "new_email"}).AddRow(userID.String(), "new@example.com"))
+	mock.ExpectQuery("SELECT EXISTS").WithArgs(userID, "new@example.com").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(false))
+	mock.ExpectExec("UPDATE users SET email").WithArgs(userID, "new@example.com").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+
+	email, err := ConfirmEmailChange(context.Background(), db, "token")
+	require.NoError(t, err)
+	assert.Equal(t, "new@example.com", email)
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("DELETE FROM email_change").WithArgs(sha256Hex("token")).
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "new_email"}))
+	mock.ExpectRollback()
+
+	_, err = ConfirmEmailChange(context.Background(), db, "token")
+	assert.ErrorIs(t, err, ErrEmailChangeNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestDeleteAccount(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+
+	// Sole owner of a team and a website: refused
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT verify_password").WithArgs(userID, "password").
+		WillReturnRows(sqlmock.NewRows([]string{"ok"}).AddRow(true))
+	mock.ExpectQuery("SELECT t.name FROM team").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"name"}).AddRow("Marketing"))
+	mock.ExpectQuery("SELECT domain FROM website").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"domain"}).AddRow("example.com"))
+	mock.ExpectRollback()
+
+	err = DeleteAccount(context.Background(), db, userID, "password")
+	var owned *AccountOwnershipError
+	require.ErrorAs(t, err, &owned)
+	assert.Equal(t, []string{"Marketing"}, owned.Teams)
+	assert.Equal(t, []string{"example.com"}, owned.Websites)
+
+	// Nothing owned alone: deleted
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT verify_password").WithArgs(userID, "password").
+		WillReturnRows(sqlmock.NewRows([]string{"ok"}).AddRow(true))
+	mock.ExpectQuery("SELECT t.name FROM team").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"name"}))
+	mock.ExpectQuery("SELECT domain FROM website").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"domain"}))
+	mock.ExpectExec("DELETE FROM users").WithArgs(userID).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+
+	require.NoError(t, DeleteAccount(context.Background(), db, userID, "password"))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,184 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"net/mail"
+	"slices"
+	"time"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/safehttp"
+)
+
+// Alert rule kinds
+const (
+	AlertSpike   = "spike"
+	AlertDrop    = "drop"
+	AlertSilence = "silence"
+)
+
+// Alert delivery channels
+const (
+	AlertChannelWebhook = "webhook"
+	AlertChannelEmail   = "email"
+)
+
+// AlertKinds lists the supported rule kinds in display order
+var AlertKinds = []string{AlertSpike, AlertDrop, AlertSilence}
+
+// ErrAlertRuleNotFound is returned when a rule does not exist or belongs to
+// another user
+var ErrAlertRuleNotFound = errors.New("alert rule not found")
+
+// AlertRule watches one website's traffic and notifies a channel when it trips
+type AlertRule struct {
+	ID            uuid.UUID  `json:"id"`
+	UserID        uuid.UUID  `json:"user_id"`
+	WebsiteID     uuid.UUID  `json:"website_id"`
+	Name          string     `json:"name"`
+	Kind          string     `json:"kind"`
+	Threshold     float64    `json:"threshold"`
+	WindowMinutes int        `json:"window_minutes"`
+	Channel       string     `json:"channel"`
+	Target        string     `json:"target"`
+	Enabled       bool       `json:"enabled"`
+	LastFiredAt   *time.Time `json:"last_fired_at,omitempty"`
+	CreatedAt     time.Time  `json:"created_at"`
+}
+
+// Validate checks the rule's kind, window, threshold and delivery target
+func (a *AlertRule) Validate() error {
+	if a.Name == "" || len(a.Name) > 100 {
+		return fmt.Errorf("name is required (up to 100 characters)")
+	}
+	if !slices.Contains(AlertKinds, a.Kind) {
+		return fmt.Errorf("invalid alert kind: %s", a.Kind)
+	}
+	if a.WindowMinutes < 5 || a.WindowMinutes > 1440 {
+		return fmt.Errorf("window must be between 5 and 1440 minutes")
+	}
+	if a.Kind != AlertSilence && (a.Threshold < 1 || a.Threshold > 1000) {
+		return fmt.Errorf("threshold must be between 1 and 1000")
+	}
+	if len(a.Target) > 500 {
+		return fmt.Errorf("target is too long")
+	}
+	switch a.Channel {
+	case AlertChannelWebhook:
+		if err := safehttp.CheckURL(a.Target); err != nil {
+			if errors.Is(err, safehttp.ErrBlockedAddress) {
+				return fmt.Errorf("webhook target must point to a public address")
+			}
+			return fmt.Errorf("webhook target must be an http(s) URL")
+		}
+	case AlertChannelEmail:
+		if _, err := mail.ParseAddress(a.Target); err != nil {
+			return fmt.Errorf("email target must be a valid address")
+		}
+	default:
+		return fmt.Errorf("invalid channel: %s", a.Channel)
+	}
+	return nil
+}
+
+const alertRuleColumns = `rule_id, user_id, website_id, name, kind, threshold, window_minutes, channel, target, enabled, last_fired_at, created_at`
+
+func scanAlertRules(rows *sql.Rows) ([]AlertRule, error) {
+	defer func() { _ = rows.Close() }()
+
+	var rules []AlertRule
+	for rows.Next() {
+		var a AlertRule
+		var lastFired sql.NullTime
+		if err := rows.Scan(&a.ID, &a.UserID, &a.WebsiteID, &a.Name, &a.Kind, &a.Threshold, &a.WindowMinutes,
+			&a.Channel, &a.Target, &a.Enabled, &lastFired, &a.CreatedAt); err != nil {
+			return nil, err
+		}
+		if lastFired.Valid {
+			a.LastFiredAt = &lastFired.Time
+		}
+		rules = append(rules, a)
+	}
+	return rules, rows.Err()
+}
+
+// ListAlertRules returns the rules owned by a user, newest first
+func ListAlertRules(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]AlertRule, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT `+alertRuleColumns+`
+		FROM alert_rule
+		WHERE user_id = $1
+		ORDER BY created_at DESC
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	return scanAlertRules(rows)
+}
+
+// ListEnabledAlertRules returns every enabled rule on a live website its
+// owner can still access, so removing someone from a website or team stops
+// their rules on it. The dashboard's team switcher doesn't pause them.
+func ListEnabledAlertRules(ctx context.Context, db *sql.DB) ([]AlertRule, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT `+alertRuleColumns+`
+		FROM alert_rule r
+		WHERE r.enabled
+		  AND r.website_id IN (SELECT a.website_id FROM user_websites_all_teams(r.user_id) a)
+	`)
+	if err != nil {
+		return nil, err
+	}
+	return scanAlertRules(rows)
+}
+
+// CreateAlertRule stores a rule for a website the user can view; the
+// caller validates the rule first
+func CreateAlertRule(ctx context.Context, db *sql.DB, rule *AlertRule) error {
+	err := db.QueryRowContext(ctx, `
+		INSERT INTO alert_rule (user_id, website_id, name, kind, threshold, window_minutes, channel, target)
+		SELECT $1, website_id, $3, $4, $5, $6, $7, $8
+		FROM user_websites($1)
+		WHERE website_id = $2
+		RETURNING rule_id, enabled, created_at
+	`, rule.UserID, rule.WebsiteID, rule.Name, rule.Kind, max(rule.Threshold, 1), rule.WindowMinutes,
+		rule.Channel, rule.Target).Scan(&rule.ID, &rule.Enabled, &rule.CreatedAt)
+	if err == sql.ErrNoRows {
+		return fmt.Errorf("website not found")
+	}
+	return err
+}
+
+// SetAlertRuleEnabled pauses or resumes a rule
+func SetAlertRuleEnabled(ctx context.Context, db *sql.DB, id, userID uuid.UUID, enabled bool) error {
+	res, err := db.ExecContext(ctx, `UPDATE alert_rule SET enabled = $3 WHERE rule_id = $1 AND user_id = $2`, id, userID, enabled)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrAlertRuleNotFound
+	}
+	return nil
+}
+
+// DeleteAlertRule removes a rule
+func DeleteAlertRule(ctx context.Context, db *sql.DB, id, userID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `DELETE FROM alert_rule WHERE rule_id = $1 AND user_id = $2`, id, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrAlertRuleNotFound
+	}
+	return nil
+}
+
+// MarkAlertRuleFired records when a rule last notified, starting its cooldown
+func MarkAlertRuleFired(ctx context.Context, db *sql.DB, id uuid.UUID, at time.Time) error {
+	_, err := db.ExecContext(ctx, `UPDATE alert_rule SET last_fired_at = $2 WHERE rule_id = $1`, id, at)
+	return err
+}
@@ -0,0 +1,77 @@
+package models
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestAlertRuleValidate(t *testing.T) {
+	valid := func() AlertRule {
+		return AlertRule{
+			Name:          "Traffic spike",
+			Kind:          AlertSpike,
+			Threshold:     3,
+			WindowMinutes: 60,
+			Channel:       AlertChannelWebhook,
+			Target:        "https://hooks.example.com/kaunta",
+		}
+	}
+
+	tests := []struct {
+		name   string
+		modify func(*AlertRule)
+		ok     bool
+	}{
+		{"webhook spike", func(*AlertRule) {}, true},
+		{"email silence ignores threshold", func(a *AlertRule) {
+			a.Kind, a.Threshold, a.Channel, a.Target = AlertSilence, 0, AlertChannelEmail, "owner@example.com"
+		}, true},
+		{"missing name", func(a *AlertRule) { a.Name = "" }, false},
+		{"unknown kind", func(a *AlertRule) { a.Kind = "anomaly" }, false},
+		{"window too short", func(a *AlertRule) { a.WindowMinutes = 1 }, false},
+		{"threshold below one", func(a *AlertRule) { a.Threshold = 0.5 }, false},
+		{"webhook not http", func(a *AlertRule) { a.Target = "ftp://example.com" }, false},
+		{"webhook on loopback", func(a *AlertRule) { a.Target = "http://127.0.0.1:9000/alert" }, false},
+		{"webhook on metadata endpoint", func(a *AlertRule) { a.Target = "http://169.254.169.254/latest/meta-data/" }, false},
+		{"bad email", func(a *AlertRule) { a.Channel, a.Target = AlertChannelEmail, "not-an-address" }, false},
+		{"unknown channel", func(a *AlertRule) { a.Channel = "sms" }, false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			rule := valid()
+			tt.modify(&rule)
+			err := rule.Validate()
+			if tt.ok {
+				assert.NoError(t, err)
+			} else {
+				assert.Error(t, err)
+			}
+		})
+	}
+}
+
+func TestListEnabledAlertRulesRechecksAccess(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	// Rules only run while their owner can still access the website
+	columns := []string{"rule_id", "user_id", "website_id", "name", "kind", "threshold", "window_minutes",
+		"channel", "target", "enabled", "last_fired_at", "created_at"}
+	mock.ExpectQuery(`website_id IN \(SELECT a.website_id FROM user_websites_all_teams\(r.user_id\) a\)`).
+		WillReturnRows(sqlmock.NewRows(columns).AddRow(uuid.New().String(), uuid.New().String(), uuid.New().String(),
+			"Traffic spike", AlertSpike, 3.0, 60, AlertChannelEmail, "ops@example.com", true, nil, time.Now()))
+
+	rules, err := ListEnabledAlertRules(context.Background(), db)
+	require.NoError(t, err)
+	assert.Len(t, rules, 1)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -5,8 +5,11 @@ import (
 	"crypto/sha256"
 	"database/sql"
 	"encoding/hex"
+	"errors"
 	"fmt"
 	"slices"
+	"strconv"
+	"strings"
 	"time"
 
 	"github.com/google/uuid"
@@ -38,6 +41,9 @@ type APIKeyCreateResult struct {
 	APIKey  *APIKey `json:"key"`
 }
 
+// ErrAPIKeyInactive is returned when rotating a revoked or expired key
+var ErrAPIKeyInactive = errors.New("api key is revoked or expired")
+
 const (
 	apiKeyPrefix = "kaunta_live_"
 	keyByteLen   = 32 // 256-bit entropy
@@ -50,12 +56,8 @@ func GenerateAPIKey(websiteID uuid.UUID, createdBy *uuid.UUID, name *string) (*A
 
 // GenerateAPIKeyWithScopes creates a new API key for a website with custom scopes
 func GenerateAPIKeyWithScopes(websiteID uuid.UUID, createdBy *uuid.UUID, name *string, scopes []string) (*APIKeyCreateResult, error) {
-	// Validate scopes
-	validScopes := map[string]bool{"ingest": true, "stats": true}
-	for _, scope := range scopes {
-		if !validScopes[scope] {
-			return nil, fmt.Errorf("invalid scope: %s (valid: ingest, stats)", scope)
-		}
+	if err := ValidateAPIKeyScopes(scopes); err != nil {
+		return nil, err
 	}
 	if len(scopes) == 0 {
 		scopes = []string{"ingest"} // Default to ingest if none specified
@@ -199,8 +201,28 @@ func GetAPIKeyByHash(keyHash string) (*APIKey, error) {
 	return &apiKey, nil
 }
 
+// ValidateAPIKeyScopes checks that every scope is one an API key can have
+func ValidateAPIKeyScopes(scopes []string) error {
+	validScopes := map[string]bool{"ingest": true, "stats": true}
+	for _, scope := range scopes {
+		if !validScopes[scope] {
+			return fmt.Errorf("invalid scope: %s (valid: ingest, stats)", scope)
+		}
+	}
+	return nil
+}
+
 // ListAPIKeys returns all API keys for a website
 func ListAPIKeys(websiteID uuid.UUID) ([]*APIKey, error) {
+	return queryAPIKeys(`WHERE website_id = $1`, websiteID)
+}
+
+// ListAllAPIKeys returns the API keys of every website
+func ListAllAPIKeys() ([]*APIKey, error) {
+	return queryAPIKeys(``)
+}
+
+func queryAPIKeys(where string, args ...any) ([]*APIKey, error) {
 	query := `
 		SELECT
 			key_id,
@@ -215,11 +237,11 @@ func ListAPIKeys(websiteID uuid.UUID) ([]*APIKey, error) {
 			revoked_at,
 			expires_at
 		FROM api_keys
-		WHERE website_id = $1
+		` + where + `
 		ORDER BY created_at DESC
 	`
 
-	rows, err := database.DB.Query(query, websiteID)
+	rows, err := database.DB.Query(query, args...)
 	if err != nil {
 		return nil, err
 	}
@@ -285,6 +307,118 @@ func RevokeAPIKey(keyID uuid.UUID) error {
 	return nil
 }
 
+// SetAPIKeyRateLimit changes how many stats API requests per minute a key may make
+func SetAPIKeyRateLimit(keyID uuid.UUID, perMinute int) error {
+	query := `UPDATE api_keys SET rate_limit_per_minute = $2 WHERE key_id = $1`
+	result, err := database.DB.Exec(query, keyID, perMinute)
+	if err != nil {
+		return err
+	}
+	rowsAffected, _ := result.RowsAffected()
+	if rowsAffected == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// SetAPIKeyExpiry changes when a key stops working; nil means never
+func SetAPIKeyExpiry(keyID uuid.UUID, expiresAt *time.Time) error {
+	query := `UPDATE api_keys SET expires_at = $2 WHERE key_id = $1`
+	result, err := database.DB.Exec(query, keyID, expiresAt)
+	if err != nil {
+		return err
+	}
+	rowsAffected, _ := result.RowsAffected()
+	if rowsAffected == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// SetAPIKeyName changes a key's label; nil clears it
+func SetAPIKeyName(keyID uuid.UUID, name *string) error {
+	query := `UPDATE api_keys SET name = $2 WHERE key_id = $1`
+	result, err := database.DB.Exec(query, keyID, name)
+	if err != nil {
+		return err
+	}
+	rowsAffected, _ := result.RowsAffected()
+	if rowsAffected == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// RotateAPIKey issues a replacement for a key with the same website, label,
+// scopes and rate limit, and the same lifetime if the key expires. The old
+// key keeps working for grace so deployments can switch over, or stops
+// immediately when grace is zero.
+func RotateAPIKey(keyID uuid.UUID, grace time.Duration) (*APIKeyCreateResult, error) {
+	old, err := GetAPIKeyByID(keyID)
+	if err != nil {
+		return nil, err
+	}
+	if !old.IsValid() {
+		return nil, ErrAPIKeyInactive
+	}
+
+	result, err := GenerateAPIKeyWithScopes(old.WebsiteID, old.CreatedBy, old.Name, old.Scopes)
+	if err != nil {
+		return nil, err
+	}
+	replacement := result.APIKey
+	if err := SetAPIKeyRateLimit(replacement.KeyID, old.RateLimitPerMinute); err != nil {
+		return nil, err
+	}
+	replacement.RateLimitPerMinute = old.RateLimitPerMinute
+	if old.ExpiresAt != nil {
+		expiresAt := replacement.CreatedAt.Add(old.ExpiresAt.Sub(old.CreatedAt))
+		if err := SetAPIKeyExpiry(replacement.KeyID, &expiresAt); err != nil {
+			return nil, err
+		}
+		replacement.ExpiresAt = &expiresAt
+	}
+
+	if grace <= 0 {
+		err = RevokeAPIKey(keyID)
+	} else if retireAt := time.Now().Add(grace); old.ExpiresAt == nil || retireAt.Before(*old.ExpiresAt) {
+		err = SetAPIKeyExpiry(keyID, &retireAt)
+	}
+	if err != nil {
+		return nil, fmt.Errorf("retire old key: %w", err)
+	}
+	return result, nil
+}
+
+// ParseAPIKeyExpiry reads a key lifetime from now ("90d", "12h"), an end
+// date (YYYY-MM-DD, exclusive) or "never", which returns nil
+func ParseAPIKeyExpiry(value string, now time.Time) (*time.Time, error) {
+	value = strings.TrimSpace(value)
+	if value == "" || value == "never" {
+		return nil, nil
+	}
+
+	var expiresAt time.Time
+	if days, ok := strings.CutSuffix(value, "d"); ok {
+		n, err := strconv.Atoi(days)
+		if err != nil || n < 1 {
+			return nil, fmt.Errorf("invalid expiry %q", value)
+		}
+		expiresAt = now.AddDate(0, 0, n)
+	} else if d, err := time.ParseDuration(value); err == nil {
+		expiresAt = now.Add(d)
+	} else if date, err := time.ParseInLocation(time.DateOnly, value, time.UTC); err == nil {
+		expiresAt = date
+	} else {
+		return nil, fmt.Errorf("invalid expiry %q (use e.g. 90d, 12h, 2026-12-31 or never)", value)
+	}
+
+	if !expiresAt.After(now) {
+		return nil, fmt.Errorf("expiry %q is not in the future", value)
+	}
+	return &expiresAt, nil
+}
+
 // RevokeAPIKeyByPrefix revokes a key by its prefix
 func RevokeAPIKeyByPrefix(prefix string) error {
 	query := `UPDATE api_keys SET revoked_at = NOW() WHERE key_prefix = $1 AND revoked_at IS NULL`
@@ -0,0 +1,101 @@
+package models
+
+import (
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/database"
+)
+
+func TestParseAPIKeyExpiry(t *testing.T) {
+	now := time.Date(2026, 5, 1, 12, 0, 0, 0, time.UTC)
+
+	expiresAt, err := ParseAPIKeyExpiry("never", now)
+	require.NoError(t, err)
+	assert.Nil(t, expiresAt)
+
+	expiresAt, err = ParseAPIKeyExpiry("90d", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2026, 7, 30, 12, 0, 0, 0, time.UTC), *expiresAt)
+
+	expiresAt, err = ParseAPIKeyExpiry("12h", now)
+	require.NoError(t, err)
+	assert.Equal(t, now.Add(12*time.Hour), *expiresAt)
+
+	expiresAt, err = ParseAPIKeyExpiry("2026-12-31", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2026, 12, 31, 0, 0, 0, 0, time.UTC), *expiresAt)
+
+	for _, value := range []string{"0d", "-1h", "2026-04-30", "soon"} {
+		_, err := ParseAPIKeyExpiry(value, now)
+		assert.Error(t, err, value)
+	}
+}
+
+func TestRotateAPIKey(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	oldID, websiteID := uuid.New(), uuid.New()
+	created := time.Now().Add(-10 * 24 * time.Hour)
+	expires := created.Add(90 * 24 * time.Hour)
+	columns := []string{"key_id", "website_id", "created_by", "key_prefix", "name", "scopes",
+		"rate_limit_per_minute", "created_at", "last_used_at", "revoked_at", "expires_at"}
+
+	mock.ExpectQuery("FROM api_keys").
+		WithArgs(oldID).
+		WillReturnRows(sqlmock.NewRows(columns).
+			AddRow(oldID.String(), websiteID.String(), nil, "kaunta_live_aaaa", "CI", "{stats}", 60, created, nil, nil, expires))
+	mock.ExpectQuery("INSERT INTO api_keys").
+		WillReturnRows(sqlmock.NewRows(columns[:8]).
+			AddRow(uuid.NewString(), websiteID.String(), nil, "kaunta_live_bbbb", "CI", "{stats}", 1000, time.Now()))
+	mock.ExpectExec("SET rate_limit_per_minute").
+		WithArgs(sqlmock.AnyArg(), 60).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("SET expires_at").
+		WithArgs(sqlmock.AnyArg(), sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("SET expires_at").
+		WithArgs(oldID, sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	result, err := RotateAPIKey(oldID, time.Hour)
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+
+	assert.Equal(t, "CI", *result.APIKey.Name)
+	assert.Equal(t, []string{"stats"}, result.APIKey.Scopes)
+	assert.Equal(t, 60, result.APIKey.RateLimitPerMinute)
+	require.NotNil(t, result.APIKey.ExpiresAt)
+	assert.WithinDuration(t, result.APIKey.CreatedAt.Add(90*24*time.Hour), *result.APIKey.ExpiresAt, time.Second)
+}
+
+func TestRotateAPIKeyRejectsRevokedKey(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	originalDB := database.DB
+	database.DB = db
+	defer func() { database.DB = originalDB }()
+
+	keyID := uuid.New()
+	mock.ExpectQuery("FROM api_keys").
+		WithArgs(keyID).
+		WillReturnRows(sqlmock.NewRows([]string{"key_id", "website_id", "created_by", "key_prefix", "name", "scopes",
+			"rate_limit_per_minute", "created_at", "last_used_at", "revoked_at", "expires_at"}).
+			AddRow(keyID.String(), uuid.NewString(), nil, "kaunta_live_aaaa", nil, "{ingest}", 1000, time.Now(), nil, time.Now(), nil))
+
+	_, err = RotateAPIKey(keyID, 0)
+	assert.ErrorIs(t, err, ErrAPIKeyInactive)
+}
@@ -0,0 +1,77 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// Audit log actions
+const (
+	AuditRetentionPurge = "retention.purge"
+	AuditPrune          = "website.prune"
+	AuditErasure        = "gdpr.erase"
+)
+
+// AuditEntry records a destructive maintenance action
+type AuditEntry struct {
+	ID        int64          `json:"id"`
+	Action    string         `json:"action"`
+	WebsiteID *uuid.UUID     `json:"website_id,omitempty"`
+	Actor     string         `json:"actor"`
+	Details   map[string]any `json:"details"`
+	CreatedAt time.Time      `json:"created_at"`
+}
+
+// execer is what RecordAudit needs from a *sql.DB or *sql.Tx
+type execer interface {
+	ExecContext(ctx context.Context, query string, args ...any) (sql.Result, error)
+}
+
+// RecordAudit appends an entry to the audit log. Pass the transaction doing
+// the work so the entry commits with it.
+func RecordAudit(ctx context.Context, db execer, action string, websiteID *uuid.UUID, actor string, details map[string]any) error {
+	detailsJSON, err := json.Marshal(details)
+	if err != nil {
+		return err
+	}
+	_, err = db.ExecContext(ctx, `
+		INSERT INTO audit_log (action, website_id, actor, details) VALUES ($1, $2, $3, $4)
+	`, action, websiteID, actor, string(detailsJSON))
+	return err
+}
+
+// ListAuditLog returns the most recent audit entries, newest first
+func ListAuditLog(ctx context.Context, db *sql.DB, limit int) ([]AuditEntry, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT audit_id, action, website_id, actor, details, created_at
+		FROM audit_log
+		ORDER BY created_at DESC, audit_id DESC
+		LIMIT $1
+	`, limit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var entries []AuditEntry
+	for rows.Next() {
+		var e AuditEntry
+		var websiteID uuid.NullUUID
+		var detailsJSON []byte
+		if err := rows.Scan(&e.ID, &e.Action, &websiteID, &e.Actor, &detailsJSON, &e.CreatedAt); err != nil {
+			return nil, err
+		}
+		if websiteID.Valid {
+			e.WebsiteID = &websiteID.UUID
+		}
+		if err := json.Unmarshal(detailsJSON, &e.Details); err != nil {
+			return nil, err
+		}
+		entries = append(entries, e)
+	}
+	return entries, rows.Err()
+}
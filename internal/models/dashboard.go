@@ -0,0 +1,255 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"slices"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// Widget kinds a custom dashboard can hold
+const (
+	WidgetMetric     = "metric"
+	WidgetTimeseries = "timeseries"
+	WidgetBreakdown  = "breakdown"
+	WidgetGoal       = "goal"
+)
+
+// WidgetKinds lists the supported widget kinds in display order
+var WidgetKinds = []string{WidgetMetric, WidgetTimeseries, WidgetBreakdown, WidgetGoal}
+
+// WidgetMetrics lists the values a metric widget can show
+var WidgetMetrics = []string{"visitors", "pageviews", "bounce_rate", "avg_visit_duration", "current_visitors"}
+
+// ErrDashboardNotFound is returned when a dashboard does not exist or is not
+// visible to the caller
+var ErrDashboardNotFound = errors.New("dashboard not found")
+
+// CustomDashboard is a user-composed set of widgets for one website
+type CustomDashboard struct {
+	ID        uuid.UUID `json:"id"`
+	UserID    uuid.UUID `json:"user_id"`
+	WebsiteID uuid.UUID `json:"website_id"`
+	Name      string    `json:"name"`
+	IsPublic  bool      `json:"is_public"`
+	CreatedAt time.Time `json:"created_at"`
+	UpdatedAt time.Time `json:"updated_at"`
+}
+
+// WidgetQuery is the saved query a widget is bound to: the period, what to
+// show, and the segment filters applied to it
+type WidgetQuery struct {
+	Days      int    `json:"days"`
+	Metric    string `json:"metric,omitempty"`
+	Dimension string `json:"dimension,omitempty"`
+	GoalID    string `json:"goal_id,omitempty"`
+	Country   string `json:"country,omitempty"`
+	Browser   string `json:"browser,omitempty"`
+	Device    string `json:"device,omitempty"`
+	Page      string `json:"page,omitempty"`
+}
+
+// DashboardWidget is one tile of a custom dashboard
+type DashboardWidget struct {
+	ID          uuid.UUID   `json:"id"`
+	DashboardID uuid.UUID   `json:"dashboard_id"`
+	Position    int         `json:"position"`
+	Kind        string      `json:"kind"`
+	Title       string      `json:"title"`
+	Query       WidgetQuery `json:"query"`
+}
+
+// Validate checks that the query carries what its widget kind needs.
+// dimensions is the set of breakdown dimensions the caller can render.
+func (q *WidgetQuery) Validate(kind string, dimensions []string) error {
+	if q.Days < 1 || q.Days > 365 {
+		return fmt.Errorf("days must be between 1 and 365")
+	}
+	switch kind {
+	case WidgetMetric:
+		if !slices.Contains(WidgetMetrics, q.Metric) {
+			return fmt.Errorf("invalid metric: %s", q.Metric)
+		}
+	case WidgetTimeseries:
+	case WidgetBreakdown:
+		if !slices.Contains(dimensions, q.Dimension) {
+			return fmt.Errorf("invalid dimension: %s", q.Dimension)
+		}
+	case WidgetGoal:
+		if _, err := uuid.Parse(q.GoalID); err != nil {
+			return fmt.Errorf("invalid goal ID")
+		}
+	default:
+		return fmt.Errorf("invalid widget kind: %s", kind)
+	}
+	return nil
+}
+
+// ListDashboards returns the dashboards owned by a user, newest first
+func ListDashboards(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]CustomDashboard, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT dashboard_id, user_id, website_id, name, is_public, created_at, updated_at
+		FROM custom_dashboard
+		WHERE user_id = $1
+		ORDER BY created_at DESC
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var dashboards []CustomDashboard
+	for rows.Next() {
+		var d CustomDashboard
+		if err := rows.Scan(&d.ID, &d.UserID, &d.WebsiteID, &d.Name, &d.IsPublic, &d.CreatedAt, &d.UpdatedAt); err != nil {
+			return nil, err
+		}
+		dashboards = append(dashboards, d)
+	}
+	return dashboards, rows.Err()
+}
+
+// GetDashboard loads a dashboard by ID. With a non-nil ownerID it must belong
+// to that user; with a nil ownerID it must be public. Either way its owner
+// must still have access to its website, so a user removed from a website
+// or team can no longer render it, nor can its share link.
+func GetDashboard(ctx context.Context, db *sql.DB, id uuid.UUID, ownerID *uuid.UUID) (*CustomDashboard, error) {
+	var d CustomDashboard
+	err := db.QueryRowContext(ctx, `
+		SELECT d.dashboard_id, d.user_id, d.website_id, d.name, d.is_public, d.created_at, d.updated_at
+		FROM custom_dashboard d
+		WHERE d.dashboard_id = $1
+		  AND d.website_id IN (SELECT a.website_id FROM user_websites_all_teams(d.user_id) a)
+	`, id).Scan(&d.ID, &d.UserID, &d.WebsiteID, &d.Name, &d.IsPublic, &d.CreatedAt, &d.UpdatedAt)
+	if err == sql.ErrNoRows {
+		return nil, ErrDashboardNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	if ownerID != nil && d.UserID != *ownerID {
+		return nil, ErrDashboardNotFound
+	}
+	if ownerID == nil && !d.IsPublic {
+		return nil, ErrDashboardNotFound
+	}
+	return &d, nil
+}
+
+// CreateDashboard stores a new empty dashboard for a website the user can view
+func CreateDashboard(ctx context.Context, db *sql.DB, userID, websiteID uuid.UUID, name string) (*CustomDashboard, error) {
+	d := CustomDashboard{UserID: userID, WebsiteID: websiteID, Name: name}
+	err := db.QueryRowContext(ctx, `
+		INSERT INTO custom_dashboard (user_id, website_id, name)
+		SELECT $1, website_id, $3
+		FROM user_websites($1)
+		WHERE website_id = $2
+		RETURNING dashboard_id, created_at, updated_at
+	`, userID, websiteID, name).Scan(&d.ID, &d.CreatedAt, &d.UpdatedAt)
+	if err == sql.ErrNoRows {
+		return nil, fmt.Errorf("website not found")
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &d, nil
+}
+
+// DeleteDashboard removes a dashboard and its widgets
+func DeleteDashboard(ctx context.Context, db *sql.DB, id, userID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `DELETE FROM custom_dashboard WHERE dashboard_id = $1 AND user_id = $2`, id, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrDashboardNotFound
+	}
+	return nil
+}
+
+// SetDashboardPublic toggles the share link for a dashboard
+func SetDashboardPublic(ctx context.Context, db *sql.DB, id, userID uuid.UUID, public bool) error {
+	res, err := db.ExecContext(ctx, `
+		UPDATE custom_dashboard SET is_public = $3, updated_at = NOW()
+		WHERE dashboard_id = $1 AND user_id = $2
+	`, id, userID, public)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrDashboardNotFound
+	}
+	return nil
+}
+
+// ListWidgets returns a dashboard's widgets in display order
+func ListWidgets(ctx context.Context, db *sql.DB, dashboardID uuid.UUID) ([]DashboardWidget, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT widget_id, dashboard_id, position, kind, title, query
+		FROM custom_dashboard_widget
+		WHERE dashboard_id = $1
+		ORDER BY position, created_at
+	`, dashboardID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var widgets []DashboardWidget
+	for rows.Next() {
+		var w DashboardWidget
+		var query []byte
+		if err := rows.Scan(&w.ID, &w.DashboardID, &w.Position, &w.Kind, &w.Title, &query); err != nil {
+			return nil, err
+		}
+		if err := json.Unmarshal(query, &w.Query); err != nil {
+			return nil, fmt.Errorf("decode widget %s query: %w", w.ID, err)
+		}
+		widgets = append(widgets, w)
+	}
+	return widgets, rows.Err()
+}
+
+// AddWidget appends a widget to the end of a dashboard; the caller validates
+// the query and dashboard ownership
+func AddWidget(ctx context.Context, db *sql.DB, dashboardID uuid.UUID, kind, title string, query WidgetQuery) (*DashboardWidget, error) {
+	raw, err := json.Marshal(query)
+	if err != nil {
+		return nil, err
+	}
+
+	w := DashboardWidget{DashboardID: dashboardID, Kind: kind, Title: title, Query: query}
+	err = db.QueryRowContext(ctx, `
+		INSERT INTO custom_dashboard_widget (dashboard_id, position, kind, title, query)
+		VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM custom_dashboard_widget WHERE dashboard_id = $1), $2, $3, $4)
+		RETURNING widget_id, position
+	`, dashboardID, kind, title, raw).Scan(&w.ID, &w.Position)
+	if err != nil {
+		return nil, err
+	}
+	return &w, nil
+}
+
+// DeleteWidget removes a widget from a dashboard owned by userID
+func DeleteWidget(ctx context.Context, db *sql.DB, widgetID, dashboardID, userID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `
+		DELETE FROM custom_dashboard_widget w
+		USING custom_dashboard d
+		WHERE w.widget_id = $1
+		  AND w.dashboard_id = $2
+		  AND d.dashboard_id = w.dashboard_id
+		  AND d.user_id = $3
+	`, widgetID, dashboardID, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrDashboardNotFound
+	}
+	return nil
+}
@@ -0,0 +1,99 @@
+package models
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestWidgetQueryValidate(t *testing.T) {
+	dims := []string{"pages", "countries"}
+	goalID := uuid.New().String()
+
+	tests := []struct {
+		name  string
+		kind  string
+		query WidgetQuery
+		ok    bool
+	}{
+		{"metric", WidgetMetric, WidgetQuery{Days: 7, Metric: "visitors"}, true},
+		{"unknown metric", WidgetMetric, WidgetQuery{Days: 7, Metric: "revenue"}, false},
+		{"timeseries", WidgetTimeseries, WidgetQuery{Days: 30, Country: "DE"}, true},
+		{"zero days", WidgetTimeseries, WidgetQuery{}, false},
+		{"too many days", WidgetTimeseries, WidgetQuery{Days: 400}, false},
+		{"breakdown", WidgetBreakdown, WidgetQuery{Days: 7, Dimension: "pages"}, true},
+		{"unknown dimension", WidgetBreakdown, WidgetQuery{Days: 7, Dimension: "shoe_size"}, false},
+		{"goal", WidgetGoal, WidgetQuery{Days: 7, GoalID: goalID}, true},
+		{"goal without id", WidgetGoal, WidgetQuery{Days: 7}, false},
+		{"unknown kind", "funnel", WidgetQuery{Days: 7}, false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			err := tt.query.Validate(tt.kind, dims)
+			if tt.ok {
+				assert.NoError(t, err)
+			} else {
+				assert.Error(t, err)
+			}
+		})
+	}
+}
+
+func TestGetDashboardVisibility(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	id, owner, other := uuid.New(), uuid.New(), uuid.New()
+	now := time.Now()
+	columns := []string{"dashboard_id", "user_id", "website_id", "name", "is_public", "created_at", "updated_at"}
+	private := func() {
+		mock.ExpectQuery("FROM custom_dashboard").WithArgs(id).
+			WillReturnRows(sqlmock.NewRows(columns).AddRow(id.String(), owner.String(), uuid.New().String(), "Launch", false, now, now))
+	}
+
+	private()
+	d, err := GetDashboard(context.Background(), db, id, &owner)
+	require.NoError(t, err)
+	assert.Equal(t, "Launch", d.Name)
+
+	private()
+	_, err = GetDashboard(context.Background(), db, id, &other)
+	assert.ErrorIs(t, err, ErrDashboardNotFound)
+
+	// Anonymous access only sees public dashboards
+	private()
+	_, err = GetDashboard(context.Background(), db, id, nil)
+	assert.ErrorIs(t, err, ErrDashboardNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestGetDashboardRechecksOwnerAccess(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	// The owner was removed from the dashboard's website: neither they nor
+	// its share link can render it
+	id, owner := uuid.New(), uuid.New()
+	columns := []string{"dashboard_id", "user_id", "website_id", "name", "is_public", "created_at", "updated_at"}
+	for range 2 {
+		mock.ExpectQuery(`website_id IN \(SELECT a.website_id FROM user_websites_all_teams\(d.user_id\) a\)`).WithArgs(id).
+			WillReturnRows(sqlmock.NewRows(columns))
+	}
+
+	_, err = GetDashboard(context.Background(), db, id, &owner)
+	assert.ErrorIs(t, err, ErrDashboardNotFound)
+
+	_, err = GetDashboard(context.Background(), db, id, nil)
+	assert.ErrorIs(t, err, ErrDashboardNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
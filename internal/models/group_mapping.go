@@ -0,0 +1,109 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+)
+
+// GroupMapping gives members of a directory group a role in a team
+type GroupMapping struct {
+	Group     string      `json:"group"`
+	TeamID    uuid.UUID   `json:"team_id"`
+	TeamName  string      `json:"team_name"`
+	Role      WebsiteRole `json:"role"`
+	CreatedAt time.Time   `json:"created_at"`
+}
+
+// SetGroupMapping maps a group to a team role, replacing the role of an
+// existing mapping. Members pick it up on their next sign-in.
+func SetGroupMapping(ctx context.Context, db *sql.DB, group string, teamID uuid.UUID, role WebsiteRole) error {
+	_, err := db.ExecContext(ctx, `
+		INSERT INTO group_mapping (group_name, team_id, role) VALUES ($1, $2, $3)
+		ON CONFLICT (group_name, team_id) DO UPDATE SET role = EXCLUDED.role
+	`, group, teamID, string(role))
+	return err
+}
+
+// RemoveGroupMapping deletes a mapping. It returns sql.ErrNoRows when there
+// was none.
+func RemoveGroupMapping(ctx context.Context, db *sql.DB, group string, teamID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `
+		DELETE FROM group_mapping WHERE group_name = $1 AND team_id = $2
+	`, group, teamID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// ListGroupMappings returns every mapping, by group and then team
+func ListGroupMappings(ctx context.Context, db *sql.DB) ([]GroupMapping, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT g.group_name, g.team_id, t.name, g.role, g.created_at
+		FROM group_mapping g
+		JOIN team t ON t.team_id = g.team_id
+		ORDER BY g.group_name, t.name
+	`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	mappings := []GroupMapping{}
+	for rows.Next() {
+		var m GroupMapping
+		var role string
+		if err := rows.Scan(&m.Group, &m.TeamID, &m.TeamName, &role, &m.CreatedAt); err != nil {
+			return nil, err
+		}
+		m.Role = WebsiteRole(role)
+		mappings = append(mappings, m)
+	}
+	return mappings, rows.Err()
+}
+
+// SyncGroupTeams brings the user's synced team memberships in line with
+// their groups: the highest mapped role in each team a group maps to, and
+// no synced membership anywhere else. Memberships set by hand are kept.
+func SyncGroupTeams(ctx context.Context, db *sql.DB, userID uuid.UUID, groups []string) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if _, err := tx.ExecContext(ctx, `
+		DELETE FROM team_member
+		WHERE user_id = $1 AND synced
+		  AND team_id NOT IN (SELECT team_id FROM group_mapping WHERE group_name = ANY($2))
+	`, userID, pq.Array(groups)); err != nil {
+		return err
+	}
+	if _, err := tx.ExecContext(ctx, `
+		INSERT INTO team_member (team_id, user_id, role, synced)
+		SELECT team_id, $1, (ARRAY['viewer', 'editor', 'owner'])[MAX(website_role_rank(role))], TRUE
+		FROM group_mapping
+		WHERE group_name = ANY($2)
+		GROUP BY team_id
+		ON CONFLICT (team_id, user_id) DO UPDATE SET role = EXCLUDED.role
+		WHERE team_member.synced
+	`, userID, pq.Array(groups)); err != nil {
+		return err
+	}
+	// Don't leave them switched to a team they can no longer see
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET current_team_id = NULL
+		WHERE user_id = $1 AND current_team_id IS NOT NULL
+		  AND current_team_id NOT IN (SELECT team_id FROM team_member WHERE user_id = $1)
+	`, userID); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
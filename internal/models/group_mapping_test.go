@@ -0,0 +1,46 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestSyncGroupTeams(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	groups := []string{"marketing", "admins"}
+	mock.ExpectBegin()
+	mock.ExpectExec("DELETE FROM team_member").WithArgs(userID, pq.Array(groups)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO team_member").WithArgs(userID, pq.Array(groups)).
+		WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectExec("UPDATE users SET current_team_id = NULL").WithArgs(userID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectCommit()
+
+	require.NoError(t, SyncGroupTeams(context.Background(), db, userID, groups))
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRemoveGroupMapping(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	teamID := uuid.New()
+	mock.ExpectExec("DELETE FROM group_mapping").WithArgs("staff", teamID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	assert.ErrorIs(t, RemoveGroupMapping(context.Background(), db, "staff", teamID), sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
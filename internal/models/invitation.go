@@ -0,0 +1,212 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"net/mail"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// InvitationTTL is how long an invitation link stays valid
+const InvitationTTL = 7 * 24 * time.Hour
+
+var (
+	// ErrInvitationNotFound is returned for unknown, expired or already
+	// accepted invitations
+	ErrInvitationNotFound = errors.New("invitation expired or not found")
+	// ErrUsernameTaken is returned when signing up with a username in use
+	ErrUsernameTaken = errors.New("username is already taken")
+)
+
+// Invitation grants a role on a website or a team to whoever accepts it
+type Invitation struct {
+	InvitationID uuid.UUID   `json:"invitation_id"`
+	Email        string      `json:"email"`
+	WebsiteID    *uuid.UUID  `json:"website_id,omitempty"`
+	TeamID       *uuid.UUID  `json:"team_id,omitempty"`
+	Target       string      `json:"target"` // Website domain or team name
+	Role         WebsiteRole `json:"role"`
+	InvitedBy    *string     `json:"invited_by,omitempty"` // Username
+	ExpiresAt    time.Time   `json:"expires_at"`
+	CreatedAt    time.Time   `json:"created_at"`
+}
+
+// Kind names what the invitation is for, "website" or "team"
+func (inv *Invitation) Kind() string {
+	if inv.TeamID != nil {
+		return "team"
+	}
+	return "website"
+}
+
+// NormalizeEmail validates an address and returns it without a display name
+func NormalizeEmail(email string) (string, error) {
+	addr, err := mail.ParseAddress(strings.TrimSpace(email))
+	if err != nil {
+		return "", fmt.Errorf("invalid email address: %s", email)
+	}
+	return addr.Address, nil
+}
+
+// CreateInvitation records an invitation to a website (websiteID set) or a
+// team (teamID set) and returns it with the token for the link. Only the
+// token's hash is stored.
+func CreateInvitation(ctx context.Context, db *sql.DB, email string, websiteID, teamID *uuid.UUID, role WebsiteRole, invitedBy *uuid.UUID) (*Invitation, string, error) {
+	email, err := NormalizeEmail(email)
+	if err != nil {
+		return nil, "", err
+	}
+	if (websiteID == nil) == (teamID == nil) {
+		return nil, "", errors.New("an invitation is for exactly one website or team")
+	}
+
+	raw := make([]byte, 32)
+	if _, err := rand.Read(raw); err != nil {
+		return nil, "", err
+	}
+	token := hex.EncodeToString(raw)
+
+	inv := Invitation{
+		InvitationID: uuid.New(),
+		Email:        email,
+		WebsiteID:    websiteID,
+		TeamID:       teamID,
+		Role:         role,
+		ExpiresAt:    time.Now().Add(InvitationTTL),
+	}
+	err = db.QueryRowContext(ctx, `
+		INSERT INTO invitation (invitation_id, token_hash, email, website_id, team_id, role, invited_by, expires_at)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+		RETURNING created_at,
+		          COALESCE((SELECT domain FROM website WHERE website_id = $4), (SELECT name FROM team WHERE team_id = $5), ''),
+		          (SELECT username FROM users WHERE user_id = $7)
+	`, inv.InvitationID, sha256Hex(token), email, websiteID, teamID, string(role), invitedBy, inv.ExpiresAt).
+		Scan(&inv.CreatedAt, &inv.Target, &inv.InvitedBy)
+	if err != nil {
+		return nil, "", err
+	}
+	return &inv, token, nil
+}
+
+// GetInvitation returns a pending invitation by its token
+func GetInvitation(ctx context.Context, db *sql.DB, token string) (*Invitation, error) {
+	var inv Invitation
+	var role string
+	err := db.QueryRowContext(ctx, `
+		SELECT i.invitation_id, i.email, i.website_id, i.team_id,
+		       COALESCE(w.domain, t.name, ''), i.role, u.username, i.expires_at, i.created_at
+		FROM invitation i
+		LEFT JOIN website w ON w.website_id = i.website_id
+		LEFT JOIN team t ON t.team_id = i.team_id
+		LEFT JOIN users u ON u.user_id = i.invited_by
+		WHERE i.token_hash = $1 AND i.accepted_at IS NULL AND i.expires_at > NOW()
+	`, sha256Hex(token)).Scan(&inv.InvitationID, &inv.Email, &inv.WebsiteID, &inv.TeamID,
+		&inv.Target, &role, &inv.InvitedBy, &inv.ExpiresAt, &inv.CreatedAt)
+	if err == sql.ErrNoRows {
+		return nil, ErrInvitationNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	inv.Role = WebsiteRole(role)
+	return &inv, nil
+}
+
+// AcceptInvitation grants the invitation's role to an existing user and
+// uses the invitation up
+func AcceptInvitation(ctx context.Context, db *sql.DB, token string, userID uuid.UUID) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if err := acceptInvitation(ctx, tx, token, userID); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+// SignUpWithInvitation creates an account and accepts the invitation with
+// it. Nothing is created when the invitation is no longer valid.
+func SignUpWithInvitation(ctx context.Context, db *sql.DB, token, username, password, name string) (uuid.UUID, error) {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return uuid.Nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var taken bool
+	if err := tx.QueryRowContext(ctx, `SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)`, username).Scan(&taken); err != nil {
+		return uuid.Nil, err
+	}
+	if taken {
+		return uuid.Nil, ErrUsernameTaken
+	}
+
+	userID := uuid.New()
+	if _, err := tx.ExecContext(ctx, `
+		INSERT INTO users (user_id, username, password_hash, name)
+		VALUES ($1, $2, hash_password($3), NULLIF($4, ''))
+	`, userID, username, password, name); err != nil {
+		return uuid.Nil, err
+	}
+	if err := acceptInvitation(ctx, tx, token, userID); err != nil {
+		return uuid.Nil, err
+	}
+	return userID, tx.Commit()
+}
+
+// acceptInvitation marks the invitation used and grants its role. An
+// existing higher role is kept, and the website's owner is left alone.
+func acceptInvitation(ctx context.Context, tx *sql.Tx, token string, userID uuid.UUID) error {
+	var email, role string
+	var websiteID, teamID *uuid.UUID
+	err := tx.QueryRowContext(ctx, `
+		UPDATE invitation SET accepted_at = NOW(), accepted_by = $2
+		WHERE token_hash = $1 AND accepted_at IS NULL AND expires_at > NOW()
+		RETURNING email, website_id, team_id, role
+	`, sha256Hex(token), userID).Scan(&email, &websiteID, &teamID, &role)
+	if err == sql.ErrNoRows {
+		return ErrInvitationNotFound
+	}
+	if err != nil {
+		return err
+	}
+
+	if websiteID != nil {
+		_, err = tx.ExecContext(ctx, `
+			INSERT INTO website_member (website_id, user_id, role)
+			SELECT $1, $2, $3
+			WHERE NOT EXISTS (SELECT 1 FROM website WHERE website_id = $1 AND user_id = $2)
+			ON CONFLICT (website_id, user_id) DO UPDATE
+				SET role = CASE WHEN website_role_rank(EXCLUDED.role) > website_role_rank(website_member.role)
+				                THEN EXCLUDED.role ELSE website_member.role END
+		`, *websiteID, userID, role)
+	} else {
+		_, err = tx.ExecContext(ctx, `
+			INSERT INTO team_member (team_id, user_id, role) VALUES ($1, $2, $3)
+			ON CONFLICT (team_id, user_id) DO UPDATE
+				SET role = CASE WHEN website_role_rank(EXCLUDED.role) > website_role_rank(team_member.role)
+				                THEN EXCLUDED.role ELSE team_member.role END
+		`, *teamID, userID, role)
+	}
+	if err != nil {
+		return err
+	}
+
+	// Remember the address unless the user has one or another account uses it
+	_, err = tx.ExecContext(ctx, `
+		UPDATE users SET email = $2
+		WHERE user_id = $1 AND email IS NULL
+		  AND NOT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($2))
+	`, userID, email)
+	return err
+}
@@ -0,0 +1,92 @@
+package models
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestCreateInvitation(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID, inviter := uuid.New(), uuid.New()
+	mock.ExpectQuery("INSERT INTO invitation").
+		WithArgs(sqlmock.AnyArg(), sqlmock.AnyArg(), "bob@example.com", &websiteID, nil, "editor", &inviter, sqlmock.AnyArg()).
+		WillReturnRows(sqlmock.NewRows([]string{"created_at", "target", "username"}).AddRow(time.Now(), "example.com", "alice"))
+
+	inv, token, err := CreateInvitation(context.Background(), db, "Bob <bob@example.com>", &websiteID, nil, RoleEditor, &inviter)
+	require.NoError(t, err)
+	assert.Len(t, token, 64)
+	assert.Equal(t, "bob@example.com", inv.Email)
+	assert.Equal(t, "website", inv.Kind())
+	assert.Equal(t, "example.com", inv.Target)
+	assert.WithinDuration(t, time.Now().Add(InvitationTTL), inv.ExpiresAt, time.Minute)
+
+	_, _, err = CreateInvitation(context.Background(), db, "not an address", &websiteID, nil, RoleEditor, &inviter)
+	assert.Error(t, err)
+
+	// Exactly one of website and team
+	_, _, err = CreateInvitation(context.Background(), db, "bob@example.com", nil, nil, RoleEditor, &inviter)
+	assert.Error(t, err)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestAcceptInvitationUsedOrExpired(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("UPDATE invitation SET accepted_at").WithArgs(sha256Hex("token"), sqlmock.AnyArg()).
+		WillReturnRows(sqlmock.NewRows([]string{"email", "website_id", "team_id", "role"}))
+	mock.ExpectRollback()
+
+	err = AcceptInvitation(context.Background(), db, "token", uuid.New())
+	assert.ErrorIs(t, err, ErrInvitationNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSignUpWithInvitation(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	teamID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT EXISTS").WithArgs("bob").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(false))
+	mock.ExpectExec("INSERT INTO users").WithArgs(sqlmock.AnyArg(), "bob", "password123", "Bob").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectQuery("UPDATE invitation SET accepted_at").WithArgs(sha256Hex("token"), sqlmock.AnyArg()).
+		WillReturnRows(sqlmock.NewRows([]string{"email", "website_id", "team_id", "role"}).
+			AddRow("bob@example.com", nil, teamID.String(), "viewer"))
+	mock.ExpectExec("INSERT INTO team_member").WithArgs(teamID, sqlmock.AnyArg(), "viewer").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("UPDATE users SET email").WithArgs(sqlmock.AnyArg(), "bob@example.com").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+
+	userID, err := SignUpWithInvitation(context.Background(), db, "token", "bob", "password123", "Bob")
+	require.NoError(t, err)
+	assert.NotEqual(t, uuid.Nil, userID)
+
+	// Taken usernames stop before anything is written
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT EXISTS").WithArgs("bob").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(true))
+	mock.ExpectRollback()
+
+	_, err = SignUpWithInvitation(context.Background(), db, "token", "bob", "password123", "Bob")
+	assert.ErrorIs(t, err, ErrUsernameTaken)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,207 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"crypto/subtle"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// kioskTokenBytes is the entropy of a kiosk token (hex-encoded to 64 chars)
+const kioskTokenBytes = 32
+
+// ErrKioskLinkNotFound is returned when a kiosk token or a website's kiosk
+// link does not exist or is not visible to the caller
+var ErrKioskLinkNotFound = errors.New("kiosk link not found")
+
+// ErrKioskPasswordIncorrect is returned when unlocking a kiosk link with the
+// wrong password
+var ErrKioskPasswordIncorrect = errors.New("incorrect kiosk link password")
+
+// KioskLink is the secret share token for a website's TV view
+type KioskLink struct {
+	Token       string     `json:"token"`
+	WebsiteID   uuid.UUID  `json:"website_id"`
+	UserID      uuid.UUID  `json:"user_id"`
+	CreatedAt   time.Time  `json:"created_at"`
+	HasPassword bool       `json:"has_password"`
+	ExpiresAt   *time.Time `json:"expires_at,omitempty"`
+}
+
+// KioskWebsite is the website a kiosk token opens
+type KioskWebsite struct {
+	WebsiteID uuid.UUID
+	Name      string
+	Domain    string
+	// accessKey is what UnlockKiosk hands out for a password-protected
+	// link; empty for an open one
+	accessKey string
+}
+
+// PasswordProtected reports whether viewers must enter a password first
+func (s *KioskWebsite) PasswordProtected() bool {
+	return s.accessKey != ""
+}
+
+// Unlocks reports whether an access key from UnlockKiosk opens the link.
+// Open links need none.
+func (s *KioskWebsite) Unlocks(key string) bool {
+	return s.accessKey == "" || subtle.ConstantTimeCompare([]byte(s.accessKey), []byte(key)) == 1
+}
+
+// kioskAccessKey derives the unlock key from the token and password hash,
+// so rotating the token or changing the password locks viewers out again
+func kioskAccessKey(token, passwordHash string) string {
+	return sha256Hex(token + ":" + passwordHash)
+}
+
+// GetKioskLink returns the kiosk link of a website the user owns
+func GetKioskLink(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) (*KioskLink, error) {
+	var k KioskLink
+	err := db.QueryRowContext(ctx, `
+		SELECT k.token, k.website_id, k.user_id, k.created_at, k.password_hash IS NOT NULL, k.expires_at
+		FROM kiosk_link k
+		JOIN user_websites($2) w ON w.website_id = k.website_id
+		WHERE k.website_id = $1 AND w.role = 'owner'
+	`, websiteID, userID).Scan(&k.Token, &k.WebsiteID, &k.UserID, &k.CreatedAt, &k.HasPassword, &k.ExpiresAt)
+	if err == sql.ErrNoRows {
+		return nil, ErrKioskLinkNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &k, nil
+}
+
+// RotateKioskLink creates the kiosk link for a website the user owns, or
+// replaces its token so the previous URL stops working. The password and
+// expiry are kept.
+func RotateKioskLink(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) (*KioskLink, error) {
+	raw := make([]byte, kioskTokenBytes)
+	if _, err := rand.Read(raw); err != nil {
+		return nil, err
+	}
+
+	k := KioskLink{Token: hex.EncodeToString(raw), WebsiteID: websiteID, UserID: userID}
+	err := db.QueryRowContext(ctx, `
+		INSERT INTO kiosk_link (token, website_id, user_id)
+		SELECT $1, website_id, $3
+		FROM user_websites($3)
+		WHERE website_id = $2 AND role = 'owner'
+		ON CONFLICT (website_id) DO UPDATE
+			SET token = EXCLUDED.token, user_id = EXCLUDED.user_id, created_at = NOW()
+		RETURNING created_at, password_hash IS NOT NULL, expires_at
+	`, k.Token, websiteID, userID).Scan(&k.CreatedAt, &k.HasPassword, &k.ExpiresAt)
+	if err == sql.ErrNoRows {
+		return nil, ErrKioskLinkNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &k, nil
+}
+
+// SetKioskLinkProtection sets the password and expiry of the kiosk link of
+// a website the user owns. A nil password keeps the current one and an
+// empty one removes it; a nil expiresAt makes the link permanent.
+func SetKioskLinkProtection(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID, password *string, expiresAt *time.Time) (*KioskLink, error) {
+	k := KioskLink{WebsiteID: websiteID}
+	err := db.QueryRowContext(ctx, `
+		UPDATE kiosk_link k SET
+			password_hash = CASE
+				WHEN $3::text IS NULL THEN k.password_hash
+				WHEN $3 = '' THEN NULL
+				ELSE hash_password($3)
+			END,
+			expires_at = $4
+		FROM user_websites($2) w
+		WHERE k.website_id = w.website_id
+		  AND k.website_id = $1 AND w.role = 'owner'
+		RETURNING k.token, k.user_id, k.created_at, k.password_hash IS NOT NULL, k.expires_at
+	`, websiteID, userID, password, expiresAt).Scan(&k.Token, &k.UserID, &k.CreatedAt, &k.HasPassword, &k.ExpiresAt)
+	if err == sql.ErrNoRows {
+		return nil, ErrKioskLinkNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &k, nil
+}
+
+// DeleteKioskLink revokes a website's kiosk link
+func DeleteKioskLink(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `
+		DELETE FROM kiosk_link k
+		USING user_websites($2) w
+		WHERE k.website_id = w.website_id
+		  AND k.website_id = $1 AND w.role = 'owner'
+	`, websiteID, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrKioskLinkNotFound
+	}
+	return nil
+}
+
+// GetKioskWebsite resolves an unexpired kiosk token to its website
+func GetKioskWebsite(ctx context.Context, db *sql.DB, token string) (*KioskWebsite, error) {
+	if len(token) != kioskTokenBytes*2 {
+		return nil, ErrKioskLinkNotFound
+	}
+
+	var site KioskWebsite
+	var passwordHash sql.NullString
+	err := db.QueryRowContext(ctx, `
+		SELECT w.website_id, COALESCE(w.name, ''), w.domain, k.password_hash
+		FROM kiosk_link k
+		JOIN website w ON w.website_id = k.website_id
+		WHERE k.token = $1 AND w.deleted_at IS NULL
+		  AND (k.expires_at IS NULL OR k.expires_at > NOW())
+	`, token).Scan(&site.WebsiteID, &site.Name, &site.Domain, &passwordHash)
+	if err == sql.ErrNoRows {
+		return nil, ErrKioskLinkNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	if passwordHash.Valid {
+		site.accessKey = kioskAccessKey(token, passwordHash.String)
+	}
+	return &site, nil
+}
+
+// UnlockKiosk checks the password of a protected kiosk link and returns the
+// access key that KioskWebsite.Unlocks accepts
+func UnlockKiosk(ctx context.Context, db *sql.DB, token, password string) (string, error) {
+	if len(token) != kioskTokenBytes*2 {
+		return "", ErrKioskLinkNotFound
+	}
+
+	var passwordHash string
+	var ok bool
+	err := db.QueryRowContext(ctx, `
+		SELECT k.password_hash, verify_password($2, k.password_hash)
+		FROM kiosk_link k
+		JOIN website w ON w.website_id = k.website_id
+		WHERE k.token = $1 AND w.deleted_at IS NULL
+		  AND k.password_hash IS NOT NULL
+		  AND (k.expires_at IS NULL OR k.expires_at > NOW())
+	`, token, password).Scan(&passwordHash, &ok)
+	if err == sql.ErrNoRows {
+		return "", ErrKioskLinkNotFound
+	}
+	if err != nil {
+		return "", err
+	}
+	if !ok {
+		return "", ErrKioskPasswordIncorrect
+	}
+	return kioskAccessKey(token, passwordHash), nil
+}
@@ -0,0 +1,100 @@
+package models
+
+import (
+	"context"
+	"strings"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestGetKioskWebsite(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	token := strings.Repeat("ab", kioskTokenBytes)
+	websiteID := uuid.New()
+	mock.ExpectQuery("FROM kiosk_link").WithArgs(token).
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "name", "domain", "password_hash"}).
+			AddRow(websiteID.String(), "Docs", "docs.example.com", nil))
+
+	site, err := GetKioskWebsite(context.Background(), db, token)
+	require.NoError(t, err)
+	assert.Equal(t, websiteID, site.WebsiteID)
+	assert.Equal(t, "docs.example.com", site.Domain)
+	assert.False(t, site.PasswordProtected())
+	assert.True(t, site.Unlocks(""))
+
+	// Malformed tokens never reach the database
+	_, err = GetKioskWebsite(context.Background(), db, "short")
+	assert.ErrorIs(t, err, ErrKioskLinkNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRotateKioskLinkRequiresOwnership(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID, userID := uuid.New(), uuid.New()
+	mock.ExpectQuery("INSERT INTO kiosk_link").
+		WithArgs(sqlmock.AnyArg(), websiteID, userID).
+		WillReturnRows(sqlmock.NewRows([]string{"created_at"}))
+
+	_, err = RotateKioskLink(context.Background(), db, websiteID, userID)
+	assert.ErrorIs(t, err, ErrKioskLinkNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestKioskPassword(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	token := strings.Repeat("cd", kioskTokenBytes)
+	mock.ExpectQuery("verify_password").WithArgs(token, "secret").
+		WillReturnRows(sqlmock.NewRows([]string{"password_hash", "ok"}).AddRow("$2a$10$hash", true))
+	key, err := UnlockKiosk(context.Background(), db, token, "secret")
+	require.NoError(t, err)
+
+	mock.ExpectQuery("verify_password").WithArgs(token, "guess").
+		WillReturnRows(sqlmock.NewRows([]string{"password_hash", "ok"}).AddRow("$2a$10$hash", false))
+	_, err = UnlockKiosk(context.Background(), db, token, "guess")
+	assert.ErrorIs(t, err, ErrKioskPasswordIncorrect)
+
+	// The key opens the link until the password changes
+	mock.ExpectQuery("FROM kiosk_link").WithArgs(token).
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "name", "domain", "password_hash"}).
+			AddRow(uuid.New().String(), "Docs", "docs.example.com", "$2a$10$hash"))
+	site, err := GetKioskWebsite(context.Background(), db, token)
+	require.NoError(t, err)
+	assert.True(t, site.PasswordProtected())
+	assert.True(t, site.Unlocks(key))
+	assert.False(t, site.Unlocks(""))
+	assert.False(t, site.Unlocks(kioskAccessKey(token, "$2a$10$other")))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSetKioskLinkProtectionRequiresOwnership(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID, userID := uuid.New(), uuid.New()
+	password := "secret"
+	mock.ExpectQuery("UPDATE kiosk_link").
+		WithArgs(websiteID, userID, password, nil).
+		WillReturnRows(sqlmock.NewRows([]string{"token", "user_id", "created_at", "protected", "expires_at"}))
+
+	_, err = SetKioskLinkProtection(context.Background(), db, websiteID, userID, &password, nil)
+	assert.ErrorIs(t, err, ErrKioskLinkNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
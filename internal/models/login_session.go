@@ -0,0 +1,68 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// LoginSession is a signed-in dashboard session (not a tracked visitor
+// session)
+type LoginSession struct {
+	SessionID  uuid.UUID `json:"session_id"`
+	UserAgent  *string   `json:"user_agent,omitempty"`
+	IPAddress  *string   `json:"ip_address,omitempty"`
+	CreatedAt  time.Time `json:"created_at"`
+	LastUsedAt time.Time `json:"last_used_at"`
+	ExpiresAt  time.Time `json:"expires_at"`
+}
+
+// ListLoginSessions returns the user's unexpired sessions, most recently
+// used first
+func ListLoginSessions(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]LoginSession, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT session_id, user_agent, HOST(ip_address), created_at, last_used_at, expires_at
+		FROM user_sessions
+		WHERE user_id = $1 AND expires_at > NOW()
+		ORDER BY last_used_at DESC
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	sessions := []LoginSession{}
+	for rows.Next() {
+		var s LoginSession
+		if err := rows.Scan(&s.SessionID, &s.UserAgent, &s.IPAddress, &s.CreatedAt, &s.LastUsedAt, &s.ExpiresAt); err != nil {
+			return nil, err
+		}
+		sessions = append(sessions, s)
+	}
+	return sessions, rows.Err()
+}
+
+// RevokeLoginSession signs one of the user's sessions out. It returns
+// sql.ErrNoRows when the user has no such session.
+func RevokeLoginSession(ctx context.Context, db *sql.DB, userID, sessionID uuid.UUID) error {
+	result, err := db.ExecContext(ctx, `DELETE FROM user_sessions WHERE user_id = $1 AND session_id = $2`, userID, sessionID)
+	if err != nil {
+		return err
+	}
+	if n, _ := result.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// RevokeOtherLoginSessions signs the user out everywhere except keep and
+// returns how many sessions ended
+func RevokeOtherLoginSessions(ctx context.Context, db *sql.DB, userID, keep uuid.UUID) (int64, error) {
+	result, err := db.ExecContext(ctx, `DELETE FROM user_sessions WHERE user_id = $1 AND session_id <> $2`, userID, keep)
+	if err != nil {
+		return 0, err
+	}
+	return result.RowsAffected()
+}
@@ -0,0 +1,36 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestRevokeLoginSession(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID, sessionID := uuid.New(), uuid.New()
+	mock.ExpectExec("DELETE FROM user_sessions").WithArgs(userID, sessionID).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	assert.NoError(t, RevokeLoginSession(context.Background(), db, userID, sessionID))
+
+	// Other users' sessions are not found
+	mock.ExpectExec("DELETE FROM user_sessions").WithArgs(userID, sessionID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	assert.ErrorIs(t, RevokeLoginSession(context.Background(), db, userID, sessionID), sql.ErrNoRows)
+
+	mock.ExpectExec("DELETE FROM user_sessions").WithArgs(userID, sessionID).
+		WillReturnResult(sqlmock.NewResult(0, 3))
+	revoked, err := RevokeOtherLoginSessions(context.Background(), db, userID, sessionID)
+	require.NoError(t, err)
+	assert.Equal(t, int64(3), revoked)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
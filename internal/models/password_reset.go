@@ -0,0 +1,113 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// PasswordResetTTL is how long a password reset link stays valid
+const PasswordResetTTL = time.Hour
+
+// ErrPasswordResetNotFound is returned for unknown, expired or used reset
+// links
+var ErrPasswordResetNotFound = errors.New("password reset link expired or not found")
+
+// PasswordResetUser is the account a reset is requested for
+type PasswordResetUser struct {
+	UserID   uuid.UUID
+	Username string
+	Email    *string
+}
+
+// FindPasswordResetUser looks an account up by username or email address.
+// It returns sql.ErrNoRows when there is none or it is disabled.
+func FindPasswordResetUser(ctx context.Context, db *sql.DB, login string) (*PasswordResetUser, error) {
+	var user PasswordResetUser
+	err := db.QueryRowContext(ctx, `
+		SELECT user_id, username, email FROM users
+		WHERE (username = $1 OR LOWER(email) = LOWER($1)) AND disabled_at IS NULL
+		ORDER BY username = $1 DESC
+		LIMIT 1
+	`, login).Scan(&user.UserID, &user.Username, &user.Email)
+	if err != nil {
+		return nil, err
+	}
+	return &user, nil
+}
+
+// CreatePasswordReset issues a reset token for the user and returns it with
+// its expiry. Only the token's hash is stored.
+func CreatePasswordReset(ctx context.Context, db *sql.DB, userID uuid.UUID) (string, time.Time, error) {
+	raw := make([]byte, 32)
+	if _, err := rand.Read(raw); err != nil {
+		return "", time.Time{}, err
+	}
+	token := hex.EncodeToString(raw)
+	expiresAt := time.Now().Add(PasswordResetTTL)
+
+	// Expired links are only ever cleaned up here
+	if _, err := db.ExecContext(ctx, `DELETE FROM password_reset WHERE expires_at < NOW()`); err != nil {
+		return "", time.Time{}, err
+	}
+	if _, err := db.ExecContext(ctx, `
+		INSERT INTO password_reset (token_hash, user_id, expires_at) VALUES ($1, $2, $3)
+	`, sha256Hex(token), userID, expiresAt); err != nil {
+		return "", time.Time{}, err
+	}
+	return token, expiresAt, nil
+}
+
+// GetPasswordResetUsername returns who a pending reset link is for
+func GetPasswordResetUsername(ctx context.Context, db *sql.DB, token string) (string, error) {
+	var username string
+	err := db.QueryRowContext(ctx, `
+		SELECT u.username FROM password_reset r
+		JOIN users u ON u.user_id = r.user_id
+		WHERE r.token_hash = $1 AND r.expires_at > NOW()
+	`, sha256Hex(token)).Scan(&username)
+	if err == sql.ErrNoRows {
+		return "", ErrPasswordResetNotFound
+	}
+	return username, err
+}
+
+// ResetPassword sets a new password with a reset token. The user's other
+// reset links and all their sessions stop working.
+func ResetPassword(ctx context.Context, db *sql.DB, token, password string) (uuid.UUID, error) {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return uuid.Nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var userID uuid.UUID
+	err = tx.QueryRowContext(ctx, `
+		DELETE FROM password_reset WHERE token_hash = $1 AND expires_at > NOW()
+		RETURNING user_id
+	`, sha256Hex(token)).Scan(&userID)
+	if err == sql.ErrNoRows {
+		return uuid.Nil, ErrPasswordResetNotFound
+	}
+	if err != nil {
+		return uuid.Nil, err
+	}
+
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET password_hash = hash_password($2), updated_at = NOW() WHERE user_id = $1
+	`, userID, password); err != nil {
+		return uuid.Nil, err
+	}
+	if _, err := tx.ExecContext(ctx, `DELETE FROM password_reset WHERE user_id = $1`, userID); err != nil {
+		return uuid.Nil, err
+	}
+	if _, err := tx.ExecContext(ctx, `DELETE FROM user_sessions WHERE user_id = $1`, userID); err != nil {
+		return uuid.Nil, err
+	}
+	return userID, tx.Commit()
+}
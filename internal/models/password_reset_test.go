@@ -0,0 +1,64 @@
+package models
+
+import (
+	"context"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestResetPassword(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("DELETE FROM password_reset WHERE token_hash").WithArgs(sha256Hex("token")).
+		WillReturnRows(sqlmock.NewRows([]string{"user_id"}).AddRow(userID.String()))
+	mock.ExpectExec("UPDATE users SET password_hash").WithArgs(userID, "newpassword").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("DELETE FROM password_reset WHERE user_id").WithArgs(userID).
+		WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectExec("DELETE FROM user_sessions").WithArgs(userID).
+		WillReturnResult(sqlmock.NewResult(0, 3))
+	mock.ExpectCommit()
+
+	got, err := ResetPassword(context.Background(), db, "token", "newpassword")
+	require.NoError(t, err)
+	assert.Equal(t, userID, got)
+
+	// Used and expired links change nothing
+	mock.ExpectBegin()
+	mock.ExpectQuery("DELETE FROM password_reset WHERE token_hash").WithArgs(sha256Hex("token")).
+		WillReturnRows(sqlmock.NewRows([]string{"user_id"}))
+	mock.ExpectRollback()
+
+	_, err = ResetPassword(context.Background(), db, "token", "newpassword")
+	assert.ErrorIs(t, err, ErrPasswordResetNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSetUserEmailTaken(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectQuery("SELECT EXISTS").WithArgs(userID, "bob@example.com").
+		WillReturnRows(sqlmock.NewRows([]string{"exists"}).AddRow(true))
+
+	err = SetUserEmail(context.Background(), db, userID, "bob@example.com")
+	assert.ErrorIs(t, err, ErrEmailTaken)
+
+	// Removing the address skips the check
+	mock.ExpectExec("UPDATE users SET email").WithArgs(userID, "").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	assert.NoError(t, SetUserEmail(context.Background(), db, userID, ""))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
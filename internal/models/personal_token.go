@@ -0,0 +1,154 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// PersonalTokenPrefix starts every personal access token, so they can be
+// told apart from session tokens and website API keys
+const PersonalTokenPrefix = "kaunta_pat_"
+
+// Personal token scopes
+const (
+	PersonalTokenRead  = "read"
+	PersonalTokenWrite = "write"
+)
+
+// PersonalToken is a user's token for the management API. The secret itself
+// is never stored; TokenPrefix is enough to recognise it.
+type PersonalToken struct {
+	TokenID     uuid.UUID  `json:"token_id"`
+	Name        string     `json:"name"`
+	TokenPrefix string     `json:"token_prefix"`
+	Scope       string     `json:"scope"`
+	CreatedAt   time.Time  `json:"created_at"`
+	LastUsedAt  *time.Time `json:"last_used_at,omitempty"`
+	ExpiresAt   *time.Time `json:"expires_at,omitempty"`
+	RevokedAt   *time.Time `json:"revoked_at,omitempty"`
+}
+
+// PersonalTokenOwner is who a valid personal token acts as
+type PersonalTokenOwner struct {
+	UserID   uuid.UUID
+	Username string
+	Scope    string
+	// TwoFactorSetupRequired mirrors the session check: the user still has
+	// to enroll in 2FA
+	TwoFactorSetupRequired bool
+}
+
+// ParsePersonalTokenScope validates a scope name
+func ParsePersonalTokenScope(scope string) (string, error) {
+	switch scope {
+	case PersonalTokenRead, PersonalTokenWrite:
+		return scope, nil
+	}
+	return "", fmt.Errorf("invalid scope %q (use read or write)", scope)
+}
+
+// CreatePersonalToken issues a token for the user and returns it with the
+// secret, which is shown only this once
+func CreatePersonalToken(ctx context.Context, db *sql.DB, userID uuid.UUID, name, scope string, expiresAt *time.Time) (*PersonalToken, string, error) {
+	name = strings.TrimSpace(name)
+	if name == "" {
+		return nil, "", fmt.Errorf("name is required")
+	}
+	scope, err := ParsePersonalTokenScope(scope)
+	if err != nil {
+		return nil, "", err
+	}
+
+	raw := make([]byte, 32)
+	if _, err := rand.Read(raw); err != nil {
+		return nil, "", err
+	}
+	secret := PersonalTokenPrefix + hex.EncodeToString(raw)
+
+	token := &PersonalToken{
+		TokenID:     uuid.New(),
+		Name:        name,
+		TokenPrefix: secret[:len(PersonalTokenPrefix)+8],
+		Scope:       scope,
+		ExpiresAt:   expiresAt,
+	}
+	err = db.QueryRowContext(ctx, `
+		INSERT INTO personal_token (token_id, user_id, token_hash, token_prefix, name, scope, expires_at)
+		VALUES ($1, $2, $3, $4, $5, $6, $7)
+		RETURNING created_at
+	`, token.TokenID, userID, sha256Hex(secret), token.TokenPrefix, token.Name, token.Scope, expiresAt).Scan(&token.CreatedAt)
+	if err != nil {
+		return nil, "", err
+	}
+	return token, secret, nil
+}
+
+// ListPersonalTokens returns the user's tokens, newest first, including
+// revoked and expired ones
+func ListPersonalTokens(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]PersonalToken, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT token_id, name, token_prefix, scope, created_at, last_used_at, expires_at, revoked_at
+		FROM personal_token
+		WHERE user_id = $1
+		ORDER BY created_at DESC
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	tokens := []PersonalToken{}
+	for rows.Next() {
+		var t PersonalToken
+		if err := rows.Scan(&t.TokenID, &t.Name, &t.TokenPrefix, &t.Scope, &t.CreatedAt, &t.LastUsedAt, &t.ExpiresAt, &t.RevokedAt); err != nil {
+			return nil, err
+		}
+		tokens = append(tokens, t)
+	}
+	return tokens, rows.Err()
+}
+
+// RevokePersonalToken stops one of the user's tokens from working. It
+// returns sql.ErrNoRows when the user has no such active token.
+func RevokePersonalToken(ctx context.Context, db *sql.DB, userID, tokenID uuid.UUID) error {
+	result, err := db.ExecContext(ctx, `
+		UPDATE personal_token SET revoked_at = NOW()
+		WHERE user_id = $1 AND token_id = $2 AND revoked_at IS NULL
+	`, userID, tokenID)
+	if err != nil {
+		return err
+	}
+	if n, _ := result.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// ValidatePersonalToken looks up an active token and records that it was
+// used. requireTwoFactor is the global 2FA policy. It returns sql.ErrNoRows
+// for unknown, revoked and expired tokens and those of disabled accounts.
+func ValidatePersonalToken(ctx context.Context, db *sql.DB, secret string, requireTwoFactor bool) (*PersonalTokenOwner, error) {
+	var owner PersonalTokenOwner
+	err := db.QueryRowContext(ctx, `
+		UPDATE personal_token t SET last_used_at = NOW()
+		FROM users u
+		WHERE t.token_hash = $1
+		  AND u.user_id = t.user_id
+		  AND u.disabled_at IS NULL
+		  AND t.revoked_at IS NULL
+		  AND (t.expires_at IS NULL OR t.expires_at > NOW())
+		RETURNING t.user_id, u.username, t.scope,
+			u.totp_enabled_at IS NULL AND (u.require_2fa OR $2)
+	`, sha256Hex(secret), requireTwoFactor).Scan(&owner.UserID, &owner.Username, &owner.Scope, &owner.TwoFactorSetupRequired)
+	if err != nil {
+		return nil, err
+	}
+	return &owner, nil
+}
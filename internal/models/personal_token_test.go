@@ -0,0 +1,82 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestCreatePersonalToken(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectQuery("INSERT INTO personal_token").
+		WithArgs(sqlmock.AnyArg(), userID, sqlmock.AnyArg(), sqlmock.AnyArg(), "Deploy", "read", nil).
+		WillReturnRows(sqlmock.NewRows([]string{"created_at"}).AddRow(time.Now()))
+
+	token, secret, err := CreatePersonalToken(context.Background(), db, userID, " Deploy ", "read", nil)
+	require.NoError(t, err)
+	assert.True(t, strings.HasPrefix(secret, PersonalTokenPrefix))
+	assert.Len(t, secret, len(PersonalTokenPrefix)+64)
+	assert.True(t, strings.HasPrefix(secret, token.TokenPrefix))
+	assert.Equal(t, "Deploy", token.Name)
+	assert.NoError(t, mock.ExpectationsWereMet())
+
+	_, _, err = CreatePersonalToken(context.Background(), db, userID, "", "read", nil)
+	assert.Error(t, err)
+	_, _, err = CreatePersonalToken(context.Background(), db, userID, "Deploy", "admin", nil)
+	assert.Error(t, err)
+}
+
+func TestValidatePersonalToken(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	secret := PersonalTokenPrefix + "abc"
+	mock.ExpectQuery("UPDATE personal_token t SET last_used_at").
+		WithArgs(sha256Hex(secret), false).
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "username", "scope", "setup"}).
+			AddRow(userID.String(), "alice", "write", false))
+
+	owner, err := ValidatePersonalToken(context.Background(), db, secret, false)
+	require.NoError(t, err)
+	assert.Equal(t, userID, owner.UserID)
+	assert.Equal(t, "alice", owner.Username)
+	assert.Equal(t, PersonalTokenWrite, owner.Scope)
+
+	// Revoked, expired and unknown tokens match no row
+	mock.ExpectQuery("UPDATE personal_token t SET last_used_at").
+		WillReturnError(sql.ErrNoRows)
+	_, err = ValidatePersonalToken(context.Background(), db, secret, false)
+	assert.ErrorIs(t, err, sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRevokePersonalToken(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID, tokenID := uuid.New(), uuid.New()
+	mock.ExpectExec("UPDATE personal_token SET revoked_at").WithArgs(userID, tokenID).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	assert.NoError(t, RevokePersonalToken(context.Background(), db, userID, tokenID))
+
+	mock.ExpectExec("UPDATE personal_token SET revoked_at").WithArgs(userID, tokenID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	assert.ErrorIs(t, RevokePersonalToken(context.Background(), db, userID, tokenID), sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
@@ -0,0 +1,52 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+
+	"github.com/google/uuid"
+)
+
+// FindOrCreateProxyUser returns the account a trusted reverse proxy signed
+// in. With create, unknown usernames get an account with an unusable random
+// password and the given name and email (unless another account has that
+// email); created reports whether that happened. Without create it returns
+// sql.ErrNoRows for unknown usernames. Disabled accounts get ErrUserDisabled.
+func FindOrCreateProxyUser(ctx context.Context, db *sql.DB, username, email, name string, create bool) (userID uuid.UUID, created bool, err error) {
+	var disabled bool
+	err = db.QueryRowContext(ctx, `SELECT user_id, disabled_at IS NOT NULL FROM users WHERE username = $1`, username).Scan(&userID, &disabled)
+	if err == nil && disabled {
+		return uuid.Nil, false, ErrUserDisabled
+	}
+	if !errors.Is(err, sql.ErrNoRows) || !create {
+		return userID, false, err
+	}
+
+	if email != "" {
+		// Ignore addresses that don't parse rather than refusing the sign-in
+		email, _ = NormalizeEmail(email)
+	}
+	raw := make([]byte, 32)
+	if _, err := rand.Read(raw); err != nil {
+		return uuid.Nil, false, err
+	}
+	err = db.QueryRowContext(ctx, `
+		INSERT INTO users (user_id, username, password_hash, name, email)
+		VALUES ($1, $2, hash_password($3), NULLIF($4, ''),
+			CASE WHEN $5 <> '' AND NOT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($5)) THEN $5 END)
+		ON CONFLICT (username) DO NOTHING
+		RETURNING user_id
+	`, uuid.New(), username, hex.EncodeToString(raw), name, email).Scan(&userID)
+	if errors.Is(err, sql.ErrNoRows) {
+		// Another request created it first
+		err = db.QueryRowContext(ctx, `SELECT user_id FROM users WHERE username = $1`, username).Scan(&userID)
+		return userID, false, err
+	}
+	if err != nil {
+		return uuid.Nil, false, err
+	}
+	return userID, true, nil
+}
@@ -0,0 +1,49 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestFindOrCreateProxyUser(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	existing := uuid.New()
+	mock.ExpectQuery("SELECT user_id, disabled_at IS NOT NULL FROM users").WithArgs("alice").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "disabled"}).AddRow(existing.String(), false))
+	userID, created, err := FindOrCreateProxyUser(context.Background(), db, "alice", "", "", true)
+	require.NoError(t, err)
+	assert.Equal(t, existing, userID)
+	assert.False(t, created)
+
+	// Disabled accounts can't sign in through the proxy either
+	mock.ExpectQuery("SELECT user_id, disabled_at IS NOT NULL FROM users").WithArgs("carol").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "disabled"}).AddRow(uuid.NewString(), true))
+	_, _, err = FindOrCreateProxyUser(context.Background(), db, "carol", "", "", true)
+	assert.ErrorIs(t, err, ErrUserDisabled)
+
+	// Unknown users are only created when allowed
+	mock.ExpectQuery("SELECT user_id, disabled_at IS NOT NULL FROM users").WithArgs("bob").WillReturnError(sql.ErrNoRows)
+	_, _, err = FindOrCreateProxyUser(context.Background(), db, "bob", "", "", false)
+	assert.ErrorIs(t, err, sql.ErrNoRows)
+
+	newID := uuid.New()
+	mock.ExpectQuery("SELECT user_id, disabled_at IS NOT NULL FROM users").WithArgs("bob").WillReturnError(sql.ErrNoRows)
+	mock.ExpectQuery("INSERT INTO users").
+		WithArgs(sqlmock.AnyArg(), "bob", sqlmock.AnyArg(), "Bob", "bob@example.com").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id"}).AddRow(newID.String()))
+	userID, created, err = FindOrCreateProxyUser(context.Background(), db, "bob", "Bob <bob@example.com>", "Bob", true)
+	require.NoError(t, err)
+	assert.Equal(t, newID, userID)
+	assert.True(t, created)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
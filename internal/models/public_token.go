@@ -0,0 +1,164 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"slices"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+)
+
+const (
+	publicTokenPrefix = "kaunta_pub_"
+	publicTokenBytes  = 16
+)
+
+// PublicTokenMetrics are the metrics a public token can expose
+var PublicTokenMetrics = []string{"online", "pageviews", "visitors", "pageviews_today", "visitors_today"}
+
+// ErrPublicTokenNotFound is returned when a public token does not exist
+var ErrPublicTokenNotFound = errors.New("public token not found")
+
+// PublicToken grants unauthenticated read access to selected metrics of a website
+type PublicToken struct {
+	Token     string     `json:"token"`
+	WebsiteID uuid.UUID  `json:"website_id"`
+	CreatedBy *uuid.UUID `json:"created_by,omitempty"`
+	Name      *string    `json:"name,omitempty"`
+	Metrics   []string   `json:"metrics"`
+	CreatedAt time.Time  `json:"created_at"`
+}
+
+// Exposes reports whether the token grants access to metric
+func (t *PublicToken) Exposes(metric string) bool {
+	return slices.Contains(t.Metrics, metric)
+}
+
+// ValidatePublicTokenMetrics checks metrics is a non-empty subset of
+// PublicTokenMetrics and returns it deduplicated in canonical order
+func ValidatePublicTokenMetrics(metrics []string) ([]string, error) {
+	if len(metrics) == 0 {
+		return nil, fmt.Errorf("at least one metric is required (valid: %s)", strings.Join(PublicTokenMetrics, ", "))
+	}
+	for _, m := range metrics {
+		if !slices.Contains(PublicTokenMetrics, m) {
+			return nil, fmt.Errorf("invalid metric: %s (valid: %s)", m, strings.Join(PublicTokenMetrics, ", "))
+		}
+	}
+	var canonical []string
+	for _, m := range PublicTokenMetrics {
+		if slices.Contains(metrics, m) {
+			canonical = append(canonical, m)
+		}
+	}
+	return canonical, nil
+}
+
+// CreatePublicToken mints a token exposing metrics of a website
+func CreatePublicToken(ctx context.Context, db *sql.DB, websiteID uuid.UUID, createdBy *uuid.UUID, name *string, metrics []string) (*PublicToken, error) {
+	metrics, err := ValidatePublicTokenMetrics(metrics)
+	if err != nil {
+		return nil, err
+	}
+
+	raw := make([]byte, publicTokenBytes)
+	if _, err := rand.Read(raw); err != nil {
+		return nil, err
+	}
+
+	t := PublicToken{
+		Token:     publicTokenPrefix + hex.EncodeToString(raw),
+		WebsiteID: websiteID,
+		CreatedBy: createdBy,
+		Name:      name,
+		Metrics:   metrics,
+	}
+	err = db.QueryRowContext(ctx, `
+		INSERT INTO public_token (token, website_id, created_by, name, metrics)
+		VALUES ($1, $2, $3, $4, $5)
+		RETURNING created_at
+	`, t.Token, websiteID, createdBy, name, pq.Array(metrics)).Scan(&t.CreatedAt)
+	if err != nil {
+		return nil, err
+	}
+	return &t, nil
+}
+
+// ListPublicTokens returns a website's public tokens, newest first
+func ListPublicTokens(ctx context.Context, db *sql.DB, websiteID uuid.UUID) ([]PublicToken, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT token, website_id, created_by, name, metrics, created_at
+		FROM public_token
+		WHERE website_id = $1
+		ORDER BY created_at DESC
+	`, websiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	tokens := make([]PublicToken, 0)
+	for rows.Next() {
+		t, err := scanPublicToken(rows)
+		if err != nil {
+			return nil, err
+		}
+		tokens = append(tokens, *t)
+	}
+	return tokens, rows.Err()
+}
+
+// GetPublicToken resolves a token of a website that has not been deleted
+func GetPublicToken(ctx context.Context, db *sql.DB, token string) (*PublicToken, error) {
+	if !strings.HasPrefix(token, publicTokenPrefix) {
+		return nil, ErrPublicTokenNotFound
+	}
+
+	t, err := scanPublicToken(db.QueryRowContext(ctx, `
+		SELECT t.token, t.website_id, t.created_by, t.name, t.metrics, t.created_at
+		FROM public_token t
+		JOIN website w ON w.website_id = t.website_id
+		WHERE t.token = $1 AND w.deleted_at IS NULL
+	`, token))
+	if err == sql.ErrNoRows {
+		return nil, ErrPublicTokenNotFound
+	}
+	return t, err
+}
+
+// DeletePublicToken revokes one of a website's public tokens
+func DeletePublicToken(ctx context.Context, db *sql.DB, websiteID uuid.UUID, token string) error {
+	res, err := db.ExecContext(ctx,
+		`DELETE FROM public_token WHERE website_id = $1 AND token = $2`,
+		websiteID, token)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrPublicTokenNotFound
+	}
+	return nil
+}
+
+func scanPublicToken(row interface{ Scan(...any) error }) (*PublicToken, error) {
+	var t PublicToken
+	var createdBy uuid.NullUUID
+	var name sql.NullString
+	if err := row.Scan(&t.Token, &t.WebsiteID, &createdBy, &name, pq.Array(&t.Metrics), &t.CreatedAt); err != nil {
+		return nil, err
+	}
+	if createdBy.Valid {
+		t.CreatedBy = &createdBy.UUID
+	}
+	if name.Valid {
+		t.Name = &name.String
+	}
+	return &t, nil
+}
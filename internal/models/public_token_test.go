@@ -0,0 +1,74 @@
+package models
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestValidatePublicTokenMetrics(t *testing.T) {
+	metrics, err := ValidatePublicTokenMetrics([]string{"visitors_today", "online", "online"})
+	require.NoError(t, err)
+	assert.Equal(t, []string{"online", "visitors_today"}, metrics)
+
+	_, err = ValidatePublicTokenMetrics(nil)
+	assert.ErrorContains(t, err, "at least one metric")
+
+	_, err = ValidatePublicTokenMetrics([]string{"online", "bounce_rate"})
+	assert.ErrorContains(t, err, "invalid metric: bounce_rate")
+}
+
+func TestCreatePublicToken(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	mock.ExpectQuery("INSERT INTO public_token").
+		WithArgs(sqlmock.AnyArg(), websiteID, nil, nil, sqlmock.AnyArg()).
+		WillReturnRows(sqlmock.NewRows([]string{"created_at"}).AddRow(time.Now()))
+
+	token, err := CreatePublicToken(context.Background(), db, websiteID, nil, nil, []string{"pageviews", "online"})
+	require.NoError(t, err)
+	assert.Regexp(t, `^kaunta_pub_[0-9a-f]{32}$`, token.Token)
+	assert.Equal(t, []string{"online", "pageviews"}, token.Metrics)
+	assert.True(t, token.Exposes("online"))
+	assert.False(t, token.Exposes("visitors"))
+	require.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestGetPublicToken(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	// Tokens without the prefix never reach the database
+	_, err = GetPublicToken(context.Background(), db, "not-a-token")
+	assert.ErrorIs(t, err, ErrPublicTokenNotFound)
+
+	websiteID := uuid.New()
+	columns := []string{"token", "website_id", "created_by", "name", "metrics", "created_at"}
+	mock.ExpectQuery("FROM public_token").
+		WithArgs("kaunta_pub_abc").
+		WillReturnRows(sqlmock.NewRows(columns).
+			AddRow("kaunta_pub_abc", websiteID.String(), nil, "Footer", "{pageviews,online}", time.Now()))
+	mock.ExpectQuery("FROM public_token").
+		WithArgs("kaunta_pub_gone").
+		WillReturnRows(sqlmock.NewRows(columns))
+
+	token, err := GetPublicToken(context.Background(), db, "kaunta_pub_abc")
+	require.NoError(t, err)
+	assert.Equal(t, websiteID, token.WebsiteID)
+	assert.Equal(t, "Footer", *token.Name)
+	assert.Nil(t, token.CreatedBy)
+	assert.Equal(t, []string{"pageviews", "online"}, token.Metrics)
+
+	_, err = GetPublicToken(context.Background(), db, "kaunta_pub_gone")
+	assert.ErrorIs(t, err, ErrPublicTokenNotFound)
+	require.NoError(t, mock.ExpectationsWereMet())
+}
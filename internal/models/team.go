@@ -0,0 +1,284 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+var (
+	// ErrTeamNotFound is returned when a team doesn't exist or the user
+	// isn't a member
+	ErrTeamNotFound = errors.New("team not found")
+	// ErrLastTeamOwner is returned when a change would leave a team
+	// without an owner
+	ErrLastTeamOwner = errors.New("a team needs at least one owner")
+	// ErrTeamExists is returned when creating a team with a taken name
+	ErrTeamExists = errors.New("a team with this name already exists")
+)
+
+// Team groups users sharing a set of websites
+type Team struct {
+	TeamID    uuid.UUID   `json:"team_id"`
+	Name      string      `json:"name"`
+	Role      WebsiteRole `json:"role"`    // The requesting user's role in the team
+	Current   bool        `json:"current"` // Selected in the user's dashboard
+	Websites  int         `json:"websites"`
+	CreatedAt time.Time   `json:"created_at"`
+}
+
+// TeamMember is a user in a team
+type TeamMember struct {
+	UserID    uuid.UUID   `json:"user_id"`
+	Username  string      `json:"username"`
+	Role      WebsiteRole `json:"role"`
+	CreatedAt time.Time   `json:"created_at"`
+}
+
+// CreateTeam creates a team with ownerID as its first owner
+func CreateTeam(ctx context.Context, db *sql.DB, name string, ownerID uuid.UUID) (*Team, error) {
+	name = strings.TrimSpace(name)
+	if name == "" {
+		return nil, errors.New("team name is required")
+	}
+
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	team := Team{TeamID: uuid.New(), Name: name, Role: RoleOwner}
+	err = tx.QueryRowContext(ctx, `
+		INSERT INTO team (team_id, name) VALUES ($1, $2)
+		ON CONFLICT (name) DO NOTHING
+		RETURNING created_at
+	`, team.TeamID, name).Scan(&team.CreatedAt)
+	if err == sql.ErrNoRows {
+		return nil, ErrTeamExists
+	}
+	if err != nil {
+		return nil, err
+	}
+	if _, err := tx.ExecContext(ctx, `
+		INSERT INTO team_member (team_id, user_id, role) VALUES ($1, $2, 'owner')
+	`, team.TeamID, ownerID); err != nil {
+		return nil, err
+	}
+	return &team, tx.Commit()
+}
+
+// ListUserTeams returns the teams a user belongs to, ordered by name
+func ListUserTeams(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]Team, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT t.team_id, t.name, m.role, COALESCE(u.current_team_id = t.team_id, false),
+		       (SELECT COUNT(*) FROM website w WHERE w.team_id = t.team_id AND w.deleted_at IS NULL),
+		       t.created_at
+		FROM team t
+		JOIN team_member m ON m.team_id = t.team_id
+		JOIN users u ON u.user_id = m.user_id
+		WHERE m.user_id = $1
+		ORDER BY t.name
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	teams := []Team{}
+	for rows.Next() {
+		var t Team
+		var role string
+		if err := rows.Scan(&t.TeamID, &t.Name, &role, &t.Current, &t.Websites, &t.CreatedAt); err != nil {
+			return nil, err
+		}
+		t.Role = WebsiteRole(role)
+		teams = append(teams, t)
+	}
+	return teams, rows.Err()
+}
+
+// GetTeamRole returns the user's role in a team, or ErrTeamNotFound
+func GetTeamRole(ctx context.Context, db *sql.DB, teamID, userID uuid.UUID) (WebsiteRole, error) {
+	var role string
+	err := db.QueryRowContext(ctx, `
+		SELECT role FROM team_member WHERE team_id = $1 AND user_id = $2
+	`, teamID, userID).Scan(&role)
+	if err == sql.ErrNoRows {
+		return "", ErrTeamNotFound
+	}
+	if err != nil {
+		return "", err
+	}
+	return WebsiteRole(role), nil
+}
+
+// FindTeamByName looks a team up by its name, for the CLI
+func FindTeamByName(ctx context.Context, db *sql.DB, name string) (uuid.UUID, error) {
+	var teamID uuid.UUID
+	err := db.QueryRowContext(ctx, `SELECT team_id FROM team WHERE name = $1`, name).Scan(&teamID)
+	if err == sql.ErrNoRows {
+		return uuid.UUID{}, ErrTeamNotFound
+	}
+	return teamID, err
+}
+
+// DeleteTeam removes a team. Its websites stay with their owners.
+func DeleteTeam(ctx context.Context, db *sql.DB, teamID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `DELETE FROM team WHERE team_id = $1`, teamID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrTeamNotFound
+	}
+	return nil
+}
+
+// ListTeamMembers returns a team's members, owners first
+func ListTeamMembers(ctx context.Context, db *sql.DB, teamID uuid.UUID) ([]TeamMember, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT u.user_id, u.username, m.role, m.created_at
+		FROM team_member m
+		JOIN users u ON u.user_id = m.user_id
+		WHERE m.team_id = $1
+		ORDER BY website_role_rank(m.role) DESC, u.username
+	`, teamID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	members := []TeamMember{}
+	for rows.Next() {
+		var m TeamMember
+		var role string
+		if err := rows.Scan(&m.UserID, &m.Username, &role, &m.CreatedAt); err != nil {
+			return nil, err
+		}
+		m.Role = WebsiteRole(role)
+		members = append(members, m)
+	}
+	return members, rows.Err()
+}
+
+// SetTeamMember adds a user to a team or changes their role. A membership
+// set this way is no longer managed by group sync. It returns
+// sql.ErrNoRows for unknown usernames and ErrLastTeamOwner when demoting
+// the only owner.
+func SetTeamMember(ctx context.Context, db *sql.DB, teamID uuid.UUID, username string, role WebsiteRole) (*TeamMember, error) {
+	m := TeamMember{Username: username, Role: role}
+	if err := db.QueryRowContext(ctx, `SELECT user_id FROM users WHERE username = $1`, username).Scan(&m.UserID); err != nil {
+		return nil, err
+	}
+
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if err := tx.QueryRowContext(ctx, `
+		INSERT INTO team_member (team_id, user_id, role) VALUES ($1, $2, $3)
+		ON CONFLICT (team_id, user_id) DO UPDATE SET role = EXCLUDED.role, synced = FALSE
+		RETURNING created_at
+	`, teamID, m.UserID, string(role)).Scan(&m.CreatedAt); err != nil {
+		return nil, err
+	}
+	if err := requireTeamOwner(ctx, tx, teamID); err != nil {
+		return nil, err
+	}
+	return &m, tx.Commit()
+}
+
+// RemoveTeamMember takes a user out of a team. It returns sql.ErrNoRows
+// when they weren't a member and ErrLastTeamOwner for the only owner. Their
+// alert rules, webhooks and custom dashboards on the team's websites stop
+// unless they have access another way, as with RemoveWebsiteMember.
+func RemoveTeamMember(ctx context.Context, db *sql.DB, teamID, userID uuid.UUID) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	res, err := tx.ExecContext(ctx, `
+		DELETE FROM team_member WHERE team_id = $1 AND user_id = $2
+	`, teamID, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	if err := requireTeamOwner(ctx, tx, teamID); err != nil {
+		return err
+	}
+	// Don't leave them switched to a team they can no longer see
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET current_team_id = NULL WHERE user_id = $1 AND current_team_id = $2
+	`, userID, teamID); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+func requireTeamOwner(ctx context.Context, tx *sql.Tx, teamID uuid.UUID) error {
+	var owners int
+	if err := tx.QueryRowContext(ctx, `
+		SELECT COUNT(*) FROM team_member WHERE team_id = $1 AND role = 'owner'
+	`, teamID).Scan(&owners); err != nil {
+		return err
+	}
+	if owners == 0 {
+		return ErrLastTeamOwner
+	}
+	return nil
+}
+
+// SetCurrentTeam switches the team the user's dashboard is scoped to.
+// A nil team shows all teams.
+func SetCurrentTeam(ctx context.Context, db *sql.DB, userID uuid.UUID, teamID *uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `
+		UPDATE users SET current_team_id = $2, updated_at = NOW()
+		WHERE user_id = $1
+		  AND ($2::uuid IS NULL OR EXISTS (SELECT 1 FROM team_member WHERE team_id = $2 AND user_id = $1))
+	`, userID, teamID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrTeamNotFound
+	}
+	return nil
+}
+
+// SetWebsiteTeam moves a website into a team, or out of any team when
+// teamID is nil. Members of the previous team lose access, and their alert
+// rules, webhooks and custom dashboards on the website stop with it.
+func SetWebsiteTeam(ctx context.Context, db *sql.DB, websiteID uuid.UUID, teamID *uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `
+		UPDATE website SET team_id = $2, updated_at = NOW() WHERE website_id = $1 AND deleted_at IS NULL
+	`, websiteID, teamID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrNoWebsiteAccess
+	}
+	return nil
+}
+
+// MoveWebsiteToCurrentTeam puts a new website in the team its creator is
+// switched to, so it shows up in their scoped dashboard
+func MoveWebsiteToCurrentTeam(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) error {
+	_, err := db.ExecContext(ctx, `
+		UPDATE website SET team_id = (SELECT current_team_id FROM users WHERE user_id = $2)
+		WHERE website_id = $1
+	`, websiteID, userID)
+	return err
+}
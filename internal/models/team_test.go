@@ -0,0 +1,88 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestCreateTeam(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	ownerID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("INSERT INTO team").WithArgs(sqlmock.AnyArg(), "Marketing").
+		WillReturnRows(sqlmock.NewRows([]string{"created_at"}).AddRow(time.Now()))
+	mock.ExpectExec("INSERT INTO team_member").WithArgs(sqlmock.AnyArg(), ownerID).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+
+	team, err := CreateTeam(context.Background(), db, "  Marketing ", ownerID)
+	require.NoError(t, err)
+	assert.Equal(t, "Marketing", team.Name)
+	assert.Equal(t, RoleOwner, team.Role)
+
+	// Taken names come back as ErrTeamExists
+	mock.ExpectBegin()
+	mock.ExpectQuery("INSERT INTO team").WithArgs(sqlmock.AnyArg(), "Marketing").
+		WillReturnRows(sqlmock.NewRows([]string{"created_at"}))
+	mock.ExpectRollback()
+
+	_, err = CreateTeam(context.Background(), db, "Marketing", ownerID)
+	assert.ErrorIs(t, err, ErrTeamExists)
+
+	_, err = CreateTeam(context.Background(), db, " ", ownerID)
+	assert.Error(t, err)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRemoveTeamMemberKeepsAnOwner(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	teamID, userID := uuid.New(), uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectExec("DELETE FROM team_member").WithArgs(teamID, userID).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectQuery("SELECT COUNT").WithArgs(teamID).
+		WillReturnRows(sqlmock.NewRows([]string{"count"}).AddRow(0))
+	mock.ExpectRollback()
+
+	err = RemoveTeamMember(context.Background(), db, teamID, userID)
+	assert.ErrorIs(t, err, ErrLastTeamOwner)
+
+	mock.ExpectBegin()
+	mock.ExpectExec("DELETE FROM team_member").WithArgs(teamID, userID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectRollback()
+
+	err = RemoveTeamMember(context.Background(), db, teamID, userID)
+	assert.ErrorIs(t, err, sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSetCurrentTeamRequiresMembership(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID, teamID := uuid.New(), uuid.New()
+	mock.ExpectExec("UPDATE users SET current_team_id").WithArgs(userID, &teamID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+
+	err = SetCurrentTeam(context.Background(), db, userID, &teamID)
+	assert.ErrorIs(t, err, ErrTeamNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
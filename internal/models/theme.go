@@ -0,0 +1,42 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"slices"
+
+	"github.com/google/uuid"
+)
+
+// DefaultTheme is used when a user has not picked a theme
+const DefaultTheme = "default"
+
+// Themes lists the dashboard themes shipped in assets/themes.css
+var Themes = []string{DefaultTheme, "retro"}
+
+// IsValidTheme reports whether name is a known theme
+func IsValidTheme(name string) bool {
+	return slices.Contains(Themes, name)
+}
+
+// GetUserTheme returns the theme stored for a user, falling back to the default
+func GetUserTheme(ctx context.Context, db *sql.DB, userID uuid.UUID) (string, error) {
+	var theme string
+	err := db.QueryRowContext(ctx, `SELECT theme FROM users WHERE user_id = $1`, userID).Scan(&theme)
+	if err == sql.ErrNoRows || (err == nil && !IsValidTheme(theme)) {
+		return DefaultTheme, nil
+	}
+	if err != nil {
+		return DefaultTheme, err
+	}
+	return theme, nil
+}
+
+// SetUserTheme persists the theme for a user; the caller validates the name
+func SetUserTheme(ctx context.Context, db *sql.DB, userID uuid.UUID, theme string) error {
+	_, err := db.ExecContext(ctx,
+		`UPDATE users SET theme = $2, updated_at = NOW() WHERE user_id = $1`,
+		userID, theme,
+	)
+	return err
+}
@@ -0,0 +1,60 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestIsValidTheme(t *testing.T) {
+	assert.True(t, IsValidTheme("default"))
+	assert.True(t, IsValidTheme("retro"))
+	assert.False(t, IsValidTheme(""))
+	assert.False(t, IsValidTheme("neon"))
+}
+
+func TestGetUserTheme(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+
+	mock.ExpectQuery("SELECT theme FROM users").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"theme"}).AddRow("retro"))
+	theme, err := GetUserTheme(context.Background(), db, userID)
+	require.NoError(t, err)
+	assert.Equal(t, "retro", theme)
+
+	// Unknown stored values and missing users fall back to the default
+	mock.ExpectQuery("SELECT theme FROM users").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"theme"}).AddRow("removed-theme"))
+	theme, err = GetUserTheme(context.Background(), db, userID)
+	require.NoError(t, err)
+	assert.Equal(t, DefaultTheme, theme)
+
+	mock.ExpectQuery("SELECT theme FROM users").WithArgs(userID).WillReturnError(sql.ErrNoRows)
+	theme, err = GetUserTheme(context.Background(), db, userID)
+	require.NoError(t, err)
+	assert.Equal(t, DefaultTheme, theme)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSetUserTheme(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectExec("UPDATE users SET theme").WithArgs(userID, "retro").
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	require.NoError(t, SetUserTheme(context.Background(), db, userID, "retro"))
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
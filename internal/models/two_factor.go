@@ -0,0 +1,324 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"crypto/sha256"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/totp"
+)
+
+const (
+	// RecoveryCodeCount is how many recovery codes are issued at a time
+	RecoveryCodeCount = 10
+	// LoginChallengeTTL is how long a user has to enter their code after
+	// the password was accepted
+	LoginChallengeTTL = 5 * time.Minute
+	// maxLoginChallengeAttempts caps code guesses per login challenge
+	maxLoginChallengeAttempts = 5
+	// recoveryCodeBytes is hex-encoded to a 10 character code
+	recoveryCodeBytes = 5
+)
+
+var (
+	// ErrTwoFactorNotEnabled is returned when a user has no confirmed
+	// authenticator
+	ErrTwoFactorNotEnabled = errors.New("two-factor authentication is not enabled")
+	// ErrTwoFactorAlreadyEnabled is returned when setup is started or
+	// confirmed twice
+	ErrTwoFactorAlreadyEnabled = errors.New("two-factor authentication is already enabled")
+	// ErrTwoFactorSetupNotStarted is returned when a code is confirmed
+	// before a secret was issued
+	ErrTwoFactorSetupNotStarted = errors.New("two-factor setup has not been started")
+	// ErrInvalidTwoFactorCode covers wrong, expired, reused and spent codes
+	ErrInvalidTwoFactorCode = errors.New("invalid two-factor code")
+	// ErrLoginChallengeNotFound is returned for unknown, expired or
+	// exhausted login challenges
+	ErrLoginChallengeNotFound = errors.New("login challenge expired or not found")
+)
+
+// TwoFactorStatus describes a user's 2FA enrollment
+type TwoFactorStatus struct {
+	EnabledAt         *time.Time `json:"enabled_at,omitempty"`
+	Required          bool       `json:"required"` // Per-user flag; the global policy is separate
+	RecoveryCodesLeft int        `json:"recovery_codes_left"`
+}
+
+// Enabled reports whether the user has a confirmed authenticator
+func (s *TwoFactorStatus) Enabled() bool {
+	return s.EnabledAt != nil
+}
+
+// GetTwoFactorStatus returns the user's enrollment state
+func GetTwoFactorStatus(ctx context.Context, db *sql.DB, userID uuid.UUID) (*TwoFactorStatus, error) {
+	var status TwoFactorStatus
+	err := db.QueryRowContext(ctx, `
+		SELECT u.totp_enabled_at, u.require_2fa,
+			(SELECT COUNT(*) FROM user_recovery_code c WHERE c.user_id = u.user_id AND c.used_at IS NULL)
+		FROM users u
+		WHERE u.user_id = $1
+	`, userID).Scan(&status.EnabledAt, &status.Required, &status.RecoveryCodesLeft)
+	if err != nil {
+		return nil, err
+	}
+	return &status, nil
+}
+
+// BeginTwoFactorSetup issues a new secret for the user to add to their
+// authenticator app. It has no effect on login until EnableTwoFactor
+// confirms a code from it.
+func BeginTwoFactorSetup(ctx context.Context, db *sql.DB, userID uuid.UUID) (string, error) {
+	secret, err := totp.GenerateSecret()
+	if err != nil {
+		return "", err
+	}
+	result, err := db.ExecContext(ctx, `
+		UPDATE users SET totp_secret = $2
+		WHERE user_id = $1 AND totp_enabled_at IS NULL
+	`, userID, secret)
+	if err != nil {
+		return "", err
+	}
+	if rows, _ := result.RowsAffected(); rows == 0 {
+		return "", ErrTwoFactorAlreadyEnabled
+	}
+	return secret, nil
+}
+
+// EnableTwoFactor confirms setup with a code from the pending secret and
+// returns a fresh set of recovery codes, which are only shown once
+func EnableTwoFactor(ctx context.Context, db *sql.DB, userID uuid.UUID, code string, now time.Time) ([]string, error) {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var secret sql.NullString
+	var enabledAt *time.Time
+	err = tx.QueryRowContext(ctx, `
+		SELECT totp_secret, totp_enabled_at FROM users WHERE user_id = $1 FOR UPDATE
+	`, userID).Scan(&secret, &enabledAt)
+	if err != nil {
+		return nil, err
+	}
+	if enabledAt != nil {
+		return nil, ErrTwoFactorAlreadyEnabled
+	}
+	if !secret.Valid {
+		return nil, ErrTwoFactorSetupNotStarted
+	}
+	step, ok := totp.Validate(secret.String, code, now)
+	if !ok {
+		return nil, ErrInvalidTwoFactorCode
+	}
+
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET totp_enabled_at = $2, totp_last_step = $3 WHERE user_id = $1
+	`, userID, now, step); err != nil {
+		return nil, err
+	}
+	codes, err := replaceRecoveryCodes(ctx, tx, userID)
+	if err != nil {
+		return nil, err
+	}
+	if err := tx.Commit(); err != nil {
+		return nil, err
+	}
+	return codes, nil
+}
+
+// DisableTwoFactor removes the user's authenticator and recovery codes
+func DisableTwoFactor(ctx context.Context, db *sql.DB, userID uuid.UUID) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE users SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL
+		WHERE user_id = $1
+	`, userID); err != nil {
+		return err
+	}
+	if _, err := tx.ExecContext(ctx, `DELETE FROM user_recovery_code WHERE user_id = $1`, userID); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+// VerifyTwoFactor accepts either a current authenticator code or an unused
+// recovery code, which is then spent. Each authenticator code works once.
+func VerifyTwoFactor(ctx context.Context, db *sql.DB, userID uuid.UUID, code string, now time.Time) error {
+	var secret string
+	var lastStep sql.NullInt64
+	err := db.QueryRowContext(ctx, `
+		SELECT totp_secret, totp_last_step FROM users
+		WHERE user_id = $1 AND totp_enabled_at IS NOT NULL AND totp_secret IS NOT NULL
+	`, userID).Scan(&secret, &lastStep)
+	if err == sql.ErrNoRows {
+		return ErrTwoFactorNotEnabled
+	}
+	if err != nil {
+		return err
+	}
+
+	if step, ok := totp.Validate(secret, code, now); ok {
+		if lastStep.Valid && step <= lastStep.Int64 {
+			return ErrInvalidTwoFactorCode
+		}
+		result, err := db.ExecContext(ctx, `
+			UPDATE users SET totp_last_step = $2
+			WHERE user_id = $1 AND (totp_last_step IS NULL OR totp_last_step < $2)
+		`, userID, step)
+		if err != nil {
+			return err
+		}
+		if rows, _ := result.RowsAffected(); rows == 0 {
+			return ErrInvalidTwoFactorCode // used concurrently
+		}
+		return nil
+	}
+
+	normalized := normalizeRecoveryCode(code)
+	if len(normalized) != recoveryCodeBytes*2 {
+		return ErrInvalidTwoFactorCode
+	}
+	result, err := db.ExecContext(ctx, `
+		UPDATE user_recovery_code SET used_at = NOW()
+		WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+	`, userID, sha256Hex(normalized))
+	if err != nil {
+		return err
+	}
+	if rows, _ := result.RowsAffected(); rows == 0 {
+		return ErrInvalidTwoFactorCode
+	}
+	return nil
+}
+
+// RegenerateRecoveryCodes replaces all of the user's recovery codes
+func RegenerateRecoveryCodes(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]string, error) {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var enabled bool
+	if err := tx.QueryRowContext(ctx, `
+		SELECT totp_enabled_at IS NOT NULL FROM users WHERE user_id = $1 FOR UPDATE
+	`, userID).Scan(&enabled); err != nil {
+		return nil, err
+	}
+	if !enabled {
+		return nil, ErrTwoFactorNotEnabled
+	}
+	codes, err := replaceRecoveryCodes(ctx, tx, userID)
+	if err != nil {
+		return nil, err
+	}
+	if err := tx.Commit(); err != nil {
+		return nil, err
+	}
+	return codes, nil
+}
+
+// SetUserRequireTwoFactor sets the per-user enrollment requirement. It
+// returns sql.ErrNoRows for unknown usernames.
+func SetUserRequireTwoFactor(ctx context.Context, db *sql.DB, username string, required bool) error {
+	result, err := db.ExecContext(ctx, `UPDATE users SET require_2fa = $2 WHERE username = $1`, username, required)
+	if err != nil {
+		return err
+	}
+	if rows, _ := result.RowsAffected(); rows == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+func replaceRecoveryCodes(ctx context.Context, tx *sql.Tx, userID uuid.UUID) ([]string, error) {
+	if _, err := tx.ExecContext(ctx, `DELETE FROM user_recovery_code WHERE user_id = $1`, userID); err != nil {
+		return nil, err
+	}
+
+	codes := make([]string, RecoveryCodeCount)
+	for i := range codes {
+		raw := make([]byte, recoveryCodeBytes)
+		if _, err := rand.Read(raw); err != nil {
+			return nil, err
+		}
+		encoded := hex.EncodeToString(raw)
+		codes[i] = encoded[:recoveryCodeBytes] + "-" + encoded[recoveryCodeBytes:]
+
+		if _, err := tx.ExecContext(ctx, `
+			INSERT INTO user_recovery_code (user_id, code_hash) VALUES ($1, $2)
+		`, userID, sha256Hex(encoded)); err != nil {
+			return nil, fmt.Errorf("store recovery code: %w", err)
+		}
+	}
+	return codes, nil
+}
+
+// normalizeRecoveryCode accepts codes with or without the dash, in any case
+func normalizeRecoveryCode(code string) string {
+	return strings.ToLower(strings.ReplaceAll(strings.TrimSpace(code), "-", ""))
+}
+
+// CreateLoginChallenge records a password-verified login that still needs
+// a second factor and returns the token the client sends back with the code
+func CreateLoginChallenge(ctx context.Context, db *sql.DB, userID uuid.UUID) (string, error) {
+	raw := make([]byte, 32)
+	if _, err := rand.Read(raw); err != nil {
+		return "", err
+	}
+	token := hex.EncodeToString(raw)
+
+	// Expired challenges are only ever cleaned up here
+	if _, err := db.ExecContext(ctx, `DELETE FROM login_challenge WHERE expires_at < NOW()`); err != nil {
+		return "", err
+	}
+	if _, err := db.ExecContext(ctx, `
+		INSERT INTO login_challenge (challenge_hash, user_id, expires_at) VALUES ($1, $2, $3)
+	`, sha256Hex(token), userID, time.Now().Add(LoginChallengeTTL)); err != nil {
+		return "", err
+	}
+	return token, nil
+}
+
+// ClaimLoginChallenge returns the user a challenge belongs to and counts
+// the attempt; a challenge stops working after maxLoginChallengeAttempts
+func ClaimLoginChallenge(ctx context.Context, db *sql.DB, token string) (uuid.UUID, error) {
+	var userID uuid.UUID
+	err := db.QueryRowContext(ctx, `
+		UPDATE login_challenge SET attempts = attempts + 1
+		WHERE challenge_hash = $1 AND expires_at > NOW() AND attempts < $2
+		RETURNING user_id
+	`, sha256Hex(token), maxLoginChallengeAttempts).Scan(&userID)
+	if err == sql.ErrNoRows {
+		return uuid.Nil, ErrLoginChallengeNotFound
+	}
+	return userID, err
+}
+
+// DeleteLoginChallenge removes a challenge once it has been exchanged for a
+// session
+func DeleteLoginChallenge(ctx context.Context, db *sql.DB, token string) error {
+	_, err := db.ExecContext(ctx, `DELETE FROM login_challenge WHERE challenge_hash = $1`, sha256Hex(token))
+	return err
+}
+
+// sha256Hex is how recovery codes and challenge tokens are stored
+func sha256Hex(value string) string {
+	sum := sha256.Sum256([]byte(value))
+	return hex.EncodeToString(sum[:])
+}
@@ -0,0 +1,141 @@
+package models
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/totp"
+)
+
+const testTOTPSecret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+
+func TestVerifyTwoFactorCode(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	now := time.Unix(1111111111, 0)
+	code, err := totp.Code(testTOTPSecret, now)
+	require.NoError(t, err)
+	step := totp.Step(now)
+
+	mock.ExpectQuery("SELECT totp_secret, totp_last_step FROM users").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"totp_secret", "totp_last_step"}).AddRow(testTOTPSecret, nil))
+	mock.ExpectExec("UPDATE users SET totp_last_step").WithArgs(userID, step).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	assert.NoError(t, VerifyTwoFactor(context.Background(), db, userID, code, now))
+
+	// The same code can't be used twice
+	mock.ExpectQuery("SELECT totp_secret, totp_last_step FROM users").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"totp_secret", "totp_last_step"}).AddRow(testTOTPSecret, step))
+	assert.ErrorIs(t, VerifyTwoFactor(context.Background(), db, userID, code, now), ErrInvalidTwoFactorCode)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestVerifyTwoFactorRecoveryCode(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	rows := func() *sqlmock.Rows {
+		return sqlmock.NewRows([]string{"totp_secret", "totp_last_step"}).AddRow(testTOTPSecret, nil)
+	}
+
+	mock.ExpectQuery("SELECT totp_secret").WithArgs(userID).WillReturnRows(rows())
+	mock.ExpectExec("UPDATE user_recovery_code SET used_at").WithArgs(userID, sha256Hex("abcde01234")).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	assert.NoError(t, VerifyTwoFactor(context.Background(), db, userID, " ABCDE-01234 ", time.Now()))
+
+	// Spent or unknown codes update nothing
+	mock.ExpectQuery("SELECT totp_secret").WithArgs(userID).WillReturnRows(rows())
+	mock.ExpectExec("UPDATE user_recovery_code SET used_at").WillReturnResult(sqlmock.NewResult(0, 0))
+	assert.ErrorIs(t, VerifyTwoFactor(context.Background(), db, userID, "abcde-01234", time.Now()), ErrInvalidTwoFactorCode)
+
+	// Anything that is neither shape never reaches the recovery table
+	mock.ExpectQuery("SELECT totp_secret").WithArgs(userID).WillReturnRows(rows())
+	assert.ErrorIs(t, VerifyTwoFactor(context.Background(), db, userID, "nope", time.Now()), ErrInvalidTwoFactorCode)
+
+	mock.ExpectQuery("SELECT totp_secret").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"totp_secret", "totp_last_step"}))
+	assert.ErrorIs(t, VerifyTwoFactor(context.Background(), db, userID, "123456", time.Now()), ErrTwoFactorNotEnabled)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEnableTwoFactor(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	now := time.Unix(1234567890, 0)
+	code, err := totp.Code(testTOTPSecret, now)
+	require.NoError(t, err)
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT totp_secret, totp_enabled_at FROM users").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"totp_secret", "totp_enabled_at"}).AddRow(testTOTPSecret, nil))
+	mock.ExpectExec("UPDATE users SET totp_enabled_at").WithArgs(userID, now, totp.Step(now)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("DELETE FROM user_recovery_code").WithArgs(userID).WillReturnResult(sqlmock.NewResult(0, 0))
+	for range RecoveryCodeCount {
+		mock.ExpectExec("INSERT INTO user_recovery_code").WithArgs(userID, sqlmock.AnyArg()).
+			WillReturnResult(sqlmock.NewResult(0, 1))
+	}
+	mock.ExpectCommit()
+
+	codes, err := EnableTwoFactor(context.Background(), db, userID, code, now)
+	require.NoError(t, err)
+	require.Len(t, codes, RecoveryCodeCount)
+	for _, c := range codes {
+		assert.Regexp(t, `^[0-9a-f]{5}-[0-9a-f]{5}$`, c)
+	}
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEnableTwoFactorRejectsWrongCode(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT totp_secret, totp_enabled_at FROM users").WithArgs(userID).
+		WillReturnRows(sqlmock.NewRows([]string{"totp_secret", "totp_enabled_at"}).AddRow(testTOTPSecret, nil))
+	mock.ExpectRollback()
+
+	_, err = EnableTwoFactor(context.Background(), db, userID, "000000", time.Unix(1234567890, 0))
+	assert.ErrorIs(t, err, ErrInvalidTwoFactorCode)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestClaimLoginChallenge(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectQuery("UPDATE login_challenge SET attempts").
+		WithArgs(sha256Hex("token"), maxLoginChallengeAttempts).
+		WillReturnRows(sqlmock.NewRows([]string{"user_id"}).AddRow(userID.String()))
+	got, err := ClaimLoginChallenge(context.Background(), db, "token")
+	require.NoError(t, err)
+	assert.Equal(t, userID, got)
+
+	// Expired or exhausted challenges return no row
+	mock.ExpectQuery("UPDATE login_challenge SET attempts").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id"}))
+	_, err = ClaimLoginChallenge(context.Background(), db, "token")
+	assert.ErrorIs(t, err, ErrLoginChallengeNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
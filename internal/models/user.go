@@ -3,10 +3,17 @@ package models
 import (
 	"context"
 	"database/sql"
+	"errors"
 
 	"github.com/google/uuid"
 )
 
+// ErrEmailTaken is returned when another account already uses an address
+var ErrEmailTaken = errors.New("email address is used by another account")
+
+// ErrUserDisabled is returned when a disabled account tries to sign in
+var ErrUserDisabled = errors.New("account is disabled")
+
 // User represents a user in the system
 type User struct {
 	UserID       uuid.UUID
@@ -102,3 +109,74 @@ func ValidateUser(ctx context.Context, db *sql.DB, username, password string) (*
 
 	return user, nil
 }
+
+// SetUserAdmin makes a user an administrator, who owns every website, or
+// takes that away. It returns sql.ErrNoRows for unknown users.
+func SetUserAdmin(ctx context.Context, db *sql.DB, userID uuid.UUID, admin bool) error {
+	result, err := db.ExecContext(ctx, `
+		UPDATE users SET is_admin = $2, updated_at = NOW() WHERE user_id = $1
+	`, userID, admin)
+	if err != nil {
+		return err
+	}
+	if n, _ := result.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
+
+// SetUserDisabled disables an account and signs it out everywhere, or lets
+// it sign in again. It returns sql.ErrNoRows for unknown users.
+func SetUserDisabled(ctx context.Context, db *sql.DB, userID uuid.UUID, disabled bool) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	result, err := tx.ExecContext(ctx, `
+		UPDATE users
+		SET disabled_at = CASE WHEN $2 THEN COALESCE(disabled_at, NOW()) END, updated_at = NOW()
+		WHERE user_id = $1
+	`, userID, disabled)
+	if err != nil {
+		return err
+	}
+	if n, _ := result.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	if disabled {
+		if _, err := tx.ExecContext(ctx, `DELETE FROM user_sessions WHERE user_id = $1`, userID); err != nil {
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+// SetUserEmail sets the address password reset links are sent to; an empty
+// email removes it. It returns sql.ErrNoRows for unknown users.
+func SetUserEmail(ctx context.Context, db *sql.DB, userID uuid.UUID, email string) error {
+	if email != "" {
+		var taken bool
+		err := db.QueryRowContext(ctx, `
+			SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($2) AND user_id <> $1)
+		`, userID, email).Scan(&taken)
+		if err != nil {
+			return err
+		}
+		if taken {
+			return ErrEmailTaken
+		}
+	}
+
+	result, err := db.ExecContext(ctx, `
+		UPDATE users SET email = NULLIF($2, ''), updated_at = NOW() WHERE user_id = $1
+	`, userID, email)
+	if err != nil {
+		return err
+	}
+	if n, _ := result.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
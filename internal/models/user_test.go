@@ -6,6 +6,7 @@ import (
 	"testing"
 
 	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
 )
@@ -114,3 +115,57 @@ func TestHasAnyUsers_QueryError(t *testing.T) {
 	err = mock.ExpectationsWereMet()
 	assert.NoError(t, err)
 }
+
+func TestSetUserAdmin(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+	mock.ExpectExec("UPDATE users SET is_admin").
+		WithArgs(userID, true).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	require.NoError(t, SetUserAdmin(context.Background(), db, userID, true))
+
+	mock.ExpectExec("UPDATE users SET is_admin").
+		WithArgs(userID, false).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	assert.ErrorIs(t, SetUserAdmin(context.Background(), db, userID, false), sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSetUserDisabled(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	userID := uuid.New()
+
+	// Disabling signs the user out everywhere
+	mock.ExpectBegin()
+	mock.ExpectExec("UPDATE users").
+		WithArgs(userID, true).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("DELETE FROM user_sessions").
+		WithArgs(userID).
+		WillReturnResult(sqlmock.NewResult(0, 2))
+	mock.ExpectCommit()
+	require.NoError(t, SetUserDisabled(context.Background(), db, userID, true))
+
+	mock.ExpectBegin()
+	mock.ExpectExec("UPDATE users").
+		WithArgs(userID, false).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectCommit()
+	require.NoError(t, SetUserDisabled(context.Background(), db, userID, false))
+
+	mock.ExpectBegin()
+	mock.ExpectExec("UPDATE users").
+		WithArgs(userID, true).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectRollback()
+	assert.ErrorIs(t, SetUserDisabled(context.Background(), db, userID, true), sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
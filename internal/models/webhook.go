@@ -0,0 +1,363 @@
+package models
+
+import (
+	"context"
+	"crypto/rand"
+	"database/sql"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"slices"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+
+	"github.com/seuros/kaunta/internal/safehttp"
+)
+
+// Webhook events a subscription can trigger on
+const (
+	WebhookGoalCompleted = "goal.completed"
+	WebhookAlertFired    = "alert.fired"
+	WebhookWeeklySummary = "summary.weekly"
+	// WebhookPing is sent by the dashboard's "Send test" button
+	WebhookPing = "ping"
+)
+
+// WebhookEvents lists the subscribable events in display order
+var WebhookEvents = []string{WebhookGoalCompleted, WebhookAlertFired, WebhookWeeklySummary}
+
+// Webhook delivery statuses
+const (
+	WebhookDeliveryPending   = "pending"
+	WebhookDeliveryDelivered = "delivered"
+	WebhookDeliveryFailed    = "failed"
+)
+
+// webhookSecretBytes is the entropy of a signing secret (hex-encoded to 64 chars)
+const webhookSecretBytes = 32
+
+// ErrWebhookNotFound is returned when a webhook does not exist or belongs to
+// another user
+var ErrWebhookNotFound = errors.New("webhook not found")
+
+// Webhook POSTs signed JSON payloads to a URL when subscribed events happen
+// on a website
+type Webhook struct {
+	ID            uuid.UUID  `json:"id"`
+	UserID        uuid.UUID  `json:"user_id"`
+	WebsiteID     uuid.UUID  `json:"website_id"`
+	URL           string     `json:"url"`
+	Secret        string     `json:"-"`
+	Events        []string   `json:"events"`
+	Enabled       bool       `json:"enabled"`
+	LastSummaryAt *time.Time `json:"last_summary_at,omitempty"`
+	CreatedAt     time.Time  `json:"created_at"`
+}
+
+// Validate checks the webhook's URL and events
+func (h *Webhook) Validate() error {
+	if len(h.URL) > 500 {
+		return fmt.Errorf("URL is too long")
+	}
+	if err := safehttp.CheckURL(h.URL); err != nil {
+		if errors.Is(err, safehttp.ErrBlockedAddress) {
+			return fmt.Errorf("URL must point to a public address")
+		}
+		return err
+	}
+	if len(h.Events) == 0 {
+		return fmt.Errorf("select at least one event")
+	}
+	for _, event := range h.Events {
+		if !slices.Contains(WebhookEvents, event) {
+			return fmt.Errorf("invalid event: %s", event)
+		}
+	}
+	return nil
+}
+
+// WebhookDelivery is one queued payload and the outcome of its latest attempt
+type WebhookDelivery struct {
+	ID             uuid.UUID       `json:"id"`
+	WebhookID      uuid.UUID       `json:"webhook_id"`
+	Event          string          `json:"event"`
+	Payload        json.RawMessage `json:"payload"`
+	Status         string          `json:"status"`
+	Attempts       int             `json:"attempts"`
+	ResponseStatus *int            `json:"response_status,omitempty"`
+	LastError      string          `json:"last_error,omitempty"`
+	NextAttemptAt  time.Time       `json:"next_attempt_at"`
+	CreatedAt      time.Time       `json:"created_at"`
+	DeliveredAt    *time.Time      `json:"delivered_at,omitempty"`
+}
+
+// DueWebhookDelivery is a claimed delivery with what the dispatcher needs to send it
+type DueWebhookDelivery struct {
+	WebhookDelivery
+	WebsiteID uuid.UUID
+	URL       string
+	Secret    string
+}
+
+const webhookColumns = `webhook_id, user_id, website_id, url, secret, events, enabled, last_summary_at, created_at`
+
+func scanWebhooks(rows *sql.Rows) ([]Webhook, error) {
+	defer func() { _ = rows.Close() }()
+
+	var hooks []Webhook
+	for rows.Next() {
+		var h Webhook
+		var lastSummary sql.NullTime
+		if err := rows.Scan(&h.ID, &h.UserID, &h.WebsiteID, &h.URL, &h.Secret, pq.Array(&h.Events),
+			&h.Enabled, &lastSummary, &h.CreatedAt); err != nil {
+			return nil, err
+		}
+		if lastSummary.Valid {
+			h.LastSummaryAt = &lastSummary.Time
+		}
+		hooks = append(hooks, h)
+	}
+	return hooks, rows.Err()
+}
+
+// ListWebhooks returns the webhooks owned by a user, newest first
+func ListWebhooks(ctx context.Context, db *sql.DB, userID uuid.UUID) ([]Webhook, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT `+webhookColumns+`
+		FROM webhook
+		WHERE user_id = $1
+		ORDER BY created_at DESC
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	return scanWebhooks(rows)
+}
+
+// CreateWebhook stores a webhook for a website the user can edit with a fresh
+// signing secret; the caller validates the webhook first
+func CreateWebhook(ctx context.Context, db *sql.DB, hook *Webhook) error {
+	raw := make([]byte, webhookSecretBytes)
+	if _, err := rand.Read(raw); err != nil {
+		return err
+	}
+	hook.Secret = hex.EncodeToString(raw)
+
+	err := db.QueryRowContext(ctx, `
+		INSERT INTO webhook (user_id, website_id, url, secret, events)
+		SELECT $1, website_id, $3, $4, $5
+		FROM user_websites($1)
+		WHERE website_id = $2 AND role IN ('owner', 'editor')
+		RETURNING webhook_id, enabled, created_at
+	`, hook.UserID, hook.WebsiteID, hook.URL, hook.Secret, pq.Array(hook.Events)).Scan(&hook.ID, &hook.Enabled, &hook.CreatedAt)
+	if err == sql.ErrNoRows {
+		return fmt.Errorf("website not found")
+	}
+	return err
+}
+
+// SetWebhookEnabled pauses or resumes a webhook
+func SetWebhookEnabled(ctx context.Context, db *sql.DB, id, userID uuid.UUID, enabled bool) error {
+	res, err := db.ExecContext(ctx, `UPDATE webhook SET enabled = $3 WHERE webhook_id = $1 AND user_id = $2`, id, userID, enabled)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrWebhookNotFound
+	}
+	return nil
+}
+
+// DeleteWebhook removes a webhook and its delivery log
+func DeleteWebhook(ctx context.Context, db *sql.DB, id, userID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `DELETE FROM webhook WHERE webhook_id = $1 AND user_id = $2`, id, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrWebhookNotFound
+	}
+	return nil
+}
+
+// ListWebhookDeliveries returns the latest deliveries of the user's webhooks,
+// newest first
+func ListWebhookDeliveries(ctx context.Context, db *sql.DB, userID uuid.UUID, limit int) ([]WebhookDelivery, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT d.delivery_id, d.webhook_id, d.event, d.payload, d.status, d.attempts,
+		       d.response_status, COALESCE(d.last_error, ''), d.next_attempt_at, d.created_at, d.delivered_at
+		FROM webhook_delivery d
+		JOIN webhook h ON h.webhook_id = d.webhook_id
+		WHERE h.user_id = $1
+		ORDER BY d.created_at DESC
+		LIMIT $2
+	`, userID, limit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var deliveries []WebhookDelivery
+	for rows.Next() {
+		var d WebhookDelivery
+		var payload []byte
+		var responseStatus sql.NullInt64
+		var deliveredAt sql.NullTime
+		if err := rows.Scan(&d.ID, &d.WebhookID, &d.Event, &payload, &d.Status, &d.Attempts,
+			&responseStatus, &d.LastError, &d.NextAttemptAt, &d.CreatedAt, &deliveredAt); err != nil {
+			return nil, err
+		}
+		d.Payload = payload
+		if responseStatus.Valid {
+			status := int(responseStatus.Int64)
+			d.ResponseStatus = &status
+		}
+		if deliveredAt.Valid {
+			d.DeliveredAt = &deliveredAt.Time
+		}
+		deliveries = append(deliveries, d)
+	}
+	return deliveries, rows.Err()
+}
+
+// webhookAccessCheck keeps webhooks whose owner can no longer edit their
+// website from being sent anything
+const webhookAccessCheck = `h.website_id IN (
+	SELECT a.website_id FROM user_websites_all_teams(h.user_id) a WHERE a.role IN ('owner', 'editor')
+)`
+
+// EnqueueWebhookEvent queues a payload for every enabled webhook of the
+// website subscribed to event whose owner can still edit the website,
+// returning how many were queued
+func EnqueueWebhookEvent(ctx context.Context, db *sql.DB, websiteID uuid.UUID, event string, payload any) (int64, error) {
+	data, err := json.Marshal(payload)
+	if err != nil {
+		return 0, err
+	}
+	res, err := db.ExecContext(ctx, `
+		INSERT INTO webhook_delivery (webhook_id, event, payload)
+		SELECT h.webhook_id, $2, $3
+		FROM webhook h
+		WHERE h.website_id = $1 AND h.enabled AND $2 = ANY(h.events)
+		  AND `+webhookAccessCheck+`
+	`, websiteID, event, string(data))
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+// EnqueueWebhookDelivery queues a payload for one webhook the user owns
+func EnqueueWebhookDelivery(ctx context.Context, db *sql.DB, id, userID uuid.UUID, event string, payload any) error {
+	data, err := json.Marshal(payload)
+	if err != nil {
+		return err
+	}
+	res, err := db.ExecContext(ctx, `
+		INSERT INTO webhook_delivery (webhook_id, event, payload)
+		SELECT webhook_id, $3, $4
+		FROM webhook
+		WHERE webhook_id = $1 AND user_id = $2
+	`, id, userID, event, string(data))
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrWebhookNotFound
+	}
+	return nil
+}
+
+// ClaimDueWebhookDeliveries leases up to limit pending deliveries whose next
+// attempt is due, pushing their next attempt back by lease so a crashed
+// dispatcher's deliveries are picked up again later
+func ClaimDueWebhookDeliveries(ctx context.Context, db *sql.DB, limit int, lease time.Duration) ([]DueWebhookDelivery, error) {
+	rows, err := db.QueryContext(ctx, `
+		UPDATE webhook_delivery d
+		SET next_attempt_at = NOW() + make_interval(secs => $2)
+		FROM webhook h
+		WHERE h.webhook_id = d.webhook_id
+		  AND d.delivery_id IN (
+			SELECT delivery_id
+			FROM webhook_delivery
+			WHERE status = 'pending' AND next_attempt_at <= NOW()
+			ORDER BY next_attempt_at
+			LIMIT $1
+			FOR UPDATE SKIP LOCKED
+		  )
+		RETURNING d.delivery_id, d.webhook_id, d.event, d.payload, d.attempts, d.created_at,
+		          h.website_id, h.url, h.secret
+	`, limit, lease.Seconds())
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var due []DueWebhookDelivery
+	for rows.Next() {
+		var d DueWebhookDelivery
+		var payload []byte
+		if err := rows.Scan(&d.ID, &d.WebhookID, &d.Event, &payload, &d.Attempts, &d.CreatedAt,
+			&d.WebsiteID, &d.URL, &d.Secret); err != nil {
+			return nil, err
+		}
+		d.Payload, d.Status = payload, WebhookDeliveryPending
+		due = append(due, d)
+	}
+	return due, rows.Err()
+}
+
+// RecordWebhookAttempt stores the outcome of a delivery attempt; responseStatus
+// is 0 when no response was received
+func RecordWebhookAttempt(ctx context.Context, db *sql.DB, id uuid.UUID, status string, responseStatus int, lastError string, nextAttempt time.Time) error {
+	_, err := db.ExecContext(ctx, `
+		UPDATE webhook_delivery
+		SET attempts = attempts + 1,
+		    status = $2,
+		    response_status = NULLIF($3, 0),
+		    last_error = NULLIF($4, ''),
+		    next_attempt_at = $5,
+		    delivered_at = CASE WHEN $2 = 'delivered' THEN NOW() END
+		WHERE delivery_id = $1
+	`, id, status, responseStatus, lastError, nextAttempt)
+	return err
+}
+
+// ListWeeklySummaryWebhooksDue returns enabled summary.weekly webhooks on live
+// websites their owner can still edit that have not queued a summary since
+// weekStart; new webhooks wait for the first Monday after they were created
+func ListWeeklySummaryWebhooksDue(ctx context.Context, db *sql.DB, weekStart time.Time) ([]Webhook, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT `+webhookColumns+`
+		FROM webhook h
+		WHERE h.enabled
+		  AND $1 = ANY(h.events)
+		  AND COALESCE(h.last_summary_at, h.created_at) < $2
+		  AND `+webhookAccessCheck+`
+	`, WebhookWeeklySummary, weekStart)
+	if err != nil {
+		return nil, err
+	}
+	return scanWebhooks(rows)
+}
+
+// MarkWebhookSummaryQueued records when a webhook's weekly summary was queued
+func MarkWebhookSummaryQueued(ctx context.Context, db *sql.DB, id uuid.UUID, at time.Time) error {
+	_, err := db.ExecContext(ctx, `UPDATE webhook SET last_summary_at = $2 WHERE webhook_id = $1`, id, at)
+	return err
+}
+
+// PruneWebhookDeliveries deletes finished deliveries created before cutoff
+func PruneWebhookDeliveries(ctx context.Context, db *sql.DB, cutoff time.Time) (int64, error) {
+	res, err := db.ExecContext(ctx, `
+		DELETE FROM webhook_delivery
+		WHERE created_at < $1 AND status <> 'pending'
+	`, cutoff)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
@@ -0,0 +1,121 @@
+package models
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestWebhookValidate(t *testing.T) {
+	valid := func() Webhook {
+		return Webhook{
+			URL:    "https://hooks.example.com/kaunta",
+			Events: []string{WebhookGoalCompleted, WebhookWeeklySummary},
+		}
+	}
+
+	tests := []struct {
+		name   string
+		modify func(*Webhook)
+		ok     bool
+	}{
+		{"valid", func(*Webhook) {}, true},
+		{"plain http", func(h *Webhook) { h.URL = "http://hooks.example.com:8080/hook" }, true},
+		{"localhost", func(h *Webhook) { h.URL = "http://localhost:8080/hook" }, false},
+		{"private address", func(h *Webhook) { h.URL = "http://192.168.1.10/hook" }, false},
+		{"metadata endpoint", func(h *Webhook) { h.URL = "http://169.254.169.254/latest/meta-data/" }, false},
+		{"not http", func(h *Webhook) { h.URL = "ftp://example.com" }, false},
+		{"missing host", func(h *Webhook) { h.URL = "https://" }, false},
+		{"no events", func(h *Webhook) { h.Events = nil }, false},
+		{"unknown event", func(h *Webhook) { h.Events = []string{"visit.started"} }, false},
+		{"ping is not subscribable", func(h *Webhook) { h.Events = []string{WebhookPing} }, false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			hook := valid()
+			tt.modify(&hook)
+			err := hook.Validate()
+			if tt.ok {
+				assert.NoError(t, err)
+			} else {
+				assert.Error(t, err)
+			}
+		})
+	}
+}
+
+func TestEnqueueWebhookEvent(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	mock.ExpectExec("INSERT INTO webhook_delivery").
+		WithArgs(websiteID, WebhookGoalCompleted, `{"goal_id":"g1"}`).
+		WillReturnResult(sqlmock.NewResult(0, 2))
+
+	queued, err := EnqueueWebhookEvent(context.Background(), db, websiteID, WebhookGoalCompleted, map[string]string{"goal_id": "g1"})
+	require.NoError(t, err)
+	assert.Equal(t, int64(2), queued)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEnqueueWebhookEventRechecksAccess(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	// A webhook whose owner was removed from the website, or is now only a
+	// viewer, isn't sent the event
+	websiteID := uuid.New()
+	mock.ExpectExec(`FROM user_websites_all_teams\(h.user_id\) a WHERE a.role IN \('owner', 'editor'\)`).
+		WithArgs(websiteID, WebhookAlertFired, `{}`).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+
+	queued, err := EnqueueWebhookEvent(context.Background(), db, websiteID, WebhookAlertFired, map[string]string{})
+	require.NoError(t, err)
+	assert.Zero(t, queued)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestListWeeklySummaryWebhooksDueRechecksAccess(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	weekStart := time.Date(2026, 10, 12, 0, 0, 0, 0, time.UTC)
+	columns := []string{"webhook_id", "user_id", "website_id", "url", "secret", "events", "enabled", "last_summary_at", "created_at"}
+	mock.ExpectQuery(`FROM user_websites_all_teams\(h.user_id\) a WHERE a.role IN \('owner', 'editor'\)`).
+		WithArgs(WebhookWeeklySummary, weekStart).
+		WillReturnRows(sqlmock.NewRows(columns))
+
+	hooks, err := ListWeeklySummaryWebhooksDue(context.Background(), db, weekStart)
+	require.NoError(t, err)
+	assert.Empty(t, hooks)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestEnqueueWebhookDeliveryRequiresOwnership(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	id, userID := uuid.New(), uuid.New()
+	mock.ExpectExec("INSERT INTO webhook_delivery").
+		WithArgs(id, userID, WebhookPing, sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+
+	err = EnqueueWebhookDelivery(context.Background(), db, id, userID, WebhookPing, map[string]string{})
+	assert.ErrorIs(t, err, ErrWebhookNotFound)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
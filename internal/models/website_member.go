@@ -0,0 +1,195 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// WebsiteRole is a user's level of access to one website
+type WebsiteRole string
+
+const (
+	// RoleOwner can do everything, including managing members, API keys and
+	// share links
+	RoleOwner WebsiteRole = "owner"
+	// RoleEditor can change settings, domains and goals
+	RoleEditor WebsiteRole = "editor"
+	// RoleViewer can only read stats
+	RoleViewer WebsiteRole = "viewer"
+)
+
+var roleRank = map[WebsiteRole]int{RoleViewer: 1, RoleEditor: 2, RoleOwner: 3}
+
+var (
+	// ErrNoWebsiteAccess is returned when a user has no role on a website,
+	// or the website doesn't exist
+	ErrNoWebsiteAccess = errors.New("website not found")
+	// ErrWebsiteOwnerRole is returned when trying to change or remove the
+	// website's primary owner through its members
+	ErrWebsiteOwnerRole = errors.New("the website's owner can't be changed here")
+)
+
+// ParseWebsiteRole validates a role name
+func ParseWebsiteRole(s string) (WebsiteRole, error) {
+	role := WebsiteRole(s)
+	if _, ok := roleRank[role]; !ok {
+		return "", fmt.Errorf("invalid role: %s (valid: owner, editor, viewer)", s)
+	}
+	return role, nil
+}
+
+// Allows reports whether the role includes everything required can do
+func (r WebsiteRole) Allows(required WebsiteRole) bool {
+	return roleRank[r] > 0 && roleRank[r] >= roleRank[required]
+}
+
+// WebsiteMember is a user with access to a website
+type WebsiteMember struct {
+	UserID    uuid.UUID   `json:"user_id"`
+	Username  string      `json:"username"`
+	Role      WebsiteRole `json:"role"`
+	Primary   bool        `json:"primary"` // The website's own user_id; can't be removed
+	CreatedAt *time.Time  `json:"created_at,omitempty"`
+}
+
+// GetWebsiteRole returns the user's role on a website, or ErrNoWebsiteAccess
+func GetWebsiteRole(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) (WebsiteRole, error) {
+	var role string
+	err := db.QueryRowContext(ctx, `
+		SELECT role FROM user_websites($2) WHERE website_id = $1
+	`, websiteID, userID).Scan(&role)
+	if err == sql.ErrNoRows {
+		return "", ErrNoWebsiteAccess
+	}
+	if err != nil {
+		return "", err
+	}
+	return WebsiteRole(role), nil
+}
+
+// UserWebsiteRoles returns the user's role on every website they can access
+func UserWebsiteRoles(ctx context.Context, db *sql.DB, userID uuid.UUID) (map[uuid.UUID]WebsiteRole, error) {
+	rows, err := db.QueryContext(ctx, `SELECT website_id, role FROM user_websites($1)`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	roles := make(map[uuid.UUID]WebsiteRole)
+	for rows.Next() {
+		var websiteID uuid.UUID
+		var role string
+		if err := rows.Scan(&websiteID, &role); err != nil {
+			return nil, err
+		}
+		roles[websiteID] = WebsiteRole(role)
+	}
+	return roles, rows.Err()
+}
+
+// SetWebsiteOwner makes a user the website's primary owner. Any member
+// role they held is dropped since ownership covers it.
+func SetWebsiteOwner(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) error {
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	res, err := tx.ExecContext(ctx, `
+		UPDATE website SET user_id = $2, updated_at = NOW() WHERE website_id = $1 AND deleted_at IS NULL
+	`, websiteID, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return ErrNoWebsiteAccess
+	}
+	if _, err := tx.ExecContext(ctx, `
+		DELETE FROM website_member WHERE website_id = $1 AND user_id = $2
+	`, websiteID, userID); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+// ListWebsiteMembers returns the website's owner followed by its members
+func ListWebsiteMembers(ctx context.Context, db *sql.DB, websiteID uuid.UUID) ([]WebsiteMember, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT u.user_id, u.username, 'owner', true, NULL::timestamptz
+		FROM website w
+		JOIN users u ON u.user_id = w.user_id
+		WHERE w.website_id = $1
+		UNION ALL
+		SELECT u.user_id, u.username, m.role, false, m.created_at
+		FROM website_member m
+		JOIN users u ON u.user_id = m.user_id
+		WHERE m.website_id = $1
+		ORDER BY 4 DESC, 2
+	`, websiteID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	members := []WebsiteMember{}
+	for rows.Next() {
+		var m WebsiteMember
+		var role string
+		if err := rows.Scan(&m.UserID, &m.Username, &role, &m.Primary, &m.CreatedAt); err != nil {
+			return nil, err
+		}
+		m.Role = WebsiteRole(role)
+		members = append(members, m)
+	}
+	return members, rows.Err()
+}
+
+// SetWebsiteMember grants a user a role on a website, replacing any role
+// they had. It returns sql.ErrNoRows for unknown usernames.
+func SetWebsiteMember(ctx context.Context, db *sql.DB, websiteID uuid.UUID, username string, role WebsiteRole) (*WebsiteMember, error) {
+	m := WebsiteMember{Username: username, Role: role}
+	var primary bool
+	err := db.QueryRowContext(ctx, `
+		SELECT u.user_id, EXISTS(SELECT 1 FROM website w WHERE w.website_id = $1 AND w.user_id = u.user_id)
+		FROM users u WHERE u.username = $2
+	`, websiteID, username).Scan(&m.UserID, &primary)
+	if err != nil {
+		return nil, err
+	}
+	if primary {
+		return nil, ErrWebsiteOwnerRole
+	}
+
+	err = db.QueryRowContext(ctx, `
+		INSERT INTO website_member (website_id, user_id, role) VALUES ($1, $2, $3)
+		ON CONFLICT (website_id, user_id) DO UPDATE SET role = EXCLUDED.role
+		RETURNING created_at
+	`, websiteID, m.UserID, string(role)).Scan(&m.CreatedAt)
+	if err != nil {
+		return nil, err
+	}
+	return &m, nil
+}
+
+// RemoveWebsiteMember revokes a user's access to a website. The primary
+// owner isn't a member and can't be removed. The user's alert rules,
+// webhooks and custom dashboards on the website stop with their access:
+// each re-checks it through user_websites_all_teams when it runs.
+func RemoveWebsiteMember(ctx context.Context, db *sql.DB, websiteID, userID uuid.UUID) error {
+	res, err := db.ExecContext(ctx, `
+		DELETE FROM website_member WHERE website_id = $1 AND user_id = $2
+	`, websiteID, userID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return sql.ErrNoRows
+	}
+	return nil
+}
@@ -0,0 +1,98 @@
+package models
+
+import (
+	"context"
+	"database/sql"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestWebsiteRoleAllows(t *testing.T) {
+	assert.True(t, RoleOwner.Allows(RoleViewer))
+	assert.True(t, RoleOwner.Allows(RoleOwner))
+	assert.True(t, RoleEditor.Allows(RoleViewer))
+	assert.False(t, RoleEditor.Allows(RoleOwner))
+	assert.False(t, RoleViewer.Allows(RoleEditor))
+	assert.False(t, WebsiteRole("").Allows(RoleViewer))
+
+	role, err := ParseWebsiteRole("editor")
+	require.NoError(t, err)
+	assert.Equal(t, RoleEditor, role)
+
+	_, err = ParseWebsiteRole("admin")
+	assert.Error(t, err)
+}
+
+func TestGetWebsiteRole(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID, userID := uuid.New(), uuid.New()
+	mock.ExpectQuery("FROM user_websites").WithArgs(websiteID, userID).
+		WillReturnRows(sqlmock.NewRows([]string{"role"}).AddRow("editor"))
+	mock.ExpectQuery("FROM user_websites").WithArgs(websiteID, userID).
+		WillReturnRows(sqlmock.NewRows([]string{"role"}))
+
+	role, err := GetWebsiteRole(context.Background(), db, websiteID, userID)
+	require.NoError(t, err)
+	assert.Equal(t, RoleEditor, role)
+
+	_, err = GetWebsiteRole(context.Background(), db, websiteID, userID)
+	assert.ErrorIs(t, err, ErrNoWebsiteAccess)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestSetWebsiteMember(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID, userID := uuid.New(), uuid.New()
+	now := time.Now()
+
+	mock.ExpectQuery("FROM users u").WithArgs(websiteID, "marketing").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "primary"}).AddRow(userID.String(), false))
+	mock.ExpectQuery("INSERT INTO website_member").WithArgs(websiteID, userID, "viewer").
+		WillReturnRows(sqlmock.NewRows([]string{"created_at"}).AddRow(now))
+
+	member, err := SetWebsiteMember(context.Background(), db, websiteID, "marketing", RoleViewer)
+	require.NoError(t, err)
+	assert.Equal(t, userID, member.UserID)
+	assert.Equal(t, RoleViewer, member.Role)
+	assert.False(t, member.Primary)
+
+	// The website's own owner can't be demoted through its members
+	mock.ExpectQuery("FROM users u").WithArgs(websiteID, "admin").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "primary"}).AddRow(userID.String(), true))
+	_, err = SetWebsiteMember(context.Background(), db, websiteID, "admin", RoleViewer)
+	assert.ErrorIs(t, err, ErrWebsiteOwnerRole)
+
+	mock.ExpectQuery("FROM users u").WithArgs(websiteID, "nobody").
+		WillReturnRows(sqlmock.NewRows([]string{"user_id", "primary"}))
+	_, err = SetWebsiteMember(context.Background(), db, websiteID, "nobody", RoleViewer)
+	assert.ErrorIs(t, err, sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestRemoveWebsiteMemberNotFound(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID, userID := uuid.New(), uuid.New()
+	mock.ExpectExec("DELETE FROM website_member").WithArgs(websiteID, userID).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+
+	err = RemoveWebsiteMember(context.Background(), db, websiteID, userID)
+	assert.ErrorIs(t, err, sql.ErrNoRows)
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
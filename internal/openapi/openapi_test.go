@@ -0,0 +1,62 @@
+package openapi
+
+import (
+	"encoding/json"
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+type base struct {
+	ID uuid.UUID `json:"id"`
+}
+
+type sample struct {
+	base
+	Name      string          `json:"name"`
+	Note      *string         `json:"note,omitempty"`
+	Tags      []string        `json:"tags"`
+	Props     map[string]any  `json:"props,omitempty"`
+	Event     string          `json:"event" validate:"required,max=50"`
+	Referrer  string          `json:"referrer" validate:"omitempty,max=2000"`
+	CreatedAt time.Time       `json:"created_at"`
+	Raw       json.RawMessage `json:"raw,omitempty"`
+	Child     *sample         `json:"child,omitempty"`
+	Hidden    string          `json:"-"`
+}
+
+func TestSchemaOf(t *testing.T) {
+	doc := New("Test", "1.0", "")
+
+	ref := doc.SchemaOf(sample{})
+	assert.Equal(t, "#/components/schemas/sample", ref.Ref)
+
+	s := doc.Components.Schemas["sample"]
+	require.NotNil(t, s)
+	assert.Equal(t, "object", s.Type)
+	assert.Equal(t, &Schema{Type: "string", Format: "uuid"}, s.Properties["id"])
+	assert.Equal(t, &Schema{Type: "string", Nullable: true}, s.Properties["note"])
+	assert.Equal(t, "array", s.Properties["tags"].Type)
+	assert.Equal(t, "object", s.Properties["props"].Type)
+	assert.Equal(t, &Schema{Type: "string", Format: "date-time"}, s.Properties["created_at"])
+	assert.Equal(t, &Schema{}, s.Properties["raw"])
+	assert.Equal(t, "#/components/schemas/sample", s.Properties["child"].Ref)
+	assert.NotContains(t, s.Properties, "Hidden")
+
+	assert.ElementsMatch(t, []string{"id", "name", "tags", "event", "created_at"}, s.Required)
+}
+
+func TestAdd(t *testing.T) {
+	doc := New("Test", "1.0", "")
+	doc.Add(http.MethodGet, "/api/things/{id}", Operation{OperationID: "showThing"})
+	doc.Add(http.MethodDelete, "/api/things/{id}", Operation{OperationID: "deleteThing"})
+
+	item := doc.Paths["/api/things/{id}"]
+	require.Len(t, item, 2)
+	assert.Equal(t, "showThing", item["get"].OperationID)
+	assert.Equal(t, "deleteThing", item["delete"].OperationID)
+}
@@ -0,0 +1,568 @@
+// Package parquet writes and reads Apache Parquet files with a flat schema
+// of optional columns, which is all Kaunta's event archives need. Values are
+// PLAIN encoded into one gzip-compressed data page per column and row group,
+// so the files open in DuckDB, Spark, pandas and other Parquet readers. The
+// reader understands the files this writer produces, not Parquet in general.
+package parquet
+
+import (
+	"bytes"
+	"compress/gzip"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+	"time"
+)
+
+const magic = "PAR1"
+
+// DefaultRowGroupSize is how many rows a Writer buffers per row group
+const DefaultRowGroupSize = 50000
+
+// Type is the type of a column's values
+type Type int
+
+// Column types, with the Go type of their values. NULL is nil.
+const (
+	String    Type = iota // string, stored as UTF8 BYTE_ARRAY
+	JSON                  // string, stored as JSON BYTE_ARRAY
+	Int32                 // int32
+	Timestamp             // time.Time, stored as INT64 microseconds in UTC
+	Int64                 // int64
+)
+
+// Parquet physical types, converted types, encodings and codecs
+const (
+	physicalInt32     = 1
+	physicalInt64     = 2
+	physicalByteArray = 6
+
+	convertedUTF8            = 0
+	convertedTimestampMicros = 10
+	convertedJSON            = 19
+
+	encodingPlain = 0
+	encodingRLE   = 3
+
+	codecUncompressed = 0
+	codecGzip         = 2
+
+	pageTypeData = 0
+
+	repetitionOptional = 1
+)
+
+// Column is a named, optional column
+type Column struct {
+	Name string
+	Type Type
+}
+
+func (c Column) physical() (physical, converted int32) {
+	switch c.Type {
+	case JSON:
+		return physicalByteArray, convertedJSON
+	case Int32:
+		return physicalInt32, -1
+	case Timestamp:
+		return physicalInt64, convertedTimestampMicros
+	case Int64:
+		return physicalInt64, -1
+	default:
+		return physicalByteArray, convertedUTF8
+	}
+}
+
+func columnType(physical, converted int64, hasConverted bool) (Type, error) {
+	switch {
+	case physical == physicalByteArray && (!hasConverted || converted == convertedUTF8):
+		return String, nil
+	case physical == physicalByteArray && converted == convertedJSON:
+		return JSON, nil
+	case physical == physicalInt32 && !hasConverted:
+		return Int32, nil
+	case physical == physicalInt64 && converted == convertedTimestampMicros:
+		return Timestamp, nil
+	case physical == physicalInt64 && !hasConverted:
+		return Int64, nil
+	}
+	return 0, fmt.Errorf("parquet: unsupported column type %d/%d", physical, converted)
+}
+
+// Writer writes rows to a Parquet file. Rows are buffered in memory until
+// RowGroupSize of them are waiting; Close writes the rest and the footer.
+type Writer struct {
+	RowGroupSize int
+
+	w       io.Writer
+	offset  int64
+	columns []Column
+	pending [][]any
+	groups  []tstruct
+	rows    int64
+}
+
+// NewWriter starts a Parquet file with the given columns on w
+func NewWriter(w io.Writer, columns []Column) (*Writer, error) {
+	pw := &Writer{RowGroupSize: DefaultRowGroupSize, w: w, columns: columns}
+	if err := pw.write([]byte(magic)); err != nil {
+		return nil, err
+	}
+	return pw, nil
+}
+
+// Write adds a row with one value per column. The writer keeps row until
+// its row group is written, so callers must not reuse it.
+func (pw *Writer) Write(row []any) error {
+	if len(row) != len(pw.columns) {
+		return fmt.Errorf("parquet: row has %d values for %d columns", len(row), len(pw.columns))
+	}
+	for i, v := range row {
+		if !valid(pw.columns[i].Type, v) {
+			return fmt.Errorf("parquet: column %s can't hold %T", pw.columns[i].Name, v)
+		}
+	}
+	pw.pending = append(pw.pending, row)
+	if len(pw.pending) >= pw.RowGroupSize {
+		return pw.flush()
+	}
+	return nil
+}
+
+// Rows returns the number of rows written so far
+func (pw *Writer) Rows() int64 {
+	return pw.rows + int64(len(pw.pending))
+}
+
+// Close writes the buffered rows and the file footer. It doesn't close the
+// underlying writer.
+func (pw *Writer) Close() error {
+	if err := pw.flush(); err != nil {
+		return err
+	}
+
+	schema := []any{tstruct{{4, "schema"}, {5, int32(len(pw.columns))}}}
+	for _, c := range pw.columns {
+		physical, converted := c.physical()
+		element := tstruct{{1, physical}, {3, int32(repetitionOptional)}, {4, c.Name}}
+		if converted >= 0 {
+			element = append(element, tfield{6, converted})
+		}
+		schema = append(schema, element)
+	}
+	groups := make([]any, len(pw.groups))
+	for i, g := range pw.groups {
+		groups[i] = g
+	}
+	footer := encodeStruct(tstruct{
+		{1, int32(1)},
+		{2, tlist{tStruct, schema}},
+		{3, pw.rows},
+		{4, tlist{tStruct, groups}},
+		{6, "kaunta"},
+	})
+
+	var length [4]byte
+	binary.LittleEndian.PutUint32(length[:], uint32(len(footer)))
+	for _, b := range [][]byte{footer, length[:], []byte(magic)} {
+		if err := pw.write(b); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (pw *Writer) write(b []byte) error {
+	n, err := pw.w.Write(b)
+	pw.offset += int64(n)
+	return err
+}
+
+// flush writes the pending rows as a row group
+func (pw *Writer) flush() error {
+	if len(pw.pending) == 0 {
+		return nil
+	}
+
+	var chunks []any
+	var groupSize int64
+	for i, c := range pw.columns {
+		page := encodePage(c, pw.pending, i)
+		var compressed bytes.Buffer
+		zw := gzip.NewWriter(&compressed)
+		if _, err := zw.Write(page); err != nil {
+			return err
+		}
+		if err := zw.Close(); err != nil {
+			return err
+		}
+		header := encodeStruct(tstruct{
+			{1, int32(pageTypeData)},
+			{2, int32(len(page))},
+			{3, int32(compressed.Len())},
+			{5, tstruct{
+				{1, int32(len(pw.pending))},
+				{2, int32(encodingPlain)},
+				{3, int32(encodingRLE)},
+				{4, int32(encodingRLE)},
+			}},
+		})
+
+		pageOffset := pw.offset
+		if err := pw.write(header); err != nil {
+			return err
+		}
+		if err := pw.write(compressed.Bytes()); err != nil {
+			return err
+		}
+
+		physical, _ := c.physical()
+		uncompressedSize := int64(len(header) + len(page))
+		groupSize += uncompressedSize
+		chunks = append(chunks, tstruct{
+			{2, pageOffset},
+			{3, tstruct{
+				{1, physical},
+				{2, tlist{tI32, []any{int32(encodingPlain), int32(encodingRLE)}}},
+				{3, tlist{tBinary, []any{c.Name}}},
+				{4, int32(codecGzip)},
+				{5, int64(len(pw.pending))},
+				{6, uncompressedSize},
+				{7, pw.offset - pageOffset},
+				{9, pageOffset},
+			}},
+		})
+	}
+
+	pw.groups = append(pw.groups, tstruct{
+		{1, tlist{tStruct, chunks}},
+		{2, groupSize},
+		{3, int64(len(pw.pending))},
+	})
+	pw.rows += int64(len(pw.pending))
+	pw.pending = pw.pending[:0]
+	return nil
+}
+
+func valid(typ Type, v any) bool {
+	if v == nil {
+		return true
+	}
+	switch typ {
+	case Int32:
+		_, ok := v.(int32)
+		return ok
+	case Timestamp:
+		_, ok := v.(time.Time)
+		return ok
+	case Int64:
+		_, ok := v.(int64)
+		return ok
+	default:
+		_, ok := v.(string)
+		return ok
+	}
+}
+
+// encodePage builds the uncompressed data page of column i: definition
+// levels as bit-packed runs, then the PLAIN values that aren't NULL
+func encodePage(c Column, rows [][]any, i int) []byte {
+	levels := make([]byte, (len(rows)+7)/8)
+	var values bytes.Buffer
+	var scratch [8]byte
+	for r, row := range rows {
+		v := row[i]
+		if v == nil {
+			continue
+		}
+		levels[r/8] |= 1 << (r % 8)
+		switch c.Type {
+		case Int32:
+			binary.LittleEndian.PutUint32(scratch[:4], uint32(v.(int32)))
+			values.Write(scratch[:4])
+		case Timestamp:
+			binary.LittleEndian.PutUint64(scratch[:], uint64(v.(time.Time).UnixMicro()))
+			values.Write(scratch[:])
+		case Int64:
+			binary.LittleEndian.PutUint64(scratch[:], uint64(v.(int64)))
+			values.Write(scratch[:])
+		default:
+			s := v.(string)
+			binary.LittleEndian.PutUint32(scratch[:4], uint32(len(s)))
+			values.Write(scratch[:4])
+			values.WriteString(s)
+		}
+	}
+
+	var rle bytes.Buffer
+	writeVarint(&rle, uint64(len(levels))<<1|1)
+	rle.Write(levels)
+
+	var page bytes.Buffer
+	binary.LittleEndian.PutUint32(scratch[:4], uint32(rle.Len()))
+	page.Write(scratch[:4])
+	page.Write(rle.Bytes())
+	page.Write(values.Bytes())
+	return page.Bytes()
+}
+
+// Reader reads a Parquet file written by Writer
+type Reader struct {
+	r       io.ReaderAt
+	columns []Column
+	groups  []rowGroup
+	rows    int64
+}
+
+type rowGroup struct {
+	rows   int64
+	chunks []columnChunk
+}
+
+type columnChunk struct {
+	codec  int64
+	values int64
+	offset int64
+	size   int64
+}
+
+// NewReader reads the footer of the size-byte Parquet file in r
+func NewReader(r io.ReaderAt, size int64) (*Reader, error) {
+	if size < int64(2*len(magic)+4) {
+		return nil, errors.New("parquet: file too short")
+	}
+	tail := make([]byte, 4+len(magic))
+	if _, err := r.ReadAt(tail, size-int64(len(tail))); err != nil {
+		return nil, err
+	}
+	if string(tail[4:]) != magic {
+		return nil, errors.New("parquet: not a parquet file")
+	}
+	footerLen := int64(binary.LittleEndian.Uint32(tail[:4]))
+	if footerLen > size-int64(len(tail)+len(magic)) {
+		return nil, errThrift
+	}
+	footer := make([]byte, footerLen)
+	if _, err := r.ReadAt(footer, size-int64(len(tail))-footerLen); err != nil {
+		return nil, err
+	}
+	meta, err := thriftReader{bytes.NewReader(footer)}.readStruct()
+	if err != nil {
+		return nil, err
+	}
+
+	pr := &Reader{r: r, rows: fieldInt(meta, 3)}
+	schema := fieldList(meta, 2)
+	if len(schema) == 0 {
+		return nil, errThrift
+	}
+	for _, item := range schema[1:] {
+		element, _ := item.(map[int16]any)
+		if _, nested := element[5]; nested {
+			return nil, errors.New("parquet: nested columns are not supported")
+		}
+		_, hasConverted := element[6]
+		typ, err := columnType(fieldInt(element, 1), fieldInt(element, 6), hasConverted)
+		if err != nil {
+			return nil, err
+		}
+		pr.columns = append(pr.columns, Column{Name: fieldString(element, 4), Type: typ})
+	}
+
+	for _, item := range fieldList(meta, 4) {
+		group, _ := item.(map[int16]any)
+		rg := rowGroup{rows: fieldInt(group, 3)}
+		for _, c := range fieldList(group, 1) {
+			chunk, _ := c.(map[int16]any)
+			cm := fieldStruct(chunk, 3)
+			rg.chunks = append(rg.chunks, columnChunk{
+				codec:  fieldInt(cm, 4),
+				values: fieldInt(cm, 5),
+				offset: fieldInt(cm, 9),
+				size:   fieldInt(cm, 7),
+			})
+		}
+		if len(rg.chunks) != len(pr.columns) {
+			return nil, errThrift
+		}
+		pr.groups = append(pr.groups, rg)
+	}
+	return pr, nil
+}
+
+// Columns returns the file's schema
+func (pr *Reader) Columns() []Column {
+	return pr.columns
+}
+
+// NumRows returns the number of rows in the file
+func (pr *Reader) NumRows() int64 {
+	return pr.rows
+}
+
+// Each calls fn for every row, in order. The row slice is reused between
+// calls. An error from fn stops the iteration and is returned.
+func (pr *Reader) Each(fn func(row []any) error) error {
+	row := make([]any, len(pr.columns))
+	for _, rg := range pr.groups {
+		columns := make([][]any, len(pr.columns))
+		for i, chunk := range rg.chunks {
+			values, err := pr.readChunk(pr.columns[i], chunk)
+			if err != nil {
+				return fmt.Errorf("parquet: column %s: %w", pr.columns[i].Name, err)
+			}
+			if int64(len(values)) != rg.rows {
+				return fmt.Errorf("parquet: column %s has %d values for %d rows", pr.columns[i].Name, len(values), rg.rows)
+			}
+			columns[i] = values
+		}
+		for r := range rg.rows {
+			for i := range columns {
+				row[i] = columns[i][r]
+			}
+			if err := fn(row); err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+func (pr *Reader) readChunk(c Column, chunk columnChunk) ([]any, error) {
+	if chunk.size < 0 || chunk.size > 1<<31 {
+		return nil, errThrift
+	}
+	buf := make([]byte, chunk.size)
+	if _, err := pr.r.ReadAt(buf, chunk.offset); err != nil {
+		return nil, err
+	}
+	in := bytes.NewReader(buf)
+
+	values := make([]any, 0, chunk.values)
+	for int64(len(values)) < chunk.values {
+		header, err := thriftReader{in}.readStruct()
+		if err != nil {
+			return nil, err
+		}
+		if fieldInt(header, 1) != pageTypeData {
+			return nil, fmt.Errorf("unsupported page type %d", fieldInt(header, 1))
+		}
+		data := fieldStruct(header, 5)
+		if fieldInt(data, 2) != encodingPlain {
+			return nil, fmt.Errorf("unsupported encoding %d", fieldInt(data, 2))
+		}
+		compressed := make([]byte, fieldInt(header, 3))
+		if _, err := io.ReadFull(in, compressed); err != nil {
+			return nil, errThrift
+		}
+
+		page := compressed
+		switch chunk.codec {
+		case codecUncompressed:
+		case codecGzip:
+			zr, err := gzip.NewReader(bytes.NewReader(compressed))
+			if err != nil {
+				return nil, err
+			}
+			if page, err = io.ReadAll(zr); err != nil {
+				return nil, err
+			}
+		default:
+			return nil, fmt.Errorf("unsupported codec %d", chunk.codec)
+		}
+
+		if values, err = decodePage(c, page, int(fieldInt(data, 1)), values); err != nil {
+			return nil, err
+		}
+	}
+	return values, nil
+}
+
+// decodePage appends the n values of a data page to values
+func decodePage(c Column, page []byte, n int, values []any) ([]any, error) {
+	if len(page) < 4 {
+		return nil, errThrift
+	}
+	levelsLen := int(binary.LittleEndian.Uint32(page))
+	if levelsLen > len(page)-4 {
+		return nil, errThrift
+	}
+	defined, err := decodeLevels(page[4:4+levelsLen], n)
+	if err != nil {
+		return nil, err
+	}
+
+	plain := page[4+levelsLen:]
+	for _, ok := range defined {
+		if !ok {
+			values = append(values, nil)
+			continue
+		}
+		switch c.Type {
+		case Int32:
+			if len(plain) < 4 {
+				return nil, errThrift
+			}
+			values = append(values, int32(binary.LittleEndian.Uint32(plain)))
+			plain = plain[4:]
+		case Timestamp:
+			if len(plain) < 8 {
+				return nil, errThrift
+			}
+			values = append(values, time.UnixMicro(int64(binary.LittleEndian.Uint64(plain))).UTC())
+			plain = plain[8:]
+		case Int64:
+			if len(plain) < 8 {
+				return nil, errThrift
+			}
+			values = append(values, int64(binary.LittleEndian.Uint64(plain)))
+			plain = plain[8:]
+		default:
+			if len(plain) < 4 {
+				return nil, errThrift
+			}
+			size := int(binary.LittleEndian.Uint32(plain))
+			if size > len(plain)-4 {
+				return nil, errThrift
+			}
+			values = append(values, string(plain[4:4+size]))
+			plain = plain[4+size:]
+		}
+	}
+	return values, nil
+}
+
+// decodeLevels decodes n definition levels of bit width 1 from the
+// RLE/bit-packed hybrid encoding
+func decodeLevels(data []byte, n int) ([]bool, error) {
+	in := bytes.NewReader(data)
+	levels := make([]bool, 0, n)
+	for len(levels) < n {
+		header, err := binary.ReadUvarint(in)
+		if err != nil {
+			return nil, errThrift
+		}
+		if header&1 == 1 {
+			// Bit-packed groups of 8 levels, one byte each at width 1
+			for range header >> 1 {
+				b, err := in.ReadByte()
+				if err != nil {
+					return nil, errThrift
+				}
+				for bit := 0; bit < 8 && len(levels) < n; bit++ {
+					levels = append(levels, b&(1<<bit) != 0)
+				}
+			}
+			continue
+		}
+		value, err := in.ReadByte()
+		if err != nil {
+			return nil, errThrift
+		}
+		for run := header >> 1; run > 0 && len(levels) < n; run-- {
+			levels = append(levels, value != 0)
+		}
+	}
+	return levels, nil
+}
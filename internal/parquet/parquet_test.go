@@ -0,0 +1,93 @@
+package parquet
+
+import (
+	"bytes"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+var testColumns = []Column{
+	{Name: "url_path", Type: String},
+	{Name: "props", Type: JSON},
+	{Name: "scroll_depth", Type: Int32},
+	{Name: "created_at", Type: Timestamp},
+	{Name: "hits", Type: Int64},
+}
+
+func TestWriterRoundTrip(t *testing.T) {
+	at := time.Date(2025, time.January, 31, 23, 59, 59, 123456000, time.UTC)
+	rows := [][]any{
+		{"/", `{"plan":"pro"}`, int32(80), at, int64(1) << 40},
+		{nil, nil, nil, nil, nil},
+		{"/pricing", nil, int32(-1), at.Add(time.Hour), int64(-3)},
+	}
+	for i := range 20 {
+		rows = append(rows, []any{"/blog", nil, int32(i), at, int64(i)})
+	}
+
+	var buf bytes.Buffer
+	w, err := NewWriter(&buf, testColumns)
+	require.NoError(t, err)
+	w.RowGroupSize = 7 // several row groups and partial level bytes
+	for _, row := range rows {
+		require.NoError(t, w.Write(append([]any(nil), row...)))
+	}
+	assert.Equal(t, int64(len(rows)), w.Rows())
+	require.NoError(t, w.Close())
+
+	data := buf.Bytes()
+	assert.Equal(t, "PAR1", string(data[:4]))
+	assert.Equal(t, "PAR1", string(data[len(data)-4:]))
+
+	r, err := NewReader(bytes.NewReader(data), int64(len(data)))
+	require.NoError(t, err)
+	assert.Equal(t, testColumns, r.Columns())
+	assert.Equal(t, int64(len(rows)), r.NumRows())
+
+	var got [][]any
+	require.NoError(t, r.Each(func(row []any) error {
+		got = append(got, append([]any(nil), row...))
+		return nil
+	}))
+	assert.Equal(t, rows, got)
+}
+
+func TestWriterRejectsMismatchedRows(t *testing.T) {
+	w, err := NewWriter(&bytes.Buffer{}, testColumns)
+	require.NoError(t, err)
+
+	assert.ErrorContains(t, w.Write([]any{"/"}), "1 values for 5 columns")
+	assert.ErrorContains(t, w.Write([]any{"/", nil, 80, nil, nil}), "scroll_depth can't hold int")
+}
+
+func TestNewReaderRejectsOtherFiles(t *testing.T) {
+	data := []byte("id,path\n1,/\n")
+	_, err := NewReader(bytes.NewReader(data), int64(len(data)))
+	assert.ErrorContains(t, err, "not a parquet file")
+}
+
+func TestDecodeLevelsHandlesRLERuns(t *testing.T) {
+	// A run of 3 defined levels, then a bit-packed group 0b00000101
+	levels, err := decodeLevels([]byte{3 << 1, 1, 1<<1 | 1, 0b101}, 6)
+	require.NoError(t, err)
+	assert.Equal(t, []bool{true, true, true, true, false, true}, levels)
+}
+
+func TestThriftRoundTrip(t *testing.T) {
+	encoded := encodeStruct(tstruct{
+		{1, int32(-5)},
+		{2, tlist{tBinary, []any{"a", "b"}}},
+		{20, int64(1 << 40)},
+		{21, tstruct{{1, "nested"}}},
+	})
+
+	decoded, err := thriftReader{bytes.NewReader(encoded)}.readStruct()
+	require.NoError(t, err)
+	assert.Equal(t, int64(-5), fieldInt(decoded, 1))
+	assert.Equal(t, []any{[]byte("a"), []byte("b")}, fieldList(decoded, 2))
+	assert.Equal(t, int64(1<<40), fieldInt(decoded, 20))
+	assert.Equal(t, "nested", fieldString(fieldStruct(decoded, 21), 1))
+}
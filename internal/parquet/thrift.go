@@ -0,0 +1,270 @@
+package parquet
+
+import (
+	"bytes"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+	"math"
+)
+
+// Parquet metadata is serialized with the Thrift compact protocol. Only the
+// subset the file format needs is implemented: structs are written from
+// field lists and read back generically into maps keyed by field id.
+
+const (
+	tBoolTrue  = 1
+	tBoolFalse = 2
+	tByte      = 3
+	tI16       = 4
+	tI32       = 5
+	tI64       = 6
+	tDouble    = 7
+	tBinary    = 8
+	tList      = 9
+	tSet       = 10
+	tMap       = 11
+	tStruct    = 12
+)
+
+var errThrift = errors.New("parquet: malformed metadata")
+
+// tstruct is a Thrift struct to encode, with fields in increasing id order
+type tstruct []tfield
+
+type tfield struct {
+	id    int16
+	value any // int32, int64, string, tlist or tstruct
+}
+
+// tlist is a Thrift list of elemType values
+type tlist struct {
+	elemType byte
+	items    []any
+}
+
+func thriftType(v any) byte {
+	switch v.(type) {
+	case int32:
+		return tI32
+	case int64:
+		return tI64
+	case string:
+		return tBinary
+	case tlist:
+		return tList
+	case tstruct:
+		return tStruct
+	}
+	panic(fmt.Sprintf("parquet: no thrift type for %T", v))
+}
+
+func encodeStruct(s tstruct) []byte {
+	var b bytes.Buffer
+	writeStruct(&b, s)
+	return b.Bytes()
+}
+
+func writeStruct(b *bytes.Buffer, s tstruct) {
+	var last int16
+	for _, f := range s {
+		typ := thriftType(f.value)
+		if delta := f.id - last; delta > 0 && delta <= 15 {
+			b.WriteByte(byte(delta)<<4 | typ)
+		} else {
+			b.WriteByte(typ)
+			writeVarint(b, zigzag(int64(f.id)))
+		}
+		last = f.id
+		writeValue(b, f.value)
+	}
+	b.WriteByte(0)
+}
+
+func writeValue(b *bytes.Buffer, v any) {
+	switch v := v.(type) {
+	case int32:
+		writeVarint(b, zigzag(int64(v)))
+	case int64:
+		writeVarint(b, zigzag(v))
+	case string:
+		writeVarint(b, uint64(len(v)))
+		b.WriteString(v)
+	case tlist:
+		if len(v.items) < 15 {
+			b.WriteByte(byte(len(v.items))<<4 | v.elemType)
+		} else {
+			b.WriteByte(0xf0 | v.elemType)
+			writeVarint(b, uint64(len(v.items)))
+		}
+		for _, item := range v.items {
+			writeValue(b, item)
+		}
+	case tstruct:
+		writeStruct(b, v)
+	}
+}
+
+func writeVarint(b *bytes.Buffer, v uint64) {
+	var buf [binary.MaxVarintLen64]byte
+	b.Write(buf[:binary.PutUvarint(buf[:], v)])
+}
+
+func zigzag(v int64) uint64 {
+	return uint64(v<<1) ^ uint64(v>>63)
+}
+
+func unzigzag(v uint64) int64 {
+	return int64(v>>1) ^ -int64(v&1)
+}
+
+// thriftReader decodes compact protocol values. Integers of any width come
+// back as int64, binaries as []byte, lists as []any and structs as
+// map[int16]any; maps are skipped.
+type thriftReader struct {
+	r *bytes.Reader
+}
+
+func (t thriftReader) readStruct() (map[int16]any, error) {
+	fields := make(map[int16]any)
+	var last int16
+	for {
+		header, err := t.r.ReadByte()
+		if err != nil {
+			return nil, errThrift
+		}
+		if header == 0 {
+			return fields, nil
+		}
+		typ := header & 0x0f
+		id := last + int16(header>>4)
+		if header>>4 == 0 {
+			v, err := binary.ReadUvarint(t.r)
+			if err != nil {
+				return nil, errThrift
+			}
+			id = int16(unzigzag(v))
+		}
+		last = id
+
+		var value any
+		switch typ {
+		case tBoolTrue:
+			value = true
+		case tBoolFalse:
+			value = false
+		default:
+			if value, err = t.readValue(typ); err != nil {
+				return nil, err
+			}
+		}
+		fields[id] = value
+	}
+}
+
+func (t thriftReader) readValue(typ byte) (any, error) {
+	switch typ {
+	case tBoolTrue, tBoolFalse:
+		// Inside lists each bool takes a byte
+		b, err := t.r.ReadByte()
+		return b == tBoolTrue, err
+	case tByte:
+		b, err := t.r.ReadByte()
+		return int64(int8(b)), err
+	case tI16, tI32, tI64:
+		v, err := binary.ReadUvarint(t.r)
+		if err != nil {
+			return nil, errThrift
+		}
+		return unzigzag(v), nil
+	case tDouble:
+		var buf [8]byte
+		if _, err := io.ReadFull(t.r, buf[:]); err != nil {
+			return nil, errThrift
+		}
+		return math.Float64frombits(binary.LittleEndian.Uint64(buf[:])), nil
+	case tBinary:
+		n, err := t.readSize()
+		if err != nil {
+			return nil, err
+		}
+		buf := make([]byte, n)
+		if _, err := io.ReadFull(t.r, buf); err != nil {
+			return nil, errThrift
+		}
+		return buf, nil
+	case tList, tSet:
+		header, err := t.r.ReadByte()
+		if err != nil {
+			return nil, errThrift
+		}
+		n := int(header >> 4)
+		if n == 15 {
+			if n, err = t.readSize(); err != nil {
+				return nil, err
+			}
+		}
+		items := make([]any, 0, min(n, t.r.Len()))
+		for range n {
+			item, err := t.readValue(header & 0x0f)
+			if err != nil {
+				return nil, err
+			}
+			items = append(items, item)
+		}
+		return items, nil
+	case tMap:
+		n, err := t.readSize()
+		if err != nil || n == 0 {
+			return nil, err
+		}
+		types, err := t.r.ReadByte()
+		if err != nil {
+			return nil, errThrift
+		}
+		for range n {
+			if _, err := t.readValue(types >> 4); err != nil {
+				return nil, err
+			}
+			if _, err := t.readValue(types & 0x0f); err != nil {
+				return nil, err
+			}
+		}
+		return nil, nil
+	case tStruct:
+		return t.readStruct()
+	}
+	return nil, errThrift
+}
+
+// readSize reads a length, rejecting ones longer than the remaining input
+func (t thriftReader) readSize() (int, error) {
+	n, err := binary.ReadUvarint(t.r)
+	if err != nil || n > uint64(t.r.Len()) {
+		return 0, errThrift
+	}
+	return int(n), nil
+}
+
+// Accessors for decoded structs; missing or mistyped fields read as zero
+
+func fieldInt(s map[int16]any, id int16) int64 {
+	v, _ := s[id].(int64)
+	return v
+}
+
+func fieldString(s map[int16]any, id int16) string {
+	v, _ := s[id].([]byte)
+	return string(v)
+}
+
+func fieldStruct(s map[int16]any, id int16) map[int16]any {
+	v, _ := s[id].(map[int16]any)
+	return v
+}
+
+func fieldList(s map[int16]any, id int16) []any {
+	v, _ := s[id].([]any)
+	return v
+}
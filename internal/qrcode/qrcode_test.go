@@ -0,0 +1,83 @@
+package qrcode
+
+import (
+	"strconv"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestReedSolomonRemainder(t *testing.T) {
+	// "HELLO WORLD" 1-M data codewords and their error correction codewords
+	data := []byte{32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17}
+	want := []byte{196, 35, 39, 119, 235, 215, 231, 226, 93, 23}
+	assert.Equal(t, want, reedSolomonRemainder(data, reedSolomonDivisor(10)))
+}
+
+func TestEncodeMatrix(t *testing.T) {
+	want := []string{
+		"#######.....#.#######",
+		"#.....#..#.#..#.....#",
+		"#.###.#.###.#.#.###.#",
+		"#.###.#.#.#.#.#.###.#",
+		"#.###.#.#.#.#.#.###.#",
+		"#.....#.##.#..#.....#",
+		"#######.#.#.#.#######",
+		"........#.#..........",
+		"#.#####...##..#####..",
+		".##....#.#######.##..",
+		"#.##..##....###..###.",
+		".###.#..######..###..",
+		"#...#.##.##.##....#.#",
+		"........###.#....#...",
+		"#######..#.#..#...##.",
+		"#.....#.###..#.#.####",
+		"#.###.#.#..#...#..#.#",
+		"#.###.#.#...######...",
+		"#.###.#.##..#..#..#..",
+		"#.....#...#.##..###..",
+		"#######.#.###...#.##.",
+	}
+
+	code, err := encode([]byte("HELLO WORLD"), 2)
+	require.NoError(t, err)
+	require.Equal(t, 1, code.Version)
+	require.Equal(t, len(want), code.Size)
+	for y, row := range want {
+		var got strings.Builder
+		for x := range code.Size {
+			if code.Dark(x, y) {
+				got.WriteByte('#')
+			} else {
+				got.WriteByte('.')
+			}
+		}
+		assert.Equal(t, row, got.String(), "row %d", y)
+	}
+}
+
+func TestEncodeVersions(t *testing.T) {
+	for length, version := range map[int]int{1: 1, 14: 1, 15: 2, 100: 6, 122: 7, 123: 8, 213: 10} {
+		code, err := Encode([]byte(strings.Repeat("k", length)))
+		require.NoError(t, err, length)
+		assert.Equal(t, version, code.Version, length)
+		assert.Equal(t, version*4+17, code.Size, length)
+	}
+
+	_, err := Encode([]byte(strings.Repeat("k", 214)))
+	assert.ErrorIs(t, err, ErrTooLong)
+}
+
+func TestSVG(t *testing.T) {
+	code, err := Encode([]byte("otpauth://totp/Kaunta:admin?secret=JBSWY3DPEHPK3PXP&issuer=Kaunta"))
+	require.NoError(t, err)
+
+	svg := string(code.SVG(200))
+	assert.True(t, strings.HasPrefix(svg, `<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200"`))
+	dim := code.Size + 2*quietZone
+	assert.Contains(t, svg, `viewBox="0 0 `+strconv.Itoa(dim)+" "+strconv.Itoa(dim)+`"`)
+	// Top-left corner of the first finder pattern
+	assert.Contains(t, svg, `d="M4 4h1v1h-1z`)
+}
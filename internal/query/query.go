@@ -0,0 +1,447 @@
+// Package query compiles ad-hoc aggregation requests (metrics grouped by
+// dimensions, narrowed by filters) into a single parameterized SQL query over
+// the raw events. Every identifier comes from the whitelists below; user
+// values only ever travel as bind parameters.
+package query
+
+import (
+	"database/sql"
+	"encoding/json"
+	"fmt"
+	"maps"
+	"slices"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+)
+
+const (
+	// DefaultDays is the period used when a request does not set one
+	DefaultDays = 7
+	// DefaultLimit is the row limit used when a request does not set one
+	DefaultLimit = 100
+	// MaxLimit caps the rows one request may return
+	MaxLimit = 10000
+
+	maxDays        = 365
+	maxDimensions  = 4
+	maxFilters     = 20
+	maxPropertyKey = 128
+
+	// PropertyPrefix marks a custom event property used as a dimension or
+	// filter, e.g. "prop:plan"
+	PropertyPrefix = "prop:"
+)
+
+// Request describes an ad-hoc aggregation
+type Request struct {
+	Metrics     []string   `json:"metrics" validate:"required"`
+	Dimensions  []string   `json:"dimensions,omitempty"`
+	Filters     []Filter   `json:"filters,omitempty"`
+	Granularity string     `json:"granularity,omitempty"` // Adds a "time" column bucketed by minute, hour, day, week or month
+	Days        int        `json:"days,omitempty"`        // Period ending now, 1-365 (default 7)
+	From        *time.Time `json:"from,omitempty"`        // Explicit period start; set with to instead of days
+	To          *time.Time `json:"to,omitempty"`          // Explicit period end (exclusive)
+	OrderBy     []Order    `json:"order_by,omitempty"`    // Default: time ascending, else the first metric descending
+	Limit       int        `json:"limit,omitempty"`       // Max rows, up to 10000 (default 100)
+}
+
+// Filter narrows the events to those whose dimension matches one of values.
+// Filters on a custom event property ("prop:plan") with is or is_not are
+// answered by the GIN index on the properties.
+type Filter struct {
+	Dimension string   `json:"dimension" validate:"required"`
+	Operator  string   `json:"operator,omitempty"` // is (default), is_not or contains
+	Values    []string `json:"values" validate:"required"`
+}
+
+// Order sorts the result by a requested metric, dimension or time
+type Order struct {
+	Field     string `json:"field" validate:"required"`
+	Direction string `json:"direction,omitempty"` // asc or desc (default)
+}
+
+// Result is the tabular response; each row holds one value per column
+type Result struct {
+	Columns []string `json:"columns"`
+	Rows    [][]any  `json:"rows"`
+}
+
+type metric struct {
+	expr    string
+	decimal bool
+}
+
+// metrics are the aggregates a request can select
+var metrics = map[string]metric{
+	"pageviews":           {expr: "COUNT(*) FILTER (WHERE e.event_type = 1)"},
+	"visitors":            {expr: "COUNT(DISTINCT e.session_id)"},
+	"visits":              {expr: "COUNT(DISTINCT e.visit_id)"},
+	"events":              {expr: "COUNT(*) FILTER (WHERE e.event_type = 2)"},
+	"avg_engagement_time": {expr: "ROUND(AVG(e.engagement_time) FILTER (WHERE e.event_type = 1))::FLOAT8", decimal: true},
+	"avg_scroll_depth":    {expr: "ROUND(AVG(e.scroll_depth) FILTER (WHERE e.event_type = 1))::FLOAT8", decimal: true},
+}
+
+type dimension struct {
+	expr    string
+	session bool // read from the session table
+}
+
+// dimensions are the columns a request can group and filter by
+var dimensions = map[string]dimension{
+	"page":            {expr: "e.url_path"},
+	"page_title":      {expr: "e.page_title"},
+	"hostname":        {expr: "e.hostname"},
+	"referrer":        {expr: "e.referrer_domain"},
+	"event":           {expr: "e.event_name"},
+	"utm_source":      {expr: "e.utm_source"},
+	"utm_medium":      {expr: "e.utm_medium"},
+	"utm_campaign":    {expr: "e.utm_campaign"},
+	"utm_term":        {expr: "e.utm_term"},
+	"utm_content":     {expr: "e.utm_content"},
+	"country":         {expr: "s.country", session: true},
+	"region":          {expr: "s.region", session: true},
+	"city":            {expr: "s.city", session: true},
+	"browser":         {expr: "s.browser", session: true},
+	"browser_version": {expr: "s.browser_version", session: true},
+	"os":              {expr: "s.os", session: true},
+	"device":          {expr: "s.device", session: true},
+	"screen":          {expr: "screen_class(s.screen)", session: true},
+	"language":        {expr: "s.language", session: true},
+	"entry_page":      {expr: "s.entry_page", session: true},
+	"exit_page":       {expr: "s.exit_page", session: true},
+}
+
+// granularities are the time buckets a request can group by
+var granularities = []string{"minute", "hour", "day", "week", "month"}
+
+// Metrics lists the metric names a request can select
+func Metrics() []string {
+	return slices.Sorted(maps.Keys(metrics))
+}
+
+// Dimensions lists the dimension names a request can group and filter by
+func Dimensions() []string {
+	return slices.Sorted(maps.Keys(dimensions))
+}
+
+// Granularities lists the supported time buckets
+func Granularities() []string {
+	return slices.Clone(granularities)
+}
+
+// Query is a compiled request
+type Query struct {
+	SQL     string
+	Args    []any
+	Columns []string
+	kinds   []columnKind
+}
+
+type columnKind int
+
+const (
+	kindText columnKind = iota
+	kindTime
+	kindCount
+	kindDecimal
+)
+
+// Compile validates req and builds the SQL for websiteID
+func Compile(websiteID uuid.UUID, req Request) (*Query, error) {
+	if len(req.Metrics) == 0 {
+		return nil, fmt.Errorf("at least one metric is required")
+	}
+	if len(req.Dimensions) > maxDimensions {
+		return nil, fmt.Errorf("at most %d dimensions per query", maxDimensions)
+	}
+	if len(req.Filters) > maxFilters {
+		return nil, fmt.Errorf("at most %d filters per query", maxFilters)
+	}
+
+	limit := req.Limit
+	if limit == 0 {
+		limit = DefaultLimit
+	}
+	if limit < 1 || limit > MaxLimit {
+		return nil, fmt.Errorf("limit must be between 1 and %d", MaxLimit)
+	}
+
+	q := &Query{Args: []any{websiteID}}
+	where, err := q.periodClauses(req)
+	if err != nil {
+		return nil, err
+	}
+	var selects, groups []string
+	joinSession := false
+
+	if req.Granularity != "" {
+		if !slices.Contains(granularities, req.Granularity) {
+			return nil, fmt.Errorf("unknown granularity %q", req.Granularity)
+		}
+		selects = append(selects, fmt.Sprintf("DATE_TRUNC('%s', e.created_at) AS \"time\"", req.Granularity))
+		q.addColumn("time", kindTime)
+	}
+	for _, name := range req.Dimensions {
+		dim, ok := q.dimension(name)
+		if !ok {
+			return nil, fmt.Errorf("unknown dimension %q", name)
+		}
+		if slices.Contains(q.Columns, name) {
+			return nil, fmt.Errorf("dimension %q requested twice", name)
+		}
+		joinSession = joinSession || dim.session
+		selects = append(selects, fmt.Sprintf("%s::TEXT AS %s", dim.expr, pq.QuoteIdentifier(name)))
+		q.addColumn(name, kindText)
+	}
+	for i := range selects {
+		groups = append(groups, fmt.Sprint(i+1))
+	}
+	for _, name := range req.Metrics {
+		m, ok := metrics[name]
+		if !ok {
+			return nil, fmt.Errorf("unknown metric %q", name)
+		}
+		if slices.Contains(q.Columns, name) {
+			return nil, fmt.Errorf("metric %q requested twice", name)
+		}
+		selects = append(selects, fmt.Sprintf("%s AS %s", m.expr, pq.QuoteIdentifier(name)))
+		if m.decimal {
+			q.addColumn(name, kindDecimal)
+		} else {
+			q.addColumn(name, kindCount)
+		}
+	}
+
+	for _, f := range req.Filters {
+		clause, err := q.filterClause(f)
+		if err != nil {
+			return nil, err
+		}
+		joinSession = joinSession || dimensions[f.Dimension].session
+		where = append(where, clause)
+	}
+
+	order, err := q.orderClause(req)
+	if err != nil {
+		return nil, err
+	}
+
+	var b strings.Builder
+	fmt.Fprintf(&b, "SELECT %s\nFROM website_event e\n", strings.Join(selects, ", "))
+	if joinSession {
+		b.WriteString("JOIN session s ON s.session_id = e.session_id\n")
+	}
+	fmt.Fprintf(&b, "WHERE %s\n", strings.Join(where, "\n  AND "))
+	if len(groups) > 0 {
+		fmt.Fprintf(&b, "GROUP BY %s\n", strings.Join(groups, ", "))
+	}
+	q.Args = append(q.Args, limit)
+	fmt.Fprintf(&b, "ORDER BY %s\nLIMIT $%d", order, len(q.Args))
+	q.SQL = b.String()
+	return q, nil
+}
+
+func (q *Query) addColumn(name string, kind columnKind) {
+	q.Columns = append(q.Columns, name)
+	q.kinds = append(q.kinds, kind)
+}
+
+// periodClauses binds the website and period, either the last days or an
+// explicit from/to range
+func (q *Query) periodClauses(req Request) ([]string, error) {
+	if req.From != nil || req.To != nil {
+		if req.From == nil || req.To == nil {
+			return nil, fmt.Errorf("from and to must be set together")
+		}
+		if req.Days != 0 {
+			return nil, fmt.Errorf("days cannot be combined with from and to")
+		}
+		if !req.To.After(*req.From) {
+			return nil, fmt.Errorf("to must be after from")
+		}
+		if req.To.Sub(*req.From) > maxDays*24*time.Hour {
+			return nil, fmt.Errorf("period must be at most %d days", maxDays)
+		}
+		q.Args = append(q.Args, *req.From, *req.To)
+		return []string{"e.website_id = $1", "e.created_at >= $2", "e.created_at < $3"}, nil
+	}
+
+	days := req.Days
+	if days == 0 {
+		days = DefaultDays
+	}
+	if days < 1 || days > maxDays {
+		return nil, fmt.Errorf("days must be between 1 and %d", maxDays)
+	}
+	q.Args = append(q.Args, days)
+	return []string{"e.website_id = $1", "e.created_at >= NOW() - ($2 || ' days')::INTERVAL"}, nil
+}
+
+// dimension resolves a dimension name; a custom event property reads its
+// key from a bind parameter
+func (q *Query) dimension(name string) (dimension, bool) {
+	if key, ok := propertyKey(name); ok {
+		q.Args = append(q.Args, key)
+		return dimension{expr: fmt.Sprintf("(e.props->>$%d::TEXT)", len(q.Args))}, true
+	}
+	dim, ok := dimensions[name]
+	return dim, ok
+}
+
+// propertyKey returns the property key of a "prop:<key>" dimension
+func propertyKey(name string) (string, bool) {
+	key, ok := strings.CutPrefix(name, PropertyPrefix)
+	return key, ok && key != "" && len(key) <= maxPropertyKey
+}
+
+func (q *Query) filterClause(f Filter) (string, error) {
+	_, isProperty := propertyKey(f.Dimension)
+	if _, ok := dimensions[f.Dimension]; !ok && !isProperty {
+		return "", fmt.Errorf("unknown filter dimension %q", f.Dimension)
+	}
+	if len(f.Values) == 0 {
+		return "", fmt.Errorf("filter on %q needs at least one value", f.Dimension)
+	}
+	if isProperty && f.Operator != "contains" {
+		return q.propertyMatchClause(f)
+	}
+	dim, _ := q.dimension(f.Dimension)
+
+	switch f.Operator {
+	case "", "is":
+		q.Args = append(q.Args, pq.Array(f.Values))
+		return fmt.Sprintf("%s::TEXT = ANY($%d::TEXT[])", dim.expr, len(q.Args)), nil
+	case "is_not":
+		q.Args = append(q.Args, pq.Array(f.Values))
+		return fmt.Sprintf("(%s IS NULL OR %s::TEXT <> ALL($%d::TEXT[]))", dim.expr, dim.expr, len(q.Args)), nil
+	case "contains":
+		patterns := make([]string, len(f.Values))
+		for i, v := range f.Values {
+			patterns[i] = "%" + escapeLike(v) + "%"
+		}
+		q.Args = append(q.Args, pq.Array(patterns))
+		return fmt.Sprintf("%s::TEXT ILIKE ANY($%d::TEXT[])", dim.expr, len(q.Args)), nil
+	default:
+		return "", fmt.Errorf("unknown filter operator %q", f.Operator)
+	}
+}
+
+// propertyMatchClause compiles is and is_not on a custom event property to
+// JSONB containment, which the props GIN index (jsonb_path_ops) can answer,
+// instead of extracting the value from every event of the period
+func (q *Query) propertyMatchClause(f Filter) (string, error) {
+	key, _ := propertyKey(f.Dimension)
+	var matches []string
+	for _, v := range f.Values {
+		for _, doc := range propertyDocuments(key, v) {
+			q.Args = append(q.Args, doc)
+			matches = append(matches, fmt.Sprintf("e.props @> $%d::JSONB", len(q.Args)))
+		}
+	}
+	clause := "(" + strings.Join(matches, " OR ") + ")"
+
+	switch f.Operator {
+	case "", "is":
+		return clause, nil
+	case "is_not":
+		return fmt.Sprintf("(e.props IS NULL OR NOT %s)", clause), nil
+	default:
+		return "", fmt.Errorf("unknown filter operator %q", f.Operator)
+	}
+}
+
+// propertyDocuments returns the JSON objects an event's properties contain
+// when key equals value. Values arrive as strings, so a number or boolean
+// also matches its JSON literal: "3" finds both {"qty": "3"} and {"qty": 3}.
+func propertyDocuments(key, value string) []string {
+	doc, _ := json.Marshal(map[string]string{key: value})
+	docs := []string{string(doc)}
+
+	var literal any
+	if json.Unmarshal([]byte(value), &literal) == nil {
+		switch literal.(type) {
+		case float64, bool:
+			doc, _ := json.Marshal(map[string]json.RawMessage{key: json.RawMessage(value)})
+			docs = append(docs, string(doc))
+		}
+	}
+	return docs
+}
+
+func (q *Query) orderClause(req Request) (string, error) {
+	if len(req.OrderBy) == 0 {
+		if req.Granularity != "" {
+			return `"time" ASC`, nil
+		}
+		return fmt.Sprintf("%s DESC", pq.QuoteIdentifier(req.Metrics[0])), nil
+	}
+
+	terms := make([]string, 0, len(req.OrderBy))
+	for _, o := range req.OrderBy {
+		if !slices.Contains(q.Columns, o.Field) {
+			return "", fmt.Errorf("cannot order by %q: not a selected column", o.Field)
+		}
+		direction := strings.ToUpper(o.Direction)
+		switch direction {
+		case "":
+			direction = "DESC"
+		case "ASC", "DESC":
+		default:
+			return "", fmt.Errorf("unknown order direction %q", o.Direction)
+		}
+		terms = append(terms, fmt.Sprintf("%s %s NULLS LAST", pq.QuoteIdentifier(o.Field), direction))
+	}
+	return strings.Join(terms, ", "), nil
+}
+
+// Scan reads the query's rows into result values: strings (or nil) for
+// dimensions, time.Time for time, int64 for counts and float64 for averages
+func (q *Query) Scan(rows *sql.Rows) ([][]any, error) {
+	out := make([][]any, 0)
+	for rows.Next() {
+		dest := make([]any, len(q.kinds))
+		for i, kind := range q.kinds {
+			switch kind {
+			case kindText:
+				dest[i] = new(sql.NullString)
+			case kindTime:
+				dest[i] = new(time.Time)
+			case kindCount:
+				dest[i] = new(int64)
+			case kindDecimal:
+				dest[i] = new(sql.NullFloat64)
+			}
+		}
+		if err := rows.Scan(dest...); err != nil {
+			return nil, err
+		}
+
+		row := make([]any, len(dest))
+		for i, d := range dest {
+			switch v := d.(type) {
+			case *sql.NullString:
+				if v.Valid {
+					row[i] = v.String
+				}
+			case *time.Time:
+				row[i] = v.UTC()
+			case *int64:
+				row[i] = *v
+			case *sql.NullFloat64:
+				if v.Valid {
+					row[i] = v.Float64
+				}
+			}
+		}
+		out = append(out, row)
+	}
+	return out, rows.Err()
+}
+
+// escapeLike escapes LIKE wildcards so values match literally
+func escapeLike(s string) string {
+	return strings.NewReplacer(`\`, `\\`, `%`, `\%`, `_`, `\_`).Replace(s)
+}
@@ -0,0 +1,141 @@
+package query
+
+import (
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/lib/pq"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestCompile(t *testing.T) {
+	websiteID := uuid.New()
+	q, err := Compile(websiteID, Request{
+		Metrics:     []string{"visitors", "pageviews"},
+		Dimensions:  []string{"country", "page"},
+		Granularity: "day",
+		Days:        30,
+		Filters: []Filter{
+			{Dimension: "device", Values: []string{"mobile", "tablet"}},
+			{Dimension: "page", Operator: "contains", Values: []string{"100%_off"}},
+		},
+		OrderBy: []Order{{Field: "pageviews"}, {Field: "country", Direction: "asc"}},
+		Limit:   50,
+	})
+	require.NoError(t, err)
+
+	assert.Equal(t, []string{"time", "country", "page", "visitors", "pageviews"}, q.Columns)
+	assert.Contains(t, q.SQL, `DATE_TRUNC('day', e.created_at) AS "time", s.country::TEXT AS "country", e.url_path::TEXT AS "page"`)
+	assert.Contains(t, q.SQL, `COUNT(DISTINCT e.session_id) AS "visitors"`)
+	assert.Contains(t, q.SQL, "JOIN session s ON s.session_id = e.session_id")
+	assert.Contains(t, q.SQL, "s.device::TEXT = ANY($3::TEXT[])")
+	assert.Contains(t, q.SQL, "e.url_path::TEXT ILIKE ANY($4::TEXT[])")
+	assert.Contains(t, q.SQL, "GROUP BY 1, 2, 3")
+	assert.Contains(t, q.SQL, `ORDER BY "pageviews" DESC NULLS LAST, "country" ASC NULLS LAST`)
+	assert.Contains(t, q.SQL, "LIMIT $5")
+	assert.Equal(t, []any{
+		websiteID, 30,
+		pq.Array([]string{"mobile", "tablet"}),
+		pq.Array([]string{`%100\%\_off%`}),
+		50,
+	}, q.Args)
+}
+
+func TestCompileDefaults(t *testing.T) {
+	q, err := Compile(uuid.New(), Request{Metrics: []string{"pageviews"}})
+	require.NoError(t, err)
+
+	assert.NotContains(t, q.SQL, "JOIN session")
+	assert.NotContains(t, q.SQL, "GROUP BY")
+	assert.Contains(t, q.SQL, `ORDER BY "pageviews" DESC`)
+	assert.Equal(t, DefaultDays, q.Args[1])
+	assert.Equal(t, DefaultLimit, q.Args[2])
+
+	q, err = Compile(uuid.New(), Request{Metrics: []string{"pageviews"}, Granularity: "hour"})
+	require.NoError(t, err)
+	assert.Contains(t, q.SQL, `ORDER BY "time" ASC`)
+}
+
+func TestCompileRange(t *testing.T) {
+	from := time.Date(2026, 5, 1, 0, 0, 0, 0, time.UTC)
+	to := from.Add(6 * time.Hour)
+	q, err := Compile(uuid.New(), Request{Metrics: []string{"visits"}, Granularity: "hour", From: &from, To: &to})
+	require.NoError(t, err)
+
+	assert.Contains(t, q.SQL, "e.created_at >= $2\n  AND e.created_at < $3")
+	assert.NotContains(t, q.SQL, "days")
+	assert.Equal(t, []any{from, to, DefaultLimit}, q.Args[1:])
+}
+
+func TestCompileProperties(t *testing.T) {
+	q, err := Compile(uuid.New(), Request{
+		Metrics:    []string{"events"},
+		Dimensions: []string{"prop:plan"},
+		Filters: []Filter{
+			{Dimension: "event", Values: []string{"purchase"}},
+			{Dimension: "prop:plan", Values: []string{"pro", "team"}},
+			{Dimension: "prop:seats", Operator: "is_not", Values: []string{"1"}},
+			{Dimension: "prop:coupon", Operator: "contains", Values: []string{"SPRING"}},
+		},
+	})
+	require.NoError(t, err)
+
+	assert.Equal(t, []string{"prop:plan", "events"}, q.Columns)
+	assert.Contains(t, q.SQL, `(e.props->>$3::TEXT)::TEXT AS "prop:plan"`)
+	assert.Contains(t, q.SQL, "(e.props @> $5::JSONB OR e.props @> $6::JSONB)")
+	assert.Contains(t, q.SQL, "(e.props IS NULL OR NOT (e.props @> $7::JSONB OR e.props @> $8::JSONB))")
+	assert.Contains(t, q.SQL, "(e.props->>$9::TEXT)::TEXT ILIKE ANY($10::TEXT[])")
+	assert.NotContains(t, q.SQL, "JOIN session")
+	assert.Equal(t, []any{
+		"plan",
+		pq.Array([]string{"purchase"}),
+		`{"plan":"pro"}`, `{"plan":"team"}`,
+		`{"seats":"1"}`, `{"seats":1}`,
+		"coupon", pq.Array([]string{"%SPRING%"}),
+		DefaultLimit,
+	}, q.Args[2:])
+}
+
+func TestCompileRejectsInvalidRequests(t *testing.T) {
+	from := time.Date(2026, 5, 1, 0, 0, 0, 0, time.UTC)
+	to := from.AddDate(2, 0, 0)
+
+	tests := []struct {
+		name string
+		req  Request
+		err  string
+	}{
+		{"no metrics", Request{}, "at least one metric"},
+		{"unknown metric", Request{Metrics: []string{"revenue"}}, `unknown metric "revenue"`},
+		{"unknown dimension", Request{Metrics: []string{"visits"}, Dimensions: []string{"s.country; DROP TABLE session"}}, "unknown dimension"},
+		{"duplicate dimension", Request{Metrics: []string{"visits"}, Dimensions: []string{"os", "os"}}, "requested twice"},
+		{"unknown granularity", Request{Metrics: []string{"visits"}, Granularity: "year"}, "unknown granularity"},
+		{"days out of range", Request{Metrics: []string{"visits"}, Days: 400}, "days must be between"},
+		{"from without to", Request{Metrics: []string{"visits"}, From: &from}, "set together"},
+		{"range and days", Request{Metrics: []string{"visits"}, From: &from, To: &to, Days: 7}, "cannot be combined"},
+		{"backwards range", Request{Metrics: []string{"visits"}, From: &to, To: &from}, "to must be after from"},
+		{"range too long", Request{Metrics: []string{"visits"}, From: &from, To: &to}, "at most 365 days"},
+		{"limit out of range", Request{Metrics: []string{"visits"}, Limit: MaxLimit + 1}, "limit must be between"},
+		{"unknown filter operator", Request{Metrics: []string{"visits"}, Filters: []Filter{{Dimension: "os", Operator: "regex", Values: []string{"."}}}}, "unknown filter operator"},
+		{"empty property name", Request{Metrics: []string{"visits"}, Dimensions: []string{"prop:"}}, "unknown dimension"},
+		{"filter without values", Request{Metrics: []string{"visits"}, Filters: []Filter{{Dimension: "os"}}}, "at least one value"},
+		{"order by unselected column", Request{Metrics: []string{"visits"}, OrderBy: []Order{{Field: "country"}}}, "not a selected column"},
+		{"bad order direction", Request{Metrics: []string{"visits"}, OrderBy: []Order{{Field: "visits", Direction: "up"}}}, "unknown order direction"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			_, err := Compile(uuid.New(), tt.req)
+			require.Error(t, err)
+			assert.Contains(t, err.Error(), tt.err)
+		})
+	}
+}
+
+func TestCatalog(t *testing.T) {
+	assert.Contains(t, Metrics(), "visitors")
+	assert.Contains(t, Dimensions(), "utm_campaign")
+	assert.Equal(t, []string{"minute", "hour", "day", "week", "month"}, Granularities())
+}
@@ -19,6 +19,9 @@ type Hub struct {
 	broadcast   chan []byte
 	clientCount chan chan int // For thread-safe client count queries
 	clients     map[*Client]struct{}
+	subscribe   chan chan []byte
+	unsubscribe chan chan []byte
+	subscribers map[chan []byte]struct{} // In-process consumers such as SSE streams
 	originMu    sync.RWMutex
 	origins     map[string]struct{}
 }
@@ -59,6 +62,9 @@ func NewHub() *Hub {
 		broadcast:   make(chan []byte, 512),
 		clientCount: make(chan chan int),
 		clients:     make(map[*Client]struct{}),
+		subscribe:   make(chan chan []byte),
+		unsubscribe: make(chan chan []byte),
+		subscribers: make(map[chan []byte]struct{}),
 	}
 
 	go h.run()
@@ -76,6 +82,13 @@ func (h *Hub) run() {
 				close(client.send)
 				_ = client.conn.Close()
 			}
+		case sub := <-h.subscribe:
+			h.subscribers[sub] = struct{}{}
+		case sub := <-h.unsubscribe:
+			if _, ok := h.subscribers[sub]; ok {
+				delete(h.subscribers, sub)
+				close(sub)
+			}
 		case message := <-h.broadcast:
 			for client := range h.clients {
 				select {
@@ -85,6 +98,13 @@ func (h *Hub) run() {
 					delete(h.clients, client)
 				}
 			}
+			for sub := range h.subscribers {
+				// Subscribers resync on their own, so a full buffer only skips this event
+				select {
+				case sub <- message:
+				default:
+				}
+			}
 		case response := <-h.clientCount:
 			response <- len(h.clients)
 		}
@@ -99,6 +119,18 @@ func (h *Hub) Broadcast(msg []byte) {
 	}
 }
 
+// Subscribe registers an in-process consumer of broadcast payloads. The
+// returned cancel func unsubscribes and closes the channel.
+func (h *Hub) Subscribe() (<-chan []byte, func()) {
+	sub := make(chan []byte, 64)
+	h.subscribe <- sub
+
+	var once sync.Once
+	return sub, func() {
+		once.Do(func() { h.unsubscribe <- sub })
+	}
+}
+
 // GetClientCount returns the number of connected clients in a thread-safe manner
 func (h *Hub) GetClientCount() int {
 	response := make(chan int)
@@ -70,6 +70,30 @@ func TestHubBroadcastDropsSlowClient(t *testing.T) {
 	}
 }
 
+func TestHubSubscribeReceivesBroadcasts(t *testing.T) {
+	hub := NewHub()
+
+	events, cancel := hub.Subscribe()
+	hub.Broadcast([]byte("hit"))
+
+	select {
+	case got := <-events:
+		assert.Equal(t, []byte("hit"), got)
+	case <-time.After(time.Second):
+		t.Fatal("subscriber did not receive broadcast message")
+	}
+
+	cancel()
+	cancel() // safe to call twice
+
+	select {
+	case _, ok := <-events:
+		assert.False(t, ok)
+	case <-time.After(time.Second):
+		t.Fatal("subscriber channel not closed after cancel")
+	}
+}
+
 func TestReadPumpSignalsUnregister(t *testing.T) {
 	unregister := make(chan *Client, 1)
 	client := &Client{
@@ -23,6 +23,9 @@ type EventPayload struct {
 	VisitID   string    `json:"visit_id"`
 	Path      string    `json:"path,omitempty"`
 	Title     string    `json:"title,omitempty"`
+	Name      string    `json:"name,omitempty"`     // Custom event name
+	Referrer  string    `json:"referrer,omitempty"` // As sent by the tracker
+	Country   string    `json:"country,omitempty"`
 	CreatedAt time.Time `json:"created_at"`
 }
 
@@ -33,6 +36,13 @@ func NotifyEvent(ctx context.Context, payload EventPayload) {
 		return
 	}
 
+	if redisClient != nil {
+		if _, err := redisClient.Do(ctx, "PUBLISH", ChannelName, data); err != nil {
+			logging.L().Warn("failed to send realtime notification", slog.Any("error", err))
+		}
+		return
+	}
+
 	if _, err := database.DB.ExecContext(ctx, "SELECT pg_notify($1, $2)", ChannelName, string(data)); err != nil {
 		logging.L().Warn("failed to send realtime notification", slog.Any("error", err))
 	}
@@ -0,0 +1,77 @@
+package realtime
+
+import (
+	"context"
+	"strconv"
+	"time"
+
+	"github.com/google/uuid"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/redis"
+)
+
+// PresenceWindow is how long a visitor counts as online after a pageview
+const PresenceWindow = 5 * time.Minute
+
+// redisClient is set when instances share realtime state through Redis
+var redisClient *redis.Client
+
+// UseRedis switches event fan-out from PostgreSQL LISTEN/NOTIFY to Redis
+// pub/sub and turns on the shared presence counters
+func UseRedis(client *redis.Client) {
+	redisClient = client
+}
+
+// StartRedisListener broadcasts events published by any instance to hub
+func StartRedisListener(ctx context.Context, client *redis.Client, hub *Hub) {
+	go client.Subscribe(ctx, ChannelName, hub.Broadcast)
+}
+
+func presenceKey(websiteID uuid.UUID) string {
+	return "kaunta:online:" + websiteID.String()
+}
+
+// MarkPresent records a pageview for the shared online visitor count. It is
+// a no-op without Redis.
+func MarkPresent(ctx context.Context, websiteID, sessionID uuid.UUID, at time.Time) {
+	if redisClient == nil || time.Since(at) > PresenceWindow {
+		return
+	}
+	ctx, cancel := context.WithTimeout(ctx, time.Second)
+	defer cancel()
+
+	key := presenceKey(websiteID)
+	if _, err := redisClient.Do(ctx, "ZADD", key, at.Unix(), sessionID.String()); err != nil {
+		logging.L().Warn("failed to record presence", slog.Any("error", err))
+		return
+	}
+	// Idle websites clean themselves up
+	_, _ = redisClient.Do(ctx, "EXPIRE", key, int(2*PresenceWindow/time.Second))
+}
+
+// OnlineVisitors returns the sessions with a pageview in the last
+// PresenceWindow across all instances. ok is false without Redis or when
+// Redis fails, and callers then count from the database.
+func OnlineVisitors(ctx context.Context, websiteID uuid.UUID) (count int, ok bool) {
+	if redisClient == nil {
+		return 0, false
+	}
+	ctx, cancel := context.WithTimeout(ctx, time.Second)
+	defer cancel()
+
+	key := presenceKey(websiteID)
+	cutoff := strconv.FormatInt(time.Now().Add(-PresenceWindow).Unix(), 10)
+	if _, err := redisClient.Do(ctx, "ZREMRANGEBYSCORE", key, "-inf", "("+cutoff); err != nil {
+		logging.L().Warn("failed to expire presence", slog.Any("error", err))
+		return 0, false
+	}
+	n, err := redisClient.Int(ctx, "ZCARD", key)
+	if err != nil {
+		logging.L().Warn("failed to count presence", slog.Any("error", err))
+		return 0, false
+	}
+	return int(n), true
+}
@@ -0,0 +1,301 @@
+// Package redis is a small Redis client covering what Kaunta shares between
+// instances: plain commands over a connection pool and a pub/sub
+// subscription. It speaks RESP2 and supports redis:// URLs with an optional
+// password and database number.
+package redis
+
+import (
+	"bufio"
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net"
+	"net/url"
+	"strconv"
+	"strings"
+	"time"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/logging"
+)
+
+// Error is an error reply from the server
+type Error string
+
+func (e Error) Error() string { return "redis: " + string(e) }
+
+// Client runs commands on a pool of connections
+type Client struct {
+	addr     string
+	password string
+	db       int
+	timeout  time.Duration
+	pool     chan *conn
+}
+
+type conn struct {
+	net.Conn
+	r *bufio.Reader
+	w *bufio.Writer
+}
+
+// Open parses a redis://[:password@]host[:port][/db] URL and checks the
+// server answers
+func Open(rawURL string, poolSize int) (*Client, error) {
+	u, err := url.Parse(rawURL)
+	if err != nil {
+		return nil, fmt.Errorf("invalid redis URL: %w", err)
+	}
+	if u.Scheme != "redis" {
+		return nil, fmt.Errorf("unsupported redis URL scheme %q (use redis://)", u.Scheme)
+	}
+	c := &Client{
+		addr:    u.Host,
+		timeout: 5 * time.Second,
+		pool:    make(chan *conn, max(poolSize, 1)),
+	}
+	if u.Port() == "" {
+		c.addr = net.JoinHostPort(u.Hostname(), "6379")
+	}
+	if password, ok := u.User.Password(); ok {
+		c.password = password
+	}
+	if db := strings.Trim(u.Path, "/"); db != "" {
+		if c.db, err = strconv.Atoi(db); err != nil {
+			return nil, fmt.Errorf("invalid redis database %q", db)
+		}
+	}
+
+	ctx, cancel := context.WithTimeout(context.Background(), c.timeout)
+	defer cancel()
+	if _, err := c.Do(ctx, "PING"); err != nil {
+		return nil, err
+	}
+	return c, nil
+}
+
+func (c *Client) dial(ctx context.Context) (*conn, error) {
+	d := net.Dialer{Timeout: c.timeout}
+	nc, err := d.DialContext(ctx, "tcp", c.addr)
+	if err != nil {
+		return nil, err
+	}
+	cn := &conn{Conn: nc, r: bufio.NewReader(nc), w: bufio.NewWriter(nc)}
+	if c.password != "" {
+		if _, err := cn.do(ctx, c.timeout, "AUTH", c.password); err != nil {
+			_ = cn.Close()
+			return nil, err
+		}
+	}
+	if c.db != 0 {
+		if _, err := cn.do(ctx, c.timeout, "SELECT", c.db); err != nil {
+			_ = cn.Close()
+			return nil, err
+		}
+	}
+	return cn, nil
+}
+
+// Do runs one command. Replies are string (status), int64, []byte (bulk,
+// nil when missing), []any (array) or an Error.
+func (c *Client) Do(ctx context.Context, args ...any) (any, error) {
+	var cn *conn
+	select {
+	case cn = <-c.pool:
+	default:
+		var err error
+		if cn, err = c.dial(ctx); err != nil {
+			return nil, err
+		}
+	}
+
+	reply, err := cn.do(ctx, c.timeout, args...)
+	var replyErr Error
+	if err != nil && !errors.As(err, &replyErr) {
+		// The connection may be mid-reply; don't reuse it
+		_ = cn.Close()
+		return nil, err
+	}
+	select {
+	case c.pool <- cn:
+	default:
+		_ = cn.Close()
+	}
+	return reply, err
+}
+
+// Int runs a command with an integer reply
+func (c *Client) Int(ctx context.Context, args ...any) (int64, error) {
+	reply, err := c.Do(ctx, args...)
+	if err != nil {
+		return 0, err
+	}
+	n, ok := reply.(int64)
+	if !ok {
+		return 0, fmt.Errorf("redis: unexpected reply %T", reply)
+	}
+	return n, nil
+}
+
+// Close closes the pooled connections
+func (c *Client) Close() error {
+	for {
+		select {
+		case cn := <-c.pool:
+			_ = cn.Close()
+		default:
+			return nil
+		}
+	}
+}
+
+// Subscribe calls handle with every message published to channel until ctx
+// is done, reconnecting after errors
+func (c *Client) Subscribe(ctx context.Context, channel string, handle func([]byte)) {
+	for ctx.Err() == nil {
+		err := c.subscribeOnce(ctx, channel, handle)
+		if ctx.Err() != nil {
+			return
+		}
+		logging.L().Warn("redis subscription lost, reconnecting", slog.String("channel", channel), slog.Any("error", err))
+		select {
+		case <-ctx.Done():
+		case <-time.After(time.Second):
+		}
+	}
+}
+
+func (c *Client) subscribeOnce(ctx context.Context, channel string, handle func([]byte)) error {
+	cn, err := c.dial(ctx)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = cn.Close() }()
+	stop := context.AfterFunc(ctx, func() { _ = cn.Close() })
+	defer stop()
+
+	if err := writeCommand(cn.w, "SUBSCRIBE", channel); err != nil {
+		return err
+	}
+	if err := cn.w.Flush(); err != nil {
+		return err
+	}
+	for {
+		reply, err := readReply(cn.r)
+		if err != nil {
+			return err
+		}
+		// ["message", channel, payload]; the subscribe confirmation is skipped
+		if msg, ok := reply.([]any); ok && len(msg) == 3 {
+			if kind, _ := msg[0].([]byte); string(kind) == "message" {
+				if payload, ok := msg[2].([]byte); ok {
+					handle(payload)
+				}
+			}
+		}
+	}
+}
+
+func (cn *conn) do(ctx context.Context, timeout time.Duration, args ...any) (any, error) {
+	deadline := time.Now().Add(timeout)
+	if d, ok := ctx.Deadline(); ok && d.Before(deadline) {
+		deadline = d
+	}
+	_ = cn.SetDeadline(deadline)
+
+	if err := writeCommand(cn.w, args...); err != nil {
+		return nil, err
+	}
+	if err := cn.w.Flush(); err != nil {
+		return nil, err
+	}
+	return readReply(cn.r)
+}
+
+// writeCommand encodes args as a RESP array of bulk strings
+func writeCommand(w io.Writer, args ...any) error {
+	if _, err := fmt.Fprintf(w, "*%d\r\n", len(args)); err != nil {
+		return err
+	}
+	for _, arg := range args {
+		var s string
+		switch v := arg.(type) {
+		case string:
+			s = v
+		case []byte:
+			s = string(v)
+		case int:
+			s = strconv.Itoa(v)
+		case int64:
+			s = strconv.FormatInt(v, 10)
+		case float64:
+			s = strconv.FormatFloat(v, 'f', -1, 64)
+		case fmt.Stringer:
+			s = v.String()
+		default:
+			return fmt.Errorf("redis: unsupported argument type %T", arg)
+		}
+		if _, err := fmt.Fprintf(w, "$%d\r\n%s\r\n", len(s), s); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// readReply decodes one RESP2 reply
+func readReply(r *bufio.Reader) (any, error) {
+	line, err := r.ReadString('\n')
+	if err != nil {
+		return nil, err
+	}
+	if len(line) < 3 || !strings.HasSuffix(line, "\r\n") {
+		return nil, fmt.Errorf("redis: malformed reply %q", line)
+	}
+	kind, body := line[0], line[1:len(line)-2]
+
+	switch kind {
+	case '+':
+		return body, nil
+	case '-':
+		return nil, Error(body)
+	case ':':
+		return strconv.ParseInt(body, 10, 64)
+	case '$':
+		n, err := strconv.Atoi(body)
+		if err != nil {
+			return nil, fmt.Errorf("redis: malformed bulk length %q", body)
+		}
+		if n < 0 {
+			return nil, nil
+		}
+		buf := make([]byte, n+2)
+		if _, err := io.ReadFull(r, buf); err != nil {
+			return nil, err
+		}
+		return buf[:n], nil
+	case '*':
+		n, err := strconv.Atoi(body)
+		if err != nil {
+			return nil, fmt.Errorf("redis: malformed array length %q", body)
+		}
+		if n < 0 {
+			return nil, nil
+		}
+		items := make([]any, n)
+		for i := range items {
+			item, err := readReply(r)
+			var replyErr Error
+			if err != nil && !errors.As(err, &replyErr) {
+				return nil, err
+			}
+			if err != nil {
+				item = replyErr
+			}
+			items[i] = item
+		}
+		return items, nil
+	}
+	return nil, fmt.Errorf("redis: unknown reply type %q", kind)
+}
@@ -0,0 +1,146 @@
+package redis
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"fmt"
+	"net"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+// fakeServer answers each command with reply(args) and records the commands
+type fakeServer struct {
+	addr     string
+	mu       sync.Mutex
+	commands []string
+}
+
+func newFakeServer(t *testing.T, reply func(args []string) string) *fakeServer {
+	t.Helper()
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = ln.Close() })
+
+	s := &fakeServer{addr: ln.Addr().String()}
+	go func() {
+		for {
+			c, err := ln.Accept()
+			if err != nil {
+				return
+			}
+			go func() {
+				defer func() { _ = c.Close() }()
+				r := bufio.NewReader(c)
+				for {
+					cmd, err := readReply(r)
+					if err != nil {
+						return
+					}
+					var args []string
+					for _, arg := range cmd.([]any) {
+						args = append(args, string(arg.([]byte)))
+					}
+					s.mu.Lock()
+					s.commands = append(s.commands, strings.Join(args, " "))
+					s.mu.Unlock()
+					if _, err := c.Write([]byte(reply(args))); err != nil {
+						return
+					}
+				}
+			}()
+		}
+	}()
+	return s
+}
+
+func (s *fakeServer) seen() []string {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	return append([]string(nil), s.commands...)
+}
+
+func TestReadReply(t *testing.T) {
+	tests := []struct {
+		name string
+		wire string
+		want any
+	}{
+		{"status", "+OK\r\n", "OK"},
+		{"integer", ":42\r\n", int64(42)},
+		{"bulk", "$5\r\nhello\r\n", []byte("hello")},
+		{"nil bulk", "$-1\r\n", nil},
+		{"array", "*2\r\n$1\r\na\r\n:1\r\n", []any{[]byte("a"), int64(1)}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, err := readReply(bufio.NewReader(strings.NewReader(tt.wire)))
+			require.NoError(t, err)
+			assert.Equal(t, tt.want, got)
+		})
+	}
+
+	_, err := readReply(bufio.NewReader(strings.NewReader("-WRONGTYPE bad key\r\n")))
+	assert.Equal(t, Error("WRONGTYPE bad key"), err)
+}
+
+func TestWriteCommand(t *testing.T) {
+	var buf bytes.Buffer
+	require.NoError(t, writeCommand(&buf, "ZADD", "key", int64(10), "member"))
+	assert.Equal(t, "*4\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$2\r\n10\r\n$6\r\nmember\r\n", buf.String())
+
+	assert.Error(t, writeCommand(&buf, struct{}{}))
+}
+
+func TestOpenAuthenticatesAndSelects(t *testing.T) {
+	server := newFakeServer(t, func(args []string) string {
+		if args[0] == "ZCARD" {
+			return ":3\r\n"
+		}
+		return "+OK\r\n"
+	})
+
+	client, err := Open(fmt.Sprintf("redis://:secret@%s/2", server.addr), 2)
+	require.NoError(t, err)
+	defer func() { _ = client.Close() }()
+
+	n, err := client.Int(context.Background(), "ZCARD", "kaunta:online")
+	require.NoError(t, err)
+	assert.Equal(t, int64(3), n)
+	assert.Equal(t, []string{"AUTH secret", "SELECT 2", "PING", "ZCARD kaunta:online"}, server.seen(), "the pooled connection is reused")
+}
+
+func TestOpenRejectsOtherSchemes(t *testing.T) {
+	_, err := Open("rediss://localhost:6379", 1)
+	assert.ErrorContains(t, err, "unsupported redis URL scheme")
+}
+
+func TestSubscribeDeliversMessages(t *testing.T) {
+	server := newFakeServer(t, func(args []string) string {
+		if args[0] == "SUBSCRIBE" {
+			return "*3\r\n$9\r\nsubscribe\r\n$6\r\nevents\r\n:1\r\n" +
+				"*3\r\n$7\r\nmessage\r\n$6\r\nevents\r\n$3\r\nhit\r\n"
+		}
+		return "+PONG\r\n"
+	})
+	client, err := Open("redis://"+server.addr, 1)
+	require.NoError(t, err)
+
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	received := make(chan []byte, 1)
+	go client.Subscribe(ctx, "events", func(payload []byte) { received <- payload })
+
+	select {
+	case payload := <-received:
+		assert.Equal(t, []byte("hit"), payload)
+	case <-time.After(2 * time.Second):
+		t.Fatal("no message delivered")
+	}
+}
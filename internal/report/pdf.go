@@ -0,0 +1,263 @@
+package report
+
+import (
+	"bytes"
+	"fmt"
+	"io"
+	"math"
+	"strings"
+)
+
+// A4 page geometry in PDF points
+const (
+	pageWidth    = 595.0
+	pageHeight   = 842.0
+	pageMargin   = 48.0
+	lineHeight   = 15.0
+	chartHeight  = 160.0
+	maxLabelRune = 60
+)
+
+// pdfDoc is a tiny PDF 1.4 writer covering what reports need:
+// Helvetica text, filled rectangles, lines and automatic page breaks.
+type pdfDoc struct {
+	pages []*bytes.Buffer
+	y     float64
+}
+
+func newPDFDoc() *pdfDoc {
+	d := &pdfDoc{}
+	d.newPage()
+	return d
+}
+
+func (d *pdfDoc) page() *bytes.Buffer {
+	return d.pages[len(d.pages)-1]
+}
+
+func (d *pdfDoc) newPage() {
+	d.pages = append(d.pages, &bytes.Buffer{})
+	d.y = pageHeight - pageMargin
+}
+
+// ensure starts a new page when fewer than h points remain
+func (d *pdfDoc) ensure(h float64) {
+	if d.y-h < pageMargin {
+		d.newPage()
+	}
+}
+
+func (d *pdfDoc) text(x, y float64, font string, size float64, s string) {
+	fmt.Fprintf(d.page(), "BT /%s %.1f Tf %.2f %.2f Td (%s) Tj ET\n", font, size, x, y, pdfEscape(s))
+}
+
+// textRight draws s so that it ends at x, using an approximate Helvetica width
+func (d *pdfDoc) textRight(x, y float64, font string, size float64, s string) {
+	d.text(x-approxTextWidth(s, size), y, font, size, s)
+}
+
+func (d *pdfDoc) rect(x, y, w, h float64, gray float64) {
+	fmt.Fprintf(d.page(), "%.3f g %.2f %.2f %.2f %.2f re f 0 g\n", gray, x, y, w, h)
+}
+
+func (d *pdfDoc) line(x1, y1, x2, y2 float64) {
+	fmt.Fprintf(d.page(), "0.8 G 0.5 w %.2f %.2f m %.2f %.2f l S 0 G\n", x1, y1, x2, y2)
+}
+
+// writeTo serialises the document with a correct cross-reference table
+func (d *pdfDoc) writeTo(w io.Writer) error {
+	var out bytes.Buffer
+	var offsets []int
+
+	obj := func(body string) {
+		offsets = append(offsets, out.Len())
+		fmt.Fprintf(&out, "%d 0 obj\n%s\nendobj\n", len(offsets), body)
+	}
+
+	out.WriteString("%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")
+
+	// 1: catalog, 2: page tree, 3-4: fonts, then a page + content pair per page
+	firstPage := 5
+	kids := make([]string, len(d.pages))
+	for i := range d.pages {
+		kids[i] = fmt.Sprintf("%d 0 R", firstPage+i*2)
+	}
+
+	obj("<< /Type /Catalog /Pages 2 0 R >>")
+	obj(fmt.Sprintf("<< /Type /Pages /Kids [%s] /Count %d >>", strings.Join(kids, " "), len(d.pages)))
+	obj("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>")
+	obj("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>")
+	for i, content := range d.pages {
+		obj(fmt.Sprintf("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 %.0f %.0f] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents %d 0 R >>",
+			pageWidth, pageHeight, firstPage+i*2+1))
+		obj(fmt.Sprintf("<< /Length %d >>\nstream\n%sendstream", content.Len(), content.String()))
+	}
+
+	xref := out.Len()
+	fmt.Fprintf(&out, "xref\n0 %d\n0000000000 65535 f \n", len(offsets)+1)
+	for _, off := range offsets {
+		fmt.Fprintf(&out, "%010d 00000 n \n", off)
+	}
+	fmt.Fprintf(&out, "trailer\n<< /Size %d /Root 1 0 R >>\nstartxref\n%d\n%%%%EOF\n", len(offsets)+1, xref)
+
+	_, err := w.Write(out.Bytes())
+	return err
+}
+
+// RenderPDF writes the report as a printable A4 PDF document
+func RenderPDF(w io.Writer, r *Report) error {
+	d := newPDFDoc()
+	left, right := pageMargin, pageWidth-pageMargin
+
+	// Header
+	d.text(left, d.y-18, "F2", 20, r.Title())
+	d.y -= 36
+	d.text(left, d.y, "F1", 10, fmt.Sprintf("%s - last %d days - generated %s UTC",
+		r.Domain, r.Days, r.GeneratedAt.Format("2006-01-02 15:04")))
+	d.y -= 30
+
+	// Summary metrics
+	metrics := []struct{ label, value string }{
+		{"Pageviews", formatCount(r.Pageviews)},
+		{"Visitors", formatCount(r.Visitors)},
+		{"Bounce rate", fmt.Sprintf("%.1f%%", r.BounceRate)},
+		{"Avg. visit", formatDuration(r.AvgVisitDuration)},
+	}
+	colWidth := (right - left) / float64(len(metrics))
+	for i, m := range metrics {
+		x := left + float64(i)*colWidth
+		d.text(x, d.y, "F1", 9, strings.ToUpper(m.label))
+		d.text(x, d.y-22, "F2", 18, m.value)
+	}
+	d.y -= 50
+
+	// Pageviews chart
+	d.text(left, d.y, "F2", 12, "Pageviews over time")
+	d.y -= 10
+	d.ensure(chartHeight + 20)
+	renderChart(d, left, d.y-chartHeight, right-left, chartHeight, r.Chart)
+	d.y -= chartHeight + 30
+
+	// Top tables
+	renderTable(d, "Top pages", "Views", r.Pages)
+	renderTable(d, "Top referrers", "Visits", r.Referrers)
+	renderTable(d, "Top countries", "Visits", r.Countries)
+
+	return d.writeTo(w)
+}
+
+func renderChart(d *pdfDoc, x, y, width, height float64, points []Point) {
+	d.line(x, y, x+width, y)
+	if len(points) == 0 {
+		d.text(x, y+height/2, "F1", 10, "No pageviews recorded in this period")
+		return
+	}
+
+	var peak int64
+	for _, p := range points {
+		peak = max(peak, p.Value)
+	}
+	if peak == 0 {
+		peak = 1
+	}
+
+	slot := width / float64(len(points))
+	barWidth := max(slot*0.7, 0.5)
+	for i, p := range points {
+		h := float64(p.Value) / float64(peak) * (height - 14)
+		d.rect(x+float64(i)*slot+(slot-barWidth)/2, y, barWidth, h, 0.35)
+	}
+	d.textRight(x+width, y+height-8, "F1", 8, "peak "+formatCount(peak))
+	d.text(x, y-12, "F1", 8, points[0].Time.Format("Jan 2"))
+	d.textRight(x+width, y-12, "F1", 8, points[len(points)-1].Time.Format("Jan 2"))
+}
+
+func renderTable(d *pdfDoc, title, countLabel string, items []Item) {
+	left, right := pageMargin, pageWidth-pageMargin
+
+	d.ensure(lineHeight * 3)
+	d.text(left, d.y, "F2", 12, title)
+	d.textRight(right, d.y, "F2", 9, countLabel)
+	d.y -= 6
+	d.line(left, d.y, right, d.y)
+	d.y -= lineHeight
+
+	if len(items) == 0 {
+		d.text(left, d.y, "F1", 10, "No data")
+		d.y -= lineHeight * 2
+		return
+	}
+
+	for _, item := range items {
+		d.ensure(lineHeight)
+		name := strings.TrimSpace(item.Name)
+		if name == "" {
+			name = "Unknown"
+		}
+		d.text(left, d.y, "F1", 10, truncateRunes(name, maxLabelRune))
+		d.textRight(right, d.y, "F1", 10, formatCount(item.Count))
+		d.y -= lineHeight
+	}
+	d.y -= lineHeight
+}
+
+// pdfEscape escapes a string literal and folds it into the WinAnsi range
+func pdfEscape(s string) string {
+	var b strings.Builder
+	for _, r := range s {
+		switch {
+		case r == '(' || r == ')' || r == '\\':
+			b.WriteByte('\\')
+			b.WriteRune(r)
+		case r < 32:
+			b.WriteByte(' ')
+		case r < 127:
+			b.WriteRune(r)
+		case r >= 160 && r <= 255:
+			fmt.Fprintf(&b, "\\%03o", r)
+		default:
+			b.WriteByte('?')
+		}
+	}
+	return b.String()
+}
+
+// approxTextWidth estimates Helvetica text width; exact metrics are not needed for right alignment of short numbers
+func approxTextWidth(s string, size float64) float64 {
+	return float64(len([]rune(s))) * size * 0.55
+}
+
+func truncateRunes(s string, n int) string {
+	runes := []rune(s)
+	if len(runes) <= n {
+		return s
+	}
+	return string(runes[:n-3]) + "..."
+}
+
+func formatCount(n int64) string {
+	s := fmt.Sprintf("%d", n)
+	if n < 0 {
+		return s
+	}
+	var b strings.Builder
+	for i, ch := range s {
+		if i > 0 && (len(s)-i)%3 == 0 {
+			b.WriteByte(',')
+		}
+		b.WriteRune(ch)
+	}
+	return b.String()
+}
+
+func formatDuration(seconds float64) string {
+	total := int(math.Round(max(seconds, 0)))
+	switch {
+	case total < 60:
+		return fmt.Sprintf("%ds", total)
+	case total < 3600:
+		return fmt.Sprintf("%dm %02ds", total/60, total%60)
+	default:
+		return fmt.Sprintf("%dh %02dm", total/3600, total%3600/60)
+	}
+}
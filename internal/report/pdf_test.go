@@ -0,0 +1,69 @@
+package report
+
+import (
+	"bytes"
+	"fmt"
+	"regexp"
+	"strconv"
+	"testing"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func sampleReport(rows int) *Report {
+	r := &Report{
+		WebsiteID:        uuid.New(),
+		WebsiteName:      "Example (Blog)",
+		Domain:           "example.com",
+		Days:             30,
+		GeneratedAt:      time.Date(2026, 1, 31, 12, 0, 0, 0, time.UTC),
+		Pageviews:        12345,
+		Visitors:         678,
+		BounceRate:       42.5,
+		AvgVisitDuration: 75,
+	}
+	for i := range 30 {
+		r.Chart = append(r.Chart, Point{Time: r.GeneratedAt.AddDate(0, 0, i-30), Value: int64(i * 10)})
+	}
+	for i := range rows {
+		r.Pages = append(r.Pages, Item{Name: fmt.Sprintf("/page-%d", i), Count: int64(1000 - i)})
+	}
+	r.Countries = []Item{{Name: "Côte d'Ivoire", Count: 3}}
+	return r
+}
+
+func TestRenderPDF(t *testing.T) {
+	var buf bytes.Buffer
+	require.NoError(t, RenderPDF(&buf, sampleReport(TopItemsLimit)))
+
+	out := buf.Bytes()
+	assert.True(t, bytes.HasPrefix(out, []byte("%PDF-1.4")))
+	assert.True(t, bytes.HasSuffix(out, []byte("%%EOF\n")))
+	assert.Contains(t, buf.String(), `(Example \(Blog\))`)
+	assert.Contains(t, buf.String(), `(12,345)`)
+	assert.Contains(t, buf.String(), `(1m 15s)`)
+	assert.Contains(t, buf.String(), `C\364te`)
+
+	// Every xref entry must point at the start of its object
+	m := regexp.MustCompile(`startxref\n(\d+)`).FindSubmatch(out)
+	require.NotNil(t, m)
+	xref, err := strconv.Atoi(string(m[1]))
+	require.NoError(t, err)
+	require.True(t, bytes.HasPrefix(out[xref:], []byte("xref")))
+
+	entries := regexp.MustCompile(`(\d{10}) 00000 n`).FindAllSubmatch(out[xref:], -1)
+	require.NotEmpty(t, entries)
+	for i, e := range entries {
+		off, _ := strconv.Atoi(string(e[1]))
+		assert.True(t, bytes.HasPrefix(out[off:], fmt.Appendf(nil, "%d 0 obj", i+1)), "object %d", i+1)
+	}
+}
+
+func TestRenderPDFPaginates(t *testing.T) {
+	var buf bytes.Buffer
+	require.NoError(t, RenderPDF(&buf, sampleReport(60)))
+	assert.Contains(t, buf.String(), "/Count 2")
+}
@@ -0,0 +1,158 @@
+// Package report assembles a period summary for a website and renders it to
+// shareable formats. The same Report value is meant to be produced once and
+// handed to any renderer, so on-demand downloads and scheduled deliveries
+// stay identical.
+package report
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// TopItemsLimit is the number of rows included in each report table
+const TopItemsLimit = 10
+
+// Item is a single named row of a report table
+type Item struct {
+	Name  string `json:"name"`
+	Count int64  `json:"count"`
+}
+
+// Point is one bucket of the pageviews chart
+type Point struct {
+	Time  time.Time
+	Value int64
+}
+
+// Report holds everything needed to render a website summary for a period
+type Report struct {
+	WebsiteID   uuid.UUID
+	WebsiteName string
+	Domain      string
+	Days        int
+	GeneratedAt time.Time
+
+	Pageviews        int64
+	Visitors         int64
+	BounceRate       float64
+	AvgVisitDuration float64 // seconds
+
+	Chart     []Point
+	Pages     []Item
+	Referrers []Item
+	Countries []Item
+}
+
+// Querier runs the report's queries: a *sql.DB, or a *sql.Tx such as the
+// tenant-scoped one from database.StatsQuerier
+type Querier interface {
+	QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error)
+	QueryRowContext(ctx context.Context, query string, args ...any) *sql.Row
+}
+
+// Build queries the analytics functions for a website and collects the report data
+func Build(ctx context.Context, db Querier, websiteID uuid.UUID, days int) (*Report, error) {
+	r := &Report{
+		WebsiteID:   websiteID,
+		Days:        days,
+		GeneratedAt: time.Now().UTC(),
+	}
+
+	err := db.QueryRowContext(ctx,
+		`SELECT COALESCE(name, ''), domain FROM website WHERE website_id = $1 AND deleted_at IS NULL`,
+		websiteID,
+	).Scan(&r.WebsiteName, &r.Domain)
+	if err != nil {
+		return nil, fmt.Errorf("load website: %w", err)
+	}
+
+	var currentVisitors int64
+	err = db.QueryRowContext(ctx,
+		`SELECT * FROM get_dashboard_stats($1, $2, NULL, NULL, NULL, NULL)`,
+		websiteID, days,
+	).Scan(&currentVisitors, &r.Pageviews, &r.Visitors, &r.BounceRate, &r.AvgVisitDuration)
+	if err != nil {
+		return nil, fmt.Errorf("load summary: %w", err)
+	}
+
+	if r.Chart, err = loadChart(ctx, db, websiteID, days); err != nil {
+		return nil, fmt.Errorf("load chart: %w", err)
+	}
+
+	if r.Pages, err = loadItems(ctx, db,
+		`SELECT path, views FROM get_top_pages($1, $2, $3, 0, NULL, NULL, NULL, 'views', 'desc')`,
+		websiteID, days, TopItemsLimit); err != nil {
+		return nil, fmt.Errorf("load pages: %w", err)
+	}
+
+	if r.Referrers, err = loadItems(ctx, db,
+		`SELECT name, count FROM get_breakdown($1, 'referrer', $2, $3, 0, NULL, NULL, NULL, NULL, 'count', 'desc')`,
+		websiteID, days, TopItemsLimit); err != nil {
+		return nil, fmt.Errorf("load referrers: %w", err)
+	}
+
+	if r.Countries, err = loadItems(ctx, db,
+		`SELECT name, count FROM get_breakdown($1, 'country', $2, $3, 0, NULL, NULL, NULL, NULL, 'count', 'desc')`,
+		websiteID, days, TopItemsLimit); err != nil {
+		return nil, fmt.Errorf("load countries: %w", err)
+	}
+
+	return r, nil
+}
+
+// Title returns the display name used in report headers
+func (r *Report) Title() string {
+	if r.WebsiteName != "" {
+		return r.WebsiteName
+	}
+	return r.Domain
+}
+
+// loadChart returns hourly buckets for short periods and daily buckets otherwise
+func loadChart(ctx context.Context, db Querier, websiteID uuid.UUID, days int) ([]Point, error) {
+	granularity := "hour"
+	if days > 2 {
+		granularity = "day"
+	}
+
+	rows, err := db.QueryContext(ctx,
+		`SELECT * FROM get_timeseries($1, $2, NULL, NULL, NULL, NULL, $3)`,
+		websiteID, days, granularity,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var points []Point
+	for rows.Next() {
+		var p Point
+		if err := rows.Scan(&p.Time, &p.Value); err != nil {
+			return nil, err
+		}
+		points = append(points, p)
+	}
+	return points, rows.Err()
+}
+
+func loadItems(ctx context.Context, db Querier, query string, args ...any) ([]Item, error) {
+	rows, err := db.QueryContext(ctx, query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var items []Item
+	for rows.Next() {
+		var item Item
+		if err := rows.Scan(&item.Name, &item.Count); err != nil {
+			return nil, err
+		}
+		items = append(items, item)
+	}
+	return items, rows.Err()
+}
@@ -0,0 +1,283 @@
+// Package retention enforces per-website retention policies. Raw events
+// older than a website's event retention are compacted into daily summaries
+// and deleted together with the sessions nothing newer references, and
+// rollups older than its rollup retention are dropped. Server-wide retention (retention_days) is handled
+// by dropping whole partitions in the database package. Prune does the same
+// for a one-off date instead of a policy.
+package retention
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"time"
+
+	"log/slog"
+
+	"github.com/google/uuid"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/models"
+)
+
+var (
+	nowFunc       = time.Now
+	purgeInterval = 24 * time.Hour
+)
+
+// Policy is a website's retention. Zero days leave that data alone.
+type Policy struct {
+	WebsiteID  uuid.UUID `json:"website_id"`
+	Domain     string    `json:"domain"`
+	EventDays  int       `json:"event_days,omitempty"`
+	RollupDays int       `json:"rollup_days,omitempty"`
+}
+
+// Result is what a purge deleted, or would delete in a dry run
+type Result struct {
+	Policy
+	EventCutoff  *time.Time `json:"event_cutoff,omitempty"`
+	RollupCutoff *time.Time `json:"rollup_cutoff,omitempty"`
+	Events       int64      `json:"events"`
+	Sessions     int64      `json:"sessions"`
+	Rollups      int64      `json:"rollups"`
+	DryRun       bool       `json:"dry_run"`
+	// Deferred is set when expired raw events were kept because the rollups
+	// haven't reached their days yet
+	Deferred bool `json:"deferred,omitempty"`
+}
+
+// Empty reports whether nothing is (or would be) deleted
+func (r Result) Empty() bool {
+	return r.Events == 0 && r.Sessions == 0 && r.Rollups == 0
+}
+
+// Policies lists the websites with a retention policy
+func Policies(ctx context.Context, db *sql.DB) ([]Policy, error) {
+	rows, err := db.QueryContext(ctx, `
+		SELECT website_id, domain, COALESCE(event_retention_days, 0), COALESCE(rollup_retention_days, 0)
+		FROM website
+		WHERE deleted_at IS NULL
+		  AND (event_retention_days IS NOT NULL OR rollup_retention_days IS NOT NULL)
+		ORDER BY domain
+	`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var policies []Policy
+	for rows.Next() {
+		var p Policy
+		if err := rows.Scan(&p.WebsiteID, &p.Domain, &p.EventDays, &p.RollupDays); err != nil {
+			return nil, err
+		}
+		policies = append(policies, p)
+	}
+	return policies, rows.Err()
+}
+
+// Run applies every website's policy. With dryRun it only counts what would
+// be deleted. Purges that delete anything are recorded in the audit log
+// under actor.
+func Run(ctx context.Context, db *sql.DB, dryRun bool, actor string) ([]Result, error) {
+	policies, err := Policies(ctx, db)
+	if err != nil {
+		return nil, err
+	}
+	results := make([]Result, 0, len(policies))
+	for _, policy := range policies {
+		result, err := Apply(ctx, db, policy, dryRun, actor)
+		if err != nil {
+			return results, fmt.Errorf("%s: %w", policy.Domain, err)
+		}
+		results = append(results, result)
+	}
+	return results, nil
+}
+
+// Apply purges one website's expired data in a single transaction, or
+// counts it with dryRun
+func Apply(ctx context.Context, db *sql.DB, policy Policy, dryRun bool, actor string) (Result, error) {
+	result := Result{Policy: policy, DryRun: dryRun}
+	now := nowFunc()
+	if policy.EventDays > 0 {
+		cutoff := now.AddDate(0, 0, -policy.EventDays)
+		result.EventCutoff = &cutoff
+	}
+	if policy.RollupDays > 0 {
+		cutoff := now.AddDate(0, 0, -policy.RollupDays)
+		result.RollupCutoff = &cutoff
+	}
+	return apply(ctx, db, result, actor, models.AuditRetentionPurge, map[string]any{
+		"event_days":  policy.EventDays,
+		"rollup_days": policy.RollupDays,
+	})
+}
+
+// Prune deletes one website's raw events before before the way a retention
+// policy would, compacting them first, for a one-off cleanup. With rollups
+// its rollups, sketches and compacted days before then go too. With dryRun
+// it only counts them.
+func Prune(ctx context.Context, db *sql.DB, websiteID uuid.UUID, domain string, before time.Time, rollups, dryRun bool, actor string) (Result, error) {
+	result := Result{Policy: Policy{WebsiteID: websiteID, Domain: domain}, EventCutoff: &before, DryRun: dryRun}
+	if rollups {
+		result.RollupCutoff = &before
+	}
+	return apply(ctx, db, result, actor, models.AuditPrune, map[string]any{})
+}
+
+// apply deletes (or counts) what is before result's cutoffs in a single
+// transaction and records it in the audit log as action with details
+func apply(ctx context.Context, db *sql.DB, result Result, actor, action string, details map[string]any) (Result, error) {
+	policy, dryRun := result.Policy, result.DryRun
+	tx, err := db.BeginTx(ctx, nil)
+	if err != nil {
+		return result, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	if result.EventCutoff != nil {
+		eventCutoff, expire := *result.EventCutoff, true
+		if !dryRun {
+			if eventCutoff, expire, err = compact(ctx, tx, policy.WebsiteID, eventCutoff); err != nil {
+				return result, err
+			}
+		}
+		if expire {
+			if result.Events, err = purge(ctx, tx, dryRun, `
+				FROM website_event WHERE website_id = $1 AND created_at < $2
+			`, policy.WebsiteID, eventCutoff); err != nil {
+				return result, err
+			}
+			// Sessions whose events all expired; counted before the events are
+			// gone in a dry run, so the condition looks past the cutoff
+			if result.Sessions, err = purge(ctx, tx, dryRun, `
+				FROM session s
+				WHERE s.website_id = $1 AND s.created_at < $2
+				  AND NOT EXISTS (
+					SELECT 1 FROM website_event e
+					WHERE e.session_id = s.session_id AND e.website_id = $1 AND e.created_at >= $2
+				  )
+			`, policy.WebsiteID, eventCutoff); err != nil {
+				return result, err
+			}
+		}
+	}
+	if result.RollupCutoff != nil {
+		if result.Rollups, err = purge(ctx, tx, dryRun, `
+			FROM event_rollup WHERE website_id = $1 AND bucket < $2
+		`, policy.WebsiteID, *result.RollupCutoff); err != nil {
+			return result, err
+		}
+		// Visitor sketches and compacted days are kept as long as the rollups
+		sketches, err := purge(ctx, tx, dryRun, `
+			FROM visitor_sketch WHERE website_id = $1 AND bucket < $2
+		`, policy.WebsiteID, *result.RollupCutoff)
+		if err != nil {
+			return result, err
+		}
+		summaries, err := purge(ctx, tx, dryRun, `
+			FROM daily_summary WHERE website_id = $1 AND day < $2
+		`, policy.WebsiteID, *result.RollupCutoff)
+		if err != nil {
+			return result, err
+		}
+		result.Rollups += sketches + summaries
+	}
+
+	if dryRun || result.Empty() {
+		return result, nil
+	}
+	details["domain"] = policy.Domain
+	details["event_cutoff"] = result.EventCutoff
+	details["rollup_cutoff"] = result.RollupCutoff
+	details["events"] = result.Events
+	details["sessions"] = result.Sessions
+	details["rollups"] = result.Rollups
+	if err := models.RecordAudit(ctx, tx, action, &policy.WebsiteID, actor, details); err != nil {
+		return result, err
+	}
+	return result, tx.Commit()
+}
+
+// compact summarizes a website's raw events before cutoff so reports keep
+// them, and returns the time before which they may be deleted. expire is
+// false while rollups and sketches haven't reached the expired days yet.
+func compact(ctx context.Context, tx *sql.Tx, websiteID uuid.UUID, cutoff time.Time) (time.Time, bool, error) {
+	var compacted sql.NullTime
+	if err := tx.QueryRowContext(ctx, `SELECT compact_events($1, $2)`, cutoff, websiteID).Scan(&compacted); err != nil {
+		return cutoff, false, err
+	}
+	if !compacted.Valid {
+		return cutoff, false, nil
+	}
+	if compacted.Time.Before(cutoff) {
+		return compacted.Time, true, nil
+	}
+	return cutoff, true, nil
+}
+
+// purge deletes the rows matched by from (a FROM ... WHERE clause), or
+// counts them with dryRun
+func purge(ctx context.Context, tx *sql.Tx, dryRun bool, from string, args ...any) (int64, error) {
+	if dryRun {
+		var count int64
+		err := tx.QueryRowContext(ctx, `SELECT COUNT(*) `+from, args...).Scan(&count)
+		return count, err
+	}
+	res, err := tx.ExecContext(ctx, `DELETE `+from, args...)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+// Scheduler applies retention policies daily
+type Scheduler struct {
+	db     *sql.DB
+	dryRun bool
+}
+
+// NewScheduler creates a retention scheduler. With dryRun it only logs what
+// each run would delete.
+func NewScheduler(db *sql.DB, dryRun bool) *Scheduler {
+	return &Scheduler{db: db, dryRun: dryRun}
+}
+
+// Job applies the policies daily
+func (s *Scheduler) Job() database.Job {
+	description := "Apply per-website retention policies"
+	if s.dryRun {
+		description += " (dry run)"
+	}
+	return database.Job{
+		Name:        "retention",
+		Description: description,
+		Every:       purgeInterval,
+		Run:         s.purge,
+	}
+}
+
+func (s *Scheduler) purge(ctx context.Context) error {
+	results, err := Run(ctx, s.db, s.dryRun, "scheduler")
+	for _, r := range results {
+		if r.Empty() {
+			continue
+		}
+		msg := "purged expired website data"
+		if r.DryRun {
+			msg = "retention dry run: would purge website data"
+		}
+		logging.L().Info(msg,
+			slog.String("website", r.Domain),
+			slog.Int64("events", r.Events),
+			slog.Int64("sessions", r.Sessions),
+			slog.Int64("rollups", r.Rollups))
+	}
+	if err != nil {
+		return fmt.Errorf("retention purge failed: %w", err)
+	}
+	return nil
+}
@@ -0,0 +1,182 @@
+package retention
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func stubNow(t *testing.T, now time.Time) {
+	t.Helper()
+	original := nowFunc
+	nowFunc = func() time.Time { return now }
+	t.Cleanup(func() { nowFunc = original })
+}
+
+func TestApplyDryRunCounts(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	now := time.Date(2026, 6, 1, 3, 0, 0, 0, time.UTC)
+	stubNow(t, now)
+	policy := Policy{WebsiteID: uuid.New(), Domain: "example.com", EventDays: 180}
+	cutoff := now.AddDate(0, 0, -180)
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT COUNT\\(\\*\\)\\s+FROM website_event").WithArgs(policy.WebsiteID, cutoff).
+		WillReturnRows(sqlmock.NewRows([]string{"count"}).AddRow(1200))
+	mock.ExpectQuery("SELECT COUNT\\(\\*\\)\\s+FROM session").WithArgs(policy.WebsiteID, cutoff).
+		WillReturnRows(sqlmock.NewRows([]string{"count"}).AddRow(300))
+	mock.ExpectRollback()
+
+	result, err := Apply(context.Background(), db, policy, true, "cli")
+	require.NoError(t, err)
+	assert.True(t, result.DryRun)
+	assert.Equal(t, int64(1200), result.Events)
+	assert.Equal(t, int64(300), result.Sessions)
+	assert.Nil(t, result.RollupCutoff)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestApplyPurgesAndAudits(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	now := time.Date(2026, 6, 1, 3, 0, 0, 0, time.UTC)
+	stubNow(t, now)
+	policy := Policy{WebsiteID: uuid.New(), Domain: "example.com", EventDays: 30, RollupDays: 730}
+
+	// Raw events are deleted up to the start of the last compacted day
+	compacted := time.Date(2026, 5, 2, 0, 0, 0, 0, time.UTC)
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT compact_events").WithArgs(now.AddDate(0, 0, -30), policy.WebsiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"compacted"}).AddRow(compacted))
+	mock.ExpectExec("DELETE\\s+FROM website_event").WithArgs(policy.WebsiteID, compacted).
+		WillReturnResult(sqlmock.NewResult(0, 50))
+	mock.ExpectExec("DELETE\\s+FROM session").WithArgs(policy.WebsiteID, compacted).
+		WillReturnResult(sqlmock.NewResult(0, 10))
+	mock.ExpectExec("DELETE\\s+FROM event_rollup").WithArgs(policy.WebsiteID, now.AddDate(0, 0, -730)).
+		WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectExec("DELETE\\s+FROM visitor_sketch").WithArgs(policy.WebsiteID, now.AddDate(0, 0, -730)).
+		WillReturnResult(sqlmock.NewResult(0, 3))
+	mock.ExpectExec("DELETE\\s+FROM daily_summary").WithArgs(policy.WebsiteID, now.AddDate(0, 0, -730)).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+	mock.ExpectExec("INSERT INTO audit_log").
+		WithArgs("retention.purge", &policy.WebsiteID, "scheduler", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(1, 1))
+	mock.ExpectCommit()
+
+	result, err := Apply(context.Background(), db, policy, false, "scheduler")
+	require.NoError(t, err)
+	assert.Equal(t, int64(50), result.Events)
+	assert.Equal(t, int64(10), result.Sessions)
+	assert.Equal(t, int64(4), result.Rollups)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestApplyWithNothingExpiredSkipsAudit(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	policy := Policy{WebsiteID: uuid.New(), Domain: "example.com", RollupDays: 365}
+
+	mock.ExpectBegin()
+	mock.ExpectExec("DELETE\\s+FROM event_rollup").WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectExec("DELETE\\s+FROM visitor_sketch").WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectExec("DELETE\\s+FROM daily_summary").WillReturnResult(sqlmock.NewResult(0, 0))
+	mock.ExpectRollback()
+
+	result, err := Apply(context.Background(), db, policy, false, "scheduler")
+	require.NoError(t, err)
+	assert.True(t, result.Empty())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestApplyKeepsEventsUntilRollupsCatchUp(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	policy := Policy{WebsiteID: uuid.New(), Domain: "example.com", EventDays: 30}
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT compact_events").
+		WillReturnRows(sqlmock.NewRows([]string{"compacted"}).AddRow(nil))
+	mock.ExpectRollback()
+
+	result, err := Apply(context.Background(), db, policy, false, "scheduler")
+	require.NoError(t, err)
+	assert.True(t, result.Empty())
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPolicies(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	id := uuid.New()
+	mock.ExpectQuery("SELECT website_id, domain").
+		WillReturnRows(sqlmock.NewRows([]string{"website_id", "domain", "event_days", "rollup_days"}).
+			AddRow(id.String(), "example.com", 180, 0))
+
+	policies, err := Policies(context.Background(), db)
+	require.NoError(t, err)
+	assert.Equal(t, []Policy{{WebsiteID: id, Domain: "example.com", EventDays: 180}}, policies)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPruneDeletesBeforeDateAndAudits(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.New()
+	before := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT compact_events").WithArgs(before, websiteID).
+		WillReturnRows(sqlmock.NewRows([]string{"compacted"}).AddRow(before))
+	mock.ExpectExec("DELETE\\s+FROM website_event").WithArgs(websiteID, before).
+		WillReturnResult(sqlmock.NewResult(0, 70))
+	mock.ExpectExec("DELETE\\s+FROM session").WithArgs(websiteID, before).
+		WillReturnResult(sqlmock.NewResult(0, 20))
+	mock.ExpectExec("INSERT INTO audit_log").
+		WithArgs("website.prune", &websiteID, "cli", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(1, 1))
+	mock.ExpectCommit()
+
+	result, err := Prune(context.Background(), db, websiteID, "example.com", before, false, false, "cli")
+	require.NoError(t, err)
+	assert.Equal(t, int64(70), result.Events)
+	assert.Equal(t, int64(20), result.Sessions)
+	assert.Nil(t, result.RollupCutoff)
+	assert.False(t, result.Deferred)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestPruneReportsDeferredEvents(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	mock.ExpectBegin()
+	mock.ExpectQuery("SELECT compact_events").
+		WillReturnRows(sqlmock.NewRows([]string{"compacted"}).AddRow(nil))
+	mock.ExpectRollback()
+
+	result, err := Prune(context.Background(), db, uuid.New(), "example.com", time.Now(), false, false, "cli")
+	require.NoError(t, err)
+	assert.True(t, result.Empty())
+	assert.True(t, result.Deferred)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
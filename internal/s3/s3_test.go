@@ -0,0 +1,87 @@
+package s3
+
+import (
+	"context"
+	"encoding/hex"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func newTestClient(t *testing.T, handler http.HandlerFunc) *Client {
+	t.Helper()
+	server := httptest.NewServer(handler)
+	t.Cleanup(server.Close)
+
+	c, err := New(Config{Endpoint: server.URL, Bucket: "archive", AccessKey: "AKID", SecretKey: "secret"})
+	require.NoError(t, err)
+	c.now = func() time.Time { return time.Date(2025, time.February, 1, 3, 4, 5, 0, time.UTC) }
+	return c
+}
+
+func TestSigningKeyMatchesAWSExample(t *testing.T) {
+	// From the AWS documentation on deriving a Signature Version 4 key
+	key := signingKey("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam")
+	assert.Equal(t, "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d", hex.EncodeToString(key))
+}
+
+func TestPutSignsAndUploads(t *testing.T) {
+	var gotPath, gotAuth, gotDate, gotBody string
+	c := newTestClient(t, func(w http.ResponseWriter, r *http.Request) {
+		gotPath = r.URL.EscapedPath()
+		gotAuth = r.Header.Get("Authorization")
+		gotDate = r.Header.Get("X-Amz-Date")
+		body, _ := io.ReadAll(r.Body)
+		gotBody = string(body)
+	})
+
+	require.NoError(t, c.Put(context.Background(), "kaunta/events/2025-01 (1).parquet", strings.NewReader("PAR1"), 4))
+	assert.Equal(t, "/archive/kaunta/events/2025-01%20%281%29.parquet", gotPath)
+	assert.Equal(t, "20250201T030405Z", gotDate)
+	assert.Equal(t, "PAR1", gotBody)
+	assert.True(t, strings.HasPrefix(gotAuth,
+		"AWS4-HMAC-SHA256 Credential=AKID/20250201/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="), gotAuth)
+}
+
+func TestGetReturnsObjectsAndNotFound(t *testing.T) {
+	c := newTestClient(t, func(w http.ResponseWriter, r *http.Request) {
+		switch r.URL.Path {
+		case "/archive/present":
+			_, _ = w.Write([]byte("content"))
+		case "/archive/forbidden":
+			http.Error(w, "AccessDenied", http.StatusForbidden)
+		default:
+			http.NotFound(w, r)
+		}
+	})
+
+	body, err := c.Get(context.Background(), "present")
+	require.NoError(t, err)
+	data, _ := io.ReadAll(body)
+	_ = body.Close()
+	assert.Equal(t, "content", string(data))
+
+	_, err = c.Get(context.Background(), "missing")
+	assert.ErrorIs(t, err, ErrNotFound)
+
+	_, err = c.Get(context.Background(), "forbidden")
+	assert.ErrorContains(t, err, "403 Forbidden: AccessDenied")
+}
+
+func TestNewValidatesConfig(t *testing.T) {
+	_, err := New(Config{Endpoint: "minio:9000", Bucket: "b", AccessKey: "a", SecretKey: "s"})
+	assert.ErrorContains(t, err, "invalid endpoint")
+
+	_, err = New(Config{Endpoint: "http://minio:9000", AccessKey: "a", SecretKey: "s"})
+	assert.ErrorContains(t, err, "bucket is required")
+
+	c, err := New(Config{Endpoint: "http://minio:9000/", Bucket: "b", AccessKey: "a", SecretKey: "s"})
+	require.NoError(t, err)
+	assert.Equal(t, "us-east-1", c.region)
+}
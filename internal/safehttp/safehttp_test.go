@@ -0,0 +1,81 @@
+package safehttp
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"net/netip"
+	"net/url"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestIsPublic(t *testing.T) {
+	tests := map[string]bool{
+		"93.184.216.34":          true,
+		"2606:4700::1111":        true,
+		"127.0.0.1":              false,
+		"::1":                    false,
+		"10.1.2.3":               false,
+		"172.16.0.1":             false,
+		"192.168.1.1":            false,
+		"169.254.169.254":        false,
+		"100.100.100.200":        false,
+		"0.0.0.0":                false,
+		"fd00:ec2::254":          false,
+		"fe80::1":                false,
+		"::ffff:127.0.0.1":       false,
+		"::ffff:169.254.169.254": false,
+		"64:ff9b::a9fe:a9fe":     false,
+		"224.0.0.1":              false,
+	}
+	for addr, public := range tests {
+		assert.Equal(t, public, IsPublic(netip.MustParseAddr(addr)), addr)
+	}
+}
+
+func TestCheckURL(t *testing.T) {
+	assert.NoError(t, CheckURL("https://hooks.example.com/kaunta"))
+	assert.NoError(t, CheckURL("http://93.184.216.34:8080/hook"))
+
+	for _, raw := range []string{
+		"http://localhost:8080/hook",
+		"http://api.localhost/hook",
+		"http://127.0.0.1/hook",
+		"http://[::1]/hook",
+		"http://169.254.169.254/latest/meta-data/",
+		"http://10.0.0.5/hook",
+	} {
+		assert.ErrorIs(t, CheckURL(raw), ErrBlockedAddress, raw)
+	}
+	for _, raw := range []string{"ftp://example.com", "https://", "not a url"} {
+		assert.Error(t, CheckURL(raw), raw)
+	}
+}
+
+func TestClientRefusesLoopback(t *testing.T) {
+	called := false
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		called = true
+	}))
+	defer server.Close()
+
+	_, err := NewClient(time.Second).Get(server.URL)
+	require.Error(t, err)
+	assert.ErrorIs(t, err, ErrBlockedAddress)
+	assert.False(t, called)
+}
+
+func TestCheckRedirect(t *testing.T) {
+	redirect := func(target string) *http.Request {
+		u, err := url.Parse(target)
+		require.NoError(t, err)
+		return &http.Request{URL: u}
+	}
+
+	assert.NoError(t, checkRedirect(redirect("https://hooks.example.com/moved"), make([]*http.Request, 1)))
+	assert.ErrorIs(t, checkRedirect(redirect("http://169.254.169.254/"), make([]*http.Request, 1)), ErrBlockedAddress)
+	assert.Error(t, checkRedirect(redirect("https://hooks.example.com/moved"), make([]*http.Request, maxRedirects)))
+}
@@ -7,6 +7,8 @@ import (
 	"fmt"
 	"net/http"
 	"runtime"
+	"sort"
+	"strconv"
 	"strings"
 	"time"
 
@@ -14,14 +16,16 @@ import (
 )
 
 const (
-	githubAPIURL = "https://api.github.com"
-	userAgent    = "kaunta-selfupdate"
+	githubAPIURL   = "https://api.github.com"
+	userAgent      = "kaunta-selfupdate"
+	migrationsPath = "internal/database/migrations"
 )
 
 // Release represents a GitHub release with its assets.
 type Release struct {
 	TagName    string    `json:"tag_name"`
 	Name       string    `json:"name"`
+	HTMLURL    string    `json:"html_url"`
 	Draft      bool      `json:"draft"`
 	Prerelease bool      `json:"prerelease"`
 	CreatedAt  time.Time `json:"created_at"`
@@ -40,6 +44,7 @@ type Asset struct {
 // Client handles GitHub API requests for release detection.
 type Client struct {
 	httpClient *http.Client
+	apiURL     string
 	owner      string
 	repo       string
 }
@@ -48,6 +53,7 @@ type Client struct {
 func NewClient(owner, repo string) *Client {
 	return &Client{
 		httpClient: &http.Client{Timeout: 30 * time.Second},
+		apiURL:     githubAPIURL,
 		owner:      owner,
 		repo:       repo,
 	}
@@ -55,18 +61,9 @@ func NewClient(owner, repo string) *Client {
 
 // DetectLatest fetches the latest non-draft, non-prerelease version from GitHub.
 func (c *Client) DetectLatest() (*Release, error) {
-	url := fmt.Sprintf("%s/repos/%s/%s/releases/latest", githubAPIURL, c.owner, c.repo)
+	url := fmt.Sprintf("%s/repos/%s/%s/releases/latest", c.apiURL, c.owner, c.repo)
 
-	req, err := http.NewRequest(http.MethodGet, url, nil)
-	if err != nil {
-		return nil, fmt.Errorf("failed to create request: %w", err)
-	}
-
-	req.Header.Set("Accept", "application/vnd.github+json")
-	req.Header.Set("User-Agent", userAgent)
-	req.Header.Set("X-GitHub-Api-Version", "2022-11-28")
-
-	resp, err := c.httpClient.Do(req)
+	resp, err := c.get(url)
 	if err != nil {
 		return nil, fmt.Errorf("failed to fetch releases: %w", err)
 	}
@@ -96,6 +93,67 @@ func (c *Client) DetectLatest() (*Release, error) {
 	return &release, nil
 }
 
+// Migration is a database migration shipped with a release
+type Migration struct {
+	Version uint   `json:"version"`
+	Name    string `json:"name"`
+}
+
+// Migrations lists the database migrations in the repository at tag, in order
+func (c *Client) Migrations(tag string) ([]Migration, error) {
+	url := fmt.Sprintf("%s/repos/%s/%s/contents/%s?ref=%s", c.apiURL, c.owner, c.repo, migrationsPath, tag)
+
+	resp, err := c.get(url)
+	if err != nil {
+		return nil, fmt.Errorf("failed to fetch migrations: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("GitHub API error: %s", resp.Status)
+	}
+
+	var files []struct {
+		Name string `json:"name"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&files); err != nil {
+		return nil, fmt.Errorf("failed to decode response: %w", err)
+	}
+
+	var migrations []Migration
+	for _, f := range files {
+		// 000057_rebuild_rollups.up.sql
+		base, ok := strings.CutSuffix(f.Name, ".up.sql")
+		if !ok {
+			continue
+		}
+		num, name, ok := strings.Cut(base, "_")
+		if !ok {
+			continue
+		}
+		version, err := strconv.ParseUint(num, 10, 64)
+		if err != nil {
+			continue
+		}
+		migrations = append(migrations, Migration{Version: uint(version), Name: name})
+	}
+	sort.Slice(migrations, func(i, j int) bool { return migrations[i].Version < migrations[j].Version })
+	return migrations, nil
+}
+
+func (c *Client) get(url string) (*http.Response, error) {
+	req, err := http.NewRequest(http.MethodGet, url, nil)
+	if err != nil {
+		return nil, fmt.Errorf("failed to create request: %w", err)
+	}
+
+	req.Header.Set("Accept", "application/vnd.github+json")
+	req.Header.Set("User-Agent", userAgent)
+	req.Header.Set("X-GitHub-Api-Version", "2022-11-28")
+
+	return c.httpClient.Do(req)
+}
+
 // FindAsset finds the appropriate asset for the current platform.
 // Asset naming convention: kaunta_{os}_{arch}.tar.gz (with ore_* fallbacks)
 func (r *Release) FindAsset() (*Asset, error) {
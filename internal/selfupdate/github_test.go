@@ -0,0 +1,33 @@
+package selfupdate
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestMigrations(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		assert.Equal(t, "/repos/seuros/kaunta/contents/internal/database/migrations", r.URL.Path)
+		assert.Equal(t, "v1.2.0", r.URL.Query().Get("ref"))
+		_, _ = w.Write([]byte(`[
+			{"name": "000058_visit_goals.up.sql"},
+			{"name": "000002_add_indexes.up.sql"},
+			{"name": "000058_visit_goals.down.sql"},
+			{"name": "README.md"}
+		]`))
+	}))
+	defer server.Close()
+
+	client := NewClient("seuros", "kaunta")
+	client.apiURL = server.URL
+	migrations, err := client.Migrations("v1.2.0")
+	require.NoError(t, err)
+	assert.Equal(t, []Migration{
+		{Version: 2, Name: "add_indexes"},
+		{Version: 58, Name: "visit_goals"},
+	}, migrations)
+}
@@ -27,26 +27,62 @@ func NewUpdater() *Updater {
 	}
 }
 
-// UpdateTo downloads and applies the update from the given URL.
-// The URL may be a .tar.gz archive (containing the executable) or a raw platform binary.
-func (u *Updater) UpdateTo(downloadURL, targetPath string) error {
-	// Download the archive
+// Update downloads a release asset, checks it against the release's signed
+// checksums and applies it. Nothing is replaced unless both check out.
+func (u *Updater) Update(release *Release, asset *Asset, targetPath string) error {
+	checksums, err := u.downloadAsset(release, ChecksumsAsset)
+	if err != nil {
+		return err
+	}
+	signature, err := u.downloadAsset(release, SignatureAsset)
+	if err != nil {
+		return err
+	}
+	if err := VerifyChecksums(checksums, signature); err != nil {
+		return err
+	}
+
+	archiveData, err := u.download(asset.BrowserDownloadURL)
+	if err != nil {
+		return err
+	}
+	if err := VerifyAsset(checksums, asset.Name, archiveData); err != nil {
+		return err
+	}
+	return apply(archiveData, asset.BrowserDownloadURL, targetPath)
+}
+
+// downloadAsset downloads the release asset called name
+func (u *Updater) downloadAsset(release *Release, name string) ([]byte, error) {
+	for _, a := range release.Assets {
+		if a.Name == name {
+			return u.download(a.BrowserDownloadURL)
+		}
+	}
+	return nil, fmt.Errorf("release v%s has no %s; it can't be verified", release.Version, name)
+}
+
+func (u *Updater) download(downloadURL string) ([]byte, error) {
 	resp, err := u.httpClient.Get(downloadURL)
 	if err != nil {
-		return fmt.Errorf("failed to download: %w", err)
+		return nil, fmt.Errorf("failed to download: %w", err)
 	}
 	defer func() { _ = resp.Body.Close() }()
 
 	if resp.StatusCode != http.StatusOK {
-		return fmt.Errorf("download failed: %s", resp.Status)
+		return nil, fmt.Errorf("download failed: %s", resp.Status)
 	}
 
-	// Read the entire response into memory for extraction
-	archiveData, err := io.ReadAll(resp.Body)
+	data, err := io.ReadAll(resp.Body)
 	if err != nil {
-		return fmt.Errorf("failed to read archive: %w", err)
+		return nil, fmt.Errorf("failed to read download: %w", err)
 	}
+	return data, nil
+}
 
+// apply replaces the executable at targetPath with the downloaded asset.
+// The asset may be a .tar.gz archive (containing the executable) or a raw platform binary.
+func apply(archiveData []byte, downloadURL, targetPath string) error {
 	// Determine binary format: tar.gz archive or raw binary
 	var binary []byte
 	if looksLikeGzip(archiveData) || strings.HasSuffix(strings.ToLower(downloadURL), ".tar.gz") {
@@ -81,9 +117,9 @@ func (u *Updater) UpdateTo(downloadURL, targetPath string) error {
 	return nil
 }
 
-// UpdateTo downloads and applies the update using a default Updater.
-func UpdateTo(downloadURL, targetPath string) error {
-	return NewUpdater().UpdateTo(downloadURL, targetPath)
+// Update verifies and applies a release asset using a default Updater.
+func Update(release *Release, asset *Asset, targetPath string) error {
+	return NewUpdater().Update(release, asset, targetPath)
 }
 
 func looksLikeGzip(b []byte) bool {
@@ -0,0 +1,62 @@
+package selfupdate
+
+import (
+	"bufio"
+	"bytes"
+	"crypto/ed25519"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"strings"
+)
+
+const (
+	// ChecksumsAsset lists the SHA-256 of every release archive, as written by sha256sum
+	ChecksumsAsset = "checksums.txt"
+	// SignatureAsset is the raw Ed25519 signature of ChecksumsAsset
+	SignatureAsset = "checksums.txt.sig"
+)
+
+// PublicKey is the base64 Ed25519 key release checksums are signed with.
+// Release builds set it with -ldflags "-X .../selfupdate.PublicKey=...".
+var PublicKey string
+
+// ErrNoPublicKey is returned when the binary was built without PublicKey
+var ErrNoPublicKey = errors.New("this build has no release signing key; download the release manually")
+
+// VerifyChecksums checks the signature of a release's checksums file
+func VerifyChecksums(checksums, signature []byte) error {
+	if PublicKey == "" {
+		return ErrNoPublicKey
+	}
+	key, err := base64.StdEncoding.DecodeString(PublicKey)
+	if err != nil || len(key) != ed25519.PublicKeySize {
+		return errors.New("invalid release signing key in this build")
+	}
+	if !ed25519.Verify(ed25519.PublicKey(key), checksums, signature) {
+		return errors.New("checksums signature is invalid")
+	}
+	return nil
+}
+
+// VerifyAsset checks data against its line in a verified checksums file
+func VerifyAsset(checksums []byte, name string, data []byte) error {
+	scanner := bufio.NewScanner(bytes.NewReader(checksums))
+	for scanner.Scan() {
+		fields := strings.Fields(scanner.Text())
+		if len(fields) != 2 || strings.TrimPrefix(fields[1], "*") != name {
+			continue
+		}
+		sum := sha256.Sum256(data)
+		if !strings.EqualFold(fields[0], hex.EncodeToString(sum[:])) {
+			return fmt.Errorf("checksum mismatch for %s", name)
+		}
+		return nil
+	}
+	if err := scanner.Err(); err != nil {
+		return err
+	}
+	return fmt.Errorf("%s is not listed in %s", name, ChecksumsAsset)
+}
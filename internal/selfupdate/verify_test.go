@@ -0,0 +1,54 @@
+package selfupdate
+
+import (
+	"crypto/ed25519"
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
+	"fmt"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func withPublicKey(t *testing.T, key string) {
+	t.Helper()
+	original := PublicKey
+	PublicKey = key
+	t.Cleanup(func() { PublicKey = original })
+}
+
+func TestVerifyChecksums(t *testing.T) {
+	pub, priv, err := ed25519.GenerateKey(rand.Reader)
+	require.NoError(t, err)
+	withPublicKey(t, base64.StdEncoding.EncodeToString(pub))
+
+	checksums := []byte("abc123  kaunta_linux_amd64.tar.gz\n")
+	signature := ed25519.Sign(priv, checksums)
+	assert.NoError(t, VerifyChecksums(checksums, signature))
+
+	tampered := []byte("def456  kaunta_linux_amd64.tar.gz\n")
+	assert.Error(t, VerifyChecksums(tampered, signature))
+
+	_, otherKey, err := ed25519.GenerateKey(rand.Reader)
+	require.NoError(t, err)
+	assert.Error(t, VerifyChecksums(checksums, ed25519.Sign(otherKey, checksums)))
+}
+
+func TestVerifyChecksumsWithoutKey(t *testing.T) {
+	withPublicKey(t, "")
+	assert.ErrorIs(t, VerifyChecksums([]byte("x"), []byte("y")), ErrNoPublicKey)
+}
+
+func TestVerifyAsset(t *testing.T) {
+	archive := []byte("release archive")
+	sum := sha256.Sum256(archive)
+	checksums := fmt.Appendf(nil, "%s  kaunta_darwin_arm64.tar.gz\n%s  kaunta_linux_amd64.tar.gz\n",
+		hex.EncodeToString(make([]byte, 32)), hex.EncodeToString(sum[:]))
+
+	assert.NoError(t, VerifyAsset(checksums, "kaunta_linux_amd64.tar.gz", archive))
+	assert.ErrorContains(t, VerifyAsset(checksums, "kaunta_darwin_arm64.tar.gz", archive), "checksum mismatch")
+	assert.ErrorContains(t, VerifyAsset(checksums, "kaunta_freebsd_amd64.tar.gz", archive), "not listed")
+}
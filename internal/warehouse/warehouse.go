@@ -0,0 +1,144 @@
+// Package warehouse produces finalized daily aggregates for syncing into a
+// data warehouse. A day is only served once it has ended (UTC), so a nightly
+// job that loads "yesterday" gets the same rows however often it runs.
+package warehouse
+
+import (
+	"context"
+	"database/sql"
+	"encoding/csv"
+	"fmt"
+	"io"
+	"strconv"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+// DateLayout is the format of the date a rollup is requested for
+const DateLayout = "2006-01-02"
+
+// Rollup is one website's aggregates for one UTC day
+type Rollup struct {
+	WebsiteID uuid.UUID `json:"website_id"`
+	Date      string    `json:"date"`
+	Rows      []Row     `json:"rows"`
+}
+
+// Row aggregates the day's traffic for one value of a dimension
+type Row struct {
+	Dimension string `json:"dimension"` // page, referrer, country or event
+	Value     string `json:"value"`
+	Pageviews int64  `json:"pageviews"`
+	Events    int64  `json:"events"`
+	Visitors  int64  `json:"visitors"`
+}
+
+// CSVHeader is the header row written by WriteCSV
+var CSVHeader = []string{"date", "website_id", "dimension", "value", "pageviews", "events", "visitors"}
+
+// ParseDate parses a YYYY-MM-DD date and rejects days that have not ended
+// yet at now. An empty string means the day before now.
+func ParseDate(s string, now time.Time) (time.Time, error) {
+	today := now.UTC().Truncate(24 * time.Hour)
+	if s == "" {
+		return today.AddDate(0, 0, -1), nil
+	}
+
+	day, err := time.Parse(DateLayout, s)
+	if err != nil {
+		return time.Time{}, fmt.Errorf("invalid date %q (use YYYY-MM-DD)", s)
+	}
+	if !day.Before(today) {
+		return time.Time{}, fmt.Errorf("date %s is not finalized yet; the latest available day is %s",
+			s, today.AddDate(0, 0, -1).Format(DateLayout))
+	}
+	return day, nil
+}
+
+// dailyQuery aggregates one website's events in [$2, $3) by every dimension
+const dailyQuery = `
+WITH day AS (
+  SELECT e.session_id, e.event_type, e.event_name, e.url_path, e.referrer_domain
+  FROM website_event e
+  WHERE e.website_id = $1
+    AND e.created_at >= $2
+    AND e.created_at < $3
+)
+SELECT 'page', d.url_path,
+  COUNT(*) FILTER (WHERE d.event_type = 1), COUNT(*) FILTER (WHERE d.event_type = 2), COUNT(DISTINCT d.session_id)
+FROM day d
+WHERE d.url_path IS NOT NULL
+GROUP BY d.url_path
+UNION ALL
+SELECT 'referrer', d.referrer_domain,
+  COUNT(*) FILTER (WHERE d.event_type = 1), COUNT(*) FILTER (WHERE d.event_type = 2), COUNT(DISTINCT d.session_id)
+FROM day d
+WHERE d.referrer_domain IS NOT NULL AND d.referrer_domain <> ''
+GROUP BY d.referrer_domain
+UNION ALL
+SELECT 'country', s.country,
+  COUNT(*) FILTER (WHERE d.event_type = 1), COUNT(*) FILTER (WHERE d.event_type = 2), COUNT(DISTINCT d.session_id)
+FROM day d
+JOIN session s ON s.session_id = d.session_id
+WHERE s.country IS NOT NULL
+GROUP BY s.country
+UNION ALL
+SELECT 'event', d.event_name, 0, COUNT(*), COUNT(DISTINCT d.session_id)
+FROM day d
+WHERE d.event_type = 2 AND d.event_name IS NOT NULL
+GROUP BY d.event_name
+ORDER BY 1, 2`
+
+// Querier runs the rollup query: a *sql.DB, or a *sql.Tx such as the
+// tenant-scoped one from database.StatsQuerier
+type Querier interface {
+	QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error)
+}
+
+// Daily aggregates websiteID's traffic on the UTC day starting at day.
+// Rows are ordered by dimension and value so repeated exports are identical.
+func Daily(ctx context.Context, db Querier, websiteID uuid.UUID, day time.Time) (*Rollup, error) {
+	start := day.UTC().Truncate(24 * time.Hour)
+	rows, err := db.QueryContext(ctx, dailyQuery, websiteID, start, start.AddDate(0, 0, 1))
+	if err != nil {
+		return nil, fmt.Errorf("query daily rollup: %w", err)
+	}
+	defer func() { _ = rows.Close() }()
+
+	rollup := &Rollup{WebsiteID: websiteID, Date: start.Format(DateLayout), Rows: make([]Row, 0)}
+	for rows.Next() {
+		var row Row
+		if err := rows.Scan(&row.Dimension, &row.Value, &row.Pageviews, &row.Events, &row.Visitors); err != nil {
+			return nil, fmt.Errorf("scan daily rollup: %w", err)
+		}
+		rollup.Rows = append(rollup.Rows, row)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, fmt.Errorf("read daily rollup: %w", err)
+	}
+	return rollup, nil
+}
+
+// WriteCSV writes the rollup as CSV with the date and website on every row,
+// so files from several days and websites can be loaded into one table
+func WriteCSV(w io.Writer, rollup *Rollup) error {
+	cw := csv.NewWriter(w)
+	if err := cw.Write(CSVHeader); err != nil {
+		return err
+	}
+	websiteID := rollup.WebsiteID.String()
+	for _, row := range rollup.Rows {
+		record := []string{
+			rollup.Date, websiteID, row.Dimension, row.Value,
+			strconv.FormatInt(row.Pageviews, 10),
+			strconv.FormatInt(row.Events, 10),
+			strconv.FormatInt(row.Visitors, 10),
+		}
+		if err := cw.Write(record); err != nil {
+			return err
+		}
+	}
+	cw.Flush()
+	return cw.Error()
+}
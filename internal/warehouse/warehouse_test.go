@@ -0,0 +1,62 @@
+package warehouse
+
+import (
+	"bytes"
+	"context"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseDate(t *testing.T) {
+	now := time.Date(2026, 5, 2, 0, 30, 0, 0, time.UTC)
+
+	day, err := ParseDate("", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2026, 5, 1, 0, 0, 0, 0, time.UTC), day)
+
+	day, err = ParseDate("2026-04-30", now)
+	require.NoError(t, err)
+	assert.Equal(t, time.Date(2026, 4, 30, 0, 0, 0, 0, time.UTC), day)
+
+	_, err = ParseDate("2026-05-02", now)
+	require.Error(t, err)
+	assert.Contains(t, err.Error(), "not finalized yet; the latest available day is 2026-05-01")
+
+	_, err = ParseDate("05/01/2026", now)
+	assert.Error(t, err)
+}
+
+func TestDaily(t *testing.T) {
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	websiteID := uuid.MustParse("11111111-2222-3333-4444-555555555555")
+	day := time.Date(2026, 5, 1, 0, 0, 0, 0, time.UTC)
+
+	mock.ExpectQuery("WITH day AS").
+		WithArgs(websiteID, day, day.AddDate(0, 0, 1)).
+		WillReturnRows(sqlmock.NewRows([]string{"dimension", "value", "pageviews", "events", "visitors"}).
+			AddRow("country", "DE", 12, 1, 5).
+			AddRow("event", "signup", 0, 3, 2).
+			AddRow("page", "/pricing, plans", 9, 0, 4))
+
+	rollup, err := Daily(context.Background(), db, websiteID, day.Add(13*time.Hour))
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+
+	assert.Equal(t, "2026-05-01", rollup.Date)
+	assert.Equal(t, Row{Dimension: "event", Value: "signup", Events: 3, Visitors: 2}, rollup.Rows[1])
+
+	var buf bytes.Buffer
+	require.NoError(t, WriteCSV(&buf, rollup))
+	assert.Equal(t, "date,website_id,dimension,value,pageviews,events,visitors\n"+
+		"2026-05-01,11111111-2222-3333-4444-555555555555,country,DE,12,1,5\n"+
+		"2026-05-01,11111111-2222-3333-4444-555555555555,event,signup,0,3,2\n"+
+		"2026-05-01,11111111-2222-3333-4444-555555555555,page,\"/pricing, plans\",9,0,4\n", buf.String())
+}
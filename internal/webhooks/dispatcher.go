@@ -0,0 +1,290 @@
+// Package webhooks delivers queued webhook payloads. Triggers (goal
+// completions, fired alerts, weekly summaries) write rows to the
+// webhook_delivery outbox; the Dispatcher POSTs them signed with each
+// webhook's secret and retries failures with backoff.
+package webhooks
+
+import (
+	"bytes"
+	"context"
+	"crypto/hmac"
+	"crypto/sha256"
+	"database/sql"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"strconv"
+	"time"
+
+	"log/slog"
+
+	"github.com/seuros/kaunta/internal/database"
+	"github.com/seuros/kaunta/internal/logging"
+	"github.com/seuros/kaunta/internal/models"
+	"github.com/seuros/kaunta/internal/report"
+	"github.com/seuros/kaunta/internal/safehttp"
+)
+
+var (
+	nowFunc = time.Now
+	// dispatchInterval is how often due deliveries are sent
+	dispatchInterval = 15 * time.Second
+	// maintenanceInterval is how often weekly summaries are queued and old
+	// deliveries pruned
+	maintenanceInterval = time.Hour
+	// retryDelays is the wait before each retry; a delivery fails for good
+	// once they are used up
+	retryDelays = []time.Duration{time.Minute, 5 * time.Minute, 30 * time.Minute, 2 * time.Hour, 12 * time.Hour}
+	// deliveryRetention is how long finished deliveries stay in the log
+	deliveryRetention = 30 * 24 * time.Hour
+	// claimBatchSize caps the deliveries sent per dispatch
+	claimBatchSize = 50
+	// claimLease is how long a claimed delivery is hidden from other dispatchers
+	claimLease = 2 * time.Minute
+)
+
+// Envelope is the JSON body POSTed to webhook URLs
+type Envelope struct {
+	ID        string          `json:"id"`
+	Event     string          `json:"event"`
+	WebsiteID string          `json:"website_id"`
+	CreatedAt string          `json:"created_at"`
+	Data      json.RawMessage `json:"data"`
+}
+
+// WeeklySummary is the data of a summary.weekly payload
+type WeeklySummary struct {
+	Website          string        `json:"website"`
+	Days             int           `json:"days"`
+	Pageviews        int64         `json:"pageviews"`
+	Visitors         int64         `json:"visitors"`
+	BounceRate       float64       `json:"bounce_rate"`
+	AvgVisitDuration float64       `json:"avg_visit_duration"`
+	TopPages         []report.Item `json:"top_pages"`
+	TopReferrers     []report.Item `json:"top_referrers"`
+	TopCountries     []report.Item `json:"top_countries"`
+}
+
+// Sign returns the X-Kaunta-Signature value for a payload: the hex
+// HMAC-SHA256 of "<timestamp>.<body>" keyed with the webhook secret
+func Sign(secret string, timestamp int64, body []byte) string {
+	mac := hmac.New(sha256.New, []byte(secret))
+	_, _ = fmt.Fprintf(mac, "%d.", timestamp)
+	_, _ = mac.Write(body)
+	return "sha256=" + hex.EncodeToString(mac.Sum(nil))
+}
+
+// nextAttempt decides what happens after a failed attempt, given how many
+// attempts have now been made
+func nextAttempt(attempts int, now time.Time) (string, time.Time) {
+	if attempts > len(retryDelays) {
+		return models.WebhookDeliveryFailed, now
+	}
+	return models.WebhookDeliveryPending, now.Add(retryDelays[attempts-1])
+}
+
+// weekStart returns the Monday 00:00 UTC that starts t's week
+func weekStart(t time.Time) time.Time {
+	t = t.UTC()
+	daysSinceMonday := (int(t.Weekday()) + 6) % 7
+	return time.Date(t.Year(), t.Month(), t.Day()-daysSinceMonday, 0, 0, 0, 0, time.UTC)
+}
+
+// Dispatcher sends due webhook deliveries on a fixed interval
+type Dispatcher struct {
+	db       *sql.DB
+	client   *http.Client
+	stopChan chan struct{}
+}
+
+// NewDispatcher creates a new webhook dispatcher
+func NewDispatcher(db *sql.DB) *Dispatcher {
+	return &Dispatcher{
+		db:       db,
+		client:   safehttp.NewClient(10 * time.Second),
+		stopChan: make(chan struct{}),
+	}
+}
+
+// Start begins dispatching webhooks
+func (d *Dispatcher) Start() {
+	logging.L().Info("starting webhook dispatcher")
+	go d.run()
+}
+
+// Stop gracefully stops the dispatcher
+func (d *Dispatcher) Stop() {
+	close(d.stopChan)
+}
+
+func (d *Dispatcher) run() {
+	ticker := time.NewTicker(dispatchInterval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ticker.C:
+			d.dispatchDue(context.Background())
+		case <-d.stopChan:
+			return
+		}
+	}
+}
+
+// MaintenanceJob queues the weekly summaries and prunes the delivery log.
+// Deliveries are claimed row by row, but only one instance queues summaries
+// and prunes.
+func (d *Dispatcher) MaintenanceJob() database.Job {
+	return database.Job{
+		Name:        "webhook-maintenance",
+		Description: "Queue weekly summary webhooks and prune the delivery log",
+		Every:       maintenanceInterval,
+		Run:         d.maintain,
+	}
+}
+
+// dispatchDue sends every delivery whose next attempt is due
+func (d *Dispatcher) dispatchDue(ctx context.Context) {
+	due, err := models.ClaimDueWebhookDeliveries(ctx, d.db, claimBatchSize, claimLease)
+	if err != nil {
+		logging.L().Warn("failed to claim webhook deliveries", slog.Any("error", err))
+		return
+	}
+
+	for _, delivery := range due {
+		responseStatus, err := d.send(ctx, delivery)
+
+		status, next, lastError := models.WebhookDeliveryDelivered, nowFunc(), ""
+		if err != nil {
+			status, next = nextAttempt(delivery.Attempts+1, nowFunc())
+			lastError = deliveryError(responseStatus, err)
+			logging.L().Warn("webhook delivery failed",
+				slog.String("delivery_id", delivery.ID.String()),
+				slog.String("event", delivery.Event),
+				slog.Int("attempt", delivery.Attempts+1),
+				slog.Any("error", err))
+		}
+
+		if err := models.RecordWebhookAttempt(ctx, d.db, delivery.ID, status, responseStatus, lastError, next); err != nil {
+			logging.L().Warn("failed to record webhook attempt", slog.String("delivery_id", delivery.ID.String()), slog.Any("error", err))
+		}
+	}
+}
+
+// send POSTs one delivery, returning the response status (0 without a response)
+func (d *Dispatcher) send(ctx context.Context, delivery models.DueWebhookDelivery) (int, error) {
+	body, err := json.Marshal(Envelope{
+		ID:        delivery.ID.String(),
+		Event:     delivery.Event,
+		WebsiteID: delivery.WebsiteID.String(),
+		CreatedAt: delivery.CreatedAt.UTC().Format(time.RFC3339),
+		Data:      delivery.Payload,
+	})
+	if err != nil {
+		return 0, err
+	}
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, delivery.URL, bytes.NewReader(body))
+	if err != nil {
+		return 0, err
+	}
+	timestamp := nowFunc().Unix()
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("User-Agent", "Kaunta-Webhooks")
+	req.Header.Set("X-Kaunta-Event", delivery.Event)
+	req.Header.Set("X-Kaunta-Delivery", delivery.ID.String())
+	req.Header.Set("X-Kaunta-Timestamp", strconv.FormatInt(timestamp, 10))
+	req.Header.Set("X-Kaunta-Signature", Sign(delivery.Secret, timestamp, body))
+
+	resp, err := d.client.Do(req)
+	if err != nil {
+		return 0, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+	_, _ = io.Copy(io.Discard, io.LimitReader(resp.Body, 64<<10))
+
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return resp.StatusCode, fmt.Errorf("webhook returned status %d", resp.StatusCode)
+	}
+	return resp.StatusCode, nil
+}
+
+// deliveryError describes a failed attempt for the delivery log, which the
+// webhook's owner can read. Transport errors are reduced to their kind so
+// the log can't be used to probe what answers where; the full error is
+// only logged on the server.
+func deliveryError(responseStatus int, err error) string {
+	var netErr net.Error
+	switch {
+	case responseStatus != 0:
+		return fmt.Sprintf("webhook returned status %d", responseStatus)
+	case errors.Is(err, safehttp.ErrBlockedAddress):
+		return "destination is not a public address"
+	case errors.As(err, &netErr) && netErr.Timeout():
+		return "request timed out"
+	default:
+		return "request failed"
+	}
+}
+
+// maintain queues this week's summaries and prunes the delivery log
+func (d *Dispatcher) maintain(ctx context.Context) error {
+	d.queueWeeklySummaries(ctx)
+
+	cutoff := nowFunc().Add(-deliveryRetention)
+	n, err := models.PruneWebhookDeliveries(ctx, d.db, cutoff)
+	if err != nil {
+		return fmt.Errorf("failed to prune webhook deliveries: %w", err)
+	}
+	if n > 0 {
+		logging.L().Info("pruned webhook deliveries", slog.Int64("count", n))
+	}
+	return nil
+}
+
+// queueWeeklySummaries queues last week's summary for every summary.weekly
+// webhook that has not had one since Monday
+func (d *Dispatcher) queueWeeklySummaries(ctx context.Context) {
+	now := nowFunc()
+	hooks, err := models.ListWeeklySummaryWebhooksDue(ctx, d.db, weekStart(now))
+	if err != nil {
+		logging.L().Warn("failed to load weekly summary webhooks", slog.Any("error", err))
+		return
+	}
+
+	summaries := make(map[string]*WeeklySummary)
+	for _, hook := range hooks {
+		summary, ok := summaries[hook.WebsiteID.String()]
+		if !ok {
+			r, err := report.Build(ctx, d.db, hook.WebsiteID, 7)
+			if err != nil {
+				logging.L().Warn("failed to build weekly summary", slog.String("website_id", hook.WebsiteID.String()), slog.Any("error", err))
+				continue
+			}
+			summary = &WeeklySummary{
+				Website:          r.Domain,
+				Days:             r.Days,
+				Pageviews:        r.Pageviews,
+				Visitors:         r.Visitors,
+				BounceRate:       r.BounceRate,
+				AvgVisitDuration: r.AvgVisitDuration,
+				TopPages:         r.Pages,
+				TopReferrers:     r.Referrers,
+				TopCountries:     r.Countries,
+			}
+			summaries[hook.WebsiteID.String()] = summary
+		}
+
+		if err := models.EnqueueWebhookDelivery(ctx, d.db, hook.ID, hook.UserID, models.WebhookWeeklySummary, summary); err != nil {
+			logging.L().Warn("failed to queue weekly summary", slog.String("webhook_id", hook.ID.String()), slog.Any("error", err))
+			continue
+		}
+		if err := models.MarkWebhookSummaryQueued(ctx, d.db, hook.ID, now); err != nil {
+			logging.L().Warn("failed to record weekly summary", slog.String("webhook_id", hook.ID.String()), slog.Any("error", err))
+		}
+	}
+}
@@ -0,0 +1,157 @@
+package webhooks
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"io"
+	"net"
+	"net/http"
+	"net/http/httptest"
+	"strconv"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/google/uuid"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+
+	"github.com/seuros/kaunta/internal/models"
+)
+
+func TestSign(t *testing.T) {
+	body := []byte(`{"event":"ping"}`)
+	sig := Sign("secret", 1700000000, body)
+
+	assert.Equal(t, Sign("secret", 1700000000, body), sig)
+	assert.NotEqual(t, sig, Sign("other", 1700000000, body))
+	assert.NotEqual(t, sig, Sign("secret", 1700000001, body))
+	assert.Len(t, sig, len("sha256=")+64)
+}
+
+func TestNextAttempt(t *testing.T) {
+	now := time.Date(2026, 3, 4, 12, 0, 0, 0, time.UTC)
+
+	status, next := nextAttempt(1, now)
+	assert.Equal(t, models.WebhookDeliveryPending, status)
+	assert.Equal(t, now.Add(time.Minute), next)
+
+	status, next = nextAttempt(len(retryDelays), now)
+	assert.Equal(t, models.WebhookDeliveryPending, status)
+	assert.Equal(t, now.Add(retryDelays[len(retryDelays)-1]), next)
+
+	status, _ = nextAttempt(len(retryDelays)+1, now)
+	assert.Equal(t, models.WebhookDeliveryFailed, status)
+}
+
+func TestWeekStart(t *testing.T) {
+	monday := time.Date(2026, 3, 2, 0, 0, 0, 0, time.UTC)
+	assert.Equal(t, monday, weekStart(time.Date(2026, 3, 2, 9, 30, 0, 0, time.UTC)))
+	assert.Equal(t, monday, weekStart(time.Date(2026, 3, 8, 23, 59, 0, 0, time.UTC)))
+	assert.Equal(t, monday.AddDate(0, 0, 7), weekStart(time.Date(2026, 3, 9, 0, 0, 0, 0, time.UTC)))
+}
+
+func TestDispatchDueSendsSignedPayload(t *testing.T) {
+	var received *http.Request
+	var receivedBody []byte
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		received = r
+		receivedBody, _ = io.ReadAll(r.Body)
+		w.WriteHeader(http.StatusNoContent)
+	}))
+	defer server.Close()
+
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	deliveryID, webhookID, websiteID := uuid.New(), uuid.New(), uuid.New()
+	mock.ExpectQuery("UPDATE webhook_delivery").
+		WillReturnRows(sqlmock.NewRows([]string{"delivery_id", "webhook_id", "event", "payload", "attempts", "created_at", "website_id", "url", "secret"}).
+			AddRow(deliveryID.String(), webhookID.String(), models.WebhookGoalCompleted, []byte(`{"goal_id":"g1"}`), 0, time.Now(), websiteID.String(), server.URL, "s3cret"))
+	mock.ExpectExec("UPDATE webhook_delivery").
+		WithArgs(deliveryID, models.WebhookDeliveryDelivered, http.StatusNoContent, "", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	d := NewDispatcher(db)
+	d.client = server.Client() // the test server listens on loopback
+	d.dispatchDue(context.Background())
+
+	require.NotNil(t, received)
+	assert.Equal(t, models.WebhookGoalCompleted, received.Header.Get("X-Kaunta-Event"))
+	timestamp, err := strconv.ParseInt(received.Header.Get("X-Kaunta-Timestamp"), 10, 64)
+	require.NoError(t, err)
+	assert.Equal(t, Sign("s3cret", timestamp, receivedBody), received.Header.Get("X-Kaunta-Signature"))
+
+	var envelope Envelope
+	require.NoError(t, json.Unmarshal(receivedBody, &envelope))
+	assert.Equal(t, deliveryID.String(), envelope.ID)
+	assert.Equal(t, websiteID.String(), envelope.WebsiteID)
+	assert.JSONEq(t, `{"goal_id":"g1"}`, string(envelope.Data))
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestDispatchDueSchedulesRetry(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusBadGateway)
+	}))
+	defer server.Close()
+
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	deliveryID := uuid.New()
+	mock.ExpectQuery("UPDATE webhook_delivery").
+		WillReturnRows(sqlmock.NewRows([]string{"delivery_id", "webhook_id", "event", "payload", "attempts", "created_at", "website_id", "url", "secret"}).
+			AddRow(deliveryID.String(), uuid.NewString(), models.WebhookAlertFired, []byte(`{}`), 2, time.Now(), uuid.NewString(), server.URL, "s3cret"))
+	mock.ExpectExec("UPDATE webhook_delivery").
+		WithArgs(deliveryID, models.WebhookDeliveryPending, http.StatusBadGateway, "webhook returned status 502", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	d := NewDispatcher(db)
+	d.client = server.Client() // the test server listens on loopback
+	d.dispatchDue(context.Background())
+
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestDispatchDueRefusesInternalAddresses(t *testing.T) {
+	called := false
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		called = true
+	}))
+	defer server.Close()
+
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+
+	// The error stored for the webhook's owner doesn't say what answered
+	deliveryID := uuid.New()
+	mock.ExpectQuery("UPDATE webhook_delivery").
+		WillReturnRows(sqlmock.NewRows([]string{"delivery_id", "webhook_id", "event", "payload", "attempts", "created_at", "website_id", "url", "secret"}).
+			AddRow(deliveryID.String(), uuid.NewString(), models.WebhookAlertFired, []byte(`{}`), 0, time.Now(), uuid.NewString(), server.URL, "s3cret"))
+	mock.ExpectExec("UPDATE webhook_delivery").
+		WithArgs(deliveryID, models.WebhookDeliveryPending, 0, "destination is not a public address", sqlmock.AnyArg()).
+		WillReturnResult(sqlmock.NewResult(0, 1))
+
+	NewDispatcher(db).dispatchDue(context.Background())
+
+	assert.False(t, called)
+	assert.NoError(t, mock.ExpectationsWereMet())
+}
+
+func TestDeliveryError(t *testing.T) {
+	assert.Equal(t, "webhook returned status 502", deliveryError(http.StatusBadGateway, errors.New("webhook returned status 502")))
+	assert.Equal(t, "request timed out", deliveryError(0, &net.OpError{Op: "dial", Err: timeoutError{}}))
+	assert.Equal(t, "request failed", deliveryError(0, errors.New("dial tcp 10.0.0.5:6379: connect: connection refused")))
+}
+
+type timeoutError struct{}
+
+func (timeoutError) Error() string   { return "i/o timeout" }
+func (timeoutError) Timeout() bool   { return true }
+func (timeoutError) Temporary() bool { return true }
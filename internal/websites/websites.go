@@ -19,18 +19,20 @@ import (
 
 // selectCols is the canonical column list returned by every website query so
 // that rows can be consumed by scanDetail.
-const selectCols = `website_id, domain, name, allowed_domains, share_id, public_stats_enabled, created_at, updated_at`
+const selectCols = `website_id, domain, name, allowed_domains, share_id, public_stats_enabled, event_retention_days, rollup_retention_days, created_at, updated_at`
 
 // Detail holds complete website information shared across CLI and API operations.
 type Detail struct {
-	WebsiteID          string    `json:"website_id"`
-	Domain             string    `json:"domain"`
-	Name               string    `json:"name"`
-	AllowedDomains     []string  `json:"allowed_domains"`
-	ShareID            *string   `json:"share_id,omitempty"`
-	PublicStatsEnabled bool      `json:"public_stats_enabled"`
-	CreatedAt          time.Time `json:"created_at"`
-	UpdatedAt          time.Time `json:"updated_at"`
+	WebsiteID           string    `json:"website_id"`
+	Domain              string    `json:"domain"`
+	Name                string    `json:"name"`
+	AllowedDomains      []string  `json:"allowed_domains"`
+	ShareID             *string   `json:"share_id,omitempty"`
+	PublicStatsEnabled  bool      `json:"public_stats_enabled"`
+	EventRetentionDays  *int      `json:"event_retention_days,omitempty"`
+	RollupRetentionDays *int      `json:"rollup_retention_days,omitempty"`
+	CreatedAt           time.Time `json:"created_at"`
+	UpdatedAt           time.Time `json:"updated_at"`
 }
 
 // rowScanner is satisfied by both *sql.Row and *sql.Rows.
@@ -51,6 +53,8 @@ func scanDetail(s rowScanner) (*Detail, error) {
 		&allowedDomainsJSON,
 		&shareID,
 		&d.PublicStatsEnabled,
+		&d.EventRetentionDays,
+		&d.RollupRetentionDays,
 		&d.CreatedAt,
 		&d.UpdatedAt,
 	); err != nil {
@@ -308,6 +312,23 @@ func SetPublicStatsEnabled(ctx context.Context, websiteDomain string, enabled bo
 	return updateAndScan(ctx, websiteDomain, query, enabled, website.WebsiteID)
 }
 
+// SetRetention sets how many days of raw events and rollups a website keeps.
+// nil leaves the server default: the server-wide retention for events and
+// forever for rollups.
+func SetRetention(ctx context.Context, websiteDomain string, eventDays, rollupDays *int) (*Detail, error) {
+	website, err := GetByDomain(ctx, websiteDomain, nil)
+	if err != nil {
+		return nil, err
+	}
+
+	query := `UPDATE website
+		SET event_retention_days = $1, rollup_retention_days = $2, updated_at = NOW()
+		WHERE website_id = $3 AND deleted_at IS NULL
+		RETURNING ` + selectCols
+
+	return updateAndScan(ctx, websiteDomain, query, eventDays, rollupDays, website.WebsiteID)
+}
+
 // setAllowedDomains persists a replacement allowed_domains array.
 func setAllowedDomains(ctx context.Context, websiteDomain, websiteID string, domains []string) (*Detail, error) {
 	domainsJSON, _ := json.Marshal(domains)
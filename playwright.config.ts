@@ -17,6 +17,12 @@ export default defineConfig({
       name: 'chromium',
       use: { ...devices['Desktop Chrome'] },
     },
+    {
+      // Dashboard scripts from cmd/kaunta/assets/js, loaded into stub pages
+      name: 'dashboard',
+      testDir: './frontend/test',
+      use: { ...devices['Desktop Chrome'] },
+    },
   ],
 
   webServer: {
@@ -3,7 +3,7 @@ set -euo pipefail
 
 ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
 OUTPUT="$ROOT_DIR/cmd/kaunta/assets/kaunta.min.js"
-SOURCE="$ROOT_DIR/tracker/kaunta.js"
+SOURCES=("$ROOT_DIR/tracker/kaunta.js" "$ROOT_DIR/tracker/src/tracker.js")
 
 echo "🔨 Building Kaunta tracker with Bun..."
 echo ""
@@ -30,12 +30,12 @@ gzip -c "$OUTPUT" > "$OUTPUT.gz"
 # Report sizes
 echo ""
 echo "📊 Sizes:"
-printf "   Original: %'8d bytes (%.2f KB)\n" $(wc -c < "$SOURCE") $(echo "scale=2; $(wc -c < "$SOURCE")/1024" | bc)
+original_size=$(cat "${SOURCES[@]}" | wc -c)
+printf "   Original: %'8d bytes (%.2f KB)\n" $original_size $(echo "scale=2; $original_size/1024" | bc)
 printf "   Minified: %'8d bytes (%.2f KB)\n" $(wc -c < "$OUTPUT") $(echo "scale=2; $(wc -c < "$OUTPUT")/1024" | bc)
 printf "   Gzipped:  %'8d bytes (%.2f KB)\n" $(wc -c < "$OUTPUT.gz") $(echo "scale=2; $(wc -c < "$OUTPUT.gz")/1024" | bc)
 
 # Calculate savings
-original_size=$(wc -c < "$SOURCE")
 minified_size=$(wc -c < "$OUTPUT")
 gzipped_size=$(wc -c < "$OUTPUT.gz")
 
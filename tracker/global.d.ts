@@ -0,0 +1,18 @@
+/**
+ * Types for the /k.js script tag's window.kaunta, for TypeScript code on
+ * pages that load the script instead of importing the module:
+ *
+ *   /// <reference types="@kaunta/tracker/global" />
+ *   window.kaunta?.track('signup', { plan: 'pro' });
+ */
+
+import type { Tracker } from './index.js';
+
+declare global {
+  interface Window {
+    /** The tracker, or the queueing snippet's stand-in until the script loads */
+    kaunta?: Tracker;
+  }
+}
+
+export {};
@@ -0,0 +1,135 @@
+/**
+ * Types for @kaunta/tracker.
+ *
+ * track() accepts any event name until the Events interface is augmented
+ * with your events, for instance with the declarations generated by
+ * `kaunta website event-schema <domain> --format ts`:
+ *
+ *   declare module '@kaunta/tracker' {
+ *     interface Events {
+ *       "signup": { "plan": string; "trial"?: boolean };
+ *     }
+ *   }
+ *
+ * Then event names are checked and each event's properties are typed.
+ */
+
+/** A property value; null removes it in setProps() */
+export type PropValue = string | number | boolean | null;
+
+export type Props = Record<string, PropValue>;
+
+/** Custom events by name, with their properties; empty until augmented */
+export interface Events {}
+
+/** The declared event names, or any string when none are declared */
+export type EventName = [keyof Events] extends [never] ? string : Extract<keyof Events, string>;
+
+/** track()'s properties: required when the event declares required ones */
+export type TrackArgs<N extends string> = N extends keyof Events
+  ? {} extends Events[N]
+    ? [props?: Events[N] & Props]
+    : [props: Events[N] & Props]
+  : [props?: Props];
+
+/** A hit as sent to /api/send, as seen by a beforeSend hook */
+export interface Payload {
+  website: string;
+  hostname: string;
+  url: string;
+  title: string;
+  referrer: string;
+  screen?: string;
+  language?: string;
+  /** Custom event name; absent for pageviews */
+  name?: string;
+  props?: Props;
+  /** The ID set with identify() */
+  id?: string;
+  /** Unix seconds of the first attempt, on hits resent from the offline queue */
+  timestamp?: number;
+  /** Set when hashRouting is on: url's hash is part of the page's path */
+  hash_routing?: boolean;
+  [field: string]: unknown;
+}
+
+/** 'event' for pageviews and custom events */
+export type HitType = 'event' | 'engagement' | 'identify';
+
+/** Returns the payload to send, changed or not, or null or false to drop the hit */
+export type BeforeSend = (payload: Payload, type: HitType) => Payload | null | false | void;
+
+/** init() options, named like the script tag's data attributes */
+export interface InitOptions {
+  /** The website's tracking ID */
+  websiteId: string;
+  /** Your Kaunta server, e.g. https://census.example.com */
+  apiUrl: string;
+  /** Track pageviews, including History API navigation (default true) */
+  autoTrack?: boolean;
+  /** Track outbound link clicks (default true) */
+  trackOutbound?: boolean;
+  /** Track clicks on links to files: a download attribute, or .pdf, .zip and other file extensions (default false) */
+  trackDownloads?: boolean;
+  /** Track clicks on mailto: links (default false) */
+  trackMailto?: boolean;
+  /** Don't track visitors with Do Not Track enabled (default true) */
+  respectDnt?: boolean;
+  /** Ignore the URL hash (default false) */
+  excludeHash?: boolean;
+  /** Count #/ routes as pages, with the hash in their path (default false) */
+  hashRouting?: boolean;
+  /** Same as hashRouting */
+  trackHash?: boolean;
+  /** Track the page as a 404 (default false) */
+  track404?: boolean;
+  /** Only track on these hostnames */
+  domains?: string | string[];
+  /** Properties sent with every hit */
+  props?: Props | string;
+  /** 'manual' holds hits until consent(true) */
+  consent?: 'manual';
+  /** Keep hits that fail to send in localStorage and retry them (default false) */
+  offline?: boolean;
+  /** Sees every hit before it is sent, to redact, rewrite or drop it */
+  beforeSend?: BeforeSend;
+  /** Log hits and server responses to the console; 'overlay' also shows the last hit on the page */
+  debug?: boolean | 'overlay';
+}
+
+export interface Tracker {
+  track<N extends EventName>(name: N, ...args: TrackArgs<N>): void;
+  pageview(url?: string): void;
+  /** @deprecated Use pageview() */
+  trackPageview(url?: string): void;
+  track404(): void;
+  identify(id: string | number | null): void;
+  setProps(props: Props): void;
+  consent(granted: boolean): void;
+  beforeSend(fn: BeforeSend | null): void;
+  destroy(): void;
+}
+
+/** Start tracking; returns null outside the browser. Later calls return the first tracker. */
+export function init(options: InitOptions): Tracker | null;
+
+/** Send a custom event */
+export function track<N extends EventName>(name: N, ...args: TrackArgs<N>): void;
+
+/** Send a pageview for the current URL, or for url */
+export function pageview(url?: string): void;
+
+/** Attach your own ID to the session and later hits; null forgets it */
+export function identify(id: string | number | null): void;
+
+/** Merge properties into every later hit; a null value removes one */
+export function setProps(props: Props): void;
+
+/** Grant or refuse consent when init() was called with consent: 'manual' */
+export function consent(granted: boolean): void;
+
+/** Set the function that sees every hit before it is sent; null removes it */
+export function beforeSend(fn: BeforeSend | null): void;
+
+/** Stop tracking and remove every listener; init() can be called again */
+export function destroy(): void;
@@ -0,0 +1,82 @@
+/**
+ * @kaunta/tracker - the Kaunta tracker as an ES module
+ *
+ *   import { init, track } from '@kaunta/tracker';
+ *
+ *   init({ websiteId: '550e8400-...', apiUrl: 'https://census.example.com' });
+ *   track('signup', { plan: 'pro' });
+ *
+ * Built from the same source as the /k.js script (src/tracker.js), and sends
+ * the same hits to /api/send. Calls made before init() are queued and run
+ * once it is called; importing the module during server-side rendering is
+ * safe, nothing runs without a browser window.
+ */
+
+import { createTracker, replay } from './src/tracker.js';
+
+var tracker = null;
+var queued = [];
+
+function call(method, args) {
+  if (tracker) {
+    tracker[method].apply(null, args);
+  } else {
+    queued.push([method].concat(args));
+  }
+}
+
+// init starts tracking with options named like the script tag's data
+// attributes: websiteId and apiUrl (required), autoTrack, trackOutbound,
+// trackDownloads, trackMailto, respectDnt, excludeHash, hashRouting (or
+// trackHash), track404, domains, props, consent, offline, beforeSend and
+// debug.
+// Later calls return the tracker from the first one.
+export function init(options) {
+  if (tracker) return tracker;
+  if (typeof window === 'undefined' || !window.document) return null;
+
+  options = options || {};
+  if (!options.websiteId || !options.apiUrl) {
+    throw new Error('kaunta: init() needs websiteId and apiUrl');
+  }
+
+  tracker = createTracker(window, options);
+  replay(tracker, queued);
+  queued = [];
+  tracker.start();
+  return tracker;
+}
+
+export function track(name, props) {
+  call('track', [name, props]);
+}
+
+export function pageview(url) {
+  call('pageview', [url]);
+}
+
+export function identify(id) {
+  call('identify', [id]);
+}
+
+export function setProps(props) {
+  call('setProps', [props]);
+}
+
+export function consent(granted) {
+  call('consent', [granted]);
+}
+
+// beforeSend sets a function that sees every hit before it is sent; it
+// returns the payload to send, or null to drop the hit
+export function beforeSend(fn) {
+  call('beforeSend', [fn]);
+}
+
+// destroy stops tracking and removes every listener; init() can be called again
+export function destroy() {
+  if (tracker) {
+    tracker.destroy();
+    tracker = null;
+  }
+}
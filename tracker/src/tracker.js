@@ -0,0 +1,1126 @@
+/**
+ * Kaunta Analytics Tracker core
+ * Shared by the script served as /k.js (tracker/kaunta.js) and the
+ * @kaunta/tracker ES module (tracker/index.js)
+ */
+
+// createTracker sets up tracking for one page with config, the script tag's
+// data attributes or the options passed to init(), and returns the public API.
+// Nothing is sent until start() is called.
+export function createTracker(window, config) {
+  var {
+    screen: { width, height },
+    navigator: { language, doNotTrack: ndnt, msDoNotTrack: msdnt },
+    location,
+    document,
+    history,
+    doNotTrack
+  } = window;
+
+  var { referrer } = document;
+
+  // ============================================================================
+  // CONFIGURATION (data attributes as strings, or init() options)
+  // ============================================================================
+
+  function hostOf(value) {
+    return String(value).trim().toLowerCase()
+      .replace(/^[a-z]+:\/\//, '')
+      .replace(/[/?#].*$/, '')
+      .replace(/:\d+$/, '')
+      .replace(/\.$/, '');
+  }
+
+  function enabled(value) {
+    return value !== false && value !== 'false';
+  }
+
+  function isSet(value) {
+    return value === true || value === 'true';
+  }
+
+  var websiteId = config.websiteId;
+  var apiUrl = config.apiUrl;
+  var autoTrack = enabled(config.autoTrack);
+  var trackOutbound = enabled(config.trackOutbound);
+  var trackDownloads = isSet(config.trackDownloads);
+  var trackMailto = isSet(config.trackMailto);
+  var respectDnt = enabled(config.respectDnt);
+  // Hash routers (#/page) navigate by the hash: every change is a pageview
+  // and the hash is part of the page's path. It wins over excludeHash.
+  // data-track-hash is the same switch.
+  var hashRouting = isSet(config.hashRouting) || isSet(config.trackHash);
+  var excludeHash = isSet(config.excludeHash) && !hashRouting;
+  var track404Page = isSet(config.track404);
+  var consentRequired = config.consent === 'manual';
+  // Hosts to track on (data-domains="example.com,www.example.com"), so
+  // localhost, staging and mirrors of the site send nothing. Entries may be
+  // pasted URLs; scheme, port, path and a trailing dot are ignored.
+  var domains = [].concat(config.domains || []).join(',').split(',').map(hostOf).filter(Boolean);
+
+  var endpoint = apiUrl.replace(/\/$/, '') + '/api/send';
+  var screen = width + 'x' + height;
+  var { hostname, origin } = location;
+
+  // ============================================================================
+  // UTM PARAMETER TRACKING
+  // Extract UTM params from URL and persist in sessionStorage for the session
+  // ============================================================================
+
+  var UTM_PARAMS = ['utm_source', 'utm_medium', 'utm_campaign', 'utm_term', 'utm_content'];
+  var UTM_STORAGE_KEY = 'kaunta_utm';
+
+  function getUtmParams() {
+    // Try to get from sessionStorage first (persists across page navigation)
+    var stored = null;
+    try {
+      var storedStr = sessionStorage.getItem(UTM_STORAGE_KEY);
+      if (storedStr) {
+        stored = JSON.parse(storedStr);
+      }
+    } catch (e) {
+      // sessionStorage not available or parse error
+    }
+
+    // Check current URL for UTM params
+    var searchParams = new URLSearchParams(location.search);
+    var currentUtm = {};
+    var hasNewUtm = false;
+
+    UTM_PARAMS.forEach(function(param) {
+      var value = searchParams.get(param);
+      if (value) {
+        currentUtm[param] = value;
+        hasNewUtm = true;
+      }
+    });
+
+    // If we have new UTM params in URL, use those and store them
+    if (hasNewUtm) {
+      try {
+        sessionStorage.setItem(UTM_STORAGE_KEY, JSON.stringify(currentUtm));
+      } catch (e) {
+        // sessionStorage not available
+      }
+      return currentUtm;
+    }
+
+    // Otherwise return stored UTM params (from landing page)
+    return stored || {};
+  }
+
+  // Get UTM params once at init (will be refreshed on navigation if URL changes)
+  var utmParams = getUtmParams();
+
+  // Static payload fields that don't change per event
+  var staticPayload = {
+    website: websiteId,
+    hostname: hostname,
+    screen: screen,
+    language: language
+  };
+  if (hashRouting) staticPayload.hash_routing = true;
+  Object.freeze(staticPayload);
+
+  // ============================================================================
+  // ENGAGEMENT & SCROLL TRACKING (from Plausible)
+  // ============================================================================
+
+  // data-debug="true" logs to the console; "overlay" also shows the last hit
+  // on the page
+  var debugOverlay = config.debug === 'overlay';
+  var debug = isSet(config.debug) || debugOverlay;
+
+  function logDebug() {
+    if (!debug || !window.console) return;
+    var args = Array.prototype.slice.call(arguments);
+    args.unshift('[Kaunta]');
+    try {
+      console.debug.apply(console, args);
+    } catch (err) {
+      try {
+        console.log.apply(console, args);
+      } catch (_) {
+        // ignore
+      }
+    }
+  }
+
+  logDebug('Tracker initialized', { apiUrl: apiUrl, websiteId: websiteId });
+
+  // ============================================================================
+  // DEBUG OVERLAY (data-debug="overlay")
+  // Answers "why is nothing showing up?" on the page itself
+  // ============================================================================
+
+  var overlay = null;
+  var OVERLAY_COLORS = { ok: '#30a46c', pending: '#8b8d98', error: '#e5484d' };
+
+  // debugHit reports what happened to a hit: state is 'ok', 'pending' or
+  // 'error' (not sent, dropped or rejected)
+  function debugHit(status, type, payload, state) {
+    logDebug(status, type, payload);
+    if (!debugOverlay) return;
+
+    var label = type;
+    if (type === 'event') label = payload && payload.name ? 'event "' + payload.name + '"' : 'pageview';
+    showOverlay('Kaunta ' + label + '\n' + ((payload && payload.url) || location.href) + '\n' + status, state);
+  }
+
+  function showOverlay(text, state) {
+    if (!document.body) {
+      document.addEventListener('DOMContentLoaded', function() {
+        showOverlay(text, state);
+      });
+      return;
+    }
+
+    if (!overlay) {
+      // Styled through the CSSOM, which a Content Security Policy allows
+      overlay = document.createElement('div');
+      overlay.setAttribute('data-kaunta-debug', '');
+      overlay.title = 'Kaunta debug overlay: click to close';
+      var style = overlay.style;
+      style.position = 'fixed';
+      style.right = '12px';
+      style.bottom = '12px';
+      style.zIndex = '2147483647';
+      style.maxWidth = '360px';
+      style.padding = '8px 10px';
+      style.borderRadius = '6px';
+      style.background = '#18191b';
+      style.color = '#edeef0';
+      style.font = '12px/1.5 ui-monospace, monospace';
+      style.whiteSpace = 'pre-wrap';
+      style.wordBreak = 'break-all';
+      style.boxShadow = '0 2px 8px rgba(0, 0, 0, 0.3)';
+      style.cursor = 'pointer';
+      overlay.addEventListener('click', removeOverlay);
+      document.body.appendChild(overlay);
+    }
+    overlay.style.borderLeft = '4px solid ' + OVERLAY_COLORS[state];
+    overlay.textContent = text;
+  }
+
+  function removeOverlay() {
+    debugOverlay = false;
+    if (overlay && overlay.parentNode) overlay.parentNode.removeChild(overlay);
+    overlay = null;
+  }
+
+  // debugResponse reports the server's answer to a hit: accepted, dropped
+  // (bots, excluded browsers, spam referrers) or rejected, with the reason
+  function debugResponse(response, type, payload) {
+    response.json().catch(function() {
+      return {};
+    }).then(function(body) {
+      body = body || {};
+      if (!response.ok) {
+        var reason = body.error || response.statusText;
+        if (body.hint) reason += '. ' + body.hint;
+        debugHit('Rejected (' + response.status + '): ' + reason, type, payload, 'error');
+      } else if (body.dropped || body.bot_detected) {
+        debugHit('Dropped by the server: ' + (body.dropped || 'bot'), type, payload, 'error');
+      } else {
+        debugHit('Accepted (' + response.status + ')', type, payload, 'ok');
+      }
+    });
+  }
+
+  var engagementListening = false;
+  var scrollScheduled = false;
+  var heightObserver = null;
+  var engagementAbort = null;
+  var currentPageUrl = location.href;
+  var maxScrollDepthPx = 0;
+  var currentDocHeight = 0;
+  var engagementStartTime = 0;
+  var totalEngagementTime = 0;
+  var reportedEngagementTime = 0;
+  var engagementIgnored = false;
+
+  function getDocHeight() {
+    var body = document.body || {};
+    var el = document.documentElement || {};
+    return Math.max(
+      body.scrollHeight || 0,
+      body.offsetHeight || 0,
+      body.clientHeight || 0,
+      el.scrollHeight || 0,
+      el.offsetHeight || 0,
+      el.clientHeight || 0
+    );
+  }
+
+  function getCurrentScrollDepthPx() {
+    var body = document.body || {};
+    var el = document.documentElement || {};
+    var viewportHeight = window.innerHeight || el.clientHeight || 0;
+    var scrollTop = window.scrollY || el.scrollTop || body.scrollTop || 0;
+
+    return currentDocHeight <= viewportHeight
+      ? currentDocHeight
+      : scrollTop + viewportHeight;
+  }
+
+  function getEngagementTime() {
+    if (engagementStartTime) {
+      return totalEngagementTime + (Date.now() - engagementStartTime);
+    }
+    return totalEngagementTime;
+  }
+
+  function updateScrollDepth() {
+    currentDocHeight = getDocHeight();
+    var currentScrollDepth = getCurrentScrollDepthPx();
+
+    if (currentScrollDepth > maxScrollDepthPx) {
+      maxScrollDepthPx = currentScrollDepth;
+    }
+  }
+
+  function onVisibilityChange() {
+    if (document.visibilityState === 'visible' && document.hasFocus() && engagementStartTime === 0) {
+      engagementStartTime = Date.now();
+    } else if (document.visibilityState === 'hidden' || !document.hasFocus()) {
+      // Save engagement time
+      totalEngagementTime = getEngagementTime();
+      engagementStartTime = 0;
+
+      // The visit may end here: report engaged time while we still can
+      if (document.visibilityState === 'hidden') {
+        sendEngagement();
+      }
+    }
+  }
+
+  // The page is going away (pagehide, or a link click leaving the site):
+  // hits sent now must not need a CORS preflight, see transmit
+  var leaving = false;
+
+  function onPageHide() {
+    leaving = true;
+    sendEngagement();
+  }
+
+  // Restored from the back/forward cache
+  function onPageShow() {
+    leaving = false;
+  }
+
+  // Report engaged time not yet sent for the current page, so visit duration
+  // and bounce rate reflect real attention instead of pageview gaps
+  function sendEngagement() {
+    var delta = Math.round(getEngagementTime() - reportedEngagementTime);
+    if (delta < 1000) return;
+
+    reportedEngagementTime += delta;
+    var payload = getBasePayload(false);
+    payload.engagement_time = delta;
+    send(payload, 'engagement');
+  }
+
+  function initEngagementTracking() {
+    if (!engagementListening) {
+      currentDocHeight = getDocHeight();
+      maxScrollDepthPx = getCurrentScrollDepthPx();
+
+      // Create AbortController for cleanup
+      engagementAbort = window.AbortController ? new AbortController() : null;
+      var signal = engagementAbort ? { signal: engagementAbort.signal } : {};
+
+      // rAF-batched scroll tracking to prevent layout thrashing
+      document.addEventListener('scroll', function() {
+        if (scrollScheduled) return;
+        scrollScheduled = true;
+        requestAnimationFrame(function() {
+          scrollScheduled = false;
+          updateScrollDepth();
+        });
+      }, Object.assign({ passive: true }, signal));
+
+      document.addEventListener('visibilitychange', onVisibilityChange, Object.assign({ passive: true }, signal));
+      window.addEventListener('blur', onVisibilityChange, Object.assign({ passive: true }, signal));
+      window.addEventListener('focus', onVisibilityChange, Object.assign({ passive: true }, signal));
+      // Safari doesn't always fire visibilitychange on unload
+      window.addEventListener('pagehide', onPageHide, Object.assign({ passive: true }, signal));
+      window.addEventListener('pageshow', onPageShow, Object.assign({ passive: true }, signal));
+
+      // Use ResizeObserver to track document height changes efficiently
+      if (window.ResizeObserver) {
+        heightObserver = new ResizeObserver(function() {
+          currentDocHeight = getDocHeight();
+        });
+        heightObserver.observe(document.documentElement);
+        if (document.body) {
+          heightObserver.observe(document.body);
+        }
+      } else {
+        // Fallback for older browsers
+        window.addEventListener('load', function() {
+          currentDocHeight = getDocHeight();
+          var count = 0;
+          var interval = setInterval(function() {
+            currentDocHeight = getDocHeight();
+            if (++count === 15) clearInterval(interval);
+          }, 200);
+        });
+      }
+
+      engagementListening = true;
+    }
+  }
+
+  // ============================================================================
+  // HELPER FUNCTIONS (from Umami)
+  // ============================================================================
+
+  function hasDoNotTrack() {
+    var dnt = doNotTrack || ndnt || msdnt;
+    return dnt === 1 || dnt === '1' || dnt === 'yes';
+  }
+
+  // Site owners keep their own visits out by opening a page of the site with
+  // ?kaunta_exclude=1 (the dashboard's "Exclude my visits" links there);
+  // ?kaunta_exclude=0 undoes it. The flag lives in this site's localStorage.
+  var EXCLUDE_KEY = 'kaunta_exclude';
+
+  function readExcludeFlag() {
+    try {
+      var param = new URLSearchParams(location.search).get(EXCLUDE_KEY);
+      if (param === '1') localStorage.setItem(EXCLUDE_KEY, '1');
+      if (param === '0') localStorage.removeItem(EXCLUDE_KEY);
+      return localStorage.getItem(EXCLUDE_KEY) === '1';
+    } catch (e) {
+      return false; // localStorage blocked
+    }
+  }
+
+  var excluded = readExcludeFlag();
+  var wrongHost = domains.length > 0 && domains.indexOf(hostOf(hostname)) === -1;
+
+  // Why nothing is sent, or '' while tracking is on
+  function disabledReason() {
+    if (!websiteId) return 'no data-website-id';
+    if (excluded) return 'this browser is excluded (kaunta_exclude)';
+    if (wrongHost) return 'host ' + hostname + ' is not in data-domains';
+    if (respectDnt && hasDoNotTrack()) return 'Do Not Track is on';
+    return '';
+  }
+
+  function isTrackingDisabled() {
+    return disabledReason() !== '';
+  }
+
+  function normalize(url) {
+    if (!url) return url;
+    try {
+      var u = new URL(url, location.href);
+      if (excludeHash) u.hash = '';
+      return u.toString();
+    } catch (e) {
+      return url;
+    }
+  }
+
+  function getBasePayload(includeEngagement) {
+    var payload = Object.assign({}, staticPayload, {
+      url: currentPageUrl,
+      title: document.title,
+      referrer: currentRef
+    });
+
+    // Only include engagement metrics for pageviews to reduce payload size
+    if (includeEngagement) {
+      var scrollDepthPercent = currentDocHeight > 0
+        ? Math.round((maxScrollDepthPx / currentDocHeight) * 100)
+        : 0;
+      var engagementTimeMs = Math.round(getEngagementTime());
+
+      payload.scroll_depth = scrollDepthPercent;
+      payload.engagement_time = engagementTimeMs;
+    }
+
+    if (distinctId) payload.id = distinctId;
+
+    // Include UTM parameters if present
+    if (utmParams.utm_source) payload.utm_source = utmParams.utm_source;
+    if (utmParams.utm_medium) payload.utm_medium = utmParams.utm_medium;
+    if (utmParams.utm_campaign) payload.utm_campaign = utmParams.utm_campaign;
+    if (utmParams.utm_term) payload.utm_term = utmParams.utm_term;
+    if (utmParams.utm_content) payload.utm_content = utmParams.utm_content;
+
+    return payload;
+  }
+
+  // ============================================================================
+  // CONSENT GATE (data-consent="manual")
+  // ============================================================================
+
+  // null until kaunta.consent() is called, then true or false
+  var consentGiven = null;
+  var consentQueue = [];
+  var MAX_CONSENT_QUEUE = 50;
+
+  // Record the visitor's choice. Hits made before it are held in memory and
+  // sent on opt-in, or dropped on opt-out; nothing is sent after an opt-out.
+  function consent(granted) {
+    consentGiven = !!granted;
+    var held = consentQueue;
+    consentQueue = [];
+
+    logDebug('Consent', consentGiven ? 'granted' : 'denied', held.length + ' held hits');
+    if (consentGiven) {
+      held.forEach(function(hit) {
+        send(hit[0], hit[1]);
+      });
+    }
+  }
+
+  // ============================================================================
+  // BEFORE-SEND HOOK
+  // Lets sites redact or rewrite hits, or drop them, before they leave the page
+  // ============================================================================
+
+  // Set with kaunta.beforeSend(fn), the beforeSend option of init(), or
+  // data-before-send="name" for a global function looked up on each hit
+  var beforeSendHook = typeof config.beforeSend === 'function' ? config.beforeSend : null;
+  var beforeSendName = typeof config.beforeSend === 'string' ? config.beforeSend.trim() : '';
+
+  function beforeSend(fn) {
+    beforeSendHook = typeof fn === 'function' ? fn : null;
+    beforeSendName = '';
+  }
+
+  // applyBeforeSend returns the payload to send, or null to drop the hit. A
+  // hook that is missing or throws drops it too: unscrubbed data never leaves.
+  function applyBeforeSend(payload, type) {
+    var hook = beforeSendHook;
+    if (!hook && beforeSendName) {
+      hook = window[beforeSendName];
+      if (typeof hook !== 'function') {
+        logDebug('data-before-send: ' + beforeSendName + ' is not a function: DROP', type, payload);
+        return null;
+      }
+    }
+    if (!hook) return payload;
+
+    try {
+      var result = hook(payload, type);
+      if (result === null || result === false) return null;
+      return result && typeof result === 'object' ? result : payload;
+    } catch (e) {
+      logDebug('beforeSend threw: DROP', type, e);
+      return null;
+    }
+  }
+
+  // ============================================================================
+  // NETWORK REQUEST (from Plausible - minimal, modern)
+  // ============================================================================
+
+  function send(payload, type) {
+    type = type || 'event';
+
+    if (isTrackingDisabled()) {
+      debugHit('Not sent: ' + disabledReason(), type, payload, 'error');
+      return;
+    }
+
+    if (consentRequired) {
+      if (consentGiven === null) {
+        if (consentQueue.length < MAX_CONSENT_QUEUE) consentQueue.push([payload, type]);
+        debugHit('Held until consent', type, payload, 'pending');
+        return;
+      }
+      if (!consentGiven) {
+        debugHit('Not sent: consent denied', type, payload, 'error');
+        return;
+      }
+      payload.consent = true;
+    }
+
+    var original = payload;
+    payload = applyBeforeSend(payload, type);
+    if (!payload) {
+      debugHit('Dropped by beforeSend', type, original, 'error');
+      return;
+    }
+
+    debugHit('Sending', type, payload, 'pending');
+    transmit(type, payload, 0);
+  }
+
+  var keepaliveSupported = !!(window.Request && 'keepalive' in window.Request.prototype);
+
+  // transmit posts a hit; queuedAt is when a hit from the offline queue was
+  // first sent. Hits that don't reach the server, or that it can't take right
+  // now, go to the offline queue when it is on.
+  function transmit(type, payload, queuedAt) {
+    var body = JSON.stringify({ type: type, payload: payload });
+
+    // Silent fail - no console spam unless debug
+    try {
+      // Determine credentials mode:
+      // - 'same-origin' for same-origin requests (enables self-tracking with auth)
+      // - 'omit' for cross-origin requests (privacy-first, no cookies)
+      var isSameOrigin = endpoint.indexOf(origin) === 0;
+      var credentialsMode = isSameOrigin ? 'same-origin' : 'omit';
+
+      // Exit hits (final engagement time, the click that leaves the page) go
+      // out as the page unloads. keepalive lets them outlive it, but can't
+      // wait for a CORS preflight: they are sent as text/plain, which needs
+      // none, and the server reads any body as JSON. sendBeacon would also
+      // outlive the page, but it always sends cookies: it is only used by
+      // browsers without fetch keepalive.
+      var exit = leaving || document.visibilityState === 'hidden';
+      if (window.fetch && (!exit || keepaliveSupported)) {
+        fetch(endpoint, {
+          method: 'POST',
+          headers: { 'Content-Type': exit ? 'text/plain' : 'application/json' },
+          body: body,
+          // Retries run while the page is open, and keepalive's 64KB budget
+          // is shared by every request in flight
+          keepalive: !queuedAt,
+          credentials: credentialsMode
+        }).then(function(response) {
+          if (debug) debugResponse(response, type, payload);
+          if (response.status === 429 || response.status >= 500) {
+            queueHit(type, payload, queuedAt);
+          } else if (queuedAt) {
+            retryDelay = RETRY_MIN_DELAY;
+          }
+        }).catch(function(err) {
+          // Offline, or blocked by an extension, a Content Security Policy or CORS
+          if (debug) debugHit('Network error: ' + (err && err.message), type, payload, 'error');
+          queueHit(type, payload, queuedAt);
+        });
+      } else if (navigator.sendBeacon) {
+        if (!navigator.sendBeacon(endpoint, body)) queueHit(type, payload, queuedAt);
+      }
+    } catch (e) {
+      if (debug) logDebug('Send exception', e);
+    }
+  }
+
+  // ============================================================================
+  // OFFLINE QUEUE (data-offline="true")
+  // Failed hits are kept in localStorage and retried with backoff, so PWAs and
+  // flaky mobile connections don't lose them
+  // ============================================================================
+
+  var offline = isSet(config.offline);
+  var QUEUE_KEY = 'kaunta_queue';
+  var MAX_QUEUED_HITS = 100;
+  var MAX_QUEUED_AGE = 24 * 60 * 60 * 1000;
+  var RETRY_MIN_DELAY = 5000;
+  var RETRY_MAX_DELAY = 5 * 60 * 1000;
+  var retryDelay = RETRY_MIN_DELAY;
+  var retryTimer = null;
+
+  // readQueue returns the queued hits of every tracker on the site, without
+  // those too old to send
+  function readQueue() {
+    try {
+      var queue = JSON.parse(localStorage.getItem(QUEUE_KEY) || '[]');
+      var now = Date.now();
+      return Array.isArray(queue) ? queue.filter(function(hit) {
+        return hit && hit.payload && now - hit.at < MAX_QUEUED_AGE;
+      }) : [];
+    } catch (e) {
+      return []; // localStorage blocked, or a corrupt queue
+    }
+  }
+
+  // writeQueue keeps the newest hits up to the cap
+  function writeQueue(queue) {
+    try {
+      if (queue.length) {
+        localStorage.setItem(QUEUE_KEY, JSON.stringify(queue.slice(-MAX_QUEUED_HITS)));
+      } else {
+        localStorage.removeItem(QUEUE_KEY);
+      }
+    } catch (e) {
+      // localStorage blocked or full: the hits are lost, as without the queue
+    }
+  }
+
+  function queueHit(type, payload, queuedAt) {
+    if (!offline) return;
+
+    var queue = readQueue();
+    queue.push({ endpoint: endpoint, type: type, payload: payload, at: queuedAt || Date.now() });
+    writeQueue(queue);
+    debugHit('Queued, retrying in ' + Math.round(retryDelay / 1000) + 's', type, payload, 'pending');
+
+    if (!retryTimer) {
+      retryTimer = setTimeout(flushQueue, retryDelay);
+      retryDelay = Math.min(retryDelay * 2, RETRY_MAX_DELAY);
+    }
+  }
+
+  // flushQueue resends this tracker's queued hits, stamped with the time they
+  // were first sent. Hits that fail again are queued for the next retry.
+  function flushQueue() {
+    clearTimeout(retryTimer);
+    retryTimer = null;
+    if (!offline || isTrackingDisabled()) return;
+
+    var queue = readQueue();
+    var ours = queue.filter(function(hit) {
+      return hit.endpoint === endpoint;
+    });
+    if (!ours.length) return;
+
+    writeQueue(queue.filter(function(hit) {
+      return hit.endpoint !== endpoint;
+    }));
+    logDebug('Retrying ' + ours.length + ' queued hits');
+    ours.forEach(function(hit) {
+      if (!hit.payload.timestamp) hit.payload.timestamp = Math.floor(hit.at / 1000);
+      transmit(hit.type, hit.payload, hit.at);
+    });
+  }
+
+  // ============================================================================
+  // TRACKING FUNCTIONS
+  // ============================================================================
+
+  // Properties sent with every pageview and custom event, set with kaunta.setProps()
+  var globalProps = {};
+  var distinctId = null;
+
+  // Site-wide properties: data-props='{"env":"prod"}' or init({ props: {...} })
+  if (config.props) {
+    try {
+      setProps(typeof config.props === 'string' ? JSON.parse(config.props) : config.props);
+    } catch (e) {
+      logDebug('Invalid data-props, expected a JSON object', e);
+    }
+  }
+
+  function withProps(properties) {
+    var props = Object.assign({}, globalProps, properties && typeof properties === 'object' ? properties : {});
+    return Object.keys(props).length ? props : null;
+  }
+
+  function trackPageview() {
+    // Flush the previous page's unreported engaged time to its visit
+    sendEngagement();
+
+    // Include engagement metrics for pageviews
+    var payload = getBasePayload(true);
+    var props = withProps();
+    if (props) payload.props = props;
+
+    // Reset engagement tracking for new page
+    maxScrollDepthPx = getCurrentScrollDepthPx();
+    totalEngagementTime = 0;
+    reportedEngagementTime = 0;
+    engagementStartTime = Date.now();
+    engagementIgnored = false;
+
+    lastPageviewUrl = currentPageUrl;
+    send(payload, 'event');
+  }
+
+  function track(eventName, properties) {
+    if (typeof eventName !== 'string') return;
+
+    // Don't include engagement metrics for custom events
+    var payload = getBasePayload(false);
+    payload.name = eventName;
+
+    var props = withProps(properties);
+    if (props) payload.props = props;
+
+    send(payload, 'event');
+  }
+
+  // Track a pageview for the current URL, or for url (a virtual page or a
+  // route change the History API hooks don't see)
+  function pageview(url) {
+    if (typeof url === 'string' && url) {
+      var newUrl = normalize(url);
+      if (newUrl !== currentPageUrl) {
+        sendEngagement();
+        currentRef = lastPageviewUrl;
+        currentPageUrl = newUrl;
+      }
+    }
+    clearTimeout(pendingPageview);
+    pendingPageview = null;
+    trackPageview();
+  }
+
+  // Attach an ID from your own system (user or account ID) to the visitor's
+  // session and every later hit; null forgets it, for instance on logout
+  function identify(id) {
+    if (id === null || id === undefined || id === '') {
+      distinctId = null;
+      return;
+    }
+    if (typeof id !== 'string' && typeof id !== 'number') return;
+
+    distinctId = String(id);
+    send(getBasePayload(false), 'identify');
+  }
+
+  // Merge properties into every later pageview and custom event; a null or
+  // undefined value removes the property
+  function setProps(properties) {
+    if (!properties || typeof properties !== 'object' || Array.isArray(properties)) return;
+    Object.keys(properties).forEach(function(key) {
+      var value = properties[key];
+      if (value === null || value === undefined) {
+        delete globalProps[key];
+      } else {
+        globalProps[key] = value;
+      }
+    });
+  }
+
+  function track404() {
+    var payload = getBasePayload(false);
+    payload.name = '404';
+    // Keep same-site referrers so broken internal links show up in the report
+    payload.referrer = currentRef || normalize(referrer || '');
+
+    send(payload, 'event');
+  }
+
+  // Detect 404 responses via Navigation Timing where the browser exposes the status
+  function isNotFoundPage() {
+    if (track404Page) return true;
+    try {
+      var nav = performance.getEntriesByType('navigation')[0];
+      return !!nav && nav.responseStatus === 404;
+    } catch (e) {
+      return false;
+    }
+  }
+
+  // ============================================================================
+  // AUTO-TRACKING: SPA NAVIGATION (from both Umami & Plausible)
+  // ============================================================================
+
+  var pendingPageview = null;
+  var lastPageviewUrl = null;
+  var unhookHistory = null;
+
+  function onNavigation() {
+    var newUrl = normalize(location.href);
+
+    // Routers call replaceState to store scroll position or state: same URL, no pageview
+    if (newUrl === currentPageUrl) return;
+
+    // Outside hash routing a new hash is an in-page anchor, not a new page
+    if (!hashRouting && newUrl.split('#')[0] === currentPageUrl.split('#')[0]) return;
+
+    // Report the engaged time of the page being left before the URL changes
+    sendEngagement();
+    currentPageUrl = newUrl;
+
+    // Refresh UTM params in case new URL has different UTM values
+    utmParams = getUtmParams();
+
+    // Debounce to prevent duplicate pageviews on rapid navigation (redirects,
+    // query updates) and to let the router set the new page's title
+    clearTimeout(pendingPageview);
+    pendingPageview = setTimeout(trackNavigation, 150);
+  }
+
+  function trackNavigation() {
+    pendingPageview = null;
+
+    // Navigated away and back before the pageview was sent
+    if (currentPageUrl === lastPageviewUrl) return;
+
+    // The referrer is the last page a pageview was sent for, not a URL
+    // skipped by the debounce
+    currentRef = lastPageviewUrl;
+    trackPageview();
+  }
+
+  function hookHistory() {
+    if (unhookHistory) return;
+
+    var hooks = [];
+    ['pushState', 'replaceState'].forEach(function(method) {
+      var orig = history[method];
+      if (typeof orig !== 'function') return;
+      var hooked = function() {
+        var result = orig.apply(this, arguments);
+        onNavigation();
+        return result;
+      };
+      history[method] = hooked;
+      hooks.push([method, orig, hooked]);
+    });
+
+    // Back/forward, and hash routers (data-hash-routing)
+    window.addEventListener('popstate', onNavigation);
+    window.addEventListener('hashchange', onNavigation);
+
+    unhookHistory = function() {
+      hooks.forEach(function(hook) {
+        // Leave wrappers installed by other scripts after ours in place
+        if (history[hook[0]] === hook[2]) history[hook[0]] = hook[1];
+      });
+      window.removeEventListener('popstate', onNavigation);
+      window.removeEventListener('hashchange', onNavigation);
+      unhookHistory = null;
+    };
+  }
+
+  // ============================================================================
+  // AUTO-TRACKING: OUTBOUND, DOWNLOAD AND MAILTO LINKS (from Plausible)
+  // ============================================================================
+
+  var DOWNLOAD_EXTENSIONS = [
+    'pdf', 'xlsx', 'docx', 'txt', 'rtf', 'csv', 'exe', 'key', 'pps', 'ppt', 'pptx', '7z', 'pkg', 'rar', 'gz',
+    'zip', 'avi', 'mov', 'mp4', 'mpeg', 'wmv', 'midi', 'mp3', 'wav', 'wma', 'dmg', 'iso', 'msi', 'epub'
+  ];
+
+  // Links with a download attribute, or to a file with a known extension
+  function isDownloadLink(link) {
+    if (link.hasAttribute && link.hasAttribute('download')) return true;
+    var path = (link.pathname || '').toLowerCase();
+    var dot = path.lastIndexOf('.');
+    return dot > path.lastIndexOf('/') && DOWNLOAD_EXTENSIONS.indexOf(path.slice(dot + 1)) !== -1;
+  }
+
+  function isMailtoLink(link) {
+    return link.protocol === 'mailto:';
+  }
+
+  // The address of a mailto: link, without its subject or body
+  function mailtoAddress(link) {
+    var address = link.href.slice('mailto:'.length).split('?')[0];
+    try {
+      return decodeURIComponent(address);
+    } catch (e) {
+      return address;
+    }
+  }
+
+  function isOutboundLink(link) {
+    return link &&
+      typeof link.href === 'string' &&
+      link.host &&
+      link.host !== location.host;
+  }
+
+  function getLinkElement(el) {
+    while (el && (typeof el.tagName === 'undefined' || el.tagName.toLowerCase() !== 'a' || !el.href)) {
+      el = el.parentNode;
+    }
+    return el;
+  }
+
+  function shouldInterceptNav(event, link) {
+    if (event.defaultPrevented) return false;
+
+    var target = link.target;
+    if (target && typeof target === 'string' && !target.match(/^_(self|parent|top)$/i)) {
+      return false;
+    }
+
+    if (event.ctrlKey || event.metaKey || event.shiftKey || event.type !== 'click') {
+      return false;
+    }
+
+    return true;
+  }
+
+  function onLinkClick(event) {
+    var link = getLinkElement(event.target);
+    if (!link) return;
+
+    // A download or mail link leaves the page open, and keepalive carries
+    // the hit if it doesn't: no need to hold the click
+    if (isMailtoLink(link)) {
+      if (trackMailto) track('Mailto Link: Click', { email: mailtoAddress(link) });
+      return;
+    }
+    if (trackDownloads && isDownloadLink(link)) {
+      track('File Download', { url: normalize(link.href) });
+      return;
+    }
+
+    if (trackOutbound && isOutboundLink(link)) {
+      var followed = false;
+
+      var followLink = function() {
+        if (!followed) {
+          followed = true;
+          window.location = link.href;
+        }
+      };
+
+      // Track the outbound click, on its way out of the page
+      leaving = true;
+      track('Outbound Link: Click', { url: normalize(link.href) });
+      leaving = false;
+
+      if (shouldInterceptNav(event, link)) {
+        event.preventDefault();
+        setTimeout(followLink, 500); // Give analytics 500ms to send
+      }
+    }
+  }
+
+  // ============================================================================
+  // AUTO-TRACKING: TAGGED ELEMENTS (data-kaunta-event)
+  // ============================================================================
+
+  var EVENT_ATTR = 'data-kaunta-event';
+  var PROP_ATTR_PREFIX = 'data-kaunta-prop-';
+
+  function getTaggedElement(el) {
+    while (el && !(el.hasAttribute && el.hasAttribute(EVENT_ATTR))) {
+      el = el.parentNode;
+    }
+    return el;
+  }
+
+  // Clicks on an element with data-kaunta-event="name" (or inside one) send a
+  // custom event, with a property per data-kaunta-prop-<name> attribute
+  function onTaggedClick(event) {
+    var el = getTaggedElement(event.target);
+    if (!el) return;
+
+    var name = (el.getAttribute(EVENT_ATTR) || '').trim();
+    if (!name) return;
+
+    var props = {};
+    for (var i = 0; i < el.attributes.length; i++) {
+      var attr = el.attributes[i];
+      if (attr.name.indexOf(PROP_ATTR_PREFIX) === 0 && attr.name.length > PROP_ATTR_PREFIX.length) {
+        props[attr.name.slice(PROP_ATTR_PREFIX.length)] = attr.value;
+      }
+    }
+
+    track(name, props);
+  }
+
+  // ============================================================================
+  // INITIALIZATION
+  // ============================================================================
+
+  currentPageUrl = normalize(location.href);
+  var currentRef = normalize((referrer || '').startsWith(origin) ? '' : referrer);
+  var initialized = false;
+
+  function init() {
+    if (initialized || isTrackingDisabled()) return;
+
+    initialized = true;
+
+    // Initialize tracking systems
+    initEngagementTracking();
+    hookHistory();
+
+    // Track initial pageview
+    trackPageview();
+
+    if (isNotFoundPage()) {
+      track404();
+    }
+
+    // Setup click handlers for outbound, download and mailto links
+    if (trackOutbound || trackDownloads || trackMailto) {
+      document.addEventListener('click', onLinkClick, true);
+    }
+    document.addEventListener('click', onTaggedClick, true);
+  }
+
+  // ============================================================================
+  // PUBLIC API
+  // ============================================================================
+
+  function destroy() {
+    // Abort all event listeners
+    if (engagementAbort) {
+      engagementAbort.abort();
+    }
+
+    // Disconnect ResizeObserver
+    if (heightObserver) {
+      heightObserver.disconnect();
+    }
+
+    window.removeEventListener('load', init);
+    document.removeEventListener('click', onLinkClick, true);
+    document.removeEventListener('click', onTaggedClick, true);
+
+    // Restore the History API and clear pending pageview
+    if (unhookHistory) {
+      unhookHistory();
+    }
+    clearTimeout(pendingPageview);
+    pendingPageview = null;
+
+    // Reset state
+    initialized = false;
+    engagementListening = false;
+    removeOverlay();
+    clearTimeout(retryTimer);
+    retryTimer = null;
+    window.removeEventListener('online', flushQueue);
+
+    logDebug('Tracker destroyed');
+  }
+
+  // ============================================================================
+  // AUTO-START
+  // ============================================================================
+
+  function start() {
+    if (isTrackingDisabled()) {
+      debugHit('Not tracking: ' + disabledReason(), 'pageview', null, 'error');
+      return;
+    }
+
+    // Hits left by earlier pages go first, then whenever the browser is back online
+    if (offline) {
+      window.addEventListener('online', flushQueue);
+      flushQueue();
+    }
+
+    if (!autoTrack) return;
+
+    if (document.readyState === 'complete') {
+      init();
+    } else {
+      window.addEventListener('load', init);
+    }
+  }
+
+  return {
+    track: track,
+    pageview: pageview,
+    identify: identify,
+    setProps: setProps,
+    consent: consent,
+    beforeSend: beforeSend,
+    trackPageview: pageview,
+    track404: track404,
+    destroy: destroy,
+    start: start
+  };
+}
+
+// replay runs calls queued as [method, ...args] before the tracker loaded
+export function replay(api, queued) {
+  (queued || []).forEach(function(call) {
+    var args = Array.prototype.slice.call(call);
+    var method = api[args.shift()];
+    if (typeof method !== 'function') return;
+    try {
+      method.apply(null, args);
+    } catch (e) {
+      // A broken call must not stop the others
+    }
+  });
+}
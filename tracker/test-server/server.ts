@@ -1,20 +1,23 @@
 /**
  * Simple test server for Playwright tests
- * Serves the tracker script at /k.js
+ * Serves the tracker script at /k.js, bundled from source on each request
  */
 
 const PORT = 5173;
 
 const server = Bun.serve({
   port: PORT,
-  fetch(req) {
+  async fetch(req) {
     const url = new URL(req.url);
 
     // Serve tracker script
     if (url.pathname === '/k.js') {
-      const trackerPath = `${import.meta.dir}/../kaunta.js`;
-      const file = Bun.file(trackerPath);
-      return new Response(file, {
+      const build = await Bun.build({
+        entrypoints: [`${import.meta.dir}/../kaunta.js`],
+        format: 'iife',
+        target: 'browser',
+      });
+      return new Response(await build.outputs[0].text(), {
         headers: {
           'Content-Type': 'application/javascript',
           'Access-Control-Allow-Origin': '*',
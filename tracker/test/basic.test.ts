@@ -1,4 +1,26 @@
-import { test, expect, createTestHtmlPage } from './setup';
+import { test, expect, createTestHtmlPage, gotoTestPage } from './setup';
+import type { Page } from '@playwright/test';
+
+/**
+ * The hits the page has sent to /api/send, in order
+ */
+async function sentHits(page: Page): Promise<{ type: string; payload: any }[]> {
+  return page.evaluate(() =>
+    (window as any).__capturedRequests
+      .filter((r: any) => String(r.url).includes('/api/send'))
+      .map((r: any) => JSON.parse(r.init.body))
+  );
+}
+
+/**
+ * The pageviews among them, as [url path, referrer path]
+ */
+async function sentPageviews(page: Page): Promise<string[][]> {
+  const path = (url: string) => (url ? new URL(url).pathname : '');
+  return (await sentHits(page))
+    .filter((hit) => hit.type === 'event' && !hit.payload.name)
+    .map((hit) => [path(hit.payload.url), path(hit.payload.referrer)]);
+}
 
 /**
  * Test that tracker loads and executes with defer attribute
@@ -84,6 +106,46 @@ test('custom event tracking works', async ({ page }) => {
   }
 });
 
+/**
+ * Conformance test for the cookie-free transport: a hit sent to another origin
+ * while the page is hidden is a keepalive request that carries no cookie and
+ * needs no CORS preflight (a text/plain POST), since an unloading page can't
+ * wait for one
+ */
+test('hidden-page hits are cross-origin keepalive sends without preflight', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123'
+  });
+
+  const hits: { headers: Record<string, string>; body: any }[] = [];
+  page.on('request', (request) => {
+    if (request.url().includes('/api/send') && request.method() === 'POST') {
+      hits.push({ headers: request.headers(), body: JSON.parse(request.postData() || '{}') });
+    }
+  });
+
+  await page.setContent(html);
+  await page.waitForTimeout(500);
+
+  await page.evaluate(() => {
+    Object.defineProperty(document, 'visibilityState', { value: 'hidden', configurable: true });
+    window.kaunta?.track('Left Page');
+  });
+  await page.waitForTimeout(500);
+
+  const hit = hits.find((h) => h.body.payload?.name === 'Left Page');
+  expect(hit).toBeTruthy();
+  expect(hit!.headers['content-type']).toBe('text/plain');
+  expect(hit!.headers['cookie']).toBeUndefined();
+
+  const init = await page.evaluate(() => {
+    const sent = (window as any).__capturedRequests.filter((r: any) => String(r.url).includes('/api/send'));
+    const last = sent[sent.length - 1].init;
+    return { keepalive: last.keepalive, credentials: last.credentials };
+  });
+  expect(init).toEqual({ keepalive: true, credentials: 'omit' });
+});
+
 /**
  * Test that tracker respects DNT header when enabled
  */
@@ -147,3 +209,422 @@ test('tracker extracts website ID from data attribute', async ({ page }) => {
 
   expect(websiteIdFromRequest).toBe('special-website-id-123');
 });
+
+/**
+ * Test that tracker sends nothing on hosts missing from data-domains
+ */
+test('tracker only sends on hosts listed in data-domains', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'domains': 'example.com,www.example.com'
+  });
+
+  let requestCaptured = false;
+  page.on('request', (request) => {
+    if (request.url().includes('/api/send')) {
+      requestCaptured = true;
+    }
+  });
+
+  await page.setContent(html);
+  await page.waitForTimeout(500);
+
+  // The test page isn't served from example.com: the API loads, but neither
+  // the automatic pageview nor a custom event is sent
+  await page.evaluate(() => {
+    window.kaunta?.track?.('signup');
+  });
+  await page.waitForTimeout(500);
+
+  const kauntaExists = await page.evaluate(() => typeof window.kaunta !== 'undefined');
+  expect(kauntaExists).toBe(true);
+  expect(requestCaptured).toBe(false);
+});
+
+/**
+ * Test that the debug overlay shows why a hit isn't sent
+ */
+test('debug overlay explains why nothing is sent', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'domains': 'example.com',
+    'debug': 'overlay'
+  });
+
+  await page.setContent(html);
+  await page.waitForTimeout(500);
+
+  const overlay = page.locator('[data-kaunta-debug]');
+  await expect(overlay).toContainText('is not in data-domains');
+
+  // Clicking closes it for good
+  await overlay.click();
+  await page.evaluate(() => {
+    window.kaunta?.track?.('signup');
+  });
+  await expect(overlay).toHaveCount(0);
+});
+
+/**
+ * Test that data-track-downloads sends a File Download event
+ */
+test('tracker sends file downloads with data-track-downloads', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'track-downloads': 'true'
+  });
+
+  const events: string[] = [];
+  page.on('request', (request) => {
+    if (request.url().includes('/api/send')) {
+      const name = JSON.parse(request.postData() || '{}').payload?.name;
+      if (name) events.push(name);
+    }
+  });
+
+  await page.setContent(html);
+  await page.waitForTimeout(500);
+
+  await page.evaluate(() => {
+    const link = document.createElement('a');
+    link.href = 'https://example.com/files/report.pdf';
+    link.textContent = 'Report';
+    link.addEventListener('click', (e) => e.preventDefault());
+    document.body.appendChild(link);
+    link.click();
+  });
+  await page.waitForTimeout(500);
+
+  expect(events).toContain('File Download');
+  expect(events).not.toContain('Outbound Link: Click');
+});
+
+/**
+ * Test SPA navigation: pushState and popstate send a pageview whose
+ * referrer is the previous pageview, rapid changes are debounced into one,
+ * and destroy() puts the History API back
+ */
+test('tracker sends SPA pageviews through the History API', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123'
+  });
+
+  await gotoTestPage(page, html);
+  await page.waitForTimeout(500);
+  expect(await sentPageviews(page)).toEqual([['/app', '']]);
+
+  await page.evaluate(() => history.pushState({}, '', '/a'));
+  await page.waitForTimeout(300);
+
+  // Storing state on the same URL isn't a new page
+  await page.evaluate(() => history.replaceState({ scroll: 120 }, '', location.href));
+  await page.waitForTimeout(300);
+
+  // A redirect right after navigating sends one pageview, for where it lands
+  await page.evaluate(() => {
+    history.pushState({}, '', '/b');
+    history.replaceState({}, '', '/c');
+  });
+  await page.waitForTimeout(300);
+
+  await page.evaluate(() => history.back());
+  await page.waitForTimeout(300);
+
+  expect(await sentPageviews(page)).toEqual([
+    ['/app', ''],
+    ['/a', '/app'],
+    ['/c', '/a'],
+    ['/a', '/c']
+  ]);
+
+  const restored = await page.evaluate(() => {
+    window.kaunta?.destroy();
+    return history.pushState === History.prototype.pushState &&
+      history.replaceState === History.prototype.replaceState;
+  });
+  expect(restored).toBe(true);
+
+  await page.evaluate(() => history.pushState({}, '', '/d'));
+  await page.waitForTimeout(300);
+  expect(await sentPageviews(page)).toHaveLength(4);
+});
+
+/**
+ * Test that a click inside a data-kaunta-event element sends its event, with
+ * a property per data-kaunta-prop-* attribute
+ */
+test('tracker sends data-kaunta-event clicks with their properties', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123'
+  });
+
+  await page.setContent(html);
+  await page.waitForTimeout(500);
+
+  await page.evaluate(() => {
+    document.body.insertAdjacentHTML('beforeend',
+      '<button data-kaunta-event="Signup" data-kaunta-prop-plan="pro" data-kaunta-prop-source="hero" data-kaunta-prop-="ignored">' +
+      '<span id="signup-label">Sign <b id="signup-bold">up</b></span></button>' +
+      '<div data-kaunta-event=" "><span id="unnamed">No name</span></div>');
+  });
+
+  // Added after the tracker started, and clicked on a nested element
+  await page.click('#signup-bold');
+  await page.click('#unnamed');
+  await page.waitForTimeout(500);
+
+  const events = (await sentHits(page)).filter((hit) => hit.payload.name);
+  expect(events).toHaveLength(1);
+  expect(events[0].type).toBe('event');
+  expect(events[0].payload.name).toBe('Signup');
+  expect(events[0].payload.website).toBe('test-123');
+  expect(events[0].payload.props).toEqual({ plan: 'pro', source: 'hero' });
+});
+
+/**
+ * Test that data-props is sent with every pageview and event, and that
+ * properties passed to track() win over it
+ */
+test('tracker merges data-props into pageviews and events', async ({ page }) => {
+  const props = JSON.stringify({ env: 'prod', plan: 'free' }).replace(/"/g, '&quot;');
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'props': props
+  });
+
+  await page.setContent(html);
+  await page.waitForTimeout(500);
+
+  await page.evaluate(() => {
+    window.kaunta?.track('Upgrade', { plan: 'pro', seats: 3 });
+    window.kaunta?.setProps({ env: null });
+    window.kaunta?.track('Downgrade');
+  });
+  await page.waitForTimeout(500);
+
+  const hits = await sentHits(page);
+  const pageview = hits.find((hit) => hit.type === 'event' && !hit.payload.name);
+  expect(pageview?.payload.props).toEqual({ env: 'prod', plan: 'free' });
+  expect(hits.find((hit) => hit.payload.name === 'Upgrade')?.payload.props)
+    .toEqual({ env: 'prod', plan: 'pro', seats: 3 });
+  expect(hits.find((hit) => hit.payload.name === 'Downgrade')?.payload.props)
+    .toEqual({ plan: 'free' });
+});
+
+/**
+ * Test that data-props that isn't a JSON object is ignored rather than
+ * breaking the tracker
+ */
+test('tracker ignores invalid data-props', async ({ context }) => {
+  for (const props of ['{env:prod}', '[1,2]', '&quot;prod&quot;']) {
+    // A fresh page each time: setContent keeps the window, and its tracker
+    const page = await context.newPage();
+    const html = createTestHtmlPage('defer', {
+      'website-id': 'test-123',
+      'props': props
+    });
+
+    await page.setContent(html);
+    await page.waitForTimeout(500);
+
+    await page.evaluate(() => {
+      window.kaunta?.track('Signup', { plan: 'pro' });
+    });
+    await page.waitForTimeout(500);
+
+    const hits = await sentHits(page);
+    const pageview = hits.find((hit) => hit.type === 'event' && !hit.payload.name);
+    expect(pageview, props).toBeTruthy();
+    expect(pageview?.payload.props, props).toBeUndefined();
+    expect(hits.find((hit) => hit.payload.name === 'Signup')?.payload.props, props).toEqual({ plan: 'pro' });
+    await page.close();
+  }
+});
+
+/**
+ * Test that a beforeSend hook can redact, rewrite or drop hits, and that a
+ * hook that throws drops the hit rather than sending it unscrubbed
+ */
+test('tracker runs hits through data-before-send and kaunta.beforeSend', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'before-send': 'scrubHit'
+  });
+
+  await page.addInitScript(() => {
+    (window as any).scrubHit = (payload: any) => {
+      if (payload.name === 'Secret') return null;
+      if (payload.name === 'Broken') throw new Error('hook failed');
+      payload.url = payload.url.replace(/token=[^&]+/, 'token=REDACTED');
+      if (payload.name === 'Rename') return Object.assign({}, payload, { name: 'Renamed' });
+      return payload;
+    };
+  });
+  await gotoTestPage(page, html, 'http://localhost:5173/app?token=abc123');
+  await page.waitForTimeout(500);
+
+  await page.evaluate(() => {
+    window.kaunta?.track('Secret');
+    window.kaunta?.track('Broken');
+    window.kaunta?.track('Rename');
+  });
+  await page.waitForTimeout(500);
+
+  let hits = await sentHits(page);
+  expect(hits.map((hit) => hit.payload.name)).toEqual([undefined, 'Renamed']);
+  expect(hits.every((hit) => hit.payload.url.endsWith('?token=REDACTED'))).toBe(true);
+
+  // kaunta.beforeSend replaces the hook; returning nothing sends the payload as left
+  await page.evaluate(() => {
+    window.kaunta?.beforeSend((payload) => {
+      payload.props = { scrubbed: true };
+    });
+    window.kaunta?.track('Secret');
+    window.kaunta?.beforeSend(null);
+    window.kaunta?.track('Plain');
+  });
+  await page.waitForTimeout(500);
+
+  hits = (await sentHits(page)).slice(2);
+  expect(hits.map((hit) => [hit.payload.name, hit.payload.props])).toEqual([
+    ['Secret', { scrubbed: true }],
+    ['Plain', undefined]
+  ]);
+  expect(hits[1].payload.url).toContain('token=abc123');
+});
+
+/**
+ * Replace localStorage with an in-memory fake holding queued hits (age in
+ * ms), and fetch with a stub that records each attempt in window.__attempts,
+ * fails while window.__online is false and records what it delivers in
+ * window.__delivered
+ */
+async function installOfflineFakes(page: Page, queued: { name: string; age: number; endpoint?: string }[]) {
+  await page.addInitScript((queued) => {
+    const now = Date.now();
+    const store = new Map<string, string>();
+    store.set('kaunta_queue', JSON.stringify(queued.map((hit) => ({
+      endpoint: hit.endpoint || 'http://localhost:5173/api/send',
+      type: 'event',
+      payload: { website: 'test-123', url: 'http://localhost:5173/earlier', name: hit.name },
+      at: now - hit.age
+    }))));
+    Object.defineProperty(window, 'localStorage', {
+      configurable: true,
+      value: {
+        getItem: (key: string) => (store.has(key) ? store.get(key) : null),
+        setItem: (key: string, value: string) => { store.set(key, String(value)); },
+        removeItem: (key: string) => { store.delete(key); }
+      }
+    });
+
+    const w = window as any;
+    w.__online = false;
+    w.__attempts = [];
+    w.__delivered = [];
+    w.fetch = (_url: string, init: RequestInit) => {
+      const hit = JSON.parse(String(init.body));
+      w.__attempts.push({ name: hit.payload.name ?? null, at: Date.now() });
+      if (!w.__online) return Promise.reject(new TypeError('Failed to fetch'));
+      w.__delivered.push(hit);
+      return Promise.resolve(new Response('{"ok":true}', { status: 200 }));
+    };
+  }, queued);
+}
+
+/**
+ * The names of the queued hits, null for pageviews
+ */
+async function queuedNames(page: Page): Promise<(string | null)[]> {
+  return page.evaluate(() =>
+    JSON.parse(localStorage.getItem('kaunta_queue') || '[]').map((hit: any) => hit.payload.name ?? null)
+  );
+}
+
+/**
+ * Test that the offline queue keeps the newest 100 hits and drops those
+ * older than a day
+ */
+test('offline queue is capped and drops expired hits', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'offline': 'true'
+  });
+
+  await installOfflineFakes(page, [{ name: 'Stale', age: 25 * 60 * 60 * 1000 }]);
+  await gotoTestPage(page, html);
+  await page.waitForTimeout(500);
+
+  // The stale hit is dropped, not retried
+  expect(await queuedNames(page)).toEqual([null]);
+
+  await page.evaluate(() => {
+    for (let i = 0; i < 120; i++) window.kaunta?.track('Burst ' + i);
+  });
+  await page.waitForTimeout(500);
+
+  const names = await queuedNames(page);
+  expect(names).toHaveLength(100);
+  expect(names[0]).toBe('Burst 20');
+  expect(names[99]).toBe('Burst 119');
+});
+
+/**
+ * Test that queued hits are retried with a doubling delay, and replayed in
+ * the order they were first sent once the browser is back online
+ */
+test('offline queue retries with backoff and replays in order when online', async ({ page }) => {
+  const html = createTestHtmlPage('defer', {
+    'website-id': 'test-123',
+    'offline': 'true'
+  });
+
+  const start = new Date('2026-01-05T09:00:00Z');
+  await page.clock.install({ time: start });
+  await installOfflineFakes(page, [
+    { name: 'Queued 1', age: 60 * 1000 },
+    { name: 'Queued 2', age: 30 * 1000 },
+    { name: 'Other site', age: 10 * 1000, endpoint: 'https://other.example.com/api/send' }
+  ]);
+  await gotoTestPage(page, html);
+  await page.waitForTimeout(500);
+
+  // Stop the clock before the first retry is due, then run it by hand
+  await page.clock.pauseAt(new Date(start.getTime() + 4000));
+
+  await page.evaluate(() => {
+    window.kaunta?.track('Signup');
+  });
+  await page.waitForTimeout(100);
+
+  // Hits left by an earlier page were retried on load and failed again
+  expect(await queuedNames(page)).toEqual(['Other site', 'Queued 1', 'Queued 2', null, 'Signup']);
+
+  await page.clock.runFor(40000);
+
+  const retries: number[] = await page.evaluate(() =>
+    (window as any).__attempts.filter((a: any) => a.name === 'Queued 1').map((a: any) => a.at)
+  );
+  expect(retries).toHaveLength(4);
+  expect(retries[1] - retries[0]).toBeGreaterThanOrEqual(5000);
+  expect(retries[1] - retries[0]).toBeLessThan(5500);
+  expect(retries[2] - retries[1]).toBe(10000);
+  expect(retries[3] - retries[2]).toBe(20000);
+
+  const queuedAt = await page.evaluate(() =>
+    JSON.parse(localStorage.getItem('kaunta_queue') || '[]').find((hit: any) => hit.payload.name === 'Queued 1').at
+  );
+
+  await page.evaluate(() => {
+    (window as any).__online = true;
+    window.dispatchEvent(new Event('online'));
+  });
+  await page.waitForTimeout(100);
+
+  const delivered = await page.evaluate(() => (window as any).__delivered);
+  expect(delivered.map((hit: any) => hit.payload.name ?? null)).toEqual(['Queued 1', 'Queued 2', null, 'Signup']);
+  expect(delivered[0].payload.timestamp).toBe(Math.floor(queuedAt / 1000));
+
+  // Another tracker's hits are left for it to send
+  expect(await queuedNames(page)).toEqual(['Other site']);
+});
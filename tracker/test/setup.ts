@@ -1,4 +1,4 @@
-import { test as base } from '@playwright/test';
+import { test as base, type Page } from '@playwright/test';
 
 /**
  * Test server that hosts HTML pages and captures tracking requests
@@ -70,3 +70,15 @@ export function createTestHtmlPage(
     </html>
   `;
 }
+
+/**
+ * Serve html at url and open it, for tests that need a real origin rather
+ * than setContent's about:blank (the History API, localStorage)
+ */
+export async function gotoTestPage(page: Page, html: string, url = 'http://localhost:5173/app'): Promise<void> {
+  const { pathname } = new URL(url);
+  await page.route((u) => u.pathname === pathname, (route) =>
+    route.fulfill({ contentType: 'text/html', body: html })
+  );
+  await page.goto(url);
+}